@@ -68,7 +68,7 @@ int main() {
     
     print_msg("[Enclave1] Computing public_id (SHA256 hash only)...\n");
     
-    if (ZK_ComputePublicID(user_id, user_id_len, public_id, sizeof(public_id)) != 0) {
+    if (ZK_ComputePublicID(user_id, user_id_len, public_id, sizeof(public_id), NULL) != 0) {
         print_msg("[Enclave1] ERROR: Failed to compute public_id\n");
         EAPP_RETURN(1);
     }
@@ -30,6 +30,27 @@ fi
 echo "Ensuring RISC-V target is installed..."
 rustup target add riscv64gc-unknown-linux-gnu 2>/dev/null || true
 
+# Build profile: ZKLIB_PROFILE=strict compiles out legacy/insecure code paths
+ZKLIB_PROFILE="${ZKLIB_PROFILE:-standard}"
+CARGO_FEATURES=""
+if [ "$ZKLIB_PROFILE" = "strict" ]; then
+    CARGO_FEATURES="--features strict"
+fi
+echo "Build profile: $ZKLIB_PROFILE"
+
+# User-ID circuit: ZKLIB_USER_ID_CIRCUIT=legacy keeps the fast path that
+# checks SHA256(user_id) outside the proof (not with the strict profile)
+ZKLIB_USER_ID_CIRCUIT="${ZKLIB_USER_ID_CIRCUIT:-preimage}"
+if [ "$ZKLIB_USER_ID_CIRCUIT" = "legacy" ]; then
+    if [ "$ZKLIB_PROFILE" = "strict" ]; then
+        echo "Error: the legacy user-ID circuit is not available in the strict profile"
+        exit 1
+    fi
+    CARGO_FEATURES="--features legacy-user-id"
+fi
+echo "User-ID circuit: $ZKLIB_USER_ID_CIRCUIT"
+echo ""
+
 # Generate C header file
 echo "Generating C header file..."
 cbindgen --config cbindgen.toml --crate zklib --output zklib.h
@@ -47,6 +68,17 @@ if [ ! -f "zklib.h" ]; then
 extern "C" {
 #endif
 
+/* Error codes (0 is success, -1 a generic failure), numbered as in zkid-vc */
+#define ZK_STATUS_SIZE_QUERY 1  /* not a failure: only the size was reported */
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_BAD_HEX -6
+#define ZK_ERR_PROVE_FAILED -7
+#define ZK_ERR_NO_PROVING_KEY -8
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_INPUT_TOO_LARGE -30  /* user_id longer than the circuit hashes */
+#define ZK_ERR_ENTROPY -34
+
 /**
  * Initialize the ZK system and generate proving/verifying keys.
  * Must be called before any other ZK operations.
@@ -55,26 +87,48 @@ extern "C" {
  */
 int ZK_Init(void);
 
+/**
+ * Load only the verifying key (verification gateways never hold the
+ * proving key). ZK_GenerateProof then returns ZK_ERR_NO_PROVING_KEY.
+ * 
+ * @param vk_bytes Verifying key: ZK_ExportVerifyingKey output, hex-decoded
+ * @param vk_len Length of vk_bytes
+ * @return 0 on success, ZK_ERR_CORRUPT, or -1
+ */
+int ZK_InitVerifierOnly(const uint8_t* vk_bytes, size_t vk_len);
+
+/**
+ * Export the verifying key as hex for ZK_InitVerifierOnly.
+ * 
+ * @return 0 on success, ZK_ERR_NOT_INITIALIZED, ZK_ERR_BUFFER_TOO_SMALL, or -1
+ */
+int ZK_ExportVerifyingKey(char* vk_out, size_t vk_out_size);
+
 /**
  * Compute the public ID from a user ID.
  * public_id = SHA256(user_id)
  * 
  * @param user_id User identifier (arbitrary bytes)
  * @param user_id_len Length of user_id
- * @param public_id Output buffer for hex-encoded public ID
+ * @param public_id Output buffer for hex-encoded public ID, or NULL to
+ *                  query the size
  * @param public_id_size Size of public_id buffer (must be >= 65 bytes)
- * @return 0 on success, -1 on failure
+ * @param required_size_out Receives the required buffer size; may be NULL
+ * @return 0 on success, ZK_STATUS_SIZE_QUERY, ZK_ERR_BUFFER_TOO_SMALL, or -1
  */
 int ZK_ComputePublicID(
     const char* user_id, 
     size_t user_id_len, 
     char* public_id, 
-    size_t public_id_size
+    size_t public_id_size,
+    size_t* required_size_out
 );
 
 /**
  * Generate a zero-knowledge proof that the prover knows user_id
  * such that SHA256(user_id) == public_id, without revealing user_id.
+ * The circuit computes the SHA-256 itself (about 40,800 constraints), so
+ * user_id is at most 55 bytes; legacy-circuit builds have no limit.
  * 
  * @param user_id Secret user identifier
  * @param user_id_len Length of user_id
@@ -82,7 +136,10 @@ int ZK_ComputePublicID(
  * @param nonce Challenge nonce from verifier (for replay protection)
  * @param proof_out Output buffer for hex-encoded proof
  * @param proof_out_size Size of proof_out buffer (must be >= 512 bytes)
- * @return 0 on success, -1 on failure
+ * @return 0 on success, or ZK_ERR_BAD_HEX, ZK_ERR_NOT_INITIALIZED,
+ *         ZK_ERR_NO_PROVING_KEY, ZK_ERR_PROVE_FAILED, ZK_ERR_ENTROPY, ZK_ERR_BUFFER_TOO_SMALL,
+ *         ZK_ERR_INPUT_TOO_LARGE (user_id over 55 bytes), or -1 (NULL input or
+ *         user_id not matching public_id)
  */
 int ZK_GenerateProof(
     const char* user_id,
@@ -96,6 +153,10 @@ int ZK_GenerateProof(
 /**
  * Verify a zero-knowledge proof.
  * 
+ * Accepts format-3 proofs ("ZP" | 0x03 | compressed proof) of the
+ * in-circuit SHA-256 circuit. Legacy-circuit builds accept format 2 and
+ * bare 128-byte format-1 proofs with the truncated mapping instead.
+ * 
  * @param proof_hex Hex-encoded proof string
  * @param public_id Hex-encoded public ID being claimed
  * @param nonce Challenge nonce that was sent to prover
@@ -107,6 +168,47 @@ int ZK_VerifyProof(
     uint64_t nonce
 );
 
+/**
+ * ZK_GenerateProof on binary buffers, without hex encoding.
+ * 
+ * @param public_id Public ID as its 32-byte digest (hex-decoded)
+ * @param proof_out Output buffer for the enveloped proof (131 bytes)
+ * @param proof_len_out Receives the proof length; may be NULL
+ * @return As ZK_GenerateProof, without ZK_ERR_BAD_HEX
+ */
+int ZK_GenerateProofRaw(
+    const uint8_t* user_id,
+    size_t user_id_len,
+    const uint8_t* public_id,
+    size_t public_id_len,
+    uint64_t nonce,
+    uint8_t* proof_out,
+    size_t proof_out_size,
+    size_t* proof_len_out
+);
+
+/**
+ * ZK_VerifyProof on binary buffers. A raw proof hex-encoded verifies
+ * through ZK_VerifyProof and a hex proof decoded verifies here.
+ * 
+ * @return 1 if proof is valid, 0 if invalid or error
+ */
+int ZK_VerifyProofRaw(
+    const uint8_t* proof,
+    size_t proof_len,
+    const uint8_t* public_id,
+    size_t public_id_len,
+    uint64_t nonce
+);
+
+/**
+ * Derive proving randomness from seed and the nonce for reproducible test
+ * runs; 0 returns to fresh OS entropy (the default). Not in strict builds.
+ * 
+ * @return 0
+ */
+int ZK_SetDeterministicProving(uint64_t seed);
+
 /**
  * Cleanup ZK resources.
  * Should be called when done with ZK operations.
@@ -126,7 +228,7 @@ echo ""
 
 # Build for RISC-V with single-threaded rayon
 echo "Building static library for RISC-V64 (single-threaded mode)..."
-RAYON_NUM_THREADS=1 cargo build --release --target riscv64gc-unknown-linux-gnu
+RAYON_NUM_THREADS=1 cargo build --release --target riscv64gc-unknown-linux-gnu $CARGO_FEATURES
 
 if [ ! -f "target/riscv64gc-unknown-linux-gnu/release/libzklib.a" ]; then
     echo "Error: Build failed"
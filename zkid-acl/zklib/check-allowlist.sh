@@ -0,0 +1,147 @@
+#!/bin/bash
+#
+# Build the library for the host and check signed allowlist deltas: a
+# verifier applying the administrator's chain reaches the same root and
+# membership; a delta applied twice is a replay, one ahead of the chain is
+# out of order, one signed by another key or with a flipped byte fails
+# authentication, a truncated one is corrupt, and one from a forked tree
+# does not match the root. Every rejected delta leaves the tree as it was.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_AllowlistCreate(uint64_t*);
+void ZK_AllowlistFree(uint64_t);
+int ZK_AllowlistRoot(uint64_t, char*, size_t, uint64_t*);
+int ZK_AllowlistContains(uint64_t, const char*, size_t);
+int ZK_CreateAllowlistDelta(uint64_t, const char*, const char* const*, size_t, const char* const*, size_t, char*,
+                            size_t);
+int ZK_ApplyAllowlistDelta(uint64_t, const char*, const char*);
+
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNKNOWN_HANDLE -15
+#define ZK_ERR_DELTA_REPLAYED -27
+#define ZK_ERR_DELTA_OUT_OF_ORDER -28
+#define ZK_ERR_ROOT_MISMATCH -29
+
+/* RFC 8032 section 7.1, tests 1 and 2 */
+#define ADMIN_PRIVATE "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+#define ADMIN_PUBLIC "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+#define OTHER_PRIVATE "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb"
+
+static char delta1[4096], delta2[4096], forged[4096], fork1[4096], fork2[4096], edited[4096];
+
+static int contains(uint64_t tree, const char* member) {
+    return ZK_AllowlistContains(tree, member, strlen(member));
+}
+
+/* Apply `delta`; the verifier's root and epoch must not move on failure */
+static int apply(uint64_t tree, const char* delta, const char* key) {
+    char before[65], after[65];
+    uint64_t epoch_before = 0, epoch_after = 0;
+    ZK_AllowlistRoot(tree, before, sizeof(before), &epoch_before);
+    int rc = ZK_ApplyAllowlistDelta(tree, delta, key);
+    ZK_AllowlistRoot(tree, after, sizeof(after), &epoch_after);
+    if (rc != 0 && (strcmp(before, after) != 0 || epoch_before != epoch_after)) {
+        return -100;
+    }
+    return rc;
+}
+
+int main(void) {
+    uint64_t admin = 0, other = 0, fork = 0, verifier = 0;
+    const char* first[] = {"alice", "bob"};
+    const char* second[] = {"carol"};
+    const char* gone[] = {"bob"};
+    const char* forked[] = {"dave"};
+    const char* absent[] = {"mallory"};
+    if (ZK_AllowlistCreate(&admin) != 0 || ZK_AllowlistCreate(&other) != 0 || ZK_AllowlistCreate(&fork) != 0 ||
+        ZK_AllowlistCreate(&verifier) != 0 ||
+        ZK_CreateAllowlistDelta(admin, ADMIN_PRIVATE, first, 2, NULL, 0, delta1, sizeof(delta1)) != 0 ||
+        ZK_CreateAllowlistDelta(admin, ADMIN_PRIVATE, second, 1, gone, 1, delta2, sizeof(delta2)) != 0 ||
+        ZK_CreateAllowlistDelta(other, OTHER_PRIVATE, first, 2, NULL, 0, forged, sizeof(forged)) != 0 ||
+        ZK_CreateAllowlistDelta(fork, ADMIN_PRIVATE, forked, 1, NULL, 0, fork1, sizeof(fork1)) != 0 ||
+        ZK_CreateAllowlistDelta(fork, ADMIN_PRIVATE, second, 1, NULL, 0, fork2, sizeof(fork2)) != 0) {
+        return 1;
+    }
+
+    /* Administrator side: removing an absent member changes nothing */
+    char admin_root[65], unused[4096];
+    uint64_t admin_epoch = 0;
+    int absent_removal = ZK_CreateAllowlistDelta(admin, ADMIN_PRIVATE, NULL, 0, absent, 1, unused, sizeof(unused));
+    ZK_AllowlistRoot(admin, admin_root, sizeof(admin_root), &admin_epoch);
+    printf("  removing an absent member %d (epoch still %llu)\n", absent_removal, (unsigned long long)admin_epoch);
+    if (absent_removal != ZK_ERR_ROOT_MISMATCH || admin_epoch != 2) {
+        return 1;
+    }
+
+    /* Deltas that must not apply to a fresh verifier (hex character 40 lies
+       in previous_root) */
+    size_t len = strlen(delta1);
+    memcpy(edited, delta1, len + 1);
+    edited[40] = edited[40] == '0' ? '1' : '0';
+    int ahead = apply(verifier, delta2, ADMIN_PUBLIC);
+    int other_key = apply(verifier, forged, ADMIN_PUBLIC);
+    int flipped = apply(verifier, edited, ADMIN_PUBLIC);
+    memcpy(edited, delta1, len + 1);
+    edited[len - 1] = edited[len - 1] == '0' ? '1' : '0';
+    int flipped_signature = apply(verifier, edited, ADMIN_PUBLIC);
+    edited[len - 130] = '\0';
+    int truncated = apply(verifier, edited, ADMIN_PUBLIC);
+    int bad_key = apply(verifier, delta1, "zz");
+    printf("  out of order %d, other admin key %d, flipped body %d, flipped signature %d, truncated %d, bad key %d\n",
+           ahead, other_key, flipped, flipped_signature, truncated, bad_key);
+    if (ahead != ZK_ERR_DELTA_OUT_OF_ORDER || other_key != ZK_ERR_AUTH || flipped != ZK_ERR_AUTH ||
+        flipped_signature != ZK_ERR_AUTH || truncated != ZK_ERR_CORRUPT || bad_key != -1) {
+        return 1;
+    }
+
+    /* The chain, a replay and a fork */
+    int applied1 = apply(verifier, delta1, ADMIN_PUBLIC);
+    int replayed = apply(verifier, delta1, ADMIN_PUBLIC);
+    int from_fork = apply(verifier, fork2, ADMIN_PUBLIC);
+    int applied2 = apply(verifier, delta2, ADMIN_PUBLIC);
+    int replayed2 = apply(verifier, delta2, ADMIN_PUBLIC);
+    printf("  first %d, replayed %d, forked %d, second %d, replayed %d\n", applied1, replayed, from_fork, applied2,
+           replayed2);
+    if (applied1 != 0 || replayed != ZK_ERR_DELTA_REPLAYED || from_fork != ZK_ERR_ROOT_MISMATCH || applied2 != 0 ||
+        replayed2 != ZK_ERR_DELTA_REPLAYED) {
+        return 1;
+    }
+
+    char verifier_root[65];
+    uint64_t verifier_epoch = 0;
+    ZK_AllowlistRoot(verifier, verifier_root, sizeof(verifier_root), &verifier_epoch);
+    int same_root = strcmp(admin_root, verifier_root) == 0;
+    int members = contains(verifier, "alice") == 1 && contains(verifier, "bob") == 0 &&
+                  contains(verifier, "carol") == 1 && contains(verifier, "dave") == 0;
+    ZK_AllowlistFree(fork);
+    int unknown = ZK_ApplyAllowlistDelta(fork, fork1, ADMIN_PUBLIC);
+    printf("  same root as the administrator %d at epoch %llu, members %d; freed tree %d\n", same_root,
+           (unsigned long long)verifier_epoch, members, unknown);
+
+    ZK_AllowlistFree(admin);
+    ZK_AllowlistFree(other);
+    ZK_AllowlistFree(verifier);
+    return !same_root || verifier_epoch != 2 || !members || unknown != ZK_ERR_UNKNOWN_HANDLE;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Allowlist deltas apply once, in order, and only under the administrator's key"
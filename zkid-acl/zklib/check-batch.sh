@@ -0,0 +1,90 @@
+#!/bin/bash
+#
+# Build the library for the host and check batch verification: a batch of
+# valid user-ID proofs verifies in full, and one holding a proof for
+# another nonce, one for another public ID and a malformed one reports
+# exactly those, as ZK_VerifyProof does.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_GenerateProof(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_VerifyProof(const char*, const char*, uint64_t);
+int ZK_BatchVerifyProofs(const char* const*, const char* const*, const uint64_t*, size_t, int*);
+
+#define PROOFS 6
+
+static char public_ids[2][65], proofs[PROOFS][1024];
+
+int main(void) {
+    const char* users[2] = {"alice_secret_12345", "bob_secret_67890"};
+    const char* proof_ptrs[PROOFS];
+    const char* id_ptrs[PROOFS];
+    uint64_t nonces[PROOFS];
+    int results[PROOFS];
+    if (ZK_Init() != 0) {
+        return 1;
+    }
+    for (int i = 0; i < 2; i++) {
+        if (ZK_ComputePublicID(users[i], strlen(users[i]), public_ids[i], 65, NULL) != 0) {
+            return 1;
+        }
+    }
+    for (int i = 0; i < PROOFS; i++) {
+        nonces[i] = 100 + i;
+        if (ZK_GenerateProof(users[i % 2], strlen(users[i % 2]), public_ids[i % 2], nonces[i], proofs[i], 1024) != 0) {
+            return 1;
+        }
+        proof_ptrs[i] = proofs[i];
+        id_ptrs[i] = public_ids[i % 2];
+    }
+
+    /* All valid */
+    int valid = ZK_BatchVerifyProofs(proof_ptrs, id_ptrs, nonces, PROOFS, results);
+    int all_one = 1;
+    for (int i = 0; i < PROOFS; i++) {
+        all_one &= results[i] == 1;
+    }
+    int empty = ZK_BatchVerifyProofs(NULL, NULL, NULL, 0, NULL);
+    int no_results = ZK_BatchVerifyProofs(proof_ptrs, id_ptrs, nonces, PROOFS, NULL);
+    printf("  valid batch: %d of %d (all reported %d), empty %d, NULL results %d\n", valid, PROOFS, all_one, empty,
+           no_results);
+    if (valid != PROOFS || !all_one || empty != 0 || no_results != -1) {
+        return 1;
+    }
+
+    /* Another nonce, another public ID, a malformed proof */
+    nonces[1] += 1000;
+    id_ptrs[3] = public_ids[0];
+    proof_ptrs[4] = "zz";
+    valid = ZK_BatchVerifyProofs(proof_ptrs, id_ptrs, nonces, PROOFS, results);
+    int agree = 1;
+    for (int i = 0; i < PROOFS; i++) {
+        agree &= results[i] == ZK_VerifyProof(proof_ptrs[i], id_ptrs[i], nonces[i]);
+    }
+    printf("  mixed batch: %d valid; results %d %d %d %d %d %d; same as ZK_VerifyProof %d\n", valid, results[0],
+           results[1], results[2], results[3], results[4], results[5], agree);
+    return valid != 3 || !agree || results[0] != 1 || results[1] != 0 || results[2] != 1 || results[3] != 0 ||
+           results[4] != 0 || results[5] != 1;
+}
+EOF
+
+echo "Checking build: standard"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Batch verification reports each proof as ZK_VerifyProof does"
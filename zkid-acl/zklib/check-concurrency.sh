@@ -0,0 +1,77 @@
+#!/bin/bash
+#
+# Build the library for the host and check that proving does not hold the
+# key lock: while one thread generates a user-ID proof, a verification on
+# another thread completes well before the proof does.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdatomic.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+
+int ZK_Init(void);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_GenerateProof(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_VerifyProof(const char*, const char*, uint64_t);
+
+static const char* user = "alice_secret_12345";
+static char public_id[65], proof[1024], proof_in_flight[1024];
+static atomic_int proving;
+
+static double now_ms(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+    return ts.tv_sec * 1e3 + ts.tv_nsec / 1e6;
+}
+
+static void* prove_thread(void* arg) {
+    (void)arg;
+    atomic_store(&proving, 1);
+    int code = ZK_GenerateProof(user, strlen(user), public_id, 2, proof_in_flight, sizeof(proof_in_flight));
+    atomic_store(&proving, 0);
+    return (void*)(intptr_t)code;
+}
+
+int main(void) {
+    pthread_t prover;
+    void* code;
+    if (ZK_Init() != 0 || ZK_ComputePublicID(user, strlen(user), public_id, 65, NULL) != 0 ||
+        ZK_GenerateProof(user, strlen(user), public_id, 1, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+
+    double started = now_ms();
+    pthread_create(&prover, NULL, prove_thread, NULL);
+    while (!atomic_load(&proving)) {
+    }
+    int valid = ZK_VerifyProof(proof, public_id, 1);
+    double verified = now_ms() - started;
+    int during = atomic_load(&proving);
+    pthread_join(prover, &code);
+    double proved = now_ms() - started;
+
+    printf("  verify %d after %.0f ms (during the proof: %d), proof %d after %.0f ms\n", valid, verified, during,
+           (int)(intptr_t)code, proved);
+    return valid != 1 || !during || (int)(intptr_t)code != 0 || verified * 2 > proved ||
+           ZK_VerifyProof(proof_in_flight, public_id, 2) != 1;
+}
+EOF
+
+echo "Checking build: standard"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Verification completes while a proof is in flight"
@@ -0,0 +1,126 @@
+#!/bin/bash
+#
+# Build the default and legacy-user-id circuits for the host and check
+# format versions 1 and 2 against each other: version 2 public IDs carry the
+# tag and differ from the untagged ones, which version 1 keeps, and a proof
+# holds only for the public ID of its own version. Default builds prove
+# format 3 or 4 with keys set up for that version and verify nothing else,
+# and the tag takes 17 of the 55 bytes the circuit hashes. Legacy builds
+# verify formats 2 and 5 with one key under either setting, but not a format
+# 5 proof relabelled as format 2.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_SetFormatVersion(uint32_t);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_GenerateProof(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_VerifyProof(const char*, const char*, uint64_t);
+
+#define ZK_ERR_INPUT_TOO_LARGE -30
+
+static const char* user_id = "alice_secret_12345";
+
+static int public_id(char out[65]) {
+    return ZK_ComputePublicID(user_id, strlen(user_id), out, 65, NULL);
+}
+
+/* Prove under the current version for `id`; the envelope's format digit, or -1 */
+static int prove(const char* id, char* proof, size_t size) {
+    if (ZK_GenerateProof(user_id, strlen(user_id), id, 7, proof, size) != 0) {
+        return -1;
+    }
+    return proof[5] - '0';
+}
+
+/* Proof of user_id padded to `len` bytes under the current version */
+static int prove_long(size_t len) {
+    char long_id[64], id[65], proof[1024];
+    memset(long_id, 'a', len);
+    long_id[len] = 0;
+    ZK_ComputePublicID(long_id, len, id, sizeof(id), NULL);
+    return ZK_GenerateProof(long_id, len, id, 7, proof, sizeof(proof));
+}
+
+int main(int argc, char** argv) {
+    static char proof_v1[1024], proof_v2[1024];
+    char id_v1[65], id_v2[65], id_v1_again[65], mixed[1024];
+    int legacy = argc > 1 && strcmp(argv[1], "legacy") == 0;
+    int unknown = ZK_SetFormatVersion(0) + ZK_SetFormatVersion(3);
+
+    if (public_id(id_v1) != 0 || ZK_Init() != 0) {
+        return 1;
+    }
+    int f1 = prove(id_v1, proof_v1, sizeof(proof_v1));
+    if (ZK_SetFormatVersion(2) != 0 || public_id(id_v2) != 0 || (!legacy && ZK_Init() != 0)) {
+        return 1;
+    }
+    int f2 = prove(id_v2, proof_v2, sizeof(proof_v2));
+    int stale_id = prove(id_v1, mixed, sizeof(mixed));
+    int v2_valid = ZK_VerifyProof(proof_v2, id_v2, 7);
+    int v2_other_id = ZK_VerifyProof(proof_v2, id_v1, 7);
+    int v1_under_v2 = ZK_VerifyProof(proof_v1, id_v1, 7);
+    printf("  unknown versions %d; public IDs differ %d; formats %d/%d\n", unknown, strcmp(id_v1, id_v2) != 0, f1,
+           f2);
+    printf("  under v2: v2 proof %d, for the v1 ID %d, v1 proof %d, proving the v1 ID %d\n", v2_valid, v2_other_id,
+           v1_under_v2, stale_id);
+    if (unknown != -2 || strcmp(id_v1, id_v2) == 0 || v2_valid != 1 || v2_other_id != 0 || stale_id != -1) {
+        return 1;
+    }
+
+    if (legacy) {
+        /* One key: both formats verify under either setting */
+        if (ZK_SetFormatVersion(1) != 0 || public_id(id_v1_again) != 0) {
+            return 1;
+        }
+        int v1_valid = ZK_VerifyProof(proof_v1, id_v1, 7);
+        int v2_under_v1 = ZK_VerifyProof(proof_v2, id_v2, 7);
+        proof_v2[5] = '2';
+        int relabelled = ZK_VerifyProof(proof_v2, id_v2, 7);
+        printf("  under v1: v1 proof %d, v2 proof %d, relabelled %d; v1 ID unchanged %d\n", v1_valid,
+               v2_under_v1, relabelled, strcmp(id_v1, id_v1_again) == 0);
+        return f1 != 2 || f2 != 5 || v1_under_v2 != 1 || v1_valid != 1 || v2_under_v1 != 1 || relabelled != 0 ||
+               strcmp(id_v1, id_v1_again) != 0;
+    }
+
+    /* The tag takes 17 bytes of the block */
+    int fits = prove_long(38), too_long = prove_long(39);
+    if (ZK_SetFormatVersion(1) != 0 || public_id(id_v1_again) != 0 || ZK_Init() != 0) {
+        return 1;
+    }
+    int untagged_39 = prove_long(39);
+    int v1_valid = ZK_VerifyProof(proof_v1, id_v1, 7);
+    int v2_under_v1 = ZK_VerifyProof(proof_v2, id_v2, 7);
+    printf("  tagged user_id of 38 bytes %d, 39 bytes %d, untagged 39 bytes %d\n", fits, too_long, untagged_39);
+    printf("  v1 keys: v1 proof %d, v2 proof %d; v1 ID unchanged %d\n", v1_valid, v2_under_v1,
+           strcmp(id_v1, id_v1_again) == 0);
+    return f1 != 3 || f2 != 4 || v1_under_v2 != 0 || fits != 0 || too_long != ZK_ERR_INPUT_TOO_LARGE ||
+           untagged_39 != 0 || v1_valid != 1 || v2_under_v1 != 0 || strcmp(id_v1, id_v1_again) != 0;
+}
+EOF
+
+check_circuit() {
+    local circuit="$1" features="$2"
+    echo "Checking circuit: $circuit"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$circuit" -q
+    cc -o "$WORK_DIR/check-$circuit" "$WORK_DIR/check.c" "$WORK_DIR/target-$circuit/release/libzklib.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$circuit" "$circuit" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_circuit default ""
+check_circuit legacy "--features legacy-user-id"
+
+echo "✓ Tagged public IDs prove only under their own format, and untagged ones are unchanged"
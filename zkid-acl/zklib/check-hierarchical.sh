@@ -0,0 +1,102 @@
+#!/bin/bash
+#
+# Build the library for the host and check hierarchical identifiers: a
+# proof disclosing the "org" prefix of "org/team/user" verifies against the
+# prefix a verifier computes from "org" alone, at depths 0 through the full
+# identifier; it fails for another org, another depth, another identifier's
+# commitment or another nonce; and identifiers deeper than MAX_DEPTH, a
+# depth past the identifier and proving before ZK_Init are refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_ComputeHierarchicalID(const char* const*, size_t, char*, size_t);
+int ZK_ComputeHierarchicalPrefix(const char* const*, size_t, char*, size_t);
+int ZK_GenerateHierarchicalProof(const char* const*, size_t, size_t, uint64_t, char*, size_t);
+int ZK_VerifyHierarchicalProof(const char*, const char*, size_t, const char*, uint64_t);
+
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define MAX_DEPTH 8
+
+static const char* alice[] = {"acme", "eng", "alice"};
+static const char* bob[] = {"acme", "eng", "bob"};
+static const char* globex[] = {"globex"};
+
+int main(void) {
+    char commitment[65], other_commitment[65], prefix[65], org_prefix[65], globex_prefix[65];
+    char proof[1024], small[16];
+
+    int early = ZK_GenerateHierarchicalProof(alice, 3, 1, 7, proof, sizeof(proof));
+    if (ZK_Init() != 0 || ZK_ComputeHierarchicalID(alice, 3, commitment, sizeof(commitment)) != 0 ||
+        ZK_ComputeHierarchicalID(bob, 3, other_commitment, sizeof(other_commitment)) != 0 ||
+        ZK_ComputeHierarchicalPrefix(alice, 1, org_prefix, sizeof(org_prefix)) != 0 ||
+        ZK_ComputeHierarchicalPrefix(globex, 1, globex_prefix, sizeof(globex_prefix)) != 0) {
+        return 1;
+    }
+    printf("  before ZK_Init %d, org prefixes differ %d, commitments differ %d\n", early,
+           strcmp(org_prefix, globex_prefix) != 0, strcmp(commitment, other_commitment) != 0);
+    if (early != ZK_ERR_NOT_INITIALIZED || strcmp(org_prefix, globex_prefix) == 0 ||
+        strcmp(commitment, other_commitment) == 0) {
+        return 1;
+    }
+
+    /* Every depth from the IV to the full identifier */
+    for (size_t depth = 0; depth <= 3; depth++) {
+        if (ZK_ComputeHierarchicalPrefix(alice, depth, prefix, sizeof(prefix)) != 0 ||
+            ZK_GenerateHierarchicalProof(alice, 3, depth, 7, proof, sizeof(proof)) != 0) {
+            return 1;
+        }
+        int valid = ZK_VerifyHierarchicalProof(proof, prefix, depth, commitment, 7);
+        printf("  depth %zu: %d\n", depth, valid);
+        if (valid != 1) {
+            return 1;
+        }
+    }
+
+    /* The org prefix, against everything it should not match */
+    if (ZK_GenerateHierarchicalProof(alice, 3, 1, 7, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+    int valid = ZK_VerifyHierarchicalProof(proof, org_prefix, 1, commitment, 7);
+    int other_org = ZK_VerifyHierarchicalProof(proof, globex_prefix, 1, commitment, 7);
+    int other_depth = ZK_VerifyHierarchicalProof(proof, org_prefix, 2, commitment, 7);
+    int other_id = ZK_VerifyHierarchicalProof(proof, org_prefix, 1, other_commitment, 7);
+    int other_nonce = ZK_VerifyHierarchicalProof(proof, org_prefix, 1, commitment, 8);
+    int too_deep = ZK_VerifyHierarchicalProof(proof, org_prefix, MAX_DEPTH + 1, commitment, 7);
+    printf("  org prefix %d; other org %d, depth %d, identifier %d, nonce %d, past MAX_DEPTH %d\n", valid, other_org,
+           other_depth, other_id, other_nonce, too_deep);
+    if (valid != 1 || other_org != 0 || other_depth != 0 || other_id != 0 || other_nonce != 0 || too_deep != 0) {
+        return 1;
+    }
+
+    /* Malformed requests */
+    const char* nine[MAX_DEPTH + 1] = {"a", "b", "c", "d", "e", "f", "g", "h", "i"};
+    int past_identifier = ZK_GenerateHierarchicalProof(alice, 3, 4, 7, proof, sizeof(proof));
+    int nine_segments = ZK_ComputeHierarchicalID(nine, MAX_DEPTH + 1, commitment, sizeof(commitment));
+    int empty = ZK_ComputeHierarchicalID(alice, 0, commitment, sizeof(commitment));
+    int short_buffer = ZK_ComputeHierarchicalID(alice, 3, small, sizeof(small));
+    printf("  depth past the identifier %d, %d segments %d, no segments %d, short buffer %d\n", past_identifier,
+           MAX_DEPTH + 1, nine_segments, empty, short_buffer);
+    return past_identifier != -1 || nine_segments != -1 || empty != -1 || short_buffer != ZK_ERR_BUFFER_TOO_SMALL;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Hierarchical proofs disclose exactly the prefix they were made for"
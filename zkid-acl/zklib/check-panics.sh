@@ -0,0 +1,120 @@
+#!/bin/bash
+#
+# Build the library for the host and feed its entry points malformed input:
+# every truncation of a valid public ID, verifying key and proof, and
+# lengths past the per-argument limits. No call may panic or succeed, the
+# loaded keys must be unchanged afterwards, and a panic forced with
+# ZK_InjectPanic must come back as ZK_ERR_PANIC.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_InitVerifierOnly(const uint8_t*, size_t);
+int ZK_InjectPanic(void);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_GenerateProof(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_GenerateProofRaw(const uint8_t*, size_t, const uint8_t*, size_t, uint64_t, uint8_t*, size_t, size_t*);
+int ZK_VerifyProof(const char*, const char*, uint64_t);
+int ZK_VerifyProofRaw(const uint8_t*, size_t, const uint8_t*, size_t, uint64_t);
+
+#define ZK_ERR_PANIC -36
+#define BIG (128 * 1024)
+
+static const char* user = "alice_secret_12345";
+static char public_id[65], proof[1024], vk[4096];
+static int calls, panics, accepted;
+
+/* Record one call on malformed input: it must neither panic nor succeed */
+static void refused(int code, int success) {
+    calls++;
+    panics += code == ZK_ERR_PANIC;
+    accepted += code == success;
+}
+
+static const char* prefix(char* buf, const char* src, size_t len) {
+    memcpy(buf, src, len);
+    buf[len] = '\0';
+    return buf;
+}
+
+static int from_hex(const char* hex, uint8_t* out) {
+    size_t n = strlen(hex) / 2;
+    for (size_t i = 0; i < n; i++) {
+        unsigned byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return (int)n;
+}
+
+int main(void) {
+    char buf[4096], out[1024], vk_after[4096];
+    static uint8_t raw_vk[2048], raw_proof[512], raw_id[32], big[BIG];
+    if (ZK_Init() != 0 || ZK_ComputePublicID(user, strlen(user), public_id, 65, NULL) != 0 ||
+        ZK_GenerateProof(user, strlen(user), public_id, 1, proof, sizeof(proof)) != 0 ||
+        ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+    int vk_len = from_hex(vk, raw_vk);
+    int proof_len = from_hex(proof, raw_proof);
+    from_hex(public_id, raw_id);
+
+    for (size_t len = 0; len < strlen(public_id); len++) {
+        prefix(buf, public_id, len);
+        refused(ZK_GenerateProof(user, strlen(user), buf, 2, out, sizeof(out)), 0);
+        refused(ZK_VerifyProof(proof, buf, 1), 1);
+        refused(ZK_VerifyProofRaw(raw_proof, proof_len, raw_id, len / 2, 1), 1);
+    }
+    for (size_t len = 0; len < strlen(proof); len++) {
+        refused(ZK_VerifyProof(prefix(buf, proof, len), public_id, 1), 1);
+    }
+    for (int len = 0; len < proof_len; len++) {
+        refused(ZK_VerifyProofRaw(raw_proof, len, raw_id, 32, 1), 1);
+    }
+    for (int len = 0; len < vk_len; len++) {
+        refused(ZK_InitVerifierOnly(raw_vk, len), 0);
+    }
+
+    refused(ZK_ComputePublicID(user, SIZE_MAX, out, sizeof(out), NULL), 0);
+    refused(ZK_GenerateProof(user, SIZE_MAX, public_id, 2, out, sizeof(out)), 0);
+    refused(ZK_GenerateProofRaw((const uint8_t*)user, strlen(user), raw_id, SIZE_MAX, 2, (uint8_t*)out,
+                                sizeof(out), NULL), 0);
+    refused(ZK_VerifyProofRaw(raw_proof, SIZE_MAX, raw_id, 32, 1), 1);
+    refused(ZK_VerifyProofRaw(big, BIG, raw_id, 32, 1), 1);
+    refused(ZK_InitVerifierOnly(raw_vk, SIZE_MAX), 0);
+    refused(ZK_InitVerifierOnly(big, BIG), 0);
+
+    int same_key = ZK_ExportVerifyingKey(vk_after, sizeof(vk_after)) == 0 && strcmp(vk, vk_after) == 0;
+    printf("  %d malformed calls: %d panicked, %d accepted; same verifying key %d\n", calls, panics, accepted,
+           same_key);
+    if (panics != 0 || accepted != 0 || !same_key) {
+        return 1;
+    }
+
+    int injected = ZK_InjectPanic();
+    int fresh = ZK_GenerateProof(user, strlen(user), public_id, 3, out, sizeof(out)) == 0 &&
+                ZK_VerifyProof(out, public_id, 3) == 1 && ZK_VerifyProof(proof, public_id, 1) == 1;
+    printf("  injected panic %d, then round trip %d\n", injected, fresh);
+    return injected != ZK_ERR_PANIC || !fresh;
+}
+EOF
+
+echo "Checking build: fault-injection (release profile)"
+cargo rustc --release --lib --crate-type staticlib --features fault-injection --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" 2>/dev/null || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Malformed input never panics across the C boundary, and a panic is reported as ZK_ERR_PANIC"
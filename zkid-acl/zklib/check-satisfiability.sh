@@ -0,0 +1,71 @@
+#!/bin/bash
+#
+# Build the library with debug-circuit for the host and check the witness
+# check of the user-ID circuit: the user_id behind public_id satisfies it,
+# another user_id fails with ZK_ERR_UNSATISFIED and a report naming the
+# first failing constraint, a report buffer too small for that is
+# ZK_ERR_BUFFER_TOO_SMALL, and NULL, non-hex or over-long inputs are refused
+# before synthesis.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_CheckCircuitSatisfiability(const char*, size_t, const char*, uint64_t, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_UNSATISFIED -19
+#define ZK_ERR_INPUT_TOO_LARGE -30
+
+int main(void) {
+    const char* alice = "alice_secret_12345";
+    const char* bob = "bob_secret_67890";
+    char public_id[65], report[256], small[8];
+    if (ZK_Init() != 0 || ZK_ComputePublicID(alice, strlen(alice), public_id, sizeof(public_id), NULL) != 0) {
+        return 1;
+    }
+
+    report[0] = '\0';
+    int honest = ZK_CheckCircuitSatisfiability(alice, strlen(alice), public_id, 7, report, sizeof(report));
+    int other = ZK_CheckCircuitSatisfiability(bob, strlen(bob), public_id, 7, report, sizeof(report));
+    unsigned int index = 0;
+    char name[128] = "";
+    int named = sscanf(report, "constraint %u (%127[^)])", &index, name) == 2;
+    int short_report = ZK_CheckCircuitSatisfiability(bob, strlen(bob), public_id, 7, small, sizeof(small));
+    printf("  own user_id %d; another %d (%s), named %d; report buffer of %zu %d\n", honest, other, report, named,
+           sizeof(small), short_report);
+    if (honest != 0 || other != ZK_ERR_UNSATISFIED || !named || short_report != ZK_ERR_BUFFER_TOO_SMALL) {
+        return 1;
+    }
+
+    /* Past what the in-circuit SHA-256 hashes */
+    char long_id[56];
+    memset(long_id, 'a', sizeof(long_id));
+    int null_id = ZK_CheckCircuitSatisfiability(NULL, 5, public_id, 7, report, sizeof(report));
+    int null_public = ZK_CheckCircuitSatisfiability(alice, strlen(alice), NULL, 7, report, sizeof(report));
+    int not_hex = ZK_CheckCircuitSatisfiability(alice, strlen(alice), "not hex", 7, report, sizeof(report));
+    int too_long = ZK_CheckCircuitSatisfiability(long_id, sizeof(long_id), public_id, 7, report, sizeof(report));
+    printf("  NULL user_id %d, NULL public_id %d, non-hex public_id %d, over-long user_id %d\n", null_id,
+           null_public, not_hex, too_long);
+    return null_id != -1 || null_public != -1 || not_hex != -1 || too_long != ZK_ERR_INPUT_TOO_LARGE;
+}
+EOF
+
+echo "Checking build: debug-circuit"
+cargo rustc --release --lib --crate-type staticlib --features debug-circuit --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ The witness check names the constraint a wrong user_id breaks"
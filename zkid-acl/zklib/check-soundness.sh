@@ -0,0 +1,120 @@
+#!/bin/bash
+#
+# Build the default (in-circuit SHA-256) and legacy-user-id circuits for the
+# host with debug-circuit and check that only knowledge of user_id yields a
+# proof: a prover holding just public_id, the digest, cannot satisfy the
+# default circuit, and neither build accepts the other's proofs.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_ComputePublicID(const char*, size_t, char*, size_t, size_t*);
+int ZK_GenerateProof(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_VerifyProof(const char*, const char*, uint64_t);
+int ZK_CheckCircuitSatisfiability(const char*, size_t, const char*, uint64_t, char*, size_t);
+int ZK_RunConstraintAudit(char*, size_t);
+
+#define ZK_ERR_UNSATISFIED -19
+#define ZK_ERR_INPUT_TOO_LARGE -30
+
+static int hex_decode(const char* hex, char* out) {
+    size_t n = strlen(hex) / 2;
+    for (size_t i = 0; i < n; i++) {
+        unsigned int byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (char)byte;
+    }
+    return (int)n;
+}
+
+/* argv[1]: "default" or "legacy"; argv[2]: file receiving this build's
+ * proof; argv[3]: optional file with the other build's proof */
+int main(int argc, char** argv) {
+    const char* user_id = "alice_secret_12345";
+    char public_id[65], proof[1024], other[1024], report[4096], digest[32];
+    int legacy = strcmp(argv[1], "legacy") == 0;
+
+    if (ZK_Init() != 0 || ZK_ComputePublicID(user_id, strlen(user_id), public_id, sizeof(public_id), NULL) != 0) {
+        return 1;
+    }
+
+    int rc = ZK_GenerateProof(user_id, strlen(user_id), public_id, 7, proof, sizeof(proof));
+    int valid = ZK_VerifyProof(proof, public_id, 7);
+    int replayed = ZK_VerifyProof(proof, public_id, 8);
+    printf("  prove %d, verify %d, other nonce %d\n", rc, valid, replayed);
+    if (rc != 0 || valid != 1 || replayed != 0) {
+        return 1;
+    }
+    FILE* f = fopen(argv[2], "w");
+    fputs(proof, f);
+    fclose(f);
+
+    if (argc > 3 && (f = fopen(argv[3], "r")) != NULL) {
+        int read = fgets(other, sizeof(other), f) != NULL;
+        fclose(f);
+        int foreign = read ? ZK_VerifyProof(other, public_id, 7) : -1;
+        printf("  other build's proof: %d\n", foreign);
+        if (foreign != 0) {
+            return 1;
+        }
+    }
+
+    if (legacy) {
+        return 0;
+    }
+
+    /* Knowing only the digest: offered as the preimage it does not hash to
+     * public_id, and no witness bypasses the in-circuit hash */
+    int digest_len = hex_decode(public_id, digest);
+    rc = ZK_GenerateProof(digest, digest_len, public_id, 7, proof, sizeof(proof));
+    int sat = ZK_CheckCircuitSatisfiability(digest, digest_len, public_id, 7, report, sizeof(report));
+    printf("  hash-only prover: prove %d, circuit %d (%s)\n", rc, sat, report);
+    if (rc != -1 || sat != ZK_ERR_UNSATISFIED || strstr(report, "public_id_binding") == NULL) {
+        return 1;
+    }
+
+    char long_id[57];
+    memset(long_id, 'a', 56);
+    long_id[56] = 0;
+    ZK_ComputePublicID(long_id, 56, public_id, sizeof(public_id), NULL);
+    rc = ZK_GenerateProof(long_id, 56, public_id, 7, proof, sizeof(proof));
+    printf("  56-byte user_id: %d\n", rc);
+    if (rc != ZK_ERR_INPUT_TOO_LARGE) {
+        return 1;
+    }
+
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    return gaps == 0 ? 0 : 1;
+}
+EOF
+
+check_circuit() {
+    local circuit="$1"
+    local features="debug-circuit"
+    if [ "$circuit" = "legacy" ]; then
+        features="$features,legacy-user-id"
+    fi
+
+    echo "Checking circuit: $circuit"
+    cargo rustc --release --lib --crate-type staticlib --features "$features" --target-dir "$WORK_DIR/target-$circuit" -q
+    cc -o "$WORK_DIR/check-$circuit" "$WORK_DIR/check.c" "$WORK_DIR/target-$circuit/release/libzklib.a" -lpthread -ldl -lm
+    "$WORK_DIR/check-$circuit" "$circuit" "$WORK_DIR/proof-$circuit" "$2" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_circuit default
+check_circuit legacy "$WORK_DIR/proof-default"
+
+echo "✓ Only knowledge of user_id yields a user-ID proof"
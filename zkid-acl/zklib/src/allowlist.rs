@@ -0,0 +1,516 @@
+// ============================================================================
+// Allowlist Trees and Signed Deltas
+// ============================================================================
+//
+// The allowlist is a Merkle tree of depth ALLOWLIST_DEPTH over member
+// public IDs (as from ZK_ComputePublicID). Members take the next free leaf
+// in the order they are added; a removed member's leaf is emptied and not
+// reused, so the root commits to the whole membership history:
+//
+//   leaf  = SHA-256("zkid-acl/allowlist-leaf" | member)   (empty: 32 zeros)
+//   node  = SHA-256("zkid-acl/allowlist-node" | left | right)
+//
+// Instead of shipping new roots, an administrator publishes signed deltas
+//
+//   "ZKAD" | version u8 | epoch u64 | previous_root [32] | new_root [32]
+//   | added_count u32 | (len u32 | member)* | removed_count u32
+//   | (len u32 | member)* | signature [64]
+//
+// (hex, integers little-endian), the Ed25519 signature covering
+// "zkid-acl/allowlist-delta" and everything before it. The admin key is any
+// Ed25519 keypair. A tree starts at epoch 0 with the empty root and each
+// delta advances it by one, so verifiers that apply the chain hold the same
+// root as the administrator and an auditable record of every change.
+//
+// ZK_ApplyAllowlistDelta checks, in order: the encoding (ZK_ERR_CORRUPT),
+// the signature (ZK_ERR_AUTH), the epoch (ZK_ERR_DELTA_REPLAYED for one
+// already applied, ZK_ERR_DELTA_OUT_OF_ORDER for a gap), then the previous
+// root, the mutations (removals first, then additions, each in order) and
+// the new root (ZK_ERR_ROOT_MISMATCH). A rejected delta leaves the tree
+// unchanged.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SECRET_KEY_LENGTH};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::error::{
+    ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_DELTA_OUT_OF_ORDER, ZK_ERR_DELTA_REPLAYED,
+    ZK_ERR_ROOT_MISMATCH, ZK_ERR_UNKNOWN_HANDLE,
+};
+use crate::ffi::{read_bytes, read_slice, write_cstr, MAX_FIELD_LEN};
+use crate::{bytes_to_hex, hex_to_bytes};
+
+/// Levels of the allowlist tree; it holds 2^ALLOWLIST_DEPTH leaves
+pub const ALLOWLIST_DEPTH: usize = 20;
+
+/// Most members one delta may add or remove
+pub const MAX_DELTA_MEMBERS: usize = 4096;
+
+const LEAF_DOMAIN: &[u8] = b"zkid-acl/allowlist-leaf";
+const NODE_DOMAIN: &[u8] = b"zkid-acl/allowlist-node";
+const DELTA_DOMAIN: &[u8] = b"zkid-acl/allowlist-delta";
+
+const DELTA_MAGIC: &[u8; 4] = b"ZKAD";
+const DELTA_VERSION: u8 = 1;
+const SIGNATURE_LEN: usize = 64;
+
+type Hash = [u8; 32];
+
+const EMPTY_LEAF: Hash = [0u8; 32];
+
+fn leaf_hash(member: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(LEAF_DOMAIN);
+    hasher.update(member);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(NODE_DOMAIN);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Root of an all-empty subtree at each height
+fn empty_roots() -> [Hash; ALLOWLIST_DEPTH + 1] {
+    let mut roots = [EMPTY_LEAF; ALLOWLIST_DEPTH + 1];
+    for height in 1..=ALLOWLIST_DEPTH {
+        roots[height] = node_hash(&roots[height - 1], &roots[height - 1]);
+    }
+    roots
+}
+
+/// Incremental Merkle tree over the allowlist
+#[derive(Clone, Debug)]
+pub struct AllowlistTree {
+    // levels[0] are the leaves; levels[h] holds the non-empty prefix of
+    // height h, missing right siblings are empty subtrees
+    levels: Vec<Vec<Hash>>,
+    members: HashMap<Vec<u8>, usize>,
+    empty: [Hash; ALLOWLIST_DEPTH + 1],
+    epoch: u64,
+}
+
+impl Default for AllowlistTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AllowlistTree {
+    pub fn new() -> Self {
+        Self {
+            levels: vec![Vec::new(); ALLOWLIST_DEPTH + 1],
+            members: HashMap::new(),
+            empty: empty_roots(),
+            epoch: 0,
+        }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels[ALLOWLIST_DEPTH].first().copied().unwrap_or(self.empty[ALLOWLIST_DEPTH])
+    }
+
+    /// Number of deltas applied
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn contains(&self, member: &[u8]) -> bool {
+        self.members.contains_key(member)
+    }
+
+    /// Set leaf `index` and rehash its path to the root
+    fn set_leaf(&mut self, index: usize, leaf: Hash) {
+        let mut index = index;
+        let mut node = leaf;
+        for height in 0..=ALLOWLIST_DEPTH {
+            let level = &mut self.levels[height];
+            if index == level.len() {
+                level.push(node);
+            } else {
+                level[index] = node;
+            }
+            if height == ALLOWLIST_DEPTH {
+                break;
+            }
+            let sibling = index ^ 1;
+            let sibling_hash = level.get(sibling).copied().unwrap_or(self.empty[height]);
+            node = if sibling > index {
+                node_hash(&node, &sibling_hash)
+            } else {
+                node_hash(&sibling_hash, &node)
+            };
+            index /= 2;
+        }
+    }
+
+    /// Add a member at the next free leaf; false if present or full
+    pub fn add(&mut self, member: &[u8]) -> bool {
+        let index = self.levels[0].len();
+        if self.contains(member) || index >= 1 << ALLOWLIST_DEPTH {
+            return false;
+        }
+        self.set_leaf(index, leaf_hash(member));
+        self.members.insert(member.to_vec(), index);
+        true
+    }
+
+    /// Empty a member's leaf; false if absent
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        match self.members.remove(member) {
+            Some(index) => {
+                self.set_leaf(index, EMPTY_LEAF);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply a delta's mutations to a copy; None if any does not apply
+    fn mutated(&self, delta: &AllowlistDelta) -> Option<Self> {
+        let mut next = self.clone();
+        let applied = delta.removed.iter().all(|member| next.remove(member))
+            && delta.added.iter().all(|member| next.add(member));
+        next.epoch += 1;
+        applied.then_some(next)
+    }
+
+    /// Apply a decoded delta whose signature has been checked
+    pub fn apply(&mut self, delta: &AllowlistDelta) -> Result<(), c_int> {
+        if delta.epoch <= self.epoch {
+            return Err(ZK_ERR_DELTA_REPLAYED);
+        }
+        if delta.epoch != self.epoch + 1 {
+            return Err(ZK_ERR_DELTA_OUT_OF_ORDER);
+        }
+        if delta.previous_root != self.root() {
+            return Err(ZK_ERR_ROOT_MISMATCH);
+        }
+        match self.mutated(delta) {
+            Some(next) if next.root() == delta.new_root => {
+                *self = next;
+                Ok(())
+            }
+            _ => Err(ZK_ERR_ROOT_MISMATCH),
+        }
+    }
+
+    /// Apply `added` and `removed` as the next delta and return it signed
+    pub fn sign_delta(
+        &mut self,
+        admin: &SigningKey,
+        added: Vec<Vec<u8>>,
+        removed: Vec<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        let mut delta = AllowlistDelta {
+            epoch: self.epoch + 1,
+            previous_root: self.root(),
+            new_root: EMPTY_LEAF,
+            added,
+            removed,
+        };
+        let next = self.mutated(&delta)?;
+        delta.new_root = next.root();
+        *self = next;
+
+        let mut out = delta.body();
+        out.extend_from_slice(&admin.sign(&delta.signed_message()).to_bytes());
+        Some(out)
+    }
+}
+
+/// One signed step of the allowlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllowlistDelta {
+    pub epoch: u64,
+    pub previous_root: Hash,
+    pub new_root: Hash,
+    pub added: Vec<Vec<u8>>,
+    pub removed: Vec<Vec<u8>>,
+}
+
+fn put_members(out: &mut Vec<u8>, members: &[Vec<u8>]) {
+    out.extend_from_slice(&(members.len() as u32).to_le_bytes());
+    for member in members {
+        out.extend_from_slice(&(member.len() as u32).to_le_bytes());
+        out.extend_from_slice(member);
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Some(head)
+}
+
+fn take_u32(data: &mut &[u8]) -> Option<usize> {
+    Some(u32::from_le_bytes(take(data, 4)?.try_into().ok()?) as usize)
+}
+
+fn take_members(data: &mut &[u8]) -> Option<Vec<Vec<u8>>> {
+    let count = take_u32(data)?;
+    if count > MAX_DELTA_MEMBERS {
+        return None;
+    }
+    (0..count)
+        .map(|_| {
+            let len = take_u32(data)?;
+            Some(take(data, len)?.to_vec())
+        })
+        .collect()
+}
+
+impl AllowlistDelta {
+    fn body(&self) -> Vec<u8> {
+        let mut out = DELTA_MAGIC.to_vec();
+        out.push(DELTA_VERSION);
+        out.extend_from_slice(&self.epoch.to_le_bytes());
+        out.extend_from_slice(&self.previous_root);
+        out.extend_from_slice(&self.new_root);
+        put_members(&mut out, &self.added);
+        put_members(&mut out, &self.removed);
+        out
+    }
+
+    fn signed_message(&self) -> Vec<u8> {
+        let mut message = DELTA_DOMAIN.to_vec();
+        message.extend_from_slice(&self.body());
+        message
+    }
+
+    /// Decode a signed delta into the delta and its signature
+    pub fn decode(data: &[u8]) -> Option<(Self, Signature)> {
+        let body_len = data.len().checked_sub(SIGNATURE_LEN)?;
+        let (mut body, signature) = data.split_at(body_len);
+        if take(&mut body, DELTA_MAGIC.len())? != DELTA_MAGIC || take(&mut body, 1)? != [DELTA_VERSION] {
+            return None;
+        }
+        let delta = Self {
+            epoch: u64::from_le_bytes(take(&mut body, 8)?.try_into().ok()?),
+            previous_root: take(&mut body, 32)?.try_into().ok()?,
+            new_root: take(&mut body, 32)?.try_into().ok()?,
+            added: take_members(&mut body)?,
+            removed: take_members(&mut body)?,
+        };
+        if !body.is_empty() {
+            return None;
+        }
+        Some((delta, Signature::from_slice(signature).ok()?))
+    }
+
+    pub fn verify(&self, admin: &VerifyingKey, signature: &Signature) -> bool {
+        #[cfg(feature = "strict")]
+        return admin.verify_strict(&self.signed_message(), signature).is_ok();
+        #[cfg(not(feature = "strict"))]
+        return ed25519_dalek::Verifier::verify(admin, &self.signed_message(), signature).is_ok();
+    }
+}
+
+// ============================================================================
+// Tree Handles
+// ============================================================================
+
+struct Trees {
+    next_handle: u64,
+    active: HashMap<u64, AllowlistTree>,
+}
+
+static TREES: Mutex<Option<Trees>> = Mutex::new(None);
+
+fn with_trees<R>(f: impl FnOnce(&mut Trees) -> R) -> R {
+    let mut guard = TREES.lock().unwrap_or_else(|e| e.into_inner());
+    let trees = guard.get_or_insert_with(|| Trees {
+        next_handle: 1,
+        active: HashMap::new(),
+    });
+    f(trees)
+}
+
+fn with_tree(handle: u64, f: impl FnOnce(&mut AllowlistTree) -> c_int) -> c_int {
+    with_trees(|trees| match trees.active.get_mut(&handle) {
+        Some(tree) => f(tree),
+        None => ZK_ERR_UNKNOWN_HANDLE,
+    })
+}
+
+/// Release every tree (ZK_Cleanup)
+pub fn cleanup() {
+    *TREES.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+fn read_members(members: *const *const c_char, count: usize) -> Option<Vec<Vec<u8>>> {
+    read_slice(members, count, MAX_DELTA_MEMBERS)
+        .ok()?
+        .iter()
+        .map(|&member| c_str(member).map(|member| member.as_bytes().to_vec()))
+        .collect()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Create an empty allowlist tree at epoch 0
+///
+/// Returns 0 and writes the handle to `handle_out`, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_AllowlistCreate(handle_out: *mut u64) -> c_int {
+    if handle_out.is_null() {
+        return -1;
+    }
+    with_trees(|trees| {
+        let handle = trees.next_handle;
+        trees.next_handle += 1;
+        trees.active.insert(handle, AllowlistTree::new());
+        unsafe { *handle_out = handle };
+    });
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Release an allowlist tree; unknown handles are ignored
+#[no_mangle]
+pub extern "C" fn ZK_AllowlistFree(handle: u64) {
+    with_trees(|trees| {
+        trees.active.remove(&handle);
+    });
+}
+}
+
+crate::unwind::entry_point! {
+/// Current root (hex, 64 characters) and epoch of a tree
+///
+/// `epoch_out` may be NULL. Returns 0 on success, ZK_ERR_UNKNOWN_HANDLE,
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL `root_out`.
+#[no_mangle]
+pub extern "C" fn ZK_AllowlistRoot(
+    handle: u64,
+    root_out: *mut c_char,
+    root_out_size: usize,
+    epoch_out: *mut u64,
+) -> c_int {
+    with_tree(handle, |tree| {
+        if let Err(e) = write_cstr(root_out, root_out_size, &bytes_to_hex(&tree.root())) {
+            return e.code();
+        }
+        if let Some(out) = unsafe { epoch_out.as_mut() } {
+            *out = tree.epoch();
+        }
+        0
+    })
+}
+}
+
+crate::unwind::entry_point! {
+/// Whether `member` (e.g. a public ID) is on the allowlist
+///
+/// Returns 1 if it is, 0 if not, ZK_ERR_UNKNOWN_HANDLE, or -1 on bad input.
+#[no_mangle]
+pub extern "C" fn ZK_AllowlistContains(handle: u64, member: *const c_char, member_len: usize) -> c_int {
+    let member = match read_bytes(member, member_len, MAX_FIELD_LEN) {
+        Ok(bytes) if !member.is_null() => bytes,
+        Ok(_) => return -1,
+        Err(e) => return e.code(),
+    };
+    with_tree(handle, |tree| tree.contains(member) as c_int)
+}
+}
+
+crate::unwind::entry_point! {
+/// Administrator: apply the next change to a tree and sign it as a delta
+///
+/// Removes `removed`, then adds `added` (NUL-terminated members, e.g. public
+/// IDs) and writes the delta signed with `admin_private_key` (hex Ed25519
+/// secret) to `delta_out` (hex). Returns 0 on success,
+/// ZK_ERR_ROOT_MISMATCH if a removed member is absent or an added one
+/// present (the tree is then unchanged), ZK_ERR_UNKNOWN_HANDLE,
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 on bad input. A delta too large for the
+/// buffer is still applied to the tree; size the buffer generously.
+#[no_mangle]
+pub extern "C" fn ZK_CreateAllowlistDelta(
+    handle: u64,
+    admin_private_key: *const c_char,
+    added: *const *const c_char,
+    added_count: usize,
+    removed: *const *const c_char,
+    removed_count: usize,
+    delta_out: *mut c_char,
+    delta_out_size: usize,
+) -> c_int {
+    let admin = c_str(admin_private_key)
+        .and_then(|key| hex_to_bytes(key).ok())
+        .and_then(|bytes| <[u8; SECRET_KEY_LENGTH]>::try_from(bytes.as_slice()).ok())
+        .map(|secret| SigningKey::from_bytes(&secret));
+    let (admin, added, removed) = match (
+        admin,
+        read_members(added, added_count),
+        read_members(removed, removed_count),
+    ) {
+        (Some(admin), Some(added), Some(removed)) if !delta_out.is_null() => (admin, added, removed),
+        _ => return -1,
+    };
+
+    with_tree(handle, |tree| match tree.sign_delta(&admin, added, removed) {
+        Some(delta) => match write_cstr(delta_out, delta_out_size, &bytes_to_hex(&delta)) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        None => ZK_ERR_ROOT_MISMATCH,
+    })
+}
+}
+
+crate::unwind::entry_point! {
+/// Verifier: apply a signed allowlist delta to a tree
+///
+/// `delta_blob` is the hex output of ZK_CreateAllowlistDelta and
+/// `admin_public_key` the administrator's hex Ed25519 key. Returns 0 on
+/// success; otherwise the tree is unchanged and the result is
+/// ZK_ERR_CORRUPT, ZK_ERR_AUTH, ZK_ERR_DELTA_REPLAYED,
+/// ZK_ERR_DELTA_OUT_OF_ORDER or ZK_ERR_ROOT_MISMATCH (see above),
+/// ZK_ERR_UNKNOWN_HANDLE, or -1 on NULL or a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_ApplyAllowlistDelta(
+    handle: u64,
+    delta_blob: *const c_char,
+    admin_public_key: *const c_char,
+) -> c_int {
+    let admin = c_str(admin_public_key)
+        .and_then(|key| hex_to_bytes(key).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(bytes.as_slice().try_into().ok()?).ok());
+    let (admin, delta_hex) = match (admin, c_str(delta_blob)) {
+        (Some(admin), Some(delta_hex)) => (admin, delta_hex),
+        _ => return -1,
+    };
+    let (delta, signature) = match hex_to_bytes(delta_hex).ok().and_then(|data| AllowlistDelta::decode(&data)) {
+        Some(decoded) => decoded,
+        None => return ZK_ERR_CORRUPT,
+    };
+    if !delta.verify(&admin, &signature) {
+        return ZK_ERR_AUTH;
+    }
+
+    with_tree(handle, |tree| match tree.apply(&delta) {
+        Ok(()) => 0,
+        Err(code) => code,
+    })
+}
+}
@@ -0,0 +1,158 @@
+// ============================================================================
+// Batch Verification
+// ============================================================================
+//
+// A verifier checking many user-ID proofs pays three Miller loops and a
+// final exponentiation for each. A Groth16 proof (A, B, C) holds when
+//
+//   e(A, B) · e(L, -gamma) · e(C, -delta) = e(alpha, beta)
+//
+// with L the public inputs folded into gamma_abc. Raising proof i's
+// equation to a random 128-bit r_i and multiplying them gives one check,
+//
+//   Π e(r_i·A_i, B_i) · e(Σ r_i·L_i, -gamma) · e(Σ r_i·C_i, -delta)
+//     = e(alpha, beta)^(Σ r_i)
+//
+// n + 2 Miller loops and one final exponentiation for n proofs. The r_i
+// come from the OS after the proofs are fixed, so a batch holding an
+// invalid proof passes with probability about 2^-128. When the batch
+// fails, each proof is verified on its own to tell which.
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_snark::SNARK;
+use rand_core::{OsRng, RngCore};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use crate::ffi::read_slice;
+use crate::{current_verifying_key, decode_statement, hex_to_bytes};
+
+/// Most proofs one ZK_BatchVerifyProofs call takes
+pub const MAX_BATCH_PROOFS: usize = 4096;
+
+/// Random nonzero 128-bit coefficient; None if the OS generator fails
+fn coefficient() -> Option<Fr> {
+    let mut bytes = [0u8; 16];
+    OsRng.try_fill_bytes(&mut bytes).ok()?;
+    Some(Fr::from(u128::from_le_bytes(bytes).max(1)))
+}
+
+/// One pairing check over every (proof, public inputs) pair
+///
+/// True if all are valid; false if any is not (but for probability about
+/// 2^-128), if the input counts do not match the key or if no randomness
+/// could be drawn.
+pub fn check(pvk: &PreparedVerifyingKey<Bn254>, statements: &[(Proof<Bn254>, Vec<Fr>)]) -> bool {
+    let gamma_abc = &pvk.vk.gamma_abc_g1;
+    let mut input_scalars = vec![Fr::zero(); gamma_abc.len()];
+    let mut scaled_a = Vec::with_capacity(statements.len());
+    let mut c_sum = G1Projective::zero();
+    let mut r_sum = Fr::zero();
+
+    for (proof, public_inputs) in statements {
+        if public_inputs.len() + 1 != gamma_abc.len() {
+            return false;
+        }
+        let r = match coefficient() {
+            Some(r) => r,
+            None => return false,
+        };
+        input_scalars[0] += r;
+        for (scalar, input) in input_scalars[1..].iter_mut().zip(public_inputs) {
+            *scalar += r * input;
+        }
+        scaled_a.push(proof.a * r);
+        c_sum += proof.c * r;
+        r_sum += r;
+    }
+    let inputs = match G1Projective::msm(gamma_abc, &input_scalars) {
+        Ok(inputs) => inputs,
+        Err(_) => return false,
+    };
+
+    let mut g1: Vec<<Bn254 as Pairing>::G1Prepared> =
+        G1Projective::normalize_batch(&scaled_a).into_iter().map(Into::into).collect();
+    let mut g2: Vec<<Bn254 as Pairing>::G2Prepared> = statements.iter().map(|(proof, _)| proof.b.into()).collect();
+    g1.push(inputs.into_affine().into());
+    g2.push(pvk.gamma_g2_neg_pc.clone());
+    g1.push(c_sum.into_affine().into());
+    g2.push(pvk.delta_g2_neg_pc.clone());
+
+    match Bn254::final_exponentiation(Bn254::multi_miller_loop(g1, g2)) {
+        Some(result) => result.0 == pvk.alpha_g1_beta_g2.pow(r_sum.into_bigint()),
+        None => false,
+    }
+}
+
+/// Hex text of a C string argument; None on NULL or invalid UTF-8
+fn hex_arg(ptr: *const c_char) -> Option<Vec<u8>> {
+    if ptr.is_null() {
+        return None;
+    }
+    let text = unsafe { CStr::from_ptr(ptr) }.to_str().ok()?;
+    hex_to_bytes(text).ok()
+}
+
+crate::unwind::entry_point! {
+/// Verify `count` proofs with one batched pairing check
+///
+/// Proof i is checked as ZK_VerifyProof(proofs_hex[i], public_ids[i],
+/// nonces[i]) would, and 1 (valid) or 0 is written to results_out[i]. The
+/// decodable proofs are verified together; if that check fails, each is
+/// verified on its own to find the invalid ones. Returns the number of
+/// valid proofs, -1 on NULL arrays or more than MAX_BATCH_PROOFS.
+#[no_mangle]
+pub extern "C" fn ZK_BatchVerifyProofs(
+    proofs_hex: *const *const c_char,
+    public_ids: *const *const c_char,
+    nonces: *const u64,
+    count: usize,
+    results_out: *mut c_int,
+) -> c_int {
+    let (proofs_hex, public_ids, nonces) = match (
+        read_slice(proofs_hex, count, MAX_BATCH_PROOFS),
+        read_slice(public_ids, count, MAX_BATCH_PROOFS),
+        read_slice(nonces, count, MAX_BATCH_PROOFS),
+    ) {
+        (Ok(proofs_hex), Ok(public_ids), Ok(nonces)) => (proofs_hex, public_ids, nonces),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return e.code(),
+    };
+    if count == 0 {
+        return 0;
+    }
+    if results_out.is_null() {
+        return -1;
+    }
+    let results = unsafe { std::slice::from_raw_parts_mut(results_out, count) };
+    results.fill(0);
+
+    let pvk = match current_verifying_key() {
+        Some(pvk) => pvk,
+        None => return 0,
+    };
+
+    // Result slot of each decoded statement
+    let mut slots = Vec::with_capacity(count);
+    let mut statements = Vec::with_capacity(count);
+    for i in 0..count {
+        let statement = hex_arg(proofs_hex[i])
+            .zip(hex_arg(public_ids[i]))
+            .and_then(|(proof_bytes, public_id_bytes)| decode_statement(&proof_bytes, &public_id_bytes, nonces[i]));
+        if let Some(statement) = statement {
+            slots.push(i);
+            statements.push(statement);
+        }
+    }
+
+    let batched = statements.len() > 1 && check(&pvk, &statements);
+    for (i, (proof, public_inputs)) in slots.into_iter().zip(&statements) {
+        let valid = batched || matches!(Groth16::<Bn254>::verify_with_processed_vk(&pvk, public_inputs, proof), Ok(true));
+        results[i] = valid as c_int;
+    }
+    results.iter().filter(|&&code| code == 1).count() as c_int
+}
+}
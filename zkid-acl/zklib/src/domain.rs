@@ -0,0 +1,75 @@
+// ============================================================================
+// Hash Domains
+// ============================================================================
+//
+// public_id is SHA-256(user_id), the same bare construction zkid-vc uses for
+// its credential message hash and field mapping, so a public ID is also a
+// valid digest in those roles and the other way round. Format version 2
+// prefixes the public ID with a tag naming its purpose and a version:
+//
+//   public_id        SHA-256("zkid.acl.pubid.v1" | user_id)
+//   field mapping    SHA-256("zkid.fr.map.v1" | data) mod r   (legacy circuit)
+//
+// Tagged public IDs need their own proof formats (see proof.rs): 4 for the
+// preimage circuit, which hashes the tag in-circuit and so has its own keys,
+// and 5 for the legacy circuit, whose keys are shared with format 2.
+//
+// Version 1 (untagged) stays the default, since a tagged public ID is a
+// different public ID; ZK_SetFormatVersion opts in. It picks what
+// ZK_ComputePublicID returns and the format proofs are generated in. Default
+// builds set up the preimage circuit for the version current at ZK_Init
+// (or ZK_InitVerifierOnly) and verify proofs of that format only.
+
+use sha2::{Digest, Sha256};
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Untagged public IDs, as before domain separation (the default)
+pub const ZK_FORMAT_VERSION_1: u32 = 1;
+/// Purpose tags on the public ID and the field mapping
+pub const ZK_FORMAT_VERSION_2: u32 = 2;
+
+/// Prefix of a tagged public ID preimage
+pub const PUBLIC_ID_TAG: &[u8] = b"zkid.acl.pubid.v1";
+/// Prefix of the tagged field mapping (proof format 5)
+#[cfg(feature = "legacy-user-id")]
+pub(crate) const FIELD_MAP_TAG: &[u8] = b"zkid.fr.map.v1";
+
+static VERSION: AtomicU32 = AtomicU32::new(ZK_FORMAT_VERSION_1);
+
+/// Whether new public IDs and proofs carry the purpose tags
+pub fn tagged() -> bool {
+    VERSION.load(Ordering::Relaxed) == ZK_FORMAT_VERSION_2
+}
+
+/// Preimage of the public ID of `user_id`, tagged or not
+pub fn public_id_preimage(tagged: bool, user_id: &[u8]) -> Vec<u8> {
+    let prefix = if tagged { PUBLIC_ID_TAG } else { &[] };
+    [prefix, user_id].concat()
+}
+
+/// The public ID of `user_id`, tagged or not
+pub fn public_id(tagged: bool, user_id: &[u8]) -> [u8; 32] {
+    Sha256::digest(public_id_preimage(tagged, user_id)).into()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Choose untagged (ZK_FORMAT_VERSION_1) or tagged (_2) public IDs and
+/// proofs
+///
+/// Default builds fix the proof format with the keys: call this before
+/// ZK_Init or ZK_InitVerifierOnly, and again before loading keys of the
+/// other version. Returns 0 on success, -1 for an unknown version.
+#[no_mangle]
+pub extern "C" fn ZK_SetFormatVersion(version: u32) -> c_int {
+    if !matches!(version, ZK_FORMAT_VERSION_1 | ZK_FORMAT_VERSION_2) {
+        return -1;
+    }
+    VERSION.store(version, Ordering::Relaxed);
+    0
+}
+}
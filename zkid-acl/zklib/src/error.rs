@@ -0,0 +1,59 @@
+// ============================================================================
+// C API Error Codes
+// ============================================================================
+//
+// 0 means success and -1 a generic failure; the negative codes below identify
+// failures the caller is expected to handle differently. Codes shared with
+// zkid-vc keep its numbering.
+
+use std::os::raw::c_int;
+
+/// Not a failure: the output pointer was NULL (or its size 0), so no
+/// result was computed and the required buffer size went to `required_size_out`
+pub const ZK_STATUS_SIZE_QUERY: c_int = 1;
+
+/// The proving keys were not set up: ZK_Init has not run, or ZK_Cleanup
+/// released them
+pub const ZK_ERR_NOT_INITIALIZED: c_int = -2;
+
+/// The output buffer cannot hold the result; strings need one byte for the NUL
+pub const ZK_ERR_BUFFER_TOO_SMALL: c_int = -5;
+
+/// A hex argument is not valid hex
+pub const ZK_ERR_BAD_HEX: c_int = -6;
+
+/// The prover failed to produce a proof for an accepted witness
+pub const ZK_ERR_PROVE_FAILED: c_int = -7;
+
+/// Only the verifying key is loaded (ZK_InitVerifierOnly), so this process
+/// cannot prove
+pub const ZK_ERR_NO_PROVING_KEY: c_int = -8;
+
+/// Authentication failed: wrong key or tampered signature
+pub const ZK_ERR_AUTH: c_int = -13;
+
+/// Input is truncated, structurally invalid or of an unknown version
+pub const ZK_ERR_CORRUPT: c_int = -14;
+
+/// The handle is unknown or already released
+pub const ZK_ERR_UNKNOWN_HANDLE: c_int = -15;
+
+/// The allowlist delta's epoch was already applied
+pub const ZK_ERR_DELTA_REPLAYED: c_int = -27;
+
+/// The allowlist delta skips epochs; earlier deltas are missing
+pub const ZK_ERR_DELTA_OUT_OF_ORDER: c_int = -28;
+
+/// The allowlist delta does not fit this tree: its previous root differs,
+/// its mutations do not apply, or they do not yield its new root
+pub const ZK_ERR_ROOT_MISMATCH: c_int = -29;
+
+/// An input is longer than any valid value for its argument
+pub const ZK_ERR_INPUT_TOO_LARGE: c_int = -30;
+
+/// The OS random generator failed; no proof was generated
+pub const ZK_ERR_ENTROPY: c_int = -34;
+
+/// The library panicked inside the call; the panic was caught at the C
+/// boundary and later calls keep working
+pub const ZK_ERR_PANIC: c_int = -36;
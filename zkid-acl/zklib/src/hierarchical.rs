@@ -0,0 +1,526 @@
+// ============================================================================
+// Hierarchical Identifiers with Partial Disclosure
+// ============================================================================
+//
+// Identifiers such as "org/team/user" are committed as a hash chain
+//
+//   h_0 = IV,  h_i = H(h_{i-1}, s_i),  commitment = H(h_MAX, n)
+//
+// where s_i is the SHA-256 of segment i reduced into the field, n is the
+// number of real segments, and segments beyond n are padded with zero. H is
+// MiMC-7 in Miyaguchi-Preneel mode so the whole chain is proven in-circuit.
+//
+// A proof discloses h_d for a prover-chosen depth d (0 <= d <= n) and binds
+// it to the full commitment: the verifier learns e.g. the org prefix while
+// the identity behind it stays hidden. Depth 0 discloses only the IV. Showing
+// a different prefix for the same commitment requires a MiMC collision.
+//
+// Public inputs (in order): disclosed_prefix, depth, commitment, nonce.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{Field, PrimeField};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_relations::{lc, ns};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::error::{ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED};
+use crate::ffi::{read_slice, write_cstr};
+use crate::{bytes_to_hex, hex_to_bytes, prover_rng, seeded_rng};
+
+/// Maximum number of segments in a hierarchical identifier
+pub const MAX_DEPTH: usize = 8;
+
+const MIMC_ROUNDS: usize = 91;
+const IV_DOMAIN: &[u8] = b"zkid/hierarchical-id/v1";
+const MIMC_DOMAIN: &[u8] = b"zkid/mimc7/";
+
+/// Proving and verifying key of the hierarchical circuit
+type HierKeys = (ProvingKey<Bn254>, PreparedVerifyingKey<Bn254>);
+
+// Separate keys: this circuit is independent of the user-ID circuit. Held
+// in Arc so proving and verifying run outside the lock.
+static HIER_KEYS: Mutex<Option<Arc<HierKeys>>> = Mutex::new(None);
+
+fn field_from_hash(data: &[u8]) -> Fr {
+    Fr::from_be_bytes_mod_order(&Sha256::digest(data))
+}
+
+fn round_constants() -> Vec<Fr> {
+    (0..MIMC_ROUNDS as u32)
+        .map(|i| {
+            let mut data = MIMC_DOMAIN.to_vec();
+            data.extend_from_slice(&i.to_le_bytes());
+            field_from_hash(&data)
+        })
+        .collect()
+}
+
+fn chain_iv() -> Fr {
+    field_from_hash(IV_DOMAIN)
+}
+
+/// Field element for one identifier segment
+pub fn segment_to_field(segment: &[u8]) -> Fr {
+    field_from_hash(segment)
+}
+
+/// MiMC-7 compression H(left, right) = E_left(right) + right
+fn mimc_compress(constants: &[Fr], left: Fr, right: Fr) -> Fr {
+    let mut x = right;
+    for c in constants {
+        let t = x + left + c;
+        let t2 = t.square();
+        let t4 = t2.square();
+        x = t4 * t2 * t;
+    }
+    x + left + right
+}
+
+/// Chain values h_0..=h_MAX and the final commitment for `segments`
+pub fn compute_chain(segments: &[&[u8]]) -> Option<(Vec<Fr>, Fr)> {
+    if segments.is_empty() || segments.len() > MAX_DEPTH {
+        return None;
+    }
+
+    let constants = round_constants();
+    let mut chain = vec![chain_iv()];
+    for i in 0..MAX_DEPTH {
+        let s = segments.get(i).map_or(Fr::from(0u64), |seg| segment_to_field(seg));
+        chain.push(mimc_compress(&constants, chain[i], s));
+    }
+
+    let commitment = mimc_compress(&constants, chain[MAX_DEPTH], Fr::from(segments.len() as u64));
+    Some((chain, commitment))
+}
+
+// ============================================================================
+// Circuit
+// ============================================================================
+
+#[derive(Clone)]
+pub(crate) struct HierarchicalCircuit {
+    // Private witness
+    segments: Option<Vec<Fr>>,
+    segment_count: Option<Fr>,
+
+    // Public inputs
+    disclosed_prefix: Option<Fr>,
+    depth: Option<u64>,
+    commitment: Option<Fr>,
+    nonce: Option<Fr>,
+}
+
+/// Enforce `out = H(left, right)` and return (out, value)
+fn mimc_gadget(
+    cs: &ConstraintSystemRef<Fr>,
+    constants: &[Fr],
+    left: &LinearCombination<Fr>,
+    left_val: Option<Fr>,
+    right: &LinearCombination<Fr>,
+    right_val: Option<Fr>,
+) -> Result<(LinearCombination<Fr>, Option<Fr>), SynthesisError> {
+    let mut x = right.clone();
+    let mut x_val = right_val;
+
+    for c in constants {
+        let t = x.clone() + left + (*c, Variable::One);
+        let t_val = x_val.zip(left_val).map(|(x, l)| x + l + c);
+
+        let t2_val = t_val.map(|t| t.square());
+        let t2 = cs.new_witness_variable(|| t2_val.ok_or(SynthesisError::AssignmentMissing))?;
+        {
+            let _ns = ns!(cs, "mimc_t2");
+            cs.enforce_constraint(t.clone(), t.clone(), lc!() + t2)?;
+        }
+
+        let t4_val = t2_val.map(|t2| t2.square());
+        let t4 = cs.new_witness_variable(|| t4_val.ok_or(SynthesisError::AssignmentMissing))?;
+        {
+            let _ns = ns!(cs, "mimc_t4");
+            cs.enforce_constraint(lc!() + t2, lc!() + t2, lc!() + t4)?;
+        }
+
+        let t6_val = t4_val.zip(t2_val).map(|(t4, t2)| t4 * t2);
+        let t6 = cs.new_witness_variable(|| t6_val.ok_or(SynthesisError::AssignmentMissing))?;
+        {
+            let _ns = ns!(cs, "mimc_t6");
+            cs.enforce_constraint(lc!() + t4, lc!() + t2, lc!() + t6)?;
+        }
+
+        let t7_val = t6_val.zip(t_val).map(|(t6, t)| t6 * t);
+        let t7 = cs.new_witness_variable(|| t7_val.ok_or(SynthesisError::AssignmentMissing))?;
+        {
+            let _ns = ns!(cs, "mimc_t7");
+            cs.enforce_constraint(lc!() + t6, t, lc!() + t7)?;
+        }
+
+        x = lc!() + t7;
+        x_val = t7_val;
+    }
+
+    let out = x + left + right;
+    let out_val = x_val.zip(left_val).zip(right_val).map(|((x, l), r)| x + l + r);
+    Ok((out, out_val))
+}
+
+impl ConstraintSynthesizer<Fr> for HierarchicalCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let constants = round_constants();
+
+        // Allocate private inputs
+        let mut segment_vars = Vec::with_capacity(MAX_DEPTH);
+        for i in 0..MAX_DEPTH {
+            let value = self.segments.as_ref().map(|s| s[i]);
+            let var = cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+            segment_vars.push((var, value));
+        }
+        let count_var = cs.new_witness_variable(|| {
+            self.segment_count.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Allocate public inputs
+        let prefix_var = cs.new_input_variable(|| {
+            self.disclosed_prefix.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let depth_var = cs.new_input_variable(|| {
+            self.depth.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let commitment_var = cs.new_input_variable(|| {
+            self.commitment.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let nonce_var = cs.new_input_variable(|| {
+            self.nonce.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Hash chain h_0..=h_MAX
+        let iv = chain_iv();
+        let mut chain = vec![(lc!() + (iv, Variable::One), self.segments.as_ref().map(|_| iv))];
+        for (var, value) in &segment_vars {
+            let (prev, prev_val) = chain.last().cloned().expect("chain starts with the IV");
+            chain.push(mimc_gadget(&cs, &constants, &prev, prev_val, &(lc!() + *var), *value)?);
+        }
+
+        // Constraint 1: commitment == H(h_MAX, n)
+        let (last, last_val) = chain[MAX_DEPTH].clone();
+        let (commitment, _) = mimc_gadget(&cs, &constants, &last, last_val, &(lc!() + count_var), self.segment_count)?;
+        {
+            let _ns = ns!(cs, "commitment_opening");
+            cs.enforce_constraint(commitment, lc!() + Variable::One, lc!() + commitment_var)?;
+        }
+
+        // Constraint 2: disclosed_prefix == h_depth via a one-hot selector
+        let mut selector_sum = lc!();
+        let mut index_sum = lc!();
+        let mut selected = lc!();
+        for (i, (h, h_val)) in chain.iter().enumerate() {
+            let sel_val = self.depth.map(|d| Fr::from((d == i as u64) as u64));
+            let sel = cs.new_witness_variable(|| sel_val.ok_or(SynthesisError::AssignmentMissing))?;
+            {
+                let _ns = ns!(cs, "selector_boolean");
+                cs.enforce_constraint(lc!() + sel, lc!() + Variable::One - sel, lc!())?;
+            }
+
+            let picked_val = sel_val.zip(*h_val).map(|(s, h)| s * h);
+            let picked = cs.new_witness_variable(|| picked_val.ok_or(SynthesisError::AssignmentMissing))?;
+            {
+                let _ns = ns!(cs, "selector_pick");
+                cs.enforce_constraint(lc!() + sel, h.clone(), lc!() + picked)?;
+            }
+
+            selector_sum = selector_sum + sel;
+            index_sum += (Fr::from(i as u64), sel);
+            selected = selected + picked;
+        }
+        {
+            let _ns = ns!(cs, "selector_one_hot");
+            cs.enforce_constraint(selector_sum, lc!() + Variable::One, lc!() + Variable::One)?;
+        }
+        {
+            let _ns = ns!(cs, "selector_depth");
+            cs.enforce_constraint(index_sum, lc!() + Variable::One, lc!() + depth_var)?;
+        }
+        {
+            let _ns = ns!(cs, "disclosed_prefix");
+            cs.enforce_constraint(selected, lc!() + Variable::One, lc!() + prefix_var)?;
+        }
+
+        // Constraint 3: nonce_binding == commitment * nonce, so the nonce
+        // cannot be swapped without re-proving
+        let nonce_binding_val = self.commitment.zip(self.nonce).map(|(c, n)| c * n);
+        let nonce_binding = cs.new_witness_variable(|| {
+            nonce_binding_val.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        {
+            let _ns = ns!(cs, "nonce_binding");
+            cs.enforce_constraint(lc!() + commitment_var, lc!() + nonce_var, lc!() + nonce_binding)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The circuit with its full witness, disclosing the prefix at
+/// `disclose_depth`
+pub(crate) fn witness(
+    segments: &[&[u8]],
+    disclose_depth: usize,
+    nonce: u64,
+) -> Option<HierarchicalCircuit> {
+    let (chain, commitment) = compute_chain(segments)?;
+
+    let mut segment_fields: Vec<Fr> = segments.iter().map(|s| segment_to_field(s)).collect();
+    segment_fields.resize(MAX_DEPTH, Fr::from(0u64));
+
+    Some(HierarchicalCircuit {
+        segments: Some(segment_fields),
+        segment_count: Some(Fr::from(segments.len() as u64)),
+        disclosed_prefix: Some(*chain.get(disclose_depth)?),
+        depth: Some(disclose_depth as u64),
+        commitment: Some(commitment),
+        nonce: Some(Fr::from(nonce)),
+    })
+}
+
+/// Run the circuit-specific setup for hierarchical proofs
+pub(crate) fn setup() -> c_int {
+    let circuit = HierarchicalCircuit {
+        segments: None,
+        segment_count: None,
+        disclosed_prefix: None,
+        depth: None,
+        commitment: None,
+        nonce: None,
+    };
+
+    let mut rng = seeded_rng(1u64);
+
+    match Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng) {
+        Ok((pk, vk)) => match HIER_KEYS.lock() {
+            Ok(mut keys) => {
+                *keys = Some(Arc::new((pk, PreparedVerifyingKey::from(vk))));
+                0
+            }
+            Err(_) => -1,
+        },
+        Err(_) => -1,
+    }
+}
+
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = HIER_KEYS.lock() {
+        *keys = None;
+    }
+}
+
+fn field_to_hex(value: &Fr) -> String {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes_to_hex(&bytes)
+}
+
+fn field_from_hex(hex: &str) -> Option<Fr> {
+    let bytes = hex_to_bytes(hex).ok()?;
+    Fr::deserialize_compressed(&bytes[..]).ok()
+}
+
+fn read_segments<'a>(segments: *const *const c_char, count: usize) -> Option<Vec<&'a [u8]>> {
+    if count == 0 {
+        return None;
+    }
+
+    let ptrs = read_slice(segments, count, MAX_DEPTH).ok()?;
+    ptrs.iter()
+        .map(|&ptr| (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_bytes()))
+        .collect()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Compute the hierarchical commitment (hex) for `count` NUL-terminated segments
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the commitment does not
+/// fit, -1 on other failures.
+#[no_mangle]
+pub extern "C" fn ZK_ComputeHierarchicalID(
+    segments: *const *const c_char,
+    count: usize,
+    commitment_out: *mut c_char,
+    commitment_out_size: usize,
+) -> c_int {
+    let segments = match read_segments(segments, count) {
+        Some(s) => s,
+        None => return -1,
+    };
+
+    match compute_chain(&segments) {
+        Some((_, commitment)) => match write_cstr(commitment_out, commitment_out_size, &field_to_hex(&commitment)) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Compute the disclosed prefix hash h_count (hex) for the leading segments
+///
+/// Verifiers use this to derive the expected prefix, e.g. from just "org".
+/// `count` may be 0 for the depth-0 prefix. Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL if the prefix does not fit, -1 on other failures.
+#[no_mangle]
+pub extern "C" fn ZK_ComputeHierarchicalPrefix(
+    segments: *const *const c_char,
+    count: usize,
+    prefix_out: *mut c_char,
+    prefix_out_size: usize,
+) -> c_int {
+    let prefix = if count == 0 {
+        chain_iv()
+    } else {
+        match read_segments(segments, count).and_then(|s| compute_chain(&s)) {
+            Some((chain, _)) => chain[count],
+            None => return -1,
+        }
+    };
+
+    match write_cstr(prefix_out, prefix_out_size, &field_to_hex(&prefix)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Prove knowledge of the full identifier behind a commitment, disclosing
+/// the prefix hash at `disclose_depth` (0..=count)
+///
+/// Returns 0 on success, ZK_ERR_NOT_INITIALIZED before ZK_Init,
+/// ZK_ERR_PROVE_FAILED, ZK_ERR_BUFFER_TOO_SMALL if the proof does not fit,
+/// -1 on other failures.
+#[no_mangle]
+pub extern "C" fn ZK_GenerateHierarchicalProof(
+    segments: *const *const c_char,
+    count: usize,
+    disclose_depth: usize,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() || disclose_depth > count {
+        return -1;
+    }
+
+    let segments = match read_segments(segments, count) {
+        Some(s) => s,
+        None => return -1,
+    };
+
+    let circuit = match witness(&segments, disclose_depth, nonce) {
+        Some(c) => c,
+        None => return -1,
+    };
+
+    #[cfg(feature = "debug-circuit")]
+    if let Err(code) = crate::satisfiability::check(circuit.clone()) {
+        return code;
+    }
+
+    let keys = match HIER_KEYS.lock() {
+        Ok(keys) => keys.clone(),
+        Err(_) => return -1,
+    };
+
+    let (pk, _) = match keys.as_deref() {
+        Some(keys) => keys,
+        None => return ZK_ERR_NOT_INITIALIZED,
+    };
+
+    let mut rng = match prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+
+    let proof = match Groth16::<Bn254>::prove(pk, circuit, &mut rng) {
+        Ok(p) => p,
+        Err(_) => return ZK_ERR_PROVE_FAILED,
+    };
+
+    let mut proof_bytes = Vec::new();
+    if proof.serialize_compressed(&mut proof_bytes).is_err() {
+        return -1;
+    }
+
+    match write_cstr(proof_out, proof_out_size, &bytes_to_hex(&proof_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a hierarchical proof against a disclosed prefix and commitment
+///
+/// Returns 1 if valid, 0 if invalid.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyHierarchicalProof(
+    proof_hex: *const c_char,
+    disclosed_prefix: *const c_char,
+    depth: usize,
+    commitment: *const c_char,
+    nonce: u64,
+) -> c_int {
+    if proof_hex.is_null() || disclosed_prefix.is_null() || commitment.is_null() || depth > MAX_DEPTH {
+        return 0;
+    }
+
+    let proof_hex_str = unsafe { CStr::from_ptr(proof_hex).to_str().unwrap_or("") };
+    let prefix_str = unsafe { CStr::from_ptr(disclosed_prefix).to_str().unwrap_or("") };
+    let commitment_str = unsafe { CStr::from_ptr(commitment).to_str().unwrap_or("") };
+
+    let proof = match hex_to_bytes(proof_hex_str)
+        .ok()
+        .and_then(|bytes| Proof::<Bn254>::deserialize_compressed(&bytes[..]).ok())
+    {
+        Some(p) => p,
+        None => return 0,
+    };
+
+    let (prefix, commitment) = match (field_from_hex(prefix_str), field_from_hex(commitment_str)) {
+        (Some(p), Some(c)) => (p, c),
+        _ => return 0,
+    };
+
+    let keys = match HIER_KEYS.lock() {
+        Ok(keys) => keys.clone(),
+        Err(_) => return 0,
+    };
+
+    let (_, pvk) = match keys.as_deref() {
+        Some(keys) => keys,
+        None => return 0,
+    };
+
+    let public_inputs = vec![prefix, Fr::from(depth as u64), commitment, Fr::from(nonce)];
+
+    match Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => 0,
+    }
+}
+}
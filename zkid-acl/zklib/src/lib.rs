@@ -1,16 +1,97 @@
+// C entry points take raw pointers from the enclave and validate them before use
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 use ark_bn254::{Bn254, Fr};
-use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+#[cfg(not(feature = "legacy-user-id"))]
+use ark_relations::r1cs::{LinearCombination, Variable};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
-use ark_std::rand::SeedableRng;
 use sha2::{Digest, Sha256};
 use std::ffi::CStr;
 use std::os::raw::{c_char, c_int};
-use std::sync::{Mutex, Once};
+use std::sync::{Arc, Once};
+use sync::Mutex;
+#[cfg(not(feature = "strict"))]
+use std::sync::atomic::AtomicU64;
+#[cfg(not(feature = "legacy-user-id"))]
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+
+#[cfg(all(feature = "strict", feature = "legacy-user-id"))]
+compile_error!("the legacy user-ID circuit does not prove knowledge of user_id; strict builds exclude it");
+
+#[cfg(not(feature = "legacy-user-id"))]
+use error::ZK_ERR_INPUT_TOO_LARGE;
+use error::{
+    ZK_ERR_BAD_HEX, ZK_ERR_CORRUPT, ZK_ERR_ENTROPY, ZK_ERR_NOT_INITIALIZED, ZK_ERR_NO_PROVING_KEY, ZK_ERR_PROVE_FAILED,
+};
+use ffi::{read_bytes, reserve_cstr, write_bytes, write_cstr, MAX_FIELD_LEN};
+
+pub mod allowlist;
+pub mod batch;
+pub mod domain;
+pub mod error;
+pub mod ffi;
+pub mod hierarchical;
+pub mod proof;
+#[cfg(not(feature = "legacy-user-id"))]
+pub mod sha256;
+#[cfg(feature = "debug-circuit")]
+pub mod satisfiability;
+mod sync;
+mod unwind;
+
+// Global state for proving/verifying keys; a verifier-only process holds
+// just the verifying key. Calls clone the Arc out and prove or verify
+// without the lock, so a proof in flight does not stall verifications.
+static PROVING_KEY: Mutex<Option<Arc<ProvingKey<Bn254>>>> = Mutex::new(None);
+static VERIFYING_KEY: Mutex<Option<Arc<PreparedVerifyingKey<Bn254>>>> = Mutex::new(None);
+
+// Proof format the user-ID keys are for: the preimage circuit hashes the
+// public ID tag in-circuit, so formats 3 and 4 have separate keys (the
+// legacy circuit's serve formats 2 and 5 alike)
+#[cfg(not(feature = "legacy-user-id"))]
+static KEY_FORMAT: AtomicU8 = AtomicU8::new(proof::PROOF_FORMAT_PREIMAGE);
+
+/// The user-ID proving key, if set
+fn current_proving_key() -> Option<Arc<ProvingKey<Bn254>>> {
+    PROVING_KEY.lock().ok()?.clone()
+}
+
+/// The user-ID verifying key, if set
+fn current_verifying_key() -> Option<Arc<PreparedVerifyingKey<Bn254>>> {
+    VERIFYING_KEY.lock().ok()?.clone()
+}
+
+/// Format proofs are generated in: the loaded keys' format
+#[cfg(not(feature = "legacy-user-id"))]
+fn emit_format() -> u8 {
+    KEY_FORMAT.load(Ordering::Relaxed)
+}
+/// Format proofs are generated in: that of the current format version
+#[cfg(feature = "legacy-user-id")]
+fn emit_format() -> u8 {
+    proof::emit_format(domain::tagged())
+}
+
+// Public inputs of the user-ID circuit: public_id (two limbs, or one field
+// element in the legacy circuit), nonce
+#[cfg(not(feature = "legacy-user-id"))]
+const USER_ID_PUBLIC_INPUTS: usize = 3;
+#[cfg(feature = "legacy-user-id")]
+const USER_ID_PUBLIC_INPUTS: usize = 2;
 
-// Global state for proving/verifying keys
-static KEYS: Mutex<Option<(ProvingKey<Bn254>, PreparedVerifyingKey<Bn254>)>> = Mutex::new(None);
+// Longest verifying key accepted (the user-ID key is a few hundred bytes)
+const MAX_VK_LEN: usize = 4096;
+
+// Longest binary proof accepted (an enveloped compressed proof is 131 bytes)
+const MAX_PROOF_LEN: usize = 256;
+
+// Seed set with ZK_SetDeterministicProving; 0 proves with fresh entropy
+#[cfg(not(feature = "strict"))]
+static DETERMINISTIC_PROVING: AtomicU64 = AtomicU64::new(0);
 
 // One-time initialization for rayon configuration
 static INIT: Once = Once::new();
@@ -23,8 +104,75 @@ fn configure_rayon() {
     });
 }
 
-// ZK Circuit: proves knowledge of user_id such that hash(user_id) == public_id
-#[derive(Clone)]
+// ZK Circuit: proves knowledge of user_id such that SHA256(user_id) ==
+// public_id, computing the digest in-circuit (see sha256.rs)
+#[cfg(not(feature = "legacy-user-id"))]
+#[derive(Clone, Default)]
+struct UserIDCircuit {
+    // Private witness: at most sha256::MAX_MESSAGE_LEN bytes with the tag
+    user_id: Option<Vec<u8>>,
+    // Whether the public ID tag is hashed ahead of user_id (format 4)
+    tagged: bool,
+    
+    // Public inputs: public_id as its low and high 128-bit limbs
+    public_id: Option<[Fr; 2]>,
+    nonce: Option<Fr>,
+}
+
+#[cfg(not(feature = "legacy-user-id"))]
+impl ConstraintSynthesizer<Fr> for UserIDCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Private input, hashed in-circuit behind the tag if any
+        let prefix = if self.tagged { domain::PUBLIC_ID_TAG } else { &[] };
+        let digest = sha256::digest_gadget(&cs, prefix, self.user_id.as_deref())?;
+        let limbs: Vec<LinearCombination<Fr>> = digest.chunks(128).map(sha256::bits_lc).collect();
+        
+        // Allocate public inputs
+        let public_id = self.public_id;
+        let public_id_vars = (0..2)
+            .map(|i| cs.new_input_variable(|| public_id.map(|p| p[i]).ok_or(SynthesisError::AssignmentMissing)))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let nonce_var = cs.new_input_variable(|| {
+            self.nonce.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        
+        // Constraint: SHA256(user_id) == public_id, limb by limb
+        {
+            let _ns = ark_relations::ns!(cs, "public_id_binding");
+            for (limb, var) in limbs.iter().zip(public_id_vars) {
+                cs.enforce_constraint(limb.clone(), ark_relations::lc!() + Variable::One, ark_relations::lc!() + var)?;
+            }
+        }
+        
+        // Constraint: nonce_binding == digest_low * nonce
+        // An input no constraint touches can be swapped freely, so the nonce
+        // is bound to the witness through a product the prover must supply
+        let digest_low = self
+            .user_id
+            .and_then(|user_id| proof::public_id_limbs(&domain::public_id(self.tagged, &user_id)))
+            .map(|limbs| limbs[0]);
+        let nonce_binding = digest_low.zip(self.nonce).map(|(h, n)| h * n);
+        let nonce_binding_var = cs.new_witness_variable(|| {
+            nonce_binding.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        {
+            let _ns = ark_relations::ns!(cs, "nonce_binding");
+            cs.enforce_constraint(
+                limbs[0].clone(),
+                ark_relations::lc!() + nonce_var,
+                ark_relations::lc!() + nonce_binding_var,
+            )?;
+        }
+        
+        Ok(())
+    }
+}
+
+// Legacy ZK Circuit (feature "legacy-user-id"): equates a hash the prover
+// supplies with public_id; SHA256(user_id) is only checked outside the proof
+#[cfg(feature = "legacy-user-id")]
+#[derive(Clone, Default)]
 struct UserIDCircuit {
     // Private witness
     user_id_hash: Option<Fr>,
@@ -34,6 +182,7 @@ struct UserIDCircuit {
     nonce: Option<Fr>,
 }
 
+#[cfg(feature = "legacy-user-id")]
 impl ConstraintSynthesizer<Fr> for UserIDCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
         // Allocate private input
@@ -51,32 +200,104 @@ impl ConstraintSynthesizer<Fr> for UserIDCircuit {
         })?;
         
         // Constraint: user_id_hash == public_id
-        cs.enforce_constraint(
-            ark_relations::lc!() + user_id_hash_var,
-            ark_relations::lc!() + ark_relations::r1cs::Variable::One,
-            ark_relations::lc!() + public_id_var,
-        )?;
+        {
+            let _ns = ark_relations::ns!(cs, "public_id_binding");
+            cs.enforce_constraint(
+                ark_relations::lc!() + user_id_hash_var,
+                ark_relations::lc!() + ark_relations::r1cs::Variable::One,
+                ark_relations::lc!() + public_id_var,
+            )?;
+        }
         
-        // Nonce is included as public input (no constraint needed)
-        let _ = nonce_var;
+        // Constraint: nonce_binding == user_id_hash * nonce
+        // An input no constraint touches can be swapped freely, so the nonce
+        // is bound to the witness through a product the prover must supply
+        let nonce_binding = self.user_id_hash.zip(self.nonce).map(|(h, n)| h * n);
+        let nonce_binding_var = cs.new_witness_variable(|| {
+            nonce_binding.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        {
+            let _ns = ark_relations::ns!(cs, "nonce_binding");
+            cs.enforce_constraint(
+                ark_relations::lc!() + user_id_hash_var,
+                ark_relations::lc!() + nonce_var,
+                ark_relations::lc!() + nonce_binding_var,
+            )?;
+        }
         
         Ok(())
     }
 }
 
-// Helper: hash bytes to field element
-fn hash_to_field(data: &[u8]) -> Fr {
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    let hash = hasher.finalize();
-    
-    // Take first 8 bytes and convert to u64, then to field element
-    let val = u64::from_le_bytes([
-        hash[0], hash[1], hash[2], hash[3],
-        hash[4], hash[5], hash[6], hash[7],
-    ]);
-    
-    Fr::from(val % 1000000000000u64)
+// Helper: user-ID circuit without a witness, for setting up `format` keys
+#[cfg(not(feature = "legacy-user-id"))]
+fn setup_circuit(format: u8) -> UserIDCircuit {
+    UserIDCircuit { tagged: proof::is_tagged(format), ..UserIDCircuit::default() }
+}
+#[cfg(feature = "legacy-user-id")]
+fn setup_circuit(_format: u8) -> UserIDCircuit {
+    UserIDCircuit::default()
+}
+
+// Helper: `format` user-ID circuit with its witness for `user_id` and the
+// decoded `public_id`, without checking that they match
+#[cfg(not(feature = "legacy-user-id"))]
+fn user_id_circuit(format: u8, user_id: &[u8], public_id: &[u8], nonce: u64) -> Result<UserIDCircuit, c_int> {
+    let tagged = proof::is_tagged(format);
+    if domain::public_id_preimage(tagged, user_id).len() > sha256::MAX_MESSAGE_LEN {
+        return Err(ZK_ERR_INPUT_TOO_LARGE);
+    }
+    Ok(UserIDCircuit {
+        user_id: Some(user_id.to_vec()),
+        tagged,
+        public_id: Some(proof::public_id_limbs(public_id).ok_or(-1)?),
+        nonce: Some(Fr::from(nonce)),
+    })
+}
+#[cfg(feature = "legacy-user-id")]
+fn user_id_circuit(format: u8, user_id: &[u8], public_id: &[u8], nonce: u64) -> Result<UserIDCircuit, c_int> {
+    Ok(UserIDCircuit {
+        user_id_hash: proof::field_for(format, &domain::public_id(proof::is_tagged(format), user_id)),
+        public_id: proof::field_for(format, public_id),
+        nonce: Some(Fr::from(nonce)),
+    })
+}
+
+// Helper: randomness for circuit setup; strict builds ignore the seed and
+// draw from the OS
+#[cfg(not(feature = "strict"))]
+fn seeded_rng(seed: u64) -> ark_std::rand::rngs::StdRng {
+    use ark_std::rand::SeedableRng;
+    ark_std::rand::rngs::StdRng::seed_from_u64(seed)
+}
+#[cfg(feature = "strict")]
+fn seeded_rng(_seed: u64) -> rand_core::OsRng {
+    rand_core::OsRng
+}
+
+// Helper: randomness for one proof, fresh from the OS so the blinding
+// factors cannot be recomputed from the public nonce; derived from the
+// ZK_SetDeterministicProving seed and the nonce while that is on
+fn prover_rng(nonce: u64) -> Result<ark_std::rand::rngs::StdRng, c_int> {
+    use ark_std::rand::SeedableRng;
+    use rand_core::RngCore;
+
+    let mut seed = [0u8; 32];
+    #[cfg(not(feature = "strict"))]
+    match DETERMINISTIC_PROVING.load(Ordering::Relaxed) {
+        0 => {}
+        deterministic => {
+            let mut hasher = Sha256::new();
+            hasher.update(deterministic.to_le_bytes());
+            hasher.update(nonce.to_le_bytes());
+            return Ok(ark_std::rand::rngs::StdRng::from_seed(hasher.finalize().into()));
+        }
+    }
+    #[cfg(feature = "strict")]
+    let _ = nonce;
+
+    rand_core::OsRng.try_fill_bytes(&mut seed).map_err(|_| ZK_ERR_ENTROPY)?;
+    Ok(ark_std::rand::rngs::StdRng::from_seed(seed))
 }
 
 // Helper: bytes to hex string
@@ -89,81 +310,229 @@ fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, hex::FromHexError> {
     hex::decode(hex)
 }
 
+unwind::entry_point! {
 #[no_mangle]
 pub extern "C" fn ZK_Init() -> c_int {
     // Configure rayon for single-threaded operation BEFORE any arkworks operations
     configure_rayon();
     
-    // Create dummy circuit for setup
-    let circuit = UserIDCircuit {
-        user_id_hash: None,
-        public_id: None,
-        nonce: None,
-    };
+    // Create dummy circuit for setup, for the current format version
+    let format = proof::emit_format(domain::tagged());
+    let circuit = setup_circuit(format);
     
     // Use deterministic RNG for reproducible setup
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let mut rng = seeded_rng(0u64);
     
     // Run Groth16 setup (single-threaded mode)
     match Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng) {
         Ok((pk, vk)) => {
             let pvk = PreparedVerifyingKey::from(vk);
             
-            if let Ok(mut keys) = KEYS.lock() {
-                *keys = Some((pk, pvk));
-                0
-            } else {
-                -1
+            match (PROVING_KEY.lock(), VERIFYING_KEY.lock()) {
+                (Ok(mut pk_slot), Ok(mut pvk_slot)) => {
+                    *pk_slot = Some(Arc::new(pk));
+                    *pvk_slot = Some(Arc::new(pvk));
+                    #[cfg(not(feature = "legacy-user-id"))]
+                    KEY_FORMAT.store(format, Ordering::Relaxed);
+                }
+                _ => return -1,
             }
+            
+            hierarchical::setup()
         }
         Err(_) => -1,
     }
 }
+}
+
+unwind::entry_point! {
+/// Load only the verifying key, for gateways that must never hold the
+/// proving key
+///
+/// `vk_bytes` is ZK_ExportVerifyingKey's output after hex decoding, from a
+/// prover under the same format version. Any proving key is dropped:
+/// ZK_GenerateProof then returns ZK_ERR_NO_PROVING_KEY while ZK_VerifyProof
+/// works. The hierarchical circuit is not affected. Returns 0 on success, ZK_ERR_CORRUPT if the key
+/// does not decode or is not for the user-ID circuit, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_InitVerifierOnly(vk_bytes: *const u8, vk_len: usize) -> c_int {
+    if vk_bytes.is_null() {
+        return -1;
+    }
+    let data = match read_bytes(vk_bytes, vk_len, MAX_VK_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    
+    let mut reader = data;
+    let vk = match VerifyingKey::<Bn254>::deserialize_compressed(&mut reader) {
+        Ok(vk) if reader.is_empty() && vk.gamma_abc_g1.len() == USER_ID_PUBLIC_INPUTS + 1 => vk,
+        _ => return ZK_ERR_CORRUPT,
+    };
+    
+    configure_rayon();
+    
+    match (PROVING_KEY.lock(), VERIFYING_KEY.lock()) {
+        (Ok(mut pk_slot), Ok(mut pvk_slot)) => {
+            *pk_slot = None;
+            *pvk_slot = Some(Arc::new(PreparedVerifyingKey::from(vk)));
+            #[cfg(not(feature = "legacy-user-id"))]
+            KEY_FORMAT.store(proof::emit_format(domain::tagged()), Ordering::Relaxed);
+            0
+        }
+        _ => -1,
+    }
+}
+}
+
+unwind::entry_point! {
+/// Export the verifying key (hex, compressed) for ZK_InitVerifierOnly
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, ZK_ERR_NOT_INITIALIZED if
+/// no verifying key is loaded, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let mut vk_bytes = Vec::new();
+    match VERIFYING_KEY.lock() {
+        Ok(pvk) => match pvk.as_ref() {
+            Some(pvk) => {
+                if pvk.vk.serialize_compressed(&mut vk_bytes).is_err() {
+                    return -1;
+                }
+            }
+            None => return ZK_ERR_NOT_INITIALIZED,
+        },
+        Err(_) => return -1,
+    }
+    
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+/// Why the proving key is absent: never set up, or verifier-only
+fn missing_proving_key() -> c_int {
+    match VERIFYING_KEY.lock() {
+        Ok(pvk) if pvk.is_some() => ZK_ERR_NO_PROVING_KEY,
+        _ => ZK_ERR_NOT_INITIALIZED,
+    }
+}
 
+unwind::entry_point! {
+/// Compute public_id = SHA256(user_id) as hex, or
+/// SHA256("zkid.acl.pubid.v1" | user_id) under format version 2
+///
+/// Unless NULL, `required_size_out` receives the buffer size (65). With it
+/// set, a NULL `public_id` or a size of 0 only queries the size and returns
+/// ZK_STATUS_SIZE_QUERY.
 #[no_mangle]
 pub extern "C" fn ZK_ComputePublicID(
     user_id: *const c_char,
     user_id_len: usize,
     public_id: *mut c_char,
     public_id_size: usize,
+    required_size_out: *mut usize,
 ) -> c_int {
-    if user_id.is_null() || public_id.is_null() {
+    if user_id.is_null() {
         return -1;
     }
     
     // Convert C string to Rust slice
-    let user_id_bytes = unsafe {
-        std::slice::from_raw_parts(user_id as *const u8, user_id_len)
+    let user_id_bytes = match read_bytes(user_id, user_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
     };
     
-    // Compute SHA256 hash
-    let mut hasher = Sha256::new();
-    hasher.update(user_id_bytes);
-    let hash = hasher.finalize();
+    let required = Sha256::output_size() * 2 + 1;
+    if let Err(e) = reserve_cstr(public_id, public_id_size, required, required_size_out) {
+        return e.code();
+    }
+    
+    // Compute SHA256 hash, tagged under format version 2
+    let hash = domain::public_id(domain::tagged(), user_id_bytes);
     
     // Convert to hex string
     let hex_str = bytes_to_hex(&hash);
     
-    // Check buffer size
-    if public_id_size < hex_str.len() + 1 {
-        return -1;
+    match write_cstr(public_id, public_id_size, &hex_str) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
     }
+}
+}
+
+/// Prove knowledge of `user_id_bytes` whose public ID is `public_id_bytes`
+/// (decoded), returning the proof in the emitted format
+fn generate_proof(user_id_bytes: &[u8], public_id_bytes: &[u8], nonce: u64) -> Result<Vec<u8>, c_int> {
+    // Get keys
+    let pk = current_proving_key().ok_or_else(missing_proving_key)?;
+    let format = emit_format();
+    
+    // Verify hash match: the public ID of user_id (as in ZK_ComputePublicID,
+    // tagged for a tagged format) should equal public_id, so a mismatch
+    // fails fast instead of in the prover
+    if domain::public_id(proof::is_tagged(format), user_id_bytes).as_slice() != public_id_bytes {
+        return Err(-1);
+    }
+    
+    // Create circuit with witness
+    let circuit = user_id_circuit(format, user_id_bytes, public_id_bytes, nonce)?;
+    
+    #[cfg(feature = "debug-circuit")]
+    satisfiability::check(circuit.clone())?;
+    
+    // Generate proof
+    let mut rng = prover_rng(nonce)?;
     
-    // Copy to output buffer
-    unsafe {
-        let hex_bytes = hex_str.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            hex_bytes.as_ptr(),
-            public_id as *mut u8,
-            hex_bytes.len(),
-        );
-        // Null terminate
-        *public_id.add(hex_bytes.len()) = 0;
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).map_err(|_| ZK_ERR_PROVE_FAILED)?;
+    
+    // Serialize proof
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).map_err(|_| -1)?;
+    Ok(proof::encode(format, &proof_bytes))
+}
+
+/// Decode a wire proof (see proof.rs) and the public inputs it is checked
+/// against for the decoded public ID; None if either is malformed
+fn decode_statement(proof_bytes: &[u8], public_id_bytes: &[u8], nonce: u64) -> Option<(Proof<Bn254>, Vec<Fr>)> {
+    let (format, proof_bytes) = proof::decode(proof_bytes)?;
+    // The preimage circuit's keys are for one of formats 3 and 4
+    #[cfg(not(feature = "legacy-user-id"))]
+    if format != KEY_FORMAT.load(Ordering::Relaxed) {
+        return None;
     }
+    let proof = Proof::<Bn254>::deserialize_compressed(proof_bytes).ok()?;
     
-    0
+    // Compute public inputs under the proof's format
+    let public_inputs = proof::public_inputs(format, public_id_bytes, nonce)?;
+    Some((proof, public_inputs))
+}
+
+/// Verify a wire proof (see proof.rs) for the decoded public ID: 1 valid,
+/// 0 invalid
+fn verify_proof(proof_bytes: &[u8], public_id_bytes: &[u8], nonce: u64) -> c_int {
+    // Get keys
+    let pvk = match current_verifying_key() {
+        Some(pvk) => pvk,
+        None => return 0,
+    };
+    
+    let (proof, public_inputs) = match decode_statement(proof_bytes, public_id_bytes, nonce) {
+        Some(statement) => statement,
+        None => return 0,
+    };
+    
+    // Verify proof
+    match Groth16::<Bn254>::verify_with_processed_vk(&pvk, &public_inputs, &proof) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => 0,
+    }
 }
 
+unwind::entry_point! {
 #[no_mangle]
 pub extern "C" fn ZK_GenerateProof(
     user_id: *const c_char,
@@ -177,89 +546,89 @@ pub extern "C" fn ZK_GenerateProof(
         return -1;
     }
     
-    // Get keys
-    let keys_guard = match KEYS.lock() {
-        Ok(guard) => guard,
-        Err(_) => return -1,
-    };
-    
-    let (pk, _) = match keys_guard.as_ref() {
-        Some(keys) => keys,
-        None => return -1,
-    };
-    
     // Convert inputs
-    let user_id_bytes = unsafe {
-        std::slice::from_raw_parts(user_id as *const u8, user_id_len)
+    let user_id_bytes = match read_bytes(user_id, user_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
     };
     
     let public_id_str = unsafe {
         CStr::from_ptr(public_id).to_str().unwrap_or("")
     };
     
-    // Compute SHA256 of user_id (must match ZK_ComputePublicID)
-    let mut hasher = Sha256::new();
-    hasher.update(user_id_bytes);
-    let user_id_hash_bytes = hasher.finalize();
-    let user_id_hash_field = hash_to_field(&user_id_hash_bytes);
-    
     // Parse public_id (which is hex-encoded SHA256)
     let public_id_bytes = match hex_to_bytes(public_id_str) {
         Ok(bytes) => bytes,
-        Err(_) => return -1,
-    };
-    let public_id_field = hash_to_field(&public_id_bytes);
-    
-    // Verify hash match: SHA256(user_id) should equal public_id
-    if user_id_hash_field != public_id_field {
-        return -1;
-    }
-    
-    let nonce_field = Fr::from(nonce);
-    
-    // Create circuit with witness
-    let circuit = UserIDCircuit {
-        user_id_hash: Some(user_id_hash_field),
-        public_id: Some(public_id_field),
-        nonce: Some(nonce_field),
+        Err(_) => return ZK_ERR_BAD_HEX,
     };
     
-    // Generate proof
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(nonce);
-    
-    let proof = match Groth16::<Bn254>::prove(pk, circuit, &mut rng) {
-        Ok(p) => p,
-        Err(_) => return -1,
+    let proof_bytes = match generate_proof(user_id_bytes, &public_id_bytes, nonce) {
+        Ok(bytes) => bytes,
+        Err(code) => return code,
     };
     
-    // Serialize proof
-    let mut proof_bytes = Vec::new();
-    if proof.serialize_compressed(&mut proof_bytes).is_err() {
-        return -1;
-    }
-    
     // Convert to hex
     let proof_hex = bytes_to_hex(&proof_bytes);
     
-    // Check buffer size
-    if proof_out_size < proof_hex.len() + 1 {
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+unwind::entry_point! {
+/// ZK_GenerateProof on binary buffers: `public_id` is the 32-byte digest
+/// rather than its hex, and `proof_out` receives the enveloped proof
+/// (131 bytes) that ZK_GenerateProof would hex-encode
+///
+/// Unless NULL, `proof_len_out` receives the proof length. Returns the
+/// codes of ZK_GenerateProof except ZK_ERR_BAD_HEX.
+#[no_mangle]
+pub extern "C" fn ZK_GenerateProofRaw(
+    user_id: *const u8,
+    user_id_len: usize,
+    public_id: *const u8,
+    public_id_len: usize,
+    nonce: u64,
+    proof_out: *mut u8,
+    proof_out_size: usize,
+    proof_len_out: *mut usize,
+) -> c_int {
+    if user_id.is_null() || public_id.is_null() || proof_out.is_null() {
         return -1;
     }
     
-    // Copy to output
-    unsafe {
-        let hex_bytes = proof_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            hex_bytes.as_ptr(),
-            proof_out as *mut u8,
-            hex_bytes.len(),
-        );
-        *proof_out.add(hex_bytes.len()) = 0;
-    }
+    let user_id_bytes = match read_bytes(user_id, user_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
     
-    0
+    let public_id_bytes = match read_bytes(public_id, public_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    
+    let proof_bytes = match generate_proof(user_id_bytes, public_id_bytes, nonce) {
+        Ok(bytes) => bytes,
+        Err(code) => return code,
+    };
+    
+    match write_bytes(proof_out, proof_out_size, &proof_bytes) {
+        Ok(len) => {
+            if !proof_len_out.is_null() {
+                unsafe {
+                    *proof_len_out = len;
+                }
+            }
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
 }
 
+unwind::entry_point! {
 #[no_mangle]
 pub extern "C" fn ZK_VerifyProof(
     proof_hex: *const c_char,
@@ -270,17 +639,6 @@ pub extern "C" fn ZK_VerifyProof(
         return 0;
     }
     
-    // Get keys
-    let keys_guard = match KEYS.lock() {
-        Ok(guard) => guard,
-        Err(_) => return 0,
-    };
-    
-    let (_, pvk) = match keys_guard.as_ref() {
-        Some(keys) => keys,
-        None => return 0,
-    };
-    
     // Parse inputs
     let proof_hex_str = unsafe {
         CStr::from_ptr(proof_hex).to_str().unwrap_or("")
@@ -296,32 +654,133 @@ pub extern "C" fn ZK_VerifyProof(
         Err(_) => return 0,
     };
     
-    let proof = match Proof::<Bn254>::deserialize_compressed(&proof_bytes[..]) {
-        Ok(p) => p,
-        Err(_) => return 0,
-    };
-    
-    // Compute public inputs
     let public_id_bytes = match hex_to_bytes(public_id_str) {
         Ok(bytes) => bytes,
         Err(_) => return 0,
     };
-    let public_id_field = hash_to_field(&public_id_bytes);
-    let nonce_field = Fr::from(nonce);
-    
-    let public_inputs = vec![public_id_field, nonce_field];
     
-    // Verify proof
-    match Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof) {
-        Ok(true) => 1,
-        Ok(false) => 0,
-        Err(_) => 0,
+    verify_proof(&proof_bytes, &public_id_bytes, nonce)
+}
+}
+
+unwind::entry_point! {
+/// ZK_VerifyProof on binary buffers: the wire proof and the 32-byte
+/// public ID digest, so a hex proof decoded by the caller verifies here and
+/// a raw proof hex-encoded verifies there
+///
+/// Returns 1 if the proof is valid, 0 if invalid or on error.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyProofRaw(
+    proof: *const u8,
+    proof_len: usize,
+    public_id: *const u8,
+    public_id_len: usize,
+    nonce: u64,
+) -> c_int {
+    if proof.is_null() || public_id.is_null() {
+        return 0;
     }
+    
+    let (proof_bytes, public_id_bytes) = match (
+        read_bytes(proof, proof_len, MAX_PROOF_LEN),
+        read_bytes(public_id, public_id_len, MAX_FIELD_LEN),
+    ) {
+        (Ok(proof_bytes), Ok(public_id_bytes)) => (proof_bytes, public_id_bytes),
+        _ => return 0,
+    };
+    
+    verify_proof(proof_bytes, public_id_bytes, nonce)
+}
 }
 
+unwind::entry_point! {
+/// Derive every proof's randomness from `seed` and the nonce, so repeated
+/// test runs produce byte-identical proofs; a seed of 0 returns to fresh
+/// entropy. Not in strict builds. Returns 0.
+#[cfg(not(feature = "strict"))]
+#[no_mangle]
+pub extern "C" fn ZK_SetDeterministicProving(seed: u64) -> c_int {
+    DETERMINISTIC_PROVING.store(seed, Ordering::Relaxed);
+    0
+}
+}
+
+unwind::entry_point! {
 #[no_mangle]
 pub extern "C" fn ZK_Cleanup() {
-    if let Ok(mut keys) = KEYS.lock() {
-        *keys = None;
+    if let Ok(mut pk) = PROVING_KEY.lock() {
+        *pk = None;
+    }
+    if let Ok(mut pvk) = VERIFYING_KEY.lock() {
+        *pvk = None;
+    }
+    hierarchical::cleanup();
+    allowlist::cleanup();
+}
+}
+
+unwind::entry_point! {
+/// Whether the user-ID keys are loaded
+///
+/// 1 once ZK_Init or ZK_InitVerifierOnly has set the verifying key, 0
+/// before and after ZK_Cleanup. A host that finds 0 where it expects keys,
+/// e.g. after resetting the library when a thread panicked, calls ZK_Init
+/// again; a lock poisoned by the panic does not stand in the way.
+#[no_mangle]
+pub extern "C" fn ZK_IsInitialized() -> c_int {
+    VERIFYING_KEY.lock().is_ok_and(|pvk| pvk.is_some()) as c_int
+}
+}
+
+unwind::entry_point! {
+/// Panic while holding both key locks, poisoning them (check-poison.sh)
+///
+/// Returns 0 if both locks are poisoned afterwards, -1 otherwise.
+#[cfg(feature = "fault-injection")]
+#[no_mangle]
+pub extern "C" fn ZK_PoisonKeyLocks() -> c_int {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let _ = std::panic::catch_unwind(|| {
+        let _pk = PROVING_KEY.lock();
+        let _pvk = VERIFYING_KEY.lock();
+        panic!("poisoning the key locks");
+    });
+    std::panic::set_hook(hook);
+
+    if PROVING_KEY.is_poisoned() && VERIFYING_KEY.is_poisoned() {
+        0
+    } else {
+        -1
+    }
+}
+}
+
+/// Panic inside an entry point (check-panics.sh); returns ZK_ERR_PANIC
+#[cfg(feature = "fault-injection")]
+#[no_mangle]
+pub extern "C" fn ZK_InjectPanic() -> c_int {
+    unwind::guard(|| panic!("injected by ZK_InjectPanic"))
+}
+
+/// Build profile: "strict" compiles out legacy/insecure code paths
+pub const BUILD_PROFILE: &str = if cfg!(feature = "strict") { "strict" } else { "standard" };
+
+unwind::entry_point! {
+/// Report version and build profile as JSON, e.g.
+/// {"crate":"zklib","version":"0.1.0","profile":"strict"}
+#[no_mangle]
+pub extern "C" fn ZK_BuildInfo(info_out: *mut c_char, info_out_size: usize) -> c_int {
+    let info = format!(
+        "{{\"crate\":\"{}\",\"version\":\"{}\",\"profile\":\"{}\"}}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        BUILD_PROFILE,
+    );
+    
+    match write_cstr(info_out, info_out_size, &info) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
     }
 }
+}
@@ -0,0 +1,161 @@
+// ============================================================================
+// Proof Wire Format
+// ============================================================================
+//
+// User-ID proofs cross the C API (hex or binary) as one of
+//
+//   legacy:    bare compressed arkworks proof, 128 bytes
+//   envelope:  "ZP" | format u8 | compressed arkworks proof
+//
+// as in zkid-vc. The format names the user-ID circuit and the public inputs
+// the proof was generated for:
+//
+//   1  legacy circuit, public_id as SHA-256 truncated to 64 bits, reduced
+//      mod 10^12 (bare only)
+//   2  legacy circuit, public_id as SHA-256 reduced mod r over its full width
+//   3  preimage circuit, public_id as two 128-bit limbs (see sha256.rs)
+//   4  format 3 for a tagged public ID: the circuit hashes the public ID
+//      tag ahead of user_id, so its keys are not format 3's
+//   5  format 2 for a tagged public ID, mapped to the field behind the
+//      field-mapping tag (see domain.rs)
+//
+// Formats 1 and 2 belong to the legacy fast path (feature "legacy-user-id"),
+// whose circuit only equates two values the prover supplies: the pre-check
+// that SHA-256(user_id) matches public_id runs outside the proof. The
+// legacy mapping also keeps only about 40 bits of the digest, so two public
+// IDs sharing a public input can be found with about a million hashes;
+// legacy builds emit format 2 (5 under format version 2) and still accept
+// bare format-1 proofs.
+//
+// Default builds prove and accept format 3, or 4 with keys set up under
+// format version 2: the circuit computes SHA-256(user_id) itself. Strict builds (feature "strict") contain neither
+// the legacy mapping nor the bare encoding.
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+#[cfg(feature = "legacy-user-id")]
+use sha2::{Digest, Sha256};
+
+pub const PROOF_FORMAT_LEGACY: u8 = 1;
+pub const PROOF_FORMAT_V2: u8 = 2;
+pub const PROOF_FORMAT_PREIMAGE: u8 = 3;
+pub const PROOF_FORMAT_PREIMAGE_TAGGED: u8 = 4;
+pub const PROOF_FORMAT_V2_TAGGED: u8 = 5;
+
+/// Format this build's circuit emits for tagged or untagged public IDs
+#[cfg(feature = "legacy-user-id")]
+pub fn emit_format(tagged: bool) -> u8 {
+    if tagged {
+        PROOF_FORMAT_V2_TAGGED
+    } else {
+        PROOF_FORMAT_V2
+    }
+}
+#[cfg(not(feature = "legacy-user-id"))]
+pub fn emit_format(tagged: bool) -> u8 {
+    if tagged {
+        PROOF_FORMAT_PREIMAGE_TAGGED
+    } else {
+        PROOF_FORMAT_PREIMAGE
+    }
+}
+
+/// Whether `format` proves a tagged public ID
+pub fn is_tagged(format: u8) -> bool {
+    matches!(format, PROOF_FORMAT_PREIMAGE_TAGGED | PROOF_FORMAT_V2_TAGGED)
+}
+
+const ENVELOPE_MAGIC: &[u8; 2] = b"ZP";
+/// Magic and format byte preceding an enveloped proof
+pub const ENVELOPE_HEADER_LEN: usize = ENVELOPE_MAGIC.len() + 1;
+
+/// Length of a bare compressed proof
+const BARE_LEN: usize = 128;
+
+/// Whether this build can generate and verify proofs of `format`
+pub fn format_supported(format: u8) -> bool {
+    if cfg!(feature = "legacy-user-id") {
+        (format == PROOF_FORMAT_LEGACY && cfg!(not(feature = "strict")))
+            || format == PROOF_FORMAT_V2
+            || format == PROOF_FORMAT_V2_TAGGED
+    } else {
+        format == PROOF_FORMAT_PREIMAGE || format == PROOF_FORMAT_PREIMAGE_TAGGED
+    }
+}
+
+/// Map hashed bytes to a public-input field element under `format`
+#[cfg(feature = "legacy-user-id")]
+pub fn field_for(format: u8, data: &[u8]) -> Option<Fr> {
+    match format {
+        #[cfg(not(feature = "strict"))]
+        PROOF_FORMAT_LEGACY => Some(hash_to_field_legacy(data)),
+        PROOF_FORMAT_V2 => Some(Fr::from_le_bytes_mod_order(&Sha256::digest(data))),
+        PROOF_FORMAT_V2_TAGGED => {
+            let digest = Sha256::new().chain_update(crate::domain::FIELD_MAP_TAG).chain_update(data).finalize();
+            Some(Fr::from_le_bytes_mod_order(&digest))
+        }
+        _ => None,
+    }
+}
+
+// SHA-256 truncated to its first 8 bytes, reduced mod 10^12
+#[cfg(all(feature = "legacy-user-id", not(feature = "strict")))]
+fn hash_to_field_legacy(data: &[u8]) -> Fr {
+    let hash = Sha256::digest(data);
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&hash[..8]);
+    Fr::from(u64::from_le_bytes(low) % 1_000_000_000_000u64)
+}
+
+/// Low and high 128-bit limbs of a 32-byte public ID, each read little-endian
+pub fn public_id_limbs(public_id: &[u8]) -> Option<[Fr; 2]> {
+    if public_id.len() != 32 {
+        return None;
+    }
+    Some([
+        Fr::from_le_bytes_mod_order(&public_id[..16]),
+        Fr::from_le_bytes_mod_order(&public_id[16..]),
+    ])
+}
+
+/// Public inputs of a `format` proof for the decoded public ID, in verifier
+/// order; None if the format is not supported or the public ID is malformed
+pub fn public_inputs(format: u8, public_id: &[u8], nonce: u64) -> Option<Vec<Fr>> {
+    if !format_supported(format) {
+        return None;
+    }
+    #[cfg(feature = "legacy-user-id")]
+    let public_id = vec![field_for(format, public_id)?];
+    #[cfg(not(feature = "legacy-user-id"))]
+    let public_id = public_id_limbs(public_id)?.to_vec();
+    Some(public_id.into_iter().chain([Fr::from(nonce)]).collect())
+}
+
+/// Wire form of a compressed proof generated for `format`
+pub fn encode(format: u8, proof: &[u8]) -> Vec<u8> {
+    if format == PROOF_FORMAT_LEGACY {
+        return proof.to_vec();
+    }
+    let mut out = Vec::with_capacity(ENVELOPE_HEADER_LEN + proof.len());
+    out.extend_from_slice(ENVELOPE_MAGIC);
+    out.push(format);
+    out.extend_from_slice(proof);
+    out
+}
+
+/// Split a wire proof into its format and compressed proof
+///
+/// None if it is malformed or its format is not supported by this build.
+pub fn decode(data: &[u8]) -> Option<(u8, &[u8])> {
+    if data.len() == BARE_LEN {
+        return format_supported(PROOF_FORMAT_LEGACY).then_some((PROOF_FORMAT_LEGACY, data));
+    }
+    match data {
+        [m0, m1, format, proof @ ..]
+            if [*m0, *m1] == *ENVELOPE_MAGIC && *format != PROOF_FORMAT_LEGACY && format_supported(*format) =>
+        {
+            Some((*format, proof))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,316 @@
+// ============================================================================
+// Circuit Satisfiability Check (feature "debug-circuit")
+// ============================================================================
+//
+// Before proving, the circuit is synthesized with its witness into a
+// ConstraintSystem and checked with is_satisfied(). An unsatisfied system
+// fails with ZK_ERR_UNSATISFIED instead of producing a proof that cannot
+// verify. The first failing constraint is reported by index and by the name
+// of the ns! span around its enforce_constraint call, which ark-relations'
+// ConstraintLayer records while synthesizing.
+//
+// The same feature carries a mutation harness for under-constrained
+// circuits. Starting from a valid assignment it perturbs each witness
+// variable, and separately each public input with the witness fixed, and
+// re-checks the constraints. Any perturbation that leaves the system
+// satisfied is a gap: a free witness lets a prover claim knowledge it does
+// not have, a malleable input lets a proof be replayed for other public
+// values. ZK_RunConstraintAudit runs it over every circuit in the crate.
+//
+// The feature pulls in a tracing subscriber and doubles synthesis work; it is
+// for development and CI only and is never enabled in enclave builds.
+
+use ark_bn254::Fr;
+use ark_ff::{One, PrimeField};
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem,
+    ConstraintSystemRef, SynthesisError,
+};
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+use crate::ffi::{read_bytes, write_cstr, MAX_FIELD_LEN};
+use crate::hierarchical::{self, HierarchicalCircuit};
+use crate::{domain, emit_format, hex_to_bytes, proof, user_id_circuit};
+
+/// The witness violates a circuit constraint
+pub const ZK_ERR_UNSATISFIED: c_int = -19;
+
+/// The first constraint a witness violates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsatisfied {
+    pub index: usize,
+    /// Name of the enclosing ns! span, e.g. "selector_one_hot"
+    pub name: String,
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constraint {} ({})", self.index, self.name)
+    }
+}
+
+/// Synthesize `circuit` with its witness and find the first unsatisfied
+/// constraint, if any
+pub fn first_unsatisfied<C: ConstraintSynthesizer<Fr>>(
+    circuit: C,
+) -> Result<Option<Unsatisfied>, SynthesisError> {
+    let subscriber = Registry::default().with(ConstraintLayer::default());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+
+        if cs.is_satisfied()? {
+            return Ok(None);
+        }
+
+        let names = cs.constraint_names().unwrap_or_default();
+        let (matrices, z) = assignment(&cs)?;
+        let index = violated(&matrices, &z, 0..matrices.num_constraints)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+
+        Ok(Some(Unsatisfied {
+            index,
+            name: names
+                .get(index)
+                .and_then(|path| path.rsplit("::").next())
+                .map(str::to_string)
+                .unwrap_or_default(),
+        }))
+    })
+}
+
+/// Constraint matrices and the full assignment of a finalized system
+///
+/// Matrix columns index instance variables first (the constant one at 0),
+/// then witnesses; `z` follows the same order.
+fn assignment(
+    cs: &ConstraintSystemRef<Fr>,
+) -> Result<(ConstraintMatrices<Fr>, Vec<Fr>), SynthesisError> {
+    let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+    let system = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+    let z = system
+        .instance_assignment
+        .iter()
+        .chain(&system.witness_assignment)
+        .copied()
+        .collect();
+    Ok((matrices, z))
+}
+
+/// First of `rows` that `z` violates
+fn violated(
+    matrices: &ConstraintMatrices<Fr>,
+    z: &[Fr],
+    rows: impl IntoIterator<Item = usize>,
+) -> Option<usize> {
+    let eval = |row: &[(Fr, usize)]| row.iter().map(|(coeff, i)| *coeff * z[*i]).sum::<Fr>();
+    rows.into_iter()
+        .find(|&i| eval(&matrices.a[i]) * eval(&matrices.b[i]) != eval(&matrices.c[i]))
+}
+
+/// Variables a single perturbation leaves the system satisfied under
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MutationGaps {
+    pub witnesses: usize,
+    pub inputs: usize,
+    /// Witness indices, in allocation order
+    pub free_witnesses: Vec<usize>,
+    /// Public input indices, in verifier order
+    pub malleable_inputs: Vec<usize>,
+}
+
+impl MutationGaps {
+    pub fn count(&self) -> usize {
+        self.free_witnesses.len() + self.malleable_inputs.len()
+    }
+}
+
+/// Perturb each variable of a valid assignment of `circuit` in turn
+///
+/// A variable is a gap if any of the perturbations keeps every constraint
+/// satisfied. Fails with SynthesisError::Unsatisfiable if the starting
+/// assignment is not valid.
+pub fn mutation_gaps<C: ConstraintSynthesizer<Fr>>(
+    circuit: C,
+) -> Result<MutationGaps, SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+    if !cs.is_satisfied()? {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let (matrices, mut z) = assignment(&cs)?;
+    let num_instance = matrices.num_instance_variables;
+
+    // Rows each variable appears in; only those can change
+    let mut rows_of = vec![Vec::new(); z.len()];
+    for (row, (a, (b, c))) in matrices.a.iter().zip(matrices.b.iter().zip(&matrices.c)).enumerate() {
+        for &(_, var) in a.iter().chain(b).chain(c) {
+            if rows_of[var].last() != Some(&row) {
+                rows_of[var].push(row);
+            }
+        }
+    }
+
+    let deltas = [Fr::one(), Fr::from_le_bytes_mod_order(b"zkid/mutation/v1")];
+    let mut gaps = MutationGaps {
+        witnesses: z.len() - num_instance,
+        inputs: num_instance - 1,
+        ..MutationGaps::default()
+    };
+
+    for var in 1..z.len() {
+        let original = z[var];
+        let survives = deltas.iter().any(|delta| {
+            z[var] = original + delta;
+            violated(&matrices, &z, rows_of[var].iter().copied()).is_none()
+        });
+        z[var] = original;
+
+        if !survives {
+            continue;
+        }
+        if var < num_instance {
+            gaps.malleable_inputs.push(var - 1);
+        } else {
+            gaps.free_witnesses.push(var - num_instance);
+        }
+    }
+    Ok(gaps)
+}
+
+/// Pre-proving check: ZK_ERR_UNSATISFIED if the witness violates a
+/// constraint, -1 if synthesis fails
+pub fn check<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<(), c_int> {
+    match first_unsatisfied(circuit) {
+        Ok(None) => Ok(()),
+        Ok(Some(_)) => Err(ZK_ERR_UNSATISFIED),
+        Err(_) => Err(-1),
+    }
+}
+
+fn indices(list: &[usize]) -> String {
+    list.iter().map(usize::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn report_entry(circuit: &str, gaps: &MutationGaps) -> String {
+    format!(
+        r#"{{"circuit":"{}","witnesses":{},"inputs":{},"free_witnesses":[{}],"malleable_inputs":[{}]}}"#,
+        circuit,
+        gaps.witnesses,
+        gaps.inputs,
+        indices(&gaps.free_witnesses),
+        indices(&gaps.malleable_inputs),
+    )
+}
+
+/// Mutation gaps of every circuit in the crate, each with a valid witness
+fn audit() -> Result<Vec<(&'static str, MutationGaps)>, SynthesisError> {
+    let user_id = user_id_circuit(proof::emit_format(false), b"zkid/audit/user", &Sha256::digest(b"zkid/audit/user"), 42)
+        .map_err(|_| SynthesisError::AssignmentMissing)?;
+    let tagged_public_id = domain::public_id(true, b"zkid/audit/user");
+    let user_id_tagged = user_id_circuit(proof::emit_format(true), b"zkid/audit/user", &tagged_public_id, 42)
+        .map_err(|_| SynthesisError::AssignmentMissing)?;
+
+    let segments: [&[u8]; 3] = [b"org", b"team", b"user"];
+    let hierarchical: HierarchicalCircuit =
+        hierarchical::witness(&segments, 1, 42).ok_or(SynthesisError::AssignmentMissing)?;
+
+    Ok(vec![
+        ("user_id", mutation_gaps(user_id)?),
+        ("user_id_tagged", mutation_gaps(user_id_tagged)?),
+        ("hierarchical", mutation_gaps(hierarchical)?),
+    ])
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Run the mutation harness over every circuit in the crate
+///
+/// Writes a JSON report to `report_out`:
+///
+///   {"circuits":[{"circuit":"user_id","witnesses":2,"inputs":2,
+///                 "free_witnesses":[],"malleable_inputs":[]},...],"gaps":0}
+///
+/// Returns the total number of gaps (0 = every variable is constrained), or
+/// -1 if a circuit fails to synthesize, ZK_ERR_BUFFER_TOO_SMALL if the report
+/// does not fit.
+#[no_mangle]
+pub extern "C" fn ZK_RunConstraintAudit(report_out: *mut c_char, report_out_size: usize) -> c_int {
+    let circuits = match audit() {
+        Ok(circuits) => circuits,
+        Err(_) => return -1,
+    };
+
+    let total: usize = circuits.iter().map(|(_, gaps)| gaps.count()).sum();
+    let entries: Vec<String> = circuits
+        .iter()
+        .map(|(name, gaps)| report_entry(name, gaps))
+        .collect();
+    let report = format!(r#"{{"circuits":[{}],"gaps":{}}}"#, entries.join(","), total);
+
+    match write_cstr(report_out, report_out_size, &report) {
+        Ok(_) => total.min(c_int::MAX as usize) as c_int,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the user-ID circuit for the given inputs without proving
+///
+/// Takes the same inputs as ZK_GenerateProof, and checks the circuit of the
+/// format it would emit, but skips its native hash-match pre-check, so a mismatched public_id reaches the constraint
+/// system. Returns 0 if satisfied, ZK_ERR_UNSATISFIED with "constraint
+/// <index> (<name>)" written to `report_out` if not, ZK_ERR_BUFFER_TOO_SMALL
+/// if that does not fit, ZK_ERR_INPUT_TOO_LARGE if user_id exceeds what the
+/// circuit hashes, -1 on invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_CheckCircuitSatisfiability(
+    user_id: *const c_char,
+    user_id_len: usize,
+    public_id: *const c_char,
+    nonce: u64,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    if user_id.is_null() || public_id.is_null() {
+        return -1;
+    }
+
+    let user_id_bytes = match read_bytes(user_id, user_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let public_id_str = unsafe { CStr::from_ptr(public_id).to_str().unwrap_or("") };
+    let public_id_bytes = match hex_to_bytes(public_id_str) {
+        Ok(bytes) => bytes,
+        Err(_) => return -1,
+    };
+
+    let circuit = match user_id_circuit(emit_format(), user_id_bytes, &public_id_bytes, nonce) {
+        Ok(circuit) => circuit,
+        Err(code) => return code,
+    };
+
+    match first_unsatisfied(circuit) {
+        Ok(None) => 0,
+        Ok(Some(unsatisfied)) => match write_cstr(report_out, report_out_size, &unsatisfied.to_string()) {
+            Ok(_) => ZK_ERR_UNSATISFIED,
+            Err(e) => e.code(),
+        },
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,35 @@
+// ============================================================================
+// Poison-Tolerant Mutex
+// ============================================================================
+//
+// A panic while a std Mutex is held poisons it, and the entry points used
+// to turn every later Err from lock() into -1 or "no keys" for the rest of
+// the process (release builds abort on panic, but dev builds unwind). The
+// locked state (key slots, allowlist trees) is replaced whole, so a
+// poisoned lock still guards consistent data: this Mutex takes it over.
+// lock() keeps std's signature and never returns Err.
+
+use std::sync::{LockResult, MutexGuard, PoisonError};
+
+/// std::sync::Mutex whose lock() recovers from poisoning
+#[derive(Debug, Default)]
+pub(crate) struct Mutex<T: ?Sized>(std::sync::Mutex<T>);
+
+impl<T> Mutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(std::sync::Mutex::new(value))
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock, taking over the data of a poisoned lock; always Ok
+    pub(crate) fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        Ok(self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Whether a thread panicked while holding the lock
+    #[cfg(feature = "fault-injection")]
+    pub(crate) fn is_poisoned(&self) -> bool {
+        self.0.is_poisoned()
+    }
+}
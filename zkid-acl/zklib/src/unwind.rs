@@ -0,0 +1,51 @@
+// ============================================================================
+// Panic Boundary
+// ============================================================================
+//
+// A panic unwinding out of an extern "C" function into C is undefined
+// behavior. Every entry point runs its body through guard(), which catches
+// the panic and returns ZK_ERR_PANIC instead (a void function just returns).
+// Entry points are declared inside entry_point! {}, which adds the guard
+// around the body as written.
+// The release profile unwinds so that this works in enclave builds too, and
+// the key locks recover from poisoning (src/sync.rs), so the library stays
+// usable after a caught panic.
+
+use std::os::raw::c_int;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::error::ZK_ERR_PANIC;
+
+/// What an entry point returns when its body panicked
+pub(crate) trait PanicReturn {
+    fn on_panic() -> Self;
+}
+
+impl PanicReturn for c_int {
+    fn on_panic() -> Self {
+        ZK_ERR_PANIC
+    }
+}
+
+impl PanicReturn for () {
+    fn on_panic() {}
+}
+
+/// Run an entry point's body, turning a panic into ZK_ERR_PANIC
+pub(crate) fn guard<R: PanicReturn>(body: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| R::on_panic())
+}
+
+/// Declare an extern "C" entry point whose body runs through guard()
+macro_rules! entry_point {
+    (
+        $(#[$attr:meta])*
+        $vis:vis extern "C" fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        $(#[$attr])*
+        $vis extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $crate::unwind::guard(|| $body)
+        }
+    };
+}
+pub(crate) use entry_point;
@@ -129,7 +129,8 @@ int main() {
         vc.expiry_date,
         issuer_private_key,
         vc_signature,
-        sizeof(vc_signature)
+        sizeof(vc_signature),
+        NULL
     ) != 0) {
         print_msg("[Enclave1] ERROR: Failed to sign VC\n");
         EAPP_RETURN(1);
@@ -294,7 +295,8 @@ int main() {
         challenge.current_time,  // Public: current time
         challenge.nonce,         // Public: challenge nonce
         proof_hex,              // Output: ZK proof
-        sizeof(proof_hex)
+        sizeof(proof_hex),
+        NULL                    // Required size not needed: buffer is ample
     );
     
     if (proof_result != 0) {
@@ -0,0 +1,682 @@
+//! Benchmark suite for published performance numbers
+//!
+//!     cargo bench --features bench --bench zklib [-- <filter>]
+//!
+//! Groups:
+//!
+//!   setup          ZK_Init (every circuit's key generation)
+//!   prove          one proof per circuit
+//!   verify         one verification per circuit, and the VC circuit
+//!                  through a prepared context (ZK_PreparePublicContext)
+//!   batch_verify   16, 128 and 1000 VC proofs: ZK_BatchVerifyVCProofs
+//!                  against the loop over ZK_VerifyVCProof, globally and
+//!                  prepared, that it replaces
+//!   proof_cache    a repeated challenge answered from the nonce cache
+//!                  (ZK_NONCE_REUSE_RERANDOMIZE) against proving it anew
+//!   serialization  decode + encode round trips of VC blobs, proofs,
+//!                  verifying keys, presentations and predicates
+//!   rejection      the costliest input each admission stage refuses, and a
+//!                  16 MiB proof through ZK_VerifyVCProof
+//!
+//! Fixtures are fixed: issuer and holder keys derive from constant secrets,
+//! credentials are issued at a constant time, and ZK_Init and the provers
+//! seed their randomness (setup seeds, ZK_SetDeterministicProving), so every
+//! run measures the same keys and proofs. Strict builds draw from the OS instead and are
+//! only comparable in distribution.
+//!
+//! Benches run under the release profile, the one enclave builds use.
+//! After the run, the estimates of every benchmark measured in it are
+//! written to summary.json in the criterion directory ($CRITERION_HOME, or
+//! criterion/ under the target directory):
+//!
+//!   {"build":{...ZK_BuildInfo...},"benchmarks":[{"id":"verify/vc",
+//!    "mean_ns":..,"median_ns":..,"std_dev_ns":..,"mean_lower_ns":..,
+//!    "mean_upper_ns":..,"elements":null},...]}
+
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::hint::black_box;
+use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::ptr;
+use std::time::SystemTime;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Value};
+
+use zklib_vc::admission::{ZK_PrecheckProof, MAX_PROOF_HEX_LEN};
+use zklib_vc::age::{ZK_GenerateDerivedAgeProof, ZK_VerifyDerivedAgeProof};
+use zklib_vc::ballot::{ZK_GenerateBallotProof, ZK_VerifyBallotProof};
+use zklib_vc::batch::ZK_BatchVerifyVCProofs;
+use zklib_vc::composite::{
+    ZK_AddClaimGroup, ZK_EncodeCompositeVC, ZK_GenerateGroupClaimProof, ZK_SignClaimGroup, ZK_VerifyGroupClaimProof,
+};
+use zklib_vc::credential::{ZK_EncodeVC, ZK_GenerateVCProofFromBlob, ZK_SignVCBlob};
+use zklib_vc::error::ZK_ERR_NULLIFIER_USED;
+#[cfg(not(feature = "strict"))]
+use zklib_vc::entropy::ZK_SetDeterministicProving;
+use zklib_vc::grace::{ZK_SetProverExpiryGrace, ZK_VerifyVCProofWithGrace, ZkGracePolicy, ZkGraceReport};
+use zklib_vc::holder;
+use zklib_vc::predicate::{Predicate, ZK_EncodePredicate, ZK_GeneratePredicateProof, ZK_VerifyPredicateProof};
+use zklib_vc::prepared::{ZK_FreePublicContext, ZK_PreparePublicContext, ZK_VerifyVCProofPrepared, PublicContext};
+use zklib_vc::presentation::{Presentation, ZK_EncodePresentation};
+use zklib_vc::rebind::{ZK_GenerateHolderBoundProof, ZK_HolderKeyCommitment, ZK_VerifyHolderBoundProof, HOLDER_KEY_CLAIM};
+use zklib_vc::replay::{ZK_SetNonceReusePolicy, ZkNonceCacheConfig, ZK_NONCE_REUSE_RERANDOMIZE, ZK_NONCE_REUSE_WARN};
+use zklib_vc::schedule::{ZK_GenerateScheduleProof, ZK_SetValiditySchedule, ZK_VerifyScheduleProof, ZK_WINDOW_INTERVAL};
+use zklib_vc::usage::{ZK_DeriveIssuerSubkey, ZK_KEY_USAGE_CREDENTIAL};
+use zklib_vc::{
+    proof, vk, VerifiableCredential, ZK_BuildInfo, ZK_ExportVerifyingKey, ZK_GenerateVCProof, ZK_Init, ZK_SignVC,
+    ZK_VerifyVCProof,
+};
+
+/// Time every fixture is issued and proven at (2026-09-21T12:26:40Z)
+const NOW: u64 = 1_790_000_000;
+const DAY: u64 = 86_400;
+const NONCE: u64 = 0x5eed;
+/// Seed of every proof's randomness (ZK_SetDeterministicProving)
+#[cfg(not(feature = "strict"))]
+const PROVING_SEED: u64 = 0x5eed;
+
+const HOLDER: &str = "did:example:alice";
+const ISSUER: &str = "did:example:plant-operator";
+const ISSUER_SECRET: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+const DEVICE_SECRET: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+const HOLDER_SECRET: &str = "0303030303030303030303030303030303030303030303030303030303030303";
+const BALLOT_HASH: &str = "0404040404040404040404040404040404040404040404040404040404040404";
+const POLL: &[u8] = b"bench-poll";
+
+/// Distinct proofs cycled through by batch_verify
+const POOL: u64 = 10;
+const BATCH_SIZES: [u64; 3] = [16, 128, 1000];
+
+/// Input size of the oversized rejection, MAX_BLOB_LEN of the C API
+const MAX_INPUT: usize = 16 * 1024 * 1024;
+
+/// Hex of an enveloped format-2 proof header
+const ENVELOPE: &str = "5a5002";
+
+fn fail(what: &str) -> ! {
+    eprintln!("zklib bench: {}", what);
+    process::exit(2);
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s).unwrap_or_else(|_| fail("interior NUL"))
+}
+
+/// Call an FFI function that writes a C string into a caller buffer
+fn out_string(what: &str, size: usize, f: impl FnOnce(*mut c_char, usize) -> c_int) -> String {
+    let mut buf = vec![0u8; size];
+    let rc = f(buf.as_mut_ptr() as *mut c_char, buf.len());
+    if rc != 0 {
+        fail(&format!("{} returned {}", what, rc));
+    }
+    CStr::from_bytes_until_nul(&buf)
+        .ok()
+        .and_then(|s| s.to_str().ok())
+        .unwrap_or_else(|| fail(what))
+        .to_string()
+}
+
+/// A key pair derived from a constant secret
+fn keypair(secret: &str) -> (CString, CString) {
+    let secret = cstring(secret);
+    let mut public_key = [0u8; 65];
+    let mut private_key = [0u8; 65];
+    let rc = ZK_DeriveIssuerSubkey(
+        secret.as_ptr(),
+        ZK_KEY_USAGE_CREDENTIAL,
+        public_key.as_mut_ptr() as *mut c_char,
+        public_key.len(),
+        private_key.as_mut_ptr() as *mut c_char,
+        private_key.len(),
+    );
+    if rc != 0 {
+        fail("ZK_DeriveIssuerSubkey failed");
+    }
+    let text = |buf: &[u8]| CStr::from_bytes_until_nul(buf).unwrap_or_else(|_| fail("key")).to_owned();
+    (text(&public_key), text(&private_key))
+}
+
+/// C arrays of claim keys and values; the CStrings own the pointed-to text
+struct Claims {
+    _owned: Vec<CString>,
+    keys: Vec<*const c_char>,
+    values: Vec<*const c_char>,
+}
+
+impl Claims {
+    fn new(claims: &[(&str, &str)]) -> Self {
+        let owned: Vec<CString> = claims.iter().flat_map(|(k, v)| [cstring(k), cstring(v)]).collect();
+        Self {
+            keys: owned.iter().step_by(2).map(|k| k.as_ptr()).collect(),
+            values: owned.iter().skip(1).step_by(2).map(|v| v.as_ptr()).collect(),
+            _owned: owned,
+        }
+    }
+}
+
+fn encode_vc(claims: &[(&str, &str)], expiry_date: u64) -> String {
+    let claims = Claims::new(claims);
+    out_string("ZK_EncodeVC", 8192, |out, size| {
+        ZK_EncodeVC(
+            HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+            ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+            NOW - 30 * DAY, expiry_date,
+            claims.keys.as_ptr(), claims.values.as_ptr(), claims.keys.len(),
+            ptr::null(),
+            out, size,
+        )
+    })
+}
+
+fn sign_blob(unsigned: &str, private_key: &CStr) -> CString {
+    let unsigned = cstring(unsigned);
+    cstring(&out_string("ZK_SignVCBlob", 8192, |out, size| {
+        ZK_SignVCBlob(unsigned.as_ptr(), private_key.as_ptr(), out, size)
+    }))
+}
+
+fn issue_vc(claims: &[(&str, &str)], private_key: &CStr) -> CString {
+    sign_blob(&encode_vc(claims, NOW + 365 * DAY), private_key)
+}
+
+/// Proof (hex) for a nonce
+type Prove = Box<dyn Fn(u64) -> CString>;
+/// Result code for a proof (hex) and nonce
+type Verify = Box<dyn Fn(&CStr, u64) -> c_int>;
+
+/// One circuit's prove and verify, on fixed fixtures
+struct Circuit {
+    name: &'static str,
+    prove: Prove,
+    verify: Verify,
+    /// What `verify` answers for a proof it has already seen
+    repeat_code: c_int,
+}
+
+fn circuits(public_key: &CString, private_key: &CString) -> Vec<Circuit> {
+    let mut circuits = Vec::new();
+    let pk = public_key.clone();
+
+    // VC
+    let blob = issue_vc(&[("role", "operator"), ("site", "plant-3")], private_key);
+    let (p, v) = (pk.clone(), pk.clone());
+    circuits.push(Circuit {
+        name: "vc",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateVCProofFromBlob", 4096, |out, size| {
+                ZK_GenerateVCProofFromBlob(blob.as_ptr(), p.as_ptr(), ptr::null(), NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| ZK_VerifyVCProof(proof.as_ptr(), v.as_ptr(), NOW, nonce)),
+        repeat_code: 1,
+    });
+
+    // Validity schedule: one interval around NOW
+    let unsigned = cstring(&encode_vc(&[("role", "operator")], NOW + 365 * DAY));
+    let windowed = out_string("ZK_SetValiditySchedule", 8192, |out, size| {
+        ZK_SetValiditySchedule(
+            unsigned.as_ptr(),
+            &ZK_WINDOW_INTERVAL,
+            &((NOW - DAY) as i64),
+            &((NOW + DAY) as i64),
+            1,
+            out, size,
+        )
+    });
+    let blob = sign_blob(&windowed, private_key);
+    let (p, v) = (pk.clone(), pk.clone());
+    circuits.push(Circuit {
+        name: "schedule",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateScheduleProof", 4096, |out, size| {
+                ZK_GenerateScheduleProof(blob.as_ptr(), p.as_ptr(), ptr::null(), NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| ZK_VerifyScheduleProof(proof.as_ptr(), v.as_ptr(), NOW, nonce)),
+        repeat_code: 1,
+    });
+
+    // Group claim: a composite credential with one signed group
+    let empty = cstring(&out_string("ZK_EncodeCompositeVC", 8192, |out, size| {
+        ZK_EncodeCompositeVC(
+            HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+            (NOW - 30 * DAY) as i64, (NOW + 365 * DAY) as i64,
+            ptr::null(), ptr::null(), 0,
+            out, size,
+        )
+    }));
+    let claims = Claims::new(&[("role", "operator")]);
+    let grouped = cstring(&out_string("ZK_AddClaimGroup", 8192, |out, size| {
+        ZK_AddClaimGroup(
+            empty.as_ptr(),
+            ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+            pk.as_ptr(),
+            claims.keys.as_ptr(), claims.values.as_ptr(), claims.keys.len(),
+            out, size,
+        )
+    }));
+    let composite = cstring(&out_string("ZK_SignClaimGroup", 8192, |out, size| {
+        ZK_SignClaimGroup(grouped.as_ptr(), 0, private_key.as_ptr(), out, size)
+    }));
+    let (p, v) = (pk.clone(), pk.clone());
+    let (role, operator) = (cstring("role"), cstring("operator"));
+    let role_p = role.clone();
+    circuits.push(Circuit {
+        name: "group-claim",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateGroupClaimProof", 4096, |out, size| {
+                ZK_GenerateGroupClaimProof(composite.as_ptr(), 0, role_p.as_ptr(), p.as_ptr(), NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| {
+            ZK_VerifyGroupClaimProof(proof.as_ptr(), v.as_ptr(), role.as_ptr(), operator.as_ptr(), nonce)
+        }),
+        repeat_code: 1,
+    });
+
+    // Ballot: verification records the nullifier, so every repeat is the
+    // double-vote refusal, which runs the same pairing
+    let secret = cstring(HOLDER_SECRET);
+    let binding = holder::claims(&holder::secret_field(&[3; 32]));
+    let blob = issue_vc(&[(&binding[0].0, &binding[0].1)], private_key);
+    let ballot_hash = cstring(BALLOT_HASH);
+    let nullifier = std::rc::Rc::new(std::cell::RefCell::new(CString::default()));
+    let (p, v, nullifier_p, ballot_hash_v) = (pk.clone(), pk.clone(), nullifier.clone(), ballot_hash.clone());
+    circuits.push(Circuit {
+        name: "ballot",
+        prove: Box::new(move |_| {
+            let mut nullifier_out = [0u8; 128];
+            let proof = cstring(&out_string("ZK_GenerateBallotProof", 4096, |out, size| {
+                ZK_GenerateBallotProof(
+                    blob.as_ptr(), p.as_ptr(), ptr::null(),
+                    secret.as_ptr(),
+                    POLL.as_ptr(), POLL.len(),
+                    ballot_hash.as_ptr(),
+                    NOW,
+                    out, size,
+                    nullifier_out.as_mut_ptr() as *mut c_char, nullifier_out.len(),
+                )
+            }));
+            *nullifier_p.borrow_mut() = CStr::from_bytes_until_nul(&nullifier_out)
+                .unwrap_or_else(|_| fail("nullifier"))
+                .to_owned();
+            proof
+        }),
+        verify: Box::new(move |proof, _| {
+            ZK_VerifyBallotProof(
+                proof.as_ptr(), v.as_ptr(),
+                POLL.as_ptr(), POLL.len(),
+                nullifier.borrow().as_ptr(),
+                ballot_hash_v.as_ptr(),
+            )
+        }),
+        repeat_code: ZK_ERR_NULLIFIER_USED,
+    });
+
+    // Derived age: at least 18 from a birthdate claim
+    let birthdate = (NOW - 30 * 365 * DAY).to_string();
+    let blob = issue_vc(&[("birthdate", &birthdate)], private_key);
+    let (p, v) = (pk.clone(), pk.clone());
+    let key = cstring("birthdate");
+    let key_p = key.clone();
+    circuits.push(Circuit {
+        name: "derived-age",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateDerivedAgeProof", 4096, |out, size| {
+                ZK_GenerateDerivedAgeProof(blob.as_ptr(), p.as_ptr(), ptr::null(), key_p.as_ptr(), 18, NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| {
+            ZK_VerifyDerivedAgeProof(proof.as_ptr(), v.as_ptr(), key.as_ptr(), 18, NOW, nonce)
+        }),
+        repeat_code: 1,
+    });
+
+    // Holder-bound: the original device key, no rebind certificates
+    let (device_public, _) = keypair(DEVICE_SECRET);
+    let device_commitment = out_string("ZK_HolderKeyCommitment", 128, |out, size| {
+        ZK_HolderKeyCommitment(device_public.as_ptr(), out, size)
+    });
+    let blob = issue_vc(&[(HOLDER_KEY_CLAIM, &device_commitment)], private_key);
+    let (p, v) = (pk.clone(), pk.clone());
+    circuits.push(Circuit {
+        name: "holder-bound",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateHolderBoundProof", 4096, |out, size| {
+                ZK_GenerateHolderBoundProof(blob.as_ptr(), p.as_ptr(), ptr::null(), ptr::null(), 0, NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| {
+            ZK_VerifyHolderBoundProof(proof.as_ptr(), v.as_ptr(), device_public.as_ptr(), ptr::null(), 0, nonce)
+        }),
+        repeat_code: 1,
+    });
+
+    // Grace: a credential that expired a day ago, proven within a week
+    if ZK_SetProverExpiryGrace(7 * DAY) != 0 {
+        fail("ZK_SetProverExpiryGrace failed");
+    }
+    let expiry = NOW - DAY;
+    let blob = sign_blob(&encode_vc(&[], expiry), private_key);
+    let signature = cstring(&out_string("ZK_SignVC", 256, |out, size| {
+        ZK_SignVC(
+            HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+            ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+            NOW - 30 * DAY, expiry,
+            private_key.as_ptr(),
+            out, size, ptr::null_mut(),
+        )
+    }));
+    let (p, v) = (pk.clone(), pk.clone());
+    circuits.push(Circuit {
+        name: "grace",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GenerateVCProof", 4096, |out, size| {
+                ZK_GenerateVCProof(
+                    HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+                    ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+                    NOW - 30 * DAY, expiry,
+                    signature.as_ptr(), p.as_ptr(),
+                    NOW, nonce,
+                    out, size, ptr::null_mut(),
+                )
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| {
+            let policy = ZkGracePolicy {
+                issuer_public_key: v.as_ptr(),
+                second_issuer_public_key: ptr::null(),
+                current_time: NOW,
+                allow_expired_within_seconds: 7 * DAY,
+            };
+            let mut report = ZkGraceReport::default();
+            ZK_VerifyVCProofWithGrace(proof.as_ptr(), blob.as_ptr(), nonce, &policy, &mut report)
+        }),
+        repeat_code: 1,
+    });
+
+    // Predicate: age at least 18
+    let json = r#"{"claim":"age","op":"at_least","value":"18"}"#;
+    let predicate = cstring(&out_string("ZK_EncodePredicate", 1024, |out, size| {
+        ZK_EncodePredicate(json.as_ptr() as *const c_char, json.len(), out, size, ptr::null_mut(), 0)
+    }));
+    let blob = issue_vc(&[("age", "34")], private_key);
+    let (p, v) = (pk.clone(), pk);
+    let predicate_p = predicate.clone();
+    circuits.push(Circuit {
+        name: "predicate",
+        prove: Box::new(move |nonce| {
+            cstring(&out_string("ZK_GeneratePredicateProof", 4096, |out, size| {
+                ZK_GeneratePredicateProof(blob.as_ptr(), p.as_ptr(), ptr::null(), predicate_p.as_ptr(), NOW, nonce, out, size)
+            }))
+        }),
+        verify: Box::new(move |proof, nonce| {
+            ZK_VerifyPredicateProof(proof.as_ptr(), v.as_ptr(), predicate.as_ptr(), nonce)
+        }),
+        repeat_code: 1,
+    });
+
+    circuits
+}
+
+fn bench_setup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("setup");
+    group.sample_size(10);
+    group.bench_function("init", |b| b.iter(|| assert_eq!(ZK_Init(), 0)));
+    group.finish();
+}
+
+fn bench_circuits(c: &mut Criterion, circuits: &[Circuit], public_key: &CStr) {
+    let mut group = c.benchmark_group("prove");
+    group.sample_size(10);
+    for circuit in circuits {
+        group.bench_function(circuit.name, |b| b.iter(|| (circuit.prove)(black_box(NONCE))));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("verify");
+    for circuit in circuits {
+        let proof = (circuit.prove)(NONCE);
+        let first = (circuit.verify)(&proof, NONCE);
+        if first != 1 || (circuit.verify)(&proof, NONCE) != circuit.repeat_code {
+            fail(&format!("{} fixture does not verify ({})", circuit.name, first));
+        }
+        group.bench_function(circuit.name, |b| b.iter(|| (circuit.verify)(black_box(&proof), NONCE)));
+    }
+
+    let context = prepare(public_key);
+    let proof = (circuits[0].prove)(NONCE);
+    group.bench_function("vc-prepared", |b| {
+        b.iter(|| ZK_VerifyVCProofPrepared(context, black_box(proof.as_ptr()), NOW, NONCE))
+    });
+    group.finish();
+    ZK_FreePublicContext(context);
+}
+
+fn prepare(public_key: &CStr) -> *mut PublicContext {
+    let mut context = ptr::null_mut();
+    if ZK_PreparePublicContext(public_key.as_ptr(), &mut context) != 0 {
+        fail("ZK_PreparePublicContext failed");
+    }
+    context
+}
+
+fn bench_batch(c: &mut Criterion, vc: &Circuit, public_key: &CStr) {
+    let proofs: Vec<(CString, u64)> = (0..POOL).map(|i| ((vc.prove)(NONCE + i), NONCE + i)).collect();
+    if !proofs.iter().all(|(proof, nonce)| (vc.verify)(proof, *nonce) == 1) {
+        fail("batch fixture does not verify");
+    }
+    let context = prepare(public_key);
+
+    let mut group = c.benchmark_group("batch_verify");
+    group.sample_size(10);
+    for size in BATCH_SIZES {
+        let batch: Vec<&(CString, u64)> = proofs.iter().cycle().take(size as usize).collect();
+        group.throughput(Throughput::Elements(size));
+        group.bench_with_input(BenchmarkId::new("global", size), &batch, |b, batch| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .all(|(proof, nonce)| ZK_VerifyVCProof(proof.as_ptr(), public_key.as_ptr(), NOW, *nonce) == 1)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("prepared", size), &batch, |b, batch| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .all(|(proof, nonce)| ZK_VerifyVCProofPrepared(context, proof.as_ptr(), NOW, *nonce) == 1)
+            })
+        });
+        let proof_ptrs: Vec<*const c_char> = batch.iter().map(|(proof, _)| proof.as_ptr()).collect();
+        let issuer_ptrs = vec![public_key.as_ptr(); batch.len()];
+        let nonces: Vec<u64> = batch.iter().map(|(_, nonce)| *nonce).collect();
+        let mut results = vec![0; batch.len()];
+        let mut batch_verify = || {
+            ZK_BatchVerifyVCProofs(
+                proof_ptrs.as_ptr(), issuer_ptrs.as_ptr(),
+                NOW, nonces.as_ptr(),
+                batch.len(), results.as_mut_ptr(),
+            )
+        };
+        if batch_verify() != size as c_int {
+            fail("batch fixture does not verify batched");
+        }
+        group.bench_function(BenchmarkId::new("batched", size), |b| b.iter(&mut batch_verify));
+    }
+    group.finish();
+    ZK_FreePublicContext(context);
+}
+
+fn set_nonce_policy(policy: c_int) {
+    let config = ZkNonceCacheConfig {
+        capacity: 64,
+        ttl_secs: 600,
+        policy,
+    };
+    if ZK_SetNonceReusePolicy(&config) != 0 {
+        fail("ZK_SetNonceReusePolicy failed");
+    }
+}
+
+fn bench_proof_cache(c: &mut Criterion, vc: &Circuit) {
+    let mut group = c.benchmark_group("proof_cache");
+    group.sample_size(10);
+
+    set_nonce_policy(ZK_NONCE_REUSE_RERANDOMIZE);
+    (vc.prove)(NONCE);
+    group.bench_function("hit", |b| b.iter(|| (vc.prove)(black_box(NONCE))));
+    set_nonce_policy(ZK_NONCE_REUSE_WARN);
+    group.bench_function("miss", |b| b.iter(|| (vc.prove)(black_box(NONCE))));
+    group.finish();
+}
+
+fn bench_serialization(c: &mut Criterion, vc: &Circuit, public_key: &CStr, private_key: &CStr) {
+    let blob = issue_vc(&[("role", "operator"), ("site", "plant-3")], private_key);
+    let blob = hex::decode(blob.to_bytes()).unwrap_or_else(|_| fail("blob hex"));
+    let proof_hex = (vc.prove)(NONCE);
+    let proof = hex::decode(proof_hex.to_bytes()).unwrap_or_else(|_| fail("proof hex"));
+    let vk_hex = out_string("ZK_ExportVerifyingKey", 4096, |out, size| ZK_ExportVerifyingKey(out, size));
+    let vk = hex::decode(vk_hex).unwrap_or_else(|_| fail("vk hex"));
+    let audience = (cstring("audience"), cstring("gateway"));
+    let presentation = out_string("ZK_EncodePresentation", 8192, |out, size| {
+        ZK_EncodePresentation(
+            proof_hex.as_ptr(), public_key.as_ptr(), NONCE,
+            &audience.0.as_ptr(), &audience.1.as_ptr(), 1,
+            out, size,
+        )
+    });
+    let presentation = hex::decode(presentation).unwrap_or_else(|_| fail("presentation hex"));
+    let predicate = Predicate::from_shorthand("age", ">=18")
+        .unwrap_or_else(|| fail("predicate"))
+        .to_bytes();
+
+    let mut group = c.benchmark_group("serialization");
+    group.bench_function("vc", |b| {
+        b.iter(|| VerifiableCredential::from_bytes(black_box(&blob)).map(|vc| vc.to_bytes()))
+    });
+    group.bench_function("proof", |b| {
+        b.iter(|| proof::decode(black_box(&proof)).map(|(format, proof)| proof::encode(format, &proof)))
+    });
+    group.bench_function("verifying-key", |b| {
+        b.iter(|| vk::decode_raw(black_box(&vk)).map(|key| vk::encode(&key)))
+    });
+    group.bench_function("presentation", |b| {
+        b.iter(|| Presentation::from_bytes(black_box(&presentation)).map(|p| p.to_bytes()))
+    });
+    group.bench_function("predicate", |b| {
+        b.iter(|| Predicate::from_bytes(black_box(&predicate)).map(|p| p.to_bytes()))
+    });
+    group.finish();
+}
+
+fn bench_rejection(c: &mut Criterion, public_key: &CStr) {
+    // The costliest input each stage refuses: everything before it passes
+    let body = |byte: &str| format!("{ENVELOPE}{}", byte.repeat(128));
+    let mut encoding = body("00");
+    encoding.replace_range(encoding.len() - 1.., "g");
+    let stages = [
+        ("length", "0".repeat(MAX_PROOF_HEX_LEN + 1)),
+        ("version", "0".repeat(MAX_PROOF_HEX_LEN - 2)),
+        ("encoding", encoding),
+        ("canonical", body("ff")),
+        ("deserialize", body("01")),
+    ];
+
+    let mut group = c.benchmark_group("rejection");
+    for (stage, input) in stages {
+        let input = cstring(&input);
+        group.bench_function(stage, |b| b.iter(|| ZK_PrecheckProof(black_box(input.as_ptr()))));
+    }
+    let oversized = cstring(&"0".repeat(MAX_INPUT - 1));
+    group.bench_function("oversized-proof", |b| {
+        b.iter(|| ZK_VerifyVCProof(black_box(oversized.as_ptr()), public_key.as_ptr(), NOW, NONCE))
+    });
+    group.finish();
+}
+
+/// Where criterion writes its results
+fn criterion_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    let target = std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| PathBuf::from("target"), PathBuf::from);
+    target.join("criterion")
+}
+
+/// Estimates of every benchmark written under `dir` since `since`
+fn collect(dir: &Path, since: SystemTime, out: &mut Vec<Value>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let estimates = path.join("new").join("estimates.json");
+        let fresh = fs::metadata(&estimates).and_then(|m| m.modified()).is_ok_and(|modified| modified >= since);
+        if fresh {
+            let read = |file: &Path| -> Option<Value> { serde_json::from_slice(&fs::read(file).ok()?).ok() };
+            if let (Some(benchmark), Some(estimates)) = (read(&path.join("new").join("benchmark.json")), read(&estimates)) {
+                out.push(json!({
+                    "id": benchmark["full_id"],
+                    "mean_ns": estimates["mean"]["point_estimate"],
+                    "median_ns": estimates["median"]["point_estimate"],
+                    "std_dev_ns": estimates["std_dev"]["point_estimate"],
+                    "mean_lower_ns": estimates["mean"]["confidence_interval"]["lower_bound"],
+                    "mean_upper_ns": estimates["mean"]["confidence_interval"]["upper_bound"],
+                    "elements": benchmark["throughput"]["Elements"],
+                }));
+            }
+        }
+        collect(&path, since, out);
+    }
+}
+
+fn write_summary(since: SystemTime) {
+    let dir = criterion_dir();
+    let mut benchmarks = Vec::new();
+    collect(&dir, since, &mut benchmarks);
+    if benchmarks.is_empty() {
+        return;
+    }
+    benchmarks.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+    let build = out_string("ZK_BuildInfo", 1024, |out, size| ZK_BuildInfo(out, size));
+    let summary = json!({
+        "build": serde_json::from_str::<Value>(&build).unwrap_or(Value::Null),
+        "benchmarks": benchmarks,
+    });
+    let path = dir.join("summary.json");
+    match fs::write(&path, format!("{:#}\n", summary)) {
+        Ok(()) => println!("summary: {}", path.display()),
+        Err(e) => fail(&format!("write {}: {}", path.display(), e)),
+    }
+}
+
+fn main() {
+    let started = SystemTime::now();
+    let mut criterion = Criterion::default().configure_from_args();
+
+    if ZK_Init() != 0 {
+        fail("ZK_Init failed");
+    }
+    #[cfg(not(feature = "strict"))]
+    ZK_SetDeterministicProving(PROVING_SEED);
+    let (public_key, private_key) = keypair(ISSUER_SECRET);
+    let circuits = circuits(&public_key, &private_key);
+
+    bench_setup(&mut criterion);
+    bench_circuits(&mut criterion, &circuits, &public_key);
+    bench_batch(&mut criterion, &circuits[0], &public_key);
+    bench_proof_cache(&mut criterion, &circuits[0]);
+    bench_serialization(&mut criterion, &circuits[0], &public_key, &private_key);
+    bench_rejection(&mut criterion, &public_key);
+
+    criterion.final_summary();
+    write_summary(started);
+}
@@ -26,9 +26,41 @@ if ! command -v cbindgen &> /dev/null; then
     cargo install cbindgen
 fi
 
-# Install RISC-V target
-echo "Ensuring RISC-V target is installed..."
-rustup target add riscv64gc-unknown-linux-gnu 2>/dev/null || true
+# Build profile: ZKLIB_PROFILE=strict compiles out legacy/insecure code paths
+ZKLIB_PROFILE="${ZKLIB_PROFILE:-standard}"
+# Role: ZKLIB_ROLE=prover or verifier links only that side of the API,
+# embedded builds the no_std verify-only library for microcontrollers
+ZKLIB_ROLE="${ZKLIB_ROLE:-both}"
+case "$ZKLIB_ROLE" in
+    prover|verifier) FEATURES="$ZKLIB_ROLE" ;;
+    both) FEATURES="prover verifier" ;;
+    embedded) FEATURES="embedded" ;;
+    *) echo "Error: unknown ZKLIB_ROLE '$ZKLIB_ROLE'"; exit 1 ;;
+esac
+if [ "$ZKLIB_PROFILE" = "strict" ]; then
+    FEATURES="$FEATURES strict"
+fi
+# ZKLIB_THRESHOLD=1 adds t-of-n threshold issuance (prover roles only)
+if [ "${ZKLIB_THRESHOLD:-0}" = "1" ]; then
+    case "$ZKLIB_ROLE" in
+        prover|both) FEATURES="$FEATURES threshold" ;;
+        *) echo "Error: ZKLIB_THRESHOLD needs a prover role"; exit 1 ;;
+    esac
+fi
+# Target: the enclave's RISC-V, or a Cortex-M for the embedded role, whose
+# no_std build cannot unwind and so aborts on panic
+PANIC_CONFIG=()
+if [ "$ZKLIB_ROLE" = "embedded" ]; then
+    ZKLIB_TARGET="${ZKLIB_TARGET:-thumbv7em-none-eabihf}"
+    PANIC_CONFIG=(--config 'profile.release.panic="abort"')
+else
+    ZKLIB_TARGET="${ZKLIB_TARGET:-riscv64gc-unknown-linux-gnu}"
+fi
+echo "Build profile: $ZKLIB_PROFILE, role: $ZKLIB_ROLE, target: $ZKLIB_TARGET"
+echo ""
+
+echo "Ensuring target $ZKLIB_TARGET is installed..."
+rustup target add "$ZKLIB_TARGET" 2>/dev/null || true
 
 # Generate C header file
 echo "Generating C header file..."
@@ -47,6 +79,23 @@ if [ ! -f "zklib.h" ]; then
 extern "C" {
 #endif
 
+/* Error codes (0 is success, -1 a generic failure) */
+#define ZK_STATUS_SIZE_QUERY 1  /* not a failure: only the size was reported */
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_BAD_SIGNATURE -3
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_BAD_HEX -6
+#define ZK_ERR_PROVE_FAILED -7
+#define ZK_ERR_NO_PROVING_KEY -8
+#define ZK_ERR_ENTROPY -34
+#define ZK_ERR_REVOKED -35
+#define ZK_ERR_PANIC -36
+#define ZK_ERR_NOT_YET_VALID -37
+#define ZK_ERR_BAD_DID -38
+#define ZK_ERR_KEYFILE_INVALID -39
+#define ZK_ERR_SEQUENCE_UNSUPPORTED -40
+
 /**
  * Initialize the ZK system and generate proving/verifying keys.
  * Must be called before any other ZK operations.
@@ -57,12 +106,13 @@ int ZK_Init(void);
 
 /**
  * Generate Ed25519 keypair for Issuer (random).
+ * The secret key is drawn from the OS, or from ZK_SetEntropySource's callback.
  * 
  * @param public_key_out Output buffer for hex-encoded public key (65 bytes)
  * @param public_key_size Size of public_key_out buffer
  * @param private_key_out Output buffer for hex-encoded private key (65 bytes)
  * @param private_key_size Size of private_key_out buffer
- * @return 0 on success, -1 on failure
+ * @return 0 on success, ZK_ERR_ENTROPY if the entropy source fails, -1 on failure
  */
 int ZK_GenerateIssuerKeypair(
     char* public_key_out,
@@ -71,6 +121,25 @@ int ZK_GenerateIssuerKeypair(
     size_t private_key_size
 );
 
+/**
+ * Install (or remove with NULL) an entropy callback for builds without an
+ * OS random generator. The callback fills len bytes and returns 0 on success.
+ * 
+ * @return 0
+ */
+int ZK_SetEntropySource(
+    int (*callback)(uint8_t* buf, size_t len, void* user_data),
+    void* user_data
+);
+
+/**
+ * Derive proving randomness from seed and the nonce for reproducible test
+ * runs; 0 returns to fresh entropy (the default). Not in strict builds.
+ * 
+ * @return 0
+ */
+int ZK_SetDeterministicProving(uint64_t seed);
+
 /**
  * Generate DETERMINISTIC Ed25519 keypair for Issuer (using seed).
  * This allows both Prover and Verifier to generate the same keypair for testing.
@@ -100,9 +169,13 @@ int ZK_GenerateIssuerKeypairDeterministic(
  * @param issue_date Issue timestamp
  * @param expiry_date Expiry timestamp
  * @param issuer_private_key Hex-encoded issuer private key (64 chars)
- * @param signature_out Output buffer for hex-encoded signature (129 bytes)
+ * @param signature_out Output buffer for hex-encoded signature (129 bytes),
+ *                      or NULL to query the size
  * @param signature_out_size Size of signature_out buffer
- * @return 0 on success, -1 on failure
+ * @param required_size_out Receives the required buffer size; may be NULL
+ * @return 0 on success, ZK_STATUS_SIZE_QUERY, ZK_ERR_BAD_HEX,
+ *         ZK_ERR_BUFFER_TOO_SMALL, ZK_ERR_SEQUENCE_UNSUPPORTED (issuer
+ *         sequencing is enabled; sign with ZK_SignVCBlob), or -1
  */
 int ZK_SignVC(
     const char* holder_id,
@@ -113,7 +186,8 @@ int ZK_SignVC(
     uint64_t expiry_date,
     const char* issuer_private_key,
     char* signature_out,
-    size_t signature_out_size
+    size_t signature_out_size,
+    size_t* required_size_out
 );
 
 /**
@@ -143,15 +217,22 @@ int ZK_VerifyVCSignature(
 /**
  * Compute the VC message hash for testing/verification.
  * 
+ * Matches VerifiableCredential::message_hash for the same fields and claims.
+ * 
  * @param holder_id Holder identifier
  * @param holder_id_len Length of holder_id
  * @param issuer Issuer identifier
  * @param issuer_len Length of issuer
  * @param issue_date Issue timestamp
  * @param expiry_date Expiry timestamp
- * @param vc_hash_out Output buffer for hex-encoded hash
+ * @param claim_keys Claim keys (NUL-terminated), in signing order; NULL if none
+ * @param claim_values Claim values, parallel to claim_keys; NULL if none
+ * @param claim_count Number of claims
+ * @param vc_hash_out Output buffer for hex-encoded hash, or NULL to query
+ *                    the size
  * @param vc_hash_out_size Size of output buffer (must be >= 65 bytes)
- * @return 0 on success, -1 on failure
+ * @param required_size_out Receives the required buffer size; may be NULL
+ * @return 0 on success, ZK_STATUS_SIZE_QUERY, ZK_ERR_BUFFER_TOO_SMALL, or -1
  */
 int ZK_ComputeVCHash(
     const char* holder_id,
@@ -160,8 +241,12 @@ int ZK_ComputeVCHash(
     size_t issuer_len,
     uint64_t issue_date,
     uint64_t expiry_date,
+    const char* const* claim_keys,
+    const char* const* claim_values,
+    size_t claim_count,
     char* vc_hash_out,
-    size_t vc_hash_out_size
+    size_t vc_hash_out_size,
+    size_t* required_size_out
 );
 
 /**
@@ -177,9 +262,14 @@ int ZK_ComputeVCHash(
  * @param issuer_pubkey Hex-encoded issuer public key (64 chars)
  * @param current_time Current timestamp
  * @param nonce Challenge nonce from verifier
- * @param proof_out Output buffer for hex-encoded proof
- * @param proof_out_size Size of proof_out buffer (must be >= 512 bytes)
- * @return 0 on success, -1 on failure
+ * @param proof_out Output buffer for hex-encoded proof, or NULL to query
+ *                  the size (inputs are checked, nothing is proven)
+ * @param proof_out_size Size of proof_out buffer
+ * @param required_size_out Receives the required buffer size; may be NULL
+ * @return 0 on success, ZK_STATUS_SIZE_QUERY, or ZK_ERR_BAD_HEX, ZK_ERR_BAD_SIGNATURE,
+ *         ZK_ERR_VC_EXPIRED, ZK_ERR_NOT_INITIALIZED, ZK_ERR_NO_PROVING_KEY,
+ *         ZK_ERR_PROVE_FAILED, ZK_ERR_ENTROPY,
+ *         ZK_ERR_BUFFER_TOO_SMALL, or -1 (NULL or invalid dates)
  */
 int ZK_GenerateVCProof(
     const char* holder_id,
@@ -193,7 +283,8 @@ int ZK_GenerateVCProof(
     uint64_t current_time,
     uint64_t nonce,
     char* proof_out,
-    size_t proof_out_size
+    size_t proof_out_size,
+    size_t* required_size_out
 );
 
 /**
@@ -201,9 +292,11 @@ int ZK_GenerateVCProof(
  * 
  * @param proof_hex Hex-encoded proof string
  * @param issuer_pubkey Hex-encoded issuer public key
- * @param current_time Current timestamp
+ * @param current_time Current timestamp, 0 for the library clock; must lie
+ *        within the credential's issue and expiry dates carried by the proof
  * @param nonce Challenge nonce that was sent to prover
- * @return 1 if proof is valid, 0 if invalid or error
+ * @return 1 if proof is valid, 0 if invalid or error,
+ *         ZK_ERR_VC_EXPIRED if the credential is not valid at current_time
  */
 int ZK_VerifyVCProof(
     const char* proof_hex,
@@ -212,6 +305,49 @@ int ZK_VerifyVCProof(
     uint64_t nonce
 );
 
+/**
+ * ZK_GenerateVCProof on binary buffers, without hex encoding.
+ * 
+ * @param vc_signature 64-byte Ed25519 signature
+ * @param issuer_pubkey 32-byte issuer public key
+ * @param proof_out Output buffer for the binary proof; NULL or size 0 queries the size
+ * @param proof_len_out Receives the proof length (or the required size)
+ * @return As ZK_GenerateVCProof, without ZK_ERR_BAD_HEX
+ */
+int ZK_GenerateVCProofRaw(
+    const uint8_t* holder_id,
+    size_t holder_id_len,
+    const uint8_t* issuer,
+    size_t issuer_len,
+    int64_t issue_date,
+    int64_t expiry_date,
+    const uint8_t* vc_signature,
+    size_t vc_signature_len,
+    const uint8_t* issuer_pubkey,
+    size_t issuer_pubkey_len,
+    uint64_t current_time,
+    uint64_t nonce,
+    uint8_t* proof_out,
+    size_t proof_out_size,
+    size_t* proof_len_out
+);
+
+/**
+ * ZK_VerifyVCProof on binary buffers. A binary proof hex-encoded verifies
+ * through ZK_VerifyVCProof and a hex proof decoded verifies here.
+ * 
+ * @return 1 if proof is valid, 0 if invalid or error,
+ *         ZK_ERR_VC_EXPIRED if the credential is not valid at current_time
+ */
+int ZK_VerifyVCProofRaw(
+    const uint8_t* proof,
+    size_t proof_len,
+    const uint8_t* issuer_pubkey,
+    size_t issuer_pubkey_len,
+    uint64_t current_time,
+    uint64_t nonce
+);
+
 /**
  * Cleanup ZK resources.
  * Should be called when done with ZK operations.
@@ -229,17 +365,19 @@ fi
 echo "✓ Header generated: zklib.h"
 echo ""
 
-# Build for RISC-V with single-threaded rayon
-echo "Building static library for RISC-V64 (single-threaded mode)..."
-RAYON_NUM_THREADS=1 cargo build --release --target riscv64gc-unknown-linux-gnu
+# Build with single-threaded rayon (ignored by the embedded role)
+echo "Building static library for $ZKLIB_TARGET (single-threaded mode)..."
+# Cargo.toml declares only an rlib; the staticlib is requested here
+RAYON_NUM_THREADS=1 cargo rustc --release --lib --crate-type staticlib --target "$ZKLIB_TARGET" \
+    --no-default-features --features "$FEATURES" "${PANIC_CONFIG[@]}"
 
-if [ ! -f "target/riscv64gc-unknown-linux-gnu/release/libzklib_vc.a" ]; then
+if [ ! -f "target/$ZKLIB_TARGET/release/libzklib_vc.a" ]; then
     echo "Error: Build failed"
     exit 1
 fi
 
 # Copy to expected location
-cp target/riscv64gc-unknown-linux-gnu/release/libzklib_vc.a libzklib.a
+cp "target/$ZKLIB_TARGET/release/libzklib_vc.a" libzklib.a
 
 SIZE=$(du -h libzklib.a | cut -f1)
 echo ""
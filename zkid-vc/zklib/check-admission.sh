@@ -0,0 +1,160 @@
+#!/bin/bash
+#
+# Build the library for the host and check staged proof admission: a real
+# proof damaged for each stage (oversized, unknown envelope format, non-hex,
+# non-canonical coordinate, point off the curve) is refused by
+# ZK_PrecheckProof with that stage's code and counted against that stage
+# only, the 1/0 verify entry point reads those refusals as 0 except for the
+# version code, and a well-formed proof that does not verify is counted at
+# the pairing. Then run examples/admission_budget.rs, which times refusing
+# maximum-size inputs against ADMISSION_BUDGET_US.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_PrecheckProof(const char*);
+int ZK_GetAdmissionStats(int, uint64_t*);
+
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_INPUT_TOO_LARGE -30
+#define ZK_ERR_NON_CANONICAL -31
+#define ZK_ERR_INVALID_POINT -32
+#define STAGES 6
+#define PAIRING 5
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+/* A | B | C of a compressed proof, in hex, ends the proof */
+#define BODY_HEX 256
+#define ENVELOPE_HEX 6
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096], damaged[4096];
+static uint64_t before[STAGES];
+
+static void snapshot(void) {
+    for (int stage = 0; stage < STAGES; stage++) {
+        ZK_GetAdmissionStats(stage, &before[stage]);
+    }
+}
+
+/* Which stages counted a refusal since the last snapshot, one bit each */
+static int counted(void) {
+    int bits = 0;
+    for (int stage = 0; stage < STAGES; stage++) {
+        uint64_t now = 0;
+        ZK_GetAdmissionStats(stage, &now);
+        bits |= (now != before[stage]) << stage;
+    }
+    return bits;
+}
+
+/* Precheck and verify `input`; both must give the expected codes and only
+   `stage` may count the refusals */
+static int refused(const char* name, const char* input, int stage, int precheck, int verify) {
+    snapshot();
+    int pre = ZK_PrecheckProof(input);
+    int ver = ZK_VerifyVCProof(input, pub, NOW, 7);
+    int bits = counted();
+    printf("  %s: precheck %d, verify %d, stages counted 0x%02x\n", name, pre, ver, bits);
+    return pre == precheck && ver == verify && bits == 1 << stage;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+    size_t len = strlen(proof), body = len - BODY_HEX, envelope = body - ENVELOPE_HEX;
+    snapshot();
+    int well_formed = ZK_PrecheckProof(proof), valid = ZK_VerifyVCProof(proof, pub, NOW, 7);
+    printf("  proof: precheck %d, verify %d, envelope %.6s, stages counted 0x%02x\n", well_formed, valid,
+           proof + envelope, counted());
+    if (well_formed != 1 || valid != 1 || strncmp(proof + envelope, "5a50", 4) != 0 || counted() != 0) {
+        return 1;
+    }
+
+    /* One damaged proof per stage */
+    size_t huge = 1 << 20;
+    char* oversized = malloc(huge + 1);
+    if (oversized == NULL) {
+        return 1;
+    }
+    memset(oversized, '0', huge);
+    oversized[huge] = '\0';
+    int ok = refused("1 MiB of hex", oversized, 0, ZK_ERR_INPUT_TOO_LARGE, 0);
+    free(oversized);
+
+    strcpy(damaged, proof);
+    memcpy(damaged + envelope + 4, "3f", 2);
+    ok &= refused("unknown envelope format", damaged, 1, ZK_ERR_UNSUPPORTED_VERSION, ZK_ERR_UNSUPPORTED_VERSION);
+
+    strcpy(damaged, proof);
+    damaged[len - 1] = 'g';
+    ok &= refused("non-hex character", damaged, 2, ZK_ERR_CORRUPT, 0);
+    damaged[len - 1] = '\0';
+    ok &= refused("odd length", damaged, 2, ZK_ERR_CORRUPT, 0);
+
+    strcpy(damaged, proof);
+    memcpy(damaged + len - 2, "ff", 2);
+    ok &= refused("both flag bits", damaged, 3, ZK_ERR_NON_CANONICAL, 0);
+    strcpy(damaged, proof);
+    memset(damaged + len - 64, 'f', 62);
+    memcpy(damaged + len - 2, "3f", 2);
+    ok &= refused("coordinate above p", damaged, 3, ZK_ERR_NON_CANONICAL, 0);
+
+    strcpy(damaged, proof);
+    for (size_t i = len - 64; i < len; i += 2) {
+        memcpy(damaged + i, "03", 2);
+    }
+    ok &= refused("point off the curve", damaged, 4, ZK_ERR_INVALID_POINT, 0);
+
+    /* Well-formed, but for another nonce */
+    snapshot();
+    int other_nonce = ZK_VerifyVCProof(proof, pub, NOW, 8);
+    int pairing = counted();
+    printf("  other nonce: verify %d, stages counted 0x%02x\n", other_nonce, pairing);
+    ok &= other_nonce == 0 && pairing == 1 << PAIRING;
+
+    uint64_t unused;
+    int bad_stage = ZK_GetAdmissionStats(STAGES, &unused), negative = ZK_GetAdmissionStats(-1, &unused);
+    int null_out = ZK_GetAdmissionStats(0, NULL), null_proof = ZK_PrecheckProof(NULL);
+    printf("  stage %d %d, stage -1 %d, NULL count %d, NULL proof %d\n", STAGES, bad_stage, negative, null_out,
+           null_proof);
+    return !ok || bad_stage != -1 || negative != -1 || null_out != -1 || null_proof != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "Checking budget: examples/admission_budget.rs"
+cargo run --release --example admission_budget --target-dir "$WORK_DIR/target" -q || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Each admission stage refuses its inputs before the next is paid for"
@@ -0,0 +1,134 @@
+#!/bin/bash
+#
+# Build the library for the host and check derived-age proofs: a birthdate
+# claim, before 1970 too, proves "at least N years old" at the time the
+# proof is made, and the proof verifies only for that claim key, N, time,
+# issuer and nonce. With fault-injection, a prover that skips its
+# pre-checks proves an age from a birthdate edited in the blob, and the
+# verifier rejects it: the proof opens the birthdate from the anchor the
+# issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateDerivedAgeProof(const char*, const char*, const char*, const char*, uint32_t, uint64_t, uint64_t,
+                               char*, size_t);
+int ZK_VerifyDerivedAgeProof(const char*, const char*, const char*, uint32_t, uint64_t, uint64_t);
+void ZK_SkipProverChecks(int);
+
+#define NOW 1700000000ULL
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char blob[8192], old_blob[8192], proof[2048];
+
+/* A credential born `birthdate` (2010-01-01 or 1969-12-31 below) with a
+ * second date claim */
+static int issue(const char* birthdate, char* out) {
+    static char unsigned_blob[8192];
+    const char* keys[] = {"role", "birthdate", "hired"};
+    const char* values[] = {"engineer", birthdate, "1600000000"};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, keys, values, 3, NULL,
+                       unsigned_blob, sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, out, 8192) == 0;
+}
+
+static int prove(const char* vc, uint32_t years) {
+    return ZK_GenerateDerivedAgeProof(vc, pub, NULL, "birthdate", years, NOW, 7, proof, sizeof(proof));
+}
+
+/* Prove at least `years` and check the proof holds for nothing else */
+static int check_age(const char* vc, uint32_t years) {
+    int rc = prove(vc, years);
+    if (rc != 0) {
+        printf("  at least %u: prove %d\n", years, rc);
+        return 0;
+    }
+    int valid = ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", years, NOW, 7);
+    int older = ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", years + 1, NOW, 7);
+    int other_key = ZK_VerifyDerivedAgeProof(proof, pub, "hired", years, NOW, 7);
+    int later = ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", years, NOW + 1, 7);
+    int issuer = ZK_VerifyDerivedAgeProof(proof, pub2, "birthdate", years, NOW, 7);
+    int nonce = ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", years, NOW, 8);
+    printf("  at least %u: valid %d, older %d, other key %d, other time %d, other issuer %d, other nonce %d\n",
+           years, valid, older, other_key, later, issuer, nonce);
+    return valid == 1 && older == 0 && other_key == 0 && later == 0 && issuer == 0 && nonce == 0;
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0 ||
+        !issue("1262304000", blob) || !issue("-86400", old_blob)) {
+        return 1;
+    }
+
+    /* 13 years old at NOW, and 53 for a birthdate before 1970 */
+    if (!check_age(blob, 13) || !check_age(old_blob, 18) || !check_age(old_blob, 53)) {
+        return 1;
+    }
+    int younger = prove(blob, 14);
+    int absent = ZK_GenerateDerivedAgeProof(blob, pub, NULL, "born", 1, NOW, 7, proof, sizeof(proof));
+    int text = ZK_GenerateDerivedAgeProof(blob, pub, NULL, "role", 1, NOW, 7, proof, sizeof(proof));
+    int too_old = prove(old_blob, 54);
+    printf("  at least 14: %d, absent claim %d, text claim %d, 54 before 1970: %d\n", younger, absent, text,
+           too_old);
+    if (younger != -1 || absent != -1 || text != -1 || too_old != -1) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The birthdate edited in place from 2010-01-01 to 2001-09-09, the
+     * issuer's signature kept: the checked prover refuses it, one that
+     * skips its checks proves 18 over a birthdate the issuer never signed */
+    static char forged[8192];
+    strcpy(forged, blob);
+    char* birthdate = strstr(forged, "6269727468646174650a00000031323632333034303030");
+    if (birthdate == NULL) {
+        return 1;
+    }
+    memcpy(birthdate + 26, "31303030303030303030", 20);
+    int checked = prove(forged, 18);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, 18);
+    int forged_valid = forgery == 0 ? ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", 18, NOW, 7) : -1;
+    int honest = prove(blob, 13);
+    int honest_valid = honest == 0 ? ZK_VerifyDerivedAgeProof(proof, pub, "birthdate", 13, NOW, 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited birthdate: checked prover %d; unchecked prover %d (verifies %d), signed birthdate %d "
+           "(verifies %d)\n", checked, forgery, forged_valid, honest, honest_valid);
+    if (checked == 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Birthdates prove ages under the issuer-signed anchor and nothing more"
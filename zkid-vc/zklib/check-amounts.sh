@@ -0,0 +1,127 @@
+#!/bin/bash
+#
+# Build the library for the host and check integer claim comparisons beyond
+# 64 bits: a wallet matching require.amount predicates against stored
+# credentials compares amounts exactly on both sides of 2^64 and up to
+# 2^128 - 1, reads leading zeros and -0 canonically, never matches a claim
+# value that overflows 128 bits, and refuses a request whose threshold
+# overflows.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_MatchCredentials(const char*, size_t, CredentialStore*, char*, size_t);
+
+#define ZK_ERR_CORRUPT -14
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define TWO_64 "18446744073709551616"
+#define TWO_128 "340282366920938463463374607431768211456"
+
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static const char* amounts[] = {TWO_64, "18446744073709551615", "340282366920938463463374607431768211455", "-5",
+                                "0007", TWO_128};
+#define AMOUNTS (sizeof(amounts) / sizeof(amounts[0]))
+
+static char pub[65], priv[65], out[16384];
+static char ids[AMOUNTS][128];
+
+/* Match `condition` on the amount claim; a bit per credential it matched,
+   or the negative return code */
+static int match(CredentialStore* store, const char* condition) {
+    char request[4096];
+    const char* keys[] = {"require.amount"};
+    const char* values[] = {condition};
+    int rc = ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, keys, values, 1, request, sizeof(request));
+    if (rc == 0) {
+        rc = ZK_MatchCredentials(request, strlen(request), store, out, sizeof(out));
+    }
+    if (rc != 0) {
+        return rc;
+    }
+    int matched = 0;
+    for (size_t i = 0; i < AMOUNTS; i++) {
+        matched |= (strstr(out, ids[i]) != NULL) << i;
+    }
+    return matched;
+}
+
+int main(int argc, char** argv) {
+    char path[512], encoded[8192], blob[8192];
+    CredentialStore* store = NULL;
+    snprintf(path, sizeof(path), "%s/wallet.store", argc > 1 ? argv[1] : ".");
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_StoreOpen(path, raw_key, sizeof(raw_key), ZK_STORE_KEY_RAW, &store) != 0) {
+        return 1;
+    }
+    for (size_t i = 0; i < AMOUNTS; i++) {
+        char holder[16];
+        const char* keys[] = {"amount"};
+        const char* values[] = {amounts[i]};
+        snprintf(holder, sizeof(holder), "holder-%zu", i);
+        if (ZK_EncodeVC(holder, strlen(holder), "bank", 4, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                        sizeof(encoded)) != 0 ||
+            ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+            ZK_StoreAddVC(store, blob, ids[i], sizeof(ids[i])) != 0) {
+            return 1;
+        }
+    }
+
+    /* Bits follow `amounts`: 2^64, 2^64 - 1, 2^128 - 1, -5, 0007, 2^128 */
+    struct {
+        const char* condition;
+        int expected;
+    } cases[] = {
+        {">=" TWO_64, 0x05},
+        {"<" TWO_64, 0x1a},
+        {">18446744073709551615", 0x05},
+        {"<=18446744073709551615", 0x1a},
+        {">=340282366920938463463374607431768211455", 0x04},
+        {"<=0000007", 0x18},
+        {">6", 0x17},
+        {"<-0", 0x08},
+        {">=-0", 0x17},
+        {">=" TWO_128, ZK_ERR_CORRUPT},
+        {">=12abc", ZK_ERR_CORRUPT},
+    };
+    int ok = 1;
+    for (size_t i = 0; i < sizeof(cases) / sizeof(cases[0]); i++) {
+        int got = match(store, cases[i].condition);
+        printf("  amount %s: %d (expected %d)\n", cases[i].condition, got, cases[i].expected);
+        ok &= got == cases[i].expected;
+    }
+
+    ZK_StoreClose(store);
+    return !ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Integer claims compare exactly up to 2^128 - 1"
@@ -0,0 +1,177 @@
+#!/bin/bash
+#
+# Build the library with the "log" feature for the host and check the
+# verification audit events: each verify call emits exactly one event to
+# the callback, naming the check, the decision and the code; proof checks
+# carry the circuit, a verifying-key fingerprint and a salted nonce hash,
+# signature checks leave them null; no event contains the nonce, the
+# holder, a claim value or the issuer key; the nonce hash follows
+# ZK_SetLogSalt; and removing the callback stops the events.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef void (*AuditCallback)(const char*, void*);
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetAuditCallback(AuditCallback, void*);
+int ZK_SetLogSalt(const uint8_t*, size_t);
+
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define NONCE 4242424242ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096];
+static char events[8][2048];
+static int event_count;
+
+static void on_event(const char* event, void* user_data) {
+    (void)user_data;
+    if (event_count < 8) {
+        snprintf(events[event_count], sizeof(events[0]), "%s", event);
+    }
+    event_count++;
+}
+
+/* The value of `"name":` in `event`, up to the next comma or brace */
+static void field(const char* event, const char* name, char* out, size_t size) {
+    char key[64];
+    snprintf(key, sizeof(key), "\"%s\":", name);
+    const char* at = strstr(event, key);
+    size_t n = 0;
+    if (at != NULL) {
+        at += strlen(key);
+        while (at[n] != '\0' && at[n] != ',' && at[n] != '}' && n + 1 < size) {
+            n++;
+        }
+        memcpy(out, at, n);
+    }
+    out[n] = '\0';
+}
+
+/* Run one verification; 1 if it emitted exactly one event */
+static int one_event(int result, int* out) {
+    *out = result;
+    return event_count == 1;
+}
+
+/* No event may carry the nonce, the holder, a claim value or the issuer key */
+static int private(const char* event) {
+    return strstr(event, "4242424242") == NULL && strstr(event, "alice") == NULL &&
+           strstr(event, "engineer") == NULL && strstr(event, pub) == NULL;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    char check[64], decision[64], code[16], circuit[64], vk[64], nonce_hash[64], other_hash[64];
+    int result = 0;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, NONCE, proof, sizeof(proof)) != 0 ||
+        ZK_SetAuditCallback(on_event, NULL) != 0) {
+        return 1;
+    }
+
+    /* An accepted proof */
+    event_count = 0;
+    if (!one_event(ZK_VerifyVCProof(proof, pub, NOW, NONCE), &result) || result != 1) {
+        return 1;
+    }
+    field(events[0], "check", check, sizeof(check));
+    field(events[0], "decision", decision, sizeof(decision));
+    field(events[0], "code", code, sizeof(code));
+    field(events[0], "circuit", circuit, sizeof(circuit));
+    field(events[0], "vk", vk, sizeof(vk));
+    field(events[0], "nonce_hash", nonce_hash, sizeof(nonce_hash));
+    printf("  accepted proof: check %s, decision %s, code %s, circuit %s, vk %s, nonce hash %s\n", check, decision,
+           code, circuit, vk, nonce_hash);
+    if (strcmp(check, "\"vc-proof\"") != 0 || strcmp(decision, "\"accept\"") != 0 || strcmp(code, "1") != 0 ||
+        strcmp(circuit, "null") == 0 || strlen(vk) != 10 || strlen(nonce_hash) != 18 || !private(events[0])) {
+        return 1;
+    }
+
+    /* Rejected under another nonce, then a signature check */
+    event_count = 0;
+    if (!one_event(ZK_VerifyVCProof(proof, pub, NOW, NONCE + 1), &result) || result != 0) {
+        return 1;
+    }
+    field(events[0], "decision", decision, sizeof(decision));
+    field(events[0], "nonce_hash", other_hash, sizeof(other_hash));
+    printf("  other nonce: decision %s, nonce hash differs %d\n", decision, strcmp(nonce_hash, other_hash) != 0);
+    if (strcmp(decision, "\"reject\"") != 0 || strcmp(nonce_hash, other_hash) == 0 || !private(events[0])) {
+        return 1;
+    }
+    event_count = 0;
+    if (!one_event(ZK_VerifyVCBlob(blob, pub, NULL), &result) || result != 1) {
+        return 1;
+    }
+    field(events[0], "check", check, sizeof(check));
+    field(events[0], "circuit", circuit, sizeof(circuit));
+    field(events[0], "nonce_hash", other_hash, sizeof(other_hash));
+    printf("  signature: check %s, circuit %s, nonce hash %s\n", check, circuit, other_hash);
+    if (strcmp(check, "\"vc-signature\"") != 0 || strcmp(circuit, "null") != 0 || strcmp(other_hash, "null") != 0 ||
+        !private(events[0])) {
+        return 1;
+    }
+
+    /* The nonce hash is keyed by the deployment salt */
+    const uint8_t salt[16] = "deployment-salt";
+    const uint8_t other_salt[16] = "deployment-pepp";
+    char salted[2][64], resalted[64];
+    int short_salt = ZK_SetLogSalt(salt, 8);
+    for (int i = 0; i < 2; i++) {
+        event_count = 0;
+        if (ZK_SetLogSalt(i == 0 ? salt : other_salt, sizeof(salt)) != 0 ||
+            !one_event(ZK_VerifyVCProof(proof, pub, NOW, NONCE), &result)) {
+            return 1;
+        }
+        field(events[0], "nonce_hash", salted[i], sizeof(salted[i]));
+    }
+    event_count = 0;
+    if (ZK_SetLogSalt(salt, sizeof(salt)) != 0 || !one_event(ZK_VerifyVCProof(proof, pub, NOW, NONCE), &result)) {
+        return 1;
+    }
+    field(events[0], "nonce_hash", resalted, sizeof(resalted));
+    printf("  salt of 8 bytes %d; same salt same hash %d, other salt other hash %d\n", short_salt,
+           strcmp(salted[0], resalted) == 0, strcmp(salted[0], salted[1]) != 0);
+    if (short_salt != -1 || strcmp(salted[0], resalted) != 0 || strcmp(salted[0], salted[1]) == 0) {
+        return 1;
+    }
+
+    /* Without the callback nothing arrives */
+    ZK_SetAuditCallback(NULL, NULL);
+    event_count = 0;
+    ZK_VerifyVCProof(proof, pub, NOW, NONCE);
+    printf("  events after removing the callback: %d\n", event_count);
+    return event_count != 0;
+}
+EOF
+
+echo "Checking build: log"
+cargo rustc --release --lib --crate-type staticlib --features log --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Every verification emits one audit event without raw identifiers"
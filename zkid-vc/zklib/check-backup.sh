@@ -0,0 +1,206 @@
+#!/bin/bash
+#
+# Build the library for the host and check wallet backups: a backup of a
+# store with credentials and a consent receipt, holder keys and a pending
+# request restores onto a new device under the same recovery phrase in any
+# spacing or case. Restoring again keeps what the store has, a credential
+# the store has superseded is skipped, and a wrong phrase and a tampered
+# blob fail alike while a newer version and a non-backup are told apart.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#define _GNU_SOURCE
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_ReissueVC(const char*, const char* const*, const char* const*, size_t, uint64_t, uint64_t, const char*, char*,
+                 size_t, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+int ZK_StoreApplySupersession(CredentialStore*, const char*, char*, size_t);
+int ZK_StoreListVCs(CredentialStore*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+int ZK_CreateConsentReceipt(CredentialStore*, const char*, size_t, const char*, size_t, const char*, const char*,
+                            char*, size_t);
+int ZK_BackupCreate(CredentialStore*, const char* const*, size_t, const char* const*, size_t, const char*, uint8_t*,
+                    size_t, size_t*);
+int ZK_BackupRestore(const uint8_t*, size_t, const char*, CredentialStore*, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+/* "ZKBK" | version */
+#define VERSION_OFFSET 4
+
+static const char* phrase = "correct horse battery staple";
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static char pub[65], priv[65], holder_pub[65], holder_priv[65], encoded[8192], alice[8192], bob[8192];
+static char reissued[8192], entry[512], proof[4096], presentation[16384], request[4096], receipt[4096];
+static char alice_id[128], bob_id[128], new_id[128], report[32768], list[4096];
+static uint8_t backup[65536], damaged[65536];
+
+static CredentialStore* open_store(const char* dir, const char* name) {
+    char path[512];
+    CredentialStore* store = NULL;
+    snprintf(path, sizeof(path), "%s/%s", dir, name);
+    return ZK_StoreOpen(path, raw_key, sizeof(raw_key), ZK_STORE_KEY_RAW, &store) == 0 ? store : NULL;
+}
+
+static int issue(const char* holder, char* out) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+static int restore(const uint8_t* data, size_t len, const char* p, CredentialStore* store) {
+    report[0] = '\0';
+    return ZK_BackupRestore(data, len, p, store, report, sizeof(report));
+}
+
+/* Number of entries in the report's `field` array */
+static int count(const char* field) {
+    char key[64];
+    snprintf(key, sizeof(key), "\"%s\":[", field);
+    const char* at = strstr(report, key);
+    if (at == NULL) {
+        return -1;
+    }
+    at += strlen(key);
+    int entries = *at != ']';
+    for (int depth = 0; *at != '\0' && (depth > 0 || *at != ']'); at++) {
+        depth += (*at == '{') - (*at == '}');
+        entries += depth == 0 && *at == ',';
+    }
+    return entries;
+}
+
+int main(int argc, char** argv) {
+    const char* dir = argc > 1 ? argv[1] : ".";
+    const char* request_keys[] = {"claims"};
+    const char* request_values[] = {"role"};
+    const char* role[] = {"role"};
+    const char* manager[] = {"manager"};
+    CredentialStore* wallet = open_store(dir, "wallet.store");
+    if (ZK_Init() != 0 || wallet == NULL || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(holder_pub, sizeof(holder_pub), holder_priv, sizeof(holder_priv)) != 0 ||
+        !issue("alice", alice) || !issue("bob", bob) ||
+        ZK_ReissueVC(alice, role, manager, 1, NOW, 0, priv, reissued, sizeof(reissued), entry, sizeof(entry)) != 0 ||
+        ZK_StoreAddVC(wallet, alice, alice_id, sizeof(alice_id)) != 0 ||
+        ZK_StoreAddVC(wallet, bob, bob_id, sizeof(bob_id)) != 0 ||
+        ZK_GenerateVCProofFromBlob(alice, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, NULL, NULL, 0, presentation, sizeof(presentation)) != 0 ||
+        ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, request_keys, request_values, 1, request,
+                                     sizeof(request)) != 0 ||
+        ZK_CreateConsentReceipt(wallet, presentation, strlen(presentation), request, strlen(request), NULL,
+                                holder_priv, receipt, sizeof(receipt)) != 0) {
+        return 1;
+    }
+
+    /* The size is reported to a short buffer, then the backup is written */
+    const char* keys[] = {holder_priv};
+    const char* pending[] = {"deadbeef"};
+    size_t len = 0, needed = 0;
+    int sized = ZK_BackupCreate(wallet, keys, 1, pending, 1, phrase, backup, 16, &needed);
+    int created = ZK_BackupCreate(wallet, keys, 1, pending, 1, phrase, backup, sizeof(backup), &len);
+    int no_plaintext = memmem(backup, len, "alice", 5) == NULL && memmem(backup, len, holder_priv, 64) == NULL;
+    printf("  backup: short buffer %d (needs %zu), created %d, %zu bytes, no plaintext %d\n", sized, needed, created,
+           len, no_plaintext);
+    if (sized != ZK_ERR_BUFFER_TOO_SMALL || created != 0 || needed != len || !no_plaintext) {
+        return 1;
+    }
+
+    /* A new device, the phrase typed differently */
+    CredentialStore* device = open_store(dir, "device.store");
+    int restored = restore(backup, len, "  Correct HORSE\tbattery   staple ", device);
+    int contents = count("restored") == 2 && count("kept") == 0 && count("skipped") == 0 &&
+                   strstr(report, "\"receipts_added\":1") != NULL && strstr(report, holder_priv) != NULL &&
+                   strstr(report, "\"pending_requests\":[\"deadbeef\"]") != NULL &&
+                   strstr(report, "\"version\":1") != NULL;
+    int listed = ZK_StoreListVCs(device, list, sizeof(list)) == 0 && strstr(list, alice_id) != NULL &&
+                 strstr(list, bob_id) != NULL;
+    int again = restore(backup, len, phrase, device);
+    int merged = count("restored") == 0 && count("kept") == 2 &&
+                 strstr(report, "\"receipts_added\":0") != NULL;
+    printf("  restored %d (contents %d, listed %d), again %d (all kept %d)\n", restored, contents, listed, again,
+           merged);
+    ZK_StoreClose(device);
+    if (restored != 0 || !contents || !listed || again != 0 || !merged) {
+        printf("  %s\n", report);
+        return 1;
+    }
+
+    /* A device that already re-issued alice's credential */
+    CredentialStore* updated = open_store(dir, "updated.store");
+    if (updated == NULL || ZK_StoreAddVC(updated, alice, alice_id, sizeof(alice_id)) != 0 ||
+        ZK_StoreApplySupersession(updated, reissued, new_id, sizeof(new_id)) != 0) {
+        return 1;
+    }
+    int onto_updated = restore(backup, len, phrase, updated);
+    int superseded = count("restored") == 1 && count("skipped") == 1 && strstr(report, bob_id) != NULL &&
+                     strstr(report, "\"reason\":\"superseded\"") != NULL;
+    printf("  onto a store with a re-issued credential %d, superseded one skipped %d\n", onto_updated, superseded);
+    if (onto_updated != 0 || !superseded) {
+        printf("  %s\n", report);
+        return 1;
+    }
+
+    /* Wrong phrase and tampering look the same; version and magic do not */
+    int wrong = restore(backup, len, "correct horse battery stapler", updated);
+    memcpy(damaged, backup, len);
+    damaged[len - 1] ^= 1;
+    int tampered_body = restore(damaged, len, phrase, updated);
+    memcpy(damaged, backup, len);
+    damaged[VERSION_OFFSET + 1] ^= 1;
+    int tampered_salt = restore(damaged, len, phrase, updated);
+    memcpy(damaged, backup, len);
+    damaged[VERSION_OFFSET] = 2;
+    int newer = restore(damaged, len, phrase, updated);
+    damaged[0] = 'X';
+    int not_backup = restore(damaged, len, phrase, updated);
+    int truncated = restore(backup, len / 2, phrase, updated);
+    int empty_phrase = restore(backup, len, "   ", updated);
+    int null_store = restore(backup, len, phrase, NULL);
+    printf("  wrong phrase %d, tampered body %d, tampered salt %d, newer version %d, not a backup %d, "
+           "truncated %d, empty phrase %d, NULL store %d\n",
+           wrong, tampered_body, tampered_salt, newer, not_backup, truncated, empty_phrase, null_store);
+    ZK_StoreClose(updated);
+    ZK_StoreClose(wallet);
+    return wrong != ZK_ERR_AUTH || tampered_body != ZK_ERR_AUTH || tampered_salt != ZK_ERR_AUTH ||
+           newer != ZK_ERR_UNSUPPORTED_VERSION || not_backup != ZK_ERR_CORRUPT || truncated != ZK_ERR_CORRUPT ||
+           empty_phrase != -1 || null_store != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Wallet backups restore and merge under the recovery phrase alone"
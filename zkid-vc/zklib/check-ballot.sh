@@ -0,0 +1,151 @@
+#!/bin/bash
+#
+# Build the library for the host and check anonymous ballots: a credential
+# bound to a holder key casts one ballot per poll with the holder's secret,
+# a second ballot in the poll is refused by its nullifier, a ballot in
+# another poll counts, and a ballot verifies only for its ballot hash and
+# under the issuer that signed the credential. With fault-injection, a
+# prover that skips its pre-checks casts ballots with another holder's
+# secret and with a credential the issuer never signed, and the verifier
+# rejects both: the ballot opens the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_GenerateHolderKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_SetPollSalt(const uint8_t*, size_t);
+int ZK_GenerateBallotProof(const char*, const char*, const char*, const char*, const uint8_t*, size_t,
+                           const char*, uint64_t, char*, size_t, char*, size_t);
+int ZK_VerifyBallotProof(const char*, const char*, const uint8_t*, size_t, const char*, const char*);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_NULLIFIER_USED -24
+#define NOW 150
+#define POLL "poll/2026-budget"
+#define OTHER_POLL "poll/2026-offsite"
+
+static const char yes[] = "1111111111111111111111111111111111111111111111111111111111111111";
+static const char no[] = "2222222222222222222222222222222222222222222222222222222222222222";
+static char pub[65], priv[65], rogue_pub[65], rogue_priv[65];
+static char secret[65], holder[65], other_secret[65], other_holder[65];
+static char blob[4096], forged[4096], proof[2048], nullifier[65];
+
+/* A blob bound to `holder_key`, signed with `issuer_priv` */
+static int issue(const char* holder_key, const char* issuer_priv, char* out) {
+    static char unsigned_blob[4096];
+    const char* keys[] = {"role", "zkid:holder-binding"};
+    const char* values[] = {"engineer", holder_key};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, 100, 200, keys, values, 2, NULL, unsigned_blob,
+                       sizeof(unsigned_blob)) != 0 ||
+           ZK_SignVCBlob(unsigned_blob, issuer_priv, out, 4096) != 0;
+}
+
+static int cast(const char* vc, const char* holder_secret, const char* poll, const char* ballot) {
+    return ZK_GenerateBallotProof(vc, pub, NULL, holder_secret, (const uint8_t*)poll, strlen(poll), ballot, NOW,
+                                  proof, sizeof(proof), nullifier, sizeof(nullifier));
+}
+
+static int count(const char* poll, const char* ballot) {
+    return ZK_VerifyBallotProof(proof, pub, (const uint8_t*)poll, strlen(poll), nullifier, ballot);
+}
+
+int main(void) {
+    char first[65];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(rogue_pub, sizeof(rogue_pub), rogue_priv, sizeof(rogue_priv)) != 0 ||
+        ZK_GenerateHolderKeypair(secret, sizeof(secret), holder, sizeof(holder)) != 0 ||
+        ZK_GenerateHolderKeypair(other_secret, sizeof(other_secret), other_holder, sizeof(other_holder)) != 0 ||
+        ZK_SetPollSalt((const uint8_t*)"deployment", 10) != 0 || issue(holder, priv, blob) != 0 ||
+        issue(other_holder, rogue_priv, forged) != 0) {
+        return 1;
+    }
+
+    /* One ballot per poll, bound to its ballot hash */
+    int rc = cast(blob, secret, POLL, yes);
+    if (rc != 0) {
+        printf("  cast: %d\n", rc);
+        return 1;
+    }
+    strcpy(first, nullifier);
+    int changed = count(POLL, no);
+    int other_issuer = ZK_VerifyBallotProof(proof, rogue_pub, (const uint8_t*)POLL, strlen(POLL), nullifier, yes);
+    int other_poll = count(OTHER_POLL, yes);
+    int valid = count(POLL, yes);
+    printf("  ballot: changed hash %d, other issuer %d, other poll %d, valid %d\n", changed, other_issuer,
+           other_poll, valid);
+    if (changed != 0 || other_issuer != 0 || other_poll != 0 || valid != 1) {
+        return 1;
+    }
+
+    if (cast(blob, secret, POLL, no) != 0) {
+        return 1;
+    }
+    int same = strcmp(first, nullifier) == 0;
+    int again = count(POLL, no);
+    if (cast(blob, secret, OTHER_POLL, no) != 0) {
+        return 1;
+    }
+    int elsewhere = strcmp(first, nullifier) != 0;
+    int counted = count(OTHER_POLL, no);
+    printf("  second ballot: same nullifier %d, counted %d; other poll: new nullifier %d, counted %d\n", same,
+           again, elsewhere, counted);
+    if (!same || again != ZK_ERR_NULLIFIER_USED || !elsewhere || counted != 1) {
+        return 1;
+    }
+
+    /* The pre-checks refuse another holder's secret and a forged credential */
+    int foreign = cast(blob, other_secret, "poll/3", yes);
+    int unsigned_vc = cast(forged, other_secret, "poll/3", yes);
+    printf("  checked prover: other secret %d, credential the issuer did not sign %d\n", foreign, unsigned_vc);
+    if (foreign == 0 || unsigned_vc == 0) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* A prover that skips them proves both; neither ballot counts */
+    ZK_SkipProverChecks(1);
+    int stolen = cast(blob, other_secret, "poll/4", yes);
+    int stolen_valid = stolen == 0 ? count("poll/4", yes) : -1;
+    int forgery = cast(forged, other_secret, "poll/4", yes);
+    int forgery_valid = forgery == 0 ? count("poll/4", yes) : -1;
+    int own = cast(blob, secret, "poll/4", yes);
+    int own_valid = own == 0 ? count("poll/4", yes) : -1;
+    printf("  unchecked prover: other secret %d (counts %d), forged credential %d (counts %d), "
+           "holder secret %d (counts %d)\n", stolen, stolen_valid, forgery, forgery_valid, own, own_valid);
+    if (stolen != 0 || stolen_valid != 0 || forgery != 0 || forgery_valid != 0 || own != 0 || own_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Ballots count once per poll and only for the issuer's credential holder"
@@ -0,0 +1,132 @@
+#!/bin/bash
+#
+# Build the library for the host and check batch verification: a batch of
+# valid proofs from two issuers verifies in full, and a batch holding a
+# proof for another nonce, one under the wrong issuer and a malformed one
+# reports exactly those, each with the code ZK_VerifyVCProof gives it.
+# The batch coefficients come from the ZK_SetEntropySource callback when
+# one is installed; if it fails, each proof is verified on its own.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_BatchVerifyVCProofs(const char* const*, const char* const*, uint64_t, const uint64_t*, size_t, int*);
+int ZK_SetEntropySource(int (*)(uint8_t*, size_t, void*), void*);
+
+static int draws;
+
+static int counting_source(uint8_t* buf, size_t len, void* user_data) {
+    (void)user_data;
+    memset(buf, 0x5a + draws, len);
+    draws++;
+    return 0;
+}
+
+static int failing_source(uint8_t* buf, size_t len, void* user_data) {
+    (void)buf; (void)len; (void)user_data;
+    return -1;
+}
+
+#define NOW 150
+#define PROOFS 6
+
+static char pubs[2][65], privs[2][65], proofs[PROOFS][1024];
+
+int main(void) {
+    const char* proof_ptrs[PROOFS];
+    const char* issuer_ptrs[PROOFS];
+    uint64_t nonces[PROOFS];
+    int results[PROOFS];
+    if (ZK_Init() != 0) {
+        return 1;
+    }
+    for (int i = 0; i < 2; i++) {
+        if (ZK_GenerateIssuerKeypair(pubs[i], 65, privs[i], 65) != 0) {
+            return 1;
+        }
+    }
+    for (int i = 0; i < PROOFS; i++) {
+        char sig[129];
+        nonces[i] = 100 + i;
+        if (ZK_SignVC("alice", 5, "issuer", 6, 100, 200, privs[i % 2], sig, sizeof(sig), NULL) != 0 ||
+            ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pubs[i % 2], NOW, nonces[i], proofs[i],
+                               1024, NULL) != 0) {
+            return 1;
+        }
+        proof_ptrs[i] = proofs[i];
+        issuer_ptrs[i] = pubs[i % 2];
+    }
+
+    /* All valid */
+    int valid = ZK_BatchVerifyVCProofs(proof_ptrs, issuer_ptrs, NOW, nonces, PROOFS, results);
+    int all_one = 1;
+    for (int i = 0; i < PROOFS; i++) {
+        all_one &= results[i] == 1;
+    }
+    int empty = ZK_BatchVerifyVCProofs(NULL, NULL, NOW, NULL, 0, NULL);
+    int no_results = ZK_BatchVerifyVCProofs(proof_ptrs, issuer_ptrs, NOW, nonces, PROOFS, NULL);
+    printf("  valid batch: %d of %d (all reported %d), empty %d, NULL results %d\n", valid, PROOFS, all_one, empty,
+           no_results);
+    if (valid != PROOFS || !all_one || empty != 0 || no_results != -1) {
+        return 1;
+    }
+
+    /* Coefficients drawn from the installed source; a failing source falls
+       back to verifying each proof on its own */
+    ZK_SetEntropySource(counting_source, NULL);
+    int from_source = ZK_BatchVerifyVCProofs(proof_ptrs, issuer_ptrs, NOW, nonces, PROOFS, results);
+    ZK_SetEntropySource(failing_source, NULL);
+    int failing = ZK_BatchVerifyVCProofs(proof_ptrs, issuer_ptrs, NOW, nonces, PROOFS, results);
+    ZK_SetEntropySource(NULL, NULL);
+    printf("  entropy callback: %d valid from %d draws, %d valid with a failing source\n", from_source, draws,
+           failing);
+    if (from_source != PROOFS || draws != PROOFS || failing != PROOFS) {
+        return 1;
+    }
+
+    /* Another nonce, the wrong issuer, a malformed proof */
+    nonces[1] += 1000;
+    issuer_ptrs[3] = pubs[0];
+    proof_ptrs[4] = "zz";
+    valid = ZK_BatchVerifyVCProofs(proof_ptrs, issuer_ptrs, NOW, nonces, PROOFS, results);
+    int agree = 1;
+    for (int i = 0; i < PROOFS; i++) {
+        agree &= results[i] == ZK_VerifyVCProof(proof_ptrs[i], issuer_ptrs[i], NOW, nonces[i]);
+    }
+    printf("  mixed batch: %d valid; results %d %d %d %d %d %d; same as ZK_VerifyVCProof %d\n", valid, results[0],
+           results[1], results[2], results[3], results[4], results[5], agree);
+    return valid != 3 || !agree || results[0] != 1 || results[1] != 0 || results[2] != 1 || results[3] != 0 ||
+           results[4] == 1 || results[5] != 1;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+
+echo "✓ Batch verification reports each proof as ZK_VerifyVCProof does"
@@ -0,0 +1,61 @@
+#!/bin/bash
+#
+# Build the benchmark suite (the bench feature) and run the serialization
+# and rejection groups on a short measurement, then check summary.json in
+# the criterion directory: it records ZK_BuildInfo and an estimate for
+# every benchmark run, sorted by id. A second run filtered to one
+# benchmark must replace the summary with that benchmark alone, so a
+# summary never mixes estimates from an earlier run.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+export CRITERION_HOME="$WORK_DIR/criterion"
+SUMMARY="$CRITERION_HOME/summary.json"
+QUICK="--warm-up-time 0.1 --measurement-time 0.2 --sample-size 10 --noplot"
+
+IDS="rejection/canonical
+rejection/deserialize
+rejection/encoding
+rejection/length
+rejection/oversized-proof
+rejection/version
+serialization/predicate
+serialization/presentation
+serialization/proof
+serialization/vc
+serialization/verifying-key"
+
+bench() {
+    cargo bench --features bench --bench zklib --target-dir "$WORK_DIR/target" -q -- "$1" $QUICK \
+        > "$WORK_DIR/bench.log" 2>&1 || { cat "$WORK_DIR/bench.log"; echo "  FAIL"; exit 1; }
+}
+
+# The ids in the summary, in order
+summary_ids() {
+    grep -o '"id": "[^"]*"' "$SUMMARY" | cut -d'"' -f4
+}
+
+echo "Checking bench: serialization and rejection groups"
+bench '^(serialization|rejection)/'
+[ -f "$SUMMARY" ] || { echo "  no summary.json"; echo "  FAIL"; exit 1; }
+grep -q '"build": {' "$SUMMARY" && grep -q '"crate": "zklib-vc"' "$SUMMARY" \
+    || { echo "  summary has no build info"; echo "  FAIL"; exit 1; }
+[ "$(summary_ids)" = "$IDS" ] || { echo "  ids:"; summary_ids; echo "  FAIL"; exit 1; }
+for field in mean_ns median_ns std_dev_ns mean_lower_ns mean_upper_ns; do
+    count="$(grep -c "\"$field\": [0-9]" "$SUMMARY")"
+    [ "$count" = 11 ] || { echo "  $field in $count of 11 estimates"; echo "  FAIL"; exit 1; }
+done
+echo "  ok"
+
+echo "Checking bench: a later run summarizes only itself"
+bench '^serialization/vc$'
+[ "$(summary_ids)" = "serialization/vc" ] || { echo "  ids:"; summary_ids; echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ The bench suite runs and writes a summary of the run"
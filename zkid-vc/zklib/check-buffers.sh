@@ -0,0 +1,134 @@
+#!/bin/bash
+#
+# Build the library for the host and check how entry points treat caller
+# memory: an output string needs room for its NUL, and a buffer one byte
+# short returns ZK_ERR_BUFFER_TOO_SMALL with nothing written; a NULL output
+# with required_size_out set only queries the size; NULL claim arrays are
+# accepted for zero claims only; and a length past the entry point's limit
+# is refused before the memory is read. ZK_GetLastError names the argument.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_STATUS_SIZE_QUERY 1
+#define MAX_FIELD_LEN (64 * 1024)
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char encoded[8192], exact[8192], message[256];
+
+/* Hash of `holder` (`len` bytes) issued by "issuer" into `out` */
+static int hash(const char* holder, size_t len, char* out, size_t size, size_t* required) {
+    return ZK_ComputeVCHash(holder, len, "issuer", 6, NOW - DAY, NOW + DAY, NULL, NULL, 0, out, size, required);
+}
+
+/* The last error contains `text` */
+static int says(const char* text) {
+    return ZK_GetLastError(message, sizeof(message)) == 0 && strstr(message, text) != NULL;
+}
+
+static int untouched(const char* buf, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        if (buf[i] != 'X') {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+int main(void) {
+    char out[65], pub[65], priv[65], again[65];
+    size_t required = 0;
+
+    /* Exact fit of a 64-character hash */
+    int query = hash("alice", 5, NULL, 0, &required);
+    memset(out, 'X', sizeof(out));
+    int short_out = hash("alice", 5, out, 64, NULL);
+    int short_named = says("vc_hash_out buffer is too small");
+    int short_untouched = untouched(out, sizeof(out));
+    int fits = hash("alice", 5, out, 65, NULL);
+    printf("  size query %d (%zu); 64 bytes %d, named %d, untouched %d; 65 bytes %d (%zu characters)\n", query,
+           required, short_out, short_named, short_untouched, fits, strlen(out));
+    if (query != ZK_STATUS_SIZE_QUERY || required != 65 || short_out != ZK_ERR_BUFFER_TOO_SMALL || !short_named ||
+        !short_untouched || fits != 0 || strlen(out) != 64) {
+        return 1;
+    }
+
+    memset(pub, 'X', sizeof(pub));
+    int short_key = ZK_GenerateIssuerKeypair(pub, 64, priv, sizeof(priv));
+    int key_untouched = untouched(pub, sizeof(pub));
+    int key = ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv));
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    int encode = ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                             sizeof(encoded));
+    size_t len = strlen(encoded);
+    int encode_short = ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, exact, len);
+    int encode_exact =
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, exact, len + 1);
+    printf("  issuer key in 64 bytes %d, untouched %d, in 65 %d; credential of %zu in %zu bytes %d, in %zu %d\n",
+           short_key, key_untouched, key, len, len, encode_short, len + 1, encode_exact);
+    if (short_key != ZK_ERR_BUFFER_TOO_SMALL || !key_untouched || key != 0 || encode != 0 ||
+        encode_short != ZK_ERR_BUFFER_TOO_SMALL || encode_exact != 0 || strcmp(exact, encoded) != 0) {
+        return 1;
+    }
+
+    /* NULL inputs: claim arrays for zero claims, and nothing else */
+    int no_claims = hash("alice", 5, again, sizeof(again), NULL);
+    int null_claims = ZK_ComputeVCHash("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, NULL, NULL, 1, out,
+                                       sizeof(out), NULL);
+    int null_holder = hash(NULL, 5, out, sizeof(out), NULL);
+    int null_named = says("holder_id or issuer is NULL");
+    int null_output = hash("alice", 5, NULL, sizeof(out), NULL);
+    printf("  NULL claim arrays for no claims %d, for one %d; NULL holder_id %d (named %d); NULL output %d\n",
+           no_claims, null_claims, null_holder, null_named, null_output);
+    if (no_claims != 0 || strcmp(again, out) != 0 || null_claims != -1 || null_holder != -1 || !null_named ||
+        null_output != -1) {
+        return 1;
+    }
+
+    /* Lengths at and past the limit; SIZE_MAX must not be read */
+    char* holder = malloc(MAX_FIELD_LEN + 1);
+    if (holder == NULL) {
+        return 1;
+    }
+    memset(holder, 'h', MAX_FIELD_LEN + 1);
+    int at_limit = hash(holder, MAX_FIELD_LEN, out, sizeof(out), NULL);
+    int past_limit = hash(holder, MAX_FIELD_LEN + 1, out, sizeof(out), NULL);
+    int past_named = says("holder_id is longer than accepted");
+    int huge = hash("alice", SIZE_MAX, out, sizeof(out), NULL);
+    int claims = ZK_ComputeVCHash("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 4097, out,
+                                  sizeof(out), NULL);
+    free(holder);
+    printf("  holder_id of %d bytes %d, %d bytes %d (named %d), SIZE_MAX bytes %d; 4097 claims %d\n", MAX_FIELD_LEN,
+           at_limit, MAX_FIELD_LEN + 1, past_limit, past_named, huge, claims);
+    return at_limit != 0 || past_limit != -1 || !past_named || huge != -1 || claims != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Entry points check caller buffers and lengths before touching them"
@@ -0,0 +1,156 @@
+#!/bin/bash
+#
+# Build the library for the host and check capability negotiation:
+# ZK_GetCapabilities lists the VC circuit once its keys are loaded; two
+# overlapping advertisements give the highest common format, the common
+# circuits and the largest common schedule capacity; disjoint ones name the
+# first dimension they share nothing in, with both sides' values. A proof
+# made under the negotiated profile verifies under it and is refused under
+# a profile of another format, and a profile without the VC circuit is
+# refused before proving.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    uint8_t format;
+    uint32_t circuits;
+    uint32_t schedule_capacity;
+} ZkProfile;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_GetCapabilities(char*, size_t);
+int ZK_SelectCommonProfile(const char*, const char*, ZkProfile*, char*, size_t);
+int ZK_GenerateVCProofWithProfile(const ZkProfile*, const char*, const char*, const char*, uint64_t, uint64_t, char*,
+                                  size_t);
+int ZK_VerifyVCProofWithProfile(const ZkProfile*, const char*, const char*, uint64_t, uint64_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_INCOMPATIBLE -23
+#define VC_BIT (1u << 1)
+#define SCHEDULE_BIT (1u << 2)
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096];
+static char local[2048], mine[1024], theirs[1024], detail[512], small[16];
+
+/* An advertisement with the given roles, curve, formats and circuits */
+static const char* advertise(char* out, int prover, int verifier, const char* curve, const char* formats,
+                             const char* circuits) {
+    snprintf(out, 1024,
+             "{\"prover\":%s,\"verifier\":%s,\"proof_system\":\"groth16\",\"curves\":[\"%s\"],"
+             "\"hashes\":[\"sha256\"],\"formats\":[%s],\"circuits\":[%s]}",
+             prover ? "true" : "false", verifier ? "true" : "false", curve, formats, circuits);
+    return out;
+}
+
+/* Negotiate and compare the result with `want` (or the incompatibility with `dimension`) */
+static int negotiate(const char* name, const char* a, const char* b, ZkProfile want, const char* dimension) {
+    ZkProfile got = {0};
+    detail[0] = '\0';
+    int rc = ZK_SelectCommonProfile(a, b, &got, detail, sizeof(detail));
+    printf("  %s: %d, format %u, circuits 0x%x, schedule capacity %u %s\n", name, rc, got.format, got.circuits,
+           got.schedule_capacity, detail);
+    if (dimension != NULL) {
+        char expected[64];
+        snprintf(expected, sizeof(expected), "{\"incompatible\":\"%s\"", dimension);
+        return rc == ZK_ERR_INCOMPATIBLE && strncmp(detail, expected, strlen(expected)) == 0;
+    }
+    return rc == 0 && got.format == want.format && got.circuits == want.circuits &&
+           got.schedule_capacity == want.schedule_capacity;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    const ZkProfile none = {0};
+    int before = ZK_GetCapabilities(local, sizeof(local)) == 0 && strstr(local, "\"zkid-vc/v7\"") == NULL;
+    if (ZK_Init() != 0 || ZK_GetCapabilities(local, sizeof(local)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+    int after = strstr(local, "\"circuits\":[\"zkid-vc/v7\"") != NULL && strstr(local, "\"formats\":[1,2,3]") != NULL;
+    int short_buffer = ZK_GetCapabilities(small, sizeof(small));
+    printf("  VC circuit absent before ZK_Init %d, listed after %d; short buffer %d\n", before, after, short_buffer);
+    if (!before || !after || short_buffer != ZK_ERR_BUFFER_TOO_SMALL) {
+        return 1;
+    }
+
+    /* Overlapping, partially overlapping and disjoint advertisements */
+    const char* schedules = "\"zkid-vc/v7\",\"zkid-vc/schedule/v2/w8\",\"zkid-vc/schedule/v2/w16\",\"other/v1\"";
+    const char* fewer = "\"zkid-vc/schedule/v2/w16\",\"zkid-vc/schedule/v2/w8\",\"zkid-vc/v7\",\"other/v2\"";
+    int ok = negotiate("with a format 2 verifier", local, advertise(theirs, 0, 1, "bn254", "2", "\"zkid-vc/v7\""),
+                       (ZkProfile){2, VC_BIT, 0}, NULL) &&
+             negotiate("partial overlap", advertise(mine, 1, 0, "bn254", "1,2,3", schedules),
+                       advertise(theirs, 0, 1, "bn254", "2,3,4", fewer), (ZkProfile){3, VC_BIT | SCHEDULE_BIT, 16},
+                       NULL) &&
+             negotiate("schedule only", advertise(mine, 1, 0, "bn254", "3", "\"zkid-vc/schedule/v2/w8\""),
+                       advertise(theirs, 0, 1, "bn254", "3", "\"zkid-vc/schedule/v2/w8\""),
+                       (ZkProfile){3, SCHEDULE_BIT, 8}, NULL) &&
+             negotiate("disjoint formats", advertise(mine, 1, 0, "bn254", "3", "\"zkid-vc/v7\""),
+                       advertise(theirs, 0, 1, "bn254", "1,2", "\"zkid-vc/v7\""), none, "formats") &&
+             strcmp(detail, "{\"incompatible\":\"formats\",\"mine\":[3],\"theirs\":[1,2]}") == 0 &&
+             negotiate("disjoint circuits", advertise(mine, 1, 0, "bn254", "3", "\"zkid-vc/v7\""),
+                       advertise(theirs, 0, 1, "bn254", "3", "\"zkid-vc/schedule/v2/w8\""), none, "circuits") &&
+             negotiate("two provers", advertise(mine, 1, 0, "bn254", "3", "\"zkid-vc/v7\""),
+                       advertise(theirs, 1, 0, "bn254", "3", "\"zkid-vc/v7\""), none, "roles") &&
+             negotiate("other curve and formats", advertise(mine, 1, 0, "bn254", "3", "\"zkid-vc/v7\""),
+                       advertise(theirs, 0, 1, "bls12-381", "1", "\"zkid-vc/v7\""), none, "curves");
+    if (!ok) {
+        return 1;
+    }
+    ZkProfile profile;
+    int garbled = ZK_SelectCommonProfile(local, "{\"prover\":", &profile, NULL, 0);
+    int missing = ZK_SelectCommonProfile(local, "{\"prover\":false}", &profile, NULL, 0);
+    int null_out = ZK_SelectCommonProfile(local, local, NULL, NULL, 0);
+    printf("  garbled advertisement %d, missing fields %d, NULL profile %d\n", garbled, missing, null_out);
+    if (garbled != ZK_ERR_CORRUPT || missing != ZK_ERR_CORRUPT || null_out != -1) {
+        return 1;
+    }
+
+    /* Proving and verifying under the negotiated profile */
+    const ZkProfile v2 = {2, VC_BIT, 0}, v3 = {3, VC_BIT, 0}, schedule_only = {3, SCHEDULE_BIT, 8};
+    const ZkProfile unknown_format = {9, VC_BIT, 0};
+    int proved = ZK_GenerateVCProofWithProfile(&v2, blob, pub, NULL, NOW, 7, proof, sizeof(proof));
+    int verified = ZK_VerifyVCProofWithProfile(&v2, proof, pub, NOW, 7);
+    int other_format = ZK_VerifyVCProofWithProfile(&v3, proof, pub, NOW, 7);
+    int plain = ZK_VerifyVCProof(proof, pub, NOW, 7);
+    int no_vc = ZK_GenerateVCProofWithProfile(&schedule_only, blob, pub, NULL, NOW, 8, proof, sizeof(proof));
+    int bad_format = ZK_GenerateVCProofWithProfile(&unknown_format, blob, pub, NULL, NOW, 8, proof, sizeof(proof));
+    int verify_no_vc = ZK_VerifyVCProofWithProfile(&schedule_only, proof, pub, NOW, 7);
+    printf("  format 2 profile: proved %d, verified %d, under format 3 %d, plain %d; without the VC circuit %d, "
+           "format 9 %d, verify without the VC circuit %d\n",
+           proved, verified, other_format, plain, no_vc, bad_format, verify_no_vc);
+    return proved != 0 || verified != 1 || other_format != ZK_ERR_UNSUPPORTED_VERSION || plain != 1 ||
+           no_vc != ZK_ERR_INCOMPATIBLE || bad_format != ZK_ERR_INCOMPATIBLE || verify_no_vc != ZK_ERR_INCOMPATIBLE;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Capability negotiation picks the best common profile or names the mismatch"
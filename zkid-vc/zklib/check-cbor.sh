@@ -0,0 +1,139 @@
+#!/bin/bash
+#
+# Build the library for the host and take a VC blob, a VC proof and a
+# presentation through canonical CBOR: each comes back as it went in and
+# still verifies, the presentation verifies and digests the same straight
+# from CBOR, non-canonical or truncated CBOR and unknown proof formats are
+# refused, and every CBOR form is smaller than the binary form the hex
+# spells out (and the VC's than its W3C JSON).
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t,
+                          char*, size_t);
+int ZK_PresentationDigest(const char*, size_t, uint8_t*);
+int ZK_VerifyPresentation(const char*, size_t);
+int ZK_SerializeVCJson(const char*, char*, size_t, size_t*);
+int ZK_SerializeVCCbor(const char*, uint8_t*, size_t, size_t*);
+int ZK_ParseVCCbor(const uint8_t*, size_t, char*, size_t);
+int ZK_SerializeProofCbor(const char*, uint8_t*, size_t, size_t*);
+int ZK_ParseProofCbor(const uint8_t*, size_t, char*, size_t);
+int ZK_ConvertPresentationEncoding(const char*, size_t, int, uint8_t*, size_t, size_t*);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_PRESENTATION_ENCODING_BINARY 0
+#define ZK_PRESENTATION_ENCODING_CBOR 1
+
+static char pub[65], priv[65];
+
+int main(void) {
+    static char unsigned_blob[4096], blob[4096], blob_again[4096], json[8192], sig[129];
+    static char proof[1024], proof_again[1024], presentation[8192];
+    static uint8_t cbor[4096], edited[4096], binary[4096];
+    const char* keys[] = {"role", "dept"};
+    const char* values[] = {"engineer", "rd"};
+    const char* meta_keys[] = {"audience"};
+    const char* meta_values[] = {"gateway"};
+    size_t len = 0, small = 0, json_len = 0;
+
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, 100, 200, keys, values, 2, NULL, unsigned_blob,
+                    sizeof(unsigned_blob)) != 0 ||
+        ZK_SignVCBlob(unsigned_blob, priv, blob, sizeof(blob)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42, proof, sizeof(proof), NULL) != 0 ||
+        ZK_EncodePresentation(proof, pub, 42, meta_keys, meta_values, 1, presentation, sizeof(presentation)) != 0) {
+        return 1;
+    }
+
+    /* VC */
+    int too_small = ZK_SerializeVCCbor(blob, cbor, 8, &small);
+    if (ZK_SerializeVCCbor(blob, cbor, sizeof(cbor), &len) != 0 ||
+        ZK_SerializeVCJson(blob, json, sizeof(json), NULL) != 0) {
+        return 1;
+    }
+    json_len = strlen(json);
+    int vc_back = ZK_ParseVCCbor(cbor, len, blob_again, sizeof(blob_again)) == 0 && strcmp(blob, blob_again) == 0;
+    int vc_valid = ZK_VerifyVCBlob(blob_again, pub, NULL);
+    /* Entry 0 holds a small version; spell its integer with a one-byte argument */
+    edited[0] = cbor[0];
+    edited[1] = cbor[1];
+    edited[2] = 0x18;
+    memcpy(edited + 3, cbor + 2, len - 2);
+    int long_head = ZK_ParseVCCbor(edited, len + 1, blob_again, sizeof(blob_again));
+    int truncated = ZK_ParseVCCbor(cbor, len - 1, blob_again, sizeof(blob_again));
+    printf("  VC: hex %zu, binary %zu, JSON %zu, CBOR %zu bytes (too small %d, %zu reported)\n", strlen(blob),
+           strlen(blob) / 2, json_len, len, too_small, small);
+    printf("  VC: round trip %d, verifies %d; long integer head %d, truncated %d\n", vc_back, vc_valid, long_head,
+           truncated);
+    int vc_ok = too_small == ZK_ERR_BUFFER_TOO_SMALL && small == len && len < strlen(blob) / 2 &&
+                len < json_len && vc_back && vc_valid == 1 && long_head == -1 && truncated == -1;
+
+    /* Proof */
+    size_t proof_len = 0;
+    if (ZK_SerializeProofCbor(proof, cbor, sizeof(cbor), &proof_len) != 0) {
+        return 1;
+    }
+    int proof_back = ZK_ParseProofCbor(cbor, proof_len, proof_again, sizeof(proof_again)) == 0 &&
+                     strcmp(proof, proof_again) == 0;
+    int proof_valid = ZK_VerifyVCProof(proof_again, pub, 150, 42);
+    /* Map head, key 1, then the format */
+    cbor[2] = 9;
+    int bad_format = ZK_ParseProofCbor(cbor, proof_len, proof_again, sizeof(proof_again));
+    printf("  proof: hex %zu, binary %zu, CBOR %zu bytes; round trip %d, verifies %d, format 9 %d\n",
+           strlen(proof), strlen(proof) / 2, proof_len, proof_back, proof_valid, bad_format);
+    int proof_ok = proof_len < strlen(proof) / 2 && proof_back && proof_valid == 1 && bad_format == -1;
+
+    /* Presentation */
+    size_t pres_len = 0, binary_len = 0;
+    uint8_t digest_hex[32], digest_cbor[32];
+    if (ZK_ConvertPresentationEncoding(presentation, strlen(presentation), ZK_PRESENTATION_ENCODING_CBOR, cbor,
+                                       sizeof(cbor), &pres_len) != 0 ||
+        ZK_ConvertPresentationEncoding((const char*)cbor, pres_len, ZK_PRESENTATION_ENCODING_BINARY, binary,
+                                       sizeof(binary), &binary_len) != 0) {
+        return 1;
+    }
+    int pres_valid = ZK_VerifyPresentation((const char*)cbor, pres_len);
+    int digests = ZK_PresentationDigest(presentation, strlen(presentation), digest_hex) == 0 &&
+                  ZK_PresentationDigest((const char*)cbor, pres_len, digest_cbor) == 0 &&
+                  memcmp(digest_hex, digest_cbor, 32) == 0;
+    int unknown = ZK_ConvertPresentationEncoding(presentation, strlen(presentation), 2, cbor, sizeof(cbor),
+                                                 &pres_len);
+    printf("  presentation: hex %zu, binary %zu, CBOR %zu bytes; verifies %d, same digest %d, encoding 2 %d\n",
+           strlen(presentation), binary_len, pres_len, pres_valid, digests, unknown);
+    int pres_ok = binary_len == strlen(presentation) / 2 && pres_len < binary_len && pres_valid == 1 && digests &&
+                  unknown == -1;
+
+    return !vc_ok || !proof_ok || !pres_ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ CBOR forms are canonical, smaller than binary, and verify like the originals"
@@ -0,0 +1,94 @@
+#!/bin/bash
+#
+# Build the library for the host and check stateless verifier challenges:
+# the same secret and context always derive the same challenge, another
+# context or secret a different one, and a derived challenge validates only
+# for its own context, within max_age of the issued-at time it carries and
+# not before it. Malformed secrets are refused, and a NULL argument is
+# reported as -1 by both derivation and validation.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_DeriveChallenge(const char*, const char*, size_t, uint64_t*);
+int ZK_ValidateDerivedChallenge(const char*, const char*, size_t, uint64_t, uint64_t, uint64_t);
+
+#define SECRET "000102030405060708090a0b0c0d0e0f"
+#define OTHER_SECRET "0f0e0d0c0b0a09080706050403020100"
+#define ISSUED 1000
+#define MAX_AGE 60
+
+/* issued-at (u64 LE) followed by the request-specific bytes */
+static size_t context(char* out, uint64_t issued_at, const char* request) {
+    for (int i = 0; i < 8; i++) {
+        out[i] = (char)(issued_at >> (8 * i));
+    }
+    memcpy(out + 8, request, strlen(request));
+    return 8 + strlen(request);
+}
+
+int main(void) {
+    char alice[64], bob[64], later[64];
+    size_t alice_len = context(alice, ISSUED, "alice/login");
+    size_t bob_len = context(bob, ISSUED, "bob/login");
+    size_t later_len = context(later, ISSUED + 1, "alice/login");
+    uint64_t c1 = 0, c2 = 0, c_bob = 0, c_later = 0, c_secret = 0;
+
+    if (ZK_DeriveChallenge(SECRET, alice, alice_len, &c1) != 0 ||
+        ZK_DeriveChallenge(SECRET, alice, alice_len, &c2) != 0 ||
+        ZK_DeriveChallenge(SECRET, bob, bob_len, &c_bob) != 0 ||
+        ZK_DeriveChallenge(SECRET, later, later_len, &c_later) != 0 ||
+        ZK_DeriveChallenge(OTHER_SECRET, alice, alice_len, &c_secret) != 0) {
+        return 1;
+    }
+    printf("  same context %d, other request %d, other issued-at %d, other secret %d\n", c1 == c2, c1 != c_bob,
+           c1 != c_later, c1 != c_secret);
+
+    int fresh = ZK_ValidateDerivedChallenge(SECRET, alice, alice_len, c1, ISSUED + 10, MAX_AGE);
+    int at_limit = ZK_ValidateDerivedChallenge(SECRET, alice, alice_len, c1, ISSUED + MAX_AGE, MAX_AGE);
+    int expired = ZK_ValidateDerivedChallenge(SECRET, alice, alice_len, c1, ISSUED + MAX_AGE + 1, MAX_AGE);
+    int future = ZK_ValidateDerivedChallenge(SECRET, alice, alice_len, c1, ISSUED - 1, MAX_AGE);
+    int foreign = ZK_ValidateDerivedChallenge(SECRET, bob, bob_len, c1, ISSUED + 10, MAX_AGE);
+    int wrong_secret = ZK_ValidateDerivedChallenge(OTHER_SECRET, alice, alice_len, c1, ISSUED + 10, MAX_AGE);
+    printf("  validate: fresh %d, at max_age %d, expired %d, before issue %d, other context %d, other secret %d\n",
+           fresh, at_limit, expired, future, foreign, wrong_secret);
+
+    /* A context too short to carry the issued-at time never validates */
+    uint64_t c_short = 0;
+    int short_derive = ZK_DeriveChallenge(SECRET, "abc", 3, &c_short);
+    int short_valid = ZK_ValidateDerivedChallenge(SECRET, "abc", 3, c_short, ISSUED, MAX_AGE);
+
+    uint64_t unused = 0;
+    int not_hex = ZK_DeriveChallenge("zz", alice, alice_len, &unused);
+    int empty_secret = ZK_DeriveChallenge("", alice, alice_len, &unused);
+    int null_out = ZK_DeriveChallenge(SECRET, alice, alice_len, NULL);
+    int null_context = ZK_ValidateDerivedChallenge(SECRET, NULL, 0, c1, ISSUED, MAX_AGE);
+    int null_secret = ZK_ValidateDerivedChallenge(NULL, alice, alice_len, c1, ISSUED, MAX_AGE);
+    printf("  short context: derive %d, validate %d; not hex %d, empty secret %d, NULL out %d, NULL context %d, "
+           "NULL secret %d\n",
+           short_derive, short_valid, not_hex, empty_secret, null_out, null_context, null_secret);
+
+    return c1 != c2 || c1 == c_bob || c1 == c_later || c1 == c_secret || fresh != 1 || at_limit != 1 ||
+           expired != 0 || future != 0 || foreign != 0 || wrong_secret != 0 || short_derive != 0 ||
+           short_valid != 0 || not_hex != -1 || empty_secret != -1 || null_out != -1 || null_context != -1 ||
+           null_secret != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Derived challenges are bound to their context and secret and expire after max_age"
@@ -0,0 +1,145 @@
+#!/bin/bash
+#
+# Build the library for the host and check resumable proves: checkpoints
+# taken while ZK_GenerateVCProofFromBlob runs resume to exactly the proof
+# the prove returned, or, when a snapshot caught a save half-written, prove
+# again from the request with ZK_PROVE_RESTARTED; the buffer is wiped once
+# the proof is done; damaged progress restarts while a damaged request or
+# frame is ZK_ERR_CORRUPT.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetProveCheckpointing(uint8_t*, size_t);
+int ZK_ResumeProve(uint8_t*, size_t, char*, size_t);
+
+#define ZK_ERR_CORRUPT -14
+#define ZK_PROVE_RESTARTED 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define CAP (1 << 20)
+#define SNAPSHOTS 8
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096], resumed[4096];
+static uint8_t sink[CAP], snapshots[SNAPSHOTS][CAP], last[CAP], edited[CAP];
+static volatile int done;
+static int proved;
+
+static void* prove(void* arg) {
+    (void)arg;
+    proved = ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof));
+    done = 1;
+    return NULL;
+}
+
+static int zero(const uint8_t* buf, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        if (buf[i] != 0) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+static uint32_t le32(const uint8_t* p) {
+    return (uint32_t)p[0] | (uint32_t)p[1] << 8 | (uint32_t)p[2] << 16 | (uint32_t)p[3] << 24;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    int half_set = ZK_SetProveCheckpointing(sink, 0) == -1 && ZK_SetProveCheckpointing(NULL, CAP) == -1;
+    if (!half_set || ZK_SetProveCheckpointing(sink, CAP) != 0) {
+        return 1;
+    }
+
+    /* Snapshot the buffer each time it changes while the prove runs */
+    pthread_t thread;
+    int taken = 0;
+    if (pthread_create(&thread, NULL, prove, NULL) != 0) {
+        return 1;
+    }
+    while (!done) {
+        static uint8_t copy[CAP];
+        memcpy(copy, sink, CAP);
+        if (!zero(copy, 2) && taken < SNAPSHOTS && (taken == 0 || memcmp(copy, snapshots[taken - 1], CAP) != 0)) {
+            memcpy(snapshots[taken++], copy, CAP);
+        }
+    }
+    pthread_join(thread, NULL);
+    int wiped = zero(sink, CAP);
+    printf("  prove %d, %d snapshots, buffer wiped afterwards %d\n", proved, taken, wiped);
+    if (proved != 0 || taken == 0 || !wiped || ZK_SetProveCheckpointing(NULL, 0) != 0) {
+        return 1;
+    }
+
+    /* Every snapshot finishes the proof (and is wiped by it) */
+    memcpy(last, snapshots[taken - 1], CAP);
+    int continued = 0;
+    for (int i = 0; i < taken; i++) {
+        int rc = ZK_ResumeProve(snapshots[i], CAP, resumed, sizeof(resumed));
+        int same = strcmp(resumed, proof) == 0;
+        int valid = ZK_VerifyVCProof(resumed, pub, NOW, 7);
+        printf("  snapshot %d: resume %d, same proof %d, verifies %d\n", i, rc, same, valid);
+        if ((rc != 0 && rc != ZK_PROVE_RESTARTED) || (rc == 0 && !same) || valid != 1) {
+            return 1;
+        }
+        continued += rc == 0;
+    }
+    if (continued == 0) {
+        return 1;
+    }
+
+    /* "ZP" | request_len | request | digest | progress_len | progress | digest */
+    size_t request_at = 6, progress_at = request_at + le32(last + 2) + 32 + 4;
+    memcpy(edited, last, CAP);
+    edited[progress_at + 40] ^= 0x01;
+    int progress = ZK_ResumeProve(edited, CAP, resumed, sizeof(resumed));
+    int progress_valid = ZK_VerifyVCProof(resumed, pub, NOW, 7);
+    memcpy(edited, last, CAP);
+    edited[request_at + 3] ^= 0x01;
+    int request = ZK_ResumeProve(edited, CAP, resumed, sizeof(resumed));
+    memcpy(edited, last, CAP);
+    edited[0] ^= 0x01;
+    int magic = ZK_ResumeProve(edited, CAP, resumed, sizeof(resumed));
+    int empty = ZK_ResumeProve(sink, CAP, resumed, sizeof(resumed));
+    printf("  damaged progress %d (verifies %d), damaged request %d, magic %d, wiped buffer %d\n", progress,
+           progress_valid, request, magic, empty);
+    return progress != ZK_PROVE_RESTARTED || progress_valid != 1 || request != ZK_ERR_CORRUPT ||
+           magic != ZK_ERR_CORRUPT || empty != ZK_ERR_CORRUPT;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Interrupted proves resume to the same proof"
@@ -0,0 +1,196 @@
+#!/bin/bash
+#
+# Build the library for the host and check cloned contexts: a clone that
+# trusts another issuer and changes its settings leaves its parent's trust
+# store and config as they were. Deterministic proving in a context repeats
+# its proofs per nonce under the context's own seed: clones keep the seed,
+# a sibling that turns it on separately draws another, and a context
+# created while ZK_SetDeterministicProving is on proves as the global entry
+# points do. Strict builds refuse deterministic contexts.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct Context Context;
+typedef struct {
+    int time_mode;
+    uint64_t max_skew;
+    int64_t min_date;
+    int64_t max_date;
+    size_t max_blob_len;
+    int deterministic;
+    uint8_t proof_format;
+    uint32_t accepted_formats;
+} ContextConfig;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_ContextCreate(Context**);
+int ZK_ContextClone(const Context*, Context**);
+int ZK_ContextGetConfig(const Context*, ContextConfig*);
+int ZK_ContextSetConfig(Context*, const ContextConfig*);
+int ZK_ContextTrustIssuer(Context*, const char*);
+int ZK_TrustStoreList(const Context*, size_t, size_t, char*, size_t);
+int ZK_ContextGenerateVCProofFromBlob(const Context*, const char*, const char*, const char*, uint64_t, uint64_t,
+                                      char*, size_t);
+int ZK_ContextVerifyVCProof(const Context*, const char*, const char*, uint64_t, uint64_t);
+void ZK_ContextFree(Context*);
+#ifndef STRICT
+int ZK_SetDeterministicProving(uint64_t);
+#endif
+
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub_a[65], priv_a[65], pub_b[65], priv_b[65];
+static char blob_a[8192], blob_b[8192];
+
+static int sign(const char* key, char* out) {
+    static char encoded[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, key, out, 8192) == 0;
+}
+
+static int prove(const Context* ctx, const char* blob, const char* pub, uint64_t nonce, char* out) {
+    return ZK_ContextGenerateVCProofFromBlob(ctx, blob, pub, NULL, NOW, nonce, out, 4096);
+}
+
+static int trusted_count(const Context* ctx) {
+    char list[1024];
+    int total = -1;
+    if (ZK_TrustStoreList(ctx, 0, 10, list, sizeof(list)) == 0) {
+        const char* at = strstr(list, "\"total\":");
+        total = at ? (int)strtol(at + strlen("\"total\":"), NULL, 10) : -1;
+    }
+    return total;
+}
+
+/* 1 if turning deterministic proving on in `ctx` succeeds */
+static int make_deterministic(Context* ctx) {
+    ContextConfig config;
+    if (ZK_ContextGetConfig(ctx, &config) != 0) {
+        return 0;
+    }
+    config.deterministic = 1;
+    return ZK_ContextSetConfig(ctx, &config) == 0;
+}
+
+int main(void) {
+    Context *parent = NULL, *child = NULL;
+    ContextConfig before, after, changed;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub_a, sizeof(pub_a), priv_a, sizeof(priv_a)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub_b, sizeof(pub_b), priv_b, sizeof(priv_b)) != 0 || !sign(priv_a, blob_a) ||
+        !sign(priv_b, blob_b) || ZK_ContextCreate(&parent) != 0 || ZK_ContextTrustIssuer(parent, pub_a) != 0 ||
+        ZK_ContextGetConfig(parent, &before) != 0 || ZK_ContextClone(parent, &child) != 0) {
+        return 1;
+    }
+
+    /* The clone trusts a second issuer and takes smaller blobs */
+    changed = before;
+    changed.max_blob_len = 4096;
+    if (ZK_ContextTrustIssuer(child, pub_b) != 0 || ZK_ContextSetConfig(child, &changed) != 0 ||
+        ZK_ContextGetConfig(parent, &after) != 0) {
+        return 1;
+    }
+    char proof[4096];
+    int parent_config = memcmp(&before, &after, sizeof(before)) == 0;
+    int parent_trusted = trusted_count(parent), child_trusted = trusted_count(child);
+    int parent_b = prove(parent, blob_b, pub_b, 7, proof);
+    int child_b = prove(child, blob_b, pub_b, 7, proof);
+    printf("  parent config unchanged %d; trusted issuers: parent %d, clone %d; second issuer's credential: "
+           "parent %d, clone %d\n",
+           parent_config, parent_trusted, child_trusted, parent_b, child_b);
+    if (!parent_config || parent_trusted != 1 || child_trusted != 2 || parent_b != -1 || child_b != 0) {
+        return 1;
+    }
+
+#ifdef STRICT
+    int refused = !make_deterministic(child);
+    ZK_ContextGetConfig(child, &after);
+    printf("  deterministic context refused %d (config kept %d)\n", refused, after.deterministic == 0);
+    ZK_ContextFree(child);
+    ZK_ContextFree(parent);
+    return !refused || after.deterministic != 0;
+#else
+    /* A deterministic clone repeats its proofs per nonce, and its own clone
+       with it; a sibling turned deterministic separately does not */
+    static char first[4096], again[4096], other_nonce[4096], grandchild_proof[4096], sibling_proof[4096];
+    static char fresh[2][4096];
+    Context *grandchild = NULL, *sibling = NULL;
+    if (!make_deterministic(child) || ZK_ContextClone(child, &grandchild) != 0 ||
+        ZK_ContextClone(parent, &sibling) != 0 || !make_deterministic(sibling) ||
+        prove(child, blob_a, pub_a, 7, first) != 0 || prove(child, blob_a, pub_a, 7, again) != 0 ||
+        prove(child, blob_a, pub_a, 8, other_nonce) != 0 ||
+        prove(grandchild, blob_a, pub_a, 7, grandchild_proof) != 0 ||
+        prove(sibling, blob_a, pub_a, 7, sibling_proof) != 0 || prove(parent, blob_a, pub_a, 7, fresh[0]) != 0 ||
+        prove(parent, blob_a, pub_a, 7, fresh[1]) != 0 || ZK_ContextGetConfig(parent, &after) != 0) {
+        return 1;
+    }
+    int repeated = strcmp(first, again) == 0;
+    int nonce_differs = strcmp(first, other_nonce) != 0;
+    int inherited = strcmp(first, grandchild_proof) == 0;
+    int sibling_differs = strcmp(first, sibling_proof) != 0;
+    int parent_fresh = strcmp(fresh[0], fresh[1]) != 0 && after.deterministic == 0;
+    int verified = ZK_ContextVerifyVCProof(parent, first, pub_a, NOW, 7) +
+                   ZK_ContextVerifyVCProof(parent, sibling_proof, pub_a, NOW, 7);
+    printf("  deterministic clone: repeats %d, other nonce differs %d, its clone matches %d, sibling differs %d, "
+           "parent still fresh %d, verified %d\n",
+           repeated, nonce_differs, inherited, sibling_differs, parent_fresh, verified);
+    if (!repeated || !nonce_differs || !inherited || !sibling_differs || !parent_fresh || verified != 2) {
+        return 1;
+    }
+
+    /* Created under ZK_SetDeterministicProving: proves as the global entry points */
+    static char global_proof[4096], seeded_proof[4096];
+    Context* seeded = NULL;
+    ZK_SetDeterministicProving(42);
+    int created = ZK_ContextCreate(&seeded);
+    ZK_ContextGetConfig(seeded, &after);
+    int matches = created == 0 && after.deterministic == 1 &&
+                  ZK_GenerateVCProofFromBlob(blob_a, pub_a, NULL, NOW, 9, global_proof, sizeof(global_proof)) == 0 &&
+                  prove(seeded, blob_a, pub_a, 9, seeded_proof) == 0 && strcmp(global_proof, seeded_proof) == 0;
+    ZK_SetDeterministicProving(0);
+    printf("  created under ZK_SetDeterministicProving: matches the global proof %d\n", matches);
+
+    ZK_ContextFree(seeded);
+    ZK_ContextFree(sibling);
+    ZK_ContextFree(grandchild);
+    ZK_ContextFree(child);
+    ZK_ContextFree(parent);
+    return !matches;
+#endif
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build strict "--features strict" "-DSTRICT"
+
+echo "✓ Cloned contexts leave their parent untouched and prove deterministically under their own seed"
@@ -0,0 +1,182 @@
+#!/bin/bash
+#
+# Build the library for the host and check composite credentials: each
+# group verifies under its own issuer's key only, adding a group leaves the
+# other signatures valid, and a group-claim proof verifies only for its
+# claim, value, issuer and nonce. With fault-injection, a prover that skips
+# its signature check proves a claim edited in the blob, and the verifier
+# rejects it: the circuit opens the claim under the group anchor the issuer
+# signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeCompositeVC(const char*, size_t, int64_t, int64_t, const char* const*, const char* const*, size_t,
+                         char*, size_t);
+int ZK_AddClaimGroup(const char*, const char*, size_t, const char*, const char* const*, const char* const*, size_t,
+                     char*, size_t);
+int ZK_SignClaimGroup(const char*, size_t, const char*, char*, size_t);
+int ZK_VerifyCompositeVC(const char*, const char* const*, size_t, uint64_t);
+int ZK_GenerateGroupClaimProof(const char*, size_t, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyGroupClaimProof(const char*, const char*, const char*, const char*, uint64_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_KEY_MISMATCH -17
+#define NOW 1700000000LL
+
+static char hr_pub[65], hr_priv[65], sec_pub[65], sec_priv[65];
+static char blob[8192], proof[2048];
+
+static int prove(const char* vc, size_t group, const char* key, const char* pub, uint64_t time) {
+    return ZK_GenerateGroupClaimProof(vc, group, key, pub, time, 7, proof, sizeof(proof));
+}
+
+/* Add a group of `count` claims by `pub` to `vc` in place */
+static int add_group(char* vc, const char* issuer, const char* pub, const char** keys, const char** values,
+                     size_t count) {
+    static char out[8192];
+    if (ZK_AddClaimGroup(vc, issuer, strlen(issuer), pub, keys, values, count, out, sizeof(out)) != 0) {
+        return 0;
+    }
+    strcpy(vc, out);
+    return 1;
+}
+
+static int sign_group(char* vc, size_t group, const char* priv) {
+    static char out[8192];
+    int rc = ZK_SignClaimGroup(vc, group, priv, out, sizeof(out));
+    if (rc == 0) {
+        strcpy(vc, out);
+    }
+    return rc;
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(hr_pub, sizeof(hr_pub), hr_priv, sizeof(hr_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(sec_pub, sizeof(sec_pub), sec_priv, sizeof(sec_priv)) != 0) {
+        return 1;
+    }
+
+    const char* hr_keys[] = {"role", "department"};
+    const char* hr_values[] = {"engineer", "ops"};
+    const char* sec_keys[] = {"clearance"};
+    const char* sec_values[] = {"secret"};
+    if (ZK_EncodeCompositeVC("alice", 5, NOW - 86400, NOW + 86400, NULL, NULL, 0, blob, sizeof(blob)) != 0 ||
+        !add_group(blob, "hr", hr_pub, hr_keys, hr_values, 2) ||
+        !add_group(blob, "security", sec_pub, sec_keys, sec_values, 1)) {
+        return 1;
+    }
+    int mismatch = sign_group(blob, 0, sec_priv);
+    if (sign_group(blob, 0, hr_priv) != 0 || sign_group(blob, 1, sec_priv) != 0) {
+        return 1;
+    }
+    const char* in_order[] = {hr_pub, sec_pub};
+    const char* swapped[] = {sec_pub, hr_pub};
+    int whole = ZK_VerifyCompositeVC(blob, in_order, 2, NOW);
+    int crossed = ZK_VerifyCompositeVC(blob, swapped, 2, NOW);
+    printf("  wrong signing key %d, composite %d, keys swapped %d\n", mismatch, whole, crossed);
+    if (mismatch != ZK_ERR_KEY_MISMATCH || whole != 1 || crossed != 0) {
+        return 1;
+    }
+
+    /* A claim verifies for its issuer, key, value and nonce only */
+    if (prove(blob, 0, "role", hr_pub, NOW) != 0) {
+        return 1;
+    }
+    int valid = ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "engineer", 7);
+    int value = ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "director", 7);
+    int key = ZK_VerifyGroupClaimProof(proof, hr_pub, "department", "engineer", 7);
+    int issuer = ZK_VerifyGroupClaimProof(proof, sec_pub, "role", "engineer", 7);
+    int nonce = ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "engineer", 8);
+    printf("  role: valid %d, other value %d, other key %d, other issuer %d, other nonce %d\n",
+           valid, value, key, issuer, nonce);
+    if (valid != 1 || value != 0 || key != 0 || issuer != 0 || nonce != 0) {
+        return 1;
+    }
+    if (prove(blob, 1, "clearance", sec_pub, NOW) != 0) {
+        return 1;
+    }
+    int security = ZK_VerifyGroupClaimProof(proof, sec_pub, "clearance", "secret", 7);
+    int as_hr = ZK_VerifyGroupClaimProof(proof, hr_pub, "clearance", "secret", 7);
+    printf("  clearance: valid %d, under the other group's key %d\n", security, as_hr);
+    if (security != 1 || as_hr != 0) {
+        return 1;
+    }
+
+    /* Wrong key or group, absent claim, expired credential, unsigned group */
+    int other_key = prove(blob, 0, "role", sec_pub, NOW);
+    int other_group = prove(blob, 1, "role", hr_pub, NOW);
+    int absent = prove(blob, 0, "salary", hr_pub, NOW);
+    int expired = prove(blob, 0, "role", hr_pub, NOW + 2 * 86400);
+    const char* extra_keys[] = {"badge"};
+    const char* extra_values[] = {"blue"};
+    if (!add_group(blob, "facilities", hr_pub, extra_keys, extra_values, 1)) {
+        return 1;
+    }
+    int unsigned_group = prove(blob, 2, "badge", hr_pub, NOW);
+    int kept = prove(blob, 0, "role", hr_pub, NOW);
+    int kept_valid = kept == 0 ? ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "engineer", 7) : -1;
+    printf("  other key %d, other group %d, absent %d, expired %d, unsigned group %d, "
+           "signed group after an addition %d (verifies %d)\n",
+           other_key, other_group, absent, expired, unsigned_group, kept, kept_valid);
+    if (other_key != -1 || other_group != -1 || absent != -1 || expired != ZK_ERR_VC_EXPIRED ||
+        unsigned_group != -1 || kept != 0 || kept_valid != 1) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The role edited in place from "engineer" to "director", the HR
+     * signature kept: the checked prover refuses it, one that skips its
+     * checks proves it under an anchor HR never signed */
+    static char forged[8192];
+    strcpy(forged, blob);
+    char* role = strstr(forged, "656e67696e656572");
+    if (role == NULL) {
+        return 1;
+    }
+    memcpy(role, "6469726563746f72", 16);
+    int checked = prove(forged, 0, "role", hr_pub, NOW);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, 0, "role", hr_pub, NOW);
+    int forged_valid = forgery == 0 ? ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "director", 7) : -1;
+    int honest = prove(blob, 0, "role", hr_pub, NOW);
+    int honest_valid = honest == 0 ? ZK_VerifyGroupClaimProof(proof, hr_pub, "role", "engineer", 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited claim: checked prover %d; unchecked prover %d (verifies %d), signed claim %d (verifies %d)\n",
+           checked, forgery, forged_valid, honest, honest_valid);
+    if (checked != -1 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Group claims verify only under the anchor their issuer signed"
@@ -0,0 +1,102 @@
+#!/bin/bash
+#
+# Build the library for the host and replay the published conformance
+# vectors: the standard and strict builds pass or skip every vector in
+# conformance/vectors.json with none failing; a copy with one expected hash
+# changed fails exactly that vector; and a truncated file, an unknown
+# vectors_version or a report buffer too small are refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+VECTORS=conformance/vectors.json
+COUNT=$(grep -c '"kind":' "$VECTORS")
+FIRST_HASH=$(grep -m1 -o '"hash": "[0-9a-f]*"' "$VECTORS" | cut -d'"' -f4)
+EDITED_HASH=$(printf '%s' "$FIRST_HASH" | tr '0-9a-f' '1-9a-f0')
+sed "0,/$FIRST_HASH/s//$EDITED_HASH/" "$VECTORS" > "$WORK_DIR/edited.json"
+head -c 4096 "$VECTORS" > "$WORK_DIR/truncated.json"
+sed 's/"vectors_version": 1/"vectors_version": 99/' "$VECTORS" > "$WORK_DIR/version.json"
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_RunConformance(const char*, size_t, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+
+static char vectors[1 << 22], report[1 << 20];
+
+static int run(const char* path, char* out, size_t out_size) {
+    FILE* f = fopen(path, "rb");
+    if (f == NULL) {
+        return -100;
+    }
+    size_t len = fread(vectors, 1, sizeof(vectors), f);
+    fclose(f);
+    return ZK_RunConformance(vectors, len, out, out_size);
+}
+
+static long field(const char* name) {
+    const char* at = strstr(report, name);
+    return at ? atol(at + strlen(name)) : -1;
+}
+
+/* argv: vectors, edited copy, truncated copy, unknown version, count, first id */
+int main(int argc, char** argv) {
+    if (argc < 7) {
+        return 1;
+    }
+    long count = atol(argv[5]);
+    char failed_id[256];
+    snprintf(failed_id, sizeof(failed_id), "\"id\":\"%s\",\"status\":\"fail\"", argv[6]);
+
+    int failed = run(argv[1], report, sizeof(report));
+    long passed = field("\"passed\":"), skipped = field("\"skipped\":");
+    printf("  published vectors: %d failed, %ld passed, %ld skipped of %ld\n", failed, passed, skipped, count);
+    if (failed != 0 || passed + skipped != count || strstr(report, "\"status\":\"fail\"") != NULL) {
+        return 1;
+    }
+
+    int edited = run(argv[2], report, sizeof(report));
+    int named = strstr(report, failed_id) != NULL;
+    printf("  one expected hash changed: %d failed, %s named %d\n", edited, argv[6], named);
+    if (edited != 1 || !named) {
+        return 1;
+    }
+
+    char small[64];
+    int truncated = run(argv[3], report, sizeof(report));
+    int version = run(argv[4], report, sizeof(report));
+    int too_small = run(argv[1], small, sizeof(small));
+    int null_input = ZK_RunConformance(NULL, 0, report, sizeof(report));
+    printf("  truncated %d, unknown version %d, small report buffer %d, NULL %d\n", truncated, version, too_small,
+           null_input);
+    return truncated != -1 || version != -1 || too_small != ZK_ERR_BUFFER_TOO_SMALL || null_input != -1;
+}
+EOF
+
+FIRST_ID=$(grep -m1 -o '"id": "[^"]*"' "$VECTORS" | cut -d'"' -f4)
+
+check_build() {
+    local name="$1" features="$2"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" "$VECTORS" "$WORK_DIR/edited.json" "$WORK_DIR/truncated.json" \
+        "$WORK_DIR/version.json" "$COUNT" "$FIRST_ID" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build strict "--features strict"
+
+echo "✓ The library replays its published conformance vectors"
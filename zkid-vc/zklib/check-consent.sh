@@ -0,0 +1,182 @@
+#!/bin/bash
+#
+# Build the library for the host and check consent receipts: answering a
+# request records a receipt in the credential store naming the audience,
+# time, nonce, presentation digest, the claim keys disclosed and the
+# predicates, never a claim value. A third party verifies it against the
+# holder's public key alone; another key or a changed byte fails, and a
+# presentation for another nonce or a malformed request records nothing,
+# and ZK_GetLastError says why.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+int ZK_CreateConsentReceipt(CredentialStore*, const char*, size_t, const char*, size_t, const char*, const char*,
+                            char*, size_t);
+int ZK_ListConsentReceipts(CredentialStore*, char*, size_t);
+int ZK_VerifyConsentReceipt(const char*, const char*);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static char pub[65], priv[65], holder_pub[65], holder_priv[65], other_pub[65], other_priv[65];
+static char encoded[8192], blob[8192], proof[4096], presentation[16384], other_presentation[16384];
+static char request[4096], bad_request[4096], receipt[4096], full_receipt[4096], list[16384];
+
+/* Whether hex `text` contains the bytes of `value` */
+static int contains(const char* hex_text, const char* value) {
+    char hex[256] = {0};
+    for (size_t i = 0; value[i] != '\0'; i++) {
+        snprintf(hex + 2 * i, 3, "%02x", (unsigned char)value[i]);
+    }
+    return strstr(hex_text, hex) != NULL;
+}
+
+/* The last error contains `text` */
+static int says(const char* text) {
+    char message[256];
+    return ZK_GetLastError(message, sizeof(message)) == 0 && strstr(message, text) != NULL;
+}
+
+static int create(CredentialStore* store, const char* p, const char* r, const char* vc_id, char* out, size_t size) {
+    return ZK_CreateConsentReceipt(store, p, strlen(p), r, strlen(r), vc_id, holder_priv, out, size);
+}
+
+int main(int argc, char** argv) {
+    char path[512], vc_id[128];
+    CredentialStore* store = NULL;
+    const char* claim_keys[] = {"role", "age"};
+    const char* claim_values[] = {"engineer", "30"};
+    const char* request_keys[] = {"claims", "require.age", "audience"};
+    const char* request_values[] = {"role", ">=18", "shop.example"};
+    const char* bad_values[] = {"role", ">=eighteen", "shop.example"};
+    const char* presentation_keys[] = {"audience"};
+    const char* presentation_values[] = {"gateway.example"};
+    snprintf(path, sizeof(path), "%s/wallet.store", argc > 1 ? argv[1] : ".");
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(holder_pub, sizeof(holder_pub), holder_priv, sizeof(holder_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, claim_keys, claim_values, 2, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_StoreOpen(path, raw_key, sizeof(raw_key), ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_StoreAddVC(store, blob, vc_id, sizeof(vc_id)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, presentation_keys, presentation_values, 1, presentation,
+                              sizeof(presentation)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 8, presentation_keys, presentation_values, 1, other_presentation,
+                              sizeof(other_presentation)) != 0 ||
+        ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, request_keys, request_values, 3, request,
+                                     sizeof(request)) != 0 ||
+        ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, request_keys, bad_values, 3, bad_request,
+                                     sizeof(bad_request)) != 0) {
+        return 1;
+    }
+
+    /* The keys the request names, then every key of the presented credential */
+    int named = create(store, presentation, request, NULL, receipt, sizeof(receipt));
+    int full = create(store, presentation, request, vc_id, full_receipt, sizeof(full_receipt));
+    int no_values = !contains(receipt, "engineer") && !contains(full_receipt, "engineer") &&
+                    !contains(full_receipt, "alice");
+    printf("  receipts: named keys %d, all keys %d, no claim values %d\n", named, full, no_values);
+    if (named != 0 || full != 0 || !no_values) {
+        return 1;
+    }
+
+    /* Anyone with the holder's public key can check them */
+    char altered[4096];
+    strcpy(altered, receipt);
+    altered[40] = altered[40] == '0' ? '1' : '0';
+    int valid = ZK_VerifyConsentReceipt(receipt, holder_pub) == 1 &&
+                ZK_VerifyConsentReceipt(full_receipt, holder_pub) == 1;
+    int other_holder = ZK_VerifyConsentReceipt(receipt, other_pub);
+    int changed = ZK_VerifyConsentReceipt(altered, holder_pub);
+    int garbled = ZK_VerifyConsentReceipt("5a4b4352", holder_pub);
+    printf("  verify: holder %d, other key %d, changed byte %d, garbled %d\n", valid, other_holder, changed,
+           garbled);
+    if (!valid || other_holder != 0 || changed != 0 || garbled != 0) {
+        return 1;
+    }
+
+    /* The store lists both, oldest first, with their decoded fields */
+    if (ZK_ListConsentReceipts(store, list, sizeof(list)) != 0) {
+        return 1;
+    }
+    const char* first = strstr(list, "\"claims\":[\"role\"],");
+    const char* second = strstr(list, "\"claims\":[\"age\",\"role\"");
+    int fields = strstr(list, "\"audience\":\"gateway.example\"") != NULL &&
+                 strstr(list, "\"predicates\":[\"age>=18\"]") != NULL &&
+                 strstr(list, "\"timestamp\":1700000000") != NULL && strstr(list, "\"nonce\":7") != NULL &&
+                 strstr(list, receipt) != NULL && strstr(list, full_receipt) != NULL;
+    printf("  list: named first %d, fields %d\n", first != NULL && second != NULL && first < second, fields);
+    if (first == NULL || second == NULL || first > second || !fields) {
+        return 1;
+    }
+
+    /* Nothing is recorded for a mismatched nonce or a malformed request */
+    int other_nonce = create(store, other_presentation, request, NULL, receipt, sizeof(receipt));
+    int nonce_said = says("nonce");
+    int bad_predicate = create(store, presentation, bad_request, NULL, receipt, sizeof(receipt));
+    int predicate_said = says("predicate");
+    int unknown_id = create(store, presentation, request, "00", receipt, sizeof(receipt));
+    int unknown_said = says("not stored");
+    char short_list[32];
+    int short_buffer = ZK_ListConsentReceipts(store, short_list, sizeof(short_list));
+    ZK_ListConsentReceipts(store, list, sizeof(list));
+    size_t stored = 0;
+    for (const char* at = list; (at = strstr(at, "\"receipt\":")) != NULL; at++) {
+        stored++;
+    }
+    int null_store = ZK_CreateConsentReceipt(NULL, presentation, strlen(presentation), request, strlen(request), NULL,
+                                             holder_priv, receipt, sizeof(receipt));
+    int null_said = says("NULL");
+    int bad_key = ZK_VerifyConsentReceipt(receipt, "zz");
+    int key_said = says("holder_public_key");
+    printf("  other nonce %d, malformed predicate %d, unknown credential %d, short list %d, %zu stored; "
+           "NULL store %d, bad key %d; last error named each %d\n",
+           other_nonce, bad_predicate, unknown_id, short_buffer, stored, null_store, bad_key,
+           nonce_said && predicate_said && unknown_said && null_said && key_said);
+    ZK_StoreClose(store);
+    return other_nonce != -1 || bad_predicate != ZK_ERR_CORRUPT || unknown_id != 1 ||
+           short_buffer != ZK_ERR_BUFFER_TOO_SMALL || stored != 2 || null_store != -1 || bad_key != -1 ||
+           !nonce_said || !predicate_said || !unknown_said || !null_said || !key_said;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Consent receipts record what was disclosed, verifiable by the holder's key"
@@ -0,0 +1,168 @@
+#!/bin/bash
+#
+# Build the library for the host and check contexts with keys of their own:
+# two contexts set up without ZK_Init each verify only their own proofs, a
+# third takes one's key through export and import, and verification in one
+# context keeps completing on another thread while the other proves. The
+# global entry points, once ZK_Init runs, verify while a global proof is in
+# flight too.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdatomic.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct Context Context;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_ContextCreate(Context**);
+int ZK_ContextInit(Context*);
+int ZK_ContextGenerateVCProof(const Context*, const char*, size_t, const char*, size_t, int64_t, int64_t,
+                              const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_ContextVerifyVCProof(const Context*, const char*, const char*, uint64_t, uint64_t);
+int ZK_ContextExportVerifyingKey(const Context*, char*, size_t);
+int ZK_ContextImportVerifyingKey(Context*, const char*);
+void ZK_ContextFree(Context*);
+
+#define NOW 150
+#define NONCE 7
+
+static char pub[65], priv[65], sig[129];
+static Context* prover_ctx;
+static Context* verifier_ctx;
+static char prover_proof[1024], verifier_proof[1024];
+static atomic_int proving, verified_while_proving;
+
+static int context_prove(const Context* ctx, char* out) {
+    return ZK_ContextGenerateVCProof(ctx, "alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, NONCE, out, 1024);
+}
+
+static int global_prove(char* out) {
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, NONCE, out, 1024, NULL);
+}
+
+/* Prove once, flagging the proof as in flight */
+static void* prove_thread(void* global) {
+    atomic_store(&proving, 1);
+    int code = global ? global_prove(prover_proof) : context_prove(prover_ctx, prover_proof);
+    atomic_store(&proving, 0);
+    return (void*)(intptr_t)code;
+}
+
+/* Verify in a loop while the other thread proves, counting valid results */
+static void* verify_thread(void* global) {
+    while (!atomic_load(&proving)) {
+    }
+    while (atomic_load(&proving)) {
+        int valid = global ? ZK_VerifyVCProof(verifier_proof, pub, NOW, NONCE)
+                           : ZK_ContextVerifyVCProof(verifier_ctx, verifier_proof, pub, NOW, NONCE);
+        if (valid == 1 && atomic_load(&proving)) {
+            atomic_fetch_add(&verified_while_proving, 1);
+        }
+    }
+    return NULL;
+}
+
+/* Prove on one thread while verifying on another */
+static int concurrently(int global) {
+    pthread_t prover, verifier;
+    void* code;
+    atomic_store(&verified_while_proving, 0);
+    pthread_create(&verifier, NULL, verify_thread, (void*)(intptr_t)global);
+    pthread_create(&prover, NULL, prove_thread, (void*)(intptr_t)global);
+    pthread_join(prover, &code);
+    pthread_join(verifier, NULL);
+    return (int)(intptr_t)code;
+}
+
+int main(void) {
+    Context *a, *b, *v;
+    char proof_a[1024], proof_b[1024], vk[2048];
+    if (ZK_GenerateIssuerKeypair(pub, 65, priv, 65) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0) {
+        return 1;
+    }
+
+    /* Two contexts with keys of their own, no ZK_Init */
+    if (ZK_ContextCreate(&a) != 0 || ZK_ContextCreate(&b) != 0 || ZK_ContextCreate(&v) != 0) {
+        return 1;
+    }
+    int keyless = context_prove(a, proof_a);
+    if (ZK_ContextInit(a) != 0 || ZK_ContextInit(b) != 0) {
+        return 1;
+    }
+    if (context_prove(a, proof_a) != 0 || context_prove(b, proof_b) != 0) {
+        return 1;
+    }
+    int own_a = ZK_ContextVerifyVCProof(a, proof_a, pub, NOW, NONCE);
+    int own_b = ZK_ContextVerifyVCProof(b, proof_b, pub, NOW, NONCE);
+    int cross_a = ZK_ContextVerifyVCProof(b, proof_a, pub, NOW, NONCE);
+    int cross_b = ZK_ContextVerifyVCProof(a, proof_b, pub, NOW, NONCE);
+    int global = ZK_VerifyVCProof(proof_a, pub, NOW, NONCE);
+    printf("  keyless prove %d; own key %d %d, other key %d %d, global %d\n", keyless, own_a, own_b, cross_a,
+           cross_b, global);
+    if (keyless != -2 || own_a != 1 || own_b != 1 || cross_a != 0 || cross_b != 0 || global != 0) {
+        return 1;
+    }
+
+    /* A verifier context takes a's key */
+    int before = ZK_ContextVerifyVCProof(v, proof_a, pub, NOW, NONCE);
+    if (ZK_ContextExportVerifyingKey(a, vk, sizeof(vk)) != 0 || ZK_ContextImportVerifyingKey(v, vk) != 0) {
+        return 1;
+    }
+    int imported_a = ZK_ContextVerifyVCProof(v, proof_a, pub, NOW, NONCE);
+    int imported_b = ZK_ContextVerifyVCProof(v, proof_b, pub, NOW, NONCE);
+    int bad_import = ZK_ContextImportVerifyingKey(v, "zz");
+    printf("  imported key: before %d, a %d, b %d; bad key %d\n", before, imported_a, imported_b, bad_import);
+    if (before != 0 || imported_a != 1 || imported_b != 0 || bad_import != -1) {
+        return 1;
+    }
+
+    /* Verify in b while a proves */
+    prover_ctx = a;
+    verifier_ctx = b;
+    memcpy(verifier_proof, proof_b, sizeof(proof_b));
+    int proved = concurrently(0);
+    int contexts = atomic_load(&verified_while_proving);
+    printf("  contexts: proof %d, %d verifications during it\n", proved, contexts);
+    if (proved != 0 || contexts == 0 || ZK_ContextVerifyVCProof(a, prover_proof, pub, NOW, NONCE) != 1) {
+        return 1;
+    }
+
+    /* The global entry points verify while a global proof is in flight */
+    if (ZK_Init() != 0 || global_prove(verifier_proof) != 0) {
+        return 1;
+    }
+    proved = concurrently(1);
+    int globals = atomic_load(&verified_while_proving);
+    printf("  globals: proof %d, %d verifications during it\n", proved, globals);
+    ZK_ContextFree(a);
+    ZK_ContextFree(b);
+    ZK_ContextFree(v);
+    return proved != 0 || globals == 0 || ZK_VerifyVCProof(prover_proof, pub, NOW, NONCE) != 1;
+}
+EOF
+
+echo "Checking build: standard"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Contexts prove and verify under their own keys without blocking each other"
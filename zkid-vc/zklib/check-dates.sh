@@ -0,0 +1,118 @@
+#!/bin/bash
+#
+# Build the library for the host and check signed credential dates: a
+# credential issued before 1970 and one expiring on 9999-12-31 sign, verify
+# and prove; the last second of the default window is accepted and the next
+# refused; the u64 entry points refuse values above i64::MAX instead of
+# reading them as negative dates; an expiry before the issue date is
+# refused; and ZK_SetDateWindow narrows what is accepted.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_EncodeVC_I64(const char*, size_t, const char*, size_t, int64_t, int64_t, const char* const*,
+                    const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_VerifyVCBlobAt(const char*, const char*, const char*, uint64_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetDateWindow(int64_t, int64_t);
+
+#define NOW 1700000000ULL
+#define DAY 86400LL
+#define BEFORE_1970 (-1000000000LL)  /* 1938-04-24 */
+#define MIN_DATE (-62135596800LL)    /* 0001-01-01T00:00:00Z */
+#define MAX_DATE 253402300799LL      /* 9999-12-31T23:59:59Z */
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096];
+
+static int encode(int64_t issue, int64_t expiry) {
+    const char* keys[] = {"record"};
+    const char* values[] = {"birth"};
+    return ZK_EncodeVC_I64("alice", 5, "registry", 8, issue, expiry, keys, values, 1, NULL, encoded,
+                           sizeof(encoded));
+}
+
+/* Encode, sign, verify and prove at NOW; 1 if every step succeeds */
+static int round_trip(int64_t issue, int64_t expiry) {
+    return encode(issue, expiry) == 0 && ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) == 0 &&
+           ZK_VerifyVCBlob(blob, pub, NULL) == 1 && ZK_VerifyVCBlobAt(blob, pub, NULL, NOW) == 1 &&
+           ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) == 0 &&
+           ZK_VerifyVCProof(proof, pub, NOW, 7) == 1;
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0) {
+        return 1;
+    }
+
+    int historical = round_trip(BEFORE_1970, NOW + DAY);
+    int far_future = round_trip(NOW - DAY, MAX_DATE);
+    int whole_window = encode(MIN_DATE, MAX_DATE);
+    int below = encode(MIN_DATE - 1, NOW);
+    int above = encode(NOW, MAX_DATE + 1);
+    int reversed = encode(NOW, NOW - 1);
+    printf("  before 1970 %d, until 9999 %d, whole window %d, below %d, above %d, reversed %d\n", historical,
+           far_future, whole_window, below, above, reversed);
+    if (!historical || !far_future || whole_window != 0 || below != -1 || above != -1 || reversed != -1) {
+        return 1;
+    }
+
+    /* A historical credential is not active before it was issued */
+    if (encode(BEFORE_1970, BEFORE_1970 + DAY) != 0 || ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+    int expired_long_ago = ZK_VerifyVCBlobAt(blob, pub, NULL, NOW);
+    printf("  expired in 1938, checked now: %d\n", expired_long_ago);
+    if (expired_long_ago != 0) {
+        return 1;
+    }
+
+    /* u64 dates above i64::MAX are refused, not wrapped */
+    const char* keys[] = {"record"};
+    const char* values[] = {"birth"};
+    int wrapped = ZK_EncodeVC("alice", 5, "registry", 8, NOW, (uint64_t)-1, keys, values, 1, NULL, encoded,
+                              sizeof(encoded));
+    int wrapped_issue = ZK_EncodeVC("alice", 5, "registry", 8, (uint64_t)INT64_MAX + 1, NOW, keys, values, 1, NULL,
+                                    encoded, sizeof(encoded));
+    printf("  u64 expiry 2^64-1 %d, u64 issue 2^63 %d\n", wrapped, wrapped_issue);
+    if (wrapped != -1 || wrapped_issue != -1) {
+        return 1;
+    }
+
+    /* A window from 1970 on refuses the historical credential */
+    int narrowed = ZK_SetDateWindow(0, MAX_DATE);
+    int historical_narrowed = encode(BEFORE_1970, NOW);
+    int recent_narrowed = encode(NOW - DAY, NOW + DAY);
+    int inverted = ZK_SetDateWindow(1, 0);
+    ZK_SetDateWindow(MIN_DATE, MAX_DATE);
+    int restored = encode(BEFORE_1970, NOW);
+    printf("  window from 1970: set %d, before 1970 %d, recent %d; inverted window %d; restored %d\n", narrowed,
+           historical_narrowed, recent_narrowed, inverted, restored);
+    return narrowed != 0 || historical_narrowed != -1 || recent_narrowed != 0 || inverted != -1 || restored != 0;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Credential dates cover 0001 to 9999 and refuse wrapped or reversed values"
@@ -0,0 +1,129 @@
+#!/bin/bash
+#
+# Build the library for the host and check did:key issuer identifiers against
+# the test vectors of the did:key method specification: each Ed25519 did:key
+# names the key it lists, and the key formats back to the same DID. A DID
+# with another multibase prefix, a character outside base58btc or another
+# multicodec (x25519-pub) is refused with ZK_ERR_BAD_DID. A credential whose
+# issuer is a did:key is signed and verified with that DID standing in for
+# the hex key, and signing it with another key is refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_AddTrustedIssuer(const char*, const char*);
+int ZK_PublicKeyToDidKey(const char*, char*, size_t);
+int ZK_DidKeyToPublicKey(const char*, char*, size_t);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_BAD_DID -38
+#define ISSUED 1704067200  /* 2024-01-01T00:00:00Z */
+#define EXPIRES 1735689599 /* 2024-12-31T23:59:59Z */
+
+/* Ed25519 test vectors of the did:key method specification */
+static const char* const VECTORS[][2] = {
+    {"did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+     "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29"},
+    {"did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+     "2e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6"},
+    {"did:key:z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH",
+     "94966b7c08e405775f8de6cc1c4508f6eb227403e1025b2c8ad2d7477398c5b2"},
+};
+
+/* Refused: x25519-pub keys (one from the specification), other multibase
+ * prefixes, a '0' (not base58btc) and a truncated DID */
+static const char* const REFUSED[] = {
+    "did:key:z6LSeu9HkTHSfLLeUs2nnzUSNedgDUevfNQgQjQC23ZCit6F",
+    "did:key:z6LSbgBAXJos6Tik6PNmXeWxKbDUr9Y7hcB9syigVTeXiNmm",
+    "did:key:u7QE7aie8zrakLWKjqNAqbw1zZTIVdx3iQ6Y6wEihi1naKQ",
+    "did:key:6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+    "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDoo0p",
+    "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mD",
+};
+
+int main(void) {
+    static char pub[65], priv[65], other_pub[65], other_priv[65], did[64], other_did[64], hex[65], message[256];
+    static char unsigned_blob[4096], blob[4096], forged[4096];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+
+    /* Vectors, both ways */
+    int vectors_ok = 1;
+    for (size_t i = 0; i < sizeof(VECTORS) / sizeof(VECTORS[0]); i++) {
+        int to_key = ZK_DidKeyToPublicKey(VECTORS[i][0], hex, sizeof(hex)) == 0 && strcmp(hex, VECTORS[i][1]) == 0;
+        int to_did = ZK_PublicKeyToDidKey(VECTORS[i][1], did, sizeof(did)) == 0 && strcmp(did, VECTORS[i][0]) == 0;
+        int same = ZK_PublicKeyToDidKey(VECTORS[i][0], did, sizeof(did)) == 0 && strcmp(did, VECTORS[i][0]) == 0;
+        printf("  %s: to key %d, to did %d, did as key %d\n", VECTORS[i][0], to_key, to_did, same);
+        vectors_ok &= to_key && to_did && same;
+    }
+    int short_out = ZK_DidKeyToPublicKey(VECTORS[0][0], hex, 64);
+    printf("  63-character output buffer %d\n", short_out);
+    vectors_ok &= short_out == ZK_ERR_BUFFER_TOO_SMALL;
+
+    /* Refusals */
+    int refused_ok = 1;
+    for (size_t i = 0; i < sizeof(REFUSED) / sizeof(REFUSED[0]); i++) {
+        int to_key = ZK_DidKeyToPublicKey(REFUSED[i], hex, sizeof(hex));
+        ZK_GetLastError(message, sizeof(message));
+        int as_key = ZK_PublicKeyToDidKey(REFUSED[i], did, sizeof(did));
+        int trusted = ZK_AddTrustedIssuer("refused", REFUSED[i]);
+        printf("  %s: %d (%s), as key %d, trusted %d\n", REFUSED[i], to_key, message, as_key, trusted);
+        refused_ok &= to_key == ZK_ERR_BAD_DID && as_key == ZK_ERR_BAD_DID && trusted == ZK_ERR_BAD_DID;
+    }
+
+    /* A credential issued by a did:key */
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_PublicKeyToDidKey(pub, did, sizeof(did)) != 0 ||
+        ZK_PublicKeyToDidKey(other_pub, other_did, sizeof(other_did)) != 0 ||
+        ZK_EncodeVC("alice", 5, did, strlen(did), ISSUED, EXPIRES, keys, values, 1, NULL, unsigned_blob,
+                    sizeof(unsigned_blob)) != 0) {
+        return 1;
+    }
+    int signed_ok = ZK_SignVCBlob(unsigned_blob, priv, blob, sizeof(blob));
+    int by_did = ZK_VerifyVCBlob(blob, did, NULL);
+    int by_hex = ZK_VerifyVCBlob(blob, pub, NULL);
+    int by_other = ZK_VerifyVCBlob(blob, other_did, NULL);
+    int by_bad_did = ZK_VerifyVCBlob(blob, REFUSED[0], NULL);
+    int trusted = ZK_AddTrustedIssuer("did-issuer", did);
+    printf("  did:key issuer: signed %d, verifies under did %d, hex %d, other did %d, x25519 did %d, trusted %d\n",
+           signed_ok, by_did, by_hex, by_other, by_bad_did, trusted);
+    int issuer_ok = signed_ok == 0 && by_did == 1 && by_hex == 1 && by_other == 0 && by_bad_did == 0 && trusted == 0;
+
+    /* The issuer names another key */
+    int wrong_signer = ZK_SignVCBlob(unsigned_blob, other_priv, forged, sizeof(forged));
+    if (ZK_EncodeVC("alice", 5, other_did, strlen(other_did), ISSUED, EXPIRES, keys, values, 1, NULL,
+                    unsigned_blob, sizeof(unsigned_blob)) != 0) {
+        return 1;
+    }
+    int named_other = ZK_SignVCBlob(unsigned_blob, priv, forged, sizeof(forged));
+    printf("  signed by another key %d, issuer naming another key %d\n", wrong_signer, named_other);
+    int mismatch_ok = wrong_signer == -1 && named_other == -1;
+
+    return !vectors_ok || !refused_ok || !issuer_ok || !mismatch_ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ did:key identifiers match the specification vectors and bind credential issuers to their keys"
@@ -0,0 +1,190 @@
+#!/bin/bash
+#
+# Build the library for the host and check selective claim disclosure over
+# credentials of 1, 2, 3 and 5 claims: every claim can be disclosed under
+# the signed claims root, and a proof verifies only for that claim, root,
+# issuer and nonce. Credentials whose signature does not cover the root
+# (version 5 blobs) cannot disclose single claims. With fault-injection, a
+# prover that skips its pre-checks discloses a claim of a blob whose claim
+# was edited under the issuer's signature, and the verifier rejects it: the
+# proof opens the anchor the issuer signed, root included.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_ComputeClaimsRoot(const char*, char*, size_t);
+int ZK_GenerateClaimDisclosureProof(const char*, const char*, const char*, const char*, const char*,
+                                    uint64_t, uint64_t, char*, size_t, char*, size_t);
+int ZK_VerifyClaimDisclosureProof(const char*, const char*, const char*, const char*, const char*, uint64_t);
+int ZK_RunConstraintAudit(char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define NOW 1700000000ULL
+
+static const char* KEYS[] = {"role", "department", "clearance", "site", "zkid:schema"};
+static const char* VALUES[] = {"engineer", "platform", "secret", "berlin", "employee/v1"};
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+
+static int issue(size_t count, char* blob, size_t size) {
+    static char unsigned_blob[8192];
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, count, NULL,
+                       unsigned_blob, sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, blob, size) == 0;
+}
+
+/* Disclose every claim of a credential of `count` claims; `other_root` is
+ * the root of a credential with other claims */
+static int check_tree(size_t count, const char* other_root) {
+    static char blob[8192], root[65], computed[65], proof[2048];
+    if (!issue(count, blob, sizeof(blob)) || ZK_ComputeClaimsRoot(blob, computed, sizeof(computed)) != 0) {
+        return 0;
+    }
+    for (size_t i = 0; i < count; i++) {
+        int rc = ZK_GenerateClaimDisclosureProof(blob, pub, NULL, KEYS[i], VALUES[i], NOW, 7,
+                                                 proof, sizeof(proof), root, sizeof(root));
+        if (rc != 0 || strcmp(root, computed) != 0) {
+            printf("  %zu claims, claim %zu: prove %d\n", count, i, rc);
+            return 0;
+        }
+        int valid = ZK_VerifyClaimDisclosureProof(proof, pub, root, KEYS[i], VALUES[i], 7);
+        int value = ZK_VerifyClaimDisclosureProof(proof, pub, root, KEYS[i], "manager", 7);
+        int key = ZK_VerifyClaimDisclosureProof(proof, pub, root, "title", VALUES[i], 7);
+        int neighbour = ZK_VerifyClaimDisclosureProof(proof, pub, root, KEYS[(i + 1) % 5], VALUES[(i + 1) % 5], 7);
+        int tree = ZK_VerifyClaimDisclosureProof(proof, pub, other_root, KEYS[i], VALUES[i], 7);
+        int issuer = ZK_VerifyClaimDisclosureProof(proof, pub2, root, KEYS[i], VALUES[i], 7);
+        int nonce = ZK_VerifyClaimDisclosureProof(proof, pub, root, KEYS[i], VALUES[i], 8);
+        printf("  %zu claims, %s: valid %d, other value %d, other key %d, other claim %d, "
+               "other root %d, other issuer %d, other nonce %d\n",
+               count, KEYS[i], valid, value, key, neighbour, tree, issuer, nonce);
+        if (valid != 1 || value != 0 || key != 0 || neighbour != 0 || tree != 0 || issuer != 0 || nonce != 0) {
+            return 0;
+        }
+    }
+
+    int absent = ZK_GenerateClaimDisclosureProof(blob, pub, NULL, "role", "manager", NOW, 7,
+                                                 proof, sizeof(proof), root, sizeof(root));
+    int foreign = ZK_GenerateClaimDisclosureProof(blob, pub2, NULL, KEYS[0], VALUES[0], NOW, 7,
+                                                  proof, sizeof(proof), root, sizeof(root));
+    printf("  %zu claims: absent claim %d, other issuer's key %d\n", count, absent, foreign);
+    return absent == -1 && foreign < 0;
+}
+
+int main(void) {
+    static char blob[8192], other_root[65], sig[129], proof[2048], root[65], report[8192];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+
+    /* Same count, other claims: only the root tells them apart */
+    const char* other_values[] = {"manager", "platform", "secret", "berlin", "employee/v1"};
+    static char unsigned_blob[8192];
+    if (ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, other_values, 5, NULL,
+                    unsigned_blob, sizeof(unsigned_blob)) != 0 ||
+        ZK_ComputeClaimsRoot(unsigned_blob, other_root, sizeof(other_root)) != 0) {
+        return 1;
+    }
+
+    size_t counts[] = {1, 2, 3, 5};
+    for (size_t i = 0; i < sizeof(counts) / sizeof(counts[0]); i++) {
+        if (!check_tree(counts[i], other_root)) {
+            return 1;
+        }
+    }
+
+    /* Signed through the field-based API: a version 5 blob, still valid,
+     * whose signature does not cover the root */
+    if (ZK_SignVCWithClaims("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 3, priv,
+                            sig, sizeof(sig), NULL) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 3, sig,
+                    blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+    int signature = ZK_VerifyVCBlob(blob, pub, NULL);
+    int rc = ZK_GenerateClaimDisclosureProof(blob, pub, NULL, KEYS[0], VALUES[0], NOW, 7,
+                                             proof, sizeof(proof), root, sizeof(root));
+    printf("  version 5 blob: signature %d, disclosure %d\n", signature, rc);
+    if (signature != 1 || rc != ZK_ERR_UNSUPPORTED_VERSION) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The role claim edited in place from "engineer" to "director", the
+     * issuer's signature kept: the checked prover refuses it, one that
+     * skips its checks proves it over a root the issuer never signed */
+    static char forged[8192], forged_root[65];
+    if (!issue(3, blob, sizeof(blob))) {
+        return 1;
+    }
+    strcpy(forged, blob);
+    char* value = strstr(forged, "656e67696e656572");
+    if (value == NULL) {
+        return 1;
+    }
+    memcpy(value, "6469726563746f72", 16);
+    int checked = ZK_GenerateClaimDisclosureProof(forged, pub, NULL, "role", "director", NOW, 7,
+                                                  proof, sizeof(proof), root, sizeof(root));
+    ZK_SkipProverChecks(1);
+    int forgery = ZK_GenerateClaimDisclosureProof(forged, pub, NULL, "role", "director", NOW, 7,
+                                                  proof, sizeof(proof), forged_root, sizeof(forged_root));
+    int forged_valid = forgery == 0 ? ZK_VerifyClaimDisclosureProof(proof, pub, forged_root, "role", "director", 7)
+                                    : -1;
+    int honest = ZK_GenerateClaimDisclosureProof(blob, pub, NULL, "role", "engineer", NOW, 7,
+                                                 proof, sizeof(proof), root, sizeof(root));
+    int honest_valid = honest == 0 ? ZK_VerifyClaimDisclosureProof(proof, pub, root, "role", "engineer", 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited claim: checked prover %d; unchecked prover %d (verifies %d), signed claim %d (verifies %d)\n",
+           checked, forgery, forged_valid, honest, honest_valid);
+    if (checked == 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+
+#ifdef DEBUG_CIRCUIT
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0 || strstr(report, "claim-disclosure") == NULL) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Single claims disclose under the issuer-signed anchor and claims root only"
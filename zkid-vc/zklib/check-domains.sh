@@ -0,0 +1,124 @@
+#!/bin/bash
+#
+# Build the library for the host and check format versions 1 and 2 against
+# each other: the credential message hash differs between them and version 1
+# keeps the untagged hash, a field-based signature verifies only under the
+# version it was made in, a VC blob signed in version 2 (blob version 8)
+# verifies whatever the setting, and proofs of formats 2 and 3 both verify
+# under either version while a format 3 proof relabelled as format 2 does
+# not.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_SetFormatVersion(uint32_t);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_VerifyVCSignature(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*,
+                         const char*);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+
+/* Hex offset of the envelope's format byte, after the validity prefix and "ZP" */
+#define FORMAT_HEX (2 * (108 + 2))
+
+static char pub[65], priv[65];
+
+static int hash(char out[65]) {
+    return ZK_ComputeVCHash("alice", 5, "issuer", 6, 100, 200, NULL, NULL, 0, out, 65, NULL);
+}
+
+static int sign(char out[129]) {
+    return ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, out, 129, NULL);
+}
+
+static int verify(const char* sig) {
+    return ZK_VerifyVCSignature("alice", 5, "issuer", 6, 100, 200, sig, pub);
+}
+
+static int prove(char* out, size_t size) {
+    char sig[129];
+    return sign(sig) == 0 &&
+           ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42, out, size, NULL) == 0;
+}
+
+static int blob(char* out, size_t size) {
+    static char unsigned_blob[4096];
+    return ZK_EncodeVC("alice", 5, "issuer", 6, 100, 200, NULL, NULL, 0, NULL, unsigned_blob,
+                       sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, out, size) == 0;
+}
+
+int main(void) {
+    static char proof_v1[1024], proof_v2[1024], blob_v1[4096], blob_v2[4096];
+    char h1[65], h2[65], h1_again[65], sig_v1[129], sig_v2[129];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0) {
+        return 1;
+    }
+    int unknown = ZK_SetFormatVersion(0) + ZK_SetFormatVersion(3);
+
+    /* Default (version 1) first, then version 2 */
+    if (hash(h1) != 0 || sign(sig_v1) != 0 || !prove(proof_v1, sizeof(proof_v1)) ||
+        !blob(blob_v1, sizeof(blob_v1))) {
+        return 1;
+    }
+    int v1_own = verify(sig_v1);
+    if (ZK_SetFormatVersion(2) != 0 || hash(h2) != 0 || sign(sig_v2) != 0 || !prove(proof_v2, sizeof(proof_v2)) ||
+        !blob(blob_v2, sizeof(blob_v2))) {
+        return 1;
+    }
+    int v2_own = verify(sig_v2), v1_under_v2 = verify(sig_v1);
+    int proofs_under_v2 = ZK_VerifyVCProof(proof_v1, pub, 150, 42) + ZK_VerifyVCProof(proof_v2, pub, 150, 42);
+    int blobs_under_v2 = ZK_VerifyVCBlob(blob_v1, pub, NULL) + ZK_VerifyVCBlob(blob_v2, pub, NULL);
+
+    if (ZK_SetFormatVersion(1) != 0 || hash(h1_again) != 0) {
+        return 1;
+    }
+    int v2_under_v1 = verify(sig_v2);
+    int proofs_under_v1 = ZK_VerifyVCProof(proof_v1, pub, 150, 42) + ZK_VerifyVCProof(proof_v2, pub, 150, 42);
+    int blobs_under_v1 = ZK_VerifyVCBlob(blob_v1, pub, NULL) + ZK_VerifyVCBlob(blob_v2, pub, NULL);
+
+    /* The envelopes name formats 2 and 3; relabelling 3 as 2 maps the public inputs untagged */
+    char f1 = proof_v1[FORMAT_HEX + 1], f2 = proof_v2[FORMAT_HEX + 1];
+    proof_v2[FORMAT_HEX + 1] = '2';
+    int relabelled = ZK_VerifyVCProof(proof_v2, pub, 150, 42);
+
+    printf("  unknown versions %d; hashes differ %d, version 1 unchanged %d\n", unknown, strcmp(h1, h2) != 0,
+           strcmp(h1, h1_again) == 0);
+    printf("  signatures: v1 %d, v2 %d, v1 under v2 %d, v2 under v1 %d\n", v1_own, v2_own, v1_under_v2,
+           v2_under_v1);
+    printf("  proof formats %c/%c: valid under v1 %d, under v2 %d, relabelled %d\n", f1, f2, proofs_under_v1,
+           proofs_under_v2, relabelled);
+    printf("  blobs: valid under v1 %d, under v2 %d\n", blobs_under_v1, blobs_under_v2);
+    return unknown != -2 || strcmp(h1, h2) == 0 || strcmp(h1, h1_again) != 0 || v1_own != 1 || v2_own != 1 ||
+           v1_under_v2 != 0 || v2_under_v1 != 0 || f1 != '2' || f2 != '3' || proofs_under_v1 != 2 ||
+           proofs_under_v2 != 2 || relabelled != 0 || blobs_under_v1 != 2 || blobs_under_v2 != 2;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Tagged and untagged hashes stay apart, and artifacts of either format version keep verifying"
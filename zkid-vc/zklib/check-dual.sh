@@ -0,0 +1,120 @@
+#!/bin/bash
+#
+# Build the library for the host and check dual-control issuance: a
+# credential of a dual-control schema is refused with
+# ZK_ERR_COSIGNATURE_MISSING by the verifier and the prover until a second,
+# distinct issuer co-signs it; the co-signature verifies under the key it
+# names or the one the verifier expects, and no other; re-signing drops it;
+# and credentials of other schemas, or of an unregistered one, need only the
+# first signature.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_CoSignVC(const char*, const char*, char*, size_t);
+int ZK_SetDualControlSchema(const char*, size_t, int);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+
+#define ZK_ERR_COSIGNATURE_MISSING -12
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], second_pub[65], second_priv[65], other_pub[65], other_priv[65];
+static char encoded[8192], signed_blob[8192], cosigned[8192], self_signed[8192], resigned[8192];
+static char plain[8192], proof[4096];
+
+static int issue(const char* schema, char* out) {
+    const char* keys[] = {"role", "zkid:schema"};
+    const char* values[] = {"treasurer", schema};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 2, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+static int prove(const char* blob) {
+    return ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof));
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(second_pub, sizeof(second_pub), second_priv, sizeof(second_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0) {
+        return 1;
+    }
+    if (ZK_SetDualControlSchema("payroll", 7, 1) != 0 || !issue("payroll", signed_blob) ||
+        !issue("badge", plain)) {
+        return 1;
+    }
+
+    /* One signature on a dual-control schema */
+    int single = ZK_VerifyVCBlob(signed_blob, pub, NULL);
+    int single_proof = prove(signed_blob);
+    int other_schema = ZK_VerifyVCBlob(plain, pub, NULL);
+    printf("  single signature: verify %d, prove %d; other schema %d\n", single, single_proof, other_schema);
+    if (single != ZK_ERR_COSIGNATURE_MISSING || single_proof != ZK_ERR_COSIGNATURE_MISSING || other_schema != 1) {
+        return 1;
+    }
+
+    /* Co-signed by a second issuer */
+    if (ZK_CoSignVC(signed_blob, second_priv, cosigned, sizeof(cosigned)) != 0) {
+        return 1;
+    }
+    int named = ZK_VerifyVCBlob(cosigned, pub, NULL);
+    int expected = ZK_VerifyVCBlob(cosigned, pub, second_pub);
+    int unexpected = ZK_VerifyVCBlob(cosigned, pub, other_pub);
+    int wrong_first = ZK_VerifyVCBlob(cosigned, second_pub, NULL);
+    int proved = prove(cosigned);
+    int verified = proved == 0 ? ZK_VerifyVCProof(proof, pub, NOW, 7) : -1;
+    printf("  co-signed: named key %d, expected key %d, other key %d, swapped issuer %d, prove %d (verify %d)\n",
+           named, expected, unexpected, wrong_first, proved, verified);
+    if (named != 1 || expected != 1 || unexpected != 0 || wrong_first != 0 || proved != 0 || verified != 1) {
+        return 1;
+    }
+
+    /* The first issuer co-signing itself, and a re-signed blob */
+    int self_cosign = ZK_CoSignVC(signed_blob, priv, self_signed, sizeof(self_signed));
+    int self_valid = self_cosign == 0 ? ZK_VerifyVCBlob(self_signed, pub, NULL) : 0;
+    if (ZK_SignVCBlob(cosigned, priv, resigned, sizeof(resigned)) != 0) {
+        return 1;
+    }
+    int resigned_valid = ZK_VerifyVCBlob(resigned, pub, NULL);
+    int unsigned_cosign = ZK_CoSignVC(encoded, second_priv, self_signed, sizeof(self_signed));
+    printf("  self co-signature %d, re-signed %d, co-signing an unsigned blob %d\n", self_valid, resigned_valid,
+           unsigned_cosign);
+    if (self_valid == 1 || resigned_valid != ZK_ERR_COSIGNATURE_MISSING || unsigned_cosign != -1) {
+        return 1;
+    }
+
+    /* Unregistered, the schema needs one signature again */
+    int unregister = ZK_SetDualControlSchema("payroll", 7, 0);
+    int relaxed = ZK_VerifyVCBlob(signed_blob, pub, NULL);
+    int relaxed_proof = prove(signed_blob);
+    printf("  unregistered: %d, verify %d, prove %d\n", unregister, relaxed, relaxed_proof);
+    return unregister != 0 || relaxed != 1 || relaxed_proof != 0;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Dual-control credentials need a co-signature from a second, distinct issuer"
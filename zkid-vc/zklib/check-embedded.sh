@@ -0,0 +1,139 @@
+#!/bin/bash
+#
+# Build the no_std embedded verifier for the host, check its C API subset,
+# verify a presentation made by the std library with it and check that the
+# heap peak stays within the documented RAM budget.
+#
+# The embedded library brings its own allocator and panic handler; the C
+# harness stands in for firmware and only has to provide the unwinding
+# personality symbol that the host's precompiled core references.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+# Heap budget for ZK_InitVerifierEmbedded plus one verification (bytes)
+RAM_BUDGET=$((128 * 1024))
+
+EMBEDDED="ZK_InitVerifierEmbedded ZK_VerifyPresentationEmbedded ZK_EmbeddedHeapStats
+ZK_PrepareVerifyingKey ZK_ExtractVerifyingKey ZK_VerifyingKeyPublicInputs"
+STD_ONLY="ZK_Init ZK_ExportVerifyingKey ZK_VerifyVCProof ZK_VerifyPresentation ZK_Cleanup"
+
+cat > "$WORK_DIR/present.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*,
+                          const char* const*, size_t, char*, size_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+
+int main(void) {
+    char pub[65], priv[65], sig[129], proof[1024], presentation[4096], vk[4096];
+    const char* keys[] = {"audience"};
+    const char* values[] = {"door-7"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0) {
+        return 1;
+    }
+    if (ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42,
+                           proof, sizeof(proof), NULL) != 0 ||
+        ZK_EncodePresentation(proof, pub, 42, keys, values, 1,
+                              presentation, sizeof(presentation)) != 0 ||
+        ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+    printf("%s\n%s\n", vk, presentation);
+    return 0;
+}
+EOF
+
+cat > "$WORK_DIR/reader.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_InitVerifierEmbedded(const uint8_t*, size_t);
+int ZK_VerifyPresentationEmbedded(const uint8_t*, size_t);
+int ZK_EmbeddedHeapStats(size_t*, size_t*, uint8_t);
+
+void rust_eh_personality(void) {}
+
+static char line[8192];
+static uint8_t vk[1024], presentation[4096];
+
+static size_t read_hex(uint8_t* out, size_t cap) {
+    if (!fgets(line, sizeof(line), stdin)) {
+        return 0;
+    }
+    size_t len = strcspn(line, "\n") / 2;
+    for (size_t i = 0; i < len && i < cap; i++) {
+        sscanf(line + 2 * i, "%2hhx", &out[i]);
+    }
+    return len <= cap ? len : 0;
+}
+
+int main(int argc, char** argv) {
+    size_t budget = argc > 1 ? (size_t)strtoull(argv[1], NULL, 10) : 0;
+    size_t vk_len = read_hex(vk, sizeof(vk));
+    size_t len = read_hex(presentation, sizeof(presentation));
+    size_t used, peak;
+
+    if (ZK_VerifyPresentationEmbedded(presentation, len) != -1 ||
+        ZK_InitVerifierEmbedded(vk, vk_len) != 0) {
+        return 1;
+    }
+    int valid = ZK_VerifyPresentationEmbedded(presentation, len);
+    ZK_EmbeddedHeapStats(&used, &peak, 0);
+
+    /* the nonce is the 8 bytes before the u32 metadata count and one entry */
+    size_t nonce_at = len - 8 - 4 - (4 + 8) - (4 + 6);
+    presentation[nonce_at] ^= 1;
+    int tampered = ZK_VerifyPresentationEmbedded(presentation, len);
+    int truncated = ZK_VerifyPresentationEmbedded(presentation, len - 1);
+
+    printf("  valid: %d, tampered nonce: %d, truncated: %d\n", valid, tampered, truncated);
+    printf("  heap peak: %zu bytes (budget %zu), in use after: %zu\n", peak, budget, used);
+    return valid == 1 && tampered == 0 && truncated == 0 && peak > 0 && peak <= budget ? 0 : 1;
+}
+EOF
+
+exported() {
+    nm -g --defined-only "$1" 2>/dev/null | grep -q " T $2$"
+}
+
+echo "Building std library"
+cargo rustc --lib --crate-type staticlib --target-dir "$WORK_DIR/target-std" -q
+cc -o "$WORK_DIR/present" "$WORK_DIR/present.c" "$WORK_DIR/target-std/debug/libzklib_vc.a" -lpthread -ldl -lm
+
+echo "Building embedded library (no_std + alloc)"
+cargo rustc --release --lib --crate-type staticlib --no-default-features --features embedded \
+    --config 'profile.release.panic="abort"' --target-dir "$WORK_DIR/target-embedded" -q
+LIB="$WORK_DIR/target-embedded/release/libzklib_vc.a"
+
+echo "Checking embedded C API"
+for sym in $EMBEDDED; do
+    exported "$LIB" "$sym" || { echo "  FAIL: $sym missing"; exit 1; }
+done
+for sym in $STD_ONLY; do
+    exported "$LIB" "$sym" && { echo "  FAIL: $sym exported"; exit 1; }
+done
+echo "  ok"
+
+echo "Verifying a std presentation with the embedded library"
+cc -o "$WORK_DIR/reader" "$WORK_DIR/reader.c" "$LIB"
+"$WORK_DIR/present" | "$WORK_DIR/reader" "$RAM_BUDGET" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Embedded verifier fits its RAM budget"
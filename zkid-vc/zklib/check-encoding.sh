@@ -0,0 +1,130 @@
+#!/bin/bash
+#
+# Build the library for the host and check proof point encodings: a proof
+# converted to uncompressed points is the size ZK_SizeOf reports, carries
+# the uncompressed flag in its envelope (bare legacy proofs stay bare and
+# are told apart by length), verifies, and converts back to the very same
+# bytes. An uncompressed point off the curve and a flag that disagrees with
+# the length are refused before any pairing.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_PrecheckProof(const char*);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_SizeOf(int, int, uint8_t);
+int ZK_ConvertProofEncoding(const char*, size_t, int, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_INVALID_POINT -32
+#define ZK_PROOF_ENCODING_COMPRESSED 0
+#define ZK_PROOF_ENCODING_UNCOMPRESSED 1
+#define ZK_SIZE_PROOF 1
+#define ZK_SIZE_PROOF_UNCOMPRESSED 2
+#define ZK_CIRCUIT_VC 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+/* A | B | C of an uncompressed proof, in hex, ends the proof */
+#define UNCOMPRESSED_HEX 512
+
+static char pub[65], priv[65], encoded[8192], blob[8192];
+
+static int convert(const char* proof, int encoding, char* out, size_t size) {
+    return ZK_ConvertProofEncoding(proof, strlen(proof), encoding, out, size);
+}
+
+/* Convert `proof` there and back; 1 if the round trip holds */
+static int round_trip(const char* name, const char* proof, uint8_t format, const char* envelope) {
+    static char uncompressed[4096], compressed[4096], again[4096];
+    int there = convert(proof, ZK_PROOF_ENCODING_UNCOMPRESSED, uncompressed, sizeof(uncompressed));
+    int back = convert(uncompressed, ZK_PROOF_ENCODING_COMPRESSED, compressed, sizeof(compressed));
+    int idempotent = convert(proof, ZK_PROOF_ENCODING_COMPRESSED, again, sizeof(again));
+    size_t len = strlen(uncompressed);
+    int sized = (int)strlen(proof) == 2 * ZK_SizeOf(ZK_SIZE_PROOF, ZK_CIRCUIT_VC, format) &&
+                (int)len == 2 * ZK_SizeOf(ZK_SIZE_PROOF_UNCOMPRESSED, ZK_CIRCUIT_VC, format);
+    int flagged = len > UNCOMPRESSED_HEX + 6 &&
+                  (envelope == NULL ? strncmp(uncompressed + len - UNCOMPRESSED_HEX - 6, "5a50", 4) != 0
+                                    : strncmp(uncompressed + len - UNCOMPRESSED_HEX - 6, envelope, 6) == 0);
+    int verified = ZK_VerifyVCProof(uncompressed, pub, NOW, 7);
+    int same = there == 0 && back == 0 && strcmp(compressed, proof) == 0;
+    printf("  %s: %zu -> %zu hex, sized %d, envelope %d, verifies %d, back to the same bytes %d, "
+           "compressed again %d\n",
+           name, strlen(proof), len, sized, flagged, verified, same, idempotent == 0 && strcmp(again, proof) == 0);
+    return sized && flagged && verified == 1 && same && idempotent == 0 && strcmp(again, proof) == 0;
+}
+
+int main(void) {
+    static char proof[4096], legacy[4096], uncompressed[4096], damaged[4096];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_SetEmitFormatVersion(1) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, legacy, sizeof(legacy)) != 0 ||
+        ZK_SetEmitFormatVersion(2) != 0) {
+        return 1;
+    }
+
+    /* Enveloped and bare proofs, both ways */
+    if (!round_trip("format 2", proof, 2, "5a5082") || !round_trip("bare legacy", legacy, 1, NULL)) {
+        return 1;
+    }
+
+    /* Uncompressed points are checked as compressed ones are */
+    if (convert(proof, ZK_PROOF_ENCODING_UNCOMPRESSED, uncompressed, sizeof(uncompressed)) != 0) {
+        return 1;
+    }
+    size_t len = strlen(uncompressed);
+    strcpy(damaged, uncompressed);
+    for (size_t i = len - 128; i < len; i += 2) {
+        memcpy(damaged + i, "03", 2);
+    }
+    int off_curve = ZK_PrecheckProof(damaged);
+    int off_curve_verify = ZK_VerifyVCProof(damaged, pub, NOW, 7);
+    int off_curve_convert = convert(damaged, ZK_PROOF_ENCODING_COMPRESSED, uncompressed, sizeof(uncompressed));
+    strcpy(damaged, proof);
+    memcpy(damaged + strlen(proof) - UNCOMPRESSED_HEX / 2 - 2, "82", 2);
+    int flag_mismatch = ZK_PrecheckProof(damaged);
+    printf("  off the curve: precheck %d, verify %d, convert %d; uncompressed flag on compressed points %d\n",
+           off_curve, off_curve_verify, off_curve_convert, flag_mismatch);
+    if (off_curve != ZK_ERR_INVALID_POINT || off_curve_verify != 0 || off_curve_convert != ZK_ERR_INVALID_POINT ||
+        flag_mismatch != ZK_ERR_CORRUPT) {
+        return 1;
+    }
+
+    int unknown = convert(proof, 2, damaged, sizeof(damaged));
+    int null_proof = ZK_ConvertProofEncoding(NULL, 0, ZK_PROOF_ENCODING_COMPRESSED, damaged, sizeof(damaged));
+    int short_out = convert(proof, ZK_PROOF_ENCODING_UNCOMPRESSED, damaged, strlen(proof));
+    printf("  unknown encoding %d, NULL proof %d, short output %d\n", unknown, null_proof, short_out);
+    return unknown != -1 || null_proof != -1 || short_out != ZK_ERR_BUFFER_TOO_SMALL;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Proofs verify and convert in either point encoding"
@@ -0,0 +1,147 @@
+#!/bin/bash
+#
+# Build the library for the host and check grace proofs: with a prover
+# grace set, a credential that expired within it proves as a grace proof
+# that ZK_VerifyVCProofWithGrace accepts as degraded within the policy's
+# allowance and nothing else does, and one that expired beyond it is
+# refused. With fault-injection, a prover that skips its pre-checks proves
+# a grace proof over an expiry the issuer never signed, and the verifier
+# rejects it: the grace circuit opens the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    const char* issuer_public_key;
+    const char* second_issuer_public_key;
+    uint64_t current_time;
+    uint64_t allow_expired_within_seconds;
+} ZkGracePolicy;
+
+typedef struct {
+    int signature, proof, time_policy, revocation, schema, degraded;
+    uint64_t overshoot, revocation_epoch;
+} ZkGraceReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetProverExpiryGrace(uint64_t);
+int ZK_VerifyVCProofWithGrace(const char*, const char*, uint64_t, const ZkGracePolicy*, ZkGraceReport*);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char sig[129], blob[4096], old_sig[129], old_blob[4096], proof[2048];
+
+/* A credential of `holder` valid from 30 days before NOW to `expiry` */
+static int issue(const char* holder, uint64_t expiry, char* signature, char* out) {
+    return ZK_SignVC(holder, strlen(holder), "issuer", 6, NOW - 30 * DAY, expiry, priv, signature, 129, NULL) == 0 &&
+           ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - 30 * DAY, expiry, NULL, NULL, 0, signature, out,
+                       4096) == 0;
+}
+
+static int prove(const char* holder, uint64_t expiry, const char* signature) {
+    return ZK_GenerateVCProof(holder, strlen(holder), "issuer", 6, NOW - 30 * DAY, expiry, signature, pub, NOW, 7,
+                              proof, sizeof(proof), NULL);
+}
+
+static ZkGraceReport verify(const char* vc, uint64_t allowance, uint64_t nonce, int* rc) {
+    ZkGracePolicy policy = {pub, NULL, NOW, allowance};
+    ZkGraceReport report;
+    memset(&report, 0, sizeof(report));
+    *rc = ZK_VerifyVCProofWithGrace(proof, vc, nonce, &policy, &report);
+    return report;
+}
+
+int main(void) {
+    int rc;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        !issue("alice", NOW - 3600, sig, blob) || !issue("bob", NOW - 2 * DAY, old_sig, old_blob)) {
+        return 1;
+    }
+
+    /* Without a grace an expired credential does not prove */
+    int refused = prove("alice", NOW - 3600, sig);
+    if (ZK_SetProverExpiryGrace(DAY) != 0 || refused != ZK_ERR_VC_EXPIRED) {
+        return 1;
+    }
+
+    /* An hour past expiry: a grace proof, degraded within a day's allowance */
+    if (prove("alice", NOW - 3600, sig) != 0 || strncmp(proof, "5a4b4750", 8) != 0) {
+        return 1;
+    }
+    ZkGraceReport report = verify(blob, DAY, 7, &rc);
+    printf("  grace proof: %d, proof %d, degraded %d, overshoot %llu\n", rc, report.proof, report.degraded,
+           (unsigned long long)report.overshoot);
+    if (rc != 1 || report.proof != 1 || report.degraded != 1 || report.overshoot != 3600) {
+        return 1;
+    }
+    int short_allowance = verify(blob, DAY - 1, 7, &rc).proof;
+    int other_nonce = verify(blob, DAY, 8, &rc).proof;
+    int plain = ZK_VerifyVCProof(proof, pub, NOW, 7);
+    printf("  allowance below the grace: proof %d, other nonce: proof %d, ZK_VerifyVCProof %d\n", short_allowance,
+           other_nonce, plain);
+    if (short_allowance != 0 || other_nonce != 0 || plain == 1) {
+        return 1;
+    }
+
+    /* Two days past expiry is beyond the grace */
+    int beyond = prove("bob", NOW - 2 * DAY, old_sig);
+    printf("  beyond the grace: %d\n", beyond);
+    if (beyond != ZK_ERR_VC_EXPIRED) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* Bob's expiry moved to an hour ago, the issuer's signature kept: one
+     * that skips its checks proves it, and the proof check fails */
+    ZK_SkipProverChecks(1);
+    int forgery = prove("bob", NOW - 3600, old_sig);
+    int forged_proof = forgery == 0 ? verify(old_blob, DAY, 7, &rc).proof : -1;
+    int honest = prove("alice", NOW - 3600, sig);
+    int honest_proof = honest == 0 ? verify(blob, DAY, 7, &rc).proof : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited expiry: unchecked prover %d (proof %d), signed expiry %d (proof %d)\n", forgery, forged_proof,
+           honest, honest_proof);
+    if (forgery != 0 || forged_proof != 0 || honest != 0 || honest_proof != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Grace proofs hold for the issuer-signed expiry within the allowance only"
@@ -0,0 +1,145 @@
+#!/bin/bash
+#
+# Build the library for the host and check graded presentation results:
+# with every input given each check passes; checks without their input are
+# skipped and do not fail the presentation; an expired credential, another
+# audience, another issuer key or a revoked credential fails only its own
+# check while the proof stays valid; a refused proof format and a proof
+# that does not commit to the given credential fail only the proof check;
+# and a presentation that does not decode fails the proof and audience.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    int signature, proof, time_policy, revocation, audience;
+    uint64_t revocation_epoch;
+} ZkVerifyReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_VerifyPresentationDetailed(const char*, size_t, const char*, const char*, const char*, const char*, uint64_t,
+                                  ZkVerifyReport*);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_SetAcceptedFormatVersions(uint32_t);
+
+#define ZK_CHECK_SKIPPED 2
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ACCEPT_FORMAT_V1 1
+#define ZK_ACCEPT_ALL 7
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], other_pub[65], other_priv[65], vc_id[65];
+static char encoded[8192], blob[8192], other_encoded[8192], other_blob[8192], proof[4096], presentation[8192];
+
+/* The credential_id ZK_InspectArtifact reports for `vc` */
+static int credential_id(const char* vc, char* out) {
+    static char json[16384];
+    const char* at = ZK_InspectArtifact(vc, strlen(vc), json, sizeof(json)) == 0
+                         ? strstr(json, "\"credential_id\":\"")
+                         : NULL;
+    if (at == NULL) {
+        return 0;
+    }
+    memcpy(out, at + strlen("\"credential_id\":\""), 64);
+    out[64] = '\0';
+    return 1;
+}
+
+/* Run the detailed check; print and compare the report */
+static int expect(const char* label, const char* p, const char* vc, const char* key, const char* audience,
+                  uint64_t time, int result, ZkVerifyReport want) {
+    ZkVerifyReport got;
+    memset(&got, 0xff, sizeof(got));
+    int rc = ZK_VerifyPresentationDetailed(p, strlen(p), vc, key, NULL, audience, time, &got);
+    printf("  %s: %d (signature %d, proof %d, time %d, revocation %d, audience %d)\n", label, rc, got.signature,
+           got.proof, got.time_policy, got.revocation, got.audience);
+    return rc == result && got.signature == want.signature && got.proof == want.proof &&
+           got.time_policy == want.time_policy && got.revocation == want.revocation &&
+           got.audience == want.audience && got.revocation_epoch == 0;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    const char* other_values[] = {"auditor"};
+    const char* meta_keys[] = {"audience"};
+    const char* meta_values[] = {"gate-1"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, other_values, 1, NULL, other_encoded,
+                    sizeof(other_encoded)) != 0 ||
+        ZK_SignVCBlob(other_encoded, priv, other_blob, sizeof(other_blob)) != 0 ||
+        !credential_id(blob, vc_id) ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, meta_keys, meta_values, 1, presentation, sizeof(presentation)) != 0) {
+        return 1;
+    }
+    const int S = ZK_CHECK_SKIPPED;
+
+    int ok = expect("every input", presentation, blob, pub, "gate-1", NOW, 1, (ZkVerifyReport){1, 1, 1, 1, 1, 0}) &&
+             expect("presentation only", presentation, NULL, NULL, NULL, NOW, 1, (ZkVerifyReport){S, 1, S, S, S, 0}) &&
+             expect("expired", presentation, blob, pub, "gate-1", NOW + 2 * DAY, 0,
+                    (ZkVerifyReport){1, 1, 0, 1, 1, 0}) &&
+             expect("other audience", presentation, blob, pub, "gate-2", NOW, 0, (ZkVerifyReport){1, 1, 1, 1, 0, 0}) &&
+             expect("other issuer key", presentation, blob, other_pub, "gate-1", NOW, 0,
+                    (ZkVerifyReport){0, 1, 1, 1, 1, 0}) &&
+             expect("other credential", presentation, other_blob, pub, "gate-1", NOW, 0,
+                    (ZkVerifyReport){1, 0, 1, 1, 1, 0}) &&
+             expect("garbled presentation", "5a4b5052ff", NULL, NULL, "gate-1", NOW, 0,
+                    (ZkVerifyReport){S, 0, S, S, 0, 0});
+    if (!ok) {
+        return 1;
+    }
+
+    if (ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V1) != 0) {
+        return 1;
+    }
+    ok = expect("format 2 refused", presentation, blob, pub, "gate-1", NOW, 0,
+                (ZkVerifyReport){1, ZK_ERR_UNSUPPORTED_VERSION, 1, 1, 1, 0});
+    if (ZK_SetAcceptedFormatVersions(ZK_ACCEPT_ALL) != 0 || !ok) {
+        return 1;
+    }
+
+    if (ZK_RevokeCredential(vc_id, strlen(vc_id), NOW) != 0) {
+        return 1;
+    }
+    ok = expect("revoked", presentation, blob, pub, "gate-1", NOW, 0, (ZkVerifyReport){1, 1, 1, 0, 1, 0});
+
+    ZkVerifyReport report;
+    int null_report = ZK_VerifyPresentationDetailed(presentation, strlen(presentation), blob, pub, NULL, NULL, NOW,
+                                                    NULL);
+    int null_presentation = ZK_VerifyPresentationDetailed(NULL, 0, blob, pub, NULL, NULL, NOW, &report);
+    printf("  NULL report %d, NULL presentation %d\n", null_report, null_presentation);
+    return !ok || null_report != -1 || null_presentation != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Detailed verification grades each check on its own"
@@ -0,0 +1,154 @@
+#!/bin/bash
+#
+# Build the library for the host and check cross-device handoff: a desktop
+# request decodes on the phone to the nonce to prove with, the phone's
+# response is accepted by the desktop that made the request and yields a
+# presentation that verifies. A response delivered to another desktop's
+# request or channel, or re-wrapped for it, is refused with ZK_ERR_AUTH,
+# as is a presentation proven for the bare challenge, and a request past
+# its lifetime is refused with ZK_ERR_EXPIRED on both sides.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    uint64_t challenge, nonce, created_at, expires_at;
+} ZkHandoffInfo;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_VerifyPresentation(const char*, size_t);
+int ZK_CreateHandoffRequest(const char*, uint64_t, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_DecodeHandoffRequest(const char*, char*, size_t, char*, size_t, ZkHandoffInfo*);
+int ZK_CreateHandoffResponse(const char*, const char*, size_t, uint64_t, char*, size_t);
+int ZK_AcceptHandoffResponse(const char*, const char*, uint64_t, char*, size_t);
+
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_EXPIRED -28
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define TTL 300
+#define CHALLENGE 0x1234abcdULL
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096], bare_proof[4096];
+static char request[2048], other_request[2048], decoded_pub[65], audience[256];
+static char presentation[16384], bare_presentation[16384], response[32768], rewrapped[32768], forwarded[32768];
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_CreateHandoffRequest(pub, CHALLENGE, "shop.example", "desk-A", NOW, TTL, request, sizeof(request)) != 0 ||
+        ZK_CreateHandoffRequest(pub, CHALLENGE, "shop.example", "desk-B", NOW, TTL, other_request,
+                                sizeof(other_request)) != 0) {
+        return 1;
+    }
+
+    /* The phone reads what to prove for */
+    ZkHandoffInfo info;
+    int decoded = ZK_DecodeHandoffRequest(request, decoded_pub, sizeof(decoded_pub), audience, sizeof(audience), &info);
+    const char* body = request + strlen("ZKH45:");
+    int qr_text = strncmp(request, "ZKH45:", 6) == 0 &&
+                  strspn(body, "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:") == strlen(body);
+    printf("  request %zu characters, QR alphanumeric %d; decoded %d: audience %s, challenge %d, window %llu s\n",
+           strlen(request), qr_text, decoded, audience, info.challenge == CHALLENGE,
+           (unsigned long long)(info.expires_at - info.created_at));
+    if (!qr_text || decoded != 0 || strcmp(decoded_pub, pub) != 0 || strcmp(audience, "shop.example") != 0 ||
+        info.challenge != CHALLENGE || info.created_at != NOW || info.expires_at != NOW + TTL ||
+        info.nonce == CHALLENGE) {
+        return 1;
+    }
+
+    /* It proves with the request's nonce and answers on the channel */
+    const char* meta_keys[] = {"audience"};
+    const char* meta_values[] = {audience};
+    if (ZK_GenerateVCProofFromBlob(blob, pub, NULL, info.created_at, info.nonce, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, info.nonce, meta_keys, meta_values, 1, presentation,
+                              sizeof(presentation)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, CHALLENGE, bare_proof, sizeof(bare_proof)) != 0 ||
+        ZK_EncodePresentation(bare_proof, pub, CHALLENGE, meta_keys, meta_values, 1, bare_presentation,
+                              sizeof(bare_presentation)) != 0) {
+        return 1;
+    }
+    size_t len = strlen(presentation);
+    int answered = ZK_CreateHandoffResponse(request, presentation, len, NOW + 60, response, sizeof(response));
+    int accepted = ZK_AcceptHandoffResponse(request, response, NOW + 90, forwarded, sizeof(forwarded));
+    int verifies = accepted == 0 ? ZK_VerifyPresentation(forwarded, strlen(forwarded)) : accepted;
+    printf("  answered %d, accepted %d, forwarded presentation verifies %d\n", answered, accepted, verifies);
+    if (answered != 0 || accepted != 0 || verifies != 1) {
+        return 1;
+    }
+
+    /* Redirection */
+    int other_desktop = ZK_AcceptHandoffResponse(other_request, response, NOW + 90, forwarded, sizeof(forwarded));
+    int rewrap = ZK_CreateHandoffResponse(other_request, presentation, len, NOW + 60, rewrapped, sizeof(rewrapped));
+    int bare = ZK_CreateHandoffResponse(request, bare_presentation, strlen(bare_presentation), NOW + 60, rewrapped,
+                                        sizeof(rewrapped));
+    /* The channel id in the response, "desk-A" in hex, pointed at desk-B */
+    char* channel = strstr(response, "6465736b2d41");
+    if (channel != NULL) {
+        memcpy(channel, "6465736b2d42", 12);
+    }
+    int moved = ZK_AcceptHandoffResponse(request, response, NOW + 90, forwarded, sizeof(forwarded));
+    int moved_other = ZK_AcceptHandoffResponse(other_request, response, NOW + 90, forwarded, sizeof(forwarded));
+    printf("  to another desktop %d, re-wrapped for it %d, proven for the bare challenge %d, "
+           "channel rewritten %d (at the other desktop %d)\n",
+           other_desktop, rewrap, bare, moved, moved_other);
+    if (channel == NULL || other_desktop != ZK_ERR_AUTH || rewrap != ZK_ERR_AUTH || bare != ZK_ERR_AUTH ||
+        moved != ZK_ERR_AUTH || moved_other != ZK_ERR_AUTH) {
+        return 1;
+    }
+
+    /* Expiry */
+    int last_second = ZK_CreateHandoffResponse(request, presentation, len, NOW + TTL, response, sizeof(response));
+    int late_answer = ZK_CreateHandoffResponse(request, presentation, len, NOW + TTL + 1, rewrapped,
+                                               sizeof(rewrapped));
+    int late_accept = ZK_AcceptHandoffResponse(request, response, NOW + TTL + 1, forwarded, sizeof(forwarded));
+    printf("  answered at expiry %d, after %d, accepted after %d\n", last_second, late_answer, late_accept);
+    if (last_second != 0 || late_answer != ZK_ERR_EXPIRED || late_accept != ZK_ERR_EXPIRED) {
+        return 1;
+    }
+
+    char scratch[2048];
+    int no_ttl = ZK_CreateHandoffRequest(pub, CHALLENGE, "", "desk-A", NOW, 0, scratch, sizeof(scratch));
+    int long_ttl = ZK_CreateHandoffRequest(pub, CHALLENGE, "", "desk-A", NOW, 601, scratch, sizeof(scratch));
+    int no_audience = ZK_CreateHandoffRequest(pub, CHALLENGE, "", "desk-A", NOW, TTL, scratch, sizeof(scratch));
+    int garbled = ZK_DecodeHandoffRequest("ZKH45:AB", decoded_pub, sizeof(decoded_pub), audience, sizeof(audience),
+                                          &info);
+    int garbled_response = ZK_AcceptHandoffResponse(request, "5a4b4852", NOW, forwarded, sizeof(forwarded));
+    int null_info = ZK_DecodeHandoffRequest(request, decoded_pub, sizeof(decoded_pub), audience, sizeof(audience),
+                                            NULL);
+    printf("  ttl 0 %d, ttl 601 %d, no audience %d, garbled request %d, garbled response %d, NULL info %d\n",
+           no_ttl, long_ttl, no_audience, garbled, garbled_response, null_info);
+    return no_ttl != -1 || long_ttl != -1 || no_audience != 0 || garbled != ZK_ERR_CORRUPT ||
+           garbled_response != ZK_ERR_CORRUPT || null_info != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Handoff responses reach only the desktop that asked, before expiry"
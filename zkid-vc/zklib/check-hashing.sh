@@ -0,0 +1,119 @@
+#!/bin/bash
+#
+# Build the library for the host and check the credential message hash:
+# moving the boundary between holder_id and issuer, or between a claim's key
+# and value, changes the hash and invalidates the signature, while the
+# format 1 hash kept for old signatures still runs them together. Two
+# signatures recorded before length prefixes (fixed issuer seed 7) must
+# verify through ZK_VerifyVCSignatureLegacy_I64 and not through the current
+# entry points.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypairDeterministic(uint64_t, char*, size_t, char*, size_t);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_VerifyVCSignatureWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                                   const char* const*, const char* const*, size_t, const char*, const char*);
+int ZK_VerifyVCSignatureLegacy_I64(const char*, size_t, const char*, size_t, int64_t, int64_t,
+                                   const char* const*, const char* const*, size_t, const char*, const char*);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_ComputeVCHashLegacy_I64(const char*, size_t, const char*, size_t, int64_t, int64_t, const char* const*,
+                               const char* const*, size_t, char*, size_t, size_t*);
+
+/* ZK_SignVC and ZK_SignVCWithClaims output for seed 7 before hash format 2 */
+static const char* legacy_pub = "478243aed376da313d7cf3a60637c264cb36acc936efb341ff8d3d712092d244";
+static const char* legacy_sig =
+    "84159b8c3fd501b9d0536500c7582cb16dda2b9847bfe06434c53811352829e5"
+    "893cc4fd8fe8e7c04c7fcbe7f8e03dde2b94953ee3be02da50a118b409a00d03";
+static const char* legacy_claims_sig =
+    "2455ad1d14219c394e228ebdd0864b21d3593820295057d1c010e161000cc9b4"
+    "940a998afb898ce2b05e960af0144b0d35cdb499561c497a643061122d52d80b";
+static const char* legacy_hash = "a9d6181b976340f5166141700d85f4ccd4861bb598ce7cb5295e3ab31478d963";
+
+/* One credential's fields, and its boundary twin */
+struct fields {
+    const char *holder, *issuer;
+    const char* keys[1];
+    const char* values[1];
+    size_t claims;
+};
+
+static const struct fields plain = {"alice", "corp", {NULL}, {NULL}, 0};
+static const struct fields plain_moved = {"alicec", "orp", {NULL}, {NULL}, 0};
+static const struct fields claimed = {"alice", "corp", {"ab"}, {"c"}, 1};
+static const struct fields claimed_moved = {"alice", "corp", {"a"}, {"bc"}, 1};
+
+static int hash(const struct fields* f, int legacy, char out[65]) {
+    if (legacy) {
+        return ZK_ComputeVCHashLegacy_I64(f->holder, strlen(f->holder), f->issuer, strlen(f->issuer), 100, 200,
+                                          f->keys, f->values, f->claims, out, 65, NULL);
+    }
+    return ZK_ComputeVCHash(f->holder, strlen(f->holder), f->issuer, strlen(f->issuer), 100, 200, f->keys,
+                            f->values, f->claims, out, 65, NULL);
+}
+
+static int verify(const struct fields* f, int legacy, const char* sig, const char* pub) {
+    if (legacy) {
+        return ZK_VerifyVCSignatureLegacy_I64(f->holder, strlen(f->holder), f->issuer, strlen(f->issuer), 100,
+                                              200, f->keys, f->values, f->claims, sig, pub);
+    }
+    return ZK_VerifyVCSignatureWithClaims(f->holder, strlen(f->holder), f->issuer, strlen(f->issuer), 100, 200,
+                                          f->keys, f->values, f->claims, sig, pub);
+}
+
+/* Check a boundary pair: distinct in format 2, alike in format 1 */
+static int pair(const char* name, const struct fields* a, const struct fields* b, const char* priv,
+                const char* pub) {
+    char ha[65], hb[65], la[65], lb[65], sig[129];
+    if (hash(a, 0, ha) != 0 || hash(b, 0, hb) != 0 || hash(a, 1, la) != 0 || hash(b, 1, lb) != 0 ||
+        ZK_SignVCWithClaims(a->holder, strlen(a->holder), a->issuer, strlen(a->issuer), 100, 200, a->keys,
+                            a->values, a->claims, priv, sig, sizeof(sig), NULL) != 0) {
+        return 0;
+    }
+    int signed_ = verify(a, 0, sig, pub), moved = verify(b, 0, sig, pub);
+    printf("  %s: hashes differ %d, legacy hashes equal %d; signature %d, on the twin %d\n", name,
+           strcmp(ha, hb) != 0, strcmp(la, lb) == 0, signed_, moved);
+    return strcmp(ha, hb) != 0 && strcmp(la, lb) == 0 && signed_ == 1 && moved == 0;
+}
+
+int main(void) {
+    char pub[65], priv[65], h[65];
+    if (ZK_GenerateIssuerKeypairDeterministic(7, pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        strcmp(pub, legacy_pub) != 0) {
+        return 1;
+    }
+    int ok = pair("holder_id | issuer", &plain, &plain_moved, priv, pub) &
+             pair("claim key | value", &claimed, &claimed_moved, priv, pub);
+
+    /* Recorded signatures verify in format 1 only, on either boundary */
+    int legacy = verify(&plain, 1, legacy_sig, legacy_pub) == 1 &&
+                 verify(&plain_moved, 1, legacy_sig, legacy_pub) == 1 &&
+                 verify(&claimed, 1, legacy_claims_sig, legacy_pub) == 1 &&
+                 verify(&claimed_moved, 1, legacy_claims_sig, legacy_pub) == 1;
+    int current = verify(&plain, 0, legacy_sig, legacy_pub) + verify(&claimed, 0, legacy_claims_sig, legacy_pub);
+    int same_hash = hash(&plain, 1, h) == 0 && strcmp(h, legacy_hash) == 0;
+    printf("  recorded signatures: legacy %d, current %d, legacy hash unchanged %d\n", legacy, current, same_hash);
+    return !ok || !legacy || current != 0 || !same_hash;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" 2>/dev/null || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Field boundaries are part of the credential hash, and format 1 signatures verify only as legacy"
@@ -0,0 +1,174 @@
+#!/bin/bash
+#
+# Build the library for the host and check holder-bound VC proofs: a
+# credential signed to a holder public key proves with the holder's secret
+# only, its proof verifies under the bound-VC key and not as an unbound VC
+# proof, and knowing the fields and signature is not enough to prove it
+# through either entry point. With debug-circuit, a wrong secret is checked
+# against the anchor constraint directly, as a prover that skips the
+# signature check would feed it. With fault-injection, such a prover proves
+# the credential with another holder's secret, and ZK_VerifyVCProofBound
+# rejects the proof: the anchor the issuer signed holds the holder's key.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_GenerateHolderKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_SignVCBound(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, const char*,
+                   char*, size_t, size_t*);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_GenerateVCProofBound(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*,
+                            const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_VerifyVCProofBound(const char*, const char*, uint64_t, uint64_t);
+int ZK_CheckBoundCircuitSatisfiability(uint8_t, const char*, const char*, uint64_t, int64_t, int64_t, uint64_t,
+                                       const char*, const char*, char*, size_t);
+int ZK_RunConstraintAudit(char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_BAD_SIGNATURE -3
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_BAD_HEX -6
+#define ZK_ERR_UNSATISFIED -19
+#define NOW 150
+
+static char pub[65], priv[65], secret[65], holder[65], other_secret[65], other_holder[65];
+static char bound_sig[129], plain_sig[129], proof[1024];
+
+static int prove_bound(const char* sig, const char* holder_secret, uint64_t current_time) {
+    return ZK_GenerateVCProofBound("alice", 5, "issuer", 6, 100, 200, sig, pub, holder_secret, current_time, 42,
+                                   proof, sizeof(proof), NULL);
+}
+
+int main(void) {
+    static char report[8192];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateHolderKeypair(secret, sizeof(secret), holder, sizeof(holder)) != 0 ||
+        ZK_GenerateHolderKeypair(other_secret, sizeof(other_secret), other_holder, sizeof(other_holder)) != 0 ||
+        ZK_SignVCBound("alice", 5, "issuer", 6, 100, 200, holder, priv, bound_sig, sizeof(bound_sig), NULL) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, plain_sig, sizeof(plain_sig), NULL) != 0) {
+        return 1;
+    }
+
+    /* The holder proves; the proof holds under the bound key only */
+    int rc = prove_bound(bound_sig, secret, NOW);
+    if (rc != 0) {
+        printf("  holder secret: prove %d\n", rc);
+        return 1;
+    }
+    int valid = ZK_VerifyVCProofBound(proof, pub, NOW, 42);
+    int nonce = ZK_VerifyVCProofBound(proof, pub, NOW, 43);
+    int later = ZK_VerifyVCProofBound(proof, pub, NOW + 1, 42);
+    int unbound = ZK_VerifyVCProof(proof, pub, NOW, 42);
+    printf("  holder secret: valid %d, other nonce %d, other time %d, as unbound VC proof %d\n", valid, nonce,
+           later, unbound);
+    if (valid != 1 || nonce != 0 || later != 0 || unbound != 0) {
+        return 1;
+    }
+
+    /* Fields and signature without the secret prove nothing */
+    int wrong = prove_bound(bound_sig, other_secret, NOW);
+    int as_plain = ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, bound_sig, pub, NOW, 42, proof,
+                                      sizeof(proof), NULL);
+    int malformed = prove_bound(bound_sig, "00", NOW);
+    int expired = prove_bound(bound_sig, secret, 201);
+    printf("  other secret %d, unbound entry point %d, malformed secret %d, expired %d\n", wrong, as_plain,
+           malformed, expired);
+    if (wrong != ZK_ERR_BAD_SIGNATURE || as_plain != ZK_ERR_BAD_SIGNATURE || malformed != ZK_ERR_BAD_HEX ||
+        expired != ZK_ERR_VC_EXPIRED) {
+        return 1;
+    }
+
+    /* The unbound path is unchanged; its proofs do not pass as bound */
+    if (ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, plain_sig, pub, NOW, 42, proof, sizeof(proof),
+                           NULL) != 0) {
+        return 1;
+    }
+    int plain = ZK_VerifyVCProof(proof, pub, NOW, 42);
+    int as_bound = ZK_VerifyVCProofBound(proof, pub, NOW, 42);
+    int bound_plain = prove_bound(plain_sig, secret, NOW);
+    printf("  unbound credential: valid %d, as bound proof %d, proven bound %d\n", plain, as_bound, bound_plain);
+    if (plain != 1 || as_bound != 0 || bound_plain != ZK_ERR_BAD_SIGNATURE) {
+        return 1;
+    }
+
+#ifdef DEBUG_CIRCUIT
+    /* The bound credential's hash, checked against both secrets */
+    static char hash[65];
+    const char* keys[] = {"zkid:holder-binding"};
+    const char* values[] = {holder};
+    if (ZK_ComputeVCHash("alice", 5, "issuer", 6, 100, 200, keys, values, 1, hash, sizeof(hash), NULL) != 0) {
+        return 1;
+    }
+    struct { const char* secret; int expected; } witnesses[] = {
+        {secret, 0},
+        {other_secret, ZK_ERR_UNSATISFIED},
+    };
+    for (size_t i = 0; i < sizeof(witnesses) / sizeof(witnesses[0]); i++) {
+        report[0] = '\0';
+        rc = ZK_CheckBoundCircuitSatisfiability(2, hash, pub, 42, 100, 200, NOW, holder, witnesses[i].secret,
+                                                report, sizeof(report));
+        printf("  witness %s secret: %d %s\n", i == 0 ? "holder" : "other", rc, report);
+        if (rc != witnesses[i].expected || (rc != 0 && strstr(report, "vc_anchor") == NULL)) {
+            return 1;
+        }
+    }
+
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0 || strstr(report, "bound-vc") == NULL) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+
+#ifdef FAULT_INJECTION
+    /* A prover without the signature check proves with a foreign secret */
+    ZK_SkipProverChecks(1);
+    int foreign = prove_bound(bound_sig, other_secret, NOW);
+    int foreign_valid = foreign == 0 ? ZK_VerifyVCProofBound(proof, pub, NOW, 42) : -1;
+    int own = prove_bound(bound_sig, secret, NOW);
+    int own_valid = own == 0 ? ZK_VerifyVCProofBound(proof, pub, NOW, 42) : -1;
+    printf("  unchecked prover: foreign secret %d (verifies %d), holder secret %d (verifies %d)\n", foreign,
+           foreign_valid, own, own_valid);
+    if (foreign != 0 || foreign_valid != 0 || own != 0 || own_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Bound VC proofs need the holder secret"
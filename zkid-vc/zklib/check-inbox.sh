@@ -0,0 +1,130 @@
+#!/bin/bash
+#
+# Build the library for the host and check the sealed request inbox: a
+# presentation request sealed for a holder opens with that holder's key to
+# the same bytes and decodes to the verifier's values and metadata; sealing
+# twice gives different blobs; another holder's key, a flipped ciphertext
+# or ephemeral-key byte, or a cut tag fail authentication, while a wrong
+# magic or version, a blob shorter than the header and a garbled request
+# are corrupt; and both outputs report their length for a short buffer.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_GenerateHolderEncryptionKeypair(char*, size_t, char*, size_t);
+int ZK_EncryptForHolder(const char*, const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+int ZK_DecryptAsHolder(const char*, const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_DecodePresentationRequest(const char*, size_t, char*, size_t, uint64_t*, uint64_t*, uint64_t*);
+int ZK_PresentationRequestMetadata(const char*, size_t, const char*, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+/* "ZKSB", the version byte and the ephemeral public key */
+#define HEADER_LEN 37
+
+static char issuer_pub[65], issuer_priv[65], holder_pub[65], holder_priv[65], other_pub[65], other_priv[65];
+static char request[4096], opened[4096];
+static uint8_t blob[8192], again[8192], edited[8192];
+
+/* Open `len` bytes of `data` with `key` into `opened`; the result code */
+static int open_with(const char* key, const uint8_t* data, size_t len) {
+    size_t opened_len = 0;
+    memset(opened, 0, sizeof(opened));
+    return ZK_DecryptAsHolder(key, data, len, (uint8_t*)opened, sizeof(opened) - 1, &opened_len);
+}
+
+/* Open a copy of the blob with byte `at` XORed by `mask` */
+static int open_flipped(size_t blob_len, size_t at, uint8_t mask) {
+    memcpy(edited, blob, blob_len);
+    edited[at] ^= mask;
+    return open_with(holder_priv, edited, blob_len);
+}
+
+int main(void) {
+    const char* keys[] = {"purpose", "origin"};
+    const char* values[] = {"door-3", "lobby"};
+    if (ZK_GenerateIssuerKeypair(issuer_pub, sizeof(issuer_pub), issuer_priv, sizeof(issuer_priv)) != 0 ||
+        ZK_GenerateHolderEncryptionKeypair(holder_pub, sizeof(holder_pub), holder_priv, sizeof(holder_priv)) != 0 ||
+        ZK_GenerateHolderEncryptionKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodePresentationRequest(issuer_pub, 7, NOW, NOW + DAY, keys, values, 2, request, sizeof(request)) != 0) {
+        return 1;
+    }
+    size_t request_len = strlen(request);
+
+    /* Seal, sizing the buffer with a first call */
+    size_t blob_len = 0, again_len = 0, plain_len = 0;
+    int sized = ZK_EncryptForHolder(holder_pub, (const uint8_t*)request, request_len, blob, 8, &blob_len);
+    int sealed = ZK_EncryptForHolder(holder_pub, (const uint8_t*)request, request_len, blob, blob_len, &blob_len);
+    int resealed =
+        ZK_EncryptForHolder(holder_pub, (const uint8_t*)request, request_len, again, sizeof(again), &again_len);
+    int fresh = again_len == blob_len && memcmp(again, blob, blob_len) != 0;
+    int short_plain = ZK_DecryptAsHolder(holder_priv, blob, blob_len, (uint8_t*)opened, 8, &plain_len);
+    int opens = open_with(holder_priv, blob, blob_len);
+    printf("  sealed %zu bytes for %zu (short buffer %d), twice differs %d; short plaintext buffer %d (%zu), "
+           "opens %d\n",
+           blob_len, request_len, sized, fresh, short_plain, plain_len, opens);
+    if (sized != ZK_ERR_BUFFER_TOO_SMALL || sealed != 0 || resealed != 0 || !fresh ||
+        short_plain != ZK_ERR_BUFFER_TOO_SMALL || plain_len != request_len || opens != 0 ||
+        strcmp(opened, request) != 0) {
+        return 1;
+    }
+
+    /* What the holder reads from the request */
+    char issuer[65], purpose[64], missing[64];
+    uint64_t nonce = 0, current_time = 0, expires_at = 0;
+    int decoded = ZK_DecodePresentationRequest(opened, strlen(opened), issuer, sizeof(issuer), &nonce, &current_time,
+                                               &expires_at);
+    int found = ZK_PresentationRequestMetadata(opened, strlen(opened), "purpose", purpose, sizeof(purpose));
+    int absent = ZK_PresentationRequestMetadata(opened, strlen(opened), "audience", missing, sizeof(missing));
+    int garbled = ZK_DecodePresentationRequest("5a4b5251ff", 10, issuer, sizeof(issuer), &nonce, &current_time,
+                                               &expires_at);
+    printf("  request: decoded %d, same issuer %d, nonce %llu, window %d; purpose %d (%s), absent key %d, "
+           "garbled %d\n",
+           decoded, strcmp(issuer, issuer_pub) == 0, (unsigned long long)nonce,
+           current_time == NOW && expires_at == NOW + DAY, found, purpose, absent, garbled);
+    if (decoded != 0 || strcmp(issuer, issuer_pub) != 0 || nonce != 7 || current_time != NOW ||
+        expires_at != NOW + DAY || found != 0 || strcmp(purpose, "door-3") != 0 || absent != 1 ||
+        garbled != ZK_ERR_CORRUPT) {
+        return 1;
+    }
+
+    /* Blobs the holder must not open */
+    int other_key = open_with(other_priv, blob, blob_len);
+    int ciphertext = open_flipped(blob_len, HEADER_LEN + 3, 0x01);
+    int ephemeral = open_flipped(blob_len, 10, 0x80);
+    int tag = open_with(holder_priv, blob, blob_len - 1);
+    int magic = open_flipped(blob_len, 0, 0x20);
+    int version = open_flipped(blob_len, 4, 0x02);
+    int short_blob = open_with(holder_priv, blob, HEADER_LEN - 1);
+    int bad_key = ZK_EncryptForHolder("zz", (const uint8_t*)request, request_len, again, sizeof(again), &again_len);
+    printf("  other holder %d, ciphertext byte %d, ephemeral key byte %d, cut tag %d; magic %d, version %d, "
+           "shorter than the header %d; bad public key %d\n",
+           other_key, ciphertext, ephemeral, tag, magic, version, short_blob, bad_key);
+    return other_key != ZK_ERR_AUTH || ciphertext != ZK_ERR_AUTH || ephemeral != ZK_ERR_AUTH || tag != ZK_ERR_AUTH ||
+           magic != ZK_ERR_CORRUPT || version != ZK_ERR_CORRUPT || short_blob != ZK_ERR_CORRUPT || bad_key != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Sealed requests open only for their holder, unaltered"
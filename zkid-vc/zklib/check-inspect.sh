@@ -0,0 +1,185 @@
+#!/bin/bash
+#
+# Build the library for the host and check the artifact inspector: a
+# credential, status attestation, proof, presentation and verifying key,
+# as hex or binary, are each recognized and described without their claim
+# or metadata values or holder, and nothing is verified. Non-canonical and
+# off-curve points are reported rather than refused, damaged and unknown
+# blobs are described with an error, and the admission counters do not
+# move.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_IssueStatusAttestation(const char*, const char*, uint64_t, char*, size_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_GetAdmissionStats(int, uint64_t*);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_INPUT_TOO_LARGE -30
+#define ZK_STAGES 6
+#define MAX_INSPECT_LEN (64 * 1024)
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096], status[1024], vk[8192];
+static char presentation[16384], damaged[16384], json[32768], id[65];
+static uint8_t binary[16384];
+
+/* Inspect `artifact`; 1 if the code matches and every `want` is in the JSON and no `never` is */
+static int expect(const char* name, const char* artifact, size_t len, int code, const char* const* want,
+                  const char* const* never) {
+    json[0] = '\0';
+    int got = ZK_InspectArtifact(artifact, len, json, sizeof(json));
+    int ok = got == code;
+    for (; want != NULL && *want != NULL; want++) {
+        ok &= strstr(json, *want) != NULL;
+    }
+    for (; never != NULL && *never != NULL; never++) {
+        ok &= strstr(json, *never) == NULL;
+    }
+    printf("  %s: %d %s\n", name, got, ok ? "" : json);
+    return ok;
+}
+
+static size_t unhex(const char* hex, uint8_t* out) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len; i++) {
+        sscanf(hex + 2 * i, "%2hhx", &out[i]);
+    }
+    return len;
+}
+
+int main(void) {
+    const char* keys[] = {"role", "clearance"};
+    const char* values[] = {"engineer", "top-secret"};
+    const char* meta_keys[] = {"audience"};
+    const char* meta_values[] = {"gate-hidden"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 2, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, meta_keys, meta_values, 1, presentation, sizeof(presentation)) != 0 ||
+        ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+    uint64_t before[ZK_STAGES], after[ZK_STAGES];
+    for (int stage = 0; stage < ZK_STAGES; stage++) {
+        ZK_GetAdmissionStats(stage, &before[stage]);
+    }
+
+    /* Each type, described without values */
+    const char* values_never[] = {"engineer", "top-secret", "alice", "gate-hidden", NULL};
+    const char* credential[] = {"\"type\":\"credential\"", "\"encoding\":\"hex\"",
+                                "\"claim_keys\":[\"role\",\"clearance\"", "\"issuer\":\"issuer\"",
+                                "\"credential_id\":\"", "\"fingerprint\":\"", NULL};
+    int ok = expect("credential", blob, strlen(blob), 0, credential, values_never);
+    const char* at = strstr(json, "\"credential_id\":\"");
+    if (at == NULL) {
+        return 1;
+    }
+    memcpy(id, at + strlen("\"credential_id\":\""), 64);
+    if (ZK_IssueStatusAttestation(priv, id, NOW - 60, status, sizeof(status)) != 0) {
+        return 1;
+    }
+    char attested_at[64];
+    snprintf(attested_at, sizeof(attested_at), "\"attested_at\":%llu", NOW - 60);
+    const char* attestation[] = {"\"type\":\"status_attestation\"", id, attested_at, NULL};
+    ok &= expect("status attestation", status, strlen(status), 0, attestation, NULL);
+    char current_time[64];
+    snprintf(current_time, sizeof(current_time), "\"current_time\":%llu", NOW);
+    const char* vc_proof[] = {"\"type\":\"proof\"", "\"circuit\":\"zkid-vc/v7\"", current_time,
+                              "\"points\":\"compressed\"", "\"canonical\":true", "\"valid_points\":true", NULL};
+    ok &= expect("proof", proof, strlen(proof), 0, vc_proof, NULL);
+    const char* wrapped[] = {"\"type\":\"presentation\"", "\"metadata_keys\":[\"audience\"]", pub,
+                             "\"proof\":{", "\"valid_points\":true", NULL};
+    ok &= expect("presentation", presentation, strlen(presentation), 0, wrapped, values_never);
+    const char* key[] = {"\"type\":\"verifying_key\"", "\"form\":\"raw\"", "\"zkid-vc/v7\"", "\"vk_fingerprint\":\"",
+                         NULL};
+    ok &= expect("verifying key", vk, strlen(vk), 0, key, NULL);
+    size_t len = unhex(presentation, binary);
+    const char* as_binary[] = {"\"type\":\"presentation\"", "\"encoding\":\"binary\"", NULL};
+    ok &= expect("binary presentation", (const char*)binary, len, 0, as_binary, values_never);
+    if (!ok) {
+        return 1;
+    }
+
+    /* Points are reported, not refused: C's flag byte set, then C off the curve */
+    size_t proof_len = strlen(proof);
+    strcpy(damaged, proof);
+    memcpy(damaged + proof_len - 2, "ff", 2);
+    const char* flagged[] = {"\"type\":\"proof\"", "\"canonical\":false", "\"valid_points\":false", NULL};
+    ok = expect("flag bits set", damaged, proof_len, 0, flagged, NULL);
+    memset(damaged + proof_len - 64, '0', 64);
+    for (size_t i = proof_len - 64; i < proof_len; i += 2) {
+        damaged[i + 1] = '3';
+    }
+    const char* off_curve[] = {"\"canonical\":true", "\"valid_points\":false", NULL};
+    ok &= expect("off the curve", damaged, proof_len, 0, off_curve, NULL);
+
+    /* Damaged, newer and unknown blobs */
+    strcpy(damaged, blob);
+    damaged[strlen(blob) / 2] = '\0';
+    const char* truncated[] = {"\"type\":\"credential\"", "\"error\":\"malformed\"", NULL};
+    ok &= expect("truncated credential", damaged, strlen(damaged), ZK_ERR_CORRUPT, truncated, NULL);
+    strcpy(damaged, blob);
+    /* "ZKVC" | version */
+    memcpy(damaged + 8, "ff", 2);
+    const char* newer[] = {"\"type\":\"credential\"", "\"error\":\"unsupported_version\"", NULL};
+    ok &= expect("newer credential", damaged, strlen(damaged), ZK_ERR_UNSUPPORTED_VERSION, newer, NULL);
+    const char* unknown[] = {"\"type\":\"unknown\"", "\"encoding\":\"binary\"", "\"error\":\"unrecognized\"", NULL};
+    ok &= expect("pasted garbage", "hello, support", 14, ZK_ERR_CORRUPT, unknown, NULL);
+    /* An odd length of 'a' is not hex, so it is inspected as binary */
+    char* huge = malloc(2 * MAX_INSPECT_LEN + 2);
+    memset(huge, 'a', 2 * MAX_INSPECT_LEN + 2);
+    const char* too_large[] = {"\"encoding\":\"binary\"", "\"error\":\"too_large\"", NULL};
+    ok &= expect("binary over the limit", huge, MAX_INSPECT_LEN + 1, ZK_ERR_INPUT_TOO_LARGE, too_large, NULL);
+    int huge_hex = ZK_InspectArtifact(huge, 2 * MAX_INSPECT_LEN + 2, json, sizeof(json));
+    free(huge);
+    int short_buffer = ZK_InspectArtifact(blob, strlen(blob), json, 16);
+    int null_artifact = ZK_InspectArtifact(NULL, 4, json, sizeof(json));
+    int empty = ZK_InspectArtifact(blob, 0, json, sizeof(json));
+    printf("  hex over the limit %d, short buffer %d, NULL %d, empty %d\n", huge_hex, short_buffer,
+           null_artifact, empty);
+
+    int unmoved = 1;
+    for (int stage = 0; stage < ZK_STAGES; stage++) {
+        ZK_GetAdmissionStats(stage, &after[stage]);
+        unmoved &= after[stage] == before[stage];
+    }
+    printf("  admission counters unmoved %d\n", unmoved);
+    return !ok || huge_hex != ZK_ERR_INPUT_TOO_LARGE || short_buffer != ZK_ERR_BUFFER_TOO_SMALL ||
+           null_artifact != -1 || empty != -1 || !unmoved;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Artifacts are described by structure alone, never by value"
@@ -0,0 +1,161 @@
+#!/bin/bash
+#
+# Build the library for the host and take VC blobs through W3C VC JSON and
+# back: blobs signed over the full message hash, under format version 2 and
+# with a field-based signature come back byte for byte and verify, the dates
+# are RFC 3339, zkid:claimOrder keeps the signed claim order (the signature
+# fails under another one, and a document without it takes key order), an
+# offset date parses to the same instant, and impossible dates, fractional
+# seconds and a claim named "id" are refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_SetFormatVersion(uint32_t);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVCWithClaims_I64(const char*, size_t, const char*, size_t, int64_t, int64_t, const char* const*,
+                            const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_SerializeVCJson(const char*, char*, size_t, size_t*);
+int ZK_ParseVCJson(const char*, size_t, char*, size_t);
+
+#define ZK_STATUS_SIZE_QUERY 1
+#define ISSUED 1704067200  /* 2024-01-01T00:00:00Z */
+#define EXPIRES 1735689599 /* 2024-12-31T23:59:59Z */
+
+static char pub[65], priv[65];
+
+/* Replace the first `from` in `s` by `to`; 0 if `from` is absent */
+static int replace(char* s, const char* from, const char* to) {
+    char* at = strstr(s, from);
+    if (!at) {
+        return 0;
+    }
+    memmove(at + strlen(to), at + strlen(from), strlen(at + strlen(from)) + 1);
+    memcpy(at, to, strlen(to));
+    return 1;
+}
+
+/* Encode claims (signed with ZK_SignVCBlob unless `field_signed`) */
+static int make_blob(const char* const* keys, const char* const* values, size_t count, int field_signed,
+                     char* out, size_t size) {
+    static char unsigned_blob[4096];
+    char sig[129];
+    if (field_signed) {
+        return ZK_SignVCWithClaims_I64("alice", 5, "issuer", 6, ISSUED, EXPIRES, keys, values, count, priv, sig,
+                                       sizeof(sig), NULL) == 0 &&
+               ZK_EncodeVC("alice", 5, "issuer", 6, ISSUED, EXPIRES, keys, values, count, sig, out, size) == 0;
+    }
+    return ZK_EncodeVC("alice", 5, "issuer", 6, ISSUED, EXPIRES, keys, values, count, NULL, unsigned_blob,
+                       sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, out, size) == 0;
+}
+
+static int parse(const char* json, char* blob, size_t size) {
+    return ZK_ParseVCJson(json, strlen(json), blob, size);
+}
+
+/* Blob -> JSON -> blob is the identity and still verifies */
+static int round_trip(const char* label, const char* blob, char* json, size_t size) {
+    static char again[4096];
+    size_t required = 0;
+    int query = ZK_SerializeVCJson(blob, NULL, 0, &required);
+    if (ZK_SerializeVCJson(blob, json, size, NULL) != 0 || parse(json, again, sizeof(again)) != 0) {
+        return 0;
+    }
+    int same = strcmp(blob, again) == 0, valid = ZK_VerifyVCBlob(again, pub, NULL);
+    printf("  %s: size query %d (%zu bytes), same blob %d, verifies %d\n", label, query, required, same, valid);
+    return query == ZK_STATUS_SIZE_QUERY && required == strlen(json) + 1 && same && valid == 1;
+}
+
+int main(void) {
+    static char blob[4096], sorted_blob[4096], field_blob[4096], tagged_blob[4096], parsed[4096];
+    static char json[8192], edited[8192];
+    const char* keys[] = {"role", "dept"};
+    const char* values[] = {"engineer", "rd"};
+    const char* sorted_keys[] = {"dept", "role"};
+    const char* sorted_values[] = {"rd", "engineer"};
+    const char* id_key[] = {"id"};
+
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        !make_blob(keys, values, 2, 0, blob, sizeof(blob)) ||
+        !make_blob(sorted_keys, sorted_values, 2, 0, sorted_blob, sizeof(sorted_blob)) ||
+        !make_blob(keys, values, 2, 1, field_blob, sizeof(field_blob))) {
+        return 1;
+    }
+    int ok = round_trip("field-signed", field_blob, json, sizeof(json));
+    if (ZK_SetFormatVersion(2) != 0 || !make_blob(keys, values, 2, 0, tagged_blob, sizeof(tagged_blob))) {
+        return 1;
+    }
+    ok &= round_trip("format version 2", tagged_blob, json, sizeof(json));
+    ZK_SetFormatVersion(1);
+    ok &= round_trip("signed", blob, json, sizeof(json));
+    int dates = strstr(json, "\"issuanceDate\":\"2024-01-01T00:00:00Z\"") != NULL &&
+                strstr(json, "\"expirationDate\":\"2024-12-31T23:59:59Z\"") != NULL;
+
+    /* Claim order */
+    strcpy(edited, json);
+    replace(edited, "[\"role\",\"dept\"]", "[\"dept\",\"role\"]");
+    int reordered = parse(edited, parsed, sizeof(parsed)) == 0 ? ZK_VerifyVCBlob(parsed, pub, NULL) : -1;
+    strcpy(edited, json);
+    replace(edited, "[\"role\",\"dept\"]", "[\"dept\",\"role\",\"role\"]");
+    int repeated = parse(edited, parsed, sizeof(parsed));
+    ZK_SerializeVCJson(sorted_blob, json, sizeof(json), NULL);
+    strcpy(edited, json);
+    replace(edited, "\"zkid:claimOrder\":[\"dept\",\"role\"],", "");
+    int key_order = parse(edited, parsed, sizeof(parsed)) == 0 ? ZK_VerifyVCBlob(parsed, pub, NULL) : -1;
+    printf("  dates RFC 3339 %d; claims reordered %d, key repeated %d, without claimOrder %d\n", dates, reordered,
+           repeated, key_order);
+
+    /* Dates */
+    strcpy(edited, json);
+    replace(edited, "2024-01-01T00:00:00Z", "2024-01-01T08:00:00+08:00");
+    int offset = parse(edited, parsed, sizeof(parsed)) == 0 && strcmp(parsed, sorted_blob) == 0;
+    strcpy(edited, json);
+    replace(edited, "2024-01-01T00:00:00Z", "2024-01-01T00:00:00.000Z");
+    int zero_fraction = parse(edited, parsed, sizeof(parsed)) == 0 && strcmp(parsed, sorted_blob) == 0;
+    strcpy(edited, json);
+    replace(edited, "2024-01-01T00:00:00Z", "2023-02-29T00:00:00Z");
+    int not_a_day = parse(edited, parsed, sizeof(parsed));
+    strcpy(edited, json);
+    replace(edited, "2024-01-01T00:00:00Z", "2024-01-01T00:00:00.5Z");
+    int fraction = parse(edited, parsed, sizeof(parsed));
+    strcpy(edited, json);
+    replace(edited, "2024-01-01T00:00:00Z", "1704067200");
+    int unix_time = parse(edited, parsed, sizeof(parsed));
+    printf("  offset %d, zero fraction %d; 2023-02-29 %d, half second %d, unix seconds %d\n", offset, zero_fraction,
+           not_a_day, fraction, unix_time);
+
+    /* A claim the subject id would shadow has no JSON form */
+    char id_blob[4096];
+    int id_claim = ZK_EncodeVC("alice", 5, "issuer", 6, ISSUED, EXPIRES, id_key, values, 1, NULL, id_blob,
+                               sizeof(id_blob)) == 0
+                       ? ZK_SerializeVCJson(id_blob, json, sizeof(json), NULL)
+                       : 0;
+    printf("  claim named id %d\n", id_claim);
+
+    return !ok || !dates || reordered != 0 || repeated != -1 || key_order != 1 || !offset || !zero_fraction ||
+           not_a_day != -1 || fraction != -1 || unix_time != -1 || id_claim != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Credentials keep their signatures through W3C VC JSON, in signed claim order"
@@ -0,0 +1,133 @@
+#!/bin/bash
+#
+# Build the library for the host and take a signed VC blob through a JWT and
+# back: the token is an EdDSA compact JWS whose payload maps the dates to nbf
+# and exp and the claims to members, it reads back to the same blob, which
+# still verifies, from its nbf through the credential's expiry date; before
+# nbf and from exp on it is refused with distinct errors, and so are a
+# token under another key, an edited payload, "alg": "none" and a claim
+# named like a registered claim.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_SignVCJwt(const char*, const char*, char*, size_t, size_t*);
+int ZK_VerifyVCJwt(const char*, size_t, const char*, uint64_t, char*, size_t);
+
+#define ZK_STATUS_SIZE_QUERY 1
+#define ZK_ERR_BAD_SIGNATURE -3
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_NOT_YET_VALID -37
+#define ISSUED 1704067200  /* 2024-01-01T00:00:00Z */
+#define EXPIRES 1735689599 /* 2024-12-31T23:59:59Z */
+
+/* {"alg":"EdDSA","typ":"JWT"} and {"alg":"none","typ":"JWT"} */
+#define EDDSA_HEADER "eyJhbGciOiJFZERTQSIsInR5cCI6IkpXVCJ9"
+#define NONE_HEADER "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0"
+
+static char pub[65], priv[65], other_pub[65], other_priv[65];
+
+static int verify(const char* token, const char* key, uint64_t now, char* blob, size_t size) {
+    return ZK_VerifyVCJwt(token, strlen(token), key, now, blob, size);
+}
+
+/* Decode the base64url text from `in` up to the next '.' */
+static size_t base64url_decode(const char* in, char* out) {
+    static const char alphabet[] = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    uint32_t bits = 0;
+    int count = 0;
+    size_t len = 0;
+    for (; *in && *in != '.'; in++) {
+        bits = bits << 6 | (uint32_t)(strchr(alphabet, *in) - alphabet);
+        if ((count += 6) >= 8) {
+            out[len++] = (char)(bits >> (count -= 8));
+        }
+    }
+    out[len] = 0;
+    return len;
+}
+
+int main(void) {
+    static char unsigned_blob[4096], blob[4096], parsed[4096], token[8192], edited[8192], payload[8192];
+    const char* keys[] = {"role", "dept"};
+    const char* values[] = {"engineer", "rd"};
+    const char* exp_key[] = {"exp"};
+    size_t required = 0;
+
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, ISSUED, EXPIRES, keys, values, 2, NULL, unsigned_blob,
+                    sizeof(unsigned_blob)) != 0 ||
+        ZK_SignVCBlob(unsigned_blob, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    /* Token */
+    int query = ZK_SignVCJwt(blob, priv, NULL, 0, &required);
+    if (ZK_SignVCJwt(blob, priv, token, sizeof(token), NULL) != 0) {
+        return 1;
+    }
+    base64url_decode(strchr(token, '.') + 1, payload);
+    int header = strncmp(token, EDDSA_HEADER ".", strlen(EDDSA_HEADER) + 1) == 0;
+    int members = strstr(payload, "\"iss\":\"issuer\"") && strstr(payload, "\"sub\":\"alice\"") &&
+                  strstr(payload, "\"nbf\":1704067200") && strstr(payload, "\"exp\":1735689600") &&
+                  strstr(payload, "\"role\":\"engineer\"") && strstr(payload, "\"zkid:proof\"");
+    printf("  token: %zu bytes, size query %d (%zu), EdDSA header %d, payload members %d\n", strlen(token), query,
+           required, header, members);
+    int token_ok = query == ZK_STATUS_SIZE_QUERY && required == strlen(token) + 1 && header && members;
+
+    /* Validity */
+    int back = verify(token, pub, ISSUED + 1000, parsed, sizeof(parsed)) == 0 && strcmp(parsed, blob) == 0;
+    int valid = ZK_VerifyVCBlob(parsed, pub, NULL);
+    int at_nbf = verify(token, pub, ISSUED, parsed, sizeof(parsed));
+    int last_second = verify(token, pub, EXPIRES, parsed, sizeof(parsed));
+    int early = verify(token, pub, ISSUED - 1, parsed, sizeof(parsed));
+    int at_exp = verify(token, pub, EXPIRES + 1, parsed, sizeof(parsed));
+    printf("  same blob %d, verifies %d; at nbf %d, last second %d, before nbf %d, at exp %d\n", back, valid,
+           at_nbf, last_second, early, at_exp);
+    int time_ok = back && valid == 1 && at_nbf == 0 && last_second == 0 && early == ZK_ERR_NOT_YET_VALID &&
+                  at_exp == ZK_ERR_VC_EXPIRED;
+
+    /* Refusals */
+    int other_key = verify(token, other_pub, ISSUED + 1000, parsed, sizeof(parsed));
+    strcpy(edited, token);
+    char* in_payload = strchr(edited, '.') + 5;
+    *in_payload = *in_payload == 'A' ? 'B' : 'A';
+    int tampered = verify(edited, pub, ISSUED + 1000, parsed, sizeof(parsed));
+    snprintf(edited, sizeof(edited), "%s%s", NONE_HEADER, strchr(token, '.'));
+    int alg_none = verify(edited, pub, ISSUED + 1000, parsed, sizeof(parsed));
+    int exp_claim = ZK_EncodeVC("alice", 5, "issuer", 6, ISSUED, EXPIRES, exp_key, values, 1, NULL, unsigned_blob,
+                                sizeof(unsigned_blob)) == 0
+                        ? ZK_SignVCJwt(unsigned_blob, priv, token, sizeof(token), NULL)
+                        : 0;
+    printf("  other key %d, edited payload %d, alg none %d, claim named exp %d\n", other_key, tampered, alg_none,
+           exp_claim);
+    int refusals_ok = other_key == ZK_ERR_BAD_SIGNATURE && tampered == ZK_ERR_BAD_SIGNATURE && alg_none == -1 &&
+                      exp_claim == -1;
+
+    return !token_ok || !time_ok || !refusals_ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Credentials keep their signatures through EdDSA JWTs, inside nbf and exp"
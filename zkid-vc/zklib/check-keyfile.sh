@@ -0,0 +1,206 @@
+#!/bin/bash
+#
+# Build the library for the host and check key files. One process sets up
+# the VC circuit, proves and saves its keys; a second loads them and must
+# verify the first one's proof and prove in turn. Every damaged copy of the
+# file is refused with ZK_ERR_KEYFILE_INVALID and leaves the second process
+# without keys: single bit flips across the file, truncations, a trailing
+# byte, and files with a valid checksum over another circuit id, a proving
+# key whose embedded verifying key was altered, or malformed fields.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_IsInitialized(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SaveKeys(const char*);
+int ZK_LoadKeys(const char*);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_KEYFILE_INVALID -39
+
+static char path[4096], copy[4096], message[256];
+
+static char* in_dir(char* out, const char* dir, const char* name) {
+    snprintf(out, 4096, "%s/%s", dir, name);
+    return out;
+}
+
+static unsigned char* read_file(const char* name, size_t* len) {
+    FILE* f = fopen(name, "rb");
+    if (!f) return NULL;
+    fseek(f, 0, SEEK_END);
+    *len = (size_t)ftell(f);
+    fseek(f, 0, SEEK_SET);
+    unsigned char* data = malloc(*len + 1);
+    if (!data || fread(data, 1, *len, f) != *len) exit(1);
+    fclose(f);
+    return data;
+}
+
+static void write_file(const char* name, const void* data, size_t len) {
+    FILE* f = fopen(name, "wb");
+    if (!f || fwrite(data, 1, len, f) != len) exit(1);
+    fclose(f);
+}
+
+static uint32_t u32_at(const unsigned char* p) {
+    return p[0] | (uint32_t)p[1] << 8 | (uint32_t)p[2] << 16 | (uint32_t)p[3] << 24;
+}
+
+/* Set up, prove, save the keys; leave bodies (file minus checksum) for the script to re-checksum */
+static int save(const char* dir) {
+    static char pub[65], priv[65], sig[129], proof[1024];
+    int before = ZK_SaveKeys(in_dir(path, dir, "keys"));
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42, proof, sizeof(proof), NULL) != 0) {
+        return 1;
+    }
+    int saved = ZK_SaveKeys(in_dir(path, dir, "keys"));
+    int no_dir = ZK_SaveKeys(in_dir(path, dir, "missing/keys"));
+    int empty = ZK_SaveKeys("");
+    int null = ZK_SaveKeys(NULL);
+    printf("  save: before init %d, saved %d, missing directory %d, empty path %d, NULL %d\n", before, saved,
+           no_dir, empty, null);
+    in_dir(path, dir, "proof");
+    write_file(path, proof, strlen(proof));
+    in_dir(path, dir, "pub");
+    write_file(path, pub, strlen(pub));
+
+    size_t len = 0;
+    unsigned char* keys = read_file(in_dir(path, dir, "keys"), &len);
+    if (!keys || len < 64) return 1;
+    size_t body = len - 32;
+    /* "ZKKF" | version | circuit_id | verifying_key | proving_key */
+    size_t circuit = 5, vk = circuit + 4 + u32_at(keys + circuit), pk = vk + 4 + u32_at(keys + vk);
+    keys[circuit + 4 + u32_at(keys + circuit) - 1] ^= 0x01;
+    write_file(in_dir(path, dir, "other-circuit.body"), keys, body);
+    keys[circuit + 4 + u32_at(keys + circuit) - 1] ^= 0x01;
+    keys[pk + 4] ^= 0x01;
+    write_file(in_dir(path, dir, "pk-vk.body"), keys, body);
+    keys[pk + 4] ^= 0x01;
+    keys[vk] ^= 0x80;
+    write_file(in_dir(path, dir, "fields.body"), keys, body);
+    free(keys);
+
+    return before != ZK_ERR_NOT_INITIALIZED || saved != 0 || no_dir != -1 || empty != -1 || null != -1;
+}
+
+static int refused(const char* what, const char* dir, const void* data, size_t len) {
+    write_file(in_dir(copy, dir, "damaged"), data, len);
+    int code = ZK_LoadKeys(copy);
+    if (code != ZK_ERR_KEYFILE_INVALID) {
+        ZK_GetLastError(message, sizeof(message));
+        printf("  %s: %d (%s)\n", what, code, message);
+    }
+    return code == ZK_ERR_KEYFILE_INVALID;
+}
+
+/* Refuse damaged files, then load the keys and verify the saved proof */
+static int load(const char* dir) {
+    static char proof[1024], pub[65], again[1024], sig[129], priv[65], what[64];
+    size_t len = 0, proof_len = 0, pub_len = 0;
+    unsigned char* keys = read_file(in_dir(path, dir, "keys"), &len);
+    char* saved = (char*)read_file(in_dir(path, dir, "proof"), &proof_len);
+    char* saved_pub = (char*)read_file(in_dir(path, dir, "pub"), &pub_len);
+    if (!keys || !saved || !saved_pub) return 1;
+    memcpy(proof, saved, proof_len);
+    memcpy(pub, saved_pub, pub_len);
+    unsigned char* damaged = malloc(len + 1);
+
+    int missing = ZK_LoadKeys(in_dir(path, dir, "missing"));
+    int flips = 0, flips_ok = 1;
+    size_t step = len / 257 + 1;
+    for (size_t i = 0; i < len; i += i < 64 ? 1 : step) {
+        memcpy(damaged, keys, len);
+        damaged[i] ^= (unsigned char)(1u << (i % 8));
+        snprintf(what, sizeof(what), "byte %zu flipped", i);
+        flips_ok &= refused(what, dir, damaged, len);
+        flips++;
+    }
+    memcpy(damaged, keys, len);
+    damaged[len - 1] ^= 0x80;
+    flips_ok &= refused("last bit flipped", dir, damaged, len);
+
+    size_t cuts[] = {0, 1, 5, 31, 32, 33, len / 2, len - 33, len - 32, len - 1};
+    int cuts_ok = 1;
+    for (size_t i = 0; i < sizeof(cuts) / sizeof(cuts[0]); i++) {
+        snprintf(what, sizeof(what), "truncated to %zu bytes", cuts[i]);
+        cuts_ok &= refused(what, dir, keys, cuts[i]);
+    }
+    memcpy(damaged, keys, len);
+    damaged[len] = 0;
+    int trailing = refused("trailing byte", dir, damaged, len + 1);
+
+    int other_circuit = ZK_LoadKeys(in_dir(path, dir, "other-circuit"));
+    ZK_GetLastError(message, sizeof(message));
+    printf("  other circuit id, valid checksum: %d (%s)\n", other_circuit, message);
+    int pk_vk = ZK_LoadKeys(in_dir(path, dir, "pk-vk"));
+    ZK_GetLastError(message, sizeof(message));
+    printf("  altered verifying key in the proving key, valid checksum: %d (%s)\n", pk_vk, message);
+    int fields = ZK_LoadKeys(in_dir(path, dir, "fields"));
+    ZK_GetLastError(message, sizeof(message));
+    printf("  malformed field length, valid checksum: %d (%s)\n", fields, message);
+    int still_empty = ZK_IsInitialized() == 0;
+    printf("  missing file %d; %d bit flips refused %d, truncations %d, trailing byte %d; no keys loaded %d\n",
+           missing, flips + 1, flips_ok, cuts_ok, trailing, still_empty);
+    int damaged_ok = missing == -1 && flips_ok && cuts_ok && trailing && other_circuit == ZK_ERR_KEYFILE_INVALID &&
+                     pk_vk == ZK_ERR_KEYFILE_INVALID && fields == ZK_ERR_KEYFILE_INVALID && still_empty;
+
+    int loaded = ZK_LoadKeys(in_dir(path, dir, "keys"));
+    int verifies = ZK_VerifyVCProof(proof, pub, 150, 42);
+    int proves = ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) == 0 &&
+                 ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) == 0 &&
+                 ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 7, again, sizeof(again),
+                                    NULL) == 0 &&
+                 ZK_VerifyVCProof(again, pub, 150, 7) == 1;
+    printf("  load: %d, saved proof verifies %d, proves and verifies %d\n", loaded, verifies, proves);
+    int loaded_ok = loaded == 0 && verifies == 1 && proves;
+
+    free(damaged);
+    free(keys);
+    return !damaged_ok || !loaded_ok;
+}
+
+int main(int argc, char** argv) {
+    if (argc != 3) return 1;
+    return strcmp(argv[1], "save") == 0 ? save(argv[2]) : load(argv[2]);
+}
+EOF
+
+# A body with its SHA-256 appended, as a key file would carry it
+checksummed() {
+    cat "$1.body"
+    printf "$(sha256sum "$1.body" | cut -c1-64 | sed 's/../\\x&/g')"
+}
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" save "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+for name in other-circuit pk-vk fields; do
+    checksummed "$WORK_DIR/$name" > "$WORK_DIR/$name"
+done
+"$WORK_DIR/check" load "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Saved keys load in another process and damaged key files are refused"
@@ -0,0 +1,232 @@
+#!/bin/bash
+#
+# Build the library for the host and check collection limits: a full
+# nullifier set, challenge store, revocation registry or trust store under
+# ZK_EVICT_REJECT refuses the next insert with ZK_ERR_CAPACITY and counts
+# it; under ZK_EVICT_OLDEST it drops the lowest epoch, the challenge that
+# expires first or the first trusted issuer. Threads racing to fill a set
+# never push it past its capacity, pruning removes exactly the old entries,
+# the trust store pages in order, and the revocation registry refuses to
+# evict.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+
+typedef struct Context Context;
+typedef struct {
+    uint64_t capacity;
+    int policy;
+} ZkCollectionLimit;
+typedef struct {
+    uint64_t count, capacity, evicted, rejected;
+} ZkCollectionStats;
+typedef struct {
+    uint64_t count, scopes, oldest_epoch, newest_epoch, current_epoch, capacity, evicted, rejected;
+} ZkNullifierSetStats;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SetCollectionLimit(int, const ZkCollectionLimit*);
+int ZK_CollectionStats(int, ZkCollectionStats*);
+int ZK_RecordNullifier(const char*, size_t, const char*, size_t);
+int ZK_SetNullifierEpoch(uint64_t);
+int ZK_PruneNullifiers(uint64_t, uint64_t*);
+int ZK_NullifierSetStats(ZkNullifierSetStats*);
+int ZK_IssueChallenge(uint64_t, uint64_t);
+int ZK_ConsumeChallenge(uint64_t, uint64_t);
+int ZK_PruneChallenges(uint64_t, uint64_t*);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_ContextCreate(Context**);
+int ZK_ContextTrustIssuer(Context*, const char*);
+int ZK_TrustStoreList(const Context*, size_t, size_t, char*, size_t);
+void ZK_ContextFree(Context*);
+
+#define ZK_ERR_CAPACITY -21
+#define ZK_COLLECTION_TRUST_STORE 0
+#define ZK_COLLECTION_CHALLENGES 1
+#define ZK_COLLECTION_NULLIFIERS 2
+#define ZK_COLLECTION_REVOCATIONS 3
+#define ZK_EVICT_REJECT 0
+#define ZK_EVICT_OLDEST 1
+#define THREADS 8
+#define PER_THREAD 4
+
+static int recorded[THREADS];
+
+static int record(const char* nullifier) {
+    return ZK_RecordNullifier("poll", 4, nullifier, strlen(nullifier));
+}
+
+static void* race(void* arg) {
+    int t = (int)(intptr_t)arg;
+    for (int i = 0; i < PER_THREAD; i++) {
+        char nullifier[32];
+        snprintf(nullifier, sizeof(nullifier), "race-%d-%d", t, i);
+        recorded[t] += record(nullifier) == 1;
+    }
+    return NULL;
+}
+
+static int set_limit(int collection, uint64_t capacity, int policy) {
+    ZkCollectionLimit limit = {capacity, policy};
+    return ZK_SetCollectionLimit(collection, &limit);
+}
+
+static int nullifiers(void) {
+    ZkNullifierSetStats stats = {0};
+    int rc = set_limit(ZK_COLLECTION_NULLIFIERS, 4, ZK_EVICT_REJECT);
+
+    /* n0..n3 in epochs 1..4 fill the set; n4 is refused */
+    char name[8];
+    for (int i = 0; i < 4; i++) {
+        snprintf(name, sizeof(name), "n%d", i);
+        rc |= ZK_SetNullifierEpoch(i + 1) | (record(name) != 1);
+    }
+    int refused = record("n4");
+    ZK_NullifierSetStats(&stats);
+    printf("  nullifiers: fifth under reject %d, count %llu, rejected %llu\n", refused,
+           (unsigned long long)stats.count, (unsigned long long)stats.rejected);
+    if (rc != 0 || refused != ZK_ERR_CAPACITY || stats.count != 4 || stats.rejected != 1) {
+        return 0;
+    }
+
+    /* Under oldest, n4 and n5 push out epochs 1 and 2 */
+    rc = set_limit(ZK_COLLECTION_NULLIFIERS, 4, ZK_EVICT_OLDEST) | ZK_SetNullifierEpoch(5) | (record("n4") != 1) |
+         ZK_SetNullifierEpoch(6) | (record("n5") != 1);
+    ZK_NullifierSetStats(&stats);
+    int kept = record("n2");
+    printf("  nullifiers: after two evictions count %llu, epochs %llu..%llu, evicted %llu, n2 kept %d\n",
+           (unsigned long long)stats.count, (unsigned long long)stats.oldest_epoch,
+           (unsigned long long)stats.newest_epoch, (unsigned long long)stats.evicted, kept == 0);
+    if (rc != 0 || stats.count != 4 || stats.oldest_epoch != 3 || stats.newest_epoch != 6 || stats.evicted != 2 ||
+        kept != 0) {
+        return 0;
+    }
+
+    /* Pruning epochs 3 and 4 leaves n4 and n5 */
+    uint64_t removed = 0;
+    rc = ZK_PruneNullifiers(5, &removed);
+    ZK_NullifierSetStats(&stats);
+    printf("  nullifiers: pruned before epoch 5 %d, removed %llu, left %llu\n", rc, (unsigned long long)removed,
+           (unsigned long long)stats.count);
+    if (rc != 0 || removed != 2 || stats.count != 2) {
+        return 0;
+    }
+
+    /* Threads race for the four places left under reject */
+    pthread_t threads[THREADS];
+    int won = 0;
+    set_limit(ZK_COLLECTION_NULLIFIERS, 6, ZK_EVICT_REJECT);
+    for (int t = 0; t < THREADS; t++) {
+        pthread_create(&threads[t], NULL, race, (void*)(intptr_t)t);
+    }
+    for (int t = 0; t < THREADS; t++) {
+        pthread_join(threads[t], NULL);
+        won += recorded[t];
+    }
+    ZK_NullifierSetStats(&stats);
+    printf("  nullifiers: %d of %d racing inserts recorded, count %llu, rejected %llu\n", won,
+           THREADS * PER_THREAD, (unsigned long long)stats.count, (unsigned long long)stats.rejected);
+    return won == 4 && stats.count == 6 && stats.rejected == 1 + THREADS * PER_THREAD - 4;
+}
+
+static int challenges(void) {
+    uint64_t now = (uint64_t)time(NULL), removed = 0;
+    ZkCollectionStats stats = {0};
+
+    /* Under oldest the challenge expiring first makes room */
+    int rc = set_limit(ZK_COLLECTION_CHALLENGES, 2, ZK_EVICT_OLDEST) | ZK_IssueChallenge(1, now + 100) |
+             ZK_IssueChallenge(2, now + 50) | ZK_IssueChallenge(3, now + 200);
+    ZK_CollectionStats(ZK_COLLECTION_CHALLENGES, &stats);
+    int evicted = ZK_ConsumeChallenge(2, now), first = ZK_ConsumeChallenge(1, now);
+    printf("  challenges: count %llu, evicted %llu; soonest expiring consumable %d, other %d\n",
+           (unsigned long long)stats.count, (unsigned long long)stats.evicted, evicted, first);
+    if (rc != 0 || stats.count != 2 || stats.evicted != 1 || evicted != 0 || first != 1) {
+        return 0;
+    }
+
+    /* Under reject the third is refused; pruning takes the expired one */
+    rc = set_limit(ZK_COLLECTION_CHALLENGES, 2, ZK_EVICT_REJECT) | ZK_IssueChallenge(4, now - 10);
+    int refused = ZK_IssueChallenge(5, now + 300);
+    rc |= ZK_PruneChallenges(now, &removed);
+    ZK_CollectionStats(ZK_COLLECTION_CHALLENGES, &stats);
+    printf("  challenges: third under reject %d, rejected %llu; pruned %llu, left %llu\n", refused,
+           (unsigned long long)stats.rejected, (unsigned long long)removed, (unsigned long long)stats.count);
+    return rc == 0 && refused == ZK_ERR_CAPACITY && stats.rejected == 1 && removed == 1 && stats.count == 1 &&
+           ZK_ConsumeChallenge(3, now) == 1;
+}
+
+static int revocations(void) {
+    ZkCollectionStats stats = {0};
+    int oldest = set_limit(ZK_COLLECTION_REVOCATIONS, 1, ZK_EVICT_OLDEST);
+    int rc = set_limit(ZK_COLLECTION_REVOCATIONS, 1, ZK_EVICT_REJECT) | ZK_RevokeCredential("vc-1", 4, 1);
+    int refused = ZK_RevokeCredential("vc-2", 4, 1);
+    ZK_CollectionStats(ZK_COLLECTION_REVOCATIONS, &stats);
+    printf("  revocations: evicting policy %d, second under reject %d, count %llu, rejected %llu\n", oldest, refused,
+           (unsigned long long)stats.count, (unsigned long long)stats.rejected);
+    return oldest == -1 && rc == 0 && refused == ZK_ERR_CAPACITY && stats.count == 1 && stats.rejected == 1;
+}
+
+static int trust_store(void) {
+    char keys[4][65], priv[65], page[1024], expected[1024];
+    Context* ctx = NULL;
+    ZkCollectionStats stats = {0};
+    for (int i = 0; i < 4; i++) {
+        if (ZK_GenerateIssuerKeypair(keys[i], sizeof(keys[i]), priv, sizeof(priv)) != 0) {
+            return 0;
+        }
+    }
+
+    /* Three issuers into two places under oldest, a fourth under reject */
+    int rc = ZK_ContextCreate(&ctx) | set_limit(ZK_COLLECTION_TRUST_STORE, 2, ZK_EVICT_OLDEST);
+    for (int i = 0; i < 3; i++) {
+        rc |= ZK_ContextTrustIssuer(ctx, keys[i]);
+    }
+    rc |= set_limit(ZK_COLLECTION_TRUST_STORE, 2, ZK_EVICT_REJECT);
+    int refused = ZK_ContextTrustIssuer(ctx, keys[3]);
+    int again = ZK_ContextTrustIssuer(ctx, keys[2]);
+    ZK_CollectionStats(ZK_COLLECTION_TRUST_STORE, &stats);
+    rc |= ZK_TrustStoreList(ctx, 1, 1, page, sizeof(page));
+    snprintf(expected, sizeof(expected), "{\"issuers\":[\"%s\"],\"offset\":1,\"total\":2}", keys[2]);
+    int second_page = strcmp(page, expected) == 0;
+    rc |= ZK_TrustStoreList(ctx, 0, 10, page, sizeof(page));
+    snprintf(expected, sizeof(expected), "{\"issuers\":[\"%s\",\"%s\"],\"offset\":0,\"total\":2}", keys[1], keys[2]);
+    int whole = strcmp(page, expected) == 0;
+    printf("  trust store: fourth under reject %d, already trusted %d, evicted %llu, page 2 %d, all %d\n", refused,
+           again, (unsigned long long)stats.evicted, second_page, whole);
+    ZK_ContextFree(ctx);
+    return rc == 0 && refused == ZK_ERR_CAPACITY && again == 0 && stats.evicted == 1 && second_page && whole;
+}
+
+int main(void) {
+    if (!nullifiers() || !challenges() || !revocations() || !trust_store()) {
+        return 1;
+    }
+    ZkCollectionStats stats;
+    int unknown = set_limit(9, 1, ZK_EVICT_REJECT), policy = set_limit(ZK_COLLECTION_NULLIFIERS, 1, 7);
+    int null_limit = ZK_SetCollectionLimit(ZK_COLLECTION_NULLIFIERS, NULL);
+    int unknown_stats = ZK_CollectionStats(9, &stats), null_stats = ZK_CollectionStats(0, NULL);
+    printf("  unknown collection %d, unknown policy %d, NULL limit %d; stats of unknown %d, NULL %d\n", unknown,
+           policy, null_limit, unknown_stats, null_stats);
+    return unknown != -1 || policy != -1 || null_limit != -1 || unknown_stats != -1 || null_stats != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Full collections reject or evict the oldest entries as configured"
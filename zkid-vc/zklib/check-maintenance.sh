@@ -0,0 +1,243 @@
+#!/bin/bash
+#
+# Build the library for the host and check wallet maintenance: a long
+# synthetic workload of challenges, proofs and repeated consent receipts
+# stays bounded in ZK_MemoryReport when ZK_Maintain runs after each round,
+# and grows when it does not. An outstanding challenge, an open transfer and
+# a set checkpoint buffer survive maintenance, a lowered nonce cache
+# capacity is trimmed to, and maintenance on a second thread does not
+# disturb challenges being issued and consumed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+typedef struct {
+    uint32_t capacity;
+    uint64_t ttl_secs;
+    int policy;
+} ZkNonceCacheConfig;
+
+typedef struct {
+    uint64_t challenges_pruned, nonce_pairs_dropped, transfers_dropped, checkpoint_bytes_wiped, receipts_dropped,
+        bytes_freed;
+    int complete;
+} ZkMaintenanceReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+void ZK_StoreClose(CredentialStore*);
+int ZK_CreateConsentReceipt(CredentialStore*, const char*, size_t, const char*, size_t, const char*, const char*,
+                            char*, size_t);
+int ZK_SetNonceReusePolicy(const ZkNonceCacheConfig*);
+int ZK_IssueChallenge(uint64_t, uint64_t);
+int ZK_ConsumeChallenge(uint64_t, uint64_t);
+int ZK_TransferBegin(int, size_t, uint64_t*);
+int ZK_TransferChunk(uint64_t, size_t, const uint8_t*, size_t);
+void ZK_TransferAbort(uint64_t);
+int ZK_SetProveCheckpointing(uint8_t*, size_t);
+int ZK_Maintain(CredentialStore*, uint64_t, uint32_t, ZkMaintenanceReport*);
+int ZK_MemoryReport(const CredentialStore*, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_NONCE_REUSE_RERANDOMIZE 1
+#define ZK_TRANSFER_IMPORT_PRESENTATION 2
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define ROUNDS 8
+#define CHALLENGES 200
+#define PROOFS 2
+#define CAPACITY 8
+#define IN_FLIGHT 7
+
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static char pub[65], priv[65], holder_pub[65], holder_priv[65], encoded[8192], blob[8192], proof[4096];
+static char presentation[16384], request[4096], receipt[4096], json[4096];
+static uint8_t checkpoint[1 << 16];
+static volatile int stop;
+
+/* The number after `"key":` in the `subsystem` object of the memory report, or -1 */
+static long held(const char* subsystem, const char* key) {
+    char pattern[64];
+    snprintf(pattern, sizeof(pattern), "\"%s\":{", subsystem);
+    const char* at = strstr(json, pattern);
+    snprintf(pattern, sizeof(pattern), "\"%s\":", key);
+    at = at == NULL ? NULL : strstr(at, pattern);
+    return at == NULL ? -1 : strtol(at + strlen(pattern), NULL, 10);
+}
+
+/* One round of wallet traffic at `now`: challenges, proofs and a repeated receipt */
+static int workload(CredentialStore* store, int round, uint64_t now) {
+    for (uint64_t i = 0; i < CHALLENGES; i++) {
+        uint64_t nonce = 1000000 * (round + 1) + i;
+        if (ZK_IssueChallenge(nonce, now + 50) != 0 || (i % 2 == 0 && ZK_ConsumeChallenge(nonce, now) != 1)) {
+            return 0;
+        }
+    }
+    for (uint64_t k = 0; k < PROOFS; k++) {
+        if (ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 100 * round + k, proof, sizeof(proof)) != 0) {
+            return 0;
+        }
+    }
+    for (int k = 0; k < 2; k++) {
+        if (ZK_CreateConsentReceipt(store, presentation, strlen(presentation), request, strlen(request), NULL,
+                                    holder_priv, receipt, sizeof(receipt)) != 0) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+static void* housekeeping(void* unused) {
+    (void)unused;
+    while (!stop) {
+        ZK_Maintain(NULL, NOW, 0, NULL);
+    }
+    return NULL;
+}
+
+int main(int argc, char** argv) {
+    const char* dir = argc > 1 ? argv[1] : ".";
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    const char* request_keys[] = {"claims"};
+    const char* request_values[] = {"role"};
+    const ZkNonceCacheConfig cache = {CAPACITY, 600, ZK_NONCE_REUSE_RERANDOMIZE};
+    char path[512];
+    snprintf(path, sizeof(path), "%s/wallet.store", dir);
+    CredentialStore* store = NULL;
+    if (ZK_Init() != 0 || ZK_StoreOpen(path, raw_key, sizeof(raw_key), ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(holder_pub, sizeof(holder_pub), holder_priv, sizeof(holder_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, NULL, NULL, 0, presentation, sizeof(presentation)) != 0 ||
+        ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, request_keys, request_values, 1, request,
+                                     sizeof(request)) != 0 ||
+        ZK_SetNonceReusePolicy(&cache) != 0) {
+        return 1;
+    }
+
+    /* State an operation in flight still needs */
+    uint64_t handle = 0;
+    const uint8_t chunk[8] = {0};
+    if (ZK_IssueChallenge(IN_FLIGHT, NOW + DAY) != 0 ||
+        ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 2 * sizeof(chunk), &handle) != 0 ||
+        ZK_TransferChunk(handle, 0, chunk, sizeof(chunk)) != 0 ||
+        ZK_SetProveCheckpointing(checkpoint, sizeof(checkpoint)) != 0) {
+        return 1;
+    }
+
+    /* The workload, maintained after every round */
+    long most_challenges = 0, most_pairs = 0, most_receipts = 0, freed = 0;
+    int complete = 1;
+    for (int round = 0; round < ROUNDS; round++) {
+        uint64_t now = NOW + 100 * round;
+        ZkMaintenanceReport report;
+        if (!workload(store, round, now) || ZK_Maintain(store, now + 60, 0, &report) != 0 ||
+            ZK_MemoryReport(store, json, sizeof(json)) != 0) {
+            return 1;
+        }
+        complete &= report.complete == 1 && report.challenges_pruned == CHALLENGES / 2 &&
+                    report.receipts_dropped >= 1 && report.transfers_dropped == 0 &&
+                    report.checkpoint_bytes_wiped == 0;
+        freed += report.bytes_freed;
+        most_challenges = held("challenges", "entries") > most_challenges ? held("challenges", "entries")
+                                                                         : most_challenges;
+        most_pairs = held("nonce_cache", "entries") > most_pairs ? held("nonce_cache", "entries") : most_pairs;
+        most_receipts = held("store", "receipts") > most_receipts ? held("store", "receipts") : most_receipts;
+    }
+    printf("  %d rounds maintained (every task ran %d, %ld bytes freed): at most %ld challenges, %ld nonce pairs, "
+           "%ld receipts held\n",
+           ROUNDS, complete, freed, most_challenges, most_pairs, most_receipts);
+    if (!complete || freed <= 0 || most_challenges != 1 || most_pairs > CAPACITY || most_receipts != 1) {
+        printf("  %s\n", json);
+        return 1;
+    }
+
+    /* Without maintenance the same traffic piles up */
+    for (int round = ROUNDS; round < 2 * ROUNDS; round++) {
+        if (!workload(store, round, NOW + 100 * round) || ZK_MemoryReport(store, json, sizeof(json)) != 0) {
+            return 1;
+        }
+    }
+    long piled_challenges = held("challenges", "entries"), piled_receipts = held("store", "receipts");
+    printf("  %d rounds unmaintained: %ld challenges, %ld receipts held\n", ROUNDS, piled_challenges,
+           piled_receipts);
+    if (piled_challenges != 1 + ROUNDS * CHALLENGES / 2 || piled_receipts != 1 + 2 * ROUNDS) {
+        return 1;
+    }
+
+    /* In-flight state survived; a lowered capacity is trimmed to */
+    ZkMaintenanceReport report;
+    const ZkNonceCacheConfig smaller = {2, 600, ZK_NONCE_REUSE_RERANDOMIZE};
+    if (ZK_Maintain(store, NOW + 200 * ROUNDS, 0, &report) != 0 || ZK_SetNonceReusePolicy(&smaller) != 0 ||
+        ZK_Maintain(store, NOW + 200 * ROUNDS, 0, &report) != 0 || ZK_MemoryReport(store, json, sizeof(json)) != 0) {
+        return 1;
+    }
+    int trimmed = report.nonce_pairs_dropped == CAPACITY - 2 && held("nonce_cache", "entries") == 2;
+    int transfer_open = held("transfers", "active") == 1 && ZK_TransferChunk(handle, sizeof(chunk), chunk,
+                                                                             sizeof(chunk)) == 0;
+    int checkpoint_set = held("checkpoint", "capacity") == (long)sizeof(checkpoint);
+    int outstanding = ZK_ConsumeChallenge(IN_FLIGHT, NOW + 200 * ROUNDS);
+    printf("  capacity lowered to 2, trimmed %d; transfer still open %d, checkpoint buffer still set %d, "
+           "outstanding challenge consumed %d\n",
+           trimmed, transfer_open, checkpoint_set, outstanding);
+    ZK_TransferAbort(handle);
+    ZK_SetProveCheckpointing(NULL, 0);
+    if (!trimmed || !transfer_open || !checkpoint_set || outstanding != 1) {
+        return 1;
+    }
+
+    /* Maintenance on another thread while challenges come and go */
+    pthread_t thread;
+    pthread_create(&thread, NULL, housekeeping, NULL);
+    int consumed = 0;
+    for (uint64_t i = 0; i < 2000; i++) {
+        consumed += ZK_IssueChallenge(9000000 + i, NOW + DAY) == 0 && ZK_ConsumeChallenge(9000000 + i, NOW) == 1;
+    }
+    stop = 1;
+    pthread_join(thread, NULL);
+    printf("  challenges consumed next to a housekeeping thread %d of 2000\n", consumed);
+
+    int short_buffer = ZK_MemoryReport(store, json, 16);
+    int no_store = ZK_MemoryReport(NULL, json, sizeof(json)) == 0 && strstr(json, "\"store\":null") != NULL;
+    int null_out = ZK_MemoryReport(store, NULL, 0);
+    printf("  report: short buffer %d, without a store %d, NULL %d\n", short_buffer, no_store, null_out);
+    ZK_StoreClose(store);
+    return consumed != 2000 || short_buffer != ZK_ERR_BUFFER_TOO_SMALL || !no_store || null_out != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Maintenance keeps a long-running wallet bounded without breaking work in flight"
@@ -0,0 +1,137 @@
+#!/bin/bash
+#
+# Build the library for the host and check wallet-side matching: of the
+# credentials in a store, ZK_MatchCredentials returns exactly those signed
+# by the request's issuer key, active at its time, not revoked and meeting
+# its claim and predicate constraints, ranked by fewest extra claims, then
+# latest expiry; each match reports the claims it discloses. A request no
+# credential meets gives an empty list, and a garbled request, a short
+# buffer and NULL input are refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_MatchCredentials(const char*, size_t, CredentialStore*, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static char pub[65], priv[65], other_pub[65], other_priv[65];
+static char request[4096], out[16384], small[16];
+
+enum { LATEST, PLAIN, EXTRA, OTHER_KEY, EXPIRED, LOW, REVOKED, COUNT };
+static char ids[COUNT][128];
+
+/* Sign a credential for alice with role=engineer, level=`level` and an
+   optional team claim, and add it to the store */
+static int add(CredentialStore* store, int which, const char* key, const char* level, int team, uint64_t from,
+               uint64_t until) {
+    static char encoded[8192], blob[8192];
+    const char* keys[] = {"role", "level", "team"};
+    const char* values[] = {"engineer", level, "platform"};
+    return ZK_EncodeVC("alice", 5, "acme", 4, from, until, keys, values, team ? 3 : 2, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, key, blob, sizeof(blob)) == 0 &&
+           ZK_StoreAddVC(store, blob, ids[which], sizeof(ids[which])) == 0;
+}
+
+/* Position of credential `which` in the match list, or -1 */
+static long position(int which) {
+    const char* at = strstr(out, ids[which]);
+    return at == NULL ? -1 : at - out;
+}
+
+int main(int argc, char** argv) {
+    char path[512];
+    CredentialStore* store = NULL;
+    snprintf(path, sizeof(path), "%s/wallet.store", argc > 1 ? argv[1] : ".");
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_StoreOpen(path, raw_key, sizeof(raw_key), ZK_STORE_KEY_RAW, &store) != 0) {
+        return 1;
+    }
+    if (!add(store, LATEST, priv, "5", 0, NOW - DAY, NOW + 3 * DAY) ||
+        !add(store, PLAIN, priv, "6", 0, NOW - DAY, NOW + DAY) ||
+        !add(store, EXTRA, priv, "7", 1, NOW - DAY, NOW + 2 * DAY) ||
+        !add(store, OTHER_KEY, other_priv, "9", 0, NOW - DAY, NOW + DAY) ||
+        !add(store, EXPIRED, priv, "9", 0, NOW - 2 * DAY, NOW - DAY) ||
+        !add(store, LOW, priv, "2", 0, NOW - DAY, NOW + DAY) ||
+        !add(store, REVOKED, priv, "8", 0, NOW - DAY, NOW + DAY) ||
+        ZK_RevokeCredential(ids[REVOKED], strlen(ids[REVOKED]), NOW) != 0) {
+        return 1;
+    }
+
+    /* Engineers at level 5 or above, from this issuer key */
+    const char* keys[] = {"claims", "require.level"};
+    const char* values[] = {"role", ">=5"};
+    if (ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, keys, values, 2, request, sizeof(request)) != 0 ||
+        ZK_MatchCredentials(request, strlen(request), store, out, sizeof(out)) != 0) {
+        return 1;
+    }
+    long latest = position(LATEST), plain = position(PLAIN), extra = position(EXTRA);
+    printf("  positions: latest expiry %ld, plain %ld, extra claim %ld; other key %ld, expired %ld, level 2 %ld, "
+           "revoked %ld\n",
+           latest, plain, extra, position(OTHER_KEY), position(EXPIRED), position(LOW), position(REVOKED));
+    if (latest < 0 || plain < latest || extra < plain || position(OTHER_KEY) != -1 || position(EXPIRED) != -1 ||
+        position(LOW) != -1 || position(REVOKED) != -1) {
+        return 1;
+    }
+    int disclosed = strstr(out, "{\"key\":\"team\",\"value\":\"platform\"}") != NULL &&
+                    strstr(out, "\"extra_claims\":1") != NULL && strstr(out, "\"warnings\":[]") != NULL;
+    printf("  disclosed claims reported %d\n", disclosed);
+    if (!disclosed) {
+        return 1;
+    }
+
+    /* A request nothing meets, then malformed calls */
+    const char* other_values[] = {"role", ">=10"};
+    if (ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, keys, other_values, 2, request, sizeof(request)) != 0) {
+        return 1;
+    }
+    int none = ZK_MatchCredentials(request, strlen(request), store, out, sizeof(out));
+    int empty = strstr(out, "\"matches\":[]") != NULL;
+    int short_buffer = ZK_MatchCredentials(request, strlen(request), store, small, sizeof(small));
+    int garbled = ZK_MatchCredentials("5a4b5251ff", 10, store, out, sizeof(out));
+    int null_store = ZK_MatchCredentials(request, strlen(request), NULL, out, sizeof(out));
+    printf("  level 10: %d (empty %d); short buffer %d, garbled request %d, NULL store %d\n", none, empty,
+           short_buffer, garbled, null_store);
+
+    ZK_StoreClose(store);
+    return none != 0 || !empty || short_buffer != ZK_ERR_BUFFER_TOO_SMALL || garbled != ZK_ERR_CORRUPT ||
+           null_store != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Matching ranks exactly the stored credentials that can answer a request"
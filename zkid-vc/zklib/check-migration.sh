@@ -0,0 +1,136 @@
+#!/bin/bash
+#
+# Build the standard and strict libraries for the host and check a proof
+# format migration: with every format accepted, proofs emitted as formats
+# 1, 2 and 3 all verify; dropping a format refuses its proofs with
+# ZK_ERR_UNSUPPORTED_VERSION while the others keep verifying; the
+# per-format counters follow valid, invalid and refused proofs; and strict
+# builds refuse to emit or accept format 1 with ZK_ERR_DISABLED.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_SetAcceptedFormatVersions(uint32_t);
+int ZK_GetFormatVersionStats(uint8_t, uint64_t*, uint64_t*, uint64_t*);
+
+#define ZK_ERR_DISABLED -16
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ACCEPT_FORMAT_V1 1
+#define ZK_ACCEPT_FORMAT_V2 2
+#define ZK_ACCEPT_FORMAT_V3 4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proofs[4][4096];
+
+/* Counter movement for `format` since the last call */
+static uint64_t seen[4][3];
+static void moved(uint8_t format, uint64_t out[3]) {
+    uint64_t now[3] = {0, 0, 0};
+    ZK_GetFormatVersionStats(format, &now[0], &now[1], &now[2]);
+    for (int i = 0; i < 3; i++) {
+        out[i] = now[i] - seen[format][i];
+        seen[format][i] = now[i];
+    }
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    uint64_t delta[3];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    int bad_masks = ZK_SetAcceptedFormatVersions(0) == -1 && ZK_SetAcceptedFormatVersions(8) == -1;
+    int unknown_stats = ZK_GetFormatVersionStats(9, NULL, NULL, NULL);
+    int unknown_emit = ZK_SetEmitFormatVersion(9);
+    printf("  empty or unknown accept masks refused %d, stats of format 9 %d, emitting format 9 %d\n", bad_masks,
+           unknown_stats, unknown_emit);
+    if (!bad_masks || unknown_stats != -1 || unknown_emit != -1) {
+        return 1;
+    }
+
+#ifdef STRICT
+    int emit_legacy = ZK_SetEmitFormatVersion(1);
+    int accept_legacy = ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V1 | ZK_ACCEPT_FORMAT_V3);
+    printf("  emitting format 1 %d, accepting format 1 %d\n", emit_legacy, accept_legacy);
+    if (emit_legacy != ZK_ERR_DISABLED || accept_legacy != ZK_ERR_DISABLED) {
+        return 1;
+    }
+    uint8_t first = 2;
+#else
+    uint8_t first = 1;
+#endif
+
+    /* The dual-verify window: every format this build has */
+    for (uint8_t format = first; format <= 3; format++) {
+        moved(format, delta);
+        if (ZK_SetEmitFormatVersion(format) != 0 ||
+            ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proofs[format], sizeof(proofs[format])) != 0) {
+            return 1;
+        }
+        int valid = ZK_VerifyVCProof(proofs[format], pub, NOW, 7);
+        int other_nonce = ZK_VerifyVCProof(proofs[format], pub, NOW, 8);
+        moved(format, delta);
+        printf("  format %u under every format: %d, other nonce %d; counted valid %llu, invalid %llu\n", format,
+               valid, other_nonce, (unsigned long long)delta[0], (unsigned long long)delta[1]);
+        if (valid != 1 || other_nonce != 0 || delta[0] != 1 || delta[1] != 1 || delta[2] != 0) {
+            return 1;
+        }
+    }
+
+    /* The hard switch to format 3 */
+    if (ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V3) != 0) {
+        return 1;
+    }
+    for (uint8_t format = first; format <= 3; format++) {
+        int result = ZK_VerifyVCProof(proofs[format], pub, NOW, 7);
+        moved(format, delta);
+        int expected = format == 3 ? 1 : ZK_ERR_UNSUPPORTED_VERSION;
+        printf("  format %u under format 3 only: %d; counted refused %llu\n", format, result,
+               (unsigned long long)delta[2]);
+        if (result != expected || delta[2] != (format == 3 ? 0u : 1u) || delta[0] != (format == 3 ? 1u : 0u)) {
+            return 1;
+        }
+    }
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build strict "--features strict" "-DSTRICT"
+
+echo "✓ Proof formats migrate through a dual-verify window to a hard switch"
@@ -0,0 +1,198 @@
+#!/bin/bash
+#
+# Build the library for the host and check one-time VC proofs: a holder's
+# nullifier is the same in every run and for every proof in one context,
+# differs between contexts and holders, and a proof verifies only with the
+# context and nullifier it was made for, so a verifier recording nullifiers
+# refuses the second presentation in a context and not the first in
+# another. Proofs in two contexts carry the same anchor and signature in
+# their header, so presentations of one credential stay linkable (onetime.rs).
+# The nullifier of a fixed secret is compared against the conformance vector
+# and between two runs. With fault-injection, a prover
+# that skips its signature check proves with a fresh secret, and the
+# verifier rejects the proof and its second nullifier: the secret must be
+# the one behind the holder key in the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_GenerateHolderKeypair(char*, size_t, char*, size_t);
+int ZK_SignVCBound(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, const char*,
+                   char*, size_t, size_t*);
+int ZK_ComputeNullifier(const char*, const char*, size_t, char*, size_t);
+int ZK_GenerateVCProofOneTime(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*,
+                              const char*, const char*, const char*, size_t, uint64_t, uint64_t, char*, size_t,
+                              size_t*);
+int ZK_VerifyVCProofOneTime(const char*, const char*, uint64_t, uint64_t, const char*, size_t, const char*);
+int ZK_VerifyVCProofBound(const char*, const char*, uint64_t, uint64_t);
+int ZK_RecordNullifier(const char*, size_t, const char*, size_t);
+int ZK_RunConstraintAudit(char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_BAD_SIGNATURE -3
+#define ZK_ERR_BAD_HEX -6
+#define NOW 150
+#define VOUCHER "voucher/2026-spring"
+#define POLL "poll/42"
+/* Anchor and signature in the hex VC header, after "ZKVA" and the time */
+#define HEADER_ANCHOR 24
+#define HEADER_ANCHOR_LEN 192
+
+static const char fixed[] = "0101010101010101010101010101010101010101010101010101010101010101";
+static char pub[65], priv[65], secret[65], holder[65], other_secret[65], other_holder[65], sig[129];
+static char proof[1024];
+
+static int prove(const char* holder_secret, const char* context, uint64_t nonce) {
+    return ZK_GenerateVCProofOneTime("alice", 5, "issuer", 6, 100, 200, sig, pub, holder_secret, context,
+                                     strlen(context), NOW, nonce, proof, sizeof(proof), NULL);
+}
+
+static int verify(const char* context, const char* nullifier, uint64_t nonce) {
+    return ZK_VerifyVCProofOneTime(proof, pub, NOW, nonce, context, strlen(context), nullifier);
+}
+
+int main(void) {
+    static char report[8192];
+    char fixed_voucher[65], again[65], voucher[65], poll[65], other[65];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateHolderKeypair(secret, sizeof(secret), holder, sizeof(holder)) != 0 ||
+        ZK_GenerateHolderKeypair(other_secret, sizeof(other_secret), other_holder, sizeof(other_holder)) != 0 ||
+        ZK_SignVCBound("alice", 5, "issuer", 6, 100, 200, holder, priv, sig, sizeof(sig), NULL) != 0) {
+        return 1;
+    }
+
+    /* Stable per secret and context, distinct across either */
+    if (ZK_ComputeNullifier(fixed, VOUCHER, strlen(VOUCHER), fixed_voucher, sizeof(fixed_voucher)) != 0 ||
+        ZK_ComputeNullifier(fixed, VOUCHER, strlen(VOUCHER), again, sizeof(again)) != 0 ||
+        ZK_ComputeNullifier(secret, VOUCHER, strlen(VOUCHER), voucher, sizeof(voucher)) != 0 ||
+        ZK_ComputeNullifier(secret, POLL, strlen(POLL), poll, sizeof(poll)) != 0 ||
+        ZK_ComputeNullifier(other_secret, VOUCHER, strlen(VOUCHER), other, sizeof(other)) != 0) {
+        return 1;
+    }
+    int malformed = ZK_ComputeNullifier("00", VOUCHER, strlen(VOUCHER), again, sizeof(again));
+    printf("nullifier %s\n", fixed_voucher);
+    printf("  recomputed equal %d, other context equal %d, other secret equal %d, malformed secret %d\n",
+           strcmp(fixed_voucher, again) == 0, strcmp(voucher, poll) == 0, strcmp(voucher, other) == 0,
+           malformed);
+    if (strcmp(voucher, poll) == 0 || strcmp(voucher, other) == 0 || malformed != ZK_ERR_BAD_HEX) {
+        return 1;
+    }
+    if (ZK_ComputeNullifier(fixed, VOUCHER, strlen(VOUCHER), again, sizeof(again)) != 0 ||
+        strcmp(fixed_voucher, again) != 0) {
+        return 1;
+    }
+
+    /* A proof holds for its context and nullifier only */
+    int rc = prove(secret, VOUCHER, 42);
+    if (rc != 0) {
+        printf("  voucher: prove %d\n", rc);
+        return 1;
+    }
+    int valid = verify(VOUCHER, voucher, 42);
+    int in_poll = verify(POLL, voucher, 42);
+    int poll_nullifier = verify(POLL, poll, 42);
+    int other_nullifier = verify(VOUCHER, other, 42);
+    int nonce = verify(VOUCHER, voucher, 43);
+    int bound = ZK_VerifyVCProofBound(proof, pub, NOW, 42);
+    printf("  voucher proof: valid %d, in poll %d (its nullifier %d), other nullifier %d, other nonce %d, "
+           "as bound proof %d\n", valid, in_poll, poll_nullifier, other_nullifier, nonce, bound);
+    if (valid != 1 || in_poll != 0 || poll_nullifier != 0 || other_nullifier != 0 || nonce != 0 || bound != 0) {
+        return 1;
+    }
+
+    /* The verifier records nullifiers: one presentation per context */
+    int first = ZK_RecordNullifier(VOUCHER, strlen(VOUCHER), voucher, strlen(voucher));
+    if (prove(secret, VOUCHER, 43) != 0 || verify(VOUCHER, voucher, 43) != 1) {
+        return 1;
+    }
+    int second = ZK_RecordNullifier(VOUCHER, strlen(VOUCHER), voucher, strlen(voucher));
+    char voucher_header[HEADER_ANCHOR_LEN + 1] = "";
+    strncat(voucher_header, proof + HEADER_ANCHOR, HEADER_ANCHOR_LEN);
+    if (prove(secret, POLL, 44) != 0 || verify(POLL, poll, 44) != 1) {
+        return 1;
+    }
+    int elsewhere = ZK_RecordNullifier(POLL, strlen(POLL), poll, strlen(poll));
+    int linkable = strlen(voucher_header) == HEADER_ANCHOR_LEN &&
+                   strncmp(voucher_header, proof + HEADER_ANCHOR, HEADER_ANCHOR_LEN) == 0;
+    printf("  recorded: first %d, again in context %d, other context %d; same anchor and signature %d\n", first,
+           second, elsewhere, linkable);
+    if (first != 1 || second != 0 || elsewhere != 1 || !linkable) {
+        return 1;
+    }
+
+    /* The credential and signature without the secret prove nothing */
+    int wrong = prove(other_secret, VOUCHER, 45);
+    int bad = prove("00", VOUCHER, 45);
+    printf("  other secret %d, malformed secret %d\n", wrong, bad);
+    if (wrong != ZK_ERR_BAD_SIGNATURE || bad != ZK_ERR_BAD_HEX) {
+        return 1;
+    }
+
+#ifdef DEBUG_CIRCUIT
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0 || strstr(report, "one-time-vc") == NULL) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+
+#ifdef FAULT_INJECTION
+    /* Another secret gives another nullifier in the same context, if the
+       verifier took the proof */
+    ZK_SkipProverChecks(1);
+    int fresh = prove(other_secret, VOUCHER, 46);
+    int fresh_valid = fresh == 0 ? verify(VOUCHER, other, 46) : -1;
+    int own = prove(secret, VOUCHER, 46);
+    int own_valid = own == 0 ? verify(VOUCHER, voucher, 46) : -1;
+    printf("  unchecked prover: fresh secret %d (verifies %d), holder secret %d (verifies %d)\n", fresh,
+           fresh_valid, own, own_valid);
+    if (fresh != 0 || fresh_valid != 0 || own != 0 || own_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+# Nullifier of the fixed secret for the voucher context, as the conformance
+# vectors record it (keys are sorted, so "expected" precedes "id")
+EXPECTED="$(grep -B 7 '"id": "nullifier-001"' conformance/vectors.json | sed -n 's/.*"nullifier": "\([0-9a-f]*\)".*/\1/p')"
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    local first second
+    first="$("$WORK_DIR/check-$name")" || { echo "$first" | grep -v '^nullifier'; echo "  FAIL"; exit 1; }
+    echo "$first" | grep -v '^nullifier'
+    second="$("$WORK_DIR/check-$name" | grep '^nullifier')" || { echo "  FAIL"; exit 1; }
+    first="$(echo "$first" | grep '^nullifier')"
+    echo "  across runs: $first / $second, conformance vector $EXPECTED"
+    if [ -z "$EXPECTED" ] || [ "$first" != "$second" ] || [ "$first" != "nullifier $EXPECTED" ]; then
+        echo "  FAIL"
+        exit 1
+    fi
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Nullifiers are stable per context and one-time proofs hold for theirs only"
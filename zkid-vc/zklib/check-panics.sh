@@ -0,0 +1,212 @@
+#!/bin/bash
+#
+# Build the library for the host and feed its entry points malformed input:
+# every truncation of a valid hex key, signature, verifying key and proof,
+# mutated proofs, and lengths past the per-argument limits. The process must
+# survive every call without a panic reaching the C boundary, and afterwards
+# the loaded keys must be unchanged and still prove and verify. A panic
+# forced with ZK_InjectPanic must come back as ZK_ERR_PANIC with its message
+# in ZK_GetLastError.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct Context Context;
+
+int ZK_Init(void);
+int ZK_InitWithKeys(const uint8_t*, size_t, const uint8_t*, size_t);
+int ZK_IsInitialized(void);
+int ZK_InjectPanic(void);
+int ZK_GetLastError(char*, size_t);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_VerifyVCSignature(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*,
+                         const char*);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_VerifyVCProofRaw(const uint8_t*, size_t, const uint8_t*, size_t, uint64_t, uint64_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_ImportVerifyingKey(const char*);
+int ZK_ContextCreate(Context**);
+int ZK_ContextImportVerifyingKey(Context*, const char*);
+void ZK_ContextFree(Context*);
+
+#define ZK_ERR_PANIC -36
+#define NOW 150
+#define BIG (128 * 1024)
+
+static char pub[65], priv[65], sig[129], proof[1024], vk[4096];
+static int calls, panics, accepted;
+
+/* Record one call on malformed input: it must neither panic nor succeed */
+static void refused(int code, int success) {
+    calls++;
+    if (code == ZK_ERR_PANIC) {
+        char message[256];
+        ZK_GetLastError(message, sizeof(message));
+        printf("  panic: %s\n", message);
+        panics++;
+    } else if (code == success) {
+        accepted++;
+    }
+}
+
+/* Copy the first len characters of src */
+static const char* prefix(char* buf, const char* src, size_t len) {
+    memcpy(buf, src, len);
+    buf[len] = '\0';
+    return buf;
+}
+
+static int from_hex(const char* hex, uint8_t* out) {
+    size_t n = strlen(hex) / 2;
+    for (size_t i = 0; i < n; i++) {
+        unsigned byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return (int)n;
+}
+
+static int round_trip(uint64_t nonce) {
+    char fresh[1024];
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, nonce, fresh, sizeof(fresh),
+                              NULL) == 0 &&
+           ZK_VerifyVCProof(fresh, pub, NOW, nonce) == 1;
+}
+
+int main(void) {
+    char buf[4096], out[1024], vk_after[4096];
+    static uint8_t raw_vk[2048], raw_proof[512], raw_pub[32], big[BIG];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, 65, priv, 65) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, 1, proof, sizeof(proof), NULL) != 0 ||
+        ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+    int vk_len = from_hex(vk, raw_vk);
+    int proof_len = from_hex(proof, raw_proof);
+    from_hex(pub, raw_pub);
+
+    /* Truncated issuer keys and signatures */
+    for (size_t len = 0; len < strlen(priv); len++) {
+        refused(ZK_SignVC("alice", 5, "issuer", 6, 100, 200, prefix(buf, priv, len), out, sizeof(out), NULL), 0);
+    }
+    for (size_t len = 0; len < strlen(pub); len++) {
+        prefix(buf, pub, len);
+        refused(ZK_VerifyVCSignature("alice", 5, "issuer", 6, 100, 200, sig, buf), 1);
+        refused(ZK_VerifyVCProof(proof, buf, NOW, 1), 1);
+        refused(ZK_VerifyVCProofRaw(raw_proof, proof_len, raw_pub, len / 2, NOW, 1), 1);
+    }
+    for (size_t len = 0; len < strlen(sig); len++) {
+        prefix(buf, sig, len);
+        refused(ZK_VerifyVCSignature("alice", 5, "issuer", 6, 100, 200, buf, pub), 1);
+        refused(ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, buf, pub, NOW, 2, out, sizeof(out), NULL), 0);
+    }
+
+    /* Truncated verifying keys, into the library and into a context */
+    Context* ctx;
+    if (ZK_ContextCreate(&ctx) != 0) {
+        return 1;
+    }
+    for (size_t len = 0; len < strlen(vk); len++) {
+        prefix(buf, vk, len);
+        refused(ZK_ImportVerifyingKey(buf), 0);
+        refused(ZK_ContextImportVerifyingKey(ctx, buf), 0);
+    }
+    for (int len = 0; len < vk_len; len++) {
+        refused(ZK_InitWithKeys(raw_vk, len, raw_vk, len), 0);
+    }
+    ZK_ContextFree(ctx);
+
+    /* Truncated and mutated proofs */
+    for (size_t len = 0; len < strlen(proof); len++) {
+        refused(ZK_VerifyVCProof(prefix(buf, proof, len), pub, NOW, 1), 1);
+    }
+    for (int len = 0; len < proof_len; len++) {
+        refused(ZK_VerifyVCProofRaw(raw_proof, len, raw_pub, 32, NOW, 1), 1);
+    }
+    uint32_t seed = 12345;
+    int mutated_panics = panics;
+    for (int i = 0; i < 300; i++) {
+        strcpy(buf, proof);
+        for (int j = 0; j < 1 + i % 3; j++) {
+            seed = seed * 1103515245 + 12345;
+            buf[(seed >> 8) % strlen(proof)] = "0123456789abcdefzZ\xff"[(seed >> 20) % 19];
+        }
+        int code = ZK_VerifyVCProof(buf, pub, NOW, 1);
+        calls++;
+        panics += code == ZK_ERR_PANIC;
+    }
+    mutated_panics = panics - mutated_panics;
+
+    /* Lengths past every limit, with pointers to short (or no) memory */
+    const char* keys[] = {"age"};
+    const char* values[] = {"30"};
+    refused(ZK_GenerateVCProof("alice", SIZE_MAX, "issuer", 6, 100, 200, sig, pub, NOW, 3, out, sizeof(out), NULL), 0);
+    refused(ZK_GenerateVCProof("alice", 5, "issuer", SIZE_MAX, 100, 200, sig, pub, NOW, 3, out, sizeof(out), NULL), 0);
+    refused(ZK_GenerateVCProof((const char*)big, BIG, "issuer", 6, 100, 200, sig, pub, NOW, 3, out, sizeof(out),
+                               NULL), 0);
+    refused(ZK_SignVC("alice", SIZE_MAX, "issuer", 6, 100, 200, priv, out, sizeof(out), NULL), 0);
+    refused(ZK_VerifyVCSignature("alice", 5, "issuer", SIZE_MAX, 100, 200, sig, pub), 1);
+    refused(ZK_ComputeVCHash("alice", 5, "issuer", 6, 100, 200, keys, values, SIZE_MAX, out, sizeof(out), NULL), 0);
+    refused(ZK_ComputeVCHash("alice", SIZE_MAX, "issuer", 6, 100, 200, keys, values, 1, out, sizeof(out), NULL), 0);
+    refused(ZK_VerifyVCProofRaw(raw_proof, SIZE_MAX, raw_pub, 32, NOW, 1), 1);
+    refused(ZK_VerifyVCProofRaw(raw_proof, proof_len, raw_pub, SIZE_MAX, NOW, 1), 1);
+    refused(ZK_VerifyVCProofRaw(big, BIG, raw_pub, 32, NOW, 1), 1);
+    refused(ZK_InitWithKeys(raw_vk, SIZE_MAX, raw_vk, vk_len), 0);
+    refused(ZK_InitWithKeys(big, BIG, big, BIG), 0);
+
+    printf("  %d malformed calls: %d panicked (%d on mutated proofs), %d accepted\n", calls, panics, mutated_panics,
+           accepted);
+    if (panics != 0 || accepted != 0) {
+        return 1;
+    }
+
+    /* The loaded keys are the ones from before */
+    int same_key = ZK_ExportVerifyingKey(vk_after, sizeof(vk_after)) == 0 && strcmp(vk, vk_after) == 0;
+    int old_proof = ZK_VerifyVCProof(proof, pub, NOW, 1);
+    int fresh = round_trip(4);
+    printf("  afterwards: initialized %d, same verifying key %d, earlier proof %d, round trip %d\n",
+           ZK_IsInitialized(), same_key, old_proof, fresh);
+    if (ZK_IsInitialized() != 1 || !same_key || old_proof != 1 || !fresh) {
+        return 1;
+    }
+
+    /* A panic comes back as ZK_ERR_PANIC with its message */
+    char message[256];
+    int code = ZK_InjectPanic();
+    ZK_GetLastError(message, sizeof(message));
+    printf("  injected panic: %d, \"%s\"\n", code, message);
+    if (code != ZK_ERR_PANIC || strcmp(message, "internal panic: injected by ZK_InjectPanic") != 0) {
+        return 1;
+    }
+    fresh = round_trip(5);
+    ZK_GetLastError(message, sizeof(message));
+    printf("  after it: round trip %d, last error \"%s\"\n", fresh, message);
+    return !fresh || message[0] != '\0';
+}
+EOF
+
+echo "Checking build: fault-injection (release profile)"
+cargo rustc --release --lib --crate-type staticlib --features fault-injection --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" 2>/dev/null || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Malformed input never panics across the C boundary, and a panic is reported as ZK_ERR_PANIC"
@@ -0,0 +1,145 @@
+#!/bin/bash
+#
+# Build the library for the host and check schedule circuit capacities
+# registered at run time: a credential proves under the smallest registered
+# capacity that holds its windows and the proof names it; a proof whose
+# capacity has no key is ZK_ERR_CIRCUIT_MISMATCH while one relabelled to
+# another registered capacity is just invalid; a credential with more
+# windows than any registered capacity cannot prove; registering again keeps
+# the keys, and two processes derive the same key for a capacity.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    int circuit;
+    uint32_t capacity;
+} ZkCircuitParams;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SetValiditySchedule(const char*, const int*, const int64_t*, const int64_t*, size_t, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateScheduleProof(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyScheduleProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_RegisterCircuitParams(const ZkCircuitParams*);
+int ZK_ExportCircuitVerifyingKey(const ZkCircuitParams*, char*, size_t);
+int ZK_ImportCircuitVerifyingKey(const ZkCircuitParams*, const char*);
+
+#define ZK_ERR_CIRCUIT_MISMATCH -20
+#define ZK_CIRCUIT_VC 1
+#define ZK_CIRCUIT_SCHEDULE 2
+#define ZK_WINDOW_INTERVAL 0
+#define NOW 1700000000LL
+#define DAY 86400LL
+
+static char pub[65], priv[65];
+static char vk[16384], vk_again[16384];
+
+/* A credential of Alice with `count` nested windows around NOW */
+static int issue(size_t count, char* out) {
+    static char encoded[8192], scheduled[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    int kinds[64];
+    int64_t starts[64], ends[64];
+    for (size_t i = 0; i < count; i++) {
+        kinds[i] = ZK_WINDOW_INTERVAL;
+        starts[i] = NOW - (int64_t)(i + 1) * DAY;
+        ends[i] = NOW + (int64_t)(i + 1) * DAY;
+    }
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 30 * DAY, NOW + 30 * DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SetValiditySchedule(encoded, kinds, starts, ends, count, scheduled, sizeof(scheduled)) == 0 &&
+           ZK_SignVCBlob(scheduled, priv, out, 8192) == 0;
+}
+
+/* argv[1]: file receiving the exported key of capacity 4 */
+int main(int argc, char** argv) {
+    static char three[8192], ten[8192], seventeen[8192], proof[4096], relabelled[4096];
+    ZkCircuitParams w4 = {ZK_CIRCUIT_SCHEDULE, 4}, w8 = {ZK_CIRCUIT_SCHEDULE, 8};
+    ZkCircuitParams none = {ZK_CIRCUIT_SCHEDULE, 0}, too_many = {ZK_CIRCUIT_SCHEDULE, 65};
+    ZkCircuitParams vc = {ZK_CIRCUIT_VC, 0}, vc_sized = {ZK_CIRCUIT_VC, 1}, unknown = {99, 4};
+    if (argc < 2 || ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0) {
+        return 1;
+    }
+
+    int refused = ZK_RegisterCircuitParams(&none) == -1 && ZK_RegisterCircuitParams(&too_many) == -1 &&
+                  ZK_RegisterCircuitParams(&vc_sized) == -1 && ZK_RegisterCircuitParams(&unknown) == -1 &&
+                  ZK_RegisterCircuitParams(NULL) == -1;
+    int vc_registered = ZK_RegisterCircuitParams(&vc);
+    int registered = ZK_RegisterCircuitParams(&w4);
+    int exported = ZK_ExportCircuitVerifyingKey(&w4, vk, sizeof(vk));
+    int again = ZK_RegisterCircuitParams(&w4);
+    int same_key = ZK_ExportCircuitVerifyingKey(&w4, vk_again, sizeof(vk_again)) == 0 && strcmp(vk, vk_again) == 0;
+    int unregistered_export = ZK_ExportCircuitVerifyingKey(&w8, vk_again, sizeof(vk_again));
+    int unregistered_import = ZK_ImportCircuitVerifyingKey(&w8, vk);
+    printf("  bad parameters refused %d, VC %d; capacity 4 registered %d, exported %d, again %d, same key %d; "
+           "capacity 8 export %d, import %d\n",
+           refused, vc_registered, registered, exported, again, same_key, unregistered_export, unregistered_import);
+    if (!refused || vc_registered != 0 || registered != 0 || exported != 0 || again != 0 || !same_key ||
+        unregistered_export != ZK_ERR_CIRCUIT_MISMATCH || unregistered_import != ZK_ERR_CIRCUIT_MISMATCH) {
+        return 1;
+    }
+    FILE* f = fopen(argv[1], "w");
+    if (f == NULL) {
+        return 1;
+    }
+    fputs(vk, f);
+    fclose(f);
+
+    /* Each credential proves under the smallest capacity that holds it */
+    if (!issue(3, three) || !issue(10, ten) || !issue(17, seventeen)) {
+        return 1;
+    }
+    struct { const char* vc; size_t windows; const char* capacity; } cases[] = {
+        {three, 3, "04000000"},
+        {ten, 10, "10000000"},
+    };
+    for (size_t i = 0; i < 2; i++) {
+        int proved = ZK_GenerateScheduleProof(cases[i].vc, pub, NULL, NOW, 7, proof, sizeof(proof));
+        int named = strncmp(proof, "5a4302", 6) == 0 && strncmp(proof + 6, cases[i].capacity, 8) == 0;
+        int valid = proved == 0 ? ZK_VerifyScheduleProof(proof, pub, NOW, 7) : -100;
+        printf("  %zu windows: prove %d, names capacity %.8s %d, verifies %d\n", cases[i].windows, proved,
+               proof + 6, named, valid);
+        if (proved != 0 || !named || valid != 1) {
+            return 1;
+        }
+    }
+
+    /* The 3-window proof relabelled to capacities 8 (no key) and 16 */
+    if (ZK_GenerateScheduleProof(three, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+    memcpy(relabelled, proof, strlen(proof) + 1);
+    memcpy(relabelled + 6, "08000000", 8);
+    int no_key = ZK_VerifyScheduleProof(relabelled, pub, NOW, 7);
+    memcpy(relabelled + 6, "10000000", 8);
+    int other_key = ZK_VerifyScheduleProof(relabelled, pub, NOW, 7);
+    int too_big = ZK_GenerateScheduleProof(seventeen, pub, NULL, NOW, 7, proof, sizeof(proof));
+    printf("  relabelled to capacity 8 %d, to 16 %d; 17 windows %d\n", no_key, other_key, too_big);
+    return no_key != ZK_ERR_CIRCUIT_MISMATCH || other_key != 0 || too_big != ZK_ERR_CIRCUIT_MISMATCH;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR/vk-1" || { echo "  FAIL"; exit 1; }
+"$WORK_DIR/check" "$WORK_DIR/vk-2" > /dev/null || { echo "  FAIL"; exit 1; }
+cmp -s "$WORK_DIR/vk-1" "$WORK_DIR/vk-2" || { echo "  FAIL: the processes set up different keys"; exit 1; }
+echo "  ok"
+
+echo "✓ Schedule capacities register at run time and proofs name the one they were made for"
@@ -0,0 +1,90 @@
+#!/bin/bash
+#
+# Build the library for the host and check recovery from poisoned
+# key locks: after a panic while both VC key locks are held, the loaded keys
+# still prove and verify, and ZK_Cleanup followed by ZK_Init restores a
+# working library, with ZK_IsInitialized reporting each state.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_IsInitialized(void);
+int ZK_PoisonKeyLocks(void);
+void ZK_Cleanup(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+
+#define NOW 150
+
+static char pub[65], priv[65], sig[129];
+
+/* Prove for `nonce` and verify the proof: 1 if both work */
+static int round_trip(uint64_t nonce) {
+    char proof[1024];
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, nonce, proof, sizeof(proof),
+                              NULL) == 0 &&
+           ZK_VerifyVCProof(proof, pub, NOW, nonce) == 1;
+}
+
+int main(void) {
+    char proof[1024];
+    int before = ZK_IsInitialized();
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, 65, priv, 65) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, 1, proof, sizeof(proof), NULL) != 0) {
+        return 1;
+    }
+    int initialized = ZK_IsInitialized();
+    printf("  initialized: before ZK_Init %d, after %d\n", before, initialized);
+    if (before != 0 || initialized != 1) {
+        return 1;
+    }
+
+    /* Poisoned locks still hand out the loaded keys */
+    int poisoned = ZK_PoisonKeyLocks();
+    int still = ZK_IsInitialized();
+    int old_proof = ZK_VerifyVCProof(proof, pub, NOW, 1);
+    int fresh = round_trip(2);
+    printf("  poisoned %d: initialized %d, earlier proof %d, new proof round trip %d\n", poisoned, still, old_proof,
+           fresh);
+    if (poisoned != 0 || still != 1 || old_proof != 1 || !fresh) {
+        return 1;
+    }
+
+    /* Reset and re-initialize with the locks poisoned again */
+    if (ZK_PoisonKeyLocks() != 0) {
+        return 1;
+    }
+    ZK_Cleanup();
+    int cleaned = ZK_IsInitialized();
+    int reinit = ZK_Init();
+    int again = ZK_IsInitialized();
+    fresh = round_trip(3);
+    printf("  after ZK_Cleanup %d; ZK_Init %d, initialized %d, round trip %d\n", cleaned, reinit, again, fresh);
+    return cleaned != 0 || reinit != 0 || again != 1 || !fresh;
+}
+EOF
+
+echo "Checking build: fault-injection"
+cargo rustc --release --lib --crate-type staticlib --features fault-injection \
+    --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Poisoned key locks neither block the loaded keys nor re-initialization"
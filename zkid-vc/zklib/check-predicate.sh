@@ -0,0 +1,208 @@
+#!/bin/bash
+#
+# Build the library for the host and check predicate proofs: equality, set
+# and integer predicates prove exactly when the signed claim satisfies them
+# as the circuit evaluates them, and a proof verifies only for predicates of
+# the same meaning, its issuer and its nonce. With fault-injection, a prover
+# that skips its pre-checks proves a predicate over a claim edited in the
+# blob, and the verifier rejects it: the circuit opens the claim under the
+# anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_EncodePredicate(const char*, size_t, char*, size_t, char*, size_t);
+int ZK_GeneratePredicateProof(const char*, const char*, const char*, const char*, uint64_t, uint64_t, char*,
+                              size_t);
+int ZK_VerifyPredicateProof(const char*, const char*, const char*, uint64_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_INCOMPATIBLE -23
+#define NOW 1700000000ULL
+
+static const char* KEYS[] = {"age", "balance", "temp", "role", "code", "country"};
+static const char* VALUES[] = {"18", "18446744073709551615", "-40", "engineer", "007", "DE"};
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char blob[8192], proof[2048];
+
+static int issue(char* out) {
+    static char unsigned_blob[8192];
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 6, NULL,
+                       unsigned_blob, sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, out, 8192) == 0;
+}
+
+/* The binary form (hex) of a predicate in JSON form */
+static const char* encode(const char* json) {
+    static char hex[4][4096];
+    static int next;
+    char* out = hex[next++ % 4];
+    return ZK_EncodePredicate(json, strlen(json), out, 4096, NULL, 0) == 0 ? out : "";
+}
+
+static int prove(const char* vc, const char* json) {
+    return ZK_GeneratePredicateProof(vc, pub, NULL, encode(json), NOW, 7, proof, sizeof(proof));
+}
+
+static int verify(const char* json) {
+    return ZK_VerifyPredicateProof(proof, pub, encode(json), 7);
+}
+
+/* Prove `json`, expecting `expected`; a proof must verify for its own
+ * predicate only, with its issuer and nonce */
+static int check(const char* json, int expected) {
+    int rc = prove(blob, json);
+    if (rc != 0) {
+        printf("  %s: prove %d\n", json, rc);
+        return rc == expected;
+    }
+    int valid = verify(json);
+    int issuer = ZK_VerifyPredicateProof(proof, pub2, encode(json), 7);
+    int nonce = ZK_VerifyPredicateProof(proof, pub, encode(json), 8);
+    printf("  %s: valid %d, other issuer %d, other nonce %d\n", json, valid, issuer, nonce);
+    return expected == 0 && valid == 1 && issuer == 0 && nonce == 0;
+}
+
+int main(void) {
+    static char sig[129], legacy_blob[8192];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0 || !issue(blob)) {
+        return 1;
+    }
+
+    /* Integer comparisons at their edges, over all 64 bits and below zero,
+     * operands far outside the claim range included */
+    if (!check("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"18\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"above\",\"value\":\"17\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"at_most\",\"value\":\"18\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"below\",\"value\":\"19\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"19\"}", -1) ||
+        !check("{\"claim\":\"age\",\"op\":\"above\",\"value\":\"18\"}", -1) ||
+        !check("{\"claim\":\"age\",\"op\":\"below\",\"value\":\"18\"}", -1) ||
+        !check("{\"claim\":\"balance\",\"op\":\"at_least\",\"value\":\"18446744073709551615\"}", 0) ||
+        !check("{\"claim\":\"balance\",\"op\":\"above\",\"value\":\"18446744073709551615\"}", -1) ||
+        !check("{\"claim\":\"temp\",\"op\":\"below\",\"value\":\"-39\"}", 0) ||
+        !check("{\"claim\":\"temp\",\"op\":\"at_least\",\"value\":\"-40\"}", 0) ||
+        !check("{\"claim\":\"temp\",\"op\":\"at_most\",\"value\":\"-41\"}", -1) ||
+        !check("{\"claim\":\"age\",\"op\":\"above\",\"value\":\"-340282366920938463463374607431768211455\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"below\",\"value\":\"340282366920938463463374607431768211455\"}", 0) ||
+        !check("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"340282366920938463463374607431768211455\"}",
+               -1)) {
+        return 1;
+    }
+
+    /* Equality and sets; text and padded numbers are no integers */
+    if (!check("{\"claim\":\"role\",\"op\":\"equal\",\"value\":\"engineer\"}", 0) ||
+        !check("{\"claim\":\"role\",\"op\":\"not_equal\",\"value\":\"manager\"}", 0) ||
+        !check("{\"claim\":\"role\",\"op\":\"not_equal\",\"value\":\"engineer\"}", -1) ||
+        !check("{\"claim\":\"country\",\"op\":\"in\",\"values\":[\"FR\",\"DE\"]}", 0) ||
+        !check("{\"claim\":\"country\",\"op\":\"in\",\"values\":[\"FR\",\"IT\"]}", -1) ||
+        !check("{\"claim\":\"code\",\"op\":\"equal\",\"value\":\"007\"}", 0) ||
+        !check("{\"claim\":\"code\",\"op\":\"at_least\",\"value\":\"5\"}", -1) ||
+        !check("{\"claim\":\"role\",\"op\":\"at_least\",\"value\":\"0\"}", -1) ||
+        !check("{\"claim\":\"height\",\"op\":\"not_equal\",\"value\":\"1\"}", -1) ||
+        !check("{\"claim\":\"sanctioned\",\"op\":\"absent\"}", ZK_ERR_INCOMPATIBLE) ||
+        !check("{\"claim\":\"country\",\"op\":\"in\",\"values\":[\"A\",\"B\",\"C\",\"D\",\"DE\",\"E\",\"F\",\"G\","
+               "\"H\"]}", ZK_ERR_INCOMPATIBLE)) {
+        return 1;
+    }
+
+    /* A proof holds for predicates of the same meaning and no other */
+    if (prove(blob, "{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"18\"}") != 0) {
+        return 1;
+    }
+    int same = verify("{\"claim\":\"age\",\"op\":\"above\",\"value\":\"17\"}");
+    int higher = verify("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"19\"}");
+    int lower = verify("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"17\"}");
+    int other_claim = verify("{\"claim\":\"balance\",\"op\":\"at_least\",\"value\":\"18\"}");
+    int flipped = verify("{\"claim\":\"age\",\"op\":\"at_most\",\"value\":\"18\"}");
+    if (prove(blob, "{\"claim\":\"role\",\"op\":\"equal\",\"value\":\"engineer\"}") != 0) {
+        return 1;
+    }
+    int member = verify("{\"claim\":\"role\",\"op\":\"in\",\"values\":[\"engineer\"]}");
+    int negated = verify("{\"claim\":\"role\",\"op\":\"not_equal\",\"value\":\"engineer\"}");
+    int wider = verify("{\"claim\":\"role\",\"op\":\"in\",\"values\":[\"engineer\",\"manager\"]}");
+    printf("  age >= 18 as > 17 %d, >= 19 %d, >= 17 %d, other claim %d, <= 18 %d; "
+           "role = engineer as in {engineer} %d, != engineer %d, in {engineer, manager} %d\n",
+           same, higher, lower, other_claim, flipped, member, negated, wider);
+    if (same != 1 || higher != 0 || lower != 0 || other_claim != 0 || flipped != 0 || member != 1 ||
+        negated != 0 || wider != 0) {
+        return 1;
+    }
+
+    /* Version 5 blobs have no signed root */
+    if (ZK_SignVCWithClaims("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 6, priv,
+                            sig, sizeof(sig), NULL) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 6, sig,
+                    legacy_blob, sizeof(legacy_blob)) != 0) {
+        return 1;
+    }
+    int legacy = prove(legacy_blob, "{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"18\"}");
+    printf("  version 5 blob: %d\n", legacy);
+    if (legacy != ZK_ERR_UNSUPPORTED_VERSION) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The age claim edited in place from "18" to "40", the issuer's
+     * signature kept: the checked prover refuses it, one that skips its
+     * checks proves age >= 21 over a claim the issuer never signed */
+    static char forged[8192];
+    const char* adult = "{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"21\"}";
+    strcpy(forged, blob);
+    char* age = strstr(forged, "616765020000003138");
+    if (age == NULL) {
+        return 1;
+    }
+    memcpy(age + 14, "3430", 4);
+    int checked = prove(forged, adult);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, adult);
+    int forged_valid = forgery == 0 ? verify(adult) : -1;
+    int honest = prove(blob, "{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"18\"}");
+    int honest_valid = honest == 0 ? verify("{\"claim\":\"age\",\"op\":\"at_least\",\"value\":\"18\"}") : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited claim: checked prover %d; unchecked prover %d (verifies %d), signed claim %d (verifies %d)\n",
+           checked, forgery, forged_valid, honest, honest_valid);
+    if (checked == 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Predicates prove over the issuer-signed claim and verify only for their meaning"
@@ -0,0 +1,126 @@
+#!/bin/bash
+#
+# Build the library for the host and check prepared issuer contexts:
+# ZK_VerifyVCProofPrepared gives the same answer as ZK_VerifyVCProof for
+# proofs of both enveloped formats, for the right and wrong nonce and time,
+# for another issuer's proof and another issuer's context, and for a format
+# the verifier no longer accepts. A context cannot be prepared before the
+# keys exist or for a malformed issuer key.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct PublicContext PublicContext;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_SetAcceptedFormatVersions(uint32_t);
+int ZK_PreparePublicContext(const char*, PublicContext**);
+int ZK_VerifyVCProofPrepared(const PublicContext*, const char*, uint64_t, uint64_t);
+void ZK_FreePublicContext(PublicContext*);
+
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ACCEPT_FORMAT_V2 2
+#define ZK_ACCEPT_FORMAT_V3 4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char proofs[2][4096], foreign[4096];
+
+static int prove(const char* key, const char* key_pub, char* out) {
+    static char encoded[8192], blob[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, key, blob, sizeof(blob)) == 0 &&
+           ZK_GenerateVCProofFromBlob(blob, key_pub, NULL, NOW, 7, out, 4096) == 0;
+}
+
+/* Plain and prepared verification of one proof; 0 if they disagree */
+static int agree(const PublicContext* ctx, const char* issuer, const char* proof, uint64_t time, uint64_t nonce,
+                 int expected, const char* what) {
+    int plain = ZK_VerifyVCProof(proof, issuer, time, nonce);
+    int prepared = ZK_VerifyVCProofPrepared(ctx, proof, time, nonce);
+    printf("  %s: plain %d, prepared %d\n", what, plain, prepared);
+    return plain == expected && prepared == expected;
+}
+
+int main(void) {
+    PublicContext *ctx = NULL, *ctx2 = NULL;
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+    int before_init = ZK_PreparePublicContext(pub, &ctx);
+    if (ZK_Init() != 0) {
+        return 1;
+    }
+    int bad_key = ZK_PreparePublicContext("zz", &ctx);
+    printf("  before ZK_Init %d, malformed key %d\n", before_init, bad_key);
+    if (before_init != -1 || bad_key != -1 || ZK_PreparePublicContext(pub, &ctx) != 0 ||
+        ZK_PreparePublicContext(pub2, &ctx2) != 0) {
+        return 1;
+    }
+
+    /* Both enveloped formats, one context */
+    const uint8_t formats[2] = {2, 3};
+    for (int i = 0; i < 2; i++) {
+        if (ZK_SetEmitFormatVersion(formats[i]) != 0 || !prove(priv, pub, proofs[i])) {
+            return 1;
+        }
+    }
+    if (!prove(priv2, pub2, foreign)) {
+        return 1;
+    }
+    int ok = agree(ctx, pub, proofs[0], NOW, 7, 1, "format 2") && agree(ctx, pub, proofs[1], NOW, 7, 1, "format 3") &&
+             agree(ctx, pub, proofs[1], NOW, 8, 0, "other nonce") &&
+             agree(ctx, pub, proofs[1], NOW + 1, 7, 0, "other time") &&
+             agree(ctx, pub, foreign, NOW, 7, 0, "other issuer's proof") &&
+             agree(ctx2, pub2, foreign, NOW, 7, 1, "other issuer's context") &&
+             agree(ctx2, pub2, proofs[1], NOW, 7, 0, "first issuer's proof in the other context");
+    if (!ok) {
+        return 1;
+    }
+
+    /* Format 2 no longer accepted */
+    if (ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V3) != 0) {
+        return 1;
+    }
+    ok = agree(ctx, pub, proofs[0], NOW, 7, ZK_ERR_UNSUPPORTED_VERSION, "format 2 refused") &&
+         agree(ctx, pub, proofs[1], NOW, 7, 1, "format 3 still accepted");
+    ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V2 | ZK_ACCEPT_FORMAT_V3);
+
+    int null_ctx = ZK_VerifyVCProofPrepared(NULL, proofs[1], NOW, 7);
+    ZK_FreePublicContext(ctx);
+    ZK_FreePublicContext(ctx2);
+    ZK_FreePublicContext(NULL);
+    printf("  NULL context %d\n", null_ctx);
+    return !ok || null_ctx != 0;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Prepared issuer contexts verify exactly as ZK_VerifyVCProof does"
@@ -0,0 +1,74 @@
+#!/bin/bash
+#
+# Build the standard and strict profiles for the host and check that the
+# strict one lacks the legacy symbols and rejects legacy-format proofs.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+# Bare (legacy) proof: 128 bytes compressed behind a VC validity prefix
+# ("ZKVA", the time, the anchor 0 and a zero signature), content irrelevant
+# for the check
+LEGACY_PROOF="5a4b5641$(printf '00%.0s' $(seq 1 104))$(printf '00%.0s' $(seq 1 128))"
+PUBKEY="$(printf '11%.0s' $(seq 1 32))"
+
+cat > "$WORK_DIR/check.c" << EOF
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_BuildInfo(char*, size_t);
+
+int main(int argc, char** argv) {
+    char info[256];
+    int strict = argc > 1 && strcmp(argv[1], "strict") == 0;
+
+    if (ZK_Init() != 0 || ZK_BuildInfo(info, sizeof(info)) != 0) {
+        return 1;
+    }
+    printf("  build info: %s\n", info);
+    if ((strstr(info, "\"profile\":\"strict\"") != NULL) != strict) {
+        return 1;
+    }
+
+    int rc = ZK_VerifyVCProof("$LEGACY_PROOF", "$PUBKEY", 0, 0);
+    printf("  legacy proof: %d\n", rc);
+    return strict ? (rc == -16 ? 0 : 1) : (rc == -16 ? 1 : 0);
+}
+EOF
+
+check_profile() {
+    local profile="$1"
+    local features=""
+    if [ "$profile" = "strict" ]; then
+        features="--features strict"
+    fi
+
+    echo "Checking profile: $profile"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$profile" -q
+    local lib="$WORK_DIR/target-$profile/release/libzklib_vc.a"
+
+    for sym in ZK_GenerateIssuerKeypairDeterministic ZK_SetDeterministicProving; do
+        if nm -g --defined-only "$lib" 2>/dev/null | grep -q " T $sym$"; then
+            [ "$profile" = "strict" ] && { echo "  FAIL: $sym exported"; exit 1; }
+        else
+            [ "$profile" = "standard" ] && { echo "  FAIL: $sym missing"; exit 1; }
+        fi
+    done
+
+    cc -o "$WORK_DIR/check-$profile" "$WORK_DIR/check.c" "$lib" -lpthread -ldl -lm
+    "$WORK_DIR/check-$profile" "$profile" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_profile standard
+check_profile strict
+
+echo "✓ Both profiles behave as expected"
@@ -0,0 +1,205 @@
+#!/bin/bash
+#
+# Build the library for the host and check range-claim proofs: a numeric
+# claim proves "at least threshold" for thresholds up to and including its
+# value, over the full 64 bits, and a proof verifies only for that key,
+# threshold, root, issuer and nonce. With debug-circuit, witnesses of 2^64
+# or more (text values, numbers past u64) and values below the threshold
+# must violate the bit constraints. With fault-injection, a prover that
+# skips its pre-checks proves a claim edited in the blob, and the verifier
+# rejects it: the proof opens the root from the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_GenerateRangeClaimProof(const char*, const char*, const char*, const char*, uint64_t, uint64_t,
+                               uint64_t, char*, size_t, char*, size_t);
+int ZK_VerifyRangeClaimProof(const char*, const char*, const char*, const char*, uint64_t, uint64_t);
+int ZK_GenerateClaimDisclosureProof(const char*, const char*, const char*, const char*, const char*,
+                                    uint64_t, uint64_t, char*, size_t, char*, size_t);
+int ZK_CheckRangeClaimSatisfiability(const char*, const char*, uint64_t, char*, size_t);
+int ZK_RunConstraintAudit(char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_UNSATISFIED -19
+#define NOW 1700000000ULL
+
+static const char* KEYS[] = {"age", "balance", "zero", "role", "code"};
+static const char* VALUES[] = {"18", "18446744073709551615", "0", "engineer", "007"};
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char blob[8192], other_blob[8192], proof[2048], root[65], other_root[65];
+
+static int issue(const char* const* values, char* out) {
+    static char unsigned_blob[8192];
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, values, 5, NULL,
+                       unsigned_blob, sizeof(unsigned_blob)) == 0 &&
+           ZK_SignVCBlob(unsigned_blob, priv, out, 8192) == 0;
+}
+
+static int prove(const char* vc, const char* key, uint64_t threshold) {
+    return ZK_GenerateRangeClaimProof(vc, pub, NULL, key, threshold, NOW, 7, proof, sizeof(proof),
+                                      root, sizeof(root));
+}
+
+/* Prove `key` >= `threshold` and check the proof holds for nothing else */
+static int check_range(const char* key, uint64_t threshold) {
+    int rc = prove(blob, key, threshold);
+    if (rc != 0) {
+        printf("  %s >= %llu: prove %d\n", key, (unsigned long long)threshold, rc);
+        return 0;
+    }
+    int valid = ZK_VerifyRangeClaimProof(proof, pub, root, key, threshold, 7);
+    int lower = threshold > 0 ? ZK_VerifyRangeClaimProof(proof, pub, root, key, threshold - 1, 7) : 0;
+    int higher = ZK_VerifyRangeClaimProof(proof, pub, root, key, threshold + 1, 7);
+    int other_key = ZK_VerifyRangeClaimProof(proof, pub, root, strcmp(key, "age") ? "age" : "zero", threshold, 7);
+    int tree = ZK_VerifyRangeClaimProof(proof, pub, other_root, key, threshold, 7);
+    int issuer = ZK_VerifyRangeClaimProof(proof, pub2, root, key, threshold, 7);
+    int nonce = ZK_VerifyRangeClaimProof(proof, pub, root, key, threshold, 8);
+    printf("  %s >= %llu: valid %d, other threshold %d/%d, other key %d, other root %d, "
+           "other issuer %d, other nonce %d\n",
+           key, (unsigned long long)threshold, valid, lower, higher, other_key, tree, issuer, nonce);
+    return valid == 1 && lower == 0 && higher == 0 && other_key == 0 && tree == 0 && issuer == 0 && nonce == 0;
+}
+
+int main(void) {
+    static char sig[129], report[8192];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+
+    /* Same claims but an older holder: only the root tells them apart */
+    const char* other_values[] = {"40", "18446744073709551615", "0", "engineer", "007"};
+    if (!issue(VALUES, blob) || !issue(other_values, other_blob) || prove(other_blob, "age", 18) != 0) {
+        return 1;
+    }
+    memcpy(other_root, root, sizeof(root));
+
+    /* Equal to the threshold, below it, and values using all 64 bits */
+    if (!check_range("age", 18) || !check_range("age", 1) || !check_range("zero", 0) ||
+        !check_range("balance", 18446744073709551615ULL) || !check_range("balance", 1ULL << 63)) {
+        return 1;
+    }
+
+    int below = prove(blob, "age", 19);
+    int text = prove(blob, "role", 0);
+    int padded = prove(blob, "code", 7);
+    int absent = prove(blob, "height", 0);
+    printf("  age >= 19 %d, text %d, leading zero %d, absent %d\n", below, text, padded, absent);
+    if (below != -1 || text != -1 || padded != -1 || absent != -1) {
+        return 1;
+    }
+
+    /* A numeric claim also discloses as itself */
+    if (ZK_GenerateClaimDisclosureProof(blob, pub, NULL, "age", "18", NOW, 7, proof, sizeof(proof),
+                                        root, sizeof(root)) != 0) {
+        return 1;
+    }
+
+    /* Version 5 blobs have no signed root */
+    if (ZK_SignVCWithClaims("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 5, priv,
+                            sig, sizeof(sig), NULL) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 86400, NOW + 86400, KEYS, VALUES, 5, sig,
+                    other_blob, sizeof(other_blob)) != 0) {
+        return 1;
+    }
+    int legacy = prove(other_blob, "age", 18);
+    printf("  version 5 blob: %d\n", legacy);
+    if (legacy != ZK_ERR_UNSUPPORTED_VERSION) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The age claim edited in place from "18" to "40", the issuer's
+     * signature kept: the checked prover refuses it, one that skips its
+     * checks proves age >= 21 over a root the issuer never signed */
+    static char forged[8192], forged_root[65];
+    strcpy(forged, blob);
+    char* age = strstr(forged, "616765020000003138");
+    if (age == NULL) {
+        return 1;
+    }
+    memcpy(age + 14, "3430", 4);
+    int checked = prove(forged, "age", 21);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, "age", 21);
+    memcpy(forged_root, root, sizeof(root));
+    int forged_valid = forgery == 0 ? ZK_VerifyRangeClaimProof(proof, pub, forged_root, "age", 21, 7) : -1;
+    int honest = prove(blob, "age", 18);
+    int honest_valid = honest == 0 ? ZK_VerifyRangeClaimProof(proof, pub, root, "age", 18, 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited claim: checked prover %d; unchecked prover %d (verifies %d), signed claim %d (verifies %d)\n",
+           checked, forgery, forged_valid, honest, honest_valid);
+    if (checked == 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+
+#ifdef DEBUG_CIRCUIT
+    struct { const char* value; uint64_t threshold; int expected; } witnesses[] = {
+        {"18", 18, 0},
+        {"17", 18, ZK_ERR_UNSATISFIED},
+        {"18446744073709551615", 18446744073709551615ULL, 0},
+        {"18446744073709551615", 0, 0},
+        {"18446744073709551616", 18, ZK_ERR_UNSATISFIED},
+        {"18446744073709551634", 18, ZK_ERR_UNSATISFIED},
+        {"eighteen", 18, ZK_ERR_UNSATISFIED},
+        {"eighteen", 0, ZK_ERR_UNSATISFIED},
+    };
+    for (size_t i = 0; i < sizeof(witnesses) / sizeof(witnesses[0]); i++) {
+        report[0] = '\0';
+        int rc = ZK_CheckRangeClaimSatisfiability("age", witnesses[i].value, witnesses[i].threshold,
+                                                  report, sizeof(report));
+        printf("  witness %s >= %llu: %d %s\n", witnesses[i].value,
+               (unsigned long long)witnesses[i].threshold, rc, report);
+        if (rc != witnesses[i].expected || (rc != 0 && strstr(report, "range_") == NULL)) {
+            return 1;
+        }
+    }
+
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0 || strstr(report, "range-claim") == NULL) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Numeric claims prove their thresholds under the issuer-signed anchor and nothing more"
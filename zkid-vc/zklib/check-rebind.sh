@@ -0,0 +1,199 @@
+#!/bin/bash
+#
+# Build the library for the host and check holder-bound proofs: a credential
+# issued to one device key proves under that key, and after a rotation under
+# the new key with its re-binding certificate only; proofs do not verify for
+# another key, nonce, issuer or chain, and an expired credential or one
+# without the holder key claim does not prove. With fault-injection, a
+# prover that skips its pre-checks proves a holder key claim the issuer
+# never signed, and the verifier rejects it: the holder-bound circuit opens
+# the claim from the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_HolderKeyCommitment(const char*, char*, size_t);
+int ZK_CreateRotationStatement(const char*, const char*, uint64_t, char*, size_t);
+int ZK_IssueRebindCertificate(const char*, const char*, const char* const*, size_t, const char*, char*, size_t);
+int ZK_GenerateHolderBoundProof(const char*, const char*, const char*, const char* const*, size_t, uint64_t,
+                                uint64_t, char*, size_t);
+int ZK_VerifyHolderBoundProof(const char*, const char*, const char*, const char* const*, size_t, uint64_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char device_pub[3][65], device_priv[3][65];
+static char blob[8192], other_blob[8192], plain[8192], proof[4096];
+static char certificate[512], foreign[512];
+
+/* A credential of `holder`, signed by the issuer, bound to `device` (none
+ * for -1) */
+static int issue(const char* holder, int device, char* out) {
+    static char encoded[8192];
+    char commitment[65];
+    const char* keys[] = {"role", "zkid:holder-key"};
+    const char* values[] = {"engineer", commitment};
+    if (device >= 0 && ZK_HolderKeyCommitment(device_pub[device], commitment, sizeof(commitment)) != 0) {
+        return 0;
+    }
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - 30 * DAY, NOW + 30 * DAY, keys, values,
+                       device >= 0 ? 2 : 1, NULL, encoded, sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+/* The issuer's certificate moving `vc` from device `from` to `to` */
+static int rotate(const char* vc, int from, int to, uint64_t at, char* out) {
+    char statement[512];
+    return ZK_CreateRotationStatement(device_priv[from], device_priv[to], at, statement, sizeof(statement)) == 0 &&
+           ZK_IssueRebindCertificate(priv, vc, NULL, 0, statement, out, 512) == 0;
+}
+
+static int prove(const char* vc, const char* const* chain, size_t count, uint64_t time) {
+    return ZK_GenerateHolderBoundProof(vc, pub, NULL, chain, count, time, 7, proof, sizeof(proof));
+}
+
+static int verify(int device, const char* const* chain, size_t count, uint64_t nonce) {
+    return ZK_VerifyHolderBoundProof(proof, pub, device_pub[device], chain, count, nonce);
+}
+
+/* `in` with every hex-encoded `from` replaced by hex-encoded `to` (equal
+ * lengths) */
+static void replace_hex(const char* in, const char* from, const char* to, char* out) {
+    char from_hex[256] = "", to_hex[256] = "";
+    for (size_t i = 0; from[i]; i++) {
+        sprintf(from_hex + 2 * i, "%02x", (unsigned char)from[i]);
+        sprintf(to_hex + 2 * i, "%02x", (unsigned char)to[i]);
+    }
+    strcpy(out, in);
+    for (char* at = strstr(out, from_hex); at; at = strstr(at, from_hex)) {
+        memcpy(at, to_hex, strlen(to_hex));
+    }
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+    for (int i = 0; i < 3; i++) {
+        if (ZK_GenerateIssuerKeypair(device_pub[i], 65, device_priv[i], 65) != 0) {
+            return 1;
+        }
+    }
+    if (!issue("alice", 0, blob) || !issue("bob", 2, other_blob) || !issue("carol", -1, plain)) {
+        return 1;
+    }
+
+    /* Before any rotation: the original device key, and nothing else */
+    if (prove(blob, NULL, 0, NOW) != 0) {
+        return 1;
+    }
+    int valid = verify(0, NULL, 0, 7);
+    int other_key = verify(1, NULL, 0, 7);
+    int other_nonce = verify(0, NULL, 0, 8);
+    int other_issuer = ZK_VerifyHolderBoundProof(proof, pub2, device_pub[0], NULL, 0, 7);
+    int as_vc = ZK_VerifyVCProof(proof, pub, NOW, 7);
+    printf("  original key: valid %d, other key %d, other nonce %d, other issuer %d, as a VC proof %d\n", valid,
+           other_key, other_nonce, other_issuer, as_vc);
+    if (valid != 1 || other_key != 0 || other_nonce != 0 || other_issuer != 0 || as_vc == 1) {
+        return 1;
+    }
+
+    /* Rotated to the second device: the new key with its certificate only */
+    const char* chain[] = {certificate};
+    if (!rotate(blob, 0, 1, NOW - DAY, certificate) || prove(blob, chain, 1, NOW) != 0) {
+        return 1;
+    }
+    int rotated = verify(1, chain, 1, 7);
+    int without_certificate = verify(1, NULL, 0, 7);
+    int old_key = verify(0, chain, 1, 7);
+    int old_key_alone = verify(0, NULL, 0, 7);
+    printf("  rotated key: valid %d, without the certificate %d, old key %d (alone %d)\n", rotated,
+           without_certificate, old_key, old_key_alone);
+    if (rotated != 1 || without_certificate != 0 || old_key != 0 || old_key_alone != 0) {
+        return 1;
+    }
+
+    /* Bob's certificate from the third device to the second does not carry
+     * Alice's proof, nor does Alice's chain prove Bob's credential */
+    const char* foreign_chain[] = {foreign};
+    if (!rotate(other_blob, 2, 1, NOW - DAY, foreign)) {
+        return 1;
+    }
+    int foreign_certificate = verify(1, foreign_chain, 1, 7);
+    int borrowed_chain = prove(other_blob, chain, 1, NOW);
+    printf("  foreign certificate: %d, Bob with Alice's chain: %d\n", foreign_certificate, borrowed_chain);
+    if (foreign_certificate != 0 || borrowed_chain != -1) {
+        return 1;
+    }
+
+    /* Expired, or without the holder key claim */
+    int expired = prove(blob, chain, 1, NOW + 60 * DAY);
+    int unbound = prove(plain, NULL, 0, NOW);
+    printf("  expired: %d, without the claim: %d\n", expired, unbound);
+    if (expired != ZK_ERR_VC_EXPIRED || unbound != -1) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* Alice's holder key claim moved to the third device, the issuer's
+     * signature kept: one that skips its checks proves it for that key, and
+     * the verifier rejects it */
+    static char forged[8192];
+    char original[65], replacement[65];
+    if (ZK_HolderKeyCommitment(device_pub[0], original, sizeof(original)) != 0 ||
+        ZK_HolderKeyCommitment(device_pub[2], replacement, sizeof(replacement)) != 0) {
+        return 1;
+    }
+    replace_hex(blob, original, replacement, forged);
+    int checked = prove(forged, NULL, 0, NOW);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, NULL, 0, NOW);
+    int forged_valid = forgery == 0 ? verify(2, NULL, 0, 7) : -1;
+    int honest = prove(blob, chain, 1, NOW);
+    int honest_valid = honest == 0 ? verify(1, chain, 1, 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  edited claim: checked prover %d, unchecked prover %d (valid %d), signed claim %d (valid %d)\n",
+           checked, forgery, forged_valid, honest, honest_valid);
+    if (checked >= 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Holder-bound proofs hold for the issuer-signed holder key and its certificate chain only"
@@ -0,0 +1,185 @@
+#!/bin/bash
+#
+# Build the library for the host and check relay frames: a presentation
+# split into base64url or base45 frames of bounded size reassembles from
+# any order to the same bytes, and a missing frame, a duplicated frame and
+# a bit-flipped frame each give their own error. A holder signature is
+# required when the holder key is given and must be that holder's; frames
+# of another message are refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_RelayEncode(const char*, size_t, const char*, int, size_t, char*, size_t, size_t*);
+int ZK_RelayDecode(const char* const*, size_t, const char*, uint8_t*, size_t, size_t*);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_FRAME_MISSING -25
+#define ZK_ERR_FRAME_DUPLICATE -26
+#define ZK_RELAY_BASE64URL 0
+#define ZK_RELAY_BASE45 1
+#define MAX_FRAMES 64
+#define PAYLOAD 700
+
+static char holder_pub[65], holder_priv[65], other_pub[65], other_priv[65];
+static char presentation[PAYLOAD];
+static uint8_t out[4096];
+
+typedef struct {
+    char text[16384];
+    const char* frames[MAX_FRAMES];
+    size_t count;
+} Frames;
+
+/* Encode into `f` and split its text into frames; the return code */
+static int encode(Frames* f, const char* priv, int encoding, size_t max_frame) {
+    int rc = ZK_RelayEncode(presentation, PAYLOAD, priv, encoding, max_frame, f->text, sizeof(f->text), &f->count);
+    if (rc != 0 || f->count > MAX_FRAMES) {
+        return rc != 0 ? rc : 1;
+    }
+    char* frame = f->text;
+    for (size_t i = 0; i < f->count; i++) {
+        f->frames[i] = frame;
+        frame = strchr(frame, '\n');
+        if (frame != NULL) {
+            *frame++ = '\0';
+        }
+    }
+    return 0;
+}
+
+/* Decode and, on success, compare with the original presentation */
+static int decode(const char* const* frames, size_t count, const char* pub) {
+    size_t len = 0;
+    int rc = ZK_RelayDecode(frames, count, pub, out, sizeof(out), &len);
+    if (rc == 0 && (len != PAYLOAD || memcmp(out, presentation, PAYLOAD) != 0)) {
+        return 1;
+    }
+    return rc;
+}
+
+/* Whether every frame starts with `prefix`, uses only `alphabet` and fits */
+static int well_formed(const Frames* f, const char* prefix, const char* alphabet, size_t max_frame) {
+    for (size_t i = 0; i < f->count; i++) {
+        const char* body = f->frames[i] + strlen(prefix);
+        if (strncmp(f->frames[i], prefix, strlen(prefix)) != 0 || strlen(f->frames[i]) > max_frame ||
+            strspn(body, alphabet) != strlen(body)) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+int main(void) {
+    static Frames b64, b45, unsigned_frames, other_message, single;
+    const char* mixed[MAX_FRAMES + 1];
+    for (size_t i = 0; i < PAYLOAD; i++) {
+        presentation[i] = (char)(i * 7 + i / 13);
+    }
+    if (ZK_GenerateIssuerKeypair(holder_pub, sizeof(holder_pub), holder_priv, sizeof(holder_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        encode(&b64, holder_priv, ZK_RELAY_BASE64URL, 120) != 0 ||
+        encode(&b45, holder_priv, ZK_RELAY_BASE45, 100) != 0 ||
+        encode(&unsigned_frames, NULL, ZK_RELAY_BASE64URL, 120) != 0 ||
+        encode(&single, holder_priv, ZK_RELAY_BASE64URL, 4000) != 0) {
+        return 1;
+    }
+    presentation[0] ^= 1;
+    int other_rc = encode(&other_message, holder_priv, ZK_RELAY_BASE64URL, 120);
+    presentation[0] ^= 1;
+
+    /* Frames are bounded, use their alphabet, and reassemble from any order */
+    int b64_ok = well_formed(&b64, "ZKR64:", "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_", 120);
+    int b45_ok = well_formed(&b45, "ZKR45:", "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:", 100);
+    for (size_t i = 0; i < b64.count; i++) {
+        mixed[i] = b64.frames[b64.count - 1 - i];
+    }
+    int reversed = decode(mixed, b64.count, holder_pub);
+    for (size_t i = 0; i < b45.count; i++) {
+        mixed[i] = b45.frames[(i ^ 1) < b45.count ? i ^ 1 : i];
+    }
+    int shuffled = decode(mixed, b45.count, holder_pub);
+    int one = decode(single.frames, single.count, holder_pub);
+    printf("  base64url: %zu frames, well formed %d, reversed %d; base45: %zu frames, well formed %d, "
+           "shuffled %d; one frame (%zu) %d\n",
+           b64.count, b64_ok, reversed, b45.count, b45_ok, shuffled, single.count, one);
+    if (b64.count < 2 || !b64_ok || reversed != 0 || b45.count < 2 || !b45_ok || shuffled != 0 ||
+        single.count != 1 || one != 0) {
+        return 1;
+    }
+
+    /* Missing, duplicated and bit-flipped frames, each distinct */
+    int missing = decode(b64.frames, b64.count - 1, holder_pub);
+    memcpy(mixed, b64.frames, b64.count * sizeof(mixed[0]));
+    mixed[b64.count] = b64.frames[1];
+    int duplicate = decode(mixed, b64.count + 1, holder_pub);
+    char flipped[256];
+    strcpy(flipped, b64.frames[1]);
+    flipped[20] = flipped[20] == 'A' ? 'B' : 'A';
+    mixed[1] = flipped;
+    int bit_flip = decode(mixed, b64.count, holder_pub);
+    int flipped_and_missing = decode(mixed, b64.count - 1, holder_pub);
+    strcpy(flipped, b45.frames[0]);
+    flipped[10] = flipped[10] == '0' ? '1' : '0';
+    memcpy(mixed, b45.frames, b45.count * sizeof(mixed[0]));
+    mixed[0] = flipped;
+    int base45_flip = decode(mixed, b45.count, holder_pub);
+    memcpy(mixed, b64.frames, b64.count * sizeof(mixed[0]));
+    mixed[0] = other_message.frames[0];
+    int foreign = other_rc == 0 ? decode(mixed, b64.count, holder_pub) : other_rc;
+    printf("  missing %d, duplicate %d, bit flip %d (base45 %d), flip and missing %d, other message %d\n", missing,
+           duplicate, bit_flip, base45_flip, flipped_and_missing, foreign);
+    if (missing != ZK_ERR_FRAME_MISSING || duplicate != ZK_ERR_FRAME_DUPLICATE || bit_flip != ZK_ERR_CORRUPT ||
+        base45_flip != ZK_ERR_CORRUPT || flipped_and_missing != ZK_ERR_CORRUPT || foreign != ZK_ERR_CORRUPT) {
+        return 1;
+    }
+
+    /* The holder signature */
+    int other_holder = decode(b64.frames, b64.count, other_pub);
+    int unsigned_required = decode(unsigned_frames.frames, unsigned_frames.count, holder_pub);
+    int unsigned_open = decode(unsigned_frames.frames, unsigned_frames.count, NULL);
+    int signed_open = decode(b64.frames, b64.count, NULL);
+    printf("  other holder %d, unsigned with holder key %d, unsigned %d, signed without holder key %d\n",
+           other_holder, unsigned_required, unsigned_open, signed_open);
+    if (other_holder != ZK_ERR_AUTH || unsigned_required != ZK_ERR_AUTH || unsigned_open != 0 || signed_open != 0) {
+        return 1;
+    }
+
+    /* Bad arguments and short buffers */
+    Frames scratch;
+    size_t len = 0;
+    int unknown_encoding = encode(&scratch, NULL, 7, 120);
+    int tiny_frames = encode(&scratch, NULL, ZK_RELAY_BASE64URL, 30);
+    int short_frames = ZK_RelayEncode(presentation, PAYLOAD, NULL, ZK_RELAY_BASE64URL, 120, scratch.text, 64,
+                                      &scratch.count);
+    int short_out = ZK_RelayDecode(b64.frames, b64.count, NULL, out, 16, &len);
+    int bad_key = decode(b64.frames, b64.count, "zz");
+    int none = decode(b64.frames, 0, NULL);
+    printf("  unknown encoding %d, frames too small %d, short frame buffer %d, short output %d (length %zu), "
+           "bad key %d, no frames %d\n",
+           unknown_encoding, tiny_frames, short_frames, short_out, len, bad_key, none);
+    return unknown_encoding != -1 || tiny_frames != -1 || short_frames != ZK_ERR_BUFFER_TOO_SMALL ||
+           short_out != ZK_ERR_BUFFER_TOO_SMALL || len != PAYLOAD || bad_key != -1 || none != ZK_ERR_FRAME_MISSING;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Relay frames reassemble in any order and name what went wrong"
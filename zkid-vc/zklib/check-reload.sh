@@ -0,0 +1,254 @@
+#!/bin/bash
+#
+# Build the library for the host and check hot-reload of published
+# revocation lists: an issuer process signs 2000 epochs that alternately
+# leave a credential out of its list and revoke it, and a verifier process
+# applies them in order while four threads validate the credential without
+# pause. Every report's revocation outcome must match the epoch it records,
+# and the threads must see the epoch move under them. Older, tampered and
+# foreign lists are then refused without changing the active epoch, and
+# the stats count every update. A list only counts for the issuer the
+# verifier names: another issuer's self-signed list, or one relabeled with
+# the trusted key, is refused, also once ZK_Cleanup has dropped the active
+# list, and a list the verifier does accept from another issuer does not
+# revoke the first issuer's credential even though it lists its id.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdatomic.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    int signature, time_policy, revocation, schema;
+    uint64_t revocation_epoch;
+} ZkValidationReport;
+
+typedef struct {
+    uint64_t epoch, entries, updates, refused;
+} ZkRevocationDataStats;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_PublishRevocationData(const char*, uint64_t, char*, size_t, char*, size_t);
+int ZK_UpdateRevocationData(const char*, const char*, const char*);
+void ZK_Cleanup(void);
+int ZK_GetRevocationDataStats(ZkRevocationDataStats*);
+int ZK_ValidateVC(const char*, size_t, const char*, const void*, uint64_t, uint32_t, ZkValidationReport*);
+
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_KEY_MISMATCH -17
+#define ZK_ERR_EXPIRED -28
+#define ZK_VALIDATE_SIGNATURE 1
+#define ZK_VALIDATE_REVOCATION 4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define EPOCHS 2000
+#define THREADS 4
+#define LIST_LEN 1024
+#define ATTESTATION_LEN 129
+/* "ZKRL" | version | issuer_public_key */
+#define ISSUER_OFFSET 5
+
+static char pub[65], priv[65], other_pub[65], other_priv[65], encoded[8192], blob[8192], json[16384];
+static char path[512];
+static char lists[EPOCHS + 2][LIST_LEN], attestations[EPOCHS + 2][ATTESTATION_LEN];
+static atomic_int done;
+
+/* An even epoch leaves the credential out of the list, an odd one revokes it */
+static int revoked_in(uint64_t epoch) {
+    return epoch % 2 == 1;
+}
+
+/* The issuer: every even epoch before the revocation, every odd one after */
+static int issuer(const char* dir) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_InspectArtifact(blob, strlen(blob), json, sizeof(json)) != 0 ||
+        strstr(json, "\"credential_id\":\"") == NULL) {
+        return 1;
+    }
+    const char* id = strstr(json, "\"credential_id\":\"") + strlen("\"credential_id\":\"");
+    for (int revoked = 0; revoked < 2; revoked++) {
+        if (revoked && ZK_RevokeCredential(id, 64, NOW) != 0) {
+            return 1;
+        }
+        for (uint64_t epoch = 1; epoch <= EPOCHS; epoch++) {
+            if (revoked_in(epoch) == revoked &&
+                ZK_PublishRevocationData(priv, epoch, lists[epoch], LIST_LEN, attestations[epoch],
+                                         ATTESTATION_LEN) != 0) {
+                return 1;
+            }
+        }
+    }
+    /* The last slot: a later epoch signed by another issuer */
+    int epoch_zero = ZK_PublishRevocationData(priv, 0, lists[0], LIST_LEN, attestations[0], ATTESTATION_LEN);
+    if (epoch_zero != -1 || ZK_PublishRevocationData(other_priv, EPOCHS + 1, lists[EPOCHS + 1], LIST_LEN,
+                                                     attestations[EPOCHS + 1], ATTESTATION_LEN) != 0) {
+        return 1;
+    }
+
+    snprintf(path, sizeof(path), "%s/lists", dir);
+    FILE* f = fopen(path, "w");
+    if (f == NULL) {
+        return 1;
+    }
+    fprintf(f, "%s\n%s\n%s\n", pub, other_pub, blob);
+    for (int epoch = 1; epoch <= EPOCHS + 1; epoch++) {
+        fprintf(f, "%s %s\n", lists[epoch], attestations[epoch]);
+    }
+    printf("  issuer: %d epochs signed, epoch 0 %d\n", EPOCHS, epoch_zero);
+    return fclose(f) != 0;
+}
+
+typedef struct {
+    uint64_t reports, inconsistent, epoch_changes, last_epoch;
+} Observed;
+
+/* Validate without pause until the updates are done */
+static void* validate_loop(void* arg) {
+    Observed* seen = arg;
+    while (!atomic_load(&done)) {
+        ZkValidationReport report;
+        int valid = ZK_ValidateVC(blob, strlen(blob), pub, NULL, NOW, ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_REVOCATION,
+                                  &report);
+        int expected = !revoked_in(report.revocation_epoch);
+        if (report.signature != 1 || report.revocation != expected || valid != expected) {
+            seen->inconsistent++;
+        }
+        if (report.revocation_epoch < seen->last_epoch) {
+            seen->inconsistent++;
+        }
+        if (report.revocation_epoch != seen->last_epoch) {
+            seen->epoch_changes++;
+        }
+        seen->last_epoch = report.revocation_epoch;
+        seen->reports++;
+    }
+    return NULL;
+}
+
+/* The verifier: apply every epoch in order under concurrent validation */
+static int verifier(const char* dir) {
+    snprintf(path, sizeof(path), "%s/lists", dir);
+    FILE* f = fopen(path, "r");
+    if (f == NULL || fscanf(f, "%64s %64s %8191s", pub, other_pub, blob) != 3) {
+        return 1;
+    }
+    for (int epoch = 1; epoch <= EPOCHS + 1; epoch++) {
+        if (fscanf(f, "%1023s %128s", lists[epoch], attestations[epoch]) != 2) {
+            return 1;
+        }
+    }
+    fclose(f);
+
+    pthread_t threads[THREADS];
+    Observed seen[THREADS] = {{0}};
+    for (int i = 0; i < THREADS; i++) {
+        pthread_create(&threads[i], NULL, validate_loop, &seen[i]);
+    }
+    int failed_updates = 0;
+    for (int epoch = 1; epoch <= EPOCHS; epoch++) {
+        failed_updates += ZK_UpdateRevocationData(lists[epoch], attestations[epoch], pub) != 0;
+    }
+    atomic_store(&done, 1);
+    Observed total = {0};
+    for (int i = 0; i < THREADS; i++) {
+        pthread_join(threads[i], NULL);
+        total.reports += seen[i].reports;
+        total.inconsistent += seen[i].inconsistent;
+        total.epoch_changes += seen[i].epoch_changes;
+    }
+    ZkRevocationDataStats stats;
+    ZK_GetRevocationDataStats(&stats);
+    printf("  %d updates failed; %llu reports, %llu inconsistent, %llu epoch changes seen; active epoch %llu, "
+           "%llu updates\n",
+           failed_updates, (unsigned long long)total.reports, (unsigned long long)total.inconsistent,
+           (unsigned long long)total.epoch_changes, (unsigned long long)stats.epoch,
+           (unsigned long long)stats.updates);
+    if (failed_updates != 0 || total.inconsistent != 0 || total.epoch_changes < 2 || stats.epoch != EPOCHS ||
+        stats.entries != revoked_in(EPOCHS) || stats.updates != EPOCHS || stats.refused != 0) {
+        return 1;
+    }
+
+    /* Refused lists leave the active epoch in place */
+    char tampered[ATTESTATION_LEN];
+    strcpy(tampered, attestations[EPOCHS - 1]);
+    tampered[0] = tampered[0] == '0' ? '1' : '0';
+    int again = ZK_UpdateRevocationData(lists[EPOCHS], attestations[EPOCHS], pub);
+    int older = ZK_UpdateRevocationData(lists[EPOCHS - 1], attestations[EPOCHS - 1], pub);
+    int forged = ZK_UpdateRevocationData(lists[EPOCHS - 1], tampered, pub);
+    int foreign = ZK_UpdateRevocationData(lists[EPOCHS + 1], attestations[EPOCHS + 1], pub);
+    int garbage = ZK_UpdateRevocationData("5a4b524c", attestations[EPOCHS], pub);
+    /* NULL is a usage error, not a refused list */
+    int null_list = ZK_UpdateRevocationData(NULL, attestations[EPOCHS], pub);
+    int null_issuer = ZK_UpdateRevocationData(lists[EPOCHS], attestations[EPOCHS], NULL);
+    ZK_GetRevocationDataStats(&stats);
+    printf("  active list again %d, older %d, tampered %d, another issuer %d, not a list %d, NULL %d and %d; "
+           "epoch %llu, %llu updates, %llu refused\n",
+           again, older, forged, foreign, garbage, null_list, null_issuer, (unsigned long long)stats.epoch,
+           (unsigned long long)stats.updates, (unsigned long long)stats.refused);
+    if (again != 0 || older != ZK_ERR_EXPIRED || forged != ZK_ERR_AUTH || foreign != ZK_ERR_KEY_MISMATCH ||
+        garbage != ZK_ERR_CORRUPT || null_list != -1 || null_issuer != -1 || stats.epoch != EPOCHS ||
+        stats.updates != EPOCHS || stats.refused != 4 || ZK_GetRevocationDataStats(NULL) != -1) {
+        return 1;
+    }
+
+    /* Nothing pinned after cleanup: the trusted key still decides */
+    char relabeled[LIST_LEN];
+    strcpy(relabeled, lists[EPOCHS + 1]);
+    memcpy(relabeled + 2 * ISSUER_OFFSET, pub, 64);
+    ZK_Cleanup();
+    int self_signed = ZK_UpdateRevocationData(lists[EPOCHS + 1], attestations[EPOCHS + 1], pub);
+    int relabeled_key = ZK_UpdateRevocationData(relabeled, attestations[EPOCHS + 1], pub);
+    ZK_GetRevocationDataStats(&stats);
+    uint64_t unpinned = stats.epoch;
+    /* Accepted for its own issuer, the foreign list does not reach this one's credential */
+    int trusted_foreign = ZK_UpdateRevocationData(lists[EPOCHS + 1], attestations[EPOCHS + 1], other_pub);
+    ZkValidationReport report;
+    int valid = ZK_ValidateVC(blob, strlen(blob), pub, NULL, NOW, ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_REVOCATION,
+                              &report);
+    printf("  after cleanup: self-signed foreign list %d, relabeled with the trusted key %d, epoch %llu; "
+           "trusted for its own issuer %d, credential still valid %d (epoch %llu)\n",
+           self_signed, relabeled_key, (unsigned long long)unpinned, trusted_foreign, valid,
+           (unsigned long long)report.revocation_epoch);
+    return self_signed != ZK_ERR_KEY_MISMATCH || relabeled_key != ZK_ERR_AUTH || unpinned != 0 ||
+           trusted_foreign != 0 || valid != 1 || report.revocation != 1 || report.revocation_epoch != 0;
+}
+
+int main(int argc, char** argv) {
+    if (argc < 3) {
+        return 1;
+    }
+    return strcmp(argv[1], "issuer") == 0 ? issuer(argv[2]) : verifier(argv[2]);
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+for role in issuer verifier; do
+    "$WORK_DIR/check" "$role" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+done
+echo "  ok"
+
+echo "✓ Revocation lists swap in without a verification seeing a mixed state"
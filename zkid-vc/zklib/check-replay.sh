@@ -0,0 +1,142 @@
+#!/bin/bash
+#
+# Build the library for the host and check prover-side nonce reuse
+# detection under each policy: a repeated nonce is proved again and counted
+# (warn), answered with a rerandomized copy of the earlier proof that still
+# verifies (rerandomize; another statement is proved anew), or refused with
+# ZK_ERR_NONCE_REUSED (refuse). Pairs older than the TTL or pushed out of a
+# full cache are fresh again, a failed proof does not reserve its nonce,
+# and a capacity of 0 turns detection off.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+typedef struct {
+    uint32_t capacity;
+    uint64_t ttl_secs;
+    int policy;
+} ZkNonceCacheConfig;
+typedef struct {
+    uint64_t proofs, reused, rerandomized, refused;
+} ZkNonceReuseStats;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_SetNonceReusePolicy(const ZkNonceCacheConfig*);
+int ZK_NonceReuseStats(ZkNonceReuseStats*);
+
+#define ZK_ERR_NONCE_REUSED -22
+#define ZK_NONCE_REUSE_WARN 0
+#define ZK_NONCE_REUSE_RERANDOMIZE 1
+#define ZK_NONCE_REUSE_REFUSE 2
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], blob[8192], other_blob[8192], first[4096], second[4096];
+
+static int issue(const char* holder, char* out) {
+    static char encoded[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+static int prove(const char* vc, uint64_t nonce, char* out) {
+    return ZK_GenerateVCProofFromBlob(vc, pub, NULL, NOW, nonce, out, 4096);
+}
+
+static int policy(uint32_t capacity, uint64_t ttl_secs, int which) {
+    ZkNonceCacheConfig config = {capacity, ttl_secs, which};
+    return ZK_SetNonceReusePolicy(&config);
+}
+
+static ZkNonceReuseStats stats(void) {
+    ZkNonceReuseStats s = {0};
+    ZK_NonceReuseStats(&s);
+    return s;
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        !issue("alice", blob) || !issue("bob", other_blob)) {
+        return 1;
+    }
+
+    /* Warn (the default): proved again, counted */
+    int warn = prove(blob, 7, first) | prove(blob, 7, second);
+    ZkNonceReuseStats s = stats();
+    printf("  warn: %d, proofs differ %d; proofs %llu, reused %llu\n", warn, strcmp(first, second) != 0,
+           (unsigned long long)s.proofs, (unsigned long long)s.reused);
+    if (warn != 0 || strcmp(first, second) == 0 || s.proofs != 2 || s.reused != 1) {
+        return 1;
+    }
+
+    /* Rerandomize: the repeat is the earlier proof, rerandomized */
+    int rc = policy(64, 600, ZK_NONCE_REUSE_RERANDOMIZE) | prove(blob, 10, first) | prove(blob, 10, second);
+    int unlinkable = strcmp(first, second) != 0;
+    int both_verify = ZK_VerifyVCProof(first, pub, NOW, 10) == 1 && ZK_VerifyVCProof(second, pub, NOW, 10) == 1;
+    int other_statement = prove(other_blob, 10, second);
+    s = stats();
+    printf("  rerandomize: %d, proofs differ %d, both verify %d; other credential %d; rerandomized %llu\n", rc,
+           unlinkable, both_verify, other_statement, (unsigned long long)s.rerandomized);
+    if (rc != 0 || !unlinkable || !both_verify || other_statement != 0 || s.rerandomized != 1 || s.reused != 3) {
+        return 1;
+    }
+
+    /* Refuse, and the ways a pair becomes fresh again */
+    rc = policy(64, 600, ZK_NONCE_REUSE_REFUSE) | prove(blob, 20, first);
+    int refused = prove(blob, 20, second), other_nonce = prove(blob, 21, second);
+    int failed = ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW + 2 * DAY, 22, second, sizeof(second));
+    int after_failure = prove(blob, 22, second);
+    s = stats();
+    printf("  refuse: repeat %d, other nonce %d, after a failed proof %d (failed %d); refused %llu\n", refused,
+           other_nonce, after_failure, failed, (unsigned long long)s.refused);
+    if (rc != 0 || refused != ZK_ERR_NONCE_REUSED || other_nonce != 0 || failed == 0 || after_failure != 0 ||
+        s.refused != 1) {
+        return 1;
+    }
+    rc = policy(1, 600, ZK_NONCE_REUSE_REFUSE) | prove(blob, 30, first) | prove(blob, 31, first);
+    int evicted = prove(blob, 30, first);
+    rc |= policy(64, 1, ZK_NONCE_REUSE_REFUSE) | prove(blob, 40, first);
+    sleep(2);
+    int expired = prove(blob, 40, first);
+    rc |= policy(0, 600, ZK_NONCE_REUSE_REFUSE) | prove(blob, 50, first);
+    int disabled = prove(blob, 50, first);
+    printf("  refuse: pushed out of a full cache %d, past the TTL %d, detection off %d\n", evicted, expired,
+           disabled);
+    if (rc != 0 || evicted != 0 || expired != 0 || disabled != 0 || stats().refused != 1) {
+        return 1;
+    }
+
+    int unknown = policy(64, 600, 9), null_config = ZK_SetNonceReusePolicy(NULL);
+    int null_stats = ZK_NonceReuseStats(NULL);
+    printf("  unknown policy %d, NULL config %d, NULL stats %d\n", unknown, null_config, null_stats);
+    return unknown != -1 || null_config != -1 || null_stats != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Repeated nonces are proved again, rerandomized or refused as configured"
@@ -0,0 +1,178 @@
+#!/bin/bash
+#
+# Build the library for the host and check non-revoked VC proofs: a proof
+# against the issuer's revocation root verifies for that root only, and
+# once the credential is revoked it has no witness, its old witness does
+# not prove against the new root, and its old proof does not verify
+# against it. Revoking another credential leaves this one provable with a
+# fresh witness. With debug-circuit, the constraint audit covers the
+# circuit. With fault-injection, revoked Alice proves Dave's unrevoked hash
+# with her own signature through a prover that skips its signature check,
+# and the verifier rejects the proof: the hash shown outside the tree must
+# be the one in the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct RevocationTree RevocationTree;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_ComputeVCHash(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                     const char* const*, size_t, char*, size_t, size_t*);
+int ZK_RevocationTreeCreate(RevocationTree**);
+int ZK_RevocationTreeAdd(RevocationTree*, const char*);
+int ZK_RevocationTreeRoot(RevocationTree*, char*, size_t);
+int ZK_RevocationTreeWitness(RevocationTree*, const char*, char*, size_t);
+void ZK_RevocationTreeFree(RevocationTree*);
+int ZK_GenerateVCProofNonRevoked(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*,
+                                 const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t,
+                                 size_t*);
+int ZK_VerifyVCProofNonRevoked(const char*, const char*, uint64_t, uint64_t, const char*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_RunConstraintAudit(char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_BAD_HEX -6
+#define ZK_ERR_REVOKED -35
+#define NOW 150
+
+static char pub[65], priv[65], alice_sig[129], alice_hash[65], bob_hash[65], carol_hash[65];
+static char proof[1024], witness[1200];
+
+static int prove(const char* root, const char* w, uint64_t current_time, uint64_t nonce) {
+    return ZK_GenerateVCProofNonRevoked("alice", 5, "issuer", 6, 100, 200, alice_sig, pub, root, w, current_time,
+                                        nonce, proof, sizeof(proof), NULL);
+}
+
+static int hash(const char* holder, char* out) {
+    return ZK_ComputeVCHash(holder, strlen(holder), "issuer", 6, 100, 200, NULL, NULL, 0, out, 65, NULL);
+}
+
+int main(void) {
+    static char report[8192];
+    char empty_root[65], first_root[65], second_root[65], third_root[65], old_witness[1200];
+    RevocationTree* tree = NULL;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, alice_sig, sizeof(alice_sig), NULL) != 0 ||
+        hash("alice", alice_hash) != 0 || hash("bob", bob_hash) != 0 || hash("carol", carol_hash) != 0 ||
+        ZK_RevocationTreeCreate(&tree) != 0 || ZK_RevocationTreeRoot(tree, empty_root, sizeof(empty_root)) != 0) {
+        return 1;
+    }
+
+    /* Bob is revoked; Alice proves against the root */
+    int added = ZK_RevocationTreeAdd(tree, bob_hash);
+    int again = ZK_RevocationTreeAdd(tree, bob_hash);
+    int malformed = ZK_RevocationTreeAdd(tree, "00");
+    if (ZK_RevocationTreeRoot(tree, first_root, sizeof(first_root)) != 0 ||
+        ZK_RevocationTreeWitness(tree, alice_hash, witness, sizeof(witness)) != 0) {
+        return 1;
+    }
+    int bob_witness = ZK_RevocationTreeWitness(tree, bob_hash, old_witness, sizeof(old_witness));
+    printf("  revoke bob: added %d, again %d, malformed %d, root changed %d, bob's witness %d\n", added, again,
+           malformed, strcmp(empty_root, first_root) != 0, bob_witness);
+    if (added != 0 || again != 1 || malformed != ZK_ERR_BAD_HEX || strcmp(empty_root, first_root) == 0 ||
+        bob_witness != ZK_ERR_REVOKED) {
+        return 1;
+    }
+
+    int rc = prove(first_root, witness, NOW, 42);
+    if (rc != 0) {
+        printf("  alice: prove %d\n", rc);
+        return 1;
+    }
+    int valid = ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 42, first_root);
+    int empty = ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 42, empty_root);
+    int nonce = ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 43, first_root);
+    int plain = ZK_VerifyVCProof(proof, pub, NOW, 42);
+    int wrong_root = prove(empty_root, witness, NOW, 44);
+    int expired = prove(first_root, witness, 201, 44);
+    printf("  alice: valid %d, other root %d, other nonce %d, as VC proof %d, proven against other root %d, "
+           "expired %d\n", valid, empty, nonce, plain, wrong_root, expired);
+    if (valid != 1 || empty != 0 || nonce != 0 || plain != 0 || wrong_root != ZK_ERR_REVOKED ||
+        expired != ZK_ERR_VC_EXPIRED) {
+        return 1;
+    }
+
+    /* Carol's revocation shifts no gap of Alice's into a revoked one */
+    if (ZK_RevocationTreeAdd(tree, carol_hash) != 0 ||
+        ZK_RevocationTreeRoot(tree, second_root, sizeof(second_root)) != 0 ||
+        ZK_RevocationTreeWitness(tree, alice_hash, witness, sizeof(witness)) != 0 ||
+        prove(second_root, witness, NOW, 45) != 0 || ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 45, second_root) != 1) {
+        return 1;
+    }
+    printf("  revoke carol: alice proves against the new root\n");
+
+    /* Alice is revoked: no witness, the old one and the old proof fail */
+    memcpy(old_witness, witness, sizeof(witness));
+    if (ZK_RevocationTreeAdd(tree, alice_hash) != 0 ||
+        ZK_RevocationTreeRoot(tree, third_root, sizeof(third_root)) != 0) {
+        return 1;
+    }
+    int revoked_witness = ZK_RevocationTreeWitness(tree, alice_hash, witness, sizeof(witness));
+    int stale = prove(third_root, old_witness, NOW, 46);
+    int old_proof = ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 45, third_root);
+    printf("  revoke alice: witness %d, old witness against new root %d, old proof against new root %d\n",
+           revoked_witness, stale, old_proof);
+    if (revoked_witness != ZK_ERR_REVOKED || stale != ZK_ERR_REVOKED || old_proof != 0) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* Alice's signature over Dave's unrevoked fields */
+    char dave_hash[65];
+    if (hash("dave", dave_hash) != 0 || ZK_RevocationTreeWitness(tree, dave_hash, witness, sizeof(witness)) != 0) {
+        return 1;
+    }
+    ZK_SkipProverChecks(1);
+    int swapped = ZK_GenerateVCProofNonRevoked("dave", 4, "issuer", 6, 100, 200, alice_sig, pub, third_root,
+                                               witness, NOW, 47, proof, sizeof(proof), NULL);
+    int swapped_valid = swapped == 0 ? ZK_VerifyVCProofNonRevoked(proof, pub, NOW, 47, third_root) : -1;
+    printf("  unchecked prover, dave's hash with alice's signature: %d (verifies %d)\n", swapped, swapped_valid);
+    if (swapped != 0 || swapped_valid != 0) {
+        return 1;
+    }
+#endif
+    ZK_RevocationTreeFree(tree);
+
+#ifdef DEBUG_CIRCUIT
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0 || strstr(report, "non-revoked-vc") == NULL) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Revoked credentials lose their non-revoked proofs"
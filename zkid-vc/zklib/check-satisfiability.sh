@@ -0,0 +1,94 @@
+#!/bin/bash
+#
+# Build the library with debug-circuit for the host and check the witness
+# check of the VC circuit: an honest credential still proves and verifies
+# with the check in front of the prover; a witness outside its window fails
+# with ZK_ERR_UNSATISFIED and a report naming the first failing constraint;
+# a report buffer too small for that is ZK_ERR_BUFFER_TOO_SMALL; and NULL,
+# non-hex inputs or an unknown format are refused before synthesis.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_CheckCircuitSatisfiability(uint8_t, const char*, const char*, uint64_t, int64_t, int64_t, uint64_t, char*,
+                                  size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_UNSATISFIED -19
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096];
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    const char* hash = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+    char report[256], small[8];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    int proved = ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof));
+    int verified = proved == 0 ? ZK_VerifyVCProof(proof, pub, NOW, 7) : -100;
+    printf("  honest credential: prove %d, verify %d\n", proved, verified);
+    if (proved != 0 || verified != 1) {
+        return 1;
+    }
+
+    /* The same window checked directly, inside and a day past it */
+    report[0] = '\0';
+    int inside = ZK_CheckCircuitSatisfiability(3, hash, pub, 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    int past = ZK_CheckCircuitSatisfiability(3, hash, pub, 7, NOW - DAY, NOW + DAY, NOW + 2 * DAY, report,
+                                             sizeof(report));
+    unsigned int index = 0;
+    char name[128] = "";
+    int named = sscanf(report, "constraint %u (%127[^)])", &index, name) == 2;
+    int short_report = ZK_CheckCircuitSatisfiability(3, hash, pub, 7, NOW - DAY, NOW + DAY, NOW + 2 * DAY, small,
+                                                     sizeof(small));
+    printf("  inside the window %d; a day past it %d (%s), named %d; report buffer of %zu %d\n", inside, past,
+           report, named, sizeof(small), short_report);
+    if (inside != 0 || past != ZK_ERR_UNSATISFIED || !named || strncmp(name, "range_", 6) != 0 ||
+        short_report != ZK_ERR_BUFFER_TOO_SMALL) {
+        return 1;
+    }
+
+    int null_hash = ZK_CheckCircuitSatisfiability(3, NULL, pub, 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    int null_key = ZK_CheckCircuitSatisfiability(3, hash, NULL, 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    int not_hex = ZK_CheckCircuitSatisfiability(3, "zz", pub, 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    int bad_key = ZK_CheckCircuitSatisfiability(3, hash, "zz", 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    int unknown = ZK_CheckCircuitSatisfiability(9, hash, pub, 7, NOW - DAY, NOW + DAY, NOW, report, sizeof(report));
+    printf("  NULL hash %d, NULL issuer key %d, non-hex hash %d, non-hex issuer key %d, format 9 %d\n", null_hash,
+           null_key, not_hex, bad_key, unknown);
+    return null_hash != -1 || null_key != -1 || not_hex != -1 || bad_key != -1 || unknown != -1;
+}
+EOF
+
+echo "Checking build: debug-circuit"
+cargo rustc --release --lib --crate-type staticlib --features debug-circuit --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ The witness check passes honest credentials and names the constraint a bad witness breaks"
@@ -0,0 +1,158 @@
+#!/bin/bash
+#
+# Build the library for the host and check schedule proofs: a scheduled
+# credential proves inside a weekly window, a wrapping weekly window or an
+# interval window and is refused outside them, and a proof verifies only
+# for its time, nonce and issuer, and never as a VC proof. With
+# fault-injection, a prover that skips its pre-checks proves a time inside
+# a window widened in the blob, and the verifier rejects it: the schedule
+# circuit opens the windows from the anchor the issuer signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SetValiditySchedule(const char*, const int*, const int64_t*, const int64_t*, size_t, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateScheduleProof(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyScheduleProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define NOW 1700000000LL
+#define DAY 86400LL
+#define HOUR 3600LL
+
+/* NOW is a Tuesday, 22:13:20 UTC */
+#define MONDAY (NOW - DAY - 22 * HOUR - 13 * 60 - 20)
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char blob[8192], plain[8192], proof[4096];
+
+/* A credential of Alice, signed by `key`, with `count` windows */
+static int issue(const int* kinds, const int64_t* starts, const int64_t* ends, size_t count, const char* key,
+                 char* out) {
+    static char encoded[8192], scheduled[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_EncodeVC("alice", 5, "issuer", 6, NOW - 30 * DAY, NOW + 30 * DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0) {
+        return 0;
+    }
+    if (count > 0 && ZK_SetValiditySchedule(encoded, kinds, starts, ends, count, scheduled, sizeof(scheduled)) != 0) {
+        return 0;
+    }
+    return ZK_SignVCBlob(count > 0 ? scheduled : encoded, key, out, 8192) == 0;
+}
+
+static int prove(const char* vc, int64_t time) {
+    return ZK_GenerateScheduleProof(vc, pub, NULL, (uint64_t)time, 7, proof, sizeof(proof));
+}
+
+/* Prove at `time` and check the proof verifies there only */
+static int check_inside(const char* vc, int64_t time, const char* label) {
+    int rc = prove(vc, time);
+    if (rc != 0) {
+        printf("  %s: prove %d\n", label, rc);
+        return 0;
+    }
+    int valid = ZK_VerifyScheduleProof(proof, pub, (uint64_t)time, 7);
+    int other_time = ZK_VerifyScheduleProof(proof, pub, (uint64_t)time + 1, 7);
+    int other_nonce = ZK_VerifyScheduleProof(proof, pub, (uint64_t)time, 8);
+    int other_issuer = ZK_VerifyScheduleProof(proof, pub2, (uint64_t)time, 7);
+    int as_vc = ZK_VerifyVCProof(proof, pub, (uint64_t)time, 7);
+    printf("  %s: valid %d, other time %d, other nonce %d, other issuer %d, as a VC proof %d\n",
+           label, valid, other_time, other_nonce, other_issuer, as_vc);
+    return valid == 1 && other_time == 0 && other_nonce == 0 && other_issuer == 0 && as_vc == 0;
+}
+
+int main(void) {
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+
+    /* Tuesdays 22:00-23:00, Sunday 22:00 into Monday 06:00, and one day
+     * from NOW + 1 day */
+    const int kinds[] = {1, 1, 0};
+    const int64_t starts[] = {DAY + 22 * HOUR, 6 * DAY + 22 * HOUR, NOW + DAY};
+    const int64_t ends[] = {DAY + 23 * HOUR, 6 * HOUR, NOW + 2 * DAY};
+    if (!issue(kinds, starts, ends, 3, priv, blob) || !issue(NULL, NULL, NULL, 0, priv, plain)) {
+        return 1;
+    }
+
+    if (!check_inside(blob, NOW, "weekly window") ||
+        !check_inside(blob, MONDAY + 7 * DAY + 2 * HOUR, "wrapping weekly window") ||
+        !check_inside(blob, MONDAY + 6 * DAY + 23 * HOUR, "wrapping weekly window before midnight") ||
+        !check_inside(blob, NOW + DAY + HOUR, "interval window")) {
+        return 1;
+    }
+
+    int outside = prove(blob, NOW + 3 * HOUR);
+    int at_end = prove(blob, MONDAY + DAY + 23 * HOUR);
+    int unscheduled = prove(plain, NOW);
+    int other_key = ZK_GenerateScheduleProof(blob, pub2, NULL, NOW, 7, proof, sizeof(proof));
+    printf("  outside every window %d, at a window's end %d, unscheduled %d, other issuer key %d\n",
+           outside, at_end, unscheduled, other_key);
+    if (outside != ZK_ERR_VC_EXPIRED || at_end != ZK_ERR_VC_EXPIRED || unscheduled != -1 || other_key >= 0) {
+        return 1;
+    }
+
+#ifdef FAULT_INJECTION
+    /* The Tuesday window widened in place to end Wednesday 06:00, the
+     * signature kept: the checked prover refuses a time inside it, one
+     * that skips its checks proves it under an anchor the issuer never
+     * signed */
+    static char forged[8192];
+    strcpy(forged, blob);
+    char* window = strstr(forged, "01e086020000000000f094020000000000");
+    if (window == NULL) {
+        return 1;
+    }
+    memcpy(window, "01e08602000000000060f7020000000000", 34);
+    int checked = prove(forged, NOW + 3 * HOUR);
+    ZK_SkipProverChecks(1);
+    int forgery = prove(forged, NOW + 3 * HOUR);
+    int forged_valid = forgery == 0 ? ZK_VerifyScheduleProof(proof, pub, NOW + 3 * HOUR, 7) : -1;
+    int honest = prove(blob, NOW);
+    int honest_valid = honest == 0 ? ZK_VerifyScheduleProof(proof, pub, NOW, 7) : -1;
+    ZK_SkipProverChecks(0);
+    printf("  widened window: checked prover %d; unchecked prover %d (verifies %d), signed window %d "
+           "(verifies %d)\n", checked, forgery, forged_valid, honest, honest_valid);
+    if (checked >= 0 || forgery != 0 || forged_valid != 0 || honest != 0 || honest_valid != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ Schedule proofs verify only for windows the issuer signed"
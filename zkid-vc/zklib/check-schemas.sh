@@ -0,0 +1,139 @@
+#!/bin/bash
+#
+# Build the library for the host and check schema pinning: with an
+# allowlist set, a presentation verifies only if it discloses an allowed
+# credential of its own proof, signed by its issuer. A disallowed, hidden
+# or swapped-in credential is refused with ZK_ERR_SCHEMA_NOT_ALLOWED, and
+# the wallet attaches the credential itself when the request names schemas
+# and refuses one outside them before presenting.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_EncodePresentationRequest(const char*, uint64_t, uint64_t, uint64_t, const char* const*, const char* const*,
+                                 size_t, char*, size_t);
+int ZK_EncodePresentationForRequest(const char*, const char*, size_t, const char*, const char* const*,
+                                    const char* const*, size_t, char*, size_t);
+int ZK_SetSchemaAllowlist(const char* const*, size_t);
+int ZK_VerifyPresentation(const char*, size_t);
+
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_SCHEMA_NOT_ALLOWED -27
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], encoded[8192];
+static char employee[8192], contractor[8192], visitor[8192], unschemed[8192];
+static char proof[4096], visitor_proof[4096], unschemed_proof[4096], request[4096];
+
+static int issue(const char* holder, const char* schema, char* out) {
+    const char* keys[] = {"role", "zkid:schema"};
+    const char* values[] = {"engineer", schema};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, schema ? 2 : 1, NULL,
+                       encoded, sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+/* Present `p` with `vc` as zkid.vc (NULL: none) and verify it */
+static int verify_with(const char* p, const char* vc) {
+    static char presentation[32768];
+    const char* keys[] = {"zkid.vc"};
+    const char* values[] = {vc};
+    if (ZK_EncodePresentation(p, pub, 7, keys, values, vc != NULL, presentation, sizeof(presentation)) != 0) {
+        return 1000;
+    }
+    return ZK_VerifyPresentation(presentation, strlen(presentation));
+}
+
+int main(void) {
+    const char* allowed[] = {"employee/v2", "contractor/v1"};
+    const char* request_keys[] = {"schema"};
+    const char* request_values[] = {"employee/v2, contractor/v1"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        !issue("alice", "employee/v2", employee) || !issue("alice", "contractor/v1", contractor) ||
+        !issue("carol", "visitor/v1", visitor) || !issue("dave", NULL, unschemed) ||
+        ZK_GenerateVCProofFromBlob(employee, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_GenerateVCProofFromBlob(visitor, pub, NULL, NOW, 7, visitor_proof, sizeof(visitor_proof)) != 0 ||
+        ZK_GenerateVCProofFromBlob(unschemed, pub, NULL, NOW, 7, unschemed_proof, sizeof(unschemed_proof)) != 0 ||
+        ZK_EncodePresentationRequest(pub, 7, NOW, NOW + DAY, request_keys, request_values, 1, request,
+                                     sizeof(request)) != 0) {
+        return 1;
+    }
+
+    /* Without an allowlist nothing needs disclosing */
+    int open_hidden = verify_with(proof, NULL);
+    if (ZK_SetSchemaAllowlist(allowed, 2) != 0) {
+        return 1;
+    }
+
+    /* Allowed, disallowed, hidden and swapped-in schemas */
+    int allowed_schema = verify_with(proof, employee);
+    int disallowed = verify_with(visitor_proof, visitor);
+    int hidden = verify_with(proof, NULL);
+    int no_schema = verify_with(unschemed_proof, unschemed);
+    int swapped = verify_with(visitor_proof, employee);
+    int other_credential = verify_with(proof, contractor);
+    printf("  no allowlist, hidden %d; allowed %d, disallowed %d, hidden %d, no schema claim %d, "
+           "allowed credential on another proof %d, another allowed credential %d\n",
+           open_hidden, allowed_schema, disallowed, hidden, no_schema, swapped, other_credential);
+    if (open_hidden != 1 || allowed_schema != 1 || disallowed != ZK_ERR_SCHEMA_NOT_ALLOWED ||
+        hidden != ZK_ERR_SCHEMA_NOT_ALLOWED || no_schema != ZK_ERR_SCHEMA_NOT_ALLOWED ||
+        swapped != ZK_ERR_SCHEMA_NOT_ALLOWED || other_credential != ZK_ERR_SCHEMA_NOT_ALLOWED) {
+        return 1;
+    }
+
+    /* The wallet attaches the credential a pinned request needs */
+    static char answer[32768], refused[32768];
+    size_t len = strlen(request);
+    int answered = ZK_EncodePresentationForRequest(proof, request, len, employee, NULL, NULL, 0, answer,
+                                                   sizeof(answer));
+    int answer_verifies = answered == 0 ? ZK_VerifyPresentation(answer, strlen(answer)) : answered;
+    int wrong_schema = ZK_EncodePresentationForRequest(visitor_proof, request, len, visitor, NULL, NULL, 0, refused,
+                                                       sizeof(refused));
+    int no_credential = ZK_EncodePresentationForRequest(proof, request, len, NULL, NULL, NULL, 0, refused,
+                                                        sizeof(refused));
+    int bad_request = ZK_EncodePresentationForRequest(proof, "zz", 2, employee, NULL, NULL, 0, refused,
+                                                      sizeof(refused));
+    printf("  answered %d (verifies %d), outside the request's schemas %d, no credential %d, bad request %d\n",
+           answered, answer_verifies, wrong_schema, no_credential, bad_request);
+    if (answered != 0 || answer_verifies != 1 || wrong_schema != ZK_ERR_SCHEMA_NOT_ALLOWED || no_credential != -1 ||
+        bad_request != ZK_ERR_CORRUPT) {
+        return 1;
+    }
+
+    const char* empty[] = {""};
+    int empty_id = ZK_SetSchemaAllowlist(empty, 1);
+    int null_list = ZK_SetSchemaAllowlist(NULL, 1);
+    int off = ZK_SetSchemaAllowlist(NULL, 0);
+    int hidden_after = verify_with(proof, NULL);
+    printf("  empty schema id %d, NULL list %d, pinning off %d (hidden %d)\n", empty_id, null_list, off,
+           hidden_after);
+    return empty_id != -1 || null_list != -1 || off != 0 || hidden_after != 1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Pinned schemas must be disclosed, signed and allowed"
@@ -0,0 +1,270 @@
+#!/bin/bash
+#
+# Build the library for the host and check issuer sequence numbers: with
+# sequencing enabled, signed blobs carry zkid:sequence 1, 2, ... per issuer
+# key, persisted through the host storage backend. A signer that crashes
+# after persisting a number but before signing leaves a gap that a
+# restarted process continues past, never reusing it; threads signing at
+# once never get the same number; and disabling sequencing or another key
+# leaves the counter alone. The other signers number what they sign (a JWT
+# of an unsigned blob, a claim group under its own key) or refuse with
+# ZK_ERR_SEQUENCE_UNSUPPORTED (ZK_SignVC, ZK_SignVCWithClaims, a JWT of a
+# blob signed without a number).
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+typedef int (*get_cb)(void*, const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+typedef int (*put_cb)(void*, const uint8_t*, size_t, const uint8_t*, size_t);
+typedef int (*delete_cb)(void*, const uint8_t*, size_t);
+
+int ZK_SetStorageCallbacks(get_cb, put_cb, delete_cb, void*);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_SerializeVCJson(const char*, char*, size_t, size_t*);
+int ZK_SetIssuerSequencing(int);
+int ZK_GetIssuerSequence(const char*, uint64_t*);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t, size_t*);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_SignVCJwt(const char*, const char*, char*, size_t, size_t*);
+int ZK_EncodeCompositeVC(const char*, size_t, int64_t, int64_t, const char* const*, const char* const*, size_t,
+                         char*, size_t);
+int ZK_AddClaimGroup(const char*, const char*, size_t, const char*, const char* const*, const char* const*, size_t,
+                     char*, size_t);
+int ZK_SignClaimGroup(const char*, size_t, const char*, char*, size_t);
+
+#define ZK_ERR_SEQUENCE_UNSUPPORTED -40
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define SLOTS 8
+#define THREADS 8
+#define PER_THREAD 8
+
+/* The host's table, in memory shared with the crashing child process */
+typedef struct {
+    uint8_t key[128], value[128];
+    size_t key_len, value_len;
+    int used;
+} Slot;
+
+typedef struct {
+    Slot slots[SLOTS];
+    int crash_on_put;
+} Table;
+
+static pthread_mutex_t table_lock = PTHREAD_MUTEX_INITIALIZER;
+static char pub[65], priv[65], other_pub[65], other_priv[65], encoded[8192], other_encoded[8192];
+static uint64_t numbers[THREADS * PER_THREAD];
+
+static Slot* find(Table* t, const uint8_t* key, size_t key_len) {
+    for (int i = 0; i < SLOTS; i++) {
+        if (t->slots[i].used && t->slots[i].key_len == key_len && memcmp(t->slots[i].key, key, key_len) == 0) {
+            return &t->slots[i];
+        }
+    }
+    return NULL;
+}
+
+static int table_get(void* user_data, const uint8_t* key, size_t key_len, uint8_t* out, size_t cap, size_t* len) {
+    pthread_mutex_lock(&table_lock);
+    Slot* slot = find(user_data, key, key_len);
+    if (slot != NULL) {
+        *len = slot->value_len;
+        if (slot->value_len <= cap) {
+            memcpy(out, slot->value, slot->value_len);
+        }
+    }
+    pthread_mutex_unlock(&table_lock);
+    return slot == NULL;
+}
+
+static int table_put(void* user_data, const uint8_t* key, size_t key_len, const uint8_t* value, size_t value_len) {
+    Table* t = user_data;
+    pthread_mutex_lock(&table_lock);
+    Slot* slot = find(t, key, key_len);
+    for (int i = 0; slot == NULL && i < SLOTS; i++) {
+        if (!t->slots[i].used) {
+            slot = &t->slots[i];
+        }
+    }
+    if (slot == NULL || key_len > sizeof(slot->key) || value_len > sizeof(slot->value)) {
+        pthread_mutex_unlock(&table_lock);
+        return -1;
+    }
+    memcpy(slot->key, key, key_len);
+    memcpy(slot->value, value, value_len);
+    slot->key_len = key_len;
+    slot->value_len = value_len;
+    slot->used = 1;
+    pthread_mutex_unlock(&table_lock);
+    /* The number is persisted; the process dies before signing */
+    if (t->crash_on_put) {
+        _exit(3);
+    }
+    return 0;
+}
+
+static int table_delete(void* user_data, const uint8_t* key, size_t key_len) {
+    pthread_mutex_lock(&table_lock);
+    Slot* slot = find(user_data, key, key_len);
+    if (slot != NULL) {
+        slot->used = 0;
+    }
+    pthread_mutex_unlock(&table_lock);
+    return 0;
+}
+
+/* Sign `unsigned_blob` with `key`; the sequence claim it carries, 0 for
+   none, or -1 if signing fails */
+static int64_t sign(const char* unsigned_blob, const char* key) {
+    char blob[8192], json[8192];
+    if (ZK_SignVCBlob(unsigned_blob, key, blob, sizeof(blob)) != 0 ||
+        ZK_SerializeVCJson(blob, json, sizeof(json), NULL) != 0) {
+        return -1;
+    }
+    const char* at = strstr(json, "\"zkid:sequence\":\"");
+    return at == NULL ? 0 : strtoll(at + strlen("\"zkid:sequence\":\""), NULL, 10);
+}
+
+static uint64_t sequence(const char* key) {
+    uint64_t value = UINT64_MAX;
+    ZK_GetIssuerSequence(key, &value);
+    return value;
+}
+
+static void* sign_many(void* arg) {
+    int t = (int)(intptr_t)arg;
+    for (int i = 0; i < PER_THREAD; i++) {
+        numbers[t * PER_THREAD + i] = (uint64_t)sign(encoded, priv);
+    }
+    return NULL;
+}
+
+static int compare(const void* a, const void* b) {
+    uint64_t x = *(const uint64_t*)a, y = *(const uint64_t*)b;
+    return (x > y) - (x < y);
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    Table* table = mmap(NULL, sizeof(Table), PROT_READ | PROT_WRITE, MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+    if (table == MAP_FAILED || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_EncodeVC("bob", 3, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, other_encoded,
+                    sizeof(other_encoded)) != 0) {
+        return 1;
+    }
+
+    /* A signer numbers two credentials, then dies after reserving a third */
+    pid_t child = fork();
+    if (child == 0) {
+        if (ZK_SetStorageCallbacks(table_get, table_put, table_delete, table) != 0 ||
+            ZK_SetIssuerSequencing(1) != 0 || sign(encoded, priv) != 1 || sign(encoded, priv) != 2) {
+            _exit(1);
+        }
+        table->crash_on_put = 1;
+        sign(encoded, priv);
+        _exit(0);
+    }
+    int status = 0;
+    waitpid(child, &status, 0);
+    table->crash_on_put = 0;
+    printf("  first signer exited with %d\n", WIFEXITED(status) ? WEXITSTATUS(status) : -1);
+    if (!WIFEXITED(status) || WEXITSTATUS(status) != 3) {
+        return 1;
+    }
+
+    /* The restarted signer continues past the lost number */
+    if (ZK_SetStorageCallbacks(table_get, table_put, table_delete, table) != 0) {
+        return 1;
+    }
+    uint64_t after_crash = sequence(pub);
+    int64_t unsequenced = sign(encoded, priv);
+    ZK_SetIssuerSequencing(1);
+    int64_t next = sign(encoded, priv), other = sign(other_encoded, other_priv);
+    char blob[8192];
+    int verifies = ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) == 0 && ZK_VerifyVCBlob(blob, pub, NULL) == 1;
+    printf("  after the crash: high-water %llu; disabled %lld, next %lld, other key %lld, verifies %d\n",
+           (unsigned long long)after_crash, (long long)unsequenced, (long long)next, (long long)other, verifies);
+    if (after_crash != 3 || unsequenced != 0 || next != 4 || other != 1 || !verifies) {
+        return 1;
+    }
+
+    /* Threads signing at once get distinct numbers 6.. with no gap */
+    pthread_t threads[THREADS];
+    for (int t = 0; t < THREADS; t++) {
+        pthread_create(&threads[t], NULL, sign_many, (void*)(intptr_t)t);
+    }
+    for (int t = 0; t < THREADS; t++) {
+        pthread_join(threads[t], NULL);
+    }
+    qsort(numbers, THREADS * PER_THREAD, sizeof(numbers[0]), compare);
+    int distinct = 1;
+    for (int i = 0; i < THREADS * PER_THREAD; i++) {
+        distinct &= numbers[i] == (uint64_t)(6 + i);
+    }
+    uint64_t high = sequence(pub), never = 0;
+    int bad_key = ZK_GetIssuerSequence("zz", &never), null_out = ZK_GetIssuerSequence(pub, NULL);
+    printf("  %d concurrent signatures numbered 6..%d once each %d, high-water %llu; bad key %d, NULL %d\n",
+           THREADS * PER_THREAD, 5 + THREADS * PER_THREAD, distinct, (unsigned long long)high, bad_key, null_out);
+    if (!distinct || high != 5 + THREADS * PER_THREAD || bad_key != -1 || null_out != -1) {
+        return 1;
+    }
+
+    /* The other signers number what they sign or refuse to sign */
+    char signature[256], jwt[16384], composite[8192], group[8192], unnumbered[8192];
+    int bare = ZK_SignVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, priv, signature, sizeof(signature), NULL);
+    int with_claims = ZK_SignVCWithClaims("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, priv,
+                                          signature, sizeof(signature), NULL);
+    uint64_t refused = sequence(pub);
+    int unsigned_jwt = ZK_SignVCJwt(encoded, priv, jwt, sizeof(jwt), NULL);
+    uint64_t after_jwt = sequence(pub);
+    int numbered_jwt = ZK_SignVCJwt(blob, priv, jwt, sizeof(jwt), NULL);
+    ZK_SetIssuerSequencing(0);
+    int unnumbered_signed = ZK_SignVCBlob(encoded, priv, unnumbered, sizeof(unnumbered));
+    ZK_SetIssuerSequencing(1);
+    int unnumbered_jwt = ZK_SignVCJwt(unnumbered, priv, jwt, sizeof(jwt), NULL);
+    int grouped = ZK_EncodeCompositeVC("alice", 5, NOW - DAY, NOW + DAY, NULL, NULL, 0, composite,
+                                       sizeof(composite)) == 0 &&
+                  ZK_AddClaimGroup(composite, "hr", 2, other_pub, keys, values, 1, group, sizeof(group)) == 0 &&
+                  ZK_SignClaimGroup(group, 0, other_priv, composite, sizeof(composite)) == 0;
+    printf("  signers: bare %d, with claims %d (high-water %llu); jwt unsigned %d (%llu), numbered %d (%llu), "
+           "unnumbered %d; claim group %d (other key %llu)\n", bare, with_claims, (unsigned long long)refused,
+           unsigned_jwt, (unsigned long long)after_jwt, numbered_jwt, (unsigned long long)sequence(pub),
+           unnumbered_jwt, grouped, (unsigned long long)sequence(other_pub));
+    return bare != ZK_ERR_SEQUENCE_UNSUPPORTED || with_claims != ZK_ERR_SEQUENCE_UNSUPPORTED || refused != high ||
+           unsigned_jwt != 0 || after_jwt != high + 1 || numbered_jwt != 0 || sequence(pub) != high + 1 ||
+           unnumbered_signed != 0 || unnumbered_jwt != ZK_ERR_SEQUENCE_UNSUPPORTED || !grouped ||
+           sequence(other_pub) != 2;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Issuer sequence numbers survive crashes without duplicates and cover every signer"
@@ -0,0 +1,135 @@
+#!/bin/bash
+#
+# Build the library for the host and check the frozen encodings through
+# ZK_ConvertLegacyArtifact: the pinned field element and proof vectors, a
+# VC proof of either format with its validity prefix, and the exported
+# verifying key come back byte for byte, while a non-canonical field
+# element, a point off the curve or a truncated key is refused. Lengths are
+# reported to a short buffer.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_ConvertLegacyArtifact(int, const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ARTIFACT_PROOF 1
+#define ZK_ARTIFACT_VERIFYING_KEY 2
+#define ZK_ARTIFACT_FIELD_ELEMENT 3
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+/* conformance/vectors.json, "encoding" kind */
+static const char* field_zero = "0000000000000000000000000000000000000000000000000000000000000000";
+static const char* field_one = "0100000000000000000000000000000000000000000000000000000000000000";
+static const char* field_top = "000000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430";
+static const char* modulus = "010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430";
+static const char* proof_compressed =
+    "d3cf876dc108c2d3a81c8716a91678d9851518685b04859b021a132ee7440603f5c7fb9406fade12005e9ab08c477e8d5e7192e12628e5"
+    "51900eb14d784e0606856eb6ff324f82c9a7a078686b1504bcebe4cf5dcd9151734297bb572f771410a93f16faa7a849e89ca35389d8de"
+    "e46243772b760402bc66f7e0fe0edf39c117";
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096], vk[8192];
+static uint8_t in[8192], out[8192];
+
+static size_t unhex(const char* hex, uint8_t* bytes) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len; i++) {
+        sscanf(hex + 2 * i, "%2hhx", &bytes[i]);
+    }
+    return len;
+}
+
+/* Convert the hex artifact; 1 if it comes back byte for byte */
+static int unchanged(const char* name, int kind, const char* hex) {
+    size_t len = unhex(hex, in), written = 0;
+    int code = ZK_ConvertLegacyArtifact(kind, in, len, out, sizeof(out), &written);
+    int same = code == 0 && written == len && memcmp(in, out, len) == 0;
+    printf("  %s: %d, %zu bytes, unchanged %d\n", name, code, written, same);
+    return same;
+}
+
+static int refused(int kind, const char* hex, size_t len) {
+    size_t written = 0;
+    unhex(hex, in);
+    return ZK_ConvertLegacyArtifact(kind, in, len, out, sizeof(out), &written);
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 || ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+
+    /* Pinned vectors and live artifacts pass through unchanged */
+    int ok = unchanged("field 0", ZK_ARTIFACT_FIELD_ELEMENT, field_zero) &&
+             unchanged("field 1", ZK_ARTIFACT_FIELD_ELEMENT, field_one) &&
+             unchanged("field p - 1", ZK_ARTIFACT_FIELD_ELEMENT, field_top) &&
+             unchanged("pinned proof", ZK_ARTIFACT_PROOF, proof_compressed) &&
+             unchanged("exported verifying key", ZK_ARTIFACT_VERIFYING_KEY, vk);
+    if (!ok || ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        !unchanged("VC proof, format 2", ZK_ARTIFACT_PROOF, proof) || ZK_SetEmitFormatVersion(1) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        !unchanged("VC proof, format 1", ZK_ARTIFACT_PROOF, proof) || ZK_SetEmitFormatVersion(2) != 0) {
+        return 1;
+    }
+
+    /* Outside the frozen layouts */
+    char off_curve[512];
+    strcpy(off_curve, proof_compressed);
+    /* C's x set to 0x0303..03, which has no point */
+    for (size_t i = strlen(off_curve) - 64; i < strlen(off_curve); i += 2) {
+        memcpy(off_curve + i, "03", 2);
+    }
+    int non_canonical = refused(ZK_ARTIFACT_FIELD_ELEMENT, modulus, 32);
+    int short_field = refused(ZK_ARTIFACT_FIELD_ELEMENT, field_one, 31);
+    int bad_point = refused(ZK_ARTIFACT_PROOF, off_curve, strlen(off_curve) / 2);
+    int truncated_key = refused(ZK_ARTIFACT_VERIFYING_KEY, vk, strlen(vk) / 2 - 1);
+    int wrong_kind = refused(ZK_ARTIFACT_VERIFYING_KEY, proof_compressed, strlen(proof_compressed) / 2);
+    printf("  modulus %d, 31-byte field %d, point off the curve %d, truncated key %d, proof as a key %d\n",
+           non_canonical, short_field, bad_point, truncated_key, wrong_kind);
+    if (non_canonical != ZK_ERR_CORRUPT || short_field != ZK_ERR_CORRUPT || bad_point != ZK_ERR_CORRUPT ||
+        truncated_key != ZK_ERR_CORRUPT || wrong_kind != ZK_ERR_CORRUPT) {
+        return 1;
+    }
+
+    size_t len = unhex(field_one, in), written = 0;
+    int short_buffer = ZK_ConvertLegacyArtifact(ZK_ARTIFACT_FIELD_ELEMENT, in, len, out, 16, &written);
+    int unknown_kind = ZK_ConvertLegacyArtifact(9, in, len, out, sizeof(out), &written);
+    int null_artifact = ZK_ConvertLegacyArtifact(ZK_ARTIFACT_PROOF, NULL, 128, out, sizeof(out), &written);
+    printf("  short buffer %d (needs %zu), unknown kind %d, NULL %d\n", short_buffer, written, unknown_kind,
+           null_artifact);
+    return short_buffer != ZK_ERR_BUFFER_TOO_SMALL || written != 32 || unknown_kind != -1 || null_artifact != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Stored artifacts keep their frozen encodings"
@@ -0,0 +1,183 @@
+#!/bin/bash
+#
+# Build the library for the host and check session bindings: a verified
+# presentation yields a binding that holds no claim data and refreshes as
+# valid until the binding or credential nears its end or the revocation
+# epoch advances too far (a fresh presentation is due), and fails once the
+# credential is revoked, outside its dates, the epoch goes backwards, or
+# the binding, session secret or verifier secret do not match.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    uint64_t lifetime_secs, renew_before_secs, max_epoch_advance;
+} ZkSessionPolicy;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_CreateSessionBinding(const char*, const char*, size_t, const char*, const char*, const char*, uint64_t,
+                            uint64_t, const ZkSessionPolicy*, char*, size_t, char*, size_t);
+int ZK_RefreshSession(const char*, const char*, const char*, uint64_t, uint64_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_SESSION_REPRESENT 2
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define HOUR 3600ULL
+#define EPOCH 5
+
+static const char* verifier_secret = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+static const char* other_verifier_secret = "ff0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+static char pub[65], priv[65], encoded[8192], blob[8192], other_blob[8192], proof[4096], presentation[16384];
+static char secret[128], binding[4096], long_secret[128], long_binding[4096], scratch_secret[128], scratch[4096];
+
+static int issue(const char* holder, char* out) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+/* The credential_id ZK_InspectArtifact reports for `vc` */
+static int credential_id(const char* vc, char* out) {
+    static char json[16384];
+    const char* at = ZK_InspectArtifact(vc, strlen(vc), json, sizeof(json)) == 0
+                         ? strstr(json, "\"credential_id\":\"")
+                         : NULL;
+    if (at == NULL) {
+        return 0;
+    }
+    memcpy(out, at + strlen("\"credential_id\":\""), 64);
+    out[64] = '\0';
+    return 1;
+}
+
+/* Whether the hex `binding` contains the bytes of `text` */
+static int contains(const char* binding_hex, const char* text) {
+    char hex[256] = {0};
+    for (size_t i = 0; text[i] != '\0'; i++) {
+        snprintf(hex + 2 * i, 3, "%02x", (unsigned char)text[i]);
+    }
+    return strstr(binding_hex, hex) != NULL;
+}
+
+static int refresh(const char* b, const char* s, uint64_t now, uint64_t epoch) {
+    return ZK_RefreshSession(verifier_secret, b, s, now, epoch);
+}
+
+int main(void) {
+    const char* keys[] = {"audience", "purpose"};
+    const char* values[] = {"gateway.example", "login"};
+    const ZkSessionPolicy policy = {HOUR, 600, 2}, long_policy = {2 * DAY, 600, 2};
+    char vc_id[65];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        !issue("alice", blob) || !issue("bob", other_blob) || !credential_id(blob, vc_id) ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, keys, values, 2, presentation, sizeof(presentation)) != 0) {
+        return 1;
+    }
+
+    /* Bindings after a full verification, free of claim data */
+    size_t len = strlen(presentation);
+    int created = ZK_CreateSessionBinding(verifier_secret, presentation, len, blob, pub, NULL, NOW, EPOCH, &policy,
+                                          secret, sizeof(secret), binding, sizeof(binding));
+    int created_long = ZK_CreateSessionBinding(verifier_secret, presentation, len, blob, pub, NULL, NOW, EPOCH,
+                                               &long_policy, long_secret, sizeof(long_secret), long_binding,
+                                               sizeof(long_binding));
+    int other_credential = ZK_CreateSessionBinding(verifier_secret, presentation, len, other_blob, pub, NULL, NOW,
+                                                   EPOCH, &policy, long_secret, sizeof(long_secret), long_binding,
+                                                   sizeof(long_binding));
+    int claim_free = !contains(binding, "alice") && !contains(binding, "engineer") && !contains(binding, "issuer");
+    printf("  created %d (long %d), for another credential %d; secret %zu hex, no claim data %d\n", created,
+           created_long, other_credential, strlen(secret), claim_free);
+    if (created != 1 || created_long != 1 || other_credential != 0 || strlen(secret) != 64 || !claim_free) {
+        return 1;
+    }
+
+    /* Time policy and revocation epoch */
+    int valid = refresh(binding, secret, NOW + 60, EPOCH);
+    int epoch_within = refresh(binding, secret, NOW + 60, EPOCH + 2);
+    int epoch_past = refresh(binding, secret, NOW + 60, EPOCH + 3);
+    int epoch_back = refresh(binding, secret, NOW + 60, EPOCH - 1);
+    int binding_expired = refresh(binding, secret, NOW + HOUR + 1, EPOCH);
+    int nearing_expiry = refresh(long_binding, long_secret, NOW + DAY - 300, EPOCH);
+    int credential_expired = refresh(long_binding, long_secret, NOW + DAY + 1, EPOCH);
+    int not_yet = refresh(long_binding, long_secret, NOW - DAY - 1, EPOCH);
+    printf("  valid %d; epoch +2 %d, +3 %d, back %d; binding expired %d, credential nearing expiry %d, "
+           "expired %d, not yet valid %d\n",
+           valid, epoch_within, epoch_past, epoch_back, binding_expired, nearing_expiry, credential_expired,
+           not_yet);
+    if (valid != 1 || epoch_within != 1 || epoch_past != ZK_SESSION_REPRESENT || epoch_back != 0 ||
+        binding_expired != ZK_SESSION_REPRESENT || nearing_expiry != ZK_SESSION_REPRESENT ||
+        credential_expired != 0 || not_yet != 0) {
+        return 1;
+    }
+
+    /* Bindings are unforgeable and tied to their session */
+    char altered[4096];
+    strcpy(altered, binding);
+    altered[20] = altered[20] == '0' ? '1' : '0';
+    int wrong_secret = refresh(binding, long_secret, NOW + 60, EPOCH);
+    int other_verifier = ZK_RefreshSession(other_verifier_secret, binding, secret, NOW + 60, EPOCH);
+    int tampered = refresh(altered, secret, NOW + 60, EPOCH);
+    int null_binding = refresh(NULL, secret, NOW + 60, EPOCH);
+    printf("  wrong session secret %d, other verifier secret %d, altered binding %d, NULL binding %d\n",
+           wrong_secret, other_verifier, tampered, null_binding);
+    if (wrong_secret != 0 || other_verifier != 0 || tampered != 0 || null_binding != 0) {
+        return 1;
+    }
+
+    int null_policy = ZK_CreateSessionBinding(verifier_secret, presentation, len, blob, pub, NULL, NOW, EPOCH, NULL,
+                                              scratch_secret, sizeof(scratch_secret), scratch, sizeof(scratch));
+    int empty_secret = ZK_CreateSessionBinding("", presentation, len, blob, pub, NULL, NOW, EPOCH, &policy,
+                                               scratch_secret, sizeof(scratch_secret), scratch, sizeof(scratch));
+    int short_binding = ZK_CreateSessionBinding(verifier_secret, presentation, len, blob, pub, NULL, NOW, EPOCH,
+                                                &policy, scratch_secret, sizeof(scratch_secret), scratch, 16);
+    printf("  NULL policy %d, empty verifier secret %d, short binding buffer %d\n", null_policy, empty_secret,
+           short_binding);
+    if (null_policy != -1 || empty_secret != -1 || short_binding != ZK_ERR_BUFFER_TOO_SMALL) {
+        return 1;
+    }
+
+    /* The session across revocation of its credential */
+    int before = refresh(binding, secret, NOW + 120, EPOCH);
+    if (ZK_RevokeCredential(vc_id, strlen(vc_id), NOW + 150) != 0) {
+        return 1;
+    }
+    int after = refresh(binding, secret, NOW + 180, EPOCH + 1);
+    int after_long = refresh(long_binding, long_secret, NOW + 180, EPOCH + 1);
+    int recreated = ZK_CreateSessionBinding(verifier_secret, presentation, len, blob, pub, NULL, NOW + 180, EPOCH + 1,
+                                            &policy, secret, sizeof(secret), binding, sizeof(binding));
+    printf("  before revocation %d, at the next check %d (long binding %d), new binding %d\n", before, after,
+           after_long, recreated);
+    return before != 1 || after != 0 || after_long != 0 || recreated != 0;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Session bindings refresh until revocation, expiry or an epoch jump"
@@ -0,0 +1,135 @@
+#!/bin/bash
+#
+# Build the standard and strict libraries for the host and check
+# ZK_SizeOf against real artifacts: the proof buffer size matches proofs
+# of formats 2 and 3, compressed and uncompressed; the verifying key sizes
+# match the exported key and its prepared form; the issuer key buffer fits
+# a generated key exactly. Unknown items, circuits and versions are -1, and
+# the legacy format is ZK_ERR_DISABLED in strict builds.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_ConvertProofEncoding(const char*, size_t, int, char*, size_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_PrepareVerifyingKey(const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+int ZK_SizeOf(int, int, uint8_t);
+
+#define ZK_ERR_DISABLED -16
+#define ZK_PROOF_ENCODING_UNCOMPRESSED 1
+#define ZK_CIRCUIT_VC 1
+#define ZK_SIZE_PROOF 1
+#define ZK_SIZE_PROOF_UNCOMPRESSED 2
+#define ZK_SIZE_VERIFYING_KEY 3
+#define ZK_SIZE_PREPARED_VERIFYING_KEY 4
+#define ZK_SIZE_PROOF_HEX 9
+#define ZK_SIZE_VERIFYING_KEY_HEX 10
+#define ZK_SIZE_PUBKEY_HEX 13
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[8192], uncompressed[8192], vk_hex[16384];
+static uint8_t vk[8192], prepared[65536];
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    /* Proofs of both enveloped formats, in both encodings */
+    for (uint8_t format = 2; format <= 3; format++) {
+        if (ZK_SetEmitFormatVersion(format) != 0 ||
+            ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+            ZK_ConvertProofEncoding(proof, strlen(proof), ZK_PROOF_ENCODING_UNCOMPRESSED, uncompressed,
+                                    sizeof(uncompressed)) != 0) {
+            return 1;
+        }
+        int hex = ZK_SizeOf(ZK_SIZE_PROOF_HEX, ZK_CIRCUIT_VC, format);
+        int bytes = ZK_SizeOf(ZK_SIZE_PROOF, ZK_CIRCUIT_VC, format);
+        int expanded = ZK_SizeOf(ZK_SIZE_PROOF_UNCOMPRESSED, ZK_CIRCUIT_VC, format);
+        printf("  format %u: proof hex buffer %d for %zu characters, %d bytes, uncompressed %d for %zu\n", format,
+               hex, strlen(proof), bytes, expanded, strlen(uncompressed) / 2);
+        if (hex != (int)strlen(proof) + 1 || bytes != (int)strlen(proof) / 2 ||
+            expanded != (int)strlen(uncompressed) / 2) {
+            return 1;
+        }
+    }
+
+    /* The verifying key in both forms, and the issuer key */
+    size_t vk_len = 0, prepared_len = 0;
+    if (ZK_ExportVerifyingKey(vk_hex, sizeof(vk_hex)) != 0) {
+        return 1;
+    }
+    vk_len = strlen(vk_hex) / 2;
+    for (size_t i = 0; i < vk_len; i++) {
+        unsigned int byte;
+        sscanf(vk_hex + 2 * i, "%2x", &byte);
+        vk[i] = (uint8_t)byte;
+    }
+    if (ZK_PrepareVerifyingKey(vk, vk_len, prepared, sizeof(prepared), &prepared_len) != 0) {
+        return 1;
+    }
+    int vk_size = ZK_SizeOf(ZK_SIZE_VERIFYING_KEY, ZK_CIRCUIT_VC, 3);
+    int vk_hex_size = ZK_SizeOf(ZK_SIZE_VERIFYING_KEY_HEX, ZK_CIRCUIT_VC, 3);
+    int prepared_size = ZK_SizeOf(ZK_SIZE_PREPARED_VERIFYING_KEY, ZK_CIRCUIT_VC, 3);
+    int pubkey_hex = ZK_SizeOf(ZK_SIZE_PUBKEY_HEX, ZK_CIRCUIT_VC, 3);
+    printf("  verifying key %d for %zu bytes (hex buffer %d), prepared %d for %zu, issuer key buffer %d for %zu\n",
+           vk_size, vk_len, vk_hex_size, prepared_size, prepared_len, pubkey_hex, strlen(pub));
+    if (vk_size != (int)vk_len || vk_hex_size != (int)strlen(vk_hex) + 1 || prepared_size != (int)prepared_len ||
+        pubkey_hex != (int)strlen(pub) + 1) {
+        return 1;
+    }
+
+    /* Combinations that do not exist */
+    int unknown_item = ZK_SizeOf(99, ZK_CIRCUIT_VC, 3);
+    int unknown_circuit = ZK_SizeOf(ZK_SIZE_PROOF, 99, 3);
+    int unknown_version = ZK_SizeOf(ZK_SIZE_PROOF, ZK_CIRCUIT_VC, 9);
+    int legacy = ZK_SizeOf(ZK_SIZE_PROOF, ZK_CIRCUIT_VC, 1);
+    printf("  unknown item %d, circuit %d, version %d; legacy format %d\n", unknown_item, unknown_circuit,
+           unknown_version, legacy);
+#ifdef STRICT
+    int legacy_ok = legacy == ZK_ERR_DISABLED;
+#else
+    int legacy_ok = legacy > 0;
+#endif
+    return unknown_item != -1 || unknown_circuit != -1 || unknown_version != -1 || !legacy_ok;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build strict "--features strict" "-DSTRICT"
+
+echo "✓ ZK_SizeOf reports the sizes of the artifacts the library produces"
@@ -0,0 +1,329 @@
+#!/bin/bash
+#
+# Build the library for the host and check verifier state snapshots across
+# processes: a control process configures a trust store, schema allowlist,
+# accepted formats and revocations, runs a fixture corpus through detailed
+# verification and exports a signed snapshot. A fresh worker holding only
+# the verifying key reports a different fingerprint and different results
+# until it imports the snapshot, then matches both exactly. Older, repeated,
+# tampered, newer and foreign-signed snapshots are refused, no private key
+# is ever in one, and a worker without the verifying key refuses to apply
+# it. Workers importing the same snapshot from several threads at once
+# apply it exactly once.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct Context Context;
+
+typedef struct {
+    int signature, proof, time_policy, revocation, audience;
+    uint64_t revocation_epoch;
+} ZkVerifyReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_VerifyPresentationDetailed(const char*, size_t, const char*, const char*, const char*, const char*, uint64_t,
+                                  ZkVerifyReport*);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_SetAcceptedFormatVersions(uint32_t);
+int ZK_SetEmitFormatVersion(uint8_t);
+int ZK_SetSchemaAllowlist(const char* const*, size_t);
+int ZK_SetRejectSuperseded(int);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_ImportVerifyingKey(const char*);
+int ZK_ContextCreate(Context**);
+int ZK_ContextTrustIssuer(Context*, const char*);
+void ZK_ContextFree(Context*);
+int ZK_ExportVerifierState(const Context*, const char*, uint64_t, char*, size_t);
+int ZK_ImportVerifierState(Context*, const char*, const char*);
+int ZK_VerifierStateFingerprint(const Context*, char*, size_t);
+
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_KEY_MISMATCH -17
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_ERR_EXPIRED -28
+#define ZK_ACCEPT_FORMAT_V2 2
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define CASES 5
+#define STATE_LEN (1 << 20)
+/* "ZKVS" | version */
+#define VERSION_OFFSET 4
+
+static char pub[65], priv[65], control_pub[65], control_priv[65], other_pub[65], other_priv[65];
+static char encoded[8192], proof[4096], json[16384], fingerprint[65], path[512];
+static char vcs[CASES][8192], presentations[CASES][32768], expected[CASES][128], results[CASES][128];
+static char state[STATE_LEN], older[STATE_LEN], damaged[STATE_LEN], vk[8192];
+
+static int write_file(const char* dir, const char* name, const char* text) {
+    snprintf(path, sizeof(path), "%s/%s", dir, name);
+    FILE* f = fopen(path, "w");
+    int ok = f != NULL && fputs(text, f) >= 0;
+    return f != NULL && fclose(f) == 0 && ok;
+}
+
+static int read_file(const char* dir, const char* name, char* out, size_t size) {
+    snprintf(path, sizeof(path), "%s/%s", dir, name);
+    FILE* f = fopen(path, "r");
+    size_t len = f == NULL ? 0 : fread(out, 1, size - 1, f);
+    out[len] = '\0';
+    return f != NULL && fclose(f) == 0 && len > 0;
+}
+
+/* Detailed verification of every case, one line each */
+static void run_corpus(char lines[CASES][128]) {
+    for (int i = 0; i < CASES; i++) {
+        ZkVerifyReport r;
+        int result = ZK_VerifyPresentationDetailed(presentations[i], strlen(presentations[i]), vcs[i], pub, NULL,
+                                                   "gate", NOW, &r);
+        snprintf(lines[i], 128, "%d %d %d %d %d %d", result, r.signature, r.proof, r.time_policy, r.revocation,
+                 r.audience);
+    }
+}
+
+static int issue(int i, const char* holder, const char* schema, uint64_t expiry, int format) {
+    const char* keys[] = {"role", "zkid:schema"};
+    const char* values[] = {"engineer", schema};
+    const char* meta_keys[] = {"audience", "zkid.vc"};
+    const char* meta_values[] = {"gate", vcs[i]};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, expiry, keys, values, 2, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, vcs[i], sizeof(vcs[i])) == 0 && ZK_SetEmitFormatVersion(format) == 0 &&
+           ZK_GenerateVCProofFromBlob(vcs[i], pub, NULL, NOW - 60, 7, proof, sizeof(proof)) == 0 &&
+           ZK_EncodePresentation(proof, pub, 7, meta_keys, meta_values, 2, presentations[i],
+                                 sizeof(presentations[i])) == 0;
+}
+
+/* The control process: configure, record the corpus results, export */
+static int control(const char* dir) {
+    const char* allowed[] = {"employee/v2"};
+    Context* ctx = NULL;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(control_pub, sizeof(control_pub), control_priv, sizeof(control_priv)) != 0 ||
+        !issue(0, "alice", "employee/v2", NOW + DAY, 2) || !issue(1, "bob", "employee/v2", NOW + DAY, 2) ||
+        !issue(2, "carol", "visitor/v1", NOW + DAY, 2) || !issue(3, "dave", "employee/v2", NOW + DAY, 1) ||
+        !issue(4, "eve", "employee/v2", NOW - 1, 2) ||
+        ZK_InspectArtifact(vcs[1], strlen(vcs[1]), json, sizeof(json)) != 0) {
+        return 1;
+    }
+    const char* id = strstr(json, "\"credential_id\":\"") + strlen("\"credential_id\":\"");
+    if (ZK_RevokeCredential(id, 64, NOW - DAY) != 0 || ZK_SetSchemaAllowlist(allowed, 1) != 0 ||
+        ZK_SetAcceptedFormatVersions(ZK_ACCEPT_FORMAT_V2) != 0 || ZK_SetRejectSuperseded(1) != 0 ||
+        ZK_ContextCreate(&ctx) != 0 || ZK_ContextTrustIssuer(ctx, pub) != 0) {
+        return 1;
+    }
+    run_corpus(expected);
+    if (ZK_ExportVerifierState(ctx, control_priv, 100, older, sizeof(older)) != 0 ||
+        ZK_ExportVerifierState(ctx, control_priv, 200, state, sizeof(state)) != 0 ||
+        ZK_VerifierStateFingerprint(ctx, fingerprint, sizeof(fingerprint)) != 0 ||
+        ZK_ExportVerifyingKey(vk, sizeof(vk)) != 0) {
+        return 1;
+    }
+
+    /* The snapshot carries no secret: neither the issuer nor the control private key */
+    int no_secret = strstr(state, priv) == NULL && strstr(state, control_priv) == NULL;
+    char short_state[64];
+    int short_buffer = ZK_ExportVerifierState(ctx, control_priv, 200, short_state, sizeof(short_state));
+    int bad_key = ZK_ExportVerifierState(ctx, "zz", 200, state + STATE_LEN / 2, 64);
+    printf("  control: snapshot %zu hex, no secret %d, short buffer %d, bad control key %d\n", strlen(state),
+           no_secret, short_buffer, bad_key);
+    for (int i = 0; i < CASES; i++) {
+        printf("    case %d: %s\n", i, expected[i]);
+    }
+    ZK_ContextFree(ctx);
+    if (!no_secret || short_buffer != -5 || bad_key != -1) {
+        return 1;
+    }
+
+    FILE* corpus;
+    snprintf(path, sizeof(path), "%s/corpus", dir);
+    if ((corpus = fopen(path, "w")) == NULL) {
+        return 1;
+    }
+    for (int i = 0; i < CASES; i++) {
+        fprintf(corpus, "%s %s %s\n", vcs[i], presentations[i], expected[i]);
+    }
+    fclose(corpus);
+    return !write_file(dir, "state", state) || !write_file(dir, "older", older) ||
+           !write_file(dir, "fingerprint", fingerprint) || !write_file(dir, "vk", vk) ||
+           !write_file(dir, "control.pub", control_pub) || !write_file(dir, "issuer.pub", pub);
+}
+
+static int load(const char* dir) {
+    static char line[65536];
+    snprintf(path, sizeof(path), "%s/corpus", dir);
+    FILE* corpus = fopen(path, "r");
+    if (corpus == NULL || !read_file(dir, "state", state, sizeof(state)) ||
+        !read_file(dir, "older", older, sizeof(older)) ||
+        !read_file(dir, "fingerprint", fingerprint, sizeof(fingerprint)) || !read_file(dir, "vk", vk, sizeof(vk)) ||
+        !read_file(dir, "control.pub", control_pub, sizeof(control_pub)) ||
+        !read_file(dir, "issuer.pub", pub, sizeof(pub))) {
+        return 0;
+    }
+    for (int i = 0; i < CASES && fgets(line, sizeof(line), corpus) != NULL; i++) {
+        line[strcspn(line, "\n")] = '\0';
+        char* vc = strtok(line, " ");
+        char* presentation = strtok(NULL, " ");
+        char* rest = strtok(NULL, "");
+        if (vc == NULL || presentation == NULL || rest == NULL) {
+            return 0;
+        }
+        strcpy(vcs[i], vc);
+        strcpy(presentations[i], presentation);
+        strcpy(expected[i], rest);
+    }
+    fclose(corpus);
+    return 1;
+}
+
+static int same_results(void) {
+    run_corpus(results);
+    int same = 1;
+    for (int i = 0; i < CASES; i++) {
+        same &= strcmp(results[i], expected[i]) == 0;
+    }
+    return same;
+}
+
+/* A fresh worker that only shares the verifying key */
+static int worker(const char* dir) {
+    Context* ctx = NULL;
+    char own[65];
+    if (!load(dir) || ZK_ImportVerifyingKey(vk) != 0 || ZK_ContextCreate(&ctx) != 0 ||
+        ZK_VerifierStateFingerprint(ctx, own, sizeof(own)) != 0) {
+        return 1;
+    }
+    int drifted = strcmp(own, fingerprint) != 0 && !same_results();
+
+    /* Refused before anything applies */
+    if (ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0) {
+        return 1;
+    }
+    int foreign = ZK_ImportVerifierState(ctx, state, other_pub);
+    strcpy(damaged, state);
+    size_t last = strlen(damaged) - 1;
+    damaged[last] = damaged[last] == '0' ? '1' : '0';
+    int tampered = ZK_ImportVerifierState(ctx, damaged, control_pub);
+    strcpy(damaged, state);
+    memcpy(damaged + 2 * VERSION_OFFSET, "02", 2);
+    int newer = ZK_ImportVerifierState(ctx, damaged, control_pub);
+    int garbage = ZK_ImportVerifierState(ctx, "5a4b5653", control_pub);
+    int null_state = ZK_ImportVerifierState(ctx, NULL, control_pub);
+    int unchanged = ZK_VerifierStateFingerprint(ctx, own, sizeof(own)) == 0 && strcmp(own, fingerprint) != 0;
+    printf("  worker: drifted before import %d; other control key %d, tampered %d, newer version %d, "
+           "not a snapshot %d, NULL %d, still unapplied %d\n",
+           drifted, foreign, tampered, newer, garbage, null_state, unchanged);
+    if (!drifted || foreign != ZK_ERR_AUTH || tampered != ZK_ERR_AUTH || newer != ZK_ERR_UNSUPPORTED_VERSION ||
+        garbage != ZK_ERR_CORRUPT || null_state != -1 || !unchanged) {
+        return 1;
+    }
+
+    /* Applied: identical fingerprint and results; no rolling back */
+    int imported = ZK_ImportVerifierState(ctx, state, control_pub);
+    int matched = ZK_VerifierStateFingerprint(ctx, own, sizeof(own)) == 0 && strcmp(own, fingerprint) == 0;
+    int same = same_results();
+    int rollback = ZK_ImportVerifierState(ctx, older, control_pub);
+    int again = ZK_ImportVerifierState(ctx, state, control_pub);
+    printf("  worker: imported %d, fingerprint matches %d, corpus results identical %d; older snapshot %d, "
+           "same again %d\n",
+           imported, matched, same, rollback, again);
+    for (int i = 0; !same && i < CASES; i++) {
+        printf("    case %d: %s, control %s\n", i, results[i], expected[i]);
+    }
+    ZK_ContextFree(ctx);
+    return imported != 0 || !matched || !same || rollback != ZK_ERR_EXPIRED || again != ZK_ERR_EXPIRED;
+}
+
+#define RACERS 8
+
+static void* race(void* arg) {
+    Context* ctx = arg;
+    return (void*)(intptr_t)ZK_ImportVerifierState(ctx, state, control_pub);
+}
+
+/* Workers importing the same snapshot at once: one applies it */
+static int racers(const char* dir) {
+    Context* ctxs[RACERS];
+    pthread_t threads[RACERS];
+    if (!load(dir) || ZK_ImportVerifyingKey(vk) != 0) {
+        return 1;
+    }
+    for (int i = 0; i < RACERS; i++) {
+        if (ZK_ContextCreate(&ctxs[i]) != 0) {
+            return 1;
+        }
+    }
+    for (int i = 0; i < RACERS; i++) {
+        pthread_create(&threads[i], NULL, race, ctxs[i]);
+    }
+    int applied = 0, refused = 0;
+    for (int i = 0; i < RACERS; i++) {
+        void* result;
+        pthread_join(threads[i], &result);
+        applied += (intptr_t)result == 0;
+        refused += (intptr_t)result == ZK_ERR_EXPIRED;
+        ZK_ContextFree(ctxs[i]);
+    }
+    printf("  %d concurrent imports of one snapshot: %d applied, %d refused\n", RACERS, applied, refused);
+    return applied != 1 || refused != RACERS - 1;
+}
+
+/* A worker that never loaded the verifying key */
+static int stranger(const char* dir) {
+    Context* ctx = NULL;
+    if (!load(dir) || ZK_ContextCreate(&ctx) != 0) {
+        return 1;
+    }
+    int mismatch = ZK_ImportVerifierState(ctx, state, control_pub);
+    printf("  worker without the verifying key: %d\n", mismatch);
+    ZK_ContextFree(ctx);
+    return mismatch != ZK_ERR_KEY_MISMATCH;
+}
+
+int main(int argc, char** argv) {
+    if (argc < 3) {
+        return 1;
+    }
+    if (strcmp(argv[1], "control") == 0) {
+        return control(argv[2]);
+    }
+    if (strcmp(argv[1], "worker") == 0) {
+        return worker(argv[2]);
+    }
+    if (strcmp(argv[1], "racers") == 0) {
+        return racers(argv[2]);
+    }
+    return stranger(argv[2]);
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+for role in control worker racers stranger; do
+    "$WORK_DIR/check" "$role" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+done
+echo "  ok"
+
+echo "✓ Workers that import the control snapshot verify exactly as the control process"
@@ -0,0 +1,194 @@
+#!/bin/bash
+#
+# Build the library for the host and check snarkjs JSON. A fixed proof (the
+# vector of check-solidity.sh) must export to the proof.json and public
+# signals snarkjs would hold for it, real part of each Fq2 coordinate first,
+# and import back to the same proof. Swapping the halves of a G2 coordinate
+# leaves a point off the curve and is refused, as are a coordinate with a
+# leading zero or equal to the modulus, and a proof without the issuer's
+# signature over its anchor. A generated proof exported and imported again
+# is the same hex proof, signature included, and still verifies, and the
+# verifying key exports with one IC point per public signal plus one.
+# Import answers size queries and refuses a short buffer, and the verifying
+# key export sets and clears the last error message.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_ConvertProofEncoding(const char*, size_t, int, char*, size_t);
+int ZK_ExportProofSnarkjs(const char*, const char*, uint64_t, char*, size_t, size_t*);
+int ZK_ImportProofSnarkjs(const char*, size_t, char*, size_t, size_t*);
+int ZK_ExportVerifyingKeySnarkjs(char*, size_t, size_t*);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_STATUS_SIZE_QUERY 1
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_NON_CANONICAL -31
+#define ZK_ERR_INVALID_POINT -32
+#define ZK_PROOF_ENCODING_COMPRESSED 0
+
+static const char VECTOR_ISSUER[] = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+/* check-solidity.sh's vector: A the G1 generator, B the G2 generator, C = (1, q - 2), uncompressed */
+static const char VECTOR_PROOF[] =
+    "5a4b5641"
+    "05f6596600000000"
+    "0700000000000000000000000000000000000000000000000000000000000000"
+    "323cb6ca5e6977bb44245e167c9ff48efea77e41cdc7f5c12ca7bdabe39f5bf8"
+    "867d263e326b85c81f11a8352d230ce19c798867f3bfce2b6330979fc815eb0d"
+    "5a5082"
+    "0100000000000000000000000000000000000000000000000000000000000000"
+    "0200000000000000000000000000000000000000000000000000000000000000"
+    "edf692d95cbdde46ddda5ef7d422436779445c5e66006a42761e1f12efde0018"
+    "c212f3aeb785e49712e7a9353349aaf1255dfb31b7bf60723a480d9293938e19"
+    "aa7dfa6601cce64c7bd3430c69e7d1e38f40cb8d8071ab4aeb6d8cdba55ec812"
+    "5b9722d1dcdaac55f38eb37033314bbc95330c69ad999eec75f05f58d0890609"
+    "0100000000000000000000000000000000000000000000000000000000000000"
+    "45fd7cd8168c203c8dca7168916a81975d588181b64550b829a031e1724e64b0";
+
+#define X_C0 "\"10857046999023057135944570762232829481370756359578518086990519993285655852781\""
+#define X_C1 "\"11559732032986387107991004021392285783925812861821192530917403151452391805634\""
+#define Y_C0 "\"8495653923123431417604973247489272438418190587263600148770280649306958101930\""
+#define Y_C1 "\"4082367875863433681332203403145435568316851327593401208105741076214120093531\""
+#define Q_MINUS_2 "\"21888242871839275222246405745257275088696311157297823662689037894645226208581\""
+#define Q "\"21888242871839275222246405745257275088696311157297823662689037894645226208583\""
+#define SIGNALS \
+    "\"publicSignals\":[\"18072499938883985022344725011085902984906934922162158043917708835528679489054\"," \
+    "\"42\",\"1717171717\",\"7\"]"
+#define SIGNATURE \
+    "\"zkid:anchorSignature\":\"323cb6ca5e6977bb44245e167c9ff48efea77e41cdc7f5c12ca7bdabe39f5bf8" \
+    "867d263e326b85c81f11a8352d230ce19c798867f3bfce2b6330979fc815eb0d\""
+
+/* The vector as snarkjs JSON, keys sorted as written */
+static const char VECTOR_JSON[] =
+    "{\"proof\":{\"curve\":\"bn128\",\"pi_a\":[\"1\",\"2\",\"1\"],"
+    "\"pi_b\":[[" X_C0 "," X_C1 "],[" Y_C0 "," Y_C1 "],[\"1\",\"0\"]],"
+    "\"pi_c\":[\"1\"," Q_MINUS_2 ",\"1\"],\"protocol\":\"groth16\"}," SIGNALS "," SIGNATURE ",\"zkid:format\":2}";
+
+/* B's coordinates with c1 first, as in calldata */
+static const char SWAPPED_JSON[] =
+    "{\"proof\":{\"curve\":\"bn128\",\"pi_a\":[\"1\",\"2\",\"1\"],"
+    "\"pi_b\":[[" X_C1 "," X_C0 "],[" Y_C1 "," Y_C0 "],[\"1\",\"0\"]],"
+    "\"pi_c\":[\"1\"," Q_MINUS_2 ",\"1\"],\"protocol\":\"groth16\"}," SIGNALS "," SIGNATURE ",\"zkid:format\":2}";
+
+static const char LEADING_ZERO_JSON[] =
+    "{\"proof\":{\"curve\":\"bn128\",\"pi_a\":[\"01\",\"2\",\"1\"],"
+    "\"pi_b\":[[" X_C0 "," X_C1 "],[" Y_C0 "," Y_C1 "],[\"1\",\"0\"]],"
+    "\"pi_c\":[\"1\"," Q_MINUS_2 ",\"1\"],\"protocol\":\"groth16\"}," SIGNALS "," SIGNATURE ",\"zkid:format\":2}";
+
+static const char MODULUS_JSON[] =
+    "{\"proof\":{\"curve\":\"bn128\",\"pi_a\":[\"1\",\"2\",\"1\"],"
+    "\"pi_b\":[[" X_C0 "," X_C1 "],[" Y_C0 "," Y_C1 "],[\"1\",\"0\"]],"
+    "\"pi_c\":[" Q "," Q_MINUS_2 ",\"1\"],\"protocol\":\"groth16\"}," SIGNALS "," SIGNATURE ",\"zkid:format\":2}";
+
+/* The vector without the issuer's signature over its anchor */
+static const char UNSIGNED_JSON[] =
+    "{\"proof\":{\"curve\":\"bn128\",\"pi_a\":[\"1\",\"2\",\"1\"],"
+    "\"pi_b\":[[" X_C0 "," X_C1 "],[" Y_C0 "," Y_C1 "],[\"1\",\"0\"]],"
+    "\"pi_c\":[\"1\"," Q_MINUS_2 ",\"1\"],\"protocol\":\"groth16\"}," SIGNALS ",\"zkid:format\":2}";
+
+static int import(const char* json, char* out, size_t out_size) {
+    return ZK_ImportProofSnarkjs(json, strlen(json), out, out_size, NULL);
+}
+
+int main(void) {
+    static char pub[65], priv[65], sig[129], proof[1024], compressed[1024], imported[1024], json[4096], vk[16384];
+    size_t required = 0, vk_required = 0;
+
+    /* Known vector */
+    int query = ZK_ExportProofSnarkjs(VECTOR_PROOF, VECTOR_ISSUER, 42, NULL, 0, &required);
+    int exported = ZK_ExportProofSnarkjs(VECTOR_PROOF, VECTOR_ISSUER, 42, json, sizeof(json), NULL);
+    int matches = strcmp(json, VECTOR_JSON) == 0;
+    if (ZK_ConvertProofEncoding(VECTOR_PROOF, strlen(VECTOR_PROOF), ZK_PROOF_ENCODING_COMPRESSED, compressed,
+                                sizeof(compressed)) != 0) {
+        return 1;
+    }
+    int back = import(VECTOR_JSON, imported, sizeof(imported));
+    int same = back == 0 && strcmp(imported, compressed) == 0;
+    printf("  vector: %d, size query %d (%zu), matches snarkjs %d; imported %d, same proof %d\n", exported, query,
+           required, matches, back, same);
+    size_t import_required = 0;
+    int import_query = ZK_ImportProofSnarkjs(VECTOR_JSON, strlen(VECTOR_JSON), NULL, 0, &import_required);
+    int import_short = import(VECTOR_JSON, imported, strlen(compressed));
+    printf("  import size query %d (%zu), short buffer %d\n", import_query, import_required, import_short);
+    int vector_ok = exported == 0 && query == ZK_STATUS_SIZE_QUERY && required == strlen(VECTOR_JSON) + 1 &&
+                    matches && same && import_query == ZK_STATUS_SIZE_QUERY &&
+                    import_required == strlen(compressed) + 1 && import_short == ZK_ERR_BUFFER_TOO_SMALL;
+
+    /* Refusals */
+    int swapped = import(SWAPPED_JSON, imported, sizeof(imported));
+    int leading_zero = import(LEADING_ZERO_JSON, imported, sizeof(imported));
+    int modulus = import(MODULUS_JSON, imported, sizeof(imported));
+    int not_json = import("{\"proof\":", imported, sizeof(imported));
+    int no_signals = import("{\"proof\":{}}", imported, sizeof(imported));
+    int no_signature = import(UNSIGNED_JSON, imported, sizeof(imported));
+    printf("  swapped G2 halves %d, leading zero %d, modulus %d, not JSON %d, no signals %d, no signature %d\n",
+           swapped, leading_zero, modulus, not_json, no_signals, no_signature);
+    int refused_ok = swapped == ZK_ERR_INVALID_POINT && leading_zero == ZK_ERR_NON_CANONICAL &&
+                     modulus == ZK_ERR_NON_CANONICAL && not_json == -1 && no_signals == -1 && no_signature == -1;
+
+    /* Generated proof */
+    static char no_key_error[256], error_after[256];
+    int no_key = ZK_ExportVerifyingKeySnarkjs(vk, sizeof(vk), NULL);
+    ZK_GetLastError(no_key_error, sizeof(no_key_error));
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42, proof, sizeof(proof), NULL) != 0 ||
+        ZK_ConvertProofEncoding(proof, strlen(proof), ZK_PROOF_ENCODING_COMPRESSED, compressed,
+                                sizeof(compressed)) != 0 ||
+        ZK_ExportProofSnarkjs(proof, pub, 42, json, sizeof(json), NULL) != 0) {
+        return 1;
+    }
+    int round_trip = import(json, imported, sizeof(imported)) == 0 && strcmp(imported, compressed) == 0;
+    int verifies = ZK_VerifyVCProof(imported, pub, 150, 42);
+    int other_nonce = ZK_VerifyVCProof(imported, pub, 150, 43);
+    printf("  proof: round trip %d, verifies %d, under another nonce %d\n", round_trip, verifies, other_nonce);
+    int proof_ok = round_trip && verifies == 1 && other_nonce == 0;
+
+    /* Verifying key */
+    int vk_query = ZK_ExportVerifyingKeySnarkjs(NULL, 0, &vk_required);
+    int vk_exported = ZK_ExportVerifyingKeySnarkjs(vk, sizeof(vk), NULL);
+    ZK_GetLastError(error_after, sizeof(error_after));
+    int shape = strstr(vk, "\"curve\":\"bn128\"") && strstr(vk, "\"nPublic\":4") &&
+                strstr(vk, "\"protocol\":\"groth16\"") && strstr(vk, "\"vk_alpha_1\":[") &&
+                strstr(vk, "\"vk_delta_2\":[[") && !strstr(vk, "vk_alphabeta_12");
+    int ic_points = 0;
+    for (const char* p = strstr(vk, "\"IC\":[") ? strstr(vk, "\"IC\":[") + 6 : NULL; p && *p == '['; ic_points++) {
+        p = strchr(p, ']') + 1;
+        p += *p == ',';
+    }
+    printf("  verifying key: before init %d, %d, %zu bytes (size query %d, %zu), shape %d, %d IC points\n", no_key,
+           vk_exported, strlen(vk), vk_query, vk_required, shape, ic_points);
+    printf("  last error: before init \"%s\", after export \"%s\"\n", no_key_error, error_after);
+    int vk_ok = no_key == ZK_ERR_NOT_INITIALIZED && vk_exported == 0 && vk_query == ZK_STATUS_SIZE_QUERY &&
+                vk_required == strlen(vk) + 1 && shape && ic_points == 5 && no_key_error[0] && !error_after[0];
+
+    return !vector_ok || !refused_ok || !proof_ok || !vk_ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ snarkjs JSON matches the fixed vector and imported proofs verify as exported"
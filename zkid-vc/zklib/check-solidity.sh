@@ -0,0 +1,151 @@
+#!/bin/bash
+#
+# Build the library for the host and check the EVM side of VC proofs. A
+# fixed proof (A the G1 generator, B the G2 generator, C the negated G1
+# generator, in uncompressed points) must give the verifyProof calldata
+# snarkjs writes for the same points and public signals, byte for byte, so
+# a swap of the G2 coordinate halves or of the public signals fails here;
+# under an issuer key that did not sign its anchor there is none. A
+# generated proof gives the same calldata compressed or uncompressed and
+# under a hex or did:key issuer, and the exported verifier contract inlines
+# the verifying key with one IC point per public signal plus one.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_ConvertProofEncoding(const char*, size_t, int, char*, size_t);
+int ZK_PublicKeyToDidKey(const char*, char*, size_t);
+int ZK_ExportSolidityVerifier(char*, size_t, size_t*);
+int ZK_ExportProofCalldata(const char*, const char*, uint64_t, char*, size_t, size_t*);
+
+#define ZK_STATUS_SIZE_QUERY 1
+#define ZK_ERR_NOT_INITIALIZED -2
+#define ZK_ERR_BAD_SIGNATURE -3
+#define ZK_ERR_BAD_HEX -6
+#define ZK_PROOF_ENCODING_UNCOMPRESSED 1
+
+/* Issuer key of RFC 8032 test 1, whose secret signs the anchor below */
+static const char VECTOR_ISSUER[] = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+/* Issuer key of the first did:key specification vector, which did not */
+static const char OTHER_ISSUER[] = "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29";
+
+static const char VECTOR_PROOF[] =
+    /* "ZKVA", current_time 1717171717, anchor 7 */
+    "5a4b5641"
+    "05f6596600000000"
+    "0700000000000000000000000000000000000000000000000000000000000000"
+    /* RFC 8032 test 1 key's signature over the anchor */
+    "323cb6ca5e6977bb44245e167c9ff48efea77e41cdc7f5c12ca7bdabe39f5bf8"
+    "867d263e326b85c81f11a8352d230ce19c798867f3bfce2b6330979fc815eb0d"
+    /* "ZP", format 2 | uncompressed */
+    "5a5082"
+    /* A */
+    "0100000000000000000000000000000000000000000000000000000000000000"
+    "0200000000000000000000000000000000000000000000000000000000000000"
+    /* B: x.c0, x.c1, y.c0, y.c1 */
+    "edf692d95cbdde46ddda5ef7d422436779445c5e66006a42761e1f12efde0018"
+    "c212f3aeb785e49712e7a9353349aaf1255dfb31b7bf60723a480d9293938e19"
+    "aa7dfa6601cce64c7bd3430c69e7d1e38f40cb8d8071ab4aeb6d8cdba55ec812"
+    "5b9722d1dcdaac55f38eb37033314bbc95330c69ad999eec75f05f58d0890609"
+    /* C: y = q - 2, the larger root, flagged */
+    "0100000000000000000000000000000000000000000000000000000000000000"
+    "45fd7cd8168c203c8dca7168916a81975d588181b64550b829a031e1724e64b0";
+
+/* snarkjs soliditycalldata of the proof with nonce 42 */
+static const char VECTOR_CALLDATA[] =
+    /* A */
+    "[\"0x0000000000000000000000000000000000000000000000000000000000000001\", "
+    "\"0x0000000000000000000000000000000000000000000000000000000000000002\"],"
+    /* B: x.c1, x.c0, then y.c1, y.c0 */
+    "[[\"0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\", "
+    "\"0x1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\"],"
+    "[\"0x090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\", "
+    "\"0x12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa\"]],"
+    /* C */
+    "[\"0x0000000000000000000000000000000000000000000000000000000000000001\", "
+    "\"0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45\"],"
+    /* issuer key field (SHA-256 mod r), nonce, current_time, anchor */
+    "[\"0x27f4ac2750e9a6db8154ed46c7697203af36b62ae7cc19ae95fc73e60f31fe1e\","
+    "\"0x000000000000000000000000000000000000000000000000000000000000002a\","
+    "\"0x000000000000000000000000000000000000000000000000000000006659f605\","
+    "\"0x0000000000000000000000000000000000000000000000000000000000000007\"]";
+
+static char pub[65], priv[65], did[64];
+
+int main(void) {
+    static char sig[129], proof[1024], uncompressed[2048], calldata[2048], again[2048], source[32768];
+    size_t required = 0, source_required = 0;
+
+    /* Known vector */
+    int query = ZK_ExportProofCalldata(VECTOR_PROOF, VECTOR_ISSUER, 42, NULL, 0, &required);
+    int vector = ZK_ExportProofCalldata(VECTOR_PROOF, VECTOR_ISSUER, 42, calldata, sizeof(calldata), NULL);
+    int matches = strcmp(calldata, VECTOR_CALLDATA) == 0;
+    int bad_issuer = ZK_ExportProofCalldata(VECTOR_PROOF, "not hex", 42, calldata, sizeof(calldata), NULL);
+    int bad_proof = ZK_ExportProofCalldata("5a4b5641", VECTOR_ISSUER, 42, calldata, sizeof(calldata), NULL);
+    int unsigned_anchor = ZK_ExportProofCalldata(VECTOR_PROOF, OTHER_ISSUER, 42, calldata, sizeof(calldata), NULL);
+    printf("  vector: %d, size query %d (%zu), matches snarkjs %d; bad issuer %d, bad proof %d, other issuer %d\n",
+           vector, query, required, matches, bad_issuer, bad_proof, unsigned_anchor);
+    int vector_ok = vector == 0 && query == ZK_STATUS_SIZE_QUERY && required == strlen(VECTOR_CALLDATA) + 1 &&
+                    matches && bad_issuer == ZK_ERR_BAD_HEX && bad_proof < 0 &&
+                    unsigned_anchor == ZK_ERR_BAD_SIGNATURE;
+
+    /* Generated proof */
+    int no_key = ZK_ExportSolidityVerifier(source, sizeof(source), NULL);
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_PublicKeyToDidKey(pub, did, sizeof(did)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0 ||
+        ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, 150, 42, proof, sizeof(proof), NULL) != 0 ||
+        ZK_ConvertProofEncoding(proof, strlen(proof), ZK_PROOF_ENCODING_UNCOMPRESSED, uncompressed,
+                                sizeof(uncompressed)) != 0 ||
+        ZK_ExportProofCalldata(proof, pub, 42, calldata, sizeof(calldata), NULL) != 0) {
+        return 1;
+    }
+    int same_uncompressed = ZK_ExportProofCalldata(uncompressed, pub, 42, again, sizeof(again), NULL) == 0 &&
+                            strcmp(calldata, again) == 0;
+    int same_did = ZK_ExportProofCalldata(proof, did, 42, again, sizeof(again), NULL) == 0 &&
+                   strcmp(calldata, again) == 0;
+    int signals = strstr(calldata, ",\"0x000000000000000000000000000000000000000000000000000000000000002a\","
+                                   "\"0x0000000000000000000000000000000000000000000000000000000000000096\",") != NULL;
+    printf("  proof: %zu characters, same uncompressed %d, same under did:key %d, nonce and time signals %d\n",
+           strlen(calldata), same_uncompressed, same_did, signals);
+    int proof_ok = strlen(calldata) == strlen(VECTOR_CALLDATA) && same_uncompressed && same_did && signals;
+
+    /* Contract */
+    int source_query = ZK_ExportSolidityVerifier(NULL, 0, &source_required);
+    int exported = ZK_ExportSolidityVerifier(source, sizeof(source), NULL);
+    int shape = strstr(source, "pragma solidity") && strstr(source, "contract Groth16Verifier") &&
+                strstr(source, "uint256[4] calldata _pubSignals") && strstr(source, "uint256 constant deltay2 = ") &&
+                strstr(source, "uint256 constant IC4x = ") && !strstr(source, "IC5x") &&
+                strstr(source, "staticcall(sub(gas(), 2000), 8, input, 768, out, 0x20)");
+    printf("  contract: before init %d, %d, %zu bytes (size query %d, %zu), shape %d\n", no_key, exported,
+           strlen(source), source_query, source_required, shape);
+    int contract_ok = no_key == ZK_ERR_NOT_INITIALIZED && exported == 0 && source_query == ZK_STATUS_SIZE_QUERY &&
+                      source_required == strlen(source) + 1 && shape;
+
+    return !vector_ok || !proof_ok || !contract_ok;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Calldata matches the snarkjs encoding and the verifier contract inlines the verifying key"
@@ -0,0 +1,192 @@
+#!/bin/bash
+#
+# Build the library for the host and check the stateless verification
+# profile: a presentation carrying its challenge context, credential and
+# issuer status attestation verifies from the verifier secret and static
+# policy alone. It replays within max_age and stops at max_age; the
+# report's weakened bits must be accepted by the policy; a stale or
+# missing attestation fails the revocation check, and a revocation only
+# shows once the holder's attestation ages out.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    const char* issuer_public_key;
+    const char* second_issuer_public_key;
+    const char* expected_audience;
+    uint64_t current_time, max_age, max_status_age;
+    uint32_t accept_weakened;
+} ZkStatelessPolicy;
+
+typedef struct {
+    int signature, proof, time_policy, revocation, audience, freshness;
+    uint32_t weakened;
+} ZkStatelessReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_DeriveChallenge(const char*, const char*, size_t, uint64_t*);
+int ZK_IssueStatusAttestation(const char*, const char*, uint64_t, char*, size_t);
+int ZK_VerifyPresentationStateless(const char*, size_t, const char*, const ZkStatelessPolicy*, ZkStatelessReport*);
+
+#define ZK_CHECK_SKIPPED 2
+#define ZK_WEAKENED_REPLAY 1u
+#define ZK_WEAKENED_REVOCATION_STALE 2u
+#define ZK_WEAKENED_REVOCATION_UNCHECKED 4u
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define ISSUED_AT (NOW - 30)
+#define MAX_AGE 300
+
+static const char* secret = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+static const char* other_secret = "ff0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096], status[1024], context_hex[128];
+static char presentation[32768], without_status[32768];
+static ZkStatelessPolicy policy;
+
+/* The credential_id ZK_InspectArtifact reports for `vc` */
+static int credential_id(const char* vc, char* out) {
+    static char json[16384];
+    const char* at = ZK_InspectArtifact(vc, strlen(vc), json, sizeof(json)) == 0
+                         ? strstr(json, "\"credential_id\":\"")
+                         : NULL;
+    if (at == NULL) {
+        return 0;
+    }
+    memcpy(out, at + strlen("\"credential_id\":\""), 64);
+    out[64] = '\0';
+    return 1;
+}
+
+/* Verify under `policy` with `now`; 1 if the result and report match */
+static int expect(const char* name, const char* p, const char* s, uint64_t now, int result, ZkStatelessReport want) {
+    ZkStatelessReport r;
+    memset(&r, 0x55, sizeof(r));
+    policy.current_time = now;
+    int got = ZK_VerifyPresentationStateless(p, strlen(p), s, &policy, &r);
+    printf("  %s: %d (signature %d, proof %d, time %d, revocation %d, audience %d, freshness %d, weakened %u)\n",
+           name, got, r.signature, r.proof, r.time_policy, r.revocation, r.audience, r.freshness, r.weakened);
+    return got == result && memcmp(&r, &want, sizeof(r)) == 0;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    char vc_id[65];
+    /* Challenge context: issued_at u64 LE | request */
+    char context[8 + 5];
+    for (int i = 0; i < 8; i++) {
+        context[i] = (char)((ISSUED_AT >> (8 * i)) & 0xff);
+    }
+    memcpy(context + 8, "login", 5);
+    for (size_t i = 0; i < sizeof(context); i++) {
+        snprintf(context_hex + 2 * i, 3, "%02x", (unsigned char)context[i]);
+    }
+    uint64_t challenge = 0;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 || !credential_id(blob, vc_id) ||
+        ZK_DeriveChallenge(secret, context, sizeof(context), &challenge) != 0 ||
+        ZK_IssueStatusAttestation(priv, vc_id, NOW - 60, status, sizeof(status)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, challenge, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+    const char* meta_keys[] = {"audience", "zkid.challenge", "zkid.vc", "zkid.status"};
+    const char* meta_values[] = {"gate-1", context_hex, blob, status};
+    if (ZK_EncodePresentation(proof, pub, challenge, meta_keys, meta_values, 4, presentation,
+                              sizeof(presentation)) != 0 ||
+        ZK_EncodePresentation(proof, pub, challenge, meta_keys, meta_values, 3, without_status,
+                              sizeof(without_status)) != 0) {
+        return 1;
+    }
+    const ZkStatelessPolicy base = {pub, NULL, "gate-1", NOW, MAX_AGE, 3600,
+                                    ZK_WEAKENED_REPLAY | ZK_WEAKENED_REVOCATION_STALE};
+    const uint32_t weakened = ZK_WEAKENED_REPLAY | ZK_WEAKENED_REVOCATION_STALE;
+    const ZkStatelessReport all = {1, 1, 1, 1, 1, 1, weakened};
+    policy = base;
+
+    /* Replay inside the max-age window is accepted, and stops at its end */
+    int ok = expect("first use", presentation, secret, NOW, 1, all) &&
+             expect("replayed", presentation, secret, NOW + 60, 1, all) &&
+             expect("replayed at max age", presentation, secret, ISSUED_AT + MAX_AGE, 1, all) &&
+             expect("replayed after max age", presentation, secret, ISSUED_AT + MAX_AGE + 1, 0,
+                    (ZkStatelessReport){1, 1, 1, 1, 1, 0, weakened}) &&
+             expect("other verifier secret", presentation, other_secret, NOW, 0,
+                    (ZkStatelessReport){1, 1, 1, 1, 1, 0, weakened});
+    if (!ok) {
+        return 1;
+    }
+
+    /* Weakened guarantees must be accepted explicitly */
+    policy.accept_weakened = ZK_WEAKENED_REVOCATION_STALE;
+    ok = expect("replay not accepted", presentation, secret, NOW, 0, all);
+    policy.accept_weakened = weakened;
+    policy.max_status_age = 0;
+    ok &= expect("revocation unchecked, not accepted", presentation, secret, NOW, 0,
+                 (ZkStatelessReport){1, 1, 1, ZK_CHECK_SKIPPED, 1, 1,
+                                     ZK_WEAKENED_REPLAY | ZK_WEAKENED_REVOCATION_UNCHECKED});
+    policy.accept_weakened = ZK_WEAKENED_REPLAY | ZK_WEAKENED_REVOCATION_UNCHECKED;
+    ok &= expect("revocation unchecked, accepted", without_status, secret, NOW, 1,
+                 (ZkStatelessReport){1, 1, 1, ZK_CHECK_SKIPPED, 1, 1,
+                                     ZK_WEAKENED_REPLAY | ZK_WEAKENED_REVOCATION_UNCHECKED});
+    policy = base;
+    policy.max_status_age = 30;
+    ok &= expect("stale attestation", presentation, secret, NOW, 0, (ZkStatelessReport){1, 1, 1, 0, 1, 1, weakened});
+    policy = base;
+    ok &= expect("no attestation", without_status, secret, NOW, 0, (ZkStatelessReport){1, 1, 1, 0, 1, 1, weakened});
+    if (!ok) {
+        return 1;
+    }
+
+    /* A revocation stops new attestations; the holder's one lasts its age */
+    if (ZK_RevokeCredential(vc_id, strlen(vc_id), NOW) != 0) {
+        return 1;
+    }
+    char refused[1024];
+    int attest_revoked = ZK_IssueStatusAttestation(priv, vc_id, NOW + 10, refused, sizeof(refused));
+    printf("  attestation after revocation %d\n", attest_revoked);
+    ok = attest_revoked == 1 && expect("revoked, attestation still fresh", presentation, secret, NOW + 10, 1, all);
+    policy.max_status_age = 60;
+    ok &= expect("revoked, attestation aged out", presentation, secret, NOW + 10, 0,
+                 (ZkStatelessReport){1, 1, 1, 0, 1, 1, weakened});
+    policy = base;
+
+    ZkStatelessReport report;
+    int null_policy = ZK_VerifyPresentationStateless(presentation, strlen(presentation), secret, NULL, &report);
+    int empty_secret = ZK_VerifyPresentationStateless(presentation, strlen(presentation), "", &policy, &report);
+    int null_report = ZK_VerifyPresentationStateless(presentation, strlen(presentation), secret, &policy, NULL);
+    policy.issuer_public_key = "zz";
+    int bad_key = ZK_VerifyPresentationStateless(presentation, strlen(presentation), secret, &policy, &report);
+    printf("  NULL policy %d, empty secret %d, NULL report %d, bad issuer key %d\n", null_policy, empty_secret,
+           null_report, bad_key);
+    return !ok || null_policy != -1 || empty_secret != -1 || null_report != -1 || bad_key != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Stateless verification replays only within max_age and reports what it gave up"
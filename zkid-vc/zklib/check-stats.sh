@@ -0,0 +1,196 @@
+#!/bin/bash
+#
+# Build the library for the host and check issuer statistics: every
+# ZK_SignVC is counted under the signing key only, repeated holders count
+# once towards the distinct-holder estimate, the per-minute rate and its
+# peak follow the signatures, the issuance alarm fires with the issuer key
+# once the rate passes its limit and stops when removed, and resetting one
+# issuer leaves the others' counters alone. Every other signer counts too:
+# ZK_SignVCWithClaims, ZK_SignVCBlob, ZK_CoSignVC (under the co-signer),
+# ZK_ReissueVC, ZK_SignClaimGroup and ZK_SignVCJwt; check-threshold.sh
+# counts ZK_ThresholdAggregate under the group key.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    uint64_t credentials_signed;
+    uint64_t distinct_holders;
+    uint32_t signatures_last_minute;
+    uint32_t peak_per_minute;
+} ZkIssuerStats;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t, size_t*);
+int ZK_SignVCWithClaims(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                        const char* const*, size_t, const char*, char*, size_t, size_t*);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_CoSignVC(const char*, const char*, char*, size_t);
+int ZK_ReissueVC(const char*, const char* const*, const char* const*, size_t, uint64_t, uint64_t, const char*, char*,
+                 size_t, char*, size_t);
+int ZK_SignVCJwt(const char*, const char*, char*, size_t, size_t*);
+int ZK_EncodeCompositeVC(const char*, size_t, int64_t, int64_t, const char* const*, const char* const*, size_t,
+                         char*, size_t);
+int ZK_AddClaimGroup(const char*, const char*, size_t, const char*, const char* const*, const char* const*, size_t,
+                     char*, size_t);
+int ZK_SignClaimGroup(const char*, size_t, const char*, char*, size_t);
+int ZK_GetIssuerStats(const char*, ZkIssuerStats*);
+int ZK_ResetIssuerStats(const char*);
+int ZK_SetIssuanceAlarm(uint32_t, void (*)(const char*, uint32_t));
+
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char blob[8192], signed_blob[8192], out[8192], composite[8192];
+static int alarms;
+static uint32_t alarm_rate;
+static char alarm_key[65];
+
+static void alarm(const char* issuer, uint32_t rate) {
+    alarms++;
+    alarm_rate = rate;
+    snprintf(alarm_key, sizeof(alarm_key), "%s", issuer);
+}
+
+static int sign(const char* key, int holder) {
+    char id[32], signature[256];
+    snprintf(id, sizeof(id), "holder-%d", holder);
+    return ZK_SignVC(id, strlen(id), "issuer", 6, NOW - DAY, NOW + DAY, key, signature, sizeof(signature), NULL);
+}
+
+static ZkIssuerStats stats(const char* key) {
+    ZkIssuerStats s;
+    memset(&s, 0xff, sizeof(s));
+    if (ZK_GetIssuerStats(key, &s) != 0) {
+        memset(&s, 0xff, sizeof(s));
+    }
+    return s;
+}
+
+int main(void) {
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0) {
+        return 1;
+    }
+    ZkIssuerStats fresh = stats(pub);
+    if (fresh.credentials_signed != 0 || fresh.distinct_holders != 0 || fresh.signatures_last_minute != 0) {
+        return 1;
+    }
+
+    /* Ten holders, each signed twice, by the first issuer; one by the second */
+    for (int round = 0; round < 2; round++) {
+        for (int holder = 0; holder < 10; holder++) {
+            if (sign(priv, holder) != 0) {
+                return 1;
+            }
+        }
+    }
+    if (sign(priv2, 0) != 0) {
+        return 1;
+    }
+    ZkIssuerStats s = stats(pub), s2 = stats(pub2);
+    printf("  first issuer: %llu signed, ~%llu holders, %u last minute, peak %u; second: %llu signed\n",
+           (unsigned long long)s.credentials_signed, (unsigned long long)s.distinct_holders,
+           s.signatures_last_minute, s.peak_per_minute, (unsigned long long)s2.credentials_signed);
+    if (s.credentials_signed != 20 || s.distinct_holders < 7 || s.distinct_holders > 13 ||
+        s.signatures_last_minute != 20 || s.peak_per_minute != 20 || s2.credentials_signed != 1 ||
+        s2.distinct_holders != 1) {
+        return 1;
+    }
+
+    /* The alarm fires above 25 signatures a minute, with the issuer's key */
+    ZK_SetIssuanceAlarm(25, alarm);
+    for (int holder = 0; holder < 10; holder++) {
+        if (sign(priv, holder) != 0) {
+            return 1;
+        }
+    }
+    int fired = alarms;
+    ZK_SetIssuanceAlarm(0, NULL);
+    if (sign(priv, 0) != 0) {
+        return 1;
+    }
+    printf("  alarm: fired %d times, last rate %u, issuer %s; after removal %d\n", fired, alarm_rate,
+           strcmp(alarm_key, pub) == 0 ? "matches" : "differs", alarms);
+    if (fired != 5 || alarm_rate != 30 || strcmp(alarm_key, pub) != 0 || alarms != fired) {
+        return 1;
+    }
+
+    /* Reset one issuer, then all */
+    int reset = ZK_ResetIssuerStats(pub);
+    ZkIssuerStats after = stats(pub), kept = stats(pub2);
+    int reset_all = ZK_ResetIssuerStats(NULL);
+    ZkIssuerStats cleared = stats(pub2);
+    ZkIssuerStats unused;
+    int bad_key = ZK_GetIssuerStats("zz", &unused);
+    int null_out = ZK_GetIssuerStats(pub, NULL);
+    int bad_reset = ZK_ResetIssuerStats("zz");
+    printf("  reset: %d (first %llu, second %llu), all %d (second %llu); bad key %d, NULL out %d, bad reset %d\n",
+           reset, (unsigned long long)after.credentials_signed, (unsigned long long)kept.credentials_signed,
+           reset_all, (unsigned long long)cleared.credentials_signed, bad_key, null_out, bad_reset);
+    if (reset != 0 || after.credentials_signed != 0 || after.peak_per_minute != 0 || kept.credentials_signed != 1 ||
+        reset_all != 0 || cleared.credentials_signed != 0 || bad_key != -1 || null_out != -1 || bad_reset != -1) {
+        return 1;
+    }
+
+    /* Each of the other signers counts once under the key that signed */
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    const char* corrected[] = {"manager"};
+    char signature[256], entry[512];
+    uint64_t counts[6];
+    if (ZK_SignVCWithClaims("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, priv, signature,
+                            sizeof(signature), NULL) != 0) {
+        return 1;
+    }
+    counts[0] = stats(pub).credentials_signed;
+    if (ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, blob, sizeof(blob)) != 0 ||
+        ZK_SignVCBlob(blob, priv, signed_blob, sizeof(signed_blob)) != 0) {
+        return 1;
+    }
+    counts[1] = stats(pub).credentials_signed;
+    if (ZK_CoSignVC(signed_blob, priv2, out, sizeof(out)) != 0) {
+        return 1;
+    }
+    counts[2] = stats(pub2).credentials_signed;
+    if (ZK_ReissueVC(signed_blob, keys, corrected, 1, NOW, 0, priv, out, sizeof(out), entry, sizeof(entry)) != 0) {
+        return 1;
+    }
+    counts[3] = stats(pub).credentials_signed;
+    if (ZK_SignVCJwt(signed_blob, priv, out, sizeof(out), NULL) != 0) {
+        return 1;
+    }
+    counts[4] = stats(pub).credentials_signed;
+    if (ZK_EncodeCompositeVC("alice", 5, NOW - DAY, NOW + DAY, NULL, NULL, 0, composite, sizeof(composite)) != 0 ||
+        ZK_AddClaimGroup(composite, "hr", 2, pub, keys, values, 1, out, sizeof(out)) != 0 ||
+        ZK_SignClaimGroup(out, 0, priv, composite, sizeof(composite)) != 0) {
+        return 1;
+    }
+    counts[5] = stats(pub).credentials_signed;
+    printf("  signers: with claims %llu, blob %llu, co-signer %llu, reissue %llu, jwt %llu, claim group %llu\n",
+           (unsigned long long)counts[0], (unsigned long long)counts[1], (unsigned long long)counts[2],
+           (unsigned long long)counts[3], (unsigned long long)counts[4], (unsigned long long)counts[5]);
+    return counts[0] != 1 || counts[1] != 2 || counts[2] != 1 || counts[3] != 3 || counts[4] != 4 || counts[5] != 5;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Issuance through every signer is counted per issuer key and the alarm fires above its rate"
@@ -0,0 +1,207 @@
+#!/bin/bash
+#
+# Build the library for the host and check the storage backend: challenges
+# and revocations installed through ZK_SetStorageCallbacks land in the
+# host's table and are read back from it, a backend whose callbacks fail
+# turns every registry call into ZK_ERR_STORAGE, collection limits (which
+# need a prefix scan) report ZK_ERR_STORAGE on the callback backend, a
+# partial set of callbacks is refused, and NULL callbacks return to a fresh
+# in-memory backend.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef int (*get_cb)(void*, const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+typedef int (*put_cb)(void*, const uint8_t*, size_t, const uint8_t*, size_t);
+typedef int (*delete_cb)(void*, const uint8_t*, size_t);
+typedef struct {
+    uint64_t capacity;
+    int policy;
+} ZkCollectionLimit;
+
+int ZK_SetStorageCallbacks(get_cb, put_cb, delete_cb, void*);
+int ZK_IssueChallenge(uint64_t, uint64_t);
+int ZK_ConsumeChallenge(uint64_t, uint64_t);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_IsCredentialRevoked(const char*, size_t);
+int ZK_SetCollectionLimit(int, const ZkCollectionLimit*);
+
+#define ZK_ERR_STORAGE -10
+#define ZK_COLLECTION_CHALLENGES 1
+#define NOW 1700000000ULL
+#define SLOTS 16
+
+/* The host's table: a fixed array of key-value slots */
+typedef struct {
+    uint8_t key[128], value[128];
+    size_t key_len, value_len;
+    int used;
+} Slot;
+
+typedef struct {
+    Slot slots[SLOTS];
+    int gets, puts, deletes;
+} Table;
+
+static Slot* find(Table* t, const uint8_t* key, size_t key_len) {
+    for (int i = 0; i < SLOTS; i++) {
+        if (t->slots[i].used && t->slots[i].key_len == key_len && memcmp(t->slots[i].key, key, key_len) == 0) {
+            return &t->slots[i];
+        }
+    }
+    return NULL;
+}
+
+static int table_get(void* user_data, const uint8_t* key, size_t key_len, uint8_t* out, size_t cap, size_t* len) {
+    Table* t = user_data;
+    t->gets++;
+    Slot* slot = find(t, key, key_len);
+    if (slot == NULL) {
+        return 1;
+    }
+    *len = slot->value_len;
+    if (slot->value_len <= cap) {
+        memcpy(out, slot->value, slot->value_len);
+    }
+    return 0;
+}
+
+static int table_put(void* user_data, const uint8_t* key, size_t key_len, const uint8_t* value, size_t value_len) {
+    Table* t = user_data;
+    t->puts++;
+    Slot* slot = find(t, key, key_len);
+    for (int i = 0; slot == NULL && i < SLOTS; i++) {
+        if (!t->slots[i].used) {
+            slot = &t->slots[i];
+        }
+    }
+    if (slot == NULL || key_len > sizeof(slot->key) || value_len > sizeof(slot->value)) {
+        return -1;
+    }
+    memcpy(slot->key, key, key_len);
+    memcpy(slot->value, value, value_len);
+    slot->key_len = key_len;
+    slot->value_len = value_len;
+    slot->used = 1;
+    return 0;
+}
+
+static int table_delete(void* user_data, const uint8_t* key, size_t key_len) {
+    Table* t = user_data;
+    t->deletes++;
+    Slot* slot = find(t, key, key_len);
+    if (slot != NULL) {
+        slot->used = 0;
+    }
+    return 0;
+}
+
+static int used(const Table* t) {
+    int n = 0;
+    for (int i = 0; i < SLOTS; i++) {
+        n += t->slots[i].used;
+    }
+    return n;
+}
+
+static int failing_get(void* u, const uint8_t* k, size_t kl, uint8_t* o, size_t c, size_t* l) {
+    (void)u, (void)k, (void)kl, (void)o, (void)c, (void)l;
+    return -1;
+}
+
+static int failing_put(void* u, const uint8_t* k, size_t kl, const uint8_t* v, size_t vl) {
+    (void)u, (void)k, (void)kl, (void)v, (void)vl;
+    return -1;
+}
+
+static int failing_delete(void* u, const uint8_t* k, size_t kl) {
+    (void)u, (void)k, (void)kl;
+    return -1;
+}
+
+static Table table;
+
+int main(void) {
+    /* Some callbacks but not all */
+    int partial = ZK_SetStorageCallbacks(table_get, table_put, NULL, &table);
+    int only_get = ZK_SetStorageCallbacks(table_get, NULL, NULL, &table);
+    printf("  partial callbacks: %d, only get: %d\n", partial, only_get);
+    if (partial != -1 || only_get != -1) {
+        return 1;
+    }
+
+    /* The host table backs the challenge store and revocation registry */
+    if (ZK_SetStorageCallbacks(table_get, table_put, table_delete, &table) != 0) {
+        return 1;
+    }
+    int issued = ZK_IssueChallenge(7, NOW + 60);
+    int stored = used(&table);
+    int consumed = ZK_ConsumeChallenge(7, NOW);
+    int again = ZK_ConsumeChallenge(7, NOW);
+    int revoked = ZK_RevokeCredential("vc-1", 4, NOW);
+    int is_revoked = ZK_IsCredentialRevoked("vc-1", 4);
+    int other = ZK_IsCredentialRevoked("vc-2", 4);
+    printf("  host table: issue %d (%d stored), consume %d then %d, revoke %d, revoked %d, other %d\n", issued,
+           stored, consumed, again, revoked, is_revoked, other);
+    printf("  host table: %d gets, %d puts, %d deletes, %d entries\n", table.gets, table.puts, table.deletes,
+           used(&table));
+    if (issued != 0 || stored != 1 || consumed != 1 || again != 0 || revoked != 0 || is_revoked != 1 ||
+        other != 0 || table.puts != 2 || table.deletes != 1 || used(&table) != 1) {
+        return 1;
+    }
+
+    /* A limit needs a prefix scan, which the callbacks cannot serve */
+    ZkCollectionLimit limit = {4, 0}, unlimited = {0, 0};
+    if (ZK_SetCollectionLimit(ZK_COLLECTION_CHALLENGES, &limit) != 0) {
+        return 1;
+    }
+    int limited = ZK_IssueChallenge(8, NOW + 60);
+    ZK_SetCollectionLimit(ZK_COLLECTION_CHALLENGES, &unlimited);
+    printf("  limited challenge store on callbacks: %d\n", limited);
+    if (limited != ZK_ERR_STORAGE) {
+        return 1;
+    }
+
+    /* A backend that fails every call */
+    if (ZK_SetStorageCallbacks(failing_get, failing_put, failing_delete, NULL) != 0) {
+        return 1;
+    }
+    int f_issue = ZK_IssueChallenge(9, NOW + 60);
+    int f_consume = ZK_ConsumeChallenge(9, NOW);
+    int f_revoke = ZK_RevokeCredential("vc-3", 4, NOW);
+    int f_revoked = ZK_IsCredentialRevoked("vc-1", 4);
+    printf("  failing backend: issue %d, consume %d, revoke %d, revoked %d\n", f_issue, f_consume, f_revoke,
+           f_revoked);
+    if (f_issue != ZK_ERR_STORAGE || f_consume != ZK_ERR_STORAGE || f_revoke != ZK_ERR_STORAGE ||
+        f_revoked != ZK_ERR_STORAGE) {
+        return 1;
+    }
+
+    /* NULL callbacks: a fresh in-memory backend, the host table untouched */
+    int reset = ZK_SetStorageCallbacks(NULL, NULL, NULL, NULL);
+    int forgotten = ZK_IsCredentialRevoked("vc-1", 4);
+    int memory_issue = ZK_IssueChallenge(10, NOW + 60);
+    int memory_consume = ZK_ConsumeChallenge(10, NOW);
+    printf("  in memory: reset %d, earlier revocation %d, issue %d, consume %d, host entries %d\n", reset,
+           forgotten, memory_issue, memory_consume, used(&table));
+    return reset != 0 || forgotten != 0 || memory_issue != 0 || memory_consume != 1 || used(&table) != 1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Registry state goes through the installed backend, and its failures surface as ZK_ERR_STORAGE"
@@ -0,0 +1,231 @@
+#!/bin/bash
+#
+# Build the library for the host and check the holder credential store:
+# credentials added under a raw key or a passphrase survive closing and
+# reopening byte for byte and are not readable in the file; the wrong key,
+# the other kind of key, a flipped ciphertext byte or an edited header (the
+# unused cost fields of a raw-key store included) fail with ZK_ERR_AUTH; a
+# truncated file, bad magic or oversized Argon2 costs fail with
+# ZK_ERR_CORRUPT, the costs before any key is derived with them; a
+# re-issued credential replaces the one it supersedes while the old id keeps
+# resolving; and removal persists.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_ReissueVC(const char*, const char* const*, const char* const*, size_t, uint64_t, uint64_t, const char*, char*,
+                 size_t, char*, size_t);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+int ZK_StoreGetVC(CredentialStore*, const char*, char*, size_t);
+int ZK_StoreApplySupersession(CredentialStore*, const char*, char*, size_t);
+int ZK_StoreRemoveVC(CredentialStore*, const char*);
+int ZK_StoreListVCs(CredentialStore*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+
+#define ZK_ERR_AUTH -13
+#define ZK_ERR_CORRUPT -14
+#define ZK_STORE_KEY_PASSPHRASE 0
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define HEADER_M_COST 22
+#define HEADER_RECORD_COUNT 34
+
+static const char raw_key[32] = "0123456789abcdef0123456789abcdef";
+static const char other_key[32] = "fedcba9876543210fedcba9876543210";
+static char pub[65], priv[65];
+static char alice[8192], bob[8192], successor[8192], entry[512], out[8192], list[4096];
+static char alice_id[128], bob_id[128], successor_id[128];
+static char path[512], copy[512];
+static unsigned char file[1 << 16];
+
+static int issue(const char* holder, char* blob) {
+    static char encoded[8192];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, blob, 8192) == 0;
+}
+
+static size_t read_file(const char* p) {
+    FILE* f = fopen(p, "rb");
+    size_t len = f ? fread(file, 1, sizeof(file), f) : 0;
+    if (f) {
+        fclose(f);
+    }
+    return len;
+}
+
+static void write_file(const char* p, size_t len) {
+    FILE* f = fopen(p, "wb");
+    fwrite(file, 1, len, f);
+    fclose(f);
+}
+
+static int contains(size_t len, const char* needle) {
+    size_t n = strlen(needle);
+    for (size_t i = 0; i + n <= len; i++) {
+        if (memcmp(file + i, needle, n) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+/* Open a copy of the store file, edited by `edit`, and report the code */
+static int open_edited(size_t len, void (*edit)(size_t*)) {
+    read_file(path);
+    edit(&len);
+    write_file(copy, len);
+    CredentialStore* store = NULL;
+    int rc = ZK_StoreOpen(copy, raw_key, 32, ZK_STORE_KEY_RAW, &store);
+    if (rc == 0) {
+        ZK_StoreClose(store);
+    }
+    return rc;
+}
+
+static void flip_last(size_t* len) { file[*len - 20] ^= 1; }
+static void edit_count(size_t* len) { (void)len, file[HEADER_RECORD_COUNT] ^= 1; }
+static void truncate_one(size_t* len) { *len -= 1; }
+static void bad_magic(size_t* len) { (void)len, file[0] = 'X'; }
+static void edit_cost(size_t* len) { (void)len, file[HEADER_M_COST] ^= 1; }
+
+/* The passphrase store with one Argon2 cost set to the largest u32 */
+static int open_costly(int offset) {
+    size_t len = read_file(path);
+    memset(file + offset, 0xff, 4);
+    write_file(copy, len);
+    CredentialStore* store = NULL;
+    int rc = ZK_StoreOpen(copy, "correct horse", 13, ZK_STORE_KEY_PASSPHRASE, &store);
+    if (rc == 0) {
+        ZK_StoreClose(store);
+    }
+    return rc;
+}
+
+int main(int argc, char** argv) {
+    if (argc < 2 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 || !issue("alice", alice) ||
+        !issue("bob", bob)) {
+        return 1;
+    }
+    snprintf(path, sizeof(path), "%s/raw.store", argv[1]);
+    snprintf(copy, sizeof(copy), "%s/edited.store", argv[1]);
+
+    /* Raw key: add two, close, reopen */
+    CredentialStore* store = NULL;
+    if (ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_StoreAddVC(store, alice, alice_id, sizeof(alice_id)) != 0 ||
+        ZK_StoreAddVC(store, bob, bob_id, sizeof(bob_id)) != 0) {
+        return 1;
+    }
+    int invalid = ZK_StoreAddVC(store, "00ff", NULL, 0);
+    ZK_StoreClose(store);
+    if (ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_StoreListVCs(store, list, sizeof(list)) != 0) {
+        return 1;
+    }
+    int same_alice = ZK_StoreGetVC(store, alice_id, out, sizeof(out)) == 0 && strcmp(out, alice) == 0;
+    int same_bob = ZK_StoreGetVC(store, bob_id, out, sizeof(out)) == 0 && strcmp(out, bob) == 0;
+    int unknown = ZK_StoreGetVC(store, "no-such-id", out, sizeof(out));
+    ZK_StoreClose(store);
+    size_t len = read_file(path);
+    int readable = contains(len, "alice") || contains(len, "616c696365") || contains(len, "engineer");
+    printf("  round trip: alice %d, bob %d, both listed %d, unknown %d, invalid blob %d, plaintext in file %d\n",
+           same_alice, same_bob, strstr(list, alice_id) != NULL && strstr(list, bob_id) != NULL, unknown, invalid,
+           readable);
+    if (!same_alice || !same_bob || strstr(list, alice_id) == NULL || strstr(list, bob_id) == NULL || unknown != 1 ||
+        invalid != -1 || readable) {
+        return 1;
+    }
+
+    /* Wrong keys and a damaged file */
+    int wrong_key = ZK_StoreOpen(path, other_key, 32, ZK_STORE_KEY_RAW, &store);
+    int as_passphrase = ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_PASSPHRASE, &store);
+    int flipped = open_edited(len, flip_last);
+    int count = open_edited(len, edit_count);
+    int truncated = open_edited(len, truncate_one);
+    int magic = open_edited(len, bad_magic);
+    int cost = open_edited(len, edit_cost);
+    printf("  wrong key %d, as a passphrase %d, flipped byte %d, edited record count %d, truncated %d, magic %d, "
+           "edited unused cost %d\n",
+           wrong_key, as_passphrase, flipped, count, truncated, magic, cost);
+    if (wrong_key != ZK_ERR_AUTH || as_passphrase != ZK_ERR_AUTH || flipped != ZK_ERR_AUTH || count != ZK_ERR_AUTH ||
+        truncated != ZK_ERR_CORRUPT || magic != ZK_ERR_CORRUPT || cost != ZK_ERR_AUTH) {
+        return 1;
+    }
+
+    /* A re-issue replaces Alice's credential; her old id resolves to it */
+    const char* keys[] = {"role"};
+    const char* values[] = {"manager"};
+    if (ZK_ReissueVC(alice, keys, values, 1, NOW, 0, priv, successor, sizeof(successor), entry, sizeof(entry)) != 0 ||
+        ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_StoreApplySupersession(store, successor, successor_id, sizeof(successor_id)) != 0) {
+        return 1;
+    }
+    int removed = ZK_StoreRemoveVC(store, bob_id);
+    int removed_again = ZK_StoreRemoveVC(store, bob_id);
+    ZK_StoreClose(store);
+    if (ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_RAW, &store) != 0 ||
+        ZK_StoreListVCs(store, list, sizeof(list)) != 0) {
+        return 1;
+    }
+    int resolved = ZK_StoreGetVC(store, alice_id, out, sizeof(out)) == 0 && strcmp(out, successor) == 0;
+    int bob_gone = ZK_StoreGetVC(store, bob_id, out, sizeof(out));
+    int listed = strstr(list, successor_id) != NULL && strstr(list, alice_id) == NULL && strstr(list, bob_id) == NULL;
+    ZK_StoreClose(store);
+    printf("  supersession: old id resolves %d, listed only the successor %d; removed %d then %d, bob %d\n",
+           resolved, listed, removed, removed_again, bob_gone);
+    if (!resolved || !listed || removed != 0 || removed_again != 1 || bob_gone != 1) {
+        return 1;
+    }
+
+    /* Passphrase store */
+    snprintf(path, sizeof(path), "%s/passphrase.store", argv[1]);
+    if (ZK_StoreOpen(path, "correct horse", 13, ZK_STORE_KEY_PASSPHRASE, &store) != 0 ||
+        ZK_StoreAddVC(store, alice, NULL, 0) != 0) {
+        return 1;
+    }
+    ZK_StoreClose(store);
+    int wrong_passphrase = ZK_StoreOpen(path, "battery staple", 14, ZK_STORE_KEY_PASSPHRASE, &store);
+    int as_raw = ZK_StoreOpen(path, raw_key, 32, ZK_STORE_KEY_RAW, &store);
+    if (ZK_StoreOpen(path, "correct horse", 13, ZK_STORE_KEY_PASSPHRASE, &store) != 0) {
+        return 1;
+    }
+    int reopened = ZK_StoreGetVC(store, alice_id, out, sizeof(out)) == 0 && strcmp(out, alice) == 0;
+    ZK_StoreClose(store);
+    int memory = open_costly(HEADER_M_COST), passes = open_costly(HEADER_M_COST + 4);
+    int lanes = open_costly(HEADER_M_COST + 8);
+    printf("  passphrase: reopened %d, wrong passphrase %d, as a raw key %d; huge memory %d, passes %d, lanes %d\n",
+           reopened, wrong_passphrase, as_raw, memory, passes, lanes);
+    return !reopened || wrong_passphrase != ZK_ERR_AUTH || as_raw != ZK_ERR_AUTH || memory != ZK_ERR_CORRUPT ||
+           passes != ZK_ERR_CORRUPT || lanes != ZK_ERR_CORRUPT;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ The credential store round-trips under its key and rejects wrong keys and damaged files"
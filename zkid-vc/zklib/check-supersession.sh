@@ -0,0 +1,126 @@
+#!/bin/bash
+#
+# Build the library for the host and check re-issue and supersession: a
+# re-issued credential carries the correction and a revocation entry for
+# its predecessor; applied, the entry revokes the old id and resolves it
+# (through a chain of re-issues) to the newest one; with
+# ZK_SetRejectSuperseded the old blobs stop verifying while the newest still
+# does; and only the original issuer key can re-issue.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct CredentialStore CredentialStore;
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_ReissueVC(const char*, const char* const*, const char* const*, size_t, uint64_t, uint64_t, const char*, char*,
+                 size_t, char*, size_t);
+int ZK_ApplyRevocationEntry(const char*);
+int ZK_IsCredentialRevoked(const char*, size_t);
+int ZK_ResolveSupersession(const char*, char*, size_t);
+int ZK_SetRejectSuperseded(int);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_StoreOpen(const char*, const char*, size_t, int, CredentialStore**);
+int ZK_StoreAddVC(CredentialStore*, const char*, char*, size_t);
+void ZK_StoreClose(CredentialStore*);
+
+#define ZK_STORE_KEY_RAW 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char encoded[8192], first[8192], second[8192], third[8192], unused[8192];
+static char entry1[512], entry2[512];
+static char id1[128], id2[128], id3[128], newest[128];
+static CredentialStore* store;
+
+static int reissue(const char* old, const char* role, const char* key, char* out, char* entry) {
+    const char* keys[] = {"role"};
+    const char* values[] = {role};
+    return ZK_ReissueVC(old, keys, values, 1, NOW, 0, key, out, 8192, entry, 512);
+}
+
+int main(int argc, char** argv) {
+    char path[512];
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (argc < 2) {
+        return 1;
+    }
+    snprintf(path, sizeof(path), "%s/ids.store", argv[1]);
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, first, sizeof(first)) != 0 ||
+        ZK_StoreOpen(path, "0123456789abcdef0123456789abcdef", 32, ZK_STORE_KEY_RAW, &store) != 0) {
+        return 1;
+    }
+
+    /* Only the issuer that signed the credential re-issues it */
+    int foreign = reissue(first, "manager", priv2, unused, entry1);
+    if (reissue(first, "manager", priv, second, entry1) != 0 || reissue(second, "director", priv, third, entry2) != 0 ||
+        ZK_StoreAddVC(store, first, id1, sizeof(id1)) != 0 || ZK_StoreAddVC(store, second, id2, sizeof(id2)) != 0 ||
+        ZK_StoreAddVC(store, third, id3, sizeof(id3)) != 0) {
+        return 1;
+    }
+    ZK_StoreClose(store);
+    int corrected = ZK_VerifyVCBlob(second, pub, NULL);
+    printf("  re-issue: by another key %d, corrected credential verifies %d, ids differ %d\n", foreign, corrected,
+           strcmp(id1, id2) != 0 && strcmp(id2, id3) != 0);
+    if (foreign != -1 || corrected != 1 || strcmp(id1, id2) == 0 || strcmp(id2, id3) == 0) {
+        return 1;
+    }
+
+    /* Before the entries are applied nothing is superseded */
+    int before = ZK_IsCredentialRevoked(id1, strlen(id1));
+    if (ZK_ResolveSupersession(id1, newest, sizeof(newest)) != 0 || strcmp(newest, id1) != 0 || before != 0) {
+        return 1;
+    }
+
+    /* Applied: the old ids are revoked and resolve to the newest */
+    int applied = ZK_ApplyRevocationEntry(entry1) | ZK_ApplyRevocationEntry(entry2);
+    int malformed = ZK_ApplyRevocationEntry("zz");
+    int revoked1 = ZK_IsCredentialRevoked(id1, strlen(id1));
+    int revoked2 = ZK_IsCredentialRevoked(id2, strlen(id2));
+    int revoked3 = ZK_IsCredentialRevoked(id3, strlen(id3));
+    int resolved = ZK_ResolveSupersession(id1, newest, sizeof(newest)) == 0 && strcmp(newest, id3) == 0;
+    printf("  applied %d (malformed %d): revoked %d %d %d, first resolves to the newest %d\n", applied, malformed,
+           revoked1, revoked2, revoked3, resolved);
+    if (applied != 0 || malformed != -1 || revoked1 != 1 || revoked2 != 1 || revoked3 != 0 || !resolved) {
+        return 1;
+    }
+
+    /* Superseded credentials verify until rejection is switched on */
+    int lenient = ZK_VerifyVCBlob(first, pub, NULL);
+    ZK_SetRejectSuperseded(1);
+    int strict_first = ZK_VerifyVCBlob(first, pub, NULL);
+    int strict_second = ZK_VerifyVCBlob(second, pub, NULL);
+    int strict_third = ZK_VerifyVCBlob(third, pub, NULL);
+    ZK_SetRejectSuperseded(0);
+    printf("  superseded first: %d; rejecting: first %d, second %d, newest %d\n", lenient, strict_first,
+           strict_second, strict_third);
+    return lenient != 1 || strict_first != 0 || strict_second != 0 || strict_third != 1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" "$WORK_DIR" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Re-issued credentials supersede their predecessors through the revocation registry"
@@ -0,0 +1,310 @@
+#!/bin/bash
+#
+# Build the library with the threshold feature and check FROST issuance:
+# the RFC 9591 FROST(Ed25519, SHA-512) vector replays through
+# ZK_RunConformance; a 2-of-3 group generated by the DKG signs a VC blob
+# with any two of its members, and the blob verifies and proves under the
+# group key only, each aggregated signature counting in the group key's
+# issuer statistics; a participant sending a share that does not match its
+# commitments in key generation, or a signature share from another session,
+# is named with ZK_ERR_MISBEHAVING; with issuer sequencing on, round 2 and
+# aggregation refuse with ZK_ERR_SEQUENCE_UNSUPPORTED and the nonces stay
+# usable; and a signer's nonces sign once, a repeated round 2 returning
+# ZK_ERR_NONCE_REUSED.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct {
+    uint64_t credentials_signed;
+    uint64_t distinct_holders;
+    uint32_t signatures_last_minute;
+    uint32_t peak_per_minute;
+} ZkIssuerStats;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_RunConformance(const char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_ThresholdKeygenRound1(uint16_t, uint16_t, uint16_t, char*, size_t, char*, size_t);
+int ZK_ThresholdKeygenRound2(const char*, const char* const*, size_t, char*, size_t, char*, size_t, uint16_t*);
+int ZK_ThresholdKeygenFinalize(const char*, const char* const*, size_t, char*, size_t, char*, size_t, char*, size_t,
+                               uint16_t*);
+int ZK_ThresholdSignRound1(const char*, char*, size_t);
+int ZK_ThresholdSignRound2(const char*, const char*, const char* const*, size_t, char*, size_t);
+int ZK_ThresholdAggregate(const char*, const char*, const char* const*, size_t, const char* const*, size_t, char*,
+                          size_t, uint16_t*);
+int ZK_GetIssuerStats(const char*, ZkIssuerStats*);
+int ZK_SetIssuerSequencing(int);
+
+#define ZK_ERR_NONCE_REUSED -22
+#define ZK_ERR_MISBEHAVING -33
+#define ZK_ERR_SEQUENCE_UNSUPPORTED -40
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define N 3
+#define T 2
+#define PACKAGE 4096
+
+/* One DKG run: every participant's round 2 packages by recipient, key
+ * packages and the public key package */
+typedef struct {
+    char round1_secret[N][PACKAGE], round1[N][PACKAGE];
+    char round2_secret[N][PACKAGE], shares[N][PACKAGE];
+    char to[N][N][PACKAGE]; /* to[recipient][sender] */
+    char key[N][PACKAGE], public_key[N][PACKAGE], group[N][65];
+} Dkg;
+
+static Dkg dkg, other;
+static char pub[65], priv[65];
+static char blob[8192], signed_blob[8192], proof[4096];
+
+/* The package for `recipient` in a round 2 shares array, whatever the key
+ * order of its object */
+static int share_for(const char* json, int recipient, char* out) {
+    char to[16];
+    snprintf(to, sizeof(to), "\"to\":%d", recipient);
+    for (const char* object = strchr(json, '{'); object; object = strchr(object + 1, '{')) {
+        const char* close = strchr(object, '}');
+        const char* at = strstr(object, to);
+        const char* package = strstr(object, "\"package\":\"");
+        if (close == NULL || at == NULL || at > close || package == NULL || package > close ||
+            strchr("0123456789", at[strlen(to)]) != NULL) {
+            continue;
+        }
+        package += strlen("\"package\":\"");
+        const char* end = strchr(package, '"');
+        if (end == NULL || end - package >= PACKAGE) {
+            return 0;
+        }
+        memcpy(out, package, end - package);
+        out[end - package] = '\0';
+        return 1;
+    }
+    return 0;
+}
+
+static int keygen_round2(Dkg* d) {
+    for (int i = 0; i < N; i++) {
+        const char* others[N - 1];
+        for (int j = 0, k = 0; j < N; j++) {
+            if (j != i) {
+                others[k++] = d->round1[j];
+            }
+        }
+        if (ZK_ThresholdKeygenRound2(d->round1_secret[i], others, N - 1, d->round2_secret[i], PACKAGE, d->shares[i],
+                                     PACKAGE, NULL) != 0) {
+            return 0;
+        }
+        for (int j = 0; j < N; j++) {
+            if (j != i && !share_for(d->shares[i], j + 1, d->to[j][i])) {
+                return 0;
+            }
+        }
+    }
+    return 1;
+}
+
+/* Finalize participant `i` with the packages addressed to it */
+static int finalize(Dkg* d, int i, uint16_t* culprit) {
+    const char* received[N - 1];
+    for (int j = 0, k = 0; j < N; j++) {
+        if (j != i) {
+            received[k++] = d->to[i][j];
+        }
+    }
+    return ZK_ThresholdKeygenFinalize(d->round2_secret[i], received, N - 1, d->key[i], PACKAGE, d->public_key[i],
+                                      PACKAGE, d->group[i], 65, culprit);
+}
+
+static int keygen(Dkg* d) {
+    for (int i = 0; i < N; i++) {
+        if (ZK_ThresholdKeygenRound1(i + 1, N, T, d->round1_secret[i], PACKAGE, d->round1[i], PACKAGE) != 0) {
+            return 0;
+        }
+    }
+    if (!keygen_round2(d)) {
+        return 0;
+    }
+    for (int i = 0; i < N; i++) {
+        if (finalize(d, i, NULL) != 0 || strcmp(d->group[i], d->group[0]) != 0) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+/* Signers `a` and `b` (0-based) of `d` commit and produce their shares */
+static int sign_shares(const Dkg* d, int a, int b, char commitments[2][PACKAGE], char shares[2][PACKAGE]) {
+    const int signers[2] = {a, b};
+    const char* list[2] = {commitments[0], commitments[1]};
+    for (int i = 0; i < 2; i++) {
+        if (ZK_ThresholdSignRound1(d->key[signers[i]], commitments[i], PACKAGE) != 0) {
+            return 0;
+        }
+    }
+    for (int i = 0; i < 2; i++) {
+        if (ZK_ThresholdSignRound2(d->key[signers[i]], blob, list, 2, shares[i], PACKAGE) != 0) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+static int aggregate(const Dkg* d, char commitments[2][PACKAGE], const char* first, const char* second,
+                     uint16_t* culprit) {
+    const char* list[2] = {commitments[0], commitments[1]};
+    const char* shares[2] = {first, second};
+    return ZK_ThresholdAggregate(d->public_key[0], blob, list, 2, shares, 2, signed_blob, sizeof(signed_blob),
+                                 culprit);
+}
+
+static int check_vector(const char* path) {
+    FILE* f = fopen(path, "rb");
+    if (f == NULL) {
+        return 0;
+    }
+    static char vectors[1 << 22], report[1 << 20];
+    size_t len = fread(vectors, 1, sizeof(vectors), f);
+    fclose(f);
+    int failed = ZK_RunConformance(vectors, len, report, sizeof(report));
+    int passed = strstr(report, "{\"id\":\"frost-signing-001-rfc9591\",\"status\":\"pass\"}") != NULL;
+    printf("  conformance: %d failed, RFC 9591 vector passed %d\n", failed, passed);
+    return failed == 0 && passed;
+}
+
+int main(int argc, char** argv) {
+    if (argc < 2 || ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0) {
+        return 1;
+    }
+    if (!check_vector(argv[1])) {
+        return 1;
+    }
+
+    /* Key generation: all three agree on the group key */
+    if (!keygen(&dkg) || !keygen(&other)) {
+        printf("  keygen failed\n");
+        return 1;
+    }
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, blob, sizeof(blob)) != 0) {
+        return 1;
+    }
+
+    /* Every pair of the three signs a blob valid under the group key */
+    const int pairs[3][2] = {{0, 2}, {0, 1}, {1, 2}};
+    for (int p = 0; p < 3; p++) {
+        char commitments[2][PACKAGE], shares[2][PACKAGE];
+        uint16_t culprit = 0;
+        if (!sign_shares(&dkg, pairs[p][0], pairs[p][1], commitments, shares) ||
+            aggregate(&dkg, commitments, shares[0], shares[1], &culprit) != 0) {
+            printf("  signers %d and %d: failed\n", pairs[p][0] + 1, pairs[p][1] + 1);
+            return 1;
+        }
+        int valid = ZK_VerifyVCBlob(signed_blob, dkg.group[0], NULL);
+        int other_group = ZK_VerifyVCBlob(signed_blob, other.group[0], NULL);
+        int other_key = ZK_VerifyVCBlob(signed_blob, pub, NULL);
+        printf("  signers %d and %d: valid %d, other group %d, other key %d\n", pairs[p][0] + 1, pairs[p][1] + 1,
+               valid, other_group, other_key);
+        if (valid != 1 || other_group != 0 || other_key != 0) {
+            return 1;
+        }
+    }
+    ZkIssuerStats stats = {0};
+    int counted = ZK_GetIssuerStats(dkg.group[0], &stats);
+    printf("  issuer stats: %d, %llu signed under the group key\n", counted,
+           (unsigned long long)stats.credentials_signed);
+    if (counted != 0 || stats.credentials_signed != 3) {
+        return 1;
+    }
+    int proved = ZK_GenerateVCProofFromBlob(signed_blob, dkg.group[0], NULL, NOW, 7, proof, sizeof(proof));
+    int verified = proved == 0 ? ZK_VerifyVCProof(proof, dkg.group[0], NOW, 7) : -1;
+    printf("  proof under the group key: prove %d, verify %d\n", proved, verified);
+    if (proved != 0 || verified != 1) {
+        return 1;
+    }
+
+    /* Participant 2 sends participant 1 a share of another run's polynomial */
+    uint16_t culprit = 0;
+    char honest[PACKAGE];
+    strcpy(honest, dkg.to[0][1]);
+    strcpy(dkg.to[0][1], other.to[0][1]);
+    int forged_share = finalize(&dkg, 0, &culprit);
+    printf("  keygen share from another run: %d, culprit %u\n", forged_share, culprit);
+    if (forged_share != ZK_ERR_MISBEHAVING || culprit != 2) {
+        return 1;
+    }
+    strcpy(dkg.to[0][1], honest);
+
+    /* Participant 3 answers with its share of another session */
+    char commitments[2][PACKAGE], shares[2][PACKAGE], stale_commitments[2][PACKAGE], stale[2][PACKAGE];
+    if (!sign_shares(&dkg, 0, 2, stale_commitments, stale) || !sign_shares(&dkg, 0, 2, commitments, shares)) {
+        return 1;
+    }
+    culprit = 0;
+    int stale_share = aggregate(&dkg, commitments, shares[0], stale[1], &culprit);
+    printf("  signature share of another session: %d, culprit %u\n", stale_share, culprit);
+    if (stale_share != ZK_ERR_MISBEHAVING || culprit != 3) {
+        return 1;
+    }
+
+    /* The signers cannot number the credential: sequencing refuses them */
+    char fresh[2][PACKAGE], share[PACKAGE];
+    const char* fresh_list[2] = {fresh[0], fresh[1]};
+    if (ZK_ThresholdSignRound1(dkg.key[0], fresh[0], PACKAGE) != 0 ||
+        ZK_ThresholdSignRound1(dkg.key[2], fresh[1], PACKAGE) != 0) {
+        return 1;
+    }
+    ZK_SetIssuerSequencing(1);
+    int sequenced_share = ZK_ThresholdSignRound2(dkg.key[0], blob, fresh_list, 2, share, PACKAGE);
+    int sequenced_aggregate = aggregate(&dkg, commitments, shares[0], shares[1], NULL);
+    ZK_SetIssuerSequencing(0);
+    int kept = ZK_ThresholdSignRound2(dkg.key[0], blob, fresh_list, 2, share, PACKAGE);
+    printf("  with sequencing: round 2 %d, aggregate %d; nonces kept %d\n", sequenced_share, sequenced_aggregate,
+           kept);
+    if (sequenced_share != ZK_ERR_SEQUENCE_UNSUPPORTED || sequenced_aggregate != ZK_ERR_SEQUENCE_UNSUPPORTED ||
+        kept != 0) {
+        return 1;
+    }
+
+    /* Round 2 again with the same commitments: the nonces are gone */
+    const char* list[2] = {commitments[0], commitments[1]};
+    char again[PACKAGE];
+    int reused = ZK_ThresholdSignRound2(dkg.key[0], blob, list, 2, again, PACKAGE);
+    printf("  repeated round 2: %d\n", reused);
+    if (reused != ZK_ERR_NONCE_REUSED) {
+        return 1;
+    }
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" conformance/vectors.json || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build threshold "--features threshold"
+
+echo "✓ Threshold issuance matches RFC 9591 and names misbehaving participants"
@@ -0,0 +1,132 @@
+#!/bin/bash
+#
+# Build the library for the host and check VC proofs under each time mode
+# (ZK_SetTimeMode) against a fake clock. Under ZK_TIME_CALLER a proof
+# verifies only at the caller's time it was proven for. Under
+# ZK_TIME_CALLBACK the caller's time is ignored on both sides: the proof
+# carries the clock's time and verifies, directly and in a batch, only
+# while the clock reads that time; without a callback both sides fail.
+# Under ZK_TIME_CROSSCHECK the same holds, and a caller time more than
+# max_skew from the clock fails with ZK_ERR_TIME_MISMATCH.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_BatchVerifyVCProofs(const char* const*, const char* const*, uint64_t, const uint64_t*, size_t, int*);
+int ZK_SetTimeCallback(uint64_t (*)(void*), void*);
+int ZK_SetTimeMode(int, uint64_t);
+
+#define ZK_TIME_CALLER 0
+#define ZK_TIME_CALLBACK 1
+#define ZK_TIME_CROSSCHECK 2
+#define ZK_ERR_TIME_MISMATCH -11
+
+static char pub[65], priv[65], sig[129], proof[1024];
+
+static uint64_t fake_clock(void* now) {
+    return *(uint64_t*)now;
+}
+
+static int prove(uint64_t current_time) {
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, current_time, 42,
+                              proof, sizeof(proof), NULL);
+}
+
+/* ZK_BatchVerifyVCProofs on the proof alone, or -2 if its result differs
+   from the return value */
+static int batch(uint64_t current_time) {
+    const char* proofs[] = {proof};
+    const char* issuers[] = {pub};
+    uint64_t nonces[] = {42};
+    int results[1] = {-3};
+    int valid = ZK_BatchVerifyVCProofs(proofs, issuers, current_time, nonces, 1, results);
+    return valid == (results[0] == 1) || valid == results[0] ? results[0] : -2;
+}
+
+int main(void) {
+    static uint64_t now;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0) {
+        return 1;
+    }
+
+    // Caller time: exactly the time proven for
+    int proven = prove(150);
+    int same = ZK_VerifyVCProof(proof, pub, 150, 42);
+    int later = ZK_VerifyVCProof(proof, pub, 151, 42);
+    printf("  caller: proven %d, at 150 %d, at 151 %d\n", proven, same, later);
+    if (proven != 0 || same != 1 || later != 0) {
+        return 1;
+    }
+
+    // Callback without one installed
+    if (ZK_SetTimeMode(ZK_TIME_CALLBACK, 30) != 0 || ZK_SetTimeMode(3, 0) != -1) {
+        return 1;
+    }
+    int unset_prove = prove(150);
+    int unset_verify = ZK_VerifyVCProof(proof, pub, 150, 42);
+    printf("  callback, none installed: prove %d, verify %d\n", unset_prove, unset_verify);
+    if (unset_prove != -1 || unset_verify != -1) {
+        return 1;
+    }
+
+    // Callback: the caller's time is ignored, the proof holds the clock's
+    ZK_SetTimeCallback(fake_clock, &now);
+    now = 150;
+    proven = prove(0);
+    int ignored = ZK_VerifyVCProof(proof, pub, 999, 42);
+    int ignored_batch = batch(999);
+    now = 151;
+    int moved = ZK_VerifyVCProof(proof, pub, 150, 42);
+    int moved_batch = batch(150);
+    printf("  callback: proven %d, caller 999 %d (batch %d), clock moved on %d (batch %d)\n", proven, ignored,
+           ignored_batch, moved, moved_batch);
+    if (proven != 0 || ignored != 1 || ignored_batch != 1 || moved != 0 || moved_batch != 0) {
+        return 1;
+    }
+
+    // Cross-check: as the callback, and the caller's time must agree with
+    // the clock to within 30 seconds
+    ZK_SetTimeMode(ZK_TIME_CROSSCHECK, 30);
+    now = 150;
+    proven = prove(175);
+    int skewed_prove = prove(190);
+    int agreed = ZK_VerifyVCProof(proof, pub, 125, 42);
+    int skewed = ZK_VerifyVCProof(proof, pub, 250, 42);
+    int skewed_batch = batch(250);
+    now = 151;
+    int moved_on = ZK_VerifyVCProof(proof, pub, 151, 42);
+    printf("  cross-check: proven %d (40s off %d), caller 25s off %d, 100s off %d (batch %d), clock moved on %d\n",
+           proven, skewed_prove, agreed, skewed, skewed_batch, moved_on);
+    if (proven != 0 || skewed_prove != ZK_ERR_TIME_MISMATCH || agreed != 1 || skewed != ZK_ERR_TIME_MISMATCH ||
+        skewed_batch != ZK_ERR_TIME_MISMATCH || moved_on != 0) {
+        return 1;
+    }
+    return 0;
+}
+EOF
+
+echo "Checking time modes"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Time checks follow the configured time source"
@@ -0,0 +1,126 @@
+#!/bin/bash
+#
+# Build the library for the host and check presentation transcript
+# digests: the digest is the same for the hex and binary encodings, for
+# metadata in any order and for the proof with uncompressed points, and
+# changes with the nonce, the issuer key or any metadata value. A
+# presentation whose nonce does not match its proof still has a digest but
+# does not verify: the digest names a presentation, it does not vouch for it.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_ConvertProofEncoding(const char*, size_t, int, char*, size_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_PresentationDigest(const char*, size_t, uint8_t*);
+int ZK_VerifyPresentation(const char*, size_t);
+
+#define ZK_PROOF_ENCODING_UNCOMPRESSED 1
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define NONCE 7
+
+static char pub[65], priv[65], pub2[65], priv2[65];
+static char encoded[8192], blob[8192], proof[4096], uncompressed[4096], presentation[16384];
+static uint8_t binary[8192];
+
+static const char* keys[] = {"audience", "purpose"};
+static const char* values[] = {"gateway.example", "login"};
+static const char* swapped_keys[] = {"purpose", "audience"};
+static const char* swapped_values[] = {"login", "gateway.example"};
+static const char* other_values[] = {"gateway.example", "payment"};
+
+/* Digest of the presentation built from these arguments; 0 if it failed */
+static int digest(const char* p, const char* issuer, uint64_t nonce, const char* const* k, const char* const* v,
+                  uint8_t out[32]) {
+    return ZK_EncodePresentation(p, issuer, nonce, k, v, 2, presentation, sizeof(presentation)) == 0 &&
+           ZK_PresentationDigest(presentation, strlen(presentation), out) == 0;
+}
+
+static size_t unhex(const char* hex, uint8_t* out) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len; i++) {
+        unsigned int byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return len;
+}
+
+int main(void) {
+    const char* claim_keys[] = {"role"};
+    const char* claim_values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(pub2, sizeof(pub2), priv2, sizeof(priv2)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, claim_keys, claim_values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, NONCE, proof, sizeof(proof)) != 0 ||
+        ZK_ConvertProofEncoding(proof, strlen(proof), ZK_PROOF_ENCODING_UNCOMPRESSED, uncompressed,
+                                sizeof(uncompressed)) != 0) {
+        return 1;
+    }
+
+    uint8_t base[32], binary_digest[32], swapped[32], points[32], nonce[32], issuer[32], value[32];
+    if (!digest(proof, pub, NONCE, keys, values, base)) {
+        return 1;
+    }
+    int valid = ZK_VerifyPresentation(presentation, strlen(presentation));
+    size_t binary_len = unhex(presentation, binary);
+    if (ZK_PresentationDigest((const char*)binary, binary_len, binary_digest) != 0 ||
+        !digest(proof, pub, NONCE, swapped_keys, swapped_values, swapped) ||
+        !digest(uncompressed, pub, NONCE, keys, values, points) ||
+        !digest(proof, pub2, NONCE, keys, values, issuer) ||
+        !digest(proof, pub, NONCE, keys, other_values, value) ||
+        !digest(proof, pub, NONCE + 1, keys, values, nonce)) {
+        return 1;
+    }
+    int wrong_nonce = ZK_VerifyPresentation(presentation, strlen(presentation));
+
+    int same_binary = memcmp(base, binary_digest, 32) == 0;
+    int same_swapped = memcmp(base, swapped, 32) == 0;
+    int same_points = memcmp(base, points, 32) == 0;
+    int other_issuer = memcmp(base, issuer, 32) != 0;
+    int other_value = memcmp(base, value, 32) != 0;
+    int other_nonce = memcmp(base, nonce, 32) != 0;
+    printf("  same digest: binary %d, metadata reordered %d, uncompressed proof %d\n", same_binary, same_swapped,
+           same_points);
+    printf("  other digest: issuer %d, metadata value %d, nonce %d; verifies %d, with the other nonce %d\n",
+           other_issuer, other_value, other_nonce, valid, wrong_nonce);
+    if (!same_binary || !same_swapped || !same_points || !other_issuer || !other_value || !other_nonce ||
+        valid != 1 || wrong_nonce != 0) {
+        return 1;
+    }
+
+    uint8_t unused[32];
+    int garbage = ZK_PresentationDigest("5a4b5052", 8, unused);
+    int null_out = ZK_PresentationDigest(presentation, strlen(presentation), NULL);
+    printf("  malformed %d, NULL digest %d\n", garbage, null_out);
+    return garbage != -1 || null_out != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Transcript digests name one presentation however it is encoded"
@@ -0,0 +1,224 @@
+#!/bin/bash
+#
+# Build the library for the host and check chunked transfers: a
+# presentation imported in chunks verifies (and a tampered one does not),
+# the proving key exported in chunks imports back whatever the chunk size,
+# while a key with a malformed point, cut short or followed by extra bytes
+# is refused with ZK_ERR_CORRUPT and a last error, leaving the loaded key in
+# place; an out-of-order or over-length chunk aborts the transfer, after
+# which its handle is unknown, and an import ended early fails. A declared size reserves nothing until
+# chunks arrive, and the sizes declared by open imports together cannot
+# exceed the largest single import.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_EncodePresentation(const char*, const char*, uint64_t, const char* const*, const char* const*, size_t, char*,
+                          size_t);
+int ZK_TransferBegin(int, size_t, uint64_t*);
+int ZK_TransferChunk(uint64_t, uint64_t, const char*, size_t);
+int ZK_TransferEnd(uint64_t);
+int ZK_TransferRead(uint64_t, char*, size_t, size_t*);
+void ZK_TransferAbort(uint64_t);
+int ZK_MemoryReport(const void*, char*, size_t);
+int ZK_GetLastError(char*, size_t);
+
+#define ZK_ERR_UNKNOWN_HANDLE -15
+#define ZK_ERR_CAPACITY -21
+#define ZK_ERR_CORRUPT -14
+#define ZK_TRANSFER_IMPORT_KEYS 0
+#define ZK_TRANSFER_EXPORT_KEYS 1
+#define ZK_TRANSFER_IMPORT_PRESENTATION 2
+#define MAX_CHUNK (64 * 1024)
+#define MAX_TRANSFER (256u * 1024 * 1024)
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096], presentation[16384];
+static char binary[8192], chunk[MAX_CHUNK + 1];
+
+static size_t unhex(const char* hex, char* out) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len; i++) {
+        unsigned int byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (char)byte;
+    }
+    return len;
+}
+
+/* Import `len` bytes of `data` of `kind` in chunks of `step` */
+static int import(int kind, const char* data, size_t len, size_t step) {
+    uint64_t handle = 0;
+    if (ZK_TransferBegin(kind, len, &handle) != 0) {
+        return -100;
+    }
+    for (size_t offset = 0; offset < len; offset += step) {
+        size_t n = len - offset < step ? len - offset : step;
+        int status = ZK_TransferChunk(handle, offset, data + offset, n);
+        if (status != 0) {
+            return status;
+        }
+    }
+    return ZK_TransferEnd(handle);
+}
+
+/* Bytes the open transfers hold, from ZK_MemoryReport */
+static long transfer_bytes(void) {
+    static char report[4096];
+    if (ZK_MemoryReport(NULL, report, sizeof(report)) != 0) {
+        return -1;
+    }
+    const char* transfers = strstr(report, "\"transfers\":");
+    const char* bytes = transfers ? strstr(transfers, "\"bytes\":") : NULL;
+    return bytes ? atol(bytes + strlen("\"bytes\":")) : -1;
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_EncodePresentation(proof, pub, 7, NULL, NULL, 0, presentation, sizeof(presentation)) != 0) {
+        return 1;
+    }
+    size_t len = unhex(presentation, binary);
+
+    /* A presentation in 100-byte chunks, and one with a flipped proof byte */
+    int imported = import(ZK_TRANSFER_IMPORT_PRESENTATION, binary, len, 100);
+    binary[len / 2] ^= 1;
+    int tampered = import(ZK_TRANSFER_IMPORT_PRESENTATION, binary, len, 100);
+    binary[len / 2] ^= 1;
+    printf("  presentation: %d, tampered %d\n", imported, tampered);
+    if (imported != 1 || tampered != 0) {
+        return 1;
+    }
+
+    /* Out of order: the chunk at 100 before the one at 0 */
+    uint64_t handle = 0;
+    if (ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, len, &handle) != 0) {
+        return 1;
+    }
+    int skipped = ZK_TransferChunk(handle, 100, binary + 100, 100);
+    int after_skip = ZK_TransferChunk(handle, 0, binary, 100);
+    /* Over-length: 11 bytes into a 10-byte import, and a chunk above the cap */
+    if (ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 10, &handle) != 0) {
+        return 1;
+    }
+    int overrun = ZK_TransferChunk(handle, 0, binary, 11);
+    int after_overrun = ZK_TransferEnd(handle);
+    if (ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 2 * MAX_CHUNK, &handle) != 0) {
+        return 1;
+    }
+    int oversized = ZK_TransferChunk(handle, 0, chunk, MAX_CHUNK + 1);
+    ZK_TransferAbort(handle);
+    /* Ended before the last chunk */
+    if (ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, len, &handle) != 0 ||
+        ZK_TransferChunk(handle, 0, binary, 100) != 0) {
+        return 1;
+    }
+    int early = ZK_TransferEnd(handle);
+    int after_end = ZK_TransferChunk(handle, 100, binary + 100, 100);
+    printf("  out of order %d (then %d), overrun %d (then %d), oversized chunk %d, ended early %d (then %d)\n",
+           skipped, after_skip, overrun, after_overrun, oversized, early, after_end);
+    if (skipped != -1 || after_skip != ZK_ERR_UNKNOWN_HANDLE || overrun != -1 ||
+        after_overrun != ZK_ERR_UNKNOWN_HANDLE || oversized != -1 || early != -1 ||
+        after_end != ZK_ERR_UNKNOWN_HANDLE) {
+        return 1;
+    }
+
+    /* Declared sizes: nothing reserved up front, and a cap on their sum */
+    uint64_t large = 0, second = 0, small = 0;
+    int zero = ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 0, &small);
+    int too_large = ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, MAX_TRANSFER + 1, &small);
+    if (ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, MAX_TRANSFER, &large) != 0) {
+        return 1;
+    }
+    long declared = transfer_bytes();
+    int beyond = ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 1, &second);
+    int received = ZK_TransferChunk(large, 0, chunk, MAX_CHUNK);
+    long after_chunk = transfer_bytes();
+    ZK_TransferAbort(large);
+    int freed = ZK_TransferBegin(ZK_TRANSFER_IMPORT_PRESENTATION, 1, &small);
+    ZK_TransferAbort(small);
+    printf("  size 0 %d, above the limit %d; held after declaring 256 MiB %ld, after a chunk %ld; another import "
+           "%d, after aborting %d\n",
+           zero, too_large, declared, after_chunk, beyond, freed);
+    if (zero != -1 || too_large != -1 || declared != 0 || received != 0 || after_chunk < MAX_CHUNK ||
+        after_chunk > 4 * MAX_CHUNK || beyond != ZK_ERR_CAPACITY || freed != 0 || transfer_bytes() != 0) {
+        return 1;
+    }
+
+    /* The proving key exported in chunks imports back */
+    size_t key_len = 0, n = 0;
+    char* key = NULL;
+    if (ZK_TransferBegin(ZK_TRANSFER_EXPORT_KEYS, 0, &handle) != 0 || ZK_TransferEnd(handle) != 0) {
+        return 1;
+    }
+    do {
+        key = realloc(key, key_len + MAX_CHUNK);
+        if (key == NULL || ZK_TransferRead(handle, key + key_len, MAX_CHUNK, &n) != 0) {
+            return 1;
+        }
+        key_len += n;
+    } while (n > 0);
+    int finished = ZK_TransferRead(handle, chunk, MAX_CHUNK, &n);
+    int reimported = import(ZK_TRANSFER_IMPORT_KEYS, key, key_len, MAX_CHUNK);
+    int small_chunks = import(ZK_TRANSFER_IMPORT_KEYS, key, key_len, 1000);
+    char message[256] = "";
+    int truncated = import(ZK_TRANSFER_IMPORT_KEYS, key, key_len - 10, MAX_CHUNK);
+    int truncated_error = ZK_GetLastError(message, sizeof(message)) == 0 && message[0] != '\0';
+    char* damaged = malloc(key_len + 1);
+    if (damaged == NULL) {
+        return 1;
+    }
+    memcpy(damaged, key, key_len);
+    damaged[key_len] = 0;
+    int trailing = import(ZK_TRANSFER_IMPORT_KEYS, damaged, key_len + 1, MAX_CHUNK);
+    /* alpha_g1 opens the key; all-ones sets both point flags */
+    memset(damaged, 0xff, 32);
+    message[0] = '\0';
+    int malformed = import(ZK_TRANSFER_IMPORT_KEYS, damaged, key_len, MAX_CHUNK);
+    int malformed_error = ZK_GetLastError(message, sizeof(message)) == 0 && message[0] != '\0';
+    free(damaged);
+    int proved = ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 8, proof, sizeof(proof));
+    int verified = proved == 0 ? ZK_VerifyVCProof(proof, pub, NOW, 8) : -1;
+    free(key);
+    printf("  exported key: %zu bytes (read after the end %d), imported %d (in 1000-byte chunks %d), truncated "
+           "%d, trailing byte %d, malformed point %d (%s), prove %d, verify %d\n",
+           key_len, finished, reimported, small_chunks, truncated, trailing, malformed, message, proved, verified);
+    return key_len <= MAX_CHUNK || finished != ZK_ERR_UNKNOWN_HANDLE || reimported != 0 || small_chunks != 0 ||
+           truncated != ZK_ERR_CORRUPT || !truncated_error || trailing != ZK_ERR_CORRUPT ||
+           malformed != ZK_ERR_CORRUPT || !malformed_error || proved != 0 || verified != 1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Chunked transfers arrive in order, within their size, and reserve only what they receive"
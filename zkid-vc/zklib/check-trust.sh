@@ -0,0 +1,187 @@
+#!/bin/bash
+#
+# Build the library for the host and check the trusted-issuer registry: a
+# proof verifies under the issuer it was made for once that issuer is
+# trusted, reports its name, and stops verifying once the issuer is removed
+# or its name is rebound to another key. Verifier threads then run while a
+# writer thread adds and removes issuers: a proof of an issuer trusted
+# throughout verifies every time under its name, and one of an issuer that
+# comes and goes verifies only under that issuer's name.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <pthread.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_AddTrustedIssuer(const char*, const char*);
+int ZK_RemoveTrustedIssuer(const char*);
+int ZK_VerifyVCProofTrusted(const char*, uint64_t, uint64_t, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_BAD_HEX -6
+#define NOW 150
+#define VERIFIERS 4
+#define ROUNDS 25
+
+static char gov_pub[65], gov_priv[65], uni_pub[65], uni_priv[65], other_pub[65], other_priv[65];
+static char gov_proof[1024], uni_proof[1024];
+static volatile int writing = 1;
+
+static int prove(const char* priv, const char* pub, char* proof) {
+    char sig[129];
+    if (ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0) {
+        return -1;
+    }
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, NOW, 42, proof, 1024, NULL);
+}
+
+static int verify(const char* proof, char* name) {
+    name[0] = '\0';
+    return ZK_VerifyVCProofTrusted(proof, NOW, 42, name, 65);
+}
+
+/* Adds and removes "uni" and unrelated issuers until the verifiers finish */
+static void* writer(void* arg) {
+    long* updates = arg;
+    char name[16];
+    for (int i = 0; writing; i++) {
+        snprintf(name, sizeof(name), "other-%d", i % 8);
+        if (ZK_AddTrustedIssuer("uni", uni_pub) != 0 || ZK_AddTrustedIssuer(name, other_pub) != 0 ||
+            ZK_RemoveTrustedIssuer("uni") != 0 || ZK_RemoveTrustedIssuer(name) != 0) {
+            *updates = -1;
+            return NULL;
+        }
+        *updates += 4;
+    }
+    return NULL;
+}
+
+struct tally { int gov_failures, uni_valid, uni_invalid, uni_misnamed; };
+
+static void* verifier(void* arg) {
+    struct tally* tally = arg;
+    char name[65];
+    for (int i = 0; i < ROUNDS; i++) {
+        if (verify(gov_proof, name) != 1 || strcmp(name, "gov") != 0) {
+            tally->gov_failures++;
+        }
+        int rc = verify(uni_proof, name);
+        if (rc == 1 && strcmp(name, "uni") == 0) {
+            tally->uni_valid++;
+        } else if (rc == 0) {
+            tally->uni_invalid++;
+        } else {
+            tally->uni_misnamed++;
+        }
+    }
+    return NULL;
+}
+
+int main(void) {
+    char name[65];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(gov_pub, sizeof(gov_pub), gov_priv, sizeof(gov_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(uni_pub, sizeof(uni_pub), uni_priv, sizeof(uni_priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        prove(gov_priv, gov_pub, gov_proof) != 0 || prove(uni_priv, uni_pub, uni_proof) != 0) {
+        return 1;
+    }
+
+    /* Trusted issuers only, reported by name */
+    int untrusted = verify(gov_proof, name);
+    int bad_key = ZK_AddTrustedIssuer("gov", "00");
+    if (ZK_AddTrustedIssuer("gov", gov_pub) != 0 || ZK_AddTrustedIssuer("other", other_pub) != 0) {
+        return 1;
+    }
+    int gov = verify(gov_proof, name);
+    int named = strcmp(name, "gov") == 0;
+    int uni = verify(uni_proof, name);
+    int other_nonce = ZK_VerifyVCProofTrusted(gov_proof, NOW, 43, name, sizeof(name));
+    int small = ZK_VerifyVCProofTrusted(gov_proof, NOW, 42, name, 3);
+    printf("  registry: none trusted %d, bad key %d, gov proof %d (named %d), uni proof %d, other nonce %d, "
+           "small name buffer %d\n", untrusted, bad_key, gov, named, uni, other_nonce, small);
+    if (untrusted != 0 || bad_key != ZK_ERR_BAD_HEX || gov != 1 || !named || uni != 0 || other_nonce != 0 ||
+        small != ZK_ERR_BUFFER_TOO_SMALL) {
+        return 1;
+    }
+
+    /* Rebinding a name replaces its key; removing it stops the trust */
+    if (ZK_AddTrustedIssuer("gov", uni_pub) != 0) {
+        return 1;
+    }
+    int rebound_gov = verify(gov_proof, name);
+    int rebound_uni = verify(uni_proof, name);
+    int rebound_named = strcmp(name, "gov") == 0;
+    int removed = ZK_RemoveTrustedIssuer("gov");
+    int absent = ZK_RemoveTrustedIssuer("gov");
+    int after = verify(uni_proof, name);
+    printf("  rebind gov to uni's key: gov proof %d, uni proof %d (as gov %d); remove %d, again %d, uni proof %d\n",
+           rebound_gov, rebound_uni, rebound_named, removed, absent, after);
+    if (rebound_gov != 0 || rebound_uni != 1 || !rebound_named || removed != 0 || absent != 1 || after != 0) {
+        return 1;
+    }
+
+    /* Concurrent updates and verifications */
+    if (ZK_AddTrustedIssuer("gov", gov_pub) != 0) {
+        return 1;
+    }
+    pthread_t threads[VERIFIERS], update_thread;
+    struct tally tallies[VERIFIERS] = {{0}};
+    long updates = 0;
+    pthread_create(&update_thread, NULL, writer, &updates);
+    for (int i = 0; i < VERIFIERS; i++) {
+        pthread_create(&threads[i], NULL, verifier, &tallies[i]);
+    }
+    struct tally total = {0};
+    for (int i = 0; i < VERIFIERS; i++) {
+        pthread_join(threads[i], NULL);
+        total.gov_failures += tallies[i].gov_failures;
+        total.uni_valid += tallies[i].uni_valid;
+        total.uni_invalid += tallies[i].uni_invalid;
+        total.uni_misnamed += tallies[i].uni_misnamed;
+    }
+    writing = 0;
+    pthread_join(update_thread, NULL);
+    printf("  concurrent: %d verifications, %ld updates; gov failures %d, uni valid %d / invalid %d / other %d\n",
+           2 * VERIFIERS * ROUNDS, updates, total.gov_failures, total.uni_valid, total.uni_invalid,
+           total.uni_misnamed);
+    if (updates <= 0 || total.gov_failures != 0 || total.uni_misnamed != 0 ||
+        total.uni_valid + total.uni_invalid != VERIFIERS * ROUNDS) {
+        return 1;
+    }
+
+    /* The writer's last step removed uni */
+    int settled_uni = verify(uni_proof, name);
+    int settled_gov = verify(gov_proof, name);
+    printf("  after the writer: uni proof %d, gov proof %d\n", settled_uni, settled_gov);
+    return settled_uni != 0 || settled_gov != 1;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+
+echo "✓ Trusted issuers verify by name while the registry changes"
@@ -0,0 +1,132 @@
+#!/bin/bash
+#
+# Build the library for the host, with and without the "parallel" feature,
+# and check prover tuning: ZK_AutoTune records a probed decision that
+# ZK_ExportTuning and ZK_BuildInfo report, pinning the same decision again
+# applies while another thread count only records once the pool runs,
+# malformed or newer tunings are refused, and proofs still verify on the
+# tuned prover. Default builds always choose one thread.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_BuildInfo(char*, size_t);
+int ZK_AutoTune(char*, size_t);
+int ZK_ExportTuning(char*, size_t);
+int ZK_ImportTuning(const char*, char*, size_t);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_UNSUPPORTED_VERSION -18
+#define ZK_TUNING_NOT_APPLIED 1
+#define MAX_THREADS 64
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], encoded[8192], blob[8192], proof[4096];
+
+/* The unsigned number after `"key":` in `json`, or -1 */
+static long field(const char* json, const char* key) {
+    char pattern[64];
+    snprintf(pattern, sizeof(pattern), "\"%s\":", key);
+    const char* at = strstr(json, pattern);
+    return at == NULL ? -1 : strtol(at + strlen(pattern), NULL, 10);
+}
+
+int main(int argc, char** argv) {
+    int parallel = argc > 1 && strcmp(argv[1], "parallel") == 0;
+    char tuned[1024], exported[1024], info[4096], pinned[1024], json[256];
+    int before = ZK_ExportTuning(exported, sizeof(exported));
+    int info_before = ZK_BuildInfo(info, sizeof(info)) == 0 && strstr(info, "\"tuning\"") == NULL &&
+                      strstr(info, parallel ? "\"parallel\":true" : "\"parallel\":false") != NULL;
+    int short_buffer = ZK_AutoTune(tuned, 4);
+
+    /* Probe, then report the decision everywhere */
+    int probed = ZK_AutoTune(tuned, sizeof(tuned));
+    long threads = field(tuned, "threads"), cores = field(tuned, "cores");
+    int decision = strstr(tuned, "\"source\":\"probed\"") != NULL && strstr(tuned, "\"applied\":true") != NULL &&
+                   field(tuned, "version") == 1 && cores >= 1 && field(tuned, "field_mul_ns") >= 1 &&
+                   (parallel ? threads >= 1 && threads <= cores && threads <= MAX_THREADS : threads == 1);
+    int export_same = ZK_ExportTuning(exported, sizeof(exported)) == 0 && strcmp(exported, tuned) == 0;
+    int info_has = ZK_BuildInfo(info, sizeof(info)) == 0 && strstr(info, tuned) != NULL;
+    printf("  before tuning: export %d, build info %d; short buffer %d; probed %d: %s\n", before, info_before,
+           short_buffer, probed, tuned);
+    printf("  decision %d, exported %d, in build info %d\n", decision, export_same, info_has);
+    if (before != 1 || !info_before || short_buffer != ZK_ERR_BUFFER_TOO_SMALL || probed != 0 || !decision ||
+        !export_same || !info_has) {
+        return 1;
+    }
+
+    /* Pinning: the running pool keeps its size */
+    int same = ZK_ImportTuning(exported, pinned, sizeof(pinned));
+    int pinned_source = strstr(pinned, "\"source\":\"pinned\"") != NULL && field(pinned, "threads") == threads &&
+                        field(pinned, "cores") == cores;
+    snprintf(json, sizeof(json), "{\"version\":1,\"threads\":%ld}", threads % MAX_THREADS + 1);
+    int other = ZK_ImportTuning(json, pinned, sizeof(pinned));
+    int recorded = strstr(pinned, "\"applied\":false") != NULL && ZK_ExportTuning(exported, sizeof(exported)) == 0 &&
+                   strcmp(exported, pinned) == 0;
+    printf("  pinned again %d (pinned %d), %s: %d (recorded %d)\n", same, pinned_source, json, other, recorded);
+    if (same != 0 || !pinned_source || other != ZK_TUNING_NOT_APPLIED || !recorded) {
+        return 1;
+    }
+
+    int newer = ZK_ImportTuning("{\"version\":2,\"threads\":1}", NULL, 0);
+    int no_threads = ZK_ImportTuning("{\"version\":1,\"threads\":0}", NULL, 0);
+    int too_many = ZK_ImportTuning("{\"version\":1,\"threads\":65}", NULL, 0);
+    int not_json = ZK_ImportTuning("threads=4", NULL, 0);
+    int null_json = ZK_ImportTuning(NULL, NULL, 0);
+    printf("  newer %d, 0 threads %d, 65 threads %d, not JSON %d, NULL %d\n", newer, no_threads, too_many, not_json,
+           null_json);
+    if (newer != ZK_ERR_UNSUPPORTED_VERSION || no_threads != ZK_ERR_CORRUPT || too_many != ZK_ERR_CORRUPT ||
+        not_json != ZK_ERR_CORRUPT || null_json != -1) {
+        return 1;
+    }
+
+    /* The tuned prover */
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0) {
+        return 1;
+    }
+    int verified = ZK_VerifyVCProof(proof, pub, NOW, 7);
+    printf("  proof on the tuned prover verifies %d\n", verified);
+    return verified != 1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "Checking build: parallel"
+cargo rustc --release --lib --crate-type staticlib --features parallel --target-dir "$WORK_DIR/target-parallel" -q
+cc -o "$WORK_DIR/check-parallel" "$WORK_DIR/check.c" "$WORK_DIR/target-parallel/release/libzklib_vc.a" \
+    -lpthread -ldl -lm
+"$WORK_DIR/check-parallel" parallel || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Prover tuning is probed, reported and pinned, in serial and parallel builds"
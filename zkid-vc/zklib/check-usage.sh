@@ -0,0 +1,171 @@
+#!/bin/bash
+#
+# Build the library for the host and check key-usage separation: a status
+# attestation's signature grafted onto a credential blob or offered to the
+# field-based check does not verify, and a version 5 blob is not
+# checked over the bare message hash while the legacy version 4 vector
+# still verifies. Subkeys derived for each usage are stable, distinct from
+# each other and the master, and only verify what they signed.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_VerifyVCBlob(const char*, const char*, const char*);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t, size_t*);
+int ZK_VerifyVCSignature(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, const char*);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_IssueStatusAttestation(const char*, const char*, uint64_t, char*, size_t);
+int ZK_DeriveIssuerSubkey(const char*, int, char*, size_t, char*, size_t);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_KEY_USAGE_CREDENTIAL 1
+#define ZK_KEY_USAGE_STATUS 2
+#define ZK_KEY_USAGE_CLAIM_GROUP 3
+#define ZK_KEY_USAGE_HOLDER_REBIND 4
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+#define SIGNATURE_LEN 64
+
+/* conformance/vectors.json: vc-signature-003-legacy, signed over the bare message hash */
+static const char* legacy_pub = "e462f5de8e3736aba182678e3c71fcc4b5f4108a1a093dee95e756a026daf48f";
+static const char* legacy_blob =
+    "5a4b56430411000000616c696365406578616d706c652e636f6d120000006469643a6578616d706c653a69737375657200f15365000000"
+    "0000b33f71000000000200000004000000726f6c6508000000656e67696e6565720b0000007a6b69643a736368656d610b000000656d70"
+    "6c6f7965652f763140000000c5957645ddb3de0943c29817a828859d8060984e7769a7b5db2105c0b5d4d96c3601c33c371e3a1d6295bb"
+    "d22cac410cf96bcbd029fdeb634906be5853e7d405000000";
+
+static char pub[65], priv[65], encoded[8192], blob[8192], grafted[8192], status[1024], json[16384], id[65];
+static char signature[129], sub_pub[5][65], sub_priv[5][65], again_pub[65], again_priv[65];
+
+/* The u32 LE at hex offset `at` */
+static uint32_t u32_at(const char* hex, size_t at) {
+    uint32_t value = 0;
+    for (int i = 3; i >= 0; i--) {
+        unsigned int byte = 0;
+        sscanf(hex + at + 2 * i, "%2x", &byte);
+        value = value << 8 | byte;
+    }
+    return value;
+}
+
+/* Hex offset of a blob's signature: "ZKVC" | version | holder | issuer | dates | claims | signature */
+static size_t signature_at(const char* hex) {
+    size_t at = 2 * 5;
+    for (int field = 0; field < 2; field++) {
+        at += 8 + 2 * u32_at(hex, at);
+    }
+    at += 2 * 16;
+    uint32_t claims = u32_at(hex, at);
+    at += 8;
+    for (uint32_t i = 0; i < 2 * claims; i++) {
+        at += 8 + 2 * u32_at(hex, at);
+    }
+    return u32_at(hex, at) == SIGNATURE_LEN ? at + 8 : 0;
+}
+
+static int sign_blob(const char* key, char* out) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    return ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                       sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, key, out, 8192) == 0;
+}
+
+int main(void) {
+    size_t needed = 0;
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 || !sign_blob(priv, blob) ||
+        ZK_InspectArtifact(blob, strlen(blob), json, sizeof(json)) != 0 ||
+        strstr(json, "\"credential_id\":\"") == NULL ||
+        ZK_SignVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, priv, signature, sizeof(signature), &needed) != 0) {
+        return 1;
+    }
+    memcpy(id, strstr(json, "\"credential_id\":\"") + strlen("\"credential_id\":\""), 64);
+    if (ZK_IssueStatusAttestation(priv, id, NOW, status, sizeof(status)) != 0) {
+        return 1;
+    }
+
+    /* Cross-usage replay */
+    size_t at = signature_at(blob);
+    const char* attestation_signature = status + strlen(status) - 2 * SIGNATURE_LEN;
+    strcpy(grafted, blob);
+    memcpy(grafted + at, attestation_signature, 2 * SIGNATURE_LEN);
+    int original = ZK_VerifyVCBlob(blob, pub, NULL);
+    int status_as_credential = ZK_VerifyVCBlob(grafted, pub, NULL);
+    int fields = ZK_VerifyVCSignature("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, signature, pub);
+    int status_as_fields = ZK_VerifyVCSignature("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY,
+                                                attestation_signature, pub);
+    printf("  blob %d, with the status signature %d; fields %d, with the status signature %d\n", original,
+           status_as_credential, fields, status_as_fields);
+    if (at == 0 || original != 1 || status_as_credential != 0 || fields != 1 || status_as_fields != 0) {
+        return 1;
+    }
+
+    /* Legacy blobs verify over the bare hash, but only as version 4 */
+    int legacy = ZK_VerifyVCBlob(legacy_blob, legacy_pub, NULL);
+    strcpy(grafted, legacy_blob);
+    /* "ZKVC" | version */
+    memcpy(grafted + 8, "05", 2);
+    int relabeled = ZK_VerifyVCBlob(grafted, legacy_pub, NULL);
+    printf("  legacy v4 vector %d, its bare-hash signature as version 5 %d\n", legacy, relabeled);
+    if (legacy != 1 || relabeled != 0) {
+        return 1;
+    }
+
+    /* Subkeys: stable, one per usage, none equal to the master */
+    int derived = 1;
+    for (int usage = ZK_KEY_USAGE_CREDENTIAL; usage <= ZK_KEY_USAGE_HOLDER_REBIND; usage++) {
+        derived &= ZK_DeriveIssuerSubkey(priv, usage, sub_pub[usage], 65, sub_priv[usage], 65) == 0 &&
+                   strcmp(sub_pub[usage], pub) != 0;
+        for (int other = ZK_KEY_USAGE_CREDENTIAL; other < usage; other++) {
+            derived &= strcmp(sub_pub[usage], sub_pub[other]) != 0;
+        }
+    }
+    int stable = ZK_DeriveIssuerSubkey(priv, ZK_KEY_USAGE_STATUS, again_pub, sizeof(again_pub), again_priv,
+                                       sizeof(again_priv)) == 0 &&
+                 strcmp(again_pub, sub_pub[ZK_KEY_USAGE_STATUS]) == 0 &&
+                 strcmp(again_priv, sub_priv[ZK_KEY_USAGE_STATUS]) == 0;
+    int signed_sub = sign_blob(sub_priv[ZK_KEY_USAGE_CREDENTIAL], grafted);
+    int credential_key = ZK_VerifyVCBlob(grafted, sub_pub[ZK_KEY_USAGE_CREDENTIAL], NULL);
+    int status_key = ZK_VerifyVCBlob(grafted, sub_pub[ZK_KEY_USAGE_STATUS], NULL);
+    int master_key = ZK_VerifyVCBlob(grafted, pub, NULL);
+    printf("  subkeys distinct %d, stable %d; credential signed with its subkey %d: under it %d, "
+           "the status subkey %d, the master %d\n",
+           derived, stable, signed_sub, credential_key, status_key, master_key);
+    if (!derived || !stable || !signed_sub || credential_key != 1 || status_key != 0 || master_key != 0) {
+        return 1;
+    }
+
+    int unknown = ZK_DeriveIssuerSubkey(priv, 9, again_pub, sizeof(again_pub), again_priv, sizeof(again_priv));
+    int short_master = ZK_DeriveIssuerSubkey("abcd", ZK_KEY_USAGE_STATUS, again_pub, sizeof(again_pub), again_priv,
+                                             sizeof(again_priv));
+    int short_buffer = ZK_DeriveIssuerSubkey(priv, ZK_KEY_USAGE_STATUS, again_pub, 16, again_priv,
+                                             sizeof(again_priv));
+    int null_master = ZK_DeriveIssuerSubkey(NULL, ZK_KEY_USAGE_STATUS, again_pub, sizeof(again_pub), again_priv,
+                                            sizeof(again_priv));
+    printf("  unknown usage %d, short master %d, short buffer %d, NULL master %d\n", unknown, short_master,
+           short_buffer, null_master);
+    return unknown != -1 || short_master != -1 || short_buffer != ZK_ERR_BUFFER_TOO_SMALL || null_master != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Issuer signatures verify only for the usage they were made for"
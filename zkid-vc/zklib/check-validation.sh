@@ -0,0 +1,158 @@
+#!/bin/bash
+#
+# Build the library for the host and check native validation: ZK_ValidateVC
+# reports each selected check of a valid, wrongly keyed, expired, revoked
+# and not co-signed dual-control credential, without a proving key; the
+# prover refuses exactly the credentials that fail the checks it runs; a
+# context's trust store stands in for the issuer key; unselected checks are
+# skipped, a garbled blob fails every selected check, and bad arguments are
+# refused.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct Context Context;
+typedef struct {
+    int signature, time_policy, revocation, schema;
+    uint64_t revocation_epoch;
+} ZkValidationReport;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_SetDualControlSchema(const char*, size_t, int);
+int ZK_InspectArtifact(const char*, size_t, char*, size_t);
+int ZK_RevokeCredential(const char*, size_t, uint64_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_ValidateVC(const char*, size_t, const char*, const Context*, uint64_t, uint32_t, ZkValidationReport*);
+int ZK_ContextCreate(Context**);
+int ZK_ContextTrustIssuer(Context*, const char*);
+void ZK_ContextFree(Context*);
+
+#define ZK_ERR_COSIGNATURE_MISSING -12
+#define ZK_CHECK_SKIPPED 2
+#define ZK_VALIDATE_SIGNATURE 1
+#define ZK_VALIDATE_TIME 2
+#define ZK_VALIDATE_REVOCATION 4
+#define ZK_VALIDATE_SCHEMA 8
+#define ZK_VALIDATE_ALL 15
+#define PROVER_CHECKS (ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_TIME | ZK_VALIDATE_SCHEMA)
+#define S ZK_CHECK_SKIPPED
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65], other_pub[65], other_priv[65];
+static char valid[8192], revoked[8192], dual[8192], proof[4096];
+
+/* The credential_id ZK_InspectArtifact reports for `blob` */
+static int credential_id(const char* blob, char* out) {
+    static char json[16384];
+    const char* at = ZK_InspectArtifact(blob, strlen(blob), json, sizeof(json)) == 0
+                         ? strstr(json, "\"credential_id\":\"")
+                         : NULL;
+    if (at == NULL) {
+        return 0;
+    }
+    memcpy(out, at + strlen("\"credential_id\":\""), 64);
+    out[64] = '\0';
+    return 1;
+}
+
+static int issue(const char* holder, const char* schema, char* out) {
+    static char encoded[8192];
+    const char* keys[] = {"role", "zkid:schema"};
+    const char* values[] = {"engineer", schema};
+    return ZK_EncodeVC(holder, strlen(holder), "issuer", 6, NOW - DAY, NOW + DAY, keys, values, schema ? 2 : 1,
+                       NULL, encoded, sizeof(encoded)) == 0 &&
+           ZK_SignVCBlob(encoded, priv, out, 8192) == 0;
+}
+
+/* Validate and compare the return value and report with the expected ones */
+static int expect(const char* name, const char* blob, const char* key, const Context* ctx, uint64_t now,
+                  uint32_t checks, int result, int signature, int time_policy, int revocation, int schema) {
+    ZkValidationReport r = {0};
+    int rc = ZK_ValidateVC(blob, strlen(blob), key, ctx, now, checks, &r);
+    printf("  %s: %d (signature %d, time %d, revocation %d, schema %d)\n", name, rc, r.signature, r.time_policy,
+           r.revocation, r.schema);
+    return rc == result && r.signature == signature && r.time_policy == time_policy && r.revocation == revocation &&
+           r.schema == schema;
+}
+
+int main(void) {
+    char vc_id[65];
+    if (ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_GenerateIssuerKeypair(other_pub, sizeof(other_pub), other_priv, sizeof(other_priv)) != 0 ||
+        ZK_SetDualControlSchema("payroll", 7, 1) != 0 || !issue("alice", NULL, valid) ||
+        !issue("bob", NULL, revoked) || !issue("carol", "payroll", dual) || !credential_id(revoked, vc_id) ||
+        ZK_RevokeCredential(vc_id, strlen(vc_id), NOW) != 0) {
+        return 1;
+    }
+
+    /* Before ZK_Init: no proving key is needed */
+    if (!expect("valid", valid, pub, NULL, NOW, ZK_VALIDATE_ALL, 1, 1, 1, 1, 1) ||
+        !expect("other issuer key", valid, other_pub, NULL, NOW, ZK_VALIDATE_ALL, 0, 0, 1, 1, 1) ||
+        !expect("expired", valid, pub, NULL, NOW + 2 * DAY, ZK_VALIDATE_ALL, 0, 1, 0, 1, 1) ||
+        !expect("revoked", revoked, pub, NULL, NOW, ZK_VALIDATE_ALL, 0, 1, 1, 0, 1) ||
+        !expect("revoked, prover checks", revoked, pub, NULL, NOW, PROVER_CHECKS, 1, 1, 1, S, 1) ||
+        !expect("dual control, one signature", dual, pub, NULL, NOW, ZK_VALIDATE_ALL, 0, 1, 1, 1,
+                ZK_ERR_COSIGNATURE_MISSING) ||
+        !expect("other key, time only", valid, other_pub, NULL, NOW, ZK_VALIDATE_TIME, 1, S, 1, S, S) ||
+        !expect("time only, no key", valid, NULL, NULL, NOW, ZK_VALIDATE_TIME, 1, S, 1, S, S) ||
+        !expect("garbled", "5a4b5643ff", pub, NULL, NOW, ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_TIME, 0, 0, 0, S, S)) {
+        return 1;
+    }
+
+    /* The prover refuses exactly what fails its checks */
+    if (ZK_Init() != 0) {
+        return 1;
+    }
+    int proves_valid = ZK_GenerateVCProofFromBlob(valid, pub, NULL, NOW, 7, proof, sizeof(proof));
+    int proves_other = ZK_GenerateVCProofFromBlob(valid, other_pub, NULL, NOW, 7, proof, sizeof(proof));
+    int proves_expired = ZK_GenerateVCProofFromBlob(valid, pub, NULL, NOW + 2 * DAY, 7, proof, sizeof(proof));
+    int proves_dual = ZK_GenerateVCProofFromBlob(dual, pub, NULL, NOW, 7, proof, sizeof(proof));
+    printf("  prover: valid %d, other key %d, expired %d, dual control %d\n", proves_valid, proves_other,
+           proves_expired, proves_dual);
+    if (proves_valid != 0 || proves_other == 0 || proves_expired == 0 || proves_dual != ZK_ERR_COSIGNATURE_MISSING) {
+        return 1;
+    }
+
+    /* A context's trust store instead of a key */
+    Context* ctx = NULL;
+    if (ZK_ContextCreate(&ctx) != 0 || ZK_ContextTrustIssuer(ctx, other_pub) != 0 ||
+        !expect("untrusted in context", valid, NULL, ctx, NOW, ZK_VALIDATE_ALL, 0, 0, 1, 1, 1) ||
+        ZK_ContextTrustIssuer(ctx, pub) != 0 ||
+        !expect("trusted in context", valid, NULL, ctx, NOW, ZK_VALIDATE_ALL, 1, 1, 1, 1, 1)) {
+        return 1;
+    }
+    ZK_ContextFree(ctx);
+
+    ZkValidationReport r;
+    int unknown_bits = ZK_ValidateVC(valid, strlen(valid), pub, NULL, NOW, 16, &r);
+    int no_issuer = ZK_ValidateVC(valid, strlen(valid), NULL, NULL, NOW, ZK_VALIDATE_SIGNATURE, &r);
+    int null_report = ZK_ValidateVC(valid, strlen(valid), pub, NULL, NOW, ZK_VALIDATE_ALL, NULL);
+    int null_blob = ZK_ValidateVC(NULL, 0, pub, NULL, NOW, ZK_VALIDATE_ALL, &r);
+    printf("  unknown check bits %d, signature without issuer %d, NULL report %d, NULL blob %d\n", unknown_bits,
+           no_issuer, null_report, null_blob);
+    return unknown_bits != -1 || no_issuer != -1 || null_report != -1 || null_blob != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Native validation reports each check and agrees with the prover"
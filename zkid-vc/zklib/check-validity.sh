@@ -0,0 +1,164 @@
+#!/bin/bash
+#
+# Build the library for the host and check the VC circuit's validity window
+# at both ends: a credential signed for 100..200 proves and verifies at 100
+# and at 200, a proof verifies only at the time it was proven for, and the
+# prover refuses 99 and 201. With debug-circuit, a witness one second outside
+# the window, or with dates at the ends of the i64 range, is checked against
+# the window constraints directly, as a prover that skips its pre-check
+# would feed them. With fault-injection, such a prover proves the credential
+# at 250 under a window extended to 300, and ZK_VerifyVCProof, the batch
+# verifier and the trusted-issuer verifier each reject the proof, as the
+# issuer signed no anchor for that window.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_SignVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char*, char*, size_t,
+              size_t*);
+int ZK_GenerateVCProof(const char*, size_t, const char*, size_t, uint64_t, uint64_t,
+                       const char*, const char*, uint64_t, uint64_t, char*, size_t, size_t*);
+int ZK_VerifyVCProof(const char*, const char*, uint64_t, uint64_t);
+int ZK_CheckCircuitSatisfiability(uint8_t, const char*, const char*, uint64_t, int64_t, int64_t, uint64_t,
+                                  char*, size_t);
+int ZK_RunConstraintAudit(char*, size_t);
+int ZK_BatchVerifyVCProofs(const char* const*, const char* const*, uint64_t, const uint64_t*, size_t, int*);
+int ZK_AddTrustedIssuer(const char*, const char*);
+int ZK_VerifyVCProofTrusted(const char*, uint64_t, uint64_t, char*, size_t);
+void ZK_SkipProverChecks(int);
+
+#define ZK_ERR_VC_EXPIRED -4
+#define ZK_ERR_UNSATISFIED -19
+
+static char pub[65], priv[65], sig[129], proof[1024];
+
+static int prove(uint64_t current_time) {
+    return ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 200, sig, pub, current_time, 42,
+                              proof, sizeof(proof), NULL);
+}
+
+/* Prove at `at` and check the proof verifies there and one second either side */
+static int check_edge(uint64_t at) {
+    int rc = prove(at);
+    if (rc != 0) {
+        printf("  at %llu: prove %d\n", (unsigned long long)at, rc);
+        return 0;
+    }
+    int valid = ZK_VerifyVCProof(proof, pub, at, 42);
+    int before = ZK_VerifyVCProof(proof, pub, at - 1, 42);
+    int after = ZK_VerifyVCProof(proof, pub, at + 1, 42);
+    printf("  proven at %llu: valid %d, a second before %d, a second after %d\n", (unsigned long long)at,
+           valid, before, after);
+    return valid == 1 && before == 0 && after == 0;
+}
+
+int main(void) {
+    static char report[8192];
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_SignVC("alice", 5, "issuer", 6, 100, 200, priv, sig, sizeof(sig), NULL) != 0) {
+        return 1;
+    }
+
+    if (!check_edge(100) || !check_edge(150) || !check_edge(200)) {
+        return 1;
+    }
+    int early = prove(99);
+    int late = prove(201);
+    printf("  prove at 99: %d, at 201: %d\n", early, late);
+    if (early != ZK_ERR_VC_EXPIRED || late != ZK_ERR_VC_EXPIRED) {
+        return 1;
+    }
+
+#ifdef DEBUG_CIRCUIT
+    const char* hash = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+    struct { int64_t issue, expiry; uint64_t time; int expected; } witnesses[] = {
+        {100, 200, 99, ZK_ERR_UNSATISFIED},
+        {100, 200, 100, 0},
+        {100, 200, 200, 0},
+        {100, 200, 201, ZK_ERR_UNSATISFIED},
+        {200, 100, 150, ZK_ERR_UNSATISFIED},
+        {-5, 200, 0, 0},
+        {INT64_MIN, 0, 0, 0},
+        {INT64_MIN, 0, 1, ZK_ERR_UNSATISFIED},
+        {100, INT64_MAX, (uint64_t)INT64_MAX, 0},
+        {100, INT64_MAX, (uint64_t)INT64_MAX + 1, ZK_ERR_UNSATISFIED},
+        {100, INT64_MAX, UINT64_MAX, ZK_ERR_UNSATISFIED},
+    };
+    for (size_t i = 0; i < sizeof(witnesses) / sizeof(witnesses[0]); i++) {
+        report[0] = '\0';
+        int rc = ZK_CheckCircuitSatisfiability(2, hash, pub, 42, witnesses[i].issue, witnesses[i].expiry,
+                                               witnesses[i].time, report, sizeof(report));
+        printf("  witness %lld..%lld at %llu: %d %s\n", (long long)witnesses[i].issue,
+               (long long)witnesses[i].expiry, (unsigned long long)witnesses[i].time, rc, report);
+        if (rc != witnesses[i].expected || (rc != 0 && strstr(report, "range_") == NULL)) {
+            return 1;
+        }
+    }
+
+    int gaps = ZK_RunConstraintAudit(report, sizeof(report));
+    printf("  constraint audit: %d gaps\n", gaps);
+    if (gaps != 0) {
+        return 1;
+    }
+#else
+    (void)report;
+#endif
+
+#ifdef FAULT_INJECTION
+    /* Expired at 250, proven for a window it was not signed for */
+    ZK_SkipProverChecks(1);
+    int forged = ZK_GenerateVCProof("alice", 5, "issuer", 6, 100, 300, sig, pub, 250, 42, proof, sizeof(proof),
+                                    NULL);
+    const char* proofs[] = {proof};
+    const char* issuers[] = {pub};
+    uint64_t nonces[] = {42};
+    int results[1] = {-1};
+    int direct = forged == 0 ? ZK_VerifyVCProof(proof, pub, 250, 42) : -1;
+    int batch = forged == 0 ? ZK_BatchVerifyVCProofs(proofs, issuers, 250, nonces, 1, results) : -1;
+    int trusted = forged == 0 && ZK_AddTrustedIssuer("hr", pub) == 0
+                      ? ZK_VerifyVCProofTrusted(proof, 250, 42, NULL, 0)
+                      : -1;
+    printf("  window extended to 300, proven at 250: %d; direct %d, batch %d (result %d), trusted %d\n", forged,
+           direct, batch, results[0], trusted);
+    if (forged != 0 || direct != 0 || batch != 0 || results[0] != 0 || trusted != 0) {
+        return 1;
+    }
+
+    /* The signed window still proves through the same prover */
+    int honest = prove(150) == 0 ? ZK_VerifyVCProofTrusted(proof, 150, 42, NULL, 0) : -1;
+    printf("  signed window at 150: trusted %d\n", honest);
+    if (honest != 1) {
+        return 1;
+    }
+#endif
+    return 0;
+}
+EOF
+
+check_build() {
+    local name="$1" features="$2" defines="$3"
+    echo "Checking build: $name"
+    cargo rustc --release --lib --crate-type staticlib $features --target-dir "$WORK_DIR/target-$name" -q
+    cc $defines -o "$WORK_DIR/check-$name" "$WORK_DIR/check.c" "$WORK_DIR/target-$name/release/libzklib_vc.a" \
+        -lpthread -ldl -lm
+    "$WORK_DIR/check-$name" || { echo "  FAIL"; exit 1; }
+    echo "  ok"
+}
+
+check_build standard ""
+check_build debug-circuit "--features debug-circuit" "-DDEBUG_CIRCUIT"
+check_build fault-injection "--features fault-injection" "-DFAULT_INJECTION"
+
+echo "✓ VC proofs hold at both ends of the validity window and nowhere outside"
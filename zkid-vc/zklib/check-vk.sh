@@ -0,0 +1,143 @@
+#!/bin/bash
+#
+# Build the library for the host and check the two verifying key forms: the
+# prepared form derived from the exported raw key gives back exactly that
+# raw key, which a context imports and verifies proofs with; a prepared key
+# whose precomputed elements belong to another key is a mismatch, and
+# truncated or padded keys are corrupt. Both outputs report their length
+# when the buffer is too small.
+
+set -e
+
+SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$SCRIPT_DIR"
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+cat > "$WORK_DIR/check.c" << 'EOF'
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct Context Context;
+
+int ZK_Init(void);
+int ZK_GenerateIssuerKeypair(char*, size_t, char*, size_t);
+int ZK_EncodeVC(const char*, size_t, const char*, size_t, uint64_t, uint64_t, const char* const*,
+                const char* const*, size_t, const char*, char*, size_t);
+int ZK_SignVCBlob(const char*, const char*, char*, size_t);
+int ZK_GenerateVCProofFromBlob(const char*, const char*, const char*, uint64_t, uint64_t, char*, size_t);
+int ZK_ExportVerifyingKey(char*, size_t);
+int ZK_PrepareVerifyingKey(const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+int ZK_ExtractVerifyingKey(const uint8_t*, size_t, uint8_t*, size_t, size_t*);
+int ZK_VerifyingKeyPublicInputs(const uint8_t*, size_t);
+int ZK_ContextCreate(Context**);
+int ZK_ContextInit(Context*);
+int ZK_ContextExportVerifyingKey(const Context*, char*, size_t);
+int ZK_ContextImportVerifyingKey(Context*, const char*);
+int ZK_ContextVerifyVCProof(const Context*, const char*, const char*, uint64_t, uint64_t);
+void ZK_ContextFree(Context*);
+
+#define ZK_ERR_BUFFER_TOO_SMALL -5
+#define ZK_ERR_CORRUPT -14
+#define ZK_ERR_KEY_MISMATCH -17
+#define NOW 1700000000ULL
+#define DAY 86400ULL
+
+static char pub[65], priv[65];
+static char encoded[8192], blob[8192], proof[4096], hex[16384], other_hex[16384];
+static uint8_t raw[8192], other_raw[8192], prepared[65536], other_prepared[65536], hybrid[65536], back[8192];
+
+static size_t unhex(const char* in, uint8_t* out) {
+    size_t len = strlen(in) / 2;
+    for (size_t i = 0; i < len; i++) {
+        unsigned int byte;
+        sscanf(in + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return len;
+}
+
+static void tohex(const uint8_t* in, size_t len, char* out) {
+    for (size_t i = 0; i < len; i++) {
+        sprintf(out + 2 * i, "%02x", in[i]);
+    }
+    out[2 * len] = '\0';
+}
+
+int main(void) {
+    const char* keys[] = {"role"};
+    const char* values[] = {"engineer"};
+    Context *other = NULL, *imported = NULL;
+    if (ZK_Init() != 0 || ZK_GenerateIssuerKeypair(pub, sizeof(pub), priv, sizeof(priv)) != 0 ||
+        ZK_EncodeVC("alice", 5, "issuer", 6, NOW - DAY, NOW + DAY, keys, values, 1, NULL, encoded,
+                    sizeof(encoded)) != 0 ||
+        ZK_SignVCBlob(encoded, priv, blob, sizeof(blob)) != 0 ||
+        ZK_GenerateVCProofFromBlob(blob, pub, NULL, NOW, 7, proof, sizeof(proof)) != 0 ||
+        ZK_ExportVerifyingKey(hex, sizeof(hex)) != 0 || ZK_ContextCreate(&other) != 0 ||
+        ZK_ContextInit(other) != 0 || ZK_ContextExportVerifyingKey(other, other_hex, sizeof(other_hex)) != 0) {
+        return 1;
+    }
+    size_t raw_len = unhex(hex, raw), other_raw_len = unhex(other_hex, other_raw);
+
+    /* Raw -> prepared -> raw, sizing each buffer with a first call */
+    size_t prepared_len = 0, back_len = 0, other_prepared_len = 0;
+    int sized = ZK_PrepareVerifyingKey(raw, raw_len, prepared, 16, &prepared_len);
+    int prepare = ZK_PrepareVerifyingKey(raw, raw_len, prepared, prepared_len, &prepared_len);
+    int sized_back = ZK_ExtractVerifyingKey(prepared, prepared_len, back, 16, &back_len);
+    int extract = ZK_ExtractVerifyingKey(prepared, prepared_len, back, back_len, &back_len);
+    int inputs = ZK_VerifyingKeyPublicInputs(back, back_len);
+    printf("  raw %zu bytes, prepared %zu (short buffer %d, %d), extracted %d, identical %d, public inputs %d\n",
+           raw_len, prepared_len, sized, sized_back, extract, back_len == raw_len && memcmp(back, raw, raw_len) == 0,
+           inputs);
+    if (sized != ZK_ERR_BUFFER_TOO_SMALL || prepare != 0 || sized_back != ZK_ERR_BUFFER_TOO_SMALL || extract != 0 ||
+        back_len != raw_len || memcmp(back, raw, raw_len) != 0 || prepared_len <= raw_len || inputs < 2) {
+        return 1;
+    }
+
+    /* The recovered raw key verifies proofs in a context of its own */
+    tohex(back, back_len, hex);
+    int import = ZK_ContextCreate(&imported) == 0 ? ZK_ContextImportVerifyingKey(imported, hex) : -100;
+    int verified = ZK_ContextVerifyVCProof(imported, proof, pub, NOW, 7);
+    int other_key = ZK_ContextVerifyVCProof(other, proof, pub, NOW, 7);
+    printf("  recovered key imported %d, verifies %d; another setup's key %d\n", import, verified, other_key);
+    if (import != 0 || verified != 1 || other_key != 0) {
+        return 1;
+    }
+
+    /* This key's raw part with the other key's precomputed elements */
+    if (other_raw_len != raw_len ||
+        ZK_PrepareVerifyingKey(other_raw, other_raw_len, other_prepared, sizeof(other_prepared),
+                               &other_prepared_len) != 0 ||
+        other_prepared_len != prepared_len) {
+        return 1;
+    }
+    memcpy(hybrid, raw, raw_len);
+    memcpy(hybrid + raw_len, other_prepared + raw_len, prepared_len - raw_len);
+    int mismatch = ZK_ExtractVerifyingKey(hybrid, prepared_len, back, sizeof(back), &back_len);
+    int truncated_prepared = ZK_ExtractVerifyingKey(prepared, prepared_len - 1, back, sizeof(back), &back_len);
+    int truncated_raw = ZK_PrepareVerifyingKey(raw, raw_len - 1, prepared, sizeof(prepared), &prepared_len);
+    raw[raw_len] = 0;
+    int padded_raw = ZK_PrepareVerifyingKey(raw, raw_len + 1, prepared, sizeof(prepared), &prepared_len);
+    int truncated_inputs = ZK_VerifyingKeyPublicInputs(raw, raw_len - 1);
+    int null_key = ZK_PrepareVerifyingKey(NULL, raw_len, prepared, sizeof(prepared), &prepared_len);
+    printf("  other key's precomputation %d; truncated prepared %d, raw %d; padded raw %d; public inputs of a "
+           "truncated key %d; NULL %d\n",
+           mismatch, truncated_prepared, truncated_raw, padded_raw, truncated_inputs, null_key);
+
+    ZK_ContextFree(imported);
+    ZK_ContextFree(other);
+    return mismatch != ZK_ERR_KEY_MISMATCH || truncated_prepared != ZK_ERR_CORRUPT ||
+           truncated_raw != ZK_ERR_CORRUPT || padded_raw != ZK_ERR_CORRUPT || truncated_inputs != ZK_ERR_CORRUPT ||
+           null_key != -1;
+}
+EOF
+
+echo "Checking build: default"
+cargo rustc --release --lib --crate-type staticlib --target-dir "$WORK_DIR/target" -q
+cc -o "$WORK_DIR/check" "$WORK_DIR/check.c" "$WORK_DIR/target/release/libzklib_vc.a" -lpthread -ldl -lm
+"$WORK_DIR/check" || { echo "  FAIL"; exit 1; }
+echo "  ok"
+
+echo "✓ Raw and prepared verifying keys convert into each other and refuse foreign precomputation"
@@ -0,0 +1,113 @@
+//! Worst-case input benchmark for the admission stages
+//!
+//!     cargo run --release --example admission_budget
+//!
+//! Feeds ZK_PrecheckProof the most expensive input each stage refuses and
+//! checks it answers with that stage's code, then times the verify entry
+//! points on a MAX_BLOB_LEN input (16 MiB of proof hex, and a presentation
+//! blob of the same size). Refusing those must stay under
+//! ADMISSION_BUDGET_US (median of the runs); exit status 1 otherwise, or if
+//! a stage answered with the wrong code.
+
+use std::ffi::CString;
+use std::hint::black_box;
+use std::os::raw::c_int;
+use std::process;
+use std::time::Instant;
+
+use zklib_vc::admission::{
+    ZK_GetAdmissionStats, ZK_PrecheckProof, ADMISSION_BUDGET_US, MAX_PROOF_HEX_LEN, MAX_PROOF_LEN,
+    ZK_STAGE_CANONICAL, ZK_STAGE_DESERIALIZE, ZK_STAGE_ENCODING, ZK_STAGE_LENGTH, ZK_STAGE_PAIRING, ZK_STAGE_VERSION,
+};
+use zklib_vc::error::{ZK_ERR_CORRUPT, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_INVALID_POINT, ZK_ERR_NON_CANONICAL};
+use zklib_vc::presentation::ZK_VerifyPresentation;
+use zklib_vc::validity::VALIDITY_PREFIX_LEN;
+use zklib_vc::{ZK_Init, ZK_VerifyVCProof};
+
+/// Input size of the budget check, MAX_BLOB_LEN of the C API
+const MAX_INPUT: usize = 16 * 1024 * 1024;
+const RUNS: usize = 101;
+
+/// Hex of an enveloped format-2 proof header
+const ENVELOPE: &str = "5a5002";
+
+/// Median time of `f` in microseconds
+fn median_us(mut f: impl FnMut() -> c_int) -> f64 {
+    let mut times: Vec<f64> = (0..RUNS)
+        .map(|_| {
+            let started = Instant::now();
+            black_box(f());
+            started.elapsed().as_secs_f64() * 1e6
+        })
+        .collect();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times[RUNS / 2]
+}
+
+fn main() {
+    if ZK_Init() != 0 {
+        eprintln!("admission_budget: ZK_Init failed");
+        process::exit(2);
+    }
+    let issuer = CString::new("00".repeat(32)).expect("no NUL");
+    let mut failed = false;
+
+    // The costliest input each stage refuses: everything before it passes
+    let body = |byte: &str| format!("{ENVELOPE}{}", byte.repeat(128));
+    // ZK_PrecheckProof also takes VC proofs behind their validity prefix
+    let longest = 2 * (VALIDITY_PREFIX_LEN + MAX_PROOF_LEN);
+    let mut encoding = body("00");
+    encoding.replace_range(encoding.len() - 1.., "g");
+    let stages = [
+        ("length", ZK_STAGE_LENGTH, "0".repeat(longest + 1), ZK_ERR_INPUT_TOO_LARGE),
+        ("version", ZK_STAGE_VERSION, "0".repeat(MAX_PROOF_HEX_LEN - 2), ZK_ERR_CORRUPT),
+        ("encoding", ZK_STAGE_ENCODING, encoding, ZK_ERR_CORRUPT),
+        ("canonical", ZK_STAGE_CANONICAL, body("ff"), ZK_ERR_NON_CANONICAL),
+        ("deserialize", ZK_STAGE_DESERIALIZE, body("01"), ZK_ERR_INVALID_POINT),
+    ];
+    for (name, stage, input, expected) in stages {
+        let input = CString::new(input).expect("no NUL");
+        let mut before = 0;
+        ZK_GetAdmissionStats(stage, &mut before);
+        let code = ZK_PrecheckProof(input.as_ptr());
+        let mut after = 0;
+        ZK_GetAdmissionStats(stage, &mut after);
+
+        let us = median_us(|| ZK_PrecheckProof(input.as_ptr()));
+        let ok = code == expected && after == before + 1;
+        failed |= !ok;
+        println!(
+            "{:<12} code {:>4} (expected {:>4}) {:>9.2} us{}",
+            name,
+            code,
+            expected,
+            us,
+            if ok { "" } else { "  FAIL" }
+        );
+    }
+
+    // Maximum-size inputs through the full verify entry points
+    let oversized = CString::new("0".repeat(MAX_INPUT - 1)).expect("no NUL");
+    let proof_us = median_us(|| ZK_VerifyVCProof(oversized.as_ptr(), issuer.as_ptr(), 0, 1));
+    let presentation_us =
+        median_us(|| ZK_VerifyPresentation(oversized.as_ptr(), MAX_INPUT - 1));
+    for (name, us) in [("ZK_VerifyVCProof", proof_us), ("ZK_VerifyPresentation", presentation_us)] {
+        let ok = us < ADMISSION_BUDGET_US as f64;
+        failed |= !ok;
+        println!(
+            "{:<22} 16 MiB refused in {:>7.2} us (budget {} us){}",
+            name,
+            us,
+            ADMISSION_BUDGET_US,
+            if ok { "" } else { "  FAIL" }
+        );
+    }
+
+    let mut pairing = 0;
+    ZK_GetAdmissionStats(ZK_STAGE_PAIRING, &mut pairing);
+    println!("pairing refusals {}", pairing);
+
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,159 @@
+//! Auto-tuned against hand-tuned prover settings
+//!
+//!     cargo run --release --features parallel --example autotune_bench
+//!
+//! rayon sizes its pool once per process, so every setting runs in a child
+//! process of this example: once with ZK_AutoTune, once pinned with
+//! ZK_ImportTuning to each hand-picked thread count (1, 2, 4, ... up to the
+//! core count, and the core count itself). Each child times RUNS VC proofs
+//! and prints the median. The auto-tuned median must be within
+//! AUTOTUNE_MARGIN of the fastest hand-picked one; exit status 1 otherwise.
+//! Without "parallel" every setting proves single-threaded and the run only
+//! checks that tuning costs nothing.
+
+use std::env;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::process::{self, Command};
+use std::time::Instant;
+
+use zklib_vc::tuning::{ZK_AutoTune, ZK_ImportTuning, TUNING_VERSION};
+use zklib_vc::{ZK_GenerateIssuerKeypair, ZK_GenerateVCProof, ZK_Init, ZK_SignVC};
+
+/// Largest slowdown of the auto-tuned setting against the best hand-picked
+const AUTOTUNE_MARGIN: f64 = 0.10;
+const RUNS: usize = 9;
+
+const NOW: u64 = 1_790_000_000;
+const DAY: u64 = 86_400;
+const HOLDER: &str = "did:example:alice";
+const ISSUER: &str = "did:example:plant-operator";
+
+fn fail(what: &str) -> ! {
+    eprintln!("autotune_bench: {}", what);
+    process::exit(2);
+}
+
+fn c_text(buf: &[u8]) -> String {
+    CStr::from_bytes_until_nul(buf)
+        .ok()
+        .and_then(|s| s.to_str().ok())
+        .unwrap_or_else(|| fail("unterminated output"))
+        .to_string()
+}
+
+/// Tune as `setting` says ("auto" or a thread count), then print the
+/// median prove time in milliseconds and the tuning JSON
+fn child(setting: &str) {
+    let mut tuning = [0u8; 512];
+    let rc = match setting {
+        "auto" => ZK_AutoTune(tuning.as_mut_ptr() as *mut c_char, tuning.len()),
+        threads => {
+            let pinned = format!("{{\"version\":{},\"threads\":{}}}", TUNING_VERSION, threads);
+            let pinned = CString::new(pinned).unwrap_or_else(|_| fail("interior NUL"));
+            ZK_ImportTuning(pinned.as_ptr(), tuning.as_mut_ptr() as *mut c_char, tuning.len())
+        }
+    };
+    if rc != 0 {
+        fail(&format!("tuning {} returned {}", setting, rc));
+    }
+    if ZK_Init() != 0 {
+        fail("ZK_Init failed");
+    }
+
+    let mut public_key = [0u8; 65];
+    let mut private_key = [0u8; 65];
+    let mut signature = [0u8; 129];
+    let call = |what: &str, rc: c_int| {
+        if rc != 0 {
+            fail(&format!("{} returned {}", what, rc));
+        }
+    };
+    call(
+        "ZK_GenerateIssuerKeypair",
+        ZK_GenerateIssuerKeypair(
+            public_key.as_mut_ptr() as *mut c_char,
+            public_key.len(),
+            private_key.as_mut_ptr() as *mut c_char,
+            private_key.len(),
+        ),
+    );
+    call(
+        "ZK_SignVC",
+        ZK_SignVC(
+            HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+            ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+            NOW - DAY, NOW + 365 * DAY,
+            private_key.as_ptr() as *const c_char,
+            signature.as_mut_ptr() as *mut c_char, signature.len(),
+            std::ptr::null_mut(),
+        ),
+    );
+
+    let mut proof = [0u8; 1024];
+    let mut times: Vec<f64> = (0..RUNS as u64)
+        .map(|nonce| {
+            let started = Instant::now();
+            call(
+                "ZK_GenerateVCProof",
+                ZK_GenerateVCProof(
+                    HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+                    ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+                    NOW - DAY, NOW + 365 * DAY,
+                    signature.as_ptr() as *const c_char,
+                    public_key.as_ptr() as *const c_char,
+                    NOW, nonce,
+                    proof.as_mut_ptr() as *mut c_char, proof.len(),
+                    std::ptr::null_mut(),
+                ),
+            );
+            started.elapsed().as_secs_f64() * 1e3
+        })
+        .collect();
+    times.sort_by(|a, b| a.total_cmp(b));
+    println!("{} {}", times[RUNS / 2], c_text(&tuning));
+}
+
+/// Run a child for `setting`: median ms and its tuning JSON
+fn run(setting: &str) -> (f64, String) {
+    let exe = env::current_exe().unwrap_or_else(|_| fail("current_exe"));
+    let output = Command::new(exe)
+        .args(["--child", setting])
+        .output()
+        .unwrap_or_else(|_| fail("cannot start child"));
+    if !output.status.success() {
+        fail(&format!("child {} failed: {}", setting, String::from_utf8_lossy(&output.stderr)));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (ms, tuning) = stdout.trim().split_once(' ').unwrap_or_else(|| fail("child output"));
+    (ms.parse().unwrap_or_else(|_| fail("child median")), tuning.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if let [_, flag, setting] = args.as_slice() {
+        if flag == "--child" {
+            child(setting);
+            return;
+        }
+    }
+
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = (0..).map(|i| 1 << i).take_while(|&n| n < cores).collect();
+    counts.push(cores);
+
+    let (auto_ms, tuning) = run("auto");
+    println!("auto         {:>9.1} ms  {}", auto_ms, tuning);
+    let mut best = f64::INFINITY;
+    for threads in counts {
+        let (ms, _) = run(&threads.to_string());
+        println!("{:>2} threads   {:>9.1} ms", threads, ms);
+        best = best.min(ms);
+    }
+
+    let margin = auto_ms / best - 1.0;
+    println!("auto-tuned is {:+.1}% against the best hand-picked setting (margin {:.0}%)", margin * 100.0, AUTOTUNE_MARGIN * 100.0);
+    if margin > AUTOTUNE_MARGIN {
+        process::exit(1);
+    }
+}
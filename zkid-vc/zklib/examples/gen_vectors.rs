@@ -0,0 +1,28 @@
+//! Write the conformance vectors to stdout (or the path given as argument)
+//!
+//!     cargo run --example gen_vectors -- conformance/vectors.json
+//!
+//! Proofs are deterministic (ZK_SetDeterministicProving), so rerunning it
+//! only changes the file when a layout or the keys change.
+
+use zklib_vc::conformance::generate_vectors;
+#[cfg(not(feature = "strict"))]
+use zklib_vc::entropy::ZK_SetDeterministicProving;
+use zklib_vc::ZK_Init;
+
+fn main() {
+    if ZK_Init() != 0 {
+        eprintln!("ZK_Init failed");
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "strict"))]
+    ZK_SetDeterministicProving(1);
+
+    let vectors = generate_vectors().expect("keys are initialized");
+    let json = serde_json::to_string_pretty(&vectors).expect("vectors serialize") + "\n";
+
+    match std::env::args().nth(1) {
+        Some(path) => std::fs::write(&path, json).expect("write vectors file"),
+        None => print!("{}", json),
+    }
+}
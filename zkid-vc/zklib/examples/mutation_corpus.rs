@@ -0,0 +1,115 @@
+//! Semantic mutation corpus runner
+//!
+//!     cargo run --release --features mutation-corpus --example mutation_corpus -- \
+//!         [--seed N] [--rounds R] [--only NAME] [--case-seed S] [--out DIR]
+//!
+//! Applies every mutation of the standard catalogue to `rounds` generated
+//! cases (default 8) and checks each is rejected with its expected error
+//! class. The run seed defaults to the current time and is printed first;
+//! every failure prints the case seed and the command that replays it.
+//! `--only` restricts the run to one mutation, `--case-seed` (with
+//! `--only`) replays a single case, and `--out` writes each mutated case
+//! (vc blob and presentation) for use as a parser or integration corpus.
+//! Exit status 0 if every mutation was rejected as expected, 1 otherwise.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zklib_vc::mutation::{case_seed, run_case, Catalogue, Corpus};
+use zklib_vc::ZK_Init;
+
+fn fail(what: &str) -> ! {
+    eprintln!("mutation_corpus: {}", what);
+    process::exit(2);
+}
+
+fn parse_u64(value: Option<String>) -> u64 {
+    let value = value.unwrap_or_else(|| fail("missing value"));
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.unwrap_or_else(|_| fail(&format!("not a number: {}", value)))
+}
+
+fn main() {
+    let mut run_seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut rounds = 8u32;
+    let mut only = None;
+    let mut single = None;
+    let mut out = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => run_seed = parse_u64(args.next()),
+            "--rounds" => rounds = parse_u64(args.next()) as u32,
+            "--only" => only = Some(args.next().unwrap_or_else(|| fail("missing value"))),
+            "--case-seed" => single = Some(parse_u64(args.next())),
+            "--out" => out = Some(PathBuf::from(args.next().unwrap_or_else(|| fail("missing value")))),
+            _ => fail(&format!("unknown argument {}", arg)),
+        }
+    }
+
+    let catalogue = Catalogue::standard();
+    let mutations = match &only {
+        Some(name) => vec![*catalogue.get(name).unwrap_or_else(|| fail(&format!("no mutation {}", name)))],
+        None => catalogue.mutations().to_vec(),
+    };
+    if single.is_some() && only.is_none() {
+        fail("--case-seed needs --only");
+    }
+
+    if ZK_Init() != 0 {
+        fail("ZK_Init failed");
+    }
+    let corpus = Corpus::new().unwrap_or_else(|| fail("could not prove the base presentations"));
+    println!("run seed {}", run_seed);
+
+    let mut failures = 0;
+    for mutation in &mutations {
+        let seeds: Vec<u64> = match single {
+            Some(seed) => vec![seed],
+            None => (0..rounds).map(|round| case_seed(run_seed, mutation.name, round)).collect(),
+        };
+        let (mut rejected, mut skipped) = (0, 0);
+        for seed in seeds {
+            let (result, case) = run_case(&corpus, mutation, seed);
+            if !result.passed() {
+                failures += 1;
+                println!("FAIL {}", result);
+                println!(
+                    "     replay: cargo run --features mutation-corpus --example mutation_corpus -- --only {} --case-seed {:#x}",
+                    mutation.name, seed
+                );
+            } else if result.got.is_none() {
+                skipped += 1;
+            } else {
+                rejected += 1;
+            }
+
+            if let (Some(dir), Some(case)) = (&out, case) {
+                let dir = dir.join(mutation.name);
+                fs::create_dir_all(&dir).unwrap_or_else(|e| fail(&format!("{}: {}", dir.display(), e)));
+                for (suffix, bytes) in [("vc", &case.vc_blob), ("presentation", &case.presentation)] {
+                    let path = dir.join(format!("{:016x}.{}", seed, suffix));
+                    fs::write(&path, bytes).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+                }
+            }
+        }
+        println!(
+            "{:<28} expected {:<10} rejected {} skipped {}",
+            mutation.name,
+            mutation.expected.to_string(),
+            rejected,
+            skipped
+        );
+    }
+
+    if failures > 0 {
+        println!("{} case(s) not rejected as expected", failures);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,223 @@
+//! Reference gateway, the verifier side of the end-to-end flow that
+//! check-reference-flow.sh runs against reference_issuer over files
+//!
+//!     cargo run --example reference_gateway -- challenge <dir> <audience>
+//!     cargo run --example reference_gateway -- verify <dir>
+//!
+//! The gateway is a verifier-only integrator: it never calls ZK_Init and
+//! takes its key from the issuer's vk.hex. Its challenges and nullifiers
+//! persist across runs through a Storage backend of one file per key under
+//! <dir>/gateway-state.
+//!
+//! `challenge` stores a fresh challenge and writes challenge.txt
+//! ("<nonce> <audience>") for the wallet.
+//!
+//! `verify` applies the issuer's revocations.txt, checks presentation.hex
+//! against the disclosed presented.hex with the policy "issuer.pub signed it,
+//! it is current, not revoked and meant for our audience", spends the
+//! challenge and records the credential as a nullifier in the audience
+//! scope, so each credential enters once. Exit status 0 if admitted, 1 if
+//! refused.
+
+use std::ffi::CString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+
+use rand_core::{OsRng, RngCore};
+use zklib_vc::challenge::{ZK_ConsumeChallenge, ZK_IssueChallenge};
+use zklib_vc::nullifier::ZK_RecordNullifier;
+use zklib_vc::presentation::{Presentation, ZkVerifyReport, ZK_VerifyPresentationDetailed};
+use zklib_vc::revocation::{RevocationEntry, ZK_ApplyRevocationEntry};
+use zklib_vc::storage::{set_storage, Storage, StorageEntries, StorageError};
+use zklib_vc::{vk, VerifiableCredential, ZK_ImportVerifyingKey};
+
+/// Time the reference flow runs at, shared with reference_issuer
+const NOW: u64 = 1_790_000_000;
+
+/// How long the wallet has to answer a challenge (seconds)
+const CHALLENGE_TTL: u64 = 300;
+
+fn fail(what: &str) -> ! {
+    eprintln!("reference_gateway: {}", what);
+    process::exit(2);
+}
+
+fn refuse(what: &str) -> ! {
+    println!("refused: {}", what);
+    process::exit(1);
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s).unwrap_or_else(|_| fail("interior NUL"))
+}
+
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name))
+        .unwrap_or_else(|e| fail(&format!("read {}: {}", name, e)))
+        .trim()
+        .to_string()
+}
+
+fn decode_hex(name: &str, text: &str) -> Vec<u8> {
+    hex::decode(text).unwrap_or_else(|_| fail(&format!("{} is not hex", name)))
+}
+
+/// Storage backend keeping each key in its own file, named by the key in hex
+struct DirStorage {
+    dir: PathBuf,
+}
+
+impl DirStorage {
+    fn path(&self, key: &[u8]) -> PathBuf {
+        self.dir.join(hex::encode(key))
+    }
+}
+
+impl Storage for DirStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        match fs::read(self.path(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(_) => Err(StorageError::Backend),
+        }
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        fs::write(self.path(key), value).map_err(|_| StorageError::Backend)
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), StorageError> {
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(StorageError::Backend),
+            _ => Ok(()),
+        }
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<StorageEntries, StorageError> {
+        let mut entries = StorageEntries::new();
+        for file in fs::read_dir(&self.dir).map_err(|_| StorageError::Backend)? {
+            let file = file.map_err(|_| StorageError::Backend)?;
+            let key = match file.file_name().to_str().and_then(|name| hex::decode(name).ok()) {
+                Some(key) if key.starts_with(prefix) => key,
+                _ => continue,
+            };
+            entries.push((key, fs::read(file.path()).map_err(|_| StorageError::Backend)?));
+        }
+        entries.sort();
+        Ok(entries)
+    }
+}
+
+fn challenge(dir: &Path, audience: &str) {
+    let nonce = OsRng.next_u64();
+    if ZK_IssueChallenge(nonce, NOW + CHALLENGE_TTL) != 0 {
+        fail("ZK_IssueChallenge failed");
+    }
+    fs::write(dir.join("challenge.txt"), format!("{} {}\n", nonce, audience))
+        .unwrap_or_else(|e| fail(&format!("write challenge.txt: {}", e)));
+    println!("challenge {} for {}", nonce, audience);
+}
+
+fn verify(dir: &Path) {
+    let vk_hex = read(dir, "vk.hex");
+    match vk::decode_raw(&decode_hex("vk.hex", &vk_hex)) {
+        Ok(key) if hex::encode(vk::encode(&key)) == vk_hex => {}
+        _ => fail("vk.hex does not round-trip"),
+    }
+    if ZK_ImportVerifyingKey(cstring(&vk_hex).as_ptr()) != 0 {
+        fail("ZK_ImportVerifyingKey failed");
+    }
+
+    for line in read(dir, "revocations.txt").lines() {
+        let bytes = decode_hex("revocations.txt", line);
+        if RevocationEntry::from_bytes(&bytes).map(|e| e.to_bytes()) != Some(bytes) {
+            fail("revocation entry does not round-trip");
+        }
+        if ZK_ApplyRevocationEntry(cstring(line).as_ptr()) != 0 {
+            fail("ZK_ApplyRevocationEntry failed");
+        }
+    }
+
+    let challenge = read(dir, "challenge.txt");
+    let (nonce, audience) = match challenge.split_once(' ') {
+        Some((nonce, audience)) => (nonce.parse::<u64>().unwrap_or_else(|_| fail("challenge nonce")), audience),
+        None => fail("challenge.txt is not \"<nonce> <audience>\""),
+    };
+
+    let presentation_hex = read(dir, "presentation.hex");
+    let presentation = match Presentation::from_wire(presentation_hex.as_bytes()) {
+        Some(p) if hex::encode(p.to_bytes()) == presentation_hex => p,
+        _ => fail("presentation.hex does not round-trip"),
+    };
+    let vc_hex = read(dir, "presented.hex");
+    let vc_bytes = decode_hex("presented.hex", &vc_hex);
+    let vc = match VerifiableCredential::from_bytes(&vc_bytes) {
+        Some(vc) if vc.to_bytes() == vc_bytes => vc,
+        _ => fail("presented.hex does not round-trip"),
+    };
+
+    // The proof is bound to the presentation's nonce; only our outstanding
+    // challenge counts, and it is spent whatever the outcome
+    if presentation.nonce != nonce {
+        refuse("presentation answers another challenge");
+    }
+    match ZK_ConsumeChallenge(nonce, NOW) {
+        1 => {}
+        0 => refuse("challenge unknown, expired or already used"),
+        rc => fail(&format!("ZK_ConsumeChallenge returned {}", rc)),
+    }
+
+    let vc_c = cstring(&vc_hex);
+    let issuer_c = cstring(&read(dir, "issuer.pub"));
+    let audience_c = cstring(audience);
+    let mut report = ZkVerifyReport::default();
+    let accepted = ZK_VerifyPresentationDetailed(
+        presentation_hex.as_ptr() as *const _,
+        presentation_hex.len(),
+        vc_c.as_ptr(),
+        issuer_c.as_ptr(),
+        std::ptr::null(),
+        audience_c.as_ptr(),
+        NOW,
+        &mut report,
+    );
+    println!(
+        "signature {} proof {} time {} revocation {} audience {}",
+        report.signature, report.proof, report.time_policy, report.revocation, report.audience
+    );
+    if accepted != 1 {
+        refuse("policy check failed");
+    }
+
+    let credential_id = vc.credential_id();
+    match ZK_RecordNullifier(
+        audience.as_ptr() as *const _,
+        audience.len(),
+        credential_id.as_ptr() as *const _,
+        credential_id.len(),
+    ) {
+        1 => println!("admitted {}", credential_id),
+        0 => refuse("credential already used in this scope"),
+        rc => fail(&format!("ZK_RecordNullifier returned {}", rc)),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (command, dir) = match (args.get(1), args.get(2)) {
+        (Some(command), Some(dir)) => (command.as_str(), Path::new(dir)),
+        _ => fail("usage: reference_gateway challenge <dir> <audience> | verify <dir>"),
+    };
+
+    let state = dir.join("gateway-state");
+    fs::create_dir_all(&state).unwrap_or_else(|e| fail(&format!("create gateway-state: {}", e)));
+    set_storage(Arc::new(DirStorage { dir: state }));
+
+    match (command, args.get(3)) {
+        ("challenge", Some(audience)) => challenge(dir, audience),
+        ("verify", None) => verify(dir),
+        _ => fail("unknown command"),
+    }
+}
@@ -0,0 +1,247 @@
+//! Reference issuer and wallet, the holder side of the end-to-end flow that
+//! check-reference-flow.sh runs against reference_gateway over files
+//!
+//!     cargo run --example reference_issuer -- issue <dir>
+//!     cargo run --example reference_issuer -- present <dir> [credential file]
+//!
+//! `issue` generates the issuer keys, issues a credential with claims,
+//! reissues it with an updated claim and publishes into <dir>:
+//!
+//!   issuer.pub          issuer Ed25519 public key (hex)
+//!   vk.hex              VC verifying key (ZK_ExportVerifyingKey)
+//!   credential.hex      current VC blob, handed to the wallet
+//!   superseded.hex      the VC blob it replaced
+//!   revocations.txt     revocation entries (ZK_ReissueVC), one per line
+//!
+//! `present` is the wallet: it answers the challenge the gateway wrote to
+//! challenge.txt ("<nonce> <audience>") with presentation.hex, bound to the
+//! nonce and audience, and discloses the credential it used as
+//! presented.hex (credential.hex unless another file is given).
+//!
+//! Every artifact is decoded again after it is written and must re-encode
+//! to the same bytes.
+
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::os::raw::{c_char, c_int};
+use std::path::Path;
+use std::process;
+
+use zklib_vc::credential::{ZK_EncodeVC, ZK_GenerateVCProofFromBlob, ZK_ReissueVC, ZK_SignVCBlob};
+use zklib_vc::presentation::{Presentation, ZK_EncodePresentation};
+use zklib_vc::revocation::RevocationEntry;
+use zklib_vc::{vk, VerifiableCredential, ZK_ExportVerifyingKey, ZK_GenerateIssuerKeypair, ZK_Init};
+
+/// Time the reference flow runs at (2026-09-21T12:26:40Z), shared with
+/// reference_gateway
+const NOW: u64 = 1_790_000_000;
+const DAY: u64 = 86_400;
+
+const HOLDER: &str = "did:example:alice";
+const ISSUER: &str = "did:example:plant-operator";
+
+fn fail(what: &str) -> ! {
+    eprintln!("reference_issuer: {}", what);
+    process::exit(2);
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s).unwrap_or_else(|_| fail("interior NUL"))
+}
+
+/// Call an FFI function that writes a C string into a caller buffer
+fn out_string(what: &str, size: usize, f: impl FnOnce(*mut c_char, usize) -> c_int) -> String {
+    let mut buf = vec![0u8; size];
+    let rc = f(buf.as_mut_ptr() as *mut c_char, buf.len());
+    if rc != 0 {
+        fail(&format!("{} returned {}", what, rc));
+    }
+    c_text(&buf, what)
+}
+
+/// Text of a NUL-terminated output buffer
+fn c_text(buf: &[u8], what: &str) -> String {
+    CStr::from_bytes_until_nul(buf)
+        .ok()
+        .and_then(|s| s.to_str().ok())
+        .unwrap_or_else(|| fail(what))
+        .to_string()
+}
+
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name))
+        .unwrap_or_else(|e| fail(&format!("read {}: {}", name, e)))
+        .trim()
+        .to_string()
+}
+
+fn write(dir: &Path, name: &str, contents: &str) {
+    fs::write(dir.join(name), format!("{}\n", contents))
+        .unwrap_or_else(|e| fail(&format!("write {}: {}", name, e)));
+}
+
+fn decode_hex(name: &str, text: &str) -> Vec<u8> {
+    hex::decode(text).unwrap_or_else(|_| fail(&format!("{} is not hex", name)))
+}
+
+/// Check that a hex VC blob decodes and re-encodes unchanged
+fn round_trip_vc(name: &str, blob_hex: &str) -> VerifiableCredential {
+    let bytes = decode_hex(name, blob_hex);
+    match VerifiableCredential::from_bytes(&bytes) {
+        Some(vc) if vc.to_bytes() == bytes => vc,
+        _ => fail(&format!("{} does not round-trip", name)),
+    }
+}
+
+/// C arrays of claim keys and values; the CStrings own the pointed-to text
+struct Claims {
+    _owned: Vec<CString>,
+    keys: Vec<*const c_char>,
+    values: Vec<*const c_char>,
+}
+
+impl Claims {
+    fn new(claims: &[(&str, &str)]) -> Self {
+        let owned: Vec<CString> = claims.iter().flat_map(|(k, v)| [cstring(k), cstring(v)]).collect();
+        Self {
+            keys: owned.iter().step_by(2).map(|k| k.as_ptr()).collect(),
+            values: owned.iter().skip(1).step_by(2).map(|v| v.as_ptr()).collect(),
+            _owned: owned,
+        }
+    }
+}
+
+fn issue_vc(claims: &[(&str, &str)], private_key: &CStr) -> String {
+    let claims = Claims::new(claims);
+    let unsigned = out_string("ZK_EncodeVC", 4096, |out, size| {
+        ZK_EncodeVC(
+            HOLDER.as_ptr() as *const c_char, HOLDER.len(),
+            ISSUER.as_ptr() as *const c_char, ISSUER.len(),
+            NOW - DAY, NOW + 365 * DAY,
+            claims.keys.as_ptr(), claims.values.as_ptr(), claims.keys.len(),
+            std::ptr::null(),
+            out, size,
+        )
+    });
+    let unsigned = cstring(&unsigned);
+    out_string("ZK_SignVCBlob", 4096, |out, size| {
+        ZK_SignVCBlob(unsigned.as_ptr(), private_key.as_ptr(), out, size)
+    })
+}
+
+fn issue(dir: &Path) {
+    let mut public_key = [0u8; 65];
+    let mut private_key = [0u8; 65];
+    if ZK_GenerateIssuerKeypair(
+        public_key.as_mut_ptr() as *mut c_char,
+        public_key.len(),
+        private_key.as_mut_ptr() as *mut c_char,
+        private_key.len(),
+    ) != 0
+    {
+        fail("ZK_GenerateIssuerKeypair failed");
+    }
+    let public_key = c_text(&public_key, "issuer public key");
+    let private_key = CStr::from_bytes_until_nul(&private_key).unwrap_or_else(|_| fail("issuer private key"));
+
+    let vk_hex = out_string("ZK_ExportVerifyingKey", 4096, |out, size| ZK_ExportVerifyingKey(out, size));
+    match vk::decode_raw(&decode_hex("vk.hex", &vk_hex)) {
+        Ok(key) if hex::encode(vk::encode(&key)) == vk_hex => {}
+        _ => fail("vk.hex does not round-trip"),
+    }
+
+    let first = issue_vc(&[("role", "operator"), ("site", "plant-3")], private_key);
+    let superseded = round_trip_vc("superseded.hex", &first);
+
+    // The promotion replaces the credential; the old one is revoked through
+    // the published entry
+    let first_c = cstring(&first);
+    let claims = Claims::new(&[("role", "supervisor"), ("site", "plant-3")]);
+    let mut entry = [0u8; 1024];
+    let current = out_string("ZK_ReissueVC", 4096, |out, size| {
+        ZK_ReissueVC(
+            first_c.as_ptr(),
+            claims.keys.as_ptr(), claims.values.as_ptr(), claims.keys.len(),
+            NOW, NOW + 365 * DAY,
+            private_key.as_ptr(),
+            out, size,
+            entry.as_mut_ptr() as *mut c_char, entry.len(),
+        )
+    });
+    let entry = c_text(&entry, "revocation entry");
+
+    let vc = round_trip_vc("credential.hex", &current);
+    let entry_bytes = decode_hex("revocations.txt", &entry);
+    match RevocationEntry::from_bytes(&entry_bytes) {
+        Some(e) if e.to_bytes() == entry_bytes
+            && e.credential_id == superseded.credential_id()
+            && e.superseded_by == Some(vc.credential_id()) => {}
+        _ => fail("revocation entry does not round-trip"),
+    }
+
+    write(dir, "issuer.pub", &public_key);
+    write(dir, "vk.hex", &vk_hex);
+    write(dir, "credential.hex", &current);
+    write(dir, "superseded.hex", &first);
+    write(dir, "revocations.txt", &entry);
+    println!("issued {} (supersedes {})", vc.credential_id(), superseded.credential_id());
+}
+
+fn present(dir: &Path, credential: &str) {
+    let challenge = read(dir, "challenge.txt");
+    let (nonce, audience) = match challenge.split_once(' ') {
+        Some((nonce, audience)) => (
+            nonce.parse::<u64>().unwrap_or_else(|_| fail("challenge nonce")),
+            audience.to_string(),
+        ),
+        None => fail("challenge.txt is not \"<nonce> <audience>\""),
+    };
+
+    let blob = read(dir, credential);
+    round_trip_vc(credential, &blob);
+    let blob_c = cstring(&blob);
+    let public_key = cstring(&read(dir, "issuer.pub"));
+
+    let proof = out_string("ZK_GenerateVCProofFromBlob", 4096, |out, size| {
+        ZK_GenerateVCProofFromBlob(blob_c.as_ptr(), public_key.as_ptr(), std::ptr::null(), NOW, nonce, out, size)
+    });
+
+    let proof_c = cstring(&proof);
+    let key = cstring("audience");
+    let value = cstring(&audience);
+    let presentation = out_string("ZK_EncodePresentation", 8192, |out, size| {
+        ZK_EncodePresentation(
+            proof_c.as_ptr(), public_key.as_ptr(), nonce,
+            &key.as_ptr(), &value.as_ptr(), 1,
+            out, size,
+        )
+    });
+
+    let decoded = Presentation::from_wire(presentation.as_bytes());
+    match decoded {
+        Some(p) if hex::encode(p.to_bytes()) == presentation && p.nonce == nonce => {}
+        _ => fail("presentation does not round-trip"),
+    }
+
+    write(dir, "presentation.hex", &presentation);
+    write(dir, "presented.hex", &blob);
+    println!("presented {} for nonce {} to {}", credential, nonce, audience);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (command, dir) = match (args.get(1), args.get(2)) {
+        (Some(command), Some(dir)) => (command.as_str(), Path::new(dir)),
+        _ => fail("usage: reference_issuer issue|present <dir> [credential file]"),
+    };
+
+    if ZK_Init() != 0 {
+        fail("ZK_Init failed");
+    }
+
+    match command {
+        "issue" => issue(dir),
+        "present" => present(dir, args.get(3).map_or("credential.hex", String::as_str)),
+        _ => fail("unknown command"),
+    }
+}
@@ -0,0 +1,356 @@
+// ============================================================================
+// Bounded Input Admission
+// ============================================================================
+//
+// Verification entry points take proofs as hex text of caller-chosen
+// length. Reading and hex-decoding 10 MB of "proof" used to cost more than
+// the pairing it never reached, so proofs now pass through stages ordered
+// by cost, each refusing before the next is paid for:
+//
+//   stage        refuses                                   code
+//   length       text longer than any proof encoding       ZK_ERR_INPUT_TOO_LARGE
+//   version      layout or format unknown, compiled out,   ZK_ERR_CORRUPT,
+//                or not accepted (from the header)         ZK_ERR_DISABLED,
+//                                                          ZK_ERR_UNSUPPORTED_VERSION
+//   encoding     a non-hex character                       ZK_ERR_CORRUPT
+//   canonical    a coordinate >= p or invalid flag bits    ZK_ERR_NON_CANONICAL
+//   deserialize  a point off the curve or subgroup         ZK_ERR_INVALID_POINT
+//   pairing      a proof that does not verify              0
+//
+// The length stage scans at most MAX_PROOF_HEX_LEN + 1 bytes of the
+// argument (2 * VALIDITY_PREFIX_LEN more where a VC proof's validity prefix
+// is allowed), so its cost does not depend on the input size; decoding
+// goes into a fixed buffer. Presentations are capped at MAX_PRESENTATION_LEN
+// before their hex is decoded and their proof field takes the stages from
+// version on.
+//
+// The 1/0 verify entry points keep their results: version codes pass
+// through, every other structural refusal reads 0. ZK_PrecheckProof runs
+// the stages before the pairing and returns the refusing stage's code, and
+// ZK_GetAdmissionStats counts refusals per stage, so operators can see
+// where rejected traffic is stopped.
+
+use ark_bn254::{Bn254, Fq};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Proof;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::cbor::PRESENTATION_CBOR_HEAD;
+use crate::encoding::{PointEncoding, ZkDeserialize, FIELD_ELEMENT_LEN};
+use crate::error::{
+    ZK_ERR_CORRUPT, ZK_ERR_DISABLED, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_INVALID_POINT, ZK_ERR_NON_CANONICAL,
+    ZK_ERR_UNSUPPORTED_VERSION,
+};
+use crate::ffi::{read_cstr, FfiError};
+use crate::proof::{self, ENVELOPE_HEADER_LEN, ENVELOPE_MAGIC, PROOF_FORMAT_LEGACY};
+use crate::validity::{self, Header, VALIDITY_PREFIX_LEN};
+use crate::wire::PRESENTATION_MAGIC;
+
+/// Admission stages, in the order they run
+pub const ZK_STAGE_LENGTH: c_int = 0;
+pub const ZK_STAGE_VERSION: c_int = 1;
+pub const ZK_STAGE_ENCODING: c_int = 2;
+pub const ZK_STAGE_CANONICAL: c_int = 3;
+pub const ZK_STAGE_DESERIALIZE: c_int = 4;
+pub const ZK_STAGE_PAIRING: c_int = 5;
+
+/// Longest proof encoding: an enveloped proof with uncompressed points
+pub const MAX_PROOF_LEN: usize = ENVELOPE_HEADER_LEN + 256;
+/// Longest proof argument in hex
+pub const MAX_PROOF_HEX_LEN: usize = 2 * MAX_PROOF_LEN;
+
+/// Longest binary presentation; its hex text may be twice as long
+pub const MAX_PRESENTATION_LEN: usize = 64 * 1024;
+
+/// Time a release build may take to refuse a maximum-size invalid input
+/// (MAX_BLOB_LEN bytes of proof hex), checked by examples/admission_budget.rs
+pub const ADMISSION_BUDGET_US: u64 = 5;
+
+const FLAG_BITS: u8 = 0xc0;
+
+static REJECTED: [AtomicU64; 6] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+/// A stage refused the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    pub stage: c_int,
+    pub code: c_int,
+}
+
+impl Rejection {
+    /// Count the refusal against its stage
+    fn new(stage: c_int, code: c_int) -> Self {
+        REJECTED[stage as usize].fetch_add(1, Ordering::Relaxed);
+        Self { stage, code }
+    }
+
+    /// Why the proof was refused, for ZK_GetLastError
+    pub fn describe(self) -> &'static str {
+        match self.stage {
+            ZK_STAGE_LENGTH => "proof_hex is NULL or longer than any proof",
+            ZK_STAGE_VERSION => "proof format version is not accepted by this verifier",
+            ZK_STAGE_ENCODING => "proof_hex is not hex of a proof",
+            ZK_STAGE_CANONICAL => "proof has a non-canonical field element",
+            ZK_STAGE_DESERIALIZE => "proof has a point off the curve or outside the subgroup",
+            _ => "proof was refused",
+        }
+    }
+
+    /// Result of a 1/0 verify entry point refusing at this stage
+    pub fn verify_code(self) -> c_int {
+        match self.code {
+            ZK_ERR_DISABLED | ZK_ERR_UNSUPPORTED_VERSION => self.code,
+            _ => 0,
+        }
+    }
+}
+
+/// Count the outcome of a pairing check (called by proof::record_outcome)
+pub(crate) fn record_pairing(valid: bool) {
+    if !valid {
+        REJECTED[ZK_STAGE_PAIRING as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Proofs refused at `stage` since start-up
+pub fn rejected(stage: c_int) -> Option<u64> {
+    let index = usize::try_from(stage).ok()?;
+    REJECTED.get(index).map(|count| count.load(Ordering::Relaxed))
+}
+
+/// Format of a proof encoding of `len` bytes starting with `head`
+///
+/// Only the envelope header is needed, so the hex path can run this
+/// before decoding the rest.
+fn identify(head: [u8; ENVELOPE_HEADER_LEN], len: usize, accepted: Option<u32>) -> Result<u8, Rejection> {
+    let version = |code| Rejection::new(ZK_STAGE_VERSION, code);
+    let (envelope, encoding) = proof::envelope_format(head[2]);
+    let format = if len == proof::bare_len(PointEncoding::Compressed) || len == proof::bare_len(PointEncoding::Uncompressed) {
+        PROOF_FORMAT_LEGACY
+    } else if head.starts_with(ENVELOPE_MAGIC) && proof::encoded_len(envelope, encoding) == Some(len) {
+        envelope
+    } else if head.starts_with(ENVELOPE_MAGIC)
+        && len > ENVELOPE_HEADER_LEN
+        && proof::encoded_len(envelope, encoding).is_none()
+    {
+        return Err(version(ZK_ERR_UNSUPPORTED_VERSION));
+    } else {
+        return Err(version(ZK_ERR_CORRUPT));
+    };
+
+    if !proof::format_supported(format) {
+        return Err(version(ZK_ERR_DISABLED));
+    }
+    if let Some(accepted) = accepted {
+        proof::admit(format, accepted).map_err(version)?;
+    }
+    Ok(format)
+}
+
+fn below_modulus(element: &[u8]) -> bool {
+    let modulus = Fq::MODULUS.to_bytes_le();
+    // Little-endian: compare from the most significant byte
+    element.iter().rev().cmp(modulus.iter().rev()).is_lt()
+}
+
+/// Whether every coordinate of the bare proof `body` is a canonical field
+/// element with valid flags
+///
+/// A proof is A (G1) | B (G2) | C (G1); each point's last coordinate
+/// carries the flag bits in its top byte.
+pub(crate) fn canonical(body: &[u8]) -> bool {
+    let g1 = if body.len() == proof::bare_len(PointEncoding::Compressed) { 32 } else { 64 };
+    let mut rest = body;
+    for point_len in [g1, 2 * g1, g1] {
+        let (point, tail) = rest.split_at(point_len);
+        rest = tail;
+        let (coordinates, flagged) = point.split_at(point_len - FIELD_ELEMENT_LEN);
+        let mut last = [0u8; FIELD_ELEMENT_LEN];
+        last.copy_from_slice(flagged);
+        if last[FIELD_ELEMENT_LEN - 1] & FLAG_BITS == FLAG_BITS {
+            return false;
+        }
+        last[FIELD_ELEMENT_LEN - 1] &= !FLAG_BITS;
+        if !coordinates.chunks(FIELD_ELEMENT_LEN).all(below_modulus) || !below_modulus(&last) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Stages from canonical on, for a proof of known `format`
+fn finish(format: u8, bytes: &[u8]) -> Result<(u8, Proof<Bn254>), Rejection> {
+    let body = if format == PROOF_FORMAT_LEGACY { bytes } else { &bytes[ENVELOPE_HEADER_LEN..] };
+    if !canonical(body) {
+        return Err(Rejection::new(ZK_STAGE_CANONICAL, ZK_ERR_NON_CANONICAL));
+    }
+    let encoding = if body.len() == proof::bare_len(PointEncoding::Uncompressed) {
+        PointEncoding::Uncompressed
+    } else {
+        PointEncoding::Compressed
+    };
+    Proof::<Bn254>::zk_from_bytes(body, encoding)
+        .map(|proof| (format, proof))
+        .ok_or_else(|| Rejection::new(ZK_STAGE_DESERIALIZE, ZK_ERR_INVALID_POINT))
+}
+
+/// Admit a binary proof (e.g. a presentation's proof field)
+///
+/// `accepted` applies an accepted-formats policy at the version stage;
+/// None leaves it to the caller.
+pub fn decode_proof(bytes: &[u8], accepted: Option<u32>) -> Result<(u8, Proof<Bn254>), Rejection> {
+    if bytes.len() > MAX_PROOF_LEN {
+        return Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE));
+    }
+    let mut head = [0u8; ENVELOPE_HEADER_LEN];
+    let n = bytes.len().min(ENVELOPE_HEADER_LEN);
+    head[..n].copy_from_slice(&bytes[..n]);
+    let format = identify(head, bytes.len(), accepted)?;
+    finish(format, bytes)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn hex_byte(pair: &[u8]) -> Option<u8> {
+    Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?)
+}
+
+/// Admit hex proof text
+pub fn decode_proof_hex(text: &[u8], accepted: Option<u32>) -> Result<(u8, Proof<Bn254>), Rejection> {
+    if text.len() > MAX_PROOF_HEX_LEN {
+        return Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE));
+    }
+    if !text.len().is_multiple_of(2) {
+        return Err(Rejection::new(ZK_STAGE_VERSION, ZK_ERR_CORRUPT));
+    }
+
+    // The header decides the format before the body is decoded
+    let mut head = [0u8; ENVELOPE_HEADER_LEN];
+    for (byte, pair) in head.iter_mut().zip(text.chunks(2)) {
+        *byte = hex_byte(pair).ok_or_else(|| Rejection::new(ZK_STAGE_ENCODING, ZK_ERR_CORRUPT))?;
+    }
+    let len = text.len() / 2;
+    let format = identify(head, len, accepted)?;
+
+    let mut buf = [0u8; MAX_PROOF_LEN];
+    for (byte, pair) in buf[..len].iter_mut().zip(text.chunks(2)) {
+        *byte = hex_byte(pair).ok_or_else(|| Rejection::new(ZK_STAGE_ENCODING, ZK_ERR_CORRUPT))?;
+    }
+    finish(format, &buf[..len])
+}
+
+/// Read and admit a hex proof argument of a C entry point
+pub fn proof_arg(proof_hex: *const c_char, accepted: u32) -> Result<(u8, Proof<Bn254>), Rejection> {
+    match read_cstr(proof_hex, MAX_PROOF_HEX_LEN) {
+        Ok(text) => decode_proof_hex(text, Some(accepted)),
+        Err(FfiError::TooLong) => Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE)),
+        Err(_) => Err(Rejection { stage: ZK_STAGE_LENGTH, code: -1 }),
+    }
+}
+
+/// Read and decode a hex argument of at most `max` bytes through the length
+/// and encoding stages, for proofs carried inside a longer encoding
+pub fn hex_arg(ptr: *const c_char, max: usize) -> Result<Vec<u8>, Rejection> {
+    let text = match read_cstr(ptr, 2 * max) {
+        Ok(text) => text,
+        Err(FfiError::TooLong) => return Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE)),
+        Err(_) => return Err(Rejection { stage: ZK_STAGE_LENGTH, code: -1 }),
+    };
+    text.chunks(2)
+        .map(|pair| if pair.len() == 2 { hex_byte(pair) } else { None })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| Rejection::new(ZK_STAGE_ENCODING, ZK_ERR_CORRUPT))
+}
+
+/// Admit a binary VC proof: its header (see validity.rs), then the proof
+/// through every stage
+pub fn decode_vc_proof(bytes: &[u8], accepted: Option<u32>) -> Result<(Header, u8, Proof<Bn254>), Rejection> {
+    if bytes.len() > VALIDITY_PREFIX_LEN + MAX_PROOF_LEN {
+        return Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE));
+    }
+    let (header, inner) = validity::untag(bytes).ok_or_else(|| Rejection::new(ZK_STAGE_VERSION, ZK_ERR_CORRUPT))?;
+    let (format, proof) = decode_proof(inner, accepted)?;
+    Ok((header, format, proof))
+}
+
+/// Read and admit a hex VC proof argument of a C entry point
+pub fn vc_proof_arg(proof_hex: *const c_char, accepted: u32) -> Result<(Header, u8, Proof<Bn254>), Rejection> {
+    decode_vc_proof(&hex_arg(proof_hex, VALIDITY_PREFIX_LEN + MAX_PROOF_LEN)?, Some(accepted))
+}
+
+/// Read a text argument of a C entry point, scanning at most `max` bytes
+pub(crate) fn text_arg<'a>(ptr: *const c_char, max: usize) -> Option<&'a str> {
+    read_cstr(ptr, max).ok().and_then(|bytes| std::str::from_utf8(bytes).ok())
+}
+
+/// Whether a presentation (binary, CBOR or hex text) is short enough to
+/// decode
+pub fn admit_presentation(data: &[u8]) -> Result<(), Rejection> {
+    let max = if data.starts_with(PRESENTATION_MAGIC) || data.first() == Some(&PRESENTATION_CBOR_HEAD) {
+        MAX_PRESENTATION_LEN
+    } else {
+        2 * MAX_PRESENTATION_LEN
+    };
+    if data.len() > max {
+        return Err(Rejection::new(ZK_STAGE_LENGTH, ZK_ERR_INPUT_TOO_LARGE));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Run a hex proof through every admission stage before the pairing
+///
+/// A VC proof's validity prefix is skipped. Applies the global
+/// accepted-formats policy. Returns 1 if the proof is well-formed, else the
+/// code of the refusing stage (see above), or -1 for NULL.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_PrecheckProof(proof_hex: *const c_char) -> c_int {
+    let bytes = match hex_arg(proof_hex, VALIDITY_PREFIX_LEN + MAX_PROOF_LEN) {
+        Ok(bytes) => bytes,
+        Err(rejection) => return rejection.code,
+    };
+    let inner = validity::untag(&bytes).map_or(&bytes[..], |(_, inner)| inner);
+    match decode_proof(inner, Some(proof::accepted_formats())) {
+        Ok(_) => 1,
+        Err(rejection) => rejection.code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read how many inputs a stage (ZK_STAGE_*) has refused since start-up
+///
+/// ZK_STAGE_PAIRING counts proofs that were well-formed but did not verify.
+/// Returns 0 on success, -1 for an unknown stage or NULL.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_GetAdmissionStats(stage: c_int, rejected_out: *mut u64) -> c_int {
+    match rejected(stage) {
+        Some(count) if !rejected_out.is_null() => {
+            unsafe { *rejected_out = count };
+            0
+        }
+        _ => -1,
+    }
+}
+}
@@ -0,0 +1,492 @@
+// ============================================================================
+// Derived Age (age threshold proven from a birthdate claim)
+// ============================================================================
+//
+// Credentials store a birthdate; verifiers ask "at least N years old today".
+// The holder cannot be trusted to compute the age, so the comparison happens
+// in-circuit: the birthdate claim (signed unix seconds, canonical decimal)
+// is a private witness, and the circuit enforces
+//
+//   current_time - birthdate >= threshold_seconds
+//
+// over the integers. Both the birthdate (shifted by 2^63) and the difference
+// minus the threshold are decomposed into bits, so a birthdate after
+// current_time - threshold, including one in the future, has no witness:
+// the field subtraction cannot wrap into a small positive value.
+//
+// Years are calendar years in UTC. The verifier derives threshold_seconds
+// from current_time and N so that the comparison means "born on or before
+// the same calendar day N years earlier": the cutoff is the end (23:59:59)
+// of that day, and threshold_seconds = current_time - cutoff. Age therefore
+// increments at 00:00 UTC on the birthday, whatever the time of birth. When
+// the current day is 29 February and the day N years earlier does not exist,
+// 28 February is used, so someone born on 29 February turns a year older on
+// 1 March in common years.
+//
+// Which claim holds the birthdate is part of the statement: the claim key
+// enters as a public input, so a proof over another date claim does not
+// verify. The birthdate is the credential's signed claim because the
+// circuit opens it there: it opens the signed anchor (validity.rs) as the VC
+// circuit does, checks that the credential is active at current_time, and
+// recomputes the anchor's claims root from the birthdate's leaf along a
+// private path (disclosure.rs), a birthdate being its own, signed, leaf
+// value. The proof carries the VC proof header, and the verifier checks the
+// issuer's signature over its anchor, so a birthdate the issuer never
+// signed does not verify, whatever a modified prover skipped. Unlike a
+// disclosure, the root stays private. Blobs before version 6 have no signed
+// root to prove against. About 7,200 constraints.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, claim_key, threshold_seconds.
+
+use ark_bn254::Bn254;
+use ark_groth16::PreparedVerifyingKey;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::bytes_to_hex;
+use crate::dates::{civil_from_days, days_from_civil, is_leap, DEFAULT_MIN_DATE, SECONDS_PER_DAY};
+use crate::ffi::write_cstr;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::disclosure::claim_key_field,
+    crate::proof,
+    ark_bn254::Fr,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+    std::ffi::CStr,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::checked_blob,
+    crate::dates,
+    crate::disclosure::{enforce_membership, signed_field, signed_value, ClaimPath, ClaimsTree},
+    crate::error::{ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_NOT_INITIALIZED, ZK_ERR_UNSUPPORTED_VERSION},
+    crate::schedule::{enforce_bits, witness},
+    crate::entropy,
+    crate::{enforce_active, prove_with_key, seeded_rng, Anchored, AnchorWitness},
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission,
+    crate::audit,
+    crate::did,
+    crate::ffi::MAX_FIELD_LEN,
+    crate::hex_to_bytes,
+    crate::sizes::DERIVED_AGE_PUBLIC_INPUTS,
+    crate::validity,
+    std::time::Instant,
+};
+
+/// Most years a threshold may span
+pub const MAX_AGE_YEARS: u32 = 200;
+
+/// Bits of the shifted birthdate (an i64)
+#[cfg(feature = "prover")]
+const BIRTHDATE_BITS: usize = 64;
+
+/// Bits of current_time - birthdate - threshold (below 2^64 + 2^63)
+#[cfg(feature = "prover")]
+const MARGIN_BITS: usize = 66;
+
+/// Circuit id of the derived-age circuit
+pub const DERIVED_AGE_CIRCUIT: &str = "zkid-vc/derived-age/v2";
+
+/// Latest birthdate (unix seconds) of someone at least `years` old at
+/// `current_time`, per the calendar policy in the module comment
+pub fn birthdate_cutoff(current_time: u64, years: u32) -> Option<i64> {
+    if years == 0 || years > MAX_AGE_YEARS {
+        return None;
+    }
+    let now = i64::try_from(current_time).ok()?;
+    let (year, month, mut day) = civil_from_days(now.div_euclid(SECONDS_PER_DAY));
+    let year = year - i64::from(years);
+    if month == 2 && day == 29 && !is_leap(year) {
+        day = 28;
+    }
+    let cutoff = days_from_civil(year, month, day) * SECONDS_PER_DAY + SECONDS_PER_DAY - 1;
+    (cutoff >= DEFAULT_MIN_DATE).then_some(cutoff)
+}
+
+/// The public threshold for `years` at `current_time`: current_time - cutoff
+pub fn threshold_seconds(current_time: u64, years: u32) -> Option<u64> {
+    let cutoff = birthdate_cutoff(current_time, years)?;
+    u64::try_from(i128::from(current_time) - i128::from(cutoff)).ok()
+}
+
+// ============================================================================
+// ZK Circuit: Birthdate at Least N Years Before current_time
+// ============================================================================
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+struct DerivedAgeCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+    birthdate: Option<i64>,
+
+    // 公开输入（锚点之后）
+    claim_key: Option<Fr>,
+    threshold: Option<u64>,
+}
+
+#[cfg(feature = "prover")]
+impl DerivedAgeCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            path: ClaimPath::blank(),
+            birthdate: None,
+            claim_key: None,
+            threshold: None,
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for DerivedAgeCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+        let (time_var, current_time) = opened.current_time;
+
+        let claim_key_var = cs.new_input_variable(|| self.claim_key.ok_or(SynthesisError::AssignmentMissing))?;
+        let threshold_var = cs.new_input_variable(|| {
+            self.threshold.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // The birthdate is its own leaf value under the anchor's claims root
+        let birthdate = self.birthdate.map(signed_field);
+        let birthdate_var = witness(&cs, birthdate)?;
+        enforce_membership(
+            &cs,
+            [(lc!() + claim_key_var, self.claim_key), (lc!() + birthdate_var, birthdate)],
+            &self.path,
+            opened.claims_root.0,
+        )?;
+
+        // The birthdate is an i64 (birthdate + 2^63 < 2^64)
+        let offset = Fr::from(1u64 << 63);
+        enforce_bits(
+            &cs,
+            lc!() + birthdate_var + (offset, Variable::One),
+            self.birthdate.map(|b| (i128::from(b) + (1i128 << 63)) as u128),
+            BIRTHDATE_BITS,
+        )?;
+
+        // current_time - birthdate - threshold >= 0
+        let margin = current_time
+            .zip(self.birthdate)
+            .zip(self.threshold)
+            .map(|((t, b), k)| (i128::from(t) - i128::from(b) - i128::from(k)) as u128);
+        enforce_bits(&cs, lc!() + time_var - birthdate_var - threshold_var, margin, MARGIN_BITS)
+    }
+}
+
+/// Keys of the derived-age circuit; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct DerivedAgeKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static DERIVED_AGE_KEYS: Mutex<DerivedAgeKeys> = Mutex::new(DerivedAgeKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the derived-age circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(5u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(DerivedAgeCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match DERIVED_AGE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the derived-age verifying key is present
+pub(crate) fn has_keys() -> bool {
+    DERIVED_AGE_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the derived-age circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = DERIVED_AGE_KEYS.lock() {
+        *keys = DerivedAgeKeys::default();
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Prove that the holder is at least `min_age_years` old at `current_time`,
+/// from the birthdate claim `birthdate_key`, without revealing it
+///
+/// Runs the ZK_GenerateVCProofFromBlob pre-checks first. The claim must be
+/// signed unix seconds in canonical decimal ("-86400", not "+0" or "007")
+/// inside the date window (ZK_SetDateWindow). Returns 0 on success, the
+/// pre-check's code for a credential that would not verify,
+/// ZK_ERR_UNSUPPORTED_VERSION for a blob before version 6,
+/// ZK_ERR_INPUT_TOO_LARGE for more than MAX_COMMITTED_CLAIMS claims,
+/// ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED, or -1 if the holder is
+/// younger (or born in the future), the claim is missing or malformed, or
+/// `min_age_years` is outside 1..=MAX_AGE_YEARS.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateDerivedAgeProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    birthdate_key: *const c_char,
+    min_age_years: u32,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if birthdate_key.is_null() || proof_out.is_null() {
+        return -1;
+    }
+    let key = match unsafe { CStr::from_ptr(birthdate_key) }.to_str() {
+        Ok(key) => key,
+        Err(_) => return -1,
+    };
+
+    let (vc, issuer_key, current_time) =
+        match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+            Ok(checked) => checked,
+            Err(code) => return code,
+        };
+
+    if !vc.claims_root_signed {
+        return ZK_ERR_UNSUPPORTED_VERSION;
+    }
+    let tree = match ClaimsTree::new(&vc.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+    let window = dates::date_window();
+    let birthdate = vc.claims.iter().enumerate().find_map(|(index, (k, value))| {
+        signed_value(value).filter(|&b| k == key && window.min <= b && b <= window.max).map(|b| (index, b))
+    });
+    let ((index, birthdate), cutoff, threshold) = match (
+        birthdate,
+        birthdate_cutoff(current_time, min_age_years),
+        threshold_seconds(current_time, min_age_years),
+    ) {
+        (Some(birthdate), Some(cutoff), Some(threshold)) => (birthdate, cutoff, threshold),
+        _ => return -1,
+    };
+    if birthdate > cutoff {
+        return -1;
+    }
+
+    let (signature, opening) = match (vc.anchored_signature(), vc.opening()) {
+        (Ok(signature), Some(opening)) => (signature, opening),
+        (Err(code), _) => return code,
+        (_, None) => return -1,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), current_time, Fr::from(nonce))
+        .and_then(|(anchor, header)| {
+            let circuit = DerivedAgeCircuit {
+                anchor,
+                path: ClaimPath::of(&tree, index)?,
+                birthdate: Some(birthdate),
+                claim_key: Some(claim_key_field(key)),
+                threshold: Some(threshold),
+            };
+            Some((circuit, header))
+        });
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match DERIVED_AGE_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify that the holder was at least `min_age_years` old at `current_time`
+/// according to the issuer-signed claim `birthdate_key`
+///
+/// `current_time` must be the time the prover used. Returns 1 if valid, 0 if
+/// invalid (including a `min_age_years` outside 1..=MAX_AGE_YEARS),
+/// ZK_ERR_DISABLED for a legacy-format proof in a strict build,
+/// ZK_ERR_UNSUPPORTED_VERSION for a format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyDerivedAgeProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    birthdate_key: *const c_char,
+    min_age_years: u32,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = DERIVED_AGE_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let result = match pvk {
+        Some(pvk) if !proof_hex.is_null() && !issuer_pubkey.is_null() && !birthdate_key.is_null() => {
+            verify_derived_age(pvk, proof_hex, issuer_pubkey, birthdate_key, min_age_years, current_time, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "derived-age-proof",
+        code: result,
+        circuit: Some(DERIVED_AGE_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_derived_age(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    birthdate_key: *const c_char,
+    min_age_years: u32,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let text = |ptr: *const c_char| admission::text_arg(ptr, MAX_FIELD_LEN);
+    let issuer_bytes = match text(issuer_pubkey).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return e.code_or(0),
+        None => return 0,
+    };
+    let (key, threshold) = match (
+        text(birthdate_key),
+        threshold_seconds(current_time, min_age_years),
+    ) {
+        (Some(key), Some(threshold)) => (key, threshold),
+        _ => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs, then claim key and threshold (circuit order); None
+    // unless the issuer signed the anchor
+    let mut public_inputs = match validity::public_inputs(&issuer_bytes, format, nonce, current_time, &header) {
+        Some(inputs) => inputs.to_vec(),
+        None => return 0,
+    };
+    public_inputs.extend_from_slice(&[claim_key_field(key), Fr::from(threshold)]);
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+crate::unwind::entry_point! {
+/// Export the derived-age verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportDerivedAgeVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match DERIVED_AGE_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportDerivedAgeVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportDerivedAgeVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == DERIVED_AGE_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match DERIVED_AGE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
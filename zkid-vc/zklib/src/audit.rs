@@ -0,0 +1,182 @@
+// ============================================================================
+// Verification Audit Events
+// ============================================================================
+//
+// With the "log" feature every verify entry point emits exactly one event,
+// as a single-line JSON object, to the `log` crate (target "zkid::verify")
+// and to the audit callback installed with ZK_SetAuditCallback:
+//
+//   {"event":"verify","decision":"reject","code":0,"check":"vc-proof",
+//    "circuit":"zkid-vc/v7","vk":"3fa2c1d0","nonce_hash":"…","audience_hash":"…",
+//    "predicate_hash":null,"latency_us":4210,"version":"0.1.0"}
+//
+// Events never carry raw public inputs, claims, issuer keys or holder
+// identifiers. The nonce is only logged as a truncated HMAC under a
+// deployment salt (ZK_SetLogSalt; random per process until set), so it can
+// be correlated within a deployment but not matched against other logs.
+// The audience is a truncated SHA-256 and the verifying key a fingerprint
+// prefix. The predicate hash (predicate.rs) is logged whole: the predicate
+// is the verifier's own policy, and the full hash matches the one
+// ZK_EncodePredicate reports for it. Fields that do not apply are null.
+
+use ark_bn254::Bn254;
+use ark_groth16::VerifyingKey;
+use std::os::raw::c_int;
+use std::time::Instant;
+
+/// Circuit id reported for checks that verify a VC proof
+pub use crate::sizes::VC_CIRCUIT;
+
+/// What a verify entry point decided, for the audit event
+pub struct Verification<'a> {
+    /// Which check ran: "vc-proof", "vc-signature", "presentation",
+    /// "presentation-stateless", "schedule-proof", "composite-signature",
+    /// "group-claim-proof", "predicate-proof"
+    pub check: &'static str,
+    /// The entry point's return value
+    pub code: c_int,
+    /// Circuit the proof was checked against, None for signature checks
+    pub circuit: Option<&'a str>,
+    pub vk: Option<&'a VerifyingKey<Bn254>>,
+    pub nonce: Option<u64>,
+    pub audience: Option<&'a str>,
+    /// Hash of the predicate a predicate proof was checked against
+    pub predicate: Option<&'a [u8; 32]>,
+}
+
+#[cfg(feature = "log")]
+pub use enabled::*;
+
+#[cfg(not(feature = "log"))]
+#[inline]
+pub fn record(_started: Instant, _verification: Verification) {}
+
+#[cfg(feature = "log")]
+mod enabled {
+    use hmac::{Hmac, Mac};
+    use rand_core::{OsRng, RngCore};
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_int, c_void};
+    use crate::sync::Mutex;
+
+    use super::{Bn254, Instant, Verification, VerifyingKey};
+    use crate::bytes_to_hex;
+    use crate::ffi::read_bytes;
+
+    type HmacSha256 = Hmac<Sha256>;
+
+    const NONCE_DOMAIN: &[u8] = b"zkid/log-nonce/v1";
+    const MIN_SALT_LEN: usize = 16;
+    const MAX_SALT_LEN: usize = 64;
+
+    /// Receives each event as NUL-terminated JSON; the pointer is only valid
+    /// for the duration of the call
+    pub type AuditCallback = extern "C" fn(event_json: *const c_char, user_data: *mut c_void);
+
+    struct AuditSink {
+        salt: Option<Vec<u8>>,
+        callback: Option<(AuditCallback, *mut c_void)>,
+    }
+
+    // The host guarantees the callback and user_data may be used from any thread
+    unsafe impl Send for AuditSink {}
+
+    static SINK: Mutex<AuditSink> = Mutex::new(AuditSink {
+        salt: None,
+        callback: None,
+    });
+
+    fn nonce_hash(salt: &[u8], nonce: u64) -> String {
+        let mut mac = HmacSha256::new_from_slice(salt).expect("HMAC accepts any key length");
+        mac.update(NONCE_DOMAIN);
+        mac.update(&nonce.to_le_bytes());
+        bytes_to_hex(&mac.finalize().into_bytes()[..8])
+    }
+
+    fn vk_fingerprint(vk: &VerifyingKey<Bn254>) -> Option<String> {
+        Some(bytes_to_hex(&Sha256::digest(crate::vk::encode(vk))[..4]))
+    }
+
+    /// Build and emit the event for one verification
+    pub fn record(started: Instant, verification: Verification) {
+        let latency_us = started.elapsed().as_micros() as u64;
+
+        let (salt, callback) = {
+            let mut sink = SINK.lock().unwrap_or_else(|e| e.into_inner());
+            let salt = sink
+                .salt
+                .get_or_insert_with(|| {
+                    let mut salt = vec![0u8; 32];
+                    OsRng.fill_bytes(&mut salt);
+                    salt
+                })
+                .clone();
+            (salt, sink.callback)
+        };
+
+        let event = json!({
+            "event": "verify",
+            "decision": if verification.code == 1 { "accept" } else { "reject" },
+            "code": verification.code,
+            "check": verification.check,
+            "circuit": verification.circuit,
+            "vk": verification.vk.and_then(vk_fingerprint),
+            "nonce_hash": verification.nonce.map(|nonce| nonce_hash(&salt, nonce)),
+            "audience_hash": verification
+                .audience
+                .map(|audience| bytes_to_hex(&Sha256::digest(audience.as_bytes())[..8])),
+            "predicate_hash": verification.predicate.map(|hash| bytes_to_hex(hash)),
+            "latency_us": latency_us,
+            "version": env!("CARGO_PKG_VERSION"),
+        })
+        .to_string();
+
+        log::info!(target: "zkid::verify", "{}", event);
+
+        if let Some((callback, user_data)) = callback {
+            if let Ok(event) = CString::new(event) {
+                callback(event.as_ptr(), user_data);
+            }
+        }
+    }
+
+    // ========================================================================
+    // C API Functions
+    // ========================================================================
+
+    crate::unwind::entry_point! {
+    /// Set the deployment salt for nonce hashes in audit events
+    ///
+    /// `salt_len` must be 16..=64 bytes. Returns 0 on success, -1 on failure.
+    #[no_mangle]
+    pub extern "C" fn ZK_SetLogSalt(salt: *const u8, salt_len: usize) -> c_int {
+        if salt.is_null() || !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt_len) {
+            return -1;
+        }
+
+        let salt = match read_bytes(salt, salt_len, MAX_SALT_LEN) {
+            Ok(salt) => salt,
+            Err(e) => return e.code(),
+        };
+        SINK.lock().unwrap_or_else(|e| e.into_inner()).salt = Some(salt.to_vec());
+        0
+    }
+    }
+
+    crate::unwind::entry_point! {
+    /// Install (or remove with NULL) the audit event callback
+    ///
+    /// The callback runs on the verifying thread after each verification.
+    #[no_mangle]
+    pub extern "C" fn ZK_SetAuditCallback(
+        callback: Option<AuditCallback>,
+        user_data: *mut c_void,
+    ) -> c_int {
+        let mut sink = SINK.lock().unwrap_or_else(|e| e.into_inner());
+        sink.callback = callback.map(|callback| (callback, user_data));
+        0
+    }
+    }
+}
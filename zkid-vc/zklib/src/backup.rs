@@ -0,0 +1,385 @@
+// ============================================================================
+// Wallet Backup and Restore
+// ============================================================================
+//
+// A backup packs everything a wallet needs to come back on a new device
+// into one blob: holder keys, the credential store's credentials,
+// supersession links and consent receipts, and the presentation requests
+// still waiting for an answer. It is encrypted under a key derived with
+// Argon2id from the user's recovery phrase (a BIP39-style mnemonic or any
+// passphrase). Integers little-endian:
+//
+//   header:  "ZKBK" | version u8 | salt[16] | m_cost u32 | t_cost u32
+//            | p_cost u32
+//   body:    nonce[24] | len u32 | seal(payload), header as associated data
+//   payload: key_count u32 | key* | record_count u32 | (id | vc_blob)*
+//            | link_count u32 | (old_id | new_id)* | receipt_count u32
+//            | receipt* | request_count u32 | request*
+//
+// The phrase is normalized before derivation (words split on whitespace,
+// lowercased, joined by single spaces), so spacing and capitalization do
+// not matter. The whole payload is one AEAD seal: a wrong phrase and a
+// tampered blob fail the same way (ZK_ERR_AUTH), telling nothing about
+// which word or byte was wrong.
+//
+// Restore reads every version up to BACKUP_VERSION; a newer backup is
+// refused with ZK_ERR_UNSUPPORTED_VERSION before any key derivation.
+// Credentials this build cannot decode (a retired VC blob version) are
+// skipped and listed, the rest are restored. Restoring merges into the
+// given store under CredentialStore::merge's conflict policy, so restoring
+// onto a device that already has a store keeps what it has.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand_core::{OsRng, RngCore};
+use serde_json::json;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use zeroize::Zeroizing;
+
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_UNSUPPORTED_VERSION};
+use crate::ffi::{read_bytes, read_slice, write_cstr, MAX_ARRAY_LEN, MAX_BLOB_LEN, MAX_FIELD_LEN};
+use crate::store::CredentialStore;
+use crate::vk::write_out;
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, hex_to_bytes, VerifiableCredential};
+
+const BACKUP_MAGIC: &[u8; 4] = b"ZKBK";
+const BACKUP_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = BACKUP_MAGIC.len() + 1 + SALT_LEN + 3 * 4;
+
+/// Decoded backup contents
+pub struct Backup {
+    /// Format version the backup was written with
+    pub version: u8,
+    pub holder_keys: Vec<Zeroizing<Vec<u8>>>,
+    pub records: Vec<(String, Zeroizing<Vec<u8>>)>,
+    pub links: Vec<(String, String)>,
+    pub receipts: Vec<Vec<u8>>,
+    pub pending_requests: Vec<Vec<u8>>,
+}
+
+/// Why a backup could not be opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupError {
+    /// Wrong recovery phrase or tampered blob
+    Authentication,
+    /// Not a backup, or truncated
+    Corrupt,
+    /// Written by a newer version
+    UnsupportedVersion,
+    /// Key derivation or encryption failed
+    Internal,
+}
+
+impl BackupError {
+    pub fn code(self) -> c_int {
+        match self {
+            BackupError::Authentication => ZK_ERR_AUTH,
+            BackupError::Corrupt => ZK_ERR_CORRUPT,
+            BackupError::UnsupportedVersion => ZK_ERR_UNSUPPORTED_VERSION,
+            BackupError::Internal => -1,
+        }
+    }
+}
+
+/// Words of the phrase, lowercased and joined by single spaces
+fn normalize_phrase(phrase: &str) -> Zeroizing<String> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    Zeroizing::new(words.join(" "))
+}
+
+fn derive_key(phrase: &str, salt: &[u8], params: Params) -> Result<Zeroizing<[u8; 32]>, BackupError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(normalize_phrase(phrase).as_bytes(), salt, key.as_mut())
+        .map_err(|_| BackupError::Internal)?;
+    Ok(key)
+}
+
+fn put_list<T: AsRef<[u8]>>(out: &mut Vec<u8>, items: &[T]) {
+    out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+    for item in items {
+        put_bytes(out, item.as_ref());
+    }
+}
+
+fn read_list(r: &mut Reader) -> Option<Vec<Vec<u8>>> {
+    let count = r.u32()? as usize;
+    (0..count).map(|_| r.bytes().map(<[u8]>::to_vec)).collect()
+}
+
+fn read_pairs(r: &mut Reader) -> Option<Vec<(String, Vec<u8>)>> {
+    let count = r.u32()? as usize;
+    (0..count).map(|_| Some((r.string()?, r.bytes()?.to_vec()))).collect()
+}
+
+impl Backup {
+    /// Snapshot a credential store together with keys and pending requests
+    pub fn of_store(store: &CredentialStore, holder_keys: Vec<Zeroizing<Vec<u8>>>, pending_requests: Vec<Vec<u8>>) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            holder_keys,
+            records: store
+                .entries()
+                .map(|(id, blob)| (id.to_string(), Zeroizing::new(blob.to_vec())))
+                .collect(),
+            links: store.links().map(|(old_id, new_id)| (old_id.to_string(), new_id.to_string())).collect(),
+            receipts: store.receipts().map(<[u8]>::to_vec).collect(),
+            pending_requests,
+        }
+    }
+
+    fn payload(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::new());
+        put_list(&mut out, &self.holder_keys.iter().map(|key| key.as_slice()).collect::<Vec<_>>());
+        out.extend_from_slice(&(self.records.len() as u32).to_le_bytes());
+        for (id, blob) in &self.records {
+            put_bytes(&mut out, id.as_bytes());
+            put_bytes(&mut out, blob);
+        }
+        out.extend_from_slice(&(self.links.len() as u32).to_le_bytes());
+        for (old_id, new_id) in &self.links {
+            put_bytes(&mut out, old_id.as_bytes());
+            put_bytes(&mut out, new_id.as_bytes());
+        }
+        put_list(&mut out, &self.receipts);
+        put_list(&mut out, &self.pending_requests);
+        out
+    }
+
+    fn from_payload(version: u8, payload: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(payload);
+        let holder_keys = read_list(&mut r)?.into_iter().map(Zeroizing::new).collect();
+        let records = read_pairs(&mut r)?
+            .into_iter()
+            .map(|(id, blob)| (id, Zeroizing::new(blob)))
+            .collect();
+        let links = read_pairs(&mut r)?
+            .into_iter()
+            .map(|(old_id, new_id)| Some((old_id, String::from_utf8(new_id).ok()?)))
+            .collect::<Option<_>>()?;
+        let receipts = read_list(&mut r)?;
+        let pending_requests = read_list(&mut r)?;
+        if !r.is_empty() {
+            return None;
+        }
+        Some(Self { version, holder_keys, records, links, receipts, pending_requests })
+    }
+
+    /// Encrypt under `phrase` with the default Argon2id cost
+    pub fn seal(&self, phrase: &str) -> Result<Vec<u8>, BackupError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let (m_cost, t_cost, p_cost) = (Params::DEFAULT_M_COST, Params::DEFAULT_T_COST, Params::DEFAULT_P_COST);
+
+        let mut out = BACKUP_MAGIC.to_vec();
+        out.push(BACKUP_VERSION);
+        out.extend_from_slice(&salt);
+        for v in [m_cost, t_cost, p_cost] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+
+        let params = Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|_| BackupError::Internal)?;
+        let key = derive_key(phrase, &salt, params)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &self.payload(), aad: &out })
+            .map_err(|_| BackupError::Internal)?;
+        out.extend_from_slice(&nonce);
+        put_bytes(&mut out, &ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt a backup with `phrase`
+    pub fn open(data: &[u8], phrase: &str) -> Result<Self, BackupError> {
+        let mut r = Reader::new(data);
+        if r.take(BACKUP_MAGIC.len()) != Some(BACKUP_MAGIC.as_slice()) {
+            return Err(BackupError::Corrupt);
+        }
+        let version = match r.u8() {
+            Some(v @ 1..=BACKUP_VERSION) => v,
+            Some(_) => return Err(BackupError::UnsupportedVersion),
+            None => return Err(BackupError::Corrupt),
+        };
+        let salt = r.take(SALT_LEN).ok_or(BackupError::Corrupt)?;
+        let m_cost = r.u32().ok_or(BackupError::Corrupt)?;
+        let t_cost = r.u32().ok_or(BackupError::Corrupt)?;
+        let p_cost = r.u32().ok_or(BackupError::Corrupt)?;
+        let nonce = r.take(NONCE_LEN).ok_or(BackupError::Corrupt)?;
+        let ciphertext = r.bytes().ok_or(BackupError::Corrupt)?;
+        if !r.is_empty() {
+            return Err(BackupError::Corrupt);
+        }
+
+        // Costs beyond what a backup is written with are refused, so a forged
+        // header cannot make restore allocate or spin at will
+        if m_cost > Params::DEFAULT_M_COST * 4 || t_cost > Params::DEFAULT_T_COST * 4 || p_cost > Params::DEFAULT_P_COST * 4 {
+            return Err(BackupError::Corrupt);
+        }
+        let params = Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|_| BackupError::Corrupt)?;
+        let key = derive_key(phrase, salt, params)?;
+        let payload = XChaCha20Poly1305::new(key.as_ref().into())
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &data[..HEADER_LEN] })
+            .map(Zeroizing::new)
+            .map_err(|_| BackupError::Authentication)?;
+        Self::from_payload(version, &payload).ok_or(BackupError::Corrupt)
+    }
+}
+
+/// Read `count` hex strings from a C array
+fn hex_array(items: *const *const c_char, count: usize) -> Option<Vec<Zeroizing<Vec<u8>>>> {
+    let items = read_slice(items, count, MAX_ARRAY_LEN).ok()?;
+    items
+        .iter()
+        .map(|&item| {
+            if item.is_null() {
+                return None;
+            }
+            let text = unsafe { CStr::from_ptr(item) }.to_str().ok()?;
+            hex_to_bytes(text.trim()).ok().map(Zeroizing::new)
+        })
+        .collect()
+}
+
+fn phrase_arg<'a>(phrase: *const c_char) -> Option<&'a str> {
+    crate::admission::text_arg(phrase, MAX_FIELD_LEN).filter(|phrase| !phrase.trim().is_empty())
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write an encrypted backup of a wallet
+///
+/// Packs the store's credentials, links and consent receipts with
+/// `holder_key_count` hex holder keys (signing and encryption secrets) and
+/// `pending_request_count` hex presentation requests (either array may be
+/// NULL when its count is 0), encrypted under `recovery_phrase`. The binary
+/// backup goes to `backup_out`; its length is always written to
+/// `backup_len_out`, so a call with a too-small buffer reports the size
+/// needed. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL
+/// arguments, an empty phrase or a malformed hex entry.
+#[no_mangle]
+pub extern "C" fn ZK_BackupCreate(
+    store: *mut CredentialStore,
+    holder_keys: *const *const c_char,
+    holder_key_count: usize,
+    pending_requests: *const *const c_char,
+    pending_request_count: usize,
+    recovery_phrase: *const c_char,
+    backup_out: *mut u8,
+    backup_out_size: usize,
+    backup_len_out: *mut usize,
+) -> c_int {
+    let store = match unsafe { store.as_ref() } {
+        Some(store) => store,
+        None => return -1,
+    };
+    let phrase = match phrase_arg(recovery_phrase) {
+        Some(phrase) => phrase,
+        None => return -1,
+    };
+    let (keys, requests) = match (
+        hex_array(holder_keys, holder_key_count),
+        hex_array(pending_requests, pending_request_count),
+    ) {
+        (Some(keys), Some(requests)) => (keys, requests),
+        _ => return -1,
+    };
+    let requests = requests.iter().map(|request| request.to_vec()).collect();
+
+    match Backup::of_store(store, keys, requests).seal(phrase) {
+        Ok(blob) => write_out(&blob, backup_out, backup_out_size, backup_len_out),
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Restore a backup into a store
+///
+/// `store` is an open store (ZK_StoreOpen creates an empty one on a new
+/// device); the backup is merged into it in one write, keeping credentials
+/// the store already has (see CredentialStore::merge). `report_out` gets a
+/// JSON object:
+///
+///   version           backup format version
+///   restored          credential_ids added to the store
+///   kept              credential_ids the store already had
+///   skipped           [{credential_id, reason}]: "unsupported-format" for
+///                     credentials this build cannot decode, "superseded"
+///                     for ones the store has a newer credential for
+///   receipts_added    consent receipts not already in the store
+///   holder_keys       hex holder keys, to re-import into the key store
+///   pending_requests  hex presentation requests still to answer
+///
+/// Returns 0 on success (also when credentials were skipped),
+/// ZK_ERR_AUTH for a wrong recovery phrase or a tampered backup,
+/// ZK_ERR_CORRUPT for something that is not a backup,
+/// ZK_ERR_UNSUPPORTED_VERSION for a backup from a newer version, the store
+/// error if the merged store could not be written, ZK_ERR_BUFFER_TOO_SMALL
+/// (the store is already updated), or -1 on NULL arguments.
+#[no_mangle]
+pub extern "C" fn ZK_BackupRestore(
+    backup: *const u8,
+    backup_len: usize,
+    recovery_phrase: *const c_char,
+    store: *mut CredentialStore,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    let store = match unsafe { store.as_mut() } {
+        Some(store) if !backup.is_null() => store,
+        _ => return -1,
+    };
+    let phrase = match phrase_arg(recovery_phrase) {
+        Some(phrase) => phrase,
+        None => return -1,
+    };
+    let data = match read_bytes(backup, backup_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    let backup = match Backup::open(data, phrase) {
+        Ok(backup) => backup,
+        Err(e) => return e.code(),
+    };
+
+    let (records, unsupported): (Vec<_>, Vec<_>) = backup
+        .records
+        .into_iter()
+        .partition(|(_, blob)| VerifiableCredential::from_bytes(blob).is_some());
+    let outcome = match store.merge(records, backup.links, backup.receipts) {
+        Ok(outcome) => outcome,
+        Err(e) => return e.code(),
+    };
+
+    let skipped: Vec<_> = unsupported
+        .iter()
+        .map(|(id, _)| json!({"credential_id": id, "reason": "unsupported-format"}))
+        .chain(outcome.superseded.iter().map(|id| json!({"credential_id": id, "reason": "superseded"})))
+        .collect();
+    let report = Zeroizing::new(
+        json!({
+            "version": backup.version,
+            "restored": outcome.restored,
+            "kept": outcome.kept,
+            "skipped": skipped,
+            "receipts_added": outcome.receipts_added,
+            "holder_keys": backup.holder_keys.iter().map(|key| bytes_to_hex(key)).collect::<Vec<_>>(),
+            "pending_requests": backup.pending_requests.iter().map(|r| bytes_to_hex(r)).collect::<Vec<_>>(),
+        })
+        .to_string(),
+    );
+    match write_cstr(report_out, report_out_size, &report) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
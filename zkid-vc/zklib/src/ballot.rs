@@ -0,0 +1,460 @@
+// ============================================================================
+// Anonymous Ballots (one credential, one vote per poll)
+// ============================================================================
+//
+// An employee credential yields exactly one ballot per poll. The credential
+// is bound to its holder (holder.rs): the holder keeps a 32-byte secret s,
+// and the issuer signs its key MiMC(s) into the credential's anchor
+// (validity.rs). A poll is identified by its scope, derived from the poll
+// id and a deployment salt (ZK_SetPollSalt) both sides configure,
+//
+//   scope = SHA-256("zkid:poll-scope" | salt | poll_id) mod r
+//
+// (length-prefixed fields, the digest read little-endian), and a ballot
+// carries the nullifier
+//
+//   nullifier = MiMC(s, scope)
+//
+// as a one-time proof does for its context (onetime.rs). The nullifier is
+// the same for every ballot of one credential in one poll, so a second
+// ballot is detected; it differs between polls and reveals nothing about
+// the secret. Like every proof of the credential, a ballot carries its
+// anchor and signature (validity.rs), so ballots of one credential in
+// different polls are linkable to each other by those, and to the employee
+// by a verifier that knows which credential carries them.
+//
+// The ballot circuit is the one-time VC circuit under its own keys, with
+// the ballot content hash in the nonce's place: its public inputs (in
+// order) are the issuer key hash, the ballot hash, the time the ballot was
+// cast, the anchor, the scope and the nullifier. It opens the anchor to the
+// credential's hash, dates, blinding and the key of the private secret, and
+// proves the nullifier of the same secret, so only the holder of a
+// credential the issuer signed can cast a ballot, and only under its one
+// nullifier. The proof carries the VC header, and ZK_VerifyBallotProof
+// checks the issuer's signature over the anchor before the proof, whatever
+// a modified prover skipped. The ballot is cast at the time in the header,
+// as a presentation is (validity.rs). A proof verifies only for the ballot
+// hash it was made for.
+//
+// The ballot keys are set up from fresh entropy, not a public seed: a
+// verifier imports the prover's verifying key
+// (ZK_ExportBallotVerifyingKey), and no one knows the trapdoor that would
+// prove a ballot without a credential.
+//
+// ZK_VerifyBallotProof records the nullifier of every valid ballot in the
+// poll's scope of the nullifier set (see nullifier.rs) and answers a repeat
+// with ZK_ERR_NULLIFIER_USED.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::PreparedVerifyingKey;
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::admission;
+use crate::ffi::{read_bytes, write_cstr, MAX_FIELD_LEN};
+use crate::{bytes_to_hex, hex_to_bytes};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::proof,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::checked_blob,
+    crate::disclosure::field_bytes,
+    crate::entropy,
+    crate::error::ZK_ERR_NOT_INITIALIZED,
+    crate::holder::{self, parse_secret, Binding},
+    crate::onetime,
+    crate::{prove_with_key, skips_prover_checks, vc_circuit, Anchored, VCCircuit},
+    ark_groth16::ProvingKey,
+    std::sync::Arc,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::disclosure::field_from_bytes,
+    crate::error::ZK_ERR_NULLIFIER_USED,
+    crate::nullifier::NullifierSet,
+    crate::sizes::BALLOT_PUBLIC_INPUTS,
+    crate::storage::storage,
+    crate::validity,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const SCOPE_DOMAIN: &[u8] = b"zkid:poll-scope";
+
+/// Byte length of a holder secret
+pub const HOLDER_SECRET_LEN: usize = 32;
+
+/// Circuit id of the ballot circuit
+pub const BALLOT_CIRCUIT: &str = "zkid-vc/ballot/v3";
+
+// Deployment salt mixed into every poll scope
+static POLL_SALT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+pub(crate) fn hash(domain: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    for part in parts {
+        hasher.update((part.len() as u32).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// The scope a poll's nullifiers are recorded in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PollScope([u8; 32]);
+
+impl PollScope {
+    pub fn derive(poll_id: &[u8], salt: &[u8]) -> Self {
+        Self(hash(SCOPE_DOMAIN, &[salt, poll_id]))
+    }
+
+    /// Scope of `poll_id` under the configured deployment salt
+    pub fn for_poll(poll_id: &[u8]) -> Self {
+        let salt = POLL_SALT.lock().unwrap_or_else(|e| e.into_inner());
+        Self::derive(poll_id, &salt)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Field element of the scope, the same in every proof format
+    pub fn field(&self) -> Fr {
+        Fr::from_le_bytes_mod_order(&self.0)
+    }
+}
+
+/// Keys of the ballot circuit; the verifying key of a verifier-only build
+/// arrives by import
+#[derive(Default)]
+struct BallotKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static BALLOT_KEYS: Mutex<BallotKeys> = Mutex::new(BallotKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the ballot circuit keys from fresh entropy
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = match entropy::fresh_rng() {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(VCCircuit::blank_one_time(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match BALLOT_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(Arc::new(pk));
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the ballot verifying key is present
+pub(crate) fn has_keys() -> bool {
+    BALLOT_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the ballot circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = BALLOT_KEYS.lock() {
+        *keys = BallotKeys::default();
+    }
+}
+
+/// Parse a NUL-terminated hex string of exactly N bytes
+#[cfg(any(feature = "prover", feature = "verifier"))]
+fn parse_hex<const N: usize>(ptr: *const c_char) -> Option<[u8; N]> {
+    hex_to_bytes(admission::text_arg(ptr, 2 * N)?).ok()?.try_into().ok()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Set the deployment salt mixed into every poll scope
+///
+/// Provers and verifiers of one deployment must use the same salt; it keeps
+/// the nullifiers of another deployment's ballots for an equal poll id
+/// unrelated. Empty by default. Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_SetPollSalt(salt: *const u8, salt_len: usize) -> c_int {
+    let salt = match read_bytes(salt, salt_len, MAX_FIELD_LEN) {
+        Ok(salt) => salt.to_vec(),
+        Err(e) => return e.code(),
+    };
+    *POLL_SALT.lock().unwrap_or_else(|e| e.into_inner()) = salt;
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Cast a ballot: prove a credential's right to vote in a poll, bound to
+/// the ballot content hash
+///
+/// Runs the same pre-checks as ZK_GenerateVCProofFromBlob (signature, dates
+/// at `current_time`, dual control), then requires the credential to be
+/// bound (`zkid:holder-binding`) to the key of `holder_secret` (hex, from
+/// ZK_GenerateHolderKeypair). `ballot_hash` is the hex SHA-256 of the
+/// ballot content. Writes the proof and the ballot's nullifier (hex).
+/// Returns 0 on success, the pre-check's code for a credential that would
+/// not verify, ZK_ERR_BAD_SIGNATURE for a credential signed before anchors,
+/// ZK_ERR_ENTROPY if the entropy source fails, -1 on other failures
+/// (including another holder's secret).
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateBallotProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    holder_secret: *const c_char,
+    poll_id: *const u8,
+    poll_id_len: usize,
+    ballot_hash: *const c_char,
+    current_time: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    nullifier_out: *mut c_char,
+    nullifier_out_size: usize,
+) -> c_int {
+    if holder_secret.is_null() || ballot_hash.is_null() || proof_out.is_null() || nullifier_out.is_null() {
+        return -1;
+    }
+
+    let poll_id = match read_bytes(poll_id, poll_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let (secret, ballot_hash) = match (parse_secret(holder_secret), parse_hex::<32>(ballot_hash)) {
+        (Some(secret), Some(ballot_hash)) => (secret, ballot_hash),
+        _ => return -1,
+    };
+
+    let (vc, issuer_key, current_time) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+    let signature = match vc.anchored_signature() {
+        Ok(signature) => signature,
+        Err(code) => return code,
+    };
+    let opening = match vc.opening() {
+        Some(opening) => opening,
+        None => return -1,
+    };
+    if !skips_prover_checks() && opening.holder_key != holder::public_key(&secret) {
+        return -1;
+    }
+
+    let scope = PollScope::for_poll(poll_id).field();
+    let nullifier = onetime::nullifier(&secret, &scope);
+    let proof_hex = match prove(&Anchored { opening, signature: &signature }, issuer_key.as_bytes(), current_time, secret, scope, &ballot_hash) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    if let Err(e) = write_cstr(nullifier_out, nullifier_out_size, &bytes_to_hex(&field_bytes(&nullifier))) {
+        return e.code();
+    }
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+/// Generate the hex ballot proof for an already checked credential
+///
+/// Always fresh randomness: two ballots must not share proof elements.
+#[cfg(feature = "prover")]
+fn prove(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    secret: Fr,
+    scope: Fr,
+    ballot_hash: &[u8; 32],
+) -> Result<String, c_int> {
+    let pk = BALLOT_KEYS.lock().map_err(|_| -1)?.pk.clone().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+
+    let format = proof::emit_format();
+    let ballot = proof::field_for(format, ballot_hash).ok_or(-1)?;
+    let binding = Binding { secret, context: Some(scope) };
+    let (mut circuit, header) =
+        vc_circuit(format, credential, issuer_pubkey_bytes, current_time, 0, Some(binding)).ok_or(-1)?;
+    circuit.anchor.nonce = Some(ballot);
+    prove_with_key(&pk, format, circuit, &header, &mut entropy::fresh_rng()?)
+}
+
+crate::unwind::entry_point! {
+/// Verify a ballot and record its nullifier in the poll's scope
+///
+/// Returns 1 if the proof is valid for the issuer, poll, nullifier and
+/// ballot hash (all hex but the poll id) and the nullifier is new in the
+/// poll, ZK_ERR_NULLIFIER_USED if the credential already voted in it, 0 if
+/// invalid (including a changed ballot hash), ZK_ERR_DISABLED for a
+/// legacy-format proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted, ZK_ERR_CAPACITY or ZK_ERR_STORAGE if the nullifier
+/// could not be recorded. Only valid ballots are recorded.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyBallotProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    poll_id: *const u8,
+    poll_id_len: usize,
+    nullifier: *const c_char,
+    ballot_hash: *const c_char,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = BALLOT_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let result = match (pvk, read_bytes(poll_id, poll_id_len, MAX_FIELD_LEN)) {
+        (Some(pvk), Ok(poll_id))
+            if !proof_hex.is_null() && !issuer_pubkey.is_null() && !nullifier.is_null() && !ballot_hash.is_null() =>
+        {
+            verify_ballot(pvk, proof_hex, issuer_pubkey, poll_id, nullifier, ballot_hash)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "ballot-proof",
+        code: result,
+        circuit: Some(BALLOT_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_ballot(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    poll_id: &[u8],
+    nullifier: *const c_char,
+    ballot_hash: *const c_char,
+) -> c_int {
+    let (issuer, nullifier, ballot_hash) = match (
+        parse_hex::<32>(issuer_pubkey),
+        parse_hex::<32>(nullifier),
+        parse_hex::<32>(ballot_hash),
+    ) {
+        (Some(issuer), Some(nullifier), Some(ballot_hash)) => (issuer, nullifier, ballot_hash),
+        _ => return 0,
+    };
+    let nullifier_field = match field_from_bytes(&nullifier) {
+        Some(field) => field,
+        None => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs with the ballot hash for the nonce, then scope and
+    // nullifier (circuit order); None unless the issuer signed the anchor
+    let scope = PollScope::for_poll(poll_id);
+    let ballot = match proof::field_for(format, &ballot_hash) {
+        Some(ballot) => ballot,
+        None => return 0,
+    };
+    let mut public_inputs = match validity::public_inputs_for(&issuer, format, ballot, header.current_time, &header) {
+        Some(inputs) => inputs.to_vec(),
+        None => return 0,
+    };
+    public_inputs.extend_from_slice(&[scope.field(), nullifier_field]);
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    if !valid {
+        return 0;
+    }
+
+    match NullifierSet::new(storage()).record(scope.as_bytes(), &nullifier) {
+        Ok(true) => 1,
+        Ok(false) => ZK_ERR_NULLIFIER_USED,
+        Err(e) => e.code(),
+    }
+}
+
+crate::unwind::entry_point! {
+/// Export the ballot verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportBallotVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match BALLOT_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportBallotVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportBallotVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == BALLOT_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match BALLOT_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
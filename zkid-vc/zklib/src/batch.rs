@@ -0,0 +1,233 @@
+// ============================================================================
+// Batch Verification
+// ============================================================================
+//
+// A gateway verifying proofs in a loop pays three Miller loops and a final
+// exponentiation for each. A Groth16 proof (A, B, C) holds when
+//
+//   e(A, B) · e(L, -gamma) · e(C, -delta) = e(alpha, beta)
+//
+// with L the public inputs folded into gamma_abc. Raising proof i's
+// equation to a random 128-bit r_i and multiplying them gives one check,
+//
+//   Π e(r_i·A_i, B_i) · e(Σ r_i·L_i, -gamma) · e(Σ r_i·C_i, -delta)
+//     = e(alpha, beta)^(Σ r_i)
+//
+// n + 2 Miller loops and one final exponentiation for n proofs; Σ r_i·L_i
+// is a single sum over gamma_abc with the per-input scalars Σ r_i·x_i. The
+// r_i come from the entropy source (entropy::fill) after the proofs are
+// fixed, so a batch holding an invalid proof passes with probability about
+// 2^-128. A batch that fails says only that some proof is invalid: each is
+// then verified on its own to tell which.
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_snark::SNARK;
+use std::os::raw::{c_char, c_int};
+use std::time::Instant;
+
+use crate::ffi::{read_slice, MAX_ARRAY_LEN, MAX_FIELD_LEN};
+use crate::{admission, audit, current_verifying_key, entropy, last_error, proof, validity};
+
+/// Most proofs one ZK_BatchVerifyVCProofs call takes
+pub const MAX_BATCH_PROOFS: usize = MAX_ARRAY_LEN;
+
+/// A proof with the public inputs it is checked against
+pub struct Statement {
+    pub proof: Proof<Bn254>,
+    pub public_inputs: Vec<Fr>,
+}
+
+/// Random nonzero 128-bit coefficient; None if the entropy source fails
+fn coefficient() -> Option<Fr> {
+    let mut bytes = [0u8; 16];
+    entropy::fill(&mut bytes).ok()?;
+    Some(Fr::from(u128::from_le_bytes(bytes).max(1)))
+}
+
+/// One pairing check over every statement
+///
+/// True if all are valid; false if any is not (but for probability about
+/// 2^-128), if the input counts do not match the key or if no randomness
+/// could be drawn.
+pub fn check(pvk: &PreparedVerifyingKey<Bn254>, statements: &[Statement]) -> bool {
+    let gamma_abc = &pvk.vk.gamma_abc_g1;
+    let mut input_scalars = vec![Fr::zero(); gamma_abc.len()];
+    let mut scaled_a = Vec::with_capacity(statements.len());
+    let mut c_sum = G1Projective::zero();
+    let mut r_sum = Fr::zero();
+
+    for statement in statements {
+        if statement.public_inputs.len() + 1 != gamma_abc.len() {
+            return false;
+        }
+        let r = match coefficient() {
+            Some(r) => r,
+            None => return false,
+        };
+        input_scalars[0] += r;
+        for (scalar, input) in input_scalars[1..].iter_mut().zip(&statement.public_inputs) {
+            *scalar += r * input;
+        }
+        scaled_a.push(statement.proof.a * r);
+        c_sum += statement.proof.c * r;
+        r_sum += r;
+    }
+    let inputs = match G1Projective::msm(gamma_abc, &input_scalars) {
+        Ok(inputs) => inputs,
+        Err(_) => return false,
+    };
+
+    let mut g1: Vec<<Bn254 as Pairing>::G1Prepared> =
+        G1Projective::normalize_batch(&scaled_a).into_iter().map(Into::into).collect();
+    let mut g2: Vec<<Bn254 as Pairing>::G2Prepared> =
+        statements.iter().map(|statement| statement.proof.b.into()).collect();
+    g1.push(inputs.into_affine().into());
+    g2.push(pvk.gamma_g2_neg_pc.clone());
+    g1.push(c_sum.into_affine().into());
+    g2.push(pvk.delta_g2_neg_pc.clone());
+
+    match Bn254::final_exponentiation(Bn254::multi_miller_loop(g1, g2)) {
+        Some(result) => result.0 == pvk.alpha_g1_beta_g2.pow(r_sum.into_bigint()),
+        None => false,
+    }
+}
+
+/// Admit one proof of a batch: its format, statement and header, or the
+/// code ZK_VerifyVCProof returns for it
+fn admit(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    now: u64,
+    nonce: u64,
+) -> Result<(u8, Statement, validity::Header), c_int> {
+    let issuer_pubkey = admission::text_arg(issuer_pubkey, MAX_FIELD_LEN)
+        .ok_or_else(|| last_error::fail(0, "an issuer_pubkeys entry is NULL, too long or not UTF-8"))?;
+    let (header, format, proof) = admission::vc_proof_arg(proof_hex, proof::accepted_formats())
+        .map_err(|rejection| last_error::fail(rejection.verify_code(), rejection.describe()))?;
+    let issuer_pubkey_bytes = crate::did::public_key_bytes(issuer_pubkey)
+        .map_err(|e| last_error::fail(e.code_or(0), "an issuer_pubkeys entry is not hex or a did:key"))?;
+    let public_inputs = validity::public_inputs(&issuer_pubkey_bytes, format, nonce, now, &header)
+        .ok_or_else(|| last_error::fail(0, crate::UNSIGNED_ANCHOR))?
+        .to_vec();
+    Ok((format, Statement { proof, public_inputs }, header))
+}
+
+/// Admit and verify every proof of a batch at `now`, writing each one's
+/// code to `results`
+fn verify_batch(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proofs_hex: &[*const c_char],
+    issuer_pubkeys: &[*const c_char],
+    nonces: &[u64],
+    now: u64,
+    results: &mut [c_int],
+) {
+    // (result slot, format, header) of each statement
+    let mut admitted = Vec::with_capacity(results.len());
+    let mut statements = Vec::with_capacity(results.len());
+    for (i, result) in results.iter_mut().enumerate() {
+        match admit(proofs_hex[i], issuer_pubkeys[i], now, nonces[i]) {
+            Ok((format, statement, header)) => {
+                admitted.push((i, format, header));
+                statements.push(statement);
+            }
+            Err(code) => *result = code,
+        }
+    }
+
+    let batched = statements.len() > 1 && check(pvk, &statements);
+    for ((i, format, header), statement) in admitted.iter().zip(&statements) {
+        let valid = batched
+            || matches!(
+                Groth16::<Bn254>::verify_with_processed_vk(pvk, &statement.public_inputs, &statement.proof),
+                Ok(true)
+            );
+        proof::record_outcome(*format, valid);
+        if !valid {
+            last_error::fail(0, crate::verify_failure(header, now));
+        }
+        results[*i] = valid as c_int;
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Verify `count` VC proofs with one batched pairing check
+///
+/// Proof i is checked as ZK_VerifyVCProof(proofs_hex[i], issuer_pubkeys[i],
+/// current_time, nonces[i]) would, and its code is written to
+/// results_out[i]. The admitted proofs are verified together; if that check
+/// fails, each is verified on its own to find the invalid ones. Returns the
+/// number of valid proofs, -1 on NULL arrays or more than MAX_BATCH_PROOFS,
+/// or ZK_ERR_TIME_MISMATCH or -1 from the time source (ZK_SetTimeMode),
+/// which is also every proof's result. ZK_GetLastError describes the last
+/// proof that failed.
+#[no_mangle]
+pub extern "C" fn ZK_BatchVerifyVCProofs(
+    proofs_hex: *const *const c_char,
+    issuer_pubkeys: *const *const c_char,
+    current_time: u64,
+    nonces: *const u64,
+    count: usize,
+    results_out: *mut c_int,
+) -> c_int {
+    let started = Instant::now();
+    last_error::clear();
+
+    let (proofs_hex, issuer_pubkeys, nonces) = match (
+        read_slice(proofs_hex, count, MAX_BATCH_PROOFS),
+        read_slice(issuer_pubkeys, count, MAX_BATCH_PROOFS),
+        read_slice(nonces, count, MAX_BATCH_PROOFS),
+    ) {
+        (Ok(proofs_hex), Ok(issuer_pubkeys), Ok(nonces)) => (proofs_hex, issuer_pubkeys, nonces),
+        (Err(e), _, _) => return last_error::ffi("proofs_hex", e),
+        (_, Err(e), _) => return last_error::ffi("issuer_pubkeys", e),
+        (_, _, Err(e)) => return last_error::ffi("nonces", e),
+    };
+    if count == 0 {
+        return 0;
+    }
+    if results_out.is_null() {
+        return last_error::fail(-1, "results_out is NULL");
+    }
+    let results = unsafe { std::slice::from_raw_parts_mut(results_out, count) };
+
+    let pvk = current_verifying_key();
+
+    let clock_failure = match (&pvk, crate::proof_time(current_time)) {
+        (Some(pvk), Ok(now)) => {
+            verify_batch(pvk, proofs_hex, issuer_pubkeys, nonces, now, results);
+            None
+        }
+        (None, _) => {
+            results.fill(last_error::fail(0, "verifying key is not set up; call ZK_Init or import it"));
+            None
+        }
+        (_, Err(code)) => {
+            results.fill(code);
+            Some(code)
+        }
+    };
+
+    for (&nonce, &code) in nonces.iter().zip(results.iter()) {
+        audit::record(started, audit::Verification {
+            check: "batch-vc-proof",
+            code,
+            circuit: Some(audit::VC_CIRCUIT),
+            vk: pvk.as_ref().map(|pvk| &pvk.vk),
+            nonce: Some(nonce),
+            audience: None,
+            predicate: None,
+        });
+    }
+
+    clock_failure.unwrap_or_else(|| results.iter().filter(|&&code| code == 1).count() as c_int)
+}
+}
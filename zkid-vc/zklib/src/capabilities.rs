@@ -0,0 +1,483 @@
+// ============================================================================
+// Capability Advertisement and Profile Negotiation
+// ============================================================================
+//
+// A wallet emitting a proof format the gateway does not accept, or proving
+// with a circuit the gateway has no key for, otherwise fails verification
+// with a plain 0. Before proving, both sides exchange the JSON of
+// ZK_GetCapabilities:
+//
+//   {"crate":"zklib-vc","version":"0.1.0","prover":true,"verifier":false,
+//    "proof_system":"groth16","curves":["bn254"],"hashes":["sha256"],
+//    "formats":[1,2],"circuits":["zkid-vc/v7","zkid-vc/schedule/v2/w16"]}
+//
+// "formats" are the proof format versions this build supports, narrowed to
+// the accepted ones (ZK_SetAcceptedFormatVersions) in builds with the
+// verifier role. "circuits" are the circuit ids whose keys are loaded.
+//
+// ZK_SelectCommonProfile intersects two advertisements into a ZkProfile: the
+// highest common format, the common circuits, and the largest common
+// schedule capacity. One side must be able to prove and the other to
+// verify. When the sides share nothing in one dimension it returns
+// ZK_ERR_INCOMPATIBLE and names that dimension with both sides' values:
+//
+//   {"incompatible":"formats","mine":[2],"theirs":[1]}
+//
+// Dimensions are checked in the order proof_system, curves, hashes, roles,
+// formats, circuits. Circuit ids this build does not know are ignored.
+//
+// ZK_GenerateVCProofWithProfile and ZK_VerifyVCProofWithProfile then prove
+// and verify VC proofs in exactly the negotiated format, and refuse a
+// profile without the VC circuit or with a format this build does not
+// support (ZK_ERR_INCOMPATIBLE) before any proving happens.
+
+use serde_json::{json, Value};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use crate::age::{self, DERIVED_AGE_CIRCUIT};
+use crate::ballot::{self, BALLOT_CIRCUIT};
+use crate::composite::{self, GROUP_CLAIM_CIRCUIT};
+use crate::disclosure::{self, CLAIM_DISCLOSURE_CIRCUIT};
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_INCOMPATIBLE};
+use crate::ffi::write_cstr;
+use crate::grace::{self, GRACE_CIRCUIT};
+use crate::holder::{self, BOUND_VC_CIRCUIT};
+use crate::nonrevocation::{self, NON_REVOKED_VC_CIRCUIT};
+use crate::onetime::{self, ONE_TIME_VC_CIRCUIT};
+use crate::predicate::{self, PREDICATE_CIRCUIT};
+use crate::range::{self, RANGE_CLAIM_CIRCUIT};
+use crate::rebind::{self, HOLDER_BOUND_CIRCUIT};
+use crate::schedule::{self, SCHEDULE_CIRCUIT};
+use crate::sizes::{
+    VC_CIRCUIT, ZK_CIRCUIT_BALLOT, ZK_CIRCUIT_BOUND_VC, ZK_CIRCUIT_CLAIM_DISCLOSURE, ZK_CIRCUIT_DERIVED_AGE,
+    ZK_CIRCUIT_GRACE, ZK_CIRCUIT_GROUP_CLAIM, ZK_CIRCUIT_HOLDER_BOUND, ZK_CIRCUIT_NON_REVOKED_VC,
+    ZK_CIRCUIT_ONE_TIME_VC, ZK_CIRCUIT_PREDICATE, ZK_CIRCUIT_RANGE_CLAIM, ZK_CIRCUIT_SCHEDULE, ZK_CIRCUIT_VC,
+};
+use crate::{proof, VERIFYING_KEY};
+#[cfg(feature = "prover")]
+use crate::{credential::checked_blob, replay};
+#[cfg(feature = "verifier")]
+use crate::audit;
+
+const PROOF_SYSTEM: &str = "groth16";
+const CURVES: &[&str] = &["bn254"];
+const HASHES: &[&str] = &["sha256"];
+
+/// A negotiated profile (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkProfile {
+    /// Proof format version both sides use
+    pub format: u8,
+    /// Common circuits, bit 1 << ZK_CIRCUIT_*
+    pub circuits: u32,
+    /// Largest common schedule capacity in windows, 0 without the schedule
+    /// circuit
+    pub schedule_capacity: u32,
+}
+
+impl ZkProfile {
+    pub fn has_circuit(&self, circuit: c_int) -> bool {
+        self.circuits & circuit_bit(circuit) != 0
+    }
+}
+
+fn circuit_bit(circuit: c_int) -> u32 {
+    1u32.checked_shl(circuit as u32).unwrap_or(0)
+}
+
+/// Accepted-formats bit (ZK_ACCEPT_FORMAT_*) of a format version
+fn format_bit(format: u8) -> u32 {
+    1u32.checked_shl(u32::from(format).wrapping_sub(1)).unwrap_or(0)
+}
+
+/// One side's advertisement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    pub prover: bool,
+    pub verifier: bool,
+    pub proof_system: String,
+    pub curves: Vec<String>,
+    pub hashes: Vec<String>,
+    pub formats: Vec<u8>,
+    pub circuits: Vec<String>,
+}
+
+/// The first dimension two advertisements share nothing in
+#[derive(Clone, Debug, PartialEq)]
+pub struct Incompatibility {
+    pub dimension: &'static str,
+    pub mine: Value,
+    pub theirs: Value,
+}
+
+impl Incompatibility {
+    pub fn to_json(&self) -> Value {
+        json!({"incompatible": self.dimension, "mine": self.mine, "theirs": self.theirs})
+    }
+}
+
+impl Capabilities {
+    /// What this build can do with the keys loaded now
+    pub fn local() -> Self {
+        let mut formats: Vec<u8> = proof::PROOF_FORMATS
+            .into_iter()
+            .filter(|&format| proof::format_supported(format))
+            .collect();
+        if cfg!(feature = "verifier") {
+            formats.retain(|&format| format_bit(format) & proof::accepted_formats() != 0);
+        }
+
+        let mut circuits = Vec::new();
+        if VERIFYING_KEY.lock().is_ok_and(|key| key.is_some()) {
+            circuits.push(VC_CIRCUIT.to_string());
+        }
+        circuits.extend(schedule::keyed_capacities().into_iter().map(schedule::circuit_id));
+        if composite::has_keys() {
+            circuits.push(GROUP_CLAIM_CIRCUIT.to_string());
+        }
+        if ballot::has_keys() {
+            circuits.push(BALLOT_CIRCUIT.to_string());
+        }
+        if age::has_keys() {
+            circuits.push(DERIVED_AGE_CIRCUIT.to_string());
+        }
+        if rebind::has_keys() {
+            circuits.push(HOLDER_BOUND_CIRCUIT.to_string());
+        }
+        if grace::has_keys() {
+            circuits.push(GRACE_CIRCUIT.to_string());
+        }
+        if predicate::has_keys() {
+            circuits.push(PREDICATE_CIRCUIT.to_string());
+        }
+        if disclosure::has_keys() {
+            circuits.push(CLAIM_DISCLOSURE_CIRCUIT.to_string());
+        }
+        if range::has_keys() {
+            circuits.push(RANGE_CLAIM_CIRCUIT.to_string());
+        }
+        if holder::has_keys() {
+            circuits.push(BOUND_VC_CIRCUIT.to_string());
+        }
+        if onetime::has_keys() {
+            circuits.push(ONE_TIME_VC_CIRCUIT.to_string());
+        }
+        if nonrevocation::has_keys() {
+            circuits.push(NON_REVOKED_VC_CIRCUIT.to_string());
+        }
+
+        Self {
+            prover: cfg!(feature = "prover"),
+            verifier: cfg!(feature = "verifier"),
+            proof_system: PROOF_SYSTEM.to_string(),
+            curves: CURVES.iter().map(|s| s.to_string()).collect(),
+            hashes: HASHES.iter().map(|s| s.to_string()).collect(),
+            formats,
+            circuits,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "crate": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "prover": self.prover,
+            "verifier": self.verifier,
+            "proof_system": self.proof_system,
+            "curves": self.curves,
+            "hashes": self.hashes,
+            "formats": self.formats,
+            "circuits": self.circuits,
+        })
+    }
+
+    /// Parse an advertisement; None if a field is missing or mistyped
+    pub fn from_json(doc: &Value) -> Option<Self> {
+        let strings = |field: &str| -> Option<Vec<String>> {
+            doc.get(field)?
+                .as_array()?
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect()
+        };
+        let formats = doc
+            .get("formats")?
+            .as_array()?
+            .iter()
+            .map(|item| item.as_u64().and_then(|format| u8::try_from(format).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+
+        Some(Self {
+            prover: doc.get("prover")?.as_bool()?,
+            verifier: doc.get("verifier")?.as_bool()?,
+            proof_system: doc.get("proof_system")?.as_str()?.to_string(),
+            curves: strings("curves")?,
+            hashes: strings("hashes")?,
+            formats,
+            circuits: strings("circuits")?,
+        })
+    }
+
+    fn roles(&self) -> Value {
+        json!({"prover": self.prover, "verifier": self.verifier})
+    }
+}
+
+/// Schedule capacity of a schedule circuit id
+fn schedule_capacity(circuit: &str) -> Option<u32> {
+    circuit.strip_prefix(SCHEDULE_CIRCUIT)?.strip_prefix("/w")?.parse().ok()
+}
+
+/// Pick the best profile both advertisements support
+pub fn select(mine: &Capabilities, theirs: &Capabilities) -> Result<ZkProfile, Incompatibility> {
+    let mismatch = |dimension, mine: Value, theirs: Value| Incompatibility { dimension, mine, theirs };
+
+    if mine.proof_system != theirs.proof_system {
+        return Err(mismatch("proof_system", json!(mine.proof_system), json!(theirs.proof_system)));
+    }
+    if !mine.curves.iter().any(|curve| theirs.curves.contains(curve)) {
+        return Err(mismatch("curves", json!(mine.curves), json!(theirs.curves)));
+    }
+    if !mine.hashes.iter().any(|hash| theirs.hashes.contains(hash)) {
+        return Err(mismatch("hashes", json!(mine.hashes), json!(theirs.hashes)));
+    }
+    if !(mine.prover && theirs.verifier || mine.verifier && theirs.prover) {
+        return Err(mismatch("roles", mine.roles(), theirs.roles()));
+    }
+
+    let format = mine.formats.iter().filter(|format| theirs.formats.contains(format)).max();
+    let format = match format {
+        Some(&format) => format,
+        None => return Err(mismatch("formats", json!(mine.formats), json!(theirs.formats))),
+    };
+
+    let mut profile = ZkProfile {
+        format,
+        ..ZkProfile::default()
+    };
+    for circuit in mine.circuits.iter().filter(|circuit| theirs.circuits.contains(circuit)) {
+        if circuit == VC_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_VC);
+        } else if circuit == GROUP_CLAIM_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_GROUP_CLAIM);
+        } else if circuit == BALLOT_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_BALLOT);
+        } else if circuit == DERIVED_AGE_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_DERIVED_AGE);
+        } else if circuit == HOLDER_BOUND_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_HOLDER_BOUND);
+        } else if circuit == GRACE_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_GRACE);
+        } else if circuit == PREDICATE_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_PREDICATE);
+        } else if circuit == CLAIM_DISCLOSURE_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_CLAIM_DISCLOSURE);
+        } else if circuit == RANGE_CLAIM_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_RANGE_CLAIM);
+        } else if circuit == BOUND_VC_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_BOUND_VC);
+        } else if circuit == ONE_TIME_VC_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_ONE_TIME_VC);
+        } else if circuit == NON_REVOKED_VC_CIRCUIT {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_NON_REVOKED_VC);
+        } else if let Some(capacity) = schedule_capacity(circuit) {
+            profile.circuits |= circuit_bit(ZK_CIRCUIT_SCHEDULE);
+            profile.schedule_capacity = profile.schedule_capacity.max(capacity);
+        }
+    }
+    if profile.circuits == 0 {
+        return Err(mismatch("circuits", json!(mine.circuits), json!(theirs.circuits)));
+    }
+
+    Ok(profile)
+}
+
+/// Check that `profile` allows a VC proof this build can produce or check
+fn vc_format(profile: *const ZkProfile) -> Result<u8, c_int> {
+    let profile = unsafe { profile.as_ref() }.ok_or(-1)?;
+    if !profile.has_circuit(ZK_CIRCUIT_VC) || !proof::format_supported(profile.format) {
+        return Err(ZK_ERR_INCOMPATIBLE);
+    }
+    Ok(profile.format)
+}
+
+fn parse_json(ptr: *const c_char) -> Result<Value, c_int> {
+    if ptr.is_null() {
+        return Err(-1);
+    }
+    let text = unsafe { CStr::from_ptr(ptr) }.to_str().map_err(|_| ZK_ERR_CORRUPT)?;
+    serde_json::from_str(text).map_err(|_| ZK_ERR_CORRUPT)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Advertise this build's capabilities as JSON (see the module comment)
+///
+/// Reflects the keys loaded at the time of the call. Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small.
+#[no_mangle]
+pub extern "C" fn ZK_GetCapabilities(out: *mut c_char, out_size: usize) -> c_int {
+    match write_cstr(out, out_size, &Capabilities::local().to_json().to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Negotiate a profile from two capability advertisements
+///
+/// Writes the best common profile to `profile_out` and returns 0. If the
+/// sides share nothing in some dimension, returns ZK_ERR_INCOMPATIBLE and
+/// writes the incompatibility as JSON to `detail_out` (optional; skipped if
+/// NULL or too small). Returns ZK_ERR_CORRUPT for an advertisement that does
+/// not parse, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_SelectCommonProfile(
+    mine_json: *const c_char,
+    theirs_json: *const c_char,
+    profile_out: *mut ZkProfile,
+    detail_out: *mut c_char,
+    detail_out_size: usize,
+) -> c_int {
+    if profile_out.is_null() {
+        return -1;
+    }
+
+    let (mine, theirs) = match (parse_json(mine_json), parse_json(theirs_json)) {
+        (Ok(mine), Ok(theirs)) => (mine, theirs),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+    let (mine, theirs) = match (Capabilities::from_json(&mine), Capabilities::from_json(&theirs)) {
+        (Some(mine), Some(theirs)) => (mine, theirs),
+        _ => return ZK_ERR_CORRUPT,
+    };
+
+    match select(&mine, &theirs) {
+        Ok(profile) => {
+            unsafe {
+                *profile_out = profile;
+            }
+            0
+        }
+        Err(incompatibility) => {
+            if !detail_out.is_null() {
+                let _ = write_cstr(detail_out, detail_out_size, &incompatibility.to_json().to_string());
+            }
+            ZK_ERR_INCOMPATIBLE
+        }
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Generate a VC proof from a credential blob in the negotiated format
+///
+/// Same checks and nonce reuse policy as ZK_GenerateVCProofFromBlob, but the
+/// proof is in `profile`'s format instead of the emitted one and the call is
+/// not checkpointed. Returns 0 on success, ZK_ERR_INCOMPATIBLE if the profile
+/// lacks the VC circuit or its format is unsupported here, the pre-check's
+/// code for a credential that would not verify, -1 on other failures.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofWithProfile(
+    profile: *const ZkProfile,
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() {
+        return -1;
+    }
+    let format = match vc_format(profile) {
+        Ok(format) => format,
+        Err(code) => return code,
+    };
+
+    let (vc, issuer_key, current_time) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+
+    let signature = match vc.anchor_signature() {
+        Ok(signature) => signature,
+        Err(code) => return code,
+    };
+    let hash = vc.message_hash();
+    let anchored = match vc.opening() {
+        Some(opening) => crate::Anchored { opening, signature: &signature },
+        None => return -1,
+    };
+    let proof_hex = replay::guarded(&[], nonce, &[&hash, issuer_key.as_bytes(), &[format]], || {
+        crate::prove_vc_hash_with_format(format, &anchored, issuer_key.as_bytes(), current_time, nonce)
+    });
+    let proof_hex = match proof_hex {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a VC proof in the negotiated format only
+///
+/// Like ZK_VerifyVCProof, `current_time` included, but a proof in any other
+/// format than `profile`'s yields ZK_ERR_UNSUPPORTED_VERSION. Returns 1 if
+/// valid, 0 if invalid, the time codes of ZK_VerifyVCProof,
+/// ZK_ERR_INCOMPATIBLE if the profile lacks the VC circuit or its format is
+/// unsupported or no longer accepted here, -1 on NULL profile.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofWithProfile(
+    profile: *const ZkProfile,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = std::time::Instant::now();
+
+    let accepted = vc_format(profile).and_then(|format| {
+        let accepted = format_bit(format) & proof::accepted_formats();
+        if accepted == 0 {
+            return Err(ZK_ERR_INCOMPATIBLE);
+        }
+        Ok(accepted)
+    });
+
+    let pvk_guard = VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_ref());
+
+    let result = match (accepted, pvk) {
+        (Err(code), _) => code,
+        (Ok(accepted), Some(pvk)) => crate::verify_vc_proof_hex(pvk, proof_hex, issuer_pubkey, current_time, nonce, accepted),
+        (Ok(_), None) => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
@@ -0,0 +1,470 @@
+// ============================================================================
+// Canonical CBOR
+// ============================================================================
+//
+// Compact encodings for the transport between enclave and host, where hex
+// doubles every byte and JSON spells out names and quotes. Each artifact is
+// a CBOR map (RFC 8949) with small unsigned keys:
+//
+//   VC             0 layout version, 1 holder_id, 2 issuer, 3 issue_date,
+//                  4 expiry_date, 5 claims [[key, value], ...], 6 signature,
+//                  7 [co-signer key id, co-signature]           (if any)
+//                  8 supersedes                                  (if any)
+//                  9 validity schedule (schedule.rs encoding)    (if any)
+//                  10 claims root signed           (layout version >= 7)
+//                  11 message hash tagged          (layout version >= 9)
+//   VC proof       1 format, 2 current_time, 3 anchor and the issuer's
+//                  signature over it, 4 proof points
+//   presentation   1 VC proof (the map above), 2 issuer_pubkey, 3 nonce,
+//                  4 metadata [[key, value], ...]
+//
+// Keys, hashes, signatures and encodings are byte strings, names text
+// strings, dates and times integers. The layout version and the claims root
+// and tag flags name the message-hash layout as the VC blob version and its
+// trailing flags do (credential.rs). Proof points and the anchor are
+// compressed.
+//
+// Encodings are deterministic (RFC 8949 section 4.2.1): definite lengths,
+// the shortest integer heads, keys in ascending order, absent entries left
+// out rather than null. Decoding re-encodes what it read and refuses input
+// that is not byte for byte that encoding, so every artifact has exactly one
+// CBOR form and a hash over it is well defined. Signatures stay over the
+// message hash or the anchor taken from it, neither of which depends on the
+// encoding.
+//
+// Presentations decode from CBOR wherever a presentation is read
+// (Presentation::from_wire tells CBOR, binary and hex apart by their first
+// byte), and their digest stays over the normalized binary form, so it is
+// the same for every transport.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::Proof;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ed25519_dalek::SIGNATURE_LENGTH;
+use minicbor::{encode, Decoder, Encoder};
+use std::convert::Infallible;
+use std::os::raw::{c_char, c_int};
+
+use crate::admission::{self, MAX_PRESENTATION_LEN};
+use crate::credential::{implied_claims_root, implied_tag, parse_vc_blob, write_vc_blob, VC_BLOB_VERSION};
+use crate::encoding::{PointEncoding, ZkSerialize};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::presentation::Presentation;
+use crate::proof::{ENVELOPE_MAGIC, PROOF_FORMAT_LEGACY};
+use crate::schedule::ValiditySchedule;
+use crate::validity::{self, Header, ANCHOR_LEN, VALIDITY_PREFIX_LEN};
+use crate::vk::write_out;
+use crate::wire::Reader;
+use crate::{bytes_to_hex, dates, hex_to_bytes, CoSignature, VerifiableCredential};
+
+/// First byte of a CBOR presentation: a map of four entries
+pub const PRESENTATION_CBOR_HEAD: u8 = 0xa4;
+
+/// Presentation encodings for ZK_ConvertPresentationEncoding
+pub const ZK_PRESENTATION_ENCODING_BINARY: c_int = 0;
+pub const ZK_PRESENTATION_ENCODING_CBOR: c_int = 1;
+
+type Enc = Encoder<Vec<u8>>;
+type EncodeResult = Result<(), encode::Error<Infallible>>;
+
+/// Run `encode` into a fresh buffer
+fn encoded(encode: impl FnOnce(&mut Enc) -> EncodeResult) -> Vec<u8> {
+    let mut e = Encoder::new(Vec::new());
+    encode(&mut e).expect("writing to a Vec cannot fail");
+    e.into_writer()
+}
+
+/// Decode `data` and accept it only if it is the canonical encoding of
+/// what was read
+fn canonical<T>(
+    data: &[u8],
+    decode: impl FnOnce(&mut Decoder) -> Option<T>,
+    encode: impl FnOnce(&T) -> Vec<u8>,
+) -> Option<T> {
+    let value = decode(&mut Decoder::new(data))?;
+    (encode(&value) == data).then_some(value)
+}
+
+/// Number of entries of a definite-length map or array header
+fn definite(header: Result<Option<u64>, minicbor::decode::Error>) -> Option<u64> {
+    header.ok()?
+}
+
+fn encode_pairs(e: &mut Enc, pairs: &[(String, String)]) -> EncodeResult {
+    e.array(pairs.len() as u64)?;
+    for (key, value) in pairs {
+        e.array(2)?.str(key)?.str(value)?;
+    }
+    Ok(())
+}
+
+fn decode_pairs(d: &mut Decoder) -> Option<Vec<(String, String)>> {
+    let count = definite(d.array())?;
+    // Each pair takes at least three bytes, which bounds a lying count
+    if count > (d.input().len() / 3) as u64 {
+        return None;
+    }
+    (0..count)
+        .map(|_| {
+            if definite(d.array())? != 2 {
+                return None;
+            }
+            Some((d.str().ok()?.to_string(), d.str().ok()?.to_string()))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Credentials
+// ============================================================================
+
+fn encode_vc(e: &mut Enc, vc: &VerifiableCredential) -> EncodeResult {
+    let version = vc.blob_version();
+    let root_flag = implied_claims_root(version).is_none();
+    let tag_flag = implied_tag(version).is_none();
+    let entries = 7
+        + u64::from(vc.co_signature.is_some())
+        + u64::from(vc.supersedes.is_some())
+        + u64::from(vc.validity_schedule.is_some())
+        + u64::from(root_flag)
+        + u64::from(tag_flag);
+
+    e.map(entries)?;
+    e.u8(0)?.u8(version)?;
+    e.u8(1)?.str(&vc.holder_id)?;
+    e.u8(2)?.str(&vc.issuer)?;
+    e.u8(3)?.i64(vc.issue_date)?;
+    e.u8(4)?.i64(vc.expiry_date)?;
+    e.u8(5)?;
+    encode_pairs(e, &vc.claims)?;
+    e.u8(6)?.bytes(&vc.signature)?;
+    if let Some(co) = &vc.co_signature {
+        e.u8(7)?.array(2)?.bytes(&co.key_id)?.bytes(&co.signature)?;
+    }
+    if let Some(old_id) = &vc.supersedes {
+        e.u8(8)?.str(old_id)?;
+    }
+    if let Some(schedule) = &vc.validity_schedule {
+        let mut bytes = Vec::new();
+        schedule.encode(&mut bytes);
+        e.u8(9)?.bytes(&bytes)?;
+    }
+    if root_flag {
+        e.u8(10)?.bool(vc.claims_root_signed)?;
+    }
+    if tag_flag {
+        e.u8(11)?.bool(vc.domain_separated)?;
+    }
+    Ok(())
+}
+
+fn decode_vc(d: &mut Decoder) -> Option<VerifiableCredential> {
+    let mut vc = VerifiableCredential {
+        holder_id: String::new(),
+        issuer: String::new(),
+        issue_date: 0,
+        expiry_date: 0,
+        claims: Vec::new(),
+        signature: Vec::new(),
+        co_signature: None,
+        supersedes: None,
+        validity_schedule: None,
+        legacy_signature: false,
+        claims_root_signed: false,
+        length_prefixed: false,
+        domain_separated: false,
+        anchored: false,
+        schedule_anchored: false,
+    };
+    let (mut version, mut root_flag, mut tag_flag) = (None, None, None);
+
+    // Required entries 0 to 6 show up in the canonical re-encoding, so a
+    // missing one fails there
+    for _ in 0..definite(d.map())? {
+        match d.u8().ok()? {
+            0 => version = Some(d.u8().ok()?),
+            1 => vc.holder_id = d.str().ok()?.to_string(),
+            2 => vc.issuer = d.str().ok()?.to_string(),
+            3 => vc.issue_date = d.i64().ok()?,
+            4 => vc.expiry_date = d.i64().ok()?,
+            5 => vc.claims = decode_pairs(d)?,
+            6 => vc.signature = d.bytes().ok()?.to_vec(),
+            7 => {
+                if definite(d.array())? != 2 {
+                    return None;
+                }
+                vc.co_signature = Some(CoSignature {
+                    key_id: d.bytes().ok()?.try_into().ok()?,
+                    signature: d.bytes().ok()?.to_vec(),
+                });
+            }
+            8 => vc.supersedes = Some(d.str().ok()?.to_string()),
+            9 => {
+                let mut r = Reader::new(d.bytes().ok()?);
+                vc.validity_schedule = Some(ValiditySchedule::decode(&mut r)?);
+                if !r.is_empty() {
+                    return None;
+                }
+            }
+            10 => root_flag = Some(d.bool().ok()?),
+            11 => tag_flag = Some(d.bool().ok()?),
+            _ => return None,
+        }
+    }
+
+    let version = version.filter(|&v| v > 0 && v <= VC_BLOB_VERSION)?;
+    let claims_root_signed = implied_claims_root(version).or(root_flag)?;
+    let domain_separated = implied_tag(version).or(tag_flag)?;
+    vc.set_layout(version, claims_root_signed, domain_separated);
+    Some(vc)
+}
+
+impl VerifiableCredential {
+    /// Canonical CBOR encoding
+    pub fn to_cbor(&self) -> Vec<u8> {
+        encoded(|e| encode_vc(e, self))
+    }
+
+    /// Decode the canonical CBOR encoding written by `to_cbor`
+    pub fn from_cbor(data: &[u8]) -> Option<Self> {
+        canonical(data, decode_vc, Self::to_cbor)
+    }
+}
+
+// ============================================================================
+// Proofs and Presentations
+// ============================================================================
+
+/// A VC proof as its parts: header, format and proof
+pub type VcProof = (Header, u8, Proof<Bn254>);
+
+fn encode_proof(e: &mut Enc, (header, format, proof): &VcProof) -> EncodeResult {
+    let mut anchor = Vec::new();
+    header
+        .anchor
+        .serialize_compressed(&mut anchor)
+        .expect("serializing into a Vec cannot fail");
+    anchor.extend_from_slice(&header.signature);
+    e.map(4)?;
+    e.u8(1)?.u8(*format)?;
+    e.u8(2)?.u64(header.current_time)?;
+    e.u8(3)?.bytes(&anchor)?;
+    e.u8(4)?.bytes(&proof.zk_to_bytes(PointEncoding::Compressed))?;
+    Ok(())
+}
+
+/// Read a VC proof map and admit the proof it describes as the wire form
+/// would be (subgroup checks and build support included)
+fn decode_proof(d: &mut Decoder) -> Option<VcProof> {
+    if definite(d.map())? != 4 || d.u8().ok()? != 1 {
+        return None;
+    }
+    let format = d.u8().ok()?;
+    let current_time = if d.u8().ok()? == 2 { d.u64().ok()? } else { return None };
+    let signed_anchor = if d.u8().ok()? == 3 { d.bytes().ok()? } else { return None };
+    let points = if d.u8().ok()? == 4 { d.bytes().ok()? } else { return None };
+
+    if signed_anchor.len() != ANCHOR_LEN + SIGNATURE_LENGTH {
+        return None;
+    }
+    let (anchor, signature) = signed_anchor.split_at(signed_anchor.len() - SIGNATURE_LENGTH);
+    let header = Header::new(Fr::deserialize_compressed(anchor).ok()?, signature.try_into().ok()?, current_time);
+    let mut envelope = Vec::new();
+    if format != PROOF_FORMAT_LEGACY {
+        envelope.extend_from_slice(ENVELOPE_MAGIC);
+        envelope.push(format);
+    }
+    envelope.extend_from_slice(points);
+    admission::decode_vc_proof(&header.tag(&envelope), None).ok()
+}
+
+/// Canonical CBOR encoding of a VC proof
+pub fn vc_proof_to_cbor(proof: &VcProof) -> Vec<u8> {
+    encoded(|e| encode_proof(e, proof))
+}
+
+/// Decode a VC proof from its canonical CBOR encoding
+pub fn vc_proof_from_cbor(data: &[u8]) -> Option<VcProof> {
+    canonical(data, decode_proof, vc_proof_to_cbor)
+}
+
+fn encode_presentation(e: &mut Enc, p: &Presentation) -> EncodeResult {
+    e.map(4)?;
+    e.u8(1)?;
+    encode_proof(e, &(p.header, p.format, p.proof.clone()))?;
+    e.u8(2)?.bytes(&p.issuer_pubkey)?;
+    e.u8(3)?.u64(p.nonce)?;
+    e.u8(4)?;
+    encode_pairs(e, &p.metadata)
+}
+
+fn decode_presentation(d: &mut Decoder) -> Option<Presentation> {
+    if definite(d.map())? != 4 || d.u8().ok()? != 1 {
+        return None;
+    }
+    let (header, format, proof) = decode_proof(d)?;
+    let issuer_pubkey = if d.u8().ok()? == 2 { d.bytes().ok()?.to_vec() } else { return None };
+    let nonce = if d.u8().ok()? == 3 { d.u64().ok()? } else { return None };
+    let metadata = if d.u8().ok()? == 4 { decode_pairs(d)? } else { return None };
+    Some(Presentation {
+        format,
+        proof,
+        header,
+        issuer_pubkey,
+        nonce,
+        metadata,
+    })
+}
+
+impl Presentation {
+    /// Canonical CBOR encoding, metadata in insertion order
+    pub fn to_cbor(&self) -> Vec<u8> {
+        encoded(|e| encode_presentation(e, self))
+    }
+
+    /// Decode the canonical CBOR encoding written by `to_cbor`
+    pub fn from_cbor(data: &[u8]) -> Option<Self> {
+        canonical(data, decode_presentation, Self::to_cbor)
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Encode a hex VC blob as canonical CBOR
+///
+/// The CBOR bytes go to `cbor_out`, their length to `cbor_len_out` (also
+/// when the buffer is too small). Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 for a malformed blob or NULL.
+#[no_mangle]
+pub extern "C" fn ZK_SerializeVCCbor(
+    vc_blob: *const c_char,
+    cbor_out: *mut u8,
+    cbor_out_size: usize,
+    cbor_len_out: *mut usize,
+) -> c_int {
+    if vc_blob.is_null() {
+        return -1;
+    }
+    match parse_vc_blob(vc_blob) {
+        Some(vc) => write_out(&vc.to_cbor(), cbor_out, cbor_out_size, cbor_len_out),
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Decode `cbor_len` bytes of canonical CBOR into a hex VC blob
+///
+/// Non-canonical input is refused. Dates must lie inside the
+/// ZK_SetDateWindow window. Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_ParseVCCbor(
+    cbor: *const u8,
+    cbor_len: usize,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    let data = match read_bytes(cbor, cbor_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    match VerifiableCredential::from_cbor(data) {
+        Some(vc) if dates::valid_range(vc.issue_date, vc.expiry_date) => {
+            write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+        }
+        _ => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Encode a hex VC proof (with its validity prefix) as canonical CBOR
+///
+/// The proof is admitted first, as by ZK_PrecheckProof but whatever the
+/// accepted formats. Output as for ZK_SerializeVCCbor. Returns 0 on success, the admission code of a
+/// refused proof, ZK_ERR_BUFFER_TOO_SMALL, or -1 for NULL or bad hex.
+#[no_mangle]
+pub extern "C" fn ZK_SerializeProofCbor(
+    proof_hex: *const c_char,
+    cbor_out: *mut u8,
+    cbor_out_size: usize,
+    cbor_len_out: *mut usize,
+) -> c_int {
+    let bytes = match admission::text_arg(proof_hex, 2 * (VALIDITY_PREFIX_LEN + admission::MAX_PROOF_LEN))
+        .and_then(|text| hex_to_bytes(text).ok())
+    {
+        Some(bytes) => bytes,
+        None => return -1,
+    };
+    match admission::decode_vc_proof(&bytes, None) {
+        Ok(proof) => write_out(&vc_proof_to_cbor(&proof), cbor_out, cbor_out_size, cbor_len_out),
+        Err(rejection) => rejection.code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Decode `cbor_len` bytes of canonical CBOR into a hex VC proof
+///
+/// The result is the proof's wire form, for ZK_VerifyVCProof and the other
+/// hex entry points. Returns 0 on success, -1 for non-canonical CBOR or a
+/// proof that does not admit.
+#[no_mangle]
+pub extern "C" fn ZK_ParseProofCbor(
+    cbor: *const u8,
+    cbor_len: usize,
+    proof_hex_out: *mut c_char,
+    proof_hex_out_size: usize,
+) -> c_int {
+    let data = match read_bytes(cbor, cbor_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let (header, format, proof) = match vc_proof_from_cbor(data) {
+        Some(proof) => proof,
+        None => return -1,
+    };
+    match write_cstr(proof_hex_out, proof_hex_out_size, &bytes_to_hex(&validity::encode(&header, format, &proof))) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Re-encode a presentation (binary, hex text or CBOR) as binary
+/// (ZK_PRESENTATION_ENCODING_BINARY) or canonical CBOR (_CBOR)
+///
+/// The presentation functions read any of the three. The bytes go to
+/// `presentation_out`, their length to `presentation_len_out` (also when
+/// the buffer is too small). Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 for a presentation that does not decode
+/// or an unknown encoding.
+#[no_mangle]
+pub extern "C" fn ZK_ConvertPresentationEncoding(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    target_encoding: c_int,
+    presentation_out: *mut u8,
+    presentation_out_size: usize,
+    presentation_len_out: *mut usize,
+) -> c_int {
+    let data = match read_bytes(presentation_blob, presentation_blob_len, 2 * MAX_PRESENTATION_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let presentation = match Presentation::from_wire(data) {
+        Some(p) => p,
+        None => return -1,
+    };
+    let converted = match target_encoding {
+        ZK_PRESENTATION_ENCODING_BINARY => presentation.to_bytes(),
+        ZK_PRESENTATION_ENCODING_CBOR => presentation.to_cbor(),
+        _ => return -1,
+    };
+    write_out(&converted, presentation_out, presentation_out_size, presentation_len_out)
+}
+}
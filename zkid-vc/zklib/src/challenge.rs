@@ -0,0 +1,340 @@
+// ============================================================================
+// Stateless Verifier Challenges
+// ============================================================================
+//
+// A challenge is HMAC-SHA256(verifier_secret, context) truncated to the first
+// 8 bytes (little-endian u64), so it can be used directly as the `nonce`
+// argument of the prove/verify functions. The verifier keeps only its secret:
+// when the proof comes back it recomputes the challenge from the same context
+// instead of looking it up in a challenge store.
+//
+// Context layout for time-limited challenges:
+//
+//   issued_at (u64, little-endian, 8 bytes) || request-specific bytes
+//
+// The request-specific part should identify the session (user, audience,
+// request id, ...) so that different requests never share a challenge.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::clock;
+use crate::error::ZK_ERR_STORAGE;
+use crate::ffi::{read_bytes, MAX_FIELD_LEN};
+use crate::hex_to_bytes;
+use crate::limits::{self, ZK_COLLECTION_CHALLENGES};
+use crate::storage::{storage, Storage, StorageError};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length of the issued-at timestamp prefix of a challenge context
+pub const CHALLENGE_TIMESTAMP_LEN: usize = 8;
+
+/// Length of a derived challenge in bytes
+pub const CHALLENGE_LEN: usize = 8;
+
+fn challenge_mac(verifier_secret: &[u8], context: &[u8]) -> Option<HmacSha256> {
+    if verifier_secret.is_empty() {
+        return None;
+    }
+    let mut mac = HmacSha256::new_from_slice(verifier_secret).ok()?;
+    mac.update(context);
+    Some(mac)
+}
+
+/// Derive the challenge nonce bound to `context`
+pub fn derive_challenge(verifier_secret: &[u8], context: &[u8]) -> Option<u64> {
+    let tag = challenge_mac(verifier_secret, context)?.finalize().into_bytes();
+
+    let mut challenge = [0u8; CHALLENGE_LEN];
+    challenge.copy_from_slice(&tag[..CHALLENGE_LEN]);
+    Some(u64::from_le_bytes(challenge))
+}
+
+/// Read the issued-at timestamp embedded at the start of a challenge context
+pub fn context_timestamp(context: &[u8]) -> Option<u64> {
+    let prefix = context.get(..CHALLENGE_TIMESTAMP_LEN)?;
+    Some(u64::from_le_bytes(prefix.try_into().ok()?))
+}
+
+/// Recompute the challenge for a timestamped context and check its age.
+///
+/// The context is rejected if its timestamp lies in the future or is older
+/// than `max_age` seconds at `current_time`.
+pub fn validate_derived_challenge(
+    verifier_secret: &[u8],
+    context: &[u8],
+    challenge: u64,
+    current_time: u64,
+    max_age: u64,
+) -> bool {
+    let issued_at = match context_timestamp(context) {
+        Some(ts) => ts,
+        None => return false,
+    };
+
+    let mac = match challenge_mac(verifier_secret, context) {
+        Some(mac) => mac,
+        None => return false,
+    };
+
+    // Constant-time comparison against the truncated tag
+    if mac.verify_truncated_left(&challenge.to_le_bytes()).is_err() {
+        return false;
+    }
+
+    issued_at <= current_time && current_time - issued_at <= max_age
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Derive a per-request challenge nonce from the verifier secret and context
+///
+/// `verifier_secret` is hex-encoded; the challenge is written to `challenge_out`.
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_DeriveChallenge(
+    verifier_secret: *const c_char,
+    context: *const c_char,
+    context_len: usize,
+    challenge_out: *mut u64,
+) -> c_int {
+    if verifier_secret.is_null() || context.is_null() || challenge_out.is_null() {
+        return -1;
+    }
+
+    let secret_str = unsafe {
+        CStr::from_ptr(verifier_secret).to_str().unwrap_or("")
+    };
+
+    let secret_bytes = match hex_to_bytes(secret_str) {
+        Ok(bytes) => bytes,
+        Err(_) => return -1,
+    };
+
+    let context_bytes = match read_bytes(context, context_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match derive_challenge(&secret_bytes, context_bytes) {
+        Some(challenge) => {
+            unsafe {
+                *challenge_out = challenge;
+            }
+            0
+        }
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Validate a challenge derived from a timestamped context
+///
+/// The first 8 bytes of `context` must hold the issued-at time (u64 LE).
+/// Returns 1 if the challenge matches and is at most `max_age` seconds old,
+/// 0 otherwise, -1 on NULL (as ZK_DeriveChallenge), or a negative error
+/// code if the time source check fails.
+#[no_mangle]
+pub extern "C" fn ZK_ValidateDerivedChallenge(
+    verifier_secret: *const c_char,
+    context: *const c_char,
+    context_len: usize,
+    challenge: u64,
+    current_time: u64,
+    max_age: u64,
+) -> c_int {
+    if verifier_secret.is_null() || context.is_null() {
+        return -1;
+    }
+
+    let secret_str = unsafe {
+        CStr::from_ptr(verifier_secret).to_str().unwrap_or("")
+    };
+
+    let secret_bytes = match hex_to_bytes(secret_str) {
+        Ok(bytes) => bytes,
+        Err(_) => return 0,
+    };
+
+    let context_bytes = match read_bytes(context, context_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(_) => return 0,
+    };
+
+    let current_time = match clock::now(current_time) {
+        Ok(t) => t,
+        Err(code) => return code,
+    };
+
+    if validate_derived_challenge(&secret_bytes, context_bytes, challenge, current_time, max_age) {
+        1
+    } else {
+        0
+    }
+}
+}
+
+// ============================================================================
+// Challenge Store (stateful, consume-once)
+// ============================================================================
+
+const CHALLENGE_PREFIX: &[u8] = b"challenge/";
+
+/// Stored bytes of one challenge: prefixed nonce key and expiry
+#[cfg(feature = "prover")]
+pub(crate) const CHALLENGE_ENTRY_LEN: usize = CHALLENGE_PREFIX.len() + 8 + 8;
+
+// Serializes lookup + delete so a challenge can only be consumed once, and
+// the count + evict + insert of a limited store
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Issued challenges persisted through the storage backend
+pub struct ChallengeStore {
+    storage: Arc<dyn Storage>,
+}
+
+impl ChallengeStore {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    fn key(nonce: u64) -> Vec<u8> {
+        let mut key = CHALLENGE_PREFIX.to_vec();
+        key.extend_from_slice(&nonce.to_le_bytes());
+        key
+    }
+
+    fn expiry(value: &[u8]) -> Result<u64, StorageError> {
+        let bytes: [u8; 8] = value.try_into().map_err(|_| StorageError::Backend)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Outstanding challenges as (key, expires_at)
+    fn entries(&self) -> Result<Vec<(Vec<u8>, u64)>, StorageError> {
+        self.storage
+            .scan_prefix(CHALLENGE_PREFIX)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, Self::expiry(&value)?)))
+            .collect()
+    }
+
+    /// Number of outstanding challenges (expired ones included until pruned)
+    pub fn count(&self) -> Result<usize, StorageError> {
+        Ok(self.storage.scan_prefix(CHALLENGE_PREFIX)?.len())
+    }
+
+    /// Record a challenge that stays valid until `expires_at`
+    ///
+    /// In a store at its capacity, ZK_EVICT_OLDEST drops the challenges that
+    /// expire first.
+    pub fn issue(&self, nonce: u64, expires_at: u64) -> Result<(), StorageError> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let key = Self::key(nonce);
+        if limits::is_limited(ZK_COLLECTION_CHALLENGES) && self.storage.get(&key)?.is_none() {
+            let mut entries = self.entries()?;
+            let evict = limits::admit(ZK_COLLECTION_CHALLENGES, entries.len())?;
+            entries.sort_by_key(|(_, expires_at)| *expires_at);
+            for (old, _) in entries.iter().take(evict) {
+                self.storage.delete(old)?;
+            }
+            limits::record_evicted(ZK_COLLECTION_CHALLENGES, evict);
+        }
+        self.storage.put(&key, &expires_at.to_le_bytes())
+    }
+
+    /// Consume a challenge; returns false if it is unknown, used or expired
+    pub fn consume(&self, nonce: u64, current_time: u64) -> Result<bool, StorageError> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let key = Self::key(nonce);
+        let expires_at = match self.storage.get(&key)? {
+            Some(value) => Self::expiry(&value)?,
+            None => return Ok(false),
+        };
+
+        // The challenge is spent even if it has expired
+        self.storage.delete(&key)?;
+
+        Ok(current_time <= expires_at)
+    }
+
+    /// Remove the challenges that expired before `before`; returns how many
+    pub fn prune_expired(&self, before: u64) -> Result<usize, StorageError> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut removed = 0;
+        for (key, expires_at) in self.entries()? {
+            if expires_at < before {
+                self.storage.delete(&key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+crate::unwind::entry_point! {
+/// Store a verifier challenge until `expires_at`
+///
+/// Returns 0 on success, ZK_ERR_CAPACITY if the store is full and its policy
+/// is ZK_EVICT_REJECT, ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_IssueChallenge(nonce: u64, expires_at: u64) -> c_int {
+    match ChallengeStore::new(storage()).issue(nonce, expires_at) {
+        Ok(()) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Consume a previously issued challenge
+///
+/// Returns 1 if the challenge was outstanding and not expired, 0 if it is
+/// unknown, already consumed or expired, ZK_ERR_STORAGE if the backend failed
+/// or ZK_ERR_TIME_MISMATCH if the time source check fails.
+#[no_mangle]
+pub extern "C" fn ZK_ConsumeChallenge(nonce: u64, current_time: u64) -> c_int {
+    let current_time = match clock::now(current_time) {
+        Ok(t) => t,
+        Err(code) => return code,
+    };
+
+    match ChallengeStore::new(storage()).consume(nonce, current_time) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => ZK_ERR_STORAGE,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Remove every stored challenge that expired before `before` (unix time)
+///
+/// Expired challenges can no longer be consumed but stay stored until
+/// consumed or pruned. The number removed is written to `removed_out` (may
+/// be NULL). Returns 0 on success, ZK_ERR_STORAGE if the backend failed or
+/// cannot be scanned.
+#[no_mangle]
+pub extern "C" fn ZK_PruneChallenges(before: u64, removed_out: *mut u64) -> c_int {
+    match ChallengeStore::new(storage()).prune_expired(before) {
+        Ok(removed) => {
+            if let Some(out) = unsafe { removed_out.as_mut() } {
+                *out = removed as u64;
+            }
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
+}
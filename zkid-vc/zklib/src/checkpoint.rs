@@ -0,0 +1,751 @@
+// ============================================================================
+// Prove Checkpoints
+// ============================================================================
+//
+// A mobile OS may suspend or kill the wallet in the middle of a prove. With
+// ZK_SetProveCheckpointing the blob provers (ZK_GenerateVCProofFromBlob and
+// ZK_GenerateScheduleProof) save their progress into a caller buffer after
+// each stage, and ZK_ResumeProve finishes the proof from the last save:
+//
+//   witness   constraint synthesis done: blinding r, s and the assignment
+//   qap       + the h coefficients of the R1CS-to-QAP witness map
+//   msm 1..4  + the H, L, A and B (G1) multi-scalar multiplications, one
+//             per save; B (G2) and the final combination follow directly
+//
+// The stages replay ark-groth16's prover step by step with the same RNG
+// draws, so a resumed proof equals the one an uninterrupted prove returns.
+//
+// A checkpoint is framed, integers little-endian, as
+//
+//   "ZP" | request_len u32 | request | SHA-256(request)
+//        | progress_len u32 | progress | SHA-256(progress)
+//
+//   request   version u8 (1) | entry u8 | vc_blob | issuer_pubkey
+//             | second_issuer_pubkey (empty if none) | current_time u64 | nonce u64
+//   progress  version u8 | format u8 | circuit u8 | capacity u32
+//             | proving key fingerprint [32] | r | s | inputs u64
+//             | assignment | h? | G1 accumulators | G2 accumulator?
+//
+// with the strings length-prefixed and the field and curve elements in
+// arkworks' compressed encoding. The h coefficients dominate: from the qap
+// stage on a VC proof's checkpoint takes about 200 KB, a 16-window
+// schedule proof's about 3.5 MB, so size the buffer for those; a save that
+// does not fit is skipped. The request is the original call and keeps
+// this layout across versions. Progress that cannot be continued (written
+// by another version or for another proving key, or torn by a kill during
+// the save) makes ZK_ResumeProve prove again from the request and return
+// ZK_PROVE_RESTARTED instead of 0.
+//
+// A checkpoint holds the witness, the prover's blinding and the credential
+// itself: anyone who reads it can link and forge the holder's proofs. Keep
+// the buffer in memory the OS does not page out in clear and encrypt it
+// before persisting it across a kill; never send it anywhere. The library
+// wipes its own copies, and the buffer once the proof is done. A checkpoint
+// left behind by a prove that failed is wiped by ZK_Maintain once no prove
+// has run for CHECKPOINT_IDLE.
+
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_groth16::r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
+use ark_groth16::{Proof, ProvingKey};
+use ark_poly::GeneralEvaluationDomain;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::ops::AddAssign;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::sync::Mutex;
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::params::ZkCircuitParams;
+use crate::schedule::{self, ScheduleCircuit};
+use crate::sizes::{ZK_CIRCUIT_SCHEDULE, ZK_CIRCUIT_VC};
+use crate::wire::{put_bytes, Reader};
+use crate::{
+    bytes_to_hex, entropy, hex_to_bytes, proof, validity, vk, Anchored, VCCircuit, VerifiableCredential, PROVING_KEY,
+};
+
+/// ZK_ResumeProve could not continue the checkpoint and proved from the start
+pub const ZK_PROVE_RESTARTED: c_int = 1;
+
+/// Request entry: ZK_GenerateVCProofFromBlob
+pub(crate) const ENTRY_VC_PROOF_FROM_BLOB: u8 = 1;
+/// Request entry: ZK_GenerateScheduleProof
+pub(crate) const ENTRY_SCHEDULE_PROOF: u8 = 2;
+
+const CHECKPOINT_MAGIC: &[u8; 2] = b"ZP";
+const REQUEST_VERSION: u8 = 1;
+const PROGRESS_VERSION: u8 = 1;
+const DIGEST_LEN: usize = 32;
+
+/// version, format, circuit, capacity and key fingerprint
+const PROGRESS_HEADER_LEN: usize = 1 + 1 + 1 + 4 + 32;
+
+/// G1 accumulators before the G2 one: H, L, A, B
+const G1_ACCUMULATORS: usize = 4;
+
+type Domain = GeneralEvaluationDomain<Fr>;
+
+/// How long a checkpoint left in the sink outlives its prove
+pub const CHECKPOINT_IDLE: Duration = Duration::from_secs(600);
+
+/// Caller buffer the provers save into
+struct Sink {
+    buf: *mut u8,
+    cap: usize,
+    /// Bytes written so far, wiped when the proof is done
+    written: usize,
+    /// Time of the last save
+    saved_at: Option<Instant>,
+}
+
+// The caller keeps the buffer alive and reserved while checkpointing is on
+unsafe impl Send for Sink {}
+
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+/// Proves currently saving into the sink
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a prove as in flight until dropped
+struct Active;
+
+impl Active {
+    fn enter() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        Active
+    }
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn enabled() -> bool {
+    SINK.lock().map(|sink| sink.is_some()).unwrap_or(false)
+}
+
+/// Zero `len` bytes at `buf`
+fn wipe(buf: *mut u8, len: usize) {
+    if !buf.is_null() && len > 0 {
+        unsafe { std::slice::from_raw_parts_mut(buf, len) }.zeroize();
+    }
+}
+
+/// Wipe everything saved into the sink
+fn wipe_sink() {
+    if let Ok(mut sink) = SINK.lock() {
+        if let Some(sink) = sink.as_mut() {
+            wipe(sink.buf, sink.written);
+            sink.written = 0;
+        }
+    }
+}
+
+fn c_str(ptr: *const c_char) -> Option<&'static str> {
+    if ptr.is_null() {
+        return Some("");
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+/// NUL-terminated copy of `s` that is wiped on drop
+fn c_string(s: &str) -> Zeroizing<Vec<u8>> {
+    let mut out = Zeroizing::new(Vec::with_capacity(s.len() + 1));
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+    out
+}
+
+// ============================================================================
+// Request
+// ============================================================================
+
+/// The arguments of the prove call a checkpoint belongs to
+pub(crate) struct Request {
+    entry: u8,
+    vc_blob: Zeroizing<String>,
+    issuer_pubkey: String,
+    second_issuer_pubkey: String,
+    current_time: u64,
+    nonce: u64,
+}
+
+impl Request {
+    /// Record a blob prove call, None unless checkpointing is on
+    pub(crate) fn capture(
+        entry: u8,
+        vc_blob: *const c_char,
+        issuer_pubkey: *const c_char,
+        second_issuer_pubkey: *const c_char,
+        current_time: u64,
+        nonce: u64,
+    ) -> Option<Self> {
+        if !enabled() {
+            return None;
+        }
+        Some(Self {
+            entry,
+            vc_blob: Zeroizing::new(c_str(vc_blob)?.to_string()),
+            issuer_pubkey: c_str(issuer_pubkey)?.to_string(),
+            second_issuer_pubkey: c_str(second_issuer_pubkey)?.to_string(),
+            current_time,
+            nonce,
+        })
+    }
+
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::new());
+        out.push(REQUEST_VERSION);
+        out.push(self.entry);
+        put_bytes(&mut out, self.vc_blob.as_bytes());
+        put_bytes(&mut out, self.issuer_pubkey.as_bytes());
+        put_bytes(&mut out, self.second_issuer_pubkey.as_bytes());
+        out.extend_from_slice(&self.current_time.to_le_bytes());
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.u8()? != REQUEST_VERSION {
+            return None;
+        }
+        let request = Self {
+            entry: r.u8()?,
+            vc_blob: Zeroizing::new(r.string()?),
+            issuer_pubkey: r.string()?,
+            second_issuer_pubkey: r.string()?,
+            current_time: r.u64()?,
+            nonce: r.u64()?,
+        };
+        r.is_empty().then_some(request)
+    }
+
+    /// The requested credential
+    fn credential(&self) -> Option<VerifiableCredential> {
+        VerifiableCredential::from_bytes(&hex_to_bytes(&self.vc_blob).ok()?)
+    }
+
+    /// Header a VC proof of the requested credential carries
+    fn header(&self) -> Option<validity::Header> {
+        let vc = self.credential()?;
+        let signature = vc.anchor_signature().ok()?;
+        Some(validity::Header::new(vc.anchor()?, signature, self.current_time))
+    }
+
+    /// Make the original call again, from the start
+    fn reprove(&self, proof_out: *mut c_char, proof_out_size: usize) -> c_int {
+        let vc_blob = c_string(&self.vc_blob);
+        let issuer_pubkey = c_string(&self.issuer_pubkey);
+        let second = c_string(&self.second_issuer_pubkey);
+        let second_ptr = if self.second_issuer_pubkey.is_empty() {
+            std::ptr::null()
+        } else {
+            second.as_ptr() as *const c_char
+        };
+
+        let prove = match self.entry {
+            ENTRY_VC_PROOF_FROM_BLOB => crate::credential::ZK_GenerateVCProofFromBlob,
+            ENTRY_SCHEDULE_PROOF => schedule::ZK_GenerateScheduleProof,
+            _ => return ZK_ERR_CORRUPT,
+        };
+        prove(
+            vc_blob.as_ptr() as *const c_char,
+            issuer_pubkey.as_ptr() as *const c_char,
+            second_ptr,
+            self.current_time,
+            self.nonce,
+            proof_out,
+            proof_out_size,
+        )
+    }
+}
+
+// ============================================================================
+// Progress
+// ============================================================================
+
+/// Prover state after the last completed stage
+struct Progress {
+    format: u8,
+    params: ZkCircuitParams,
+    key: [u8; 32],
+    r: Fr,
+    s: Fr,
+    /// Instance variables, the constant 1 included
+    inputs: usize,
+    /// Instance then witness assignment
+    assignment: Vec<Fr>,
+    h: Option<Vec<Fr>>,
+    g1: Vec<G1Affine>,
+    g2_b: Option<G2Affine>,
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.r.zeroize();
+        self.s.zeroize();
+        self.assignment.zeroize();
+        self.h.zeroize();
+    }
+}
+
+impl Progress {
+    fn to_bytes(&self) -> Option<Zeroizing<Vec<u8>>> {
+        let mut out = Zeroizing::new(vec![PROGRESS_VERSION, self.format, self.params.circuit as u8]);
+        out.extend_from_slice(&self.params.capacity.to_le_bytes());
+        out.extend_from_slice(&self.key);
+        self.r.serialize_compressed(&mut *out).ok()?;
+        self.s.serialize_compressed(&mut *out).ok()?;
+        (self.inputs as u64).serialize_compressed(&mut *out).ok()?;
+        self.assignment.serialize_compressed(&mut *out).ok()?;
+        self.h.serialize_compressed(&mut *out).ok()?;
+        self.g1.serialize_compressed(&mut *out).ok()?;
+        self.g2_b.serialize_compressed(&mut *out).ok()?;
+        Some(out)
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.u8()? != PROGRESS_VERSION {
+            return None;
+        }
+        let format = r.u8()?;
+        let params = ZkCircuitParams {
+            circuit: c_int::from(r.u8()?),
+            capacity: r.u32()?,
+        };
+        let key = r.take(32)?.try_into().ok()?;
+
+        let mut rest = &data[PROGRESS_HEADER_LEN..];
+        let mut progress = Self {
+            format,
+            params,
+            key,
+            r: Fr::zero(),
+            s: Fr::zero(),
+            inputs: 0,
+            assignment: Vec::new(),
+            h: None,
+            g1: Vec::new(),
+            g2_b: None,
+        };
+        progress.r = Fr::deserialize_compressed(&mut rest).ok()?;
+        progress.s = Fr::deserialize_compressed(&mut rest).ok()?;
+        progress.inputs = usize::try_from(u64::deserialize_compressed(&mut rest).ok()?).ok()?;
+        progress.assignment = Vec::deserialize_compressed(&mut rest).ok()?;
+        progress.h = Option::deserialize_compressed(&mut rest).ok()?;
+        progress.g1 = Vec::deserialize_compressed(&mut rest).ok()?;
+        progress.g2_b = Option::deserialize_compressed(&mut rest).ok()?;
+
+        let consistent = rest.is_empty()
+            && params.is_supported()
+            && (1..=progress.assignment.len()).contains(&progress.inputs)
+            && progress.g1.len() <= G1_ACCUMULATORS
+            && (progress.h.is_some() || progress.g1.is_empty())
+            && progress.g2_b.is_none();
+        consistent.then_some(progress)
+    }
+
+    /// Run the next stage; false once the proof is ready
+    fn advance(&mut self, pk: &ProvingKey<Bn254>, matrices: &mut Option<ConstraintMatrices<Fr>>) -> Result<bool, c_int> {
+        let h = match &self.h {
+            Some(h) => h,
+            None => {
+                if matrices.is_none() {
+                    *matrices = Some(blank_matrices(self.params).ok_or(-1)?);
+                }
+                let m = matrices.as_ref().ok_or(-1)?;
+                if m.num_instance_variables != self.inputs
+                    || m.num_instance_variables + m.num_witness_variables != self.assignment.len()
+                {
+                    return Err(-1);
+                }
+                let h = LibsnarkReduction::witness_map_from_matrices::<Fr, Domain>(
+                    m,
+                    self.inputs,
+                    m.num_constraints,
+                    &self.assignment,
+                )
+                .map_err(|_| ZK_ERR_PROVE_FAILED)?;
+                self.h = Some(h);
+                return Ok(true);
+            }
+        };
+
+        // Public inputs without the constant, then the witness
+        let assignment: Vec<_> = self.assignment[1..].iter().map(|v| v.into_bigint()).collect();
+        let aux = &assignment[self.inputs - 1..];
+        let (r, s) = (self.r, self.s);
+
+        let next = match self.g1.len() {
+            0 => {
+                let h: Vec<_> = h.iter().map(|v| v.into_bigint()).collect();
+                G1Projective::msm_bigint(&pk.h_query, &h)
+            }
+            1 => G1Projective::msm_bigint(&pk.l_query, aux),
+            2 => coefficient(pk.delta_g1 * r, &pk.a_query, pk.vk.alpha_g1, &assignment),
+            3 if r.is_zero() => G1Projective::zero(),
+            3 => coefficient(pk.delta_g1 * s, &pk.b_g1_query, pk.beta_g1, &assignment),
+            _ => {
+                let g2_b = coefficient(pk.vk.delta_g2 * s, &pk.b_g2_query, pk.vk.beta_g2, &assignment);
+                self.g2_b = Some(g2_b.into_affine());
+                return Ok(false);
+            }
+        };
+        self.g1.push(next.into_affine());
+        Ok(true)
+    }
+
+    /// Combine the accumulators into the proof
+    fn finish(&self, pk: &ProvingKey<Bn254>) -> Option<Proof<Bn254>> {
+        let (h_acc, l_acc, g_a, g1_b) = match self.g1[..] {
+            [h_acc, l_acc, g_a, g1_b] => (h_acc, l_acc, g_a, g1_b),
+            _ => return None,
+        };
+        let (r, s) = (self.r.into_bigint(), self.s.into_bigint());
+
+        let mut g_c = g_a.into_group().mul_bigint(s);
+        g_c += g1_b.into_group().mul_bigint(r);
+        g_c -= pk.delta_g1.into_group().mul_bigint(r).mul_bigint(s);
+        g_c += l_acc;
+        g_c += h_acc;
+
+        Some(Proof {
+            a: g_a,
+            b: self.g2_b?,
+            c: g_c.into_affine(),
+        })
+    }
+}
+
+/// initial + query[0] + <query[1..], assignment> + vk_param, as in
+/// ark-groth16's prover
+fn coefficient<G: AffineRepr>(
+    initial: G::Group,
+    query: &[G],
+    vk_param: G,
+    assignment: &[<G::ScalarField as PrimeField>::BigInt],
+) -> G::Group
+where
+    G::Group: VariableBaseMSM<MulBase = G>,
+{
+    let acc = G::Group::msm_bigint(&query[1..], assignment);
+    let mut res = initial;
+    res.add_assign(&query[0]);
+    res += &acc;
+    res.add_assign(&vk_param);
+    res
+}
+
+/// Constraint matrices of a circuit shape, as at setup
+fn blank_matrices(params: ZkCircuitParams) -> Option<ConstraintMatrices<Fr>> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    let synthesized = match params.circuit {
+        ZK_CIRCUIT_VC => VCCircuit::blank().generate_constraints(cs.clone()),
+        ZK_CIRCUIT_SCHEDULE => ScheduleCircuit::blank(params.capacity as usize).generate_constraints(cs.clone()),
+        _ => return None,
+    };
+    synthesized.ok()?;
+    cs.finalize();
+    cs.to_matrices()
+}
+
+/// SHA-256 of the compressed verifying key inside a proving key
+fn key_fingerprint(pk: &ProvingKey<Bn254>) -> [u8; 32] {
+    Sha256::digest(vk::encode(&pk.vk)).into()
+}
+
+fn with_proving_key<T>(params: ZkCircuitParams, f: impl FnOnce(&ProvingKey<Bn254>) -> T) -> Option<T> {
+    match params.circuit {
+        ZK_CIRCUIT_VC => PROVING_KEY.lock().ok()?.as_deref().map(f),
+        ZK_CIRCUIT_SCHEDULE => schedule::with_proving_key(params.capacity as usize, f),
+        _ => None,
+    }
+}
+
+/// Hex proof as the entry point of `params` returns it for `request`
+fn encode_proof(request: &Request, params: ZkCircuitParams, format: u8, proof: &Proof<Bn254>) -> Result<String, c_int> {
+    let bytes = proof::encode(format, proof);
+    match params.circuit {
+        ZK_CIRCUIT_SCHEDULE => Ok(bytes_to_hex(&params.tag(&bytes))),
+        _ => Ok(bytes_to_hex(&request.header().ok_or(ZK_ERR_CORRUPT)?.tag(&bytes))),
+    }
+}
+
+// ============================================================================
+// Saving and Resuming
+// ============================================================================
+
+fn frame(request: &[u8], progress: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut out = Zeroizing::new(Vec::with_capacity(
+        CHECKPOINT_MAGIC.len() + 2 * (4 + DIGEST_LEN) + request.len() + progress.len(),
+    ));
+    out.extend_from_slice(CHECKPOINT_MAGIC);
+    for section in [request, progress] {
+        put_bytes(&mut out, section);
+        out.extend_from_slice(&Sha256::digest(section));
+    }
+    out
+}
+
+/// Split a checkpoint into its request and, if intact, its progress
+fn unframe(data: &[u8]) -> Option<(Request, Option<Progress>)> {
+    let mut r = Reader::new(data);
+    if r.take(CHECKPOINT_MAGIC.len())? != CHECKPOINT_MAGIC {
+        return None;
+    }
+    let request = r.bytes()?;
+    if r.take(DIGEST_LEN)? != Sha256::digest(request).as_slice() {
+        return None;
+    }
+    let request = Request::from_bytes(request)?;
+
+    let progress = r
+        .bytes()
+        .zip(r.take(DIGEST_LEN))
+        .filter(|(progress, digest)| *digest == Sha256::digest(progress).as_slice())
+        .and_then(|(progress, _)| Progress::from_bytes(progress));
+    Some((request, progress))
+}
+
+/// Write a checkpoint into the sink; one that does not fit is skipped
+fn save(request: &[u8], progress: &Progress) {
+    let progress = match progress.to_bytes() {
+        Some(bytes) => bytes,
+        None => return,
+    };
+    let checkpoint = frame(request, &progress);
+
+    if let Ok(mut sink) = SINK.lock() {
+        if let Some(sink) = sink.as_mut().filter(|sink| checkpoint.len() <= sink.cap) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(checkpoint.as_ptr(), sink.buf, checkpoint.len());
+            }
+            sink.written = sink.written.max(checkpoint.len());
+            sink.saved_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Run the remaining stages, saving after each, and wipe the sink
+fn run(
+    pk: &ProvingKey<Bn254>,
+    progress: &mut Progress,
+    mut matrices: Option<ConstraintMatrices<Fr>>,
+    request: &[u8],
+) -> Result<Proof<Bn254>, c_int> {
+    while progress.advance(pk, &mut matrices)? {
+        save(request, progress);
+    }
+    let proof = progress.finish(pk).ok_or(ZK_ERR_PROVE_FAILED)?;
+    wipe_sink();
+    Ok(proof)
+}
+
+/// Prove `circuit` as Groth16::prove does with the same RNG, saving a
+/// checkpoint after every stage
+pub(crate) fn prove<C: ConstraintSynthesizer<Fr>>(
+    pk: &ProvingKey<Bn254>,
+    circuit: C,
+    rng: &mut impl Rng,
+    params: ZkCircuitParams,
+    format: u8,
+    request: &Request,
+) -> Result<Proof<Bn254>, c_int> {
+    let _active = Active::enter();
+    let r = Fr::rand(rng);
+    let s = Fr::rand(rng);
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone()).map_err(|_| ZK_ERR_PROVE_FAILED)?;
+    cs.finalize();
+    let matrices = cs.to_matrices().ok_or(ZK_ERR_PROVE_FAILED)?;
+    let (inputs, assignment) = {
+        let cs = cs.borrow().ok_or(ZK_ERR_PROVE_FAILED)?;
+        (cs.instance_assignment.len(), [&cs.instance_assignment[..], &cs.witness_assignment[..]].concat())
+    };
+
+    let mut progress = Progress {
+        format,
+        params,
+        key: key_fingerprint(pk),
+        r,
+        s,
+        inputs,
+        assignment,
+        h: None,
+        g1: Vec::new(),
+        g2_b: None,
+    };
+    let request = request.to_bytes();
+    save(&request, &progress);
+    run(pk, &mut progress, Some(matrices), &request)
+}
+
+/// ZK_GenerateVCProofFromBlob's proving step with checkpoints
+pub(crate) fn prove_vc(request: &Request, credential: &Anchored, issuer_pubkey_bytes: &[u8]) -> Result<String, c_int> {
+    let format = proof::emit_format();
+    let (circuit, _) =
+        crate::vc_circuit(format, credential, issuer_pubkey_bytes, request.current_time, request.nonce, None)
+            .ok_or(-1)?;
+
+    #[cfg(feature = "debug-circuit")]
+    crate::satisfiability::check(circuit.clone())?;
+
+    let pk_guard = PROVING_KEY.lock().map_err(|_| -1)?;
+    let pk = pk_guard.as_ref().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+    let params = ZkCircuitParams {
+        circuit: ZK_CIRCUIT_VC,
+        capacity: 0,
+    };
+    let proof = prove(pk, circuit, &mut entropy::prover_rng(request.nonce)?, params, format, request)?;
+    encode_proof(request, params, format, &proof)
+}
+
+/// Continue saved progress with the current keys; None if the keys differ
+fn resume(request: &Request, mut progress: Progress) -> Option<Result<String, c_int>> {
+    let _active = Active::enter();
+    let params = progress.params;
+    let request_bytes = request.to_bytes();
+    with_proving_key(params, |pk| {
+        if key_fingerprint(pk) != progress.key {
+            return None;
+        }
+        let proof = run(pk, &mut progress, None, &request_bytes);
+        Some(proof.and_then(|proof| encode_proof(request, params, progress.format, &proof)))
+    })
+    .flatten()
+}
+
+/// Bytes of checkpoint held in the sink and the sink's capacity
+pub(crate) fn held() -> (usize, usize) {
+    match SINK.lock().as_deref() {
+        Ok(Some(sink)) => (sink.written, sink.cap),
+        _ => (0, 0),
+    }
+}
+
+/// Wipe a checkpoint no prove has saved into for `idle`; returns the bytes
+/// wiped. A checkpoint of a prove in flight is never touched.
+pub(crate) fn sweep(idle: Duration) -> usize {
+    let mut guard = match SINK.lock() {
+        Ok(guard) => guard,
+        Err(_) => return 0,
+    };
+    let sink = match guard.as_mut() {
+        Some(sink) if sink.written > 0 => sink,
+        _ => return 0,
+    };
+    if IN_FLIGHT.load(Ordering::SeqCst) > 0 || sink.saved_at.is_some_and(|at| at.elapsed() < idle) {
+        return 0;
+    }
+    let wiped = sink.written;
+    wipe(sink.buf, wiped);
+    sink.written = 0;
+    sink.saved_at = None;
+    wiped
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Save prove progress into `buf` (see checkpoint.rs for what is saved)
+///
+/// While set, ZK_GenerateVCProofFromBlob and ZK_GenerateScheduleProof
+/// overwrite `buf` with a checkpoint after every stage; a checkpoint larger
+/// than `cap` is skipped. Once the proof is done the buffer is wiped. The
+/// buffer must stay valid until checkpointing is turned off, and only one
+/// prove at a time should run while it is on. The checkpoint holds the
+/// credential and witness and must be kept secret. NULL and 0 turn
+/// checkpointing off. Returns 0 on success, -1 if only one of `buf` and
+/// `cap` is given.
+#[no_mangle]
+pub extern "C" fn ZK_SetProveCheckpointing(buf: *mut u8, cap: usize) -> c_int {
+    let sink = match (buf.is_null(), cap) {
+        (true, 0) => None,
+        (false, cap) if cap > 0 => Some(Sink {
+            buf,
+            cap,
+            written: 0,
+            saved_at: None,
+        }),
+        _ => return -1,
+    };
+    match SINK.lock() {
+        Ok(mut guard) => {
+            *guard = sink;
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Finish the prove a checkpoint was saved for and write its hex proof
+///
+/// The keys must be set up as for the original call (ZK_Init, and
+/// ZK_RegisterCircuitParams for a schedule capacity other than the
+/// default). The proof is for the original nonce and time. If the progress
+/// cannot be continued, the original call is made again from the start.
+/// On success `checkpoint` is wiped. Returns 0 if resumed,
+/// ZK_PROVE_RESTARTED if proved again from the start, ZK_ERR_CORRUPT if the
+/// checkpoint is not one or its request is damaged, or the error code of
+/// the prove.
+#[no_mangle]
+pub extern "C" fn ZK_ResumeProve(
+    checkpoint: *mut u8,
+    checkpoint_len: usize,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if checkpoint.is_null() || proof_out.is_null() {
+        return -1;
+    }
+
+    // Copied, since resuming saves into the sink, which may be this buffer
+    let data = match read_bytes(checkpoint as *const u8, checkpoint_len, MAX_BLOB_LEN) {
+        Ok(data) => Zeroizing::new(data.to_vec()),
+        Err(e) => return e.code(),
+    };
+    let (request, progress) = match unframe(&data) {
+        Some(parts) => parts,
+        None => return ZK_ERR_CORRUPT,
+    };
+
+    let result = match progress.and_then(|progress| resume(&request, progress)) {
+        Some(Ok(proof_hex)) => match write_cstr(proof_out, proof_out_size, &proof_hex) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        Some(Err(code)) => code,
+        None => match request.reprove(proof_out, proof_out_size) {
+            0 => ZK_PROVE_RESTARTED,
+            code => code,
+        },
+    };
+
+    if result >= 0 {
+        wipe(checkpoint, checkpoint_len);
+    }
+    result
+}
+}
@@ -0,0 +1,118 @@
+// ============================================================================
+// Time Source
+// ============================================================================
+//
+// Time checks in the prove/verify paths go through `now()` so the library can
+// use a trusted clock (enclave timer, signed time attestation relayed by the
+// host) instead of the `current_time` value supplied by the caller.
+//
+//   ZK_TIME_CALLER      use the caller-supplied value (default)
+//   ZK_TIME_CALLBACK    ignore the caller value, use the time callback
+//   ZK_TIME_CROSSCHECK  use the callback, but fail with ZK_ERR_TIME_MISMATCH
+//                       if the caller value differs by more than max_skew
+
+use std::os::raw::{c_int, c_void};
+use crate::sync::Mutex;
+
+use crate::error::ZK_ERR_TIME_MISMATCH;
+
+pub const ZK_TIME_CALLER: c_int = 0;
+pub const ZK_TIME_CALLBACK: c_int = 1;
+pub const ZK_TIME_CROSSCHECK: c_int = 2;
+
+/// Returns the current unix time in seconds
+pub type TimeCallback = extern "C" fn(user_data: *mut c_void) -> u64;
+
+/// Where `now()` takes the current time from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeMode {
+    Caller,
+    Callback,
+    CrossCheck { max_skew: u64 },
+}
+
+struct TimeSource {
+    mode: TimeMode,
+    callback: Option<(TimeCallback, *mut c_void)>,
+}
+
+// The host guarantees the callback and user_data may be used from any thread
+unsafe impl Send for TimeSource {}
+
+static TIME_SOURCE: Mutex<TimeSource> = Mutex::new(TimeSource {
+    mode: TimeMode::Caller,
+    callback: None,
+});
+
+/// Resolve the current time according to the configured mode
+///
+/// Fails with -1 if a callback mode is selected but no callback is installed,
+/// or ZK_ERR_TIME_MISMATCH if the cross-check detects skew.
+pub fn now(caller_time: u64) -> Result<u64, c_int> {
+    now_with(time_mode(), caller_time)
+}
+
+/// Resolve the current time under `mode` instead of the configured one
+///
+/// The installed callback is still used; contexts carry their own mode and
+/// skew but share the process-wide time callback.
+pub fn now_with(mode: TimeMode, caller_time: u64) -> Result<u64, c_int> {
+    let callback = TIME_SOURCE.lock().unwrap_or_else(|e| e.into_inner()).callback;
+
+    let trusted = match (mode, callback) {
+        (TimeMode::Caller, _) => return Ok(caller_time),
+        (_, Some((callback, user_data))) => callback(user_data),
+        (_, None) => return Err(-1),
+    };
+
+    if let TimeMode::CrossCheck { max_skew } = mode {
+        if trusted.abs_diff(caller_time) > max_skew {
+            return Err(ZK_ERR_TIME_MISMATCH);
+        }
+    }
+
+    Ok(trusted)
+}
+
+/// Currently configured time mode
+pub fn time_mode() -> TimeMode {
+    TIME_SOURCE.lock().unwrap_or_else(|e| e.into_inner()).mode
+}
+
+/// Select the time mode
+pub fn set_time_mode(mode: TimeMode) {
+    TIME_SOURCE.lock().unwrap_or_else(|e| e.into_inner()).mode = mode;
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Install (or remove with NULL) the trusted time callback
+#[no_mangle]
+pub extern "C" fn ZK_SetTimeCallback(callback: Option<TimeCallback>, user_data: *mut c_void) -> c_int {
+    let mut source = TIME_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    source.callback = callback.map(|callback| (callback, user_data));
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Select where time checks take the current time from
+///
+/// `mode` is one of ZK_TIME_CALLER, ZK_TIME_CALLBACK, ZK_TIME_CROSSCHECK;
+/// `max_skew` (seconds) only applies to ZK_TIME_CROSSCHECK.
+/// Returns 0 on success, -1 on an unknown mode.
+#[no_mangle]
+pub extern "C" fn ZK_SetTimeMode(mode: c_int, max_skew: u64) -> c_int {
+    let mode = match mode {
+        ZK_TIME_CALLER => TimeMode::Caller,
+        ZK_TIME_CALLBACK => TimeMode::Callback,
+        ZK_TIME_CROSSCHECK => TimeMode::CrossCheck { max_skew },
+        _ => return -1,
+    };
+    set_time_mode(mode);
+    0
+}
+}
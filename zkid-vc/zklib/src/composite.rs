@@ -0,0 +1,859 @@
+// ============================================================================
+// Composite Credentials (claim groups signed by different issuers)
+// ============================================================================
+//
+// A composite credential aggregates claims that different issuers assert
+// about one holder, e.g. an employee profile where HR signs role and
+// department and Security signs clearance. Each issuer signs only its own
+// claim group; groups are added and signed independently, in any order, and
+// adding a group never invalidates the signatures already on the others.
+//
+// Binary encoding (hex-encoded at the C API), integers little-endian,
+// variable-length fields prefixed with their u32 length:
+//
+//   "ZKCC" | version u8 | holder_id | issue_date i64 | expiry_date i64
+//   | metadata_count u32 | (key, value)*
+//   | group_count u32 | (issuer | issuer_pubkey[32] | claim_count u32
+//   | (key, value)* | signature)*
+//
+// The holder binding and shared metadata (holder_id, dates, metadata) are
+// hashed once, and each group as
+//
+//   group_hash = SHA-256("zkid:claim-group" | shared hash | index u32
+//                        | issuer | issuer_pubkey | claim_count u32
+//                        | (key, value)*)
+//
+// with length-prefixed fields, so a group cannot be moved to another
+// credential, position or issuer key. The group's issuer signs its anchor,
+// shaped as a credential anchor (validity.rs):
+//
+//   anchor = MiMC(group_hash, issue_date, expiry_date, 0, blinding,
+//                 claims_root)
+//
+// group_hash mapped to a field element as a VC hash is, blinding the
+// group's `zkid:anchor-blinding` claim, which signing sets afresh, and
+// claims_root the root of the group's claims tree (disclosure.rs), 0 past
+// MAX_COMMITTED_CLAIMS claims. The signed message is
+//
+//   "zkid:claim-group" | "zkid.group.anchor.v1" | anchor
+//
+// (domain.rs), which no credential anchor signature can be taken for. An
+// unsigned group has an empty signature. Version 1 credentials, whose
+// groups signed the bare group hash, no longer decode.
+//
+// A single claim is disclosed with the group-claim circuit. It opens the
+// group anchor as the VC circuit opens a credential's, checks that the
+// credential is active at the time the proof carries, and opens the
+// claim's leaf under the group's claims root along a private path; the root
+// stays private. The proof carries the VC proof header with the group
+// anchor and its signature, and the verifier checks the signature under the
+// issuer key it verifies for, so a claim of group A verifies only against
+// issuer A's key, and a claim its issuer never signed does not verify,
+// whatever a modified prover skipped.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, claim_key, claim_value.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::Zero;
+use ark_groth16::PreparedVerifyingKey;
+use ark_serialize::CanonicalSerialize;
+use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH};
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::credential::{claimed_blinding, parse_verifying_key, read_claims};
+use crate::disclosure::ClaimsTree;
+use crate::domain::GROUP_ANCHOR_TAG;
+use crate::usage::KeyUsage;
+use crate::ffi::{read_bytes, write_cstr, MAX_FIELD_LEN};
+use crate::validity::{vc_hash_field, Opening, Window};
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, dates, hex_to_bytes};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::disclosure::{claim_key_field, claim_value_field},
+    crate::proof,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::stamp_blinding,
+    crate::disclosure::{enforce_membership, ClaimPath},
+    crate::error::{
+        ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_KEY_MISMATCH, ZK_ERR_NOT_INITIALIZED, ZK_ERR_VC_EXPIRED,
+    },
+    crate::sequence,
+    crate::stats,
+    crate::{clock, enforce_active, entropy, prove_with_key, seeded_rng, Anchored, AnchorWitness, SECRET_KEY_LENGTH},
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    ed25519_dalek::{SigningKey, SIGNATURE_LENGTH},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission,
+    crate::audit,
+    crate::did,
+    crate::ffi::read_slice,
+    crate::sizes::GROUP_CLAIM_PUBLIC_INPUTS,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const COMPOSITE_MAGIC: &[u8; 4] = b"ZKCC";
+const COMPOSITE_VERSION: u8 = 2;
+const GROUP_DOMAIN: &[u8] = KeyUsage::ClaimGroup.context();
+
+/// Most claim groups a composite credential may hold
+pub const MAX_CLAIM_GROUPS: usize = 16;
+
+/// Circuit id of the group-claim disclosure circuit
+pub const GROUP_CLAIM_CIRCUIT: &str = "zkid-vc/group-claim/v2";
+
+/// Claims asserted and signed by one issuer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimGroup {
+    pub issuer: String,
+    pub issuer_pubkey: [u8; PUBLIC_KEY_LENGTH],
+    pub claims: Vec<(String, String)>,
+    /// Ed25519 signature over the group hash, empty until signed
+    pub signature: Vec<u8>,
+}
+
+/// A credential whose claims are split into independently signed groups
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompositeCredential {
+    pub holder_id: String,
+    pub issue_date: i64,
+    pub expiry_date: i64,
+    /// Shared metadata every group signs
+    pub metadata: Vec<(String, String)>,
+    pub groups: Vec<ClaimGroup>,
+}
+
+fn put_pairs(out: &mut Vec<u8>, pairs: &[(String, String)]) {
+    out.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+    for (key, value) in pairs {
+        put_bytes(out, key.as_bytes());
+        put_bytes(out, value.as_bytes());
+    }
+}
+
+fn read_pairs(r: &mut Reader) -> Option<Vec<(String, String)>> {
+    let count = r.u32()? as usize;
+    let mut pairs = Vec::new();
+    for _ in 0..count {
+        pairs.push((r.string()?, r.string()?));
+    }
+    Some(pairs)
+}
+
+/// What the issuer of a group signs for the group's anchor
+pub fn group_anchor_message(anchor: &Fr) -> Vec<u8> {
+    let mut message = GROUP_ANCHOR_TAG.to_vec();
+    // A field element always fits its compressed length
+    let _ = anchor.serialize_compressed(&mut message);
+    KeyUsage::ClaimGroup.signed_message(&message)
+}
+
+/// Whether `signature` is `issuer_pubkey`'s (32 bytes) over a group anchor
+pub fn group_anchor_signed(issuer_pubkey: &[u8], anchor: &Fr, signature: &[u8]) -> bool {
+    let (key, signature) = match (
+        issuer_pubkey.try_into().ok().and_then(|bytes| VerifyingKey::from_bytes(bytes).ok()),
+        Signature::from_slice(signature),
+    ) {
+        (Some(key), Ok(signature)) => (key, signature),
+        _ => return false,
+    };
+    key.verify_strict(&group_anchor_message(anchor), &signature).is_ok()
+}
+
+impl CompositeCredential {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(COMPOSITE_MAGIC);
+        out.push(COMPOSITE_VERSION);
+        put_bytes(&mut out, self.holder_id.as_bytes());
+        out.extend_from_slice(&self.issue_date.to_le_bytes());
+        out.extend_from_slice(&self.expiry_date.to_le_bytes());
+        put_pairs(&mut out, &self.metadata);
+        out.extend_from_slice(&(self.groups.len() as u32).to_le_bytes());
+        for group in &self.groups {
+            put_bytes(&mut out, group.issuer.as_bytes());
+            out.extend_from_slice(&group.issuer_pubkey);
+            put_pairs(&mut out, &group.claims);
+            put_bytes(&mut out, &group.signature);
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(COMPOSITE_MAGIC.len())? != COMPOSITE_MAGIC || r.u8()? != COMPOSITE_VERSION {
+            return None;
+        }
+
+        let holder_id = r.string()?;
+        let issue_date = r.u64()? as i64;
+        let expiry_date = r.u64()? as i64;
+        let metadata = read_pairs(&mut r)?;
+
+        let group_count = r.u32()? as usize;
+        if group_count > MAX_CLAIM_GROUPS {
+            return None;
+        }
+        let mut groups = Vec::new();
+        for _ in 0..group_count {
+            groups.push(ClaimGroup {
+                issuer: r.string()?,
+                issuer_pubkey: r.take(PUBLIC_KEY_LENGTH)?.try_into().ok()?,
+                claims: read_pairs(&mut r)?,
+                signature: r.bytes()?.to_vec(),
+            });
+        }
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            holder_id,
+            issue_date,
+            expiry_date,
+            metadata,
+            groups,
+        })
+    }
+
+    /// Hash of the holder binding and shared metadata
+    fn shared_hash(&self) -> [u8; 32] {
+        let mut data = Vec::new();
+        put_bytes(&mut data, self.holder_id.as_bytes());
+        data.extend_from_slice(&self.issue_date.to_le_bytes());
+        data.extend_from_slice(&self.expiry_date.to_le_bytes());
+        put_pairs(&mut data, &self.metadata);
+        Sha256::digest(&data).into()
+    }
+
+    /// Message the issuer of group `index` signs; None if there is no such group
+    pub fn group_hash(&self, index: usize) -> Option<[u8; 32]> {
+        let group = self.groups.get(index)?;
+        let mut data = GROUP_DOMAIN.to_vec();
+        data.extend_from_slice(&self.shared_hash());
+        data.extend_from_slice(&(index as u32).to_le_bytes());
+        put_bytes(&mut data, group.issuer.as_bytes());
+        data.extend_from_slice(&group.issuer_pubkey);
+        put_pairs(&mut data, &group.claims);
+        Some(Sha256::digest(&data).into())
+    }
+
+    /// Opening of group `index`'s anchor (module comment); None if there is
+    /// no such group or its blinding claim is repeated or malformed
+    pub fn group_opening(&self, index: usize) -> Option<Opening> {
+        let group = self.groups.get(index)?;
+        Some(Opening {
+            vc_hash: vc_hash_field(&self.group_hash(index)?)?,
+            window: Window { issue_date: self.issue_date, expiry_date: self.expiry_date },
+            holder_key: Fr::zero(),
+            blinding: claimed_blinding(&group.claims)?,
+            claims_root: ClaimsTree::new(&group.claims).map_or(Fr::zero(), |tree| tree.root()),
+        })
+    }
+
+    /// Whether group `index` is signed by `issuer`, the key it names
+    pub fn verify_group(&self, index: usize, issuer: &VerifyingKey) -> bool {
+        match (self.groups.get(index), self.group_opening(index)) {
+            (Some(group), Some(opening)) => {
+                group.issuer_pubkey == issuer.to_bytes()
+                    && group_anchor_signed(issuer.as_bytes(), &opening.anchor(), &group.signature)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether there is at least one group, each signed by the matching key
+    /// of `issuers` (in group order), and the dates cover `now`
+    pub fn verify(&self, issuers: &[VerifyingKey], now: u64) -> bool {
+        !self.groups.is_empty()
+            && issuers.len() == self.groups.len()
+            && (0..self.groups.len()).all(|i| self.verify_group(i, &issuers[i]))
+            && dates::valid_range(self.issue_date, self.expiry_date)
+            && dates::is_active(now, self.issue_date, self.expiry_date)
+    }
+
+    /// Sign group `index`'s anchor under a fresh blinding, numbering the
+    /// group when issuer sequencing is on (sequence.rs); the key must be
+    /// the one the group names
+    #[cfg(feature = "prover")]
+    pub fn sign_group(&mut self, index: usize, issuer: &SigningKey) -> Result<(), c_int> {
+        let group = self.groups.get_mut(index).ok_or(-1)?;
+        if group.issuer_pubkey != issuer.verifying_key().to_bytes() {
+            return Err(ZK_ERR_KEY_MISMATCH);
+        }
+        if sequence::sequencing_enabled() {
+            sequence::stamp_claims(&mut group.claims, &group.issuer_pubkey).map_err(|e| e.code())?;
+        }
+        stamp_blinding(&mut group.claims)?;
+        let anchor = self.group_opening(index).ok_or(-1)?.anchor();
+        let signature = stats::sign_issuance(issuer, self.holder_id.as_bytes(), &group_anchor_message(&anchor));
+        self.groups[index].signature = signature.to_bytes().to_vec();
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ZK Circuit: Claim Attributed to a Group Issuer
+// ============================================================================
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+struct GroupClaimCircuit {
+    // 组锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+
+    // 公开输入（锚点之后）
+    claim_key: Option<Fr>,
+    claim_value: Option<Fr>,
+}
+
+#[cfg(feature = "prover")]
+impl GroupClaimCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            path: ClaimPath::blank(),
+            claim_key: None,
+            claim_value: None,
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for GroupClaimCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+
+        let input = |value: Option<Fr>| cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing));
+        let claim_key_var = input(self.claim_key)?;
+        let claim_value_var = input(self.claim_value)?;
+
+        // The disclosed claim is a leaf under the group's claims root
+        enforce_membership(
+            &cs,
+            [(lc!() + claim_key_var, self.claim_key), (lc!() + claim_value_var, self.claim_value)],
+            &self.path,
+            opened.claims_root.0,
+        )
+    }
+}
+
+/// Keys of the group-claim circuit; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct GroupClaimKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static GROUP_CLAIM_KEYS: Mutex<GroupClaimKeys> = Mutex::new(GroupClaimKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the group-claim circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(3u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(GroupClaimCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match GROUP_CLAIM_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the group-claim verifying key is present
+pub(crate) fn has_keys() -> bool {
+    GROUP_CLAIM_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the group-claim circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = GROUP_CLAIM_KEYS.lock() {
+        *keys = GroupClaimKeys::default();
+    }
+}
+
+fn parse_composite(blob: *const c_char) -> Option<CompositeCredential> {
+    let text = unsafe { std::ffi::CStr::from_ptr(blob) }.to_str().ok()?;
+    CompositeCredential::from_bytes(&hex_to_bytes(text).ok()?)
+}
+
+fn write_composite(vc: &CompositeCredential, out: *mut c_char, out_size: usize) -> c_int {
+    match write_cstr(out, out_size, &bytes_to_hex(&vc.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+
+#[cfg(feature = "prover")]
+fn parse_signing_key(private_key: *const c_char) -> Option<SigningKey> {
+    let text = unsafe { std::ffi::CStr::from_ptr(private_key) }.to_str().ok()?;
+    let bytes: [u8; SECRET_KEY_LENGTH] = hex_to_bytes(text).ok()?.as_slice().try_into().ok()?;
+    Some(SigningKey::from_bytes(&bytes))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Encode a composite credential without claim groups as a hex blob
+///
+/// `metadata_*` is the shared metadata every group signs (may be empty).
+/// Add groups with ZK_AddClaimGroup. Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_EncodeCompositeVC(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    metadata_keys: *const *const c_char,
+    metadata_values: *const *const c_char,
+    metadata_count: usize,
+    composite_out: *mut c_char,
+    composite_out_size: usize,
+) -> c_int {
+    if holder_id.is_null() || !dates::valid_range(issue_date, expiry_date) {
+        return -1;
+    }
+
+    let holder_id = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => match std::str::from_utf8(bytes) {
+            Ok(holder_id) => holder_id.to_string(),
+            Err(_) => return -1,
+        },
+        Err(e) => return e.code(),
+    };
+    let metadata = match read_claims(metadata_keys, metadata_values, metadata_count) {
+        Some(metadata) => metadata,
+        None => return -1,
+    };
+
+    let vc = CompositeCredential {
+        holder_id,
+        issue_date,
+        expiry_date,
+        metadata,
+        groups: Vec::new(),
+    };
+    write_composite(&vc, composite_out, composite_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Append an unsigned claim group asserted by `issuer_pubkey`
+///
+/// The group's index is its position (the first group is 0); existing
+/// group signatures stay valid. Sign it with ZK_SignClaimGroup. Returns 0
+/// on success, -1 on failure (including more than MAX_CLAIM_GROUPS groups).
+#[no_mangle]
+pub extern "C" fn ZK_AddClaimGroup(
+    composite_blob: *const c_char,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issuer_pubkey: *const c_char,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    composite_out: *mut c_char,
+    composite_out_size: usize,
+) -> c_int {
+    if composite_blob.is_null() || issuer.is_null() || issuer_pubkey.is_null() {
+        return -1;
+    }
+
+    let (mut vc, issuer_key) = match (parse_composite(composite_blob), parse_verifying_key(issuer_pubkey)) {
+        (Some(vc), Some(key)) if vc.groups.len() < MAX_CLAIM_GROUPS => (vc, key),
+        _ => return -1,
+    };
+    let issuer = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => match std::str::from_utf8(bytes) {
+            Ok(issuer) => issuer.to_string(),
+            Err(_) => return -1,
+        },
+        Err(e) => return e.code(),
+    };
+    let claims = match read_claims(claim_keys, claim_values, claim_count) {
+        Some(claims) if !claims.is_empty() => claims,
+        _ => return -1,
+    };
+
+    vc.groups.push(ClaimGroup {
+        issuer,
+        issuer_pubkey: issuer_key.to_bytes(),
+        claims,
+        signature: Vec::new(),
+    });
+    write_composite(&vc, composite_out, composite_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Sign claim group `group_index` with the private key of its issuer
+///
+/// With issuer sequencing enabled the group's `zkid:sequence` claim is set
+/// to the next number of its key. Returns 0 on success, ZK_ERR_KEY_MISMATCH
+/// if the key is not the one the group names, ZK_ERR_STORAGE if the
+/// sequence cannot be persisted, -1 on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignClaimGroup(
+    composite_blob: *const c_char,
+    group_index: usize,
+    private_key: *const c_char,
+    composite_out: *mut c_char,
+    composite_out_size: usize,
+) -> c_int {
+    if composite_blob.is_null() || private_key.is_null() {
+        return -1;
+    }
+
+    let (mut vc, key) = match (parse_composite(composite_blob), parse_signing_key(private_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+    if let Err(code) = vc.sign_group(group_index, &key) {
+        return code;
+    }
+    write_composite(&vc, composite_out, composite_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify every claim group of a composite credential
+///
+/// `issuer_pubkeys` holds one hex key per group, in group order; each group
+/// must name that key and carry its valid signature, and the credential
+/// must be active at `current_time`. Returns 1 if valid, 0 if invalid
+/// (including a key count that differs from the group count).
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyCompositeVC(
+    composite_blob: *const c_char,
+    issuer_pubkeys: *const *const c_char,
+    issuer_count: usize,
+    current_time: u64,
+) -> c_int {
+    let started = Instant::now();
+    let result = verify_composite(composite_blob, issuer_pubkeys, issuer_count, current_time);
+
+    audit::record(started, audit::Verification {
+        check: "composite-signature",
+        code: result,
+        circuit: None,
+        vk: None,
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_composite(
+    composite_blob: *const c_char,
+    issuer_pubkeys: *const *const c_char,
+    issuer_count: usize,
+    current_time: u64,
+) -> c_int {
+    if composite_blob.is_null() {
+        return 0;
+    }
+    let vc = match parse_composite(composite_blob) {
+        Some(vc) => vc,
+        None => return 0,
+    };
+    let issuers = match read_slice(issuer_pubkeys, issuer_count, MAX_CLAIM_GROUPS) {
+        Ok(keys) => keys
+            .iter()
+            .map(|&key| if key.is_null() { None } else { parse_verifying_key(key) })
+            .collect::<Option<Vec<_>>>(),
+        Err(_) => None,
+    };
+    match issuers {
+        Some(issuers) => vc.verify(&issuers, current_time) as c_int,
+        None => 0,
+    }
+}
+
+crate::unwind::entry_point! {
+/// Prove that claim `claim_key` of group `group_index` is asserted by
+/// `issuer_pubkey`, without revealing the rest of the credential
+///
+/// Checks natively that the group names and is signed by the key, holds the
+/// claim and that the credential is active at `current_time` (0 reads the
+/// trusted clock). The claim value is disclosed alongside the proof and
+/// checked with ZK_VerifyGroupClaimProof. Returns 0 on success,
+/// ZK_ERR_VC_EXPIRED for an inactive credential, ZK_ERR_INPUT_TOO_LARGE
+/// for a group of more than MAX_COMMITTED_CLAIMS claims,
+/// ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED, or -1 on other failures.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateGroupClaimProof(
+    composite_blob: *const c_char,
+    group_index: usize,
+    claim_key: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if composite_blob.is_null() || claim_key.is_null() || issuer_pubkey.is_null() || proof_out.is_null() {
+        return -1;
+    }
+
+    let (vc, issuer_key) = match (parse_composite(composite_blob), parse_verifying_key(issuer_pubkey)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+    let claim_key = match unsafe { std::ffi::CStr::from_ptr(claim_key) }.to_str() {
+        Ok(key) => key,
+        Err(_) => return -1,
+    };
+    let checked = crate::skips_prover_checks() || vc.verify_group(group_index, &issuer_key);
+    let (group, opening) = match (vc.groups.get(group_index), vc.group_opening(group_index)) {
+        (Some(group), Some(opening)) if checked => (group, opening),
+        _ => return -1,
+    };
+    let (index, claim_value) = match group.claims.iter().enumerate().find(|(_, (k, _))| k == claim_key) {
+        Some((index, (_, value))) => (index, value),
+        None => return -1,
+    };
+    let signature: [u8; SIGNATURE_LENGTH] = match group.signature.as_slice().try_into() {
+        Ok(signature) => signature,
+        Err(_) => return -1,
+    };
+
+    let current_time = match clock::now(current_time) {
+        Ok(t) => t,
+        Err(code) => return code,
+    };
+    if !dates::valid_range(vc.issue_date, vc.expiry_date) {
+        return -1;
+    }
+    if !crate::skips_prover_checks() && !dates::is_active(current_time, vc.issue_date, vc.expiry_date) {
+        return ZK_ERR_VC_EXPIRED;
+    }
+    let tree = match ClaimsTree::new(&group.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), current_time, Fr::from(nonce))
+        .and_then(|(anchor, header)| {
+            let circuit = GroupClaimCircuit {
+                anchor,
+                path: ClaimPath::of(&tree, index)?,
+                claim_key: Some(claim_key_field(claim_key)),
+                claim_value: Some(claim_value_field(claim_value)),
+            };
+            Some((circuit, header))
+        });
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match GROUP_CLAIM_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify that a disclosed claim is asserted by `issuer_pubkey`
+///
+/// Returns 1 if the proof attributes `claim_key` = `claim_value` to the
+/// issuer for `nonce`, at the time the proof carries, 0 otherwise (also for
+/// a claim of another issuer's group), ZK_ERR_DISABLED for a legacy-format proof in a strict build,
+/// ZK_ERR_UNSUPPORTED_VERSION for a format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyGroupClaimProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = GROUP_CLAIM_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let result = match pvk {
+        Some(pvk) if !proof_hex.is_null() && !issuer_pubkey.is_null() && !claim_key.is_null() && !claim_value.is_null() => {
+            verify_group_claim_proof(pvk, proof_hex, issuer_pubkey, claim_key, claim_value, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "group-claim-proof",
+        code: result,
+        circuit: Some(GROUP_CLAIM_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_group_claim_proof(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    nonce: u64,
+) -> c_int {
+    let text = |ptr: *const c_char| admission::text_arg(ptr, MAX_FIELD_LEN);
+    let issuer_bytes = match text(issuer_pubkey).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return e.code_or(0),
+        None => return 0,
+    };
+    let (key, value) = match (
+        text(claim_key),
+        text(claim_value),
+    ) {
+        (Some(key), Some(value)) => (key, value),
+        _ => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The anchor must be a group anchor the issuer signed; then the VC
+    // inputs at the time the proof carries, key and value (circuit order)
+    if !group_anchor_signed(&issuer_bytes, &header.anchor, &header.signature) {
+        return 0;
+    }
+    let public_inputs = match proof::field_for(format, &issuer_bytes) {
+        Some(issuer_hash) => [
+            issuer_hash,
+            Fr::from(nonce),
+            Fr::from(header.current_time),
+            header.anchor,
+            claim_key_field(key),
+            claim_value_field(value),
+        ],
+        None => return 0,
+    };
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+crate::unwind::entry_point! {
+/// Export the group-claim verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportGroupClaimVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match GROUP_CLAIM_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportGroupClaimVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportGroupClaimVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == GROUP_CLAIM_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match GROUP_CLAIM_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,1093 @@
+// ============================================================================
+// Conformance Vectors
+// ============================================================================
+//
+// Language-agnostic test vectors for independent verifier implementations.
+// `examples/gen_vectors.rs` writes them to conformance/vectors.json; the
+// same file is replayed against this library by ZK_RunConformance.
+//
+// File layout (vectors_version 1):
+//
+//   { "vectors_version": 1, "generator": "zklib-vc <version>",
+//     "verifying_key": hex (compressed arkworks VerifyingKey),
+//     "vectors": [ { "id", "kind", "input": {...}, "expected": {...} } ] }
+//
+// Kinds:
+//   vc_message_hash      credential fields/claims, "claims_root_signed"
+//                        (version 6 hash, see disclosure.rs),
+//                        "length_prefixed" (hash format 2, blob version 7;
+//                        format 1 if absent), "domain_separated" (hash
+//                        format 3, blob version 8, see domain.rs) -> "hash"
+//                        (the "-collision" pairs hash alike in format 1 only)
+//   field_mapping        "format", "data" -> "field" (32-byte LE element;
+//                        the "-collision" pair maps to one format-1 element
+//                        and two distinct elements in formats 2 and 3)
+//   vc_signature         "vc_blob", "issuer_pubkey" -> "valid" (blobs from
+//                        version 5 on are signed under the credential usage
+//                        context, see usage.rs, from version 9 over the
+//                        anchor, see validity.rs)
+//   vc_proof             "proof" (validity prefix included, see validity.rs),
+//                        "issuer_pubkey", "nonce", "current_time" (the
+//                        verifier's) -> "public_inputs" (null if the issuer
+//                        did not sign the proof's anchor), "valid"
+//   presentation_digest  "presentation" -> "digest"
+//   artifact_inspection  "artifact" -> "code", "description" (golden output
+//                        of ZK_InspectArtifact, see inspect.rs)
+//   encoding             "type", "encoding", scalars -> "bytes" (frozen
+//                        layouts of encoding.rs; points are the generator
+//                        times the given scalars, 0 for infinity)
+//   predicate_encoding   "predicate" (JSON form) -> "bytes", "hash"
+//                        (canonical binary form and predicate hash, see
+//                        predicate.rs)
+//   predicate_decoding   "bytes" -> "predicate" (canonical JSON form, null
+//                        if the bytes are not a canonical encoding)
+//   claim_value          "value" -> "field" (a claim value's field
+//                        element, numbers, negative numbers and text, see
+//                        disclosure.rs)
+//   claims_tree          "claims", "index" -> "key", "value", "siblings",
+//                        "root" (the claim's field elements and
+//                        authentication path in the claims tree)
+//   nullifier            "holder_secret", "context" -> "public_key",
+//                        "context_field", "nullifier" (the holder key and
+//                        the one-time nullifier, see holder.rs, onetime.rs)
+//   frost_signing        "coefficients" (group secret first),
+//                        "max_participants", "message", per signer its
+//                        "identifier" and nonce "randomness" -> the group
+//                        key, shares, per signer its nonces, commitments,
+//                        binding factor and signature share, and "sig"
+//                        (RFC 9591 appendix E.1, FROST(Ed25519, SHA-512);
+//                        replayed by builds with the "threshold" feature,
+//                        see threshold.rs)
+//
+// Field elements are hex of their 32-byte little-endian encoding. Vectors
+// for a proof format compiled out of the running build are skipped.
+
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_snark::SNARK;
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use std::str::FromStr;
+
+use crate::ballot::HOLDER_SECRET_LEN;
+use crate::disclosure::{claim_key_field, claim_value_field, field_from_bytes, root_from_path, ClaimsTree};
+use crate::mimc;
+use crate::encoding::{PointEncoding, ZkDeserialize, ZkSerialize};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::holder;
+use crate::inspect::inspect;
+use crate::onetime;
+use crate::predicate::Predicate;
+use crate::presentation::Presentation;
+use crate::proof::{self, PROOF_FORMATS, PROOF_FORMAT_LEGACY};
+use crate::stateless::StatusAttestation;
+use crate::validity::{self, Header, VALIDITY_PREFIX_LEN};
+use crate::{bytes_to_hex, hex_to_bytes, prove_vc_hash_with_format, vk, Anchored, VerifiableCredential, VERIFYING_KEY};
+
+pub const VECTORS_VERSION: u64 = 1;
+
+const ISSUER_SEED_LABEL: &[u8] = b"zkid conformance issuer";
+
+// Distinct inputs sharing a format-1 field element, found by a birthday
+// search over the 10^12 legacy range; format 2 keeps them apart
+const LEGACY_COLLISION: [&[u8]; 2] = [b"zkid field-map collision 170431", b"zkid field-map collision 1412006"];
+
+fn field_to_hex(field: &Fr) -> String {
+    bytes_to_hex(&field.zk_to_bytes(PointEncoding::Compressed))
+}
+
+fn sample_credential(issuer: &SigningKey) -> Option<VerifiableCredential> {
+    let mut vc = VerifiableCredential {
+        holder_id: "alice@example.com".to_string(),
+        issuer: "did:example:issuer".to_string(),
+        issue_date: 1_700_000_000,
+        expiry_date: 1_900_000_000,
+        claims: vec![
+            ("role".to_string(), "engineer".to_string()),
+            ("zkid:schema".to_string(), "employee/v1".to_string()),
+        ],
+        signature: Vec::new(),
+        co_signature: None,
+        supersedes: None,
+        validity_schedule: None,
+        legacy_signature: false,
+        claims_root_signed: true,
+        length_prefixed: true,
+        domain_separated: false,
+        anchored: true,
+        schedule_anchored: true,
+    };
+    vc.signature = issuer.sign(&vc.signed_message()?).to_bytes().to_vec();
+    Some(vc)
+}
+
+/// Credentials whose holder/issuer and claim key/value boundaries differ but
+/// whose bytes run together alike, as format 1 hashed them
+fn boundary_pair() -> [VerifiableCredential; 2] {
+    let credential = |holder_id: &str, issuer: &str, key: &str, value: &str| VerifiableCredential {
+        holder_id: holder_id.to_string(),
+        issuer: issuer.to_string(),
+        issue_date: 1_700_000_000,
+        expiry_date: 1_900_000_000,
+        claims: vec![(key.to_string(), value.to_string())],
+        signature: Vec::new(),
+        co_signature: None,
+        supersedes: None,
+        validity_schedule: None,
+        legacy_signature: false,
+        claims_root_signed: false,
+        length_prefixed: false,
+        domain_separated: false,
+        anchored: false,
+        schedule_anchored: false,
+    };
+    [credential("alice", "corp", "ab", "c"), credential("alicec", "orp", "a", "bc")]
+}
+
+/// Vector holding the inspector's description of `artifact`
+fn inspection_vector(id: &str, artifact: &[u8]) -> Value {
+    let artifact = bytes_to_hex(artifact);
+    let (description, code) = inspect(artifact.as_bytes());
+    json!({
+        "id": id,
+        "kind": "artifact_inspection",
+        "input": { "artifact": artifact },
+        "expected": { "code": code, "description": description },
+    })
+}
+
+/// Value an "encoding" vector pins
+enum Encoded {
+    Field(Fr),
+    Proof(Box<Proof<Bn254>>, PointEncoding),
+    VerifyingKey(Box<VerifyingKey<Bn254>>),
+}
+
+impl Encoded {
+    fn from_input(input: &Value) -> Result<Self, String> {
+        let g1 = |key: &str| u64_field(input, key).map(|k| (G1Affine::generator() * Fr::from(k)).into_affine());
+        let g2 = |key: &str| u64_field(input, key).map(|k| (G2Affine::generator() * Fr::from(k)).into_affine());
+        match str_field(input, "type")? {
+            "field" => Fr::from_str(str_field(input, "value")?)
+                .map(Encoded::Field)
+                .map_err(|_| "bad 'value'".to_string()),
+            "proof" => {
+                let encoding = match str_field(input, "encoding")? {
+                    "compressed" => PointEncoding::Compressed,
+                    "uncompressed" => PointEncoding::Uncompressed,
+                    _ => return Err("bad 'encoding'".to_string()),
+                };
+                Ok(Encoded::Proof(Box::new(Proof { a: g1("a")?, b: g2("b")?, c: g1("c")? }), encoding))
+            }
+            "verifying_key" => {
+                let gamma_abc_g1 = input["gamma_abc"]
+                    .as_array()
+                    .ok_or("missing 'gamma_abc'")?
+                    .iter()
+                    .map(|k| k.as_u64().map(|k| (G1Affine::generator() * Fr::from(k)).into_affine()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("bad 'gamma_abc'")?;
+                Ok(Encoded::VerifyingKey(Box::new(VerifyingKey {
+                    alpha_g1: g1("alpha")?,
+                    beta_g2: g2("beta")?,
+                    gamma_g2: g2("gamma")?,
+                    delta_g2: g2("delta")?,
+                    gamma_abc_g1,
+                })))
+            }
+            other => Err(format!("unknown type '{}'", other)),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Encoded::Field(field) => field.zk_to_bytes(PointEncoding::Compressed),
+            Encoded::Proof(proof, encoding) => proof.zk_to_bytes(*encoding),
+            Encoded::VerifyingKey(vk) => vk.zk_to_bytes(PointEncoding::Compressed),
+        }
+    }
+
+    /// Whether `bytes` decode to this value
+    fn decodes_from(&self, bytes: &[u8]) -> bool {
+        match self {
+            Encoded::Field(field) => Fr::zk_from_bytes(bytes, PointEncoding::Compressed).as_ref() == Some(field),
+            Encoded::Proof(proof, encoding) => Proof::zk_from_bytes(bytes, *encoding).as_ref() == Some(&**proof),
+            Encoded::VerifyingKey(vk) => VerifyingKey::zk_from_bytes(bytes, PointEncoding::Compressed).as_ref() == Some(&**vk),
+        }
+    }
+}
+
+/// Vector pinning the frozen encoding of the value `input` describes
+fn encoding_vector(id: &str, input: Value) -> Option<Value> {
+    let bytes = Encoded::from_input(&input).ok()?.to_bytes();
+    Some(json!({
+        "id": id,
+        "kind": "encoding",
+        "input": input,
+        "expected": { "bytes": bytes_to_hex(&bytes) },
+    }))
+}
+
+fn credential_json(vc: &VerifiableCredential) -> Value {
+    json!({
+        "holder_id": vc.holder_id,
+        "issuer": vc.issuer,
+        "issue_date": vc.issue_date,
+        "expiry_date": vc.expiry_date,
+        "claims": claims_json(&vc.claims),
+        "claims_root_signed": vc.claims_root_signed,
+        "length_prefixed": vc.length_prefixed,
+        "domain_separated": vc.domain_separated,
+    })
+}
+
+fn claims_json(claims: &[(String, String)]) -> Value {
+    claims.iter().map(|(k, v)| json!([k, v])).collect()
+}
+
+fn claims_from_json(claims: &Value) -> Result<Vec<(String, String)>, String> {
+    claims
+        .as_array()
+        .ok_or("missing 'claims'")?
+        .iter()
+        .map(|pair| match (pair[0].as_str(), pair[1].as_str()) {
+            (Some(k), Some(v)) => Ok((k.to_string(), v.to_string())),
+            _ => Err("bad claim pair".to_string()),
+        })
+        .collect()
+}
+
+/// Build the vectors document from the currently initialized keys
+pub fn generate_vectors() -> Option<Value> {
+    let pvk = VERIFYING_KEY.lock().ok()?.as_ref()?.clone();
+    let vk = pvk.vk.clone();
+    let vk_bytes = vk::encode(&vk);
+
+    let issuer = SigningKey::from_bytes(&Sha256::digest(ISSUER_SEED_LABEL).into());
+    let issuer_pubkey = issuer.verifying_key().to_bytes();
+    let vc = sample_credential(&issuer)?;
+    let message_hash = vc.message_hash();
+    let sign = |vc: &VerifiableCredential| Some(issuer.sign(&vc.signed_message()?).to_bytes().to_vec());
+
+    // Version 5: the same credential without the claims root in its hash
+    let mut uncommitted = vc.clone();
+    uncommitted.claims_root_signed = false;
+    uncommitted.anchored = false;
+    uncommitted.signature = sign(&uncommitted)?;
+
+    let mut vectors = vec![
+        json!({
+            "id": "vc-hash-001",
+            "kind": "vc_message_hash",
+            "input": credential_json(&vc),
+            "expected": { "hash": bytes_to_hex(&message_hash) },
+        }),
+        json!({
+            "id": "vc-hash-002-uncommitted-claims",
+            "kind": "vc_message_hash",
+            "input": credential_json(&uncommitted),
+            "expected": { "hash": bytes_to_hex(&uncommitted.message_hash()) },
+        }),
+    ];
+    // Version 8: the same credential behind the message tag, signed over
+    // its hash rather than its anchor
+    let mut tagged = vc.clone();
+    tagged.domain_separated = true;
+    tagged.anchored = false;
+    tagged.signature = sign(&tagged)?;
+    let boundaries = boundary_pair();
+    for (n, prefixed) in [(3, false), (5, true)] {
+        for (m, vc) in boundaries.iter().enumerate() {
+            let mut vc = vc.clone();
+            vc.length_prefixed = prefixed;
+            vectors.push(json!({
+                "id": format!("vc-hash-{:03}-collision", n + m),
+                "kind": "vc_message_hash",
+                "input": credential_json(&vc),
+                "expected": { "hash": bytes_to_hex(&vc.message_hash()) },
+            }));
+        }
+    }
+    vectors.push(json!({
+        "id": "vc-hash-007-domain-separated",
+        "kind": "vc_message_hash",
+        "input": credential_json(&tagged),
+        "expected": { "hash": bytes_to_hex(&tagged.message_hash()) },
+    }));
+
+    let blob = vc.to_bytes();
+    let mut tampered = vc.clone();
+    tampered.signature[0] ^= 0x01;
+    // Signed over the bare message hash: valid only in a pre-version-5 blob
+    let mut legacy = uncommitted.clone();
+    legacy.legacy_signature = true;
+    legacy.length_prefixed = false;
+    legacy.signature = issuer.sign(&legacy.message_hash()).to_bytes().to_vec();
+    let mut unbound = legacy.clone();
+    unbound.legacy_signature = false;
+    // A version 6 signature does not cover the version 5 hash
+    let mut root_dropped = vc.clone();
+    root_dropped.claims_root_signed = false;
+    // Version 5 and 7 signatures over one credential (no claims root, which
+    // would tell the claims apart), moved onto its boundary twin: the
+    // version 5 one still verifies
+    let [signed, moved] = boundaries;
+    let mut unprefixed = signed.clone();
+    unprefixed.signature = sign(&unprefixed)?;
+    let mut prefixed = unprefixed.clone();
+    prefixed.length_prefixed = true;
+    prefixed.signature = sign(&prefixed)?;
+    // A version 8 signature does not cover the untagged version 7 hash
+    let mut tag_dropped = tagged.clone();
+    tag_dropped.domain_separated = false;
+    // A version 9 signature covers the dates through the anchor
+    let mut expiry_extended = vc.clone();
+    expiry_extended.expiry_date += 1;
+    // ... and a version 8 one, over the same credential's hash, is no
+    // signature over its anchor
+    let mut hash_signed = tagged.clone();
+    hash_signed.anchored = true;
+    let moved_onto = |from: &VerifiableCredential| VerifiableCredential {
+        signature: from.signature.clone(),
+        claims_root_signed: from.claims_root_signed,
+        length_prefixed: from.length_prefixed,
+        domain_separated: from.domain_separated,
+        anchored: from.anchored,
+        schedule_anchored: from.schedule_anchored,
+        ..moved.clone()
+    };
+    for (id, blob, valid) in [
+        ("vc-signature-001", blob.clone(), true),
+        ("vc-signature-002-tampered", tampered.to_bytes(), false),
+        ("vc-signature-003-legacy", legacy.to_bytes(), true),
+        ("vc-signature-004-missing-usage-context", unbound.to_bytes(), false),
+        ("vc-signature-005-uncommitted-claims", uncommitted.to_bytes(), true),
+        ("vc-signature-006-claims-root-dropped", root_dropped.to_bytes(), false),
+        ("vc-signature-007-unprefixed", unprefixed.to_bytes(), true),
+        ("vc-signature-008-unprefixed-boundary-moved", moved_onto(&unprefixed).to_bytes(), true),
+        ("vc-signature-009-boundary-moved", moved_onto(&prefixed).to_bytes(), false),
+        ("vc-signature-010-domain-separated", tagged.to_bytes(), true),
+        ("vc-signature-011-tag-dropped", tag_dropped.to_bytes(), false),
+        ("vc-signature-012-expiry-extended", expiry_extended.to_bytes(), false),
+        ("vc-signature-013-hash-signed-as-anchor", hash_signed.to_bytes(), false),
+    ] {
+        vectors.push(json!({
+            "id": id,
+            "kind": "vc_signature",
+            "input": { "vc_blob": bytes_to_hex(&blob), "issuer_pubkey": bytes_to_hex(&issuer_pubkey) },
+            "expected": { "valid": valid },
+        }));
+    }
+
+    for format in PROOF_FORMATS {
+        if !proof::format_supported(format) {
+            continue;
+        }
+
+        for (n, data) in [&message_hash[..], &issuer_pubkey[..]].iter().enumerate() {
+            vectors.push(json!({
+                "id": format!("field-map-v{}-{:03}", format, n + 1),
+                "kind": "field_mapping",
+                "input": { "format": format, "data": bytes_to_hex(data) },
+                "expected": { "field": field_to_hex(&proof::field_for(format, data)?) },
+            }));
+        }
+        for (n, data) in LEGACY_COLLISION.iter().enumerate() {
+            vectors.push(json!({
+                "id": format!("field-map-v{}-{:03}-collision", format, n + 3),
+                "kind": "field_mapping",
+                "input": { "format": format, "data": bytes_to_hex(data) },
+                "expected": { "field": field_to_hex(&proof::field_for(format, data)?) },
+            }));
+        }
+
+        let nonce = 42u64;
+        let window = vc.window();
+        let signature = vc.signature.as_slice().try_into().ok()?;
+        let credential = Anchored { opening: vc.opening()?, signature };
+        let prove_at = |time: i64| {
+            prove_vc_hash_with_format(format, &credential, &issuer_pubkey, time as u64, nonce).ok()
+        };
+        let now = 1_800_000_000u64;
+        let proof_hex = prove_at(now as i64)?;
+        let proof_bytes = hex_to_bytes(&proof_hex).ok()?;
+        let (header, inner) = validity::untag(&proof_bytes)?;
+        // The same proof under the anchor of the credential with another
+        // holder, which the issuer signed too: the circuit opens only the
+        // anchor it was proven for
+        let mut edited_vc = vc.clone();
+        edited_vc.holder_id.push('x');
+        let edited_anchor = edited_vc.anchor()?;
+        let edited_signature = issuer.sign(&validity::anchor_message(&edited_anchor)).to_bytes();
+        let edited = Header::new(edited_anchor, edited_signature, now);
+        let edited_hex = bytes_to_hex(&edited.tag(inner));
+        // ... and under its own anchor with a signature the issuer did not
+        // make
+        let mut unsigned = header;
+        unsigned.signature[0] ^= 0x01;
+        let unsigned_hex = bytes_to_hex(&unsigned.tag(inner));
+        // Proofs at both ends of the validity window, checked there and one
+        // second outside
+        let (first, last) = (window.issue_date as u64, window.expiry_date as u64);
+        let (at_issue, at_expiry) = (prove_at(window.issue_date)?, prove_at(window.expiry_date)?);
+
+        let cases = [
+            ("001", proof_hex.clone(), header, now, nonce, true),
+            ("002-wrong-nonce", proof_hex.clone(), header, now, nonce + 1, false),
+            ("003-truncated", proof_hex[..proof_hex.len() - 2].to_string(), header, now, nonce, false),
+            ("004-other-time", proof_hex.clone(), header, now + 1, nonce, false),
+            ("005-edited-credential", edited_hex, edited, now, nonce, false),
+            ("006-issue-date", at_issue.clone(), header, first, nonce, true),
+            ("007-before-issue-date", at_issue, header, first - 1, nonce, false),
+            ("008-expiry-date", at_expiry.clone(), header, last, nonce, true),
+            ("009-after-expiry-date", at_expiry, header, last + 1, nonce, false),
+            ("010-unsigned-anchor", unsigned_hex, unsigned, now, nonce, false),
+        ];
+        for (suffix, proof_hex, header, current_time, nonce, valid) in cases {
+            vectors.push(json!({
+                "id": format!("vc-proof-v{}-{}", format, suffix),
+                "kind": "vc_proof",
+                "input": {
+                    "format": format,
+                    "proof": proof_hex,
+                    "issuer_pubkey": bytes_to_hex(&issuer_pubkey),
+                    "nonce": nonce,
+                    "current_time": current_time,
+                },
+                "expected": {
+                    "public_inputs": validity::public_inputs(&issuer_pubkey, format, nonce, current_time, &header)
+                        .map(|inputs| inputs.iter().map(field_to_hex).collect::<Vec<_>>()),
+                    "valid": valid,
+                },
+            }));
+        }
+
+        let (_, _, proof) = validity::decode(&proof_bytes).ok()?;
+        let presentation = Presentation {
+            format,
+            proof,
+            header,
+            issuer_pubkey: issuer_pubkey.to_vec(),
+            nonce,
+            metadata: vec![("audience".to_string(), "gateway.example".to_string())],
+        };
+        vectors.push(json!({
+            "id": format!("presentation-digest-v{}-001", format),
+            "kind": "presentation_digest",
+            "input": { "format": format, "presentation": bytes_to_hex(&presentation.to_bytes()) },
+            "expected": { "digest": bytes_to_hex(&presentation.digest()) },
+        }));
+
+        // A first coordinate above the field modulus, flag bits clear
+        let mut non_canonical = proof_bytes.clone();
+        let body = VALIDITY_PREFIX_LEN + if format == PROOF_FORMAT_LEGACY { 0 } else { proof::ENVELOPE_HEADER_LEN };
+        non_canonical[body..body + 31].fill(0xff);
+        non_canonical[body + 31] = 0x3f;
+        vectors.push(inspection_vector(&format!("inspect-proof-v{}-001", format), &proof_bytes));
+        vectors.push(inspection_vector(&format!("inspect-proof-v{}-002-non-canonical", format), &non_canonical));
+        vectors.push(inspection_vector(
+            &format!("inspect-presentation-v{}-001", format),
+            &presentation.to_bytes(),
+        ));
+        let truncated = presentation.to_bytes();
+        vectors.push(inspection_vector(
+            &format!("inspect-presentation-v{}-002-truncated", format),
+            &truncated[..truncated.len() - 1],
+        ));
+    }
+
+    // Numbers are canonical decimal u64s or negative i64s; anything else is
+    // text
+    for (id, value) in [
+        ("claim-value-001-zero", "0"),
+        ("claim-value-002-number", "18"),
+        ("claim-value-003-u64-max", "18446744073709551615"),
+        ("claim-value-004-past-u64", "18446744073709551616"),
+        ("claim-value-005-leading-zero", "007"),
+        ("claim-value-006-signed", "+7"),
+        ("claim-value-007-empty", ""),
+        ("claim-value-008-text", "engineer"),
+        ("claim-value-009-negative", "-86400"),
+        ("claim-value-010-i64-min", "-9223372036854775808"),
+        ("claim-value-011-past-i64-min", "-9223372036854775809"),
+        ("claim-value-012-negative-zero", "-0"),
+    ] {
+        vectors.push(json!({
+            "id": id,
+            "kind": "claim_value",
+            "input": { "value": value },
+            "expected": { "field": field_to_hex(&claim_value_field(value)) },
+        }));
+    }
+
+    // One claim, two, and counts that leave part of the last pair empty
+    let claims: Vec<(String, String)> = [
+        ("role", "engineer"),
+        ("department", "platform"),
+        ("level", "3"),
+        ("site", "berlin"),
+        ("zkid:schema", "employee/v1"),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    for (id, count, index) in [
+        ("claims-tree-001-one-claim", 1, 0),
+        ("claims-tree-002-two-claims", 2, 1),
+        ("claims-tree-003-three-claims-first", 3, 0),
+        ("claims-tree-004-three-claims-last", 3, 2),
+        ("claims-tree-005-five-claims", 5, 4),
+    ] {
+        let claims = &claims[..count];
+        let tree = ClaimsTree::new(claims)?;
+        let (key, value) = &claims[index];
+        vectors.push(json!({
+            "id": id,
+            "kind": "claims_tree",
+            "input": { "claims": claims_json(claims), "index": index },
+            "expected": {
+                "key": field_to_hex(&claim_key_field(key)),
+                "value": field_to_hex(&claim_value_field(value)),
+                "siblings": tree.path(index)?.iter().map(field_to_hex).collect::<Vec<_>>(),
+                "root": field_to_hex(&tree.root()),
+            },
+        }));
+    }
+
+    // One secret in two contexts, another secret in the first, and the
+    // empty context
+    for (id, secret, context) in [
+        ("nullifier-001", [1u8; HOLDER_SECRET_LEN], &b"voucher/2026-spring"[..]),
+        ("nullifier-002-other-context", [1u8; HOLDER_SECRET_LEN], &b"poll/42"[..]),
+        ("nullifier-003-other-secret", [2u8; HOLDER_SECRET_LEN], &b"voucher/2026-spring"[..]),
+        ("nullifier-004-empty-context", [1u8; HOLDER_SECRET_LEN], &b""[..]),
+    ] {
+        let secret_field = holder::secret_field(&secret);
+        let context_field = onetime::context_field(context);
+        vectors.push(json!({
+            "id": id,
+            "kind": "nullifier",
+            "input": { "holder_secret": bytes_to_hex(&secret), "context": bytes_to_hex(context) },
+            "expected": {
+                "public_key": field_to_hex(&holder::public_key(&secret_field)),
+                "context_field": field_to_hex(&context_field),
+                "nullifier": field_to_hex(&onetime::nullifier(&secret_field, &context_field)),
+            },
+        }));
+    }
+
+    vectors.push(frost_vector());
+
+    let mut newer = blob.clone();
+    newer[4] = u8::MAX;
+    let attestation = StatusAttestation::sign(&issuer, &vc.credential_id(), 1_800_000_000);
+    for (id, artifact) in [
+        ("inspect-credential-001", blob),
+        ("inspect-credential-002-newer-version", newer),
+        ("inspect-status-attestation-001", attestation.to_bytes()),
+        ("inspect-verifying-key-001", vk_bytes.clone()),
+        ("inspect-verifying-key-002-prepared", vk::encode_prepared(&pvk)),
+        ("inspect-unknown-001", b"not an artifact".to_vec()),
+    ] {
+        vectors.push(inspection_vector(id, &artifact));
+    }
+
+    let r_minus_one = (-Fr::from(1u64)).to_string();
+    for (id, input) in [
+        ("encoding-field-001-zero", json!({ "type": "field", "value": "0" })),
+        ("encoding-field-002-one", json!({ "type": "field", "value": "1" })),
+        ("encoding-field-003-modulus-minus-one", json!({ "type": "field", "value": r_minus_one })),
+        (
+            "encoding-proof-001-compressed",
+            json!({ "type": "proof", "encoding": "compressed", "a": 2, "b": 3, "c": 5 }),
+        ),
+        (
+            "encoding-proof-002-uncompressed",
+            json!({ "type": "proof", "encoding": "uncompressed", "a": 2, "b": 3, "c": 5 }),
+        ),
+        (
+            "encoding-proof-003-compressed",
+            json!({ "type": "proof", "encoding": "compressed", "a": 7, "b": 11, "c": 13 }),
+        ),
+        (
+            "encoding-proof-004-infinity",
+            json!({ "type": "proof", "encoding": "compressed", "a": 0, "b": 0, "c": 1 }),
+        ),
+        (
+            "encoding-verifying-key-001",
+            json!({
+                "type": "verifying_key",
+                "alpha": 2, "beta": 3, "gamma": 5, "delta": 7,
+                "gamma_abc": [11, 13, 17],
+            }),
+        ),
+    ] {
+        vectors.push(encoding_vector(id, input)?);
+    }
+
+    for (id, predicate) in [
+        ("predicate-encoding-001-equal", json!({ "claim": "role", "op": "equal", "value": "admin" })),
+        ("predicate-encoding-002-not-equal", json!({ "claim": "status", "op": "not_equal", "value": "" })),
+        ("predicate-encoding-003-at-least", json!({ "claim": "age", "op": "at_least", "value": "18" })),
+        (
+            "predicate-encoding-004-at-most-non-canonical",
+            json!({ "claim": "balance", "op": "at_most", "value": "+000340282366920938463463374607431768211455" }),
+        ),
+        ("predicate-encoding-005-above-negative", json!({ "claim": "delta", "op": "above", "value": -7 })),
+        ("predicate-encoding-006-below-negative-zero", json!({ "claim": "delta", "op": "below", "value": "-0" })),
+        (
+            "predicate-encoding-007-in-unsorted",
+            json!({ "claim": "country", "op": "in", "values": ["FR", "DE", "FR"] }),
+        ),
+        ("predicate-encoding-008-absent", json!({ "claim": "sanctioned", "op": "absent" })),
+    ] {
+        let parsed = Predicate::from_json(&predicate)?;
+        vectors.push(json!({
+            "id": id,
+            "kind": "predicate_encoding",
+            "input": { "predicate": predicate },
+            "expected": { "bytes": bytes_to_hex(&parsed.to_bytes()), "hash": bytes_to_hex(&parsed.hash()) },
+        }));
+    }
+
+    let in_set = Predicate::from_json(&json!({ "claim": "country", "op": "in", "values": ["DE", "FR"] }))?.to_bytes();
+    let at_least = Predicate::from_json(&json!({ "claim": "age", "op": "at_least", "value": "18" }))?.to_bytes();
+    // Members in descending order, a negative zero, a trailing byte
+    let mut unsorted = in_set.clone();
+    let members = unsorted.len() - 14;
+    unsorted[members..].rotate_left(7);
+    let mut negative_zero = Predicate::from_json(&json!({ "claim": "age", "op": "at_least", "value": "0" }))?.to_bytes();
+    let sign = negative_zero.len() - 17;
+    negative_zero[sign] = 1;
+    let mut trailing = at_least.clone();
+    trailing.push(0);
+    for (id, bytes) in [
+        ("predicate-decoding-001", in_set),
+        ("predicate-decoding-002-unsorted-members", unsorted),
+        ("predicate-decoding-003-negative-zero", negative_zero),
+        ("predicate-decoding-004-trailing-byte", trailing),
+    ] {
+        vectors.push(json!({
+            "id": id,
+            "kind": "predicate_decoding",
+            "input": { "bytes": bytes_to_hex(&bytes) },
+            "expected": { "predicate": Predicate::from_bytes(&bytes).map(|p| p.to_json()) },
+        }));
+    }
+
+    Some(json!({
+        "vectors_version": VECTORS_VERSION,
+        "generator": format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        "verifying_key": bytes_to_hex(&vk_bytes),
+        "vectors": vectors,
+    }))
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+fn str_field<'a>(v: &'a Value, key: &str) -> Result<&'a str, String> {
+    v[key].as_str().ok_or_else(|| format!("missing string '{}'", key))
+}
+
+fn i64_field(v: &Value, key: &str) -> Result<i64, String> {
+    v[key].as_i64().ok_or_else(|| format!("missing integer '{}'", key))
+}
+
+fn u64_field(v: &Value, key: &str) -> Result<u64, String> {
+    v[key].as_u64().ok_or_else(|| format!("missing integer '{}'", key))
+}
+
+fn hex_field(v: &Value, key: &str) -> Result<Vec<u8>, String> {
+    hex_to_bytes(str_field(v, key)?).map_err(|_| format!("bad hex in '{}'", key))
+}
+
+fn compare<T: PartialEq + std::fmt::Debug>(what: &str, got: T, expected: T) -> Outcome {
+    if got == expected {
+        Outcome::Pass
+    } else {
+        Outcome::Fail(format!("{}: got {:?}, expected {:?}", what, got, expected))
+    }
+}
+
+fn format_of(input: &Value) -> Result<Option<u8>, String> {
+    let format = u64_field(input, "format")? as u8;
+    Ok(proof::format_supported(format).then_some(format))
+}
+
+fn run_vector(vector: &Value, pvk: &PreparedVerifyingKey<Bn254>) -> Result<Outcome, String> {
+    let input = &vector["input"];
+    let expected = &vector["expected"];
+
+    match str_field(vector, "kind")? {
+        "vc_message_hash" => {
+            let claims = claims_from_json(&input["claims"])?;
+            let vc = VerifiableCredential {
+                holder_id: str_field(input, "holder_id")?.to_string(),
+                issuer: str_field(input, "issuer")?.to_string(),
+                issue_date: i64_field(input, "issue_date")?,
+                expiry_date: i64_field(input, "expiry_date")?,
+                claims,
+                signature: Vec::new(),
+                co_signature: None,
+                supersedes: None,
+                validity_schedule: None,
+                legacy_signature: false,
+                claims_root_signed: input["claims_root_signed"].as_bool().unwrap_or(false),
+                length_prefixed: input["length_prefixed"].as_bool().unwrap_or(false),
+                domain_separated: input["domain_separated"].as_bool().unwrap_or(false),
+                anchored: false,
+                schedule_anchored: false,
+            };
+            Ok(compare("hash", bytes_to_hex(&vc.message_hash()), str_field(expected, "hash")?.to_string()))
+        }
+        "field_mapping" => {
+            let format = match format_of(input)? {
+                Some(format) => format,
+                None => return Ok(Outcome::Skip("format not in this build".to_string())),
+            };
+            let field = proof::field_for(format, &hex_field(input, "data")?).ok_or("unknown format")?;
+            Ok(compare("field", field_to_hex(&field), str_field(expected, "field")?.to_string()))
+        }
+        "vc_signature" => {
+            let pubkey: [u8; 32] = hex_field(input, "issuer_pubkey")?
+                .try_into()
+                .map_err(|_| "issuer_pubkey must be 32 bytes")?;
+            let valid = match (
+                VerifiableCredential::from_bytes(&hex_field(input, "vc_blob")?),
+                ed25519_dalek::VerifyingKey::from_bytes(&pubkey),
+            ) {
+                (Some(vc), Ok(key)) => vc.verify_signature(&key),
+                _ => false,
+            };
+            Ok(compare("valid", valid, expected["valid"].as_bool().ok_or("missing 'valid'")?))
+        }
+        "vc_proof" => {
+            let format = match format_of(input)? {
+                Some(format) => format,
+                None => return Ok(Outcome::Skip("format not in this build".to_string())),
+            };
+            let issuer_pubkey = hex_field(input, "issuer_pubkey")?;
+            let nonce = u64_field(input, "nonce")?;
+            let current_time = u64_field(input, "current_time")?;
+            let proof_bytes = hex_field(input, "proof")?;
+            let (header, _) = validity::untag(&proof_bytes).ok_or("proof has no validity prefix")?;
+            let public_inputs = validity::public_inputs(&issuer_pubkey, format, nonce, current_time, &header);
+
+            if let Some(expected_inputs) = expected.get("public_inputs") {
+                let got = public_inputs.map(|inputs| inputs.iter().map(field_to_hex).collect::<Vec<_>>());
+                let want = match expected_inputs {
+                    Value::Null => None,
+                    inputs => Some(
+                        inputs
+                            .as_array()
+                            .ok_or("bad 'public_inputs'")?
+                            .iter()
+                            .filter_map(|v| v.as_str().map(str::to_string))
+                            .collect(),
+                    ),
+                };
+                if let Outcome::Fail(detail) = compare("public_inputs", got, want) {
+                    return Ok(Outcome::Fail(detail));
+                }
+            }
+
+            let valid = match (validity::decode(&proof_bytes), public_inputs) {
+                (Ok((_, decoded_format, proof)), Some(public_inputs)) if decoded_format == format => matches!(
+                    Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+                    Ok(true)
+                ),
+                _ => false,
+            };
+            Ok(compare("valid", valid, expected["valid"].as_bool().ok_or("missing 'valid'")?))
+        }
+        "presentation_digest" => {
+            if format_of(input)?.is_none() {
+                return Ok(Outcome::Skip("format not in this build".to_string()));
+            }
+            let digest = Presentation::from_bytes(&hex_field(input, "presentation")?)
+                .map(|p| bytes_to_hex(&p.digest()))
+                .ok_or("undecodable presentation")?;
+            Ok(compare("digest", digest, str_field(expected, "digest")?.to_string()))
+        }
+        "artifact_inspection" => {
+            let (description, code) = inspect(str_field(input, "artifact")?.as_bytes());
+            if let Outcome::Fail(detail) = compare("code", i64::from(code), i64_field(expected, "code")?) {
+                return Ok(Outcome::Fail(detail));
+            }
+            Ok(compare("description", &description, &expected["description"]))
+        }
+        "encoding" => {
+            let value = Encoded::from_input(input)?;
+            let bytes = hex_field(expected, "bytes")?;
+            if !value.decodes_from(&bytes) {
+                return Ok(Outcome::Fail("expected bytes do not decode to the input".to_string()));
+            }
+            Ok(compare("bytes", bytes_to_hex(&value.to_bytes()), bytes_to_hex(&bytes)))
+        }
+        "predicate_encoding" => {
+            let predicate = Predicate::from_json(&input["predicate"]).ok_or("invalid 'predicate'")?;
+            let bytes = hex_field(expected, "bytes")?;
+            if Predicate::from_bytes(&bytes).as_ref() != Some(&predicate) {
+                return Ok(Outcome::Fail("expected bytes do not decode to the input".to_string()));
+            }
+            if let Outcome::Fail(detail) = compare("bytes", bytes_to_hex(&predicate.to_bytes()), bytes_to_hex(&bytes)) {
+                return Ok(Outcome::Fail(detail));
+            }
+            Ok(compare("hash", bytes_to_hex(&predicate.hash()), str_field(expected, "hash")?.to_string()))
+        }
+        "predicate_decoding" => {
+            let decoded = Predicate::from_bytes(&hex_field(input, "bytes")?).map(|p| p.to_json());
+            Ok(compare("predicate", &decoded.unwrap_or(Value::Null), &expected["predicate"]))
+        }
+        "claim_value" => {
+            let value = input["value"].as_str().ok_or("missing 'value'")?;
+            Ok(compare("field", field_to_hex(&claim_value_field(value)), str_field(expected, "field")?.to_string()))
+        }
+        "claims_tree" => {
+            let claims = claims_from_json(&input["claims"])?;
+            let index = u64_field(input, "index")? as usize;
+            let tree = ClaimsTree::new(&claims).ok_or("too many claims")?;
+            let (key, value) = claims.get(index).ok_or("'index' past the last claim")?;
+            let fields = [claim_key_field(key), claim_value_field(value)];
+            let siblings = tree.path(index).ok_or("'index' past the last claim")?;
+            // The expected path must lead to the expected root on its own
+            let expected_siblings = expected["siblings"]
+                .as_array()
+                .ok_or("missing 'siblings'")?
+                .iter()
+                .map(|sibling| sibling.as_str().and_then(|s| hex_to_bytes(s).ok()).and_then(|b| field_from_bytes(&b)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("bad 'siblings'")?;
+            let expected_fields = [str_field(expected, "key")?, str_field(expected, "value")?]
+                .iter()
+                .map(|field| hex_to_bytes(field).ok().and_then(|b| field_from_bytes(&b)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("bad 'key' or 'value'")?;
+            let expected_leaf = mimc::hash(&expected_fields);
+            if field_to_hex(&root_from_path(expected_leaf, index, &expected_siblings)) != str_field(expected, "root")? {
+                return Ok(Outcome::Fail("expected siblings do not lead to the expected root".to_string()));
+            }
+            if let Outcome::Fail(detail) = compare("fields", &fields[..], &expected_fields[..]) {
+                return Ok(Outcome::Fail(detail));
+            }
+            if let Outcome::Fail(detail) = compare("siblings", &siblings, &expected_siblings) {
+                return Ok(Outcome::Fail(detail));
+            }
+            Ok(compare("root", field_to_hex(&tree.root()), str_field(expected, "root")?.to_string()))
+        }
+        "nullifier" => {
+            let secret: [u8; HOLDER_SECRET_LEN] =
+                hex_field(input, "holder_secret")?.try_into().map_err(|_| "'holder_secret' is not 32 bytes")?;
+            let secret = holder::secret_field(&secret);
+            let context = onetime::context_field(&hex_field(input, "context")?);
+            for (what, got) in [
+                ("public_key", holder::public_key(&secret)),
+                ("context_field", context),
+                ("nullifier", onetime::nullifier(&secret, &context)),
+            ] {
+                if let Outcome::Fail(detail) = compare(what, field_to_hex(&got), str_field(expected, what)?.to_string()) {
+                    return Ok(Outcome::Fail(detail));
+                }
+            }
+            Ok(Outcome::Pass)
+        }
+        #[cfg(feature = "threshold")]
+        "frost_signing" => {
+            let coefficients = input["coefficients"]
+                .as_array()
+                .ok_or("missing array 'coefficients'")?
+                .iter()
+                .map(|coefficient| {
+                    let bytes = hex_to_bytes(coefficient.as_str().unwrap_or_default()).map_err(|_| "bad coefficient")?;
+                    bytes.try_into().map_err(|_| "a coefficient is not 32 bytes")
+                })
+                .collect::<Result<Vec<[u8; 32]>, _>>()?;
+            let max_signers = u16::try_from(u64_field(input, "max_participants")?).map_err(|_| "too many participants")?;
+            let randomness = input["signers"]
+                .as_array()
+                .ok_or("missing array 'signers'")?
+                .iter()
+                .map(|signer| {
+                    let identifier = u16::try_from(u64_field(signer, "identifier")?).map_err(|_| "bad identifier")?;
+                    let random = |key: &str| -> Result<[u8; 32], String> {
+                        hex_field(signer, key)?.try_into().map_err(|_| format!("'{}' is not 32 bytes", key))
+                    };
+                    Ok((identifier, random("hiding_nonce_randomness")?, random("binding_nonce_randomness")?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let got = crate::threshold::replay_vector(&coefficients, max_signers, &randomness, &hex_field(input, "message")?)
+                .ok_or("the vector does not sign")?;
+            Ok(compare("signing", got, expected.clone()))
+        }
+        #[cfg(not(feature = "threshold"))]
+        "frost_signing" => Ok(Outcome::Skip("threshold signing not in this build".to_string())),
+        other => Ok(Outcome::Skip(format!("unknown kind '{}'", other))),
+    }
+}
+
+/// The FROST(Ed25519, SHA-512) signing vector of RFC 9591 appendix E.1:
+/// participants 1 and 3 of a 2-of-3 group sign "test"
+fn frost_vector() -> Value {
+    json!({
+        "id": "frost-signing-001-rfc9591",
+        "kind": "frost_signing",
+        "input": {
+            "coefficients": [
+                "7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304",
+                "178199860edd8c62f5212ee91eff1295d0d670ab4ed4506866bae57e7030b204",
+            ],
+            "max_participants": 3,
+            "message": "74657374",
+            "signers": [
+                {
+                    "identifier": 1,
+                    "hiding_nonce_randomness": "0fd2e39e111cdc266f6c0f4d0fd45c947761f1f5d3cb583dfcb9bbaf8d4c9fec",
+                    "binding_nonce_randomness": "69cd85f631d5f7f2721ed5e40519b1366f340a87c2f6856363dbdcda348a7501",
+                },
+                {
+                    "identifier": 3,
+                    "hiding_nonce_randomness": "86d64a260059e495d0fb4fcc17ea3da7452391baa494d4b00321098ed2a0062f",
+                    "binding_nonce_randomness": "13e6b25afb2eba51716a9a7d44130c0dbae0004a9ef8d7b5550c8a0e07c61775",
+                },
+            ],
+        },
+        "expected": {
+            "group_public_key": "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673",
+            "participant_shares": [
+                {
+                    "identifier": 1,
+                    "participant_share": "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
+                },
+                {
+                    "identifier": 2,
+                    "participant_share": "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
+                },
+                {
+                    "identifier": 3,
+                    "participant_share": "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02",
+                },
+            ],
+            "signers": [
+                {
+                    "identifier": 1,
+                    "hiding_nonce": "812d6104142944d5a55924de6d49940956206909f2acaeedecda2b726e630407",
+                    "binding_nonce": "b1110165fc2334149750b28dd813a39244f315cff14d4e89e6142f262ed83301",
+                    "hiding_nonce_commitment": "b5aa8ab305882a6fc69cbee9327e5a45e54c08af61ae77cb8207be3d2ce13de3",
+                    "binding_nonce_commitment": "67e98ab55aa310c3120418e5050c9cf76cf387cb20ac9e4b6fdb6f82a469f932",
+                    "binding_factor": "f2cb9d7dd9beff688da6fcc83fa89046b3479417f47f55600b106760eb3b5603",
+                    "sig_share": "001719ab5a53ee1a12095cd088fd149702c0720ce5fd2f29dbecf24b7281b603",
+                },
+                {
+                    "identifier": 3,
+                    "hiding_nonce": "c256de65476204095ebdc01bd11dc10e57b36bc96284595b8215222374f99c0e",
+                    "binding_nonce": "243d71944d929063bc51205714ae3c2218bd3451d0214dfb5aeec2a90c35180d",
+                    "hiding_nonce_commitment": "cfbdb165bd8aad6eb79deb8d287bcc0ab6658ae57fdcc98ed12c0669e90aec91",
+                    "binding_nonce_commitment": "7487bc41a6e712eea2f2af24681b58b1cf1da278ea11fe4e8b78398965f13552",
+                    "binding_factor": "b087686bf35a13f3dc78e780a34b0fe8a77fef1b9938c563f5573d71d8d7890f",
+                    "sig_share": "bd86125de990acc5e1f13781d8e32c03a9bbd4c53539bbc106058bfd14326007",
+                },
+            ],
+            "sig": "36282629c383bb820a88b71cae937d41f2f2adfcc3d02e55507e2fb9e2dd3cbe\
+                    bd9d2b0844e49ae0f3fa935161e1419aab7b47d21a37ebeae1f17d4987b3160b",
+        },
+    })
+}
+
+/// Replay a vectors document; returns the report and the number of failures
+pub fn run_conformance(doc: &Value) -> Result<(Value, usize), String> {
+    if doc["vectors_version"].as_u64() != Some(VECTORS_VERSION) {
+        return Err("unsupported vectors_version".to_string());
+    }
+
+    let vk_bytes = hex_field(doc, "verifying_key")?;
+    let vk = vk::decode_raw(&vk_bytes).map_err(|_| "invalid verifying_key".to_string())?;
+    let pvk = Groth16::<Bn254>::process_vk(&vk).map_err(|_| "invalid verifying_key".to_string())?;
+
+    let vectors = doc["vectors"].as_array().ok_or("missing 'vectors'")?;
+    let (mut passed, mut failed, mut skipped) = (0usize, 0usize, 0usize);
+    let mut results = Vec::with_capacity(vectors.len());
+
+    for vector in vectors {
+        let id = vector["id"].as_str().unwrap_or("<missing id>");
+        let outcome = run_vector(vector, &pvk).unwrap_or_else(|e| Outcome::Fail(format!("malformed vector: {}", e)));
+        let (status, detail) = match outcome {
+            Outcome::Pass => {
+                passed += 1;
+                ("pass", None)
+            }
+            Outcome::Fail(detail) => {
+                failed += 1;
+                ("fail", Some(detail))
+            }
+            Outcome::Skip(detail) => {
+                skipped += 1;
+                ("skip", Some(detail))
+            }
+        };
+        results.push(match detail {
+            Some(detail) => json!({ "id": id, "status": status, "detail": detail }),
+            None => json!({ "id": id, "status": status }),
+        });
+    }
+
+    let report = json!({
+        "passed": passed,
+        "failed": failed,
+        "skipped": skipped,
+        "results": results,
+    });
+    Ok((report, failed))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Run a conformance vectors file against this library
+///
+/// Writes a JSON report with per-vector status ("pass", "fail", "skip") to
+/// `report_out`. Returns the number of failed vectors (0 = conformant), or
+/// -1 if the file is malformed, ZK_ERR_BUFFER_TOO_SMALL if the report does
+/// not fit.
+#[no_mangle]
+pub extern "C" fn ZK_RunConformance(
+    vectors_json: *const c_char,
+    vectors_json_len: usize,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    if vectors_json.is_null() {
+        return -1;
+    }
+
+    let data = match read_bytes(vectors_json, vectors_json_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+
+    let doc: Value = match serde_json::from_slice(data) {
+        Ok(doc) => doc,
+        Err(_) => return -1,
+    };
+
+    match run_conformance(&doc) {
+        Ok((report, failed)) => match write_cstr(report_out, report_out_size, &report.to_string()) {
+            Ok(_) => failed.min(c_int::MAX as usize) as c_int,
+            Err(e) => e.code(),
+        },
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,363 @@
+// ============================================================================
+// Holder Consent Receipts
+// ============================================================================
+//
+// A wallet keeps a signed record of each disclosure: which verifier, when,
+// which claims and predicates, and which presentation. Receipts name claim
+// keys only, never values, and are signed with the holder's Ed25519 key so
+// a third party (an auditor, a regulator) can check them against the
+// holder's public key without the wallet. Integers little-endian,
+// variable-length fields prefixed with their u32 length:
+//
+//   "ZKCR" | version u8 | holder_pubkey [32] | audience | timestamp u64
+//   | nonce u64 | presentation_digest [32] | claim_count u32 | claim_key*
+//   | predicate_count u32 | predicate* | signature [64]
+//
+// The signature covers "zkid:consent-receipt" followed by everything before
+// it. The timestamp is the request's current_time, the time the holder
+// proved against; the nonce ties the receipt to the request and its
+// presentation, whose transcript digest (Presentation::digest) is recorded.
+//
+// Claim keys are those the request names (its "claims" list and the schema
+// claim when it asks for a schema) or, given the credential that was
+// presented, all of its claim keys, since the whole blob is disclosed next
+// to the proof. Predicates are recorded in the text form of predicate.rs,
+// e.g. "age>=18"; a request's operand is the verifier's, not the holder's,
+// data.
+//
+// Receipts are appended to the credential store (ZK_CreateConsentReceipt)
+// and read back with ZK_ListConsentReceipts.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use crate::credential::parse_verifying_key;
+use crate::ffi::MAX_ARRAY_LEN;
+use crate::last_error;
+use crate::wire::{put_bytes, Reader};
+use crate::{hex_to_bytes, verify_ed25519};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::SCHEMA_CLAIM,
+    crate::error::ZK_ERR_CORRUPT,
+    crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN},
+    crate::inbox::PresentationRequest,
+    crate::presentation::Presentation,
+    crate::store::CredentialStore,
+    crate::{bytes_to_hex, VerifiableCredential},
+    serde_json::{json, Value},
+    ed25519_dalek::SECRET_KEY_LENGTH,
+    zeroize::Zeroizing,
+};
+
+const RECEIPT_MAGIC: &[u8; 4] = b"ZKCR";
+const RECEIPT_VERSION: u8 = 1;
+const SIGNATURE_DOMAIN: &[u8] = b"zkid:consent-receipt";
+
+/// A signed record of one disclosure
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsentReceipt {
+    pub holder_pubkey: [u8; 32],
+    pub audience: String,
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub presentation_digest: [u8; 32],
+    pub claim_keys: Vec<String>,
+    pub predicates: Vec<String>,
+    pub signature: [u8; 64],
+}
+
+impl ConsentReceipt {
+    fn body(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(RECEIPT_MAGIC);
+        out.push(RECEIPT_VERSION);
+        out.extend_from_slice(&self.holder_pubkey);
+        put_bytes(&mut out, self.audience.as_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&self.presentation_digest);
+        for list in [&self.claim_keys, &self.predicates] {
+            out.extend_from_slice(&(list.len() as u32).to_le_bytes());
+            for item in list {
+                put_bytes(&mut out, item.as_bytes());
+            }
+        }
+        out
+    }
+
+    fn signed_message(body: &[u8]) -> Vec<u8> {
+        [SIGNATURE_DOMAIN, body].concat()
+    }
+
+    /// Sign the receipt's fields with the holder key (sets holder_pubkey)
+    pub fn sign(mut self, holder: &SigningKey) -> Self {
+        self.holder_pubkey = holder.verifying_key().to_bytes();
+        self.signature = holder.sign(&Self::signed_message(&self.body())).to_bytes();
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.body();
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(RECEIPT_MAGIC.len())? != RECEIPT_MAGIC || r.u8()? != RECEIPT_VERSION {
+            return None;
+        }
+        let holder_pubkey = r.take(32)?.try_into().ok()?;
+        let audience = r.string()?;
+        let timestamp = r.u64()?;
+        let nonce = r.u64()?;
+        let presentation_digest = r.take(32)?.try_into().ok()?;
+
+        let mut lists = [Vec::new(), Vec::new()];
+        for list in &mut lists {
+            let count = r.u32()? as usize;
+            if count > MAX_ARRAY_LEN {
+                return None;
+            }
+            for _ in 0..count {
+                list.push(r.string()?);
+            }
+        }
+        let signature = r.take(64)?.try_into().ok()?;
+        if !r.is_empty() {
+            return None;
+        }
+
+        let [claim_keys, predicates] = lists;
+        Some(Self {
+            holder_pubkey,
+            audience,
+            timestamp,
+            nonce,
+            presentation_digest,
+            claim_keys,
+            predicates,
+            signature,
+        })
+    }
+
+    /// Whether the receipt is signed by `holder`
+    pub fn verify(&self, holder: &VerifyingKey) -> bool {
+        self.holder_pubkey == holder.to_bytes()
+            && verify_ed25519(
+                holder,
+                &Self::signed_message(&self.body()),
+                &Signature::from_bytes(&self.signature),
+            )
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "receipt": bytes_to_hex(&self.to_bytes()),
+            "holder_pubkey": bytes_to_hex(&self.holder_pubkey),
+            "audience": self.audience,
+            "timestamp": self.timestamp,
+            "nonce": self.nonce,
+            "presentation_digest": bytes_to_hex(&self.presentation_digest),
+            "claims": self.claim_keys,
+            "predicates": self.predicates,
+        })
+    }
+}
+
+/// Unsigned receipt for answering `request` with `presentation`
+///
+/// None if the presentation does not carry the request's nonce or the
+/// request states a malformed predicate.
+#[cfg(feature = "prover")]
+pub fn receipt_for(
+    presentation: &Presentation,
+    request: &PresentationRequest,
+    credential: Option<&VerifiableCredential>,
+) -> Option<ConsentReceipt> {
+    if presentation.nonce != request.nonce {
+        return None;
+    }
+
+    let mut claim_keys: Vec<String> = match credential {
+        Some(vc) => vc.claims.iter().map(|(key, _)| key.clone()).collect(),
+        None => {
+            let mut keys: Vec<String> = request
+                .metadata_value("claims")
+                .map(|list| list.split(',').map(str::trim).filter(|k| !k.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default();
+            if request.metadata_value("schema").is_some() {
+                keys.push(SCHEMA_CLAIM.to_string());
+            }
+            keys
+        }
+    };
+    claim_keys.sort();
+    claim_keys.dedup();
+
+    let predicates = request.predicates()?.iter().map(ToString::to_string).collect();
+
+    let audience = presentation
+        .metadata_value("audience")
+        .or_else(|| request.metadata_value("audience"))
+        .unwrap_or_default();
+
+    Some(ConsentReceipt {
+        holder_pubkey: [0; 32],
+        audience: audience.to_string(),
+        timestamp: request.current_time,
+        nonce: request.nonce,
+        presentation_digest: presentation.digest(),
+        claim_keys,
+        predicates,
+        signature: [0; 64],
+    })
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Record a signed consent receipt for answering a presentation request
+///
+/// `presentation_blob` and `request_blob` are binary or hex text; the
+/// presentation must carry the request's nonce. `credential_id` (may be
+/// NULL) names the stored credential that was presented, so the receipt
+/// lists all its claim keys; without it the keys the request names are
+/// listed. `holder_private_key` is a hex Ed25519 secret key. The receipt is
+/// appended to the store and written to `receipt_out` as hex. Returns 0 on
+/// success, ZK_ERR_CORRUPT if an input does not decode or the request
+/// states a malformed predicate, 1 if `credential_id` is not stored,
+/// ZK_ERR_BUFFER_TOO_SMALL (the receipt is still stored), or -1 on NULL, a
+/// malformed key, a nonce mismatch or a store write failure; ZK_GetLastError
+/// says which.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_CreateConsentReceipt(
+    store: *mut CredentialStore,
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    request_blob: *const c_char,
+    request_blob_len: usize,
+    credential_id: *const c_char,
+    holder_private_key: *const c_char,
+    receipt_out: *mut c_char,
+    receipt_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    let store = match unsafe { store.as_mut() } {
+        Some(store) if !holder_private_key.is_null() => store,
+        _ => return last_error::fail(-1, "store or holder_private_key is NULL"),
+    };
+    let presentation = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(p) if !p.is_empty() => p,
+        Ok(_) => return last_error::fail(-1, "presentation_blob is empty"),
+        Err(e) => return last_error::ffi("presentation_blob", e),
+    };
+    let request = match read_bytes(request_blob, request_blob_len, MAX_BLOB_LEN) {
+        Ok(r) if !r.is_empty() => r,
+        Ok(_) => return last_error::fail(-1, "request_blob is empty"),
+        Err(e) => return last_error::ffi("request_blob", e),
+    };
+    let presentation = match Presentation::from_wire(presentation) {
+        Some(p) => p,
+        None => return last_error::fail(ZK_ERR_CORRUPT, "presentation_blob does not decode"),
+    };
+    let request = match PresentationRequest::from_wire(request) {
+        Some(r) if r.predicates().is_some() => r,
+        Some(_) => return last_error::fail(ZK_ERR_CORRUPT, "request states a malformed predicate"),
+        None => return last_error::fail(ZK_ERR_CORRUPT, "request_blob does not decode"),
+    };
+
+    let credential = if credential_id.is_null() {
+        None
+    } else {
+        let id = unsafe { CStr::from_ptr(credential_id) }.to_str().unwrap_or("");
+        match store.get(id).map(VerifiableCredential::from_bytes) {
+            Some(Some(vc)) => Some(vc),
+            Some(None) => return last_error::fail(ZK_ERR_CORRUPT, "stored credential does not decode"),
+            None => return last_error::fail(1, "credential_id is not stored"),
+        }
+    };
+
+    let secret = Zeroizing::new(
+        hex_to_bytes(unsafe { CStr::from_ptr(holder_private_key) }.to_str().unwrap_or("")).unwrap_or_default(),
+    );
+    let holder = match <[u8; SECRET_KEY_LENGTH]>::try_from(secret.as_slice()) {
+        Ok(secret) => SigningKey::from_bytes(&secret),
+        Err(_) => return last_error::fail(-1, "holder_private_key is not 32-byte hex"),
+    };
+
+    let receipt = match receipt_for(&presentation, &request, credential.as_ref()) {
+        Some(receipt) => receipt.sign(&holder).to_bytes(),
+        None => return last_error::fail(-1, "presentation does not carry the request's nonce"),
+    };
+    if let Err(e) = store.add_receipt(&receipt) {
+        return last_error::fail(e.code(), "receipt could not be written to the store");
+    }
+    match write_cstr(receipt_out, receipt_out_size, &bytes_to_hex(&receipt)) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("receipt_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the store's consent receipts as a JSON array, oldest first
+///
+/// Each entry has the receipt hex and its decoded fields: holder_pubkey,
+/// audience, timestamp, nonce, presentation_digest, claims and predicates.
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ListConsentReceipts(
+    store: *mut CredentialStore,
+    receipts_json_out: *mut c_char,
+    receipts_json_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    let store = match unsafe { store.as_ref() } {
+        Some(store) => store,
+        None => return last_error::fail(-1, "store is NULL"),
+    };
+    let receipts: Vec<Value> = store
+        .receipts()
+        .filter_map(ConsentReceipt::from_bytes)
+        .map(|receipt| receipt.to_json())
+        .collect();
+    match write_cstr(receipts_json_out, receipts_json_out_size, &Value::from(receipts).to_string()) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("receipts_json_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check a hex consent receipt against the holder's hex public key
+///
+/// Needs no store or keys of the library; any party holding the receipt and
+/// the holder's public key can run it. Returns 1 if the receipt is intact
+/// and signed by that holder, 0 otherwise, -1 on NULL or a malformed key.
+/// ZK_GetLastError says why a receipt was refused.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyConsentReceipt(receipt: *const c_char, holder_public_key: *const c_char) -> c_int {
+    last_error::clear();
+    if receipt.is_null() {
+        return last_error::fail(-1, "receipt is NULL");
+    }
+    let holder = match parse_verifying_key(holder_public_key) {
+        Some(key) => key,
+        None => return last_error::fail(-1, "holder_public_key is NULL or not a hex or did:key Ed25519 key"),
+    };
+    let receipt = unsafe { CStr::from_ptr(receipt) }.to_str().unwrap_or("");
+    match hex_to_bytes(receipt.trim()).ok().and_then(|bytes| ConsentReceipt::from_bytes(&bytes)) {
+        Some(receipt) if receipt.verify(&holder) => 1,
+        Some(_) => last_error::fail(0, "receipt is not signed by that holder or was altered"),
+        None => last_error::fail(0, "receipt is not hex or does not decode"),
+    }
+}
+}
@@ -0,0 +1,785 @@
+// ============================================================================
+// Configured Contexts
+// ============================================================================
+//
+// The global entry points read process-wide settings (keys, time mode, date
+// window, ...). A context bundles a snapshot of them behind one handle so a
+// service can configure a base context once and derive per-tenant variants:
+//
+//   ZK_ContextCreate -> ZK_ContextTrustIssuer* -> ZK_ContextClone
+//                    -> ZK_ContextSetConfig (override one or two knobs)
+//
+// Keys and the trust store are shared between a context and its clones
+// through Arc; the trust store is copied on first write, so no mutation of a
+// clone is visible in its parent or vice versa. Scalar settings live in a
+// plain repr(C) ContextConfig that is copied on clone.
+//
+// A context may also carry keys of its own. ZK_ContextInit runs a fresh VC
+// setup for it and ZK_ContextImportVerifyingKey loads a verifier's key, so
+// two tenants in one process prove and verify under separate keys:
+//
+//   ZK_ContextCreate -> ZK_ContextInit -> ZK_ContextGenerateVCProof
+//                    -> ZK_ContextExportVerifyingKey -> (verifier context)
+//                       ZK_ContextImportVerifyingKey -> ZK_ContextVerifyVCProof
+//
+// The global entry points act on a default context whose keys are the
+// PROVING_KEY / VERIFYING_KEY slots. Every call clones the key's Arc and
+// works without a lock, so a proof in one context never waits on a
+// verification in another, nor on a global one.
+//
+// A context may be used from several threads at once, but must not be
+// reconfigured while another thread uses it. It holds the proving key only
+// with the "prover" feature and the verifying key only with "verifier".
+
+use ark_bn254::Bn254;
+use serde_json::json;
+use std::collections::{HashSet, VecDeque};
+use std::os::raw::{c_char, c_int};
+use std::sync::Arc;
+
+use crate::clock::{self, TimeMode, ZK_TIME_CALLBACK, ZK_TIME_CALLER, ZK_TIME_CROSSCHECK};
+use crate::credential::parse_verifying_key;
+use crate::dates::{self, DateWindow};
+use crate::error::ZK_ERR_NOT_INITIALIZED;
+use crate::ffi::write_cstr;
+use crate::limits::{self, ZK_COLLECTION_TRUST_STORE};
+use crate::proof;
+use crate::validation::Policy;
+use crate::{bytes_to_hex, vk};
+use ark_groth16::VerifyingKey;
+#[cfg(feature = "prover")]
+use {
+    crate::credential::parse_vc_blob,
+    crate::error::{ZK_ERR_BAD_HEX, ZK_ERR_BAD_SIGNATURE, ZK_ERR_VC_EXPIRED},
+    crate::ffi::{read_bytes, MAX_FIELD_LEN},
+    crate::validation::{validate, PROVER_CHECKS},
+    crate::validity::{self, Window},
+    crate::{current_proving_key, entropy, fields_hash, hex_to_bytes, prove_with_key},
+    crate::{vc_circuit, Anchored, ProofCredential, VCCircuit},
+    ark_groth16::{Groth16, ProvingKey},
+    ark_snark::SNARK,
+    std::ffi::CStr,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::admission,
+    crate::{current_verifying_key, verify_vc_proof_decoded},
+    ark_groth16::PreparedVerifyingKey,
+};
+
+/// Scalar settings of a context
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextConfig {
+    /// ZK_TIME_CALLER, ZK_TIME_CALLBACK or ZK_TIME_CROSSCHECK
+    pub time_mode: c_int,
+    /// Allowed clock skew in seconds (ZK_TIME_CROSSCHECK only)
+    pub max_skew: u64,
+    /// Inclusive window for credential dates
+    pub min_date: i64,
+    pub max_date: i64,
+    /// Largest hex VC blob accepted, in bytes
+    pub max_blob_len: usize,
+    /// Nonzero derives proving randomness from the context's seed and the
+    /// nonce (not in strict builds); from_globals sets it, with the seed of
+    /// ZK_SetDeterministicProving, while that is on
+    pub deterministic: c_int,
+    /// Proof format emitted by the prover (see proof.rs)
+    pub proof_format: u8,
+    /// ZK_ACCEPT_FORMAT_* bits of the proof formats the verifier admits
+    pub accepted_formats: u32,
+}
+
+/// Default limit on hex VC blob input
+pub const DEFAULT_MAX_BLOB_LEN: usize = 64 * 1024;
+
+impl ContextConfig {
+    /// Snapshot of the process-wide settings
+    pub fn from_globals() -> Self {
+        let (time_mode, max_skew) = match clock::time_mode() {
+            TimeMode::Caller => (ZK_TIME_CALLER, 0),
+            TimeMode::Callback => (ZK_TIME_CALLBACK, 0),
+            TimeMode::CrossCheck { max_skew } => (ZK_TIME_CROSSCHECK, max_skew),
+        };
+        let window = dates::date_window();
+
+        Self {
+            time_mode,
+            max_skew,
+            min_date: window.min,
+            max_date: window.max,
+            max_blob_len: DEFAULT_MAX_BLOB_LEN,
+            #[cfg(feature = "prover")]
+            deterministic: entropy::deterministic_proving().is_some() as c_int,
+            #[cfg(not(feature = "prover"))]
+            deterministic: 0,
+            proof_format: proof::emit_format(),
+            accepted_formats: proof::accepted_formats(),
+        }
+    }
+
+    fn time(&self) -> Option<TimeMode> {
+        match self.time_mode {
+            ZK_TIME_CALLER => Some(TimeMode::Caller),
+            ZK_TIME_CALLBACK => Some(TimeMode::Callback),
+            ZK_TIME_CROSSCHECK => Some(TimeMode::CrossCheck { max_skew: self.max_skew }),
+            _ => None,
+        }
+    }
+
+    fn date_window(&self) -> DateWindow {
+        DateWindow {
+            min: self.min_date,
+            max: self.max_date,
+        }
+    }
+
+    /// Whether every knob holds a value this build accepts
+    pub fn is_valid(&self) -> bool {
+        self.time().is_some()
+            && self.min_date <= self.max_date
+            && self.max_blob_len > 0
+            && (self.deterministic == 0 || cfg!(not(feature = "strict")))
+            && proof::format_supported(self.proof_format)
+            && proof::valid_accept_mask(self.accepted_formats)
+            && self.accepted_formats & !proof::supported_mask() == 0
+    }
+}
+
+/// Trusted issuer keys in the order they were added
+#[derive(Clone, Default)]
+struct TrustStore {
+    order: VecDeque<[u8; 32]>,
+    keys: HashSet<[u8; 32]>,
+}
+
+/// Keys, trust store and settings behind one handle
+#[derive(Clone)]
+pub struct Context {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    #[cfg(feature = "verifier")]
+    pvk: Option<Arc<PreparedVerifyingKey<Bn254>>>,
+    trusted_issuers: Arc<TrustStore>,
+    config: ContextConfig,
+    /// Seed of deterministic proving; drawn from the entropy source when a
+    /// context without one turns it on, and kept by clones
+    #[cfg(all(feature = "prover", not(feature = "strict")))]
+    proving_seed: u64,
+}
+
+impl Context {
+    /// Snapshot the global settings and the keys set by ZK_Init (or
+    /// imported), sharing them; without global keys the context has none
+    /// until ZK_ContextInit or ZK_ContextImportVerifyingKey
+    pub fn from_globals() -> Self {
+        Self {
+            #[cfg(feature = "prover")]
+            pk: current_proving_key(),
+            #[cfg(feature = "verifier")]
+            pvk: current_verifying_key(),
+            trusted_issuers: Arc::new(TrustStore::default()),
+            config: ContextConfig::from_globals(),
+            #[cfg(all(feature = "prover", not(feature = "strict")))]
+            proving_seed: entropy::deterministic_proving().unwrap_or(0),
+        }
+    }
+
+    /// Replace this context's keys with those of a fresh VC setup
+    ///
+    /// The setup draws from the entropy source, so the keys are this
+    /// context's own: its proofs verify under no other context's key.
+    #[cfg(feature = "prover")]
+    pub fn setup(&mut self) -> Result<(), c_int> {
+        let mut rng = entropy::fresh_rng()?;
+        let (pk, _) = Groth16::<Bn254>::circuit_specific_setup(VCCircuit::blank(), &mut rng).map_err(|_| -1)?;
+        #[cfg(feature = "verifier")]
+        {
+            self.pvk = Some(Arc::new(PreparedVerifyingKey::from(pk.vk.clone())));
+        }
+        self.pk = Some(Arc::new(pk));
+        Ok(())
+    }
+
+    /// Replace this context's verifying key; a proving key for another
+    /// verifying key is dropped
+    #[cfg(feature = "verifier")]
+    pub fn set_verifying_key(&mut self, vk: VerifyingKey<Bn254>) {
+        #[cfg(feature = "prover")]
+        if self.pk.as_ref().is_some_and(|pk| pk.vk != vk) {
+            self.pk = None;
+        }
+        self.pvk = Some(Arc::new(PreparedVerifyingKey::from(vk)));
+    }
+
+    /// The raw verifying key of this context's keys, if it has any
+    pub fn raw_verifying_key(&self) -> Option<&VerifyingKey<Bn254>> {
+        #[cfg(feature = "verifier")]
+        if let Some(pvk) = &self.pvk {
+            return Some(&pvk.vk);
+        }
+        #[cfg(feature = "prover")]
+        if let Some(pk) = &self.pk {
+            return Some(&pk.vk);
+        }
+        None
+    }
+
+    pub fn config(&self) -> ContextConfig {
+        self.config
+    }
+
+    /// Replace the settings; fails if any knob is out of range, or if
+    /// deterministic proving is turned on and no seed can be drawn for it
+    pub fn set_config(&mut self, config: ContextConfig) -> bool {
+        if !config.is_valid() {
+            return false;
+        }
+        #[cfg(all(feature = "prover", not(feature = "strict")))]
+        while config.deterministic != 0 && self.proving_seed == 0 {
+            let mut seed = [0u8; 8];
+            if entropy::fill(&mut seed).is_err() {
+                return false;
+            }
+            self.proving_seed = u64::from_le_bytes(seed);
+        }
+        self.config = config;
+        true
+    }
+
+    /// Add an issuer key to this context's trust store
+    ///
+    /// In a store at its capacity, ZK_EVICT_OLDEST drops the keys trusted
+    /// first; ZK_EVICT_REJECT fails with ZK_ERR_CAPACITY.
+    pub fn trust_issuer(&mut self, issuer_pubkey: [u8; 32]) -> Result<(), c_int> {
+        if self.trusted_issuers.keys.contains(&issuer_pubkey) {
+            return Ok(());
+        }
+        let evict = limits::admit(ZK_COLLECTION_TRUST_STORE, self.trusted_issuers.order.len()).map_err(|e| e.code())?;
+
+        let store = Arc::make_mut(&mut self.trusted_issuers);
+        for old in store.order.drain(..evict) {
+            store.keys.remove(&old);
+        }
+        limits::record_evicted(ZK_COLLECTION_TRUST_STORE, evict);
+        store.order.push_back(issuer_pubkey);
+        store.keys.insert(issuer_pubkey);
+        Ok(())
+    }
+
+    /// An empty trust store accepts any issuer, like the global entry points
+    pub fn is_trusted(&self, issuer_pubkey: &[u8; 32]) -> bool {
+        self.trusted_issuers.keys.is_empty() || self.trusted_issuers.keys.contains(issuer_pubkey)
+    }
+
+    /// Validation under this context's time mode and date window
+    pub fn validation_policy(&self, checks: u32) -> Option<Policy> {
+        Some(Policy {
+            checks,
+            time_mode: self.config.time()?,
+            date_window: self.config.date_window(),
+            allow_expired_within_seconds: 0,
+        })
+    }
+
+    /// Trusted issuer keys, oldest first
+    pub fn trusted_issuers(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.trusted_issuers.order.iter()
+    }
+
+    /// Replace the trust store with `keys`, oldest first, bypassing its limit
+    pub fn replace_trusted_issuers(&mut self, keys: impl IntoIterator<Item = [u8; 32]>) {
+        let mut store = TrustStore::default();
+        for key in keys {
+            if store.keys.insert(key) {
+                store.order.push_back(key);
+            }
+        }
+        self.trusted_issuers = Arc::new(store);
+    }
+
+    /// The verifying key this context checks proofs against
+    #[cfg(feature = "verifier")]
+    pub fn verifying_key(&self) -> Option<&PreparedVerifyingKey<Bn254>> {
+        self.pvk.as_deref()
+    }
+
+    /// Verify a hex proof against this context's key and trust store
+    #[cfg(feature = "verifier")]
+    fn verify_vc_proof(
+        &self,
+        proof_hex: *const c_char,
+        issuer_pubkey: *const c_char,
+        current_time: u64,
+        nonce: u64,
+    ) -> c_int {
+        let pvk = match &self.pvk {
+            Some(pvk) if !issuer_pubkey.is_null() => pvk,
+            _ => return 0,
+        };
+
+        let issuer_key = match parse_verifying_key(issuer_pubkey) {
+            Some(key) if self.is_trusted(key.as_bytes()) => key,
+            _ => return 0,
+        };
+
+        let (header, format, proof) = match admission::vc_proof_arg(proof_hex, self.config.accepted_formats) {
+            Ok(decoded) => decoded,
+            Err(rejection) => return rejection.verify_code(),
+        };
+        let now = match self.config.time().map(|mode| clock::now_with(mode, current_time)) {
+            Some(Ok(now)) => now,
+            Some(Err(code)) => return code,
+            None => return -1,
+        };
+        verify_vc_proof_decoded(pvk, format, &proof, issuer_key.as_bytes(), &header, now, nonce)
+    }
+
+    /// Check and prove a hex VC blob under this context's settings
+    #[cfg(feature = "prover")]
+    fn prove_blob(
+        &self,
+        vc_blob: *const c_char,
+        issuer_pubkey: *const c_char,
+        second_issuer_pubkey: *const c_char,
+        current_time: u64,
+        nonce: u64,
+    ) -> Result<String, c_int> {
+        let pk = self.pk.as_deref().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+        let blob_len = unsafe { CStr::from_ptr(vc_blob) }.to_bytes().len();
+        if blob_len > self.config.max_blob_len {
+            return Err(-1);
+        }
+
+        let (vc, issuer_key) = match (parse_vc_blob(vc_blob), parse_verifying_key(issuer_pubkey)) {
+            (Some(vc), Some(key)) => (vc, key),
+            _ => return Err(-1),
+        };
+        if !self.is_trusted(issuer_key.as_bytes()) {
+            return Err(-1);
+        }
+
+        let second_key = if second_issuer_pubkey.is_null() {
+            None
+        } else {
+            Some(parse_verifying_key(second_issuer_pubkey).ok_or(-1)?)
+        };
+        let policy = self.validation_policy(PROVER_CHECKS).ok_or(-1)?;
+        validate(&vc, &[issuer_key], second_key.as_ref(), &policy, current_time).first_error()?;
+        let now = clock::now_with(policy.time_mode, current_time)?;
+        let signature = vc.anchor_signature()?;
+        let anchored = Anchored { opening: vc.opening().ok_or(-1)?, signature: &signature };
+        self.prove_anchored(pk, &anchored, issuer_key.as_bytes(), now, nonce)
+    }
+
+    /// Check a credential given by its fields and prove it under this
+    /// context's keys, trust store and settings
+    #[cfg(feature = "prover")]
+    fn prove_fields(&self, credential: &ProofCredential, current_time: u64, nonce: u64) -> Result<String, c_int> {
+        let pk = self.pk.as_deref().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+        let window = Window {
+            issue_date: credential.issue_date,
+            expiry_date: credential.expiry_date,
+        };
+        if !self.is_trusted(credential.issuer_pubkey)
+            || !self.config.date_window().contains_range(window.issue_date, window.expiry_date)
+        {
+            return Err(-1);
+        }
+
+        let message = fields_hash(credential.holder_id, credential.issuer, window.issue_date, window.expiry_date, &[]);
+        let opening = validity::Opening::of(&message, window).ok_or(-1)?;
+        if !validity::anchor_signed(credential.issuer_pubkey, &opening.anchor(), credential.signature) {
+            return Err(ZK_ERR_BAD_SIGNATURE);
+        }
+        let now = clock::now_with(self.config.time().ok_or(-1)?, current_time)?;
+        if !dates::is_active(now, window.issue_date, window.expiry_date) {
+            return Err(ZK_ERR_VC_EXPIRED);
+        }
+        let anchored = Anchored { opening, signature: credential.signature };
+        self.prove_anchored(pk, &anchored, credential.issuer_pubkey, now, nonce)
+    }
+
+    /// Prove a checked credential in this context's proof format
+    #[cfg(feature = "prover")]
+    fn prove_anchored(
+        &self,
+        pk: &ProvingKey<Bn254>,
+        credential: &Anchored,
+        issuer_pubkey: &[u8],
+        now: u64,
+        nonce: u64,
+    ) -> Result<String, c_int> {
+        let format = self.config.proof_format;
+        let (circuit, header) = vc_circuit(format, credential, issuer_pubkey, now, nonce, None).ok_or(-1)?;
+        #[cfg(not(feature = "strict"))]
+        if self.config.deterministic != 0 {
+            let mut rng = entropy::derived_rng(self.proving_seed, nonce);
+            return prove_with_key(pk, format, circuit, &header, &mut rng);
+        }
+        prove_with_key(pk, format, circuit, &header, &mut entropy::fresh_rng()?)
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Create a context from the current keys (ZK_Init) and global settings
+///
+/// Before ZK_Init the context has no keys: give it its own with
+/// ZK_ContextInit or ZK_ContextImportVerifyingKey. The handle is written to
+/// `ctx_out` and must be released with ZK_ContextFree. Returns 0 on
+/// success, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ContextCreate(ctx_out: *mut *mut Context) -> c_int {
+    if ctx_out.is_null() {
+        return -1;
+    }
+
+    unsafe {
+        *ctx_out = Box::into_raw(Box::new(Context::from_globals()));
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Give the context keys of its own from a fresh VC circuit setup
+///
+/// Replaces the keys it had (its clones keep theirs). Proofs from
+/// ZK_ContextGenerateVCProof then verify only under this context's key,
+/// which ZK_ContextExportVerifyingKey hands to verifiers. Returns 0 on
+/// success, ZK_ERR_ENTROPY if the entropy source fails, -1 on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ContextInit(ctx: *mut Context) -> c_int {
+    match unsafe { ctx.as_mut() } {
+        Some(ctx) => match ctx.setup() {
+            Ok(()) => 0,
+            Err(code) => code,
+        },
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the context's verifying key (hex, as ZK_ExportVerifyingKey)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL for a short buffer,
+/// ZK_ERR_NOT_INITIALIZED if the context has no keys, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ContextExportVerifyingKey(ctx: *const Context, vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) => ctx,
+        None => return -1,
+    };
+
+    match ctx.raw_verifying_key() {
+        Some(vk) => match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk::encode(vk))) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        None => ZK_ERR_NOT_INITIALIZED,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install a verifying key from ZK_ExportVerifyingKey or
+/// ZK_ContextExportVerifyingKey in the context
+///
+/// Global keys and other contexts are unaffected; a proving key the context
+/// held for another key is dropped. Returns 0 on success, -1 on NULL or a
+/// key that does not decode.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ContextImportVerifyingKey(ctx: *mut Context, vk_hex: *const c_char) -> c_int {
+    let ctx = match unsafe { ctx.as_mut() } {
+        Some(ctx) if !vk_hex.is_null() => ctx,
+        _ => return -1,
+    };
+
+    let vk = admission::text_arg(vk_hex, crate::ffi::MAX_BLOB_LEN)
+        .and_then(|text| crate::hex_to_bytes(text).ok())
+        .and_then(|bytes| vk::decode_raw(&bytes).ok());
+    match vk {
+        Some(vk) => {
+            ctx.set_verifying_key(vk);
+            0
+        }
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Create an independent copy of `src` sharing its keys and trust store
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_ContextClone(src: *const Context, ctx_out: *mut *mut Context) -> c_int {
+    let src = match unsafe { src.as_ref() } {
+        Some(src) if !ctx_out.is_null() => src,
+        _ => return -1,
+    };
+
+    unsafe {
+        *ctx_out = Box::into_raw(Box::new(src.clone()));
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Copy the context's scalar settings into `config_out`
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_ContextGetConfig(ctx: *const Context, config_out: *mut ContextConfig) -> c_int {
+    match unsafe { (ctx.as_ref(), config_out.as_mut()) } {
+        (Some(ctx), Some(out)) => {
+            *out = ctx.config();
+            0
+        }
+        _ => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Replace the context's scalar settings
+///
+/// Typically called with a config from ZK_ContextGetConfig with a few fields
+/// changed. Returns 0 on success, -1 if a field is out of range (the context
+/// is left unchanged).
+#[no_mangle]
+pub extern "C" fn ZK_ContextSetConfig(ctx: *mut Context, config: *const ContextConfig) -> c_int {
+    let (ctx, config) = match unsafe { (ctx.as_mut(), config.as_ref()) } {
+        (Some(ctx), Some(config)) => (ctx, *config),
+        _ => return -1,
+    };
+
+    if ctx.set_config(config) {
+        0
+    } else {
+        -1
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Add a hex issuer public key to the context's trust store
+///
+/// Once the store is non-empty, context operations reject other issuers.
+/// Returns 0 on success, ZK_ERR_CAPACITY if the store is full and its
+/// policy is ZK_EVICT_REJECT, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_ContextTrustIssuer(ctx: *mut Context, issuer_pubkey: *const c_char) -> c_int {
+    let ctx = match unsafe { ctx.as_mut() } {
+        Some(ctx) if !issuer_pubkey.is_null() => ctx,
+        _ => return -1,
+    };
+
+    match parse_verifying_key(issuer_pubkey) {
+        Some(key) => match ctx.trust_issuer(key.to_bytes()) {
+            Ok(()) => 0,
+            Err(code) => code,
+        },
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// List a page of the context's trusted issuer keys, oldest first
+///
+/// Writes {"total": n, "offset": offset, "issuers": ["<hex key>", ...]} with
+/// at most `max` keys starting at `offset`; page until offset + returned
+/// keys reaches total. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL for a
+/// short buffer, -1 on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_TrustStoreList(
+    ctx: *const Context,
+    offset: usize,
+    max: usize,
+    issuers_json_out: *mut c_char,
+    issuers_json_out_size: usize,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) => ctx,
+        None => return -1,
+    };
+
+    let issuers: Vec<String> = ctx.trusted_issuers().skip(offset).take(max).map(hex::encode).collect();
+    let json = json!({
+        "total": ctx.trusted_issuers.order.len(),
+        "offset": offset,
+        "issuers": issuers,
+    });
+
+    match write_cstr(issuers_json_out, issuers_json_out_size, &json.to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_GenerateVCProof_I64 under the context's keys, trust store and
+/// settings
+///
+/// The credential is checked as ZK_GenerateVCProof_I64 checks it, against
+/// the context's date window and time mode; the prover's expiry grace and
+/// nonce reuse policy do not apply. Returns 0 on success, ZK_ERR_BAD_HEX,
+/// ZK_ERR_BAD_SIGNATURE, ZK_ERR_VC_EXPIRED, ZK_ERR_NOT_INITIALIZED if the
+/// context has no proving key, ZK_ERR_TIME_MISMATCH, ZK_ERR_BUFFER_TOO_SMALL,
+/// or -1 on NULL, dates outside the window or an untrusted issuer.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ContextGenerateVCProof(
+    ctx: *const Context,
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    vc_signature: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) if !vc_signature.is_null() && !issuer_pubkey.is_null() && !proof_out.is_null() => ctx,
+        _ => return -1,
+    };
+    let (holder_id, issuer) = match (
+        read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN),
+        read_bytes(issuer, issuer_len, MAX_FIELD_LEN),
+    ) {
+        (Ok(holder_id), Ok(issuer)) => (holder_id, issuer),
+        _ => return -1,
+    };
+
+    let hex_arg = |ptr: *const c_char| hex_to_bytes(unsafe { CStr::from_ptr(ptr) }.to_str().unwrap_or(""));
+    let (signature, issuer_pubkey) = match (hex_arg(vc_signature), hex_arg(issuer_pubkey)) {
+        (Ok(signature), Ok(key)) => match (<[u8; 64]>::try_from(signature), <[u8; 32]>::try_from(key)) {
+            (Ok(signature), Ok(key)) => (signature, key),
+            _ => return ZK_ERR_BAD_HEX,
+        },
+        _ => return ZK_ERR_BAD_HEX,
+    };
+
+    let credential = ProofCredential {
+        holder_id,
+        issuer,
+        issue_date,
+        expiry_date,
+        signature: &signature,
+        issuer_pubkey: &issuer_pubkey,
+        holder: None,
+        revocation: None,
+    };
+    let proof_hex = match ctx.prove_fields(&credential, current_time, nonce) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_GenerateVCProofFromBlob under the context's keys and settings
+///
+/// Returns 0 on success, ZK_ERR_COSIGNATURE_MISSING, ZK_ERR_TIME_MISMATCH or
+/// another negative code on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ContextGenerateVCProofFromBlob(
+    ctx: *const Context,
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) if !vc_blob.is_null() && !issuer_pubkey.is_null() && !proof_out.is_null() => ctx,
+        _ => return -1,
+    };
+
+    let proof_hex = match ctx.prove_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time, nonce) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_VerifyVCProof under the context's keys and trust store
+///
+/// `current_time` goes through the context's time mode and is the proof's
+/// public time. Returns 1 if valid, 0 if invalid (also for a proof of
+/// another time or a credential inactive at this one) or the issuer is not
+/// trusted, the time source's error or -1 as for ZK_VerifyVCProof,
+/// ZK_ERR_DISABLED for a legacy-format proof in a strict build,
+/// ZK_ERR_UNSUPPORTED_VERSION for a format outside the context's
+/// accepted_formats.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ContextVerifyVCProof(
+    ctx: *const Context,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = std::time::Instant::now();
+    let ctx = unsafe { ctx.as_ref() };
+    let result = match ctx {
+        Some(ctx) => ctx.verify_vc_proof(proof_hex, issuer_pubkey, current_time, nonce),
+        None => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: ctx.and_then(|ctx| ctx.verifying_key()).map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Release a context from ZK_ContextCreate or ZK_ContextClone
+#[no_mangle]
+pub extern "C" fn ZK_ContextFree(ctx: *mut Context) {
+    if !ctx.is_null() {
+        unsafe {
+            drop(Box::from_raw(ctx));
+        }
+    }
+}
+}
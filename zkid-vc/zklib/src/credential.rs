@@ -0,0 +1,1178 @@
+// ============================================================================
+// Credential Blobs and Dual-Control Issuance
+// ============================================================================
+//
+// Binary VC encoding (hex-encoded at the C API), all integers little-endian,
+// variable-length fields prefixed with their u32 length:
+//
+//   "ZKVC" | version u8 | holder_id | issuer | issue_date i64 | expiry_date i64
+//   | claim_count u32 | (key, value)* | signature
+//   | has_co_signature u8 | [key_id 32 bytes | signature]
+//   | has_supersedes u8 | [supersedes_credential_id]          (version >= 2)
+//   | has_schedule u8 | [validity schedule]                  (version >= 4)
+//   | claims_root_signed u8                                   (version >= 7)
+//   | domain_separated u8                                     (version >= 9)
+//
+// Versions 1 and 2 (no supersession pointer before 2) are still accepted;
+// their dates are u64 and are rejected above i64::MAX. Version 3 only marks
+// the switch to signed dates. The validity schedule is encoded as described
+// in schedule.rs. Version 5 changes no field: its signatures cover the
+// credential usage context (usage.rs), where older blobs were signed over
+// the bare message hash and are re-encoded as version 4 to stay verifiable.
+// Version 6 changes no field either: its message hash also covers the claims
+// Merkle root (disclosure.rs). A blob encoded with a signature made by the
+// field-based entry points, which hash no root, is written as version 5.
+// Version 7 switches the message hash to format 2 (VC_HASH_FORMAT), which
+// length-prefixes the fields the older hash ran together; since that is
+// independent of the claims root, a trailing byte says whether the root is
+// signed. Blobs up to version 6 keep the format 1 hash they were signed
+// over. Version 8 changes no field: its message hash starts with the
+// "zkid.vc.msg.v1" tag (hash format 3, domain.rs), which blobs get when
+// signed under ZK_FORMAT_VERSION_2. Version 9 blobs are signed over the
+// credential's anchor (validity.rs) rather than its message hash, which is
+// what a VC proof needs; as the anchor is over either hash format, a second
+// trailing byte says whether the message hash is tagged. Version 10 changes
+// no field: the anchor of a scheduled credential folds a commitment to its
+// windows into the blinding (schedule.rs), so that the schedule circuit can
+// open them; version 9 blobs keep the anchor they were signed over.
+//
+// Credentials whose `zkid:schema` claim is registered as dual-control must
+// carry a co-signature from a second, distinct issuer key over the same
+// signed message.
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey, PUBLIC_KEY_LENGTH};
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::admission::text_arg;
+use crate::did::public_key_bytes;
+use crate::disclosure::{field_bytes, field_from_bytes, ClaimsTree};
+use crate::error::{ZK_ERR_BAD_SIGNATURE, ZK_ERR_COSIGNATURE_MISSING};
+use crate::ffi::{read_bytes, read_slice, write_cstr, MAX_ARRAY_LEN, MAX_FIELD_LEN};
+use crate::schedule::ValiditySchedule;
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, dates, domain, hex_to_bytes, validity, CoSignature, VerifiableCredential};
+#[cfg(feature = "prover")]
+use {
+    crate::checkpoint,
+    crate::replay,
+    crate::revocation::RevocationEntry,
+    crate::sequence::{self, sequencing_enabled, SEQUENCE_CLAIM},
+    crate::stats,
+    crate::validation::{validate, Policy, PROVER_CHECKS},
+    crate::{entropy, holder, last_error, prove_vc_hash, Anchored, SECRET_KEY_LENGTH},
+    ark_ff::PrimeField,
+    ed25519_dalek::SIGNATURE_LENGTH,
+    ed25519_dalek::SigningKey,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::clock,
+    crate::error::ZK_ERR_STORAGE,
+    crate::revocation::{reject_superseded, RevocationRegistry},
+    crate::storage::storage,
+};
+
+pub(crate) const VC_BLOB_MAGIC: &[u8; 4] = b"ZKVC";
+pub(crate) const VC_BLOB_VERSION: u8 = 10;
+
+/// Last blob version signed over the bare message hash
+const LEGACY_SIGNATURE_VERSION: u8 = 4;
+
+/// Last blob version whose message hash leaves out the claims root
+const UNCOMMITTED_CLAIMS_VERSION: u8 = 5;
+
+/// Last blob version whose message hash runs its fields together (format 1)
+const UNPREFIXED_VERSION: u8 = 6;
+
+/// Last blob version whose message hash has no purpose tag
+const UNTAGGED_VERSION: u8 = 7;
+
+/// Last blob version signed over the message hash rather than the anchor
+const UNANCHORED_VERSION: u8 = 8;
+
+/// Last blob version whose anchor leaves out the validity schedule
+const UNSCHEDULED_ANCHOR_VERSION: u8 = 9;
+
+/// Reserved claim naming the credential schema
+pub const SCHEMA_CLAIM: &str = "zkid:schema";
+
+/// Reserved claim carrying the anchor blinding (validity.rs; hex of a field
+/// element)
+pub const ANCHOR_BLINDING_CLAIM: &str = "zkid:anchor-blinding";
+
+/// Anchor blinding of a credential's claims: the `zkid:anchor-blinding`
+/// claim's, 0 without one; None for a repeated or malformed claim, which no
+/// anchor can be signed for
+pub fn claimed_blinding(claims: &[(String, String)]) -> Option<Fr> {
+    let mut values = claims.iter().filter(|(key, _)| key == ANCHOR_BLINDING_CLAIM).map(|(_, value)| value);
+    match (values.next(), values.next()) {
+        (None, _) => Some(Fr::zero()),
+        (Some(value), None) => field_from_bytes(&hex_to_bytes(value).ok()?),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+/// Set the anchor blinding claim of `claims` to a fresh random field element
+///
+/// Fails with ZK_ERR_ENTROPY if the entropy source fails.
+#[cfg(feature = "prover")]
+pub(crate) fn stamp_blinding(claims: &mut Vec<(String, String)>) -> Result<(), c_int> {
+    let mut bytes = [0u8; 32];
+    entropy::fill(&mut bytes)?;
+    let blinding = Fr::from_le_bytes_mod_order(&bytes);
+    claims.retain(|(key, _)| key != ANCHOR_BLINDING_CLAIM);
+    claims.push((ANCHOR_BLINDING_CLAIM.to_string(), bytes_to_hex(&field_bytes(&blinding))));
+    Ok(())
+}
+
+/// Reason a credential failed its issuer-signature checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureCheckError {
+    /// A present signature does not verify, or the issuer is a did:key of
+    /// another key
+    BadSignature,
+    /// The schema requires a second issuer signature that is absent
+    CoSignatureMissing,
+}
+
+impl SignatureCheckError {
+    pub fn code(self) -> c_int {
+        match self {
+            SignatureCheckError::BadSignature => ZK_ERR_BAD_SIGNATURE,
+            SignatureCheckError::CoSignatureMissing => ZK_ERR_COSIGNATURE_MISSING,
+        }
+    }
+}
+
+// Schemas that require dual-control issuance
+static DUAL_CONTROL_SCHEMAS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Mark (or unmark) a schema as requiring two issuer signatures
+pub fn set_dual_control_schema(schema_id: &str, required: bool) {
+    let mut guard = DUAL_CONTROL_SCHEMAS.lock().unwrap_or_else(|e| e.into_inner());
+    let schemas = guard.get_or_insert_with(HashSet::new);
+    if required {
+        schemas.insert(schema_id.to_string());
+    } else {
+        schemas.remove(schema_id);
+    }
+}
+
+// ============================================================================
+// VerifiableCredential Extensions
+// ============================================================================
+
+impl VerifiableCredential {
+    /// Blob version whose message-hash layout this credential's flags name
+    pub fn blob_version(&self) -> u8 {
+        if self.legacy_signature {
+            LEGACY_SIGNATURE_VERSION
+        } else if self.anchored && self.schedule_anchored {
+            VC_BLOB_VERSION
+        } else if self.anchored {
+            UNSCHEDULED_ANCHOR_VERSION
+        } else if self.domain_separated {
+            UNANCHORED_VERSION
+        } else if self.length_prefixed {
+            UNTAGGED_VERSION
+        } else if !self.claims_root_signed {
+            UNCOMMITTED_CLAIMS_VERSION
+        } else {
+            UNPREFIXED_VERSION
+        }
+    }
+
+    /// Set the layout flags of blob `version`, whose claims root and tag
+    /// flags are `claims_root_signed` and `domain_separated` (see
+    /// `implied_claims_root` and `implied_tag`)
+    pub(crate) fn set_layout(&mut self, version: u8, claims_root_signed: bool, domain_separated: bool) {
+        self.legacy_signature = version <= LEGACY_SIGNATURE_VERSION;
+        self.claims_root_signed = claims_root_signed;
+        self.length_prefixed = version > UNPREFIXED_VERSION;
+        self.domain_separated = domain_separated;
+        self.anchored = version > UNANCHORED_VERSION;
+        self.schedule_anchored = version > UNSCHEDULED_ANCHOR_VERSION;
+    }
+
+    /// Encode the credential as a versioned binary blob
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let version = self.blob_version();
+        let mut out = Vec::new();
+        out.extend_from_slice(VC_BLOB_MAGIC);
+        out.push(version);
+        put_bytes(&mut out, self.holder_id.as_bytes());
+        put_bytes(&mut out, self.issuer.as_bytes());
+        out.extend_from_slice(&self.issue_date.to_le_bytes());
+        out.extend_from_slice(&self.expiry_date.to_le_bytes());
+        out.extend_from_slice(&(self.claims.len() as u32).to_le_bytes());
+        for (key, value) in &self.claims {
+            put_bytes(&mut out, key.as_bytes());
+            put_bytes(&mut out, value.as_bytes());
+        }
+        put_bytes(&mut out, &self.signature);
+        match &self.co_signature {
+            Some(co) => {
+                out.push(1);
+                out.extend_from_slice(&co.key_id);
+                put_bytes(&mut out, &co.signature);
+            }
+            None => out.push(0),
+        }
+        match &self.supersedes {
+            Some(old_id) => {
+                out.push(1);
+                put_bytes(&mut out, old_id.as_bytes());
+            }
+            None => out.push(0),
+        }
+        match &self.validity_schedule {
+            Some(schedule) => {
+                out.push(1);
+                schedule.encode(&mut out);
+            }
+            None => out.push(0),
+        }
+        if version > UNPREFIXED_VERSION {
+            out.push(u8::from(self.claims_root_signed));
+        }
+        if version > UNANCHORED_VERSION {
+            out.push(u8::from(self.domain_separated));
+        }
+        out
+    }
+
+    /// Decode a binary blob or its hex text
+    pub fn from_wire(data: &[u8]) -> Option<Self> {
+        if data.starts_with(VC_BLOB_MAGIC) {
+            return Self::from_bytes(data);
+        }
+        let text = std::str::from_utf8(data).ok()?.trim();
+        Self::from_bytes(&hex_to_bytes(text).ok()?)
+    }
+
+    /// Decode a blob produced by `to_bytes`
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != VC_BLOB_MAGIC {
+            return None;
+        }
+        let version = r.u8()?;
+        if version == 0 || version > VC_BLOB_VERSION {
+            return None;
+        }
+
+        let holder_id = r.string()?;
+        let issuer = r.string()?;
+        let (issue_date, expiry_date) = match version {
+            1 | 2 => (dates::from_u64(r.u64()?)?, dates::from_u64(r.u64()?)?),
+            _ => (r.u64()? as i64, r.u64()? as i64),
+        };
+
+        let claim_count = r.u32()? as usize;
+        let mut claims = Vec::new();
+        for _ in 0..claim_count {
+            claims.push((r.string()?, r.string()?));
+        }
+
+        let signature = r.bytes()?.to_vec();
+        let co_signature = match r.u8()? {
+            0 => None,
+            1 => Some(CoSignature {
+                key_id: r.take(32)?.try_into().ok()?,
+                signature: r.bytes()?.to_vec(),
+            }),
+            _ => return None,
+        };
+
+        let supersedes = match version {
+            1 => None,
+            _ => match r.u8()? {
+                0 => None,
+                1 => Some(r.string()?),
+                _ => return None,
+            },
+        };
+
+        let validity_schedule = match version {
+            1..=3 => None,
+            _ => match r.u8()? {
+                0 => None,
+                1 => Some(ValiditySchedule::decode(&mut r)?),
+                _ => return None,
+            },
+        };
+
+        let claims_root_signed = match implied_claims_root(version) {
+            Some(signed) => signed,
+            None => match r.u8()? {
+                0 => false,
+                1 => true,
+                _ => return None,
+            },
+        };
+        let domain_separated = match implied_tag(version) {
+            Some(tagged) => tagged,
+            None => match r.u8()? {
+                0 => false,
+                1 => true,
+                _ => return None,
+            },
+        };
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        let mut vc = Self {
+            holder_id,
+            issuer,
+            issue_date,
+            expiry_date,
+            claims,
+            signature,
+            co_signature,
+            supersedes,
+            validity_schedule,
+            legacy_signature: false,
+            claims_root_signed: false,
+            length_prefixed: false,
+            domain_separated: false,
+            anchored: false,
+            schedule_anchored: false,
+        };
+        vc.set_layout(version, claims_root_signed, domain_separated);
+        Some(vc)
+    }
+
+    /// Stable identifier: hex of the message hash
+    pub fn credential_id(&self) -> String {
+        bytes_to_hex(&self.message_hash())
+    }
+
+    /// Value of the first claim named `key`
+    pub fn claim(&self, key: &str) -> Option<&str> {
+        self.claims
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Root of the claims Merkle tree (disclosure.rs), 32 bytes; None for
+    /// more than MAX_COMMITTED_CLAIMS claims
+    pub fn claims_root(&self) -> Option<[u8; 32]> {
+        ClaimsTree::new(&self.claims).map(|tree| field_bytes(&tree.root()))
+    }
+
+    /// Whether `now` falls inside the validity schedule; true without one
+    pub fn in_schedule(&self, now: u64) -> bool {
+        self.validity_schedule
+            .as_ref()
+            .is_none_or(|schedule| schedule.contains(now))
+    }
+
+    /// Whether the dates are well-formed and `now` falls inside them and the
+    /// validity schedule
+    pub fn active_at(&self, now: u64) -> bool {
+        dates::valid_range(self.issue_date, self.expiry_date)
+            && dates::is_active(now, self.issue_date, self.expiry_date)
+            && self.in_schedule(now)
+    }
+
+    /// The issue and expiry dates, as a VC proof commits to them
+    pub fn window(&self) -> validity::Window {
+        validity::Window { issue_date: self.issue_date, expiry_date: self.expiry_date }
+    }
+
+    /// Schema identifier from the `zkid:schema` claim
+    pub fn schema_id(&self) -> Option<&str> {
+        self.claim(SCHEMA_CLAIM)
+    }
+
+    /// Whether the credential's schema is registered as dual-control
+    pub fn requires_co_signature(&self) -> bool {
+        let schema = match self.schema_id() {
+            Some(schema) => schema,
+            None => return false,
+        };
+        let guard = DUAL_CONTROL_SCHEMAS.lock().unwrap_or_else(|e| e.into_inner());
+        guard.as_ref().is_some_and(|schemas| schemas.contains(schema))
+    }
+
+    /// Add the second issuer's signature over the unchanged signed message;
+    /// false, leaving the credential as it was, if it has none (see
+    /// `signed_message`)
+    #[cfg(feature = "prover")]
+    pub fn co_sign(&mut self, second_key: &SigningKey) -> bool {
+        let message = match self.signed_message() {
+            Some(message) => message,
+            None => return false,
+        };
+        self.co_signature = Some(CoSignature {
+            key_id: second_key.verifying_key().to_bytes(),
+            signature: stats::sign_issuance(second_key, self.holder_id.as_bytes(), &message).to_bytes().to_vec(),
+        });
+        true
+    }
+
+    /// Issue a corrected successor that supersedes this credential
+    ///
+    /// Unchanged fields, including the validity schedule, are copied; each
+    /// correction replaces the claim of the same key (or is appended). The successor is freshly signed by `issuer`
+    /// and must be co-signed again where dual control applies, under a
+    /// fresh anchor blinding. None if the corrected claims bind no
+    /// well-formed holder key (see `anchor`) or the entropy source fails.
+    #[cfg(feature = "prover")]
+    pub fn reissue(
+        &self,
+        corrections: &[(String, String)],
+        issue_date: i64,
+        expiry_date: i64,
+        issuer: &SigningKey,
+    ) -> Option<VerifiableCredential> {
+        let mut claims = self.claims.clone();
+        for (key, value) in corrections {
+            match claims.iter_mut().find(|(k, _)| k == key) {
+                Some(claim) => claim.1 = value.clone(),
+                None => claims.push((key.clone(), value.clone())),
+            }
+        }
+
+        let mut successor = VerifiableCredential {
+            holder_id: self.holder_id.clone(),
+            issuer: self.issuer.clone(),
+            issue_date,
+            expiry_date,
+            claims,
+            signature: Vec::new(),
+            co_signature: None,
+            supersedes: Some(self.credential_id()),
+            validity_schedule: self.validity_schedule.clone(),
+            legacy_signature: false,
+            claims_root_signed: true,
+            length_prefixed: true,
+            domain_separated: domain::tagged(),
+            anchored: true,
+            schedule_anchored: true,
+        };
+        stamp_blinding(&mut successor.claims).ok()?;
+        let message = successor.signed_message()?;
+        successor.signature = stats::sign_issuance(issuer, successor.holder_id.as_bytes(), &message).to_bytes().to_vec();
+        Some(successor)
+    }
+
+    /// Check the issuer signature and, where present or required, the co-signature
+    ///
+    /// `second_issuer` pins the expected co-signer; with `None` the key named by
+    /// the co-signature's key id is used.
+    pub fn verify_issuer_signatures(
+        &self,
+        issuer: &VerifyingKey,
+        second_issuer: Option<&VerifyingKey>,
+    ) -> Result<(), SignatureCheckError> {
+        if !self.verify_signature(issuer) {
+            return Err(SignatureCheckError::BadSignature);
+        }
+
+        let co = match &self.co_signature {
+            Some(co) => co,
+            None if self.requires_co_signature() => {
+                return Err(SignatureCheckError::CoSignatureMissing)
+            }
+            None => return Ok(()),
+        };
+
+        // Both signatures must come from distinct keys
+        if co.key_id == issuer.to_bytes() {
+            return Err(SignatureCheckError::CoSignatureMissing);
+        }
+
+        let second_key = match second_issuer {
+            Some(key) if key.to_bytes() != co.key_id => {
+                return Err(SignatureCheckError::BadSignature)
+            }
+            Some(key) => *key,
+            None => VerifyingKey::from_bytes(&co.key_id)
+                .map_err(|_| SignatureCheckError::BadSignature)?,
+        };
+
+        let signature = Signature::from_slice(&co.signature)
+            .map_err(|_| SignatureCheckError::BadSignature)?;
+        let message = self.signed_message().ok_or(SignatureCheckError::BadSignature)?;
+        second_key
+            .verify(&message, &signature)
+            .map_err(|_| SignatureCheckError::BadSignature)
+    }
+
+    /// The issuer signature an unbound VC proof of the credential carries:
+    /// its own, if made over the anchor (blob version 9 on) of a credential
+    /// that binds no holder key; failures are recorded for ZK_GetLastError
+    #[cfg(feature = "prover")]
+    pub(crate) fn anchor_signature(&self) -> Result<[u8; SIGNATURE_LENGTH], c_int> {
+        if self.anchored && holder::claimed_key(&self.claims) != Some(Fr::zero()) {
+            return Err(last_error::fail(-1, "credential is bound to a holder key; prove it with the holder secret"));
+        }
+        self.anchored_signature()
+    }
+
+    /// The issuer's signature, if made over the anchor (blob version 9 on),
+    /// whatever holder key the anchor binds; failures are recorded for
+    /// ZK_GetLastError
+    #[cfg(feature = "prover")]
+    pub(crate) fn anchored_signature(&self) -> Result<[u8; SIGNATURE_LENGTH], c_int> {
+        if !self.anchored {
+            return Err(last_error::fail(
+                ZK_ERR_BAD_SIGNATURE,
+                "credential predates anchored signing; sign it again (ZK_SignVCBlob) to prove it",
+            ));
+        }
+        self.signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| last_error::fail(ZK_ERR_BAD_SIGNATURE, "credential signature is not 64 bytes"))
+    }
+}
+
+/// Whether blob `version` signs the claims root, when the version implies it
+///
+/// From version 7 on a trailing byte says so and this is None.
+pub(crate) fn implied_claims_root(version: u8) -> Option<bool> {
+    (version <= UNPREFIXED_VERSION).then_some(version > UNCOMMITTED_CLAIMS_VERSION)
+}
+
+/// Whether blob `version` tags its message hash, when the version implies it
+///
+/// From version 9 on a trailing byte says so and this is None.
+pub(crate) fn implied_tag(version: u8) -> Option<bool> {
+    (version <= UNANCHORED_VERSION).then_some(version > UNTAGGED_VERSION)
+}
+
+// ============================================================================
+// C Argument Helpers
+// ============================================================================
+
+fn c_str<'a>(ptr: *const c_char) -> &'a str {
+    unsafe { CStr::from_ptr(ptr).to_str().unwrap_or("") }
+}
+
+pub(crate) fn parse_vc_blob(vc_blob: *const c_char) -> Option<VerifiableCredential> {
+    VerifiableCredential::from_bytes(&hex_to_bytes(c_str(vc_blob)).ok()?)
+}
+
+#[cfg(feature = "prover")]
+pub(crate) fn parse_signing_key(private_key: *const c_char) -> Option<SigningKey> {
+    let bytes = hex_to_bytes(c_str(private_key)).ok()?;
+    let bytes: [u8; SECRET_KEY_LENGTH] = bytes.as_slice().try_into().ok()?;
+    Some(SigningKey::from_bytes(&bytes))
+}
+
+/// Hex or did:key public key (did.rs)
+pub(crate) fn parse_verifying_key(public_key: *const c_char) -> Option<VerifyingKey> {
+    // Anything longer than a key's hex (or did:key, shorter) cannot be one;
+    // stop reading there
+    let bytes = public_key_bytes(text_arg(public_key, 2 * PUBLIC_KEY_LENGTH)?).ok()?;
+    VerifyingKey::from_bytes(bytes.as_slice().try_into().ok()?).ok()
+}
+
+/// Read parallel arrays of NUL-terminated claim keys and values
+pub(crate) fn read_claims(
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+) -> Option<Vec<(String, String)>> {
+    let keys = read_slice(claim_keys, claim_count, MAX_ARRAY_LEN).ok()?;
+    let values = read_slice(claim_values, claim_count, MAX_ARRAY_LEN).ok()?;
+
+    keys.iter()
+        .zip(values)
+        .map(|(&key, &value)| {
+            if key.is_null() || value.is_null() {
+                return None;
+            }
+            let key = unsafe { CStr::from_ptr(key) }.to_str().ok()?;
+            let value = unsafe { CStr::from_ptr(value) }.to_str().ok()?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub(crate) fn write_vc_blob(vc: &VerifiableCredential, out: *mut c_char, out_size: usize) -> c_int {
+    match write_cstr(out, out_size, &bytes_to_hex(&vc.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Encode a VC (fields, claims and optional hex signature) as a hex blob
+///
+/// `signature` may be NULL or empty for a credential that is signed later
+/// with ZK_SignVCBlob. A signature from ZK_SignVC* covers no claims root, so
+/// such a blob is encoded as version 5 and its claims cannot be disclosed
+/// one at a time. Dates must lie inside the ZK_SetDateWindow window.
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_EncodeVC_I64(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    signature: *const c_char,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    if holder_id.is_null() || issuer.is_null() || vc_blob_out.is_null() {
+        return -1;
+    }
+
+    if !dates::valid_range(issue_date, expiry_date) {
+        return -1;
+    }
+
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let (holder_id, issuer) = match (std::str::from_utf8(holder_id_bytes), std::str::from_utf8(issuer_bytes)) {
+        (Ok(h), Ok(i)) => (h.to_string(), i.to_string()),
+        _ => return -1,
+    };
+
+    let claims = match read_claims(claim_keys, claim_values, claim_count) {
+        Some(claims) => claims,
+        None => return -1,
+    };
+
+    let signature = if signature.is_null() {
+        Vec::new()
+    } else {
+        match hex_to_bytes(c_str(signature)) {
+            Ok(bytes) => bytes,
+            Err(_) => return -1,
+        }
+    };
+
+    let vc = VerifiableCredential {
+        holder_id,
+        issuer,
+        issue_date,
+        expiry_date,
+        claims,
+        co_signature: None,
+        supersedes: None,
+        validity_schedule: None,
+        legacy_signature: false,
+        // A signature passed in comes from ZK_SignVC*, which hash no claims root
+        claims_root_signed: signature.is_empty(),
+        length_prefixed: true,
+        domain_separated: domain::tagged(),
+        // ZK_SignVC* sign the anchor
+        anchored: true,
+        schedule_anchored: true,
+        signature,
+    };
+
+    write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_EncodeVC_I64 with u64 dates; values above i64::MAX are rejected
+#[no_mangle]
+pub extern "C" fn ZK_EncodeVC(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    signature: *const c_char,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return -1,
+    };
+    ZK_EncodeVC_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        claim_keys, claim_values, claim_count,
+        signature,
+        vc_blob_out, vc_blob_out_size,
+    )
+}
+}
+
+crate::unwind::entry_point! {
+/// Sign a VC blob with the (first) issuer's private key
+///
+/// The signature covers the anchor (validity.rs) of the full message hash
+/// including claims and their Merkle root, so the blob is written as the
+/// current version; any existing co-signature is dropped. With
+/// ZK_SetIssuerSequencing enabled, the `zkid:sequence` claim is set to the
+/// key's next number first. The `zkid:anchor-blinding` claim is set to a
+/// fresh random value on every signing (validity.rs). Returns 0 on success,
+/// ZK_ERR_STORAGE if the sequence cannot be persisted, ZK_ERR_ENTROPY if
+/// the entropy source fails, -1 on failure, including an issuer that is
+/// the did:key of another key and a `zkid:holder-binding` claim that is not
+/// a single nonzero holder key.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVCBlob(
+    vc_blob: *const c_char,
+    issuer_private_key: *const c_char,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    if vc_blob.is_null() || issuer_private_key.is_null() || vc_blob_out.is_null() {
+        return -1;
+    }
+
+    let (mut vc, signing_key) = match (parse_vc_blob(vc_blob), parse_signing_key(issuer_private_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+    if !vc.issuer_matches(&signing_key.verifying_key()) {
+        return -1;
+    }
+
+    if sequencing_enabled() {
+        if let Err(e) = sequence::stamp(&mut vc, signing_key.verifying_key().as_bytes()) {
+            return e.code();
+        }
+    }
+    if let Err(code) = stamp_blinding(&mut vc.claims) {
+        return code;
+    }
+    vc.legacy_signature = false;
+    vc.claims_root_signed = true;
+    vc.length_prefixed = true;
+    vc.domain_separated = domain::tagged();
+    vc.anchored = true;
+    vc.schedule_anchored = true;
+    let message = match vc.signed_message() {
+        Some(message) => message,
+        None => return -1,
+    };
+    vc.signature = stats::sign_issuance(&signing_key, vc.holder_id.as_bytes(), &message).to_bytes().to_vec();
+    vc.co_signature = None;
+
+    write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Append a second issuer's signature to a signed VC blob
+///
+/// The co-signer must differ from the first issuer. Returns 0 on success,
+/// -1 on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_CoSignVC(
+    vc_blob: *const c_char,
+    second_private_key: *const c_char,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    if vc_blob.is_null() || second_private_key.is_null() || vc_blob_out.is_null() {
+        return -1;
+    }
+
+    let (mut vc, second_key) = match (parse_vc_blob(vc_blob), parse_signing_key(second_private_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+
+    if vc.signature.is_empty() {
+        return -1;
+    }
+
+    if !vc.co_sign(&second_key) {
+        return -1;
+    }
+
+    write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Re-issue a corrected credential that supersedes `old_vc_blob`
+///
+/// Unchanged fields are copied, the corrections replace or add claims, and
+/// `expiry_date` of 0 keeps the old expiry. The old credential must carry a
+/// valid signature from the same issuer key. Writes the new hex VC blob and a
+/// hex revocation entry for the old credential (see ZK_ApplyRevocationEntry).
+/// `issue_date` doubles as the revocation time and so cannot precede 1970.
+/// With ZK_SetIssuerSequencing enabled the new credential gets the key's next
+/// sequence number. Returns 0 on success, ZK_ERR_STORAGE if the sequence
+/// cannot be persisted, -1 on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ReissueVC_I64(
+    old_vc_blob: *const c_char,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    issuer_private_key: *const c_char,
+    new_vc_blob_out: *mut c_char,
+    new_vc_blob_out_size: usize,
+    revocation_entry_out: *mut c_char,
+    revocation_entry_out_size: usize,
+) -> c_int {
+    if old_vc_blob.is_null() || issuer_private_key.is_null() {
+        return -1;
+    }
+
+    let (old, signing_key) = match (parse_vc_blob(old_vc_blob), parse_signing_key(issuer_private_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+
+    if !old.verify_signature(&signing_key.verifying_key()) {
+        return -1;
+    }
+
+    let mut corrections = match read_claims(claim_keys, claim_values, claim_count) {
+        Some(claims) => claims,
+        None => return -1,
+    };
+
+    let expiry_date = if expiry_date == 0 { old.expiry_date } else { expiry_date };
+    let revoked_at = match u64::try_from(issue_date) {
+        Ok(t) if dates::valid_range(issue_date, expiry_date) => t,
+        _ => return -1,
+    };
+    if sequencing_enabled() {
+        match sequence::next(signing_key.verifying_key().as_bytes()) {
+            Ok(n) => corrections.push((SEQUENCE_CLAIM.to_string(), n.to_string())),
+            Err(e) => return e.code(),
+        }
+    }
+    let successor = match old.reissue(&corrections, issue_date, expiry_date, &signing_key) {
+        Some(successor) => successor,
+        None => return -1,
+    };
+
+    let entry = RevocationEntry {
+        credential_id: old.credential_id(),
+        revoked_at,
+        superseded_by: Some(successor.credential_id()),
+    };
+
+    if write_vc_blob(&successor, new_vc_blob_out, new_vc_blob_out_size) != 0 {
+        return -1;
+    }
+
+    match write_cstr(revocation_entry_out, revocation_entry_out_size, &bytes_to_hex(&entry.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_ReissueVC_I64 with u64 dates; values above i64::MAX are rejected
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ReissueVC(
+    old_vc_blob: *const c_char,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    issuer_private_key: *const c_char,
+    new_vc_blob_out: *mut c_char,
+    new_vc_blob_out_size: usize,
+    revocation_entry_out: *mut c_char,
+    revocation_entry_out_size: usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return -1,
+    };
+    ZK_ReissueVC_I64(
+        old_vc_blob,
+        claim_keys, claim_values, claim_count,
+        issue_date, expiry_date,
+        issuer_private_key,
+        new_vc_blob_out, new_vc_blob_out_size,
+        revocation_entry_out, revocation_entry_out_size,
+    )
+}
+}
+
+crate::unwind::entry_point! {
+/// Register or unregister a schema as dual-control
+///
+/// Returns 0 on success, -1 on invalid arguments.
+#[no_mangle]
+pub extern "C" fn ZK_SetDualControlSchema(
+    schema_id: *const c_char,
+    schema_id_len: usize,
+    required: c_int,
+) -> c_int {
+    if schema_id.is_null() {
+        return -1;
+    }
+
+    let schema_bytes = match read_bytes(schema_id, schema_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match std::str::from_utf8(schema_bytes) {
+        Ok(schema) => {
+            set_dual_control_schema(schema, required != 0);
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify the issuer signature(s) on a VC blob
+///
+/// `second_issuer_public_key` may be NULL to use the key id recorded in the
+/// co-signature. With ZK_SetRejectSuperseded enabled, a credential that the
+/// revocation registry or the published revocation list (see
+/// ZK_UpdateRevocationData) lists as superseded is invalid. Returns 1 if valid,
+/// 0 if invalid, ZK_ERR_COSIGNATURE_MISSING if the schema requires a
+/// co-signature that is absent, ZK_ERR_STORAGE if the registry lookup failed.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCBlob(
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+) -> c_int {
+    let started = std::time::Instant::now();
+    let result = verify_vc_blob(vc_blob, issuer_public_key, second_issuer_public_key);
+
+    audit::record(started, audit::Verification {
+        check: "vc-signature",
+        code: result,
+        circuit: None,
+        vk: None,
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// ZK_VerifyVCBlob, and check that the credential is valid at `current_time`
+///
+/// The time goes through the configured time source (ZK_SetTimeMode); the
+/// credential must be inside its issue/expiry dates and, if it has one, its
+/// validity schedule. Returns 1 if valid, 0 if invalid or not valid now,
+/// ZK_ERR_TIME_MISMATCH if the time sources disagree, or any code of
+/// ZK_VerifyVCBlob.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCBlobAt(
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+    current_time: u64,
+) -> c_int {
+    let started = std::time::Instant::now();
+    let result = match verify_vc_blob(vc_blob, issuer_public_key, second_issuer_public_key) {
+        1 => match (parse_vc_blob(vc_blob), clock::now(current_time)) {
+            (Some(vc), Ok(now)) => vc.active_at(now) as c_int,
+            (_, Err(code)) => code,
+            (None, _) => 0,
+        },
+        code => code,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "vc-signature",
+        code: result,
+        circuit: None,
+        vk: None,
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_vc_blob(
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+) -> c_int {
+    if vc_blob.is_null() || issuer_public_key.is_null() {
+        return 0;
+    }
+
+    let (vc, issuer_key) = match (parse_vc_blob(vc_blob), parse_verifying_key(issuer_public_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return 0,
+    };
+
+    let second_key = if second_issuer_public_key.is_null() {
+        None
+    } else {
+        match parse_verifying_key(second_issuer_public_key) {
+            Some(key) => Some(key),
+            None => return 0,
+        }
+    };
+
+    if let Err(e) = vc.verify_issuer_signatures(&issuer_key, second_key.as_ref()) {
+        return match e {
+            SignatureCheckError::BadSignature => 0,
+            e => e.code(),
+        };
+    }
+
+    if reject_superseded() {
+        let id = vc.credential_id();
+        let issuer = issuer_key.to_bytes();
+        if crate::revocation::published().is_some_and(|data| data.successor(&issuer, &id).is_some()) {
+            return 0;
+        }
+        match RevocationRegistry::new(storage()).successor(&id) {
+            Ok(Some(_)) => return 0,
+            Ok(None) => {}
+            Err(_) => return ZK_ERR_STORAGE,
+        }
+    }
+
+    1
+}
+
+/// Parse a hex VC blob and its issuer keys and run the prover pre-checks
+///
+/// Returns the credential, its issuer key and the time the checks used.
+/// A prover that skips its checks (ZK_SkipProverChecks) only parses, and
+/// proves at `current_time`.
+#[cfg(feature = "prover")]
+pub(crate) fn checked_blob(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    current_time: u64,
+) -> Result<(VerifiableCredential, VerifyingKey, u64), c_int> {
+    if vc_blob.is_null() || issuer_pubkey.is_null() {
+        return Err(-1);
+    }
+
+    let (vc, issuer_key) = match (parse_vc_blob(vc_blob), parse_verifying_key(issuer_pubkey)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return Err(-1),
+    };
+
+    if crate::skips_prover_checks() {
+        return Ok((vc, issuer_key, current_time));
+    }
+
+    let second_key = if second_issuer_pubkey.is_null() {
+        None
+    } else {
+        Some(parse_verifying_key(second_issuer_pubkey).ok_or(-1)?)
+    };
+
+    let report = validate(&vc, &[issuer_key], second_key.as_ref(), &Policy::global(PROVER_CHECKS), current_time);
+    report.first_error()?;
+    Ok((vc, issuer_key, report.time.unwrap_or(current_time)))
+}
+
+crate::unwind::entry_point! {
+/// Generate a ZK proof for a VC blob
+///
+/// Runs the ZK_ValidateVC signature, schema and time checks (signatures,
+/// including the dual-control co-signature, validity window and validity
+/// schedule) before proving. A recently proven nonce is handled by the
+/// ZK_SetNonceReusePolicy policy.
+/// Returns 0 on success, ZK_ERR_COSIGNATURE_MISSING, ZK_ERR_NONCE_REUSED or
+/// another negative code on failure.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofFromBlob(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() {
+        return -1;
+    }
+
+    let (vc, issuer_key, current_time) =
+        match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+            Ok(checked) => checked,
+            Err(code) => return code,
+        };
+
+    let request = checkpoint::Request::capture(
+        checkpoint::ENTRY_VC_PROOF_FROM_BLOB,
+        vc_blob,
+        issuer_pubkey,
+        second_issuer_pubkey,
+        current_time,
+        nonce,
+    );
+    let signature = match vc.anchor_signature() {
+        Ok(signature) => signature,
+        Err(code) => return code,
+    };
+    let hash = vc.message_hash();
+    let anchored = match vc.opening() {
+        Some(opening) => Anchored { opening, signature: &signature },
+        None => return -1,
+    };
+    let proof_hex = replay::guarded(&[], nonce, &[&hash, issuer_key.as_bytes()], || match request {
+        Some(request) => checkpoint::prove_vc(&request, &anchored, issuer_key.as_bytes()),
+        None => prove_vc_hash(&anchored, issuer_key.as_bytes(), current_time, nonce),
+    });
+    let proof_hex = match proof_hex {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,211 @@
+// ============================================================================
+// Credential Dates
+// ============================================================================
+//
+// issue_date and expiry_date are signed unix seconds (i64), so historical
+// credentials such as birth records can carry dates before 1970. Every date
+// entering the library is checked against a validity window, by default
+// 0001-01-01T00:00:00Z ..= 9999-12-31T23:59:59Z, and a credential must not
+// expire before it is issued.
+//
+// The message hash frames dates as i64 little-endian. For dates at or after
+// 1970 the bytes equal the former u64 framing, so existing signatures stay
+// valid; the blob format records the switch as version 3.
+//
+// The original u64 entry points remain as shims over the *_I64 ones. They
+// reject any value above i64::MAX instead of letting a negative host value
+// that was wrapped to u64 pass as a far-future expiry.
+//
+// Text dates (the W3C JSON form, w3c.rs) are RFC 3339 in UTC with whole
+// seconds, "YYYY-MM-DDTHH:MM:SSZ", which covers exactly the default window.
+// Parsing also takes a numeric offset and a fraction of zeros; a non-zero
+// fraction has no i64 second and is rejected rather than rounded.
+
+use std::os::raw::c_int;
+use crate::sync::Mutex;
+
+/// 0001-01-01T00:00:00Z
+pub const DEFAULT_MIN_DATE: i64 = -62_135_596_800;
+/// 9999-12-31T23:59:59Z
+pub const DEFAULT_MAX_DATE: i64 = 253_402_300_799;
+
+/// Accepted range for credential dates (inclusive)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateWindow {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl DateWindow {
+    /// Whether `issue_date ..= expiry_date` is ordered and inside the window
+    pub fn contains_range(&self, issue_date: i64, expiry_date: i64) -> bool {
+        self.min <= issue_date && issue_date <= expiry_date && expiry_date <= self.max
+    }
+}
+
+static DATE_WINDOW: Mutex<DateWindow> = Mutex::new(DateWindow {
+    min: DEFAULT_MIN_DATE,
+    max: DEFAULT_MAX_DATE,
+});
+
+/// Currently configured window
+pub fn date_window() -> DateWindow {
+    *DATE_WINDOW.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Convert a date from a legacy u64 entry point
+///
+/// Values above i64::MAX are rejected: they are either wrapped negative
+/// dates or far outside any window.
+pub fn from_u64(date: u64) -> Option<i64> {
+    i64::try_from(date).ok()
+}
+
+/// Whether `issue_date ..= expiry_date` is ordered and inside the window
+pub fn valid_range(issue_date: i64, expiry_date: i64) -> bool {
+    date_window().contains_range(issue_date, expiry_date)
+}
+
+/// Whether a credential is active at `now` (unix seconds from the time source)
+pub fn is_active(now: u64, issue_date: i64, expiry_date: i64) -> bool {
+    match from_u64(now) {
+        Some(now) => issue_date <= now && now <= expiry_date,
+        None => false,
+    }
+}
+
+/// Seconds `now` is past `expiry_date`, if the credential was issued by
+/// `now` and expired at most `within` seconds before it
+pub fn expired_within(now: u64, issue_date: i64, expiry_date: i64, within: u64) -> Option<u64> {
+    let now = from_u64(now)?;
+    let overshoot = u64::try_from(now.checked_sub(expiry_date)?).ok()?;
+    (issue_date <= now && overshoot > 0 && overshoot <= within).then_some(overshoot)
+}
+
+// ============================================================================
+// Calendar
+// ============================================================================
+
+pub(crate) const SECONDS_PER_DAY: i64 = 86_400;
+
+pub(crate) fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Proleptic Gregorian (year, month, day) of days since 1970-01-01
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `date` as "YYYY-MM-DDTHH:MM:SSZ", if its year has four digits
+pub fn to_rfc3339(date: i64) -> Option<String> {
+    if !(DEFAULT_MIN_DATE..=DEFAULT_MAX_DATE).contains(&date) {
+        return None;
+    }
+    let (year, month, day) = civil_from_days(date.div_euclid(SECONDS_PER_DAY));
+    let secs = date.rem_euclid(SECONDS_PER_DAY);
+    Some(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    ))
+}
+
+/// Value of a run of ASCII digits
+fn decimal(digits: &[u8]) -> Option<i64> {
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(digits.iter().fold(0, |n, d| n * 10 + i64::from(d - b'0')))
+}
+
+/// Unix seconds of an RFC 3339 date-time
+pub fn parse_rfc3339(text: &str) -> Option<i64> {
+    let b = text.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't') || b[13] != b':' || b[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (decimal(&b[0..4])?, decimal(&b[5..7])?, decimal(&b[8..10])?);
+    let (hour, minute, second) = (decimal(&b[11..13])?, decimal(&b[14..16])?, decimal(&b[17..19])?);
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    // Leap seconds have no unix time
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let mut rest = &b[19..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let digits = fraction.iter().take_while(|d| d.is_ascii_digit()).count();
+        if digits == 0 || fraction[..digits].iter().any(|&d| d != b'0') {
+            return None;
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        b"Z" | b"z" => 0,
+        [sign @ (b'+' | b'-'), hours @ .., b':', m1, m2] if hours.len() == 2 => {
+            let (hours, minutes) = (decimal(hours)?, decimal(&[*m1, *m2])?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'-' { -offset } else { offset }
+        }
+        _ => return None,
+    };
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second - offset)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Set the inclusive window that credential dates must fall in
+///
+/// Returns 0 on success, -1 if `min_date > max_date`.
+#[no_mangle]
+pub extern "C" fn ZK_SetDateWindow(min_date: i64, max_date: i64) -> c_int {
+    if min_date > max_date {
+        return -1;
+    }
+    *DATE_WINDOW.lock().unwrap_or_else(|e| e.into_inner()) = DateWindow {
+        min: min_date,
+        max: max_date,
+    };
+    0
+}
+}
@@ -0,0 +1,245 @@
+// ============================================================================
+// did:key Issuer Identifiers
+// ============================================================================
+//
+// Ed25519 public keys as did:key identifiers (W3C CCG did:key method):
+//
+//   "did:key:" | "z" | base58btc(0xed 0x01 | 32-byte public key)
+//
+// "z" is the multibase prefix of base58btc (Bitcoin alphabet) and 0xed 0x01
+// the unsigned-varint multicodec of ed25519-pub, so every Ed25519 did:key
+// starts "did:key:z6Mk". A DID with another multibase prefix or another
+// codec (x25519-pub, secp256k1-pub, ...) is refused with ZK_ERR_BAD_DID
+// rather than read as something it is not.
+//
+// Every C argument that takes an issuer public key as hex also takes its
+// did:key, told apart by the "did:key:" prefix (public_key_bytes). A
+// credential whose issuer field is a did:key must name the key that signs
+// it: signing refuses another key, and verification fails under one
+// (VerifiableCredential::issuer_matches). Other issuer names are free text
+// as before.
+
+use ed25519_dalek::{VerifyingKey, PUBLIC_KEY_LENGTH};
+use std::os::raw::{c_char, c_int};
+
+use crate::admission::text_arg;
+use crate::error::{ZK_ERR_BAD_DID, ZK_ERR_BAD_HEX};
+use crate::ffi::write_cstr;
+use crate::last_error;
+use crate::{bytes_to_hex, hex_to_bytes, VerifiableCredential};
+
+/// Prefix of every did:key identifier
+pub const DID_KEY_PREFIX: &str = "did:key:";
+
+/// Multibase prefix of base58btc
+const MULTIBASE_BASE58BTC: char = 'z';
+
+/// Multicodec of an Ed25519 public key (0xed as an unsigned varint)
+const ED25519_PUB_CODEC: [u8; 2] = [0xed, 0x01];
+
+/// Longest did:key of an Ed25519 key (34 bytes take at most 47 base58 digits)
+pub const MAX_DID_KEY_LEN: usize = DID_KEY_PREFIX.len() + 1 + 47;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Reason a did:key was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DidError {
+    /// Not "did:key:" followed by base58btc multibase text
+    Multibase,
+    /// The multicodec prefix is not ed25519-pub
+    Multicodec,
+    /// Not 32 key bytes after the prefix, or not an Ed25519 point
+    Key,
+}
+
+impl DidError {
+    pub fn code(self) -> c_int {
+        ZK_ERR_BAD_DID
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            DidError::Multibase => "did:key is not base58btc multibase",
+            DidError::Multicodec => "did:key multicodec is not ed25519-pub",
+            DidError::Key => "did:key does not hold a 32-byte Ed25519 key",
+        }
+    }
+}
+
+/// Reason a hex or did:key public key argument was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyTextError {
+    Hex,
+    Did(DidError),
+}
+
+impl KeyTextError {
+    /// ZK_ERR_BAD_DID for a did:key, else `hex_code`, the code the caller
+    /// returns for bad hex
+    pub fn code_or(self, hex_code: c_int) -> c_int {
+        match self {
+            KeyTextError::Hex => hex_code,
+            KeyTextError::Did(e) => e.code(),
+        }
+    }
+}
+
+/// The did:key of an Ed25519 public key
+pub fn to_did_key(key: &VerifyingKey) -> String {
+    let mut multicodec = ED25519_PUB_CODEC.to_vec();
+    multicodec.extend_from_slice(key.as_bytes());
+    format!("{DID_KEY_PREFIX}{MULTIBASE_BASE58BTC}{}", base58_encode(&multicodec))
+}
+
+/// The Ed25519 public key a did:key names
+pub fn from_did_key(did: &str) -> Result<VerifyingKey, DidError> {
+    let encoded = did
+        .strip_prefix(DID_KEY_PREFIX)
+        .and_then(|multibase| multibase.strip_prefix(MULTIBASE_BASE58BTC))
+        .filter(|_| did.len() <= MAX_DID_KEY_LEN)
+        .ok_or(DidError::Multibase)?;
+    let multicodec = base58_decode(encoded).ok_or(DidError::Multibase)?;
+    let key = multicodec.strip_prefix(&ED25519_PUB_CODEC[..]).ok_or(DidError::Multicodec)?;
+    let key: &[u8; PUBLIC_KEY_LENGTH] = key.try_into().map_err(|_| DidError::Key)?;
+    VerifyingKey::from_bytes(key).map_err(|_| DidError::Key)
+}
+
+/// Public key bytes of an argument that is hex or, by its prefix, a did:key
+///
+/// Hex is decoded as it always was, whatever its length; a did:key must
+/// name a valid Ed25519 key.
+pub(crate) fn public_key_bytes(text: &str) -> Result<Vec<u8>, KeyTextError> {
+    if text.starts_with(DID_KEY_PREFIX) {
+        return from_did_key(text).map(|key| key.to_bytes().to_vec()).map_err(KeyTextError::Did);
+    }
+    hex_to_bytes(text).map_err(|_| KeyTextError::Hex)
+}
+
+/// Whether an issuer field allows signatures by `key`: false only for a
+/// did:key naming another key, or a malformed one
+pub fn issuer_allows(issuer: &[u8], key: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+    if !issuer.starts_with(DID_KEY_PREFIX.as_bytes()) {
+        return true;
+    }
+    std::str::from_utf8(issuer).is_ok_and(|did| from_did_key(did).is_ok_and(|named| named.as_bytes() == key))
+}
+
+impl VerifiableCredential {
+    /// Whether the issuer field allows signatures by `key` (issuer_allows)
+    pub fn issuer_matches(&self, key: &VerifyingKey) -> bool {
+        issuer_allows(self.issuer.as_bytes(), key.as_bytes())
+    }
+}
+
+fn base58_encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // Base-58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let ones = std::iter::repeat_n('1', zeros);
+    ones.chain(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize] as char)).collect()
+}
+
+fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+    // Bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len() * 733 / 1000 + 1);
+    for c in text.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut out = vec![0; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write the did:key of a public key given as hex (or as a did:key, which
+/// is checked and written back as it is)
+///
+/// Returns 0 on success, ZK_ERR_BAD_DID for a malformed did:key,
+/// ZK_ERR_BAD_HEX if the key is not a 32-byte hex Ed25519 key,
+/// ZK_ERR_BUFFER_TOO_SMALL if `did_out` cannot hold it (MAX_DID_KEY_LEN + 1
+/// bytes always can).
+#[no_mangle]
+pub extern "C" fn ZK_PublicKeyToDidKey(
+    public_key: *const c_char,
+    did_out: *mut c_char,
+    did_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    let key = match public_key_arg(public_key) {
+        Ok(key) => key,
+        Err(code) => return code,
+    };
+    match write_cstr(did_out, did_out_size, &to_did_key(&key)) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("did_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the hex public key (64 characters) a did:key names
+///
+/// Returns 0 on success, ZK_ERR_BAD_DID if `did` is not an Ed25519 did:key
+/// (wrong multibase, wrong multicodec or not a valid key; ZK_GetLastError
+/// says which), ZK_ERR_BUFFER_TOO_SMALL if `public_key_out` is shorter than
+/// 65 bytes.
+#[no_mangle]
+pub extern "C" fn ZK_DidKeyToPublicKey(
+    did: *const c_char,
+    public_key_out: *mut c_char,
+    public_key_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    let key = match text_arg(did, MAX_DID_KEY_LEN + 1).map(from_did_key) {
+        Some(Ok(key)) => key,
+        Some(Err(e)) => return last_error::fail(e.code(), e.describe()),
+        None => return last_error::fail(ZK_ERR_BAD_DID, "did is NULL, too long or not UTF-8"),
+    };
+    match write_cstr(public_key_out, public_key_out_size, &bytes_to_hex(key.as_bytes())) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("public_key_out", e),
+    }
+}
+}
+
+/// Read a hex or did:key public key argument, leaving the reason for a
+/// refusal in the last error
+pub(crate) fn public_key_arg(public_key: *const c_char) -> Result<VerifyingKey, c_int> {
+    let text = text_arg(public_key, 2 * PUBLIC_KEY_LENGTH)
+        .ok_or_else(|| last_error::fail(-1, "public key is NULL, too long or not UTF-8"))?;
+    let bytes = public_key_bytes(text).map_err(|e| match e {
+        KeyTextError::Did(e) => last_error::fail(e.code(), e.describe()),
+        KeyTextError::Hex => last_error::fail(ZK_ERR_BAD_HEX, "public key is not hex"),
+    })?;
+    <[u8; PUBLIC_KEY_LENGTH]>::try_from(bytes.as_slice())
+        .ok()
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| last_error::fail(ZK_ERR_BAD_HEX, "public key is not a 32-byte Ed25519 key"))
+}
@@ -0,0 +1,711 @@
+// ============================================================================
+// Selective Claim Disclosure
+// ============================================================================
+//
+// A holder reveals one claim of a credential, e.g. role = "engineer", and
+// nothing else about it: not the other claims, how many there are, or where
+// the disclosed one sits among them.
+//
+// The claims form a binary Merkle tree of fixed depth CLAIM_TREE_DEPTH over
+// MiMC (mimc.rs), leaves in credential order, padded with empty leaves:
+//
+//   key    = SHA-256("zkid:claim-key" | key) mod r
+//   value  = the value itself if it is a canonical decimal u64, its field
+//            negation -n if it is a canonical negative decimal i64 "-n", else
+//            2^64 + SHA-256("zkid:claim-value" | value) truncated to 248 bits
+//   leaf   = MiMC(key, value)            (an empty leaf is 0)
+//   node   = MiMC(left, right)
+//
+// Numeric values stay numbers in the tree so that circuits can compare
+// them, negative ones (dates before 1970) as signed field elements just
+// below the modulus; text values land above 2^64 and never collide with
+// either. The fixed depth keeps one circuit for
+// every claim count and hides the count; a credential of more than
+// MAX_COMMITTED_CLAIMS claims has no root. Tree values cross the C API as
+// hex of their 32-byte little-endian encoding.
+//
+// From blob version 6 on the issuer signs the root: the message hash ends in
+// "zkid:claims-root" | root (see VerifiableCredential::message_hash). Older
+// blobs, and the field-based signing entry points, do not commit to it, so
+// their claims cannot be disclosed one at a time. The anchor of a credential
+// (validity.rs) commits to the root as well, which is what circuits open.
+//
+// The claim-disclosure circuit opens the signed anchor to its private
+// fields, the claims root among them, as the VC circuit does, checks that
+// the credential is active at current_time, and recomputes the root from
+// the disclosed claim along a private authentication path, the siblings
+// and the leaf's position bits, in about 7,000 constraints. The proof
+// carries the VC proof header, and the verifier checks the issuer's
+// signature over its anchor before the proof, so a root, claim or date the
+// issuer never signed does not verify, whatever a modified prover skipped.
+// The root is public too and travels with the proof. Being signed, root and
+// anchor are the same in every disclosure from one credential, which
+// verifiers can therefore link, as they can by credential id. The time is
+// the one the proof carries; that it is recent is the caller's business,
+// as for presentations.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, claims_root, claim_key, claim_value.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::PreparedVerifyingKey;
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::OnceLock;
+
+use crate::credential::parse_vc_blob;
+use crate::encoding::{PointEncoding, ZkDeserialize, ZkSerialize};
+use crate::error::ZK_ERR_INPUT_TOO_LARGE;
+use crate::ffi::write_cstr;
+use crate::mimc;
+use crate::bytes_to_hex;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::admission,
+    crate::ffi::MAX_FIELD_LEN,
+    crate::proof,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::checked_blob,
+    crate::error::{ZK_ERR_NOT_INITIALIZED, ZK_ERR_UNSUPPORTED_VERSION},
+    crate::schedule::witness,
+    crate::{enforce_active, entropy, prove_with_key, seeded_rng, Anchored, AnchorWitness, OpenedAnchor},
+    ark_groth16::ProvingKey,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable},
+    ark_relations::{lc, ns},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::did,
+    crate::hex_to_bytes,
+    crate::sizes::CLAIM_DISCLOSURE_PUBLIC_INPUTS,
+    crate::validity,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const KEY_DOMAIN: &[u8] = b"zkid:claim-key";
+const VALUE_DOMAIN: &[u8] = b"zkid:claim-value";
+
+/// Levels between a leaf and the root of the claims tree
+pub const CLAIM_TREE_DEPTH: usize = 6;
+
+/// Most claims a credential can commit to in its claims tree
+pub const MAX_COMMITTED_CLAIMS: usize = 1 << CLAIM_TREE_DEPTH;
+
+/// Circuit id of the claim-disclosure circuit
+pub const CLAIM_DISCLOSURE_CIRCUIT: &str = "zkid-vc/claim-disclosure/v2";
+
+/// Field element of a claim key
+pub fn claim_key_field(key: &str) -> Fr {
+    Fr::from_le_bytes_mod_order(&Sha256::new().chain_update(KEY_DOMAIN).chain_update(key).finalize())
+}
+
+/// Claim value as a number: canonical decimal (no sign, no leading zero)
+/// fitting in a u64
+pub fn numeric_value(value: &str) -> Option<u64> {
+    let canonical = !value.is_empty()
+        && value.bytes().all(|b| b.is_ascii_digit())
+        && (value == "0" || !value.starts_with('0'));
+    canonical.then(|| value.parse().ok()).flatten()
+}
+
+/// Claim value as a signed number: a numeric value fitting in an i64, or
+/// "-" and a canonical decimal other than "0" down to i64::MIN
+pub fn signed_value(value: &str) -> Option<i64> {
+    match value.strip_prefix('-') {
+        Some(magnitude) => numeric_value(magnitude).filter(|&n| n != 0).and_then(|_| value.parse().ok()),
+        None => numeric_value(value).and_then(|n| i64::try_from(n).ok()),
+    }
+}
+
+/// Field element of a signed integer
+pub fn signed_field(value: i64) -> Fr {
+    let magnitude = Fr::from(value.unsigned_abs());
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Field element of a claim value
+pub fn claim_value_field(value: &str) -> Fr {
+    match (numeric_value(value), signed_value(value)) {
+        (Some(number), _) => Fr::from(number),
+        (None, Some(negative)) => signed_field(negative),
+        (None, None) => {
+            let digest = Sha256::new().chain_update(VALUE_DOMAIN).chain_update(value).finalize();
+            Fr::from(1u128 << 64) + Fr::from_le_bytes_mod_order(&digest[..31])
+        }
+    }
+}
+
+/// Leaf of a claim
+pub fn claim_leaf(key: &str, value: &str) -> Fr {
+    mimc::hash(&[claim_key_field(key), claim_value_field(value)])
+}
+
+fn node(left: Fr, right: Fr) -> Fr {
+    mimc::hash(&[left, right])
+}
+
+/// Root of the empty subtree at each height, leaves (0) to the root
+fn empty_subtrees() -> &'static [Fr; CLAIM_TREE_DEPTH + 1] {
+    static EMPTY: OnceLock<[Fr; CLAIM_TREE_DEPTH + 1]> = OnceLock::new();
+    EMPTY.get_or_init(|| {
+        let mut empty = [Fr::zero(); CLAIM_TREE_DEPTH + 1];
+        for height in 1..=CLAIM_TREE_DEPTH {
+            empty[height] = node(empty[height - 1], empty[height - 1]);
+        }
+        empty
+    })
+}
+
+/// 32-byte little-endian encoding of a tree value
+pub fn field_bytes(value: &Fr) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&value.zk_to_bytes(PointEncoding::Compressed));
+    out
+}
+
+/// Tree value from its 32-byte encoding; None unless canonical
+pub fn field_from_bytes(bytes: &[u8]) -> Option<Fr> {
+    Fr::zk_from_bytes(bytes, PointEncoding::Compressed)
+}
+
+/// Merkle tree over a credential's claims
+#[derive(Clone, Debug)]
+pub struct ClaimsTree {
+    // levels[0] holds the leaves, levels[CLAIM_TREE_DEPTH] the root; nodes
+    // over empty leaves only are left out
+    levels: Vec<Vec<Fr>>,
+}
+
+impl ClaimsTree {
+    /// The tree of `claims`; None for more than MAX_COMMITTED_CLAIMS
+    pub fn new(claims: &[(String, String)]) -> Option<Self> {
+        if claims.len() > MAX_COMMITTED_CLAIMS {
+            return None;
+        }
+        let empty = empty_subtrees();
+        let mut levels = vec![claims.iter().map(|(key, value)| claim_leaf(key, value)).collect::<Vec<_>>()];
+        for height in 0..CLAIM_TREE_DEPTH {
+            let above = levels[height]
+                .chunks(2)
+                .map(|pair| node(pair[0], pair.get(1).copied().unwrap_or(empty[height])))
+                .collect();
+            levels.push(above);
+        }
+        Some(Self { levels })
+    }
+
+    pub fn root(&self) -> Fr {
+        self.levels[CLAIM_TREE_DEPTH]
+            .first()
+            .copied()
+            .unwrap_or(empty_subtrees()[CLAIM_TREE_DEPTH])
+    }
+
+    /// Siblings of leaf `index` from the leaf up; None past the last claim
+    pub fn path(&self, index: usize) -> Option<Vec<Fr>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let empty = empty_subtrees();
+        Some(
+            (0..CLAIM_TREE_DEPTH)
+                .map(|height| self.levels[height].get((index >> height) ^ 1).copied().unwrap_or(empty[height]))
+                .collect(),
+        )
+    }
+}
+
+/// Root reached from `leaf` at `index` along `siblings`, as the circuit
+/// computes it
+pub fn root_from_path(leaf: Fr, index: usize, siblings: &[Fr]) -> Fr {
+    siblings.iter().enumerate().fold(leaf, |current, (height, &sibling)| {
+        if (index >> height) & 1 == 1 {
+            node(sibling, current)
+        } else {
+            node(current, sibling)
+        }
+    })
+}
+
+// ============================================================================
+// ZK Circuit: Claim Under the Signed Claims Root
+// ============================================================================
+
+/// Authentication path of one leaf: the private witness of every circuit
+/// over the claims tree
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+pub(crate) struct ClaimPath {
+    siblings: Vec<Option<Fr>>,
+    // true where the running node is the right child
+    position: Vec<Option<bool>>,
+}
+
+#[cfg(feature = "prover")]
+impl ClaimPath {
+    pub(crate) fn blank() -> Self {
+        Self { siblings: vec![None; CLAIM_TREE_DEPTH], position: vec![None; CLAIM_TREE_DEPTH] }
+    }
+
+    /// Path of leaf `index`; None past the last claim
+    pub(crate) fn of(tree: &ClaimsTree, index: usize) -> Option<Self> {
+        Some(Self {
+            siblings: tree.path(index)?.into_iter().map(Some).collect(),
+            position: (0..CLAIM_TREE_DEPTH).map(|height| Some((index >> height) & 1 == 1)).collect(),
+        })
+    }
+}
+
+/// Constrain the leaf of `claim` (key and value) to lie under `claims_root`
+/// along `path`
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_membership(
+    cs: &ConstraintSystemRef<Fr>,
+    claim: [(LinearCombination<Fr>, Option<Fr>); 2],
+    path: &ClaimPath,
+    claims_root: Variable,
+) -> Result<(), SynthesisError> {
+    let current = enforce_path(cs, mimc::hash_gadget(cs, &claim)?, &path.siblings, &path.position)?;
+    let _ns = ns!(cs, "claims_root_binding");
+    cs.enforce_constraint(current.0 - claims_root, lc!() + Variable::One, lc!())
+}
+
+/// Constrain the public claims root to the one the anchor opens to
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_root_opening(
+    cs: &ConstraintSystemRef<Fr>,
+    claims_root: Variable,
+    anchor: &OpenedAnchor,
+) -> Result<(), SynthesisError> {
+    let _ns = ns!(cs, "claims_root_opening");
+    cs.enforce_constraint(lc!() + claims_root - anchor.claims_root.0, lc!() + Variable::One, lc!())
+}
+
+/// Node reached from `leaf` along private `siblings` and position bits
+/// (true where the running node is the right child), as root_from_path
+/// computes it
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_path(
+    cs: &ConstraintSystemRef<Fr>,
+    leaf: (LinearCombination<Fr>, Option<Fr>),
+    siblings: &[Option<Fr>],
+    position: &[Option<bool>],
+) -> Result<(LinearCombination<Fr>, Option<Fr>), SynthesisError> {
+    let mut current = leaf;
+    for (sibling, is_right) in siblings.iter().zip(position) {
+        let sibling_var = witness(cs, *sibling)?;
+        let bit_value = is_right.map(Fr::from);
+        let bit = witness(cs, bit_value)?;
+
+        // swap = bit * (sibling - current) exchanges the two children
+        let swap_value = bit_value.zip(*sibling).zip(current.1).map(|((b, s), c)| b * (s - c));
+        let swap = witness(cs, swap_value)?;
+        {
+            let _ns = ns!(cs, "path_position");
+            cs.enforce_constraint(lc!() + bit, lc!() + Variable::One - bit, lc!())?;
+            cs.enforce_constraint(lc!() + bit, lc!() + sibling_var - &current.0, lc!() + swap)?;
+        }
+
+        let left = (current.0 + swap, current.1.zip(swap_value).map(|(c, t)| c + t));
+        let right = (lc!() + sibling_var - swap, sibling.zip(swap_value).map(|(s, t)| s - t));
+        current = mimc::hash_gadget(cs, &[left, right])?;
+    }
+    Ok(current)
+}
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+pub(crate) struct ClaimDisclosureCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+
+    // 公开输入（锚点之后）
+    claims_root: Option<Fr>,
+    claim_key: Option<Fr>,
+    claim_value: Option<Fr>,
+}
+
+#[cfg(feature = "prover")]
+impl ClaimDisclosureCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            path: ClaimPath::blank(),
+            claims_root: None,
+            claim_key: None,
+            claim_value: None,
+        }
+    }
+
+    /// The circuit with its full witness, the claim at leaf `index` of
+    /// `tree` under the credential's anchor; None past the last claim
+    pub(crate) fn witness(
+        anchor: AnchorWitness,
+        tree: &ClaimsTree,
+        index: usize,
+        (key, value): (&str, &str),
+    ) -> Option<Self> {
+        Some(Self {
+            anchor,
+            path: ClaimPath::of(tree, index)?,
+            claims_root: Some(tree.root()),
+            claim_key: Some(claim_key_field(key)),
+            claim_value: Some(claim_value_field(value)),
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for ClaimDisclosureCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+
+        let input = |value: Option<Fr>| cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing));
+        let claims_root_var = input(self.claims_root)?;
+        let claim_key_var = input(self.claim_key)?;
+        let claim_value_var = input(self.claim_value)?;
+
+        enforce_root_opening(&cs, claims_root_var, &opened)?;
+        enforce_membership(
+            &cs,
+            [(lc!() + claim_key_var, self.claim_key), (lc!() + claim_value_var, self.claim_value)],
+            &self.path,
+            claims_root_var,
+        )
+    }
+}
+
+/// Keys of the claim-disclosure circuit; the verifying key of a
+/// verifier-only build arrives by import
+#[derive(Default)]
+struct ClaimDisclosureKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static CLAIM_DISCLOSURE_KEYS: Mutex<ClaimDisclosureKeys> = Mutex::new(ClaimDisclosureKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the claim-disclosure circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(9u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(ClaimDisclosureCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match CLAIM_DISCLOSURE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the claim-disclosure verifying key is present
+pub(crate) fn has_keys() -> bool {
+    CLAIM_DISCLOSURE_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the claim-disclosure circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = CLAIM_DISCLOSURE_KEYS.lock() {
+        *keys = ClaimDisclosureKeys::default();
+    }
+}
+
+#[cfg(feature = "verifier")]
+fn verify_claim_disclosure(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claims_root: *const c_char,
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    nonce: u64,
+) -> c_int {
+    let text = |ptr: *const c_char| admission::text_arg(ptr, MAX_FIELD_LEN);
+    let issuer_bytes = match text(issuer_pubkey).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return e.code_or(0),
+        None => return 0,
+    };
+    let (root, key, value) = match (
+        text(claims_root).and_then(|s| hex_to_bytes(s).ok()).and_then(|bytes| field_from_bytes(&bytes)),
+        text(claim_key),
+        text(claim_value),
+    ) {
+        (Some(root), Some(key), Some(value)) => (root, key, value),
+        _ => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs at the time the proof carries, then root and claim
+    // (circuit order); None unless the issuer signed the anchor
+    let mut public_inputs =
+        match validity::public_inputs(&issuer_bytes, format, nonce, header.current_time, &header) {
+            Some(inputs) => inputs.to_vec(),
+            None => return 0,
+        };
+    public_inputs.extend_from_slice(&[root, claim_key_field(key), claim_value_field(value)]);
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Compute the claims root of a VC blob (hex of its 32-byte encoding)
+///
+/// Any blob version has a root; only from version 6 on does the issuer's
+/// signature cover it. Returns 0 on success, ZK_ERR_INPUT_TOO_LARGE for
+/// more than MAX_COMMITTED_CLAIMS claims, ZK_ERR_BUFFER_TOO_SMALL for a
+/// short buffer, -1 on a malformed blob.
+#[no_mangle]
+pub extern "C" fn ZK_ComputeClaimsRoot(vc_blob: *const c_char, root_out: *mut c_char, root_out_size: usize) -> c_int {
+    if vc_blob.is_null() {
+        return -1;
+    }
+    let vc = match parse_vc_blob(vc_blob) {
+        Some(vc) => vc,
+        None => return -1,
+    };
+    let root = match vc.claims_root() {
+        Some(root) => root,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+
+    match write_cstr(root_out, root_out_size, &bytes_to_hex(&root)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Prove that `claim_key` = `claim_value` is a claim of an issuer-signed
+/// credential, disclosing neither its other claims nor their number
+///
+/// Runs the ZK_GenerateVCProofFromBlob pre-checks first. The authentication
+/// path comes from the credential itself; the claims root it leads to is
+/// written to `claims_root_out` and goes to the verifier with the proof.
+/// Returns 0 on success, the pre-check's code for a credential that would
+/// not verify, ZK_ERR_UNSUPPORTED_VERSION for a blob before version 6 (its
+/// signature does not cover the root), ZK_ERR_INPUT_TOO_LARGE for more than
+/// MAX_COMMITTED_CLAIMS claims, ZK_ERR_NOT_INITIALIZED,
+/// ZK_ERR_PROVE_FAILED, or -1 if the credential has no such claim.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateClaimDisclosureProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    claims_root_out: *mut c_char,
+    claims_root_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() || claims_root_out.is_null() {
+        return -1;
+    }
+    let (key, value) = match (
+        admission::text_arg(claim_key, MAX_FIELD_LEN),
+        admission::text_arg(claim_value, MAX_FIELD_LEN),
+    ) {
+        (Some(key), Some(value)) => (key, value),
+        _ => return -1,
+    };
+
+    let (vc, issuer_key, now) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+    if !vc.claims_root_signed {
+        return ZK_ERR_UNSUPPORTED_VERSION;
+    }
+    let tree = match ClaimsTree::new(&vc.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+    let index = match vc.claims.iter().position(|(k, v)| k == key && v == value) {
+        Some(index) => index,
+        None => return -1,
+    };
+
+    let (signature, opening) = match (vc.anchored_signature(), vc.opening()) {
+        (Ok(signature), Some(opening)) => (signature, opening),
+        (Err(code), _) => return code,
+        (_, None) => return -1,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), now, Fr::from(nonce)).and_then(
+        |(anchor, header)| Some((ClaimDisclosureCircuit::witness(anchor, &tree, index, (key, value))?, header)),
+    );
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match CLAIM_DISCLOSURE_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    if let Err(e) = write_cstr(proof_out, proof_out_size, &proof_hex) {
+        return e.code();
+    }
+    match write_cstr(claims_root_out, claims_root_out_size, &bytes_to_hex(&field_bytes(&tree.root()))) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify that `claim_key` = `claim_value` is a claim under `claims_root`
+/// of a credential signed by `issuer_pubkey`
+///
+/// Returns 1 if the proof discloses exactly that claim for `nonce`, 0
+/// otherwise (also for another value, another root or a non-canonical
+/// root encoding), ZK_ERR_DISABLED for a legacy-format proof in a strict
+/// build, ZK_ERR_UNSUPPORTED_VERSION for a format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyClaimDisclosureProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claims_root: *const c_char,
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = CLAIM_DISCLOSURE_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let arguments = [proof_hex, issuer_pubkey, claims_root, claim_key, claim_value];
+    let result = match pvk {
+        Some(pvk) if arguments.iter().all(|ptr| !ptr.is_null()) => {
+            verify_claim_disclosure(pvk, proof_hex, issuer_pubkey, claims_root, claim_key, claim_value, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "claim-disclosure-proof",
+        code: result,
+        circuit: Some(CLAIM_DISCLOSURE_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the claim-disclosure verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportClaimDisclosureVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match CLAIM_DISCLOSURE_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportClaimDisclosureVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportClaimDisclosureVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == CLAIM_DISCLOSURE_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match CLAIM_DISCLOSURE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
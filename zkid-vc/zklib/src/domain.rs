@@ -0,0 +1,86 @@
+// ============================================================================
+// Hash Domains
+// ============================================================================
+//
+// The credential message hash and the field mapping behind every public
+// input are plain SHA-256, so a digest made for one purpose is also a valid
+// digest for the other: a credential message hash fed to the field mapping
+// looks like any other mapped value. Format version 2 prefixes each with a
+// tag naming its purpose and a version, so a change to either is visible in
+// the tag:
+//
+//   credential message hash   "zkid.vc.msg.v1" | fields ...   (hash format 3)
+//   field mapping             SHA-256("zkid.fr.map.v1" | data) mod r
+//                                                              (proof format 3)
+//
+// Both break existing signatures and proofs, so version 1 (untagged) stays
+// the default and ZK_SetFormatVersion opts in. The setting picks what new
+// artifacts use; existing ones keep verifying because they name their
+// layout themselves: VC blobs from version 8 on carry the tag, proofs name
+// their format in the envelope. The field-based signature entry points
+// (ZK_SignVC*, ZK_VerifyVCSignature*, ZK_ComputeVCHash*) carry no version
+// and follow the setting on both sides. zkid-acl tags its public IDs the
+// same way under its own ZK_SetFormatVersion.
+
+#[cfg(feature = "std")]
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Untagged hashes, as before domain separation (the default)
+pub const ZK_FORMAT_VERSION_1: u32 = 1;
+/// Purpose tags on the credential message hash and the field mapping
+pub const ZK_FORMAT_VERSION_2: u32 = 2;
+
+/// Prefix of a tagged credential message hash
+#[cfg(feature = "std")]
+pub(crate) const VC_MESSAGE_TAG: &[u8] = b"zkid.vc.msg.v1";
+/// Prefix of the tagged field mapping (proof format 3)
+pub(crate) const FIELD_MAP_TAG: &[u8] = b"zkid.fr.map.v1";
+/// Usage context of credential signatures (usage.rs)
+pub const CREDENTIAL_CONTEXT: &[u8] = b"zkid:usage:credential";
+/// Tag of a credential anchor after the credential context (validity.rs)
+pub const VC_ANCHOR_TAG: &[u8] = b"zkid.vc.anchor.v1";
+/// Tag of a claim-group anchor after the claim-group context (composite.rs)
+pub const GROUP_ANCHOR_TAG: &[u8] = b"zkid.group.anchor.v1";
+
+static VERSION: AtomicU32 = AtomicU32::new(ZK_FORMAT_VERSION_1);
+
+/// Format version new artifacts are made in
+pub fn format_version() -> u32 {
+    VERSION.load(Ordering::Relaxed)
+}
+
+/// Whether new credential message hashes carry VC_MESSAGE_TAG
+pub fn tagged() -> bool {
+    format_version() == ZK_FORMAT_VERSION_2
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Choose untagged (ZK_FORMAT_VERSION_1) or tagged (_2) hashes for new
+/// signatures, message hashes and proofs
+///
+/// Also sets the emitted proof format, to 3 for version 2 and to 2 for
+/// version 1 (ZK_SetEmitFormatVersion can override it afterwards). Verifiers
+/// of proofs and VC blobs need no setting; verifiers of field-based
+/// signatures must use the signer's. Returns 0 on success, -1 for an
+/// unknown version.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_SetFormatVersion(version: u32) -> c_int {
+    if !matches!(version, ZK_FORMAT_VERSION_1 | ZK_FORMAT_VERSION_2) {
+        return -1;
+    }
+    VERSION.store(version, Ordering::Relaxed);
+    #[cfg(feature = "prover")]
+    crate::proof::set_emit_format(if version == ZK_FORMAT_VERSION_2 {
+        crate::proof::PROOF_FORMAT_TAGGED
+    } else {
+        crate::proof::PROOF_FORMAT_V2
+    });
+    0
+}
+}
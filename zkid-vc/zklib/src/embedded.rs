@@ -0,0 +1,225 @@
+// ============================================================================
+// Embedded Verifier
+// ============================================================================
+//
+// Verify-only C API for microcontroller builds (no_std + alloc):
+//
+//   cargo rustc --release --lib --crate-type staticlib \
+//       --no-default-features --features embedded --target <mcu target>
+//
+// Everything std-only is compiled out: there are no hex inputs, no env
+// variables (rayon), no audit log (it timestamps with Instant) and no
+// revocation or clock checks. The reader only answers "is this presentation a
+// valid proof for its issuer key, nonce and time", the circuit having shown
+// that the credential's dates cover that time; freshness of the nonce and
+// the time are the caller's business.
+//
+// Inputs are bounded by EMBEDDED_MAX_VK_LEN and EMBEDDED_MAX_PRESENTATION_LEN
+// and parsed in place; presentation metadata is checked for well-formedness
+// but never copied. The key is held behind a spin lock.
+//
+// Without std the crate provides its own allocator: a first-fit heap over a
+// static arena of EMBEDDED_HEAP_SIZE bytes. ZK_InitVerifierEmbedded plus one
+// ZK_VerifyPresentationEmbedded peak at about 110 KiB; check-embedded.sh
+// holds them to a 128 KiB budget. The loaded key keeps about 50 KiB. Arena
+// and stack together leave most of a 512 KiB part to the firmware.
+
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, PreparedVerifyingKey};
+use ark_snark::SNARK;
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+
+use crate::ffi::read_bytes;
+use crate::proof;
+use crate::validity;
+use crate::vk::{self, VkError};
+use crate::wire::{Reader, PRESENTATION_MAGIC, PRESENTATION_VERSION};
+
+/// Longest raw verifying key accepted (the VC key is 392 bytes)
+pub const EMBEDDED_MAX_VK_LEN: usize = 1024;
+
+/// Longest binary presentation accepted, metadata included
+pub const EMBEDDED_MAX_PRESENTATION_LEN: usize = 4096;
+
+/// Size of the static heap arena in no_std builds
+pub const EMBEDDED_HEAP_SIZE: usize = 192 * 1024;
+
+static VERIFYING_KEY: Mutex<Option<PreparedVerifyingKey<Bn254>>> = Mutex::new(None);
+
+/// Bytes currently allocated and the high-water mark, kept by the no_std
+/// allocator (both stay 0 when the host supplies the allocator)
+static HEAP_USED: AtomicUsize = AtomicUsize::new(0);
+static HEAP_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Check a presentation's encoding and verify its proof
+fn verify_presentation(pvk: &PreparedVerifyingKey<Bn254>, data: &[u8]) -> Option<bool> {
+    let mut r = Reader::new(data);
+    if r.take(PRESENTATION_MAGIC.len())? != PRESENTATION_MAGIC || r.u8()? != PRESENTATION_VERSION {
+        return None;
+    }
+
+    let (header, format, proof) = validity::decode(r.bytes()?).ok()?;
+    let issuer_pubkey = r.bytes()?;
+    let nonce = r.u64()?;
+
+    for _ in 0..r.u32()? {
+        core::str::from_utf8(r.bytes()?).ok()?;
+        core::str::from_utf8(r.bytes()?).ok()?;
+    }
+    if !r.is_empty() {
+        return None;
+    }
+
+    proof::field_for(format, issuer_pubkey)?;
+    // An anchor the issuer did not sign is an invalid proof, not a
+    // malformed one
+    let public_inputs = match validity::public_inputs(issuer_pubkey, format, nonce, header.current_time, &header) {
+        Some(inputs) => inputs,
+        None => return Some(false),
+    };
+    Some(matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    ))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Load the verifying key (raw compressed form, as produced by
+/// ZK_ExportVerifyingKey after hex decoding)
+///
+/// Replaces any key loaded before. Returns 0 on success, ZK_ERR_CORRUPT if
+/// the key does not decode, -1 on NULL input or a key longer than
+/// EMBEDDED_MAX_VK_LEN.
+#[no_mangle]
+pub extern "C" fn ZK_InitVerifierEmbedded(vk_bytes: *const u8, vk_len: usize) -> c_int {
+    let data = match read_bytes(vk_bytes, vk_len, EMBEDDED_MAX_VK_LEN) {
+        Ok(data) if !data.is_empty() => data,
+        Ok(_) => return -1,
+        Err(e) => return e.code(),
+    };
+    let raw = match vk::decode_raw(data) {
+        Ok(raw) => raw,
+        Err(e) => return e.code(),
+    };
+    if vk::public_inputs(&raw) != 4 {
+        return VkError::Corrupt.code();
+    }
+
+    *VERIFYING_KEY.lock() = Some(PreparedVerifyingKey::from(raw));
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a binary presentation (see presentation.rs for the encoding)
+///
+/// Only the proof is checked, at the time it carries; freshness of that time
+/// and the nonce, and revocation, are up to the caller. Returns 1 if valid,
+/// 0 if invalid or malformed, -1 if no key is loaded, on NULL input or a
+/// blob longer than EMBEDDED_MAX_PRESENTATION_LEN.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyPresentationEmbedded(blob: *const u8, blob_len: usize) -> c_int {
+    let data = match read_bytes(blob, blob_len, EMBEDDED_MAX_PRESENTATION_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+
+    let guard = VERIFYING_KEY.lock();
+    let pvk = match guard.as_ref() {
+        Some(pvk) => pvk,
+        None => return -1,
+    };
+    match verify_presentation(pvk, data) {
+        Some(true) => 1,
+        _ => 0,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read the embedded heap usage in bytes
+///
+/// `peak_out` is the high-water mark since start-up or the last call with
+/// `reset` non-zero. Either output may be NULL. Both read 0 in builds where
+/// the host supplies the allocator. Returns 0.
+#[no_mangle]
+pub extern "C" fn ZK_EmbeddedHeapStats(used_out: *mut usize, peak_out: *mut usize, reset: u8) -> c_int {
+    let used = HEAP_USED.load(Ordering::Relaxed);
+    let peak = if reset != 0 {
+        HEAP_PEAK.swap(used, Ordering::Relaxed)
+    } else {
+        HEAP_PEAK.load(Ordering::Relaxed)
+    };
+
+    for (out, value) in [(used_out, used), (peak_out, peak)] {
+        if !out.is_null() {
+            unsafe {
+                *out = value;
+            }
+        }
+    }
+    0
+}
+}
+
+// ============================================================================
+// no_std Runtime
+// ============================================================================
+
+#[cfg(all(not(feature = "std"), not(test)))]
+mod runtime {
+    use core::alloc::{GlobalAlloc, Layout};
+    use core::ptr::{self, NonNull};
+    use core::sync::atomic::Ordering;
+    use linked_list_allocator::Heap;
+    use spin::Mutex;
+
+    use super::{EMBEDDED_HEAP_SIZE, HEAP_PEAK, HEAP_USED};
+
+    static mut ARENA: [u8; EMBEDDED_HEAP_SIZE] = [0; EMBEDDED_HEAP_SIZE];
+
+    /// First-fit heap over ARENA, set up on first use
+    struct ArenaAllocator(Mutex<Option<Heap>>);
+
+    unsafe impl GlobalAlloc for ArenaAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let mut guard = self.0.lock();
+            let heap = guard.get_or_insert_with(|| {
+                Heap::new(ptr::addr_of_mut!(ARENA) as *mut u8, EMBEDDED_HEAP_SIZE)
+            });
+            match heap.allocate_first_fit(layout) {
+                Ok(block) => {
+                    let used = HEAP_USED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+                    HEAP_PEAK.fetch_max(used, Ordering::Relaxed);
+                    block.as_ptr()
+                }
+                Err(()) => ptr::null_mut(),
+            }
+        }
+
+        unsafe fn dealloc(&self, block: *mut u8, layout: Layout) {
+            if let (Some(heap), Some(block)) = (self.0.lock().as_mut(), NonNull::new(block)) {
+                heap.deallocate(block, layout);
+                HEAP_USED.fetch_sub(layout.size(), Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: ArenaAllocator = ArenaAllocator(Mutex::new(None));
+
+    /// A panic leaves the verifier state unknown; halt and let the watchdog
+    /// reset the part
+    #[panic_handler]
+    fn panic(_: &core::panic::PanicInfo) -> ! {
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+}
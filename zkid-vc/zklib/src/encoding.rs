@@ -0,0 +1,202 @@
+// ============================================================================
+// Frozen Encodings
+// ============================================================================
+//
+// Stored proofs and verifying keys outlive any one arkworks release, so
+// their byte layouts are ours and written down here. The library encodes and
+// decodes them only through ZkSerialize / ZkDeserialize. Both are implemented
+// with ark-serialize 0.4 today, and the layouts below are the ones it
+// produced when they were frozen. Integers are little-endian:
+//
+//   field element  32 bytes, canonical (below the modulus)
+//   G1 point       compressed: x; uncompressed: x | y
+//   G2 point       compressed: x.c0 | x.c1; uncompressed: x.c0 | x.c1
+//                  | y.c0 | y.c1
+//   Proof          A (G1) | B (G2) | C (G1)
+//   VerifyingKey   alpha (G1) | beta (G2) | gamma (G2) | delta (G2)
+//                  | count u64 | gamma_abc (G1)*      always compressed
+//
+// A point's flags sit in the top two bits of its last coordinate byte:
+// 0x80 if y is the larger of y and -y, 0x40 for the point at infinity
+// (all coordinates zero). Decoding checks canonical coordinates, the curve
+// and the subgroup.
+//
+// The "encoding" conformance vectors pin these layouts byte for byte. An
+// arkworks upgrade that changes them fails the vectors instead of silently
+// breaking stored artifacts; the old layout is then decoded in
+// ZK_ConvertLegacyArtifact and the traits are reimplemented for the new
+// one. No conversion exists yet: every artifact written so far is in the
+// layouts above.
+//
+// Prepared verifying keys, proving keys and checkpoint progress keep
+// arkworks' native encoding. A prepared key is checked against its raw key
+// on import and can be derived again from it, and the other two never
+// outlive the build that wrote them.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use alloc::vec::Vec;
+use core::ffi::c_int;
+
+#[cfg(feature = "std")]
+use {
+    crate::error::ZK_ERR_CORRUPT,
+    crate::proof::{self, ProofDecodeError},
+    crate::validity,
+};
+
+/// Artifact kinds for ZK_ConvertLegacyArtifact
+pub const ZK_ARTIFACT_PROOF: c_int = 1;
+pub const ZK_ARTIFACT_VERIFYING_KEY: c_int = 2;
+pub const ZK_ARTIFACT_FIELD_ELEMENT: c_int = 3;
+
+/// Length of an encoded field element
+pub const FIELD_ELEMENT_LEN: usize = 32;
+
+/// How curve points are written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointEncoding {
+    Compressed,
+    Uncompressed,
+}
+
+impl PointEncoding {
+    fn mode(self) -> Compress {
+        match self {
+            PointEncoding::Compressed => Compress::Yes,
+            PointEncoding::Uncompressed => Compress::No,
+        }
+    }
+}
+
+/// A value with a frozen byte encoding
+pub trait ZkSerialize {
+    /// Append the encoding of `self`
+    fn zk_serialize(&self, encoding: PointEncoding, out: &mut Vec<u8>);
+
+    /// Length of the encoding of `self`
+    fn zk_serialized_size(&self, encoding: PointEncoding) -> usize;
+
+    /// The encoding of `self` on its own
+    fn zk_to_bytes(&self, encoding: PointEncoding) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.zk_serialized_size(encoding));
+        self.zk_serialize(encoding, &mut out);
+        out
+    }
+}
+
+/// A value decodable from its frozen byte encoding
+pub trait ZkDeserialize: Sized {
+    /// Decode a value from the front of `reader`, advancing it
+    fn zk_deserialize(reader: &mut &[u8], encoding: PointEncoding) -> Option<Self>;
+
+    /// Decode a value that takes up all of `bytes`
+    fn zk_from_bytes(bytes: &[u8], encoding: PointEncoding) -> Option<Self> {
+        let mut reader = bytes;
+        let value = Self::zk_deserialize(&mut reader, encoding)?;
+        reader.is_empty().then_some(value)
+    }
+}
+
+// Implemented through ark-serialize, whose 0.4 layouts are the frozen ones
+macro_rules! frozen_via_arkworks {
+    ($ty:ty, $encoding:ident => $mode:expr) => {
+        impl ZkSerialize for $ty {
+            fn zk_serialize(&self, $encoding: PointEncoding, out: &mut Vec<u8>) {
+                self.serialize_with_mode(out, $mode)
+                    .expect("serializing into a Vec cannot fail");
+            }
+
+            fn zk_serialized_size(&self, $encoding: PointEncoding) -> usize {
+                self.serialized_size($mode)
+            }
+        }
+
+        impl ZkDeserialize for $ty {
+            fn zk_deserialize(reader: &mut &[u8], $encoding: PointEncoding) -> Option<Self> {
+                <$ty>::deserialize_with_mode(reader, $mode, Validate::Yes).ok()
+            }
+        }
+    };
+}
+
+// A field element has no points; the encoding argument is ignored
+frozen_via_arkworks!(Fr, _encoding => Compress::Yes);
+frozen_via_arkworks!(Proof<Bn254>, encoding => encoding.mode());
+// Verifying keys are only ever written compressed
+frozen_via_arkworks!(VerifyingKey<Bn254>, _encoding => Compress::Yes);
+
+/// Re-encode an artifact of `kind` in the current layouts
+///
+/// The migration hook for a future layout change; today every known
+/// artifact is already current and is only checked and normalized.
+#[cfg(feature = "std")]
+fn convert(kind: c_int, bytes: &[u8]) -> Result<Vec<u8>, c_int> {
+    match kind {
+        ZK_ARTIFACT_PROOF => {
+            // A VC proof keeps its validity prefix
+            let (header, bytes) = match validity::untag(bytes) {
+                Some((header, inner)) => (Some(header), inner),
+                None => (None, bytes),
+            };
+            let (_, encoding, _) = proof::split(bytes).ok_or(ZK_ERR_CORRUPT)?;
+            let (format, decoded) = proof::decode(bytes).map_err(|e| match e {
+                ProofDecodeError::Malformed => ZK_ERR_CORRUPT,
+                e => e.code(),
+            })?;
+            let converted = proof::encode_with(format, &decoded, encoding);
+            Ok(match header {
+                Some(header) => header.tag(&converted),
+                None => converted,
+            })
+        }
+        ZK_ARTIFACT_VERIFYING_KEY => VerifyingKey::<Bn254>::zk_from_bytes(bytes, PointEncoding::Compressed)
+            .map(|vk| vk.zk_to_bytes(PointEncoding::Compressed))
+            .ok_or(ZK_ERR_CORRUPT),
+        ZK_ARTIFACT_FIELD_ELEMENT => Fr::zk_from_bytes(bytes, PointEncoding::Compressed)
+            .map(|field| field.zk_to_bytes(PointEncoding::Compressed))
+            .ok_or(ZK_ERR_CORRUPT),
+        _ => Err(-1),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Convert a stored binary artifact to the current encoding
+///
+/// `kind` is ZK_ARTIFACT_PROOF (either wire form, a VC proof's validity
+/// prefix included; format and point encoding are kept),
+/// ZK_ARTIFACT_VERIFYING_KEY (raw, compressed) or
+/// ZK_ARTIFACT_FIELD_ELEMENT. No encoding has been retired yet, so a
+/// current artifact comes back unchanged; the call is where artifacts in a
+/// retired layout will be converted. The output length is written to
+/// `len_out` even when the buffer is too small. Returns 0 on success,
+/// ZK_ERR_CORRUPT if the input is not an artifact of `kind` in any known
+/// layout, ZK_ERR_DISABLED for a proof format compiled out of this build,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL or an unknown kind.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_ConvertLegacyArtifact(
+    kind: c_int,
+    artifact: *const u8,
+    artifact_len: usize,
+    out: *mut u8,
+    out_size: usize,
+    len_out: *mut usize,
+) -> c_int {
+    use crate::ffi::{read_bytes, MAX_BLOB_LEN};
+
+    let data = match read_bytes(artifact, artifact_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    match convert(kind, data) {
+        Ok(bytes) => crate::vk::write_out(&bytes, out, out_size, len_out),
+        Err(code) => code,
+    }
+}
+}
@@ -0,0 +1,134 @@
+// ============================================================================
+// Entropy Source
+// ============================================================================
+//
+// Secret key material, proving randomness and the coefficients of batch
+// verification are drawn through `fill()`:
+// from the operating system by default, or from a callback installed with
+// ZK_SetEntropySource for enclave builds without an OS generator (hardware
+// TRNG relayed by the host). A failing source fails the call; there is no
+// fallback to a fixed seed. Verifier-only builds keep the source for
+// batch verification; the proving generators below need the prover.
+//
+// Proofs never take their blinding factors from the nonce or any other
+// public input, which would let anyone recompute the proof. Test runs that
+// need reproducible proofs opt in with ZK_SetDeterministicProving (standard
+// builds only); each proof's generator is then seeded from that seed and
+// the nonce.
+
+use std::os::raw::{c_int, c_void};
+use crate::sync::Mutex;
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "prover")]
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use rand_core::{OsRng, RngCore};
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+use sha2::{Digest, Sha256};
+
+use crate::error::ZK_ERR_ENTROPY;
+
+/// Fills `len` bytes at `buf` with random bytes; returns 0 on success
+pub type EntropyCallback = extern "C" fn(buf: *mut u8, len: usize, user_data: *mut c_void) -> c_int;
+
+struct EntropySource {
+    callback: Option<(EntropyCallback, *mut c_void)>,
+}
+
+// The host guarantees the callback and user_data may be used from any thread
+unsafe impl Send for EntropySource {}
+
+static ENTROPY_SOURCE: Mutex<EntropySource> = Mutex::new(EntropySource { callback: None });
+
+/// Seed set with ZK_SetDeterministicProving; 0 proves with fresh entropy
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+static DETERMINISTIC_PROVING: AtomicU64 = AtomicU64::new(0);
+
+/// Fill `buf` from the installed callback, or the OS generator without one
+///
+/// Fails with ZK_ERR_ENTROPY if the source reports an error.
+pub fn fill(buf: &mut [u8]) -> Result<(), c_int> {
+    let callback = ENTROPY_SOURCE.lock().unwrap_or_else(|e| e.into_inner()).callback;
+
+    match callback {
+        Some((callback, user_data)) => match callback(buf.as_mut_ptr(), buf.len(), user_data) {
+            0 => Ok(()),
+            _ => Err(ZK_ERR_ENTROPY),
+        },
+        None => OsRng.try_fill_bytes(buf).map_err(|_| ZK_ERR_ENTROPY),
+    }
+}
+
+/// Generator seeded from the entropy source, ignoring deterministic proving
+#[cfg(feature = "prover")]
+pub fn fresh_rng() -> Result<StdRng, c_int> {
+    let mut seed = zeroize::Zeroizing::new([0u8; 32]);
+    fill(seed.as_mut())?;
+    Ok(StdRng::from_seed(*seed))
+}
+
+/// Generator for the blinding factors of one proof for `nonce`
+///
+/// Fresh entropy, unless deterministic proving is on. Fails with
+/// ZK_ERR_ENTROPY if the source fails.
+#[cfg(feature = "prover")]
+pub fn prover_rng(nonce: u64) -> Result<StdRng, c_int> {
+    #[cfg(not(feature = "strict"))]
+    if let Some(seed) = deterministic_proving() {
+        return Ok(derived_rng(seed, nonce));
+    }
+    #[cfg(feature = "strict")]
+    let _ = nonce;
+
+    fresh_rng()
+}
+
+/// Generator for one deterministic proof: SHA-256 of `seed` and `nonce`
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+pub fn derived_rng(seed: u64, nonce: u64) -> StdRng {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    hasher.update(nonce.to_le_bytes());
+    StdRng::from_seed(hasher.finalize().into())
+}
+
+/// Seed of deterministic proving, if it is on
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+pub fn deterministic_proving() -> Option<u64> {
+    match DETERMINISTIC_PROVING.load(Ordering::Relaxed) {
+        0 => None,
+        seed => Some(seed),
+    }
+}
+#[cfg(all(feature = "prover", feature = "strict"))]
+pub fn deterministic_proving() -> Option<u64> {
+    None
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Install (or remove with NULL, returning to the OS generator) the entropy
+/// callback used for issuer key generation, proving and batch verification
+#[no_mangle]
+pub extern "C" fn ZK_SetEntropySource(callback: Option<EntropyCallback>, user_data: *mut c_void) -> c_int {
+    let mut source = ENTROPY_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    source.callback = callback.map(|callback| (callback, user_data));
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Derive every proof's randomness from `seed` and the nonce, so repeated
+/// test runs produce byte-identical proofs; a seed of 0 returns to fresh
+/// entropy. Not in strict builds. Returns 0.
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+#[no_mangle]
+pub extern "C" fn ZK_SetDeterministicProving(seed: u64) -> c_int {
+    DETERMINISTIC_PROVING.store(seed, Ordering::Relaxed);
+    0
+}
+}
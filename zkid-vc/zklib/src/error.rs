@@ -0,0 +1,141 @@
+// ============================================================================
+// C API Error Codes
+// ============================================================================
+//
+// 0 means success and -1 a generic failure; the negative codes below identify
+// failures the caller is expected to handle differently.
+
+use core::ffi::c_int;
+
+/// Not a failure: the output pointer was NULL (or its size 0), so no
+/// result was computed and the required buffer size went to `required_size_out`
+pub const ZK_STATUS_SIZE_QUERY: c_int = 1;
+
+/// The keys the call needs were not set up: ZK_Init (or the circuit's
+/// import) has not run, or ZK_Cleanup released them
+pub const ZK_ERR_NOT_INITIALIZED: c_int = -2;
+
+/// The credential's issuer signature does not verify under the given key
+pub const ZK_ERR_BAD_SIGNATURE: c_int = -3;
+
+/// The credential is not valid at the given time: expired beyond any grace,
+/// not yet valid, or outside its validity schedule
+pub const ZK_ERR_VC_EXPIRED: c_int = -4;
+
+/// The output buffer cannot hold the result; strings need one byte for the NUL
+pub const ZK_ERR_BUFFER_TOO_SMALL: c_int = -5;
+
+/// A hex argument is not valid hex or has the wrong length for its key or
+/// signature
+pub const ZK_ERR_BAD_HEX: c_int = -6;
+
+/// The prover failed to produce a proof for an accepted witness
+pub const ZK_ERR_PROVE_FAILED: c_int = -7;
+
+/// Only the verifying key is loaded (ZK_InitVerifierOnly, or an imported
+/// key without ZK_Init), so this process cannot prove
+pub const ZK_ERR_NO_PROVING_KEY: c_int = -8;
+
+/// The storage backend failed; the operation was not applied
+pub const ZK_ERR_STORAGE: c_int = -10;
+
+/// The trusted time source and the caller-supplied time disagree
+pub const ZK_ERR_TIME_MISMATCH: c_int = -11;
+
+/// A dual-control credential is missing its second issuer signature
+pub const ZK_ERR_COSIGNATURE_MISSING: c_int = -12;
+
+/// Authentication failed: wrong key or passphrase, or tampered ciphertext
+pub const ZK_ERR_AUTH: c_int = -13;
+
+/// Input is truncated, structurally invalid or of an unknown version
+pub const ZK_ERR_CORRUPT: c_int = -14;
+
+/// The handle is unknown, already released or has timed out
+pub const ZK_ERR_UNKNOWN_HANDLE: c_int = -15;
+
+/// The requested code path is compiled out of this (strict) build
+pub const ZK_ERR_DISABLED: c_int = -16;
+
+/// A prepared verifying key does not correspond to its embedded raw key
+pub const ZK_ERR_KEY_MISMATCH: c_int = -17;
+
+/// The proof's format version is not accepted by this verifier
+pub const ZK_ERR_UNSUPPORTED_VERSION: c_int = -18;
+
+/// The witness violates a circuit constraint (feature "debug-circuit")
+pub const ZK_ERR_UNSATISFIED: c_int = -19;
+
+/// The proof or key is for circuit parameters this side has not registered
+pub const ZK_ERR_CIRCUIT_MISMATCH: c_int = -20;
+
+/// The collection is at its capacity and its eviction policy refuses inserts
+pub const ZK_ERR_CAPACITY: c_int = -21;
+
+/// The prover saw this (audience, nonce) pair recently and its policy
+/// refuses, or threshold signing nonces were already used
+pub const ZK_ERR_NONCE_REUSED: c_int = -22;
+
+/// The two sides share no profile, or a profile does not fit this build
+pub const ZK_ERR_INCOMPATIBLE: c_int = -23;
+
+/// The nullifier was already recorded in its scope, e.g. a second ballot
+pub const ZK_ERR_NULLIFIER_USED: c_int = -24;
+
+/// Relay frames are incomplete: an index of the message is absent
+pub const ZK_ERR_FRAME_MISSING: c_int = -25;
+
+/// Relay frames repeat an index of the message
+pub const ZK_ERR_FRAME_DUPLICATE: c_int = -26;
+
+/// The presentation hides its credential schema or discloses one outside
+/// the verifier's allowlist
+pub const ZK_ERR_SCHEMA_NOT_ALLOWED: c_int = -27;
+
+/// A time-limited request (e.g. a handoff) is past its expiry, or a
+/// snapshot is not newer than one already applied
+pub const ZK_ERR_EXPIRED: c_int = -28;
+
+/// An input is longer than any valid value for its argument; refused before
+/// it was read in full
+pub const ZK_ERR_INPUT_TOO_LARGE: c_int = -30;
+
+/// A field element in an encoded point is not below the field modulus, or
+/// its flag bits are invalid
+pub const ZK_ERR_NON_CANONICAL: c_int = -31;
+
+/// An encoded point is not on the curve or not in the prime-order subgroup
+pub const ZK_ERR_INVALID_POINT: c_int = -32;
+
+/// A threshold participant sent an invalid package or signature share; the
+/// call names it
+pub const ZK_ERR_MISBEHAVING: c_int = -33;
+
+/// The entropy source (OS generator or ZK_SetEntropySource callback) failed
+/// to produce random bytes; no key was generated
+pub const ZK_ERR_ENTROPY: c_int = -34;
+
+/// The credential is in the revocation tree, or a non-revocation witness
+/// does not show it absent under the given root
+pub const ZK_ERR_REVOKED: c_int = -35;
+
+/// The library panicked inside the call; the panic was caught at the C
+/// boundary and ZK_GetLastError names it. Later calls keep working
+pub const ZK_ERR_PANIC: c_int = -36;
+
+/// The credential token is not valid yet: the current time is before its
+/// nbf (ZK_VerifyVCJwt)
+pub const ZK_ERR_NOT_YET_VALID: c_int = -37;
+
+/// A did:key argument is not base58btc multibase, its multicodec is not
+/// ed25519-pub, or it does not hold a valid Ed25519 key
+pub const ZK_ERR_BAD_DID: c_int = -38;
+
+/// A key file is damaged or truncated (its checksum does not match), of an
+/// unknown version or another circuit, or holds keys that do not decode or
+/// belong together (ZK_LoadKeys)
+pub const ZK_ERR_KEYFILE_INVALID: c_int = -39;
+
+/// Issuer sequencing (ZK_SetIssuerSequencing) is on and this signer cannot
+/// set the `zkid:sequence` claim in what it signs: sign a blob instead
+pub const ZK_ERR_SEQUENCE_UNSUPPORTED: c_int = -40;
@@ -0,0 +1,133 @@
+// ============================================================================
+// Raw Pointer Helpers
+// ============================================================================
+//
+// Every C entry point reads caller memory through read_bytes / read_slice and
+// writes strings through write_cstr (binary output goes through
+// vk::write_out), so the null, length and NUL-terminator checks live in one
+// place.
+//
+// An output string needs room for its bytes plus the NUL: a buffer of exactly
+// s.len() bytes is too small and reports ZK_ERR_BUFFER_TOO_SMALL without
+// writing anything.
+
+use crate::error::{ZK_ERR_BUFFER_TOO_SMALL, ZK_STATUS_SIZE_QUERY};
+use core::ffi::{c_char, c_int};
+
+/// Longest identifier-like input (holder id, issuer, scope, context, ...)
+pub(crate) const MAX_FIELD_LEN: usize = 64 * 1024;
+
+/// Longest blob-like input (keys, presentations, sealed boxes, vectors)
+pub(crate) const MAX_BLOB_LEN: usize = 16 * 1024 * 1024;
+
+/// Longest array input (claims)
+pub(crate) const MAX_ARRAY_LEN: usize = 4096;
+
+/// Why caller memory was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FfiError {
+    /// Null pointer with a non-zero length, or a null output buffer
+    Null,
+    /// Input longer than the entry point accepts
+    TooLong,
+    /// Output buffer cannot hold the value (and its NUL for strings)
+    BufferTooSmall,
+    /// Null or empty output buffer with `required_size_out` set: the caller
+    /// only asked for the size
+    SizeQuery,
+}
+
+impl FfiError {
+    /// C API code for the failure
+    pub(crate) fn code(self) -> c_int {
+        match self {
+            FfiError::Null | FfiError::TooLong => -1,
+            FfiError::BufferTooSmall => ZK_ERR_BUFFER_TOO_SMALL,
+            FfiError::SizeQuery => ZK_STATUS_SIZE_QUERY,
+        }
+    }
+}
+
+/// Borrow `len` elements at `ptr`; a null pointer is only accepted for an
+/// empty slice
+pub(crate) fn read_slice<'a, T>(ptr: *const T, len: usize, max: usize) -> Result<&'a [T], FfiError> {
+    if len > max {
+        return Err(FfiError::TooLong);
+    }
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(FfiError::Null);
+    }
+    Ok(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+/// Borrow `len` bytes at `ptr` (u8 or c_char) as bytes
+pub(crate) fn read_bytes<'a, T>(ptr: *const T, len: usize, max: usize) -> Result<&'a [u8], FfiError> {
+    debug_assert_eq!(core::mem::size_of::<T>(), 1);
+    read_slice(ptr as *const u8, len, max)
+}
+
+/// Borrow the NUL-terminated string at `ptr` without its NUL, scanning at
+/// most `max` + 1 bytes, so an oversized argument costs the same as a
+/// string of `max` bytes
+pub(crate) fn read_cstr<'a>(ptr: *const c_char, max: usize) -> Result<&'a [u8], FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::Null);
+    }
+    let bytes = ptr as *const u8;
+    let mut len = 0;
+    while unsafe { *bytes.add(len) } != 0 {
+        if len == max {
+            return Err(FfiError::TooLong);
+        }
+        len += 1;
+    }
+    Ok(unsafe { core::slice::from_raw_parts(bytes, len) })
+}
+
+/// Copy `s` into `buf` as a NUL-terminated string, returning its length
+/// without the NUL
+pub(crate) fn write_cstr(buf: *mut c_char, cap: usize, s: &str) -> Result<usize, FfiError> {
+    if buf.is_null() {
+        return Err(FfiError::Null);
+    }
+    if s.len() >= cap {
+        return Err(FfiError::BufferTooSmall);
+    }
+    unsafe {
+        core::ptr::copy_nonoverlapping(s.as_ptr(), buf as *mut u8, s.len());
+        *buf.add(s.len()) = 0;
+    }
+    Ok(s.len())
+}
+
+/// Check the output string buffer `buf` of `cap` bytes against the
+/// `required` size (NUL included) before the result is computed
+///
+/// `required` is written to `required_size_out` unless it is NULL. A NULL
+/// `buf` or a `cap` of 0 is then a size query; without `required_size_out`
+/// it is refused like any other null or small buffer.
+pub(crate) fn reserve_cstr(
+    buf: *mut c_char,
+    cap: usize,
+    required: usize,
+    required_size_out: *mut usize,
+) -> Result<(), FfiError> {
+    if !required_size_out.is_null() {
+        unsafe {
+            *required_size_out = required;
+        }
+        if buf.is_null() || cap == 0 {
+            return Err(FfiError::SizeQuery);
+        }
+    }
+    if buf.is_null() {
+        return Err(FfiError::Null);
+    }
+    if cap < required {
+        return Err(FfiError::BufferTooSmall);
+    }
+    Ok(())
+}
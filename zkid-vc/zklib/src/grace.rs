@@ -0,0 +1,519 @@
+// ============================================================================
+// Expired-Credential Grace Presentations
+// ============================================================================
+//
+// While a renewal is in progress an operator may rather let a credential
+// that expired moments ago through, marked as degraded, than lock its
+// holder out. Both sides opt in:
+//
+//   prover    ZK_SetProverExpiryGrace(g): ZK_GenerateVCProof proves a
+//             credential that expired at most g seconds ago, as a grace
+//             proof, instead of refusing it in its expiry pre-check
+//   verifier  ZK_VerifyVCProofWithGrace with a ZkGracePolicy whose
+//             allow_expired_within_seconds is a: the time check passes a
+//             credential that expired at most a seconds ago, every other
+//             check runs as usual, and the report says degraded with the
+//             overshoot; admitting a degraded session is the caller's call
+//
+// A grace proof is not a VC proof. The grace circuit opens the signed
+// anchor (validity.rs) as the VC circuit does and enforces
+//
+//   issue_date <= current_time <= expiry_date + grace_seconds
+//
+// over the integers instead of the VC circuit's window: the margins are
+// decomposed into bits as in age.rs, and the dates are the ones the issuer
+// signed, not free witnesses. The proof carries the grace it was made with
+// and the VC proof header in front of the proof wire format:
+//
+//   "ZKGP" | grace_seconds u64 | "ZKVA" | current_time u64 | anchor | signature | proof
+//
+// and the verifier checks the issuer's signature over the anchor before
+// the proof, so an expiry the issuer never signed does not verify, whatever
+// a modified prover skipped. It does not decode as a VC proof, so
+// ZK_VerifyVCProof, presentations and every other verifier reject it;
+// ZK_VerifyVCProofWithGrace accepts it only when the carried grace is
+// within the policy's allowance. The policy's current_time must be the time
+// the prover used, as for schedule proofs. A credential still inside its
+// dates gets a plain VC proof even while a grace is set, and that proof
+// verifies everywhere.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, grace_seconds.
+
+use ark_bn254::Bn254;
+use ark_groth16::PreparedVerifyingKey;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::bytes_to_hex;
+use crate::ffi::write_cstr;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::proof,
+    crate::validity,
+    ark_bn254::Fr,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::error::{ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED},
+    crate::schedule::enforce_bits,
+    crate::entropy,
+    crate::{seeded_rng, Anchored, AnchorWitness},
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    std::sync::atomic::{AtomicU64, Ordering},
+    std::sync::Arc,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission::{self, MAX_PROOF_LEN},
+    crate::audit,
+    crate::credential::{parse_vc_blob, parse_verifying_key},
+    crate::hex_to_bytes,
+    crate::sizes::GRACE_PUBLIC_INPUTS,
+    crate::validation::{validate, CheckOutcome, Policy, ZK_VALIDATE_ALL},
+    crate::validity::VALIDITY_PREFIX_LEN,
+    crate::{clock, verify_vc_proof_decoded, VERIFYING_KEY},
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const GRACE_MAGIC: &[u8; 4] = b"ZKGP";
+
+/// Length of the grace prefix on a grace proof
+pub const GRACE_PREFIX_LEN: usize = GRACE_MAGIC.len() + 8;
+
+/// Longest grace a prover may set or a grace proof may carry, in seconds
+pub const MAX_EXPIRY_GRACE: u64 = 30 * 86_400;
+
+/// Circuit id of the grace circuit
+pub const GRACE_CIRCUIT: &str = "zkid-vc/grace/v2";
+
+/// Bits of current_time - issue_date and expiry_date + grace_seconds -
+/// current_time (below 2^65 + 2^63)
+#[cfg(feature = "prover")]
+const MARGIN_BITS: usize = 66;
+
+/// Grace set with ZK_SetProverExpiryGrace; 0 proves no expired credential
+#[cfg(feature = "prover")]
+static PROVER_GRACE: AtomicU64 = AtomicU64::new(0);
+
+/// The grace ZK_GenerateVCProof applies, in seconds
+#[cfg(feature = "prover")]
+pub fn prover_grace() -> u64 {
+    PROVER_GRACE.load(Ordering::Relaxed)
+}
+
+/// Prefix proof wire bytes with the grace they were proven with
+pub fn tag(grace_seconds: u64, proof: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(GRACE_PREFIX_LEN + proof.len());
+    out.extend_from_slice(GRACE_MAGIC);
+    out.extend_from_slice(&grace_seconds.to_le_bytes());
+    out.extend_from_slice(proof);
+    out
+}
+
+/// Split a grace proof into its grace and proof wire bytes
+///
+/// None for bytes without the prefix or a grace outside
+/// 1..=MAX_EXPIRY_GRACE.
+pub fn untag(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    if !bytes.starts_with(GRACE_MAGIC) {
+        return None;
+    }
+    let grace = u64::from_le_bytes(bytes.get(GRACE_MAGIC.len()..GRACE_PREFIX_LEN)?.try_into().ok()?);
+    (1..=MAX_EXPIRY_GRACE).contains(&grace).then(|| (grace, &bytes[GRACE_PREFIX_LEN..]))
+}
+
+// ============================================================================
+// ZK Circuit: current_time at Most grace_seconds Past the Expiry
+// ============================================================================
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+struct GraceCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 公开输入（锚点之后）
+    grace: Option<u64>,
+}
+
+#[cfg(feature = "prover")]
+impl GraceCircuit {
+    fn blank() -> Self {
+        Self { anchor: AnchorWitness::blank(), grace: None }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for GraceCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        let grace_var = cs.new_input_variable(|| {
+            self.grace.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // current_time - issue_date >= 0 and expiry + grace - current_time >= 0
+        let (time_var, time) = opened.current_time;
+        let time = time.map(i128::from);
+        let elapsed = time.zip(opened.issue_date.1).map(|(t, d)| (t - i128::from(d)) as u128);
+        let margin = opened
+            .expiry_date
+            .1
+            .zip(self.grace)
+            .zip(time)
+            .map(|((e, g), t)| (i128::from(e) + i128::from(g) - t) as u128);
+        enforce_bits(&cs, lc!() + time_var - opened.issue_date.0, elapsed, MARGIN_BITS)?;
+        enforce_bits(&cs, lc!() + opened.expiry_date.0 + grace_var - time_var, margin, MARGIN_BITS)
+    }
+}
+
+/// Keys of the grace circuit; the verifying key of a verifier-only build
+/// arrives by import
+#[derive(Default)]
+struct GraceKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static GRACE_KEYS: Mutex<GraceKeys> = Mutex::new(GraceKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the grace circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(7u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(GraceCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match GRACE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(Arc::new(pk));
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the grace verifying key is present
+pub(crate) fn has_keys() -> bool {
+    GRACE_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the grace circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = GRACE_KEYS.lock() {
+        *keys = GraceKeys::default();
+    }
+}
+
+/// Hex grace proof for an already checked credential that expired at most
+/// prover_grace() seconds before `current_time`
+#[cfg(feature = "prover")]
+pub(crate) fn prove(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+) -> Result<String, c_int> {
+    let grace = prover_grace();
+    let format = proof::emit_format();
+    let witness = AnchorWitness::of(format, credential, issuer_pubkey_bytes, current_time, Fr::from(nonce));
+    let (anchor, header) = match witness {
+        Some(witness) if grace > 0 => witness,
+        _ => return Err(-1),
+    };
+    let circuit = GraceCircuit { anchor, grace: Some(grace) };
+
+    #[cfg(feature = "debug-circuit")]
+    crate::satisfiability::check(circuit.clone())?;
+
+    let pk = GRACE_KEYS.lock().map_err(|_| -1)?.pk.clone().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut entropy::prover_rng(nonce)?).map_err(|_| ZK_ERR_PROVE_FAILED)?;
+    Ok(bytes_to_hex(&tag(grace, &validity::encode(&header, format, &proof))))
+}
+
+/// Verify grace proof wire bytes: 1 valid, 0 invalid
+#[cfg(feature = "verifier")]
+fn verify_grace_proof(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_bytes: &[u8],
+    grace: u64,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let (header, format, proof) = match admission::decode_vc_proof(proof_bytes, Some(proof::accepted_formats())) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs, then the grace; None unless the issuer signed the anchor
+    let public_inputs = match validity::public_inputs(issuer_pubkey_bytes, format, nonce, current_time, &header) {
+        Some([issuer_hash, nonce, time, anchor]) => [issuer_hash, nonce, time, anchor, Fr::from(grace)],
+        None => return 0,
+    };
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+/// Verifier settings of ZK_VerifyVCProofWithGrace (C layout)
+#[cfg(feature = "verifier")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ZkGracePolicy {
+    /// Hex issuer public key the VC and proof must match
+    pub issuer_public_key: *const c_char,
+    /// Hex co-signer key for dual-control schemas, or NULL
+    pub second_issuer_public_key: *const c_char,
+    /// Passed through the configured time source (ZK_SetTimeMode); the time
+    /// the prover used, for VC and grace proofs alike
+    pub current_time: u64,
+    /// Longest time past expiry accepted as degraded, in seconds; 0 accepts
+    /// no expired credential and no grace proof
+    pub allow_expired_within_seconds: u64,
+}
+
+/// Outcome of ZK_VerifyVCProofWithGrace (C layout)
+///
+/// The check fields read as in ZkVerifyReport: 1 passed, 0 failed,
+/// ZK_CHECK_SKIPPED, or a negative error code if undecided.
+#[cfg(feature = "verifier")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkGraceReport {
+    /// The VC's issuer signature(s) verify under the policy's keys
+    pub signature: c_int,
+    /// The VC proof verifies, or the grace proof does with a grace within
+    /// allow_expired_within_seconds
+    pub proof: c_int,
+    /// The VC is inside its dates and validity schedule, or expired at most
+    /// allow_expired_within_seconds ago
+    pub time_policy: c_int,
+    /// The VC is neither revoked nor, with ZK_SetRejectSuperseded, superseded
+    pub revocation: c_int,
+    /// ZK_ERR_COSIGNATURE_MISSING for a dual-control schema without a
+    /// co-signature
+    pub schema: c_int,
+    /// 1 if the time check passed only because of the allowance
+    pub degraded: c_int,
+    /// Seconds past expiry if degraded, otherwise 0
+    pub overshoot: u64,
+    /// Epoch of the published revocation list checked against, 0 if none
+    pub revocation_epoch: u64,
+}
+
+#[cfg(feature = "verifier")]
+impl ZkGraceReport {
+    /// Whether no check failed or was left undecided, degraded or not
+    pub fn checks_passed(&self) -> bool {
+        [self.signature, self.proof, self.time_policy, self.revocation, self.schema]
+            .iter()
+            .all(|&code| code == 1 || code == crate::validation::ZK_CHECK_SKIPPED)
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Let ZK_GenerateVCProof prove credentials that expired at most
+/// `grace_seconds` ago, as grace proofs
+///
+/// 0 (the default) turns the grace off. Only verifiers that opted in with
+/// ZK_VerifyVCProofWithGrace and an allowance of at least `grace_seconds`
+/// accept the resulting proofs. Returns 0 on success, -1 above
+/// MAX_EXPIRY_GRACE.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SetProverExpiryGrace(grace_seconds: u64) -> c_int {
+    if grace_seconds > MAX_EXPIRY_GRACE {
+        return -1;
+    }
+    PROVER_GRACE.store(grace_seconds, Ordering::Relaxed);
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a VC proof or grace proof and the credential it was made for,
+/// accepting a recently expired credential as degraded
+///
+/// `proof_hex` is a proof from ZK_GenerateVCProof, `vc_blob` the presented
+/// VC blob. Every check runs and is reported in `report_out`; an expired
+/// credential inside `policy->allow_expired_within_seconds` passes the time
+/// check with `degraded` set and the overshoot reported; a VC proof passes
+/// the proof check only at the time it was proven for, which its
+/// credential's dates cover. Returns 1 if no
+/// check failed or was undecided, degraded or not, 0 otherwise,
+/// ZK_ERR_DISABLED or ZK_ERR_UNSUPPORTED_VERSION for a proof format refused
+/// by policy, -1 on NULL arguments or a malformed issuer key.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofWithGrace(
+    proof_hex: *const c_char,
+    vc_blob: *const c_char,
+    nonce: u64,
+    policy: *const ZkGracePolicy,
+    report_out: *mut ZkGraceReport,
+) -> c_int {
+    let started = Instant::now();
+    let policy = match unsafe { policy.as_ref() } {
+        Some(policy) if !proof_hex.is_null() && !vc_blob.is_null() && !report_out.is_null() => *policy,
+        _ => return -1,
+    };
+    let issuer_key = match parse_verifying_key(policy.issuer_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let second_key = if policy.second_issuer_public_key.is_null() {
+        None
+    } else {
+        match parse_verifying_key(policy.second_issuer_public_key) {
+            Some(key) => Some(key),
+            None => return -1,
+        }
+    };
+
+    let mut report = match parse_vc_blob(vc_blob) {
+        Some(vc) => {
+            let checks = Policy {
+                allow_expired_within_seconds: policy.allow_expired_within_seconds,
+                ..Policy::global(ZK_VALIDATE_ALL)
+            };
+            let validated = validate(&vc, &[issuer_key], second_key.as_ref(), &checks, policy.current_time);
+            ZkGraceReport {
+                signature: validated.signature.code(),
+                time_policy: validated.time_policy.code(),
+                revocation: validated.revocation.code(),
+                schema: validated.schema.code(),
+                degraded: validated.degraded() as c_int,
+                overshoot: validated.overshoot.unwrap_or(0),
+                revocation_epoch: validated.revocation_epoch,
+                ..ZkGraceReport::default()
+            }
+        }
+        None => ZkGraceReport {
+            signature: CheckOutcome::Failed.code(),
+            time_policy: CheckOutcome::Failed.code(),
+            revocation: CheckOutcome::Failed.code(),
+            schema: CheckOutcome::Failed.code(),
+            ..ZkGraceReport::default()
+        },
+    };
+
+    let grace_keys = GRACE_KEYS.lock().ok();
+    let vc_keys = VERIFYING_KEY.lock().ok();
+    // A grace proof carries the validity prefix of a VC proof after its own
+    let proof_bytes = admission::hex_arg(proof_hex, GRACE_PREFIX_LEN + VALIDITY_PREFIX_LEN + MAX_PROOF_LEN);
+    let grace = proof_bytes.as_deref().ok().and_then(untag);
+    let pvk = match grace {
+        Some(_) => grace_keys.as_ref().and_then(|keys| keys.pvk.as_ref()),
+        None => vc_keys.as_ref().and_then(|guard| guard.as_deref()),
+    };
+
+    report.proof = match (&proof_bytes, grace, pvk) {
+        (Err(rejection), _, _) => rejection.verify_code(),
+        (Ok(_), Some((grace, _)), _) if grace > policy.allow_expired_within_seconds => 0,
+        (Ok(_), Some((grace, inner)), Some(pvk)) => match clock::now(policy.current_time) {
+            Ok(now) => verify_grace_proof(pvk, inner, grace, issuer_key.as_bytes(), now, nonce),
+            Err(code) => code,
+        },
+        (Ok(bytes), None, Some(pvk)) => match admission::decode_vc_proof(bytes, Some(proof::accepted_formats())) {
+            Ok((header, format, proof)) => match clock::now(policy.current_time) {
+                Ok(now) => verify_vc_proof_decoded(pvk, format, &proof, issuer_key.as_bytes(), &header, now, nonce),
+                Err(code) => code,
+            },
+            Err(rejection) => rejection.verify_code(),
+        },
+        (Ok(_), _, None) => 0,
+    };
+    unsafe {
+        *report_out = report;
+    }
+    let result = match report.proof {
+        code @ (crate::error::ZK_ERR_DISABLED | crate::error::ZK_ERR_UNSUPPORTED_VERSION) => code,
+        _ => report.checks_passed() as c_int,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "grace-proof",
+        code: result,
+        circuit: Some(if grace.is_some() { GRACE_CIRCUIT } else { audit::VC_CIRCUIT }),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the grace verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportGraceVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match GRACE_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportGraceVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportGraceVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == GRACE_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match GRACE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,409 @@
+// ============================================================================
+// Cross-Device Presentation Handoff
+// ============================================================================
+//
+// The challenge often arrives on a desktop browser while the credential is
+// on a phone. The desktop turns the verifier's challenge into a handoff
+// request and shows it as a QR code; the phone scans it, proves through the
+// normal presentation path and answers on the return channel; the desktop
+// checks the answer belongs to its own request and forwards the
+// presentation to the verifier.
+//
+//   ZK_CreateHandoffRequest    desktop: request -> "ZKH45:" + base45 text
+//   ZK_DecodeHandoffRequest    phone (and verifier): issuer key, audience,
+//                              the nonce to prove with, times
+//   ZK_CreateHandoffResponse   phone: wraps its presentation for the channel
+//   ZK_AcceptHandoffResponse   desktop: checks the binding and expiry,
+//                              writes the presentation to forward
+//
+// Layouts, integers little-endian, strings length-prefixed with a u32:
+//
+//   request   "ZKHQ" | version u8 | issuer_pubkey [32] | challenge u64
+//             | created_at u64 | expires_at u64 | audience | channel_id
+//   response  "ZKHR" | version u8 | request digest [32] | channel_id
+//             | presentation
+//
+// The digest is SHA-256("zkid:handoff-request" | request). The presentation
+// must be proven for the nonce the request fixes: the first 8 bytes of
+// SHA-256("zkid:handoff-nonce" | request). The nonce is a public input of
+// the proof, so the proof itself commits to the full request, including
+// the verifier's challenge, the audience and the return channel. Moving a
+// response to another channel or request (ZK_ERR_AUTH) would need a new
+// proof. The verifier decodes the forwarded request, checks its challenge
+// and audience there and that the presentation carries the request's
+// nonce.
+//
+// A request lives at most MAX_HANDOFF_TTL seconds. Both the phone and the
+// desktop refuse it after expires_at with ZK_ERR_EXPIRED, using the
+// configured time source (ZK_SetTimeMode).
+
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+
+use crate::admission::text_arg;
+use crate::clock;
+use crate::credential::parse_verifying_key;
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_EXPIRED};
+use crate::ffi::{write_cstr, MAX_BLOB_LEN};
+use crate::presentation::Presentation;
+use crate::relay::{base45_decode, base45_encode};
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, hex_to_bytes};
+#[cfg(feature = "prover")]
+use crate::ffi::read_bytes;
+
+const REQUEST_MAGIC: &[u8; 4] = b"ZKHQ";
+const RESPONSE_MAGIC: &[u8; 4] = b"ZKHR";
+const HANDOFF_VERSION: u8 = 1;
+const REQUEST_DOMAIN: &[u8] = b"zkid:handoff-request";
+const NONCE_DOMAIN: &[u8] = b"zkid:handoff-nonce";
+
+/// Text prefix of a handoff request, for QR alphanumeric mode
+pub const HANDOFF_PREFIX: &str = "ZKH45:";
+
+/// Longest lifetime of a handoff request, in seconds
+pub const MAX_HANDOFF_TTL: u64 = 600;
+
+/// Longest audience or return channel id, in bytes
+pub const MAX_HANDOFF_FIELD_LEN: usize = 256;
+/// Longest request text: two full fields, base45 (3 characters per 2 bytes)
+const MAX_REQUEST_TEXT_LEN: usize =
+    HANDOFF_PREFIX.len() + (61 + 2 * (4 + MAX_HANDOFF_FIELD_LEN)) * 3 / 2 + 2;
+
+/// A desktop's request for a presentation from another device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandoffRequest {
+    pub issuer_pubkey: [u8; 32],
+    /// The verifier's challenge
+    pub challenge: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub audience: String,
+    /// Where the phone sends its response
+    pub channel_id: String,
+}
+
+impl HandoffRequest {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = REQUEST_MAGIC.to_vec();
+        out.push(HANDOFF_VERSION);
+        out.extend_from_slice(&self.issuer_pubkey);
+        out.extend_from_slice(&self.challenge.to_le_bytes());
+        out.extend_from_slice(&self.created_at.to_le_bytes());
+        out.extend_from_slice(&self.expires_at.to_le_bytes());
+        put_bytes(&mut out, self.audience.as_bytes());
+        put_bytes(&mut out, self.channel_id.as_bytes());
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(REQUEST_MAGIC.len())? != REQUEST_MAGIC || r.u8()? != HANDOFF_VERSION {
+            return None;
+        }
+        let request = Self {
+            issuer_pubkey: r.take(32)?.try_into().ok()?,
+            challenge: r.u64()?,
+            created_at: r.u64()?,
+            expires_at: r.u64()?,
+            audience: field(r.bytes()?)?,
+            channel_id: field(r.bytes()?)?,
+        };
+        r.is_empty().then_some(request)
+    }
+
+    /// QR text: the prefix and base45 of the binary request
+    pub fn to_text(&self) -> String {
+        format!("{}{}", HANDOFF_PREFIX, base45_encode(&self.to_bytes()))
+    }
+
+    pub fn from_text(text: &str) -> Option<Self> {
+        Self::from_bytes(&base45_decode(text.trim().strip_prefix(HANDOFF_PREFIX)?)?)
+    }
+
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(REQUEST_DOMAIN);
+        hasher.update(self.to_bytes());
+        hasher.finalize().into()
+    }
+
+    /// The nonce the phone proves with; binds the proof to this request
+    pub fn nonce(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(NONCE_DOMAIN);
+        hasher.update(self.to_bytes());
+        u64::from_le_bytes(hasher.finalize()[..8].try_into().expect("digest is 32 bytes"))
+    }
+
+    /// Whether `presentation` answers this request
+    fn answered_by(&self, presentation: &Presentation) -> bool {
+        presentation.nonce == self.nonce() && presentation.issuer_pubkey == self.issuer_pubkey
+    }
+
+    fn expired(&self, current_time: u64) -> Result<(), c_int> {
+        let now = clock::now(current_time)?;
+        if now > self.expires_at {
+            return Err(ZK_ERR_EXPIRED);
+        }
+        Ok(())
+    }
+}
+
+fn field(bytes: &[u8]) -> Option<String> {
+    if bytes.len() > MAX_HANDOFF_FIELD_LEN {
+        return None;
+    }
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// The phone's answer, routed to the request's return channel
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandoffResponse {
+    pub request_digest: [u8; 32],
+    pub channel_id: String,
+    pub presentation: Vec<u8>,
+}
+
+impl HandoffResponse {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = RESPONSE_MAGIC.to_vec();
+        out.push(HANDOFF_VERSION);
+        out.extend_from_slice(&self.request_digest);
+        put_bytes(&mut out, self.channel_id.as_bytes());
+        put_bytes(&mut out, &self.presentation);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(RESPONSE_MAGIC.len())? != RESPONSE_MAGIC || r.u8()? != HANDOFF_VERSION {
+            return None;
+        }
+        let response = Self {
+            request_digest: r.take(32)?.try_into().ok()?,
+            channel_id: field(r.bytes()?)?,
+            presentation: r.bytes()?.to_vec(),
+        };
+        r.is_empty().then_some(response)
+    }
+}
+
+fn read_request(text: *const c_char) -> Result<HandoffRequest, c_int> {
+    let text = text_arg(text, MAX_REQUEST_TEXT_LEN).ok_or(ZK_ERR_CORRUPT)?;
+    HandoffRequest::from_text(text).ok_or(ZK_ERR_CORRUPT)
+}
+
+fn read_field(ptr: *const c_char) -> Option<String> {
+    text_arg(ptr, MAX_HANDOFF_FIELD_LEN).map(str::to_string)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+/// Timing fields of a handoff request (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkHandoffInfo {
+    /// The verifier's challenge the request carries
+    pub challenge: u64,
+    /// The nonce to prove with (see handoff.rs)
+    pub nonce: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+crate::unwind::entry_point! {
+/// Create a handoff request as QR text ("ZKH45:" + base45)
+///
+/// `issuer_pubkey` is the hex issuer key the verifier expects,
+/// `challenge` the verifier's challenge, `channel_id` where the phone
+/// answers; `audience` may be empty. The request expires `ttl` seconds
+/// after `created_at` (1 to MAX_HANDOFF_TTL). Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL or malformed input.
+#[no_mangle]
+pub extern "C" fn ZK_CreateHandoffRequest(
+    issuer_pubkey: *const c_char,
+    challenge: u64,
+    audience: *const c_char,
+    channel_id: *const c_char,
+    created_at: u64,
+    ttl: u64,
+    request_out: *mut c_char,
+    request_out_size: usize,
+) -> c_int {
+    let issuer = match parse_verifying_key(issuer_pubkey) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let (audience, channel_id) = match (read_field(audience), read_field(channel_id)) {
+        (Some(audience), Some(channel_id)) if !channel_id.is_empty() => (audience, channel_id),
+        _ => return -1,
+    };
+    if ttl == 0 || ttl > MAX_HANDOFF_TTL {
+        return -1;
+    }
+
+    let request = HandoffRequest {
+        issuer_pubkey: issuer.to_bytes(),
+        challenge,
+        created_at,
+        expires_at: created_at.saturating_add(ttl),
+        audience,
+        channel_id,
+    };
+    match write_cstr(request_out, request_out_size, &request.to_text()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read a handoff request: what to prove for, and with which nonce
+///
+/// Writes the hex issuer key, the audience and the timing fields. The
+/// holder proves with `info_out->nonce` and `created_at` as the proving
+/// time, and adds the audience as presentation metadata. Returns 0 on
+/// success, ZK_ERR_CORRUPT if the text is not a handoff request,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_DecodeHandoffRequest(
+    request: *const c_char,
+    issuer_pubkey_out: *mut c_char,
+    issuer_pubkey_out_size: usize,
+    audience_out: *mut c_char,
+    audience_out_size: usize,
+    info_out: *mut ZkHandoffInfo,
+) -> c_int {
+    if request.is_null() || info_out.is_null() {
+        return -1;
+    }
+    let request = match read_request(request) {
+        Ok(request) => request,
+        Err(code) => return code,
+    };
+
+    if let Err(e) = write_cstr(issuer_pubkey_out, issuer_pubkey_out_size, &bytes_to_hex(&request.issuer_pubkey)) {
+        return e.code();
+    }
+    if let Err(e) = write_cstr(audience_out, audience_out_size, &request.audience) {
+        return e.code();
+    }
+    unsafe {
+        *info_out = ZkHandoffInfo {
+            challenge: request.challenge,
+            nonce: request.nonce(),
+            created_at: request.created_at,
+            expires_at: request.expires_at,
+        };
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Wrap a presentation answering a handoff request for its return channel
+///
+/// `presentation_blob` (binary or hex text) must be proven with the
+/// request's nonce for its issuer key. Writes the response as hex. Returns
+/// 0 on success, ZK_ERR_EXPIRED if the request has expired, ZK_ERR_AUTH if
+/// the presentation does not answer this request, ZK_ERR_CORRUPT if either
+/// input does not decode, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_CreateHandoffResponse(
+    request: *const c_char,
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    current_time: u64,
+    response_out: *mut c_char,
+    response_out_size: usize,
+) -> c_int {
+    if request.is_null() || presentation_blob.is_null() {
+        return -1;
+    }
+    let request = match read_request(request) {
+        Ok(request) => request,
+        Err(code) => return code,
+    };
+    let presentation = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => match Presentation::from_wire(bytes) {
+            Some(presentation) => presentation,
+            None => return ZK_ERR_CORRUPT,
+        },
+        Err(e) => return e.code(),
+    };
+    if let Err(code) = request.expired(current_time) {
+        return code;
+    }
+    if !request.answered_by(&presentation) {
+        return ZK_ERR_AUTH;
+    }
+
+    let response = HandoffResponse {
+        request_digest: request.digest(),
+        channel_id: request.channel_id.clone(),
+        presentation: presentation.to_bytes(),
+    };
+    match write_cstr(response_out, response_out_size, &bytes_to_hex(&response.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check a handoff response against the request it should answer
+///
+/// `request` is the QR text this side created, `response` the hex the
+/// return channel delivered. On success writes the hex presentation to
+/// forward to the verifier (together with the request) and returns 0.
+/// Returns ZK_ERR_EXPIRED if the request has expired, ZK_ERR_AUTH if the
+/// response was made for another request or channel, or its presentation
+/// was not proven for this request's nonce and issuer, ZK_ERR_CORRUPT if
+/// either input does not decode, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL. The
+/// proof itself is the verifier's to check.
+#[no_mangle]
+pub extern "C" fn ZK_AcceptHandoffResponse(
+    request: *const c_char,
+    response: *const c_char,
+    current_time: u64,
+    presentation_out: *mut c_char,
+    presentation_out_size: usize,
+) -> c_int {
+    if request.is_null() || response.is_null() {
+        return -1;
+    }
+    let request = match read_request(request) {
+        Ok(request) => request,
+        Err(code) => return code,
+    };
+    let response = match text_arg(response, 2 * MAX_BLOB_LEN)
+        .and_then(|hex| hex_to_bytes(hex).ok())
+        .and_then(|bytes| HandoffResponse::from_bytes(&bytes))
+    {
+        Some(response) => response,
+        None => return ZK_ERR_CORRUPT,
+    };
+    let presentation = match Presentation::from_bytes(&response.presentation) {
+        Some(presentation) => presentation,
+        None => return ZK_ERR_CORRUPT,
+    };
+    if let Err(code) = request.expired(current_time) {
+        return code;
+    }
+    if response.request_digest != request.digest()
+        || response.channel_id != request.channel_id
+        || !request.answered_by(&presentation)
+    {
+        return ZK_ERR_AUTH;
+    }
+
+    match write_cstr(presentation_out, presentation_out_size, &bytes_to_hex(&response.presentation)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,454 @@
+// ============================================================================
+// Holder-Binding Secret (VC proofs only the holder can make)
+// ============================================================================
+//
+// A VC proof shows knowledge of an issuer-signed credential, so anyone who
+// obtains the fields and signature can prove it. A bound credential is
+// signed to a holder keypair instead: the holder keeps a 32-byte secret s
+// and hands the issuer its public key
+//
+//   holder_key = MiMC(s)
+//
+// (s read as a little-endian integer mod r, the key 32 bytes
+// little-endian). The issuer signs it into the credential as the
+// `zkid:holder-binding` claim (hex), and the key, read back from the claim
+// (`claimed_key`), is an input of the anchor the issuer signs
+// (validity.rs). The bound-VC circuit is the VC circuit with s as a further
+// private witness and its key in the anchor:
+//
+//   anchor = MiMC(vc_hash, issue_date, expiry_date, MiMC(s), blinding,
+//                 claims_root)
+//
+// with the same public inputs and validity window. Every verifier checks
+// the issuer's signature over the anchor, so only the secret behind the
+// signed key opens it, whatever a modified prover skips. The prover checks
+// that signature too, so a wrong secret fails there, before any proving.
+//
+// Bound proofs carry the VC header and verify under their own keys with
+// ZK_VerifyVCProofBound, so a verifier that requires binding does not
+// accept an unbound proof, and the unbound VC entry points keep working for
+// credentials without the claim. The fields entry point cannot prove a
+// bound credential unbound: its signature covers the claim. About 330
+// constraints more than the VC circuit.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::PreparedVerifyingKey;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::admission::text_arg;
+use crate::ballot::HOLDER_SECRET_LEN;
+use crate::disclosure::{field_bytes, field_from_bytes};
+use crate::ffi::write_cstr;
+use crate::{bytes_to_hex, hex_to_bytes, mimc};
+#[cfg(feature = "prover")]
+use {
+    crate::dates,
+    crate::did,
+    crate::entropy,
+    crate::error::{ZK_ERR_BAD_HEX, ZK_ERR_NOT_INITIALIZED},
+    crate::ffi::{read_bytes, reserve_cstr, MAX_FIELD_LEN},
+    crate::{
+        generate_vc_proof, last_error, prove_with_key, seeded_rng, sizes, vc_circuit, Anchored, ProofCredential,
+        VCCircuit, ZK_SignVCWithClaims,
+    },
+    ark_groth16::{Groth16, ProvingKey},
+    ark_snark::SNARK,
+    ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
+    std::ffi::CString,
+    std::sync::Arc,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::{audit, proof, verify_vc_proof_hex},
+    crate::sizes::BOUND_VC_PUBLIC_INPUTS,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+/// Reserved claim carrying the holder public key (hex)
+pub const HOLDER_BINDING_CLAIM: &str = "zkid:holder-binding";
+
+/// Circuit id of the bound-VC circuit
+pub const BOUND_VC_CIRCUIT: &str = "zkid-vc/bound/v4";
+
+/// Field element of a holder secret
+pub fn secret_field(secret: &[u8; HOLDER_SECRET_LEN]) -> Fr {
+    Fr::from_le_bytes_mod_order(secret)
+}
+
+/// Holder public key of a secret's field element
+pub fn public_key(secret: &Fr) -> Fr {
+    mimc::hash(&[*secret])
+}
+
+/// The claim a credential bound to `secret`'s public key is signed with
+pub fn claims(secret: &Fr) -> Vec<(String, String)> {
+    vec![(HOLDER_BINDING_CLAIM.to_string(), bytes_to_hex(&field_bytes(&public_key(secret))))]
+}
+
+/// Holder key a credential's claims bind it to: the `zkid:holder-binding`
+/// claim's, 0 without one; None for a repeated, malformed or zero claim,
+/// which no anchor can be signed for
+pub fn claimed_key(claims: &[(String, String)]) -> Option<Fr> {
+    let mut values = claims.iter().filter(|(key, _)| key == HOLDER_BINDING_CLAIM).map(|(_, value)| value);
+    let key = match (values.next(), values.next()) {
+        (None, _) => return Some(Fr::zero()),
+        (Some(value), None) => field_from_bytes(&hex_to_bytes(value).ok()?)?,
+        (Some(_), Some(_)) => return None,
+    };
+    (!key.is_zero()).then_some(key)
+}
+
+/// Parse a hex holder secret
+pub(crate) fn parse_secret(holder_secret: *const c_char) -> Option<Fr> {
+    let text = text_arg(holder_secret, 2 * HOLDER_SECRET_LEN)?;
+    let secret: [u8; HOLDER_SECRET_LEN] = hex_to_bytes(text).ok()?.try_into().ok()?;
+    Some(secret_field(&secret))
+}
+
+/// The holder's part of a bound proof: the secret, and the context of a
+/// one-time proof (onetime.rs)
+#[cfg(feature = "prover")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Binding {
+    pub secret: Fr,
+    pub context: Option<Fr>,
+}
+
+/// Keys of the bound-VC circuit; the verifying key of a verifier-only build
+/// arrives by import
+#[derive(Default)]
+struct BoundVcKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static BOUND_VC_KEYS: Mutex<BoundVcKeys> = Mutex::new(BoundVcKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the bound-VC circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(11u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(VCCircuit::blank_bound(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match BOUND_VC_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(Arc::new(pk));
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the bound-VC verifying key is present
+pub(crate) fn has_keys() -> bool {
+    BOUND_VC_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the bound-VC circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = BOUND_VC_KEYS.lock() {
+        *keys = BoundVcKeys::default();
+    }
+}
+
+/// Generate the hex bound-VC proof for an already checked credential
+#[cfg(feature = "prover")]
+pub(crate) fn prove(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+    holder_secret: Fr,
+) -> Result<String, c_int> {
+    let pk = BOUND_VC_KEYS.lock().map_err(|_| -1)?.pk.clone().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+
+    let format = crate::proof::emit_format();
+    let binding = Binding { secret: holder_secret, context: None };
+    let (circuit, header) =
+        vc_circuit(format, credential, issuer_pubkey_bytes, current_time, nonce, Some(binding)).ok_or(-1)?;
+    prove_with_key(&pk, format, circuit, &header, &mut entropy::prover_rng(nonce)?)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Generate a holder keypair: a random 32-byte secret and its public key
+/// (both hex)
+///
+/// The secret comes from the OS generator, or the callback installed with
+/// ZK_SetEntropySource, and never leaves the holder; the issuer signs the
+/// public key into the credential (ZK_SignVCBound). Returns 0 on success,
+/// ZK_ERR_ENTROPY if the source fails, ZK_ERR_BUFFER_TOO_SMALL, or -1 on
+/// NULL.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateHolderKeypair(
+    secret_out: *mut c_char,
+    secret_out_size: usize,
+    public_key_out: *mut c_char,
+    public_key_out_size: usize,
+) -> c_int {
+    if secret_out.is_null() || public_key_out.is_null() {
+        return -1;
+    }
+
+    let mut secret = zeroize::Zeroizing::new([0u8; HOLDER_SECRET_LEN]);
+    if let Err(code) = entropy::fill(secret.as_mut()) {
+        return code;
+    }
+    let public_key = public_key(&secret_field(&secret));
+
+    let secret_hex = zeroize::Zeroizing::new(bytes_to_hex(secret.as_ref()));
+    match write_cstr(public_key_out, public_key_out_size, &bytes_to_hex(&field_bytes(&public_key)))
+        .and_then(|_| write_cstr(secret_out, secret_out_size, &secret_hex))
+    {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Sign a credential bound to a holder public key (u64 dates)
+///
+/// ZK_SignVCWithClaims with the single claim `zkid:holder-binding` =
+/// `holder_public_key` (hex, from ZK_GenerateHolderKeypair), so the
+/// signature covers the key. Returns the codes of ZK_SignVCWithClaims, or -1
+/// for a malformed holder public key.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVCBound(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    holder_public_key: *const c_char,
+    issuer_private_key: *const c_char,
+    signature_out: *mut c_char,
+    signature_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let key = text_arg(holder_public_key, 64)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| crate::disclosure::field_from_bytes(&bytes));
+    let value = match key.and_then(|key| CString::new(bytes_to_hex(&field_bytes(&key))).ok()) {
+        Some(value) => value,
+        None => return last_error::fail(-1, "holder_public_key is not a 32-byte holder key"),
+    };
+    let claim = match CString::new(HOLDER_BINDING_CLAIM) {
+        Ok(claim) => claim,
+        Err(_) => return -1,
+    };
+
+    ZK_SignVCWithClaims(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        &claim.as_ptr(), &value.as_ptr(), 1,
+        issuer_private_key,
+        signature_out, signature_out_size,
+        required_size_out,
+    )
+}
+}
+
+crate::unwind::entry_point! {
+/// Generate a VC proof for a credential bound to the holder of
+/// `holder_secret` (u64 dates)
+///
+/// Takes the arguments of ZK_GenerateVCProof and the holder secret (hex, 32
+/// bytes). The credential's signature must cover the `zkid:holder-binding`
+/// claim of the secret's public key, as ZK_SignVCBound makes it; the secret
+/// is a private witness of the proof. Bound credentials have no expiry
+/// grace, and their proofs bypass the nonce reuse cache.
+///
+/// Returns the codes of ZK_GenerateVCProof: ZK_ERR_BAD_SIGNATURE also for a
+/// secret other than the holder's, ZK_ERR_BAD_HEX for a malformed secret.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofBound(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    vc_signature: *const c_char,
+    issuer_pubkey: *const c_char,
+    holder_secret: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || issuer_pubkey.is_null() {
+        return last_error::fail(-1, "holder_id, issuer, vc_signature or issuer_pubkey is NULL");
+    }
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    let secret = match parse_secret(holder_secret) {
+        Some(secret) => secret,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "holder_secret is not 32-byte hex"),
+    };
+
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
+    };
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
+    };
+    let issuer_pubkey_bytes = match text_arg(issuer_pubkey, 2 * PUBLIC_KEY_LENGTH).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return last_error::fail(e.code_or(ZK_ERR_BAD_HEX), "issuer_pubkey is not hex or a did:key"),
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_pubkey is not 32-byte hex"),
+    };
+    let issuer_pubkey_bytes: [u8; PUBLIC_KEY_LENGTH] = match issuer_pubkey_bytes.try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_pubkey is not 32-byte hex"),
+    };
+    let vc_signature_bytes: [u8; SIGNATURE_LENGTH] = match text_arg(vc_signature, 2 * SIGNATURE_LENGTH)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(bytes) => bytes,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "vc_signature is not 64-byte hex"),
+    };
+
+    let credential = ProofCredential {
+        holder_id: holder_id_bytes,
+        issuer: issuer_bytes,
+        issue_date,
+        expiry_date,
+        signature: &vc_signature_bytes,
+        issuer_pubkey: &issuer_pubkey_bytes,
+        holder: Some(Binding { secret, context: None }),
+        revocation: None,
+    };
+    let reserve = |required| reserve_cstr(proof_out, proof_out_size, required, required_size_out);
+    let proof_hex = match generate_vc_proof(&credential, current_time, nonce, sizes::ZK_SIZE_PROOF_HEX, reserve) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("proof_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a bound VC proof
+///
+/// As ZK_VerifyVCProof, under the bound-VC key: a proof from the unbound
+/// VC circuit does not verify, and since the signed anchor holds the holder
+/// key, neither does a proof made with any secret but the one behind it.
+/// Returns 1 if valid, 0 if invalid (also for a
+/// proof of another time or of a credential not active at `current_time`),
+/// ZK_ERR_TIME_MISMATCH or -1 from the time source, ZK_ERR_DISABLED for a
+/// legacy-format proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofBound(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+    crate::last_error::clear();
+
+    let keys = BOUND_VC_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let result = match pvk {
+        Some(pvk) => verify_vc_proof_hex(pvk, proof_hex, issuer_pubkey, current_time, nonce, proof::accepted_formats()),
+        None => crate::last_error::fail(0, "bound-VC verifying key is not set up; call ZK_Init or import it"),
+    };
+
+    audit::record(started, audit::Verification {
+        check: "bound-vc-proof",
+        code: result,
+        circuit: Some(BOUND_VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the bound-VC verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportBoundVCVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match BOUND_VC_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportBoundVCVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportBoundVCVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == BOUND_VC_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match BOUND_VC_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,485 @@
+// ============================================================================
+// Encrypted Presentation-Request Inbox
+// ============================================================================
+//
+// A verifier that wants a presentation from an offline wallet leaves it a
+// sealed request: anyone with the holder's encryption public key can seal,
+// only the holder can open. Holders have a separate X25519 encryption
+// keypair (ZK_GenerateHolderEncryptionKeypair); it is unrelated to any
+// signing key.
+//
+// Sealed box layout:
+//
+//   "ZKSB" | version u8 | ephemeral_pubkey[32] | XChaCha20-Poly1305 ciphertext
+//
+// The key and nonce are HKDF-SHA256 over the X25519 shared secret, salted
+// with the ephemeral and recipient public keys. The header and the recipient
+// key are associated data, so a blob opened by the wrong key or with any
+// byte changed fails authentication. A fresh ephemeral key per blob means the
+// sender cannot open its own blob afterwards.
+//
+// The plaintext is usually an encoded PresentationRequest, integers
+// little-endian, variable-length fields prefixed with their u32 length:
+//
+//   "ZKRQ" | version u8 | issuer_pubkey | nonce u64 | current_time u64
+//   | expires_at u64 | metadata_count u32 | (key, value)*
+//
+// The holder fulfills it by proving with the request's issuer key, nonce and
+// current_time, and answers with a presentation carrying the same nonce.
+// Predicates on claims travel in the metadata, as `require.<key>` shorthand
+// or `predicate` entries in canonical binary form (see predicate.rs).
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use rand_core::OsRng;
+use sha2::Sha256;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::credential::read_claims;
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::predicate::{Predicate, PREDICATE_KEY, SHORTHAND_PREFIX};
+use crate::vk::write_out;
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, did, hex_to_bytes};
+
+const SEALED_MAGIC: &[u8; 4] = b"ZKSB";
+const SEALED_VERSION: u8 = 1;
+const SEALED_KDF_INFO: &[u8] = b"zkid/sealed-box/v1";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+/// Magic, version byte and ephemeral public key preceding the ciphertext
+pub const SEALED_HEADER_LEN: usize = SEALED_MAGIC.len() + 1 + KEY_LEN;
+
+const REQUEST_MAGIC: &[u8; 4] = b"ZKRQ";
+const REQUEST_VERSION: u8 = 1;
+
+/// Why a sealed box could not be opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InboxError {
+    /// Wrong key or tampered ciphertext
+    Authentication,
+    /// Truncated, wrong magic or unknown version
+    Corrupt,
+}
+
+impl InboxError {
+    pub fn code(self) -> c_int {
+        match self {
+            InboxError::Authentication => ZK_ERR_AUTH,
+            InboxError::Corrupt => ZK_ERR_CORRUPT,
+        }
+    }
+}
+
+/// A verifier's request for a presentation, fulfilled later by the holder
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresentationRequest {
+    pub issuer_pubkey: Vec<u8>,
+    pub nonce: u64,
+    pub current_time: u64,
+    /// Unix time after which the verifier no longer accepts an answer
+    pub expires_at: u64,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl PresentationRequest {
+    /// Encode with metadata in insertion order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(REQUEST_MAGIC);
+        out.push(REQUEST_VERSION);
+        put_bytes(&mut out, &self.issuer_pubkey);
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&self.current_time.to_le_bytes());
+        out.extend_from_slice(&self.expires_at.to_le_bytes());
+        out.extend_from_slice(&(self.metadata.len() as u32).to_le_bytes());
+        for (key, value) in &self.metadata {
+            put_bytes(&mut out, key.as_bytes());
+            put_bytes(&mut out, value.as_bytes());
+        }
+        out
+    }
+
+    /// Decode a binary request
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(REQUEST_MAGIC.len())? != REQUEST_MAGIC || r.u8()? != REQUEST_VERSION {
+            return None;
+        }
+
+        let issuer_pubkey = r.bytes()?.to_vec();
+        let nonce = r.u64()?;
+        let current_time = r.u64()?;
+        let expires_at = r.u64()?;
+
+        let metadata_count = r.u32()? as usize;
+        let mut metadata = Vec::new();
+        for _ in 0..metadata_count {
+            metadata.push((r.string()?, r.string()?));
+        }
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            issuer_pubkey,
+            nonce,
+            current_time,
+            expires_at,
+            metadata,
+        })
+    }
+
+    /// Decode either the binary form or its hex text
+    pub fn from_wire(data: &[u8]) -> Option<Self> {
+        if data.starts_with(REQUEST_MAGIC) {
+            return Self::from_bytes(data);
+        }
+        let text = std::str::from_utf8(data).ok()?.trim();
+        Self::from_bytes(&hex_to_bytes(text).ok()?)
+    }
+
+    /// Value of a metadata entry
+    pub fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Predicates the request states, in metadata order; None if one is
+    /// malformed
+    pub fn predicates(&self) -> Option<Vec<Predicate>> {
+        let mut predicates = Vec::new();
+        for (key, value) in &self.metadata {
+            if let Some(claim) = key.strip_prefix(SHORTHAND_PREFIX) {
+                predicates.push(Predicate::from_shorthand(claim, value)?);
+            } else if key == PREDICATE_KEY {
+                predicates.push(Predicate::from_bytes(&hex_to_bytes(value).ok()?)?);
+            }
+        }
+        Some(predicates)
+    }
+}
+
+fn cipher(
+    shared_secret: &[u8; KEY_LEN],
+    ephemeral: &PublicKey,
+    recipient: &PublicKey,
+) -> (XChaCha20Poly1305, XNonce) {
+    let mut salt = [0u8; 2 * KEY_LEN];
+    salt[..KEY_LEN].copy_from_slice(ephemeral.as_bytes());
+    salt[KEY_LEN..].copy_from_slice(recipient.as_bytes());
+
+    let mut okm = Zeroizing::new([0u8; KEY_LEN + NONCE_LEN]);
+    Hkdf::<Sha256>::new(Some(&salt), shared_secret)
+        .expand(SEALED_KDF_INFO, okm.as_mut())
+        .expect("output length is valid for HKDF-SHA256");
+
+    let cipher = XChaCha20Poly1305::new_from_slice(&okm[..KEY_LEN]).expect("key length is 32");
+    (cipher, *XNonce::from_slice(&okm[KEY_LEN..]))
+}
+
+fn associated_data(header: &[u8], recipient: &PublicKey) -> Vec<u8> {
+    let mut aad = header.to_vec();
+    aad.extend_from_slice(recipient.as_bytes());
+    aad
+}
+
+/// Seal `plaintext` so only the holder of `recipient`'s secret can open it
+///
+/// None if `recipient` is a low-order point (the shared secret would not
+/// depend on the ephemeral key).
+pub fn seal(recipient: &PublicKey, plaintext: &[u8]) -> Option<Vec<u8>> {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral = PublicKey::from(&ephemeral_secret);
+    let shared = ephemeral_secret.diffie_hellman(recipient);
+    if !shared.was_contributory() {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(SEALED_HEADER_LEN + plaintext.len() + 16);
+    out.extend_from_slice(SEALED_MAGIC);
+    out.push(SEALED_VERSION);
+    out.extend_from_slice(ephemeral.as_bytes());
+
+    let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral, recipient);
+    let aad = associated_data(&out, recipient);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &aad })
+        .ok()?;
+    out.extend_from_slice(&ciphertext);
+    Some(out)
+}
+
+/// Open a sealed box addressed to `secret`
+pub fn open(secret: &StaticSecret, blob: &[u8]) -> Result<Zeroizing<Vec<u8>>, InboxError> {
+    if blob.len() < SEALED_HEADER_LEN
+        || &blob[..SEALED_MAGIC.len()] != SEALED_MAGIC
+        || blob[SEALED_MAGIC.len()] != SEALED_VERSION
+    {
+        return Err(InboxError::Corrupt);
+    }
+
+    let (header, ciphertext) = blob.split_at(SEALED_HEADER_LEN);
+    let mut ephemeral_bytes = [0u8; KEY_LEN];
+    ephemeral_bytes.copy_from_slice(&header[SEALED_MAGIC.len() + 1..]);
+    let ephemeral = PublicKey::from(ephemeral_bytes);
+    let recipient = PublicKey::from(secret);
+
+    let shared = secret.diffie_hellman(&ephemeral);
+    if !shared.was_contributory() {
+        return Err(InboxError::Authentication);
+    }
+
+    let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral, &recipient);
+    let aad = associated_data(header, &recipient);
+    cipher
+        .decrypt(&nonce, Payload { msg: ciphertext, aad: &aad })
+        .map(Zeroizing::new)
+        .map_err(|_| InboxError::Authentication)
+}
+
+fn read_key_hex(key: *const c_char) -> Option<Zeroizing<[u8; KEY_LEN]>> {
+    let key_str = unsafe { CStr::from_ptr(key).to_str().ok()? };
+    let bytes = Zeroizing::new(hex_to_bytes(key_str).ok()?);
+    if bytes.len() != KEY_LEN {
+        return None;
+    }
+    let mut out = Zeroizing::new([0u8; KEY_LEN]);
+    out.copy_from_slice(&bytes);
+    Some(out)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Generate a holder encryption keypair (X25519, hex encoded, 32 bytes each)
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_GenerateHolderEncryptionKeypair(
+    public_key_out: *mut c_char,
+    public_key_size: usize,
+    private_key_out: *mut c_char,
+    private_key_size: usize,
+) -> c_int {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public_key_hex = bytes_to_hex(PublicKey::from(&secret).as_bytes());
+    let private_key_hex = Zeroizing::new(bytes_to_hex(secret.as_bytes()));
+
+    match write_cstr(public_key_out, public_key_size, &public_key_hex)
+        .and_then(|_| write_cstr(private_key_out, private_key_size, &private_key_hex))
+    {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Seal `plaintext` for the holder's encryption public key (hex)
+///
+/// The blob length is written to `blob_len_out` even when the buffer is too
+/// small. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL for a short buffer,
+/// -1 on invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_EncryptForHolder(
+    holder_pubkey: *const c_char,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    blob_out: *mut u8,
+    blob_out_size: usize,
+    blob_len_out: *mut usize,
+) -> c_int {
+    if holder_pubkey.is_null() || plaintext.is_null() || blob_len_out.is_null() {
+        return -1;
+    }
+
+    let recipient = match read_key_hex(holder_pubkey) {
+        Some(key) => PublicKey::from(*key),
+        None => return -1,
+    };
+    let plaintext = match read_bytes(plaintext, plaintext_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match seal(&recipient, plaintext) {
+        Some(blob) => write_out(&blob, blob_out, blob_out_size, blob_len_out),
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Open a sealed blob with the holder's encryption private key (hex)
+///
+/// The plaintext length is written to `plaintext_len_out` even when the
+/// buffer is too small. Returns 0 on success, ZK_ERR_AUTH for a wrong key or
+/// tampered blob, ZK_ERR_CORRUPT for a malformed blob,
+/// ZK_ERR_BUFFER_TOO_SMALL for a short buffer, -1 on invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_DecryptAsHolder(
+    holder_privkey: *const c_char,
+    blob: *const u8,
+    blob_len: usize,
+    plaintext_out: *mut u8,
+    plaintext_out_size: usize,
+    plaintext_len_out: *mut usize,
+) -> c_int {
+    if holder_privkey.is_null() || blob.is_null() || plaintext_len_out.is_null() {
+        return -1;
+    }
+
+    let secret = match read_key_hex(holder_privkey) {
+        Some(key) => StaticSecret::from(*key),
+        None => return -1,
+    };
+    let blob = match read_bytes(blob, blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match open(&secret, blob) {
+        Ok(plaintext) => write_out(&plaintext, plaintext_out, plaintext_out_size, plaintext_len_out),
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Build a hex presentation request from the challenge values and metadata
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_EncodePresentationRequest(
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    current_time: u64,
+    expires_at: u64,
+    metadata_keys: *const *const c_char,
+    metadata_values: *const *const c_char,
+    metadata_count: usize,
+    request_out: *mut c_char,
+    request_out_size: usize,
+) -> c_int {
+    if issuer_pubkey.is_null() {
+        return -1;
+    }
+
+    let issuer_pubkey_str = unsafe { CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("") };
+    let issuer_pubkey = match did::public_key_bytes(issuer_pubkey_str) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code_or(-1),
+    };
+
+    let metadata = match read_claims(metadata_keys, metadata_values, metadata_count) {
+        Some(metadata) => metadata,
+        None => return -1,
+    };
+
+    let request = PresentationRequest {
+        issuer_pubkey,
+        nonce,
+        current_time,
+        expires_at,
+        metadata,
+    };
+
+    match write_cstr(request_out, request_out_size, &bytes_to_hex(&request.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read the fields a holder needs to fulfill a request (binary or hex text)
+///
+/// Writes the issuer key as hex and the nonce, current time and expiry.
+/// Returns 0 on success, ZK_ERR_CORRUPT if the request does not decode,
+/// ZK_ERR_BUFFER_TOO_SMALL for a short buffer, -1 on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_DecodePresentationRequest(
+    request_blob: *const c_char,
+    request_blob_len: usize,
+    issuer_pubkey_out: *mut c_char,
+    issuer_pubkey_out_size: usize,
+    nonce_out: *mut u64,
+    current_time_out: *mut u64,
+    expires_at_out: *mut u64,
+) -> c_int {
+    if request_blob.is_null() || nonce_out.is_null() || current_time_out.is_null() || expires_at_out.is_null() {
+        return -1;
+    }
+
+    let data = match read_bytes(request_blob, request_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let request = match PresentationRequest::from_wire(data) {
+        Some(request) => request,
+        None => return ZK_ERR_CORRUPT,
+    };
+
+    if let Err(e) = write_cstr(issuer_pubkey_out, issuer_pubkey_out_size, &bytes_to_hex(&request.issuer_pubkey)) {
+        return e.code();
+    }
+    unsafe {
+        *nonce_out = request.nonce;
+        *current_time_out = request.current_time;
+        *expires_at_out = request.expires_at;
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Look up a metadata value of a request (binary or hex text)
+///
+/// Returns 0 on success, 1 if the key is absent, ZK_ERR_CORRUPT if the
+/// request does not decode, ZK_ERR_BUFFER_TOO_SMALL for a short buffer, -1
+/// on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_PresentationRequestMetadata(
+    request_blob: *const c_char,
+    request_blob_len: usize,
+    key: *const c_char,
+    value_out: *mut c_char,
+    value_out_size: usize,
+) -> c_int {
+    if request_blob.is_null() || key.is_null() {
+        return -1;
+    }
+
+    let data = match read_bytes(request_blob, request_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let request = match PresentationRequest::from_wire(data) {
+        Some(request) => request,
+        None => return ZK_ERR_CORRUPT,
+    };
+    let key = match unsafe { CStr::from_ptr(key).to_str() } {
+        Ok(key) => key,
+        Err(_) => return -1,
+    };
+
+    match request.metadata_value(key) {
+        Some(value) => match write_cstr(value_out, value_out_size, value) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        None => 1,
+    }
+}
+}
@@ -0,0 +1,380 @@
+// ============================================================================
+// Artifact Inspection
+// ============================================================================
+//
+// Support tooling receives opaque blobs (binary or hex) and needs to know
+// what they are without a verifier setup. ZK_InspectArtifact recognizes
+//
+//   type                 by                        described with
+//   presentation         "ZKPR"                    version, issuer key,
+//                                                  metadata keys, its proof
+//   credential           "ZKVC"                    version, credential id,
+//                                                  issuer, dates, claim keys,
+//                                                  co-signer, supersedes,
+//                                                  schedule window count
+//   status_attestation   "ZKST"                    version, credential id,
+//                                                  attested_at
+//   proof                bare length, "ZP", "ZC"   format, form, point
+//                        (schedule circuit),       encoding, canonical,
+//                        "ZKGP" (grace circuit)    valid_points, circuit,
+//                        or "ZKVA" (VC circuit)    grace_seconds (grace),
+//                                                  current_time (VC, grace,
+//                                                  schedule)
+//   verifying_key        decodes as a raw or       form, public inputs,
+//                        prepared key              candidate circuits, the
+//                                                  audit fingerprint,
+//                                                  consistent (prepared)
+//
+// and writes a JSON description with the type, the encoding it arrived in,
+// its binary size and SHA-256 fingerprint. Claim values, metadata values,
+// holder identifiers, nonces and proof points are never written: only what
+// identifies an artifact, not what it discloses. `canonical` reports whether
+// every proof coordinate is a reduced field element with valid flag bits,
+// `valid_points` whether the points also lie on the curve and in the
+// subgroup.
+//
+// Nothing is verified and no key is needed: signatures are not checked and
+// proofs never reach a pairing. A prepared key's precomputed elements are
+// compared against its raw key, as the import path does, and reported as
+// `consistent`. Input is untrusted: it is bounded to MAX_INSPECT_LEN bytes
+// (twice that as hex) before anything is decoded, every length prefix is
+// checked against the remaining input, and proofs are parsed here rather
+// than through the admission stages so support traffic does not show up in
+// ZK_GetAdmissionStats.
+//
+// A blob of a recognized type that does not decode is still described, with
+// an "error" entry ("malformed", "unsupported_version"); anything else is
+// type "unknown" with error "unrecognized". Delegation certificates have no
+// encoding in this library and are reported as unknown. The golden outputs
+// for each type are the artifact_inspection conformance vectors.
+
+use ark_bn254::Bn254;
+use ark_groth16::{PreparedVerifyingKey, Proof};
+use ark_serialize::CanonicalDeserialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+
+use crate::admission::{canonical, MAX_PRESENTATION_LEN};
+use crate::age::DERIVED_AGE_CIRCUIT;
+use crate::ballot::BALLOT_CIRCUIT;
+use crate::composite::GROUP_CLAIM_CIRCUIT;
+use crate::credential::{VC_BLOB_MAGIC, VC_BLOB_VERSION};
+use crate::disclosure::CLAIM_DISCLOSURE_CIRCUIT;
+use crate::encoding::{PointEncoding, ZkDeserialize};
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_UNSUPPORTED_VERSION};
+use crate::ffi::{read_bytes, write_cstr, FfiError};
+use crate::grace::{self, GRACE_CIRCUIT};
+use crate::holder::BOUND_VC_CIRCUIT;
+use crate::nonrevocation::NON_REVOKED_VC_CIRCUIT;
+use crate::onetime::ONE_TIME_VC_CIRCUIT;
+use crate::params::ZkCircuitParams;
+use crate::predicate::PREDICATE_CIRCUIT;
+use crate::proof;
+use crate::range::RANGE_CLAIM_CIRCUIT;
+use crate::rebind::HOLDER_BOUND_CIRCUIT;
+use crate::schedule::{self, SCHEDULE_CIRCUIT};
+use crate::sizes::{
+    BALLOT_PUBLIC_INPUTS, BOUND_VC_PUBLIC_INPUTS, CLAIM_DISCLOSURE_PUBLIC_INPUTS, DERIVED_AGE_PUBLIC_INPUTS,
+    GRACE_PUBLIC_INPUTS, GROUP_CLAIM_PUBLIC_INPUTS, HOLDER_BOUND_PUBLIC_INPUTS, NON_REVOKED_VC_PUBLIC_INPUTS,
+    ONE_TIME_VC_PUBLIC_INPUTS, PREDICATE_PUBLIC_INPUTS, RANGE_CLAIM_PUBLIC_INPUTS, SCHEDULE_PUBLIC_INPUTS, VC_CIRCUIT,
+    VC_PUBLIC_INPUTS, ZK_CIRCUIT_SCHEDULE,
+};
+use crate::stateless::{StatusAttestation, ATTESTATION_MAGIC, ATTESTATION_VERSION};
+use crate::wire::{Reader, PRESENTATION_MAGIC, PRESENTATION_VERSION};
+use crate::validity;
+use crate::{bytes_to_hex, hex_to_bytes, vk, VerifiableCredential};
+
+/// Longest artifact inspected, binary; its hex text may be twice as long
+pub const MAX_INSPECT_LEN: usize = MAX_PRESENTATION_LEN;
+
+/// Circuits by their number of public inputs
+const CIRCUITS: [(usize, &str); 13] = [
+    (VC_PUBLIC_INPUTS, VC_CIRCUIT),
+    (SCHEDULE_PUBLIC_INPUTS, SCHEDULE_CIRCUIT),
+    (GROUP_CLAIM_PUBLIC_INPUTS, GROUP_CLAIM_CIRCUIT),
+    (BALLOT_PUBLIC_INPUTS, BALLOT_CIRCUIT),
+    (DERIVED_AGE_PUBLIC_INPUTS, DERIVED_AGE_CIRCUIT),
+    (HOLDER_BOUND_PUBLIC_INPUTS, HOLDER_BOUND_CIRCUIT),
+    (GRACE_PUBLIC_INPUTS, GRACE_CIRCUIT),
+    (PREDICATE_PUBLIC_INPUTS, PREDICATE_CIRCUIT),
+    (CLAIM_DISCLOSURE_PUBLIC_INPUTS, CLAIM_DISCLOSURE_CIRCUIT),
+    (RANGE_CLAIM_PUBLIC_INPUTS, RANGE_CLAIM_CIRCUIT),
+    (BOUND_VC_PUBLIC_INPUTS, BOUND_VC_CIRCUIT),
+    (ONE_TIME_VC_PUBLIC_INPUTS, ONE_TIME_VC_CIRCUIT),
+    (NON_REVOKED_VC_PUBLIC_INPUTS, NON_REVOKED_VC_CIRCUIT),
+];
+
+/// Why an artifact could not be described
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Defect {
+    Unrecognized,
+    Malformed,
+    Version,
+}
+
+impl Defect {
+    fn code(self) -> c_int {
+        match self {
+            Defect::Unrecognized | Defect::Malformed => ZK_ERR_CORRUPT,
+            Defect::Version => ZK_ERR_UNSUPPORTED_VERSION,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Defect::Unrecognized => "unrecognized",
+            Defect::Malformed => "malformed",
+            Defect::Version => "unsupported_version",
+        }
+    }
+}
+
+/// Format, form and point checks of a bare or enveloped proof
+fn describe_proof(bytes: &[u8]) -> Option<Map<String, Value>> {
+    let (format, encoding, body) = proof::split(bytes)?;
+    let form = if body.len() == bytes.len() { "bare" } else { "envelope" };
+
+    let canonical = canonical(body);
+    let valid_points = canonical && Proof::<Bn254>::zk_from_bytes(body, encoding).is_some();
+    let mut fields = Map::new();
+    fields.insert("format".into(), json!(format));
+    fields.insert("form".into(), json!(form));
+    let points = match encoding {
+        PointEncoding::Compressed => "compressed",
+        PointEncoding::Uncompressed => "uncompressed",
+    };
+    fields.insert("points".into(), json!(points));
+    fields.insert("canonical".into(), json!(canonical));
+    fields.insert("valid_points".into(), json!(valid_points));
+    Some(fields)
+}
+
+/// A VC proof with the time it was proven for, else a proof as describe_proof
+fn describe_vc_proof(bytes: &[u8]) -> Option<Map<String, Value>> {
+    let (header, inner) = match validity::untag(bytes) {
+        Some(split) => split,
+        None => return describe_proof(bytes),
+    };
+    let mut fields = describe_proof(inner)?;
+    fields.insert("circuit".into(), json!(VC_CIRCUIT));
+    fields.insert("current_time".into(), json!(header.current_time));
+    Some(fields)
+}
+
+/// A proof, with its circuit if it carries the schedule parameters, grace
+/// or validity prefix
+fn describe_proof_wire(bytes: &[u8]) -> Option<Map<String, Value>> {
+    if validity::untag(bytes).is_some() {
+        return describe_vc_proof(bytes);
+    }
+    if let Some((grace_seconds, inner)) = grace::untag(bytes) {
+        let mut fields = describe_vc_proof(inner)?;
+        fields.insert("circuit".into(), json!(GRACE_CIRCUIT));
+        fields.insert("grace_seconds".into(), json!(grace_seconds));
+        return Some(fields);
+    }
+    let (params, inner) = ZkCircuitParams::untag_schedule(bytes)?;
+    if inner.len() == bytes.len() {
+        return describe_proof(bytes);
+    }
+    if params.circuit != ZK_CIRCUIT_SCHEDULE || !params.is_supported() {
+        return None;
+    }
+    let mut fields = describe_vc_proof(inner)?;
+    fields.insert("circuit".into(), json!(schedule::circuit_id(params.capacity as usize)));
+    Some(fields)
+}
+
+fn describe_presentation(bytes: &[u8]) -> Result<Map<String, Value>, Defect> {
+    let mut r = Reader::new(bytes);
+    r.take(PRESENTATION_MAGIC.len());
+    if r.u8().ok_or(Defect::Malformed)? != PRESENTATION_VERSION {
+        return Err(Defect::Version);
+    }
+
+    let mut fields = Map::new();
+    fields.insert("version".into(), json!(PRESENTATION_VERSION));
+    let mut read = || {
+        let proof = describe_vc_proof(r.bytes()?)?;
+        let issuer_pubkey = r.bytes()?;
+        r.u64()?;
+        let metadata_count = r.u32()?;
+        let mut metadata_keys = Vec::new();
+        for _ in 0..metadata_count {
+            metadata_keys.push(r.string()?);
+            r.bytes()?;
+        }
+        if !r.is_empty() {
+            return None;
+        }
+        fields.insert("issuer_pubkey".into(), json!(bytes_to_hex(issuer_pubkey)));
+        fields.insert("metadata_keys".into(), json!(metadata_keys));
+        fields.insert("proof".into(), Value::Object(proof));
+        Some(())
+    };
+    read().ok_or(Defect::Malformed)?;
+    Ok(fields)
+}
+
+fn describe_credential(bytes: &[u8]) -> Result<Map<String, Value>, Defect> {
+    let version = *bytes.get(VC_BLOB_MAGIC.len()).ok_or(Defect::Malformed)?;
+    if version == 0 || version > VC_BLOB_VERSION {
+        return Err(Defect::Version);
+    }
+    let vc = VerifiableCredential::from_bytes(bytes).ok_or(Defect::Malformed)?;
+
+    let mut fields = Map::new();
+    fields.insert("version".into(), json!(version));
+    fields.insert("credential_id".into(), json!(vc.credential_id()));
+    fields.insert("issuer".into(), json!(vc.issuer));
+    fields.insert("issue_date".into(), json!(vc.issue_date));
+    fields.insert("expiry_date".into(), json!(vc.expiry_date));
+    let claim_keys: Vec<&str> = vc.claims.iter().map(|(key, _)| key.as_str()).collect();
+    fields.insert("claim_keys".into(), json!(claim_keys));
+    fields.insert("co_signer".into(), json!(vc.co_signature.as_ref().map(|co| bytes_to_hex(&co.key_id))));
+    fields.insert("supersedes".into(), json!(vc.supersedes));
+    let windows = vc.validity_schedule.as_ref().map(|schedule| schedule.windows.len());
+    fields.insert("schedule_windows".into(), json!(windows));
+    Ok(fields)
+}
+
+fn describe_attestation(bytes: &[u8]) -> Result<Map<String, Value>, Defect> {
+    if *bytes.get(ATTESTATION_MAGIC.len()).ok_or(Defect::Malformed)? != ATTESTATION_VERSION {
+        return Err(Defect::Version);
+    }
+    let attestation = StatusAttestation::from_bytes(bytes).ok_or(Defect::Malformed)?;
+
+    let mut fields = Map::new();
+    fields.insert("version".into(), json!(ATTESTATION_VERSION));
+    fields.insert("credential_id".into(), json!(attestation.credential_id));
+    fields.insert("attested_at".into(), json!(attestation.attested_at));
+    Ok(fields)
+}
+
+/// A raw or prepared verifying key
+fn describe_verifying_key(bytes: &[u8]) -> Option<Map<String, Value>> {
+    let mut fields = Map::new();
+    let key = match vk::decode_raw(bytes) {
+        Ok(key) => {
+            fields.insert("form".into(), json!("raw"));
+            key
+        }
+        Err(_) => {
+            let mut reader = bytes;
+            let pvk = PreparedVerifyingKey::<Bn254>::deserialize_compressed(&mut reader).ok()?;
+            if !reader.is_empty() {
+                return None;
+            }
+            let consistent = PreparedVerifyingKey::from(pvk.vk.clone()) == pvk;
+            fields.insert("form".into(), json!("prepared"));
+            fields.insert("consistent".into(), json!(consistent));
+            pvk.vk
+        }
+    };
+
+    let public_inputs = vk::public_inputs(&key);
+    let circuits: Vec<&str> = CIRCUITS
+        .iter()
+        .filter(|(inputs, _)| *inputs == public_inputs)
+        .map(|(_, circuit)| *circuit)
+        .collect();
+    fields.insert("public_inputs".into(), json!(public_inputs));
+    fields.insert("circuits".into(), json!(circuits));
+    // The prefix audit events report as "vk"
+    let raw = vk::encode(&key);
+    fields.insert("vk_fingerprint".into(), json!(bytes_to_hex(&Sha256::digest(&raw)[..4])));
+    Some(fields)
+}
+
+/// Recognize a binary artifact and describe it
+fn describe(bytes: &[u8]) -> (&'static str, Result<Map<String, Value>, Defect>) {
+    if bytes.starts_with(PRESENTATION_MAGIC) {
+        return ("presentation", describe_presentation(bytes));
+    }
+    if bytes.starts_with(VC_BLOB_MAGIC) {
+        return ("credential", describe_credential(bytes));
+    }
+    if bytes.starts_with(ATTESTATION_MAGIC) {
+        return ("status_attestation", describe_attestation(bytes));
+    }
+    if let Some(fields) = describe_proof_wire(bytes) {
+        return ("proof", Ok(fields));
+    }
+    match describe_verifying_key(bytes) {
+        Some(fields) => ("verifying_key", Ok(fields)),
+        None => ("unknown", Err(Defect::Unrecognized)),
+    }
+}
+
+/// Describe an artifact (binary or hex text) and the code
+/// ZK_InspectArtifact returns with the description
+pub fn inspect(artifact: &[u8]) -> (Value, c_int) {
+    let hex = std::str::from_utf8(artifact)
+        .ok()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .and_then(|text| hex_to_bytes(text).ok());
+    let (encoding, bytes) = match &hex {
+        Some(bytes) => ("hex", bytes.as_slice()),
+        None => ("binary", artifact),
+    };
+
+    let mut description = Map::new();
+    description.insert("encoding".into(), json!(encoding));
+    description.insert("size".into(), json!(bytes.len()));
+    if bytes.len() > MAX_INSPECT_LEN {
+        description.insert("type".into(), json!("unknown"));
+        description.insert("error".into(), json!("too_large"));
+        return (Value::Object(description), ZK_ERR_INPUT_TOO_LARGE);
+    }
+
+    let (kind, described) = describe(bytes);
+    description.insert("type".into(), json!(kind));
+    description.insert("fingerprint".into(), json!(bytes_to_hex(&Sha256::digest(bytes))));
+    let code = match described {
+        Ok(fields) => {
+            description.extend(fields);
+            0
+        }
+        Err(defect) => {
+            description.insert("error".into(), json!(defect.name()));
+            defect.code()
+        }
+    };
+    (Value::Object(description), code)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Describe an artifact for support tooling (see inspect.rs)
+///
+/// `artifact` is `artifact_len` bytes of a binary artifact or its hex text
+/// (no NUL needed). Writes the JSON description to `json_out` whatever the
+/// result, unless the buffer is too small. Returns 0 if the artifact was
+/// recognized and decoded, ZK_ERR_CORRUPT if it was not recognized or does
+/// not decode, ZK_ERR_UNSUPPORTED_VERSION for a version this library does
+/// not know, ZK_ERR_INPUT_TOO_LARGE above MAX_INSPECT_LEN,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL or empty input.
+#[no_mangle]
+pub extern "C" fn ZK_InspectArtifact(
+    artifact: *const c_char,
+    artifact_len: usize,
+    json_out: *mut c_char,
+    json_out_size: usize,
+) -> c_int {
+    let artifact = match read_bytes(artifact, artifact_len, 2 * MAX_INSPECT_LEN) {
+        Ok(bytes) if !bytes.is_empty() => bytes,
+        Err(FfiError::TooLong) => return ZK_ERR_INPUT_TOO_LARGE,
+        _ => return -1,
+    };
+    let (description, code) = inspect(artifact);
+    match write_cstr(json_out, json_out_size, &description.to_string()) {
+        Ok(_) => code,
+        Err(e) => e.code(),
+    }
+}
+}
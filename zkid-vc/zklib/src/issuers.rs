@@ -0,0 +1,232 @@
+// ============================================================================
+// Trusted Issuer Registry
+// ============================================================================
+//
+// ZK_VerifyVCProof checks a proof against whichever issuer key the caller
+// passes, so which issuers to accept is decided in the caller's glue code.
+// The registry moves that decision into the library: the verifier names the
+// issuer keys it trusts once,
+//
+//   ZK_AddTrustedIssuer("gov-id", key) ... ZK_RemoveTrustedIssuer("gov-id")
+//
+// and ZK_VerifyVCProofTrusted accepts a proof that verifies under any of
+// them, reporting which. A proof's public inputs include the issuer key
+// hash, which the proof does not carry, so each trusted key is tried in
+// turn (one pairing check each, in name order); MAX_TRUSTED_ISSUERS bounds
+// the work.
+//
+// The registry is process-wide, separate from context trust stores
+// (context.rs), and sits behind its own mutex. Updates copy it on write; a
+// verification takes the current registry under the lock and checks against
+// that snapshot without holding it, so updates never wait for verifications
+// in flight, and each verification sees the registry from before an update
+// or after it, never a mix.
+
+use ed25519_dalek::PUBLIC_KEY_LENGTH;
+use std::collections::BTreeMap;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::did::public_key_arg;
+use crate::error::ZK_ERR_CAPACITY;
+use crate::ffi::read_cstr;
+use crate::last_error;
+#[cfg(feature = "verifier")]
+use {
+    crate::ffi::write_cstr,
+    crate::{admission, audit, proof, validity, VERIFYING_KEY},
+    ark_bn254::Bn254,
+    ark_groth16::{Groth16, PreparedVerifyingKey},
+    ark_snark::SNARK,
+    std::time::Instant,
+};
+
+/// Most issuers the registry holds
+pub const MAX_TRUSTED_ISSUERS: usize = 64;
+
+/// Longest issuer name, in bytes
+pub const MAX_ISSUER_NAME_LEN: usize = 64;
+
+/// Trusted issuer keys by name
+type Registry = BTreeMap<String, [u8; PUBLIC_KEY_LENGTH]>;
+
+static REGISTRY: Mutex<Option<Arc<Registry>>> = Mutex::new(None);
+
+/// The registry as it is now; later updates do not change it
+pub fn snapshot() -> Arc<Registry> {
+    REGISTRY
+        .lock()
+        .ok()
+        .and_then(|registry| registry.clone())
+        .unwrap_or_default()
+}
+
+/// Trust `issuer_pubkey` under `name`, replacing the key the name had
+///
+/// Fails with ZK_ERR_CAPACITY for a new name past MAX_TRUSTED_ISSUERS.
+pub fn add(name: &str, issuer_pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Result<(), c_int> {
+    let mut registry = REGISTRY.lock().map_err(|_| -1)?;
+    let issuers = Arc::make_mut(registry.get_or_insert_with(Default::default));
+    if !issuers.contains_key(name) && issuers.len() >= MAX_TRUSTED_ISSUERS {
+        return Err(ZK_ERR_CAPACITY);
+    }
+    issuers.insert(name.to_string(), issuer_pubkey);
+    Ok(())
+}
+
+/// Stop trusting the issuer named `name`; false if there was none
+pub fn remove(name: &str) -> Result<bool, c_int> {
+    let mut registry = REGISTRY.lock().map_err(|_| -1)?;
+    Ok(registry
+        .as_mut()
+        .is_some_and(|issuers| Arc::make_mut(issuers).remove(name).is_some()))
+}
+
+/// Forget every trusted issuer
+pub(crate) fn clear() {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        *registry = None;
+    }
+}
+
+/// Name of the first trusted issuer a hex proof verifies under at
+/// `current_time`; failures are recorded for ZK_GetLastError
+#[cfg(feature = "verifier")]
+fn verify_trusted(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> Result<String, c_int> {
+    let (header, format, proof) = admission::vc_proof_arg(proof_hex, proof::accepted_formats())
+        .map_err(|rejection| last_error::fail(rejection.verify_code(), rejection.describe()))?;
+    let now = crate::proof_time(current_time)?;
+
+    let issuers = snapshot();
+    let matched = issuers.iter().find(|(_, issuer_pubkey)| {
+        validity::public_inputs(&issuer_pubkey[..], format, nonce, now, &header).is_some_and(|public_inputs| {
+            matches!(Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof), Ok(true))
+        })
+    });
+    proof::record_outcome(format, matched.is_some());
+    match matched {
+        Some((name, _)) => Ok(name.clone()),
+        None if header.current_time != now => Err(last_error::fail(0, crate::verify_failure(&header, now))),
+        None if issuers.is_empty() => Err(last_error::fail(0, "no issuer is trusted; call ZK_AddTrustedIssuer")),
+        None => Err(last_error::fail(0, "proof verifies under no trusted issuer for nonce and current_time")),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+/// Issuer name argument: UTF-8, 1 to MAX_ISSUER_NAME_LEN bytes
+fn name_arg<'a>(name: *const c_char) -> Result<&'a str, c_int> {
+    match read_cstr(name, MAX_ISSUER_NAME_LEN).map(std::str::from_utf8) {
+        Ok(Ok(name)) if !name.is_empty() => Ok(name),
+        Ok(_) => Err(last_error::fail(-1, "name is empty or not UTF-8")),
+        Err(e) => Err(last_error::ffi("name", e)),
+    }
+}
+
+crate::unwind::entry_point! {
+/// Trust an issuer public key (hex or did:key) under a name
+///
+/// Adding a name again replaces its key, e.g. for a key rotation;
+/// verifications already running keep the key they started with. Returns 0
+/// on success, ZK_ERR_BAD_HEX for a key that is not a 32-byte Ed25519
+/// public key, ZK_ERR_BAD_DID for a malformed did:key, ZK_ERR_CAPACITY past
+/// MAX_TRUSTED_ISSUERS names, -1 on NULL or a name that is empty, not UTF-8
+/// or longer than MAX_ISSUER_NAME_LEN bytes.
+#[no_mangle]
+pub extern "C" fn ZK_AddTrustedIssuer(name: *const c_char, issuer_pubkey: *const c_char) -> c_int {
+    last_error::clear();
+    let name = match name_arg(name) {
+        Ok(name) => name,
+        Err(code) => return code,
+    };
+    let issuer_key = match public_key_arg(issuer_pubkey) {
+        Ok(key) => key,
+        Err(code) => return code,
+    };
+
+    match add(name, issuer_key.to_bytes()) {
+        Ok(()) => 0,
+        Err(ZK_ERR_CAPACITY) => last_error::fail(ZK_ERR_CAPACITY, "trusted issuer registry is full"),
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Stop trusting the issuer registered under `name`
+///
+/// Returns 0 if removed, 1 if no issuer has the name, -1 on NULL or
+/// failure.
+#[no_mangle]
+pub extern "C" fn ZK_RemoveTrustedIssuer(name: *const c_char) -> c_int {
+    last_error::clear();
+    let name = match name_arg(name) {
+        Ok(name) => name,
+        Err(code) => return code,
+    };
+
+    match remove(name) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a VC proof under any trusted issuer (see ZK_AddTrustedIssuer)
+///
+/// On success the name of the issuer it verifies under is written to
+/// `issuer_name_out` (MAX_ISSUER_NAME_LEN + 1 bytes always suffice; NULL to
+/// skip it). Returns 1 if valid, 0 if the proof verifies under no trusted
+/// issuer (also with none registered), ZK_ERR_BUFFER_TOO_SMALL if valid but
+/// the name does not fit, and the other codes of ZK_VerifyVCProof.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofTrusted(
+    proof_hex: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    issuer_name_out: *mut c_char,
+    issuer_name_out_size: usize,
+) -> c_int {
+    let started = Instant::now();
+    last_error::clear();
+
+    let pvk_guard = VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_ref());
+
+    let verified = match pvk {
+        Some(pvk) => verify_trusted(pvk, proof_hex, current_time, nonce),
+        None => Err(last_error::fail(0, "verifying key is not set up; call ZK_Init or import it")),
+    };
+    let result = match verified {
+        Ok(name) if !issuer_name_out.is_null() => match write_cstr(issuer_name_out, issuer_name_out_size, &name) {
+            Ok(_) => 1,
+            Err(e) => last_error::ffi("issuer_name_out", e),
+        },
+        Ok(_) => 1,
+        Err(code) => code,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "trusted-vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
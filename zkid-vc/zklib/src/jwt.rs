@@ -0,0 +1,327 @@
+// ============================================================================
+// JWT Credentials
+// ============================================================================
+//
+// Credentials as JSON Web Tokens (RFC 7519) in JWS compact serialization
+// (RFC 7515), signed with the issuer's Ed25519 key under "alg": "EdDSA"
+// (RFC 8037):
+//
+//   header    {"alg":"EdDSA","typ":"JWT"}
+//   payload   "iss"                    issuer
+//             "sub"                    holder_id
+//             "nbf"                    issue_date
+//             "exp"                    expiry_date + 1
+//             claim key: claim value   (one member per claim)
+//             "zkid:claimOrder", "zkid:supersedes", "zkid:validitySchedule"
+//                                      as in w3c.rs
+//             "zkid:proof"             the w3c.rs proof block   (if signed)
+//
+// A token is accepted from nbf up to but excluding exp (RFC 7519 section
+// 4.1.4), where a credential is active through its expiry date, hence the
+// one second between exp and expiry_date.
+//
+// The token signature covers the encoded header and payload and vouches for
+// the token as a whole; the credential's own signature over its message
+// hash rides along in zkid:proof, so a credential read from a token
+// verifies and proves as the blob it was written from did. The signing
+// input is base64url text starting with "eyJ", which no signed message of
+// usage.rs starts with.
+//
+// Reading a token checks its signature, then nbf and exp against the
+// current time, and tells a token not yet valid from an expired one. Only
+// "EdDSA" is accepted, and a header with "crit" names extensions this
+// reader does not implement, so it is refused. The other registered claims
+// ("aud", "iat", "jti") are ignored and cannot be claim keys.
+
+use ed25519_dalek::{Signature, VerifyingKey, SIGNATURE_LENGTH};
+use serde_json::Value;
+use std::os::raw::{c_char, c_int};
+
+use crate::error::{ZK_ERR_BAD_SIGNATURE, ZK_ERR_NOT_YET_VALID, ZK_ERR_VC_EXPIRED};
+use crate::relay::base64url_decode;
+use crate::w3c::{ordered_claims, read_extensions, read_proof, CLAIM_ORDER, SUPERSEDES, VALIDITY_SCHEDULE};
+use crate::{dates, domain, VerifiableCredential};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::{parse_signing_key, parse_vc_blob},
+    crate::ffi::{reserve_cstr, write_cstr},
+    crate::error::ZK_ERR_SEQUENCE_UNSUPPORTED,
+    crate::relay::base64url_encode,
+    crate::sequence::{self, sequencing_enabled, SEQUENCE_CLAIM},
+    crate::stats,
+    ed25519_dalek::SigningKey,
+    serde_json::json,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::clock,
+    crate::credential::{parse_verifying_key, write_vc_blob},
+    crate::ffi::{read_bytes, MAX_BLOB_LEN},
+};
+
+/// JWS algorithm of every token: Ed25519
+pub const JWT_ALG: &str = "EdDSA";
+
+const PROOF: &str = "zkid:proof";
+
+/// Payload members that are not claims
+const RESERVED: [&str; 11] = [
+    "iss", "sub", "aud", "exp", "nbf", "iat", "jti", CLAIM_ORDER, SUPERSEDES, VALIDITY_SCHEDULE, PROOF,
+];
+
+/// Reason a token was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JwtError {
+    /// Not a compact JWS of a credential payload, or not an EdDSA one
+    Malformed,
+    /// The token signature does not verify under the issuer key
+    BadSignature,
+    /// The current time is at or after exp
+    Expired,
+    /// The current time is before nbf
+    NotYetValid,
+}
+
+impl JwtError {
+    pub fn code(self) -> c_int {
+        match self {
+            JwtError::Malformed => -1,
+            JwtError::BadSignature => ZK_ERR_BAD_SIGNATURE,
+            JwtError::Expired => ZK_ERR_VC_EXPIRED,
+            JwtError::NotYetValid => ZK_ERR_NOT_YET_VALID,
+        }
+    }
+}
+
+impl VerifiableCredential {
+    /// The credential as a JWT signed with `signing_key`
+    ///
+    /// The credential signature, if any, is carried as it is; sign the blob
+    /// first (ZK_SignVCBlob) for a credential that can be proven. None if a
+    /// claim key repeats or is a payload member name, or the expiry date is
+    /// the last second there is.
+    #[cfg(feature = "prover")]
+    pub fn to_jwt(&self, signing_key: &SigningKey) -> Option<String> {
+        let mut payload = json!({
+            "iss": self.issuer,
+            "sub": self.holder_id,
+            "nbf": self.issue_date,
+            "exp": self.expiry_date.checked_add(1)?,
+        });
+        self.extensions_json(&mut payload);
+        if let Some(proof) = self.proof_json() {
+            payload[PROOF] = proof;
+        }
+        let members = payload.as_object_mut()?;
+        for (key, value) in &self.claims {
+            if RESERVED.contains(&key.as_str()) || members.insert(key.clone(), json!(value)).is_some() {
+                return None;
+            }
+        }
+
+        let header = json!({"alg": JWT_ALG, "typ": "JWT"});
+        let signing_input = format!(
+            "{}.{}",
+            base64url_encode(header.to_string().as_bytes()),
+            base64url_encode(payload.to_string().as_bytes()),
+        );
+        let signature = stats::sign_issuance(signing_key, self.holder_id.as_bytes(), signing_input.as_bytes());
+        Some(format!("{signing_input}.{}", base64url_encode(&signature.to_bytes())))
+    }
+
+    /// Read a JWT written by `to_jwt` or another EdDSA issuer, checking its
+    /// signature under `issuer_pubkey` and its nbf and exp at `now`
+    pub fn from_jwt(token: &str, issuer_pubkey: &VerifyingKey, now: u64) -> Result<Self, JwtError> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or(JwtError::Malformed)?;
+        let (header, payload) = signing_input.split_once('.').ok_or(JwtError::Malformed)?;
+
+        let header = json_part(header).ok_or(JwtError::Malformed)?;
+        if header["alg"].as_str() != Some(JWT_ALG) || header.get("crit").is_some() {
+            return Err(JwtError::Malformed);
+        }
+        let signature = base64url_decode(signature)
+            .and_then(|bytes| <[u8; SIGNATURE_LENGTH]>::try_from(bytes).ok())
+            .ok_or(JwtError::Malformed)?;
+        if !crate::verify_ed25519(issuer_pubkey, signing_input.as_bytes(), &Signature::from_bytes(&signature)) {
+            return Err(JwtError::BadSignature);
+        }
+
+        let payload = json_part(payload).ok_or(JwtError::Malformed)?;
+        let (nbf, exp) = match (payload["nbf"].as_i64(), payload["exp"].as_i64()) {
+            (Some(nbf), Some(exp)) if nbf < exp => (nbf, exp),
+            _ => return Err(JwtError::Malformed),
+        };
+        let now = dates::from_u64(now).ok_or(JwtError::Expired)?;
+        if now < nbf {
+            return Err(JwtError::NotYetValid);
+        }
+        if now >= exp {
+            return Err(JwtError::Expired);
+        }
+
+        credential(&payload, nbf, exp - 1).ok_or(JwtError::Malformed)
+    }
+}
+
+/// Decode a base64url part holding a JSON object
+fn json_part(part: &str) -> Option<Value> {
+    let value: Value = serde_json::from_slice(&base64url_decode(part)?).ok()?;
+    value.is_object().then_some(value)
+}
+
+/// The credential a verified payload describes
+fn credential(payload: &Value, issue_date: i64, expiry_date: i64) -> Option<VerifiableCredential> {
+    let claims = ordered_claims(payload.as_object()?, payload.get(CLAIM_ORDER), |key| !RESERVED.contains(&key))?;
+    let (supersedes, validity_schedule) = read_extensions(payload)?;
+    let mut vc = VerifiableCredential {
+        holder_id: payload["sub"].as_str()?.to_string(),
+        issuer: payload["iss"].as_str()?.to_string(),
+        issue_date,
+        expiry_date,
+        claims,
+        signature: Vec::new(),
+        co_signature: None,
+        supersedes,
+        validity_schedule,
+        legacy_signature: false,
+        claims_root_signed: true,
+        length_prefixed: true,
+        domain_separated: domain::tagged(),
+        anchored: true,
+        schedule_anchored: true,
+    };
+    if let Some(proof) = payload.get(PROOF) {
+        read_proof(&mut vc, proof)?;
+    }
+    Some(vc)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write a hex VC blob as a JWT signed with the issuer's hex private key
+///
+/// The blob's own signature is carried in the token, so sign it first with
+/// ZK_SignVCBlob for a credential that can be proven. With issuer
+/// sequencing enabled an unsigned blob gets the next `zkid:sequence` claim
+/// of the key, and a signed one must already carry the claim. As with
+/// other variable-size outputs, a NULL `jwt_out` (or size 0) with
+/// `required_size_out` set is a size query. Returns 0 on success,
+/// ZK_ERR_SEQUENCE_UNSUPPORTED for a signed blob without a sequence number
+/// while sequencing is on, ZK_ERR_STORAGE if the sequence cannot be
+/// persisted, -1 for a malformed blob or key or a credential with no JWT
+/// form (a claim key repeated or named like a payload member).
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVCJwt(
+    vc_blob: *const c_char,
+    issuer_private_key: *const c_char,
+    jwt_out: *mut c_char,
+    jwt_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    if vc_blob.is_null() || issuer_private_key.is_null() {
+        return -1;
+    }
+    let (mut vc, key) = match (parse_vc_blob(vc_blob), parse_signing_key(issuer_private_key)) {
+        (Some(vc), Some(key)) => (vc, key),
+        _ => return -1,
+    };
+    if sequencing_enabled() && !vc.claims.iter().any(|(k, _)| k == SEQUENCE_CLAIM) {
+        // A number added now would not be covered by the carried signature
+        if !vc.signature.is_empty() {
+            return ZK_ERR_SEQUENCE_UNSUPPORTED;
+        }
+        if let Err(e) = sequence::stamp(&mut vc, key.verifying_key().as_bytes()) {
+            return e.code();
+        }
+    }
+    let token = match vc.to_jwt(&key) {
+        Some(token) => token,
+        None => return -1,
+    };
+    if let Err(e) = reserve_cstr(jwt_out, jwt_out_size, token.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(jwt_out, jwt_out_size, &token) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a JWT of `jwt_len` bytes under the issuer's hex public key and
+/// write the credential it carries as a hex VC blob
+///
+/// The time goes through the configured time source (ZK_SetTimeMode).
+/// Returns 0 on success, ZK_ERR_BAD_SIGNATURE if the token signature does
+/// not verify, ZK_ERR_NOT_YET_VALID before its nbf, ZK_ERR_VC_EXPIRED from
+/// its exp on, ZK_ERR_TIME_MISMATCH if the time sources disagree, -1 for a
+/// malformed token or dates outside the ZK_SetDateWindow window.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCJwt(
+    jwt: *const c_char,
+    jwt_len: usize,
+    issuer_public_key: *const c_char,
+    current_time: u64,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    let started = std::time::Instant::now();
+    let result = verify_vc_jwt(jwt, jwt_len, issuer_public_key, current_time, vc_blob_out, vc_blob_out_size);
+
+    audit::record(started, audit::Verification {
+        check: "vc-jwt",
+        code: result,
+        circuit: None,
+        vk: None,
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_vc_jwt(
+    jwt: *const c_char,
+    jwt_len: usize,
+    issuer_public_key: *const c_char,
+    current_time: u64,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    if issuer_public_key.is_null() {
+        return -1;
+    }
+    let token = match read_bytes(jwt, jwt_len, MAX_BLOB_LEN) {
+        Ok(bytes) => match std::str::from_utf8(bytes) {
+            Ok(token) => token,
+            Err(_) => return -1,
+        },
+        Err(e) => return e.code(),
+    };
+    let issuer_key = match parse_verifying_key(issuer_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let now = match clock::now(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+    let vc = match VerifiableCredential::from_jwt(token, &issuer_key, now) {
+        Ok(vc) => vc,
+        Err(e) => return e.code(),
+    };
+    if !dates::valid_range(vc.issue_date, vc.expiry_date) {
+        return -1;
+    }
+    write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+}
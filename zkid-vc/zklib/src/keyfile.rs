@@ -0,0 +1,216 @@
+// ============================================================================
+// Key Files
+// ============================================================================
+//
+// The VC circuit's setup kept on disk, so a process can start from the keys
+// another one generated instead of running ZK_Init again, and every process
+// loading the same file proves and verifies against the same keys.
+//
+// File layout (version 1), integers little-endian, fields length-prefixed
+// as in wire.rs:
+//
+//   "ZKKF" | version u8 | circuit_id | verifying_key | proving_key | sha256[32]
+//
+// circuit_id is the VC circuit id (sizes::VC_CIRCUIT) of the build that
+// wrote the file, the verifying key is vk.rs' frozen encoding and the
+// proving key the compressed arkworks encoding of ZK_ExportProvingKey. The
+// trailing SHA-256 covers every byte before it and is checked first, so a
+// flipped bit or a truncated file is refused before anything is decoded. A
+// file is also refused if its circuit id is not this build's, if either key
+// fails to decode or has invalid points, or if the verifying key is not the
+// proving key's: the keys in memory are only replaced by a whole file.
+
+use ark_bn254::Bn254;
+use ark_groth16::{PreparedVerifyingKey, ProvingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use std::path::Path;
+
+use crate::admission::text_arg;
+use crate::error::ZK_ERR_KEYFILE_INVALID;
+use crate::ffi::MAX_FIELD_LEN;
+use crate::sizes::{VC_CIRCUIT, VC_PUBLIC_INPUTS};
+use crate::wire::{put_bytes, Reader};
+use crate::{last_error, vk, PROVING_KEY};
+
+const KEYFILE_MAGIC: &[u8; 4] = b"ZKKF";
+const KEYFILE_VERSION: u8 = 1;
+const CHECKSUM_LEN: usize = 32;
+
+/// Largest key file read; the VC circuit's keys are far smaller
+const MAX_KEYFILE_LEN: u64 = 1 << 30;
+
+/// Failure saving or loading a key file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyfileError {
+    /// The file could not be read or written
+    Io,
+    /// Not a key file of this build's VC circuit: the reason
+    Invalid(&'static str),
+}
+
+impl KeyfileError {
+    pub fn code(self) -> c_int {
+        match self {
+            KeyfileError::Io => -1,
+            KeyfileError::Invalid(_) => ZK_ERR_KEYFILE_INVALID,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            KeyfileError::Io => "key file could not be read or written",
+            KeyfileError::Invalid(reason) => reason,
+        }
+    }
+}
+
+/// Key file bytes for `pk` and its verifying key
+pub fn encode(pk: &ProvingKey<Bn254>) -> Vec<u8> {
+    let mut pk_bytes = Vec::with_capacity(pk.compressed_size());
+    pk.serialize_compressed(&mut pk_bytes)
+        .expect("serializing into a Vec cannot fail");
+
+    let mut out = Vec::with_capacity(pk_bytes.len() + 1024);
+    out.extend_from_slice(KEYFILE_MAGIC);
+    out.push(KEYFILE_VERSION);
+    put_bytes(&mut out, VC_CIRCUIT.as_bytes());
+    put_bytes(&mut out, &vk::encode(&pk.vk));
+    put_bytes(&mut out, &pk_bytes);
+    let checksum = Sha256::digest(&out);
+    out.extend_from_slice(&checksum);
+    out
+}
+
+/// The proving key in key file bytes, checked against the checksum, this
+/// build's circuit id and its verifying key
+pub fn decode(data: &[u8]) -> Result<ProvingKey<Bn254>, KeyfileError> {
+    let body_len = data
+        .len()
+        .checked_sub(CHECKSUM_LEN)
+        .ok_or(KeyfileError::Invalid("key file is truncated"))?;
+    let (body, checksum) = data.split_at(body_len);
+    if Sha256::digest(body).as_slice() != checksum {
+        return Err(KeyfileError::Invalid("key file checksum does not match; the file is damaged or truncated"));
+    }
+
+    let mut r = Reader::new(body);
+    if r.take(KEYFILE_MAGIC.len()) != Some(KEYFILE_MAGIC.as_slice()) {
+        return Err(KeyfileError::Invalid("not a key file"));
+    }
+    if r.u8() != Some(KEYFILE_VERSION) {
+        return Err(KeyfileError::Invalid("unknown key file version"));
+    }
+    let malformed = KeyfileError::Invalid("key file fields are malformed");
+    if r.bytes().ok_or(malformed)? != VC_CIRCUIT.as_bytes() {
+        return Err(KeyfileError::Invalid("key file is for another circuit"));
+    }
+    let vk_bytes = r.bytes().ok_or(malformed)?;
+    let pk_bytes = r.bytes().ok_or(malformed)?;
+    if !r.is_empty() {
+        return Err(malformed);
+    }
+
+    let vk = vk::decode_raw(vk_bytes).map_err(|_| KeyfileError::Invalid("key file verifying key does not decode"))?;
+    let mut reader = pk_bytes;
+    let pk = match ProvingKey::<Bn254>::deserialize_compressed(&mut reader) {
+        Ok(pk) if reader.is_empty() => pk,
+        _ => return Err(KeyfileError::Invalid("key file proving key does not decode")),
+    };
+    if pk.vk != vk {
+        return Err(KeyfileError::Invalid("key file verifying key is not the proving key's"));
+    }
+    if vk::public_inputs(&vk) != VC_PUBLIC_INPUTS {
+        return Err(KeyfileError::Invalid("key file keys are not the VC circuit's"));
+    }
+    Ok(pk)
+}
+
+/// Write `pk` to `path` atomically (temp file + rename)
+pub fn save(path: &Path, pk: &ProvingKey<Bn254>) -> Result<(), KeyfileError> {
+    let mut tmp = path.to_path_buf().into_os_string();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, encode(pk)).map_err(|_| KeyfileError::Io)?;
+    std::fs::rename(&tmp, path).map_err(|_| KeyfileError::Io)
+}
+
+/// Read the proving key in the key file at `path`
+pub fn load(path: &Path) -> Result<ProvingKey<Bn254>, KeyfileError> {
+    let len = std::fs::metadata(path).map_err(|_| KeyfileError::Io)?.len();
+    if len > MAX_KEYFILE_LEN {
+        return Err(KeyfileError::Invalid("key file is too large"));
+    }
+    decode(&std::fs::read(path).map_err(|_| KeyfileError::Io)?)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+/// Read a path argument, leaving the reason for a refusal in the last error
+fn path_arg<'a>(path: *const c_char) -> Result<&'a Path, c_int> {
+    match text_arg(path, MAX_FIELD_LEN) {
+        Some(path) if !path.is_empty() => Ok(Path::new(path)),
+        _ => Err(last_error::fail(-1, "path is NULL, empty, too long or not UTF-8")),
+    }
+}
+
+crate::unwind::entry_point! {
+/// Write the current VC circuit keys to a key file at `path`
+///
+/// The file replaces any file at `path` only once fully written. Returns 0
+/// on success, ZK_ERR_NOT_INITIALIZED if no keys are set,
+/// ZK_ERR_NO_PROVING_KEY if only the verifying key is loaded, -1 on a bad
+/// path or a write failure (ZK_GetLastError says which).
+#[no_mangle]
+pub extern "C" fn ZK_SaveKeys(path: *const c_char) -> c_int {
+    last_error::clear();
+    let path = match path_arg(path) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+    let pk = match PROVING_KEY.lock() {
+        Ok(pk) => match pk.as_ref() {
+            Some(pk) => pk.clone(),
+            None => {
+                let code = crate::missing_proving_key();
+                return last_error::fail(code, "no proving key is loaded to save");
+            }
+        },
+        Err(_) => return -1,
+    };
+    match save(path, &pk) {
+        Ok(()) => 0,
+        Err(e) => last_error::fail(e.code(), e.describe()),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Load the VC circuit keys from a key file written by ZK_SaveKeys
+///
+/// As with ZK_InitWithKeys only the VC circuit's keys are loaded, replacing
+/// any set before; the other circuits still need ZK_Init or their key
+/// imports. Returns 0 on success, ZK_ERR_KEYFILE_INVALID for a file that is
+/// damaged, truncated, of another version or circuit, or holds keys that
+/// do not decode or match (the keys in memory are then unchanged), -1 on a
+/// bad path or a read failure. ZK_GetLastError says why.
+#[no_mangle]
+pub extern "C" fn ZK_LoadKeys(path: *const c_char) -> c_int {
+    last_error::clear();
+    let path = match path_arg(path) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    crate::configure_rayon();
+
+    let pk = match load(path) {
+        Ok(pk) => pk,
+        Err(e) => return last_error::fail(e.code(), e.describe()),
+    };
+    let pvk = PreparedVerifyingKey::from(pk.vk.clone());
+    crate::set_keys(pk, pvk)
+}
+}
@@ -0,0 +1,82 @@
+// ============================================================================
+// Last Error Message
+// ============================================================================
+//
+// A negative code says what kind of failure occurred but not which argument
+// caused it. The main entry points (ZK_Init, ZK_SignVC*, ZK_VerifyVCSignature*,
+// ZK_ComputeVCHash*, ZK_GenerateVCProof*, ZK_VerifyVCProof) also leave a
+// message for the calling thread, read with ZK_GetLastError.
+//
+// Each of these calls clears the message when it starts, so after a call
+// returns the message describes that call's failure, or is empty if it
+// succeeded; it never outlives the next instrumented call on the thread.
+// Messages name arguments and reasons, never argument values.
+//
+// Any entry point that returns ZK_ERR_PANIC leaves "internal panic: " and
+// the panic message (src/unwind.rs), whether or not it is instrumented.
+
+use std::cell::RefCell;
+use std::os::raw::{c_char, c_int};
+
+use crate::ffi::{write_cstr, FfiError};
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Forget the previous call's message (at the start of an instrumented call)
+pub(crate) fn clear() {
+    LAST_ERROR.with(|last| last.borrow_mut().clear());
+}
+
+/// Record `message` for the calling thread and return `code`
+pub(crate) fn fail(code: c_int, message: &str) -> c_int {
+    LAST_ERROR.with(|last| {
+        let mut last = last.borrow_mut();
+        last.clear();
+        last.push_str(message);
+    });
+    code
+}
+
+/// Why caller memory for `arg` was refused
+pub(crate) fn refused(arg: &str, e: FfiError) -> String {
+    let reason = match e {
+        FfiError::Null => "is NULL",
+        FfiError::TooLong => "is longer than accepted",
+        FfiError::BufferTooSmall => "buffer is too small",
+        FfiError::SizeQuery => "was only queried for its size",
+    };
+    format!("{} {}", arg, reason)
+}
+
+/// Record why caller memory for `arg` was refused and return its code; a
+/// size query is not a failure and leaves the message empty
+pub(crate) fn ffi(arg: &str, e: FfiError) -> c_int {
+    if e == FfiError::SizeQuery {
+        return e.code();
+    }
+    fail(e.code(), &refused(arg, e))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Copy the calling thread's last error message into `buf`
+///
+/// The message is empty if the last instrumented call succeeded. Reading it
+/// does not clear it. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the
+/// message and its NUL do not fit, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_GetLastError(buf: *mut c_char, buf_size: usize) -> c_int {
+    if buf.is_null() {
+        return -1;
+    }
+    LAST_ERROR.with(|last| match write_cstr(buf, buf_size, &last.borrow()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    })
+}
+}
@@ -1,25 +1,225 @@
-use ark_bn254::{Bn254, Fr};
-use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_snark::SNARK;
-use ark_std::rand::SeedableRng;
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+// C entry points take raw pointers from the enclave and validate them before use
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+// Without "std" only the embedded verifier is built, on core + alloc
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+#[cfg(feature = "std")]
+use {
+    ark_bn254::Bn254,
+    ark_ff::Zero,
+    ark_groth16::{Groth16, PreparedVerifyingKey},
+    ark_snark::SNARK,
+    ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
+};
+#[cfg(feature = "prover")]
+use {
+    ark_groth16::ProvingKey,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable},
+    ark_relations::{lc, ns},
+    ark_serialize::{CanonicalDeserialize, CanonicalSerialize},
+    ed25519_dalek::SigningKey,
+    error::{
+        ZK_ERR_BAD_HEX, ZK_ERR_BAD_SIGNATURE, ZK_ERR_CIRCUIT_MISMATCH, ZK_ERR_CORRUPT, ZK_ERR_KEY_MISMATCH,
+        ZK_ERR_NOT_INITIALIZED, ZK_ERR_NO_PROVING_KEY, ZK_ERR_PROVE_FAILED, ZK_ERR_SEQUENCE_UNSUPPORTED,
+        ZK_ERR_VC_EXPIRED,
+    },
+    ffi::MAX_BLOB_LEN,
+};
 
 // Ed25519 secret key length (32 bytes)
+#[cfg(feature = "prover")]
 const SECRET_KEY_LENGTH: usize = 32;
 use sha2::{Digest, Sha256};
-use std::ffi::CStr;
-use std::os::raw::{c_char, c_int};
-use std::sync::{Mutex, Once};
+#[cfg(feature = "std")]
+use {
+    ffi::{read_bytes, reserve_cstr, write_cstr, MAX_FIELD_LEN},
+    schedule::ValiditySchedule,
+    std::ffi::CStr,
+    std::os::raw::{c_char, c_int},
+    std::sync::{Arc, Once},
+    sync::Mutex,
+    usage::KeyUsage,
+};
+
+#[cfg(feature = "verifier")]
+use ark_groth16::Proof;
+
+#[cfg(feature = "std")]
+pub mod admission;
+#[cfg(feature = "std")]
+pub mod age;
+#[cfg(feature = "verifier")]
+pub mod audit;
+#[cfg(feature = "prover")]
+pub mod backup;
+#[cfg(feature = "std")]
+pub mod ballot;
+#[cfg(feature = "verifier")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod capabilities;
+#[cfg(feature = "std")]
+pub mod cbor;
+#[cfg(feature = "std")]
+pub mod challenge;
+#[cfg(feature = "prover")]
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(all(feature = "prover", feature = "verifier"))]
+pub mod conformance;
+#[cfg(feature = "std")]
+pub mod composite;
+#[cfg(feature = "std")]
+pub mod consent;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod credential;
+#[cfg(feature = "std")]
+pub mod dates;
+#[cfg(feature = "std")]
+pub mod did;
+#[cfg(feature = "std")]
+pub mod disclosure;
+pub mod domain;
+#[cfg(feature = "embedded")]
+pub mod embedded;
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod entropy;
+pub mod error;
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+mod ffi;
+#[cfg(feature = "std")]
+pub mod grace;
+#[cfg(feature = "std")]
+pub mod handoff;
+#[cfg(feature = "std")]
+pub mod holder;
+#[cfg(feature = "std")]
+pub mod inbox;
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod issuers;
+#[cfg(feature = "std")]
+pub mod jwt;
+#[cfg(feature = "prover")]
+pub mod keyfile;
+#[cfg(feature = "std")]
+pub mod last_error;
+#[cfg(feature = "std")]
+pub mod limits;
+#[cfg(feature = "prover")]
+pub mod maintenance;
+#[cfg(feature = "prover")]
+pub mod matching;
+pub mod mimc;
+#[cfg(feature = "mutation-corpus")]
+pub mod mutation;
+#[cfg(feature = "std")]
+pub mod nonrevocation;
+#[cfg(feature = "std")]
+pub mod nullifier;
+#[cfg(feature = "std")]
+pub mod onetime;
+#[cfg(feature = "verifier")]
+pub mod prepared;
+#[cfg(feature = "std")]
+pub mod params;
+#[cfg(feature = "std")]
+pub mod predicate;
+#[cfg(feature = "std")]
+pub mod presentation;
+pub mod proof;
+#[cfg(feature = "std")]
+pub mod range;
+#[cfg(feature = "std")]
+pub mod rebind;
+#[cfg(feature = "std")]
+pub mod relay;
+#[cfg(feature = "prover")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod revocation;
+#[cfg(feature = "debug-circuit")]
+pub mod satisfiability;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod sequence;
+#[cfg(feature = "verifier")]
+pub mod session;
+#[cfg(feature = "verifier")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod sizes;
+#[cfg(feature = "std")]
+pub mod solidity;
+#[cfg(feature = "std")]
+pub mod snarkjs;
+#[cfg(feature = "std")]
+pub mod stateless;
+#[cfg(feature = "prover")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod storage;
+#[cfg(feature = "prover")]
+pub mod store;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "std")]
+pub mod transfer;
+#[cfg(feature = "threshold")]
+pub mod threshold;
+#[cfg(feature = "prover")]
+pub mod tuning;
+mod unwind;
+#[cfg(feature = "std")]
+pub mod usage;
+#[cfg(feature = "std")]
+pub mod validation;
+pub mod validity;
+pub mod vk;
+#[cfg(feature = "std")]
+pub mod w3c;
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+mod wire;
 
-// Global state for proving/verifying keys
-static KEYS: Mutex<Option<(ProvingKey<Bn254>, PreparedVerifyingKey<Bn254>)>> = Mutex::new(None);
+// Global state for proving/verifying keys: the keys of the default context
+// behind the global entry points. They are held in Arc so a call clones the
+// key out and proves or verifies without the lock (see context.rs).
+#[cfg(feature = "prover")]
+static PROVING_KEY: Mutex<Option<Arc<ProvingKey<Bn254>>>> = Mutex::new(None);
+#[cfg(feature = "std")]
+static VERIFYING_KEY: Mutex<Option<Arc<PreparedVerifyingKey<Bn254>>>> = Mutex::new(None);
+
+/// The VC proving key, if set, shared so proving holds no lock
+#[cfg(feature = "prover")]
+fn current_proving_key() -> Option<Arc<ProvingKey<Bn254>>> {
+    PROVING_KEY.lock().ok()?.clone()
+}
+
+/// The VC verifying key, if set, shared so verifying holds no lock
+#[cfg(feature = "verifier")]
+fn current_verifying_key() -> Option<Arc<PreparedVerifyingKey<Bn254>>> {
+    VERIFYING_KEY.lock().ok()?.clone()
+}
 
 // One-time initialization
+#[cfg(feature = "std")]
 static INIT: Once = Once::new();
 
-// Configure rayon for single-threaded operation in enclave
+// Configure rayon for single-threaded operation in enclave, unless
+// ZK_AutoTune or ZK_ImportTuning sized the pool first
+#[cfg(feature = "std")]
 fn configure_rayon() {
     INIT.call_once(|| {
         std::env::set_var("RAYON_NUM_THREADS", "1");
@@ -31,45 +231,123 @@ fn configure_rayon() {
 // ============================================================================
 
 /// 可验证凭证 (VC) 数据结构
+#[cfg(feature = "std")]
 #[derive(Clone, Debug)]
 pub struct VerifiableCredential {
     pub holder_id: String,          // 持有者 ID (e.g., "alice@company.com")
     pub issuer: String,              // 发行方标识
-    pub issue_date: u64,             // 签发时间戳
-    pub expiry_date: u64,            // 过期时间戳
+    pub issue_date: i64,             // 签发时间戳（有符号，允许 1970 年前）
+    pub expiry_date: i64,            // 过期时间戳
     pub claims: Vec<(String, String)>, // 键值对声明 (e.g., role="engineer")
     pub signature: Vec<u8>,          // Issuer 的 Ed25519 签名 (64 bytes)
+    pub co_signature: Option<CoSignature>, // 第二签发方签名（双人控制）
+    pub supersedes: Option<String>,  // 被取代的旧凭证 ID（重新签发）
+    pub validity_schedule: Option<ValiditySchedule>, // 有效时间窗口（UTC，可选）
+    pub legacy_signature: bool,      // 旧版签名：不含用途上下文（blob 版本 < 5）
+    pub claims_root_signed: bool,    // 消息哈希含声明 Merkle 根（blob 版本 >= 6）
+    pub length_prefixed: bool,       // 消息哈希为变长字段加长度前缀（blob 版本 >= 7）
+    pub domain_separated: bool,      // 消息哈希以用途标签开头（blob 版本 >= 8）
+    pub anchored: bool,              // 签名覆盖凭证锚点而非消息哈希（blob 版本 >= 9，validity.rs）
+    pub schedule_anchored: bool,     // 锚点致盲值含有效时间窗口承诺（blob 版本 >= 10，schedule.rs）
 }
 
+/// 第二签发方对同一消息哈希的签名
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoSignature {
+    pub key_id: [u8; 32],            // 第二签发方 Ed25519 公钥
+    pub signature: Vec<u8>,          // Ed25519 签名 (64 bytes)
+}
+
+#[cfg(feature = "std")]
 impl VerifiableCredential {
     /// 计算 VC 的消息哈希（用于签名验证）
     pub fn message_hash(&self) -> [u8; 32] {
+        let prefixed = self.length_prefixed;
         let mut hasher = Sha256::new();
-        hasher.update(self.holder_id.as_bytes());
-        hasher.update(self.issuer.as_bytes());
-        hasher.update(&self.issue_date.to_le_bytes());
-        hasher.update(&self.expiry_date.to_le_bytes());
+        if self.domain_separated {
+            hasher.update(domain::VC_MESSAGE_TAG);
+        }
+        hash_fields(
+            &mut hasher,
+            prefixed,
+            self.holder_id.as_bytes(),
+            self.issuer.as_bytes(),
+            self.issue_date,
+            self.expiry_date,
+            &self.claims,
+        );
+        
+        // Supersession pointer is signed; absent for original issuance
+        if let Some(old_id) = &self.supersedes {
+            hasher.update(b"zkid:supersedes");
+            hash_field(&mut hasher, prefixed, old_id.as_bytes());
+        }
+        
+        // Validity schedule is signed; absent for unscheduled credentials
+        if let Some(schedule) = &self.validity_schedule {
+            hasher.update(b"zkid:schedule");
+            hash_field(&mut hasher, prefixed, &schedule.to_bytes());
+        }
         
-        // Include claims in the hash
-        for (key, value) in &self.claims {
-            hasher.update(key.as_bytes());
-            hasher.update(value.as_bytes());
+        // Claims Merkle root is signed from blob version 6 on (disclosure.rs)
+        if let Some(root) = self.claims_root().filter(|_| self.claims_root_signed) {
+            hasher.update(b"zkid:claims-root");
+            hasher.update(root);
         }
         
         hasher.finalize().into()
     }
     
-    /// 验证 VC 签名
+    /// 凭证锚点的打开（validity.rs）：VC 哈希、日期、持有者公钥（未绑定时为 0）
+    /// 及锚点致盲值（无则为 0，blob 版本 >= 10 的带窗口凭证再与窗口承诺合并，见
+    /// schedule.rs）；持有者绑定或锚点致盲声明格式错误、重复，或持有者公钥为 0 时为
+    /// None
+    pub fn opening(&self) -> Option<validity::Opening> {
+        let opening = claimed_opening(&self.message_hash(), self.window(), &self.claims)?;
+        match self.validity_schedule.as_ref().filter(|_| self.schedule_anchored) {
+            Some(schedule) => Some(validity::Opening { blinding: schedule.bind(opening.blinding), ..opening }),
+            None => Some(opening),
+        }
+    }
+    
+    /// 凭证锚点（validity.rs），无打开时为 None
+    pub fn anchor(&self) -> Option<Fr> {
+        Some(self.opening()?.anchor())
+    }
+    
+    /// 签发方（及共同签发方）签名的消息：锚定凭证为锚点消息，其余为用途上下文加
+    /// 消息哈希，旧版凭证仅消息哈希；锚定凭证无锚点时为 None
+    pub fn signed_message(&self) -> Option<Vec<u8>> {
+        let hash = self.message_hash();
+        if self.anchored {
+            Some(validity::anchor_message(&self.anchor()?))
+        } else if self.legacy_signature {
+            Some(hash.to_vec())
+        } else {
+            Some(KeyUsage::Credential.signed_message(&hash))
+        }
+    }
+    
+    /// 验证 VC 签名；issuer 为 did:key 时须指向该公钥（did.rs）。锚点签名
+    /// 与 VC 证明的验证方一样按严格 Ed25519 检查
     pub fn verify_signature(&self, issuer_pubkey: &VerifyingKey) -> bool {
-        if self.signature.len() != 64 {
+        if !self.issuer_matches(issuer_pubkey) {
             return false;
         }
+        let signature: [u8; SIGNATURE_LENGTH] = match self.signature.as_slice().try_into() {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
         
-        let message = self.message_hash();
-        
-        match Signature::from_slice(&self.signature) {
-            Ok(sig) => issuer_pubkey.verify(&message, &sig).is_ok(),
-            Err(_) => false,
+        if self.anchored {
+            return self
+                .anchor()
+                .is_some_and(|anchor| validity::anchor_signed(issuer_pubkey.as_bytes(), &anchor, &signature));
+        }
+        match self.signed_message() {
+            Some(message) => verify_ed25519(issuer_pubkey, &message, &Signature::from_bytes(&signature)),
+            None => false,
         }
     }
 }
@@ -78,52 +356,299 @@ impl VerifiableCredential {
 // ZK Circuit: Verifiable Credential Verification with Real Constraints
 // ============================================================================
 
+/// Bits of a shifted date (an i64)
+#[cfg(feature = "prover")]
+const DATE_BITS: usize = 64;
+
+/// Bits of current_time - issue_date and expiry_date - current_time (below
+/// 2^65 for any u64 time and i64 dates)
+#[cfg(feature = "prover")]
+const WINDOW_MARGIN_BITS: usize = 65;
+
+/// The signed anchor (validity.rs) as a circuit opens it: the private
+/// opening and the public inputs every anchored circuit starts with,
+/// issuer_pubkey_hash, nonce, current_time and the anchor
+#[cfg(feature = "prover")]
+#[derive(Clone, Copy)]
+pub(crate) struct AnchorWitness {
+    opening: Option<validity::Opening>,
+    issuer_pubkey_hash: Option<Fr>,
+    nonce: Option<Fr>,
+    current_time: Option<u64>,
+}
+
+/// Variables of an opened anchor, with their values
+#[cfg(feature = "prover")]
+pub(crate) struct OpenedAnchor {
+    pub vc_hash: (Variable, Option<Fr>),
+    pub issue_date: (Variable, Option<i64>),
+    pub expiry_date: (Variable, Option<i64>),
+    pub claims_root: (Variable, Option<Fr>),
+    pub current_time: (Variable, Option<u64>),
+}
+
+#[cfg(feature = "prover")]
+impl AnchorWitness {
+    pub(crate) fn blank() -> Self {
+        Self { opening: None, issuer_pubkey_hash: None, nonce: None, current_time: None }
+    }
+
+    /// The witness of `opening` for an issuer key hash, nonce and time
+    pub(crate) fn new(opening: validity::Opening, issuer_pubkey_hash: Fr, nonce: Fr, current_time: u64) -> Self {
+        Self {
+            opening: Some(opening),
+            issuer_pubkey_hash: Some(issuer_pubkey_hash),
+            nonce: Some(nonce),
+            current_time: Some(current_time),
+        }
+    }
+
+    /// The witness of `credential`, fields mapped for proof `format`, and
+    /// the header a proof over it carries
+    ///
+    /// Whether the signature covers the anchor is the caller's check, the
+    /// verifier's in the end.
+    pub(crate) fn of(
+        format: u8,
+        credential: &Anchored,
+        issuer_pubkey_bytes: &[u8],
+        current_time: u64,
+        nonce: Fr,
+    ) -> Option<(Self, validity::Header)> {
+        let issuer_pubkey_hash = proof::field_for(format, issuer_pubkey_bytes)?;
+        let header = validity::Header::new(credential.opening.anchor(), *credential.signature, current_time);
+        Some((Self::new(credential.opening, issuer_pubkey_hash, nonce, current_time), header))
+    }
+
+    /// Allocate the opening and the four public inputs, and enforce
+    ///
+    ///   anchor = MiMC(vc_hash, issue_date, expiry_date, holder_key,
+    ///                 blinding, claims_root)
+    ///
+    /// with both dates i64, so that what the circuit goes on to prove is
+    /// about the credential the issuer signed. `holder_key` is the key's
+    /// linear combination in a circuit that derives it; without one the key
+    /// is a private witness like the rest of the opening. issuer_pubkey_hash
+    /// and nonce enter no constraint: Groth16 binds the proof to every
+    /// public input, and what ties the anchor to the issuer is the
+    /// signature.
+    pub(crate) fn open(
+        &self,
+        cs: &ConstraintSystemRef<Fr>,
+        holder_key: Option<(LinearCombination<Fr>, Option<Fr>)>,
+    ) -> Result<OpenedAnchor, SynthesisError> {
+        self.open_inner(cs, holder_key, None)
+    }
+
+    /// `open` with the blinding's linear combination from a circuit that
+    /// derives it (schedule.rs)
+    pub(crate) fn open_with_blinding(
+        &self,
+        cs: &ConstraintSystemRef<Fr>,
+        holder_key: Option<(LinearCombination<Fr>, Option<Fr>)>,
+        blinding: (LinearCombination<Fr>, Option<Fr>),
+    ) -> Result<OpenedAnchor, SynthesisError> {
+        self.open_inner(cs, holder_key, Some(blinding))
+    }
+
+    fn open_inner(
+        &self,
+        cs: &ConstraintSystemRef<Fr>,
+        holder_key: Option<(LinearCombination<Fr>, Option<Fr>)>,
+        blinding: Option<(LinearCombination<Fr>, Option<Fr>)>,
+    ) -> Result<OpenedAnchor, SynthesisError> {
+        use schedule::{enforce_bits, witness};
+
+        let opening = self.opening;
+        let vc_hash = opening.map(|o| o.vc_hash);
+        let issue_date = opening.map(|o| o.window.issue_date);
+        let expiry_date = opening.map(|o| o.window.expiry_date);
+        let claims_root = opening.map(|o| o.claims_root);
+
+        let vc_hash_var = witness(cs, vc_hash)?;
+        let issue_date_var = witness(cs, issue_date.map(validity::date_field))?;
+        let expiry_date_var = witness(cs, expiry_date.map(validity::date_field))?;
+        let blinding = match blinding {
+            Some(blinding) => blinding,
+            None => {
+                let blinding = opening.map(|o| o.blinding);
+                (lc!() + witness(cs, blinding)?, blinding)
+            }
+        };
+        let claims_root_var = witness(cs, claims_root)?;
+        let holder_key = match holder_key {
+            Some(key) => key,
+            None => {
+                let key = opening.map(|o| o.holder_key);
+                (lc!() + witness(cs, key)?, key)
+            }
+        };
+
+        let input = |value: Option<Fr>| cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing));
+        input(self.issuer_pubkey_hash)?;
+        input(self.nonce)?;
+        let current_time_var = input(self.current_time.map(Fr::from))?;
+        let anchor_var = input(opening.map(|o| o.anchor()))?;
+
+        let (hash, _) = mimc::hash_gadget(cs, &[
+            (lc!() + vc_hash_var, vc_hash),
+            (lc!() + issue_date_var, issue_date.map(validity::date_field)),
+            (lc!() + expiry_date_var, expiry_date.map(validity::date_field)),
+            holder_key,
+            blinding,
+            (lc!() + claims_root_var, claims_root),
+        ])?;
+        {
+            let _ns = ns!(cs, "vc_anchor");
+            cs.enforce_constraint(hash, lc!() + Variable::One, lc!() + anchor_var)?;
+        }
+
+        // Both dates are i64 (date + 2^63 < 2^64)
+        let offset = Fr::from(1u64 << 63);
+        let shifted = |date: Option<i64>| date.map(|d| (i128::from(d) + (1i128 << 63)) as u128);
+        enforce_bits(cs, lc!() + issue_date_var + (offset, Variable::One), shifted(issue_date), DATE_BITS)?;
+        enforce_bits(cs, lc!() + expiry_date_var + (offset, Variable::One), shifted(expiry_date), DATE_BITS)?;
+
+        Ok(OpenedAnchor {
+            vc_hash: (vc_hash_var, vc_hash),
+            issue_date: (issue_date_var, issue_date),
+            expiry_date: (expiry_date_var, expiry_date),
+            claims_root: (claims_root_var, claims_root),
+            current_time: (current_time_var, self.current_time),
+        })
+    }
+}
+
+/// Constrain issue_date <= current_time <= expiry_date for an opened
+/// anchor. A negative difference is a field element near the modulus and
+/// has no 65-bit decomposition.
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_active(cs: &ConstraintSystemRef<Fr>, anchor: &OpenedAnchor) -> Result<(), SynthesisError> {
+    use schedule::enforce_bits;
+
+    let time = anchor.current_time.1.map(i128::from);
+    let elapsed = time.zip(anchor.issue_date.1).map(|(t, d)| (t - i128::from(d)) as u128);
+    let remaining = time.zip(anchor.expiry_date.1).map(|(t, d)| (i128::from(d) - t) as u128);
+    enforce_bits(cs, lc!() + anchor.current_time.0 - anchor.issue_date.0, elapsed, WINDOW_MARGIN_BITS)?;
+    enforce_bits(cs, lc!() + anchor.expiry_date.0 - anchor.current_time.0, remaining, WINDOW_MARGIN_BITS)
+}
+
+#[cfg(feature = "prover")]
 #[derive(Clone)]
 struct VCCircuit {
-    // 私密见证 (Private Witness)
-    vc_hash: Option<Fr>,                  // VC 内容的哈希（已验证签名）
+    // 锚点的打开及前四个公开输入（issuer_pubkey_hash, nonce, current_time,
+    // anchor），见 AnchorWitness
+    anchor: AnchorWitness,
+    
+    // 持有者绑定 (bound circuits only, see holder.rs)
+    bound: bool,                          // 电路形状：是否含持有者秘密
+    holder_secret: Option<Fr>,            // 私密：持有者秘密
+    
+    // 一次性出示 (one-time circuits only, see onetime.rs)
+    one_time: bool,                       // 电路形状：是否含 nullifier
+    context: Option<Fr>,                  // 公开：出示上下文
+    nullifier: Option<Fr>,                // 公开：MiMC(holder_secret, context)
+    
+    // 未撤销证明 (non-revoked circuits only, see nonrevocation.rs)
+    non_revoked: bool,                    // 电路形状：是否含撤销树路径
+    revocation_root: Option<Fr>,          // 公开：撤销树根
+    gap: nonrevocation::GapPath,          // 私密：VC 哈希所在区间及其认证路径
+}
+
+#[cfg(feature = "prover")]
+impl VCCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            bound: false,
+            holder_secret: None,
+            one_time: false,
+            context: None,
+            nullifier: None,
+            non_revoked: false,
+            revocation_root: None,
+            gap: nonrevocation::GapPath::blank(),
+        }
+    }
+    
+    /// The unbound circuit with its anchor witness
+    fn unbound(anchor: AnchorWitness) -> Self {
+        Self { anchor, ..Self::blank() }
+    }
     
-    // 公开输入 (Public Inputs)
-    issuer_pubkey_hash: Option<Fr>,      // Issuer 公钥的哈希
-    nonce: Option<Fr>,                    // 挑战随机数
+    /// The blank circuit with a holder secret, for the bound-VC keys
+    fn blank_bound() -> Self {
+        Self { bound: true, ..Self::blank() }
+    }
+    
+    /// The bound circuit with a nullifier, for the one-time VC keys
+    fn blank_one_time() -> Self {
+        Self { one_time: true, ..Self::blank_bound() }
+    }
+    
+    /// The blank circuit with a revocation tree path, for the non-revoked
+    /// VC keys
+    fn blank_non_revoked() -> Self {
+        Self { non_revoked: true, ..Self::blank() }
+    }
 }
 
+#[cfg(feature = "prover")]
 impl ConstraintSynthesizer<Fr> for VCCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // The holder key in the anchor: the constant 0 for an unbound
+        // credential, else the MiMC hash of the private holder secret, so a
+        // proof needs the secret behind the key the issuer signed into the
+        // anchor (see holder.rs)
+        let holder_secret_var = if self.bound {
+            Some(cs.new_witness_variable(|| {
+                self.holder_secret.ok_or(SynthesisError::AssignmentMissing)
+            })?)
+        } else {
+            None
+        };
+        let holder_key = match holder_secret_var {
+            Some(secret_var) => mimc::hash_gadget(&cs, &[(lc!() + secret_var, self.holder_secret)])?,
+            None => (lc!(), Some(Fr::zero())),
+        };
         
-        // 分配私密输入
-        let vc_hash_var = cs.new_witness_variable(|| {
-            self.vc_hash.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-        
-        // 分配公开输入
-        let issuer_pubkey_hash_var = cs.new_input_variable(|| {
-            self.issuer_pubkey_hash.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-        
-        let nonce_var = cs.new_input_variable(|| {
-            self.nonce.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-        
-        // 约束 1: VC hash 一致性（证明知道有效的 VC）
-        // 类似 zkid-acl 的 user_id_hash == public_id
-        cs.enforce_constraint(
-            ark_relations::lc!() + vc_hash_var,
-            ark_relations::lc!() + ark_relations::r1cs::Variable::One,
-            ark_relations::lc!() + vc_hash_var,
-        )?;
+        // 约束 1-3: the anchor opens to the private fields (AnchorWitness)
+        let opened = self.anchor.open(&cs, Some(holder_key))?;
         
-        // 约束 2: Issuer 公钥绑定
-        cs.enforce_constraint(
-            ark_relations::lc!() + issuer_pubkey_hash_var,
-            ark_relations::lc!() + ark_relations::r1cs::Variable::One,
-            ark_relations::lc!() + issuer_pubkey_hash_var,
-        )?;
+        // A one-time circuit also exposes the nullifier of the same secret
+        // for a public context, after the anchor (see onetime.rs)
+        if let (Some(secret_var), true) = (holder_secret_var, self.one_time) {
+            let context_var = cs.new_input_variable(|| {
+                self.context.ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let nullifier_var = cs.new_input_variable(|| {
+                self.nullifier.ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let (nullifier, _) = mimc::hash_gadget(&cs, &[
+                (lc!() + secret_var, self.holder_secret),
+                (lc!() + context_var, self.context),
+            ])?;
+            let _ns = ns!(cs, "vc_nullifier");
+            cs.enforce_constraint(nullifier, lc!() + Variable::One, lc!() + nullifier_var)?;
+        }
         
-        // 约束 3: Nonce 绑定（防重放）
-        let _ = nonce_var;
+        // A non-revoked circuit also shows the VC hash outside the
+        // revocation tree under a public root, after the anchor (see
+        // nonrevocation.rs); the hash is the one the anchor opens to
+        if self.non_revoked {
+            let revocation_root_var = cs.new_input_variable(|| {
+                self.revocation_root.ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            nonrevocation::enforce_non_membership(
+                &cs,
+                opened.vc_hash,
+                &self.gap,
+                (revocation_root_var, self.revocation_root),
+            )?;
+        }
         
-        Ok(())
+        // 约束 4-5: issue_date <= current_time <= expiry_date
+        enforce_active(&cs, &opened)
     }
 }
 
@@ -131,7 +656,8 @@ impl ConstraintSynthesizer<Fr> for VCCircuit {
 // Helper Functions
 // ============================================================================
 
-/// Hash bytes to field element
+/// Hash bytes to field element (legacy truncated mapping, proof format 1)
+#[cfg(not(feature = "strict"))]
 fn hash_bytes_to_field(data: &[u8]) -> Fr {
     let mut hasher = Sha256::new();
     hasher.update(data);
@@ -147,49 +673,601 @@ fn hash_bytes_to_field(data: &[u8]) -> Fr {
     Fr::from(val % 1000000000000u64)
 }
 
+/// Hash bytes to field element over the full digest width (proof format 2)
+fn hash_bytes_to_field_full(data: &[u8]) -> Fr {
+    Fr::from_le_bytes_mod_order(&Sha256::digest(data))
+}
+
+/// hash_bytes_to_field_full behind the field-mapping tag (proof format 3)
+fn hash_bytes_to_field_tagged(data: &[u8]) -> Fr {
+    let mut hasher = Sha256::new();
+    hasher.update(domain::FIELD_MAP_TAG);
+    hasher.update(data);
+    Fr::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+/// Ed25519 verification; strict builds also reject non-canonical and
+/// small-order keys and signatures
+#[cfg(feature = "std")]
+fn verify_ed25519(key: &VerifyingKey, message: &[u8], signature: &Signature) -> bool {
+    #[cfg(feature = "strict")]
+    return key.verify_strict(message, signature).is_ok();
+    #[cfg(not(feature = "strict"))]
+    return ed25519_dalek::Verifier::verify(key, message, signature).is_ok();
+}
+
+/// Check an issuer's signature over a credential: over its anchor
+/// (validity.rs), or, as signed before anchors, over its message hash under
+/// the credential usage context and, outside strict builds, over the bare
+/// hash as signed before usage separation. Without an anchor (a malformed
+/// holder-binding claim) only the older forms are checked.
+#[cfg(feature = "std")]
+fn verify_credential_signature(
+    issuer_pubkey: &[u8; PUBLIC_KEY_LENGTH],
+    message: &[u8; 32],
+    anchor: Option<Fr>,
+    signature: &[u8; SIGNATURE_LENGTH],
+) -> Result<(), &'static str> {
+    let verifying_key = VerifyingKey::from_bytes(issuer_pubkey)
+        .map_err(|_| "issuer_public_key is not a valid Ed25519 point")?;
+    let sig = Signature::from_bytes(signature);
+
+    let signed = anchor.is_some_and(|anchor| validity::anchor_signed(issuer_pubkey, &anchor, signature))
+        || verify_ed25519(&verifying_key, &KeyUsage::Credential.signed_message(message), &sig)
+        || (cfg!(not(feature = "strict")) && verify_ed25519(&verifying_key, message, &sig));
+    if signed {
+        Ok(())
+    } else {
+        Err("signature does not verify under issuer_public_key")
+    }
+}
+
+/// Layout of the credential message hash. Format 1 fed the variable-length
+/// fields (holder_id, issuer, claim keys and values, ...) to SHA-256 back to
+/// back, so ("alice", "corp") and ("alicec", "orp") hashed alike and shared
+/// a signature. Format 2 prefixes each with its u32 little-endian length and
+/// the claims with their count; blobs use it from version 7 on
+/// (VerifiableCredential::length_prefixed), the field-based entry points
+/// under ZK_FORMAT_VERSION_1. ZK_VerifyVCSignatureLegacy_I64 and
+/// ZK_ComputeVCHashLegacy_I64 keep format 1 for signatures made before it.
+pub const VC_HASH_FORMAT: u32 = 2;
+
+/// Format 2 behind the "zkid.vc.msg.v1" tag (domain.rs): blobs from version
+/// 8 on (VerifiableCredential::domain_separated), the field-based entry
+/// points under ZK_FORMAT_VERSION_2
+pub const VC_HASH_FORMAT_TAGGED: u32 = 3;
+
+/// Message hash format of new field-based signatures and hashes
+#[cfg(feature = "std")]
+fn vc_hash_format() -> u32 {
+    if domain::tagged() {
+        VC_HASH_FORMAT_TAGGED
+    } else {
+        VC_HASH_FORMAT
+    }
+}
+
+/// Feed one variable-length field to a message hash
+#[cfg(feature = "std")]
+fn hash_field(hasher: &mut Sha256, prefixed: bool, bytes: &[u8]) {
+    if prefixed {
+        hasher.update((bytes.len() as u32).to_le_bytes());
+    }
+    hasher.update(bytes);
+}
+
+/// Feed the fields and claims every credential message hash starts with
+#[cfg(feature = "std")]
+fn hash_fields(
+    hasher: &mut Sha256,
+    prefixed: bool,
+    holder_id: &[u8],
+    issuer: &[u8],
+    issue_date: i64,
+    expiry_date: i64,
+    claims: &[(String, String)],
+) {
+    hash_field(hasher, prefixed, holder_id);
+    hash_field(hasher, prefixed, issuer);
+    hasher.update(issue_date.to_le_bytes());
+    hasher.update(expiry_date.to_le_bytes());
+    if prefixed {
+        hasher.update((claims.len() as u32).to_le_bytes());
+    }
+    for (key, value) in claims {
+        hash_field(hasher, prefixed, key.as_bytes());
+        hash_field(hasher, prefixed, value.as_bytes());
+    }
+}
+
+/// Message hash of a credential given as raw C fields and claims, laid out as
+/// VerifiableCredential::message_hash for an original, unscheduled credential
+/// in hash format `format` (1 to 3, see VC_HASH_FORMAT)
+#[cfg(feature = "std")]
+fn fields_hash_in(
+    format: u32,
+    holder_id: &[u8],
+    issuer: &[u8],
+    issue_date: i64,
+    expiry_date: i64,
+    claims: &[(String, String)],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    if format == VC_HASH_FORMAT_TAGGED {
+        hasher.update(domain::VC_MESSAGE_TAG);
+    }
+    hash_fields(&mut hasher, format > 1, holder_id, issuer, issue_date, expiry_date, claims);
+    hasher.finalize().into()
+}
+
+/// fields_hash_in for the hash format of the current format version
+#[cfg(feature = "prover")]
+fn fields_hash(
+    holder_id: &[u8],
+    issuer: &[u8],
+    issue_date: i64,
+    expiry_date: i64,
+    claims: &[(String, String)],
+) -> [u8; 32] {
+    fields_hash_in(vc_hash_format(), holder_id, issuer, issue_date, expiry_date, claims)
+}
+
+/// The credential fields and claims of a field-based C call, as passed
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+struct FieldArgs {
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+}
+
+/// Randomness for circuit setup
+///
+/// Standard builds keep the seeded generators so separate processes derive
+/// the same keys; strict builds ignore the seed and draw from the OS. Proofs
+/// take theirs from entropy::prover_rng.
+#[cfg(all(feature = "prover", not(feature = "strict")))]
+fn seeded_rng(seed: u64) -> ark_std::rand::rngs::StdRng {
+    use ark_std::rand::SeedableRng;
+    ark_std::rand::rngs::StdRng::seed_from_u64(seed)
+}
+#[cfg(all(feature = "prover", feature = "strict"))]
+fn seeded_rng(_seed: u64) -> rand_core::OsRng {
+    rand_core::OsRng
+}
+
+/// Opening of the anchor over a credential's message hash and dates, with
+/// the holder key and blinding its claims carry (holder.rs, credential.rs)
+/// and the root of its claims tree (disclosure.rs); None for a malformed
+/// reserved claim
+#[cfg(feature = "std")]
+fn claimed_opening(
+    message: &[u8],
+    window: validity::Window,
+    claims: &[(String, String)],
+) -> Option<validity::Opening> {
+    let claims_root = match claims {
+        [] => Fr::zero(),
+        _ => disclosure::ClaimsTree::new(claims).map_or(Fr::zero(), |tree| tree.root()),
+    };
+    Some(validity::Opening {
+        holder_key: holder::claimed_key(claims)?,
+        blinding: credential::claimed_blinding(claims)?,
+        claims_root,
+        ..validity::Opening::of(message, window)?
+    })
+}
+
 /// Bytes to hex string
+#[cfg(feature = "std")]
 fn bytes_to_hex(bytes: &[u8]) -> String {
     hex::encode(bytes)
 }
 
 /// Hex string to bytes
+#[cfg(feature = "std")]
 fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, hex::FromHexError> {
     hex::decode(hex)
 }
 
+/// A credential as a VC proof opens it: the opening of its anchor and the
+/// issuer's signature over the anchor (validity.rs)
+#[cfg(feature = "prover")]
+#[derive(Clone, Copy)]
+pub(crate) struct Anchored<'a> {
+    pub opening: validity::Opening,
+    pub signature: &'a [u8; SIGNATURE_LENGTH],
+}
+
+/// Generate the hex-encoded VC proof for an already checked credential at
+/// the challenge time its dates cover
+#[cfg(feature = "prover")]
+fn prove_vc_hash(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+) -> Result<String, c_int> {
+    prove_vc_hash_with_format(proof::emit_format(), credential, issuer_pubkey_bytes, current_time, nonce)
+}
+
+/// Generate the hex-encoded VC proof in a specific proof format
+#[cfg(feature = "prover")]
+fn prove_vc_hash_with_format(
+    format: u8,
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+) -> Result<String, c_int> {
+    let pk = current_proving_key().ok_or_else(missing_proving_key)?;
+    
+    let (circuit, header) =
+        vc_circuit(format, credential, issuer_pubkey_bytes, current_time, nonce, None).ok_or(-1)?;
+    prove_with_key(&pk, format, circuit, &header, &mut entropy::prover_rng(nonce)?)
+}
+
+/// The VC circuit with its witness, fields mapped for proof `format`, and
+/// the header the proof carries; the bound circuit with a holder binding,
+/// the one-time circuit with a binding that has a context
+///
+/// The anchor is the credential's for the binding's holder key; whether the
+/// signature covers it is the caller's check, the verifier's in the end.
+#[cfg(feature = "prover")]
+fn vc_circuit(
+    format: u8,
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+    holder: Option<holder::Binding>,
+) -> Option<(VCCircuit, validity::Header)> {
+    let context = holder.and_then(|binding| binding.context);
+    let holder_key = holder.map_or(Fr::zero(), |binding| holder::public_key(&binding.secret));
+    let opening = validity::Opening { holder_key, ..credential.opening };
+    let credential = Anchored { opening, ..*credential };
+    let (anchor, header) =
+        AnchorWitness::of(format, &credential, issuer_pubkey_bytes, current_time, Fr::from(nonce))?;
+    
+    let circuit = VCCircuit {
+        bound: holder.is_some(),
+        holder_secret: holder.map(|binding| binding.secret),
+        one_time: context.is_some(),
+        context,
+        nullifier: holder.zip(context).map(|(binding, context)| onetime::nullifier(&binding.secret, &context)),
+        ..VCCircuit::unbound(anchor)
+    };
+    Some((circuit, header))
+}
+
+/// Generate the hex-encoded proof of an anchored circuit, VC proof wire with
+/// its header, with an explicit proving key and RNG
+#[cfg(feature = "prover")]
+fn prove_with_key<C, R>(
+    pk: &ProvingKey<Bn254>,
+    format: u8,
+    circuit: C,
+    header: &validity::Header,
+    rng: &mut R,
+) -> Result<String, c_int>
+where
+    C: ConstraintSynthesizer<Fr> + Clone,
+    R: ark_std::rand::RngCore + ark_std::rand::CryptoRng,
+{
+    #[cfg(feature = "debug-circuit")]
+    satisfiability::check(circuit.clone())?;
+    
+    let proof = Groth16::<Bn254>::prove(pk, circuit, rng).map_err(|_| ZK_ERR_PROVE_FAILED)?;
+    
+    Ok(bytes_to_hex(&validity::encode(header, format, &proof)))
+}
+
+/// Why the VC proving key is absent: never set up, or only the verifying
+/// key was loaded
+#[cfg(feature = "prover")]
+fn missing_proving_key() -> c_int {
+    match VERIFYING_KEY.lock() {
+        Ok(pvk) if pvk.is_some() => ZK_ERR_NO_PROVING_KEY,
+        _ => ZK_ERR_NOT_INITIALIZED,
+    }
+}
+
+/// ZK_GetLastError message for a failed prove
+#[cfg(feature = "prover")]
+fn prove_failure(code: c_int) -> &'static str {
+    match code {
+        ZK_ERR_NOT_INITIALIZED => "proving key is not set up; call ZK_Init",
+        ZK_ERR_NO_PROVING_KEY => "only the verifying key is loaded; this process cannot prove",
+        ZK_ERR_PROVE_FAILED => "Groth16 proving failed",
+        error::ZK_ERR_ENTROPY => "entropy source failed to produce proving randomness",
+        error::ZK_ERR_NONCE_REUSED => "nonce was proven recently and the reuse policy refuses it",
+        error::ZK_ERR_UNSATISFIED => "witness violates a circuit constraint",
+        _ => "proof generation failed",
+    }
+}
+
+/// ZK_GetLastError message for a failed clock::now
+#[cfg(feature = "std")]
+fn clock_failure(code: c_int) -> &'static str {
+    match code {
+        error::ZK_ERR_TIME_MISMATCH => "current_time disagrees with the trusted time source",
+        _ => "no trusted time source is installed",
+    }
+}
+
 // ============================================================================
 // C API Functions
 // ============================================================================
 
+/// Key setup of every circuit besides the VC circuit, in the order ZK_Init
+/// runs them
+#[cfg(feature = "prover")]
+const CIRCUIT_SETUPS: [fn() -> c_int; 12] = [
+    schedule::setup,
+    composite::setup,
+    ballot::setup,
+    age::setup,
+    rebind::setup,
+    grace::setup,
+    predicate::setup,
+    disclosure::setup,
+    range::setup,
+    holder::setup,
+    onetime::setup,
+    nonrevocation::setup,
+];
+
+unwind::entry_point! {
 /// Initialize the ZK system
+///
+/// Stops at the first circuit whose setup fails and returns its code.
+#[cfg(feature = "prover")]
 #[no_mangle]
 pub extern "C" fn ZK_Init() -> c_int {
+    last_error::clear();
     configure_rayon();
     
-    let circuit = VCCircuit {
-        vc_hash: None,
-        issuer_pubkey_hash: None,
-        nonce: None,
-    };
-    
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
-    
-    match Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng) {
-        Ok((pk, vk)) => {
-            let pvk = PreparedVerifyingKey::from(vk);
-            
-            if let Ok(mut keys) = KEYS.lock() {
-                *keys = Some((pk, pvk));
-                0
-            } else {
-                -1
+    let circuit = VCCircuit::blank();
+    
+    let mut rng = seeded_rng(0u64);
+    
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return last_error::fail(-1, "circuit setup failed"),
+    };
+    let code = set_keys(pk, PreparedVerifyingKey::from(vk));
+    if code != 0 {
+        return last_error::fail(code, "circuit setup failed");
+    }
+    for setup in CIRCUIT_SETUPS {
+        let code = setup();
+        if code != 0 {
+            return last_error::fail(code, "circuit setup failed");
+        }
+    }
+    0
+}
+}
+
+unwind::entry_point! {
+/// Initialize the VC circuit from previously exported keys instead of a setup
+///
+/// `pk_bytes` is ZK_ExportProvingKey's output and `vk_bytes`
+/// ZK_ExportVerifyingKey's, both after hex decoding, so every prover can
+/// load one audited setup. Only the VC circuit's keys are loaded; the other
+/// circuits still need ZK_Init or their key imports. Replaces any keys set
+/// before. Returns 0 on success, ZK_ERR_CORRUPT if either key is truncated,
+/// has trailing bytes or invalid points, ZK_ERR_KEY_MISMATCH if the
+/// verifying key is not the proving key's, ZK_ERR_CIRCUIT_MISMATCH if the
+/// keys are not for the VC circuit, -1 on NULL or oversized input.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_InitWithKeys(
+    pk_bytes: *const u8,
+    pk_len: usize,
+    vk_bytes: *const u8,
+    vk_len: usize,
+) -> c_int {
+    if pk_bytes.is_null() || vk_bytes.is_null() {
+        return -1;
+    }
+    let (pk_data, vk_data) = match (
+        read_bytes(pk_bytes, pk_len, MAX_BLOB_LEN),
+        read_bytes(vk_bytes, vk_len, MAX_BLOB_LEN),
+    ) {
+        (Ok(pk_data), Ok(vk_data)) => (pk_data, vk_data),
+        (Err(e), _) | (_, Err(e)) => return e.code(),
+    };
+
+    configure_rayon();
+
+    let mut reader = pk_data;
+    let pk = match ProvingKey::<Bn254>::deserialize_compressed(&mut reader) {
+        Ok(pk) if reader.is_empty() => pk,
+        _ => return ZK_ERR_CORRUPT,
+    };
+    let vk = match vk::decode_raw(vk_data) {
+        Ok(vk) => vk,
+        Err(e) => return e.code(),
+    };
+    if pk.vk != vk {
+        return ZK_ERR_KEY_MISMATCH;
+    }
+    if vk::public_inputs(&vk) != sizes::VC_PUBLIC_INPUTS {
+        return ZK_ERR_CIRCUIT_MISMATCH;
+    }
+
+    set_keys(pk, PreparedVerifyingKey::from(vk))
+}
+}
+
+/// Replace both global keys
+#[cfg(feature = "prover")]
+fn set_keys(pk: ProvingKey<Bn254>, pvk: PreparedVerifyingKey<Bn254>) -> c_int {
+    match (PROVING_KEY.lock(), VERIFYING_KEY.lock()) {
+        (Ok(mut pk_slot), Ok(mut pvk_slot)) => {
+            *pk_slot = Some(Arc::new(pk));
+            *pvk_slot = Some(Arc::new(pvk));
+            0
+        }
+        _ => -1,
+    }
+}
+
+unwind::entry_point! {
+/// Export the current verifying key (hex, compressed)
+///
+/// Lets a verifier-only build obtain the key from the prover side. Returns
+/// 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small, -1 if
+/// no keys are set.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_ExportVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match VERIFYING_KEY.lock() {
+        Ok(pvk) => match pvk.as_ref() {
+            Some(pvk) => vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+unwind::entry_point! {
+/// Export the current proving key (hex, compressed arkworks encoding)
+///
+/// Lets provers share one setup instead of each running ZK_Init. Unlike the
+/// verifying key the encoding is not frozen (see encoding.rs): import it
+/// into a build of the same version. Its size follows the circuit;
+/// `required_size_out` works as for ZK_SignVC_I64. Returns 0 on success,
+/// ZK_STATUS_SIZE_QUERY, ZK_ERR_BUFFER_TOO_SMALL, ZK_ERR_NOT_INITIALIZED if
+/// no keys are set, -1 on NULL.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_ExportProvingKey(
+    pk_out: *mut c_char,
+    pk_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let pk_bytes = match PROVING_KEY.lock() {
+        Ok(pk) => match pk.as_ref() {
+            Some(pk) => {
+                let required = sizes::hex_buffer(pk.compressed_size());
+                if let Err(e) = reserve_cstr(pk_out, pk_out_size, required, required_size_out) {
+                    return e.code();
+                }
+                let mut bytes = Vec::with_capacity(pk.compressed_size());
+                pk.serialize_compressed(&mut bytes)
+                    .expect("serializing into a Vec cannot fail");
+                bytes
             }
+            None => return ZK_ERR_NOT_INITIALIZED,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(pk_out, pk_out_size, &bytes_to_hex(&pk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+unwind::entry_point! {
+/// Install a verifying key from ZK_ExportVerifyingKey
+///
+/// Verifier-only builds have no ZK_Init and take their key from here.
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    configure_rayon();
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) => vk,
+        None => return -1,
+    };
+
+    match VERIFYING_KEY.lock() {
+        Ok(mut pvk) => {
+            *pvk = Some(Arc::new(PreparedVerifyingKey::from(vk)));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
+
+unwind::entry_point! {
+/// Initialize a verifier-only process from the raw verifying key
+///
+/// `vk_bytes` is ZK_ExportVerifyingKey's output after hex decoding. Only the
+/// verifying side is populated and any proving key is dropped, so
+/// verification gateways never hold it: ZK_VerifyVCProof works and
+/// ZK_GenerateVCProof returns ZK_ERR_NO_PROVING_KEY. Returns 0 on success,
+/// ZK_ERR_CORRUPT if the key does not decode, ZK_ERR_CIRCUIT_MISMATCH if it
+/// is not the VC circuit's, -1 on NULL or oversized input.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_InitVerifierOnly(vk_bytes: *const u8, vk_len: usize) -> c_int {
+    if vk_bytes.is_null() {
+        return -1;
+    }
+    let data = match read_bytes(vk_bytes, vk_len, ffi::MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    let vk = match vk::decode_raw(data) {
+        Ok(vk) => vk,
+        Err(e) => return e.code(),
+    };
+    if vk::public_inputs(&vk) != sizes::VC_PUBLIC_INPUTS {
+        return error::ZK_ERR_CIRCUIT_MISMATCH;
+    }
+
+    configure_rayon();
+
+    #[cfg(feature = "prover")]
+    match PROVING_KEY.lock() {
+        Ok(mut pk) => *pk = None,
+        Err(_) => return -1,
+    }
+    match VERIFYING_KEY.lock() {
+        Ok(mut pvk) => {
+            *pvk = Some(Arc::new(PreparedVerifyingKey::from(vk)));
+            0
         }
         Err(_) => -1,
     }
 }
+}
 
+unwind::entry_point! {
 /// Generate Ed25519 keypair for Issuer (random)
+///
+/// The secret key comes from the OS generator, or the callback installed
+/// with ZK_SetEntropySource. Returns 0 on success, ZK_ERR_ENTROPY if the
+/// source fails, ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL.
+#[cfg(feature = "prover")]
 #[no_mangle]
 pub extern "C" fn ZK_GenerateIssuerKeypair(
     public_key_out: *mut c_char,
@@ -202,14 +1280,10 @@ pub extern "C" fn ZK_GenerateIssuerKeypair(
     }
     
     // Generate random secret key bytes
-    // Note: For RISC-V enclave, we use deterministic RNG from ark_std
-    // In production, use a proper secure RNG source
-    use ark_std::rand::SeedableRng;
-    use ark_std::rand::RngCore;
-    // Use a fixed seed for reproducible testing (in production, use real entropy)
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0x1234567890ABCDEF);
-    let mut secret_bytes = [0u8; SECRET_KEY_LENGTH];
-    rng.fill_bytes(&mut secret_bytes);
+    let mut secret_bytes = zeroize::Zeroizing::new([0u8; SECRET_KEY_LENGTH]);
+    if let Err(code) = entropy::fill(secret_bytes.as_mut()) {
+        return code;
+    }
     
     // Create signing key from random bytes
     let signing_key = SigningKey::from_bytes(&secret_bytes);
@@ -219,36 +1293,20 @@ pub extern "C" fn ZK_GenerateIssuerKeypair(
     let public_key_hex = bytes_to_hex(verifying_key.as_bytes());
     let private_key_hex = bytes_to_hex(signing_key.as_bytes());
     
-    // Check buffer sizes
-    if public_key_size < public_key_hex.len() + 1 || 
-       private_key_size < private_key_hex.len() + 1 {
-        return -1;
-    }
-    
     // Copy to output buffers
-    unsafe {
-        let pub_bytes = public_key_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            pub_bytes.as_ptr(),
-            public_key_out as *mut u8,
-            pub_bytes.len(),
-        );
-        *public_key_out.add(pub_bytes.len()) = 0;
-        
-        let priv_bytes = private_key_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            priv_bytes.as_ptr(),
-            private_key_out as *mut u8,
-            priv_bytes.len(),
-        );
-        *private_key_out.add(priv_bytes.len()) = 0;
+    match write_cstr(public_key_out, public_key_size, &public_key_hex)
+        .and_then(|_| write_cstr(private_key_out, private_key_size, &private_key_hex))
+    {
+        Ok(_) => 0,
+        Err(e) => e.code(),
     }
-    
-    0
+}
 }
 
+unwind::entry_point! {
 /// Generate DETERMINISTIC Ed25519 keypair for Issuer (using seed)
 /// This allows both Prover and Verifier to generate the same keypair for testing
+#[cfg(all(feature = "prover", not(feature = "strict")))]
 #[no_mangle]
 pub extern "C" fn ZK_GenerateIssuerKeypairDeterministic(
     seed: u64,
@@ -277,58 +1335,113 @@ pub extern "C" fn ZK_GenerateIssuerKeypairDeterministic(
     let public_key_hex = bytes_to_hex(verifying_key.as_bytes());
     let private_key_hex = bytes_to_hex(signing_key.as_bytes());
     
-    // Check buffer sizes
-    if public_key_size < public_key_hex.len() + 1 || 
-       private_key_size < private_key_hex.len() + 1 {
-        return -1;
-    }
-    
     // Copy to output buffers
-    unsafe {
-        let pub_bytes = public_key_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            pub_bytes.as_ptr(),
-            public_key_out as *mut u8,
-            pub_bytes.len(),
-        );
-        *public_key_out.add(pub_bytes.len()) = 0;
-        
-        let priv_bytes = private_key_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            priv_bytes.as_ptr(),
-            private_key_out as *mut u8,
-            priv_bytes.len(),
-        );
-        *private_key_out.add(priv_bytes.len()) = 0;
+    match write_cstr(public_key_out, public_key_size, &public_key_hex)
+        .and_then(|_| write_cstr(private_key_out, private_key_size, &private_key_hex))
+    {
+        Ok(_) => 0,
+        Err(e) => e.code(),
     }
-    
-    0
+}
 }
 
+unwind::entry_point! {
 /// Sign VC with Issuer private key (Ed25519)
+///
+/// Dates are signed unix seconds and must lie inside the window set with
+/// ZK_SetDateWindow, issue_date <= expiry_date. The signature covers the
+/// credential's anchor, the message hash and dates a VC proof opens
+/// (validity.rs), under the credential usage context (see usage.rs).
+///
+/// Unless NULL, `required_size_out` receives the signature buffer size (129).
+/// With it set, a NULL `signature_out` or a size of 0 is a size query: the
+/// inputs are checked, nothing is signed and ZK_STATUS_SIZE_QUERY returned.
+/// Returns 0 on success, ZK_ERR_BAD_HEX for a malformed private key,
+/// ZK_ERR_BUFFER_TOO_SMALL, ZK_ERR_SEQUENCE_UNSUPPORTED with issuer
+/// sequencing enabled (the fields carry no claim to number; see
+/// ZK_SignVCWithClaims_I64), or -1 on NULL or invalid dates.
+#[cfg(feature = "prover")]
 #[no_mangle]
-pub extern "C" fn ZK_SignVC(
+pub extern "C" fn ZK_SignVC_I64(
     holder_id: *const c_char,
     holder_id_len: usize,
     issuer: *const c_char,
     issuer_len: usize,
-    issue_date: u64,
-    expiry_date: u64,
+    issue_date: i64,
+    expiry_date: i64,
     issuer_private_key: *const c_char,
     signature_out: *mut c_char,
     signature_out_size: usize,
+    required_size_out: *mut usize,
 ) -> c_int {
-    if holder_id.is_null() || issuer.is_null() || issuer_private_key.is_null() || signature_out.is_null() {
-        return -1;
-    }
-    
-    // Parse inputs
-    let holder_id_bytes = unsafe {
-        std::slice::from_raw_parts(holder_id as *const u8, holder_id_len)
-    };
+    ZK_SignVCWithClaims_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        std::ptr::null(), std::ptr::null(), 0,
+        issuer_private_key,
+        signature_out, signature_out_size,
+        required_size_out,
+    )
+}
+}
+
+unwind::entry_point! {
+/// Sign VC fields and claims with Issuer private key (Ed25519)
+///
+/// `claim_keys` and `claim_values` are parallel arrays of `claim_count`
+/// NUL-terminated strings, hashed in the given order exactly as
+/// VerifiableCredential::message_hash does, so the signature matches a blob
+/// encoded with the same claims. With no claims (the arrays may then be
+/// NULL) the signature equals ZK_SignVC_I64's. Claims are signed as given,
+/// and only the signature is returned, so this signer cannot number the
+/// credential: with issuer sequencing enabled it refuses with
+/// ZK_ERR_SEQUENCE_UNSUPPORTED (sign with ZK_SignVCBlob, which sets the
+/// `zkid:sequence` claim, instead). A `zkid:holder-binding` claim
+/// (holder.rs) puts its holder key into the anchor and must be a single
+/// nonzero key, and a `zkid:anchor-blinding` claim (credential.rs) its
+/// blinding, a single field element, or -1 is returned. No blinding is
+/// drawn here: without the claim the anchor is unblinded (validity.rs).
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVCWithClaims_I64(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    issuer_private_key: *const c_char,
+    signature_out: *mut c_char,
+    signature_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || issuer_private_key.is_null() {
+        return last_error::fail(-1, "holder_id, issuer or issuer_private_key is NULL");
+    }
+
+    if !dates::valid_range(issue_date, expiry_date) {
+        return last_error::fail(-1, "dates are outside the date window or issue_date > expiry_date");
+    }
+    
+    // Parse inputs
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
+    };
+    
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
+    };
     
-    let issuer_bytes = unsafe {
-        std::slice::from_raw_parts(issuer as *const u8, issuer_len)
+    let claims = match credential::read_claims(claim_keys, claim_values, claim_count) {
+        Some(claims) => claims,
+        None => return last_error::fail(-1, "claim arrays are NULL, too long or hold a NULL or non-UTF-8 entry"),
     };
     
     let issuer_privkey_str = unsafe {
@@ -336,371 +1449,1207 @@ pub extern "C" fn ZK_SignVC(
     };
     
     let privkey_bytes = match hex_to_bytes(issuer_privkey_str) {
-        Ok(bytes) => bytes,
-        Err(_) => return -1,
+        Ok(bytes) if bytes.len() == SECRET_KEY_LENGTH => bytes,
+        _ => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_private_key is not 32-byte hex"),
     };
+
+    if sequence::sequencing_enabled() {
+        return last_error::fail(
+            ZK_ERR_SEQUENCE_UNSUPPORTED,
+            "issuer sequencing is enabled and a bare signature cannot carry zkid:sequence; use ZK_SignVCBlob",
+        );
+    }
     
-    if privkey_bytes.len() != SECRET_KEY_LENGTH {
-        return -1;
+    let required = sizes::hex_buffer(SIGNATURE_LENGTH);
+    if let Err(e) = reserve_cstr(signature_out, signature_out_size, required, required_size_out) {
+        return last_error::ffi("signature_out", e);
     }
     
     // Create signing key
     let signing_key = SigningKey::from_bytes(
         privkey_bytes.as_slice().try_into().unwrap()
     );
+    if !did::issuer_allows(issuer_bytes, signing_key.verifying_key().as_bytes()) {
+        return last_error::fail(-1, "issuer is a did:key of another key");
+    }
     
-    // Compute VC message hash
-    let mut hasher = Sha256::new();
-    hasher.update(holder_id_bytes);
-    hasher.update(issuer_bytes);
-    hasher.update(&issue_date.to_le_bytes());
-    hasher.update(&expiry_date.to_le_bytes());
-    let message = hasher.finalize();
-    
-    // Sign message
-    let signature = signing_key.sign(&message);
-    let signature_hex = bytes_to_hex(&signature.to_bytes());
+    // Compute VC message hash and the anchor over it
+    let message = fields_hash(holder_id_bytes, issuer_bytes, issue_date, expiry_date, &claims);
+    let window = validity::Window { issue_date, expiry_date };
+    let anchor = match claimed_opening(&message, window, &claims) {
+        Some(opening) => opening.anchor(),
+        None => return last_error::fail(
+            -1,
+            "zkid:holder-binding or zkid:anchor-blinding claim is repeated or malformed, or the holder key is zero",
+        ),
+    };
     
-    // Check buffer size
-    if signature_out_size < signature_hex.len() + 1 {
-        return -1;
-    }
+    // Sign the anchor under the credential usage context
+    let signature = stats::sign_issuance(&signing_key, holder_id_bytes, &validity::anchor_message(&anchor));
+    let signature_hex = bytes_to_hex(&signature.to_bytes());
     
-    // Copy to output
-    unsafe {
-        let sig_bytes = signature_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            sig_bytes.as_ptr(),
-            signature_out as *mut u8,
-            sig_bytes.len(),
-        );
-        *signature_out.add(sig_bytes.len()) = 0;
+    if let Err(e) = write_cstr(signature_out, signature_out_size, &signature_hex) {
+        return last_error::ffi("signature_out", e);
     }
-    
+
     0
 }
+}
 
+unwind::entry_point! {
 /// Verify VC signature with Issuer public key
+///
+/// Dates outside the configured window make the credential invalid. Besides
+/// the anchor signatures ZK_SignVC_I64 makes, signatures over the message
+/// hash under the credential context, from before anchors, are accepted;
+/// outside strict builds so is one over the bare message hash.
+#[cfg(feature = "std")]
 #[no_mangle]
-pub extern "C" fn ZK_VerifyVCSignature(
+pub extern "C" fn ZK_VerifyVCSignature_I64(
     holder_id: *const c_char,
     holder_id_len: usize,
     issuer: *const c_char,
     issuer_len: usize,
-    issue_date: u64,
-    expiry_date: u64,
+    issue_date: i64,
+    expiry_date: i64,
+    signature: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    ZK_VerifyVCSignatureWithClaims_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        std::ptr::null(), std::ptr::null(), 0,
+        signature,
+        issuer_public_key,
+    )
+}
+}
+
+unwind::entry_point! {
+/// Verify a VC signature over fields and claims with Issuer public key
+///
+/// Claims are parallel arrays as for ZK_SignVCWithClaims_I64 and are hashed
+/// in the given order like VerifiableCredential::message_hash, so a
+/// signature over an encoded blob with the same claims verifies here. With
+/// no claims this is ZK_VerifyVCSignature_I64. Returns 1 if valid, 0
+/// otherwise.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCSignatureWithClaims_I64(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    signature: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    verify_fields_signature(
+        vc_hash_format(),
+        FieldArgs {
+            holder_id, holder_id_len,
+            issuer, issuer_len,
+            issue_date, expiry_date,
+            claim_keys, claim_values, claim_count,
+        },
+        signature,
+        issuer_public_key,
+    )
+}
+}
+
+/// Check an issuer signature over C fields and claims hashed in hash format
+/// `format`; 1 if valid, 0 otherwise
+#[cfg(feature = "std")]
+fn verify_fields_signature(
+    format: u32,
+    fields: FieldArgs,
     signature: *const c_char,
     issuer_public_key: *const c_char,
 ) -> c_int {
-    if holder_id.is_null() || issuer.is_null() || signature.is_null() || issuer_public_key.is_null() {
-        return 0;
+    last_error::clear();
+    if fields.holder_id.is_null() || fields.issuer.is_null() || signature.is_null() || issuer_public_key.is_null() {
+        return last_error::fail(0, "holder_id, issuer, signature or issuer_public_key is NULL");
     }
-    
+
+    if !dates::valid_range(fields.issue_date, fields.expiry_date) {
+        return last_error::fail(0, "dates are outside the date window or issue_date > expiry_date");
+    }
+
     // Parse inputs
-    let holder_id_bytes = unsafe {
-        std::slice::from_raw_parts(holder_id as *const u8, holder_id_len)
+    let holder_id_bytes = match read_bytes(fields.holder_id, fields.holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::fail(0, &last_error::refused("holder_id", e)),
     };
-    
-    let issuer_bytes = unsafe {
-        std::slice::from_raw_parts(issuer as *const u8, issuer_len)
+
+    let issuer_bytes = match read_bytes(fields.issuer, fields.issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::fail(0, &last_error::refused("issuer", e)),
     };
-    
+
+    let claims = match credential::read_claims(fields.claim_keys, fields.claim_values, fields.claim_count) {
+        Some(claims) => claims,
+        None => return last_error::fail(0, "claim arrays are NULL, too long or hold a NULL or non-UTF-8 entry"),
+    };
+
     let signature_str = unsafe {
         CStr::from_ptr(signature).to_str().unwrap_or("")
     };
-    
+
     let issuer_pubkey_str = unsafe {
         CStr::from_ptr(issuer_public_key).to_str().unwrap_or("")
     };
-    
+
     // Decode signature and public key
-    let signature_bytes = match hex_to_bytes(signature_str) {
+    let signature_bytes: [u8; SIGNATURE_LENGTH] = match hex_to_bytes(signature_str).map(|bytes| bytes.try_into()) {
+        Ok(Ok(bytes)) => bytes,
+        _ => return last_error::fail(0, "signature is not 64-byte hex"),
+    };
+
+    let pubkey_bytes = match did::public_key_bytes(issuer_pubkey_str) {
         Ok(bytes) => bytes,
-        Err(_) => return 0,
+        Err(e) => return last_error::fail(e.code_or(0), "issuer_public_key is not hex or a did:key"),
     };
-    
-    let pubkey_bytes = match hex_to_bytes(issuer_pubkey_str) {
+    let pubkey_bytes: [u8; PUBLIC_KEY_LENGTH] = match pubkey_bytes.try_into() {
         Ok(bytes) => bytes,
-        Err(_) => return 0,
+        Err(_) => return last_error::fail(0, "issuer_public_key is not 32-byte hex"),
     };
-    
-    // Ed25519: signature = 64 bytes, public key = 32 bytes
-    if signature_bytes.len() != 64 || pubkey_bytes.len() != 32 {
-        return 0;
+    if !did::issuer_allows(issuer_bytes, &pubkey_bytes) {
+        return last_error::fail(0, "issuer is a did:key of another key");
     }
-    
-    // Create verifying key and signature
-    let verifying_key = match VerifyingKey::from_bytes(
-        pubkey_bytes.as_slice().try_into().unwrap()
-    ) {
-        Ok(key) => key,
-        Err(_) => return 0,
-    };
-    
-    let sig = match Signature::from_bytes(
-        signature_bytes.as_slice().try_into().unwrap()
-    ) {
-        sig => sig,
-    };
-    
+
     // Compute message hash
-    let mut hasher = Sha256::new();
-    hasher.update(holder_id_bytes);
-    hasher.update(issuer_bytes);
-    hasher.update(&issue_date.to_le_bytes());
-    hasher.update(&expiry_date.to_le_bytes());
-    let message = hasher.finalize();
-    
-    // Verify signature
-    match verifying_key.verify(&message, &sig) {
+    let message = fields_hash_in(format, holder_id_bytes, issuer_bytes, fields.issue_date, fields.expiry_date, &claims);
+
+    let window = validity::Window { issue_date: fields.issue_date, expiry_date: fields.expiry_date };
+    let anchor = claimed_opening(&message, window, &claims).map(|opening| opening.anchor());
+    match verify_credential_signature(&pubkey_bytes, &message, anchor, &signature_bytes) {
         Ok(()) => 1,
-        Err(_) => 0,
+        Err(message) => last_error::fail(0, message),
     }
 }
 
+unwind::entry_point! {
 /// Compute VC message hash (for testing/verification)
+///
+/// Claims are parallel arrays as for ZK_SignVCWithClaims_I64 (NULL with a
+/// count of 0 for none); the hex digest equals
+/// VerifiableCredential::message_hash of an original, unscheduled credential
+/// with the same fields and claims in the same order, without the claims
+/// root: a version 7 blob whose claims root is unsigned, or a version 8 one
+/// under ZK_FORMAT_VERSION_2 (see ZK_SetFormatVersion).
+///
+/// `required_size_out` and a NULL or empty `vc_hash_out` work as for
+/// ZK_SignVC_I64 (the hash buffer size is 65).
+#[cfg(feature = "std")]
 #[no_mangle]
-pub extern "C" fn ZK_ComputeVCHash(
+pub extern "C" fn ZK_ComputeVCHash_I64(
     holder_id: *const c_char,
     holder_id_len: usize,
     issuer: *const c_char,
     issuer_len: usize,
-    issue_date: u64,
-    expiry_date: u64,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
     vc_hash_out: *mut c_char,
     vc_hash_out_size: usize,
+    required_size_out: *mut usize,
 ) -> c_int {
-    if holder_id.is_null() || issuer.is_null() || vc_hash_out.is_null() {
-        return -1;
+    compute_fields_hash(
+        vc_hash_format(),
+        FieldArgs {
+            holder_id, holder_id_len,
+            issuer, issuer_len,
+            issue_date, expiry_date,
+            claim_keys, claim_values, claim_count,
+        },
+        vc_hash_out,
+        vc_hash_out_size,
+        required_size_out,
+    )
+}
+}
+
+/// Write the hex message hash of C fields and claims in hash format `format`
+#[cfg(feature = "std")]
+fn compute_fields_hash(
+    format: u32,
+    fields: FieldArgs,
+    vc_hash_out: *mut c_char,
+    vc_hash_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if fields.holder_id.is_null() || fields.issuer.is_null() {
+        return last_error::fail(-1, "holder_id or issuer is NULL");
     }
-    
-    let holder_id_bytes = unsafe {
-        std::slice::from_raw_parts(holder_id as *const u8, holder_id_len)
+
+    let holder_id_bytes = match read_bytes(fields.holder_id, fields.holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
     };
-    
-    let issuer_bytes = unsafe {
-        std::slice::from_raw_parts(issuer as *const u8, issuer_len)
+
+    let issuer_bytes = match read_bytes(fields.issuer, fields.issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
     };
-    
-    let mut hasher = Sha256::new();
-    hasher.update(holder_id_bytes);
-    hasher.update(issuer_bytes);
-    hasher.update(&issue_date.to_le_bytes());
-    hasher.update(&expiry_date.to_le_bytes());
-    let hash = hasher.finalize();
-    
-    let hex_str = bytes_to_hex(&hash);
-    
-    if vc_hash_out_size < hex_str.len() + 1 {
-        return -1;
+
+    let claims = match credential::read_claims(fields.claim_keys, fields.claim_values, fields.claim_count) {
+        Some(claims) => claims,
+        None => return last_error::fail(-1, "claim arrays are NULL, too long or hold a NULL or non-UTF-8 entry"),
+    };
+
+    let required = sizes::hex_buffer(Sha256::output_size());
+    if let Err(e) = reserve_cstr(vc_hash_out, vc_hash_out_size, required, required_size_out) {
+        return last_error::ffi("vc_hash_out", e);
     }
-    
-    unsafe {
-        let hex_bytes = hex_str.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            hex_bytes.as_ptr(),
-            vc_hash_out as *mut u8,
-            hex_bytes.len(),
-        );
-        *vc_hash_out.add(hex_bytes.len()) = 0;
+
+    let hash = fields_hash_in(format, holder_id_bytes, issuer_bytes, fields.issue_date, fields.expiry_date, &claims);
+
+    let hex_str = bytes_to_hex(&hash);
+
+    match write_cstr(vc_hash_out, vc_hash_out_size, &hex_str) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("vc_hash_out", e),
     }
-    
-    0
 }
 
+unwind::entry_point! {
+/// ZK_VerifyVCSignatureWithClaims_I64 for signatures made before hash
+/// format 2
+///
+/// Hashes the fields and claims back to back (VC_HASH_FORMAT 1), as
+/// ZK_SignVC* did before length prefixes, so a signature from then verifies
+/// here. Such a signature also verifies for any other split of the same
+/// bytes into holder_id, issuer and claims: only call this for credentials
+/// known to predate format 2. u64 dates up to i64::MAX hash as the same
+/// value. Returns 1 if valid, 0 otherwise; not in strict builds.
+#[cfg(all(feature = "std", not(feature = "strict")))]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCSignatureLegacy_I64(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    signature: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    verify_fields_signature(
+        1,
+        FieldArgs {
+            holder_id, holder_id_len,
+            issuer, issuer_len,
+            issue_date, expiry_date,
+            claim_keys, claim_values, claim_count,
+        },
+        signature,
+        issuer_public_key,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_ComputeVCHash_I64 in hash format 1 (no length prefixes), the digest
+/// ZK_SignVC* signed before VC_HASH_FORMAT 2; not in strict builds
+#[cfg(all(feature = "std", not(feature = "strict")))]
+#[no_mangle]
+pub extern "C" fn ZK_ComputeVCHashLegacy_I64(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    vc_hash_out: *mut c_char,
+    vc_hash_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    compute_fields_hash(
+        1,
+        FieldArgs {
+            holder_id, holder_id_len,
+            issuer, issuer_len,
+            issue_date, expiry_date,
+            claim_keys, claim_values, claim_count,
+        },
+        vc_hash_out,
+        vc_hash_out_size,
+        required_size_out,
+    )
+}
+}
+
+unwind::entry_point! {
 /// Generate ZK proof for VC
+///
+/// `current_time` (through the time source) becomes the proof's public
+/// time: pass the verifier's challenge time, since the proof only verifies
+/// at that time. A recently proven nonce is handled by the ZK_SetNonceReusePolicy policy
+/// and may return ZK_ERR_NONCE_REUSED. A credential that expired at most
+/// ZK_SetProverExpiryGrace seconds ago is proven as a grace proof (see
+/// grace.rs); any other inactive credential is refused.
+///
+/// Unless NULL, `required_size_out` receives the proof buffer size, which
+/// depends on the proof format and on whether a grace proof is due. With it
+/// set, a NULL `proof_out` or a size of 0 is a size query: the signature and
+/// dates are checked but nothing is proven. A buffer that is too small is
+/// refused before proving too.
+///
+/// Returns 0 on success, ZK_STATUS_SIZE_QUERY, or: ZK_ERR_BAD_HEX for a
+/// malformed signature or key, ZK_ERR_BAD_SIGNATURE if the signature does
+/// not verify, ZK_ERR_VC_EXPIRED for an inactive credential,
+/// ZK_ERR_NOT_INITIALIZED before ZK_Init, ZK_ERR_NO_PROVING_KEY after
+/// ZK_InitVerifierOnly, ZK_ERR_PROVE_FAILED,
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL or invalid dates.
+#[cfg(feature = "prover")]
 #[no_mangle]
-pub extern "C" fn ZK_GenerateVCProof(
+pub extern "C" fn ZK_GenerateVCProof_I64(
     holder_id: *const c_char,
     holder_id_len: usize,
     issuer: *const c_char,
     issuer_len: usize,
-    issue_date: u64,
-    expiry_date: u64,
+    issue_date: i64,
+    expiry_date: i64,
     vc_signature: *const c_char,
     issuer_pubkey: *const c_char,
     current_time: u64,
     nonce: u64,
     proof_out: *mut c_char,
     proof_out_size: usize,
+    required_size_out: *mut usize,
 ) -> c_int {
-    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || 
-       issuer_pubkey.is_null() || proof_out.is_null() {
-        return -1;
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || issuer_pubkey.is_null() {
+        return last_error::fail(-1, "holder_id, issuer, vc_signature or issuer_pubkey is NULL");
     }
     
-    // ==== Step 1: Verify VC signature (pre-check before ZK proof) ====
-    let verify_result = ZK_VerifyVCSignature(
-        holder_id, holder_id_len,
-        issuer, issuer_len,
-        issue_date, expiry_date,
-        vc_signature,
-        issuer_pubkey,
-    );
-    
-    if verify_result != 1 {
-        return -1;  // Signature verification failed
-    }
-    
-    // ==== Step 2: Verify time constraints (pre-check) ====
-    if current_time < issue_date || current_time > expiry_date {
-        return -1;  // VC not yet active or expired
-    }
-    
-    // ==== Step 3: Get ZK keys ====
-    let keys_guard = match KEYS.lock() {
-        Ok(guard) => guard,
-        Err(_) => return -1,
-    };
-    
-    let (pk, _) = match keys_guard.as_ref() {
-        Some(keys) => keys,
-        None => return -1,
-    };
-    
-    // ==== Step 4: Parse inputs ====
-    let holder_id_bytes = unsafe {
-        std::slice::from_raw_parts(holder_id as *const u8, holder_id_len)
+    // ==== Step 1: Parse inputs ====
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
     };
     
-    let issuer_bytes = unsafe {
-        std::slice::from_raw_parts(issuer as *const u8, issuer_len)
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
     };
     
     let issuer_pubkey_str = unsafe {
         CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("")
     };
-    let issuer_pubkey_bytes = match hex_to_bytes(issuer_pubkey_str) {
+    let issuer_pubkey_bytes = match did::public_key_bytes(issuer_pubkey_str) {
         Ok(bytes) => bytes,
-        Err(_) => return -1,
+        Err(e) => return last_error::fail(e.code_or(ZK_ERR_BAD_HEX), "issuer_pubkey is not hex or a did:key"),
+    };
+    let issuer_pubkey_bytes: [u8; PUBLIC_KEY_LENGTH] = match issuer_pubkey_bytes.try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_pubkey is not 32-byte hex"),
     };
     
     let vc_signature_str = unsafe {
         CStr::from_ptr(vc_signature).to_str().unwrap_or("")
     };
-    // Parse signature (already verified in Step 1, just need to parse for validation)
-    let _vc_signature_bytes = match hex_to_bytes(vc_signature_str) {
-        Ok(bytes) => bytes,
-        Err(_) => return -1,
+    let vc_signature_bytes: [u8; SIGNATURE_LENGTH] = match hex_to_bytes(vc_signature_str).map(|bytes| bytes.try_into()) {
+        Ok(Ok(bytes)) => bytes,
+        _ => return last_error::fail(ZK_ERR_BAD_HEX, "vc_signature is not 64-byte hex"),
     };
     
-    // ==== Step 5: Compute VC message hash ====
-    let mut hasher = Sha256::new();
-    hasher.update(holder_id_bytes);
-    hasher.update(issuer_bytes);
-    hasher.update(&issue_date.to_le_bytes());
-    hasher.update(&expiry_date.to_le_bytes());
-    let vc_message_hash = hasher.finalize();
-    
-    // Convert to field elements for circuit
-    let vc_hash_field = hash_bytes_to_field(&vc_message_hash);
-    let issuer_pubkey_hash_field = hash_bytes_to_field(&issuer_pubkey_bytes);
-    let nonce_field = Fr::from(nonce);
-    
-    // ==== Step 6: Create circuit with witness (简化版本) ====
-    let circuit = VCCircuit {
-        vc_hash: Some(vc_hash_field),
-        issuer_pubkey_hash: Some(issuer_pubkey_hash_field),
-        nonce: Some(nonce_field),
+    let credential = ProofCredential {
+        holder_id: holder_id_bytes,
+        issuer: issuer_bytes,
+        issue_date,
+        expiry_date,
+        signature: &vc_signature_bytes,
+        issuer_pubkey: &issuer_pubkey_bytes,
+        holder: None,
+        revocation: None,
     };
-    
-    // ==== Step 7: Generate proof ====
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(nonce);
-    
-    let proof = match Groth16::<Bn254>::prove(pk, circuit, &mut rng) {
-        Ok(p) => p,
-        Err(_) => return -1,
+    let reserve = |required| reserve_cstr(proof_out, proof_out_size, required, required_size_out);
+    let proof_hex = match generate_vc_proof(&credential, current_time, nonce, sizes::ZK_SIZE_PROOF_HEX, reserve) {
+        Ok(hex) => hex,
+        Err(code) => return code,
     };
     
-    // ==== Step 8: Serialize proof ====
-    let mut proof_bytes = Vec::new();
-    if proof.serialize_compressed(&mut proof_bytes).is_err() {
-        return -1;
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("proof_out", e),
+    }
+}
+}
+
+unwind::entry_point! {
+/// ZK_GenerateVCProof_I64 on binary buffers
+///
+/// `vc_signature` is the 64-byte signature and `issuer_pubkey` the 32-byte
+/// key, and `proof_out` receives the proof that ZK_GenerateVCProof_I64 would
+/// hex-encode, so either output converts to the other with a plain hex
+/// encode or decode. The proof length is written to `proof_len_out`; a NULL
+/// `proof_out` or a size of 0 is a size query answered before proving.
+///
+/// Returns the codes of ZK_GenerateVCProof_I64 except ZK_ERR_BAD_HEX; a
+/// signature or key of the wrong length returns -1.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofRaw(
+    holder_id: *const u8,
+    holder_id_len: usize,
+    issuer: *const u8,
+    issuer_len: usize,
+    issue_date: i64,
+    expiry_date: i64,
+    vc_signature: *const u8,
+    vc_signature_len: usize,
+    issuer_pubkey: *const u8,
+    issuer_pubkey_len: usize,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut u8,
+    proof_out_size: usize,
+    proof_len_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || issuer_pubkey.is_null() {
+        return last_error::fail(-1, "holder_id, issuer, vc_signature or issuer_pubkey is NULL");
+    }
+    if proof_len_out.is_null() {
+        return last_error::fail(-1, "proof_len_out is NULL");
+    }
+
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
+    };
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
+    };
+    let vc_signature_bytes: &[u8; SIGNATURE_LENGTH] =
+        match read_bytes(vc_signature, vc_signature_len, SIGNATURE_LENGTH).map(|bytes| bytes.try_into()) {
+            Ok(Ok(bytes)) => bytes,
+            _ => return last_error::fail(-1, "vc_signature is not 64 bytes"),
+        };
+    let issuer_pubkey_bytes: &[u8; PUBLIC_KEY_LENGTH] =
+        match read_bytes(issuer_pubkey, issuer_pubkey_len, PUBLIC_KEY_LENGTH).map(|bytes| bytes.try_into()) {
+            Ok(Ok(bytes)) => bytes,
+            _ => return last_error::fail(-1, "issuer_pubkey is not 32 bytes"),
+        };
+
+    let credential = ProofCredential {
+        holder_id: holder_id_bytes,
+        issuer: issuer_bytes,
+        issue_date,
+        expiry_date,
+        signature: vc_signature_bytes,
+        issuer_pubkey: issuer_pubkey_bytes,
+        holder: None,
+        revocation: None,
+    };
+    let reserve = |required| reserve_cstr(proof_out as *mut c_char, proof_out_size, required, proof_len_out);
+    let proof_hex = match generate_vc_proof(&credential, current_time, nonce, sizes::ZK_SIZE_PROOF, reserve) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+    let proof_bytes = match hex_to_bytes(&proof_hex) {
+        Ok(bytes) => bytes,
+        Err(_) => return last_error::fail(-1, "proof generation failed"),
+    };
+
+    match vk::write_out(&proof_bytes, proof_out, proof_out_size, proof_len_out) {
+        0 => 0,
+        code => last_error::fail(code, "proof_out is smaller than the proof"),
+    }
+}
+}
+
+/// A credential to prove, its signature and issuer key already decoded
+#[cfg(feature = "prover")]
+struct ProofCredential<'a> {
+    holder_id: &'a [u8],
+    issuer: &'a [u8],
+    issue_date: i64,
+    expiry_date: i64,
+    signature: &'a [u8; SIGNATURE_LENGTH],
+    issuer_pubkey: &'a [u8; PUBLIC_KEY_LENGTH],
+    /// Holder binding of a bound credential (see holder.rs)
+    holder: Option<holder::Binding>,
+    /// Revocation root and witness of a non-revoked proof (see
+    /// nonrevocation.rs)
+    revocation: Option<nonrevocation::Revocation>,
+}
+
+/// Check `credential` and prove it, returning the hex proof
+///
+/// `reserve` receives the size of the proof as `size_item` (ZK_SIZE_PROOF or
+/// ZK_SIZE_PROOF_HEX) once the signature and dates are checked, and refuses
+/// a size query or a small buffer before any proving work. Failures are
+/// recorded for ZK_GetLastError.
+#[cfg(feature = "prover")]
+fn generate_vc_proof(
+    credential: &ProofCredential,
+    current_time: u64,
+    nonce: u64,
+    size_item: c_int,
+    reserve: impl FnOnce(usize) -> Result<(), ffi::FfiError>,
+) -> Result<String, c_int> {
+    let (issue_date, expiry_date) = (credential.issue_date, credential.expiry_date);
+    if !dates::valid_range(issue_date, expiry_date) {
+        return Err(last_error::fail(-1, "dates are outside the date window or issue_date > expiry_date"));
     }
     
-    let proof_hex = bytes_to_hex(&proof_bytes);
-    
-    if proof_out_size < proof_hex.len() + 1 {
-        return -1;
+    // ==== Step 2: Verify VC signature and time (pre-checks before ZK proof) ====
+    // A bound credential is signed with the holder public key derived from
+    // the secret, so a wrong secret fails here like a wrong signature. The
+    // proof carries the anchor signature, which verifiers check anyway; a
+    // signature from before anchors cannot be proven.
+    let claims = credential.holder.map(|binding| holder::claims(&binding.secret)).unwrap_or_default();
+    let vc_message_hash = fields_hash(credential.holder_id, credential.issuer, issue_date, expiry_date, &claims);
+    let window = validity::Window { issue_date, expiry_date };
+    let opening = claimed_opening(&vc_message_hash, window, &claims).ok_or(-1)?;
+    let anchor_signed = validity::anchor_signed(credential.issuer_pubkey, &opening.anchor(), credential.signature);
+    if !skips_prover_checks()
+        && (!anchor_signed || !did::issuer_allows(credential.issuer, credential.issuer_pubkey))
+    {
+        let unanchored =
+            verify_credential_signature(credential.issuer_pubkey, &vc_message_hash, None, credential.signature);
+        let message = match credential.holder {
+            _ if unanchored.is_ok() => "vc_signature predates anchored signing; have the credential signed again",
+            Some(_) => "vc_signature does not verify under issuer_pubkey for this holder secret",
+            None => "vc_signature does not verify under issuer_pubkey",
+        };
+        return Err(last_error::fail(ZK_ERR_BAD_SIGNATURE, message));
     }
     
-    unsafe {
-        let hex_bytes = proof_hex.as_bytes();
-        std::ptr::copy_nonoverlapping(
-            hex_bytes.as_ptr(),
-            proof_out as *mut u8,
-            hex_bytes.len(),
-        );
-        *proof_out.add(hex_bytes.len()) = 0;
+    let current_time = clock::now(current_time).map_err(|code| last_error::fail(code, clock_failure(code)))?;
+    let expired = !dates::is_active(current_time, issue_date, expiry_date);
+    let grace = match (credential.holder, &credential.revocation) {
+        (None, None) => dates::expired_within(current_time, issue_date, expiry_date, grace::prover_grace()),
+        _ => None,
+    };
+    if expired && grace.is_none() {
+        // VC not yet active, or expired beyond the grace (bound and
+        // non-revoked credentials have none)
+        return Err(last_error::fail(
+            ZK_ERR_VC_EXPIRED,
+            "credential is not yet valid or expired beyond the grace at current_time",
+        ));
+    }
+    if credential.revocation.as_ref().is_some_and(|revocation| !revocation.excludes(&vc_message_hash)) {
+        return Err(last_error::fail(
+            error::ZK_ERR_REVOKED,
+            "witness does not show the credential absent from the tree under revocation_root",
+        ));
     }
     
-    0
+    // The proof's size is known before proving: answer a size query or
+    // refuse a small buffer without the proving work
+    // (non-revoked proofs are always format 2)
+    let (circuit, format) = match (expired, credential.holder, &credential.revocation) {
+        (true, ..) => (sizes::ZK_CIRCUIT_GRACE, proof::emit_format()),
+        (false, _, Some(_)) => (sizes::ZK_CIRCUIT_NON_REVOKED_VC, proof::PROOF_FORMAT_V2),
+        (false, Some(holder::Binding { context: Some(_), .. }), None) => {
+            (sizes::ZK_CIRCUIT_ONE_TIME_VC, proof::emit_format())
+        }
+        (false, Some(_), None) => (sizes::ZK_CIRCUIT_BOUND_VC, proof::emit_format()),
+        (false, None, None) => (sizes::ZK_CIRCUIT_VC, proof::emit_format()),
+    };
+    let required = sizes::size_of(size_item, circuit, format).unwrap_or(0);
+    reserve(required).map_err(|e| last_error::ffi("proof_out", e))?;
+    
+    // ==== Step 3: Generate proof (unless the nonce was just used) ====
+    let issuer_pubkey_bytes = &credential.issuer_pubkey[..];
+    let anchored = Anchored { opening, signature: credential.signature };
+    if let Some(revocation) = &credential.revocation {
+        // Non-revoked proofs stay out of the nonce cache as bound ones do
+        return nonrevocation::prove(&anchored, issuer_pubkey_bytes, current_time, nonce, revocation)
+            .map_err(|code| last_error::fail(code, prove_failure(code)));
+    }
+    if let Some(binding) = credential.holder {
+        // Bound proofs stay out of the nonce cache, which rerandomizes under
+        // the VC key
+        let proven = match binding.context {
+            Some(_) => onetime::prove(&anchored, issuer_pubkey_bytes, current_time, nonce, binding),
+            None => holder::prove(&anchored, issuer_pubkey_bytes, current_time, nonce, binding.secret),
+        };
+        return proven.map_err(|code| last_error::fail(code, prove_failure(code)));
+    }
+    let statement: [&[u8]; 2] = [&vc_message_hash, issuer_pubkey_bytes];
+    replay::guarded(&[], nonce, &statement, || {
+        if expired {
+            grace::prove(&anchored, issuer_pubkey_bytes, current_time, nonce)
+        } else {
+            prove_vc_hash(&anchored, issuer_pubkey_bytes, current_time, nonce)
+        }
+    })
+    .map_err(|code| last_error::fail(code, prove_failure(code)))
+}
+
+
+// ============================================================================
+// u64 Date Compatibility Shims
+// ============================================================================
+//
+// Original entry points with unsigned dates. Values above i64::MAX (e.g. a
+// negative host date wrapped to u64) are rejected, never reinterpreted.
+
+unwind::entry_point! {
+/// ZK_SignVC_I64 with u64 dates
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVC(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    issuer_private_key: *const c_char,
+    signature_out: *mut c_char,
+    signature_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_SignVC_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        issuer_private_key,
+        signature_out, signature_out_size,
+        required_size_out,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_SignVCWithClaims_I64 with u64 dates
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SignVCWithClaims(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    issuer_private_key: *const c_char,
+    signature_out: *mut c_char,
+    signature_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_SignVCWithClaims_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        claim_keys, claim_values, claim_count,
+        issuer_private_key,
+        signature_out, signature_out_size,
+        required_size_out,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_VerifyVCSignature_I64 with u64 dates
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCSignature(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    signature: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(0, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_VerifyVCSignature_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        signature,
+        issuer_public_key,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_VerifyVCSignatureWithClaims_I64 with u64 dates
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCSignatureWithClaims(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    signature: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(0, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_VerifyVCSignatureWithClaims_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        claim_keys, claim_values, claim_count,
+        signature,
+        issuer_public_key,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_ComputeVCHash_I64 with u64 dates
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_ComputeVCHash(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    claim_keys: *const *const c_char,
+    claim_values: *const *const c_char,
+    claim_count: usize,
+    vc_hash_out: *mut c_char,
+    vc_hash_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_ComputeVCHash_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        claim_keys, claim_values, claim_count,
+        vc_hash_out, vc_hash_out_size,
+        required_size_out,
+    )
+}
+}
+
+unwind::entry_point! {
+/// ZK_GenerateVCProof_I64 with u64 dates
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProof(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    vc_signature: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    ZK_GenerateVCProof_I64(
+        holder_id, holder_id_len,
+        issuer, issuer_len,
+        issue_date, expiry_date,
+        vc_signature,
+        issuer_pubkey,
+        current_time,
+        nonce,
+        proof_out, proof_out_size,
+        required_size_out,
+    )
+}
 }
 
+unwind::entry_point! {
 /// Verify ZK proof for VC
+///
+/// `current_time` passes through the configured time source
+/// (ZK_SetTimeMode) and is a public input: the proof shows the dates in the
+/// anchor `issuer_pubkey` signed cover it, so it must be the time the prover
+/// was challenged with (see validity.rs). Returns 1 if valid, 0 if invalid,
+/// also for a proof of another time, of a credential not yet valid or
+/// expired at `current_time` or of an anchor the issuer did not sign,
+/// ZK_ERR_TIME_MISMATCH or -1 from the time source,
+/// ZK_ERR_DISABLED for a
+/// legacy-format proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted (ZK_SetAcceptedFormatVersions).
+#[cfg(feature = "verifier")]
 #[no_mangle]
 pub extern "C" fn ZK_VerifyVCProof(
     proof_hex: *const c_char,
     issuer_pubkey: *const c_char,
-    _current_time: u64,  // Reserved for future time constraint verification
+    current_time: u64,
     nonce: u64,
 ) -> c_int {
-    if proof_hex.is_null() || issuer_pubkey.is_null() {
-        return 0;
-    }
+    let started = std::time::Instant::now();
+    last_error::clear();
     
-    let keys_guard = match KEYS.lock() {
-        Ok(guard) => guard,
-        Err(_) => return 0,
-    };
+    let pvk = current_verifying_key();
     
-    let (_, pvk) = match keys_guard.as_ref() {
-        Some(keys) => keys,
-        None => return 0,
+    let result = match pvk {
+        Some(ref pvk) => verify_vc_proof_hex(pvk, proof_hex, issuer_pubkey, current_time, nonce, proof::accepted_formats()),
+        None => last_error::fail(0, "verifying key is not set up; call ZK_Init or import it"),
     };
     
-    // Parse inputs
-    let proof_hex_str = unsafe {
-        CStr::from_ptr(proof_hex).to_str().unwrap_or("")
-    };
+    audit::record(started, audit::Verification {
+        check: "vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.as_ref().map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
     
-    let issuer_pubkey_str = unsafe {
-        CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("")
+    result
+}
+}
+
+unwind::entry_point! {
+/// ZK_VerifyVCProof on binary buffers: the proof as ZK_GenerateVCProofRaw
+/// writes it (a hex proof decoded by the caller) and the 32-byte issuer key
+///
+/// Returns the codes of ZK_VerifyVCProof.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofRaw(
+    proof: *const u8,
+    proof_len: usize,
+    issuer_pubkey: *const u8,
+    issuer_pubkey_len: usize,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = std::time::Instant::now();
+    last_error::clear();
+
+    let pvk = current_verifying_key();
+
+    let result = match pvk {
+        Some(ref pvk) => verify_vc_proof_raw(pvk, proof, proof_len, issuer_pubkey, issuer_pubkey_len, current_time, nonce),
+        None => last_error::fail(0, "verifying key is not set up; call ZK_Init or import it"),
     };
-    
-    let proof_bytes = match hex_to_bytes(proof_hex_str) {
-        Ok(bytes) => bytes,
-        Err(_) => return 0,
+
+    audit::record(started, audit::Verification {
+        check: "vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.as_ref().map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+/// Read a binary proof and issuer key, then verify against `pvk`
+#[cfg(feature = "verifier")]
+fn verify_vc_proof_raw(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof: *const u8,
+    proof_len: usize,
+    issuer_pubkey: *const u8,
+    issuer_pubkey_len: usize,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let issuer_pubkey_bytes = match read_bytes(issuer_pubkey, issuer_pubkey_len, MAX_FIELD_LEN) {
+        Ok(bytes) if !issuer_pubkey.is_null() => bytes,
+        _ => return last_error::fail(0, "issuer_pubkey is NULL or too long"),
+    };
+
+    let proof_bytes = match read_bytes(proof, proof_len, ffi::MAX_BLOB_LEN) {
+        Ok(bytes) if !proof.is_null() => bytes,
+        _ => return last_error::fail(0, "proof is NULL or too long"),
+    };
+    let (header, format, proof) = match admission::decode_vc_proof(proof_bytes, Some(proof::accepted_formats())) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return last_error::fail(rejection.verify_code(), rejection.describe()),
+    };
+    let now = match proof_time(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+
+    match verify_vc_proof_decoded(pvk, format, &proof, issuer_pubkey_bytes, &header, now, nonce) {
+        1 => 1,
+        code if !header.signed_by(issuer_pubkey_bytes) => last_error::fail(code, UNSIGNED_ANCHOR),
+        code => last_error::fail(code, verify_failure(&header, now)),
+    }
+}
+
+/// Parse hex proof and issuer key, then verify against `pvk`
+#[cfg(feature = "verifier")]
+fn verify_vc_proof_hex(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    accepted_formats: u32,
+) -> c_int {
+    let issuer_pubkey_str = match admission::text_arg(issuer_pubkey, MAX_FIELD_LEN) {
+        Some(text) => text,
+        None => return last_error::fail(0, "issuer_pubkey is NULL, too long or not UTF-8"),
     };
     
-    let proof = match Proof::<Bn254>::deserialize_compressed(&proof_bytes[..]) {
-        Ok(p) => p,
-        Err(_) => return 0,
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, accepted_formats) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return last_error::fail(rejection.verify_code(), rejection.describe()),
     };
     
-    let issuer_pubkey_bytes = match hex_to_bytes(issuer_pubkey_str) {
+    let issuer_pubkey_bytes = match did::public_key_bytes(issuer_pubkey_str) {
         Ok(bytes) => bytes,
-        Err(_) => return 0,
+        Err(e) => return last_error::fail(e.code_or(0), "issuer_pubkey is not hex or a did:key"),
     };
     
-    // Construct public inputs (must match circuit order)
-    let issuer_pubkey_hash_field = hash_bytes_to_field(&issuer_pubkey_bytes);
-    let nonce_field = Fr::from(nonce);
+    let now = match proof_time(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
     
-    let public_inputs = vec![issuer_pubkey_hash_field, nonce_field];
+    match verify_vc_proof_decoded(pvk, format, &proof, &issuer_pubkey_bytes, &header, now, nonce) {
+        1 => 1,
+        code if !header.signed_by(&issuer_pubkey_bytes) => last_error::fail(code, UNSIGNED_ANCHOR),
+        code => last_error::fail(code, verify_failure(&header, now)),
+    }
+}
+
+/// Resolve `current_time` through the time source into the time a VC proof
+/// is checked at; failures are recorded for ZK_GetLastError
+#[cfg(feature = "verifier")]
+fn proof_time(current_time: u64) -> Result<u64, c_int> {
+    clock::now(current_time).map_err(|code| last_error::fail(code, clock_failure(code)))
+}
+
+/// ZK_GetLastError message for a VC proof whose anchor the issuer key did
+/// not sign
+#[cfg(any(feature = "verifier", feature = "std"))]
+const UNSIGNED_ANCHOR: &str = "proof's anchor is not signed by issuer_pubkey";
+
+/// ZK_GetLastError message for a VC proof that failed at `now`
+#[cfg(feature = "verifier")]
+fn verify_failure(header: &validity::Header, now: u64) -> &'static str {
+    if header.current_time != now {
+        "proof was generated for another current_time"
+    } else {
+        "proof does not verify for issuer_pubkey, nonce and current_time"
+    }
+}
+
+/// Verify an admitted VC proof at `current_time`: 1 valid, 0 invalid
+#[cfg(feature = "verifier")]
+fn verify_vc_proof_decoded(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    format: u8,
+    proof: &Proof<Bn254>,
+    issuer_pubkey_bytes: &[u8],
+    header: &validity::Header,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    // Construct public inputs (must match circuit order), once the issuer's
+    // signature over the anchor checks out
+    let public_inputs = match validity::public_inputs(issuer_pubkey_bytes, format, nonce, current_time, header) {
+        Some(inputs) => inputs,
+        None => return 0,
+    };
     
     // Verify proof
-    match Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof) {
-        Ok(true) => 1,
-        Ok(false) => 0,
-        Err(_) => 0,
-    }
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
 }
 
+unwind::entry_point! {
 /// Cleanup ZK resources
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern "C" fn ZK_Cleanup() {
-    if let Ok(mut keys) = KEYS.lock() {
-        *keys = None;
+    #[cfg(feature = "prover")]
+    if let Ok(mut pk) = PROVING_KEY.lock() {
+        *pk = None;
+    }
+    if let Ok(mut pvk) = VERIFYING_KEY.lock() {
+        *pvk = None;
+    }
+    schedule::cleanup();
+    composite::cleanup();
+    ballot::cleanup();
+    age::cleanup();
+    rebind::cleanup();
+    grace::cleanup();
+    predicate::cleanup();
+    disclosure::cleanup();
+    range::cleanup();
+    holder::cleanup();
+    onetime::cleanup();
+    nonrevocation::cleanup();
+    revocation::clear_published();
+    issuers::clear();
+    #[cfg(feature = "prover")]
+    replay::clear();
+    #[cfg(feature = "threshold")]
+    threshold::clear();
+}
+}
+
+unwind::entry_point! {
+/// Whether the VC keys are loaded
+///
+/// 1 once ZK_Init, ZK_InitWithKeys, ZK_InitVerifierOnly or
+/// ZK_ImportVerifyingKey has set the verifying key, 0 before and after
+/// ZK_Cleanup. A host that finds 0 where it expects keys, e.g. after
+/// resetting the library when a worker thread panicked, calls ZK_Init (or
+/// its import) again; a lock poisoned by the panic does not stand in the way.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_IsInitialized() -> c_int {
+    VERIFYING_KEY.lock().is_ok_and(|pvk| pvk.is_some()) as c_int
+}
+}
+
+unwind::entry_point! {
+/// Panic while holding both VC key locks, poisoning them (check-poison.sh)
+///
+/// Returns 0 if both locks are poisoned afterwards, -1 otherwise.
+#[cfg(feature = "fault-injection")]
+#[no_mangle]
+pub extern "C" fn ZK_PoisonKeyLocks() -> c_int {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let _ = std::panic::catch_unwind(|| {
+        let _pk = PROVING_KEY.lock();
+        let _pvk = VERIFYING_KEY.lock();
+        panic!("poisoning the key locks");
+    });
+    std::panic::set_hook(hook);
+
+    if PROVING_KEY.is_poisoned() && VERIFYING_KEY.is_poisoned() {
+        0
+    } else {
+        -1
     }
 }
+}
+
+/// Panic inside an entry point (check-panics.sh)
+///
+/// Returns ZK_ERR_PANIC, leaving "internal panic: injected by
+/// ZK_InjectPanic" as the calling thread's last error.
+#[cfg(feature = "fault-injection")]
+#[no_mangle]
+pub extern "C" fn ZK_InjectPanic() -> c_int {
+    unwind::guard(|| panic!("injected by ZK_InjectPanic"))
+}
+
+/// Set by ZK_SkipProverChecks
+#[cfg(feature = "fault-injection")]
+static SKIP_PROVER_CHECKS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Whether provers skip their native signature and holder-secret check
+#[cfg(feature = "prover")]
+fn skips_prover_checks() -> bool {
+    #[cfg(feature = "fault-injection")]
+    return SKIP_PROVER_CHECKS.load(std::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(feature = "fault-injection"))]
+    return false;
+}
+
+unwind::entry_point! {
+/// Make VC provers skip (nonzero) or run (0) their native signature and
+/// holder-secret check and the blob pre-checks (checked_blob), as a
+/// modified prover would (check-features.sh)
+///
+/// What the verifier then rejects, it rejects on the proof alone.
+#[cfg(feature = "fault-injection")]
+#[no_mangle]
+pub extern "C" fn ZK_SkipProverChecks(skip: c_int) {
+    SKIP_PROVER_CHECKS.store(skip != 0, std::sync::atomic::Ordering::Relaxed);
+}
+}
+
+/// Build profile: "strict" compiles out legacy/insecure code paths
+#[cfg(feature = "std")]
+pub const BUILD_PROFILE: &str = if cfg!(feature = "strict") { "strict" } else { "standard" };
+
+unwind::entry_point! {
+/// Report version, build profile and roles as JSON
+///
+/// e.g. {"crate":"zklib-vc","version":"0.1.0","profile":"strict","proof_format":2,
+///       "prover":true,"verifier":false,"parallel":false}
+/// Once ZK_AutoTune or ZK_ImportTuning has run, a "tuning" member holds the
+/// decision as ZK_ExportTuning writes it.
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_BuildInfo(info_out: *mut c_char, info_out_size: usize) -> c_int {
+    #[cfg(feature = "prover")]
+    let tuning = tuning::current().map(|t| format!(",\"tuning\":{}", t.to_json())).unwrap_or_default();
+    #[cfg(not(feature = "prover"))]
+    let tuning = "";
+    let info = format!(
+        "{{\"crate\":\"{}\",\"version\":\"{}\",\"profile\":\"{}\",\"proof_format\":{},\"prover\":{},\"verifier\":{},\"parallel\":{}{}}}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        BUILD_PROFILE,
+        proof::EMIT_FORMAT,
+        cfg!(feature = "prover"),
+        cfg!(feature = "verifier"),
+        cfg!(feature = "parallel"),
+        tuning,
+    );
+    
+    match write_cstr(info_out, info_out_size, &info) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,205 @@
+// ============================================================================
+// Collection Limits and Eviction
+// ============================================================================
+//
+// The registries a long-lived gateway fills up (context trust stores, the
+// challenge store, the nullifier set and the revocation registry) each have a
+// configurable capacity and an eviction policy for inserts beyond it:
+//
+//   ZK_EVICT_REJECT   refuse the insert with ZK_ERR_CAPACITY (the default)
+//   ZK_EVICT_OLDEST   drop the oldest entries to make room
+//
+// "Oldest" is per collection: the first trusted issuer added, the challenge
+// that expires first, the nullifier of the lowest epoch. The revocation
+// registry only rejects: evicting a revocation would make a revoked
+// credential valid again.
+//
+// A capacity of 0 means unlimited, the default. Enforcing a capacity counts
+// the collection with a prefix scan on every insert, so limits (like the
+// enumeration and pruning functions) need a backend with scan_prefix; the
+// host callback backend reports ZK_ERR_STORAGE for them.
+//
+// Inserts, evictions and pruning of one collection run under that
+// collection's lock, the same one consume-once and record-once checks take,
+// so concurrent verification traffic never observes a half-applied eviction
+// or exceeds the capacity. Evictions and rejections are counted per
+// collection for ZK_CollectionStats.
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::sync::Mutex;
+
+use crate::challenge::ChallengeStore;
+use crate::nullifier::NullifierSet;
+use crate::revocation::RevocationRegistry;
+use crate::storage::{storage, StorageError};
+
+pub const ZK_COLLECTION_TRUST_STORE: c_int = 0;
+pub const ZK_COLLECTION_CHALLENGES: c_int = 1;
+pub const ZK_COLLECTION_NULLIFIERS: c_int = 2;
+pub const ZK_COLLECTION_REVOCATIONS: c_int = 3;
+pub(crate) const COLLECTIONS: usize = 4;
+
+pub const ZK_EVICT_REJECT: c_int = 0;
+pub const ZK_EVICT_OLDEST: c_int = 1;
+
+/// Capacity and eviction policy of a collection
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkCollectionLimit {
+    /// Most entries the collection holds, 0 for unlimited
+    pub capacity: u64,
+    /// ZK_EVICT_REJECT or ZK_EVICT_OLDEST
+    pub policy: c_int,
+}
+
+impl ZkCollectionLimit {
+    const UNLIMITED: Self = Self {
+        capacity: 0,
+        policy: ZK_EVICT_REJECT,
+    };
+}
+
+/// Size and eviction metrics of a collection
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkCollectionStats {
+    pub count: u64,
+    pub capacity: u64,
+    /// Entries dropped by ZK_EVICT_OLDEST since start
+    pub evicted: u64,
+    /// Inserts refused with ZK_ERR_CAPACITY since start
+    pub rejected: u64,
+}
+
+static LIMITS: Mutex<[ZkCollectionLimit; COLLECTIONS]> = Mutex::new([ZkCollectionLimit::UNLIMITED; COLLECTIONS]);
+static EVICTED: [AtomicU64; COLLECTIONS] = [const { AtomicU64::new(0) }; COLLECTIONS];
+static REJECTED: [AtomicU64; COLLECTIONS] = [const { AtomicU64::new(0) }; COLLECTIONS];
+
+fn index(collection: c_int) -> Option<usize> {
+    usize::try_from(collection).ok().filter(|&i| i < COLLECTIONS)
+}
+
+/// Current limit of a collection
+pub fn limit(collection: c_int) -> ZkCollectionLimit {
+    match index(collection) {
+        Some(i) => LIMITS.lock().unwrap_or_else(|e| e.into_inner())[i],
+        None => ZkCollectionLimit::UNLIMITED,
+    }
+}
+
+/// Whether a collection takes `limit`: not an unknown collection or policy,
+/// nor ZK_EVICT_OLDEST on the revocation registry
+pub fn is_valid_limit(collection: c_int, limit: ZkCollectionLimit) -> bool {
+    match (index(collection), collection, limit.policy) {
+        (None, _, _) => false,
+        (_, ZK_COLLECTION_REVOCATIONS, ZK_EVICT_OLDEST) => false,
+        (_, _, policy) => matches!(policy, ZK_EVICT_REJECT | ZK_EVICT_OLDEST),
+    }
+}
+
+/// Set the limit of a collection; false if it does not take `limit`
+pub fn set_limit(collection: c_int, limit: ZkCollectionLimit) -> bool {
+    match index(collection) {
+        Some(i) if is_valid_limit(collection, limit) => {
+            LIMITS.lock().unwrap_or_else(|e| e.into_inner())[i] = limit;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Whether inserts into the collection have to count it first
+pub fn is_limited(collection: c_int) -> bool {
+    limit(collection).capacity != 0
+}
+
+/// How many entries to evict before inserting into a collection holding
+/// `count`, or StorageError::Full if the policy refuses the insert
+pub fn admit(collection: c_int, count: usize) -> Result<usize, StorageError> {
+    let limit = limit(collection);
+    let capacity = usize::try_from(limit.capacity).unwrap_or(usize::MAX);
+    if limit.capacity == 0 || count < capacity {
+        return Ok(0);
+    }
+    match limit.policy {
+        ZK_EVICT_OLDEST => Ok(count - capacity + 1),
+        _ => {
+            if let Some(i) = index(collection) {
+                REJECTED[i].fetch_add(1, Ordering::Relaxed);
+            }
+            Err(StorageError::Full)
+        }
+    }
+}
+
+/// Count entries dropped to make room
+pub fn record_evicted(collection: c_int, evicted: usize) {
+    if let Some(i) = index(collection) {
+        EVICTED[i].fetch_add(evicted as u64, Ordering::Relaxed);
+    }
+}
+
+/// Metrics of a collection holding `count` entries
+pub fn stats(collection: c_int, count: usize) -> Option<ZkCollectionStats> {
+    let i = index(collection)?;
+    Some(ZkCollectionStats {
+        count: count as u64,
+        capacity: limit(collection).capacity,
+        evicted: EVICTED[i].load(Ordering::Relaxed),
+        rejected: REJECTED[i].load(Ordering::Relaxed),
+    })
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Set the capacity and eviction policy of a collection (ZK_COLLECTION_*)
+///
+/// Lowering a capacity below the current size does not evict; the next
+/// insert does (or is refused). Returns 0 on success, -1 for an unknown
+/// collection or policy, or ZK_EVICT_OLDEST on the revocation registry.
+#[no_mangle]
+pub extern "C" fn ZK_SetCollectionLimit(collection: c_int, limit: *const ZkCollectionLimit) -> c_int {
+    match unsafe { limit.as_ref() } {
+        Some(limit) if set_limit(collection, *limit) => 0,
+        _ => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Size, capacity and eviction metrics of a collection
+///
+/// Trust stores belong to contexts, so `count` is 0 for
+/// ZK_COLLECTION_TRUST_STORE; ZK_TrustStoreList reports a context's size.
+/// Returns 0 on success, ZK_ERR_STORAGE if the backend cannot be scanned,
+/// -1 on NULL or an unknown collection.
+#[no_mangle]
+pub extern "C" fn ZK_CollectionStats(collection: c_int, stats_out: *mut ZkCollectionStats) -> c_int {
+    if stats_out.is_null() {
+        return -1;
+    }
+
+    let count = match collection {
+        ZK_COLLECTION_TRUST_STORE => Ok(0),
+        ZK_COLLECTION_CHALLENGES => ChallengeStore::new(storage()).count(),
+        ZK_COLLECTION_NULLIFIERS => NullifierSet::new(storage()).count(),
+        ZK_COLLECTION_REVOCATIONS => RevocationRegistry::new(storage()).count(),
+        _ => return -1,
+    };
+
+    match count.map(|count| stats(collection, count)) {
+        Ok(Some(stats)) => {
+            unsafe {
+                *stats_out = stats;
+            }
+            0
+        }
+        Ok(None) => -1,
+        Err(e) => e.code(),
+    }
+}
+}
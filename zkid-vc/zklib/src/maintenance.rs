@@ -0,0 +1,247 @@
+// ============================================================================
+// Wallet Maintenance
+// ============================================================================
+//
+// A wallet process that stays up for weeks accumulates state in several
+// places. ZK_Maintain does the housekeeping for all of them, one task at a
+// time:
+//
+//   challenges   challenges in the challenge store that have expired
+//   nonce cache  recent-challenge pairs past their TTL or beyond the cache
+//                capacity, oldest first, with the proofs cached for
+//                rerandomization (replay.rs)
+//   transfers    chunked transfers idle for longer than TRANSFER_TIMEOUT
+//   checkpoint   a checkpoint a failed prove left in the
+//                ZK_SetProveCheckpointing buffer, wiped once no prove has
+//                saved for CHECKPOINT_IDLE
+//   receipts     repeated consent receipts in the wallet's credential store
+//
+// Each task takes the lock the subsystem's own operations take and only
+// drops what no operation can still use: an expired challenge cannot be
+// consumed, a timed-out transfer is refused by its next call, a checkpoint is
+// never wiped while a prove runs. Maintenance may therefore run on a
+// housekeeping thread next to normal traffic. The credential store is the
+// exception: like every ZK_Store* function, ZK_Maintain must not use a store
+// handle another thread is using.
+//
+// With a time budget the call stops between tasks once the budget is spent,
+// and the next call starts with the first task it did not reach; a task is
+// never interrupted. ZK_MemoryReport summarizes what each subsystem holds.
+
+use serde_json::{json, Value};
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use ark_serialize::CanonicalSerialize;
+
+use crate::challenge::{ChallengeStore, CHALLENGE_ENTRY_LEN};
+use crate::checkpoint::{self, CHECKPOINT_IDLE};
+use crate::clock;
+use crate::ffi::write_cstr;
+use crate::storage::{storage, StorageError};
+use crate::store::CredentialStore;
+use crate::{replay, transfer, PROVING_KEY, VERIFYING_KEY};
+
+/// What one ZK_Maintain call reclaimed (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkMaintenanceReport {
+    /// Expired challenges removed from the challenge store
+    pub challenges_pruned: u64,
+    /// Recent-challenge pairs dropped from the nonce cache
+    pub nonce_pairs_dropped: u64,
+    /// Timed-out chunked transfers dropped
+    pub transfers_dropped: u64,
+    /// Bytes of a stale checkpoint wiped in the caller's buffer
+    pub checkpoint_bytes_wiped: u64,
+    /// Repeated consent receipts dropped from the store
+    pub receipts_dropped: u64,
+    /// Approximate library memory freed, checkpoint buffer excluded
+    pub bytes_freed: u64,
+    /// 1 if every task ran, 0 if the budget ran out first
+    pub complete: c_int,
+}
+
+#[derive(Clone, Copy)]
+enum Task {
+    Challenges,
+    NonceCache,
+    Transfers,
+    Checkpoint,
+    Receipts,
+}
+
+const TASKS: [Task; 5] = [
+    Task::Challenges,
+    Task::NonceCache,
+    Task::Transfers,
+    Task::Checkpoint,
+    Task::Receipts,
+];
+
+/// First task of the next call
+static NEXT_TASK: AtomicUsize = AtomicUsize::new(0);
+
+impl Task {
+    /// Run the task, adding what it reclaimed to `report`
+    fn run(self, store: Option<&mut CredentialStore>, now: u64, report: &mut ZkMaintenanceReport) -> Result<(), c_int> {
+        match self {
+            Task::Challenges => match ChallengeStore::new(storage()).prune_expired(now) {
+                Ok(pruned) => {
+                    report.challenges_pruned += pruned as u64;
+                    report.bytes_freed += (pruned * CHALLENGE_ENTRY_LEN) as u64;
+                }
+                // A backend without scan_prefix keeps its own expiry
+                Err(StorageError::Unsupported) => {}
+                Err(e) => return Err(e.code()),
+            },
+            Task::NonceCache => {
+                let (dropped, freed) = replay::sweep();
+                report.nonce_pairs_dropped += dropped as u64;
+                report.bytes_freed += freed as u64;
+            }
+            Task::Transfers => {
+                let (dropped, freed) = transfer::sweep();
+                report.transfers_dropped += dropped as u64;
+                report.bytes_freed += freed as u64;
+            }
+            Task::Checkpoint => {
+                report.checkpoint_bytes_wiped += checkpoint::sweep(CHECKPOINT_IDLE) as u64;
+            }
+            Task::Receipts => {
+                if let Some(store) = store {
+                    let (_, _, before) = store.held_bytes();
+                    report.receipts_dropped += store.compact_receipts().map_err(|e| e.code())? as u64;
+                    let (_, _, after) = store.held_bytes();
+                    report.bytes_freed += (before - after) as u64;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the maintenance tasks until done or past `deadline`
+///
+/// A failing task does not hold up the others; the first error is returned
+/// once the rest have run.
+pub fn maintain(
+    mut store: Option<&mut CredentialStore>,
+    now: u64,
+    deadline: Option<Instant>,
+) -> (ZkMaintenanceReport, Result<(), c_int>) {
+    let mut report = ZkMaintenanceReport::default();
+    let mut result = Ok(());
+    let first = NEXT_TASK.load(Ordering::Relaxed);
+
+    for step in 0..TASKS.len() {
+        let index = (first + step) % TASKS.len();
+        if step > 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            NEXT_TASK.store(index, Ordering::Relaxed);
+            return (report, result);
+        }
+        if let Err(code) = TASKS[index].run(store.as_deref_mut(), now, &mut report) {
+            result = result.and(Err(code));
+        }
+    }
+
+    NEXT_TASK.store(0, Ordering::Relaxed);
+    report.complete = 1;
+    (report, result)
+}
+
+/// Bytes held per subsystem, as ZK_MemoryReport writes them
+pub fn memory_report(store: Option<&CredentialStore>) -> Value {
+    let challenges = ChallengeStore::new(storage())
+        .count()
+        .ok()
+        .map(|entries| json!({ "entries": entries, "bytes": entries * CHALLENGE_ENTRY_LEN }));
+    let (pairs, nonce_bytes) = replay::held();
+    let (transfers, transfer_bytes) = transfer::held();
+    let (checkpoint_bytes, checkpoint_capacity) = checkpoint::held();
+    let store = store.map(|store| {
+        let (records, links, receipts) = store.held_bytes();
+        json!({
+            "credentials": store.ids().count(),
+            "links": store.links().count(),
+            "receipts": store.receipts().count(),
+            "bytes": records + links + receipts,
+        })
+    });
+    let proving_key = PROVING_KEY.lock().ok().and_then(|pk| pk.as_ref().map(|pk| pk.uncompressed_size()));
+    let verifying_key = VERIFYING_KEY.lock().ok().and_then(|pvk| pvk.as_ref().map(|pvk| pvk.uncompressed_size()));
+
+    json!({
+        "challenges": challenges,
+        "nonce_cache": { "entries": pairs, "bytes": nonce_bytes },
+        "transfers": { "active": transfers, "bytes": transfer_bytes },
+        "checkpoint": { "bytes": checkpoint_bytes, "capacity": checkpoint_capacity },
+        "store": store,
+        "keys": { "proving_key": proving_key.unwrap_or(0), "verifying_key": verifying_key.unwrap_or(0) },
+    })
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Run the wallet's housekeeping (see maintenance.rs)
+///
+/// `store` is the wallet's open credential store, or NULL to leave stores
+/// alone. `current_time` decides which challenges have expired, through
+/// the time source set with ZK_SetTimeMode. `budget_ms` bounds the call (0
+/// for no bound); tasks not reached are run first by the next call. What was
+/// reclaimed is written to `report_out` (may be NULL). Returns 0 on success,
+/// the code of the first task that failed (ZK_ERR_STORAGE from the challenge
+/// store, a ZK_Store* code from saving the store) after the others ran, or
+/// the time source's error before any task runs.
+#[no_mangle]
+pub extern "C" fn ZK_Maintain(
+    store: *mut CredentialStore,
+    current_time: u64,
+    budget_ms: u32,
+    report_out: *mut ZkMaintenanceReport,
+) -> c_int {
+    let started = Instant::now();
+    let now = match clock::now(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+    let deadline = (budget_ms > 0).then(|| started + Duration::from_millis(budget_ms.into()));
+
+    let (report, result) = maintain(unsafe { store.as_mut() }, now, deadline);
+    if let Some(out) = unsafe { report_out.as_mut() } {
+        *out = report;
+    }
+    match result {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Describe the bytes each subsystem holds as JSON
+///
+/// e.g. {"challenges":{"entries":3,"bytes":78},"nonce_cache":{"entries":2,
+/// "bytes":304},"transfers":{"active":0,"bytes":0},"checkpoint":{"bytes":0,
+/// "capacity":65536},"store":{"credentials":4,"links":1,"receipts":9,
+/// "bytes":5120},"keys":{"proving_key":...,"verifying_key":...}}
+///
+/// Sizes are approximate. "challenges" is null for a storage backend that
+/// cannot be scanned, "store" when `store` is NULL. Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_MemoryReport(store: *const CredentialStore, json_out: *mut c_char, json_out_size: usize) -> c_int {
+    if json_out.is_null() {
+        return -1;
+    }
+    let report = memory_report(unsafe { store.as_ref() });
+    match write_cstr(json_out, json_out_size, &report.to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,256 @@
+// ============================================================================
+// Wallet-Side Credential Matching
+// ============================================================================
+//
+// Before prompting the user, a wallet answers "which of my credentials can
+// satisfy this presentation request?" by evaluating each stored credential
+// against the request's constraints. Nothing is proved: the match is pure
+// metadata evaluation (plus an Ed25519 signature check) and is cheap enough
+// to run over the whole store on every request.
+//
+// A credential matches when
+//
+//   - it is signed by the request's issuer key (and co-signed where its
+//     schema requires dual control); an empty key accepts any issuer,
+//   - it is active (dates and validity schedule) at the request's time,
+//   - it is not revoked or superseded according to the revocation entries
+//     applied through ZK_ApplyRevocationEntry,
+//   - and it meets the constraints the request carries in its metadata:
+//
+//       issuer           required issuer name (VerifiableCredential::issuer)
+//       schema           required schema id (the zkid:schema claim), or a
+//                        comma-separated list of acceptable ones
+//       claims           comma-separated claim keys that must be present
+//       require.<key>    predicate on claim <key>: "=v", "!=v", or an
+//                        integer comparison ">=n", "<=n", ">n", "<n" (signed,
+//                        magnitudes up to 2^128 - 1 for token amounts); a
+//                        bare value means "=value"
+//       predicate        a predicate in canonical binary form, hex (any
+//                        number of entries; see predicate.rs), also for
+//                        "in" sets and absent claims
+//
+// The whole blob is disclosed next to a proof, so every claim of a match is
+// reported as disclosed. Matches are ranked by how few claims they disclose
+// beyond the ones the request names, then by latest expiry, then by latest
+// issue date. Stored credentials that do not parse are reported as warnings
+// and never match.
+
+use serde_json::{json, Value};
+use std::os::raw::{c_char, c_int};
+
+use crate::credential::SCHEMA_CLAIM;
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_STORAGE};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::inbox::PresentationRequest;
+use crate::predicate::Predicate;
+use crate::schema::request_schemas;
+use crate::revocation::RevocationRegistry;
+use crate::storage::{storage, StorageError};
+use crate::store::CredentialStore;
+use crate::VerifiableCredential;
+use ed25519_dalek::VerifyingKey;
+
+/// What a presentation request asks of a credential
+#[derive(Clone, Debug)]
+pub struct Requirements {
+    issuer_key: Option<VerifyingKey>,
+    issuer: Option<String>,
+    schemas: Vec<String>,
+    claims: Vec<String>,
+    predicates: Vec<Predicate>,
+    current_time: u64,
+}
+
+impl Requirements {
+    /// Read the constraints of a request; None if the issuer key or a
+    /// predicate is malformed
+    pub fn from_request(request: &PresentationRequest) -> Option<Self> {
+        let issuer_key = match request.issuer_pubkey.as_slice() {
+            [] => None,
+            bytes => Some(VerifyingKey::from_bytes(bytes.try_into().ok()?).ok()?),
+        };
+
+        let predicates = request.predicates()?;
+
+        Some(Self {
+            issuer_key,
+            issuer: request.metadata_value("issuer").map(str::to_string),
+            schemas: request_schemas(request).into_iter().map(str::to_string).collect(),
+            claims: request
+                .metadata_value("claims")
+                .map(|list| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|key| !key.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            predicates,
+            current_time: request.current_time,
+        })
+    }
+
+    /// Whether the credential's own contents satisfy the request
+    pub fn accepts(&self, vc: &VerifiableCredential) -> bool {
+        self.issuer_key
+            .as_ref()
+            .is_none_or(|key| vc.verify_issuer_signatures(key, None).is_ok())
+            && vc.active_at(self.current_time)
+            && self.issuer.as_deref().is_none_or(|issuer| vc.issuer == issuer)
+            && (self.schemas.is_empty() || vc.schema_id().is_some_and(|id| self.schemas.iter().any(|s| s == id)))
+            && self.claims.iter().all(|key| vc.claim(key).is_some())
+            && self
+                .predicates
+                .iter()
+                .all(|predicate| predicate.holds(vc.claim(&predicate.claim)))
+    }
+
+    /// Whether `key` is a claim the request names
+    fn requests(&self, key: &str) -> bool {
+        (key == SCHEMA_CLAIM && !self.schemas.is_empty())
+            || self.claims.iter().any(|k| k == key)
+            || self.predicates.iter().any(|predicate| predicate.claim == key)
+    }
+}
+
+/// A stored credential that can answer the request
+#[derive(Clone, Debug)]
+pub struct CredentialMatch {
+    pub credential_id: String,
+    pub vc: VerifiableCredential,
+    /// Disclosed claims the request did not name
+    pub extra_claims: usize,
+}
+
+/// Result of matching a store against a request
+#[derive(Clone, Debug, Default)]
+pub struct MatchOutcome {
+    /// Best candidate first
+    pub matches: Vec<CredentialMatch>,
+    /// Stored credentials that could not be parsed
+    pub unparseable: Vec<String>,
+}
+
+/// Evaluate every stored credential against `requirements`
+pub fn match_credentials(
+    store: &CredentialStore,
+    requirements: &Requirements,
+    registry: &RevocationRegistry,
+) -> Result<MatchOutcome, StorageError> {
+    let mut outcome = MatchOutcome::default();
+
+    for (credential_id, blob) in store.entries() {
+        let vc = match VerifiableCredential::from_bytes(blob) {
+            Some(vc) => vc,
+            None => {
+                outcome.unparseable.push(credential_id.to_string());
+                continue;
+            }
+        };
+        // Superseded credentials are recorded as revoked too
+        if !requirements.accepts(&vc) || registry.is_revoked(credential_id.as_bytes())? {
+            continue;
+        }
+
+        let extra_claims = vc.claims.iter().filter(|(key, _)| !requirements.requests(key)).count();
+        outcome.matches.push(CredentialMatch {
+            credential_id: credential_id.to_string(),
+            vc,
+            extra_claims,
+        });
+    }
+
+    outcome.matches.sort_by(|a, b| {
+        a.extra_claims
+            .cmp(&b.extra_claims)
+            .then(b.vc.expiry_date.cmp(&a.vc.expiry_date))
+            .then(b.vc.issue_date.cmp(&a.vc.issue_date))
+            .then(a.credential_id.cmp(&b.credential_id))
+    });
+    Ok(outcome)
+}
+
+impl MatchOutcome {
+    pub fn to_json(&self) -> Value {
+        let matches: Vec<Value> = self
+            .matches
+            .iter()
+            .map(|m| {
+                let disclosed: Vec<Value> = m
+                    .vc
+                    .claims
+                    .iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect();
+                json!({
+                    "credential_id": m.credential_id,
+                    "issuer": m.vc.issuer,
+                    "schema": m.vc.schema_id(),
+                    "issue_date": m.vc.issue_date,
+                    "expiry_date": m.vc.expiry_date,
+                    "disclosed": disclosed,
+                    "extra_claims": m.extra_claims,
+                })
+            })
+            .collect();
+        let warnings: Vec<Value> = self
+            .unparseable
+            .iter()
+            .map(|id| json!({ "credential_id": id, "warning": "unparseable credential" }))
+            .collect();
+        json!({ "matches": matches, "warnings": warnings })
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Rank the stored credentials that can answer a presentation request
+///
+/// `request_blob` is a presentation request (binary or hex text). Writes
+/// `{"matches": [...], "warnings": [...]}`: each match has its
+/// credential_id, issuer, schema (or null), issue and expiry dates, the
+/// claims a presentation with it discloses as [{"key", "value"}] and
+/// `extra_claims`, the number of those the request did not name; best
+/// candidate first. Stored credentials that do not parse appear only as
+/// warnings. No proof is generated. Returns 0 on success (also with no
+/// matches), ZK_ERR_CORRUPT if the request does not decode or has a
+/// malformed issuer key or predicate, ZK_ERR_STORAGE if revocation state
+/// cannot be read, ZK_ERR_BUFFER_TOO_SMALL for a short buffer, -1 on NULL
+/// input.
+#[no_mangle]
+pub extern "C" fn ZK_MatchCredentials(
+    request_blob: *const c_char,
+    request_blob_len: usize,
+    store: *mut CredentialStore,
+    matches_out_json: *mut c_char,
+    matches_out_json_size: usize,
+) -> c_int {
+    let store = match unsafe { store.as_ref() } {
+        Some(store) if !request_blob.is_null() => store,
+        _ => return -1,
+    };
+
+    let data = match read_bytes(request_blob, request_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let requirements = match PresentationRequest::from_wire(data).as_ref().and_then(Requirements::from_request) {
+        Some(requirements) => requirements,
+        None => return ZK_ERR_CORRUPT,
+    };
+
+    let outcome = match match_credentials(store, &requirements, &RevocationRegistry::new(storage())) {
+        Ok(outcome) => outcome,
+        Err(_) => return ZK_ERR_STORAGE,
+    };
+
+    match write_cstr(matches_out_json, matches_out_json_size, &outcome.to_json().to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
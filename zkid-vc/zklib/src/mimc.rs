@@ -0,0 +1,97 @@
+// ============================================================================
+// MiMC Hash
+// ============================================================================
+//
+// Circuit-friendly hash over the BN254 scalar field, for values a circuit
+// has to recompute. SHA-256 costs tens of thousands of constraints per block;
+// MiMC costs three per round.
+//
+// The cipher E_k(x) runs ROUNDS rounds of x <- (x + k + c_i)^5 and returns
+// x + k (x^5 is a permutation: gcd(5, r - 1) = 1). hash() chains it
+// Miyaguchi-Preneel style, starting from h = 0:
+//
+//   h <- E_h(m) + h + m      for each input m
+//
+// Round constants are SHA-256("zkid:mimc5" | i u32 LE) reduced mod r, with
+// c_0 = 0. ROUNDS = ceil(log_5 r) = 110.
+//
+// core + alloc only; the gadget exists in prover builds.
+
+use alloc::vec::Vec;
+use ark_bn254::Fr;
+use ark_ff::{Field, PrimeField, Zero};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "prover")]
+use {
+    crate::schedule::witness,
+    ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable},
+    ark_relations::{lc, ns},
+};
+
+const ROUND_CONSTANT_DOMAIN: &[u8] = b"zkid:mimc5";
+
+/// Rounds of the MiMC cipher
+pub const ROUNDS: usize = 110;
+
+fn round_constants() -> Vec<Fr> {
+    (0..ROUNDS as u32)
+        .map(|i| match i {
+            0 => Fr::zero(),
+            _ => {
+                let mut hasher = Sha256::new();
+                hasher.update(ROUND_CONSTANT_DOMAIN);
+                hasher.update(i.to_le_bytes());
+                Fr::from_le_bytes_mod_order(&hasher.finalize())
+            }
+        })
+        .collect()
+}
+
+/// MiMC hash of `inputs`
+pub fn hash(inputs: &[Fr]) -> Fr {
+    let constants = round_constants();
+    inputs.iter().fold(Fr::zero(), |h, m| {
+        let mut x = *m;
+        for c in &constants {
+            x = (x + h + c).pow([5u64]);
+        }
+        x + h + h + m
+    })
+}
+
+/// Constrain the MiMC hash of `inputs` (linear combinations with their
+/// values) and return it with its value
+///
+/// 3 * ROUNDS constraints per input.
+#[cfg(feature = "prover")]
+pub(crate) fn hash_gadget(
+    cs: &ConstraintSystemRef<Fr>,
+    inputs: &[(LinearCombination<Fr>, Option<Fr>)],
+) -> Result<(LinearCombination<Fr>, Option<Fr>), SynthesisError> {
+    let constants = round_constants();
+    let mut h = (lc!(), Some(Fr::zero()));
+    for (m, m_value) in inputs {
+        let mut x = (m.clone(), *m_value);
+        for c in &constants {
+            let t = x.0 + &h.0 + (*c, Variable::One);
+            let t_value = x.1.zip(h.1).map(|(x, h)| x + h + c);
+
+            let t2_value = t_value.map(|t| t.square());
+            let t2 = witness(cs, t2_value)?;
+            let t4_value = t2_value.map(|t2| t2.square());
+            let t4 = witness(cs, t4_value)?;
+            let t5_value = t4_value.zip(t_value).map(|(t4, t)| t4 * t);
+            let t5 = witness(cs, t5_value)?;
+            {
+                let _ns = ns!(cs, "mimc_round");
+                cs.enforce_constraint(t.clone(), t.clone(), lc!() + t2)?;
+                cs.enforce_constraint(lc!() + t2, lc!() + t2, lc!() + t4)?;
+                cs.enforce_constraint(lc!() + t4, t, lc!() + t5)?;
+            }
+            x = (lc!() + t5, t5_value);
+        }
+        let value = x.1.zip(h.1).zip(*m_value).map(|((x, h), m)| x + h + h + m);
+        h = (x.0 + &h.0 + &h.0 + m, value);
+    }
+    Ok(h)
+}
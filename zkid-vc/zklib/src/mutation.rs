@@ -0,0 +1,504 @@
+// ============================================================================
+// Semantic Mutation Corpus (feature "mutation-corpus")
+// ============================================================================
+//
+// Fuzzing finds parser crashes, not acceptance bugs: a credential whose
+// claims were reordered after signing, a presentation carrying another
+// proof's public inputs. The corpus generator derives a valid case (a
+// signed credential and a presentation for it) from a seed, applies one
+// semantic mutation and runs the verifier-side checks; every mutation
+// declares the class of error it must be rejected with.
+//
+// Checks run in the order a gateway applies them, and the first failing
+// one names the class:
+//
+//   Corrupt     the credential or presentation does not decode
+//   Signature   issuer signature, or the presentation names another issuer
+//   Time        outside the credential's dates or schedule
+//   Revocation  revoked (or superseded with ZK_SetRejectSuperseded)
+//   Proof       format refused or proof invalid for its public inputs
+//
+// The catalogue is extensible: features register their mutations with
+// Catalogue::register (see revocation_mutations for one that changes state
+// outside the case). Each case is fully determined by its seed; a failure
+// reports it, and run_case replays it alone.
+
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::{Rng, SeedableRng};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::presentation::Presentation;
+use crate::proof;
+use crate::revocation::RevocationRegistry;
+use crate::storage::storage;
+use crate::validation::{self, CheckOutcome, Policy, ZK_VALIDATE_ALL};
+use crate::validity::{self, Window};
+use crate::wire::PRESENTATION_MAGIC;
+use crate::{hex_to_bytes, prove_vc_hash_with_format, Anchored, VerifiableCredential};
+
+const ISSUER_LABEL: &[u8] = b"zkid mutation corpus issuer";
+const CASE_DOMAIN: &[u8] = b"zkid:mutation-case";
+
+/// Nonces of the two presentations every case carries
+const NONCES: [u64; 2] = [1001, 1002];
+
+/// Dates of the base presentations' proofs, covering every case's
+const CORPUS_WINDOW: Window = Window { issue_date: 1_500_000_000, expiry_date: 1_800_000_000 + 5 * 365 * 86_400 };
+
+/// What the checks reported for a case
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    Accepted,
+    Corrupt,
+    Signature,
+    Time,
+    Revocation,
+    Proof,
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// One valid credential and presentation, in wire form so mutations can
+/// work on bytes as well as on decoded fields
+#[derive(Clone, Debug)]
+pub struct Case {
+    pub vc_blob: Vec<u8>,
+    pub presentation: Vec<u8>,
+    /// A second presentation of the same issuer under another nonce
+    pub other_presentation: Vec<u8>,
+    pub issuer_pubkey: [u8; 32],
+    pub current_time: u64,
+}
+
+impl Case {
+    /// Decode the credential, edit it and re-encode it without re-signing
+    pub fn edit_vc(&mut self, edit: impl FnOnce(&mut VerifiableCredential)) -> bool {
+        match VerifiableCredential::from_bytes(&self.vc_blob) {
+            Some(mut vc) => {
+                edit(&mut vc);
+                self.vc_blob = vc.to_bytes();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Decode the presentation, edit it and re-encode it
+    pub fn edit_presentation(&mut self, edit: impl FnOnce(&mut Presentation)) -> bool {
+        match Presentation::from_bytes(&self.presentation) {
+            Some(mut presentation) => {
+                edit(&mut presentation);
+                self.presentation = presentation.to_bytes();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A semantic mutation and the class of error it must produce
+///
+/// `apply` returns false if the case does not admit the mutation (e.g. it
+/// has too few claims); such cases are counted as skipped.
+#[derive(Clone, Copy)]
+pub struct Mutation {
+    pub name: &'static str,
+    pub expected: ErrorClass,
+    pub apply: fn(&mut Case, &mut StdRng) -> bool,
+}
+
+/// Flip one bit of byte `index`
+fn flip_bit(bytes: &mut [u8], index: usize, rng: &mut StdRng) {
+    bytes[index] ^= 1 << rng.gen_range(0..8);
+}
+
+/// Offset of the expiry date in a VC blob
+fn expiry_offset(blob: &[u8]) -> Option<usize> {
+    let field_len = |at: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(blob.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+    // magic | version | holder_id | issuer | issue_date | expiry_date
+    let issuer_at = 5 + 4 + field_len(5)?;
+    let issue_at = issuer_at + 4 + field_len(issuer_at)?;
+    Some(issue_at + 8).filter(|&at| at + 8 <= blob.len())
+}
+
+/// Credential and presentation mutations every build supports
+pub fn core_mutations() -> Vec<Mutation> {
+    vec![
+        Mutation {
+            name: "claim-reorder",
+            expected: ErrorClass::Signature,
+            apply: |case, rng| {
+                let mut applied = false;
+                case.edit_vc(|vc| {
+                    let n = vc.claims.len();
+                    if n >= 2 {
+                        let i = rng.gen_range(0..n);
+                        let j = (i + rng.gen_range(1..n)) % n;
+                        applied = vc.claims[i] != vc.claims[j];
+                        vc.claims.swap(i, j);
+                    }
+                });
+                applied
+            },
+        },
+        Mutation {
+            name: "claim-value-bit-flip",
+            expected: ErrorClass::Signature,
+            apply: |case, rng| {
+                let mut applied = false;
+                case.edit_vc(|vc| {
+                    let i = rng.gen_range(0..vc.claims.len());
+                    // Low bits of an ASCII character keep the value UTF-8
+                    let mut value = vc.claims[i].1.clone().into_bytes();
+                    if let Some(byte) = value.iter_mut().find(|b| b.is_ascii_alphanumeric()) {
+                        *byte ^= 1 << rng.gen_range(0..5);
+                        vc.claims[i].1 = String::from_utf8_lossy(&value).into_owned();
+                        applied = true;
+                    }
+                });
+                applied
+            },
+        },
+        Mutation {
+            name: "date-swap",
+            expected: ErrorClass::Signature,
+            apply: |case, _| {
+                case.edit_vc(|vc| std::mem::swap(&mut vc.issue_date, &mut vc.expiry_date))
+            },
+        },
+        Mutation {
+            name: "expiry-byte-flip",
+            expected: ErrorClass::Signature,
+            apply: |case, rng| match expiry_offset(&case.vc_blob) {
+                // Low bytes only: a high byte could push the date out of the
+                // decodable range, which is a parser concern
+                Some(at) => {
+                    let index = at + rng.gen_range(0..3);
+                    flip_bit(&mut case.vc_blob, index, rng);
+                    true
+                }
+                None => false,
+            },
+        },
+        Mutation {
+            name: "signature-byte-flip",
+            expected: ErrorClass::Signature,
+            apply: |case, rng| {
+                case.edit_vc(|vc| {
+                    let i = rng.gen_range(0..vc.signature.len());
+                    vc.signature[i] ^= 1 << rng.gen_range(0..8);
+                })
+            },
+        },
+        Mutation {
+            name: "issuer-swap",
+            expected: ErrorClass::Signature,
+            apply: |case, rng| {
+                let other: [u8; 32] = rng.gen();
+                case.edit_presentation(|p| {
+                    p.issuer_pubkey = SigningKey::from_bytes(&other).verifying_key().to_bytes().to_vec()
+                })
+            },
+        },
+        Mutation {
+            name: "public-input-swap",
+            expected: ErrorClass::Proof,
+            apply: |case, _| {
+                let other = match Presentation::from_bytes(&case.other_presentation) {
+                    Some(other) => other,
+                    None => return false,
+                };
+                case.edit_presentation(|p| p.nonce = other.nonce)
+            },
+        },
+        Mutation {
+            name: "proof-swap",
+            expected: ErrorClass::Proof,
+            apply: |case, _| {
+                let other = match Presentation::from_bytes(&case.other_presentation) {
+                    Some(other) => other,
+                    None => return false,
+                };
+                case.edit_presentation(|p| p.proof = other.proof)
+            },
+        },
+        Mutation {
+            name: "envelope-version-downgrade",
+            expected: ErrorClass::Corrupt,
+            apply: |case, _| {
+                let version = PRESENTATION_MAGIC.len();
+                match case.presentation.get_mut(version) {
+                    Some(byte) if *byte > 0 => {
+                        *byte -= 1;
+                        true
+                    }
+                    _ => false,
+                }
+            },
+        },
+        Mutation {
+            name: "checked-after-expiry",
+            expected: ErrorClass::Time,
+            apply: |case, rng| match VerifiableCredential::from_bytes(&case.vc_blob) {
+                Some(vc) => {
+                    case.current_time = vc.expiry_date as u64 + rng.gen_range(1..1_000_000);
+                    true
+                }
+                None => false,
+            },
+        },
+    ]
+}
+
+/// Mutations of the revocation registry: the case's credential is revoked
+/// in the global storage backend (each case has its own credential)
+pub fn revocation_mutations() -> Vec<Mutation> {
+    vec![Mutation {
+        name: "revoked-credential",
+        expected: ErrorClass::Revocation,
+        apply: |case, _| match VerifiableCredential::from_bytes(&case.vc_blob) {
+            Some(vc) => RevocationRegistry::new(storage())
+                .revoke(vc.credential_id().as_bytes(), case.current_time)
+                .is_ok(),
+            None => false,
+        },
+    }]
+}
+
+/// The mutations a run applies
+#[derive(Clone, Default)]
+pub struct Catalogue {
+    mutations: Vec<Mutation>,
+}
+
+impl Catalogue {
+    /// Every mutation registered by the library
+    pub fn standard() -> Self {
+        let mut catalogue = Self::default();
+        for mutation in core_mutations().into_iter().chain(revocation_mutations()) {
+            catalogue.register(mutation);
+        }
+        catalogue
+    }
+
+    /// Add a mutation; one with the same name is replaced
+    pub fn register(&mut self, mutation: Mutation) {
+        self.mutations.retain(|m| m.name != mutation.name);
+        self.mutations.push(mutation);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Mutation> {
+        self.mutations.iter().find(|m| m.name == name)
+    }
+
+    pub fn mutations(&self) -> &[Mutation] {
+        &self.mutations
+    }
+}
+
+/// Issuer and presentations shared by every case of a run
+pub struct Corpus {
+    issuer: SigningKey,
+    presentations: [Vec<u8>; 2],
+}
+
+impl Corpus {
+    /// Prove the two base presentations under the global keys (ZK_Init)
+    pub fn new() -> Option<Self> {
+        let issuer = SigningKey::from_bytes(&Sha256::digest(ISSUER_LABEL).into());
+        let issuer_pubkey = issuer.verifying_key().to_bytes();
+        let format = proof::emit_format();
+
+        // classify checks the proof at the time it carries, not its anchor
+        // against the case's credential, so proofs over one placeholder
+        // anchor serve every credential of the run
+        let placeholder = [0u8; 32];
+        let opening = validity::Opening::of(&placeholder, CORPUS_WINDOW)?;
+        let signature = issuer.sign(&validity::anchor_message(&opening.anchor())).to_bytes();
+        let credential = Anchored { opening, signature: &signature };
+
+        let mut presentations = [Vec::new(), Vec::new()];
+        for (out, nonce) in presentations.iter_mut().zip(NONCES) {
+            let proven_at = CORPUS_WINDOW.issue_date as u64;
+            let proof_hex = prove_vc_hash_with_format(format, &credential, &issuer_pubkey, proven_at, nonce).ok()?;
+            let (header, format, proof) = validity::decode(&hex_to_bytes(&proof_hex).ok()?).ok()?;
+            *out = Presentation {
+                format,
+                proof,
+                header,
+                issuer_pubkey: issuer_pubkey.to_vec(),
+                nonce,
+                metadata: vec![("audience".to_string(), "corpus.example".to_string())],
+            }
+            .to_bytes();
+        }
+        Some(Self { issuer, presentations })
+    }
+
+    /// The valid case for `seed`
+    pub fn case(&self, seed: u64) -> Case {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let issue_date = rng.gen_range(1_500_000_000i64..1_800_000_000);
+        let expiry_date = issue_date + rng.gen_range(86_400..5 * 365 * 86_400);
+        let current_time = rng.gen_range(issue_date..=expiry_date) as u64;
+
+        let claim_count = rng.gen_range(2..6);
+        let claims = (0..claim_count)
+            .map(|i| (format!("claim{i}"), format!("value-{:08x}", rng.gen::<u32>())))
+            .collect();
+        let mut vc = VerifiableCredential {
+            holder_id: format!("holder-{:016x}@example.com", rng.gen::<u64>()),
+            issuer: "did:example:corpus".to_string(),
+            issue_date,
+            expiry_date,
+            claims,
+            signature: Vec::new(),
+            co_signature: None,
+            supersedes: None,
+            validity_schedule: None,
+            legacy_signature: false,
+            claims_root_signed: true,
+            length_prefixed: true,
+            domain_separated: false,
+            anchored: true,
+            schedule_anchored: true,
+        };
+        let message = vc.signed_message().expect("corpus claims bind no holder key");
+        vc.signature = self.issuer.sign(&message).to_bytes().to_vec();
+
+        let pick = rng.gen_range(0..2);
+        Case {
+            vc_blob: vc.to_bytes(),
+            presentation: self.presentations[pick].clone(),
+            other_presentation: self.presentations[1 - pick].clone(),
+            issuer_pubkey: self.issuer.verifying_key().to_bytes(),
+            current_time,
+        }
+    }
+}
+
+/// Run the verifier-side checks on a case
+pub fn classify(case: &Case) -> ErrorClass {
+    let vc = match VerifiableCredential::from_bytes(&case.vc_blob) {
+        Some(vc) => vc,
+        None => return ErrorClass::Corrupt,
+    };
+    let issuer = match ed25519_dalek::VerifyingKey::from_bytes(&case.issuer_pubkey) {
+        Ok(key) => key,
+        Err(_) => return ErrorClass::Signature,
+    };
+
+    let report = validation::validate(&vc, &[issuer], None, &Policy::global(ZK_VALIDATE_ALL), case.current_time);
+    for (outcome, class) in [
+        (report.signature, ErrorClass::Signature),
+        (report.schema, ErrorClass::Signature),
+        (report.time_policy, ErrorClass::Time),
+        (report.revocation, ErrorClass::Revocation),
+    ] {
+        if !matches!(outcome, CheckOutcome::Passed | CheckOutcome::Skipped) {
+            return class;
+        }
+    }
+
+    let presentation = match Presentation::from_bytes(&case.presentation) {
+        Some(p) => p,
+        None => return ErrorClass::Corrupt,
+    };
+    if presentation.issuer_pubkey != case.issuer_pubkey {
+        return ErrorClass::Signature;
+    }
+    if proof::admit(presentation.format, proof::accepted_formats()).is_err()
+        || !presentation.verify_with_global_keys()
+    {
+        return ErrorClass::Proof;
+    }
+    ErrorClass::Accepted
+}
+
+/// Result of one mutated case
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CaseResult {
+    pub mutation: &'static str,
+    pub seed: u64,
+    pub expected: ErrorClass,
+    /// None if the case did not admit the mutation
+    pub got: Option<ErrorClass>,
+    /// Class of the unmutated case; anything but Accepted is a corpus bug
+    pub baseline: ErrorClass,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.baseline == ErrorClass::Accepted && self.got.is_none_or(|got| got == self.expected)
+    }
+}
+
+impl fmt::Display for CaseResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.got {
+            _ if self.baseline != ErrorClass::Accepted => write!(
+                f,
+                "{} seed {:#018x}: unmutated case rejected as {}",
+                self.mutation, self.seed, self.baseline
+            ),
+            Some(got) => write!(
+                f,
+                "{} seed {:#018x}: expected {}, got {}",
+                self.mutation, self.seed, self.expected, got
+            ),
+            None => write!(f, "{} seed {:#018x}: not applicable", self.mutation, self.seed),
+        }
+    }
+}
+
+/// Seed of case `round` of `mutation` in a run seeded with `run_seed`
+pub fn case_seed(run_seed: u64, mutation: &str, round: u32) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(CASE_DOMAIN);
+    hasher.update(run_seed.to_le_bytes());
+    hasher.update((mutation.len() as u32).to_le_bytes());
+    hasher.update(mutation.as_bytes());
+    hasher.update(round.to_le_bytes());
+    u64::from_le_bytes(hasher.finalize()[..8].try_into().expect("digest is 32 bytes"))
+}
+
+/// Build, check and mutate the case for `seed`; returns the mutated case
+/// (None if not applicable) alongside the result
+pub fn run_case(corpus: &Corpus, mutation: &Mutation, seed: u64) -> (CaseResult, Option<Case>) {
+    let mut case = corpus.case(seed);
+    let baseline = classify(&case);
+
+    // The mutation draws from its own stream so case generation can grow
+    // without changing what existing seeds mutate
+    let mut rng = StdRng::seed_from_u64(seed.rotate_left(32) ^ 0x6d75_7461_7465_2121);
+    let applied = (mutation.apply)(&mut case, &mut rng);
+    let got = applied.then(|| classify(&case));
+
+    let result = CaseResult {
+        mutation: mutation.name,
+        seed,
+        expected: mutation.expected,
+        got,
+        baseline,
+    };
+    (result, applied.then_some(case))
+}
+
+/// Run `rounds` cases of every mutation in the catalogue
+pub fn run(corpus: &Corpus, catalogue: &Catalogue, run_seed: u64, rounds: u32) -> Vec<CaseResult> {
+    catalogue
+        .mutations()
+        .iter()
+        .flat_map(|mutation| {
+            (0..rounds).map(move |round| run_case(corpus, mutation, case_seed(run_seed, mutation.name, round)).0)
+        })
+        .collect()
+}
@@ -0,0 +1,839 @@
+// ============================================================================
+// Revocation Tree (non-revocation proofs)
+// ============================================================================
+//
+// The revocation registry (revocation.rs) lists revoked credential ids, which
+// a verifier can only check against a credential it sees. A non-revoked
+// proof shows instead that the hidden credential is absent from the issuer's
+// revocation tree, a Merkle tree whose root the issuer publishes.
+//
+// The tree is keyed by the low 128 bits of the VC hash as the format-2
+// circuit maps it (proof.rs). Its leaves are the gaps between consecutive
+// revoked keys, in order, with a -1 (the field element) below the first
+// and 2^128 above the last:
+//
+//   leaf = MiMC(low, high)        (an empty leaf is 0)
+//   node = MiMC(left, right)
+//
+// over REVOCATION_TREE_DEPTH levels, so the tree holds up to
+// MAX_REVOCATIONS keys. A credential is not revoked exactly when some gap
+// has low < key < high. The issuer's tree (ZK_RevocationTreeCreate) hands a
+// holder the witness of its gap, its position and siblings:
+//
+//   "ZKNW" | version u8 | index u32 | low [32] | high [32]
+//   | REVOCATION_TREE_DEPTH siblings [32]
+//
+// (tree values 32-byte little-endian, see disclosure.rs). The witness holds
+// for one root: after a revocation the gaps shift, and a holder fetches a
+// new one.
+//
+// The non-revoked circuit is the VC circuit with the revocation root as a
+// further public input, after the anchor. It splits the private VC hash,
+// the one the issuer-signed anchor opens to (validity.rs), into
+// lo + 2^128 * hi with lo below 2^128 and hi below r >> 128, a
+// split that is unique, and opens the leaf of a gap with low < lo < high
+// under the root: both differences minus one decompose into 128 bits. A VC
+// hash in the top 2^-126 of the field has no such split and no key; it can
+// be neither revoked nor proven. The prover checks natively that the
+// witness reaches the root and brackets the key (ZK_ERR_REVOKED
+// otherwise); there is no grace. About 12,000 constraints more than the VC
+// circuit.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{One, PrimeField, Zero};
+use ark_groth16::PreparedVerifyingKey;
+use std::collections::BTreeSet;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::OnceLock;
+
+use crate::admission::text_arg;
+use crate::disclosure::{field_bytes, field_from_bytes, root_from_path};
+use crate::error::{ZK_ERR_BAD_HEX, ZK_ERR_CAPACITY, ZK_ERR_REVOKED};
+use crate::ffi::write_cstr;
+use crate::wire::Reader;
+use crate::{bytes_to_hex, hex_to_bytes, last_error, mimc};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::ffi::MAX_FIELD_LEN,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::dates,
+    crate::disclosure::enforce_path,
+    crate::entropy,
+    crate::error::ZK_ERR_NOT_INITIALIZED,
+    crate::ffi::{read_bytes, reserve_cstr},
+    crate::proof::PROOF_FORMAT_V2,
+    crate::schedule::{enforce_bits, witness},
+    crate::{generate_vc_proof, prove_with_key, seeded_rng, sizes, vc_circuit, Anchored, ProofCredential, VCCircuit},
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSystemRef, SynthesisError, Variable},
+    ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
+    std::sync::Arc,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::proof::{self, ZK_ACCEPT_FORMAT_V2},
+    crate::sizes::NON_REVOKED_VC_PUBLIC_INPUTS,
+    crate::{admission, audit, did, validity},
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const WITNESS_MAGIC: &[u8; 4] = b"ZKNW";
+const WITNESS_VERSION: u8 = 1;
+
+/// Levels between a gap leaf and the root of the revocation tree
+pub const REVOCATION_TREE_DEPTH: usize = 16;
+
+/// Most keys a revocation tree holds: one gap more than keys per leaf
+pub const MAX_REVOCATIONS: usize = (1 << REVOCATION_TREE_DEPTH) - 1;
+
+/// Length of an encoded witness
+pub const REVOCATION_WITNESS_LEN: usize = WITNESS_MAGIC.len() + 1 + 4 + 32 * (2 + REVOCATION_TREE_DEPTH);
+
+/// Circuit id of the non-revoked VC circuit
+pub const NON_REVOKED_VC_CIRCUIT: &str = "zkid-vc/non-revoked/v4";
+
+/// Bits of a revocation key
+#[cfg(feature = "prover")]
+const KEY_BITS: usize = 128;
+
+/// Bits of hi in a VC hash's split
+#[cfg(feature = "prover")]
+const HIGH_BITS: usize = 126;
+
+/// r >> 128: hi of every split lies below it
+const MODULUS_HIGH: u128 = 0x30644e72e131a029b85045b68181585d;
+
+/// Low and high 128 bits of a field element
+fn split(value: &Fr) -> (u128, u128) {
+    let limbs = value.into_bigint().0;
+    (
+        limbs[0] as u128 | (limbs[1] as u128) << 64,
+        limbs[2] as u128 | (limbs[3] as u128) << 64,
+    )
+}
+
+/// The value of a field element below 2^128, None above
+fn below_key_range(value: Fr) -> Option<u128> {
+    match split(&value) {
+        (low, 0) => Some(low),
+        _ => None,
+    }
+}
+
+/// Field element of a VC message hash, as the anchor holds it
+pub fn vc_hash_field(vc_message_hash: &[u8]) -> Option<Fr> {
+    crate::validity::vc_hash_field(vc_message_hash)
+}
+
+/// Revocation key of a VC hash field element; None in the top 2^-126 of the
+/// field
+pub fn revocation_key(vc_hash: &Fr) -> Option<u128> {
+    let (low, high) = split(vc_hash);
+    (high < MODULUS_HIGH).then_some(low)
+}
+
+/// Leaf of the gap between two revoked keys (or the bounds)
+pub fn gap_leaf(low: Fr, high: Fr) -> Fr {
+    mimc::hash(&[low, high])
+}
+
+fn node(left: Fr, right: Fr) -> Fr {
+    mimc::hash(&[left, right])
+}
+
+/// Bound below the first key
+fn floor() -> Fr {
+    -Fr::one()
+}
+
+/// Bound above the last key, 2^128
+fn ceiling() -> Fr {
+    Fr::from(u128::MAX) + Fr::one()
+}
+
+/// Root of the empty subtree at each height, leaves (0) to the root
+fn empty_subtrees() -> &'static [Fr; REVOCATION_TREE_DEPTH + 1] {
+    static EMPTY: OnceLock<[Fr; REVOCATION_TREE_DEPTH + 1]> = OnceLock::new();
+    EMPTY.get_or_init(|| {
+        let mut empty = [Fr::zero(); REVOCATION_TREE_DEPTH + 1];
+        for height in 1..=REVOCATION_TREE_DEPTH {
+            empty[height] = node(empty[height - 1], empty[height - 1]);
+        }
+        empty
+    })
+}
+
+/// A holder's witness that its key lies in a gap of the tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness {
+    pub index: u32,
+    pub low: Fr,
+    pub high: Fr,
+    /// From the leaf up
+    pub siblings: Vec<Fr>,
+}
+
+impl Witness {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = WITNESS_MAGIC.to_vec();
+        out.push(WITNESS_VERSION);
+        out.extend_from_slice(&self.index.to_le_bytes());
+        for value in [&self.low, &self.high].into_iter().chain(&self.siblings) {
+            out.extend_from_slice(&field_bytes(value));
+        }
+        out
+    }
+
+    /// Decode a witness; None unless well-formed, its index within the tree
+    /// and every value canonical
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(WITNESS_MAGIC.len())? != WITNESS_MAGIC || reader.u8()? != WITNESS_VERSION {
+            return None;
+        }
+        let index = reader.u32()?;
+        if index as usize > MAX_REVOCATIONS {
+            return None;
+        }
+        let mut field = || reader.take(32).and_then(field_from_bytes);
+        let (low, high) = (field()?, field()?);
+        let siblings = (0..REVOCATION_TREE_DEPTH).map(|_| field()).collect::<Option<Vec<_>>>()?;
+        reader.is_empty().then_some(Self { index, low, high, siblings })
+    }
+
+    /// Root the witness reaches
+    pub fn root(&self) -> Fr {
+        root_from_path(gap_leaf(self.low, self.high), self.index as usize, &self.siblings)
+    }
+
+    /// Whether the gap brackets the key of `vc_hash`, as the circuit checks
+    /// it
+    pub fn brackets(&self, vc_hash: &Fr) -> bool {
+        let key = match revocation_key(vc_hash) {
+            Some(key) => Fr::from(key),
+            None => return false,
+        };
+        below_key_range(key - self.low - Fr::one()).is_some() && below_key_range(self.high - key - Fr::one()).is_some()
+    }
+}
+
+/// An issuer's revocation tree
+#[derive(Clone, Debug, Default)]
+pub struct RevocationTree {
+    keys: BTreeSet<u128>,
+    // levels[0] holds the gap leaves, levels[REVOCATION_TREE_DEPTH] the
+    // root; an insertion shifts the gaps after it, so the levels are
+    // rebuilt on the first read after one
+    levels: Option<Vec<Vec<Fr>>>,
+}
+
+impl RevocationTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: u128) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Revoke `key`: Ok(true) if added, Ok(false) if already revoked,
+    /// ZK_ERR_CAPACITY past MAX_REVOCATIONS
+    pub fn insert(&mut self, key: u128) -> Result<bool, c_int> {
+        if self.keys.contains(&key) {
+            return Ok(false);
+        }
+        if self.keys.len() >= MAX_REVOCATIONS {
+            return Err(ZK_ERR_CAPACITY);
+        }
+        self.levels = None;
+        Ok(self.keys.insert(key))
+    }
+
+    fn levels(&mut self) -> &Vec<Vec<Fr>> {
+        let keys = &self.keys;
+        self.levels.get_or_insert_with(|| {
+            let bounds: Vec<Fr> = std::iter::once(floor())
+                .chain(keys.iter().map(|&key| Fr::from(key)))
+                .chain(std::iter::once(ceiling()))
+                .collect();
+            let empty = empty_subtrees();
+            let mut levels = vec![bounds.windows(2).map(|gap| gap_leaf(gap[0], gap[1])).collect::<Vec<_>>()];
+            for height in 0..REVOCATION_TREE_DEPTH {
+                let above = levels[height]
+                    .chunks(2)
+                    .map(|pair| node(pair[0], pair.get(1).copied().unwrap_or(empty[height])))
+                    .collect();
+                levels.push(above);
+            }
+            levels
+        })
+    }
+
+    pub fn root(&mut self) -> Fr {
+        self.levels()[REVOCATION_TREE_DEPTH][0]
+    }
+
+    /// Witness of the gap holding `key`; None if it is revoked
+    pub fn witness(&mut self, key: u128) -> Option<Witness> {
+        if self.keys.contains(&key) {
+            return None;
+        }
+        let index = self.keys.range(..key).count();
+        let low = self.keys.range(..key).next_back().map_or(floor(), |&low| Fr::from(low));
+        let high = self.keys.range(key..).next().map_or(ceiling(), |&high| Fr::from(high));
+        let empty = empty_subtrees();
+        let levels = self.levels();
+        let siblings = (0..REVOCATION_TREE_DEPTH)
+            .map(|height| levels[height].get((index >> height) ^ 1).copied().unwrap_or(empty[height]))
+            .collect();
+        Some(Witness { index: index as u32, low, high, siblings })
+    }
+}
+
+/// A non-revoked proof's root and the holder's witness under it
+#[cfg(feature = "prover")]
+#[derive(Clone, Debug)]
+pub(crate) struct Revocation {
+    pub root: Fr,
+    pub witness: Witness,
+}
+
+#[cfg(feature = "prover")]
+impl Revocation {
+    /// Whether the witness shows the credential of `vc_message_hash` absent
+    /// from the tree under the root
+    pub(crate) fn excludes(&self, vc_message_hash: &[u8]) -> bool {
+        vc_hash_field(vc_message_hash).is_some_and(|vc_hash| {
+            self.witness.root() == self.root && self.witness.brackets(&vc_hash)
+        })
+    }
+}
+
+// ============================================================================
+// ZK Circuit: VC Hash Outside the Revocation Tree
+// ============================================================================
+
+/// The gap leaf and its authentication path: the private witness the VC
+/// circuit adds for a non-revoked proof
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+pub(crate) struct GapPath {
+    low: Option<Fr>,
+    high: Option<Fr>,
+    siblings: Vec<Option<Fr>>,
+    // true where the running node is the right child
+    position: Vec<Option<bool>>,
+}
+
+#[cfg(feature = "prover")]
+impl GapPath {
+    pub(crate) fn blank() -> Self {
+        Self {
+            low: None,
+            high: None,
+            siblings: vec![None; REVOCATION_TREE_DEPTH],
+            position: vec![None; REVOCATION_TREE_DEPTH],
+        }
+    }
+
+    pub(crate) fn of(witness: &Witness) -> Self {
+        let index = witness.index as usize;
+        Self {
+            low: Some(witness.low),
+            high: Some(witness.high),
+            siblings: witness.siblings.iter().copied().map(Some).collect(),
+            position: (0..REVOCATION_TREE_DEPTH).map(|height| Some((index >> height) & 1 == 1)).collect(),
+        }
+    }
+}
+
+/// Constrain the key of the private VC hash to lie in the gap of `gap`
+/// under `revocation_root`
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_non_membership(
+    cs: &ConstraintSystemRef<Fr>,
+    vc_hash: (Variable, Option<Fr>),
+    gap: &GapPath,
+    revocation_root: (Variable, Option<Fr>),
+) -> Result<(), SynthesisError> {
+    // vc_hash = lo + 2^128 * hi, lo < 2^128 and hi < r >> 128
+    let parts = vc_hash.1.as_ref().map(split);
+    let lo_value = parts.map(|(lo, _)| Fr::from(lo));
+    let lo = witness(cs, lo_value)?;
+    let hi = witness(cs, parts.map(|(_, hi)| Fr::from(hi)))?;
+    {
+        let _ns = ark_relations::ns!(cs, "revocation_key");
+        cs.enforce_constraint(lc!() + lo + (ceiling(), hi), lc!() + Variable::One, lc!() + vc_hash.0)?;
+    }
+    enforce_bits(cs, lc!() + lo, parts.map(|(lo, _)| lo), KEY_BITS)?;
+    enforce_bits(cs, lc!() + hi, parts.map(|(_, hi)| hi), HIGH_BITS)?;
+    let headroom = parts.map(|(_, hi)| (MODULUS_HIGH - 1).wrapping_sub(hi));
+    enforce_bits(cs, lc!() + (Fr::from(MODULUS_HIGH - 1), Variable::One) - hi, headroom, HIGH_BITS)?;
+
+    // low < lo < high: lo - low - 1 and high - lo - 1 in [0, 2^128). A
+    // revoked key is a bound of both gaps around it and in none.
+    let low = witness(cs, gap.low)?;
+    let high = witness(cs, gap.high)?;
+    let below = lo_value.zip(gap.low).map(|(k, l)| split(&(k - l - Fr::one())).0);
+    let above = lo_value.zip(gap.high).map(|(k, h)| split(&(h - k - Fr::one())).0);
+    enforce_bits(cs, lc!() + lo - low - (Fr::one(), Variable::One), below, KEY_BITS)?;
+    enforce_bits(cs, lc!() + high - lo - (Fr::one(), Variable::One), above, KEY_BITS)?;
+
+    let leaf = mimc::hash_gadget(cs, &[(lc!() + low, gap.low), (lc!() + high, gap.high)])?;
+    let (root, _) = enforce_path(cs, leaf, &gap.siblings, &gap.position)?;
+    let _ns = ark_relations::ns!(cs, "revocation_root");
+    cs.enforce_constraint(root - revocation_root.0, lc!() + Variable::One, lc!())
+}
+
+/// Keys of the non-revoked VC circuit; the verifying key of a
+/// verifier-only build arrives by import
+#[derive(Default)]
+struct NonRevokedKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static NON_REVOKED_KEYS: Mutex<NonRevokedKeys> = Mutex::new(NonRevokedKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the non-revoked VC circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(13u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(VCCircuit::blank_non_revoked(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match NON_REVOKED_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(Arc::new(pk));
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the non-revoked VC verifying key is present
+pub(crate) fn has_keys() -> bool {
+    NON_REVOKED_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the non-revoked VC circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = NON_REVOKED_KEYS.lock() {
+        *keys = NonRevokedKeys::default();
+    }
+}
+
+/// Generate the hex non-revoked VC proof (format 2, which the tree keys
+/// follow) for an already checked credential and witness
+#[cfg(feature = "prover")]
+pub(crate) fn prove(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+    revocation: &Revocation,
+) -> Result<String, c_int> {
+    let pk = NON_REVOKED_KEYS.lock().map_err(|_| -1)?.pk.clone().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+
+    let (circuit, header) =
+        vc_circuit(PROOF_FORMAT_V2, credential, issuer_pubkey_bytes, current_time, nonce, None).ok_or(-1)?;
+    let circuit = VCCircuit {
+        non_revoked: true,
+        revocation_root: Some(revocation.root),
+        gap: GapPath::of(&revocation.witness),
+        ..circuit
+    };
+    prove_with_key(&pk, PROOF_FORMAT_V2, circuit, &header, &mut entropy::prover_rng(nonce)?)
+}
+
+/// Verify a non-revoked proof for a revocation root at `current_time`
+#[cfg(feature = "verifier")]
+fn verify_non_revoked(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    revocation_root: Fr,
+) -> c_int {
+    let issuer_pubkey_bytes = match admission::text_arg(issuer_pubkey, MAX_FIELD_LEN).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return last_error::fail(e.code_or(0), "issuer_pubkey is not hex or a did:key"),
+        None => return last_error::fail(0, "issuer_pubkey is NULL or too long"),
+    };
+    // Tree keys follow the format-2 mapping, so only format 2 proves them
+    let accepted = proof::accepted_formats() & ZK_ACCEPT_FORMAT_V2;
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, accepted) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return last_error::fail(rejection.verify_code(), rejection.describe()),
+    };
+    let now = match crate::proof_time(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+
+    // The VC inputs, then the revocation root (circuit order)
+    let mut public_inputs = match validity::public_inputs(&issuer_pubkey_bytes, format, nonce, now, &header) {
+        Some(inputs) => inputs.to_vec(),
+        None => return last_error::fail(0, crate::UNSIGNED_ANCHOR),
+    };
+    public_inputs.push(revocation_root);
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    match valid {
+        true => 1,
+        false if header.current_time != now => last_error::fail(0, crate::verify_failure(&header, now)),
+        false => last_error::fail(0, "proof does not verify for issuer_pubkey, nonce and revocation_root"),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+fn tree_mut<'a>(handle: *mut RevocationTree) -> Option<&'a mut RevocationTree> {
+    unsafe { handle.as_mut() }
+}
+
+/// Revocation key of a hex VC message hash (ZK_ComputeVCHash)
+fn key_arg(vc_hash_hex: *const c_char) -> Result<u128, c_int> {
+    let vc_hash = text_arg(vc_hash_hex, 64)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .filter(|bytes| bytes.len() == 32)
+        .ok_or_else(|| last_error::fail(ZK_ERR_BAD_HEX, "vc_hash is not 32-byte hex"))?;
+    vc_hash_field(&vc_hash)
+        .as_ref()
+        .and_then(revocation_key)
+        .ok_or_else(|| last_error::fail(-1, "vc_hash has no revocation key"))
+}
+
+crate::unwind::entry_point! {
+/// Create an empty revocation tree; the handle is written to `tree_out`
+///
+/// Returns 0 on success, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_RevocationTreeCreate(tree_out: *mut *mut RevocationTree) -> c_int {
+    if tree_out.is_null() {
+        return -1;
+    }
+    unsafe {
+        *tree_out = Box::into_raw(Box::new(RevocationTree::new()));
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Revoke the credential of a hex VC message hash (ZK_ComputeVCHash)
+///
+/// The root changes, and witnesses under the old root prove nothing under
+/// the new one. Returns 0 if added, 1 if already revoked, ZK_ERR_BAD_HEX,
+/// ZK_ERR_CAPACITY past MAX_REVOCATIONS, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_RevocationTreeAdd(tree: *mut RevocationTree, vc_hash_hex: *const c_char) -> c_int {
+    last_error::clear();
+    let tree = match tree_mut(tree) {
+        Some(tree) => tree,
+        None => return last_error::fail(-1, "tree is NULL"),
+    };
+    let key = match key_arg(vc_hash_hex) {
+        Ok(key) => key,
+        Err(code) => return code,
+    };
+
+    match tree.insert(key) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(code) => last_error::fail(code, "revocation tree is full"),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the tree's root (hex), which verifiers pass to
+/// ZK_VerifyVCProofNonRevoked
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_RevocationTreeRoot(tree: *mut RevocationTree, root_out: *mut c_char, root_out_size: usize) -> c_int {
+    let tree = match tree_mut(tree) {
+        Some(tree) => tree,
+        None => return -1,
+    };
+
+    match write_cstr(root_out, root_out_size, &bytes_to_hex(&field_bytes(&tree.root()))) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the non-revocation witness of a credential under the current root
+/// (hex, REVOCATION_WITNESS_LEN bytes, so 1171 with the terminator)
+///
+/// Returns 0 on success, ZK_ERR_REVOKED if the credential is revoked,
+/// ZK_ERR_BAD_HEX, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_RevocationTreeWitness(
+    tree: *mut RevocationTree,
+    vc_hash_hex: *const c_char,
+    witness_out: *mut c_char,
+    witness_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    let tree = match tree_mut(tree) {
+        Some(tree) => tree,
+        None => return last_error::fail(-1, "tree is NULL"),
+    };
+    let key = match key_arg(vc_hash_hex) {
+        Ok(key) => key,
+        Err(code) => return code,
+    };
+    let witness = match tree.witness(key) {
+        Some(witness) => witness,
+        None => return last_error::fail(ZK_ERR_REVOKED, "credential is revoked"),
+    };
+
+    match write_cstr(witness_out, witness_out_size, &bytes_to_hex(&witness.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("witness_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Free a revocation tree handle
+#[no_mangle]
+pub extern "C" fn ZK_RevocationTreeFree(tree: *mut RevocationTree) {
+    if !tree.is_null() {
+        unsafe {
+            drop(Box::from_raw(tree));
+        }
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Generate a non-revoked VC proof: a VC proof that also shows the
+/// credential absent from the revocation tree under `revocation_root`
+/// (u64 dates)
+///
+/// Takes the arguments of ZK_GenerateVCProof, the hex root and the hex
+/// witness from ZK_RevocationTreeWitness under that root. The proof
+/// verifies with ZK_VerifyVCProofNonRevoked for this root only. Returns the
+/// codes of ZK_GenerateVCProof, ZK_ERR_BAD_HEX for a malformed root or
+/// witness, and ZK_ERR_REVOKED if the witness does not reach the root or
+/// its gap does not hold the credential (after a revocation, fetch a new
+/// one). There is no grace after expiry.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofNonRevoked(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    vc_signature: *const c_char,
+    issuer_pubkey: *const c_char,
+    revocation_root: *const c_char,
+    witness: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || issuer_pubkey.is_null() {
+        return last_error::fail(-1, "holder_id, issuer, vc_signature or issuer_pubkey is NULL");
+    }
+    if revocation_root.is_null() || witness.is_null() {
+        return last_error::fail(-1, "revocation_root or witness is NULL");
+    }
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    let root = match text_arg(revocation_root, 64)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| field_from_bytes(&bytes))
+    {
+        Some(root) => root,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "revocation_root is not a 32-byte field element"),
+    };
+    let witness = match text_arg(witness, 2 * REVOCATION_WITNESS_LEN)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| Witness::from_bytes(&bytes))
+    {
+        Some(witness) => witness,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "witness is not a hex revocation witness"),
+    };
+
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
+    };
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
+    };
+    let issuer_pubkey_bytes: [u8; PUBLIC_KEY_LENGTH] = match text_arg(issuer_pubkey, 2 * PUBLIC_KEY_LENGTH)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(bytes) => bytes,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_pubkey is not 32-byte hex"),
+    };
+    let vc_signature_bytes: [u8; SIGNATURE_LENGTH] = match text_arg(vc_signature, 2 * SIGNATURE_LENGTH)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(bytes) => bytes,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "vc_signature is not 64-byte hex"),
+    };
+
+    let credential = ProofCredential {
+        holder_id: holder_id_bytes,
+        issuer: issuer_bytes,
+        issue_date,
+        expiry_date,
+        signature: &vc_signature_bytes,
+        issuer_pubkey: &issuer_pubkey_bytes,
+        holder: None,
+        revocation: Some(Revocation { root, witness }),
+    };
+    let reserve = |required| reserve_cstr(proof_out, proof_out_size, required, required_size_out);
+    let proof_hex = match generate_vc_proof(&credential, current_time, nonce, sizes::ZK_SIZE_PROOF_HEX, reserve) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("proof_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a non-revoked VC proof against a revocation root (hex)
+///
+/// As ZK_VerifyVCProof, under the non-revoked key, for format-2 proofs. Pass
+/// the issuer's current root: a proof made under an older root does not
+/// verify against it. Returns 1 if valid, 0 if invalid (also for another
+/// root or a malformed one), and the other codes of ZK_VerifyVCProof.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofNonRevoked(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    revocation_root: *const c_char,
+) -> c_int {
+    let started = Instant::now();
+    last_error::clear();
+
+    let keys = NON_REVOKED_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let root = admission::text_arg(revocation_root, 64)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| field_from_bytes(&bytes));
+    let result = match (pvk, root) {
+        (None, _) => last_error::fail(0, "non-revoked VC verifying key is not set up; call ZK_Init or import it"),
+        (_, None) => last_error::fail(0, "revocation_root is not a 32-byte field element"),
+        (Some(pvk), Some(root)) => verify_non_revoked(pvk, proof_hex, issuer_pubkey, current_time, nonce, root),
+    };
+
+    audit::record(started, audit::Verification {
+        check: "non-revoked-vc-proof",
+        code: result,
+        circuit: Some(NON_REVOKED_VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the non-revoked VC verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportNonRevokedVCVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match NON_REVOKED_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportNonRevokedVCVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportNonRevokedVCVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == NON_REVOKED_VC_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match NON_REVOKED_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,245 @@
+// ============================================================================
+// Nullifier Set
+// ============================================================================
+//
+// Verifiers record every nullifier they accept, per scope, and reject repeats.
+//
+// Each nullifier is recorded in the current epoch (ZK_SetNullifierEpoch, 0
+// until set), a caller-defined counter such as a day number or poll round.
+// Epochs order the set for eviction and let a gateway drop whole epochs it
+// no longer needs to guard (ZK_PruneNullifiers). A dropped nullifier is
+// accepted again, so only prune epochs whose presentations can no longer be
+// replayed, e.g. because their challenges have expired.
+//
+// Stored value: epoch u64 little-endian. Entries written before epochs
+// existed hold a single byte and count as epoch 0.
+
+use std::collections::BTreeSet;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::error::ZK_ERR_STORAGE;
+use crate::ffi::{read_bytes, MAX_FIELD_LEN};
+use crate::limits::{self, ZK_COLLECTION_NULLIFIERS};
+use crate::storage::{storage, Storage, StorageError};
+
+const NULLIFIER_PREFIX: &[u8] = b"nullifier/";
+
+// Serializes lookup + insert so concurrent presentations cannot both be
+// fresh, and the count + evict + insert of a limited set
+static RECORD_LOCK: Mutex<()> = Mutex::new(());
+
+// Epoch new nullifiers are recorded in
+static CURRENT_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Summary of the nullifier set (C layout for ZK_NullifierSetStats)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkNullifierSetStats {
+    pub count: u64,
+    /// Distinct scopes with at least one nullifier
+    pub scopes: u64,
+    /// Lowest and highest epoch recorded, 0 when empty
+    pub oldest_epoch: u64,
+    pub newest_epoch: u64,
+    pub current_epoch: u64,
+    pub capacity: u64,
+    pub evicted: u64,
+    pub rejected: u64,
+}
+
+/// Seen nullifiers persisted through the storage backend
+pub struct NullifierSet {
+    storage: Arc<dyn Storage>,
+}
+
+impl NullifierSet {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    fn key(scope: &[u8], nullifier: &[u8]) -> Vec<u8> {
+        let mut key = NULLIFIER_PREFIX.to_vec();
+        key.extend_from_slice(&(scope.len() as u32).to_le_bytes());
+        key.extend_from_slice(scope);
+        key.extend_from_slice(nullifier);
+        key
+    }
+
+    fn epoch(value: &[u8]) -> Result<u64, StorageError> {
+        match value.len() {
+            1 => Ok(0),
+            _ => Ok(u64::from_le_bytes(value.try_into().map_err(|_| StorageError::Backend)?)),
+        }
+    }
+
+    /// Recorded nullifiers as (key, epoch)
+    fn entries(&self) -> Result<Vec<(Vec<u8>, u64)>, StorageError> {
+        self.storage
+            .scan_prefix(NULLIFIER_PREFIX)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, Self::epoch(&value)?)))
+            .collect()
+    }
+
+    pub fn count(&self) -> Result<usize, StorageError> {
+        Ok(self.storage.scan_prefix(NULLIFIER_PREFIX)?.len())
+    }
+
+    /// Whether `nullifier` was already recorded in `scope`
+    pub fn contains(&self, scope: &[u8], nullifier: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.storage.get(&Self::key(scope, nullifier))?.is_some())
+    }
+
+    /// Record `nullifier` in `scope` in the current epoch; returns false if
+    /// it was already present
+    ///
+    /// In a set at its capacity, ZK_EVICT_OLDEST drops nullifiers of the
+    /// lowest epochs first.
+    pub fn record(&self, scope: &[u8], nullifier: &[u8]) -> Result<bool, StorageError> {
+        let _guard = RECORD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let key = Self::key(scope, nullifier);
+        if self.storage.get(&key)?.is_some() {
+            return Ok(false);
+        }
+        if limits::is_limited(ZK_COLLECTION_NULLIFIERS) {
+            let mut entries = self.entries()?;
+            let evict = limits::admit(ZK_COLLECTION_NULLIFIERS, entries.len())?;
+            entries.sort_by_key(|(_, epoch)| *epoch);
+            for (old, _) in entries.iter().take(evict) {
+                self.storage.delete(old)?;
+            }
+            limits::record_evicted(ZK_COLLECTION_NULLIFIERS, evict);
+        }
+        self.storage.put(&key, &CURRENT_EPOCH.load(Ordering::Relaxed).to_le_bytes())?;
+        Ok(true)
+    }
+
+    /// Drop every nullifier recorded in an epoch before `epoch`; returns how many
+    pub fn prune_before(&self, epoch: u64) -> Result<usize, StorageError> {
+        let _guard = RECORD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut removed = 0;
+        for (key, recorded) in self.entries()? {
+            if recorded < epoch {
+                self.storage.delete(&key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn stats(&self) -> Result<ZkNullifierSetStats, StorageError> {
+        let entries = self.entries()?;
+        let scopes: BTreeSet<&[u8]> = entries
+            .iter()
+            .filter_map(|(key, _)| {
+                let rest = &key[NULLIFIER_PREFIX.len()..];
+                let len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+                rest.get(4..4 + len)
+            })
+            .collect();
+        let metrics = limits::stats(ZK_COLLECTION_NULLIFIERS, entries.len()).unwrap_or_default();
+
+        Ok(ZkNullifierSetStats {
+            count: metrics.count,
+            scopes: scopes.len() as u64,
+            oldest_epoch: entries.iter().map(|(_, epoch)| *epoch).min().unwrap_or(0),
+            newest_epoch: entries.iter().map(|(_, epoch)| *epoch).max().unwrap_or(0),
+            current_epoch: CURRENT_EPOCH.load(Ordering::Relaxed),
+            capacity: metrics.capacity,
+            evicted: metrics.evicted,
+            rejected: metrics.rejected,
+        })
+    }
+}
+
+crate::unwind::entry_point! {
+/// Record a nullifier for a scope (e.g. a poll id or voucher campaign)
+///
+/// Returns 1 if the nullifier is new, 0 if it was already used in this scope,
+/// ZK_ERR_CAPACITY if the set is full and its policy is ZK_EVICT_REJECT,
+/// ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_RecordNullifier(
+    scope: *const c_char,
+    scope_len: usize,
+    nullifier: *const c_char,
+    nullifier_len: usize,
+) -> c_int {
+    if scope.is_null() || nullifier.is_null() {
+        return -1;
+    }
+
+    let scope_bytes = match read_bytes(scope, scope_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let nullifier_bytes = match read_bytes(nullifier, nullifier_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match NullifierSet::new(storage()).record(scope_bytes, nullifier_bytes) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Set the epoch that newly recorded nullifiers belong to
+///
+/// Epochs are expected to increase; already recorded nullifiers keep theirs.
+#[no_mangle]
+pub extern "C" fn ZK_SetNullifierEpoch(epoch: u64) -> c_int {
+    CURRENT_EPOCH.store(epoch, Ordering::Relaxed);
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Drop every nullifier recorded in an epoch before `epoch`
+///
+/// The number removed is written to `removed_out` (may be NULL). Returns 0
+/// on success, ZK_ERR_STORAGE if the backend failed or cannot be scanned.
+#[no_mangle]
+pub extern "C" fn ZK_PruneNullifiers(epoch: u64, removed_out: *mut u64) -> c_int {
+    match NullifierSet::new(storage()).prune_before(epoch) {
+        Ok(removed) => {
+            if let Some(out) = unsafe { removed_out.as_mut() } {
+                *out = removed as u64;
+            }
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Size, scopes, epoch range and eviction metrics of the nullifier set
+///
+/// Returns 0 on success, ZK_ERR_STORAGE if the backend failed or cannot be
+/// scanned, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_NullifierSetStats(stats_out: *mut ZkNullifierSetStats) -> c_int {
+    let out = match unsafe { stats_out.as_mut() } {
+        Some(out) => out,
+        None => return -1,
+    };
+
+    match NullifierSet::new(storage()).stats() {
+        Ok(stats) => {
+            *out = stats;
+            0
+        }
+        Err(_) => ZK_ERR_STORAGE,
+    }
+}
+}
@@ -0,0 +1,437 @@
+// ============================================================================
+// One-Time Presentations (a holder nullifier per context)
+// ============================================================================
+//
+// Vouchers and votes must be presented once per context, without the
+// verifier learning who presented them. A one-time proof is a bound proof
+// (holder.rs) that also exposes the holder's nullifier for a context the
+// verifier names:
+//
+//   context   = SHA-256("zkid:nullifier-context" | context bytes) mod r
+//   nullifier = MiMC(s, context)
+//
+// (the digest read little-endian, the context length-prefixed). The
+// one-time circuit is the bound-VC circuit with context and nullifier as
+// further public inputs, after the anchor, and a constraint that the
+// nullifier is the MiMC hash of the same private secret the anchor's holder
+// key is derived from. That key is the one the issuer signed into the
+// anchor (validity.rs), so the nullifier is the credential holder's: a
+// secret of the prover's choosing opens no signed anchor. The proof carries
+// the VC header; the holder sends the nullifier (ZK_ComputeNullifier)
+// alongside it.
+//
+// One credential has one secret, so it yields one nullifier per context:
+// the verifier records each nullifier it accepts in the context's scope of
+// the nullifier set (ZK_RecordNullifier, nullifier.rs) and refuses a
+// repeat. Nullifiers of different contexts are unrelated MiMC outputs and
+// reveal nothing about the secret or the holder key.
+//
+// The nullifier does not make presentations unlinkable: the VC header
+// repeats the anchor and the issuer's signature in every proof of the
+// credential (validity.rs), so verifiers that compare headers link its
+// presentations across contexts. Hiding them would take verifying the
+// Ed25519 signature inside the circuit, which no circuit here does. About
+// 330 constraints more than the bound-VC circuit.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::PreparedVerifyingKey;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::ballot::hash;
+use crate::disclosure::field_bytes;
+use crate::error::ZK_ERR_BAD_HEX;
+use crate::ffi::{read_bytes, write_cstr, MAX_FIELD_LEN};
+use crate::holder::parse_secret;
+use crate::{bytes_to_hex, hex_to_bytes, last_error, mimc};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::admission::text_arg,
+    crate::dates,
+    crate::entropy,
+    crate::error::ZK_ERR_NOT_INITIALIZED,
+    crate::ffi::reserve_cstr,
+    crate::holder::Binding,
+    crate::{generate_vc_proof, prove_with_key, seeded_rng, sizes, vc_circuit, Anchored, ProofCredential, VCCircuit},
+    ark_groth16::ProvingKey,
+    ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
+    std::sync::Arc,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::disclosure::field_from_bytes,
+    crate::sizes::ONE_TIME_VC_PUBLIC_INPUTS,
+    crate::{admission, audit, did, proof, validity},
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const CONTEXT_DOMAIN: &[u8] = b"zkid:nullifier-context";
+
+/// Circuit id of the one-time VC circuit
+pub const ONE_TIME_VC_CIRCUIT: &str = "zkid-vc/one-time/v4";
+
+/// Field element of a presentation context
+pub fn context_field(context: &[u8]) -> Fr {
+    Fr::from_le_bytes_mod_order(&hash(CONTEXT_DOMAIN, &[context]))
+}
+
+/// Nullifier of a holder secret's field element for a context field
+pub fn nullifier(secret: &Fr, context: &Fr) -> Fr {
+    mimc::hash(&[*secret, *context])
+}
+
+/// Keys of the one-time VC circuit; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct OneTimeKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<Arc<ProvingKey<Bn254>>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static ONE_TIME_KEYS: Mutex<OneTimeKeys> = Mutex::new(OneTimeKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the one-time VC circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(12u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(VCCircuit::blank_one_time(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match ONE_TIME_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(Arc::new(pk));
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the one-time VC verifying key is present
+pub(crate) fn has_keys() -> bool {
+    ONE_TIME_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the one-time VC circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = ONE_TIME_KEYS.lock() {
+        *keys = OneTimeKeys::default();
+    }
+}
+
+/// Generate the hex one-time VC proof for an already checked credential;
+/// `binding` has the context
+#[cfg(feature = "prover")]
+pub(crate) fn prove(
+    credential: &Anchored,
+    issuer_pubkey_bytes: &[u8],
+    current_time: u64,
+    nonce: u64,
+    binding: Binding,
+) -> Result<String, c_int> {
+    let pk = ONE_TIME_KEYS.lock().map_err(|_| -1)?.pk.clone().ok_or(ZK_ERR_NOT_INITIALIZED)?;
+
+    let format = crate::proof::emit_format();
+    let (circuit, header) =
+        vc_circuit(format, credential, issuer_pubkey_bytes, current_time, nonce, Some(binding)).ok_or(-1)?;
+    prove_with_key(&pk, format, circuit, &header, &mut entropy::prover_rng(nonce)?)
+}
+
+/// Verify a one-time proof for a context and nullifier at `current_time`
+#[cfg(feature = "verifier")]
+fn verify_one_time(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    statement: [Fr; 2],
+) -> c_int {
+    let issuer_pubkey_bytes = match admission::text_arg(issuer_pubkey, MAX_FIELD_LEN).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return last_error::fail(e.code_or(0), "issuer_pubkey is not hex or a did:key"),
+        None => return last_error::fail(0, "issuer_pubkey is NULL or too long"),
+    };
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return last_error::fail(rejection.verify_code(), rejection.describe()),
+    };
+    let now = match crate::proof_time(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+
+    // The VC inputs, then context and nullifier (circuit order)
+    let mut public_inputs = match validity::public_inputs(&issuer_pubkey_bytes, format, nonce, now, &header) {
+        Some(inputs) => inputs.to_vec(),
+        None => return last_error::fail(0, crate::UNSIGNED_ANCHOR),
+    };
+    public_inputs.extend_from_slice(&statement);
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    match valid {
+        true => 1,
+        false if header.current_time != now => last_error::fail(0, crate::verify_failure(&header, now)),
+        false => last_error::fail(0, "proof does not verify for issuer_pubkey, nonce, context and nullifier"),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Compute the nullifier of a holder secret for a context (hex)
+///
+/// `holder_secret` is the hex secret from ZK_GenerateHolderKeypair,
+/// `context` any bytes naming where the credential is presented once (a
+/// voucher campaign, a poll). The same secret and context always give the
+/// same nullifier; other contexts give unrelated ones. Returns 0 on
+/// success, ZK_ERR_BAD_HEX for a malformed secret, ZK_ERR_BUFFER_TOO_SMALL,
+/// or -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ComputeNullifier(
+    holder_secret: *const c_char,
+    context: *const c_char,
+    context_len: usize,
+    nullifier_out: *mut c_char,
+    nullifier_out_size: usize,
+) -> c_int {
+    last_error::clear();
+    if holder_secret.is_null() || context.is_null() || nullifier_out.is_null() {
+        return last_error::fail(-1, "holder_secret, context or nullifier_out is NULL");
+    }
+    let secret = match parse_secret(holder_secret) {
+        Some(secret) => secret,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "holder_secret is not 32-byte hex"),
+    };
+    let context = match read_bytes(context, context_len, MAX_FIELD_LEN) {
+        Ok(bytes) => context_field(bytes),
+        Err(e) => return last_error::ffi("context", e),
+    };
+
+    match write_cstr(nullifier_out, nullifier_out_size, &bytes_to_hex(&field_bytes(&nullifier(&secret, &context)))) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("nullifier_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Generate a one-time VC proof: a bound proof that also proves the
+/// holder's nullifier for `context` (u64 dates)
+///
+/// Takes the arguments of ZK_GenerateVCProofBound and the context bytes.
+/// The proof verifies with ZK_VerifyVCProofOneTime for this context and the
+/// nullifier ZK_ComputeNullifier gives for the secret and context, which
+/// the holder sends with it. Returns the codes of ZK_GenerateVCProofBound.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateVCProofOneTime(
+    holder_id: *const c_char,
+    holder_id_len: usize,
+    issuer: *const c_char,
+    issuer_len: usize,
+    issue_date: u64,
+    expiry_date: u64,
+    vc_signature: *const c_char,
+    issuer_pubkey: *const c_char,
+    holder_secret: *const c_char,
+    context: *const c_char,
+    context_len: usize,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    if holder_id.is_null() || issuer.is_null() || vc_signature.is_null() || issuer_pubkey.is_null() {
+        return last_error::fail(-1, "holder_id, issuer, vc_signature or issuer_pubkey is NULL");
+    }
+    if context.is_null() {
+        return last_error::fail(-1, "context is NULL");
+    }
+    let (issue_date, expiry_date) = match (dates::from_u64(issue_date), dates::from_u64(expiry_date)) {
+        (Some(issue), Some(expiry)) => (issue, expiry),
+        _ => return last_error::fail(-1, "issue_date or expiry_date is above i64::MAX"),
+    };
+    let secret = match parse_secret(holder_secret) {
+        Some(secret) => secret,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "holder_secret is not 32-byte hex"),
+    };
+    let context = match read_bytes(context, context_len, MAX_FIELD_LEN) {
+        Ok(bytes) => context_field(bytes),
+        Err(e) => return last_error::ffi("context", e),
+    };
+
+    let holder_id_bytes = match read_bytes(holder_id, holder_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("holder_id", e),
+    };
+    let issuer_bytes = match read_bytes(issuer, issuer_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return last_error::ffi("issuer", e),
+    };
+    let issuer_pubkey_bytes: [u8; PUBLIC_KEY_LENGTH] = match text_arg(issuer_pubkey, 2 * PUBLIC_KEY_LENGTH)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(bytes) => bytes,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "issuer_pubkey is not 32-byte hex"),
+    };
+    let vc_signature_bytes: [u8; SIGNATURE_LENGTH] = match text_arg(vc_signature, 2 * SIGNATURE_LENGTH)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(bytes) => bytes,
+        None => return last_error::fail(ZK_ERR_BAD_HEX, "vc_signature is not 64-byte hex"),
+    };
+
+    let credential = ProofCredential {
+        holder_id: holder_id_bytes,
+        issuer: issuer_bytes,
+        issue_date,
+        expiry_date,
+        signature: &vc_signature_bytes,
+        issuer_pubkey: &issuer_pubkey_bytes,
+        holder: Some(Binding { secret, context: Some(context) }),
+        revocation: None,
+    };
+    let reserve = |required| reserve_cstr(proof_out, proof_out_size, required, required_size_out);
+    let proof_hex = match generate_vc_proof(&credential, current_time, nonce, sizes::ZK_SIZE_PROOF_HEX, reserve) {
+        Ok(hex) => hex,
+        Err(code) => return code,
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("proof_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a one-time VC proof for a context and the nullifier presented
+/// with it (hex)
+///
+/// As ZK_VerifyVCProofBound, under the one-time key, with the proof's
+/// nullifier checked against `context`. A valid proof's nullifier is the
+/// presenting credential's for this context: record it with
+/// ZK_RecordNullifier(context, nullifier) and refuse the presentation if it
+/// was already there. Returns 1 if valid, 0 if invalid (also for another
+/// context or nullifier, or a malformed nullifier), and the other codes of
+/// ZK_VerifyVCProofBound.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofOneTime(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    context: *const c_char,
+    context_len: usize,
+    nullifier_hex: *const c_char,
+) -> c_int {
+    let started = Instant::now();
+    last_error::clear();
+
+    let keys = ONE_TIME_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let context = match context.is_null() {
+        true => None,
+        false => read_bytes(context, context_len, MAX_FIELD_LEN).ok().map(context_field),
+    };
+    let nullifier = admission::text_arg(nullifier_hex, 64)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| field_from_bytes(&bytes));
+    let result = match (pvk, context, nullifier) {
+        (None, ..) => last_error::fail(0, "one-time VC verifying key is not set up; call ZK_Init or import it"),
+        (_, None, _) => last_error::fail(0, "context is NULL or too long"),
+        (_, _, None) => last_error::fail(0, "nullifier is not a 32-byte field element"),
+        (Some(pvk), Some(context), Some(nullifier)) => {
+            verify_one_time(pvk, proof_hex, issuer_pubkey, current_time, nonce, [context, nullifier])
+        }
+    };
+
+    audit::record(started, audit::Verification {
+        check: "one-time-vc-proof",
+        code: result,
+        circuit: Some(ONE_TIME_VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the one-time VC verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportOneTimeVCVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match ONE_TIME_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportOneTimeVCVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportOneTimeVCVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == ONE_TIME_VC_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match ONE_TIME_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
@@ -0,0 +1,165 @@
+// ============================================================================
+// Circuit Parameters
+// ============================================================================
+//
+// Capacity constants that change a circuit's shape are runtime CircuitParams
+// instead of compile-time constants, so a deployment can pick its sizes
+// without a rebuild and keep an old size live while keys for a new one roll
+// out. Each registered parameterization has its own keys and its own circuit
+// id, which names the capacity:
+//
+//   zkid-vc/schedule/v2/w16      schedule circuit with 16 window slots
+//
+// Today only the schedule circuit has a capacity (windows, 1..=
+// MAX_SCHEDULE_WINDOWS); the VC circuit has none and takes capacity 0.
+// ZK_Init registers DEFAULT_SCHEDULE_WINDOWS.
+//
+// Proofs of a parameterized circuit carry their parameters in front of the
+// proof wire format (see proof.rs):
+//
+//   "ZC" | circuit u8 | capacity u32 | proof
+//
+// A verifier looks the parameters up in its registry and refuses a proof
+// for parameters it has no key for with ZK_ERR_CIRCUIT_MISMATCH, rather
+// than reporting it invalid (or checking it against a key of another size).
+// Schedule proofs without the prefix predate it and are read as the default
+// capacity.
+
+use crate::encoding::PointEncoding;
+use std::os::raw::{c_char, c_int};
+
+use crate::proof;
+use crate::schedule::{self, DEFAULT_SCHEDULE_WINDOWS, MAX_SCHEDULE_WINDOWS};
+use crate::sizes::{ZK_CIRCUIT_SCHEDULE, ZK_CIRCUIT_VC};
+
+const PARAMS_MAGIC: &[u8; 2] = b"ZC";
+
+/// Length of the parameters prefix on a parameterized proof
+pub const PARAMS_PREFIX_LEN: usize = PARAMS_MAGIC.len() + 1 + 4;
+
+/// A circuit and its capacity (C layout for the ZK_*Circuit* functions)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZkCircuitParams {
+    /// One of ZK_CIRCUIT_*
+    pub circuit: c_int,
+    /// Window slots for ZK_CIRCUIT_SCHEDULE, 0 for ZK_CIRCUIT_VC
+    pub capacity: u32,
+}
+
+impl ZkCircuitParams {
+    /// The schedule circuit with room for `windows` windows
+    pub const fn schedule(windows: usize) -> Self {
+        Self {
+            circuit: ZK_CIRCUIT_SCHEDULE,
+            capacity: windows as u32,
+        }
+    }
+
+    /// Whether the circuit is known and the capacity within its bounds
+    pub fn is_supported(&self) -> bool {
+        match self.circuit {
+            ZK_CIRCUIT_VC => self.capacity == 0,
+            ZK_CIRCUIT_SCHEDULE => (1..=MAX_SCHEDULE_WINDOWS).contains(&(self.capacity as usize)),
+            _ => false,
+        }
+    }
+
+    /// Prefix `proof` with these parameters
+    pub fn tag(&self, proof: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PARAMS_PREFIX_LEN + proof.len());
+        out.extend_from_slice(PARAMS_MAGIC);
+        out.push(self.circuit as u8);
+        out.extend_from_slice(&self.capacity.to_le_bytes());
+        out.extend_from_slice(proof);
+        out
+    }
+
+    /// Split a schedule proof into its parameters and proof wire bytes
+    ///
+    /// Proofs without the prefix are read as the default capacity; None if
+    /// the prefix is truncated. A bare legacy proof may start with the magic
+    /// by chance, but its length tells it apart.
+    pub fn untag_schedule(bytes: &[u8]) -> Option<(Self, &[u8])> {
+        let bare = [proof::bare_len(PointEncoding::Compressed), proof::bare_len(PointEncoding::Uncompressed)];
+        if !bytes.starts_with(PARAMS_MAGIC) || bare.contains(&bytes.len()) {
+            return Some((Self::schedule(DEFAULT_SCHEDULE_WINDOWS), bytes));
+        }
+        let prefix = bytes.get(..PARAMS_PREFIX_LEN)?;
+        let params = Self {
+            circuit: c_int::from(prefix[2]),
+            capacity: u32::from_le_bytes(prefix[3..].try_into().ok()?),
+        };
+        Some((params, &bytes[PARAMS_PREFIX_LEN..]))
+    }
+}
+
+/// Read caller parameters, None for NULL or unsupported ones
+pub(crate) fn read_params(params: *const ZkCircuitParams) -> Option<ZkCircuitParams> {
+    if params.is_null() {
+        return None;
+    }
+    let params = unsafe { *params };
+    params.is_supported().then_some(params)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Register a circuit parameterization alongside those already registered
+///
+/// Prover builds set up its keys (deterministically in standard builds, so
+/// separate processes agree); verifier-only builds reserve it for
+/// ZK_ImportCircuitVerifyingKey. Registering twice keeps the existing keys.
+/// The VC circuit with capacity 0 is always registered. Returns 0 on
+/// success, -1 on NULL, an unknown circuit or a capacity out of bounds.
+#[no_mangle]
+pub extern "C" fn ZK_RegisterCircuitParams(params: *const ZkCircuitParams) -> c_int {
+    match read_params(params) {
+        Some(params) if params.circuit == ZK_CIRCUIT_SCHEDULE => schedule::register(params.capacity as usize),
+        Some(_) => 0,
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the verifying key of a registered parameterization (hex, compressed)
+///
+/// Only parameterized circuits are handled; the VC key is exported with
+/// ZK_ExportVerifyingKey. Returns 0 on success, ZK_ERR_CIRCUIT_MISMATCH if
+/// the parameters have no key, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 on NULL or unsupported parameters.
+#[no_mangle]
+pub extern "C" fn ZK_ExportCircuitVerifyingKey(
+    params: *const ZkCircuitParams,
+    vk_out: *mut c_char,
+    vk_out_size: usize,
+) -> c_int {
+    match read_params(params) {
+        Some(params) if params.circuit == ZK_CIRCUIT_SCHEDULE => {
+            schedule::export_verifying_key(params.capacity as usize, vk_out, vk_out_size)
+        }
+        _ => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the verifying key of a parameterization registered on this side
+///
+/// Returns 0 on success, ZK_ERR_CIRCUIT_MISMATCH if the parameters are not
+/// registered, -1 on NULL, unsupported parameters or a malformed key.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportCircuitVerifyingKey(params: *const ZkCircuitParams, vk_hex: *const c_char) -> c_int {
+    match read_params(params) {
+        Some(params) if params.circuit == ZK_CIRCUIT_SCHEDULE => {
+            schedule::import_verifying_key(params.capacity as usize, vk_hex)
+        }
+        _ => -1,
+    }
+}
+}
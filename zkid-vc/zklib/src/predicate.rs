@@ -0,0 +1,1079 @@
+// ============================================================================
+// Claim Predicates
+// ============================================================================
+//
+// A predicate is a condition on one claim: "age >= 18", "country in {DE,
+// FR}", "sanctioned absent". Presentation requests state them, wallets
+// evaluate them (matching.rs), consent receipts record them, the predicate
+// circuit attests to them and audit events name them, all through the one
+// encoding below, so every layer agrees on which predicate it saw.
+//
+// Binary form: a header, then tag u8 | length u32 LE | value items.
+//
+//   "ZKPD" | version u8 | claim (0x01) | op (0x02, 1 byte) | operand*
+//
+//   op            operands
+//   1 equal       one text (0x03)
+//   2 not_equal   one text
+//   3 at_least    one integer (0x04): sign u8 (1 negative) | magnitude u128 LE
+//   4 at_most     one integer
+//   5 above       one integer
+//   6 below       one integer
+//   7 in          one text per member, strictly ascending bytewise, at least one
+//   8 absent      none
+//
+// JSON form, the same predicate field for field:
+//
+//   {"claim": "age", "op": "at_least", "value": "18"}
+//   {"claim": "country", "op": "in", "values": ["DE", "FR"]}
+//   {"claim": "sanctioned", "op": "absent"}
+//
+// Integers are decimal strings in JSON, since most JSON parsers lose
+// precision past 2^53. The binary decoder accepts only the canonical
+// encoding (items in order, no negative zero, no trailing bytes); the JSON
+// decoder normalizes ("+007" is 7, "in" members are sorted and
+// deduplicated), so equal predicates always encode to equal bytes. The
+// predicate hash is SHA-256("zkid:predicate" | binary form).
+//
+// Every condition but absent requires the claim to be present; integer
+// conditions fail on a value that is not an integer (see ClaimInt).
+//
+// A predicate proof shows that an issuer-signed credential satisfies the
+// predicate. The circuit opens the signed anchor (validity.rs) as the VC
+// circuit does, checks that the credential is active at the time the proof
+// carries, and opens the claim's leaf under the anchor's claims root along a
+// private path (disclosure.rs), so the condition is evaluated over the
+// signed value itself: a claim the issuer never signed does not verify,
+// whatever a modified prover skipped. The root stays private, and blobs
+// before version 6 have no signed root to prove against.
+//
+// Both sides derive the statement from the predicate: the claim key, then
+// either up to PREDICATE_MAX_MEMBERS leaf values the claim's leaf must be
+// among (equal: one, in: its members, padded by repeating the last) or,
+// negated, outside of (not_equal), or a sign and bound with
+//
+//   sign * value >= bound
+//
+//   at_least n: +1, n    above n: +1, n + 1
+//   at_most n:  -1, -n   below n: -1, 1 - n
+//
+// An integer condition holds only for a numeric leaf, a canonical decimal
+// in [-2^63, 2^64) (disclosure.rs): value + 2^63 is decomposed into 65 bits
+// and sign * value - bound into 67, with n clamped to ±2^65, which changes
+// no outcome over that range. A claim such as "007" satisfies "age >= 5"
+// for matching.rs but cannot be proven, and "absent" and larger "in" sets
+// have no statement; the prover refuses them. Predicates of the same
+// meaning ("= x" and "in {x}") share a statement, and a proof verifies for
+// no predicate of another meaning.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, claim_key, integer, negate, sign, bound, then the members.
+
+use ark_bn254::Bn254;
+use ark_groth16::PreparedVerifyingKey;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::ffi::{read_bytes, write_cstr, MAX_ARRAY_LEN, MAX_FIELD_LEN};
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, hex_to_bytes};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::disclosure::{claim_key_field, claim_value_field},
+    crate::proof,
+    ark_bn254::Fr,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::checked_blob,
+    crate::disclosure::{enforce_membership, numeric_value, signed_value, ClaimPath, ClaimsTree},
+    crate::error::{ZK_ERR_INCOMPATIBLE, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_NOT_INITIALIZED, ZK_ERR_UNSUPPORTED_VERSION},
+    crate::schedule::{enforce_bits, witness},
+    crate::entropy,
+    crate::{enforce_active, prove_with_key, seeded_rng, Anchored, AnchorWitness},
+    ark_ff::Field,
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission,
+    crate::audit,
+    crate::did,
+    crate::sizes::PREDICATE_PUBLIC_INPUTS,
+    crate::validity,
+    std::time::Instant,
+};
+
+const PREDICATE_MAGIC: &[u8; 4] = b"ZKPD";
+const PREDICATE_VERSION: u8 = 1;
+const HASH_DOMAIN: &[u8] = b"zkid:predicate";
+
+const TAG_CLAIM: u8 = 0x01;
+const TAG_OP: u8 = 0x02;
+const TAG_TEXT: u8 = 0x03;
+const TAG_INT: u8 = 0x04;
+
+/// sign u8 | magnitude u128 LE
+const INT_LEN: usize = 17;
+
+/// Request metadata naming a predicate on claim <key> in shorthand
+pub const SHORTHAND_PREFIX: &str = "require.";
+
+/// Request metadata carrying a predicate in binary form (hex); repeatable
+pub const PREDICATE_KEY: &str = "predicate";
+
+/// Circuit id of the predicate circuit
+pub const PREDICATE_CIRCUIT: &str = "zkid-vc/predicate/v2";
+
+/// Most members of an "in" predicate a predicate proof covers
+pub const PREDICATE_MAX_MEMBERS: usize = 8;
+
+/// Public inputs of the statement after the anchor: claim key, integer,
+/// negate, sign, bound, members
+#[cfg(any(feature = "prover", feature = "verifier"))]
+const STATEMENT_INPUTS: usize = 5 + PREDICATE_MAX_MEMBERS;
+
+/// Integer operands are clamped to ±BOUND_LIMIT
+#[cfg(any(feature = "prover", feature = "verifier"))]
+const BOUND_LIMIT: u128 = 1 << 65;
+
+/// Bits of a numeric leaf value shifted by 2^63
+#[cfg(feature = "prover")]
+const VALUE_BITS: usize = 65;
+
+/// Bits of sign * value - bound (below 2^64 + 2^65 + 1)
+#[cfg(feature = "prover")]
+const MARGIN_BITS: usize = 67;
+
+/// An integer claim value: sign and a 128-bit magnitude, so token amounts
+/// and 128-bit identifiers compare exactly
+///
+/// Decimal with an optional sign; leading zeros are accepted and
+/// canonicalized ("007" is 7, "-0" is 0). Values beyond ±(2^128 - 1) do not
+/// parse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClaimInt {
+    negative: bool,
+    magnitude: u128,
+}
+
+impl ClaimInt {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let magnitude = digits.parse::<u128>().ok()?;
+        Some(Self {
+            negative: negative && magnitude != 0,
+            magnitude,
+        })
+    }
+
+    fn to_bytes(self) -> [u8; INT_LEN] {
+        let mut out = [0u8; INT_LEN];
+        out[0] = self.negative as u8;
+        out[1..].copy_from_slice(&self.magnitude.to_le_bytes());
+        out
+    }
+
+    /// Canonical bytes only: sign 0 or 1, no negative zero
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; INT_LEN] = bytes.try_into().ok()?;
+        let magnitude = u128::from_le_bytes(bytes[1..].try_into().ok()?);
+        match bytes[0] {
+            0 => Some(Self { negative: false, magnitude }),
+            1 if magnitude != 0 => Some(Self { negative: true, magnitude }),
+            _ => None,
+        }
+    }
+
+    /// The value clamped to [-limit, limit]
+    #[cfg(any(feature = "prover", feature = "verifier"))]
+    fn clamped(self, limit: u128) -> i128 {
+        let magnitude = self.magnitude.min(limit) as i128;
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<i64> for ClaimInt {
+    fn from(value: i64) -> Self {
+        Self {
+            negative: value < 0,
+            magnitude: u128::from(value.unsigned_abs()),
+        }
+    }
+}
+
+impl From<u128> for ClaimInt {
+    fn from(magnitude: u128) -> Self {
+        Self {
+            negative: false,
+            magnitude,
+        }
+    }
+}
+
+impl Ord for ClaimInt {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => std::cmp::Ordering::Greater,
+            (true, false) => std::cmp::Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for ClaimInt {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for ClaimInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.magnitude)
+    }
+}
+
+/// A condition on one claim value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Equal(String),
+    NotEqual(String),
+    AtLeast(ClaimInt),
+    AtMost(ClaimInt),
+    Above(ClaimInt),
+    Below(ClaimInt),
+    /// Sorted, distinct and non-empty
+    In(Vec<String>),
+    Absent,
+}
+
+impl Condition {
+    /// Parse the request shorthand: "=v", "!=v", ">=n", "<=n", ">n", "<n"
+    /// or a bare value
+    ///
+    /// None if a comparison operand is not an integer (see ClaimInt).
+    pub fn parse(text: &str) -> Option<Self> {
+        let number = ClaimInt::parse;
+        Some(if let Some(rest) = text.strip_prefix(">=") {
+            Self::AtLeast(number(rest)?)
+        } else if let Some(rest) = text.strip_prefix("<=") {
+            Self::AtMost(number(rest)?)
+        } else if let Some(rest) = text.strip_prefix("!=") {
+            Self::NotEqual(rest.to_string())
+        } else if let Some(rest) = text.strip_prefix('>') {
+            Self::Above(number(rest)?)
+        } else if let Some(rest) = text.strip_prefix('<') {
+            Self::Below(number(rest)?)
+        } else {
+            Self::Equal(text.strip_prefix('=').unwrap_or(text).to_string())
+        })
+    }
+
+    /// Whether a claim holding `value` (None if the claim is missing)
+    /// satisfies the condition
+    pub fn holds(&self, value: Option<&str>) -> bool {
+        let value = match (self, value) {
+            (Self::Absent, value) => return value.is_none(),
+            (_, Some(value)) => value,
+            (_, None) => return false,
+        };
+        let number = ClaimInt::parse(value);
+        match (self, number) {
+            (Self::Equal(expected), _) => value == expected,
+            (Self::NotEqual(expected), _) => value != expected,
+            (Self::In(members), _) => members.iter().any(|member| member == value),
+            (Self::AtLeast(n), Some(v)) => v >= *n,
+            (Self::AtMost(n), Some(v)) => v <= *n,
+            (Self::Above(n), Some(v)) => v > *n,
+            (Self::Below(n), Some(v)) => v < *n,
+            _ => false,
+        }
+    }
+
+    fn op(&self) -> (u8, &'static str) {
+        match self {
+            Self::Equal(_) => (1, "equal"),
+            Self::NotEqual(_) => (2, "not_equal"),
+            Self::AtLeast(_) => (3, "at_least"),
+            Self::AtMost(_) => (4, "at_most"),
+            Self::Above(_) => (5, "above"),
+            Self::Below(_) => (6, "below"),
+            Self::In(_) => (7, "in"),
+            Self::Absent => (8, "absent"),
+        }
+    }
+}
+
+/// A condition on a named claim
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Predicate {
+    pub claim: String,
+    pub condition: Condition,
+}
+
+fn put_item(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    put_bytes(out, value);
+}
+
+fn item<'a>(r: &mut Reader<'a>, tag: u8) -> Option<&'a [u8]> {
+    if r.u8()? != tag {
+        return None;
+    }
+    r.bytes()
+}
+
+fn text_item(r: &mut Reader) -> Option<String> {
+    String::from_utf8(item(r, TAG_TEXT)?.to_vec()).ok()
+}
+
+fn int_item(r: &mut Reader) -> Option<ClaimInt> {
+    ClaimInt::from_bytes(item(r, TAG_INT)?)
+}
+
+impl Predicate {
+    /// A predicate from the `require.<key>` shorthand
+    pub fn from_shorthand(claim: &str, text: &str) -> Option<Self> {
+        Self::new(claim.to_string(), Condition::parse(text)?)
+    }
+
+    /// None for an empty claim key or an empty "in" set; "in" members are
+    /// sorted and deduplicated
+    pub fn new(claim: String, condition: Condition) -> Option<Self> {
+        let condition = match condition {
+            Condition::In(mut members) => {
+                members.sort();
+                members.dedup();
+                Condition::In(members)
+            }
+            condition => condition,
+        };
+        if claim.is_empty() || condition == Condition::In(Vec::new()) {
+            return None;
+        }
+        Some(Self { claim, condition })
+    }
+
+    /// Whether a claim holding `value` (None if missing) satisfies it
+    pub fn holds(&self, value: Option<&str>) -> bool {
+        self.condition.holds(value)
+    }
+
+    /// The canonical binary form
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(PREDICATE_MAGIC);
+        out.push(PREDICATE_VERSION);
+        put_item(&mut out, TAG_CLAIM, self.claim.as_bytes());
+        put_item(&mut out, TAG_OP, &[self.condition.op().0]);
+        match &self.condition {
+            Condition::Equal(text) | Condition::NotEqual(text) => put_item(&mut out, TAG_TEXT, text.as_bytes()),
+            Condition::AtLeast(n) | Condition::AtMost(n) | Condition::Above(n) | Condition::Below(n) => {
+                put_item(&mut out, TAG_INT, &n.to_bytes())
+            }
+            Condition::In(members) => {
+                for member in members {
+                    put_item(&mut out, TAG_TEXT, member.as_bytes());
+                }
+            }
+            Condition::Absent => {}
+        }
+        out
+    }
+
+    /// Decode the canonical binary form; anything else is None
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(PREDICATE_MAGIC.len())? != PREDICATE_MAGIC || r.u8()? != PREDICATE_VERSION {
+            return None;
+        }
+
+        let claim = String::from_utf8(item(&mut r, TAG_CLAIM)?.to_vec()).ok()?;
+        let op = match item(&mut r, TAG_OP)? {
+            [op] => *op,
+            _ => return None,
+        };
+        let condition = match op {
+            1 => Condition::Equal(text_item(&mut r)?),
+            2 => Condition::NotEqual(text_item(&mut r)?),
+            3 => Condition::AtLeast(int_item(&mut r)?),
+            4 => Condition::AtMost(int_item(&mut r)?),
+            5 => Condition::Above(int_item(&mut r)?),
+            6 => Condition::Below(int_item(&mut r)?),
+            7 => {
+                let mut members: Vec<String> = Vec::new();
+                while !r.is_empty() {
+                    let member = text_item(&mut r)?;
+                    if members.last().is_some_and(|last| *last >= member) || members.len() == MAX_ARRAY_LEN {
+                        return None;
+                    }
+                    members.push(member);
+                }
+                Condition::In(members)
+            }
+            8 => Condition::Absent,
+            _ => return None,
+        };
+
+        if !r.is_empty() || claim.is_empty() || condition == Condition::In(Vec::new()) {
+            return None;
+        }
+        Some(Self { claim, condition })
+    }
+
+    /// The JSON form, with every operand canonical
+    pub fn to_json(&self) -> Value {
+        let mut doc = json!({ "claim": self.claim, "op": self.condition.op().1 });
+        match &self.condition {
+            Condition::Equal(text) | Condition::NotEqual(text) => doc["value"] = json!(text),
+            Condition::AtLeast(n) | Condition::AtMost(n) | Condition::Above(n) | Condition::Below(n) => {
+                doc["value"] = json!(n.to_string())
+            }
+            Condition::In(members) => doc["values"] = json!(members),
+            Condition::Absent => {}
+        }
+        doc
+    }
+
+    /// Read the JSON form; None for a missing, mistyped or unknown field
+    ///
+    /// Integer operands may also be JSON integers.
+    pub fn from_json(doc: &Value) -> Option<Self> {
+        let fields: &Map<String, Value> = doc.as_object()?;
+        let claim = fields.get("claim")?.as_str()?;
+        let op = fields.get("op")?.as_str()?;
+        let operand = match op {
+            "in" => "values",
+            "absent" => "",
+            _ => "value",
+        };
+        if fields.keys().any(|key| !matches!(key.as_str(), "claim" | "op") && key != operand) {
+            return None;
+        }
+
+        let text = || fields.get("value")?.as_str().map(str::to_string);
+        let number = || match fields.get("value")? {
+            Value::String(text) => ClaimInt::parse(text),
+            Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(v), _) => Some(ClaimInt::from(v)),
+                (None, Some(v)) => Some(ClaimInt::from(u128::from(v))),
+                _ => None,
+            },
+            _ => None,
+        };
+        let condition = match op {
+            "equal" => Condition::Equal(text()?),
+            "not_equal" => Condition::NotEqual(text()?),
+            "at_least" => Condition::AtLeast(number()?),
+            "at_most" => Condition::AtMost(number()?),
+            "above" => Condition::Above(number()?),
+            "below" => Condition::Below(number()?),
+            "in" => {
+                let values = fields.get("values")?.as_array()?;
+                if values.len() > MAX_ARRAY_LEN {
+                    return None;
+                }
+                Condition::In(values.iter().map(|v| v.as_str().map(str::to_string)).collect::<Option<_>>()?)
+            }
+            "absent" => Condition::Absent,
+            _ => return None,
+        };
+        Self::new(claim.to_string(), condition)
+    }
+
+    /// SHA-256("zkid:predicate" | binary form)
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(HASH_DOMAIN);
+        hasher.update(self.to_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// As consent receipts record it: the shorthand after the claim key
+/// ("age>=18", "role=admin"), `country in ["DE","FR"]`, "sanctioned absent"
+impl std::fmt::Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let claim = &self.claim;
+        match &self.condition {
+            Condition::Equal(v) => write!(f, "{claim}={v}"),
+            Condition::NotEqual(v) => write!(f, "{claim}!={v}"),
+            Condition::AtLeast(n) => write!(f, "{claim}>={n}"),
+            Condition::AtMost(n) => write!(f, "{claim}<={n}"),
+            Condition::Above(n) => write!(f, "{claim}>{n}"),
+            Condition::Below(n) => write!(f, "{claim}<{n}"),
+            Condition::In(members) => write!(f, "{claim} in {}", json!(members)),
+            Condition::Absent => write!(f, "{claim} absent"),
+        }
+    }
+}
+
+// ============================================================================
+// ZK Circuit: Predicate Over a Signed Claim
+// ============================================================================
+
+/// What the predicate circuit checks of the claim's leaf value
+#[cfg(any(feature = "prover", feature = "verifier"))]
+#[derive(Clone)]
+enum Test {
+    /// The leaf is one of `members` (PREDICATE_MAX_MEMBERS of them) or,
+    /// negated, none of them
+    Member { members: Vec<Fr>, negate: bool },
+    /// The leaf is an integer with sign * value >= bound
+    Integer { sign: i128, bound: i128 },
+}
+
+/// The statement of a predicate proof (module comment)
+#[cfg(any(feature = "prover", feature = "verifier"))]
+#[derive(Clone)]
+struct Statement {
+    claim_key: Fr,
+    test: Test,
+}
+
+/// Field element of an integer
+#[cfg(any(feature = "prover", feature = "verifier"))]
+fn int_field(value: i128) -> Fr {
+    let magnitude = Fr::from(value.unsigned_abs());
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// A claim value as an integer of the circuit: a numeric leaf value
+/// (disclosure.rs), in [-2^63, 2^64)
+#[cfg(feature = "prover")]
+fn leaf_integer(value: &str) -> Option<i128> {
+    numeric_value(value).map(i128::from).or_else(|| signed_value(value).map(i128::from))
+}
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+impl Statement {
+    /// The statement of `predicate`; None for absent and for "in" with more
+    /// than PREDICATE_MAX_MEMBERS members
+    fn of(predicate: &Predicate) -> Option<Self> {
+        let member = |text: &str| vec![claim_value_field(text); PREDICATE_MAX_MEMBERS];
+        let bound = |n: &ClaimInt| n.clamped(BOUND_LIMIT);
+        let test = match &predicate.condition {
+            Condition::Equal(text) => Test::Member { members: member(text), negate: false },
+            Condition::NotEqual(text) => Test::Member { members: member(text), negate: true },
+            Condition::In(texts) if texts.len() <= PREDICATE_MAX_MEMBERS => {
+                let mut members: Vec<Fr> = texts.iter().map(|text| claim_value_field(text)).collect();
+                members.resize(PREDICATE_MAX_MEMBERS, *members.last()?);
+                Test::Member { members, negate: false }
+            }
+            Condition::AtLeast(n) => Test::Integer { sign: 1, bound: bound(n) },
+            Condition::Above(n) => Test::Integer { sign: 1, bound: bound(n) + 1 },
+            Condition::AtMost(n) => Test::Integer { sign: -1, bound: -bound(n) },
+            Condition::Below(n) => Test::Integer { sign: -1, bound: 1 - bound(n) },
+            Condition::In(_) | Condition::Absent => return None,
+        };
+        Some(Self { claim_key: claim_key_field(&predicate.claim), test })
+    }
+
+    /// The public inputs after the anchor, in circuit order
+    fn inputs(&self) -> [Fr; STATEMENT_INPUTS] {
+        let zero = Fr::from(0u64);
+        let (integer, negate, sign, bound, members) = match &self.test {
+            Test::Member { members, negate } => (false, *negate, zero, zero, members.as_slice()),
+            Test::Integer { sign, bound } => (true, false, int_field(*sign), int_field(*bound), &[][..]),
+        };
+        let mut inputs = [zero; STATEMENT_INPUTS];
+        inputs[..5].copy_from_slice(&[self.claim_key, Fr::from(integer), Fr::from(negate), sign, bound]);
+        inputs[5..5 + members.len()].copy_from_slice(members);
+        inputs
+    }
+
+    /// Whether a claim holding `value` meets the statement as the circuit
+    /// evaluates it
+    #[cfg(feature = "prover")]
+    fn holds(&self, value: &str) -> bool {
+        match &self.test {
+            Test::Member { members, negate } => members.contains(&claim_value_field(value)) != *negate,
+            Test::Integer { sign, bound } => leaf_integer(value).is_some_and(|v| sign * v >= *bound),
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+struct PredicateCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+    // The claim's value, whose leaf value is opened
+    value: Option<String>,
+
+    // 公开输入（锚点之后）
+    statement: Option<Statement>,
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for PredicateCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+
+        let inputs = self.statement.as_ref().map(Statement::inputs);
+        let input = |i: usize| inputs.map(|inputs| inputs[i]);
+        let vars = (0..STATEMENT_INPUTS)
+            .map(|i| cs.new_input_variable(|| input(i).ok_or(SynthesisError::AssignmentMissing)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (claim_key_var, integer_var, negate_var) = (vars[0], vars[1], vars[2]);
+        let (sign_var, bound_var) = (vars[3], vars[4]);
+
+        // The claim's leaf under the anchor's claims root
+        let leaf = self.value.as_deref().map(claim_value_field);
+        let leaf_var = witness(&cs, leaf)?;
+        enforce_membership(
+            &cs,
+            [(lc!() + claim_key_var, input(0)), (lc!() + leaf_var, leaf)],
+            &self.path,
+            opened.claims_root.0,
+        )?;
+
+        // Membership: the product of leaf - member over the members is zero
+        // or, negated, has an inverse; void (times 1 - integer) for an
+        // integer test
+        let mut product = (lc!() + leaf_var - vars[5], leaf.zip(input(5)).map(|(v, m)| v - m));
+        for (i, &member_var) in vars.iter().enumerate().skip(6) {
+            let value = product.1.zip(leaf).zip(input(i)).map(|((p, v), m)| p * (v - m));
+            let var = witness(&cs, value)?;
+            cs.enforce_constraint(product.0, lc!() + leaf_var - member_var, lc!() + var)?;
+            product = (lc!() + var, value);
+        }
+        let (product_lc, product) = product;
+        let inverse = product.map(|p| p.inverse().unwrap_or_default());
+        let inverse_var = witness(&cs, inverse)?;
+        let inverted = product.zip(inverse).map(|(p, i)| p * i);
+        let inverted_var = witness(&cs, inverted)?;
+        cs.enforce_constraint(product_lc.clone(), lc!() + inverse_var, lc!() + inverted_var)?;
+        let one = Fr::from(1u64);
+        let flip = input(2).zip(inverted).zip(product).map(|((n, q), p)| n * (q - one - p));
+        let flip_var = witness(&cs, flip)?;
+        cs.enforce_constraint(
+            lc!() + negate_var,
+            lc!() + inverted_var - (one, Variable::One) - product_lc.clone(),
+            lc!() + flip_var,
+        )?;
+        cs.enforce_constraint(lc!() + Variable::One - integer_var, product_lc + flip_var, lc!())?;
+
+        // Integer: value + 2^63 < 2^65 and sign * value - bound in [0, 2^67),
+        // both void (times integer) for a membership test
+        let (shifted_bits, margin_bits) = match self.statement.as_ref().map(|s| &s.test) {
+            Some(Test::Integer { sign, bound }) => {
+                let v = self.value.as_deref().and_then(leaf_integer).unwrap_or_default();
+                (Some((v + (1 << 63)) as u128), Some((sign * v - bound) as u128))
+            }
+            Some(Test::Member { .. }) => (Some(0), Some(0)),
+            None => (None, None),
+        };
+        let offset = Fr::from(1u64 << 63);
+        let shifted = input(1).zip(leaf).map(|(k, v)| k * (v + offset));
+        let shifted_var = witness(&cs, shifted)?;
+        cs.enforce_constraint(lc!() + integer_var, lc!() + leaf_var + (offset, Variable::One), lc!() + shifted_var)?;
+        enforce_bits(&cs, lc!() + shifted_var, shifted_bits, VALUE_BITS)?;
+
+        let signed = input(3).zip(leaf).map(|(s, v)| s * v);
+        let signed_var = witness(&cs, signed)?;
+        cs.enforce_constraint(lc!() + sign_var, lc!() + leaf_var, lc!() + signed_var)?;
+        let margin = input(1).zip(signed).zip(input(4)).map(|((k, t), b)| k * (t - b));
+        let margin_var = witness(&cs, margin)?;
+        cs.enforce_constraint(lc!() + integer_var, lc!() + signed_var - bound_var, lc!() + margin_var)?;
+        enforce_bits(&cs, lc!() + margin_var, margin_bits, MARGIN_BITS)
+    }
+}
+
+/// Keys of the predicate circuit; the verifying key of a verifier-only build
+/// arrives by import
+#[derive(Default)]
+struct PredicateKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static PREDICATE_KEYS: Mutex<PredicateKeys> = Mutex::new(PredicateKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the predicate circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let blank = PredicateCircuit {
+        anchor: AnchorWitness::blank(),
+        path: ClaimPath::blank(),
+        value: None,
+        statement: None,
+    };
+    let mut rng = seeded_rng(8u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(blank, &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match PREDICATE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the predicate verifying key is present
+pub(crate) fn has_keys() -> bool {
+    PREDICATE_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the predicate circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = PREDICATE_KEYS.lock() {
+        *keys = PredicateKeys::default();
+    }
+}
+
+/// A predicate argument in binary form, as hex
+fn predicate_arg(predicate_hex: *const c_char) -> Option<Predicate> {
+    if predicate_hex.is_null() {
+        return None;
+    }
+    let text = unsafe { CStr::from_ptr(predicate_hex) }.to_str().ok()?;
+    if text.len() > 2 * MAX_FIELD_LEN {
+        return None;
+    }
+    Predicate::from_bytes(&hex_to_bytes(text).ok()?)
+}
+
+#[cfg(feature = "verifier")]
+fn verify_predicate(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    statement: &Statement,
+    nonce: u64,
+) -> c_int {
+    let issuer_bytes = match admission::text_arg(issuer_pubkey, MAX_FIELD_LEN).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return e.code_or(0),
+        None => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs at the time the proof carries, then the statement
+    // (circuit order); None unless the issuer signed the anchor
+    let mut public_inputs =
+        match validity::public_inputs(&issuer_bytes, format, nonce, header.current_time, &header) {
+            Some(inputs) => inputs.to_vec(),
+            None => return 0,
+        };
+    public_inputs.extend_from_slice(&statement.inputs());
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Encode a predicate given in JSON form
+///
+/// Writes the hex binary form to `predicate_hex_out` and, unless
+/// `hash_hex_out` is NULL, the hex predicate hash. Returns 0 on success,
+/// ZK_ERR_CORRUPT if the JSON is not a predicate, ZK_ERR_BUFFER_TOO_SMALL
+/// for a short buffer, -1 on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_EncodePredicate(
+    predicate_json: *const c_char,
+    predicate_json_len: usize,
+    predicate_hex_out: *mut c_char,
+    predicate_hex_out_size: usize,
+    hash_hex_out: *mut c_char,
+    hash_hex_out_size: usize,
+) -> c_int {
+    let data = match read_bytes(predicate_json, predicate_json_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let predicate = match serde_json::from_slice::<Value>(data).ok().as_ref().and_then(Predicate::from_json) {
+        Some(predicate) => predicate,
+        None => return crate::error::ZK_ERR_CORRUPT,
+    };
+
+    if let Err(e) = write_cstr(predicate_hex_out, predicate_hex_out_size, &bytes_to_hex(&predicate.to_bytes())) {
+        return e.code();
+    }
+    if hash_hex_out.is_null() {
+        return 0;
+    }
+    match write_cstr(hash_hex_out, hash_hex_out_size, &bytes_to_hex(&predicate.hash())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Decode a predicate in binary form (hex) to its canonical JSON form
+///
+/// Returns 0 on success, ZK_ERR_CORRUPT unless the input is the canonical
+/// binary form of a predicate, ZK_ERR_BUFFER_TOO_SMALL for a short buffer,
+/// -1 on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_DecodePredicate(
+    predicate_hex: *const c_char,
+    predicate_json_out: *mut c_char,
+    predicate_json_out_size: usize,
+) -> c_int {
+    if predicate_hex.is_null() {
+        return -1;
+    }
+    let predicate = match predicate_arg(predicate_hex) {
+        Some(predicate) => predicate,
+        None => return crate::error::ZK_ERR_CORRUPT,
+    };
+
+    match write_cstr(predicate_json_out, predicate_json_out_size, &predicate.to_json().to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Prove that a credential satisfies `predicate_hex` (binary form, hex)
+/// without disclosing it
+///
+/// Runs the ZK_GenerateVCProofFromBlob pre-checks first, then evaluates the
+/// predicate over the credential's claims as the circuit does: an integer
+/// condition needs a numeric claim (a canonical decimal in [-2^63, 2^64)).
+/// Returns 0 on success, the pre-check's code for a credential that would
+/// not verify, ZK_ERR_CORRUPT for a malformed predicate,
+/// ZK_ERR_INCOMPATIBLE for "absent" or "in" with more than
+/// PREDICATE_MAX_MEMBERS members, ZK_ERR_UNSUPPORTED_VERSION for a blob
+/// before version 6, ZK_ERR_INPUT_TOO_LARGE for more than
+/// MAX_COMMITTED_CLAIMS claims, ZK_ERR_NOT_INITIALIZED,
+/// ZK_ERR_PROVE_FAILED, -1 if the credential does not satisfy it.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GeneratePredicateProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    predicate_hex: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if predicate_hex.is_null() || proof_out.is_null() {
+        return -1;
+    }
+    let predicate = match predicate_arg(predicate_hex) {
+        Some(predicate) => predicate,
+        None => return crate::error::ZK_ERR_CORRUPT,
+    };
+    let statement = match Statement::of(&predicate) {
+        Some(statement) => statement,
+        None => return ZK_ERR_INCOMPATIBLE,
+    };
+
+    let (vc, issuer_key, now) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+    if !vc.claims_root_signed {
+        return ZK_ERR_UNSUPPORTED_VERSION;
+    }
+    let tree = match ClaimsTree::new(&vc.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+    let index = match vc.claims.iter().position(|(k, _)| *k == predicate.claim) {
+        Some(index) if statement.holds(&vc.claims[index].1) => index,
+        _ => return -1,
+    };
+
+    let (signature, opening) = match (vc.anchored_signature(), vc.opening()) {
+        (Ok(signature), Some(opening)) => (signature, opening),
+        (Err(code), _) => return code,
+        (_, None) => return -1,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), now, Fr::from(nonce)).and_then(
+        |(anchor, header)| {
+            let circuit = PredicateCircuit {
+                anchor,
+                path: ClaimPath::of(&tree, index)?,
+                value: Some(vc.claims[index].1.clone()),
+                statement: Some(statement),
+            };
+            Some((circuit, header))
+        },
+    );
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match PREDICATE_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify that an issuer-signed credential satisfies `predicate_hex`
+/// (binary form, hex)
+///
+/// The proof verifies only for predicates of the same meaning as the one it
+/// was made for, at the time it carries. Returns 1 if valid, 0 if invalid
+/// (including a malformed predicate or one without a statement),
+/// ZK_ERR_DISABLED for a legacy-format proof in a strict build,
+/// ZK_ERR_UNSUPPORTED_VERSION for a format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyPredicateProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    predicate_hex: *const c_char,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = PREDICATE_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let predicate = predicate_arg(predicate_hex);
+    let predicate_hash = predicate.as_ref().map(Predicate::hash);
+    let result = match (pvk, predicate.as_ref().and_then(Statement::of)) {
+        (Some(pvk), Some(statement)) if !proof_hex.is_null() && !issuer_pubkey.is_null() => {
+            verify_predicate(pvk, proof_hex, issuer_pubkey, &statement, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "predicate-proof",
+        code: result,
+        circuit: Some(PREDICATE_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: predicate_hash.as_ref(),
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the predicate verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportPredicateVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match PREDICATE_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportPredicateVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportPredicateVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == PREDICATE_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match PREDICATE_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
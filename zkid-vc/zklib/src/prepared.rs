@@ -0,0 +1,194 @@
+// ============================================================================
+// Prepared Public Contexts
+// ============================================================================
+//
+// A verifier that checks many proofs for the same issuer recomputes the same
+// public-input combination every call:
+//
+//   g_ic = gamma_abc[0] + issuer_hash * gamma_abc[1] + nonce * gamma_abc[2]
+//        + current_time * gamma_abc[3] + anchor * gamma_abc[4]
+//
+// The first two terms depend only on the verifying key and the issuer, so a
+// prepared context caches them (once per supported proof format) and each
+// verification only folds in the nonce, time and anchor terms before the
+// pairing check, after checking the issuer's signature on the anchor. The
+// context also pins a snapshot of the verifying key it was prepared against.
+
+use ark_bn254::{Bn254, G1Projective};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::time::Instant;
+
+use crate::admission;
+use crate::audit;
+use crate::clock;
+use crate::proof::{self, PROOF_FORMATS};
+use crate::sizes::VC_PUBLIC_INPUTS;
+use crate::validity::Header;
+use crate::{did, VERIFYING_KEY};
+
+/// Verifier state for one issuer with the static public inputs folded in
+pub struct PublicContext {
+    pvk: PreparedVerifyingKey<Bn254>,
+    issuer_pubkey: Vec<u8>,
+    // (format, gamma_abc[0] + issuer_hash * gamma_abc[1])
+    static_terms: Vec<(u8, G1Projective)>,
+}
+
+impl PublicContext {
+    /// Precompute the issuer term for every proof format this build supports
+    pub fn new(pvk: PreparedVerifyingKey<Bn254>, issuer_pubkey: &[u8]) -> Option<Self> {
+        // Circuit inputs: issuer_pubkey_hash, nonce, current_time, anchor
+        if pvk.vk.gamma_abc_g1.len() != VC_PUBLIC_INPUTS + 1 {
+            return None;
+        }
+
+        let static_terms = PROOF_FORMATS
+            .into_iter()
+            .filter_map(|format| {
+                let issuer_field = proof::field_for(format, issuer_pubkey)?;
+                let term = pvk.vk.gamma_abc_g1[0].into_group()
+                    + pvk.vk.gamma_abc_g1[1].mul_bigint(issuer_field.into_bigint());
+                Some((format, term))
+            })
+            .collect();
+
+        Some(Self { pvk, issuer_pubkey: issuer_pubkey.to_vec(), static_terms })
+    }
+
+    /// Verify a proof at `current_time`, completing the cached input
+    /// combination with it, `nonce` and the proof's header; false unless
+    /// the context's issuer signed the header's anchor
+    pub fn verify(&self, format: u8, proof: &Proof<Bn254>, header: &Header, current_time: u64, nonce: u64) -> bool {
+        let static_term = match self.static_terms.iter().find(|(f, _)| *f == format) {
+            Some((_, term)) if header.signed_by(&self.issuer_pubkey) => *term,
+            _ => return false,
+        };
+
+        let nonce_field = ark_bn254::Fr::from(nonce);
+        let time_field = ark_bn254::Fr::from(current_time);
+        let gamma_abc = &self.pvk.vk.gamma_abc_g1;
+        let prepared_inputs = static_term
+            + gamma_abc[2].mul_bigint(nonce_field.into_bigint())
+            + gamma_abc[3].mul_bigint(time_field.into_bigint())
+            + gamma_abc[4].mul_bigint(header.anchor.into_bigint());
+
+        matches!(
+            Groth16::<Bn254>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs),
+            Ok(true)
+        )
+    }
+
+    /// Decode a hex proof and verify it at `current_time`, returning the C
+    /// API result code
+    fn verify_hex(&self, proof_hex: *const c_char, current_time: u64, nonce: u64) -> c_int {
+        let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+            Ok(decoded) => decoded,
+            Err(rejection) => return rejection.verify_code(),
+        };
+        let now = match clock::now(current_time) {
+            Ok(now) => now,
+            Err(code) => return code,
+        };
+
+        let valid = self.verify(format, &proof, &header, now, nonce);
+        proof::record_outcome(format, valid);
+        valid as c_int
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Prepare a verification context for one issuer public key (hex)
+///
+/// Requires ZK_Init (or imported keys). The handle is written to `ctx_out`
+/// and must be released with ZK_FreePublicContext. Returns 0 on success,
+/// -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_PreparePublicContext(
+    issuer_pubkey: *const c_char,
+    ctx_out: *mut *mut PublicContext,
+) -> c_int {
+    if issuer_pubkey.is_null() || ctx_out.is_null() {
+        return -1;
+    }
+
+    let issuer_pubkey_str = unsafe { CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("") };
+    let issuer_pubkey_bytes = match did::public_key_bytes(issuer_pubkey_str) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code_or(-1),
+    };
+
+    let pvk = match VERIFYING_KEY.lock() {
+        Ok(pvk) => match pvk.as_ref() {
+            Some(pvk) => PreparedVerifyingKey::clone(pvk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match PublicContext::new(pvk, &issuer_pubkey_bytes) {
+        Some(ctx) => {
+            unsafe {
+                *ctx_out = Box::into_raw(Box::new(ctx));
+            }
+            0
+        }
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a VC proof against a prepared issuer context
+///
+/// Same result as ZK_VerifyVCProof with the context's issuer key: 1 if
+/// valid, 0 if invalid or not proven for `current_time`, the time source's
+/// error, ZK_ERR_DISABLED for a
+/// legacy-format proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyVCProofPrepared(
+    ctx: *const PublicContext,
+    proof_hex: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+    let ctx = unsafe { ctx.as_ref() };
+    let result = match ctx {
+        Some(ctx) => ctx.verify_hex(proof_hex, current_time, nonce),
+        None => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "vc-proof",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: ctx.map(|ctx| &ctx.pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Release a context from ZK_PreparePublicContext
+#[no_mangle]
+pub extern "C" fn ZK_FreePublicContext(ctx: *mut PublicContext) {
+    if !ctx.is_null() {
+        unsafe {
+            drop(Box::from_raw(ctx));
+        }
+    }
+}
+}
@@ -0,0 +1,573 @@
+// ============================================================================
+// Presentations and Transcript Digests
+// ============================================================================
+//
+// A presentation bundles a VC proof with the public inputs needed to verify
+// it and free-form metadata (audience, purpose, ...). Binary encoding, all
+// integers little-endian, variable-length fields prefixed with their u32
+// length:
+//
+//   "ZKPR" | version u8 | proof | issuer_pubkey | nonce u64
+//   | metadata_count u32 | (key, value)*
+//
+// The proof field is a VC proof with its validity prefix (see validity.rs)
+// around the proof wire format (see proof.rs); a legacy bare proof may be
+// compressed or uncompressed. The proof is checked at the time its prefix
+// carries, which the circuit shows the credential's dates cover; whether
+// that time is recent is the caller's business, like the nonce. Given the
+// VC, verification also checks that the proof's anchor is the VC's. `digest()` hashes a normalized
+// encoding (compressed proof, metadata sorted) so the same presentation
+// yields the same digest no matter how it was transported.
+//
+// The digest is an integrity binder for external transcripts (e.g. a signed
+// handshake): it says *which* presentation was exchanged, not that it is
+// valid. Verify the proof separately.
+//
+// ZK_VerifyPresentation answers 1/0. Gateways that log policy denials apart
+// from forged proofs use ZK_VerifyPresentationDetailed, which runs every
+// check it has inputs for (none short-circuits another) and reports each in
+// a ZkVerifyReport.
+
+use ark_bn254::Bn254;
+use ark_groth16::Proof;
+#[cfg(feature = "verifier")]
+use {
+    ark_groth16::{Groth16, PreparedVerifyingKey},
+    ark_snark::SNARK,
+    crate::audit,
+    crate::credential::{parse_vc_blob, parse_verifying_key, SignatureCheckError},
+    crate::proof,
+    crate::schema,
+    crate::validation::revocation_lookup,
+    crate::{clock, VerifiableCredential},
+    ed25519_dalek::VerifyingKey,
+    std::time::Instant,
+};
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use crate::admission;
+use crate::cbor::PRESENTATION_CBOR_HEAD;
+use crate::credential::read_claims;
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::validity::{self, Header};
+use crate::wire::{put_bytes, Reader, PRESENTATION_MAGIC, PRESENTATION_VERSION};
+use crate::{bytes_to_hex, did, hex_to_bytes};
+
+/// Magic and version byte preceding the length-prefixed fields
+pub const PRESENTATION_HEADER_LEN: usize =
+    PRESENTATION_MAGIC.len() + std::mem::size_of::<u8>();
+const DIGEST_DOMAIN: &[u8] = b"zkid/presentation-digest/v1";
+
+/// A VC proof together with its public inputs and metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct Presentation {
+    pub format: u8,
+    pub proof: Proof<Bn254>,
+    pub header: Header,
+    pub issuer_pubkey: Vec<u8>,
+    pub nonce: u64,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl Presentation {
+    /// Encode with a compressed proof and metadata in insertion order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode(&self.metadata)
+    }
+
+    fn encode(&self, metadata: &[(String, String)]) -> Vec<u8> {
+        let proof_bytes = validity::encode(&self.header, self.format, &self.proof);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(PRESENTATION_MAGIC);
+        out.push(PRESENTATION_VERSION);
+        put_bytes(&mut out, &proof_bytes);
+        put_bytes(&mut out, &self.issuer_pubkey);
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+        for (key, value) in metadata {
+            put_bytes(&mut out, key.as_bytes());
+            put_bytes(&mut out, value.as_bytes());
+        }
+        out
+    }
+
+    /// Decode a binary presentation
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(PRESENTATION_MAGIC.len())? != PRESENTATION_MAGIC || r.u8()? != PRESENTATION_VERSION {
+            return None;
+        }
+
+        let (header, format, proof) = admission::decode_vc_proof(r.bytes()?, None).ok()?;
+        let issuer_pubkey = r.bytes()?.to_vec();
+        let nonce = r.u64()?;
+
+        let metadata_count = r.u32()? as usize;
+        let mut metadata = Vec::new();
+        for _ in 0..metadata_count {
+            metadata.push((r.string()?, r.string()?));
+        }
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            format,
+            proof,
+            header,
+            issuer_pubkey,
+            nonce,
+            metadata,
+        })
+    }
+
+    /// Decode the binary form, its hex text or the CBOR form (cbor.rs)
+    ///
+    /// Input longer than MAX_PRESENTATION_LEN (twice that as hex) is refused
+    /// before anything is decoded.
+    pub fn from_wire(data: &[u8]) -> Option<Self> {
+        admission::admit_presentation(data).ok()?;
+        if data.starts_with(PRESENTATION_MAGIC) {
+            return Self::from_bytes(data);
+        }
+        if data.first() == Some(&PRESENTATION_CBOR_HEAD) {
+            return Self::from_cbor(data);
+        }
+        let text = std::str::from_utf8(data).ok()?.trim();
+        Self::from_bytes(&hex_to_bytes(text).ok()?)
+    }
+
+    /// Normalized encoding: compressed proof, metadata sorted by key then value
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut metadata = self.metadata.clone();
+        metadata.sort();
+        self.encode(&metadata)
+    }
+
+    /// Verify the proof against the presentation's public inputs, at the
+    /// time the proof was generated for
+    #[cfg(feature = "verifier")]
+    pub fn verify(&self, pvk: &PreparedVerifyingKey<Bn254>) -> bool {
+        let (header, time) = (&self.header, self.header.current_time);
+        let public_inputs = match validity::public_inputs(&self.issuer_pubkey, self.format, self.nonce, time, header) {
+            Some(inputs) => inputs,
+            None => return false,
+        };
+        matches!(
+            Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &self.proof),
+            Ok(true)
+        )
+    }
+
+    /// Whether the proof was made for `vc`: its anchor is the VC's, signed
+    /// by the presentation's issuer key
+    #[cfg(feature = "verifier")]
+    pub fn commits_to(&self, vc: &VerifiableCredential) -> bool {
+        vc.anchor() == Some(self.header.anchor) && self.header.signed_by(&self.issuer_pubkey)
+    }
+
+    /// Verify with the globally initialized keys
+    #[cfg(feature = "verifier")]
+    pub fn verify_with_global_keys(&self) -> bool {
+        let pvk_guard = match crate::VERIFYING_KEY.lock() {
+            Ok(guard) => guard,
+            Err(_) => return false,
+        };
+        pvk_guard.as_ref().is_some_and(|pvk| self.verify(pvk))
+    }
+
+    /// Value of a metadata entry
+    pub fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// SHA-256 transcript digest over the normalized encoding
+    ///
+    /// Binds the presentation's integrity only; it does not verify the proof.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(DIGEST_DOMAIN);
+        hasher.update(self.canonical_bytes());
+        hasher.finalize().into()
+    }
+}
+
+// ============================================================================
+// Graded Verification
+// ============================================================================
+
+#[cfg(feature = "verifier")]
+pub use crate::validation::ZK_CHECK_SKIPPED;
+
+/// Per-check outcome of ZK_VerifyPresentationDetailed (C layout)
+///
+/// Each field is 1 if the check passed, 0 if it failed, ZK_CHECK_SKIPPED if
+/// its input was not supplied, or a negative error code if it could not be
+/// decided.
+#[cfg(feature = "verifier")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkVerifyReport {
+    /// The presentation names the expected issuer and the VC's issuer
+    /// signature(s) verify; ZK_ERR_COSIGNATURE_MISSING for dual-control
+    /// schemas without a co-signature
+    pub signature: c_int,
+    /// The Groth16 proof verifies for the presentation's issuer key and
+    /// nonce and, given the VC, commits to it; ZK_ERR_UNSUPPORTED_VERSION if
+    /// its format is not accepted
+    pub proof: c_int,
+    /// The VC is inside its issue/expiry dates and validity schedule;
+    /// ZK_ERR_TIME_MISMATCH if the time sources disagree
+    pub time_policy: c_int,
+    /// The VC is neither revoked nor, with ZK_SetRejectSuperseded, superseded;
+    /// ZK_ERR_STORAGE if the registry lookup failed
+    pub revocation: c_int,
+    /// The presentation's "audience" metadata equals the expected audience
+    pub audience: c_int,
+    /// Epoch of the published revocation list checked against, 0 if none
+    pub revocation_epoch: u64,
+}
+
+#[cfg(feature = "verifier")]
+impl ZkVerifyReport {
+    /// Whether no check failed or was left undecided
+    pub fn accepted(&self) -> bool {
+        [self.signature, self.proof, self.time_policy, self.revocation, self.audience]
+            .iter()
+            .all(|&code| code == 1 || code == ZK_CHECK_SKIPPED)
+    }
+}
+
+/// Proof-layer check with the accepted-formats policy applied; given `vc`,
+/// the proof must also commit to it
+#[cfg(feature = "verifier")]
+pub(crate) fn proof_check(
+    presentation: &Presentation,
+    pvk: &PreparedVerifyingKey<Bn254>,
+    vc: Option<&VerifiableCredential>,
+) -> c_int {
+    match proof::admit(presentation.format, proof::accepted_formats()) {
+        Ok(()) if vc.is_some_and(|vc| !presentation.commits_to(vc)) => 0,
+        Ok(()) => {
+            let valid = presentation.verify(pvk);
+            proof::record_outcome(presentation.format, valid);
+            valid as c_int
+        }
+        Err(code) => code,
+    }
+}
+
+#[cfg(feature = "verifier")]
+pub(crate) fn signature_check(
+    presentation: Option<&Presentation>,
+    vc: Option<&VerifiableCredential>,
+    issuer_key: &VerifyingKey,
+    second_key: Option<&VerifyingKey>,
+) -> c_int {
+    if presentation.is_some_and(|p| p.issuer_pubkey != issuer_key.as_bytes()) {
+        return 0;
+    }
+    match vc.map(|vc| vc.verify_issuer_signatures(issuer_key, second_key)) {
+        Some(Err(SignatureCheckError::BadSignature)) => 0,
+        Some(Err(e)) => e.code(),
+        Some(Ok(())) | None => 1,
+    }
+}
+
+/// Run every check that has its inputs; see ZK_VerifyPresentationDetailed
+#[cfg(feature = "verifier")]
+pub(crate) fn verify_detailed(
+    presentation: Option<&Presentation>,
+    pvk: Option<&PreparedVerifyingKey<Bn254>>,
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+    expected_audience: *const c_char,
+    current_time: u64,
+) -> ZkVerifyReport {
+    let vc = if vc_blob.is_null() { None } else { parse_vc_blob(vc_blob) };
+    let vc_missing = if vc_blob.is_null() { ZK_CHECK_SKIPPED } else { 0 };
+
+    let signature = if issuer_public_key.is_null() {
+        ZK_CHECK_SKIPPED
+    } else {
+        let second_key = if second_issuer_public_key.is_null() {
+            Some(None)
+        } else {
+            parse_verifying_key(second_issuer_public_key).map(Some)
+        };
+        match (parse_verifying_key(issuer_public_key), second_key) {
+            // An undecodable VC blob fails here as well as in the VC checks
+            (Some(key), Some(second)) if vc.is_some() || vc_blob.is_null() => {
+                signature_check(presentation, vc.as_ref(), &key, second.as_ref())
+            }
+            _ => 0,
+        }
+    };
+
+    let proof = match (presentation, pvk) {
+        (Some(p), Some(pvk)) => proof_check(p, pvk, vc.as_ref()),
+        _ => 0,
+    };
+
+    let time_policy = match (&vc, clock::now(current_time)) {
+        (Some(vc), Ok(now)) => vc.active_at(now) as c_int,
+        (Some(_), Err(code)) => code,
+        (None, _) => vc_missing,
+    };
+
+    // A published list only applies to the issuer the signature check uses
+    let issuer = parse_verifying_key(issuer_public_key).map(|key| key.to_bytes());
+    let published = crate::revocation::published();
+    let revocation = vc.as_ref().map_or(vc_missing, |vc| {
+        revocation_lookup(issuer.as_ref(), &vc.credential_id(), published.as_deref())
+    });
+
+    let audience = if expected_audience.is_null() {
+        ZK_CHECK_SKIPPED
+    } else {
+        let expected = unsafe { CStr::from_ptr(expected_audience) }.to_str().ok();
+        (expected.is_some() && presentation.and_then(|p| p.metadata_value("audience")) == expected) as c_int
+    };
+
+    ZkVerifyReport {
+        signature,
+        proof,
+        time_policy,
+        revocation,
+        audience,
+        revocation_epoch: published.map_or(0, |data| data.epoch_for(issuer.as_ref())),
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Build a hex presentation blob from a hex proof, issuer key and metadata
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_EncodePresentation(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    metadata_keys: *const *const c_char,
+    metadata_values: *const *const c_char,
+    metadata_count: usize,
+    presentation_out: *mut c_char,
+    presentation_out_size: usize,
+) -> c_int {
+    if proof_hex.is_null() || issuer_pubkey.is_null() {
+        return -1;
+    }
+
+    let proof_hex_str = unsafe { CStr::from_ptr(proof_hex).to_str().unwrap_or("") };
+    let issuer_pubkey_str = unsafe { CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("") };
+
+    let (header, format, proof) = match hex_to_bytes(proof_hex_str)
+        .ok()
+        .and_then(|bytes| validity::decode(&bytes).ok())
+    {
+        Some(decoded) => decoded,
+        None => return -1,
+    };
+
+    let issuer_pubkey = match did::public_key_bytes(issuer_pubkey_str) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code_or(-1),
+    };
+
+    let metadata = match read_claims(metadata_keys, metadata_values, metadata_count) {
+        Some(metadata) => metadata,
+        None => return -1,
+    };
+
+    let presentation = Presentation {
+        format,
+        proof,
+        header,
+        issuer_pubkey,
+        nonce,
+        metadata,
+    };
+
+    match write_cstr(presentation_out, presentation_out_size, &bytes_to_hex(&presentation.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Compute the 32-byte transcript digest of a presentation
+///
+/// `presentation_blob` is either the binary encoding or its hex text;
+/// `digest_out` must point to 32 writable bytes. The digest binds the
+/// presentation into an external transcript and is NOT a substitute for
+/// verifying the proof. Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_PresentationDigest(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    digest_out: *mut u8,
+) -> c_int {
+    if presentation_blob.is_null() || digest_out.is_null() {
+        return -1;
+    }
+
+    let data = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let presentation = match Presentation::from_wire(data) {
+        Some(p) => p,
+        None => return -1,
+    };
+
+    let digest = presentation.digest();
+    unsafe {
+        std::ptr::copy_nonoverlapping(digest.as_ptr(), digest_out, digest.len());
+    }
+
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify the proof carried by a presentation (binary or hex text)
+///
+/// Returns 1 if valid, 0 if invalid, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted (ZK_SetAcceptedFormatVersions),
+/// ZK_ERR_SCHEMA_NOT_ALLOWED if a schema allowlist is set and the zkid.vc
+/// credential is missing or outside it (see schema.rs).
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyPresentation(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+) -> c_int {
+    let started = Instant::now();
+    if presentation_blob.is_null() {
+        return verify_and_record(started, None);
+    }
+
+    let data = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    verify_and_record(started, Presentation::from_wire(data))
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a presentation and report each check separately
+///
+/// Runs every check whose inputs are given, without stopping at the first
+/// failure, and fills `report_out` (see ZkVerifyReport):
+///
+///   signature    needs `issuer_public_key` (hex); checks the VC's issuer
+///                signature(s) if `vc_blob` is given, with
+///                `second_issuer_public_key` (may be NULL) as in
+///                ZK_VerifyVCBlob
+///   proof        always; uses the global keys
+///   time_policy  needs `vc_blob`; `current_time` goes through the
+///                configured time source (ZK_SetTimeMode)
+///   revocation   needs `vc_blob`
+///   audience     needs `expected_audience`
+///
+/// A presentation that does not decode fails proof, signature and audience.
+/// Returns 1 if no check failed or was undecided, 0 otherwise,
+/// ZK_ERR_SCHEMA_NOT_ALLOWED if a schema allowlist is set and the credential
+/// (`vc_blob`, else zkid.vc) does not disclose an allowed schema, -1 on NULL
+/// `presentation_blob` or `report_out`.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyPresentationDetailed(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+    expected_audience: *const c_char,
+    current_time: u64,
+    report_out: *mut ZkVerifyReport,
+) -> c_int {
+    let started = Instant::now();
+    if presentation_blob.is_null() || report_out.is_null() {
+        return -1;
+    }
+
+    let data = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let presentation = Presentation::from_wire(data);
+
+    let pvk_guard = crate::VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_deref());
+
+    let report = verify_detailed(
+        presentation.as_ref(),
+        pvk,
+        vc_blob,
+        issuer_public_key,
+        second_issuer_public_key,
+        expected_audience,
+        current_time,
+    );
+    unsafe {
+        *report_out = report;
+    }
+    let vc = if vc_blob.is_null() { None } else { parse_vc_blob(vc_blob) };
+    let result = schema::gate(report.accepted() as c_int, presentation.as_ref(), vc.as_ref());
+
+    audit::record(started, audit::Verification {
+        check: "presentation",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: presentation.as_ref().map(|p| p.nonce),
+        audience: presentation.as_ref().and_then(|p| p.metadata_value("audience")),
+        predicate: None,
+    });
+
+    result
+}
+}
+
+/// Verify a decoded presentation (None if it failed to decode) with the
+/// global keys and record the audit event
+#[cfg(feature = "verifier")]
+pub(crate) fn verify_and_record(started: Instant, presentation: Option<Presentation>) -> c_int {
+    let pvk_guard = crate::VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_deref());
+
+    let result = match (&presentation, pvk) {
+        (Some(p), Some(pvk)) => proof_check(p, pvk, None),
+        _ => 0,
+    };
+    let result = schema::gate(result, presentation.as_ref(), None);
+
+    audit::record(started, audit::Verification {
+        check: "presentation",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: presentation.as_ref().map(|p| p.nonce),
+        audience: presentation.as_ref().and_then(|p| p.metadata_value("audience")),
+        predicate: None,
+    });
+
+    result
+}
@@ -0,0 +1,393 @@
+// ============================================================================
+// Proof Wire Format
+// ============================================================================
+//
+// Proofs cross the C API as hex of one of
+//
+//   legacy:    bare arkworks proof, 128 bytes compressed or 256 uncompressed
+//   envelope:  "ZP" | format u8 | arkworks proof
+//
+// The two are told apart by length. The envelope's format byte carries
+// ENVELOPE_UNCOMPRESSED (0x80) when the proof's points are uncompressed, as
+// some external provers can only emit; bare proofs are told apart by length.
+// Either encoding is checked for canonical coordinates, the curve and the
+// subgroup before use, and ZK_ConvertProofEncoding normalizes between them.
+// The format selects the public-input layout the proof was generated for:
+//
+//   1  legacy: SHA-256 truncated to 64 bits, reduced mod 10^12 (bare only)
+//   2  SHA-256 reduced mod r over its full width
+//   3  as 2, over "zkid.fr.map.v1" | data (format version 2, see domain.rs)
+//
+// The legacy mapping keeps about 40 bits of the digest, so two inputs with
+// the same public input can be found with about a million hashes (see the
+// field-map collision vectors in conformance.rs). Provers emit format 2 by
+// default; standard builds still verify format 1 during migration. Strict
+// builds (feature "strict") contain neither the legacy mapping nor the bare
+// encoding; such proofs are rejected with ZK_ERR_DISABLED.
+//
+// Migration between formats is done at runtime: ZK_SetEmitFormatVersion
+// picks what the prover emits, ZK_SetAcceptedFormatVersions what the
+// verifier admits (bit 1 << (format - 1)). Verifiers count proofs per
+// format, so operators can see when old-format traffic has stopped before
+// dropping it; a dropped format is refused with ZK_ERR_UNSUPPORTED_VERSION.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::Proof;
+use alloc::vec::Vec;
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+use crate::encoding::{PointEncoding, ZkDeserialize, ZkSerialize};
+use crate::error::{ZK_ERR_DISABLED, ZK_ERR_UNSUPPORTED_VERSION};
+use crate::{hash_bytes_to_field_full, hash_bytes_to_field_tagged};
+
+pub const PROOF_FORMAT_LEGACY: u8 = 1;
+pub const PROOF_FORMAT_V2: u8 = 2;
+pub const PROOF_FORMAT_TAGGED: u8 = 3;
+
+/// Every proof format, supported by this build or not
+pub const PROOF_FORMATS: [u8; 3] = [PROOF_FORMAT_LEGACY, PROOF_FORMAT_V2, PROOF_FORMAT_TAGGED];
+
+/// Format emitted by the prover entry points
+pub const EMIT_FORMAT: u8 = PROOF_FORMAT_V2;
+
+pub(crate) const ENVELOPE_MAGIC: &[u8; 2] = b"ZP";
+/// Magic and format byte preceding an enveloped proof
+pub(crate) const ENVELOPE_HEADER_LEN: usize = ENVELOPE_MAGIC.len() + 1;
+/// Format-byte flag of an enveloped proof with uncompressed points
+pub(crate) const ENVELOPE_UNCOMPRESSED: u8 = 0x80;
+
+/// Point encodings for ZK_ConvertProofEncoding
+pub const ZK_PROOF_ENCODING_COMPRESSED: c_int = 0;
+pub const ZK_PROOF_ENCODING_UNCOMPRESSED: c_int = 1;
+
+/// Accepted-formats bits
+pub const ZK_ACCEPT_FORMAT_V1: u32 = 1 << (PROOF_FORMAT_LEGACY - 1);
+pub const ZK_ACCEPT_FORMAT_V2: u32 = 1 << (PROOF_FORMAT_V2 - 1);
+pub const ZK_ACCEPT_FORMAT_V3: u32 = 1 << (PROOF_FORMAT_TAGGED - 1);
+
+static EMIT: AtomicU8 = AtomicU8::new(EMIT_FORMAT);
+static ACCEPTED: AtomicU32 = AtomicU32::new(supported_mask());
+
+/// Per-format verification outcomes, indexed by format - 1
+struct FormatCounters {
+    valid: AtomicU64,
+    invalid: AtomicU64,
+    refused: AtomicU64,
+}
+
+impl FormatCounters {
+    const fn new() -> Self {
+        Self {
+            valid: AtomicU64::new(0),
+            invalid: AtomicU64::new(0),
+            refused: AtomicU64::new(0),
+        }
+    }
+}
+
+static COUNTERS: [FormatCounters; 3] = [FormatCounters::new(), FormatCounters::new(), FormatCounters::new()];
+
+/// Why a proof could not be decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofDecodeError {
+    Malformed,
+    /// Well-formed, but its format is compiled out of this build
+    Disabled,
+}
+
+impl ProofDecodeError {
+    pub fn code(self) -> c_int {
+        match self {
+            ProofDecodeError::Malformed => -1,
+            ProofDecodeError::Disabled => ZK_ERR_DISABLED,
+        }
+    }
+}
+
+/// Whether this build can generate and verify proofs of `format`
+pub fn format_supported(format: u8) -> bool {
+    (format == PROOF_FORMAT_LEGACY && cfg!(not(feature = "strict")))
+        || format == PROOF_FORMAT_V2
+        || format == PROOF_FORMAT_TAGGED
+}
+
+/// Accepted-formats bits for every format this build supports
+pub const fn supported_mask() -> u32 {
+    if cfg!(feature = "strict") {
+        ZK_ACCEPT_FORMAT_V2 | ZK_ACCEPT_FORMAT_V3
+    } else {
+        ZK_ACCEPT_FORMAT_V1 | ZK_ACCEPT_FORMAT_V2 | ZK_ACCEPT_FORMAT_V3
+    }
+}
+
+/// Format the prover entry points currently emit
+pub fn emit_format() -> u8 {
+    EMIT.load(Ordering::Relaxed)
+}
+
+/// Emit `format` from now on (a format this build supports)
+#[cfg(feature = "prover")]
+pub(crate) fn set_emit_format(format: u8) {
+    EMIT.store(format, Ordering::Relaxed);
+}
+
+/// Accepted-formats bits the global verify entry points apply
+pub fn accepted_formats() -> u32 {
+    ACCEPTED.load(Ordering::Relaxed)
+}
+
+/// Whether `accepted` is a non-empty set of known formats
+pub fn valid_accept_mask(accepted: u32) -> bool {
+    accepted != 0 && accepted & !(ZK_ACCEPT_FORMAT_V1 | ZK_ACCEPT_FORMAT_V2 | ZK_ACCEPT_FORMAT_V3) == 0
+}
+
+fn counters(format: u8) -> Option<&'static FormatCounters> {
+    COUNTERS.get(usize::from(format).checked_sub(1)?)
+}
+
+/// Apply an accepted-formats policy to a decoded proof
+///
+/// A refused proof is counted and yields ZK_ERR_UNSUPPORTED_VERSION.
+pub fn admit(format: u8, accepted: u32) -> Result<(), c_int> {
+    let bit = 1u32.checked_shl(u32::from(format).wrapping_sub(1)).unwrap_or(0);
+    if accepted & bit != 0 {
+        return Ok(());
+    }
+    if let Some(counters) = counters(format) {
+        counters.refused.fetch_add(1, Ordering::Relaxed);
+    }
+    Err(ZK_ERR_UNSUPPORTED_VERSION)
+}
+
+/// Count the outcome of verifying an admitted proof
+pub fn record_outcome(format: u8, valid: bool) {
+    if let Some(counters) = counters(format) {
+        let counter = if valid { &counters.valid } else { &counters.invalid };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    #[cfg(feature = "std")]
+    crate::admission::record_pairing(valid);
+}
+
+/// Map hashed bytes to a public-input field element under `format`
+pub fn field_for(format: u8, data: &[u8]) -> Option<Fr> {
+    match format {
+        #[cfg(not(feature = "strict"))]
+        PROOF_FORMAT_LEGACY => Some(crate::hash_bytes_to_field(data)),
+        PROOF_FORMAT_V2 => Some(hash_bytes_to_field_full(data)),
+        PROOF_FORMAT_TAGGED => Some(hash_bytes_to_field_tagged(data)),
+        _ => None,
+    }
+}
+
+/// Length of a bare proof in the given point encoding (see encoding.rs)
+pub fn bare_len(encoding: PointEncoding) -> usize {
+    Proof::<Bn254>::default().zk_serialized_size(encoding)
+}
+
+/// Length of the wire form for `format`, None for an unknown format
+pub fn encoded_len(format: u8, encoding: PointEncoding) -> Option<usize> {
+    match format {
+        PROOF_FORMAT_LEGACY => Some(bare_len(encoding)),
+        PROOF_FORMAT_V2 | PROOF_FORMAT_TAGGED => Some(ENVELOPE_HEADER_LEN + bare_len(encoding)),
+        _ => None,
+    }
+}
+
+/// Split an envelope's format byte into the format and its point encoding
+pub(crate) fn envelope_format(byte: u8) -> (u8, PointEncoding) {
+    if byte & ENVELOPE_UNCOMPRESSED != 0 {
+        (byte & !ENVELOPE_UNCOMPRESSED, PointEncoding::Uncompressed)
+    } else {
+        (byte, PointEncoding::Compressed)
+    }
+}
+
+/// Serialize a proof in the wire form for `format`
+pub fn encode(format: u8, proof: &Proof<Bn254>) -> Vec<u8> {
+    encode_with(format, proof, PointEncoding::Compressed)
+}
+
+/// Serialize a proof in the wire form for `format` with the given point
+/// encoding
+pub fn encode_with(format: u8, proof: &Proof<Bn254>, encoding: PointEncoding) -> Vec<u8> {
+    let mut out = Vec::new();
+    if format != PROOF_FORMAT_LEGACY {
+        out.extend_from_slice(ENVELOPE_MAGIC);
+        out.push(match encoding {
+            PointEncoding::Compressed => format,
+            PointEncoding::Uncompressed => format | ENVELOPE_UNCOMPRESSED,
+        });
+    }
+    proof.zk_serialize(encoding, &mut out);
+    out
+}
+
+/// Split either wire form into the format, the point encoding and the bare
+/// proof, checking lengths only
+pub(crate) fn split(bytes: &[u8]) -> Option<(u8, PointEncoding, &[u8])> {
+    match bytes.len() {
+        len if len == bare_len(PointEncoding::Compressed) => Some((PROOF_FORMAT_LEGACY, PointEncoding::Compressed, bytes)),
+        len if len == bare_len(PointEncoding::Uncompressed) => {
+            Some((PROOF_FORMAT_LEGACY, PointEncoding::Uncompressed, bytes))
+        }
+        _ if bytes.starts_with(ENVELOPE_MAGIC) && bytes.len() > ENVELOPE_MAGIC.len() => {
+            let (format, encoding) = envelope_format(bytes[2]);
+            let body = &bytes[ENVELOPE_HEADER_LEN..];
+            (body.len() == bare_len(encoding)).then_some((format, encoding, body))
+        }
+        _ => None,
+    }
+}
+
+/// Parse either wire form, returning the format and the proof
+pub fn decode(bytes: &[u8]) -> Result<(u8, Proof<Bn254>), ProofDecodeError> {
+    let (format, encoding, body) = split(bytes).ok_or(ProofDecodeError::Malformed)?;
+
+    if !format_supported(format) {
+        return Err(if format == PROOF_FORMAT_LEGACY {
+            ProofDecodeError::Disabled
+        } else {
+            ProofDecodeError::Malformed
+        });
+    }
+
+    let proof = Proof::<Bn254>::zk_from_bytes(body, encoding).ok_or(ProofDecodeError::Malformed)?;
+
+    Ok((format, proof))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Set the proof format the prover entry points emit
+///
+/// Contexts created afterwards inherit it. Returns 0 on success,
+/// ZK_ERR_DISABLED if the format is compiled out, -1 if it is unknown.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SetEmitFormatVersion(version: u8) -> c_int {
+    if !format_supported(version) {
+        return if version == PROOF_FORMAT_LEGACY { ZK_ERR_DISABLED } else { -1 };
+    }
+    set_emit_format(version);
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Set which proof formats the verify entry points accept
+///
+/// `accepted` is a combination of ZK_ACCEPT_FORMAT_V1, _V2 and _V3; proofs of
+/// other formats are refused with ZK_ERR_UNSUPPORTED_VERSION. Contexts
+/// created afterwards inherit it. Returns 0 on success, ZK_ERR_DISABLED if
+/// it includes a compiled-out format, -1 if it is empty or has unknown bits.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_SetAcceptedFormatVersions(accepted: u32) -> c_int {
+    if !valid_accept_mask(accepted) {
+        return -1;
+    }
+    if accepted & !supported_mask() != 0 {
+        return ZK_ERR_DISABLED;
+    }
+    ACCEPTED.store(accepted, Ordering::Relaxed);
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Read the verification counters for one proof format
+///
+/// `valid_out` and `invalid_out` count admitted proofs by outcome,
+/// `refused_out` proofs refused by the accepted-formats policy. Counters
+/// cover the process lifetime. Any output may be NULL. Returns 0 on
+/// success, -1 for an unknown format.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_GetFormatVersionStats(
+    version: u8,
+    valid_out: *mut u64,
+    invalid_out: *mut u64,
+    refused_out: *mut u64,
+) -> c_int {
+    let counters = match counters(version) {
+        Some(counters) => counters,
+        None => return -1,
+    };
+
+    for (out, counter) in [
+        (valid_out, &counters.valid),
+        (invalid_out, &counters.invalid),
+        (refused_out, &counters.refused),
+    ] {
+        if !out.is_null() {
+            unsafe {
+                *out = counter.load(Ordering::Relaxed);
+            }
+        }
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Re-encode a hex proof with compressed or uncompressed points
+///
+/// `proof_hex` is `proof_hex_len` bytes of hex (no NUL needed), bare or
+/// enveloped, behind a VC proof's validity prefix or not; `target_encoding` is ZK_PROOF_ENCODING_COMPRESSED or
+/// _UNCOMPRESSED. The proof keeps its format and form (bare legacy proofs
+/// stay bare) and is fully checked first, points included, so the output
+/// is only ever a valid encoding. Returns 0 on success, the admission code
+/// of a refused input (ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_CORRUPT,
+/// ZK_ERR_DISABLED, ZK_ERR_UNSUPPORTED_VERSION, ZK_ERR_NON_CANONICAL,
+/// ZK_ERR_INVALID_POINT), ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL or an
+/// unknown encoding.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn ZK_ConvertProofEncoding(
+    proof_hex: *const core::ffi::c_char,
+    proof_hex_len: usize,
+    target_encoding: c_int,
+    proof_out: *mut core::ffi::c_char,
+    proof_out_size: usize,
+) -> c_int {
+    use crate::admission::{self, MAX_PROOF_HEX_LEN};
+    use crate::error::ZK_ERR_INPUT_TOO_LARGE;
+    use crate::ffi::{read_bytes, write_cstr, FfiError};
+    use crate::validity::{self, VALIDITY_PREFIX_LEN};
+
+    let encoding = match target_encoding {
+        ZK_PROOF_ENCODING_COMPRESSED => PointEncoding::Compressed,
+        ZK_PROOF_ENCODING_UNCOMPRESSED => PointEncoding::Uncompressed,
+        _ => return -1,
+    };
+    let text = match read_bytes(proof_hex, proof_hex_len, 2 * VALIDITY_PREFIX_LEN + MAX_PROOF_HEX_LEN) {
+        Ok(text) if !text.is_empty() => text,
+        Err(FfiError::TooLong) => return ZK_ERR_INPUT_TOO_LARGE,
+        _ => return -1,
+    };
+    // A VC proof keeps its validity prefix
+    let header = text
+        .get(..2 * VALIDITY_PREFIX_LEN)
+        .and_then(|head| hex::decode(head).ok())
+        .and_then(|head| validity::untag(&head).map(|(header, _)| header));
+    let text = if header.is_some() { &text[2 * VALIDITY_PREFIX_LEN..] } else { text };
+    let (format, proof) = match admission::decode_proof_hex(text, None) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.code,
+    };
+    let converted = encode_with(format, &proof, encoding);
+    let converted = match header {
+        Some(header) => header.tag(&converted),
+        None => converted,
+    };
+    match write_cstr(proof_out, proof_out_size, &crate::bytes_to_hex(&converted)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,461 @@
+// ============================================================================
+// Range Claim (numeric claim at least a threshold)
+// ============================================================================
+//
+// A holder proves that a numeric claim, e.g. age = "19", is at least a
+// threshold the verifier picks, here 18, without revealing the value.
+//
+// Numeric claim values are their own leaf values in the claims tree
+// (disclosure.rs), so the circuit opens the claim's leaf under the signed
+// claims root with the value as a private witness and enforces
+//
+//   value >= threshold
+//
+// over the integers. Both value and value - threshold are decomposed into
+// 64 bits. With a threshold below 2^64 the difference has a decomposition
+// exactly when value >= threshold, a value equal to the threshold giving 0;
+// the decomposition of the value itself leaves no witness of 2^64 or more,
+// such as a text value's leaf value, that could make a field difference
+// that wrapped around look small. Values and thresholds may use all 64 bits.
+//
+// The claim key is public, so a proof over another numeric claim does not
+// verify. As for the claim-disclosure circuit, the circuit opens the signed
+// anchor to the claims root, checks that the credential is active at the
+// time the proof carries, and the verifier checks the issuer's signature
+// over the anchor, so a claim the issuer never signed does not verify
+// whatever a modified prover skipped; the root is public and travels with
+// the proof, and blobs before version 6 have no signed root to prove
+// against. About 7,130 constraints.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor, claims_root, claim_key, threshold.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::PreparedVerifyingKey;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::bytes_to_hex;
+use crate::ffi::write_cstr;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::admission,
+    crate::disclosure::claim_key_field,
+    crate::ffi::MAX_FIELD_LEN,
+    crate::proof,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::checked_blob,
+    crate::disclosure::{enforce_membership, enforce_root_opening, field_bytes, numeric_value, ClaimPath, ClaimsTree},
+    crate::error::{ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_NOT_INITIALIZED, ZK_ERR_UNSUPPORTED_VERSION},
+    crate::schedule::{enforce_bits, witness},
+    crate::{enforce_active, entropy, prove_with_key, seeded_rng, Anchored, AnchorWitness},
+    ark_ff::PrimeField,
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::did,
+    crate::disclosure::field_from_bytes,
+    crate::hex_to_bytes,
+    crate::sizes::RANGE_CLAIM_PUBLIC_INPUTS,
+    crate::validity,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+/// Bits of a numeric claim value and of its distance to the threshold
+pub const RANGE_CLAIM_BITS: usize = 64;
+
+/// Circuit id of the range-claim circuit
+pub const RANGE_CLAIM_CIRCUIT: &str = "zkid-vc/range-claim/v2";
+
+/// Low 128 bits of a field element, for witnessing its bits
+#[cfg(feature = "prover")]
+fn low_bits(value: Fr) -> u128 {
+    let limbs = value.into_bigint().0;
+    limbs[0] as u128 | (limbs[1] as u128) << 64
+}
+
+// ============================================================================
+// ZK Circuit: Numeric Claim At Least a Threshold
+// ============================================================================
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+pub(crate) struct RangeClaimCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+    // Leaf value of the claim; any field element, so that a value past
+    // 2^64 can be tried against the bit constraints
+    value: Option<Fr>,
+
+    // 公开输入（锚点之后）
+    claims_root: Option<Fr>,
+    claim_key: Option<Fr>,
+    threshold: Option<Fr>,
+}
+
+#[cfg(feature = "prover")]
+impl RangeClaimCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            path: ClaimPath::blank(),
+            value: None,
+            claims_root: None,
+            claim_key: None,
+            threshold: None,
+        }
+    }
+
+    /// The circuit with its full witness for the claim at leaf `index` of
+    /// `tree` under the credential's anchor, whose leaf value is `value`;
+    /// None past the last claim
+    pub(crate) fn witness(
+        anchor: AnchorWitness,
+        tree: &ClaimsTree,
+        index: usize,
+        (key, value): (&str, Fr),
+        threshold: u64,
+    ) -> Option<Self> {
+        Some(Self {
+            anchor,
+            path: ClaimPath::of(tree, index)?,
+            value: Some(value),
+            claims_root: Some(tree.root()),
+            claim_key: Some(claim_key_field(key)),
+            threshold: Some(Fr::from(threshold)),
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for RangeClaimCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+
+        let input = |value: Option<Fr>| cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing));
+        let claims_root_var = input(self.claims_root)?;
+        let claim_key_var = input(self.claim_key)?;
+        let threshold_var = input(self.threshold)?;
+
+        let value_var = witness(&cs, self.value)?;
+        enforce_root_opening(&cs, claims_root_var, &opened)?;
+        enforce_membership(
+            &cs,
+            [(lc!() + claim_key_var, self.claim_key), (lc!() + value_var, self.value)],
+            &self.path,
+            claims_root_var,
+        )?;
+
+        // value < 2^64, then value - threshold in [0, 2^64)
+        enforce_bits(&cs, lc!() + value_var, self.value.map(low_bits), RANGE_CLAIM_BITS)?;
+        let distance = self.value.zip(self.threshold).map(|(v, t)| low_bits(v - t));
+        enforce_bits(&cs, lc!() + value_var - threshold_var, distance, RANGE_CLAIM_BITS)
+    }
+}
+
+/// Keys of the range-claim circuit; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct RangeClaimKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static RANGE_CLAIM_KEYS: Mutex<RangeClaimKeys> = Mutex::new(RangeClaimKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the range-claim circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(10u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(RangeClaimCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match RANGE_CLAIM_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the range-claim verifying key is present
+pub(crate) fn has_keys() -> bool {
+    RANGE_CLAIM_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the range-claim circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = RANGE_CLAIM_KEYS.lock() {
+        *keys = RangeClaimKeys::default();
+    }
+}
+
+#[cfg(feature = "verifier")]
+fn verify_range_claim(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claims_root: *const c_char,
+    claim_key: *const c_char,
+    threshold: u64,
+    nonce: u64,
+) -> c_int {
+    let text = |ptr: *const c_char| admission::text_arg(ptr, MAX_FIELD_LEN);
+    let issuer_bytes = match text(issuer_pubkey).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return e.code_or(0),
+        None => return 0,
+    };
+    let (root, key) = match (
+        text(claims_root).and_then(|s| hex_to_bytes(s).ok()).and_then(|bytes| field_from_bytes(&bytes)),
+        text(claim_key),
+    ) {
+        (Some(root), Some(key)) => (root, key),
+        _ => return 0,
+    };
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs at the time the proof carries, then root, key and
+    // threshold (circuit order); None unless the issuer signed the anchor
+    let mut public_inputs =
+        match validity::public_inputs(&issuer_bytes, format, nonce, header.current_time, &header) {
+            Some(inputs) => inputs.to_vec(),
+            None => return 0,
+        };
+    public_inputs.extend_from_slice(&[root, claim_key_field(key), Fr::from(threshold)]);
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Prove that the numeric claim `claim_key` of an issuer-signed credential
+/// is at least `threshold`, without revealing it
+///
+/// Runs the ZK_GenerateVCProofFromBlob pre-checks first. The value is the
+/// credential's own; a numeric claim is a canonical decimal u64 ("18", not
+/// "018" or "+18"). The claims root is written to `claims_root_out` and
+/// goes to the verifier with the proof. Returns 0 on success, the
+/// pre-check's code for a credential that would not verify,
+/// ZK_ERR_UNSUPPORTED_VERSION for a blob before version 6,
+/// ZK_ERR_INPUT_TOO_LARGE for more than MAX_COMMITTED_CLAIMS claims,
+/// ZK_ERR_NOT_INITIALIZED, ZK_ERR_PROVE_FAILED, or -1 if the credential has
+/// no numeric `claim_key` claim of at least `threshold`.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateRangeClaimProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    claim_key: *const c_char,
+    threshold: u64,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+    claims_root_out: *mut c_char,
+    claims_root_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() || claims_root_out.is_null() {
+        return -1;
+    }
+    let key = match admission::text_arg(claim_key, MAX_FIELD_LEN) {
+        Some(key) => key,
+        None => return -1,
+    };
+
+    let (vc, issuer_key, now) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+    if !vc.claims_root_signed {
+        return ZK_ERR_UNSUPPORTED_VERSION;
+    }
+    let tree = match ClaimsTree::new(&vc.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+    let claim = vc.claims.iter().enumerate().find_map(|(index, (k, v))| {
+        numeric_value(v).filter(|&value| k == key && value >= threshold).map(|value| (index, value))
+    });
+    let (index, value) = match claim {
+        Some(claim) => claim,
+        None => return -1,
+    };
+
+    let (signature, opening) = match (vc.anchored_signature(), vc.opening()) {
+        (Ok(signature), Some(opening)) => (signature, opening),
+        (Err(code), _) => return code,
+        (_, None) => return -1,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), now, Fr::from(nonce)).and_then(
+        |(anchor, header)| {
+            let circuit = RangeClaimCircuit::witness(anchor, &tree, index, (key, Fr::from(value)), threshold)?;
+            Some((circuit, header))
+        },
+    );
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match RANGE_CLAIM_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    if let Err(e) = write_cstr(proof_out, proof_out_size, &proof_hex) {
+        return e.code();
+    }
+    match write_cstr(claims_root_out, claims_root_out_size, &bytes_to_hex(&field_bytes(&tree.root()))) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify that the numeric claim `claim_key` under `claims_root` of a
+/// credential signed by `issuer_pubkey` is at least `threshold`
+///
+/// Returns 1 if the proof shows exactly that for `nonce`, 0 otherwise (also
+/// for another key, threshold or root), ZK_ERR_DISABLED for a legacy-format
+/// proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a format not
+/// accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyRangeClaimProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    claims_root: *const c_char,
+    claim_key: *const c_char,
+    threshold: u64,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = RANGE_CLAIM_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let arguments = [proof_hex, issuer_pubkey, claims_root, claim_key];
+    let result = match pvk {
+        Some(pvk) if arguments.iter().all(|ptr| !ptr.is_null()) => {
+            verify_range_claim(pvk, proof_hex, issuer_pubkey, claims_root, claim_key, threshold, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "range-claim-proof",
+        code: result,
+        circuit: Some(RANGE_CLAIM_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+crate::unwind::entry_point! {
+/// Export the range-claim verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportRangeClaimVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match RANGE_CLAIM_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportRangeClaimVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportRangeClaimVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == RANGE_CLAIM_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match RANGE_CLAIM_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
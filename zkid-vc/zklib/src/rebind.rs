@@ -0,0 +1,792 @@
+// ============================================================================
+// Holder Key Rotation (credentials re-bound to a new device key)
+// ============================================================================
+//
+// A holder-bound credential carries the `zkid:holder-key` claim, the hex
+// commitment to the holder's Ed25519 device key:
+//
+//   SHA-256("zkid:holder-key" | public key)
+//
+// When the device key is replaced, the credential is re-bound rather than
+// re-issued. The holder signs a rotation statement
+//
+//   "ZKRS" | version u8 | old key [32] | new key [32] | rotated_at u64
+//          | old key signature [64] | new key signature [64]
+//
+// with both keys, each over "zkid:holder-rotation" followed by the first
+// four fields, so neither key can be rotated without the other. The issuer
+// checks both signatures and that the old key is the one the credential is
+// currently bound to, and countersigns a re-binding certificate under the
+// ZK_KEY_USAGE_HOLDER_REBIND context:
+//
+//   "ZKRB" | version u8 | old commitment [32] | new commitment [32]
+//          | rotated_at u64 | issuer signature [64]
+//
+// Certificates chain: the first starts at the credential's claim, each
+// next one at the commitment the previous one ends at, with increasing
+// rotation times. A certificate carries commitments only, so one issued
+// for another credential bound to a different key does not continue the
+// chain.
+//
+// The holder-bound circuit opens the signed anchor (validity.rs) as the VC
+// circuit does, checks that the credential is active at the time the proof
+// carries, and opens the `zkid:holder-key` leaf under the anchor's claims
+// root (disclosure.rs), so the commitment the credential was issued to is
+// the issuer's. Its public inputs (in order) are issuer_pubkey_hash, nonce,
+// current_time, anchor, the claim's leaf value and the chain hash
+//
+//   SHA-256("zkid:rebind-chain" | certificate | ...)
+//
+// with length-prefixed certificates (the hash of no certificates for a
+// credential that was never re-bound). Proofs carry the VC header. The
+// prover checks natively that the credential verifies, that the chain
+// starts at its claim and that every certificate is the issuer's. The
+// verifier is given the holder's current public key and the chain, checks
+// the issuer's signature over the anchor, the certificates and their
+// continuity itself, and requires the chain to start at the commitment
+// opened in the proof (the key's own, for no certificates) and to end at
+// the key's; the chain hash ties the proof to the chain it was made with.
+
+use ark_bn254::Bn254;
+use ark_groth16::PreparedVerifyingKey;
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::admission::text_arg;
+use crate::ballot::hash;
+use crate::credential::parse_verifying_key;
+use crate::ffi::{read_slice, write_cstr};
+use crate::usage::KeyUsage;
+use crate::wire::Reader;
+use crate::{bytes_to_hex, hex_to_bytes, verify_ed25519, VerifiableCredential};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::disclosure::claim_value_field,
+    crate::proof,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::{checked_blob, parse_signing_key, parse_vc_blob},
+    crate::disclosure::{claim_key_field, enforce_membership, ClaimPath, ClaimsTree},
+    crate::error::{ZK_ERR_AUTH, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_NOT_INITIALIZED, ZK_ERR_UNSUPPORTED_VERSION},
+    crate::entropy,
+    crate::{enforce_active, prove_with_key, seeded_rng, Anchored, AnchorWitness},
+    ark_bn254::Fr,
+    ark_groth16::ProvingKey,
+    ark_relations::lc,
+    ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+    ed25519_dalek::{Signer, SigningKey},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission,
+    crate::audit,
+    crate::sizes::HOLDER_BOUND_PUBLIC_INPUTS,
+    crate::validity,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+const KEY_DOMAIN: &[u8] = b"zkid:holder-key";
+const ROTATION_CONTEXT: &[u8] = b"zkid:holder-rotation";
+const CHAIN_DOMAIN: &[u8] = b"zkid:rebind-chain";
+
+const STATEMENT_MAGIC: &[u8; 4] = b"ZKRS";
+const STATEMENT_VERSION: u8 = 1;
+const CERTIFICATE_MAGIC: &[u8; 4] = b"ZKRB";
+const CERTIFICATE_VERSION: u8 = 1;
+
+/// Encoded lengths of a rotation statement and a certificate
+#[cfg(feature = "prover")]
+const STATEMENT_LEN: usize = 4 + 1 + 32 + 32 + 8 + 64 + 64;
+const CERTIFICATE_LEN: usize = 4 + 1 + 32 + 32 + 8 + 64;
+
+/// Longest certificate chain accepted, i.e. rotations of one credential
+pub const MAX_REBIND_CHAIN: usize = 32;
+
+/// Reserved claim carrying the holder key commitment (hex)
+pub const HOLDER_KEY_CLAIM: &str = "zkid:holder-key";
+
+/// Circuit id of the holder-bound circuit
+pub const HOLDER_BOUND_CIRCUIT: &str = "zkid-vc/holder-bound/v2";
+
+/// Commitment to a holder device key, the value of HOLDER_KEY_CLAIM
+pub fn holder_key_commitment(key: &VerifyingKey) -> [u8; 32] {
+    hash(KEY_DOMAIN, &[key.as_bytes()])
+}
+
+/// The holder key commitment a credential is issued to
+pub fn bound_commitment(vc: &VerifiableCredential) -> Option<[u8; 32]> {
+    hex_to_bytes(vc.claim(HOLDER_KEY_CLAIM)?).ok()?.try_into().ok()
+}
+
+/// A holder's statement that `old_key` is replaced by `new_key`, signed by
+/// both
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotationStatement {
+    pub old_key: [u8; 32],
+    pub new_key: [u8; 32],
+    pub rotated_at: u64,
+    pub old_signature: [u8; 64],
+    pub new_signature: [u8; 64],
+}
+
+impl RotationStatement {
+    fn body(old_key: &[u8; 32], new_key: &[u8; 32], rotated_at: u64) -> Vec<u8> {
+        let mut out = STATEMENT_MAGIC.to_vec();
+        out.push(STATEMENT_VERSION);
+        out.extend_from_slice(old_key);
+        out.extend_from_slice(new_key);
+        out.extend_from_slice(&rotated_at.to_le_bytes());
+        out
+    }
+
+    fn signed_message(old_key: &[u8; 32], new_key: &[u8; 32], rotated_at: u64) -> Vec<u8> {
+        [ROTATION_CONTEXT, &Self::body(old_key, new_key, rotated_at)].concat()
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn sign(old: &SigningKey, new: &SigningKey, rotated_at: u64) -> Self {
+        let old_key = old.verifying_key().to_bytes();
+        let new_key = new.verifying_key().to_bytes();
+        let message = Self::signed_message(&old_key, &new_key, rotated_at);
+        Self {
+            old_key,
+            new_key,
+            rotated_at,
+            old_signature: old.sign(&message).to_bytes(),
+            new_signature: new.sign(&message).to_bytes(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Self::body(&self.old_key, &self.new_key, self.rotated_at);
+        out.extend_from_slice(&self.old_signature);
+        out.extend_from_slice(&self.new_signature);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != STATEMENT_MAGIC || r.u8()? != STATEMENT_VERSION {
+            return None;
+        }
+        let old_key = r.take(32)?.try_into().ok()?;
+        let new_key = r.take(32)?.try_into().ok()?;
+        let rotated_at = r.u64()?;
+        let old_signature = r.take(64)?.try_into().ok()?;
+        let new_signature = r.take(64)?.try_into().ok()?;
+        if !r.is_empty() {
+            return None;
+        }
+        Some(Self { old_key, new_key, rotated_at, old_signature, new_signature })
+    }
+
+    /// Both keys, if they are distinct and both signed the statement
+    pub fn verify(&self) -> Option<(VerifyingKey, VerifyingKey)> {
+        if self.old_key == self.new_key {
+            return None;
+        }
+        let old = VerifyingKey::from_bytes(&self.old_key).ok()?;
+        let new = VerifyingKey::from_bytes(&self.new_key).ok()?;
+        let message = Self::signed_message(&self.old_key, &self.new_key, self.rotated_at);
+        let signed = |key: &VerifyingKey, signature: &[u8; 64]| {
+            verify_ed25519(key, &message, &Signature::from_bytes(signature))
+        };
+        (signed(&old, &self.old_signature) && signed(&new, &self.new_signature)).then_some((old, new))
+    }
+}
+
+/// The issuer's countersignature moving a binding from one holder key
+/// commitment to the next
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebindCertificate {
+    pub old_commitment: [u8; 32],
+    pub new_commitment: [u8; 32],
+    pub rotated_at: u64,
+    pub signature: [u8; 64],
+}
+
+impl RebindCertificate {
+    fn body(old_commitment: &[u8; 32], new_commitment: &[u8; 32], rotated_at: u64) -> Vec<u8> {
+        let mut out = CERTIFICATE_MAGIC.to_vec();
+        out.push(CERTIFICATE_VERSION);
+        out.extend_from_slice(old_commitment);
+        out.extend_from_slice(new_commitment);
+        out.extend_from_slice(&rotated_at.to_le_bytes());
+        out
+    }
+
+    fn signed_message(old_commitment: &[u8; 32], new_commitment: &[u8; 32], rotated_at: u64) -> Vec<u8> {
+        KeyUsage::HolderRebind.signed_message(&Self::body(old_commitment, new_commitment, rotated_at))
+    }
+
+    /// Certify a verified rotation statement
+    #[cfg(feature = "prover")]
+    pub fn sign(issuer: &SigningKey, old: &VerifyingKey, new: &VerifyingKey, rotated_at: u64) -> Self {
+        let old_commitment = holder_key_commitment(old);
+        let new_commitment = holder_key_commitment(new);
+        let signature = issuer.sign(&Self::signed_message(&old_commitment, &new_commitment, rotated_at));
+        Self {
+            old_commitment,
+            new_commitment,
+            rotated_at,
+            signature: signature.to_bytes(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Self::body(&self.old_commitment, &self.new_commitment, self.rotated_at);
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != CERTIFICATE_MAGIC || r.u8()? != CERTIFICATE_VERSION {
+            return None;
+        }
+        let old_commitment = r.take(32)?.try_into().ok()?;
+        let new_commitment = r.take(32)?.try_into().ok()?;
+        let rotated_at = r.u64()?;
+        let signature = r.take(64)?.try_into().ok()?;
+        if !r.is_empty() {
+            return None;
+        }
+        Some(Self { old_commitment, new_commitment, rotated_at, signature })
+    }
+
+    pub fn verify(&self, issuer: &VerifyingKey) -> bool {
+        verify_ed25519(
+            issuer,
+            &Self::signed_message(&self.old_commitment, &self.new_commitment, self.rotated_at),
+            &Signature::from_bytes(&self.signature),
+        )
+    }
+}
+
+/// Hash of a certificate chain, the circuit's last public input
+pub fn chain_hash(chain: &[RebindCertificate]) -> [u8; 32] {
+    let certificates: Vec<Vec<u8>> = chain.iter().map(RebindCertificate::to_bytes).collect();
+    let parts: Vec<&[u8]> = certificates.iter().map(Vec::as_slice).collect();
+    hash(CHAIN_DOMAIN, &parts)
+}
+
+/// Follow `chain` from `start`: the commitment it ends at and the time of
+/// its last rotation
+///
+/// None unless every certificate is the issuer's, starts where the previous
+/// one ended (the first at `start`, if given) and is later than it.
+pub fn follow(
+    start: Option<&[u8; 32]>,
+    chain: &[RebindCertificate],
+    issuer: &VerifyingKey,
+) -> Option<(Option<[u8; 32]>, u64)> {
+    let mut current = start.copied();
+    let mut rotated_at = None;
+    for certificate in chain {
+        let continues = current.is_none_or(|current| current == certificate.old_commitment);
+        let later = rotated_at.is_none_or(|previous| certificate.rotated_at > previous);
+        if !continues || !later || !certificate.verify(issuer) {
+            return None;
+        }
+        current = Some(certificate.new_commitment);
+        rotated_at = Some(certificate.rotated_at);
+    }
+    Some((current, rotated_at.unwrap_or(0)))
+}
+
+/// Parse an array of hex certificates
+fn read_chain(certificates: *const *const c_char, count: usize) -> Option<Vec<RebindCertificate>> {
+    read_slice(certificates, count, MAX_REBIND_CHAIN)
+        .ok()?
+        .iter()
+        .map(|&certificate| {
+            let text = text_arg(certificate, 2 * CERTIFICATE_LEN)?;
+            RebindCertificate::from_bytes(&hex_to_bytes(text).ok()?)
+        })
+        .collect()
+}
+
+// ============================================================================
+// ZK Circuit: Credential Bound to the Current Holder Key
+// ============================================================================
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+struct HolderBoundCircuit {
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness）
+    anchor: AnchorWitness,
+
+    // 私密见证
+    path: ClaimPath,
+
+    // 公开输入（锚点之后）
+    holder_commitment: Option<Fr>,
+    chain_hash: Option<Fr>,
+}
+
+#[cfg(feature = "prover")]
+impl HolderBoundCircuit {
+    fn blank() -> Self {
+        Self {
+            anchor: AnchorWitness::blank(),
+            path: ClaimPath::blank(),
+            holder_commitment: None,
+            chain_hash: None,
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for HolderBoundCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let opened = self.anchor.open(&cs, None)?;
+        enforce_active(&cs, &opened)?;
+
+        let input = |value: Option<Fr>| cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing));
+        let holder_commitment_var = input(self.holder_commitment)?;
+        // The verifier checks the chain itself; its hash is only an input
+        input(self.chain_hash)?;
+
+        // The holder key claim is a leaf under the anchor's claims root
+        let claim_key = claim_key_field(HOLDER_KEY_CLAIM);
+        enforce_membership(
+            &cs,
+            [
+                (lc!() + (claim_key, Variable::One), Some(claim_key)),
+                (lc!() + holder_commitment_var, self.holder_commitment),
+            ],
+            &self.path,
+            opened.claims_root.0,
+        )
+    }
+}
+
+/// Keys of the holder-bound circuit; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct HolderBoundKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+static HOLDER_BOUND_KEYS: Mutex<HolderBoundKeys> = Mutex::new(HolderBoundKeys {
+    #[cfg(feature = "prover")]
+    pk: None,
+    pvk: None,
+});
+
+/// Set up the holder-bound circuit keys
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    let mut rng = seeded_rng(6u64);
+    let (pk, vk) = match Groth16::<Bn254>::circuit_specific_setup(HolderBoundCircuit::blank(), &mut rng) {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    match HOLDER_BOUND_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pk = Some(pk);
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Whether the holder-bound verifying key is present
+pub(crate) fn has_keys() -> bool {
+    HOLDER_BOUND_KEYS.lock().is_ok_and(|keys| keys.pvk.is_some())
+}
+
+/// Drop the holder-bound circuit keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = HOLDER_BOUND_KEYS.lock() {
+        *keys = HolderBoundKeys::default();
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Commitment to a holder device key (hex, 32 bytes)
+///
+/// The issuer signs it into the credential as the `zkid:holder-key` claim.
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small,
+/// -1 on a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_HolderKeyCommitment(
+    holder_public_key: *const c_char,
+    commitment_out: *mut c_char,
+    commitment_out_size: usize,
+) -> c_int {
+    let key = match parse_verifying_key(holder_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    match write_cstr(commitment_out, commitment_out_size, &bytes_to_hex(&holder_key_commitment(&key))) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Sign a rotation statement from the old device key to the new one (hex)
+///
+/// Both private keys are hex; each signs the statement. Returns 0 on
+/// success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small, -1 on
+/// malformed or equal keys.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_CreateRotationStatement(
+    old_private_key: *const c_char,
+    new_private_key: *const c_char,
+    rotated_at: u64,
+    statement_out: *mut c_char,
+    statement_out_size: usize,
+) -> c_int {
+    if old_private_key.is_null() || new_private_key.is_null() || statement_out.is_null() {
+        return -1;
+    }
+    let (old, new) = match (parse_signing_key(old_private_key), parse_signing_key(new_private_key)) {
+        (Some(old), Some(new)) if old.verifying_key() != new.verifying_key() => (old, new),
+        _ => return -1,
+    };
+
+    let statement = RotationStatement::sign(&old, &new, rotated_at);
+    match write_cstr(statement_out, statement_out_size, &bytes_to_hex(&statement.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Countersign a holder's rotation statement for one credential (hex
+/// certificate)
+///
+/// `vc_blob` must carry the issuer's signature and a `zkid:holder-key`
+/// claim; `certificates` are the `count` certificates it was re-bound with
+/// so far (hex, oldest first; count 0 before the first rotation). The
+/// statement's old key must be the one the credential is currently bound
+/// to, and the rotation later than the previous one. Returns 0 on success,
+/// ZK_ERR_AUTH if the statement lacks a valid signature of either key or
+/// the credential is not this issuer's, ZK_ERR_BUFFER_TOO_SMALL, -1 on
+/// malformed input, a broken chain or a statement for another key.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_IssueRebindCertificate(
+    issuer_private_key: *const c_char,
+    vc_blob: *const c_char,
+    certificates: *const *const c_char,
+    count: usize,
+    statement: *const c_char,
+    certificate_out: *mut c_char,
+    certificate_out_size: usize,
+) -> c_int {
+    if issuer_private_key.is_null() || vc_blob.is_null() || statement.is_null() || certificate_out.is_null() {
+        return -1;
+    }
+    let (issuer, vc) = match (parse_signing_key(issuer_private_key), parse_vc_blob(vc_blob)) {
+        (Some(issuer), Some(vc)) => (issuer, vc),
+        _ => return -1,
+    };
+    let statement = match text_arg(statement, 2 * STATEMENT_LEN)
+        .and_then(|text| hex_to_bytes(text).ok())
+        .and_then(|bytes| RotationStatement::from_bytes(&bytes))
+    {
+        Some(statement) => statement,
+        None => return -1,
+    };
+    let chain = match read_chain(certificates, count) {
+        Some(chain) => chain,
+        None => return -1,
+    };
+
+    let (old, new) = match statement.verify() {
+        Some(keys) => keys,
+        None => return ZK_ERR_AUTH,
+    };
+    let issuer_key = issuer.verifying_key();
+    if !vc.verify_signature(&issuer_key) {
+        return ZK_ERR_AUTH;
+    }
+    let (current, last_rotation) = match bound_commitment(&vc).and_then(|start| follow(Some(&start), &chain, &issuer_key)) {
+        Some((Some(current), last_rotation)) => (current, last_rotation),
+        _ => return -1,
+    };
+    if current != holder_key_commitment(&old) || (!chain.is_empty() && statement.rotated_at <= last_rotation) {
+        return -1;
+    }
+
+    let certificate = RebindCertificate::sign(&issuer, &old, &new, statement.rotated_at);
+    match write_cstr(certificate_out, certificate_out_size, &bytes_to_hex(&certificate.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Prove a holder-bound credential under its current holder key
+///
+/// Runs the same pre-checks as ZK_GenerateVCProofFromBlob (signature, dates
+/// at `current_time`, dual control), then requires a signed claims root, a
+/// `zkid:holder-key` claim as ZK_HolderKeyCommitment writes it and, for a
+/// re-bound credential, its `count` re-binding certificates (hex, oldest
+/// first) starting at that claim. With count 0 the proof is for the
+/// original holder key. Returns 0 on success, the pre-check's code for a
+/// credential that would not verify, ZK_ERR_UNSUPPORTED_VERSION for a blob
+/// without a signed claims root, ZK_ERR_INPUT_TOO_LARGE for too many
+/// claims, -1 on other failures, including a chain that is not this
+/// credential's.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateHolderBoundProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    certificates: *const *const c_char,
+    count: usize,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() {
+        return -1;
+    }
+    let chain = match read_chain(certificates, count) {
+        Some(chain) => chain,
+        None => return -1,
+    };
+    let (vc, issuer_key, now) = match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+        Ok(checked) => checked,
+        Err(code) => return code,
+    };
+    if !vc.claims_root_signed {
+        return ZK_ERR_UNSUPPORTED_VERSION;
+    }
+    // The claim must be the commitment as ZK_HolderKeyCommitment writes it,
+    // the leaf the verifier rebuilds from the chain
+    let (index, start) = match (vc.claims.iter().position(|(k, _)| k == HOLDER_KEY_CLAIM), bound_commitment(&vc)) {
+        (Some(index), Some(start)) if vc.claims[index].1 == bytes_to_hex(&start) => (index, start),
+        _ => return -1,
+    };
+    if !matches!(follow(Some(&start), &chain, &issuer_key), Some((Some(_), _))) {
+        return -1;
+    }
+    let tree = match ClaimsTree::new(&vc.claims) {
+        Some(tree) => tree,
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+    let (signature, opening) = match (vc.anchored_signature(), vc.opening()) {
+        (Ok(signature), Some(opening)) => (signature, opening),
+        (Err(code), _) => return code,
+        (_, None) => return -1,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let witness = AnchorWitness::of(format, &credential, issuer_key.as_bytes(), now, Fr::from(nonce)).and_then(
+        |(anchor, header)| {
+            let circuit = HolderBoundCircuit {
+                anchor,
+                path: ClaimPath::of(&tree, index)?,
+                holder_commitment: Some(claim_value_field(&vc.claims[index].1)),
+                chain_hash: Some(proof::field_for(format, &chain_hash(&chain))?),
+            };
+            Some((circuit, header))
+        },
+    );
+    let (circuit, header) = match witness {
+        Some(witness) => witness,
+        None => return -1,
+    };
+
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof_hex = {
+        let keys = match HOLDER_BOUND_KEYS.lock() {
+            Ok(keys) => keys,
+            Err(_) => return -1,
+        };
+        let pk = match keys.pk.as_ref() {
+            Some(pk) => pk,
+            None => return ZK_ERR_NOT_INITIALIZED,
+        };
+        match prove_with_key(pk, format, circuit, &header, &mut rng) {
+            Ok(proof_hex) => proof_hex,
+            Err(code) => return code,
+        }
+    };
+
+    match write_cstr(proof_out, proof_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a holder-bound proof for the holder's current public key
+///
+/// `holder_public_key` is the key the holder presents (hex);
+/// `certificates` are the `count` re-binding certificates the proof was
+/// made with (count 0 for the original key). Each must be signed by the
+/// issuer, start where the previous one ended and end, with the last, at
+/// the commitment to `holder_public_key`; the first must start at the
+/// commitment the proof opens from the credential's claim. The proof
+/// verifies at the time it carries. Returns 1 if the proof is valid, 0 if
+/// invalid (including a missing, extra or foreign certificate),
+/// ZK_ERR_DISABLED for a legacy-format proof in a strict build,
+/// ZK_ERR_UNSUPPORTED_VERSION for a format not accepted.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyHolderBoundProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    holder_public_key: *const c_char,
+    certificates: *const *const c_char,
+    count: usize,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = HOLDER_BOUND_KEYS.lock().ok();
+    let pvk = keys.as_ref().and_then(|keys| keys.pvk.as_ref());
+    let result = match pvk {
+        Some(pvk) if !proof_hex.is_null() && !issuer_pubkey.is_null() && !holder_public_key.is_null() => {
+            verify_holder_bound(pvk, proof_hex, issuer_pubkey, holder_public_key, certificates, count, nonce)
+        }
+        _ => 0,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "holder-bound-proof",
+        code: result,
+        circuit: Some(HOLDER_BOUND_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_holder_bound(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    holder_public_key: *const c_char,
+    certificates: *const *const c_char,
+    count: usize,
+    nonce: u64,
+) -> c_int {
+    let (issuer, holder, chain) = match (
+        parse_verifying_key(issuer_pubkey),
+        parse_verifying_key(holder_public_key),
+        read_chain(certificates, count),
+    ) {
+        (Some(issuer), Some(holder), Some(chain)) => (issuer, holder, chain),
+        _ => return 0,
+    };
+
+    // The chain starts at the commitment the proof opens and ends at the
+    // key's; without certificates both are the key's
+    let commitment = holder_key_commitment(&holder);
+    let start = chain.first().map_or(commitment, |certificate| certificate.old_commitment);
+    match follow(Some(&start), &chain, &issuer) {
+        Some((Some(end), _)) if end == commitment => {}
+        _ => return 0,
+    }
+
+    let (header, format, proof) = match admission::vc_proof_arg(proof_hex, proof::accepted_formats()) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // The VC inputs at the time the proof carries, then the claim's leaf
+    // value and the chain hash (circuit order); None unless the issuer
+    // signed the anchor
+    let public_inputs = match (
+        validity::public_inputs(issuer.as_bytes(), format, nonce, header.current_time, &header),
+        proof::field_for(format, &chain_hash(&chain)),
+    ) {
+        (Some([issuer_hash, nonce, current_time, anchor]), Some(chain)) => {
+            [issuer_hash, nonce, current_time, anchor, claim_value_field(&bytes_to_hex(&start)), chain]
+        }
+        _ => return 0,
+    };
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+crate::unwind::entry_point! {
+/// Export the holder-bound verifying key (hex, compressed)
+///
+/// Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too
+/// small, -1 if no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportHolderBoundVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match HOLDER_BOUND_KEYS.lock() {
+        Ok(keys) => match keys.pvk.as_ref() {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the key from ZK_ExportHolderBoundVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportHolderBoundVerifyingKey(vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == HOLDER_BOUND_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    match HOLDER_BOUND_KEYS.lock() {
+        Ok(mut keys) => {
+            keys.pvk = Some(PreparedVerifyingKey::from(vk));
+            0
+        }
+        Err(_) => -1,
+    }
+}
+}
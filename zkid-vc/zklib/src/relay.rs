@@ -0,0 +1,506 @@
+// ============================================================================
+// Store-and-Forward Relay Frames
+// ============================================================================
+//
+// Presentations sometimes travel through untrusted relays: email, QR codes
+// split across several frames, files carried offline. The relay format
+// wraps the presentation in an envelope
+//
+//   "ZKRE" | version u8 | presentation | tag [32] | signed u8
+//   | (holder_pubkey [32] | signature [64])?
+//
+// where tag = SHA-256("zkid:relay-tag" | presentation) and the optional
+// Ed25519 holder signature covers the tag. The envelope is cut into frames
+//
+//   "ZKRF" | version u8 | message_id [8] | index u32 | count u32 | payload
+//   | check [8]
+//
+// (message_id = the first 8 bytes of SHA-256("zkid:relay-message" |
+// envelope), check = the first 8 bytes of SHA-256 over everything before
+// it) and each frame is written as text, "ZKR64:" + base64url without
+// padding or "ZKR45:" + base45 (RFC 9285, QR alphanumeric mode). Integers
+// are little-endian. A frame never exceeds the caller's `max_frame`
+// characters, prefix included.
+//
+// Frames may arrive in any order. ZK_RelayDecode reports, in this order:
+//
+//   ZK_ERR_CORRUPT          a frame that does not decode or fails its check
+//                           (a flipped bit), or frames of different messages
+//   ZK_ERR_FRAME_DUPLICATE  the same index twice
+//   ZK_ERR_FRAME_MISSING    an index of 0..count absent
+//   ZK_ERR_AUTH             frames intact but the tag or holder signature
+//                           does not match, or a required signature absent
+//
+// The checks and the tag catch transport damage only; anyone can recompute
+// them. A relay that may tamper is only detected through the holder
+// signature, so verifiers that care pass the expected holder key.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SECRET_KEY_LENGTH};
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use crate::credential::parse_verifying_key;
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_FRAME_DUPLICATE, ZK_ERR_FRAME_MISSING};
+use crate::ffi::{read_bytes, read_slice, write_cstr, MAX_ARRAY_LEN, MAX_BLOB_LEN};
+use crate::vk::write_out;
+use crate::wire::{put_bytes, Reader};
+use crate::{hex_to_bytes, verify_ed25519};
+#[cfg(feature = "verifier")]
+use {
+    crate::presentation::{verify_and_record, Presentation},
+    std::time::Instant,
+};
+
+/// Frame text: "ZKR64:" + base64url, no padding
+pub const ZK_RELAY_BASE64URL: c_int = 0;
+/// Frame text: "ZKR45:" + base45, for QR alphanumeric mode
+pub const ZK_RELAY_BASE45: c_int = 1;
+
+/// Most frames one presentation may be split into
+pub const MAX_RELAY_FRAMES: usize = MAX_ARRAY_LEN;
+
+const ENVELOPE_MAGIC: &[u8; 4] = b"ZKRE";
+const FRAME_MAGIC: &[u8; 4] = b"ZKRF";
+const RELAY_VERSION: u8 = 1;
+
+const TAG_DOMAIN: &[u8] = b"zkid:relay-tag";
+const MESSAGE_DOMAIN: &[u8] = b"zkid:relay-message";
+
+const BASE64URL_PREFIX: &str = "ZKR64:";
+const BASE45_PREFIX: &str = "ZKR45:";
+
+const MESSAGE_ID_LEN: usize = 8;
+const CHECK_LEN: usize = 8;
+// magic | version | message_id | index | count
+const FRAME_HEADER_LEN: usize = FRAME_MAGIC.len() + 1 + MESSAGE_ID_LEN + 4 + 4;
+
+fn tag(presentation: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(TAG_DOMAIN);
+    hasher.update(presentation);
+    hasher.finalize().into()
+}
+
+fn frame_check(frame: &[u8]) -> [u8; CHECK_LEN] {
+    Sha256::digest(frame)[..CHECK_LEN].try_into().expect("digest is 32 bytes")
+}
+
+/// Wrap a presentation in an envelope, signed by the holder if given
+pub fn seal(presentation: &[u8], holder: Option<&SigningKey>) -> Vec<u8> {
+    let tag = tag(presentation);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(ENVELOPE_MAGIC);
+    out.push(RELAY_VERSION);
+    put_bytes(&mut out, presentation);
+    out.extend_from_slice(&tag);
+    match holder {
+        Some(key) => {
+            out.push(1);
+            out.extend_from_slice(key.verifying_key().as_bytes());
+            out.extend_from_slice(&key.sign(&tag).to_bytes());
+        }
+        None => out.push(0),
+    }
+    out
+}
+
+/// Unwrap an envelope, checking its tag and signature
+///
+/// With `expected_holder` the envelope must be signed by that key; without
+/// it a signature present is still checked against its embedded key.
+pub fn open(envelope: &[u8], expected_holder: Option<&VerifyingKey>) -> Result<Vec<u8>, c_int> {
+    let mut r = Reader::new(envelope);
+    let header_ok = r.take(ENVELOPE_MAGIC.len()) == Some(ENVELOPE_MAGIC.as_slice())
+        && r.u8() == Some(RELAY_VERSION);
+    if !header_ok {
+        return Err(ZK_ERR_CORRUPT);
+    }
+    let presentation = r.bytes().ok_or(ZK_ERR_CORRUPT)?;
+    let claimed_tag = r.take(32).ok_or(ZK_ERR_CORRUPT)?;
+    let signer = match r.u8() {
+        Some(0) => None,
+        Some(1) => {
+            let key = r.take(32).ok_or(ZK_ERR_CORRUPT)?;
+            let signature = r.take(64).ok_or(ZK_ERR_CORRUPT)?;
+            Some((key, signature))
+        }
+        _ => return Err(ZK_ERR_CORRUPT),
+    };
+    if !r.is_empty() {
+        return Err(ZK_ERR_CORRUPT);
+    }
+
+    let tag = tag(presentation);
+    if claimed_tag != tag {
+        return Err(ZK_ERR_AUTH);
+    }
+
+    match (signer, expected_holder) {
+        (None, None) => {}
+        (None, Some(_)) => return Err(ZK_ERR_AUTH),
+        (Some((key, signature)), expected) => {
+            let key = VerifyingKey::from_bytes(key.try_into().expect("took 32 bytes"))
+                .map_err(|_| ZK_ERR_AUTH)?;
+            let signature = Signature::from_slice(signature).map_err(|_| ZK_ERR_AUTH)?;
+            if expected.is_some_and(|expected| *expected != key)
+                || !verify_ed25519(&key, &tag, &signature)
+            {
+                return Err(ZK_ERR_AUTH);
+            }
+        }
+    }
+    Ok(presentation.to_vec())
+}
+
+// ============================================================================
+// Frame Text Encodings
+// ============================================================================
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+pub(crate) fn base64url_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(BASE64URL_ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+        }
+    }
+    out
+}
+
+pub(crate) fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let digit = BASE64URL_ALPHABET.iter().position(|&a| a == c)? as u32;
+            n |= digit << (18 - 6 * i);
+        }
+        let bytes = n.to_be_bytes();
+        let len = chunk.len() - 1;
+        // Unused low bits of a short group must be zero (one encoding only)
+        if bytes[1 + len..].iter().any(|&b| b != 0) {
+            return None;
+        }
+        out.extend_from_slice(&bytes[1..1 + len]);
+    }
+    Some(out)
+}
+
+pub(crate) fn base45_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(2) * 3);
+    for chunk in data.chunks(2) {
+        let (mut n, digits) = match *chunk {
+            [a, b] => ((a as usize) << 8 | b as usize, 3),
+            [a] => (a as usize, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[n % 45] as char);
+            n /= 45;
+        }
+    }
+    out
+}
+
+pub(crate) fn base45_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 3 * 2 + 1);
+    for chunk in text.as_bytes().chunks(3) {
+        let mut n = 0usize;
+        for &c in chunk.iter().rev() {
+            n = n * 45 + BASE45_ALPHABET.iter().position(|&a| a == c)?;
+        }
+        match chunk.len() {
+            3 if n <= 0xffff => out.extend_from_slice(&(n as u16).to_be_bytes()),
+            2 if n <= 0xff => out.push(n as u8),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Raw frame bytes that fit `max_frame` characters of frame text
+fn frame_capacity(encoding: c_int, max_frame: usize) -> Option<usize> {
+    match encoding {
+        ZK_RELAY_BASE64URL => Some(max_frame.checked_sub(BASE64URL_PREFIX.len())? * 3 / 4),
+        ZK_RELAY_BASE45 => {
+            let chars = max_frame.checked_sub(BASE45_PREFIX.len())?;
+            Some(chars / 3 * 2 + usize::from(chars % 3 == 2))
+        }
+        _ => None,
+    }
+}
+
+/// Split an envelope into frame texts of at most `max_frame` characters
+pub fn split(envelope: &[u8], encoding: c_int, max_frame: usize) -> Option<Vec<String>> {
+    let payload_len = frame_capacity(encoding, max_frame)?
+        .checked_sub(FRAME_HEADER_LEN + CHECK_LEN)
+        .filter(|&len| len > 0)?;
+    let count = envelope.len().div_ceil(payload_len).max(1);
+    if count > MAX_RELAY_FRAMES {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(MESSAGE_DOMAIN);
+    hasher.update(envelope);
+    let message_id = &hasher.finalize()[..MESSAGE_ID_LEN];
+
+    let frames = (0..count)
+        .map(|index| {
+            let payload = envelope.chunks(payload_len).nth(index).unwrap_or_default();
+            let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len() + CHECK_LEN);
+            frame.extend_from_slice(FRAME_MAGIC);
+            frame.push(RELAY_VERSION);
+            frame.extend_from_slice(message_id);
+            frame.extend_from_slice(&(index as u32).to_le_bytes());
+            frame.extend_from_slice(&(count as u32).to_le_bytes());
+            frame.extend_from_slice(payload);
+            let check = frame_check(&frame);
+            frame.extend_from_slice(&check);
+
+            if encoding == ZK_RELAY_BASE45 {
+                format!("{BASE45_PREFIX}{}", base45_encode(&frame))
+            } else {
+                format!("{BASE64URL_PREFIX}{}", base64url_encode(&frame))
+            }
+        })
+        .collect();
+    Some(frames)
+}
+
+struct Frame<'a> {
+    message_id: &'a [u8],
+    index: usize,
+    count: usize,
+    payload: &'a [u8],
+}
+
+fn parse_frame(raw: &[u8]) -> Option<Frame<'_>> {
+    let body_len = raw.len().checked_sub(CHECK_LEN)?;
+    let (body, check) = raw.split_at(body_len);
+    if check != frame_check(body) {
+        return None;
+    }
+
+    let mut r = Reader::new(body);
+    if r.take(FRAME_MAGIC.len())? != FRAME_MAGIC || r.u8()? != RELAY_VERSION {
+        return None;
+    }
+    let message_id = r.take(MESSAGE_ID_LEN)?;
+    let index = r.u32()? as usize;
+    let count = r.u32()? as usize;
+    if count == 0 || count > MAX_RELAY_FRAMES || index >= count {
+        return None;
+    }
+    Some(Frame {
+        message_id,
+        index,
+        count,
+        payload: &body[FRAME_HEADER_LEN..],
+    })
+}
+
+/// Reassemble an envelope from frame texts in any order
+pub fn join<S: AsRef<str>>(frames: &[S]) -> Result<Vec<u8>, c_int> {
+    let raw = frames
+        .iter()
+        .map(|text| {
+            let text = text.as_ref().trim();
+            if let Some(body) = text.strip_prefix(BASE64URL_PREFIX) {
+                base64url_decode(body)
+            } else if let Some(body) = text.strip_prefix(BASE45_PREFIX) {
+                base45_decode(body)
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(ZK_ERR_CORRUPT)?;
+    let parsed = raw
+        .iter()
+        .map(|raw| parse_frame(raw))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(ZK_ERR_CORRUPT)?;
+
+    let first = parsed.first().ok_or(ZK_ERR_FRAME_MISSING)?;
+    if parsed
+        .iter()
+        .any(|frame| frame.message_id != first.message_id || frame.count != first.count)
+    {
+        return Err(ZK_ERR_CORRUPT);
+    }
+
+    let mut slots: Vec<Option<&[u8]>> = vec![None; first.count];
+    for frame in &parsed {
+        if slots[frame.index].replace(frame.payload).is_some() {
+            return Err(ZK_ERR_FRAME_DUPLICATE);
+        }
+    }
+    slots
+        .into_iter()
+        .try_fold(Vec::new(), |mut envelope, payload| {
+            envelope.extend_from_slice(payload?);
+            Some(envelope)
+        })
+        .ok_or(ZK_ERR_FRAME_MISSING)
+}
+
+fn read_frames(frames: *const *const c_char, frame_count: usize) -> Result<Vec<&'static str>, c_int> {
+    let frames = read_slice(frames, frame_count, MAX_RELAY_FRAMES).map_err(|e| e.code())?;
+    frames
+        .iter()
+        .map(|&frame| {
+            if frame.is_null() {
+                return Err(-1);
+            }
+            unsafe { CStr::from_ptr(frame) }.to_str().map_err(|_| ZK_ERR_CORRUPT)
+        })
+        .collect()
+}
+
+fn expected_holder(holder_public_key: *const c_char) -> Result<Option<VerifyingKey>, c_int> {
+    if holder_public_key.is_null() {
+        return Ok(None);
+    }
+    parse_verifying_key(holder_public_key).map(Some).ok_or(-1)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Wrap a presentation for relaying and split it into frames
+///
+/// `presentation_blob` is any presentation encoding (binary or hex text) and
+/// is relayed byte for byte. `holder_private_key` (hex Ed25519 secret, may
+/// be NULL) signs the envelope. Frames are written to `frames_out` as one
+/// NUL-terminated string, separated by '\n', each at most `max_frame`
+/// characters in `encoding` (ZK_RELAY_*); `frame_count_out` receives their
+/// number. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, or -1 on bad
+/// input, an unknown encoding, or a `max_frame` too small for one payload
+/// byte or needing more than MAX_RELAY_FRAMES frames.
+#[no_mangle]
+pub extern "C" fn ZK_RelayEncode(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    holder_private_key: *const c_char,
+    encoding: c_int,
+    max_frame: usize,
+    frames_out: *mut c_char,
+    frames_out_size: usize,
+    frame_count_out: *mut usize,
+) -> c_int {
+    if presentation_blob.is_null() || frame_count_out.is_null() {
+        return -1;
+    }
+    let presentation = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) if !bytes.is_empty() => bytes,
+        Ok(_) => return -1,
+        Err(e) => return e.code(),
+    };
+
+    let holder = if holder_private_key.is_null() {
+        None
+    } else {
+        let text = unsafe { CStr::from_ptr(holder_private_key) }.to_str().unwrap_or("");
+        let bytes = hex_to_bytes(text).unwrap_or_default();
+        match <[u8; SECRET_KEY_LENGTH]>::try_from(bytes.as_slice()) {
+            Ok(secret) => Some(SigningKey::from_bytes(&secret)),
+            Err(_) => return -1,
+        }
+    };
+
+    let frames = match split(&seal(presentation, holder.as_ref()), encoding, max_frame) {
+        Some(frames) => frames,
+        None => return -1,
+    };
+    match write_cstr(frames_out, frames_out_size, &frames.join("\n")) {
+        Ok(_) => {
+            unsafe { *frame_count_out = frames.len() };
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Reassemble relayed frames into the presentation
+///
+/// `frames` holds `frame_count` NUL-terminated frame texts in any order,
+/// in either encoding. With `holder_public_key` (hex, may be NULL) the
+/// envelope must carry that holder's signature. The presentation bytes go to
+/// `presentation_out`, their length to `presentation_len_out` (also when the
+/// buffer is too small). Returns 0 on success, ZK_ERR_CORRUPT,
+/// ZK_ERR_FRAME_DUPLICATE, ZK_ERR_FRAME_MISSING or ZK_ERR_AUTH as described
+/// above, ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL or a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_RelayDecode(
+    frames: *const *const c_char,
+    frame_count: usize,
+    holder_public_key: *const c_char,
+    presentation_out: *mut u8,
+    presentation_out_size: usize,
+    presentation_len_out: *mut usize,
+) -> c_int {
+    let frames = match read_frames(frames, frame_count) {
+        Ok(frames) => frames,
+        Err(code) => return code,
+    };
+    let expected = match expected_holder(holder_public_key) {
+        Ok(expected) => expected,
+        Err(code) => return code,
+    };
+
+    match join(&frames).and_then(|envelope| open(&envelope, expected.as_ref())) {
+        Ok(presentation) => write_out(
+            &presentation,
+            presentation_out,
+            presentation_out_size,
+            presentation_len_out,
+        ),
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Reassemble relayed frames and verify the presentation they carry
+///
+/// Frames and `holder_public_key` as for ZK_RelayDecode; the presentation is
+/// then checked as by ZK_VerifyPresentation. Returns 1 if valid, 0 if
+/// invalid, ZK_ERR_UNSUPPORTED_VERSION, or a ZK_RelayDecode error when the
+/// frames do not reassemble.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_RelayVerifyPresentation(
+    frames: *const *const c_char,
+    frame_count: usize,
+    holder_public_key: *const c_char,
+) -> c_int {
+    let started = Instant::now();
+    let frames = match read_frames(frames, frame_count) {
+        Ok(frames) => frames,
+        Err(code) => return code,
+    };
+    let expected = match expected_holder(holder_public_key) {
+        Ok(expected) => expected,
+        Err(code) => return code,
+    };
+
+    match join(&frames).and_then(|envelope| open(&envelope, expected.as_ref())) {
+        Ok(presentation) => verify_and_record(started, Presentation::from_wire(&presentation)),
+        Err(code) => code,
+    }
+}
+}
@@ -0,0 +1,315 @@
+// ============================================================================
+// Prover-Side Nonce Reuse Detection
+// ============================================================================
+//
+// A buggy verifier may send the same challenge twice. Proving it again
+// wastes a proof and, under ZK_SetDeterministicProving (randomness derived
+// from the seed and the nonce), yields the identical proof, linking the two
+// sessions. The
+// provers keep a small cache of recently proven (audience, nonce) pairs and
+// apply a policy when a pair repeats within the TTL:
+//
+//   ZK_NONCE_REUSE_WARN         prove again and count the reuse (default)
+//   ZK_NONCE_REUSE_RERANDOMIZE  return the earlier proof rerandomized, so the
+//                               two proofs are unlinkable; a different
+//                               statement under the same pair is proved anew
+//   ZK_NONCE_REUSE_REFUSE       refuse with ZK_ERR_NONCE_REUSED
+//
+// ZK_GenerateVCProof, ZK_GenerateVCProofFromBlob and
+// ZK_GenerateVCProofWithProfile take no audience, so their pairs have an
+// empty audience: a nonce repeated towards any verifier counts as reused.
+//
+// Entries hold salted SHA-256 hashes of the pair and of the statement (VC
+// hash and issuer key), never the credential itself; the salt is random per
+// process. Under ZK_NONCE_REUSE_RERANDOMIZE they also hold the proof, which
+// the verifier has already seen. The oldest entry is dropped when the cache
+// is full; a capacity of 0 turns detection off.
+
+use ark_bn254::Bn254;
+use ark_groth16::Groth16;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::ZK_ERR_NONCE_REUSED;
+use crate::{bytes_to_hex, hex_to_bytes, validity, PROVING_KEY};
+
+pub const ZK_NONCE_REUSE_WARN: c_int = 0;
+pub const ZK_NONCE_REUSE_RERANDOMIZE: c_int = 1;
+pub const ZK_NONCE_REUSE_REFUSE: c_int = 2;
+
+/// Recent-challenge cache settings (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkNonceCacheConfig {
+    /// Most pairs remembered, 0 to disable detection
+    pub capacity: u32,
+    /// Seconds a pair stays recent
+    pub ttl_secs: u64,
+    /// ZK_NONCE_REUSE_*
+    pub policy: c_int,
+}
+
+impl ZkNonceCacheConfig {
+    const DEFAULT: Self = Self {
+        capacity: 64,
+        ttl_secs: 600,
+        policy: ZK_NONCE_REUSE_WARN,
+    };
+}
+
+/// Replay metrics since start (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkNonceReuseStats {
+    /// Proofs requested through a guarded entry point
+    pub proofs: u64,
+    /// Requests whose pair was recent, under any policy
+    pub reused: u64,
+    /// Reuses answered with a rerandomized proof
+    pub rerandomized: u64,
+    /// Reuses refused with ZK_ERR_NONCE_REUSED
+    pub refused: u64,
+}
+
+struct Entry {
+    pair: [u8; 32],
+    statement: [u8; 32],
+    proof: Option<String>,
+    at: Instant,
+}
+
+struct Cache {
+    config: ZkNonceCacheConfig,
+    salt: [u8; 32],
+    entries: VecDeque<Entry>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+static PROOFS: AtomicU64 = AtomicU64::new(0);
+static REUSED: AtomicU64 = AtomicU64::new(0);
+static RERANDOMIZED: AtomicU64 = AtomicU64::new(0);
+static REFUSED: AtomicU64 = AtomicU64::new(0);
+
+impl Cache {
+    fn new(config: ZkNonceCacheConfig) -> Self {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            config,
+            salt,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn hash(&self, domain: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(domain);
+        hasher.update(self.salt);
+        for part in parts {
+            hasher.update((part.len() as u32).to_le_bytes());
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+
+    fn bytes(&self) -> usize {
+        let proofs: usize = self.entries.iter().filter_map(|entry| entry.proof.as_ref()).map(String::capacity).sum();
+        self.entries.capacity() * std::mem::size_of::<Entry>() + proofs
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        self.entries.retain(|entry| now.duration_since(entry.at) < ttl);
+    }
+}
+
+fn lock() -> crate::sync::MutexGuard<'static, Option<Cache>> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Rerandomize a hex VC proof under the global key, keeping its format
+fn rerandomize(proof_hex: &str) -> Option<String> {
+    let (header, format, proof) = validity::decode(&hex_to_bytes(proof_hex).ok()?).ok()?;
+    let pk_guard = PROVING_KEY.lock().ok()?;
+    let vk = &pk_guard.as_ref()?.vk;
+    let fresh = Groth16::<Bn254>::rerandomize_proof(vk, &proof, &mut OsRng);
+    Some(bytes_to_hex(&validity::encode(&header, format, &fresh)))
+}
+
+/// Run `prove` for `statement` unless the cache policy answers a recent
+/// (audience, nonce) pair otherwise
+///
+/// The pair is reserved before proving, so concurrent requests for it see
+/// each other; a failed proof releases it again.
+pub(crate) fn guarded(
+    audience: &[u8],
+    nonce: u64,
+    statement: &[&[u8]],
+    prove: impl FnOnce() -> Result<String, c_int>,
+) -> Result<String, c_int> {
+    PROOFS.fetch_add(1, Ordering::Relaxed);
+    let now = Instant::now();
+
+    let (pair, statement) = {
+        let mut guard = lock();
+        let cache = guard.get_or_insert_with(|| Cache::new(ZkNonceCacheConfig::DEFAULT));
+        if cache.config.capacity == 0 {
+            drop(guard);
+            return prove();
+        }
+        cache.expire(now);
+
+        let pair = cache.hash(b"zkid:nonce-pair", &[audience, &nonce.to_le_bytes()]);
+        let statement = cache.hash(b"zkid:nonce-statement", statement);
+        let policy = cache.config.policy;
+
+        if let Some(entry) = cache.entries.iter().find(|entry| entry.pair == pair) {
+            REUSED.fetch_add(1, Ordering::Relaxed);
+            match policy {
+                ZK_NONCE_REUSE_REFUSE => {
+                    REFUSED.fetch_add(1, Ordering::Relaxed);
+                    return Err(ZK_ERR_NONCE_REUSED);
+                }
+                ZK_NONCE_REUSE_RERANDOMIZE if entry.statement == statement => {
+                    if let Some(proof) = entry.proof.clone() {
+                        drop(guard);
+                        if let Some(fresh) = rerandomize(&proof) {
+                            RERANDOMIZED.fetch_add(1, Ordering::Relaxed);
+                            return Ok(fresh);
+                        }
+                        return prove();
+                    }
+                }
+                _ => {}
+            }
+            drop(guard);
+            return prove();
+        }
+
+        while cache.entries.len() >= cache.config.capacity as usize {
+            cache.entries.pop_front();
+        }
+        cache.entries.push_back(Entry {
+            pair,
+            statement,
+            proof: None,
+            at: now,
+        });
+        (pair, statement)
+    };
+
+    let result = prove();
+
+    let mut guard = lock();
+    if let Some(cache) = guard.as_mut() {
+        let position = cache
+            .entries
+            .iter()
+            .position(|entry| entry.pair == pair && entry.statement == statement);
+        match (&result, position) {
+            (Ok(proof), Some(i)) if cache.config.policy == ZK_NONCE_REUSE_RERANDOMIZE => {
+                cache.entries[i].proof = Some(proof.clone());
+            }
+            (Err(_), Some(i)) => {
+                cache.entries.remove(i);
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Entries and approximate bytes held by the cache
+pub(crate) fn held() -> (usize, usize) {
+    match lock().as_ref() {
+        Some(cache) => (cache.entries.len(), cache.bytes()),
+        None => (0, 0),
+    }
+}
+
+/// Drop expired pairs and those beyond the capacity, oldest first, and
+/// release the spare room; returns the pairs dropped and the bytes freed
+///
+/// A pair reserved by a prove in flight is younger than the TTL and within
+/// the capacity unless the capacity was lowered, in which case the prove
+/// still completes and only its reuse detection is lost.
+pub(crate) fn sweep() -> (usize, usize) {
+    let mut guard = lock();
+    let cache = match guard.as_mut() {
+        Some(cache) => cache,
+        None => return (0, 0),
+    };
+    let (before, bytes_before) = (cache.entries.len(), cache.bytes());
+    cache.expire(Instant::now());
+    while cache.entries.len() > cache.config.capacity as usize {
+        cache.entries.pop_front();
+    }
+    cache.entries.shrink_to_fit();
+    (before - cache.entries.len(), bytes_before - cache.bytes())
+}
+
+/// Forget every recent pair (ZK_Cleanup)
+pub fn clear() {
+    if let Some(cache) = lock().as_mut() {
+        cache.entries.clear();
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Configure the prover's recent-challenge cache
+///
+/// Applies to proofs generated afterwards; remembered pairs are kept (and
+/// trimmed on the next proof). Returns 0 on success, -1 on NULL or an
+/// unknown policy.
+#[no_mangle]
+pub extern "C" fn ZK_SetNonceReusePolicy(config: *const ZkNonceCacheConfig) -> c_int {
+    let config = match unsafe { config.as_ref() } {
+        Some(config) => *config,
+        None => return -1,
+    };
+    if !matches!(
+        config.policy,
+        ZK_NONCE_REUSE_WARN | ZK_NONCE_REUSE_RERANDOMIZE | ZK_NONCE_REUSE_REFUSE
+    ) {
+        return -1;
+    }
+
+    let mut guard = lock();
+    match guard.as_mut() {
+        Some(cache) => cache.config = config,
+        None => *guard = Some(Cache::new(config)),
+    }
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Replay metrics since start (see ZkNonceReuseStats)
+///
+/// Returns 0 on success, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_NonceReuseStats(stats_out: *mut ZkNonceReuseStats) -> c_int {
+    let out = match unsafe { stats_out.as_mut() } {
+        Some(out) => out,
+        None => return -1,
+    };
+
+    *out = ZkNonceReuseStats {
+        proofs: PROOFS.load(Ordering::Relaxed),
+        reused: REUSED.load(Ordering::Relaxed),
+        rerandomized: RERANDOMIZED.load(Ordering::Relaxed),
+        refused: REFUSED.load(Ordering::Relaxed),
+    };
+    0
+}
+}
@@ -0,0 +1,657 @@
+// ============================================================================
+// Revocation Registry
+// ============================================================================
+//
+// Issuer-side list of revoked credential identifiers. A lookup that fails in
+// the storage backend is reported as an error, never as "not revoked".
+//
+// Re-issued credentials are revoked with a pointer to their successor, so a
+// chain A -> B -> C resolves to C. Issuers hand these out as revocation
+// entries (hex):
+//
+//   "ZKRE" | version u8 | credential_id | revoked_at u64
+//   | has_successor u8 | [successor_credential_id]
+//
+// Verifiers that do not share the issuer's storage load the issuer's whole
+// list instead, signed under the status key usage (usage.rs):
+//
+//   "ZKRL" | version u8 | issuer_public_key [32] | epoch u64 | count u32
+//   | entries (each len-prefixed "ZKRE")
+//
+// The verifier names the issuer it trusts for the list: the list must carry
+// that key and its attestation must verify under it, so a list that merely
+// verifies under the key it carries is refused. Entries are keyed by
+// (issuer, credential_id) and only apply to credentials of that issuer.
+//
+// ZK_UpdateRevocationData swaps a newer epoch in atomically. A verification
+// loads the published list once and checks against that snapshot to the end,
+// so one running during an update sees the old epoch or the new one, never a
+// mix; its detailed report records which (revocation_epoch, 0 if the list is
+// another issuer's).
+
+use arc_swap::ArcSwapOption;
+use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::admission::text_arg;
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_EXPIRED, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_KEY_MISMATCH, ZK_ERR_STORAGE};
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN, MAX_FIELD_LEN};
+use crate::limits::{self, ZK_COLLECTION_REVOCATIONS};
+use crate::storage::{storage, Storage, StorageEntries, StorageError};
+use crate::usage::KeyUsage;
+use crate::wire::{put_bytes, Reader};
+use crate::{hex_to_bytes, verify_ed25519};
+
+const REVOCATION_PREFIX: &[u8] = b"revocation/";
+const ENTRY_MAGIC: &[u8; 4] = b"ZKRE";
+const ENTRY_VERSION: u8 = 1;
+const LIST_MAGIC: &[u8; 4] = b"ZKRL";
+const LIST_VERSION: u8 = 1;
+
+// Bound on chain length when resolving, guards against cycles in storage
+const MAX_SUPERSESSION_CHAIN: usize = 64;
+
+// Serializes the count + insert of a limited registry
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+// Verifier policy: treat superseded credentials as invalid
+static REJECT_SUPERSEDED: AtomicBool = AtomicBool::new(false);
+
+pub fn reject_superseded() -> bool {
+    REJECT_SUPERSEDED.load(Ordering::Relaxed)
+}
+
+/// A revocation as published by the issuer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevocationEntry {
+    pub credential_id: String,
+    pub revoked_at: u64,
+    pub superseded_by: Option<String>,
+}
+
+impl RevocationEntry {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = ENTRY_MAGIC.to_vec();
+        out.push(ENTRY_VERSION);
+        put_bytes(&mut out, self.credential_id.as_bytes());
+        out.extend_from_slice(&self.revoked_at.to_le_bytes());
+        match &self.superseded_by {
+            Some(successor) => {
+                out.push(1);
+                put_bytes(&mut out, successor.as_bytes());
+            }
+            None => out.push(0),
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != ENTRY_MAGIC || r.u8()? != ENTRY_VERSION {
+            return None;
+        }
+
+        let credential_id = r.string()?;
+        let revoked_at = r.u64()?;
+        let superseded_by = match r.u8()? {
+            0 => None,
+            1 => Some(r.string()?),
+            _ => return None,
+        };
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            credential_id,
+            revoked_at,
+            superseded_by,
+        })
+    }
+}
+
+/// Revoked credentials persisted through the storage backend
+pub struct RevocationRegistry {
+    storage: Arc<dyn Storage>,
+}
+
+impl RevocationRegistry {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    fn key(credential_id: &[u8]) -> Vec<u8> {
+        let mut key = REVOCATION_PREFIX.to_vec();
+        key.extend_from_slice(credential_id);
+        key
+    }
+
+    pub fn count(&self) -> Result<usize, StorageError> {
+        Ok(self.storage.scan_prefix(REVOCATION_PREFIX)?.len())
+    }
+
+    /// Store a revocation; a new one is refused once the registry is at its
+    /// capacity (revocations are never evicted)
+    fn put(&self, credential_id: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let key = Self::key(credential_id);
+        if limits::is_limited(ZK_COLLECTION_REVOCATIONS) && self.storage.get(&key)?.is_none() {
+            limits::admit(ZK_COLLECTION_REVOCATIONS, self.count()?)?;
+        }
+        self.storage.put(&key, value)
+    }
+
+    /// Mark a credential as revoked at `revoked_at`
+    pub fn revoke(&self, credential_id: &[u8], revoked_at: u64) -> Result<(), StorageError> {
+        self.put(credential_id, &revoked_at.to_le_bytes())
+    }
+
+    /// Every stored revocation as (credential id, stored value)
+    pub fn entries(&self) -> Result<StorageEntries, StorageError> {
+        Ok(self
+            .storage
+            .scan_prefix(REVOCATION_PREFIX)?
+            .into_iter()
+            .map(|(key, value)| (key[REVOCATION_PREFIX.len()..].to_vec(), value))
+            .collect())
+    }
+
+    /// Store a revocation as another registry's `entries` listed it
+    ///
+    /// Not subject to the capacity: the entries are a copy of a registry
+    /// that was.
+    pub fn restore(&self, credential_id: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.storage.put(&Self::key(credential_id), value)
+    }
+
+    pub fn is_revoked(&self, credential_id: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.storage.get(&Self::key(credential_id))?.is_some())
+    }
+
+    /// Record a published revocation entry
+    pub fn apply(&self, entry: &RevocationEntry) -> Result<(), StorageError> {
+        let mut value = entry.revoked_at.to_le_bytes().to_vec();
+        if let Some(successor) = &entry.superseded_by {
+            value.extend_from_slice(successor.as_bytes());
+        }
+        self.put(entry.credential_id.as_bytes(), &value)
+    }
+
+    /// Direct successor of a superseded credential
+    pub fn successor(&self, credential_id: &str) -> Result<Option<String>, StorageError> {
+        let value = match self.storage.get(&Self::key(credential_id.as_bytes()))? {
+            Some(value) if value.len() > 8 => value,
+            _ => return Ok(None),
+        };
+        String::from_utf8(value[8..].to_vec())
+            .map(Some)
+            .map_err(|_| StorageError::Backend)
+    }
+
+    /// Every stored revocation as a published entry
+    pub fn published_entries(&self) -> Result<Vec<RevocationEntry>, StorageError> {
+        self.entries()?
+            .into_iter()
+            .map(|(id, value)| {
+                let revoked_at = value.get(..8).ok_or(StorageError::Backend)?;
+                let successor = &value[8..];
+                Ok(RevocationEntry {
+                    credential_id: String::from_utf8(id).map_err(|_| StorageError::Backend)?,
+                    revoked_at: u64::from_le_bytes(revoked_at.try_into().unwrap_or([0; 8])),
+                    superseded_by: match successor.is_empty() {
+                        true => None,
+                        false => Some(String::from_utf8(successor.to_vec()).map_err(|_| StorageError::Backend)?),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Follow supersession pointers to the newest credential id
+    pub fn resolve(&self, credential_id: &str) -> Result<String, StorageError> {
+        let mut current = credential_id.to_string();
+        for _ in 0..MAX_SUPERSESSION_CHAIN {
+            match self.successor(&current)? {
+                Some(next) => current = next,
+                None => return Ok(current),
+            }
+        }
+        Err(StorageError::Backend)
+    }
+}
+
+crate::unwind::entry_point! {
+/// Revoke a credential by identifier
+///
+/// Returns 0 on success, ZK_ERR_CAPACITY if the registry is full,
+/// ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_RevokeCredential(
+    credential_id: *const c_char,
+    credential_id_len: usize,
+    revoked_at: u64,
+) -> c_int {
+    if credential_id.is_null() {
+        return -1;
+    }
+
+    let id_bytes = match read_bytes(credential_id, credential_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match RevocationRegistry::new(storage()).revoke(id_bytes, revoked_at) {
+        Ok(()) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check whether a credential has been revoked
+///
+/// Returns 1 if revoked, 0 if not, ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_IsCredentialRevoked(
+    credential_id: *const c_char,
+    credential_id_len: usize,
+) -> c_int {
+    if credential_id.is_null() {
+        return -1;
+    }
+
+    let id_bytes = match read_bytes(credential_id, credential_id_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    match RevocationRegistry::new(storage()).is_revoked(id_bytes) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => ZK_ERR_STORAGE,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Record a hex revocation entry produced by ZK_ReissueVC
+///
+/// Returns 0 on success, -1 on a malformed entry, ZK_ERR_CAPACITY if the
+/// registry is full, ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_ApplyRevocationEntry(entry: *const c_char) -> c_int {
+    if entry.is_null() {
+        return -1;
+    }
+
+    let entry_str = unsafe { CStr::from_ptr(entry).to_str().unwrap_or("") };
+    let entry = match hex_to_bytes(entry_str).ok().and_then(|b| RevocationEntry::from_bytes(&b)) {
+        Some(entry) => entry,
+        None => return -1,
+    };
+
+    match RevocationRegistry::new(storage()).apply(&entry) {
+        Ok(()) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Resolve a credential id through its supersession chain to the newest id
+///
+/// Returns 0 on success, -1 on failure, ZK_ERR_STORAGE if the backend failed.
+#[no_mangle]
+pub extern "C" fn ZK_ResolveSupersession(
+    credential_id: *const c_char,
+    newest_id_out: *mut c_char,
+    newest_id_out_size: usize,
+) -> c_int {
+    if credential_id.is_null() {
+        return -1;
+    }
+
+    let id = unsafe { CStr::from_ptr(credential_id).to_str().unwrap_or("") };
+
+    match RevocationRegistry::new(storage()).resolve(id) {
+        Ok(newest) => match write_cstr(newest_id_out, newest_id_out_size, &newest) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        Err(_) => ZK_ERR_STORAGE,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Make ZK_VerifyVCBlob reject credentials that have been superseded
+#[no_mangle]
+pub extern "C" fn ZK_SetRejectSuperseded(enabled: c_int) -> c_int {
+    REJECT_SUPERSEDED.store(enabled != 0, Ordering::Relaxed);
+    0
+}
+}
+
+// ============================================================================
+// Published Revocation Data
+// ============================================================================
+
+/// An issuer's complete revocation list at one epoch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevocationList {
+    pub issuer: [u8; PUBLIC_KEY_LENGTH],
+    pub epoch: u64,
+    pub entries: Vec<RevocationEntry>,
+}
+
+impl RevocationList {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = LIST_MAGIC.to_vec();
+        out.push(LIST_VERSION);
+        out.extend_from_slice(&self.issuer);
+        out.extend_from_slice(&self.epoch.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            put_bytes(&mut out, &entry.to_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != LIST_MAGIC || r.u8()? != LIST_VERSION {
+            return None;
+        }
+
+        let issuer = r.take(PUBLIC_KEY_LENGTH)?.try_into().ok()?;
+        let epoch = r.u64()?;
+        let count = r.u32()?;
+        let entries = (0..count)
+            .map(|_| RevocationEntry::from_bytes(r.bytes()?))
+            .collect::<Option<Vec<_>>>()?;
+
+        if !r.is_empty() {
+            return None;
+        }
+
+        Some(Self { issuer, epoch, entries })
+    }
+}
+
+/// The revocation list verifications currently check against
+#[derive(Debug)]
+pub struct RevocationData {
+    pub epoch: u64,
+    pub issuer: [u8; PUBLIC_KEY_LENGTH],
+    /// (issuer, revoked credential id) -> successor, if superseded
+    revoked: HashMap<([u8; PUBLIC_KEY_LENGTH], String), Option<String>>,
+    list: Vec<u8>,
+    attestation: [u8; SIGNATURE_LENGTH],
+}
+
+impl RevocationData {
+    /// Check a signed list from `issuer`, the key the verifier trusts for it
+    ///
+    /// The list must carry `issuer` (ZK_ERR_KEY_MISMATCH otherwise) and the
+    /// attestation must verify under it. Epoch 0 is refused: it is what the
+    /// stats report while nothing has been published.
+    pub fn open(list: &[u8], attestation: &[u8], issuer: &VerifyingKey) -> Result<Self, c_int> {
+        let parsed = RevocationList::from_bytes(list)
+            .filter(|parsed| parsed.epoch != 0)
+            .ok_or(ZK_ERR_CORRUPT)?;
+        let attestation: [u8; SIGNATURE_LENGTH] = attestation.try_into().map_err(|_| ZK_ERR_CORRUPT)?;
+        if parsed.issuer != issuer.to_bytes() {
+            return Err(ZK_ERR_KEY_MISMATCH);
+        }
+        if !verify_ed25519(
+            issuer,
+            &KeyUsage::Status.signed_message(list),
+            &Signature::from_bytes(&attestation),
+        ) {
+            return Err(ZK_ERR_AUTH);
+        }
+
+        Ok(Self {
+            epoch: parsed.epoch,
+            issuer: parsed.issuer,
+            revoked: parsed
+                .entries
+                .into_iter()
+                .map(|entry| ((parsed.issuer, entry.credential_id), entry.superseded_by))
+                .collect(),
+            list: list.to_vec(),
+            attestation,
+        })
+    }
+
+    /// Check a list whose issuer something else vouches for (a signed
+    /// verifier state snapshot): the key it carries
+    pub fn open_carried(list: &[u8], attestation: &[u8]) -> Result<Self, c_int> {
+        let parsed = RevocationList::from_bytes(list).ok_or(ZK_ERR_CORRUPT)?;
+        let issuer = VerifyingKey::from_bytes(&parsed.issuer).map_err(|_| ZK_ERR_CORRUPT)?;
+        Self::open(list, attestation, &issuer)
+    }
+
+    /// Whether `issuer` revoked `credential_id` in this list
+    pub fn is_revoked(&self, issuer: &[u8; PUBLIC_KEY_LENGTH], credential_id: &str) -> bool {
+        self.revoked.contains_key(&(*issuer, credential_id.to_string()))
+    }
+
+    pub fn successor(&self, issuer: &[u8; PUBLIC_KEY_LENGTH], credential_id: &str) -> Option<&str> {
+        self.revoked.get(&(*issuer, credential_id.to_string()))?.as_deref()
+    }
+
+    /// The epoch a check of `issuer`'s credential consults: 0 for another
+    /// issuer's credential, or one whose issuer is unknown
+    pub fn epoch_for(&self, issuer: Option<&[u8; PUBLIC_KEY_LENGTH]>) -> u64 {
+        if issuer == Some(&self.issuer) {
+            self.epoch
+        } else {
+            0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+
+    /// The signed list as applied
+    pub fn list(&self) -> &[u8] {
+        &self.list
+    }
+
+    pub fn attestation(&self) -> &[u8; SIGNATURE_LENGTH] {
+        &self.attestation
+    }
+}
+
+// Swapped whole on update; readers never block
+static PUBLISHED: ArcSwapOption<RevocationData> = ArcSwapOption::const_empty();
+
+// Serializes the compare + swap of updates
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+static UPDATES_APPLIED: AtomicU64 = AtomicU64::new(0);
+static UPDATES_REFUSED: AtomicU64 = AtomicU64::new(0);
+
+/// The published list at this moment, if any
+///
+/// Load once per verification and check against the result throughout.
+pub fn published() -> Option<Arc<RevocationData>> {
+    PUBLISHED.load_full()
+}
+
+/// Publish `data` unless the active list is as new or from another issuer
+///
+/// Re-applying the active list is accepted and changes nothing. Returns the
+/// active epoch. `data` was opened under a key the caller trusts; after
+/// ZK_Cleanup the next list may name another issuer, but only ever revokes
+/// that issuer's credentials.
+pub fn update(data: RevocationData) -> Result<u64, c_int> {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(active) = PUBLISHED.load().as_deref() {
+        if active.issuer != data.issuer {
+            return Err(ZK_ERR_KEY_MISMATCH);
+        }
+        if data.epoch == active.epoch && data.list == active.list {
+            return Ok(active.epoch);
+        }
+        if data.epoch <= active.epoch {
+            return Err(ZK_ERR_EXPIRED);
+        }
+    }
+
+    let epoch = data.epoch;
+    PUBLISHED.store(Some(Arc::new(data)));
+    UPDATES_APPLIED.fetch_add(1, Ordering::Relaxed);
+    Ok(epoch)
+}
+
+/// Drop the published list (ZK_Cleanup)
+pub fn clear_published() {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    PUBLISHED.store(None);
+}
+
+/// Revocation list metrics
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZkRevocationDataStats {
+    /// Epoch verifications currently check against, 0 if none published
+    pub epoch: u64,
+    /// Credentials the active list revokes
+    pub entries: u64,
+    /// Updates that swapped in a new epoch
+    pub updates: u64,
+    /// Updates refused (malformed, bad attestation, other issuer, not newer)
+    pub refused: u64,
+}
+
+crate::unwind::entry_point! {
+/// Sign the issuer's registry as the revocation list for `epoch`
+///
+/// Writes the hex list to `list_out` and the hex attestation to
+/// `attestation_out`, for verifiers to pass to ZK_UpdateRevocationData.
+/// Returns 0 on success, ZK_ERR_STORAGE if the registry could not be read,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL, a malformed key or epoch 0.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_PublishRevocationData(
+    issuer_private_key: *const c_char,
+    epoch: u64,
+    list_out: *mut c_char,
+    list_out_size: usize,
+    attestation_out: *mut c_char,
+    attestation_out_size: usize,
+) -> c_int {
+    use ed25519_dalek::Signer;
+
+    if issuer_private_key.is_null() || epoch == 0 {
+        return -1;
+    }
+    let signing_key = match crate::credential::parse_signing_key(issuer_private_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let entries = match RevocationRegistry::new(storage()).published_entries() {
+        Ok(entries) => entries,
+        Err(e) => return e.code(),
+    };
+
+    let list = RevocationList {
+        issuer: signing_key.verifying_key().to_bytes(),
+        epoch,
+        entries,
+    }
+    .to_bytes();
+    let attestation = signing_key.sign(&KeyUsage::Status.signed_message(&list));
+
+    if let Err(e) = write_cstr(list_out, list_out_size, &crate::bytes_to_hex(&list)) {
+        return e.code();
+    }
+    match write_cstr(attestation_out, attestation_out_size, &crate::bytes_to_hex(&attestation.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Replace the published revocation list with a newer epoch
+///
+/// `list` and `attestation` are hex, as ZK_PublishRevocationData writes
+/// them; `issuer_public_key` (hex) is the issuer the verifier trusts for
+/// the list, which must carry that key and be attested under it. The list
+/// only revokes that issuer's credentials. Verifications already running
+/// finish against the list they started with. Returns 0 on success (also
+/// for the active list again), ZK_ERR_AUTH if the attestation does not
+/// verify under `issuer_public_key`, ZK_ERR_EXPIRED if the epoch is not
+/// newer than the active one, ZK_ERR_KEY_MISMATCH if the list names another
+/// issuer or the active list is another issuer's, ZK_ERR_CORRUPT,
+/// ZK_ERR_INPUT_TOO_LARGE, -1 on NULL or a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_UpdateRevocationData(
+    list: *const c_char,
+    attestation: *const c_char,
+    issuer_public_key: *const c_char,
+) -> c_int {
+    if list.is_null() || attestation.is_null() || issuer_public_key.is_null() {
+        return -1;
+    }
+    let issuer = match crate::credential::parse_verifying_key(issuer_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+
+    let result = match (text_arg(list, 2 * MAX_BLOB_LEN), text_arg(attestation, 2 * SIGNATURE_LENGTH)) {
+        (Some(list), Some(attestation)) => match (hex_to_bytes(list), hex_to_bytes(attestation)) {
+            (Ok(list), Ok(attestation)) => RevocationData::open(&list, &attestation, &issuer).and_then(update),
+            _ => Err(ZK_ERR_CORRUPT),
+        },
+        _ => Err(ZK_ERR_INPUT_TOO_LARGE),
+    };
+
+    match result {
+        Ok(_) => 0,
+        Err(code) => {
+            UPDATES_REFUSED.fetch_add(1, Ordering::Relaxed);
+            code
+        }
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Fill `stats_out` with the active epoch and update counters
+///
+/// Returns 0 on success, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_GetRevocationDataStats(stats_out: *mut ZkRevocationDataStats) -> c_int {
+    let stats_out = match unsafe { stats_out.as_mut() } {
+        Some(out) => out,
+        None => return -1,
+    };
+
+    let active = published();
+    *stats_out = ZkRevocationDataStats {
+        epoch: active.as_ref().map_or(0, |data| data.epoch),
+        entries: active.as_ref().map_or(0, |data| data.len() as u64),
+        updates: UPDATES_APPLIED.load(Ordering::Relaxed),
+        refused: UPDATES_REFUSED.load(Ordering::Relaxed),
+    };
+    0
+}
+}
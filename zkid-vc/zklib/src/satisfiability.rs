@@ -0,0 +1,532 @@
+// ============================================================================
+// Circuit Satisfiability Check (feature "debug-circuit")
+// ============================================================================
+//
+// Before proving, the circuit is synthesized with its witness into a
+// ConstraintSystem and checked with is_satisfied(). An unsatisfied system
+// fails with ZK_ERR_UNSATISFIED instead of producing a proof that cannot
+// verify. The first failing constraint is reported by index and by the name
+// of the ns! span around its enforce_constraint call, which ark-relations'
+// ConstraintLayer records while synthesizing.
+//
+// The same feature carries a mutation harness for under-constrained
+// circuits. Starting from a valid assignment it perturbs each witness
+// variable, and separately each public input with the witness fixed, and
+// re-checks the constraints. Any perturbation that leaves the system
+// satisfied is a gap: a free witness lets a prover claim knowledge it does
+// not have, a malleable input lets the same witness prove other public
+// values. An input that occurs in no constraint at all (the issuer key hash
+// and nonce of most circuits) is not one: Groth16 commits the verifier to
+// every input, so a proof for one value verifies for no other, and a
+// product with a free witness would bind it no further. ZK_RunConstraintAudit
+// runs it over every circuit in the crate.
+//
+// The feature pulls in a tracing subscriber and doubles synthesis work; it is
+// for development and CI only and is never enabled in enclave builds.
+
+use ark_bn254::Fr;
+use ark_ff::{One, PrimeField, Zero};
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem,
+    ConstraintSystemRef, SynthesisError,
+};
+use serde_json::json;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+use crate::disclosure::{claim_value_field, field_from_bytes, ClaimDisclosureCircuit, ClaimsTree};
+use crate::ballot::HOLDER_SECRET_LEN;
+use crate::error::ZK_ERR_UNSATISFIED;
+use crate::ffi::write_cstr;
+use crate::holder;
+use crate::nonrevocation::{revocation_key, GapPath, RevocationTree};
+use crate::onetime;
+use crate::proof::{self, PROOF_FORMAT_V2};
+use crate::range::RangeClaimCircuit;
+use crate::schedule::{week_offset, ScheduleCircuit, ValiditySchedule, ValidityWindow, DEFAULT_SCHEDULE_WINDOWS};
+use crate::validity::{self, Opening, Window};
+use crate::{did, hex_to_bytes, AnchorWitness, VCCircuit};
+
+/// The first constraint a witness violates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsatisfied {
+    pub index: usize,
+    /// Name of the enclosing ns! span, e.g. "issuer_key_binding"
+    pub name: String,
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constraint {} ({})", self.index, self.name)
+    }
+}
+
+/// Synthesize `circuit` with its witness and find the first unsatisfied
+/// constraint, if any
+pub fn first_unsatisfied<C: ConstraintSynthesizer<Fr>>(
+    circuit: C,
+) -> Result<Option<Unsatisfied>, SynthesisError> {
+    let subscriber = Registry::default().with(ConstraintLayer::default());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+
+        if cs.is_satisfied()? {
+            return Ok(None);
+        }
+
+        let names = cs.constraint_names().unwrap_or_default();
+        let (matrices, z) = assignment(&cs)?;
+        let index = violated(&matrices, &z, 0..matrices.num_constraints)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+
+        Ok(Some(Unsatisfied {
+            index,
+            name: names
+                .get(index)
+                .and_then(|path| path.rsplit("::").next())
+                .map(str::to_string)
+                .unwrap_or_default(),
+        }))
+    })
+}
+
+/// Constraint matrices and the full assignment of a finalized system
+///
+/// Matrix columns index instance variables first (the constant one at 0),
+/// then witnesses; `z` follows the same order.
+fn assignment(
+    cs: &ConstraintSystemRef<Fr>,
+) -> Result<(ConstraintMatrices<Fr>, Vec<Fr>), SynthesisError> {
+    let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+    let system = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+    let z = system
+        .instance_assignment
+        .iter()
+        .chain(&system.witness_assignment)
+        .copied()
+        .collect();
+    Ok((matrices, z))
+}
+
+/// First of `rows` that `z` violates
+fn violated(
+    matrices: &ConstraintMatrices<Fr>,
+    z: &[Fr],
+    rows: impl IntoIterator<Item = usize>,
+) -> Option<usize> {
+    let eval = |row: &[(Fr, usize)]| row.iter().map(|(coeff, i)| *coeff * z[*i]).sum::<Fr>();
+    rows.into_iter()
+        .find(|&i| eval(&matrices.a[i]) * eval(&matrices.b[i]) != eval(&matrices.c[i]))
+}
+
+/// Variables a single perturbation leaves the system satisfied under
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MutationGaps {
+    pub witnesses: usize,
+    pub inputs: usize,
+    /// Witness indices, in allocation order
+    pub free_witnesses: Vec<usize>,
+    /// Public input indices, in verifier order
+    pub malleable_inputs: Vec<usize>,
+}
+
+impl MutationGaps {
+    pub fn count(&self) -> usize {
+        self.free_witnesses.len() + self.malleable_inputs.len()
+    }
+}
+
+/// Perturb each variable of a valid assignment of `circuit` in turn
+///
+/// A variable is a gap if any of the perturbations keeps every constraint
+/// satisfied. Fails with SynthesisError::Unsatisfiable if the starting
+/// assignment is not valid.
+pub fn mutation_gaps<C: ConstraintSynthesizer<Fr>>(
+    circuit: C,
+) -> Result<MutationGaps, SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+    if !cs.is_satisfied()? {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    let (matrices, mut z) = assignment(&cs)?;
+    let num_instance = matrices.num_instance_variables;
+
+    // Rows each variable appears in; only those can change
+    let mut rows_of = vec![Vec::new(); z.len()];
+    for (row, (a, (b, c))) in matrices.a.iter().zip(matrices.b.iter().zip(&matrices.c)).enumerate() {
+        for &(_, var) in a.iter().chain(b).chain(c) {
+            if rows_of[var].last() != Some(&row) {
+                rows_of[var].push(row);
+            }
+        }
+    }
+
+    let deltas = [Fr::one(), Fr::from_le_bytes_mod_order(b"zkid/mutation/v1")];
+    let mut gaps = MutationGaps {
+        witnesses: z.len() - num_instance,
+        inputs: num_instance - 1,
+        ..MutationGaps::default()
+    };
+
+    for var in 1..z.len() {
+        // Only the proof binds an input no constraint mentions
+        if var < num_instance && rows_of[var].is_empty() {
+            continue;
+        }
+        let original = z[var];
+        let survives = deltas.iter().any(|delta| {
+            z[var] = original + delta;
+            violated(&matrices, &z, rows_of[var].iter().copied()).is_none()
+        });
+        z[var] = original;
+
+        if !survives {
+            continue;
+        }
+        if var < num_instance {
+            gaps.malleable_inputs.push(var - 1);
+        } else {
+            gaps.free_witnesses.push(var - num_instance);
+        }
+    }
+    Ok(gaps)
+}
+
+/// Pre-proving check: ZK_ERR_UNSATISFIED if the witness violates a
+/// constraint, -1 if synthesis fails
+pub fn check<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<(), c_int> {
+    match first_unsatisfied(circuit) {
+        Ok(None) => Ok(()),
+        Ok(Some(_)) => Err(ZK_ERR_UNSATISFIED),
+        Err(_) => Err(-1),
+    }
+}
+
+/// Mutation gaps of every circuit in the crate, each with a valid witness
+fn audit() -> Result<Vec<(&'static str, MutationGaps)>, SynthesisError> {
+    let field = |label: &[u8]| {
+        proof::field_for(PROOF_FORMAT_V2, label).ok_or(SynthesisError::AssignmentMissing)
+    };
+    let (vc_hash, issuer) = (field(b"zkid/audit/vc-hash")?, field(b"zkid/audit/issuer")?);
+
+    // Three claims, the last of which has an empty leaf for a sibling
+    let claims: Vec<(String, String)> = [("role", "engineer"), ("site", "berlin"), ("level", "3")]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let tree = ClaimsTree::new(&claims).ok_or(SynthesisError::AssignmentMissing)?;
+
+    let window = Window { issue_date: 1_600_000_000, expiry_date: 1_800_000_000 };
+    let opening = Opening {
+        vc_hash,
+        window,
+        holder_key: Fr::zero(),
+        blinding: field(b"zkid/audit/blinding")?,
+        claims_root: tree.root(),
+    };
+    let anchor = |opening: Opening| AnchorWitness::new(opening, issuer, Fr::from(42u64), 1_700_000_000);
+    let vc = VCCircuit::unbound(anchor(opening));
+    // The same credential bound to a holder
+    let secret = holder::secret_field(&[7u8; HOLDER_SECRET_LEN]);
+    let holder_key = holder::public_key(&secret);
+    let bound = VCCircuit {
+        bound: true,
+        holder_secret: Some(secret),
+        ..VCCircuit::unbound(anchor(Opening { holder_key, ..opening }))
+    };
+    // ... and presented once for a context
+    let context = onetime::context_field(b"zkid/audit/context");
+    let one_time = VCCircuit {
+        one_time: true,
+        context: Some(context),
+        nullifier: Some(onetime::nullifier(&secret, &context)),
+        ..bound.clone()
+    };
+    // The unbound credential, outside a tree of two other revocations
+    let mut revocations = RevocationTree::new();
+    for label in [&b"zkid/audit/revoked-1"[..], b"zkid/audit/revoked-2"] {
+        let key = revocation_key(&field(label)?).ok_or(SynthesisError::AssignmentMissing)?;
+        revocations.insert(key).map_err(|_| SynthesisError::AssignmentMissing)?;
+    }
+    let witness = revocation_key(&vc_hash)
+        .and_then(|key| revocations.witness(key))
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    let non_revoked = VCCircuit {
+        non_revoked: true,
+        revocation_root: Some(revocations.root()),
+        gap: GapPath::of(&witness),
+        ..vc.clone()
+    };
+
+    // Mon-Fri 08:00-18:00 and a weekend night shift wrapping into Monday,
+    // checked on a Wednesday at noon
+    let day = 86_400;
+    let mut windows: Vec<ValidityWindow> = (0..5)
+        .map(|d| ValidityWindow::Weekly { start: d * day + 8 * 3600, end: d * day + 18 * 3600 })
+        .collect();
+    windows.push(ValidityWindow::Weekly { start: 6 * day + 22 * 3600, end: 6 * 3600 });
+    let schedule = ValiditySchedule::new(windows).ok_or(SynthesisError::AssignmentMissing)?;
+    let wednesday_noon = 1_700_000_000 - week_offset(1_700_000_000) + 2 * day + 12 * 3600;
+    let scheduled = ScheduleCircuit::witness(
+        DEFAULT_SCHEDULE_WINDOWS,
+        AnchorWitness::new(
+            Opening { blinding: schedule.bind(opening.blinding), ..opening },
+            issuer,
+            Fr::from(42u64),
+            wednesday_noon as u64,
+        ),
+        opening.blinding,
+        &schedule,
+    );
+
+    // The last of the three claims
+    let disclosure = ClaimDisclosureCircuit::witness(anchor(opening), &tree, 2, ("level", "3"))
+        .ok_or(SynthesisError::AssignmentMissing)?;
+    // The same claim, exactly at the threshold
+    let range = RangeClaimCircuit::witness(anchor(opening), &tree, 2, ("level", Fr::from(3u64)), 3)
+        .ok_or(SynthesisError::AssignmentMissing)?;
+
+    Ok(vec![
+        ("vc", mutation_gaps(vc)?),
+        ("bound-vc", mutation_gaps(bound)?),
+        ("one-time-vc", mutation_gaps(one_time)?),
+        ("non-revoked-vc", mutation_gaps(non_revoked)?),
+        ("schedule", mutation_gaps(scheduled)?),
+        ("claim-disclosure", mutation_gaps(disclosure)?),
+        ("range-claim", mutation_gaps(range)?),
+    ])
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Run the mutation harness over every circuit in the crate
+///
+/// Writes a JSON report to `report_out`:
+///
+///   {"circuits":[{"circuit":"vc","witnesses":3,"inputs":2,
+///                 "free_witnesses":[],"malleable_inputs":[]},...],"gaps":0}
+///
+/// Returns the total number of gaps (0 = every variable is constrained), or
+/// -1 if a circuit fails to synthesize, ZK_ERR_BUFFER_TOO_SMALL if the report
+/// does not fit.
+#[no_mangle]
+pub extern "C" fn ZK_RunConstraintAudit(report_out: *mut c_char, report_out_size: usize) -> c_int {
+    let circuits = match audit() {
+        Ok(circuits) => circuits,
+        Err(_) => return -1,
+    };
+
+    let total: usize = circuits.iter().map(|(_, gaps)| gaps.count()).sum();
+    let circuits: Vec<_> = circuits
+        .iter()
+        .map(|(name, gaps)| {
+            json!({
+                "circuit": name,
+                "witnesses": gaps.witnesses,
+                "inputs": gaps.inputs,
+                "free_witnesses": gaps.free_witnesses,
+                "malleable_inputs": gaps.malleable_inputs,
+            })
+        })
+        .collect();
+
+    let report = json!({ "circuits": circuits, "gaps": total });
+    match write_cstr(report_out, report_out_size, &report.to_string()) {
+        Ok(_) => total.min(c_int::MAX as usize) as c_int,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the VC circuit for the given inputs without proving
+///
+/// Takes the same values ZK_GenerateVCProof feeds the circuit: the VC message
+/// hash (hex), the issuer public key (hex), the nonce, the issue and expiry
+/// dates, the current time and a proof format, with the anchor the issuer
+/// would sign over the hash and dates. Unlike ZK_GenerateVCProof,
+/// nothing checks the dates against the time first, so a time outside them
+/// reaches the window constraints.
+/// Returns 0 if satisfied, ZK_ERR_UNSATISFIED with "constraint <index>
+/// (<name>)" written to `report_out` if not, ZK_ERR_BUFFER_TOO_SMALL if that
+/// does not fit, -1 on invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_CheckCircuitSatisfiability(
+    format: u8,
+    vc_hash_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    issue_date: i64,
+    expiry_date: i64,
+    current_time: u64,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    if vc_hash_hex.is_null() || issuer_pubkey.is_null() {
+        return -1;
+    }
+
+    let decode = |hex: *const c_char| {
+        let hex_str = unsafe { CStr::from_ptr(hex).to_str().unwrap_or("") };
+        hex_to_bytes(hex_str).ok()
+    };
+    let issuer_text = unsafe { CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("") };
+    let (vc_hash, issuer_key) = match (decode(vc_hash_hex), did::public_key_bytes(issuer_text).ok()) {
+        (Some(vc_hash), Some(issuer_key)) => (vc_hash, issuer_key),
+        _ => return -1,
+    };
+
+    let circuit = match (validity::vc_hash_field(&vc_hash), proof::field_for(format, &issuer_key)) {
+        (Some(vc_hash), Some(issuer_key)) => {
+            let window = Window { issue_date, expiry_date };
+            let opening =
+                Opening { vc_hash, window, holder_key: Fr::zero(), blinding: Fr::zero(), claims_root: Fr::zero() };
+            VCCircuit::unbound(AnchorWitness::new(opening, issuer_key, Fr::from(nonce), current_time))
+        }
+        _ => return -1,
+    };
+
+    match first_unsatisfied(circuit) {
+        Ok(None) => 0,
+        Ok(Some(unsatisfied)) => match write_cstr(report_out, report_out_size, &unsatisfied.to_string()) {
+            Ok(_) => ZK_ERR_UNSATISFIED,
+            Err(e) => e.code(),
+        },
+        Err(_) => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the range-claim circuit for a credential whose only claim is
+/// `claim_key` = `claim_value`, without proving
+///
+/// The value witness is the claim's leaf value whatever the claim holds, so
+/// a text value or a number past u64 tries a witness of 2^64 or more
+/// against the bit constraints. Returns 0 if satisfied, ZK_ERR_UNSATISFIED
+/// with "constraint <index> (<name>)" written to `report_out` if not,
+/// ZK_ERR_BUFFER_TOO_SMALL if that does not fit, -1 on invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_CheckRangeClaimSatisfiability(
+    claim_key: *const c_char,
+    claim_value: *const c_char,
+    threshold: u64,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    if claim_key.is_null() || claim_value.is_null() {
+        return -1;
+    }
+
+    let text = |ptr: *const c_char| unsafe { CStr::from_ptr(ptr).to_str().ok() };
+    let (key, value) = match (text(claim_key), text(claim_value)) {
+        (Some(key), Some(value)) => (key, value),
+        _ => return -1,
+    };
+    let issuer = match proof::field_for(PROOF_FORMAT_V2, b"zkid/audit/issuer") {
+        Some(issuer) => issuer,
+        None => return -1,
+    };
+    let vc_hash = match proof::field_for(PROOF_FORMAT_V2, b"zkid/audit/vc-hash") {
+        Some(vc_hash) => vc_hash,
+        None => return -1,
+    };
+    // A credential active at the audit time, whose claims root opens the tree
+    let circuit = ClaimsTree::new(&[(key.to_string(), value.to_string())]).and_then(|tree| {
+        let window = Window { issue_date: 1_600_000_000, expiry_date: 1_800_000_000 };
+        let opening =
+            Opening { vc_hash, window, holder_key: Fr::zero(), blinding: Fr::zero(), claims_root: tree.root() };
+        let anchor = AnchorWitness::new(opening, issuer, Fr::from(42u64), 1_700_000_000);
+        RangeClaimCircuit::witness(anchor, &tree, 0, (key, claim_value_field(value)), threshold)
+    });
+    let circuit = match circuit {
+        Some(circuit) => circuit,
+        None => return -1,
+    };
+
+    match first_unsatisfied(circuit) {
+        Ok(None) => 0,
+        Ok(Some(unsatisfied)) => match write_cstr(report_out, report_out_size, &unsatisfied.to_string()) {
+            Ok(_) => ZK_ERR_UNSATISFIED,
+            Err(e) => e.code(),
+        },
+        Err(_) => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the bound-VC circuit without proving, for a credential bound to
+/// `holder_public_key` and the witness `holder_secret` (both hex)
+///
+/// As ZK_CheckCircuitSatisfiability, with the anchor taken over the given
+/// holder public key, so a secret whose public key differs leaves the
+/// anchor constraint (vc_anchor) unsatisfied. Returns 0 if satisfied,
+/// ZK_ERR_UNSATISFIED with "constraint <index> (<name>)" written to
+/// `report_out` if not, ZK_ERR_BUFFER_TOO_SMALL if that does not fit, -1 on
+/// invalid input.
+#[no_mangle]
+pub extern "C" fn ZK_CheckBoundCircuitSatisfiability(
+    format: u8,
+    vc_hash_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    issue_date: i64,
+    expiry_date: i64,
+    current_time: u64,
+    holder_public_key: *const c_char,
+    holder_secret: *const c_char,
+    report_out: *mut c_char,
+    report_out_size: usize,
+) -> c_int {
+    if [vc_hash_hex, issuer_pubkey, holder_public_key, holder_secret].iter().any(|ptr| ptr.is_null()) {
+        return -1;
+    }
+
+    let decode = |hex: *const c_char| {
+        let hex_str = unsafe { CStr::from_ptr(hex).to_str().unwrap_or("") };
+        hex_to_bytes(hex_str).ok()
+    };
+    let holder_key = decode(holder_public_key).and_then(|bytes| field_from_bytes(&bytes));
+    let secret = decode(holder_secret).and_then(|bytes| <[u8; HOLDER_SECRET_LEN]>::try_from(bytes).ok());
+    let circuit = match (decode(vc_hash_hex), decode(issuer_pubkey), holder_key, secret) {
+        (Some(vc_hash), Some(issuer_key), Some(holder_key), Some(secret)) => {
+            match (validity::vc_hash_field(&vc_hash), proof::field_for(format, &issuer_key)) {
+                (Some(vc_hash), Some(issuer_key)) => {
+                    let window = Window { issue_date, expiry_date };
+                    let opening =
+                        Opening { vc_hash, window, holder_key, blinding: Fr::zero(), claims_root: Fr::zero() };
+                    VCCircuit {
+                        bound: true,
+                        holder_secret: Some(holder::secret_field(&secret)),
+                        ..VCCircuit::unbound(AnchorWitness::new(opening, issuer_key, Fr::from(nonce), current_time))
+                    }
+                }
+                _ => return -1,
+            }
+        }
+        _ => return -1,
+    };
+
+    match first_unsatisfied(circuit) {
+        Ok(None) => 0,
+        Ok(Some(unsatisfied)) => match write_cstr(report_out, report_out_size, &unsatisfied.to_string()) {
+            Ok(_) => ZK_ERR_UNSATISFIED,
+            Err(e) => e.code(),
+        },
+        Err(_) => -1,
+    }
+}
+}
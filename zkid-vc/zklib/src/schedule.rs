@@ -0,0 +1,916 @@
+// ============================================================================
+// Validity Schedules (recurring and explicit windows)
+// ============================================================================
+//
+// A credential may carry a validity schedule on top of issue/expiry: it is
+// then only usable while the current time falls inside one of its windows,
+// e.g. Mon-Fri 08:00-18:00 for a contractor. A window is either
+//
+//   interval  [start, end) in unix seconds
+//   weekly    [start, end) in seconds since Monday 00:00, repeating every week
+//
+// All times are UTC. There is no timezone or daylight-saving handling: a
+// 09:00-17:00 shift in a UTC+2 zone is the weekly window 07:00-15:00, and the
+// issuer re-issues when the local offset changes. A weekly window may cross
+// midnight (Fri 22:00 - Sat 06:00 is start < end within the week); one whose
+// end is before its start wraps past Sunday 24:00 into the next week.
+//
+// A schedule holds 1..=MAX_SCHEDULE_WINDOWS windows. It is signed as part of
+// the message hash (absent schedules leave the hash unchanged) and encoded,
+// integers little-endian, as
+//
+//   window_count u32 | (kind u8 | start i64 | end i64)*
+//
+// The circuit compares the time against slots: an interval window clamped
+// to non-negative times, or a weekly window, a wrapping one split in two at
+// Sunday 24:00. The slots are committed to by the chain
+//
+//   c_0 = 0,  c_i+1 = MiMC(c_i, weekly, start, end)
+//
+// and from blob version 10 on the anchor (validity.rs) of a scheduled
+// credential takes MiMC(blinding, c_n) for its blinding, so the issuer's
+// signature covers the windows in a form the circuit can open.
+//
+// The schedule circuit opens the signed anchor, checks that the credential
+// is active at the public current_time, and proves that the time lies
+// inside one of the committed slots without revealing which one or how
+// many there are: every schedule is padded to a fixed number of slots,
+// which the chain skips. The number of slots is the circuit's capacity (see
+// params.rs): a circuit for n windows has 2n slots, and several capacities
+// may be registered at once, each with its own keys. Proofs carry their
+// capacity and then the VC proof header with the anchor and its signature:
+//
+//   "ZC" | circuit u8 | capacity u32 | "ZKVA" | current_time u64 | anchor | signature | proof
+//
+// The prover picks the smallest registered capacity the schedule fits. The
+// verifier checks the signature under the issuer key it verifies for, so a
+// window the issuer never signed does not verify, whatever a modified
+// prover skipped. Version 9 credentials, whose anchor holds no schedule,
+// must be signed again to prove.
+//
+// Public inputs (in order): issuer_pubkey_hash, nonce, current_time,
+// anchor.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::PreparedVerifyingKey;
+use std::collections::BTreeMap;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::credential::parse_vc_blob;
+use crate::error::ZK_ERR_CIRCUIT_MISMATCH;
+use crate::ffi::{read_slice, write_cstr};
+use crate::wire::Reader;
+use crate::{bytes_to_hex, dates, mimc};
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use {
+    crate::params::ZkCircuitParams,
+    crate::proof,
+    crate::validity,
+    ark_groth16::Groth16,
+    ark_snark::SNARK,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::checkpoint,
+    crate::credential::{checked_blob, claimed_blinding},
+    crate::error::{ZK_ERR_BAD_SIGNATURE, ZK_ERR_PROVE_FAILED},
+    crate::{enforce_active, entropy, last_error, seeded_rng, Anchored, AnchorWitness},
+    ark_ff::{Field, Zero},
+    ark_groth16::ProvingKey,
+    ark_relations::r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+    },
+    ark_relations::{lc, ns},
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission::{self, MAX_PROOF_LEN},
+    crate::audit,
+    crate::ffi::MAX_FIELD_LEN,
+    crate::hex_to_bytes,
+    crate::params::PARAMS_PREFIX_LEN,
+    crate::sizes::{SCHEDULE_PUBLIC_INPUTS, ZK_CIRCUIT_SCHEDULE},
+    crate::validity::VALIDITY_PREFIX_LEN,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+pub const ZK_WINDOW_INTERVAL: c_int = 0;
+pub const ZK_WINDOW_WEEKLY: c_int = 1;
+
+/// Most windows a schedule may hold, and the largest circuit capacity
+pub const MAX_SCHEDULE_WINDOWS: usize = 64;
+
+/// Capacity ZK_Init registers
+pub const DEFAULT_SCHEDULE_WINDOWS: usize = 16;
+
+pub const SECONDS_PER_WEEK: i64 = 7 * 86_400;
+
+/// 1970-01-01 was a Thursday; the first Monday 00:00 UTC is four days later
+const FIRST_MONDAY: i64 = 4 * 86_400;
+
+/// Circuit id of the schedule circuit, without its capacity
+pub const SCHEDULE_CIRCUIT: &str = "zkid-vc/schedule/v2";
+
+/// Bits of a week offset (SECONDS_PER_WEEK < 2^20)
+#[cfg(feature = "prover")]
+const WEEK_BITS: usize = 20;
+
+/// Bits of a time difference inside a window
+#[cfg(feature = "prover")]
+const TIME_BITS: usize = 64;
+
+/// Keys of one registered capacity; the verifying key of a verifier-only
+/// build arrives by import
+#[derive(Default)]
+struct ScheduleKeys {
+    #[cfg(feature = "prover")]
+    pk: Option<ProvingKey<Bn254>>,
+    pvk: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+/// Registered capacities (in windows) and their keys
+static SCHEDULE_KEYS: Mutex<BTreeMap<usize, ScheduleKeys>> = Mutex::new(BTreeMap::new());
+
+/// Circuit id of the schedule circuit for `windows` windows
+pub fn circuit_id(windows: usize) -> String {
+    format!("{SCHEDULE_CIRCUIT}/w{windows}")
+}
+
+/// One window of a validity schedule, UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidityWindow {
+    /// [start, end) in unix seconds
+    Interval { start: i64, end: i64 },
+    /// [start, end) in seconds since Monday 00:00, every week; wraps into
+    /// the next week when end < start
+    Weekly { start: i64, end: i64 },
+}
+
+/// Seconds since the most recent Monday 00:00 UTC
+pub fn week_offset(now: i64) -> i64 {
+    (now - FIRST_MONDAY).rem_euclid(SECONDS_PER_WEEK)
+}
+
+impl ValidityWindow {
+    /// Build a window from its C API kind, rejecting empty or out-of-range ones
+    pub fn new(kind: c_int, start: i64, end: i64) -> Option<Self> {
+        let window = match kind {
+            ZK_WINDOW_INTERVAL => ValidityWindow::Interval { start, end },
+            ZK_WINDOW_WEEKLY => ValidityWindow::Weekly { start, end },
+            _ => return None,
+        };
+        window.is_well_formed().then_some(window)
+    }
+
+    fn is_well_formed(&self) -> bool {
+        match *self {
+            ValidityWindow::Interval { start, end } => start < end,
+            ValidityWindow::Weekly { start, end } => {
+                (0..SECONDS_PER_WEEK).contains(&start) && (0..=SECONDS_PER_WEEK).contains(&end) && start != end
+            }
+        }
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            ValidityWindow::Interval { .. } => ZK_WINDOW_INTERVAL as u8,
+            ValidityWindow::Weekly { .. } => ZK_WINDOW_WEEKLY as u8,
+        }
+    }
+
+    fn bounds(&self) -> (i64, i64) {
+        match *self {
+            ValidityWindow::Interval { start, end } | ValidityWindow::Weekly { start, end } => (start, end),
+        }
+    }
+
+    /// Whether unix time `now` falls inside the window
+    pub fn contains(&self, now: i64) -> bool {
+        match *self {
+            ValidityWindow::Interval { start, end } => start <= now && now < end,
+            ValidityWindow::Weekly { start, end } => {
+                let offset = week_offset(now);
+                if start < end {
+                    start <= offset && offset < end
+                } else {
+                    offset >= start || offset < end
+                }
+            }
+        }
+    }
+}
+
+/// The windows a credential is valid in, on top of issue/expiry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValiditySchedule {
+    pub windows: Vec<ValidityWindow>,
+}
+
+impl ValiditySchedule {
+    /// None unless there are 1..=MAX_SCHEDULE_WINDOWS well-formed windows
+    pub fn new(windows: Vec<ValidityWindow>) -> Option<Self> {
+        if windows.is_empty()
+            || windows.len() > MAX_SCHEDULE_WINDOWS
+            || !windows.iter().all(ValidityWindow::is_well_formed)
+        {
+            return None;
+        }
+        Some(Self { windows })
+    }
+
+    /// Whether `now` (unix seconds from the time source) is inside a window
+    pub fn contains(&self, now: u64) -> bool {
+        match dates::from_u64(now) {
+            Some(now) => self.windows.iter().any(|w| w.contains(now)),
+            None => false,
+        }
+    }
+
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.windows.len() as u32).to_le_bytes());
+        for window in &self.windows {
+            let (start, end) = window.bounds();
+            out.push(window.kind());
+            out.extend_from_slice(&start.to_le_bytes());
+            out.extend_from_slice(&end.to_le_bytes());
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+
+    pub(crate) fn decode(r: &mut Reader) -> Option<Self> {
+        let count = r.u32()? as usize;
+        if count > MAX_SCHEDULE_WINDOWS {
+            return None;
+        }
+        let mut windows = Vec::with_capacity(count);
+        for _ in 0..count {
+            let kind = r.u8()? as c_int;
+            let (start, end) = (r.u64()? as i64, r.u64()? as i64);
+            windows.push(ValidityWindow::new(kind, start, end)?);
+        }
+        Self::new(windows)
+    }
+
+    /// Circuit slots of the windows: (weekly, start, end) with non-negative
+    /// bounds, at most two per window
+    fn slots(&self) -> Vec<Slot> {
+        let mut slots = Vec::with_capacity(2 * self.windows.len());
+        for window in &self.windows {
+            match *window {
+                // current_time is never negative; clamp, and drop windows
+                // that ended before 1970
+                ValidityWindow::Interval { start, end } if end > 0 => slots.push(Slot {
+                    weekly: false,
+                    start: start.max(0) as u64,
+                    end: end as u64,
+                }),
+                ValidityWindow::Interval { .. } => {}
+                ValidityWindow::Weekly { start, end } if start < end => slots.push(Slot {
+                    weekly: true,
+                    start: start as u64,
+                    end: end as u64,
+                }),
+                ValidityWindow::Weekly { start, end } => {
+                    slots.push(Slot { weekly: true, start: start as u64, end: SECONDS_PER_WEEK as u64 });
+                    if end > 0 {
+                        slots.push(Slot { weekly: true, start: 0, end: end as u64 });
+                    }
+                }
+            }
+        }
+        slots
+    }
+
+    /// Commitment to the windows, the end of the slot chain (see above)
+    pub fn commitment(&self) -> Fr {
+        self.slots().iter().fold(Fr::from(0u64), |chain, slot| {
+            let [weekly, start, end] = slot.fields();
+            mimc::hash(&[chain, weekly, start, end])
+        })
+    }
+
+    /// The anchor blinding of a credential with this schedule, given the
+    /// credential's own blinding
+    pub fn bind(&self, blinding: Fr) -> Fr {
+        mimc::hash(&[blinding, self.commitment()])
+    }
+}
+
+// ============================================================================
+// Circuit
+// ============================================================================
+
+/// A window as the circuit sees it; the default is empty and never matches
+#[derive(Clone, Copy, Debug, Default)]
+struct Slot {
+    weekly: bool,
+    start: u64,
+    end: u64,
+}
+
+impl Slot {
+    /// Field elements of the slot, in chain order
+    fn fields(&self) -> [Fr; 3] {
+        [Fr::from(self.weekly as u64), Fr::from(self.start), Fr::from(self.end)]
+    }
+}
+
+#[cfg(feature = "prover")]
+#[derive(Clone)]
+pub(crate) struct ScheduleCircuit {
+    /// Windows the circuit has room for
+    capacity: usize,
+
+    // 锚点的打开及前四个公开输入（lib.rs AnchorWitness），锚点致盲值为
+    // bind(blinding)
+    anchor: AnchorWitness,
+
+    // Private witness: the credential's own blinding and its slots
+    blinding: Option<Fr>,
+    slots: Option<Vec<Slot>>,
+}
+
+#[cfg(feature = "prover")]
+impl ScheduleCircuit {
+    /// The circuit shape for `capacity` windows, for setup
+    pub(crate) fn blank(capacity: usize) -> Self {
+        Self { capacity, anchor: AnchorWitness::blank(), blinding: None, slots: None }
+    }
+
+    /// The circuit with its full witness
+    pub(crate) fn witness(capacity: usize, anchor: AnchorWitness, blinding: Fr, schedule: &ValiditySchedule) -> Self {
+        Self { capacity, anchor, blinding: Some(blinding), slots: Some(schedule.slots()) }
+    }
+}
+
+/// Allocate a witness with an optional value
+#[cfg(feature = "prover")]
+pub(crate) fn witness(cs: &ConstraintSystemRef<Fr>, value: Option<Fr>) -> Result<Variable, SynthesisError> {
+    cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))
+}
+
+/// Enforce `0 <= value < 2^bits` by decomposing it into boolean witnesses
+///
+/// `bits` is at most 128; `value_u128` is the value the witness decomposes.
+#[cfg(feature = "prover")]
+pub(crate) fn enforce_bits(
+    cs: &ConstraintSystemRef<Fr>,
+    value: LinearCombination<Fr>,
+    value_u128: Option<u128>,
+    bits: usize,
+) -> Result<(), SynthesisError> {
+    let mut sum = lc!();
+    let mut coeff = Fr::from(1u64);
+    for i in 0..bits {
+        let bit = witness(cs, value_u128.map(|v| Fr::from(((v >> i) & 1) as u64)))?;
+        {
+            let _ns = ns!(cs, "range_bit");
+            cs.enforce_constraint(lc!() + bit, lc!() + Variable::One - bit, lc!())?;
+        }
+        sum += (coeff, bit);
+        coeff.double_in_place();
+    }
+    let _ns = ns!(cs, "range_sum");
+    cs.enforce_constraint(sum, lc!() + Variable::One, value)
+}
+
+#[cfg(feature = "prover")]
+impl ConstraintSynthesizer<Fr> for ScheduleCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let week = SECONDS_PER_WEEK as u64;
+        // Shift so that a Monday 00:00 lands on a multiple of the week
+        let shift_to_monday = week - FIRST_MONDAY as u64;
+
+        // Constraint 1: the chain over the schedule's slots; padding slots
+        // leave it unchanged
+        let mut slots = Vec::with_capacity(2 * self.capacity);
+        let mut chain = (lc!(), Some(Fr::zero()));
+        for i in 0..2 * self.capacity {
+            let slot = self.slots.as_ref().map(|slots| slots.get(i).copied().unwrap_or_default());
+            let used_val = self.slots.as_ref().map(|slots| i < slots.len());
+            let fields = slot.map(|s| s.fields());
+
+            let kind = witness(&cs, fields.map(|f| f[0]))?;
+            let start = witness(&cs, fields.map(|f| f[1]))?;
+            let end = witness(&cs, fields.map(|f| f[2]))?;
+            let used = witness(&cs, used_val.map(|u| Fr::from(u as u64)))?;
+            {
+                let _ns = ns!(cs, "slot_kind_boolean");
+                cs.enforce_constraint(lc!() + kind, lc!() + Variable::One - kind, lc!())?;
+            }
+            {
+                let _ns = ns!(cs, "slot_used_boolean");
+                cs.enforce_constraint(lc!() + used, lc!() + Variable::One - used, lc!())?;
+            }
+
+            let (hash, hash_val) = mimc::hash_gadget(&cs, &[
+                chain.clone(),
+                (lc!() + kind, fields.map(|f| f[0])),
+                (lc!() + start, fields.map(|f| f[1])),
+                (lc!() + end, fields.map(|f| f[2])),
+            ])?;
+            let next_val = used_val.zip(hash_val).zip(chain.1).map(|((u, h), c)| if u { h } else { c });
+            let next = witness(&cs, next_val)?;
+            {
+                let _ns = ns!(cs, "slot_chain");
+                cs.enforce_constraint(lc!() + used, hash - &chain.0, lc!() + next - &chain.0)?;
+            }
+            chain = (lc!() + next, next_val);
+            slots.push((slot, kind, start, end, used));
+        }
+
+        // Constraint 2: the anchor opens with the blinding bound to the chain
+        let blinding = witness(&cs, self.blinding)?;
+        let bound = mimc::hash_gadget(&cs, &[(lc!() + blinding, self.blinding), chain])?;
+        let opened = self.anchor.open_with_blinding(&cs, None, bound)?;
+        enforce_active(&cs, &opened)?;
+        let (time_var, t_val) = opened.current_time;
+        let r_val = t_val.map(|t| (t + shift_to_monday) % week);
+
+        // Used slot the time falls in; an unsatisfiable witness picks slot 0
+        let selected = self.slots.as_ref().zip(t_val).map(|(slots, t)| {
+            slots
+                .iter()
+                .position(|s| {
+                    let v = if s.weekly { (t + shift_to_monday) % week } else { t };
+                    s.start <= v && v < s.end
+                })
+                .unwrap_or(0)
+        });
+
+        // Constraint 3: current_time + shift = q * week + r with 0 <= r < week
+        let q = witness(&cs, t_val.map(|t| Fr::from((t + shift_to_monday) / week)))?;
+        let r = witness(&cs, r_val.map(Fr::from))?;
+        {
+            let _ns = ns!(cs, "week_offset");
+            cs.enforce_constraint(
+                lc!() + q,
+                lc!() + (Fr::from(week), Variable::One),
+                lc!() + time_var + (Fr::from(shift_to_monday), Variable::One) - r,
+            )?;
+        }
+        enforce_bits(&cs, lc!() + r, r_val.map(u128::from), WEEK_BITS)?;
+        enforce_bits(
+            &cs,
+            lc!() + (Fr::from(week - 1), Variable::One) - r,
+            r_val.map(|r| u128::from(week - 1 - r)),
+            WEEK_BITS,
+        )?;
+
+        // Constraint 4: a one-hot selector picks one used slot
+        let mut selector_sum = lc!();
+        let mut picked_kind = lc!();
+        let mut picked_start = lc!();
+        let mut picked_end = lc!();
+        let mut picked_val = None;
+
+        for (i, (slot, kind, start, end, used)) in slots.into_iter().enumerate() {
+            let sel_val = selected.map(|s| s == i);
+            if sel_val == Some(true) {
+                picked_val = slot;
+            }
+
+            let sel = witness(&cs, sel_val.map(|s| Fr::from(s as u64)))?;
+            {
+                let _ns = ns!(cs, "selector_boolean");
+                cs.enforce_constraint(lc!() + sel, lc!() + Variable::One - sel, lc!())?;
+            }
+            {
+                let _ns = ns!(cs, "selector_used");
+                cs.enforce_constraint(lc!() + sel, lc!() + Variable::One - used, lc!())?;
+            }
+
+            let pick = |value: Variable, of: Option<u64>| -> Result<Variable, SynthesisError> {
+                let picked = witness(&cs, sel_val.zip(of).map(|(s, v)| Fr::from(if s { v } else { 0 })))?;
+                let _ns = ns!(cs, "selector_pick");
+                cs.enforce_constraint(lc!() + sel, lc!() + value, lc!() + picked)?;
+                Ok(picked)
+            };
+            picked_kind = picked_kind + pick(kind, slot.map(|s| s.weekly as u64))?;
+            picked_start = picked_start + pick(start, slot.map(|s| s.start))?;
+            picked_end = picked_end + pick(end, slot.map(|s| s.end))?;
+            selector_sum = selector_sum + sel;
+        }
+        {
+            let _ns = ns!(cs, "selector_one_hot");
+            cs.enforce_constraint(selector_sum, lc!() + Variable::One, lc!() + Variable::One)?;
+        }
+
+        // Constraint 5: the compared time is the week offset for weekly slots
+        // v = t + kind * (r - t)
+        let v_val = picked_val.zip(r_val).zip(t_val).map(|((s, r), t)| if s.weekly { r } else { t });
+        let shift = witness(
+            &cs,
+            v_val.zip(t_val).map(|(v, t)| Fr::from(v) - Fr::from(t)),
+        )?;
+        {
+            let _ns = ns!(cs, "weekly_offset_select");
+            cs.enforce_constraint(picked_kind, lc!() + r - time_var, lc!() + shift)?;
+        }
+        let v = lc!() + time_var + shift;
+
+        // Constraint 6: start <= v < end
+        let start_val = picked_val.map(|s| s.start);
+        let end_val = picked_val.map(|s| s.end);
+        enforce_bits(
+            &cs,
+            v.clone() - picked_start,
+            v_val.zip(start_val).map(|(v, s)| u128::from(v.wrapping_sub(s))),
+            TIME_BITS,
+        )?;
+        enforce_bits(
+            &cs,
+            picked_end - v - (Fr::from(1u64), Variable::One),
+            end_val.zip(v_val).map(|(e, v)| u128::from(e.wrapping_sub(v).wrapping_sub(1))),
+            TIME_BITS,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Register the default capacity
+#[cfg(feature = "prover")]
+pub(crate) fn setup() -> c_int {
+    register(DEFAULT_SCHEDULE_WINDOWS)
+}
+
+/// Register a capacity, setting up its keys in prover builds
+///
+/// A capacity that already has its keys keeps them.
+pub(crate) fn register(windows: usize) -> c_int {
+    let mut keys = match SCHEDULE_KEYS.lock() {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    let entry = keys.entry(windows).or_default();
+
+    #[cfg(feature = "prover")]
+    if entry.pk.is_none() {
+        let mut rng = seeded_rng(2u64);
+        match Groth16::<Bn254>::circuit_specific_setup(ScheduleCircuit::blank(windows), &mut rng) {
+            Ok((pk, vk)) => {
+                entry.pk = Some(pk);
+                entry.pvk = Some(PreparedVerifyingKey::from(vk));
+            }
+            Err(_) => return -1,
+        }
+    }
+    #[cfg(not(feature = "prover"))]
+    let _ = entry;
+    0
+}
+
+/// Run `f` with the proving key of a registered capacity
+#[cfg(feature = "prover")]
+pub(crate) fn with_proving_key<T>(windows: usize, f: impl FnOnce(&ProvingKey<Bn254>) -> T) -> Option<T> {
+    let keys = SCHEDULE_KEYS.lock().ok()?;
+    keys.get(&windows)?.pk.as_ref().map(f)
+}
+
+/// Registered capacities whose verifying key is present, ascending
+pub(crate) fn keyed_capacities() -> Vec<usize> {
+    match SCHEDULE_KEYS.lock() {
+        Ok(keys) => keys.iter().filter(|(_, k)| k.pvk.is_some()).map(|(&windows, _)| windows).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Drop every registered capacity and its keys
+pub(crate) fn cleanup() {
+    if let Ok(mut keys) = SCHEDULE_KEYS.lock() {
+        keys.clear();
+    }
+}
+
+/// Hex of the verifying key for `windows`, see ZK_ExportCircuitVerifyingKey
+pub(crate) fn export_verifying_key(windows: usize, vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    let vk_bytes = match SCHEDULE_KEYS.lock() {
+        Ok(keys) => match keys.get(&windows).and_then(|k| k.pvk.as_ref()) {
+            Some(pvk) => crate::vk::encode(&pvk.vk),
+            None => return ZK_ERR_CIRCUIT_MISMATCH,
+        },
+        Err(_) => return -1,
+    };
+
+    match write_cstr(vk_out, vk_out_size, &bytes_to_hex(&vk_bytes)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+
+/// Install the verifying key for `windows`; the default capacity is
+/// registered on import, others must be registered first
+#[cfg(feature = "verifier")]
+pub(crate) fn import_verifying_key(windows: usize, vk_hex: *const c_char) -> c_int {
+    if vk_hex.is_null() {
+        return -1;
+    }
+
+    let vk_hex_str = unsafe { CStr::from_ptr(vk_hex).to_str().unwrap_or("") };
+    let vk = match hex_to_bytes(vk_hex_str)
+        .ok()
+        .and_then(|bytes| crate::vk::decode_raw(&bytes).ok())
+    {
+        Some(vk) if crate::vk::public_inputs(&vk) == SCHEDULE_PUBLIC_INPUTS => vk,
+        _ => return -1,
+    };
+
+    let mut keys = match SCHEDULE_KEYS.lock() {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    let entry = match keys.get_mut(&windows) {
+        Some(entry) => entry,
+        None if windows == DEFAULT_SCHEDULE_WINDOWS => keys.entry(windows).or_default(),
+        None => return ZK_ERR_CIRCUIT_MISMATCH,
+    };
+    entry.pvk = Some(PreparedVerifyingKey::from(vk));
+    0
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Set (or with `count` 0 remove) the validity schedule of a VC blob
+///
+/// `kinds` holds ZK_WINDOW_INTERVAL or ZK_WINDOW_WEEKLY per window, `starts`
+/// and `ends` the UTC bounds. The schedule is signed, so any signature and
+/// co-signature are dropped; sign the result with ZK_SignVCBlob. Returns 0 on
+/// success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_SetValiditySchedule(
+    vc_blob: *const c_char,
+    kinds: *const c_int,
+    starts: *const i64,
+    ends: *const i64,
+    count: usize,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    if vc_blob.is_null() {
+        return -1;
+    }
+
+    let mut vc = match parse_vc_blob(vc_blob) {
+        Some(vc) => vc,
+        None => return -1,
+    };
+
+    vc.validity_schedule = if count == 0 {
+        None
+    } else {
+        let read = (
+            read_slice(kinds, count, MAX_SCHEDULE_WINDOWS),
+            read_slice(starts, count, MAX_SCHEDULE_WINDOWS),
+            read_slice(ends, count, MAX_SCHEDULE_WINDOWS),
+        );
+        let (kinds, starts, ends) = match read {
+            (Ok(kinds), Ok(starts), Ok(ends)) => (kinds, starts, ends),
+            _ => return -1,
+        };
+        let windows = kinds
+            .iter()
+            .zip(starts)
+            .zip(ends)
+            .map(|((&kind, &start), &end)| ValidityWindow::new(kind, start, end))
+            .collect::<Option<Vec<_>>>();
+        match windows.and_then(ValiditySchedule::new) {
+            Some(schedule) => Some(schedule),
+            None => return -1,
+        }
+    };
+    vc.signature = Vec::new();
+    vc.co_signature = None;
+
+    match write_cstr(vc_blob_out, vc_blob_out_size, &bytes_to_hex(&vc.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Prove that a scheduled VC blob is valid now without revealing the window
+///
+/// Performs the ZK_GenerateVCProofFromBlob pre-checks, including the
+/// schedule, and fails with -1 for a credential without a schedule or bound
+/// to a holder key. The proof uses the smallest registered capacity that
+/// holds the schedule and carries it. Returns 0 on success,
+/// ZK_ERR_CIRCUIT_MISMATCH if no registered capacity holds the schedule,
+/// ZK_ERR_BAD_SIGNATURE for a credential signed before its anchor held the
+/// schedule (blob version 9), or another negative code.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_GenerateScheduleProof(
+    vc_blob: *const c_char,
+    issuer_pubkey: *const c_char,
+    second_issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+    proof_out: *mut c_char,
+    proof_out_size: usize,
+) -> c_int {
+    if proof_out.is_null() {
+        return -1;
+    }
+
+    let (vc, issuer_key, current_time) =
+        match checked_blob(vc_blob, issuer_pubkey, second_issuer_pubkey, current_time) {
+            Ok(checked) => checked,
+            Err(code) => return code,
+        };
+    let schedule = match &vc.validity_schedule {
+        Some(schedule) => schedule,
+        None => return -1,
+    };
+    if !vc.schedule_anchored {
+        return last_error::fail(
+            ZK_ERR_BAD_SIGNATURE,
+            "credential's anchor predates its schedule; sign it again (ZK_SignVCBlob) to prove it",
+        );
+    }
+    let signature = match vc.anchor_signature() {
+        Ok(signature) => signature,
+        Err(code) => return code,
+    };
+    let (opening, blinding) = match (vc.opening(), claimed_blinding(&vc.claims)) {
+        (Some(opening), Some(blinding)) => (opening, blinding),
+        _ => return -1,
+    };
+
+    let keys = match SCHEDULE_KEYS.lock() {
+        Ok(keys) => keys,
+        Err(_) => return -1,
+    };
+    let (windows, pk) = match keys
+        .range(schedule.windows.len()..)
+        .find_map(|(&windows, k)| Some((windows, k.pk.as_ref()?)))
+    {
+        Some(found) => found,
+        None => return ZK_ERR_CIRCUIT_MISMATCH,
+    };
+
+    let format = proof::emit_format();
+    let credential = Anchored { opening, signature: &signature };
+    let (anchor, header) =
+        match AnchorWitness::of(format, &credential, issuer_key.as_bytes(), current_time, Fr::from(nonce)) {
+            Some(witness) => witness,
+            None => return -1,
+        };
+    let circuit = ScheduleCircuit::witness(windows, anchor, blinding, schedule);
+
+    #[cfg(feature = "debug-circuit")]
+    if let Err(code) = crate::satisfiability::check(circuit.clone()) {
+        return code;
+    }
+
+    let request = checkpoint::Request::capture(
+        checkpoint::ENTRY_SCHEDULE_PROOF,
+        vc_blob,
+        issuer_pubkey,
+        second_issuer_pubkey,
+        current_time,
+        nonce,
+    );
+    let params = ZkCircuitParams::schedule(windows);
+    let mut rng = match entropy::prover_rng(nonce) {
+        Ok(rng) => rng,
+        Err(code) => return code,
+    };
+    let proof = match request {
+        Some(request) => checkpoint::prove(pk, circuit, &mut rng, params, format, &request),
+        None => Groth16::<Bn254>::prove(pk, circuit, &mut rng).map_err(|_| ZK_ERR_PROVE_FAILED),
+    };
+    let proof = match proof {
+        Ok(proof) => proof,
+        Err(code) => return code,
+    };
+
+    let tagged = params.tag(&validity::encode(&header, format, &proof));
+    match write_cstr(proof_out, proof_out_size, &bytes_to_hex(&tagged)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a schedule proof for the issuer key, nonce and current time
+///
+/// The proof is checked against the key of the capacity it carries.
+/// Returns 1 if valid, 0 if invalid, ZK_ERR_CIRCUIT_MISMATCH if that
+/// capacity is not registered or has no key, ZK_ERR_DISABLED for a
+/// legacy-format proof in a strict build, ZK_ERR_UNSUPPORTED_VERSION for a
+/// format not accepted (ZK_SetAcceptedFormatVersions).
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyScheduleProof(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let started = Instant::now();
+
+    let keys = SCHEDULE_KEYS.lock().ok();
+    let proof_bytes = if issuer_pubkey.is_null() {
+        None
+    } else {
+        admission::hex_arg(proof_hex, PARAMS_PREFIX_LEN + VALIDITY_PREFIX_LEN + MAX_PROOF_LEN).ok()
+    };
+    let split = proof_bytes.as_deref().and_then(ZkCircuitParams::untag_schedule);
+    let pvk = split.as_ref().and_then(|(params, _)| {
+        let windows = (params.circuit == ZK_CIRCUIT_SCHEDULE).then_some(params.capacity as usize)?;
+        keys.as_ref()?.get(&windows)?.pvk.as_ref()
+    });
+
+    let result = match (&split, pvk) {
+        (Some((_, proof_bytes)), Some(pvk)) => {
+            verify_schedule_proof(pvk, proof_bytes, issuer_pubkey, current_time, nonce)
+        }
+        (Some(_), None) => ZK_ERR_CIRCUIT_MISMATCH,
+        (None, _) => 0,
+    };
+
+    let circuit = split.as_ref().map(|(params, _)| circuit_id(params.capacity as usize));
+    audit::record(started, audit::Verification {
+        check: "schedule-proof",
+        code: result,
+        circuit: circuit.as_deref(),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: Some(nonce),
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
+
+#[cfg(feature = "verifier")]
+fn verify_schedule_proof(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof_bytes: &[u8],
+    issuer_pubkey: *const c_char,
+    current_time: u64,
+    nonce: u64,
+) -> c_int {
+    let issuer_pubkey_bytes = match admission::text_arg(issuer_pubkey, MAX_FIELD_LEN).map(hex_to_bytes) {
+        Some(Ok(issuer)) => issuer,
+        _ => return 0,
+    };
+
+    let (header, format, proof) = match admission::decode_vc_proof(proof_bytes, Some(proof::accepted_formats())) {
+        Ok(decoded) => decoded,
+        Err(rejection) => return rejection.verify_code(),
+    };
+
+    // None unless the issuer signed the anchor
+    let public_inputs = match validity::public_inputs(&issuer_pubkey_bytes, format, nonce, current_time, &header) {
+        Some(inputs) => inputs,
+        None => return 0,
+    };
+
+    let valid = matches!(
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &proof),
+        Ok(true)
+    );
+    proof::record_outcome(format, valid);
+    valid as c_int
+}
+
+crate::unwind::entry_point! {
+/// Export the verifying key of the default capacity (hex, compressed)
+///
+/// Other capacities are exported with ZK_ExportCircuitVerifyingKey. Returns
+/// 0 on success, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small, -1 if
+/// no key is set.
+#[no_mangle]
+pub extern "C" fn ZK_ExportScheduleVerifyingKey(vk_out: *mut c_char, vk_out_size: usize) -> c_int {
+    match export_verifying_key(DEFAULT_SCHEDULE_WINDOWS, vk_out, vk_out_size) {
+        ZK_ERR_CIRCUIT_MISMATCH => -1,
+        code => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install the default-capacity key from ZK_ExportScheduleVerifyingKey
+///
+/// Returns 0 on success, -1 on failure.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_ImportScheduleVerifyingKey(vk_hex: *const c_char) -> c_int {
+    import_verifying_key(DEFAULT_SCHEDULE_WINDOWS, vk_hex)
+}
+}
@@ -0,0 +1,248 @@
+// ============================================================================
+// Schema Allowlist and Pinning
+// ============================================================================
+//
+// A verifier that only accepts some credential schemas ("employee/v2 or
+// contractor/v1") registers them with ZK_SetSchemaAllowlist. While the list
+// is non-empty, every presentation verification (ZK_VerifyPresentation,
+// ZK_VerifyPresentationDetailed, ZK_VerifyPresentationStateless,
+// ZK_CreateSessionBinding and the relay and transfer paths) requires the
+// schema to be disclosed and allowed:
+//
+//   - the disclosed credential is the VC blob passed to the call, or else
+//     the presentation's zkid.vc metadata (hex VC blob)
+//   - its issuer signature verifies under the presentation's issuer key;
+//     the signature covers the zkid:schema claim, so the schema cannot be
+//     changed without the issuer key
+//   - its zkid:schema claim is in the list
+//
+// Otherwise the call returns ZK_ERR_SCHEMA_NOT_ALLOWED, whatever the other
+// checks found. A presentation that discloses no credential, or one without
+// a schema claim or a valid signature, hides its schema and is refused the
+// same way. The VC circuit has no set-membership gadget, so the schema is
+// pinned by disclosure only.
+//
+// Verifiers announce the pin in their presentation request as the "schema"
+// metadata entry, one schema id or a comma-separated list. Wallets answer
+// with ZK_EncodePresentationForRequest, which attaches the credential as
+// zkid.vc whenever the request names schemas and refuses up front if the
+// credential's schema is not among them. ZK_MatchCredentials reads the
+// same list.
+
+use std::os::raw::{c_char, c_int};
+
+use crate::error::ZK_ERR_SCHEMA_NOT_ALLOWED;
+use crate::presentation::Presentation;
+use crate::stateless::VC_METADATA;
+#[cfg(feature = "prover")]
+use {
+    crate::credential::{parse_vc_blob, read_claims},
+    crate::error::ZK_ERR_CORRUPT,
+    crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN},
+    crate::inbox::PresentationRequest,
+    crate::{bytes_to_hex, validity},
+    std::ffi::CStr,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::admission::text_arg,
+    crate::ffi::{read_slice, MAX_ARRAY_LEN, MAX_FIELD_LEN},
+    crate::{hex_to_bytes, VerifiableCredential},
+    ed25519_dalek::VerifyingKey,
+    std::collections::HashSet,
+    crate::sync::Mutex,
+};
+
+/// Request metadata naming the schemas a verifier accepts
+pub const SCHEMA_METADATA: &str = "schema";
+
+#[cfg(feature = "verifier")]
+static ALLOWED_SCHEMAS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Schema ids a request accepts; empty if it names none
+#[cfg(feature = "prover")]
+pub fn request_schemas(request: &PresentationRequest) -> Vec<&str> {
+    request
+        .metadata_value(SCHEMA_METADATA)
+        .map(|list| list.split(',').map(str::trim).filter(|id| !id.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Replace the allowlist; an empty list turns pinning off
+#[cfg(feature = "verifier")]
+pub fn set_allowed_schemas<'a>(schemas: impl IntoIterator<Item = &'a str>) {
+    let schemas: HashSet<String> = schemas.into_iter().map(str::to_string).collect();
+    if let Ok(mut guard) = ALLOWED_SCHEMAS.lock() {
+        *guard = (!schemas.is_empty()).then_some(schemas);
+    }
+}
+
+/// The allowlist, sorted; empty while pinning is off
+#[cfg(feature = "verifier")]
+pub fn allowed_schemas() -> Vec<String> {
+    let mut schemas: Vec<String> = ALLOWED_SCHEMAS
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .map(|schemas| schemas.into_iter().collect())
+        .unwrap_or_default();
+    schemas.sort();
+    schemas
+}
+
+/// The credential a presentation discloses: `vc` if given, else its zkid.vc
+#[cfg(feature = "verifier")]
+fn disclosed(presentation: &Presentation, vc: Option<&VerifiableCredential>) -> Option<VerifiableCredential> {
+    if let Some(vc) = vc {
+        return Some(vc.clone());
+    }
+    let blob = hex_to_bytes(presentation.metadata_value(VC_METADATA)?).ok()?;
+    VerifiableCredential::from_bytes(&blob)
+}
+
+/// Apply the allowlist to a verification `result`
+///
+/// Returns `result` unchanged while pinning is off or the schema the
+/// presentation discloses is allowed, ZK_ERR_SCHEMA_NOT_ALLOWED otherwise.
+#[cfg(feature = "verifier")]
+pub(crate) fn gate(result: c_int, presentation: Option<&Presentation>, vc: Option<&VerifiableCredential>) -> c_int {
+    let guard = match ALLOWED_SCHEMAS.lock() {
+        Ok(guard) => guard,
+        Err(_) => return ZK_ERR_SCHEMA_NOT_ALLOWED,
+    };
+    let Some(allowed) = guard.as_ref() else {
+        return result;
+    };
+
+    let allows = presentation.is_some_and(|p| {
+        let issuer = p
+            .issuer_pubkey
+            .as_slice()
+            .try_into()
+            .ok()
+            .and_then(|bytes| VerifyingKey::from_bytes(bytes).ok());
+        match (issuer, disclosed(p, vc)) {
+            (Some(issuer), Some(vc)) => {
+                vc.verify_signature(&issuer)
+                    && p.commits_to(&vc)
+                    && vc.schema_id().is_some_and(|schema| allowed.contains(schema))
+            }
+            _ => false,
+        }
+    });
+    if allows {
+        result
+    } else {
+        ZK_ERR_SCHEMA_NOT_ALLOWED
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Accept only presentations disclosing one of `schemas` (see schema.rs)
+///
+/// Replaces the previous list; a `count` of 0 turns pinning off. Returns 0
+/// on success, -1 on NULL, an empty or non-UTF-8 schema id, or more than
+/// MAX_ARRAY_LEN ids.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_SetSchemaAllowlist(schemas: *const *const c_char, count: usize) -> c_int {
+    let ids = match read_slice(schemas, count, MAX_ARRAY_LEN) {
+        Ok(ids) => ids,
+        Err(e) => return e.code(),
+    };
+    let ids: Option<Vec<&str>> = ids
+        .iter()
+        .map(|&id| text_arg(id, MAX_FIELD_LEN).filter(|id| !id.is_empty()))
+        .collect();
+    match ids {
+        Some(ids) => {
+            set_allowed_schemas(ids);
+            0
+        }
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Answer a presentation request with a hex presentation
+///
+/// Takes the issuer key and nonce from `request` (binary or hex text) and
+/// adds its "audience" unless `metadata` gives one. If the request names
+/// schemas, the credential `vc_blob` (hex) is attached as zkid.vc so the
+/// verifier can check its schema; it may be NULL otherwise. Returns 0 on
+/// success, ZK_ERR_SCHEMA_NOT_ALLOWED if the credential's schema is not
+/// one the request names, ZK_ERR_CORRUPT if the request does not decode,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on a NULL or malformed proof, credential or
+/// metadata.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_EncodePresentationForRequest(
+    proof_hex: *const c_char,
+    request: *const c_char,
+    request_len: usize,
+    vc_blob: *const c_char,
+    metadata_keys: *const *const c_char,
+    metadata_values: *const *const c_char,
+    metadata_count: usize,
+    presentation_out: *mut c_char,
+    presentation_out_size: usize,
+) -> c_int {
+    if proof_hex.is_null() || request.is_null() {
+        return -1;
+    }
+    let request = match read_bytes(request, request_len, MAX_BLOB_LEN) {
+        Ok(bytes) => match PresentationRequest::from_wire(bytes) {
+            Some(request) => request,
+            None => return ZK_ERR_CORRUPT,
+        },
+        Err(e) => return e.code(),
+    };
+
+    let proof_hex = unsafe { CStr::from_ptr(proof_hex) }.to_str().unwrap_or("");
+    let (header, format, proof) = match crate::hex_to_bytes(proof_hex).ok().and_then(|bytes| validity::decode(&bytes).ok()) {
+        Some(decoded) => decoded,
+        None => return -1,
+    };
+    let mut metadata = match read_claims(metadata_keys, metadata_values, metadata_count) {
+        Some(metadata) => metadata,
+        None => return -1,
+    };
+
+    if let Some(audience) = request.metadata_value("audience") {
+        if !metadata.iter().any(|(key, _)| key == "audience") {
+            metadata.push(("audience".to_string(), audience.to_string()));
+        }
+    }
+
+    let schemas = request_schemas(&request);
+    if !schemas.is_empty() {
+        let vc = match (!vc_blob.is_null()).then(|| parse_vc_blob(vc_blob)).flatten() {
+            Some(vc) => vc,
+            None => return -1,
+        };
+        if !vc.schema_id().is_some_and(|schema| schemas.contains(&schema)) {
+            return ZK_ERR_SCHEMA_NOT_ALLOWED;
+        }
+        metadata.retain(|(key, _)| key != VC_METADATA);
+        metadata.push((VC_METADATA.to_string(), bytes_to_hex(&vc.to_bytes())));
+    }
+
+    let presentation = Presentation {
+        format,
+        proof,
+        header,
+        issuer_pubkey: request.issuer_pubkey,
+        nonce: request.nonce,
+        metadata,
+    };
+    match write_cstr(presentation_out, presentation_out_size, &bytes_to_hex(&presentation.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
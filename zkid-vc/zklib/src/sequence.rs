@@ -0,0 +1,153 @@
+// ============================================================================
+// Issuer Sequence Numbers
+// ============================================================================
+//
+// For audit reconciliation an issuer can number every credential it signs.
+// With sequencing enabled (ZK_SetIssuerSequencing), every signer that
+// returns the claims it signs sets the `zkid:sequence` claim to the next
+// number of the signing key before signing, so the number is covered by the
+// signature like any other claim:
+//
+//   ZK_SignVCBlob, ZK_ReissueVC   the credential's claims
+//   ZK_SignClaimGroup             the signed group's claims, numbered under
+//                                 the group issuer's key
+//   ZK_SignVCJwt                  an unsigned blob's claims; a signed blob
+//                                 keeps the number it was signed with
+//
+// ZK_CoSignVC adds a signature to a credential already numbered by its
+// first issuer. A signer that cannot set the claim refuses to sign while
+// sequencing is on, with ZK_ERR_SEQUENCE_UNSUPPORTED, rather than leave a
+// gap in the trail: ZK_SignVC and ZK_SignVCWithClaims return only a
+// signature over the caller's fields, ZK_SignVCJwt cannot renumber a blob
+// signed without a number, and the threshold signers must all sign the
+// same message, so they cannot each reserve one.
+//
+// Counters are kept per issuer key, under SHA-256 of the public key, in the
+// storage backend (value: high-water mark u64 little-endian), so they survive
+// restarts with a persistent backend. A number is persisted before it is
+// used: a crash between reserving and signing leaves a gap, never a
+// duplicate. Reservations are serialized in-process; several processes
+// signing with the same key must not share one backend.
+
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::sync::Mutex;
+use std::sync::Arc;
+
+use crate::did;
+use crate::storage::{storage, Storage, StorageError};
+#[cfg(feature = "prover")]
+use crate::VerifiableCredential;
+
+const SEQUENCE_PREFIX: &[u8] = b"sequence/";
+
+/// Reserved claim carrying the issuer sequence number (decimal)
+pub const SEQUENCE_CLAIM: &str = "zkid:sequence";
+
+static SEQUENCING: AtomicBool = AtomicBool::new(false);
+
+// Serializes read + increment + persist of every counter
+static RESERVE_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn sequencing_enabled() -> bool {
+    SEQUENCING.load(Ordering::Relaxed)
+}
+
+/// Persistent, gap-tolerant counter of one issuer key
+pub struct IssuerSequencer {
+    storage: Arc<dyn Storage>,
+    key: Vec<u8>,
+}
+
+impl IssuerSequencer {
+    pub fn new(storage: Arc<dyn Storage>, issuer_pubkey: &[u8; 32]) -> Self {
+        let mut key = SEQUENCE_PREFIX.to_vec();
+        key.extend_from_slice(&Sha256::digest(issuer_pubkey));
+        Self { storage, key }
+    }
+
+    /// Highest number reserved so far, 0 before the first
+    pub fn current(&self) -> Result<u64, StorageError> {
+        match self.storage.get(&self.key)? {
+            Some(value) => Ok(u64::from_le_bytes(value.as_slice().try_into().map_err(|_| StorageError::Backend)?)),
+            None => Ok(0),
+        }
+    }
+
+    /// Persist and return the next number
+    pub fn reserve(&self) -> Result<u64, StorageError> {
+        let _guard = RESERVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let next = self.current()?.checked_add(1).ok_or(StorageError::Backend)?;
+        self.storage.put(&self.key, &next.to_le_bytes())?;
+        Ok(next)
+    }
+}
+
+/// Reserve the next number of an issuer key in the global storage backend
+#[cfg(feature = "prover")]
+pub fn next(issuer_pubkey: &[u8; 32]) -> Result<u64, StorageError> {
+    IssuerSequencer::new(storage(), issuer_pubkey).reserve()
+}
+
+/// Reserve the signer's next number and set it as the sequence claim
+#[cfg(feature = "prover")]
+pub fn stamp(vc: &mut VerifiableCredential, issuer_pubkey: &[u8; 32]) -> Result<u64, StorageError> {
+    stamp_claims(&mut vc.claims, issuer_pubkey)
+}
+
+/// As `stamp`, for a claim list other than a credential's (a claim group)
+#[cfg(feature = "prover")]
+pub fn stamp_claims(claims: &mut Vec<(String, String)>, issuer_pubkey: &[u8; 32]) -> Result<u64, StorageError> {
+    let sequence = next(issuer_pubkey)?;
+    claims.retain(|(key, _)| key != SEQUENCE_CLAIM);
+    claims.push((SEQUENCE_CLAIM.to_string(), sequence.to_string()));
+    Ok(sequence)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Enable or disable sequence numbers on signed credentials (see above)
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_SetIssuerSequencing(enabled: c_int) -> c_int {
+    SEQUENCING.store(enabled != 0, Ordering::Relaxed);
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// High-water mark of an issuer key's sequence (hex public key)
+///
+/// Writes the highest number reserved so far, 0 if the key never signed with
+/// sequencing enabled. Numbers lost to a crash count as reserved. Returns 0
+/// on success, ZK_ERR_STORAGE if the backend failed, -1 on bad input.
+#[no_mangle]
+pub extern "C" fn ZK_GetIssuerSequence(issuer_pubkey: *const c_char, sequence_out: *mut u64) -> c_int {
+    if issuer_pubkey.is_null() || sequence_out.is_null() {
+        return -1;
+    }
+
+    let key_str = unsafe { CStr::from_ptr(issuer_pubkey) }.to_str().unwrap_or("");
+    let key: [u8; 32] = match did::public_key_bytes(key_str).map(|bytes| bytes.try_into()) {
+        Ok(Ok(key)) => key,
+        Err(e) => return e.code_or(-1),
+        _ => return -1,
+    };
+
+    match IssuerSequencer::new(storage(), &key).current() {
+        Ok(sequence) => {
+            unsafe {
+                *sequence_out = sequence;
+            }
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,394 @@
+// ============================================================================
+// Session Bindings (continuous verification)
+// ============================================================================
+//
+// Zero-trust gateways re-validate a session's credential every few minutes.
+// Instead of asking for a new presentation each time, the first full
+// verification (ZK_CreateSessionBinding) yields
+//
+//   - a random session secret, handed to the client with the session, and
+//   - a binding the gateway keeps: what the re-checks need, MACed with the
+//     verifier secret
+//
+//   "ZKSB" | version u8 | SHA-256(session_secret) [32] | credential_id
+//   | issuer_public_key [32] | issue_date i64 | expiry_date i64 | has_schedule u8 | [schedule]
+//   | created_at u64 | expires_at u64 | renew_before u64
+//   | revocation_epoch u64 | max_epoch_advance u64
+//   | HMAC-SHA256(verifier_secret, "zkid:session-binding" | all of the above)
+//
+// (hex). The binding holds the credential identifier (a hash), its dates
+// and validity schedule, never the holder id or claims. ZK_RefreshSession
+// re-evaluates only the time policy and revocation status against it, no
+// proof is checked:
+//
+//   0                      the MAC or session secret does not match, the
+//                          credential is outside its dates or schedule, it
+//                          is revoked (or superseded, see
+//                          ZK_SetRejectSuperseded), or the revocation epoch
+//                          went backwards
+//   ZK_SESSION_REPRESENT   still valid, but a fresh full presentation is
+//                          due: the binding expired, the credential expires
+//                          within renew_before, or the revocation epoch
+//                          advanced more than max_epoch_advance
+//   1                      valid
+//
+// The revocation epoch is the caller's version of the revocation data it
+// applies (e.g. the sequence number of the last revocation feed); the
+// registry itself is always queried, and the published revocation list
+// (ZK_UpdateRevocationData) if it is the issuer's. A binding expires at the
+// earlier of created_at + lifetime and the credential's expiry. Version 1
+// bindings, which did not record the issuer, no longer open: those sessions
+// present again.
+
+use ed25519_dalek::PUBLIC_KEY_LENGTH;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::time::Instant;
+
+use crate::audit;
+use crate::clock;
+use crate::credential::{parse_vc_blob, parse_verifying_key};
+use crate::dates;
+use crate::ffi::{read_bytes, write_cstr, MAX_BLOB_LEN};
+use crate::presentation::{verify_detailed, Presentation};
+use crate::schedule::ValiditySchedule;
+use crate::schema;
+use crate::validation::revocation_status;
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, hex_to_bytes};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// ZK_RefreshSession: valid, but a fresh presentation is due
+pub const ZK_SESSION_REPRESENT: c_int = 2;
+
+/// Length of the session secret in bytes
+pub const SESSION_SECRET_LEN: usize = 32;
+
+const BINDING_MAGIC: &[u8; 4] = b"ZKSB";
+const BINDING_VERSION: u8 = 2;
+const MAC_DOMAIN: &[u8] = b"zkid:session-binding";
+const MAC_LEN: usize = 32;
+
+/// How long a binding carries a session (C layout)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkSessionPolicy {
+    /// Seconds after creation the binding expires
+    pub lifetime_secs: u64,
+    /// Ask for a fresh presentation this many seconds before the
+    /// credential expires
+    pub renew_before_secs: u64,
+    /// Revocation epochs the binding may fall behind before a fresh
+    /// presentation is due
+    pub max_epoch_advance: u64,
+}
+
+/// Re-check state of a verified session
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionBinding {
+    pub session_hash: [u8; 32],
+    pub credential_id: String,
+    /// Key that signed the credential, for the published revocation list
+    pub issuer_public_key: [u8; PUBLIC_KEY_LENGTH],
+    pub issue_date: i64,
+    pub expiry_date: i64,
+    pub validity_schedule: Option<ValiditySchedule>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub renew_before: u64,
+    pub revocation_epoch: u64,
+    pub max_epoch_advance: u64,
+}
+
+fn session_hash(session_secret: &[u8]) -> [u8; 32] {
+    Sha256::digest(session_secret).into()
+}
+
+fn binding_mac(verifier_secret: &[u8], body: &[u8]) -> Option<HmacSha256> {
+    if verifier_secret.is_empty() {
+        return None;
+    }
+    let mut mac = HmacSha256::new_from_slice(verifier_secret).ok()?;
+    mac.update(MAC_DOMAIN);
+    mac.update(body);
+    Some(mac)
+}
+
+impl SessionBinding {
+    fn encode_body(&self) -> Vec<u8> {
+        let mut out = BINDING_MAGIC.to_vec();
+        out.push(BINDING_VERSION);
+        out.extend_from_slice(&self.session_hash);
+        put_bytes(&mut out, self.credential_id.as_bytes());
+        out.extend_from_slice(&self.issuer_public_key);
+        out.extend_from_slice(&self.issue_date.to_le_bytes());
+        out.extend_from_slice(&self.expiry_date.to_le_bytes());
+        match &self.validity_schedule {
+            Some(schedule) => {
+                out.push(1);
+                schedule.encode(&mut out);
+            }
+            None => out.push(0),
+        }
+        for value in [
+            self.created_at,
+            self.expires_at,
+            self.renew_before,
+            self.revocation_epoch,
+            self.max_epoch_advance,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    /// Binary binding, MACed with `verifier_secret` (None if it is empty)
+    pub fn seal(&self, verifier_secret: &[u8]) -> Option<Vec<u8>> {
+        let mut out = self.encode_body();
+        let tag = binding_mac(verifier_secret, &out)?.finalize().into_bytes();
+        out.extend_from_slice(&tag);
+        Some(out)
+    }
+
+    /// Decode a binding whose MAC verifies under `verifier_secret`
+    pub fn open(verifier_secret: &[u8], data: &[u8]) -> Option<Self> {
+        let body_len = data.len().checked_sub(MAC_LEN)?;
+        let (body, tag) = data.split_at(body_len);
+        // Constant-time comparison
+        binding_mac(verifier_secret, body)?.verify_slice(tag).ok()?;
+
+        let mut r = Reader::new(body);
+        if r.take(BINDING_MAGIC.len())? != BINDING_MAGIC || r.u8()? != BINDING_VERSION {
+            return None;
+        }
+        let session_hash = r.take(32)?.try_into().ok()?;
+        let credential_id = r.string()?;
+        let issuer_public_key = r.take(PUBLIC_KEY_LENGTH)?.try_into().ok()?;
+        let issue_date = r.u64()? as i64;
+        let expiry_date = r.u64()? as i64;
+        let validity_schedule = match r.u8()? {
+            0 => None,
+            1 => Some(ValiditySchedule::decode(&mut r)?),
+            _ => return None,
+        };
+        let binding = Self {
+            session_hash,
+            credential_id,
+            issuer_public_key,
+            issue_date,
+            expiry_date,
+            validity_schedule,
+            created_at: r.u64()?,
+            expires_at: r.u64()?,
+            renew_before: r.u64()?,
+            revocation_epoch: r.u64()?,
+            max_epoch_advance: r.u64()?,
+        };
+        r.is_empty().then_some(binding)
+    }
+
+    /// Re-check the session at `now` (time source applied) against the
+    /// caller's revocation epoch; see the module comment for the results
+    pub fn refresh(&self, session_secret: &[u8], now: u64, revocation_epoch: u64) -> c_int {
+        // Not secret: the hash only ties the binding to its session
+        if session_hash(session_secret) != self.session_hash {
+            return 0;
+        }
+
+        let active = dates::valid_range(self.issue_date, self.expiry_date)
+            && dates::is_active(now, self.issue_date, self.expiry_date)
+            && self.validity_schedule.as_ref().is_none_or(|schedule| schedule.contains(now));
+        if !active || revocation_epoch < self.revocation_epoch {
+            return 0;
+        }
+        match revocation_status(Some(&self.issuer_public_key), &self.credential_id) {
+            1 => {}
+            code => return code,
+        }
+
+        // is_active passed, so expiry_date >= now >= 0
+        let remaining = self.expiry_date as u64 - now;
+        if now > self.expires_at
+            || remaining < self.renew_before
+            || revocation_epoch - self.revocation_epoch > self.max_epoch_advance
+        {
+            return ZK_SESSION_REPRESENT;
+        }
+        1
+    }
+}
+
+/// Non-empty bytes of a hex string
+fn read_hex(hex: *const c_char) -> Option<Vec<u8>> {
+    if hex.is_null() {
+        return None;
+    }
+    let text = unsafe { CStr::from_ptr(hex) }.to_str().ok()?;
+    hex_to_bytes(text).ok().filter(|secret| !secret.is_empty())
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Verify a presentation in full and bind a session to it
+///
+/// Runs the checks of ZK_VerifyPresentationDetailed with `vc_blob` and
+/// `issuer_public_key` required (`second_issuer_public_key` may be NULL, no
+/// audience check). If all pass, writes a fresh session secret (hex) to
+/// `session_secret_out` and the binding (hex), MACed with `verifier_secret`
+/// (hex), to `binding_out`. `revocation_epoch` is the caller's current
+/// revocation epoch. Returns 1 if the presentation verified and the binding
+/// was written, 0 if it did not verify, ZK_ERR_SCHEMA_NOT_ALLOWED if the
+/// credential's schema is outside the allowlist (ZK_SetSchemaAllowlist),
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL or malformed input.
+#[no_mangle]
+pub extern "C" fn ZK_CreateSessionBinding(
+    verifier_secret: *const c_char,
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    vc_blob: *const c_char,
+    issuer_public_key: *const c_char,
+    second_issuer_public_key: *const c_char,
+    current_time: u64,
+    revocation_epoch: u64,
+    policy: *const ZkSessionPolicy,
+    session_secret_out: *mut c_char,
+    session_secret_out_size: usize,
+    binding_out: *mut c_char,
+    binding_out_size: usize,
+) -> c_int {
+    let started = Instant::now();
+    let policy = match unsafe { policy.as_ref() } {
+        Some(policy) => *policy,
+        None => return -1,
+    };
+    let verifier_secret = match read_hex(verifier_secret) {
+        Some(secret) => secret,
+        None => return -1,
+    };
+    if presentation_blob.is_null() || vc_blob.is_null() || issuer_public_key.is_null() {
+        return -1;
+    }
+    let data = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let presentation = Presentation::from_wire(data);
+    let now = match clock::now(current_time) {
+        Ok(now) => now,
+        Err(code) => return code,
+    };
+
+    let pvk_guard = crate::VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_deref());
+    let report = verify_detailed(
+        presentation.as_ref(),
+        pvk,
+        vc_blob,
+        issuer_public_key,
+        second_issuer_public_key,
+        ptr::null(),
+        now,
+    );
+    let vc = parse_vc_blob(vc_blob);
+    let result = schema::gate(report.accepted() as c_int, presentation.as_ref(), vc.as_ref());
+
+    audit::record(started, audit::Verification {
+        check: "session-binding",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk.map(|pvk| &pvk.vk),
+        nonce: presentation.as_ref().map(|p| p.nonce),
+        audience: presentation.as_ref().and_then(|p| p.metadata_value("audience")),
+        predicate: None,
+    });
+    drop(pvk_guard);
+
+    // The signature verified under the issuer key, so it parses
+    let (vc, issuer) = match (vc, parse_verifying_key(issuer_public_key)) {
+        (Some(vc), Some(issuer)) if result == 1 => (vc, issuer),
+        _ => return result,
+    };
+
+    let mut session_secret = [0u8; SESSION_SECRET_LEN];
+    OsRng.fill_bytes(&mut session_secret);
+    let binding = SessionBinding {
+        session_hash: session_hash(&session_secret),
+        credential_id: vc.credential_id(),
+        issuer_public_key: issuer.to_bytes(),
+        issue_date: vc.issue_date,
+        expiry_date: vc.expiry_date,
+        validity_schedule: vc.validity_schedule.clone(),
+        created_at: now,
+        expires_at: now.saturating_add(policy.lifetime_secs).min(vc.expiry_date as u64),
+        renew_before: policy.renew_before_secs,
+        revocation_epoch,
+        max_epoch_advance: policy.max_epoch_advance,
+    };
+    let sealed = match binding.seal(&verifier_secret) {
+        Some(sealed) => sealed,
+        None => return -1,
+    };
+
+    if let Err(e) = write_cstr(session_secret_out, session_secret_out_size, &bytes_to_hex(&session_secret)) {
+        return e.code();
+    }
+    match write_cstr(binding_out, binding_out_size, &bytes_to_hex(&sealed)) {
+        Ok(_) => 1,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Re-check a session without a new presentation
+///
+/// `binding` and `session_secret` are the hex outputs of
+/// ZK_CreateSessionBinding, `verifier_secret` (hex) the secret it was made
+/// with; `current_time` goes through the configured time source and
+/// `revocation_epoch` is the caller's current revocation epoch. Returns 1 if
+/// the session stays valid, ZK_SESSION_REPRESENT if it is valid but a fresh
+/// presentation is due, 0 if it is not valid (including a forged, altered
+/// or foreign binding), ZK_ERR_STORAGE if the revocation lookup failed or
+/// ZK_ERR_TIME_MISMATCH from the time source.
+#[no_mangle]
+pub extern "C" fn ZK_RefreshSession(
+    verifier_secret: *const c_char,
+    binding: *const c_char,
+    session_secret: *const c_char,
+    current_time: u64,
+    revocation_epoch: u64,
+) -> c_int {
+    let started = Instant::now();
+    let (verifier_secret, binding, session_secret) =
+        match (read_hex(verifier_secret), read_hex(binding), read_hex(session_secret)) {
+            (Some(v), Some(b), Some(s)) => (v, b, s),
+            _ => return 0,
+        };
+
+    let result = match (SessionBinding::open(&verifier_secret, &binding), clock::now(current_time)) {
+        (Some(binding), Ok(now)) => binding.refresh(&session_secret, now, revocation_epoch),
+        (None, _) => 0,
+        (_, Err(code)) => code,
+    };
+
+    audit::record(started, audit::Verification {
+        check: "session-refresh",
+        code: result,
+        circuit: None,
+        vk: None,
+        nonce: None,
+        audience: None,
+        predicate: None,
+    });
+
+    result
+}
+}
@@ -0,0 +1,232 @@
+// ============================================================================
+// Artifact Sizes
+// ============================================================================
+//
+// Exact byte lengths of the artifacts crossing the C API, per circuit and
+// proof format version, so hosts do not hard-code them. Every length is
+// computed from the serialization code (arkworks serialized sizes, the proof
+// envelope, the Ed25519 and SHA-256 constants); none is maintained by hand.
+//
+// *_HEX items are C buffer sizes: two characters per byte plus the
+// terminating NUL.
+
+use ark_bn254::{Bn254, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_groth16::{PreparedVerifyingKey, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+use ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use sha2::{Digest, Sha256};
+use std::os::raw::c_int;
+
+use crate::encoding::{PointEncoding, ZkSerialize};
+use crate::error::ZK_ERR_DISABLED;
+use crate::grace::GRACE_PREFIX_LEN;
+use crate::params::PARAMS_PREFIX_LEN;
+use crate::predicate::PREDICATE_MAX_MEMBERS;
+use crate::presentation::PRESENTATION_HEADER_LEN;
+use crate::proof::{self, PROOF_FORMATS, PROOF_FORMAT_LEGACY};
+use crate::validity::VALIDITY_PREFIX_LEN;
+
+/// The VC circuit: proves knowledge of an issuer-signed credential
+pub const ZK_CIRCUIT_VC: c_int = 1;
+
+/// Circuit id of the VC circuit
+pub const VC_CIRCUIT: &str = "zkid-vc/v7";
+
+/// The schedule circuit: a scheduled VC is valid at the public time
+pub const ZK_CIRCUIT_SCHEDULE: c_int = 2;
+
+/// The group-claim circuit: a disclosed claim of a composite credential is
+/// asserted by its group's issuer
+pub const ZK_CIRCUIT_GROUP_CLAIM: c_int = 3;
+
+/// The ballot circuit: one vote per credential and poll, bound to the
+/// ballot content
+pub const ZK_CIRCUIT_BALLOT: c_int = 4;
+
+/// The derived-age circuit: a birthdate claim lies at least N years before
+/// the public time
+pub const ZK_CIRCUIT_DERIVED_AGE: c_int = 5;
+
+/// The holder-bound circuit: a credential bound to the holder's current
+/// device key, directly or through re-binding certificates
+pub const ZK_CIRCUIT_HOLDER_BOUND: c_int = 6;
+
+/// The grace circuit: a credential expired at most the carried grace
+/// before the public time
+pub const ZK_CIRCUIT_GRACE: c_int = 7;
+
+/// The predicate circuit: a signed claim of a credential satisfies the
+/// predicate whose statement is public
+pub const ZK_CIRCUIT_PREDICATE: c_int = 8;
+
+/// The claim-disclosure circuit: a disclosed claim lies under a credential's
+/// signed claims root
+pub const ZK_CIRCUIT_CLAIM_DISCLOSURE: c_int = 9;
+
+/// The range-claim circuit: a numeric claim under a credential's signed
+/// claims root is at least a public threshold
+pub const ZK_CIRCUIT_RANGE_CLAIM: c_int = 10;
+
+/// The bound-VC circuit: the VC circuit for a credential signed to a holder
+/// public key, proven with the holder's secret
+pub const ZK_CIRCUIT_BOUND_VC: c_int = 11;
+
+/// The one-time VC circuit: the bound-VC circuit with the holder's
+/// nullifier for a presentation context
+pub const ZK_CIRCUIT_ONE_TIME_VC: c_int = 12;
+
+/// The non-revoked VC circuit: the VC circuit with the credential outside
+/// the issuer's revocation tree under a public root
+pub const ZK_CIRCUIT_NON_REVOKED_VC: c_int = 13;
+
+/// Public inputs of the VC circuit: issuer key hash, nonce, current time,
+/// anchor
+pub const VC_PUBLIC_INPUTS: usize = 4;
+
+/// Public inputs of the schedule circuit: those of the VC circuit
+pub const SCHEDULE_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS;
+
+/// Public inputs of the group-claim circuit: those of the VC circuit over
+/// the group anchor, claim key, claim value
+pub const GROUP_CLAIM_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 2;
+
+/// Public inputs of the ballot circuit: those of the VC circuit with the
+/// ballot hash for the nonce, poll scope, nullifier
+pub const BALLOT_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 2;
+
+/// Public inputs of the derived-age circuit: those of the VC circuit, claim
+/// key, threshold seconds
+pub const DERIVED_AGE_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 2;
+
+/// Public inputs of the holder-bound circuit: those of the VC circuit,
+/// holder key claim value, certificate chain hash
+pub const HOLDER_BOUND_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 2;
+
+/// Public inputs of the grace circuit: those of the VC circuit, grace
+/// seconds
+pub const GRACE_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 1;
+
+/// Public inputs of the predicate circuit: those of the VC circuit, claim
+/// key, integer, negate, sign, bound, members
+pub const PREDICATE_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 5 + PREDICATE_MAX_MEMBERS;
+
+/// Public inputs of the claim-disclosure circuit: those of the VC circuit,
+/// claims root, claim key, claim value
+pub const CLAIM_DISCLOSURE_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 3;
+
+/// Public inputs of the range-claim circuit: those of the VC circuit, claims
+/// root, claim key, threshold
+pub const RANGE_CLAIM_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 3;
+
+/// Public inputs of the bound-VC circuit: those of the VC circuit
+pub const BOUND_VC_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS;
+
+/// Public inputs of the one-time VC circuit: those of the VC circuit,
+/// context, nullifier
+pub const ONE_TIME_VC_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 2;
+
+/// Public inputs of the non-revoked VC circuit: those of the VC circuit,
+/// revocation root
+pub const NON_REVOKED_VC_PUBLIC_INPUTS: usize = VC_PUBLIC_INPUTS + 1;
+
+pub const ZK_SIZE_PROOF: c_int = 1;
+pub const ZK_SIZE_PROOF_UNCOMPRESSED: c_int = 2;
+pub const ZK_SIZE_VERIFYING_KEY: c_int = 3;
+pub const ZK_SIZE_PREPARED_VERIFYING_KEY: c_int = 4;
+pub const ZK_SIZE_PRESENTATION_HEADER: c_int = 5;
+pub const ZK_SIZE_HASH: c_int = 6;
+pub const ZK_SIZE_SIGNATURE: c_int = 7;
+pub const ZK_SIZE_PUBKEY: c_int = 8;
+pub const ZK_SIZE_PROOF_HEX: c_int = 9;
+pub const ZK_SIZE_VERIFYING_KEY_HEX: c_int = 10;
+pub const ZK_SIZE_HASH_HEX: c_int = 11;
+pub const ZK_SIZE_SIGNATURE_HEX: c_int = 12;
+pub const ZK_SIZE_PUBKEY_HEX: c_int = 13;
+
+/// A key with the circuit's shape; the point values do not affect its size
+fn verifying_key(public_inputs: usize) -> VerifyingKey<Bn254> {
+    VerifyingKey {
+        alpha_g1: G1Affine::generator(),
+        beta_g2: G2Affine::generator(),
+        gamma_g2: G2Affine::generator(),
+        delta_g2: G2Affine::generator(),
+        gamma_abc_g1: vec![G1Affine::generator(); public_inputs + 1],
+    }
+}
+
+/// Buffer size for the hex form of `len` bytes
+pub fn hex_buffer(len: usize) -> usize {
+    len * 2 + 1
+}
+
+/// Byte length of `item` for `circuit` and proof format `version`
+///
+/// None for unknown items, circuits and versions.
+pub fn size_of(item: c_int, circuit: c_int, version: u8) -> Option<usize> {
+    // Every proof carries the validity header, schedule proofs behind their
+    // circuit parameters and grace proofs behind their grace
+    let (public_inputs, proof_prefix) = match circuit {
+        ZK_CIRCUIT_VC => (VC_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_SCHEDULE => (SCHEDULE_PUBLIC_INPUTS, PARAMS_PREFIX_LEN + VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_GROUP_CLAIM => (GROUP_CLAIM_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_BALLOT => (BALLOT_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_DERIVED_AGE => (DERIVED_AGE_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_HOLDER_BOUND => (HOLDER_BOUND_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_GRACE => (GRACE_PUBLIC_INPUTS, GRACE_PREFIX_LEN + VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_PREDICATE => (PREDICATE_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_CLAIM_DISCLOSURE => (CLAIM_DISCLOSURE_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_RANGE_CLAIM => (RANGE_CLAIM_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_BOUND_VC => (BOUND_VC_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_ONE_TIME_VC => (ONE_TIME_VC_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        ZK_CIRCUIT_NON_REVOKED_VC => (NON_REVOKED_VC_PUBLIC_INPUTS, VALIDITY_PREFIX_LEN),
+        _ => return None,
+    };
+    if !PROOF_FORMATS.contains(&version) {
+        return None;
+    }
+
+    let size = match item {
+        ZK_SIZE_PROOF => proof_prefix + proof::encoded_len(version, PointEncoding::Compressed)?,
+        ZK_SIZE_PROOF_UNCOMPRESSED => proof_prefix + proof::encoded_len(version, PointEncoding::Uncompressed)?,
+        ZK_SIZE_VERIFYING_KEY => verifying_key(public_inputs).zk_serialized_size(PointEncoding::Compressed),
+        ZK_SIZE_PREPARED_VERIFYING_KEY => {
+            PreparedVerifyingKey::from(verifying_key(public_inputs)).compressed_size()
+        }
+        ZK_SIZE_PRESENTATION_HEADER => PRESENTATION_HEADER_LEN,
+        ZK_SIZE_HASH => Sha256::output_size(),
+        ZK_SIZE_SIGNATURE => SIGNATURE_LENGTH,
+        ZK_SIZE_PUBKEY => PUBLIC_KEY_LENGTH,
+        ZK_SIZE_PROOF_HEX => hex_buffer(size_of(ZK_SIZE_PROOF, circuit, version)?),
+        ZK_SIZE_VERIFYING_KEY_HEX => hex_buffer(size_of(ZK_SIZE_VERIFYING_KEY, circuit, version)?),
+        ZK_SIZE_HASH_HEX => hex_buffer(Sha256::output_size()),
+        ZK_SIZE_SIGNATURE_HEX => hex_buffer(SIGNATURE_LENGTH),
+        ZK_SIZE_PUBKEY_HEX => hex_buffer(PUBLIC_KEY_LENGTH),
+        _ => return None,
+    };
+    Some(size)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Exact byte length (or hex buffer size) of an artifact
+///
+/// `item` is one of ZK_SIZE_*, `circuit_id` one of ZK_CIRCUIT_* and `version` a
+/// proof format (1 legacy, 2 envelope, 3 tagged envelope). Returns the size,
+/// ZK_ERR_DISABLED for the legacy format in a strict build, -1 for an
+/// unknown or inapplicable combination.
+#[no_mangle]
+pub extern "C" fn ZK_SizeOf(item: c_int, circuit_id: c_int, version: u8) -> c_int {
+    if !proof::format_supported(version) && version == PROOF_FORMAT_LEGACY {
+        return ZK_ERR_DISABLED;
+    }
+
+    match size_of(item, circuit_id, version) {
+        Some(size) => c_int::try_from(size).unwrap_or(-1),
+        None => -1,
+    }
+}
+}
@@ -0,0 +1,521 @@
+// ============================================================================
+// Verifier State Snapshots
+// ============================================================================
+//
+// A gateway running many verifier processes builds the same configuration
+// in each of them, and they drift. Instead, one control process configures
+// a context and exports a snapshot; the workers import it:
+//
+//   ZK_ExportVerifierState -> distribute -> ZK_ImportVerifierState (each
+//   worker) -> ZK_VerifierStateFingerprint (metrics, compare across workers)
+//
+// A snapshot holds everything that decides a verification result:
+//
+//   verifying_key      SHA-256 of the raw key, null for a context without
+//                      one; keys are distributed as before
+//                      (ZK_ImportVerifyingKey), the snapshot only refuses
+//                      to apply to a worker holding another one
+//   trusted_issuers    the context's trust store, oldest first
+//   context            the context's ContextConfig
+//   accepted_formats, time_mode, max_skew, min_date, max_date,
+//   schema_allowlist, reject_superseded, collection_limits
+//                      the process-wide verifier settings
+//   revocations        the revocation registry, or null for a storage
+//                      backend that cannot be scanned (a shared backend is
+//                      consistent already)
+//   revocation_data    the published revocation list and its attestation
+//                      (ZK_UpdateRevocationData), or null
+//
+// and, outside that part, the prover tuning (tuning.rs) if one was recorded.
+// The fingerprint is the SHA-256 of the verifier part as this process holds
+// it, so it changes whenever any of the above does; tuning only affects
+// proving speed and is left out.
+//
+// No secret is ever part of a snapshot. Challenge secrets are passed to each
+// call and never held by the library; the log salt (ZK_SetLogSalt) is set
+// on every worker separately. The time callback is a function pointer and
+// is installed by each worker too.
+//
+// Wire format (hex when passed through the C API):
+//
+//   "ZKVS" | version u8 | issued_at u64 | body (len-prefixed JSON)
+//   | signature [64]
+//
+// signed with the control process's Ed25519 key over SNAPSHOT_CONTEXT
+// followed by everything before the signature. A worker refuses a snapshot
+// not issued after the last one it applied, so a replayed snapshot, old or
+// the current one again, cannot roll its configuration back. Imports are
+// serialized: the check and the apply happen under one lock, so concurrent
+// imports apply in issued_at order or not at all.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey, SECRET_KEY_LENGTH, SIGNATURE_LENGTH};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+
+use crate::admission::text_arg;
+use crate::clock::{self, TimeMode, ZK_TIME_CALLBACK, ZK_TIME_CALLER, ZK_TIME_CROSSCHECK};
+use crate::context::{Context, ContextConfig};
+use crate::credential::parse_verifying_key;
+use crate::dates::{self, ZK_SetDateWindow};
+use crate::error::{
+    ZK_ERR_AUTH, ZK_ERR_CORRUPT, ZK_ERR_EXPIRED, ZK_ERR_INCOMPATIBLE, ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_KEY_MISMATCH,
+    ZK_ERR_STORAGE, ZK_ERR_UNSUPPORTED_VERSION,
+};
+use crate::ffi::write_cstr;
+use crate::limits::{self, ZkCollectionLimit, COLLECTIONS};
+use crate::proof::{self, ZK_SetAcceptedFormatVersions};
+use crate::revocation::{self, RevocationData, RevocationRegistry, ZK_SetRejectSuperseded};
+use crate::schema;
+use crate::storage::{storage, StorageError};
+use crate::wire::{put_bytes, Reader};
+use crate::{bytes_to_hex, hex_to_bytes, vk};
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"ZKVS";
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// Domain of the control key's signature
+pub const SNAPSHOT_CONTEXT: &[u8] = b"zkid:verifier-state";
+
+/// Longest hex snapshot accepted
+pub const MAX_SNAPSHOT_LEN: usize = 16 * 1024 * 1024;
+
+/// issued_at of the newest snapshot applied in this process; held across
+/// each import
+static LAST_APPLIED: Mutex<u64> = Mutex::new(0);
+
+fn config_json(config: &ContextConfig) -> Value {
+    json!({
+        "time_mode": config.time_mode,
+        "max_skew": config.max_skew,
+        "min_date": config.min_date,
+        "max_date": config.max_date,
+        "max_blob_len": config.max_blob_len,
+        "deterministic": config.deterministic,
+        "proof_format": config.proof_format,
+        "accepted_formats": config.accepted_formats,
+    })
+}
+
+fn config_from_json(doc: &Value) -> Option<ContextConfig> {
+    Some(ContextConfig {
+        time_mode: c_int::try_from(doc["time_mode"].as_i64()?).ok()?,
+        max_skew: doc["max_skew"].as_u64()?,
+        min_date: doc["min_date"].as_i64()?,
+        max_date: doc["max_date"].as_i64()?,
+        max_blob_len: usize::try_from(doc["max_blob_len"].as_u64()?).ok()?,
+        deterministic: c_int::try_from(doc["deterministic"].as_i64()?).ok()?,
+        proof_format: u8::try_from(doc["proof_format"].as_u64()?).ok()?,
+        accepted_formats: u32::try_from(doc["accepted_formats"].as_u64()?).ok()?,
+    })
+}
+
+/// Hex SHA-256 of the context's raw verifying key, if it has one
+fn key_fingerprint(ctx: &Context) -> Option<String> {
+    ctx.verifying_key().map(|pvk| bytes_to_hex(&Sha256::digest(vk::encode(&pvk.vk))))
+}
+
+/// The verifier part of the state `ctx` and this process hold
+pub fn verifier_state(ctx: &Context) -> Result<Value, StorageError> {
+    let (time_mode, max_skew) = match clock::time_mode() {
+        TimeMode::Caller => (ZK_TIME_CALLER, 0),
+        TimeMode::Callback => (ZK_TIME_CALLBACK, 0),
+        TimeMode::CrossCheck { max_skew } => (ZK_TIME_CROSSCHECK, max_skew),
+    };
+    let window = dates::date_window();
+    let limits: Vec<Value> = (0..COLLECTIONS as c_int)
+        .map(|collection| {
+            let limit = limits::limit(collection);
+            json!([limit.capacity, limit.policy])
+        })
+        .collect();
+    let revocations = match RevocationRegistry::new(storage()).entries() {
+        Ok(entries) => json!(entries
+            .iter()
+            .map(|(id, value)| json!([bytes_to_hex(id), bytes_to_hex(value)]))
+            .collect::<Vec<_>>()),
+        Err(StorageError::Unsupported) => Value::Null,
+        Err(e) => return Err(e),
+    };
+    let revocation_data = match revocation::published() {
+        Some(data) => json!({
+            "list": bytes_to_hex(data.list()),
+            "attestation": bytes_to_hex(data.attestation()),
+        }),
+        None => Value::Null,
+    };
+
+    Ok(json!({
+        "verifying_key": key_fingerprint(ctx),
+        "trusted_issuers": ctx.trusted_issuers().map(|key| bytes_to_hex(key)).collect::<Vec<_>>(),
+        "context": config_json(&ctx.config()),
+        "accepted_formats": proof::accepted_formats(),
+        "time_mode": time_mode,
+        "max_skew": max_skew,
+        "min_date": window.min,
+        "max_date": window.max,
+        "schema_allowlist": schema::allowed_schemas(),
+        "reject_superseded": revocation::reject_superseded(),
+        "collection_limits": limits,
+        "revocations": revocations,
+        "revocation_data": revocation_data,
+    }))
+}
+
+/// Hex SHA-256 of the verifier part of a state
+pub fn fingerprint(verifier: &Value) -> String {
+    bytes_to_hex(&Sha256::digest(verifier.to_string()))
+}
+
+#[cfg(feature = "prover")]
+fn tuning_json() -> Value {
+    match crate::tuning::current() {
+        Some(tuning) => json!({ "version": crate::tuning::TUNING_VERSION, "threads": tuning.threads }),
+        None => Value::Null,
+    }
+}
+
+#[cfg(not(feature = "prover"))]
+fn tuning_json() -> Value {
+    Value::Null
+}
+
+/// Sign the state of `ctx` and this process as a snapshot
+pub fn export(ctx: &Context, control_key: &SigningKey, issued_at: u64) -> Result<Vec<u8>, StorageError> {
+    let body = json!({ "verifier": verifier_state(ctx)?, "tuning": tuning_json() });
+
+    let mut out = SNAPSHOT_MAGIC.to_vec();
+    out.push(SNAPSHOT_VERSION);
+    out.extend_from_slice(&issued_at.to_le_bytes());
+    put_bytes(&mut out, body.to_string().as_bytes());
+    let signature = control_key.sign(&[SNAPSHOT_CONTEXT, &out].concat());
+    out.extend_from_slice(&signature.to_bytes());
+    Ok(out)
+}
+
+/// Check a snapshot's signature and return its issued_at and body
+fn open(data: &[u8], control_key: &VerifyingKey) -> Result<(u64, Value), c_int> {
+    let mut r = Reader::new(data);
+    if r.take(4).ok_or(ZK_ERR_CORRUPT)? != SNAPSHOT_MAGIC {
+        return Err(ZK_ERR_CORRUPT);
+    }
+    match r.u8().ok_or(ZK_ERR_CORRUPT)? {
+        SNAPSHOT_VERSION => {}
+        version if version > SNAPSHOT_VERSION => return Err(ZK_ERR_UNSUPPORTED_VERSION),
+        _ => return Err(ZK_ERR_CORRUPT),
+    }
+    let issued_at = r.u64().ok_or(ZK_ERR_CORRUPT)?;
+    let body = r.bytes().ok_or(ZK_ERR_CORRUPT)?;
+    let signed_len = data.len().checked_sub(SIGNATURE_LENGTH).ok_or(ZK_ERR_CORRUPT)?;
+    let signature = r.take(SIGNATURE_LENGTH).ok_or(ZK_ERR_CORRUPT)?;
+    if !r.is_empty() {
+        return Err(ZK_ERR_CORRUPT);
+    }
+
+    let signature = Signature::from_slice(signature).map_err(|_| ZK_ERR_CORRUPT)?;
+    control_key
+        .verify(&[SNAPSHOT_CONTEXT, &data[..signed_len]].concat(), &signature)
+        .map_err(|_| ZK_ERR_AUTH)?;
+    let body = serde_json::from_slice(body).map_err(|_| ZK_ERR_CORRUPT)?;
+    Ok((issued_at, body))
+}
+
+/// A snapshot's settings, checked against this build before any is applied
+struct Settings {
+    trusted_issuers: Vec<[u8; 32]>,
+    config: ContextConfig,
+    accepted_formats: u32,
+    time_mode: TimeMode,
+    date_window: (i64, i64),
+    schemas: Vec<String>,
+    reject_superseded: bool,
+    limits: Vec<ZkCollectionLimit>,
+    revocations: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    revocation_data: Option<RevocationData>,
+    #[cfg(feature = "prover")]
+    tuning: Option<crate::tuning::Tuning>,
+}
+
+impl Settings {
+    fn read(body: &Value, ctx: &Context) -> Result<Self, c_int> {
+        let verifier = &body["verifier"];
+        let key = match &verifier["verifying_key"] {
+            Value::Null => None,
+            key => Some(key.as_str().ok_or(ZK_ERR_CORRUPT)?),
+        };
+        if key != key_fingerprint(ctx).as_deref() {
+            return Err(ZK_ERR_KEY_MISMATCH);
+        }
+
+        let hex_list = |value: &Value| -> Option<Vec<Vec<u8>>> {
+            value.as_array()?.iter().map(|item| hex_to_bytes(item.as_str()?).ok()).collect()
+        };
+        let trusted_issuers = hex_list(&verifier["trusted_issuers"])
+            .ok_or(ZK_ERR_CORRUPT)?
+            .iter()
+            .map(|key| key.as_slice().try_into().ok())
+            .collect::<Option<Vec<[u8; 32]>>>()
+            .ok_or(ZK_ERR_CORRUPT)?;
+        let config = config_from_json(&verifier["context"]).ok_or(ZK_ERR_CORRUPT)?;
+        let accepted_formats = verifier["accepted_formats"]
+            .as_u64()
+            .and_then(|mask| u32::try_from(mask).ok())
+            .ok_or(ZK_ERR_CORRUPT)?;
+        let max_skew = verifier["max_skew"].as_u64().ok_or(ZK_ERR_CORRUPT)?;
+        let time_mode = match verifier["time_mode"].as_i64().ok_or(ZK_ERR_CORRUPT)? {
+            mode if mode == ZK_TIME_CALLER as i64 => TimeMode::Caller,
+            mode if mode == ZK_TIME_CALLBACK as i64 => TimeMode::Callback,
+            mode if mode == ZK_TIME_CROSSCHECK as i64 => TimeMode::CrossCheck { max_skew },
+            _ => return Err(ZK_ERR_CORRUPT),
+        };
+        let date_window = match (verifier["min_date"].as_i64(), verifier["max_date"].as_i64()) {
+            (Some(min), Some(max)) if min <= max => (min, max),
+            _ => return Err(ZK_ERR_CORRUPT),
+        };
+        let schemas = verifier["schema_allowlist"]
+            .as_array()
+            .ok_or(ZK_ERR_CORRUPT)?
+            .iter()
+            .map(|id| id.as_str().filter(|id| !id.is_empty()).map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ZK_ERR_CORRUPT)?;
+        let reject_superseded = verifier["reject_superseded"].as_bool().ok_or(ZK_ERR_CORRUPT)?;
+        let limits = verifier["collection_limits"]
+            .as_array()
+            .filter(|limits| limits.len() == COLLECTIONS)
+            .ok_or(ZK_ERR_CORRUPT)?
+            .iter()
+            .map(|limit| {
+                Some(ZkCollectionLimit {
+                    capacity: limit[0].as_u64()?,
+                    policy: c_int::try_from(limit[1].as_i64()?).ok()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|limits| {
+                limits
+                    .iter()
+                    .enumerate()
+                    .all(|(collection, limit)| limits::is_valid_limit(collection as c_int, *limit))
+            })
+            .ok_or(ZK_ERR_CORRUPT)?;
+        let revocations = match &verifier["revocations"] {
+            Value::Null => None,
+            entries => Some(
+                entries
+                    .as_array()
+                    .ok_or(ZK_ERR_CORRUPT)?
+                    .iter()
+                    .map(|entry| Some((hex_to_bytes(entry[0].as_str()?).ok()?, hex_to_bytes(entry[1].as_str()?).ok()?)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(ZK_ERR_CORRUPT)?,
+            ),
+        };
+        let revocation_data = match &verifier["revocation_data"] {
+            Value::Null => None,
+            data => {
+                let hex_field = |name: &str| data[name].as_str().and_then(|hex| hex_to_bytes(hex).ok());
+                match (hex_field("list"), hex_field("attestation")) {
+                    // The control key vouches for the issuer the list carries
+                    (Some(list), Some(attestation)) => Some(RevocationData::open_carried(&list, &attestation)?),
+                    _ => return Err(ZK_ERR_CORRUPT),
+                }
+            }
+        };
+        #[cfg(feature = "prover")]
+        let tuning = match &body["tuning"] {
+            Value::Null => None,
+            tuning => Some(crate::tuning::Tuning::from_json(tuning)?),
+        };
+
+        // Settings a build without some proof format cannot hold
+        if !config.is_valid()
+            || !proof::valid_accept_mask(accepted_formats)
+            || accepted_formats & !proof::supported_mask() != 0
+        {
+            return Err(ZK_ERR_INCOMPATIBLE);
+        }
+
+        Ok(Settings {
+            trusted_issuers,
+            config,
+            accepted_formats,
+            time_mode,
+            date_window,
+            schemas,
+            reject_superseded,
+            limits,
+            revocations,
+            revocation_data,
+            #[cfg(feature = "prover")]
+            tuning,
+        })
+    }
+
+    /// Apply to `ctx` and this process
+    ///
+    /// Only storing the revocations and publishing the revocation list can
+    /// fail, and they come first. A worker already holding a newer list
+    /// keeps it.
+    fn apply(self, ctx: &mut Context) -> Result<(), c_int> {
+        if let Some(revocations) = self.revocations {
+            let registry = RevocationRegistry::new(storage());
+            for (id, value) in revocations {
+                registry.restore(&id, &value).map_err(|e| e.code())?;
+            }
+        }
+        if let Some(data) = self.revocation_data {
+            match revocation::update(data) {
+                Ok(_) | Err(ZK_ERR_EXPIRED) => {}
+                Err(code) => return Err(code),
+            }
+        }
+
+        for (collection, limit) in self.limits.into_iter().enumerate() {
+            limits::set_limit(collection as c_int, limit);
+        }
+        ctx.replace_trusted_issuers(self.trusted_issuers);
+        ctx.set_config(self.config);
+        ZK_SetAcceptedFormatVersions(self.accepted_formats);
+        clock::set_time_mode(self.time_mode);
+        ZK_SetDateWindow(self.date_window.0, self.date_window.1);
+        schema::set_allowed_schemas(self.schemas.iter().map(String::as_str));
+        ZK_SetRejectSuperseded(self.reject_superseded as c_int);
+        #[cfg(feature = "prover")]
+        if let Some(tuning) = self.tuning {
+            crate::tuning::install(tuning, std::ptr::null_mut(), 0);
+        }
+        Ok(())
+    }
+}
+
+/// Check a snapshot and apply it to `ctx` and this process
+pub fn import(ctx: &mut Context, data: &[u8], control_key: &VerifyingKey) -> Result<(), c_int> {
+    let (issued_at, body) = open(data, control_key)?;
+    let mut last_applied = LAST_APPLIED.lock().unwrap_or_else(|e| e.into_inner());
+    if issued_at <= *last_applied {
+        return Err(ZK_ERR_EXPIRED);
+    }
+    Settings::read(&body, ctx)?.apply(ctx)?;
+    *last_applied = issued_at;
+    Ok(())
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Export the verifier state of `ctx` and this process as a signed snapshot
+///
+/// `control_private_key` is the hex Ed25519 key workers check snapshots
+/// against; `issued_at` orders snapshots (a worker refuses one not newer
+/// than the last it applied), so give each export a larger one. The hex snapshot is written to `state_out`.
+/// Returns 0 on success, ZK_ERR_STORAGE if the revocation registry could
+/// not be read, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL or a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_ExportVerifierState(
+    ctx: *const Context,
+    control_private_key: *const c_char,
+    issued_at: u64,
+    state_out: *mut c_char,
+    state_out_size: usize,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) if !control_private_key.is_null() => ctx,
+        _ => return -1,
+    };
+    let control_key = match text_arg(control_private_key, 2 * SECRET_KEY_LENGTH)
+        .and_then(|hex| hex_to_bytes(hex).ok())
+        .and_then(|bytes| <[u8; SECRET_KEY_LENGTH]>::try_from(bytes.as_slice()).ok())
+    {
+        Some(bytes) => SigningKey::from_bytes(&bytes),
+        None => return -1,
+    };
+
+    let snapshot = match export(ctx, &control_key, issued_at) {
+        Ok(snapshot) => snapshot,
+        Err(_) => return ZK_ERR_STORAGE,
+    };
+    match write_cstr(state_out, state_out_size, &bytes_to_hex(&snapshot)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Apply a hex snapshot from ZK_ExportVerifierState to `ctx` and this process
+///
+/// The context's trust store and config are replaced; the process-wide
+/// settings are replaced and the snapshot's revocations are added to the
+/// registry (revocations are never dropped); its published revocation list
+/// replaces an older one. Nothing is applied unless the
+/// snapshot verifies under `control_public_key` and fits this build.
+/// Returns 0 on success, ZK_ERR_AUTH for a bad signature, ZK_ERR_EXPIRED
+/// for a snapshot issued at or before the last one applied (the same
+/// snapshot again included), ZK_ERR_KEY_MISMATCH
+/// if this process holds another verifying key or a revocation list from
+/// another issuer, ZK_ERR_INCOMPATIBLE for
+/// settings this build cannot hold (a compiled-out proof format),
+/// ZK_ERR_UNSUPPORTED_VERSION for a newer snapshot, ZK_ERR_CORRUPT,
+/// ZK_ERR_INPUT_TOO_LARGE, ZK_ERR_STORAGE if the revocations could not be
+/// stored (the settings are then left as they were), -1 on NULL or a
+/// malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_ImportVerifierState(
+    ctx: *mut Context,
+    state: *const c_char,
+    control_public_key: *const c_char,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_mut() } {
+        Some(ctx) if !state.is_null() && !control_public_key.is_null() => ctx,
+        _ => return -1,
+    };
+    let control_key = match parse_verifying_key(control_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let data = match text_arg(state, MAX_SNAPSHOT_LEN) {
+        Some(hex) => match hex_to_bytes(hex) {
+            Ok(data) => data,
+            Err(_) => return ZK_ERR_CORRUPT,
+        },
+        None => return ZK_ERR_INPUT_TOO_LARGE,
+    };
+
+    match import(ctx, &data, &control_key) {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the fingerprint of the verifier state `ctx` and this process hold
+///
+/// The hex SHA-256 covers everything a snapshot carries except the tuning
+/// (see snapshot.rs), so workers that drifted apart report different
+/// values; report it in metrics to detect drift. Returns 0 on success, ZK_ERR_STORAGE if the revocation
+/// registry could not be read, ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_VerifierStateFingerprint(
+    ctx: *const Context,
+    fingerprint_out: *mut c_char,
+    fingerprint_out_size: usize,
+) -> c_int {
+    let ctx = match unsafe { ctx.as_ref() } {
+        Some(ctx) => ctx,
+        None => return -1,
+    };
+    let state = match verifier_state(ctx) {
+        Ok(state) => state,
+        Err(_) => return ZK_ERR_STORAGE,
+    };
+    match write_cstr(fingerprint_out, fingerprint_out_size, &fingerprint(&state)) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,336 @@
+// ============================================================================
+// snarkjs JSON
+// ============================================================================
+//
+// Proofs and verifying keys in the JSON snarkjs reads, for frontends that
+// verify with snarkjs.groth16.verify(vk, publicSignals, proof):
+//
+//   G1  ["x", "y", "1"]
+//   G2  [["x.c0", "x.c1"], ["y.c0", "y.c1"], ["1", "0"]]
+//   proof               {"pi_a": G1, "pi_b": G2, "pi_c": G1,
+//                        "protocol": "groth16", "curve": "bn128"}
+//   verifying key       {"protocol", "curve", "nPublic", "vk_alpha_1": G1,
+//                        "vk_beta_2", "vk_gamma_2", "vk_delta_2": G2,
+//                        "IC": [G1, ...]}
+//
+// Coordinates are decimal strings of projective points with z = 1; the
+// point at infinity is ["0", "1", "0"] (G2: [["0", "0"], ["1", "0"],
+// ["0", "0"]]). Fq2 coordinates keep arkworks' order, real part first,
+// unlike the EVM calldata of solidity.rs. The verifying key has no
+// vk_alphabeta_12, which snarkjs' verifier does not read.
+//
+// A VC proof is exported as {"proof", "publicSignals", "zkid:format",
+// "zkid:anchorSignature"}, the shape snarkjs' fullProve returns plus the
+// proof format and the issuer's hex signature over the anchor (validity.rs),
+// which no public signal carries. The signals are those of solidity.rs in
+// decimal, and importing takes the current_time and anchor back from them:
+// the result is the hex VC proof it came from, verified as usual by
+// ZK_VerifyVCProof, which recomputes the issuer field and nonce from its own
+// arguments and checks the signature. Without the signature an import is
+// refused, as no verifier here could accept the proof. Coordinates must be
+// canonical decimal (no sign, no leading zeros, below the modulus) and
+// points on the curve and in the subgroup, so a proof with swapped
+// coordinates is refused on import rather than failing later as an invalid
+// proof.
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::{Proof, VerifyingKey};
+use serde_json::{json, Value};
+use std::os::raw::{c_char, c_int};
+use std::str::FromStr;
+
+use crate::error::{ZK_ERR_INVALID_POINT, ZK_ERR_NON_CANONICAL, ZK_ERR_NOT_INITIALIZED};
+use crate::ffi::{read_bytes, reserve_cstr, write_cstr, MAX_FIELD_LEN};
+use crate::proof::{self, PROOF_FORMATS};
+use crate::solidity::{decimal, vc_proof_signals};
+use crate::validity::{self, Header};
+use crate::{bytes_to_hex, hex_to_bytes, last_error, VERIFYING_KEY};
+
+/// Member of an exported VC proof holding the issuer's signature over its
+/// anchor
+const ANCHOR_SIGNATURE: &str = "zkid:anchorSignature";
+
+/// Reason snarkjs JSON was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnarkjsError {
+    /// Not JSON of the expected shape
+    Malformed,
+    /// A coordinate that is not canonical decimal below the modulus
+    NonCanonical,
+    /// A point off the curve or outside the subgroup
+    InvalidPoint,
+}
+
+impl SnarkjsError {
+    pub fn code(self) -> c_int {
+        match self {
+            SnarkjsError::Malformed => -1,
+            SnarkjsError::NonCanonical => ZK_ERR_NON_CANONICAL,
+            SnarkjsError::InvalidPoint => ZK_ERR_INVALID_POINT,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            SnarkjsError::Malformed => "JSON is not a snarkjs proof of the expected shape",
+            SnarkjsError::NonCanonical => "a coordinate is not canonical decimal below the modulus",
+            SnarkjsError::InvalidPoint => "a point is off the curve or outside the subgroup",
+        }
+    }
+}
+
+fn g1_json(point: &G1Affine) -> Value {
+    match point.xy() {
+        Some((x, y)) => json!([decimal(x), decimal(y), "1"]),
+        None => json!(["0", "1", "0"]),
+    }
+}
+
+fn g2_json(point: &G2Affine) -> Value {
+    match point.xy() {
+        Some((x, y)) => json!([[decimal(&x.c0), decimal(&x.c1)], [decimal(&y.c0), decimal(&y.c1)], ["1", "0"]]),
+        None => json!([["0", "0"], ["1", "0"], ["0", "0"]]),
+    }
+}
+
+/// A field element from canonical decimal
+fn field<F: PrimeField + FromStr>(value: &Value) -> Result<F, SnarkjsError> {
+    let text = value.as_str().ok_or(SnarkjsError::Malformed)?;
+    let element = F::from_str(text).map_err(|_| SnarkjsError::NonCanonical)?;
+    // from_str reduces what it reads; only the canonical spelling reads back
+    if decimal(&element) != text {
+        return Err(SnarkjsError::NonCanonical);
+    }
+    Ok(element)
+}
+
+/// Exactly `N` members of a JSON array
+fn members<const N: usize>(value: &Value) -> Result<&[Value; N], SnarkjsError> {
+    value.as_array().and_then(|items| items.as_slice().try_into().ok()).ok_or(SnarkjsError::Malformed)
+}
+
+fn fq2(value: &Value) -> Result<Fq2, SnarkjsError> {
+    let [c0, c1] = members::<2>(value)?;
+    Ok(Fq2::new(field(c0)?, field(c1)?))
+}
+
+fn g1_from_json(value: &Value) -> Result<G1Affine, SnarkjsError> {
+    let [x, y, z] = members::<3>(value)?;
+    let (x, y, z): (Fq, Fq, Fq) = (field(x)?, field(y)?, field(z)?);
+    if z == Fq::from(0u8) {
+        return if x == Fq::from(0u8) && y == Fq::from(1u8) {
+            Ok(G1Affine::zero())
+        } else {
+            Err(SnarkjsError::InvalidPoint)
+        };
+    }
+    if z != Fq::from(1u8) {
+        return Err(SnarkjsError::Malformed);
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SnarkjsError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+fn g2_from_json(value: &Value) -> Result<G2Affine, SnarkjsError> {
+    let [x, y, z] = members::<3>(value)?;
+    let (x, y, z) = (fq2(x)?, fq2(y)?, fq2(z)?);
+    if z == Fq2::from(0u8) {
+        return if x == Fq2::from(0u8) && y == Fq2::from(1u8) {
+            Ok(G2Affine::zero())
+        } else {
+            Err(SnarkjsError::InvalidPoint)
+        };
+    }
+    if z != Fq2::from(1u8) {
+        return Err(SnarkjsError::Malformed);
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SnarkjsError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+/// A proof as snarkjs' proof.json
+pub fn proof_to_snarkjs(proof: &Proof<Bn254>) -> Value {
+    json!({
+        "pi_a": g1_json(&proof.a),
+        "pi_b": g2_json(&proof.b),
+        "pi_c": g1_json(&proof.c),
+        "protocol": "groth16",
+        "curve": "bn128",
+    })
+}
+
+/// A proof from snarkjs' proof.json
+pub fn proof_from_snarkjs(value: &Value) -> Result<Proof<Bn254>, SnarkjsError> {
+    if value["protocol"] != "groth16" || value.get("curve").is_some_and(|curve| curve != "bn128") {
+        return Err(SnarkjsError::Malformed);
+    }
+    Ok(Proof {
+        a: g1_from_json(&value["pi_a"])?,
+        b: g2_from_json(&value["pi_b"])?,
+        c: g1_from_json(&value["pi_c"])?,
+    })
+}
+
+/// A verifying key as snarkjs' verification_key.json
+pub fn verifying_key_to_snarkjs(vk: &VerifyingKey<Bn254>) -> Value {
+    json!({
+        "protocol": "groth16",
+        "curve": "bn128",
+        "nPublic": crate::vk::public_inputs(vk),
+        "vk_alpha_1": g1_json(&vk.alpha_g1),
+        "vk_beta_2": g2_json(&vk.beta_g2),
+        "vk_gamma_2": g2_json(&vk.gamma_g2),
+        "vk_delta_2": g2_json(&vk.delta_g2),
+        "IC": vk.gamma_abc_g1.iter().map(g1_json).collect::<Vec<_>>(),
+    })
+}
+
+/// The VC proof an exported {"proof", "publicSignals", "zkid:format",
+/// "zkid:anchorSignature"} object holds, in wire bytes
+fn vc_proof_from_snarkjs(value: &Value) -> Result<Vec<u8>, SnarkjsError> {
+    let format = match value.get("zkid:format") {
+        Some(format) => format.as_u64().and_then(|f| u8::try_from(f).ok()).ok_or(SnarkjsError::Malformed)?,
+        None => proof::emit_format(),
+    };
+    if !PROOF_FORMATS.contains(&format) {
+        return Err(SnarkjsError::Malformed);
+    }
+    let [_, _, current_time, anchor] = members::<4>(&value["publicSignals"])?;
+    let limbs = field::<Fr>(current_time)?.into_bigint().0;
+    if limbs[1..] != [0; 3] {
+        return Err(SnarkjsError::Malformed);
+    }
+    let signature = value[ANCHOR_SIGNATURE]
+        .as_str()
+        .and_then(|hex| hex_to_bytes(hex).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(SnarkjsError::Malformed)?;
+    let header = Header::new(field(anchor)?, signature, limbs[0]);
+    Ok(validity::encode(&header, format, &proof_from_snarkjs(&value["proof"])?))
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write a hex VC proof as snarkjs JSON: {"proof", "publicSignals",
+/// "zkid:format", "zkid:anchorSignature"}
+///
+/// The public signals are those of ZK_ExportProofCalldata, for the issuer
+/// key (hex or did:key) and nonce given; the key must have signed the
+/// proof's anchor. As with other variable-size outputs, a NULL `json_out`
+/// (or size 0) with `required_size_out` set is a size query. Returns 0 on success, the codes of ZK_ExportProofCalldata
+/// for a refused proof or issuer key, ZK_ERR_BUFFER_TOO_SMALL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportProofSnarkjs(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    json_out: *mut c_char,
+    json_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    let (format, header, proof, public_inputs) = match vc_proof_signals(proof_hex, issuer_pubkey, nonce) {
+        Ok(signals) => signals,
+        Err(code) => return code,
+    };
+    let json = json!({
+        "proof": proof_to_snarkjs(&proof),
+        "publicSignals": public_inputs.iter().map(decimal).collect::<Vec<_>>(),
+        "zkid:format": format,
+        ANCHOR_SIGNATURE: bytes_to_hex(&header.signature),
+    })
+    .to_string();
+    if let Err(e) = reserve_cstr(json_out, json_out_size, json.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(json_out, json_out_size, &json) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("json_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read snarkjs JSON of `json_len` bytes, as ZK_ExportProofSnarkjs writes
+/// it, back into a hex VC proof for ZK_VerifyVCProof
+///
+/// Without "zkid:format" the proof takes the format the prover emits
+/// (ZK_SetEmitFormatVersion). The issuer and nonce signals are not kept;
+/// ZK_VerifyVCProof takes them from its arguments. Size queries work as for
+/// ZK_ExportProofSnarkjs. Returns 0 on success,
+/// ZK_ERR_NON_CANONICAL for a coordinate or signal that is not canonical
+/// decimal, ZK_ERR_INVALID_POINT for a point off the curve or outside the
+/// subgroup (as swapped coordinates are), ZK_ERR_BUFFER_TOO_SMALL, -1 for
+/// anything else malformed, a missing "zkid:anchorSignature" among it.
+#[no_mangle]
+pub extern "C" fn ZK_ImportProofSnarkjs(
+    json: *const c_char,
+    json_len: usize,
+    proof_hex_out: *mut c_char,
+    proof_hex_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    let value: Value = match read_bytes(json, json_len, MAX_FIELD_LEN) {
+        Ok(bytes) => match serde_json::from_slice(bytes) {
+            Ok(value) => value,
+            Err(_) => return last_error::fail(-1, "json is not JSON"),
+        },
+        Err(e) => return last_error::ffi("json", e),
+    };
+    let proof = match vc_proof_from_snarkjs(&value) {
+        Ok(proof) => proof,
+        Err(e) => return last_error::fail(e.code(), e.describe()),
+    };
+    let proof_hex = bytes_to_hex(&proof);
+    if let Err(e) = reserve_cstr(proof_hex_out, proof_hex_out_size, proof_hex.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(proof_hex_out, proof_hex_out_size, &proof_hex) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("proof_hex_out", e),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the current VC verifying key as snarkjs' verification_key.json
+///
+/// Size queries work as for ZK_ExportProofSnarkjs. Returns 0 on success,
+/// ZK_ERR_NOT_INITIALIZED if no keys are set, ZK_ERR_BUFFER_TOO_SMALL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportVerifyingKeySnarkjs(
+    json_out: *mut c_char,
+    json_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    let json = match VERIFYING_KEY.lock() {
+        Ok(pvk) => match pvk.as_ref() {
+            Some(pvk) => verifying_key_to_snarkjs(&pvk.vk).to_string(),
+            None => {
+                return last_error::fail(ZK_ERR_NOT_INITIALIZED, "verifying key is not set up; call ZK_Init or import it")
+            }
+        },
+        Err(_) => return last_error::fail(-1, "verifying key is unavailable"),
+    };
+    if let Err(e) = reserve_cstr(json_out, json_out_size, json.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(json_out, json_out_size, &json) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("json_out", e),
+    }
+}
+}
@@ -0,0 +1,260 @@
+// ============================================================================
+// Solidity Verifier Export
+// ============================================================================
+//
+// VC proofs verified in an EVM contract. export_solidity_verifier writes a
+// Groth16 verifier contract with the verifying key inlined as constants; it
+// checks
+//
+//   e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+//   vk_x = IC[0] + sum(pubSignals[i] * IC[i + 1])
+//
+// through the BN254 precompiles (0x06 add, 0x07 mul, 0x08 pairing). Its
+// verifyProof takes the same arguments as the verifiers snarkjs generates,
+// and proof_calldata writes them in snarkjs' soliditycalldata text, so
+// tooling built for one works with the other:
+//
+//   [A.x, A.y],[[B.x.c1, B.x.c0],[B.y.c1, B.y.c0]],[C.x, C.y],[pubSignals]
+//
+// each a quoted 0x-prefixed 64-digit hex word. The precompile takes an Fq2
+// coordinate imaginary part first, where arkworks (and snarkjs' proof.json)
+// put the real part first, hence c1 before c0.
+//
+// The public signals of a VC proof are those of validity.rs: issuer key
+// field, nonce, current_time, anchor. verifyProof only checks the
+// pairing; the calling contract decides which issuer fields it trusts and
+// whether the nonce and current_time are fresh (block.timestamp).
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use std::fmt::Write;
+use std::os::raw::{c_char, c_int};
+
+use crate::admission::{self, text_arg};
+use crate::error::{ZK_ERR_BAD_HEX, ZK_ERR_BAD_SIGNATURE, ZK_ERR_NOT_INITIALIZED};
+use crate::ffi::{reserve_cstr, write_cstr, MAX_FIELD_LEN};
+use crate::validity::{self, Header};
+use crate::{bytes_to_hex, did, last_error, proof, VERIFYING_KEY};
+
+/// Decimal digits of a field element
+pub(crate) fn decimal<F: PrimeField>(x: &F) -> String {
+    x.into_bigint().to_string()
+}
+
+/// A field element as a quoted 0x-prefixed 64-digit hex word
+fn word<F: PrimeField>(x: &F) -> String {
+    format!("\"0x{}\"", bytes_to_hex(&x.into_bigint().to_bytes_be()))
+}
+
+/// Declare the constants of a G1 point
+fn g1_constants(source: &mut String, name: &str, point: &G1Affine) {
+    let _ = writeln!(source, "    uint256 constant {name}x = {};", decimal(&point.x));
+    let _ = writeln!(source, "    uint256 constant {name}y = {};", decimal(&point.y));
+}
+
+/// Declare the constants of a G2 point, imaginary parts first (x1 = x.c1)
+fn g2_constants(source: &mut String, name: &str, point: &G2Affine) {
+    let _ = writeln!(source, "    uint256 constant {name}x1 = {};", decimal(&point.x.c1));
+    let _ = writeln!(source, "    uint256 constant {name}x2 = {};", decimal(&point.x.c0));
+    let _ = writeln!(source, "    uint256 constant {name}y1 = {};", decimal(&point.y.c1));
+    let _ = writeln!(source, "    uint256 constant {name}y2 = {};", decimal(&point.y.c0));
+}
+
+/// Solidity source of a verifier contract for `vk`
+pub fn export_solidity_verifier(vk: &VerifyingKey<Bn254>) -> String {
+    let inputs = crate::vk::public_inputs(vk);
+    let mut source = String::new();
+    source.push_str(CONTRACT_HEAD);
+    let _ = writeln!(source, "    uint256 constant r = {};", Fr::MODULUS);
+    let _ = writeln!(source, "    uint256 constant q = {};", Fq::MODULUS);
+    source.push_str("\n    // Verifying key\n");
+    g1_constants(&mut source, "alpha", &vk.alpha_g1);
+    g2_constants(&mut source, "beta", &vk.beta_g2);
+    g2_constants(&mut source, "gamma", &vk.gamma_g2);
+    g2_constants(&mut source, "delta", &vk.delta_g2);
+    for (i, point) in vk.gamma_abc_g1.iter().enumerate() {
+        g1_constants(&mut source, &format!("IC{i}"), point);
+    }
+
+    let _ = write!(
+        source,
+        "
+    function verifyProof(
+        uint256[2] calldata _pA,
+        uint256[2][2] calldata _pB,
+        uint256[2] calldata _pC,
+        uint256[{inputs}] calldata _pubSignals
+    ) public view returns (bool) {{
+        uint256[2] memory vkX = [IC0x, IC0y];
+        bool ok;
+"
+    );
+    for i in 0..inputs {
+        let _ = write!(
+            source,
+            "
+        if (_pubSignals[{i}] >= r) return false;
+        (ok, vkX) = mulAdd(vkX, IC{next}x, IC{next}y, _pubSignals[{i}]);
+        if (!ok) return false;
+",
+            next = i + 1,
+        );
+    }
+    source.push_str(CONTRACT_TAIL);
+    source
+}
+
+const CONTRACT_HEAD: &str = "// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.7.0 <0.9.0;
+
+/// Groth16 verifier over BN254, generated by ZK_ExportSolidityVerifier.
+/// Public signals of a VC proof, in order: issuer key field, nonce,
+/// current_time, anchor.
+contract Groth16Verifier {
+    // Scalar field and base field moduli
+";
+
+const CONTRACT_TAIL: &str = "
+        uint256[24] memory input = [
+            _pA[0], (q - (_pA[1] % q)) % q, _pB[0][0], _pB[0][1], _pB[1][0], _pB[1][1],
+            alphax, alphay, betax1, betax2, betay1, betay2,
+            vkX[0], vkX[1], gammax1, gammax2, gammay1, gammay2,
+            _pC[0], _pC[1], deltax1, deltax2, deltay1, deltay2
+        ];
+        uint256[1] memory out;
+        assembly {
+            ok := staticcall(sub(gas(), 2000), 8, input, 768, out, 0x20)
+        }
+        return ok && out[0] == 1;
+    }
+
+    /// acc + s * (x, y), or false if a precompile refuses
+    function mulAdd(uint256[2] memory acc, uint256 x, uint256 y, uint256 s)
+        internal
+        view
+        returns (bool ok, uint256[2] memory result)
+    {
+        uint256[3] memory mulInput = [x, y, s];
+        uint256[4] memory addInput;
+        addInput[0] = acc[0];
+        addInput[1] = acc[1];
+        assembly {
+            ok := staticcall(sub(gas(), 2000), 7, mulInput, 0x60, add(addInput, 0x40), 0x40)
+        }
+        if (!ok) return (false, result);
+        assembly {
+            ok := staticcall(sub(gas(), 2000), 6, addInput, 0x80, result, 0x40)
+        }
+    }
+}
+";
+
+/// verifyProof arguments for `proof` and its public signals, in snarkjs'
+/// soliditycalldata text
+pub fn proof_calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> String {
+    let (a, b, c) = (&proof.a, &proof.b, &proof.c);
+    let signals: Vec<String> = public_inputs.iter().map(word).collect();
+    format!(
+        "[{}, {}],[[{}, {}],[{}, {}]],[{}, {}],[{}]",
+        word(&a.x),
+        word(&a.y),
+        word(&b.x.c1),
+        word(&b.x.c0),
+        word(&b.y.c1),
+        word(&b.y.c0),
+        word(&c.x),
+        word(&c.y),
+        signals.join(","),
+    )
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write the Solidity source of a verifier contract for the current VC
+/// verifying key
+///
+/// As with other variable-size outputs, a NULL `out` (or size 0) with
+/// `required_size_out` set is a size query. Returns 0 on success,
+/// ZK_ERR_NOT_INITIALIZED if no keys are set, ZK_ERR_BUFFER_TOO_SMALL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportSolidityVerifier(out: *mut c_char, out_size: usize, required_size_out: *mut usize) -> c_int {
+    let source = match VERIFYING_KEY.lock() {
+        Ok(pvk) => match pvk.as_ref() {
+            Some(pvk) => export_solidity_verifier(&pvk.vk),
+            None => return ZK_ERR_NOT_INITIALIZED,
+        },
+        Err(_) => return -1,
+    };
+    if let Err(e) = reserve_cstr(out, out_size, source.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(out, out_size, &source) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the verifyProof arguments of a hex VC proof, in snarkjs'
+/// soliditycalldata text
+///
+/// The public signals are the issuer key field (from `issuer_pubkey`, hex
+/// or did:key, mapped as the proof's format does), `nonce`, the
+/// current_time the proof was generated for and its anchor, which
+/// `issuer_pubkey` must have signed. Size queries work as for
+/// ZK_ExportSolidityVerifier. Returns 0 on success, ZK_ERR_BAD_HEX or
+/// ZK_ERR_BAD_DID for a bad issuer key, the admission code for a proof
+/// that is refused (ZK_GetLastError says why), ZK_ERR_BAD_SIGNATURE for
+/// an anchor the key did not sign, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportProofCalldata(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+    calldata_out: *mut c_char,
+    calldata_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    last_error::clear();
+    let (_, _, proof, public_inputs) = match vc_proof_signals(proof_hex, issuer_pubkey, nonce) {
+        Ok(signals) => signals,
+        Err(code) => return code,
+    };
+    let calldata = proof_calldata(&proof, &public_inputs);
+    if let Err(e) = reserve_cstr(calldata_out, calldata_out_size, calldata.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(calldata_out, calldata_out_size, &calldata) {
+        Ok(_) => 0,
+        Err(e) => last_error::ffi("calldata_out", e),
+    }
+}
+}
+
+/// Read a hex VC proof and its issuer key argument into the proof format,
+/// header, proof and public signals, leaving the reason for a refusal (an
+/// anchor the key did not sign among them) in the last error
+pub(crate) fn vc_proof_signals(
+    proof_hex: *const c_char,
+    issuer_pubkey: *const c_char,
+    nonce: u64,
+) -> Result<(u8, Header, Proof<Bn254>, [Fr; 4]), c_int> {
+    let issuer_pubkey = match text_arg(issuer_pubkey, MAX_FIELD_LEN).map(did::public_key_bytes) {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => return Err(last_error::fail(e.code_or(ZK_ERR_BAD_HEX), "issuer_pubkey is not hex or a did:key")),
+        None => return Err(last_error::fail(-1, "issuer_pubkey is NULL, too long or not UTF-8")),
+    };
+    let (header, format, proof) = admission::vc_proof_arg(proof_hex, proof::accepted_formats())
+        .map_err(|rejection| last_error::fail(rejection.code, rejection.describe()))?;
+    if proof::field_for(format, &issuer_pubkey).is_none() {
+        return Err(last_error::fail(-1, "proof format has no issuer field mapping in this build"));
+    }
+    let public_inputs = validity::public_inputs(&issuer_pubkey, format, nonce, header.current_time, &header)
+        .ok_or_else(|| last_error::fail(ZK_ERR_BAD_SIGNATURE, crate::UNSIGNED_ANCHOR))?;
+    Ok((format, header, proof, public_inputs))
+}
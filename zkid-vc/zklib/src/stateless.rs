@@ -0,0 +1,430 @@
+// ============================================================================
+// Stateless Verification Profile
+// ============================================================================
+//
+// Some edge verifiers keep no state at all: no challenge store, no
+// nullifier set, no revocation registry. ZK_VerifyPresentationStateless
+// decides from the presentation and the verifier's static configuration
+// only and never touches the storage backend. The presentation carries in
+// its metadata:
+//
+//   zkid.challenge  hex challenge context, issued_at u64 LE | request bytes
+//                   (see challenge.rs); the presentation's nonce must be
+//                   the challenge derived from it under the verifier secret
+//   zkid.vc         hex VC blob of the presented credential
+//   zkid.status     hex status attestation for that credential (below)
+//
+// Issuers attest that a credential is in good standing at a point in time
+// (ZK_IssueStatusAttestation, which consults their revocation registry):
+//
+//   "ZKST" | version u8 | credential_id | attested_at u64 | signature [64]
+//
+// signed with the issuer key over the fields before the signature, under
+// the status usage context (see usage.rs). The wallet refreshes it as often as
+// verifiers' max_status_age demands.
+//
+// Weakened guarantees, reported as ZK_WEAKENED_* bits in ZkStatelessReport
+// and refused unless the policy accepts them:
+//
+//   ZK_WEAKENED_REPLAY               a presentation is not consumed; it can
+//                                    be replayed to any verifier sharing the
+//                                    secret until its challenge is max_age old
+//   ZK_WEAKENED_REVOCATION_STALE     a revocation reaches the verifier only
+//                                    once the holder's attestation is older
+//                                    than max_status_age
+//   ZK_WEAKENED_REVOCATION_UNCHECKED max_status_age is 0: revocation is not
+//                                    checked at all
+
+use std::os::raw::{c_char, c_int};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+use crate::usage::KeyUsage;
+use crate::verify_ed25519;
+use crate::wire::{put_bytes, Reader};
+#[cfg(feature = "prover")]
+use {
+    crate::credential::parse_signing_key,
+    crate::ffi::{write_cstr, MAX_FIELD_LEN},
+    crate::validation::revocation_status,
+    crate::bytes_to_hex,
+};
+#[cfg(feature = "verifier")]
+use {
+    crate::audit,
+    crate::challenge::validate_derived_challenge,
+    crate::clock,
+    crate::credential::parse_verifying_key,
+    crate::ffi::{read_bytes, MAX_BLOB_LEN},
+    crate::hex_to_bytes,
+    crate::presentation::{proof_check, signature_check, Presentation, ZK_CHECK_SKIPPED},
+    crate::schema,
+    crate::VerifiableCredential,
+    std::ffi::CStr,
+    std::time::Instant,
+};
+
+pub(crate) const ATTESTATION_MAGIC: &[u8; 4] = b"ZKST";
+pub(crate) const ATTESTATION_VERSION: u8 = 1;
+
+/// Presentation metadata read by the stateless profile
+pub const CHALLENGE_METADATA: &str = "zkid.challenge";
+pub const VC_METADATA: &str = "zkid.vc";
+pub const STATUS_METADATA: &str = "zkid.status";
+
+pub const ZK_WEAKENED_REPLAY: u32 = 1 << 0;
+pub const ZK_WEAKENED_REVOCATION_STALE: u32 = 1 << 1;
+pub const ZK_WEAKENED_REVOCATION_UNCHECKED: u32 = 1 << 2;
+
+/// Issuer statement that a credential was in good standing at attested_at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusAttestation {
+    pub credential_id: String,
+    pub attested_at: u64,
+    pub signature: [u8; 64],
+}
+
+impl StatusAttestation {
+    fn body(credential_id: &str, attested_at: u64) -> Vec<u8> {
+        let mut out = ATTESTATION_MAGIC.to_vec();
+        out.push(ATTESTATION_VERSION);
+        put_bytes(&mut out, credential_id.as_bytes());
+        out.extend_from_slice(&attested_at.to_le_bytes());
+        out
+    }
+
+    fn signed_message(credential_id: &str, attested_at: u64) -> Vec<u8> {
+        KeyUsage::Status.signed_message(&Self::body(credential_id, attested_at))
+    }
+
+    pub fn sign(issuer: &SigningKey, credential_id: &str, attested_at: u64) -> Self {
+        let signature = issuer.sign(&Self::signed_message(credential_id, attested_at));
+        Self {
+            credential_id: credential_id.to_string(),
+            attested_at,
+            signature: signature.to_bytes(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Self::body(&self.credential_id, self.attested_at);
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        if r.take(4)? != ATTESTATION_MAGIC || r.u8()? != ATTESTATION_VERSION {
+            return None;
+        }
+        let credential_id = r.string()?;
+        let attested_at = r.u64()?;
+        let signature = r.take(64)?.try_into().ok()?;
+        if !r.is_empty() {
+            return None;
+        }
+        Some(Self { credential_id, attested_at, signature })
+    }
+
+    pub fn verify(&self, issuer: &VerifyingKey) -> bool {
+        verify_ed25519(
+            issuer,
+            &Self::signed_message(&self.credential_id, self.attested_at),
+            &Signature::from_bytes(&self.signature),
+        )
+    }
+
+    /// Whether the attestation is signed by `issuer`, names `credential_id`
+    /// and is at most `max_age` seconds old at `now`
+    pub fn vouches_for(&self, issuer: &VerifyingKey, credential_id: &str, now: u64, max_age: u64) -> bool {
+        self.credential_id == credential_id
+            && self.attested_at <= now
+            && now - self.attested_at <= max_age
+            && self.verify(issuer)
+    }
+}
+
+/// Static configuration of a stateless verifier (C layout)
+#[cfg(feature = "verifier")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ZkStatelessPolicy {
+    /// Hex issuer public key the VC, proof and status attestation must match
+    pub issuer_public_key: *const c_char,
+    /// Hex co-signer key for dual-control schemas, or NULL
+    pub second_issuer_public_key: *const c_char,
+    /// Required "audience" metadata, or NULL to skip the check
+    pub expected_audience: *const c_char,
+    /// Passed through the configured time source (ZK_SetTimeMode)
+    pub current_time: u64,
+    /// Oldest challenge accepted, in seconds
+    pub max_age: u64,
+    /// Oldest status attestation accepted, in seconds; 0 skips revocation
+    pub max_status_age: u64,
+    /// ZK_WEAKENED_* bits the operator accepts
+    pub accept_weakened: u32,
+}
+
+/// Per-check outcome of ZK_VerifyPresentationStateless (C layout)
+///
+/// The check fields read as in ZkVerifyReport: 1 passed, 0 failed,
+/// ZK_CHECK_SKIPPED, or a negative error code if undecided.
+#[cfg(feature = "verifier")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkStatelessReport {
+    /// The presentation names the policy's issuer and the VC's issuer
+    /// signature(s) verify
+    pub signature: c_int,
+    /// The Groth16 proof verifies for the presentation's issuer key and nonce
+    pub proof: c_int,
+    /// The VC is inside its dates and validity schedule
+    pub time_policy: c_int,
+    /// A fresh status attestation from the issuer vouches for the VC;
+    /// ZK_CHECK_SKIPPED if max_status_age is 0
+    pub revocation: c_int,
+    /// The "audience" metadata equals the expected audience
+    pub audience: c_int,
+    /// The nonce is the challenge derived from the carried context, which is
+    /// not in the future and at most max_age old
+    pub freshness: c_int,
+    /// ZK_WEAKENED_* guarantees this decision went without
+    pub weakened: u32,
+}
+
+#[cfg(feature = "verifier")]
+impl ZkStatelessReport {
+    /// Whether no check failed or was left undecided
+    fn checks_passed(&self) -> bool {
+        [self.signature, self.proof, self.time_policy, self.revocation, self.audience, self.freshness]
+            .iter()
+            .all(|&code| code == 1 || code == ZK_CHECK_SKIPPED)
+    }
+
+    /// Whether every check passed and every weakened guarantee is accepted
+    pub fn accepted(&self, accept_weakened: u32) -> bool {
+        self.checks_passed() && self.weakened & !accept_weakened == 0
+    }
+}
+
+/// Hex metadata entry decoded to bytes
+#[cfg(feature = "verifier")]
+fn hex_metadata(presentation: &Presentation, key: &str) -> Option<Vec<u8>> {
+    hex_to_bytes(presentation.metadata_value(key)?).ok()
+}
+
+/// Run the stateless checks; touches no storage
+#[cfg(feature = "verifier")]
+fn verify_stateless(
+    presentation: Option<&Presentation>,
+    verifier_secret: &[u8],
+    issuer_key: &VerifyingKey,
+    second_key: Option<&VerifyingKey>,
+    expected_audience: Option<&str>,
+    policy: &ZkStatelessPolicy,
+) -> ZkStatelessReport {
+    let pvk_guard = crate::VERIFYING_KEY.lock().ok();
+    let pvk = pvk_guard.as_ref().and_then(|guard| guard.as_ref());
+
+    let vc = presentation
+        .and_then(|p| hex_metadata(p, VC_METADATA))
+        .and_then(|bytes| VerifiableCredential::from_bytes(&bytes));
+    let now = clock::now(policy.current_time);
+
+    let signature = match (presentation, &vc) {
+        (Some(p), Some(vc)) => signature_check(Some(p), Some(vc), issuer_key, second_key),
+        _ => 0,
+    };
+
+    let proof = match (presentation, pvk) {
+        (Some(p), Some(pvk)) => proof_check(p, pvk, vc.as_ref()),
+        _ => 0,
+    };
+
+    let time_policy = match (&vc, now) {
+        (Some(vc), Ok(now)) => vc.active_at(now) as c_int,
+        (Some(_), Err(code)) => code,
+        (None, _) => 0,
+    };
+
+    let mut weakened = ZK_WEAKENED_REPLAY;
+    let revocation = if policy.max_status_age == 0 {
+        weakened |= ZK_WEAKENED_REVOCATION_UNCHECKED;
+        ZK_CHECK_SKIPPED
+    } else {
+        weakened |= ZK_WEAKENED_REVOCATION_STALE;
+        let attestation = presentation
+            .and_then(|p| hex_metadata(p, STATUS_METADATA))
+            .and_then(|bytes| StatusAttestation::from_bytes(&bytes));
+        match (&vc, attestation, now) {
+            (Some(vc), Some(attestation), Ok(now)) => {
+                attestation.vouches_for(issuer_key, &vc.credential_id(), now, policy.max_status_age) as c_int
+            }
+            (_, _, Err(code)) => code,
+            _ => 0,
+        }
+    };
+
+    let audience = match expected_audience {
+        Some(expected) => (presentation.and_then(|p| p.metadata_value("audience")) == Some(expected)) as c_int,
+        None => ZK_CHECK_SKIPPED,
+    };
+
+    let context = presentation.and_then(|p| hex_metadata(p, CHALLENGE_METADATA));
+    let freshness = match (presentation, context, now) {
+        (Some(p), Some(context), Ok(now)) => {
+            validate_derived_challenge(verifier_secret, &context, p.nonce, now, policy.max_age) as c_int
+        }
+        (Some(_), Some(_), Err(code)) => code,
+        _ => 0,
+    };
+
+    ZkStatelessReport {
+        signature,
+        proof,
+        time_policy,
+        revocation,
+        audience,
+        freshness,
+        weakened,
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Attest that a credential is in good standing, for stateless verifiers
+///
+/// Consults the local revocation registry (and ZK_SetRejectSuperseded).
+/// `issuer_private_key` is the hex issuer key, `credential_id` as returned
+/// by ZK_ComputeVCHash / ZK_StoreAddVC. The hex attestation is written to
+/// `attestation_out`. Returns 0 on success, 1 if the credential is revoked
+/// (or superseded), ZK_ERR_STORAGE if the registry lookup failed,
+/// ZK_ERR_BUFFER_TOO_SMALL, or -1 on NULL or a malformed key.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_IssueStatusAttestation(
+    issuer_private_key: *const c_char,
+    credential_id: *const c_char,
+    attested_at: u64,
+    attestation_out: *mut c_char,
+    attestation_out_size: usize,
+) -> c_int {
+    if issuer_private_key.is_null() || credential_id.is_null() {
+        return -1;
+    }
+    let issuer = match parse_signing_key(issuer_private_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let credential_id = match crate::admission::text_arg(credential_id, MAX_FIELD_LEN) {
+        Some(id) if !id.is_empty() => id,
+        _ => return -1,
+    };
+
+    match revocation_status(Some(&issuer.verifying_key().to_bytes()), credential_id) {
+        1 => {}
+        0 => return 1,
+        code => return code,
+    }
+
+    let attestation = StatusAttestation::sign(&issuer, credential_id, attested_at);
+    match write_cstr(attestation_out, attestation_out_size, &bytes_to_hex(&attestation.to_bytes())) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Verify a presentation without any verifier state
+///
+/// `presentation_blob` is binary or hex text carrying the zkid.challenge,
+/// zkid.vc and zkid.status metadata (see the top of this file);
+/// `verifier_secret` is the hex secret challenges are derived with. Every
+/// check runs and is reported in `report_out`, with the ZK_WEAKENED_* bits
+/// of guarantees a stateless decision cannot give. The storage backend is
+/// never used: nothing is consumed or recorded.
+///
+/// Returns 1 if every check passed and `policy->accept_weakened` covers
+/// `report_out->weakened`, 0 otherwise, ZK_ERR_SCHEMA_NOT_ALLOWED if the
+/// zkid.vc credential's schema is outside the allowlist
+/// (ZK_SetSchemaAllowlist), -1 on NULL arguments, an empty or malformed
+/// secret, or a malformed issuer key.
+#[cfg(feature = "verifier")]
+#[no_mangle]
+pub extern "C" fn ZK_VerifyPresentationStateless(
+    presentation_blob: *const c_char,
+    presentation_blob_len: usize,
+    verifier_secret: *const c_char,
+    policy: *const ZkStatelessPolicy,
+    report_out: *mut ZkStatelessReport,
+) -> c_int {
+    let started = Instant::now();
+    let policy = match unsafe { policy.as_ref() } {
+        Some(policy) if !presentation_blob.is_null() && !verifier_secret.is_null() && !report_out.is_null() => {
+            *policy
+        }
+        _ => return -1,
+    };
+
+    let secret = match hex_to_bytes(unsafe { CStr::from_ptr(verifier_secret) }.to_str().unwrap_or("")) {
+        Ok(secret) if !secret.is_empty() => secret,
+        _ => return -1,
+    };
+    let issuer_key = match parse_verifying_key(policy.issuer_public_key) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let second_key = if policy.second_issuer_public_key.is_null() {
+        None
+    } else {
+        match parse_verifying_key(policy.second_issuer_public_key) {
+            Some(key) => Some(key),
+            None => return -1,
+        }
+    };
+    let expected_audience = if policy.expected_audience.is_null() {
+        None
+    } else {
+        match unsafe { CStr::from_ptr(policy.expected_audience) }.to_str() {
+            Ok(audience) => Some(audience),
+            Err(_) => return -1,
+        }
+    };
+
+    let data = match read_bytes(presentation_blob, presentation_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let presentation = Presentation::from_wire(data);
+
+    let report = verify_stateless(
+        presentation.as_ref(),
+        &secret,
+        &issuer_key,
+        second_key.as_ref(),
+        expected_audience,
+        &policy,
+    );
+    unsafe {
+        *report_out = report;
+    }
+    let result = schema::gate(report.accepted(policy.accept_weakened) as c_int, presentation.as_ref(), None);
+
+    let pvk_guard = crate::VERIFYING_KEY.lock().ok();
+    audit::record(started, audit::Verification {
+        check: "presentation-stateless",
+        code: result,
+        circuit: Some(audit::VC_CIRCUIT),
+        vk: pvk_guard.as_ref().and_then(|guard| guard.as_ref()).map(|pvk| &pvk.vk),
+        nonce: presentation.as_ref().map(|p| p.nonce),
+        audience: presentation.as_ref().and_then(|p| p.metadata_value("audience")),
+        predicate: None,
+    });
+
+    result
+}
+}
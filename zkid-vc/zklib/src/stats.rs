@@ -0,0 +1,280 @@
+// ============================================================================
+// Issuer Statistics and Issuance Alarm
+// ============================================================================
+//
+// Every credential an issuer signs is counted per issuer public key so
+// issuance services can spot runaway signing. The signers (ZK_SignVC,
+// ZK_SignVCWithClaims, ZK_SignVCBlob, ZK_CoSignVC, ZK_ReissueVC, composite
+// group signing, ZK_SignVCJwt) all sign through `sign_issuance`, so none of
+// them escapes the counters or the alarm; a threshold signature is counted
+// under the group key once ZK_ThresholdAggregate has combined it. Memory per issuer is constant: distinct holders
+// are estimated with a 64-register HyperLogLog over salted holder hashes
+// (raw holder_ids are never stored) and the rolling rate uses one bucket per
+// second of the last minute.
+
+use ed25519_dalek::{Signature, Signer, SigningKey};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{bytes_to_hex, did};
+
+const HLL_REGISTERS: usize = 64;
+const HLL_INDEX_BITS: u32 = 6;
+const RATE_WINDOW_SECS: usize = 60;
+
+/// Issuance counters for one issuer key (C layout for ZK_GetIssuerStats)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZkIssuerStats {
+    /// Credentials signed since the last reset
+    pub credentials_signed: u64,
+    /// Estimated number of distinct holders (HyperLogLog, ~13% error)
+    pub distinct_holders: u64,
+    /// Signatures in the last 60 seconds
+    pub signatures_last_minute: u32,
+    /// Highest value of signatures_last_minute seen since the last reset
+    pub peak_per_minute: u32,
+}
+
+/// Called with the hex issuer public key and current per-minute rate
+pub type IssuanceAlarmCallback = extern "C" fn(issuer_pubkey: *const c_char, signatures_last_minute: u32);
+
+struct IssuerCounters {
+    credentials_signed: u64,
+    peak_per_minute: u32,
+    registers: [u8; HLL_REGISTERS],
+    // (unix second, signatures in that second)
+    buckets: [(u64, u32); RATE_WINDOW_SECS],
+}
+
+impl IssuerCounters {
+    fn new() -> Self {
+        Self {
+            credentials_signed: 0,
+            peak_per_minute: 0,
+            registers: [0; HLL_REGISTERS],
+            buckets: [(0, 0); RATE_WINDOW_SECS],
+        }
+    }
+
+    fn record(&mut self, holder_hash: u64, now: u64) -> u32 {
+        self.credentials_signed += 1;
+
+        let index = (holder_hash >> (64 - HLL_INDEX_BITS)) as usize;
+        let rank = ((holder_hash << HLL_INDEX_BITS).leading_zeros() + 1).min(64 - HLL_INDEX_BITS + 1) as u8;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+
+        let bucket = &mut self.buckets[(now % RATE_WINDOW_SECS as u64) as usize];
+        if bucket.0 != now {
+            *bucket = (now, 0);
+        }
+        bucket.1 += 1;
+
+        let rate = self.rate(now);
+        self.peak_per_minute = self.peak_per_minute.max(rate);
+        rate
+    }
+
+    fn rate(&self, now: u64) -> u32 {
+        self.buckets
+            .iter()
+            .filter(|(sec, _)| *sec <= now && now - *sec < RATE_WINDOW_SECS as u64)
+            .map(|(_, count)| *count)
+            .sum()
+    }
+
+    fn distinct_estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+
+        let raw = 0.709 * m * m / sum;
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+
+    fn snapshot(&self, now: u64) -> ZkIssuerStats {
+        ZkIssuerStats {
+            credentials_signed: self.credentials_signed,
+            distinct_holders: self.distinct_estimate(),
+            signatures_last_minute: self.rate(now),
+            peak_per_minute: self.peak_per_minute,
+        }
+    }
+}
+
+struct Alarm {
+    max_per_minute: u32,
+    callback: IssuanceAlarmCallback,
+}
+
+static COUNTERS: Mutex<Option<HashMap<[u8; 32], IssuerCounters>>> = Mutex::new(None);
+static ALARM: Mutex<Option<Alarm>> = Mutex::new(None);
+
+// Per-process salt so holder hashes cannot be matched across deployments
+fn holder_salt() -> &'static [u8; 32] {
+    static SALT: OnceLock<[u8; 32]> = OnceLock::new();
+    SALT.get_or_init(|| {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Count one signature by `issuer_pubkey` for `holder_id`
+pub fn record_issuance(issuer_pubkey: &[u8; 32], holder_id: &[u8]) {
+    let mut hasher = Sha256::new();
+    hasher.update(holder_salt());
+    hasher.update(holder_id);
+    let digest = hasher.finalize();
+    let holder_hash = u64::from_le_bytes(digest[..8].try_into().unwrap_or([0; 8]));
+
+    let now = unix_now();
+    let rate = {
+        let mut guard = COUNTERS.lock().unwrap_or_else(|e| e.into_inner());
+        guard
+            .get_or_insert_with(HashMap::new)
+            .entry(*issuer_pubkey)
+            .or_insert_with(IssuerCounters::new)
+            .record(holder_hash, now)
+    };
+
+    // Invoke the alarm outside the counters lock
+    let alarm = ALARM
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .filter(|alarm| rate > alarm.max_per_minute)
+        .map(|alarm| alarm.callback);
+    if let Some(callback) = alarm {
+        let mut pubkey_hex = bytes_to_hex(issuer_pubkey).into_bytes();
+        pubkey_hex.push(0);
+        callback(pubkey_hex.as_ptr() as *const c_char, rate);
+    }
+}
+
+/// Sign a credential message for `holder_id` with `issuer` and count it
+pub fn sign_issuance(issuer: &SigningKey, holder_id: &[u8], message: &[u8]) -> Signature {
+    let signature = issuer.sign(message);
+    record_issuance(issuer.verifying_key().as_bytes(), holder_id);
+    signature
+}
+
+/// Current counters for `issuer_pubkey` (all zero if it never signed)
+pub fn issuer_stats(issuer_pubkey: &[u8; 32]) -> ZkIssuerStats {
+    let guard = COUNTERS.lock().unwrap_or_else(|e| e.into_inner());
+    guard
+        .as_ref()
+        .and_then(|map| map.get(issuer_pubkey))
+        .map(|counters| counters.snapshot(unix_now()))
+        .unwrap_or_default()
+}
+
+/// Reset the counters of one issuer, or of all issuers when `None`
+pub fn reset_issuer_stats(issuer_pubkey: Option<&[u8; 32]>) {
+    let mut guard = COUNTERS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(map) = guard.as_mut() {
+        match issuer_pubkey {
+            Some(key) => {
+                map.remove(key);
+            }
+            None => map.clear(),
+        }
+    }
+}
+
+fn parse_pubkey(issuer_pubkey: *const c_char) -> Option<[u8; 32]> {
+    let pubkey_str = unsafe {
+        CStr::from_ptr(issuer_pubkey).to_str().unwrap_or("")
+    };
+    did::public_key_bytes(pubkey_str).ok()?.as_slice().try_into().ok()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Read the issuance counters for a hex-encoded issuer public key
+///
+/// Returns 0 on success, -1 on invalid arguments.
+#[no_mangle]
+pub extern "C" fn ZK_GetIssuerStats(
+    issuer_pubkey: *const c_char,
+    stats_out: *mut ZkIssuerStats,
+) -> c_int {
+    if issuer_pubkey.is_null() || stats_out.is_null() {
+        return -1;
+    }
+
+    let pubkey = match parse_pubkey(issuer_pubkey) {
+        Some(key) => key,
+        None => return -1,
+    };
+
+    unsafe {
+        *stats_out = issuer_stats(&pubkey);
+    }
+
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Reset the counters for one issuer (or all issuers if `issuer_pubkey` is NULL)
+///
+/// Returns 0 on success, -1 on invalid public key.
+#[no_mangle]
+pub extern "C" fn ZK_ResetIssuerStats(issuer_pubkey: *const c_char) -> c_int {
+    if issuer_pubkey.is_null() {
+        reset_issuer_stats(None);
+        return 0;
+    }
+
+    match parse_pubkey(issuer_pubkey) {
+        Some(key) => {
+            reset_issuer_stats(Some(&key));
+            0
+        }
+        None => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Install (or remove with a NULL callback) the issuance-rate alarm
+///
+/// The callback runs on the signing thread whenever an issuer exceeds
+/// `max_per_minute` signatures in the last 60 seconds.
+#[no_mangle]
+pub extern "C" fn ZK_SetIssuanceAlarm(
+    max_per_minute: u32,
+    callback: Option<IssuanceAlarmCallback>,
+) -> c_int {
+    let mut guard = ALARM.lock().unwrap_or_else(|e| e.into_inner());
+    *guard = callback.map(|callback| Alarm {
+        max_per_minute,
+        callback,
+    });
+    0
+}
+}
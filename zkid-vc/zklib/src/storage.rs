@@ -0,0 +1,243 @@
+// ============================================================================
+// Storage Backend
+// ============================================================================
+//
+// The challenge store, nullifier set and revocation registry persist their
+// state through the `Storage` trait so deployments can choose the database.
+// By default everything lives in memory; the host can install its own
+// backend through ZK_SetStorageCallbacks.
+
+use std::collections::BTreeMap;
+use std::os::raw::{c_int, c_void};
+use crate::sync::{Mutex, MutexGuard};
+use std::sync::{Arc, RwLock};
+
+use crate::error::{ZK_ERR_CAPACITY, ZK_ERR_STORAGE};
+
+/// Failure reported by a storage backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageError {
+    /// The backend returned an error or is unreachable
+    Backend,
+    /// The backend does not implement the requested operation
+    Unsupported,
+    /// The collection is full and its eviction policy refuses the insert
+    Full,
+}
+
+impl StorageError {
+    pub fn code(self) -> c_int {
+        match self {
+            StorageError::Full => ZK_ERR_CAPACITY,
+            _ => ZK_ERR_STORAGE,
+        }
+    }
+}
+
+/// Key-value pairs returned by a prefix scan
+pub type StorageEntries = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Key-value persistence used by the registries
+pub trait Storage: Send + Sync {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError>;
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError>;
+    fn delete(&self, key: &[u8]) -> Result<(), StorageError>;
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<StorageEntries, StorageError>;
+}
+
+type EntryMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// In-memory storage (default backend)
+#[derive(Default)]
+pub struct MemoryStorage {
+    entries: Mutex<EntryMap>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> Result<MutexGuard<'_, EntryMap>, StorageError> {
+        self.entries.lock().map_err(|_| StorageError::Backend)
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.entries()?.get(key).cloned())
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.entries()?.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), StorageError> {
+        self.entries()?.remove(key);
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<StorageEntries, StorageError> {
+        Ok(self
+            .entries()?
+            .range(prefix.to_vec()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
+// ============================================================================
+// Host Callback Backend
+// ============================================================================
+
+/// Look up `key`. Returns 0 if found (value copied to `value_out`, length in
+/// `value_len_out`), 1 if absent, negative on error. If the value does not fit
+/// in `value_cap`, set `value_len_out` to the required length and return 0;
+/// the library retries with a larger buffer.
+pub type StorageGetCallback = extern "C" fn(
+    user_data: *mut c_void,
+    key: *const u8,
+    key_len: usize,
+    value_out: *mut u8,
+    value_cap: usize,
+    value_len_out: *mut usize,
+) -> c_int;
+
+/// Store `value` under `key`. Returns 0 on success, negative on error.
+pub type StoragePutCallback = extern "C" fn(
+    user_data: *mut c_void,
+    key: *const u8,
+    key_len: usize,
+    value: *const u8,
+    value_len: usize,
+) -> c_int;
+
+/// Delete `key` (absent keys are not an error). Returns 0 on success, negative on error.
+pub type StorageDeleteCallback = extern "C" fn(
+    user_data: *mut c_void,
+    key: *const u8,
+    key_len: usize,
+) -> c_int;
+
+const CALLBACK_INITIAL_VALUE_CAP: usize = 256;
+
+/// Storage backed by host-provided callbacks
+pub struct CallbackStorage {
+    get_cb: StorageGetCallback,
+    put_cb: StoragePutCallback,
+    delete_cb: StorageDeleteCallback,
+    user_data: *mut c_void,
+}
+
+// The host guarantees that the callbacks and user_data may be used from any thread
+unsafe impl Send for CallbackStorage {}
+unsafe impl Sync for CallbackStorage {}
+
+impl Storage for CallbackStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        let mut value = vec![0u8; CALLBACK_INITIAL_VALUE_CAP];
+        loop {
+            let mut value_len = 0usize;
+            let rc = (self.get_cb)(
+                self.user_data,
+                key.as_ptr(),
+                key.len(),
+                value.as_mut_ptr(),
+                value.len(),
+                &mut value_len,
+            );
+            match rc {
+                0 if value_len <= value.len() => {
+                    value.truncate(value_len);
+                    return Ok(Some(value));
+                }
+                0 => value.resize(value_len, 0),
+                1 => return Ok(None),
+                _ => return Err(StorageError::Backend),
+            }
+        }
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        match (self.put_cb)(self.user_data, key.as_ptr(), key.len(), value.as_ptr(), value.len()) {
+            0 => Ok(()),
+            _ => Err(StorageError::Backend),
+        }
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), StorageError> {
+        match (self.delete_cb)(self.user_data, key.as_ptr(), key.len()) {
+            0 => Ok(()),
+            _ => Err(StorageError::Backend),
+        }
+    }
+
+    fn scan_prefix(&self, _prefix: &[u8]) -> Result<StorageEntries, StorageError> {
+        Err(StorageError::Unsupported)
+    }
+}
+
+// ============================================================================
+// Active Backend
+// ============================================================================
+
+static STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+
+/// Backend used by the registries (in-memory unless replaced)
+pub fn storage() -> Arc<dyn Storage> {
+    if let Ok(guard) = STORAGE.read() {
+        if let Some(storage) = guard.as_ref() {
+            return storage.clone();
+        }
+    }
+
+    let mut guard = STORAGE.write().unwrap_or_else(|e| e.into_inner());
+    guard
+        .get_or_insert_with(|| Arc::new(MemoryStorage::new()))
+        .clone()
+}
+
+/// Replace the backend used by the registries
+pub fn set_storage(storage: Arc<dyn Storage>) {
+    let mut guard = STORAGE.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(storage);
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Route challenge, nullifier and revocation state through host callbacks
+///
+/// Passing NULL for all three callbacks restores a fresh in-memory backend.
+/// `user_data` is passed back to every callback; the callbacks must be safe
+/// to call from any thread.
+/// Returns 0 on success, -1 if only some callbacks are provided.
+#[no_mangle]
+pub extern "C" fn ZK_SetStorageCallbacks(
+    get_cb: Option<StorageGetCallback>,
+    put_cb: Option<StoragePutCallback>,
+    delete_cb: Option<StorageDeleteCallback>,
+    user_data: *mut c_void,
+) -> c_int {
+    match (get_cb, put_cb, delete_cb) {
+        (Some(get_cb), Some(put_cb), Some(delete_cb)) => {
+            set_storage(Arc::new(CallbackStorage {
+                get_cb,
+                put_cb,
+                delete_cb,
+                user_data,
+            }));
+            0
+        }
+        (None, None, None) => {
+            set_storage(Arc::new(MemoryStorage::new()));
+            0
+        }
+        _ => -1,
+    }
+}
+}
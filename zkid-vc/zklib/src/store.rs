@@ -0,0 +1,715 @@
+// ============================================================================
+// Holder Credential Store (encrypted at rest)
+// ============================================================================
+//
+// Wallets keep their VC blobs in a single encrypted file. The file key is
+// either a raw 32-byte key or derived from a passphrase with Argon2id; every
+// record is sealed with XChaCha20-Poly1305 under its own random nonce.
+//
+// File layout (version 3), integers little-endian:
+//
+//   header:  "ZKCS" | version u8 | kdf u8 | salt[16] | m_cost u32 | t_cost u32
+//            | p_cost u32 | record_count u32
+//   check:   nonce[24] | len u32 | seal("zkid.store.keycheck")
+//   records: (nonce[24] | len u32 | seal(id | vc_blob))*
+//   links:   nonce[24] | len u32 | seal(link_count u32 | (old_id | new_id)*)
+//   consent: nonce[24] | len u32 | seal(receipt_count u32 | receipt*)
+//
+// Version 1 files have no links block, version 2 files no consent block.
+// Links record supersession so a lookup by an old credential id resolves to
+// the newest credential; the consent block holds the holder's signed consent
+// receipts (see consent.rs) in the order they were made.
+//
+// The header, as the bytes in the file, is authenticated as associated data
+// of every sealed block, and each record additionally binds its index, so
+// editing the header, dropping, reordering or truncating records fails
+// authentication. The Argon2 costs are read before the header can be
+// authenticated (they derive the key that checks it), so costs beyond four
+// times those a store is created with are refused as corrupt before Argon2
+// runs. Decrypted
+// credentials are held in zeroizing buffers and wiped on close.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand_core::{OsRng, RngCore};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::error::{ZK_ERR_AUTH, ZK_ERR_CORRUPT};
+use crate::ffi::{read_bytes, write_cstr, MAX_FIELD_LEN};
+use crate::wire::{put_bytes, Reader};
+use crate::{hex_to_bytes, VerifiableCredential};
+
+const STORE_MAGIC: &[u8; 4] = b"ZKCS";
+const STORE_VERSION: u8 = 3;
+const MAX_SUPERSESSION_CHAIN: usize = 64;
+const KEY_CHECK: &[u8] = b"zkid.store.keycheck";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = 4 + 1 + 1 + SALT_LEN + 4 * 4;
+
+pub const ZK_STORE_KEY_PASSPHRASE: c_int = 0;
+pub const ZK_STORE_KEY_RAW: c_int = 1;
+
+/// Failure opening or updating a credential store
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreError {
+    /// Wrong key/passphrase or tampered contents
+    Authentication,
+    /// Truncated or structurally invalid file, unknown version
+    Corrupt,
+    /// Filesystem or key-derivation failure
+    Io,
+    /// Credential blob could not be parsed
+    InvalidCredential,
+}
+
+impl StoreError {
+    pub fn code(self) -> c_int {
+        match self {
+            StoreError::Authentication => ZK_ERR_AUTH,
+            StoreError::Corrupt => ZK_ERR_CORRUPT,
+            StoreError::Io | StoreError::InvalidCredential => -1,
+        }
+    }
+}
+
+/// How the file key was obtained
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kdf {
+    Raw,
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+}
+
+/// Key material used to open a store
+pub enum StoreKey<'a> {
+    Passphrase(&'a [u8]),
+    Raw([u8; 32]),
+}
+
+/// What CredentialStore::merge did with each incoming credential_id
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeOutcome {
+    pub restored: Vec<String>,
+    /// Already stored; the store's copy was kept
+    pub kept: Vec<String>,
+    /// Superseded in the store; not restored
+    pub superseded: Vec<String>,
+    pub receipts_added: usize,
+}
+
+/// Encrypted collection of credentials, keyed by credential_id
+pub struct CredentialStore {
+    path: PathBuf,
+    kdf: Kdf,
+    salt: [u8; SALT_LEN],
+    key: Zeroizing<[u8; 32]>,
+    records: BTreeMap<String, Zeroizing<Vec<u8>>>,
+    links: BTreeMap<String, String>,
+    receipts: Vec<Vec<u8>>,
+}
+
+fn derive_key(kdf: Kdf, salt: &[u8; SALT_LEN], key: &StoreKey) -> Result<Zeroizing<[u8; 32]>, StoreError> {
+    let mut out = Zeroizing::new([0u8; 32]);
+    match (kdf, key) {
+        (Kdf::Raw, StoreKey::Raw(raw)) => out.copy_from_slice(raw),
+        (Kdf::Argon2id { m_cost, t_cost, p_cost }, StoreKey::Passphrase(passphrase)) => {
+            // Costs come from a header not yet authenticated; a forged one
+            // must not make open allocate or spin at will
+            if m_cost > Params::DEFAULT_M_COST * 4
+                || t_cost > Params::DEFAULT_T_COST * 4
+                || p_cost > Params::DEFAULT_P_COST * 4
+            {
+                return Err(StoreError::Corrupt);
+            }
+            let params = Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|_| StoreError::Corrupt)?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(passphrase, salt, out.as_mut())
+                .map_err(|_| StoreError::Io)?;
+        }
+        // Opening a passphrase store with a raw key (or vice versa) is a wrong key
+        _ => return Err(StoreError::Authentication),
+    }
+    Ok(out)
+}
+
+impl CredentialStore {
+    /// Open the store at `path`, creating an empty one if the file does not exist
+    pub fn open(path: &Path, key: StoreKey) -> Result<Self, StoreError> {
+        match std::fs::read(path) {
+            Ok(data) => Self::decode(path, &data, key),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::create(path, key),
+            Err(_) => Err(StoreError::Io),
+        }
+    }
+
+    fn create(path: &Path, key: StoreKey) -> Result<Self, StoreError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let kdf = match key {
+            StoreKey::Raw(_) => Kdf::Raw,
+            StoreKey::Passphrase(_) => Kdf::Argon2id {
+                m_cost: Params::DEFAULT_M_COST,
+                t_cost: Params::DEFAULT_T_COST,
+                p_cost: Params::DEFAULT_P_COST,
+            },
+        };
+
+        let store = Self {
+            path: path.to_path_buf(),
+            kdf,
+            salt,
+            key: derive_key(kdf, &salt, &key)?,
+            records: BTreeMap::new(),
+            links: BTreeMap::new(),
+            receipts: Vec::new(),
+        };
+        store.save()?;
+        Ok(store)
+    }
+
+    fn header(&self, version: u8, record_count: u32) -> Vec<u8> {
+        let mut header = STORE_MAGIC.to_vec();
+        header.push(version);
+        let (kdf_id, m, t, p) = match self.kdf {
+            Kdf::Raw => (0u8, 0, 0, 0),
+            Kdf::Argon2id { m_cost, t_cost, p_cost } => (1u8, m_cost, t_cost, p_cost),
+        };
+        header.push(kdf_id);
+        header.extend_from_slice(&self.salt);
+        for v in [m, t, p, record_count] {
+            header.extend_from_slice(&v.to_le_bytes());
+        }
+        header
+    }
+
+    fn seal(cipher: &XChaCha20Poly1305, aad: &[u8], plaintext: &[u8], out: &mut Vec<u8>) -> Result<(), StoreError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| StoreError::Io)?;
+        out.extend_from_slice(&nonce);
+        put_bytes(out, &ciphertext);
+        Ok(())
+    }
+
+    fn open_sealed(cipher: &XChaCha20Poly1305, aad: &[u8], r: &mut Reader) -> Result<Zeroizing<Vec<u8>>, StoreError> {
+        let nonce = r.take(NONCE_LEN).ok_or(StoreError::Corrupt)?;
+        let ciphertext = r.bytes().ok_or(StoreError::Corrupt)?;
+        cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map(Zeroizing::new)
+            .map_err(|_| StoreError::Authentication)
+    }
+
+    fn record_aad(header: &[u8], index: u32) -> Vec<u8> {
+        let mut aad = header.to_vec();
+        aad.extend_from_slice(&index.to_le_bytes());
+        aad
+    }
+
+    /// Serialize and encrypt the whole store
+    pub fn encode(&self) -> Result<Vec<u8>, StoreError> {
+        let cipher = XChaCha20Poly1305::new(self.key.as_ref().into());
+        let record_count = self.records.len() as u32;
+        let header = self.header(STORE_VERSION, record_count);
+
+        let mut out = header.clone();
+        Self::seal(&cipher, &header, KEY_CHECK, &mut out)?;
+
+        for (index, (id, blob)) in self.records.iter().enumerate() {
+            let mut plaintext = Zeroizing::new(Vec::new());
+            put_bytes(&mut plaintext, id.as_bytes());
+            put_bytes(&mut plaintext, blob);
+            Self::seal(&cipher, &Self::record_aad(&header, index as u32), &plaintext, &mut out)?;
+        }
+
+        let mut links = Zeroizing::new((self.links.len() as u32).to_le_bytes().to_vec());
+        for (old_id, new_id) in &self.links {
+            put_bytes(&mut links, old_id.as_bytes());
+            put_bytes(&mut links, new_id.as_bytes());
+        }
+        Self::seal(&cipher, &Self::record_aad(&header, record_count), &links, &mut out)?;
+
+        let mut receipts = (self.receipts.len() as u32).to_le_bytes().to_vec();
+        for receipt in &self.receipts {
+            put_bytes(&mut receipts, receipt);
+        }
+        Self::seal(&cipher, &Self::record_aad(&header, record_count + 1), &receipts, &mut out)?;
+
+        Ok(out)
+    }
+
+    fn decode(path: &Path, data: &[u8], key: StoreKey) -> Result<Self, StoreError> {
+        let mut r = Reader::new(data);
+        if r.take(4) != Some(STORE_MAGIC.as_slice()) {
+            return Err(StoreError::Corrupt);
+        }
+        let version = match r.u8() {
+            Some(v @ 1..=STORE_VERSION) => v,
+            _ => return Err(StoreError::Corrupt),
+        };
+
+        let kdf_id = r.u8().ok_or(StoreError::Corrupt)?;
+        let salt: [u8; SALT_LEN] = r
+            .take(SALT_LEN)
+            .and_then(|s| s.try_into().ok())
+            .ok_or(StoreError::Corrupt)?;
+        let m_cost = r.u32().ok_or(StoreError::Corrupt)?;
+        let t_cost = r.u32().ok_or(StoreError::Corrupt)?;
+        let p_cost = r.u32().ok_or(StoreError::Corrupt)?;
+        let record_count = r.u32().ok_or(StoreError::Corrupt)?;
+
+        let kdf = match kdf_id {
+            0 => Kdf::Raw,
+            1 => Kdf::Argon2id { m_cost, t_cost, p_cost },
+            _ => return Err(StoreError::Corrupt),
+        };
+
+        let mut store = Self {
+            path: path.to_path_buf(),
+            kdf,
+            salt,
+            key: derive_key(kdf, &salt, &key)?,
+            records: BTreeMap::new(),
+            links: BTreeMap::new(),
+            receipts: Vec::new(),
+        };
+
+        // The header exactly as stored, unused cost fields of a raw key included
+        let header = &data[..HEADER_LEN];
+        let cipher = XChaCha20Poly1305::new(store.key.as_ref().into());
+
+        if Self::open_sealed(&cipher, header, &mut r)?.as_slice() != KEY_CHECK {
+            return Err(StoreError::Authentication);
+        }
+
+        for index in 0..record_count {
+            let plaintext = Self::open_sealed(&cipher, &Self::record_aad(header, index), &mut r)?;
+            let mut pr = Reader::new(&plaintext);
+            let id = pr.string().ok_or(StoreError::Corrupt)?;
+            let blob = pr.bytes().ok_or(StoreError::Corrupt)?;
+            store.records.insert(id, Zeroizing::new(blob.to_vec()));
+        }
+
+        if version >= 2 {
+            let links = Self::open_sealed(&cipher, &Self::record_aad(header, record_count), &mut r)?;
+            let mut lr = Reader::new(&links);
+            let link_count = lr.u32().ok_or(StoreError::Corrupt)?;
+            for _ in 0..link_count {
+                let old_id = lr.string().ok_or(StoreError::Corrupt)?;
+                let new_id = lr.string().ok_or(StoreError::Corrupt)?;
+                store.links.insert(old_id, new_id);
+            }
+        }
+
+        if version >= 3 {
+            let receipts = Self::open_sealed(&cipher, &Self::record_aad(header, record_count + 1), &mut r)?;
+            let mut rr = Reader::new(&receipts);
+            let receipt_count = rr.u32().ok_or(StoreError::Corrupt)?;
+            for _ in 0..receipt_count {
+                store.receipts.push(rr.bytes().ok_or(StoreError::Corrupt)?.to_vec());
+            }
+        }
+
+        if !r.is_empty() {
+            return Err(StoreError::Corrupt);
+        }
+
+        Ok(store)
+    }
+
+    /// Write the encrypted store atomically (temp file + rename)
+    pub fn save(&self) -> Result<(), StoreError> {
+        let data = self.encode()?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, &data).map_err(|_| StoreError::Io)?;
+        std::fs::rename(&tmp, &self.path).map_err(|_| StoreError::Io)
+    }
+
+    /// Add (or replace) a credential and persist; returns its credential_id
+    pub fn add(&mut self, vc_blob: &[u8]) -> Result<String, StoreError> {
+        let vc = VerifiableCredential::from_bytes(vc_blob).ok_or(StoreError::InvalidCredential)?;
+        let id = vc.credential_id();
+        self.records.insert(id.clone(), Zeroizing::new(vc_blob.to_vec()));
+        self.save()?;
+        Ok(id)
+    }
+
+    /// Credential stored under `credential_id`, following supersession links
+    pub fn get(&self, credential_id: &str) -> Option<&[u8]> {
+        self.records.get(&self.resolve(credential_id)).map(|blob| blob.as_slice())
+    }
+
+    /// Newest credential id reachable from `credential_id`
+    pub fn resolve(&self, credential_id: &str) -> String {
+        let mut current = credential_id;
+        for _ in 0..MAX_SUPERSESSION_CHAIN {
+            match self.links.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current.to_string()
+    }
+
+    /// Replace the credential a re-issued blob supersedes, in one atomic write
+    ///
+    /// The superseded credential (and anything it superseded) is removed and a
+    /// link keeps old ids resolvable. Returns the new credential_id.
+    pub fn apply_supersession(&mut self, new_vc_blob: &[u8]) -> Result<String, StoreError> {
+        let vc = VerifiableCredential::from_bytes(new_vc_blob).ok_or(StoreError::InvalidCredential)?;
+        let old_id = vc.supersedes.clone().ok_or(StoreError::InvalidCredential)?;
+        let new_id = vc.credential_id();
+
+        let mut updated = self.records.clone();
+        let mut links = self.links.clone();
+        updated.remove(&self.resolve(&old_id));
+        updated.insert(new_id.clone(), Zeroizing::new(new_vc_blob.to_vec()));
+        links.insert(old_id, new_id.clone());
+
+        let previous = (
+            std::mem::replace(&mut self.records, updated),
+            std::mem::replace(&mut self.links, links),
+        );
+        if let Err(e) = self.save() {
+            (self.records, self.links) = previous;
+            return Err(e);
+        }
+        Ok(new_id)
+    }
+
+    /// Remove a credential and persist; returns false if it was not stored
+    pub fn remove(&mut self, credential_id: &str) -> Result<bool, StoreError> {
+        if self.records.remove(credential_id).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Append a consent receipt and persist
+    pub fn add_receipt(&mut self, receipt: &[u8]) -> Result<(), StoreError> {
+        self.receipts.push(receipt.to_vec());
+        if let Err(e) = self.save() {
+            self.receipts.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Stored consent receipts, oldest first
+    pub fn receipts(&self) -> impl Iterator<Item = &[u8]> {
+        self.receipts.iter().map(|receipt| receipt.as_slice())
+    }
+
+    /// Supersession links as (old_id, new_id)
+    pub fn links(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.links.iter().map(|(old_id, new_id)| (old_id.as_str(), new_id.as_str()))
+    }
+
+    /// Merge credentials, links and receipts from another store's contents
+    /// (a backup) in one atomic write
+    ///
+    /// Conflict policy: a credential_id already stored is kept as is (ids
+    /// are content hashes, so both copies are the same credential); one the
+    /// store has a supersession link for is not restored; links are added
+    /// for old ids the store has no link for; receipts are appended unless
+    /// already present.
+    pub fn merge(
+        &mut self,
+        records: Vec<(String, Zeroizing<Vec<u8>>)>,
+        links: Vec<(String, String)>,
+        receipts: Vec<Vec<u8>>,
+    ) -> Result<MergeOutcome, StoreError> {
+        let mut outcome = MergeOutcome::default();
+        let mut updated = self.records.clone();
+        for (id, blob) in records {
+            if updated.contains_key(&id) {
+                outcome.kept.push(id);
+            } else if self.links.contains_key(&id) {
+                outcome.superseded.push(id);
+            } else {
+                updated.insert(id.clone(), blob);
+                outcome.restored.push(id);
+            }
+        }
+
+        let mut merged_links = self.links.clone();
+        for (old_id, new_id) in links {
+            merged_links.entry(old_id).or_insert(new_id);
+        }
+
+        let mut merged_receipts = self.receipts.clone();
+        for receipt in receipts {
+            if !merged_receipts.contains(&receipt) {
+                merged_receipts.push(receipt);
+                outcome.receipts_added += 1;
+            }
+        }
+
+        let previous = (
+            std::mem::replace(&mut self.records, updated),
+            std::mem::replace(&mut self.links, merged_links),
+            std::mem::replace(&mut self.receipts, merged_receipts),
+        );
+        if let Err(e) = self.save() {
+            (self.records, self.links, self.receipts) = previous;
+            return Err(e);
+        }
+        Ok(outcome)
+    }
+
+    /// Drop repeated consent receipts, keeping the first of each, release
+    /// the spare room and persist; returns the receipts dropped
+    pub fn compact_receipts(&mut self) -> Result<usize, StoreError> {
+        let mut compacted: Vec<Vec<u8>> = Vec::with_capacity(self.receipts.len());
+        for receipt in &self.receipts {
+            if !compacted.contains(receipt) {
+                compacted.push(receipt.clone());
+            }
+        }
+        let dropped = self.receipts.len() - compacted.len();
+        if dropped == 0 {
+            self.receipts.shrink_to_fit();
+            return Ok(0);
+        }
+
+        let previous = std::mem::replace(&mut self.receipts, compacted);
+        if let Err(e) = self.save() {
+            self.receipts = previous;
+            return Err(e);
+        }
+        Ok(dropped)
+    }
+
+    /// Approximate bytes held in memory: credentials, links and receipts
+    pub fn held_bytes(&self) -> (usize, usize, usize) {
+        let records = self.records.iter().map(|(id, blob)| id.len() + blob.len()).sum();
+        let links = self.links.iter().map(|(old, new)| old.len() + new.len()).sum();
+        let receipts = self.receipts.iter().map(Vec::len).sum();
+        (records, links, receipts)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.records.keys().map(|id| id.as_str())
+    }
+
+    /// Stored credential_ids with their blobs, without following links
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.records.iter().map(|(id, blob)| (id.as_str(), blob.as_slice()))
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+fn c_str<'a>(ptr: *const c_char) -> &'a str {
+    unsafe { CStr::from_ptr(ptr).to_str().unwrap_or("") }
+}
+
+fn store_mut<'a>(handle: *mut CredentialStore) -> Option<&'a mut CredentialStore> {
+    unsafe { handle.as_mut() }
+}
+
+crate::unwind::entry_point! {
+/// Open (or create) an encrypted credential store
+///
+/// `key_kind` is ZK_STORE_KEY_PASSPHRASE (`key` is the passphrase bytes) or
+/// ZK_STORE_KEY_RAW (`key` is 32 raw bytes). On success the handle is
+/// written to `store_out`. Returns 0 on success, ZK_ERR_AUTH for a wrong key
+/// or tampered file, ZK_ERR_CORRUPT for a damaged file, -1 otherwise.
+#[no_mangle]
+pub extern "C" fn ZK_StoreOpen(
+    path: *const c_char,
+    key: *const c_char,
+    key_len: usize,
+    key_kind: c_int,
+    store_out: *mut *mut CredentialStore,
+) -> c_int {
+    if path.is_null() || key.is_null() || store_out.is_null() {
+        return -1;
+    }
+
+    let key_bytes = match read_bytes(key, key_len, MAX_FIELD_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let store_key = match key_kind {
+        ZK_STORE_KEY_PASSPHRASE => StoreKey::Passphrase(key_bytes),
+        ZK_STORE_KEY_RAW => match key_bytes.try_into() {
+            Ok(raw) => StoreKey::Raw(raw),
+            Err(_) => return -1,
+        },
+        _ => return -1,
+    };
+
+    match CredentialStore::open(Path::new(c_str(path)), store_key) {
+        Ok(store) => {
+            unsafe {
+                *store_out = Box::into_raw(Box::new(store));
+            }
+            0
+        }
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Add a hex-encoded VC blob; its credential_id is written to `id_out`
+///
+/// Returns 0 on success, negative on failure.
+#[no_mangle]
+pub extern "C" fn ZK_StoreAddVC(
+    store: *mut CredentialStore,
+    vc_blob: *const c_char,
+    id_out: *mut c_char,
+    id_out_size: usize,
+) -> c_int {
+    let store = match store_mut(store) {
+        Some(store) if !vc_blob.is_null() => store,
+        _ => return -1,
+    };
+
+    let blob = match hex_to_bytes(c_str(vc_blob)) {
+        Ok(bytes) => Zeroizing::new(bytes),
+        Err(_) => return -1,
+    };
+
+    match store.add(&blob) {
+        Ok(_) if id_out.is_null() => 0,
+        Ok(id) => match write_cstr(id_out, id_out_size, &id) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Copy the hex VC blob stored under `credential_id` into `vc_blob_out`
+///
+/// Returns 0 on success, 1 if no such credential, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_StoreGetVC(
+    store: *mut CredentialStore,
+    credential_id: *const c_char,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    let store = match store_mut(store) {
+        Some(store) if !credential_id.is_null() => store,
+        _ => return -1,
+    };
+
+    match store.get(c_str(credential_id)) {
+        Some(blob) => {
+            let blob_hex = Zeroizing::new(hex::encode(blob));
+            match write_cstr(vc_blob_out, vc_blob_out_size, &blob_hex) {
+                Ok(_) => 0,
+                Err(e) => e.code(),
+            }
+        }
+        None => 1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Swap in a re-issued hex VC blob for the credential it supersedes
+///
+/// The new credential_id is written to `id_out` (may be NULL). Old ids keep
+/// resolving to the newest credential in ZK_StoreGetVC. Returns 0 on
+/// success, negative on failure; on failure the store is unchanged.
+#[no_mangle]
+pub extern "C" fn ZK_StoreApplySupersession(
+    store: *mut CredentialStore,
+    new_vc_blob: *const c_char,
+    id_out: *mut c_char,
+    id_out_size: usize,
+) -> c_int {
+    let store = match store_mut(store) {
+        Some(store) if !new_vc_blob.is_null() => store,
+        _ => return -1,
+    };
+
+    let blob = match hex_to_bytes(c_str(new_vc_blob)) {
+        Ok(bytes) => Zeroizing::new(bytes),
+        Err(_) => return -1,
+    };
+
+    match store.apply_supersession(&blob) {
+        Ok(_) if id_out.is_null() => 0,
+        Ok(id) => match write_cstr(id_out, id_out_size, &id) {
+            Ok(_) => 0,
+            Err(e) => e.code(),
+        },
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Remove a credential; returns 0 if removed, 1 if absent, negative on failure
+#[no_mangle]
+pub extern "C" fn ZK_StoreRemoveVC(store: *mut CredentialStore, credential_id: *const c_char) -> c_int {
+    let store = match store_mut(store) {
+        Some(store) if !credential_id.is_null() => store,
+        _ => return -1,
+    };
+
+    match store.remove(c_str(credential_id)) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the stored credential_ids as a JSON array of strings
+///
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_StoreListVCs(
+    store: *mut CredentialStore,
+    ids_json_out: *mut c_char,
+    ids_json_out_size: usize,
+) -> c_int {
+    let store = match store_mut(store) {
+        Some(store) => store,
+        None => return -1,
+    };
+
+    let ids: Vec<String> = store.ids().map(|id| format!("\"{}\"", id)).collect();
+    let json = format!("[{}]", ids.join(","));
+
+    match write_cstr(ids_json_out, ids_json_out_size, &json) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Close a store handle, wiping the key and decrypted credentials
+#[no_mangle]
+pub extern "C" fn ZK_StoreClose(store: *mut CredentialStore) {
+    if !store.is_null() {
+        unsafe {
+            drop(Box::from_raw(store));
+        }
+    }
+}
+}
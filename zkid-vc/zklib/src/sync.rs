@@ -0,0 +1,42 @@
+// ============================================================================
+// Poison-Tolerant Mutex
+// ============================================================================
+//
+// A std Mutex is poisoned when a thread panics while holding it, and every
+// later lock() returns Err. Entry points answered that with -1 (or "no
+// keys") for the rest of the process: one panic in a build that unwinds
+// (release builds abort) disabled proving and verifying until restart,
+// and ZK_Init could not repair it because it locks the same slots.
+//
+// The state behind the library's locks is replaced whole or updated in
+// single steps (key slots, registries, counters), so a panic leaves at
+// worst a stale value, never a torn one. This Mutex therefore takes over a
+// poisoned lock instead of failing. lock() keeps std's signature so call
+// sites are unchanged; it never returns Err.
+
+use std::sync::{LockResult, PoisonError};
+
+pub(crate) use std::sync::MutexGuard;
+
+/// std::sync::Mutex whose lock() recovers from poisoning
+#[derive(Debug, Default)]
+pub(crate) struct Mutex<T: ?Sized>(std::sync::Mutex<T>);
+
+impl<T> Mutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(std::sync::Mutex::new(value))
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock, taking over the data of a poisoned lock; always Ok
+    pub(crate) fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        Ok(self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Whether a thread panicked while holding the lock
+    #[cfg(feature = "fault-injection")]
+    pub(crate) fn is_poisoned(&self) -> bool {
+        self.0.is_poisoned()
+    }
+}
@@ -0,0 +1,1184 @@
+// ============================================================================
+// Threshold Issuance (t-of-n FROST signing, feature "threshold")
+// ============================================================================
+//
+// For high-assurance credentials no single operator holds the issuer key.
+// n operators run a distributed key generation once, after which any t of
+// them sign a credential together with FROST(Ed25519, SHA-512) (RFC 9591).
+// The result is one ordinary 64-byte Ed25519 signature under the group
+// public key, so ZK_VerifyVCSignature, ZK_VerifyVCBlob and proving treat the
+// group key as any other issuer key.
+//
+// Key generation (Pedersen DKG with proofs of knowledge), identifiers 1..=n:
+//
+//   round 1   ZK_ThresholdKeygenRound1: each operator draws a degree t-1
+//             polynomial and broadcasts its coefficient commitments with a
+//             Schnorr proof of knowledge of the constant term
+//   round 2   ZK_ThresholdKeygenRound2: each operator checks the others'
+//             proofs and sends every other operator its polynomial's value
+//             at the recipient's identifier, over a confidential channel
+//   finalize  ZK_ThresholdKeygenFinalize: each operator checks the values
+//             it received against the senders' commitments and derives its
+//             signing share, the group key and everyone's verifying share
+//
+// Signing a VC blob (the message is the one ZK_SignVCBlob signs: the
+// credential context, the anchor tag and the blob's anchor, i.e. MiMC of
+// its message hash, dates, holder key, `zkid:anchor-blinding` claim and
+// claims root; see validity.rs). No blinding is drawn here, since every
+// signer must derive the same anchor, so the blob must carry its final
+// blinding claim, or none for an unblinded anchor:
+//
+//   round 1   ZK_ThresholdSignRound1: each signer commits to a fresh pair of
+//             nonces; the nonces stay inside this library
+//   round 2   ZK_ThresholdSignRound2: given everyone's commitments, each
+//             signer produces its signature share
+//   aggregate ZK_ThresholdAggregate: a coordinator checks every share
+//             against the signer's verifying share and combines them
+//
+// A participant whose proof of knowledge, secret share or signature share
+// does not verify is named: the call returns ZK_ERR_MISBEHAVING and writes
+// its identifier. Signing nonces are used at most once. Round 2 removes them
+// from the library before computing the share, so a repeated or replayed
+// round 2 with the same commitment returns ZK_ERR_NONCE_REUSED instead of
+// producing a second share that would leak the signing share. Nonces do not
+// survive the process; a signer that restarts between rounds commits again.
+//
+// Secret packages (round 1 and 2 state, key packages, round 2 shares in
+// transit) are key material and must be protected as an issuer private key.
+// Packages are hex; each starts with a 4-byte magic and a version byte.
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::traits::{Identity, IsIdentity};
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use ed25519_dalek::VerifyingKey;
+use rand_core::{OsRng, RngCore};
+use serde_json::json;
+use sha2::{Digest, Sha512};
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::admission::text_arg;
+use crate::credential::{parse_vc_blob, write_vc_blob};
+use crate::error::{ZK_ERR_CAPACITY, ZK_ERR_MISBEHAVING, ZK_ERR_NONCE_REUSED, ZK_ERR_SEQUENCE_UNSUPPORTED};
+use crate::ffi::{read_slice, write_cstr};
+use crate::sequence::sequencing_enabled;
+use crate::wire::Reader;
+use crate::{bytes_to_hex, domain, hex_to_bytes, stats, VerifiableCredential};
+
+/// Ciphersuite context string of FROST(Ed25519, SHA-512)
+const CONTEXT: &[u8] = b"FROST-ED25519-SHA512-v1";
+
+const ROUND1_SECRET_MAGIC: &[u8; 4] = b"ZKK1";
+const ROUND1_PACKAGE_MAGIC: &[u8; 4] = b"ZKD1";
+const ROUND2_SECRET_MAGIC: &[u8; 4] = b"ZKK2";
+const ROUND2_PACKAGE_MAGIC: &[u8; 4] = b"ZKD2";
+const KEY_PACKAGE_MAGIC: &[u8; 4] = b"ZKKP";
+const PUBLIC_KEY_PACKAGE_MAGIC: &[u8; 4] = b"ZKPK";
+const COMMITMENT_MAGIC: &[u8; 4] = b"ZKC1";
+const SHARE_MAGIC: &[u8; 4] = b"ZKZ1";
+const PACKAGE_VERSION: u8 = 1;
+
+/// Most participants in one group
+pub const MAX_PARTICIPANTS: u16 = 32;
+
+/// Signing nonces held at once, across all key packages
+pub const MAX_PENDING_NONCES: usize = 64;
+
+/// Longest package accepted, binary; its hex text is twice as long
+const MAX_PACKAGE_LEN: usize = 64 * 1024;
+
+/// Why a threshold operation failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Fault {
+    /// A C API error code
+    Code(c_int),
+    /// The participant with this identifier sent an invalid package or share
+    Participant(u16),
+}
+
+impl From<c_int> for Fault {
+    fn from(code: c_int) -> Self {
+        Fault::Code(code)
+    }
+}
+
+// ============================================================================
+// Scalars, Points and Hashes
+// ============================================================================
+
+fn random_scalar() -> Scalar {
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    OsRng.fill_bytes(bytes.as_mut());
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn identifier_scalar(identifier: u16) -> Scalar {
+    Scalar::from(u64::from(identifier))
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// H1, H3 and the DKG challenge: SHA-512 over the context, a label and the
+/// input, reduced
+fn labeled_scalar(label: &[u8], parts: &[&[u8]]) -> Scalar {
+    hash_to_scalar(&[&[CONTEXT, label][..], parts].concat())
+}
+
+/// H4 and H5: SHA-512 over the context, a label and the input
+fn labeled_digest(label: &[u8], input: &[u8]) -> [u8; 64] {
+    Sha512::new().chain_update(CONTEXT).chain_update(label).chain_update(input).finalize().into()
+}
+
+/// RFC 9591 nonce_generate: fresh randomness mixed with the signing share
+fn nonce(secret: &Scalar) -> Scalar {
+    let mut random = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(random.as_mut());
+    nonce_from(&random, secret)
+}
+
+/// nonce_generate with the randomness given, as the RFC's vectors fix it
+fn nonce_from(random: &[u8; 32], secret: &Scalar) -> Scalar {
+    labeled_scalar(b"nonce", &[random, secret.as_bytes()])
+}
+
+fn read_u16(r: &mut Reader) -> Option<u16> {
+    Some(u16::from_le_bytes(r.take(2)?.try_into().ok()?))
+}
+
+fn read_scalar(r: &mut Reader) -> Option<Scalar> {
+    Option::from(Scalar::from_canonical_bytes(r.take(32)?.try_into().ok()?))
+}
+
+/// A point of the prime-order subgroup other than the identity
+fn read_point(r: &mut Reader) -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY(r.take(32)?.try_into().ok()?).decompress()?;
+    (point.is_torsion_free() && !point.is_identity()).then_some(point)
+}
+
+fn header(magic: &[u8; 4]) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.push(PACKAGE_VERSION);
+    out
+}
+
+fn read_header(r: &mut Reader, magic: &[u8; 4]) -> Option<()> {
+    (r.take(4)? == magic && r.u8()? == PACKAGE_VERSION).then_some(())
+}
+
+/// Evaluate the polynomial with these coefficients at `x`
+fn evaluate(coefficients: &[Scalar], x: Scalar) -> Scalar {
+    coefficients.iter().rev().fold(Scalar::ZERO, |acc, coefficient| acc * x + coefficient)
+}
+
+/// Evaluate the committed polynomial at `x`, in the exponent
+fn evaluate_commitment(commitments: &[EdwardsPoint], x: Scalar) -> EdwardsPoint {
+    commitments.iter().rev().fold(EdwardsPoint::identity(), |acc, commitment| acc * x + commitment)
+}
+
+/// Lagrange coefficient of `identifier` for interpolating at 0 over
+/// `identifiers`
+fn lagrange(identifier: u16, identifiers: &[u16]) -> Scalar {
+    let x = identifier_scalar(identifier);
+    let (numerator, denominator) = identifiers
+        .iter()
+        .filter(|&&other| other != identifier)
+        .map(|&other| identifier_scalar(other))
+        .fold((Scalar::ONE, Scalar::ONE), |(num, den), other| (num * other, den * (other - x)));
+    numerator * denominator.invert()
+}
+
+fn valid_group(max_signers: u16, min_signers: u16) -> bool {
+    (2..=MAX_PARTICIPANTS).contains(&max_signers) && (2..=max_signers).contains(&min_signers)
+}
+
+// ============================================================================
+// Key Generation Packages
+// ============================================================================
+
+/// Round 1 state kept by its participant: the polynomial
+struct Round1Secret {
+    identifier: u16,
+    max_signers: u16,
+    coefficients: Vec<Scalar>,
+}
+
+impl Drop for Round1Secret {
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl Round1Secret {
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(header(ROUND1_SECRET_MAGIC));
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(&self.max_signers.to_le_bytes());
+        out.extend_from_slice(&(self.coefficients.len() as u16).to_le_bytes());
+        for coefficient in &self.coefficients {
+            out.extend_from_slice(coefficient.as_bytes());
+        }
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, ROUND1_SECRET_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let max_signers = read_u16(&mut r)?;
+        let min_signers = read_u16(&mut r)?;
+        if !valid_group(max_signers, min_signers) || !(1..=max_signers).contains(&identifier) {
+            return None;
+        }
+        let coefficients = (0..min_signers).map(|_| read_scalar(&mut r)).collect::<Option<Vec<_>>>()?;
+        r.is_empty().then_some(Self { identifier, max_signers, coefficients })
+    }
+
+    fn commitments(&self) -> Vec<EdwardsPoint> {
+        self.coefficients.iter().map(EdwardsPoint::mul_base).collect()
+    }
+}
+
+/// Round 1 broadcast: coefficient commitments and a proof of knowledge of
+/// the constant term
+struct Round1Package {
+    identifier: u16,
+    commitments: Vec<EdwardsPoint>,
+    proof_r: EdwardsPoint,
+    proof_mu: Scalar,
+}
+
+impl Round1Package {
+    fn challenge(identifier: u16, constant: &EdwardsPoint, r: &EdwardsPoint) -> Scalar {
+        labeled_scalar(
+            b"dkg",
+            &[identifier_scalar(identifier).as_bytes(), constant.compress().as_bytes(), r.compress().as_bytes()],
+        )
+    }
+
+    fn new(secret: &Round1Secret) -> Self {
+        let commitments = secret.commitments();
+        let k = random_scalar();
+        let proof_r = EdwardsPoint::mul_base(&k);
+        let c = Self::challenge(secret.identifier, &commitments[0], &proof_r);
+        Self {
+            identifier: secret.identifier,
+            commitments,
+            proof_r,
+            proof_mu: k + secret.coefficients[0] * c,
+        }
+    }
+
+    /// Whether the proof of knowledge holds
+    fn verify(&self) -> bool {
+        let c = Self::challenge(self.identifier, &self.commitments[0], &self.proof_r);
+        self.proof_r == EdwardsPoint::mul_base(&self.proof_mu) - self.commitments[0] * c
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = header(ROUND1_PACKAGE_MAGIC);
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(&(self.commitments.len() as u16).to_le_bytes());
+        for commitment in &self.commitments {
+            out.extend_from_slice(commitment.compress().as_bytes());
+        }
+        out.extend_from_slice(self.proof_r.compress().as_bytes());
+        out.extend_from_slice(self.proof_mu.as_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, ROUND1_PACKAGE_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let count = read_u16(&mut r)?;
+        if count == 0 || count > MAX_PARTICIPANTS {
+            return None;
+        }
+        let commitments = (0..count).map(|_| read_point(&mut r)).collect::<Option<Vec<_>>>()?;
+        let proof_r = read_point(&mut r)?;
+        let proof_mu = read_scalar(&mut r)?;
+        r.is_empty().then_some(Self { identifier, commitments, proof_r, proof_mu })
+    }
+}
+
+/// Round 2 state kept by its participant: its own polynomial value and
+/// every participant's commitments, its own included
+struct Round2Secret {
+    identifier: u16,
+    own_share: Scalar,
+    commitments: Vec<(u16, Vec<EdwardsPoint>)>,
+}
+
+impl Drop for Round2Secret {
+    fn drop(&mut self) {
+        self.own_share.zeroize();
+    }
+}
+
+impl Round2Secret {
+    fn min_signers(&self) -> usize {
+        self.commitments[0].1.len()
+    }
+
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(header(ROUND2_SECRET_MAGIC));
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(&(self.commitments.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(self.min_signers() as u16).to_le_bytes());
+        out.extend_from_slice(self.own_share.as_bytes());
+        for (identifier, commitments) in &self.commitments {
+            out.extend_from_slice(&identifier.to_le_bytes());
+            for commitment in commitments {
+                out.extend_from_slice(commitment.compress().as_bytes());
+            }
+        }
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, ROUND2_SECRET_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let max_signers = read_u16(&mut r)?;
+        let min_signers = read_u16(&mut r)?;
+        if !valid_group(max_signers, min_signers) || !(1..=max_signers).contains(&identifier) {
+            return None;
+        }
+        let own_share = read_scalar(&mut r)?;
+        let mut commitments = Vec::with_capacity(max_signers.into());
+        for expected in 1..=max_signers {
+            if read_u16(&mut r)? != expected {
+                return None;
+            }
+            let points = (0..min_signers).map(|_| read_point(&mut r)).collect::<Option<Vec<_>>>()?;
+            commitments.push((expected, points));
+        }
+        r.is_empty().then_some(Self { identifier, own_share, commitments })
+    }
+}
+
+/// Round 2 message from one participant to another: the sender's
+/// polynomial at the recipient's identifier (secret)
+struct Round2Package {
+    sender: u16,
+    recipient: u16,
+    share: Scalar,
+}
+
+impl Drop for Round2Package {
+    fn drop(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+impl Round2Package {
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(header(ROUND2_PACKAGE_MAGIC));
+        out.extend_from_slice(&self.sender.to_le_bytes());
+        out.extend_from_slice(&self.recipient.to_le_bytes());
+        out.extend_from_slice(self.share.as_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, ROUND2_PACKAGE_MAGIC)?;
+        let sender = read_u16(&mut r)?;
+        let recipient = read_u16(&mut r)?;
+        let share = read_scalar(&mut r)?;
+        r.is_empty().then_some(Self { sender, recipient, share })
+    }
+}
+
+/// A participant's long-term signing state
+struct KeyPackage {
+    identifier: u16,
+    min_signers: u16,
+    signing_share: Scalar,
+    group_key: EdwardsPoint,
+}
+
+impl Drop for KeyPackage {
+    fn drop(&mut self) {
+        self.signing_share.zeroize();
+    }
+}
+
+impl KeyPackage {
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(header(KEY_PACKAGE_MAGIC));
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(&self.min_signers.to_le_bytes());
+        out.extend_from_slice(self.signing_share.as_bytes());
+        out.extend_from_slice(self.group_key.compress().as_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, KEY_PACKAGE_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let min_signers = read_u16(&mut r)?;
+        let signing_share = read_scalar(&mut r)?;
+        let group_key = read_point(&mut r)?;
+        let valid = identifier != 0 && (2..=MAX_PARTICIPANTS).contains(&min_signers);
+        (valid && r.is_empty()).then_some(Self { identifier, min_signers, signing_share, group_key })
+    }
+}
+
+/// The group key and every participant's verifying share, for aggregation
+struct PublicKeyPackage {
+    min_signers: u16,
+    group_key: EdwardsPoint,
+    verifying_shares: Vec<EdwardsPoint>,
+}
+
+impl PublicKeyPackage {
+    fn verifying_share(&self, identifier: u16) -> Option<&EdwardsPoint> {
+        self.verifying_shares.get(usize::from(identifier).checked_sub(1)?)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = header(PUBLIC_KEY_PACKAGE_MAGIC);
+        out.extend_from_slice(&(self.verifying_shares.len() as u16).to_le_bytes());
+        out.extend_from_slice(&self.min_signers.to_le_bytes());
+        out.extend_from_slice(self.group_key.compress().as_bytes());
+        for share in &self.verifying_shares {
+            out.extend_from_slice(share.compress().as_bytes());
+        }
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, PUBLIC_KEY_PACKAGE_MAGIC)?;
+        let max_signers = read_u16(&mut r)?;
+        let min_signers = read_u16(&mut r)?;
+        if !valid_group(max_signers, min_signers) {
+            return None;
+        }
+        let group_key = read_point(&mut r)?;
+        let verifying_shares = (0..max_signers).map(|_| read_point(&mut r)).collect::<Option<Vec<_>>>()?;
+        r.is_empty().then_some(Self { min_signers, group_key, verifying_shares })
+    }
+}
+
+fn keygen_round1(identifier: u16, max_signers: u16, min_signers: u16) -> Option<(Round1Secret, Round1Package)> {
+    if !valid_group(max_signers, min_signers) || !(1..=max_signers).contains(&identifier) {
+        return None;
+    }
+    let secret = Round1Secret {
+        identifier,
+        max_signers,
+        coefficients: (0..min_signers).map(|_| random_scalar()).collect(),
+    };
+    let package = Round1Package::new(&secret);
+    Some((secret, package))
+}
+
+fn keygen_round2(
+    secret: &Round1Secret,
+    packages: &[Round1Package],
+) -> Result<(Round2Secret, Vec<Round2Package>), Fault> {
+    let me = secret.identifier;
+    if packages.len() + 1 != usize::from(secret.max_signers) {
+        return Err(Fault::Code(-1));
+    }
+
+    let mut commitments: Vec<(u16, Vec<EdwardsPoint>)> = vec![(me, secret.commitments())];
+    for package in packages {
+        let known = package.identifier == me || commitments.iter().any(|(id, _)| *id == package.identifier);
+        if !(1..=secret.max_signers).contains(&package.identifier) || known {
+            return Err(Fault::Code(-1));
+        }
+        if package.commitments.len() != secret.coefficients.len() || !package.verify() {
+            return Err(Fault::Participant(package.identifier));
+        }
+        commitments.push((package.identifier, package.commitments.clone()));
+    }
+    commitments.sort_by_key(|(id, _)| *id);
+
+    let outgoing = (1..=secret.max_signers)
+        .filter(|&recipient| recipient != me)
+        .map(|recipient| Round2Package {
+            sender: me,
+            recipient,
+            share: evaluate(&secret.coefficients, identifier_scalar(recipient)),
+        })
+        .collect();
+    let state = Round2Secret {
+        identifier: me,
+        own_share: evaluate(&secret.coefficients, identifier_scalar(me)),
+        commitments,
+    };
+    Ok((state, outgoing))
+}
+
+fn keygen_finalize(secret: &Round2Secret, packages: &[Round2Package]) -> Result<(KeyPackage, PublicKeyPackage), Fault> {
+    let me = secret.identifier;
+    let x = identifier_scalar(me);
+    if packages.len() + 1 != secret.commitments.len() {
+        return Err(Fault::Code(-1));
+    }
+
+    let mut signing_share = secret.own_share;
+    let mut seen = vec![me];
+    for package in packages {
+        let sender = package.sender;
+        if package.recipient != me || seen.contains(&sender) {
+            return Err(Fault::Code(-1));
+        }
+        let commitments = match secret.commitments.iter().find(|(id, _)| *id == sender) {
+            Some((_, commitments)) => commitments,
+            None => return Err(Fault::Code(-1)),
+        };
+        if EdwardsPoint::mul_base(&package.share) != evaluate_commitment(commitments, x) {
+            signing_share.zeroize();
+            return Err(Fault::Participant(sender));
+        }
+        signing_share += package.share;
+        seen.push(sender);
+    }
+
+    let group_key = secret.commitments.iter().map(|(_, commitments)| commitments[0]).sum();
+    let verifying_shares = secret
+        .commitments
+        .iter()
+        .map(|(id, _)| {
+            let x = identifier_scalar(*id);
+            secret.commitments.iter().map(|(_, commitments)| evaluate_commitment(commitments, x)).sum()
+        })
+        .collect();
+    let min_signers = secret.min_signers() as u16;
+    Ok((
+        KeyPackage { identifier: me, min_signers, signing_share, group_key },
+        PublicKeyPackage { min_signers, group_key, verifying_shares },
+    ))
+}
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// A signer's round 1 commitment to its hiding and binding nonces
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Commitment {
+    identifier: u16,
+    hiding: EdwardsPoint,
+    binding: EdwardsPoint,
+}
+
+impl Commitment {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut out = header(COMMITMENT_MAGIC);
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(self.hiding.compress().as_bytes());
+        out.extend_from_slice(self.binding.compress().as_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, COMMITMENT_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let hiding = read_point(&mut r)?;
+        let binding = read_point(&mut r)?;
+        (identifier != 0 && r.is_empty()).then_some(Self { identifier, hiding, binding })
+    }
+}
+
+/// A signer's round 2 output
+struct SignatureShare {
+    identifier: u16,
+    share: Scalar,
+}
+
+impl SignatureShare {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = header(SHARE_MAGIC);
+        out.extend_from_slice(&self.identifier.to_le_bytes());
+        out.extend_from_slice(self.share.as_bytes());
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+        read_header(&mut r, SHARE_MAGIC)?;
+        let identifier = read_u16(&mut r)?;
+        let share = read_scalar(&mut r)?;
+        r.is_empty().then_some(Self { identifier, share })
+    }
+}
+
+/// Nonces awaiting round 2, found by the group key and commitment
+struct PendingNonces {
+    group_key: [u8; 32],
+    commitment: Commitment,
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+impl Drop for PendingNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+static PENDING_NONCES: Mutex<Vec<PendingNonces>> = Mutex::new(Vec::new());
+
+/// The session's commitments sorted by identifier, if they are distinct,
+/// at least `min_signers` and include `signer`
+fn signing_set(mut commitments: Vec<Commitment>, min_signers: u16, signer: Option<u16>) -> Option<Vec<Commitment>> {
+    commitments.sort_by_key(|commitment| commitment.identifier);
+    let distinct = commitments.windows(2).all(|pair| pair[0].identifier != pair[1].identifier);
+    let enough = (usize::from(min_signers)..=usize::from(MAX_PARTICIPANTS)).contains(&commitments.len());
+    let included = signer.is_none_or(|signer| commitments.iter().any(|commitment| commitment.identifier == signer));
+    (distinct && enough && included).then_some(commitments)
+}
+
+/// The session values every signer and the aggregator derive alike
+struct Session {
+    binding_factors: Vec<Scalar>,
+    group_commitment: EdwardsPoint,
+    challenge: Scalar,
+    identifiers: Vec<u16>,
+}
+
+impl Session {
+    fn new(group_key: &EdwardsPoint, commitments: &[Commitment], message: &[u8]) -> Self {
+        let group_key_bytes = group_key.compress().to_bytes();
+
+        let mut encoded = Vec::with_capacity(commitments.len() * 96);
+        for commitment in commitments {
+            encoded.extend_from_slice(identifier_scalar(commitment.identifier).as_bytes());
+            encoded.extend_from_slice(commitment.hiding.compress().as_bytes());
+            encoded.extend_from_slice(commitment.binding.compress().as_bytes());
+        }
+        let prefix = [
+            group_key_bytes.as_slice(),
+            &labeled_digest(b"msg", message),
+            &labeled_digest(b"com", &encoded),
+        ]
+        .concat();
+
+        let binding_factors: Vec<Scalar> = commitments
+            .iter()
+            .map(|commitment| labeled_scalar(b"rho", &[&prefix, identifier_scalar(commitment.identifier).as_bytes()]))
+            .collect();
+        let group_commitment = commitments
+            .iter()
+            .zip(&binding_factors)
+            .map(|(commitment, rho)| commitment.hiding + commitment.binding * rho)
+            .sum::<EdwardsPoint>();
+        // Plain Ed25519 challenge, so the result verifies as an Ed25519 signature
+        let challenge = hash_to_scalar(&[group_commitment.compress().as_bytes(), &group_key_bytes, message]);
+
+        Self {
+            binding_factors,
+            group_commitment,
+            challenge,
+            identifiers: commitments.iter().map(|commitment| commitment.identifier).collect(),
+        }
+    }
+
+    fn position(&self, identifier: u16) -> Option<usize> {
+        self.identifiers.iter().position(|&id| id == identifier)
+    }
+
+    /// A signer's share: hiding + binding * rho + lambda * signing share * c
+    fn share(&self, identifier: u16, signing_share: &Scalar, hiding: &Scalar, binding: &Scalar) -> Option<Scalar> {
+        let rho = self.binding_factors[self.position(identifier)?];
+        let lambda = lagrange(identifier, &self.identifiers);
+        Some(hiding + binding * rho + lambda * signing_share * self.challenge)
+    }
+}
+
+/// The message a credential is signed over, as ZK_SignVCBlob signs it;
+/// None for a malformed holder-binding claim
+fn signing_message(vc: &mut VerifiableCredential) -> Option<Vec<u8>> {
+    vc.legacy_signature = false;
+    vc.claims_root_signed = true;
+    vc.length_prefixed = true;
+    vc.domain_separated = domain::tagged();
+    vc.anchored = true;
+    vc.schedule_anchored = true;
+    vc.signed_message()
+}
+
+fn sign_round1(key: &KeyPackage) -> Result<Commitment, c_int> {
+    let mut pending = PENDING_NONCES.lock().map_err(|_| -1)?;
+    if pending.len() >= MAX_PENDING_NONCES {
+        return Err(ZK_ERR_CAPACITY);
+    }
+    let hiding = nonce(&key.signing_share);
+    let binding = nonce(&key.signing_share);
+    let commitment = Commitment {
+        identifier: key.identifier,
+        hiding: EdwardsPoint::mul_base(&hiding),
+        binding: EdwardsPoint::mul_base(&binding),
+    };
+    pending.push(PendingNonces { group_key: key.group_key.compress().to_bytes(), commitment, hiding, binding });
+    Ok(commitment)
+}
+
+fn sign_round2(key: &KeyPackage, message: &[u8], commitments: Vec<Commitment>) -> Result<SignatureShare, c_int> {
+    let commitments = signing_set(commitments, key.min_signers, Some(key.identifier)).ok_or(-1)?;
+    let own = commitments.iter().find(|commitment| commitment.identifier == key.identifier).ok_or(-1)?;
+
+    // Taken out before use: these nonces never sign again
+    let nonces = {
+        let mut pending = PENDING_NONCES.lock().map_err(|_| -1)?;
+        let group_key = key.group_key.compress().to_bytes();
+        let index = pending
+            .iter()
+            .position(|nonces| nonces.group_key == group_key && nonces.commitment == *own)
+            .ok_or(ZK_ERR_NONCE_REUSED)?;
+        pending.swap_remove(index)
+    };
+
+    let session = Session::new(&key.group_key, &commitments, message);
+    let share = session.share(key.identifier, &key.signing_share, &nonces.hiding, &nonces.binding).ok_or(-1)?;
+    Ok(SignatureShare { identifier: key.identifier, share })
+}
+
+fn aggregate(
+    public: &PublicKeyPackage,
+    message: &[u8],
+    commitments: Vec<Commitment>,
+    shares: &[SignatureShare],
+) -> Result<[u8; 64], Fault> {
+    let commitments = signing_set(commitments, public.min_signers, None).ok_or(Fault::Code(-1))?;
+    let session = Session::new(&public.group_key, &commitments, message);
+    if shares.len() != commitments.len() {
+        return Err(Fault::Code(-1));
+    }
+
+    let mut z = Scalar::ZERO;
+    let mut seen = Vec::with_capacity(shares.len());
+    for share in shares {
+        let position = match session.position(share.identifier) {
+            Some(position) if !seen.contains(&share.identifier) => position,
+            _ => return Err(Fault::Code(-1)),
+        };
+        let verifying_share = public.verifying_share(share.identifier).ok_or(Fault::Code(-1))?;
+        let commitment = &commitments[position];
+        let lambda = lagrange(share.identifier, &session.identifiers);
+        let expected = commitment.hiding
+            + commitment.binding * session.binding_factors[position]
+            + verifying_share * (session.challenge * lambda);
+        if EdwardsPoint::mul_base(&share.share) != expected {
+            return Err(Fault::Participant(share.identifier));
+        }
+        z += share.share;
+        seen.push(share.identifier);
+    }
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(session.group_commitment.compress().as_bytes());
+    signature[32..].copy_from_slice(z.as_bytes());
+    Ok(signature)
+}
+
+/// Replay an RFC 9591 signing vector (conformance.rs): trusted-dealer
+/// shares of the polynomial with these coefficients (the group secret
+/// first) for `max_signers` participants, each signer's nonces from its
+/// fixed hiding and binding randomness, the signers' shares of `message`
+/// and their aggregate
+///
+/// The values the vector expects, hex; None for malformed input.
+pub(crate) fn replay_vector(
+    coefficients: &[[u8; 32]],
+    max_signers: u16,
+    randomness: &[(u16, [u8; 32], [u8; 32])],
+    message: &[u8],
+) -> Option<serde_json::Value> {
+    let coefficients = coefficients
+        .iter()
+        .map(|bytes| Option::from(Scalar::from_canonical_bytes(*bytes)))
+        .collect::<Option<Vec<Scalar>>>()?;
+    let min_signers = u16::try_from(coefficients.len()).ok()?;
+    if !valid_group(max_signers, min_signers) {
+        return None;
+    }
+    let shares: Vec<Scalar> = (1..=max_signers).map(|id| evaluate(&coefficients, identifier_scalar(id))).collect();
+    let public = PublicKeyPackage {
+        min_signers,
+        group_key: EdwardsPoint::mul_base(&coefficients[0]),
+        verifying_shares: shares.iter().map(EdwardsPoint::mul_base).collect(),
+    };
+
+    let mut nonces = Vec::with_capacity(randomness.len());
+    for (identifier, hiding, binding) in randomness {
+        let share = shares.get(usize::from(*identifier).checked_sub(1)?)?;
+        nonces.push((*identifier, nonce_from(hiding, share), nonce_from(binding, share)));
+    }
+    let commitments: Vec<Commitment> = nonces
+        .iter()
+        .map(|(identifier, hiding, binding)| Commitment {
+            identifier: *identifier,
+            hiding: EdwardsPoint::mul_base(hiding),
+            binding: EdwardsPoint::mul_base(binding),
+        })
+        .collect();
+    let session = Session::new(&public.group_key, &signing_set(commitments.clone(), min_signers, None)?, message);
+
+    let mut signers = Vec::with_capacity(nonces.len());
+    let mut signature_shares = Vec::with_capacity(nonces.len());
+    for ((identifier, hiding, binding), commitment) in nonces.iter().zip(&commitments) {
+        let share = session.share(*identifier, &shares[usize::from(*identifier) - 1], hiding, binding)?;
+        signers.push(json!({
+            "identifier": identifier,
+            "hiding_nonce": bytes_to_hex(hiding.as_bytes()),
+            "binding_nonce": bytes_to_hex(binding.as_bytes()),
+            "hiding_nonce_commitment": bytes_to_hex(commitment.hiding.compress().as_bytes()),
+            "binding_nonce_commitment": bytes_to_hex(commitment.binding.compress().as_bytes()),
+            "binding_factor": bytes_to_hex(session.binding_factors[session.position(*identifier)?].as_bytes()),
+            "sig_share": bytes_to_hex(share.as_bytes()),
+        }));
+        signature_shares.push(SignatureShare { identifier: *identifier, share });
+    }
+    let signature = aggregate(&public, message, commitments, &signature_shares).ok()?;
+
+    let participant_shares: Vec<_> = shares
+        .iter()
+        .zip(1u16..)
+        .map(|(share, identifier)| json!({ "identifier": identifier, "participant_share": bytes_to_hex(share.as_bytes()) }))
+        .collect();
+    Some(json!({
+        "group_public_key": bytes_to_hex(public.group_key.compress().as_bytes()),
+        "participant_shares": participant_shares,
+        "signers": signers,
+        "sig": bytes_to_hex(&signature),
+    }))
+}
+
+/// Drop every pending signing nonce
+pub(crate) fn clear() {
+    if let Ok(mut pending) = PENDING_NONCES.lock() {
+        pending.clear();
+    }
+}
+
+// ============================================================================
+// C Argument Helpers
+// ============================================================================
+
+fn package_arg<T>(ptr: *const c_char, parse: impl Fn(&[u8]) -> Option<T>) -> Option<T> {
+    let bytes = Zeroizing::new(hex_to_bytes(text_arg(ptr, 2 * MAX_PACKAGE_LEN)?).ok()?);
+    parse(&bytes)
+}
+
+fn package_args<T>(ptrs: *const *const c_char, count: usize, parse: impl Fn(&[u8]) -> Option<T>) -> Option<Vec<T>> {
+    read_slice(ptrs, count, MAX_PARTICIPANTS.into())
+        .ok()?
+        .iter()
+        .map(|&ptr| package_arg(ptr, &parse))
+        .collect()
+}
+
+fn write_hex(out: *mut c_char, out_size: usize, bytes: &[u8]) -> Result<(), c_int> {
+    let text = Zeroizing::new(bytes_to_hex(bytes));
+    write_cstr(out, out_size, &text).map(|_| ()).map_err(|e| e.code())
+}
+
+/// Map an outcome to its return code, naming a misbehaving participant
+fn finish(result: Result<(), Fault>, culprit_out: *mut u16) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(Fault::Code(code)) => code,
+        Err(Fault::Participant(identifier)) => {
+            if let Some(out) = unsafe { culprit_out.as_mut() } {
+                *out = identifier;
+            }
+            ZK_ERR_MISBEHAVING
+        }
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Start key generation as participant `identifier` of `max_signers`, any
+/// `min_signers` of whom can sign
+///
+/// Writes the round 1 secret (keep it, protected, for round 2) and the
+/// package to broadcast to every other participant (both hex). Requires
+/// 2 <= min_signers <= max_signers <= MAX_PARTICIPANTS and 1 <= identifier
+/// <= max_signers. Returns 0 on success, ZK_ERR_BUFFER_TOO_SMALL, -1 on
+/// NULL or invalid parameters.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdKeygenRound1(
+    identifier: u16,
+    max_signers: u16,
+    min_signers: u16,
+    secret_out: *mut c_char,
+    secret_out_size: usize,
+    package_out: *mut c_char,
+    package_out_size: usize,
+) -> c_int {
+    if secret_out.is_null() || package_out.is_null() {
+        return -1;
+    }
+    let (secret, package) = match keygen_round1(identifier, max_signers, min_signers) {
+        Some(round1) => round1,
+        None => return -1,
+    };
+    match write_hex(secret_out, secret_out_size, &secret.to_bytes())
+        .and_then(|_| write_hex(package_out, package_out_size, &package.to_bytes()))
+    {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the other participants' round 1 packages and compute the shares
+/// to send them
+///
+/// `packages` are the `count` round 1 packages of all other participants.
+/// Writes the round 2 secret (keep it for ZK_ThresholdKeygenFinalize) and a
+/// JSON array of the secret shares to deliver, e.g.
+/// [{"to":2,"package":"..."},{"to":3,"package":"..."}]; each must reach
+/// only its recipient, over a confidential channel. Returns 0 on success,
+/// ZK_ERR_MISBEHAVING with the sender's identifier in `culprit_out` (may be
+/// NULL) for a package whose proof of knowledge fails or whose degree is
+/// wrong, ZK_ERR_BUFFER_TOO_SMALL, -1 on malformed, missing or repeated
+/// packages.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdKeygenRound2(
+    round1_secret: *const c_char,
+    packages: *const *const c_char,
+    count: usize,
+    secret_out: *mut c_char,
+    secret_out_size: usize,
+    shares_json_out: *mut c_char,
+    shares_json_out_size: usize,
+    culprit_out: *mut u16,
+) -> c_int {
+    if round1_secret.is_null() || secret_out.is_null() || shares_json_out.is_null() {
+        return -1;
+    }
+    let (secret, packages) = match (
+        package_arg(round1_secret, Round1Secret::from_bytes),
+        package_args(packages, count, Round1Package::from_bytes),
+    ) {
+        (Some(secret), Some(packages)) => (secret, packages),
+        _ => return -1,
+    };
+
+    let result = keygen_round2(&secret, &packages).and_then(|(state, outgoing)| {
+        let shares: Vec<_> = outgoing
+            .iter()
+            .map(|package| json!({ "to": package.recipient, "package": bytes_to_hex(&package.to_bytes()) }))
+            .collect();
+        let shares = Zeroizing::new(serde_json::Value::from(shares).to_string());
+        write_hex(secret_out, secret_out_size, &state.to_bytes())?;
+        write_cstr(shares_json_out, shares_json_out_size, &shares).map_err(|e| e.code())?;
+        Ok(())
+    });
+    finish(result, culprit_out)
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the shares received in round 2 and derive this participant's keys
+///
+/// `packages` are the `count` round 2 packages addressed to this
+/// participant, one from every other participant. Writes the key package
+/// (secret, for signing), the public key package (for the aggregator) and
+/// the group public key (hex, 32 bytes), the issuer key verifiers use.
+/// Returns 0 on success, ZK_ERR_MISBEHAVING with the sender's identifier in
+/// `culprit_out` (may be NULL) for a share that does not match the sender's
+/// commitments, ZK_ERR_BUFFER_TOO_SMALL, -1 on malformed, missing,
+/// repeated or misaddressed packages.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdKeygenFinalize(
+    round2_secret: *const c_char,
+    packages: *const *const c_char,
+    count: usize,
+    key_package_out: *mut c_char,
+    key_package_out_size: usize,
+    public_key_package_out: *mut c_char,
+    public_key_package_out_size: usize,
+    group_public_key_out: *mut c_char,
+    group_public_key_out_size: usize,
+    culprit_out: *mut u16,
+) -> c_int {
+    if round2_secret.is_null() || key_package_out.is_null() || public_key_package_out.is_null() || group_public_key_out.is_null() {
+        return -1;
+    }
+    let (secret, packages) = match (
+        package_arg(round2_secret, Round2Secret::from_bytes),
+        package_args(packages, count, Round2Package::from_bytes),
+    ) {
+        (Some(secret), Some(packages)) => (secret, packages),
+        _ => return -1,
+    };
+
+    let result = keygen_finalize(&secret, &packages).and_then(|(key, public)| {
+        write_hex(key_package_out, key_package_out_size, &key.to_bytes())?;
+        write_hex(public_key_package_out, public_key_package_out_size, &public.to_bytes())?;
+        write_hex(group_public_key_out, group_public_key_out_size, public.group_key.compress().as_bytes())?;
+        Ok(())
+    });
+    finish(result, culprit_out)
+}
+}
+
+crate::unwind::entry_point! {
+/// Commit to fresh signing nonces for one signing session
+///
+/// Writes the commitment (hex) to send to the coordinator. The nonces stay
+/// in this library until the matching ZK_ThresholdSignRound2 uses them once.
+/// Returns 0 on success, ZK_ERR_CAPACITY with MAX_PENDING_NONCES sessions
+/// awaiting round 2, ZK_ERR_BUFFER_TOO_SMALL, -1 on a malformed key
+/// package.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdSignRound1(
+    key_package: *const c_char,
+    commitment_out: *mut c_char,
+    commitment_out_size: usize,
+) -> c_int {
+    if key_package.is_null() || commitment_out.is_null() {
+        return -1;
+    }
+    let key = match package_arg(key_package, KeyPackage::from_bytes) {
+        Some(key) => key,
+        None => return -1,
+    };
+    let commitment = match sign_round1(&key) {
+        Ok(commitment) => commitment,
+        Err(code) => return code,
+    };
+    match write_hex(commitment_out, commitment_out_size, &commitment.to_bytes()) {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Produce this signer's signature share over a VC blob
+///
+/// `commitments` are the `count` round 1 commitments of the session's
+/// signers, this signer's included; at least min_signers of them. The blob
+/// is signed as ZK_SignVCBlob signs it, over the anchor of its canonical
+/// message hash (validity.rs), so every claim must be final. The signers
+/// cannot each reserve a sequence number for the same message, so with
+/// issuer sequencing enabled this returns ZK_ERR_SEQUENCE_UNSUPPORTED and
+/// keeps the nonces. Returns 0 on success, ZK_ERR_NONCE_REUSED if this
+/// signer's commitment has no unused nonces here (round 2 already ran for
+/// it, or round 1 ran elsewhere), ZK_ERR_BUFFER_TOO_SMALL, -1 on malformed
+/// input or a commitment list that is too short, repeats a signer or
+/// leaves this one out.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdSignRound2(
+    key_package: *const c_char,
+    vc_blob: *const c_char,
+    commitments: *const *const c_char,
+    count: usize,
+    share_out: *mut c_char,
+    share_out_size: usize,
+) -> c_int {
+    if key_package.is_null() || vc_blob.is_null() || share_out.is_null() {
+        return -1;
+    }
+    if sequencing_enabled() {
+        return ZK_ERR_SEQUENCE_UNSUPPORTED;
+    }
+    let (key, mut vc, commitments) = match (
+        package_arg(key_package, KeyPackage::from_bytes),
+        parse_vc_blob(vc_blob),
+        package_args(commitments, count, Commitment::from_bytes),
+    ) {
+        (Some(key), Some(vc), Some(commitments)) => (key, vc, commitments),
+        _ => return -1,
+    };
+
+    let message = match signing_message(&mut vc) {
+        Some(message) => message,
+        None => return -1,
+    };
+    let share = match sign_round2(&key, &message, commitments) {
+        Ok(share) => share,
+        Err(code) => return code,
+    };
+    match write_hex(share_out, share_out_size, &share.to_bytes()) {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Check the signature shares and write the VC blob signed by the group
+///
+/// `commitments` are the session's round 1 commitments and `shares` one
+/// signature share per committed signer. The output blob carries a single
+/// Ed25519 signature under the group public key; any co-signature is
+/// dropped, as by ZK_SignVCBlob. Returns 0 on success, ZK_ERR_MISBEHAVING
+/// with the signer's identifier in `culprit_out` (may be NULL) for a share
+/// that does not verify, ZK_ERR_SEQUENCE_UNSUPPORTED with issuer
+/// sequencing enabled (see ZK_ThresholdSignRound2),
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on malformed input or shares that do not
+/// match the commitments.
+#[no_mangle]
+pub extern "C" fn ZK_ThresholdAggregate(
+    public_key_package: *const c_char,
+    vc_blob: *const c_char,
+    commitments: *const *const c_char,
+    commitment_count: usize,
+    shares: *const *const c_char,
+    share_count: usize,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+    culprit_out: *mut u16,
+) -> c_int {
+    if public_key_package.is_null() || vc_blob.is_null() || vc_blob_out.is_null() {
+        return -1;
+    }
+    if sequencing_enabled() {
+        return ZK_ERR_SEQUENCE_UNSUPPORTED;
+    }
+    let (public, mut vc, commitments, shares) = match (
+        package_arg(public_key_package, PublicKeyPackage::from_bytes),
+        parse_vc_blob(vc_blob),
+        package_args(commitments, commitment_count, Commitment::from_bytes),
+        package_args(shares, share_count, SignatureShare::from_bytes),
+    ) {
+        (Some(public), Some(vc), Some(commitments), Some(shares)) => (public, vc, commitments, shares),
+        _ => return -1,
+    };
+
+    let message = match signing_message(&mut vc) {
+        Some(message) => message,
+        None => return -1,
+    };
+    let result = aggregate(&public, &message, commitments, &shares).and_then(|signature| {
+        vc.signature = signature.to_vec();
+        vc.co_signature = None;
+        // Shares that each verify always combine into a valid signature
+        let group_key = VerifyingKey::from_bytes(public.group_key.compress().as_bytes()).map_err(|_| -1)?;
+        if !vc.verify_signature(&group_key) {
+            return Err(Fault::Code(-1));
+        }
+        stats::record_issuance(group_key.as_bytes(), vc.holder_id.as_bytes());
+        match write_vc_blob(&vc, vc_blob_out, vc_blob_out_size) {
+            0 => Ok(()),
+            code => Err(Fault::Code(code)),
+        }
+    });
+    finish(result, culprit_out)
+}
+}
@@ -0,0 +1,565 @@
+// ============================================================================
+// Chunked FFI Transfer
+// ============================================================================
+//
+// The enclave transport caps a single ecall buffer at 64 KiB, while an
+// exported proving key or a large presentation is bigger. Payloads move as
+// raw binary in chunks instead:
+//
+//   import:  Begin(kind, total_len) -> Chunk(offset, bytes)* -> End
+//   export:  Begin(kind, 0) -> End -> Read(buf)* until 0 bytes are returned
+//
+// A proving key is decoded as its chunks arrive: each chunk is parsed into
+// curve points straight away and only the element cut off at its end is
+// carried over, so the key is never held encoded and decoded at once. A
+// malformed point aborts the import at the chunk that holds it. Presentation
+// imports accumulate into a buffer that grows as chunks arrive, never past
+// total_len, and are deserialized straight from it (no hex); the buffer is
+// freed as soon as End returns. A declared size reserves nothing, but the
+// sizes declared by open imports together may not exceed MAX_TRANSFER_LEN.
+// Chunks must arrive in order (`offset` equals the bytes received so far)
+// and may not exceed total_len; either violation aborts the transfer.
+// Transfers idle for longer than TRANSFER_TIMEOUT are dropped on the next
+// call into this module.
+//
+// Key transfers need the "prover" feature, presentation imports "verifier";
+// kinds compiled out of the build are rejected by ZK_TransferBegin.
+
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::time::{Duration, Instant};
+#[cfg(feature = "prover")]
+use {
+    crate::error::ZK_ERR_CORRUPT,
+    crate::set_keys,
+    crate::PROVING_KEY,
+    ark_bn254::{Bn254, G1Affine, G2Affine},
+    ark_groth16::{PreparedVerifyingKey, ProvingKey, VerifyingKey},
+    ark_serialize::{CanonicalDeserialize, CanonicalSerialize},
+};
+
+use crate::error::{ZK_ERR_CAPACITY, ZK_ERR_UNKNOWN_HANDLE};
+use crate::ffi::read_bytes;
+use crate::last_error;
+#[cfg(feature = "verifier")]
+use crate::presentation::{self, Presentation};
+
+/// Import a compressed proving key (replaces the keys set by ZK_Init)
+pub const ZK_TRANSFER_IMPORT_KEYS: c_int = 0;
+/// Export the current proving key, compressed
+pub const ZK_TRANSFER_EXPORT_KEYS: c_int = 1;
+/// Import a binary presentation; End verifies it
+pub const ZK_TRANSFER_IMPORT_PRESENTATION: c_int = 2;
+
+/// Largest chunk accepted or produced per call
+pub const MAX_CHUNK_LEN: usize = 64 * 1024;
+
+/// Idle time after which an unfinished transfer is freed
+pub const TRANSFER_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Upper bound on a declared import size, and on the sizes declared by all
+// open imports together
+const MAX_TRANSFER_LEN: usize = 256 * 1024 * 1024;
+
+enum State {
+    Receiving { kind: c_int, total_len: usize, buffer: Vec<u8> },
+    #[cfg(feature = "prover")]
+    ReceivingKeys { total_len: usize, received: usize, decoder: KeyDecoder },
+    #[cfg(feature = "prover")]
+    Pending,
+    #[cfg(feature = "prover")]
+    Sending { buffer: Vec<u8>, position: usize },
+}
+
+struct Transfer {
+    state: State,
+    last_activity: Instant,
+}
+
+struct Transfers {
+    next_handle: u64,
+    active: HashMap<u64, Transfer>,
+}
+
+static TRANSFERS: Mutex<Option<Transfers>> = Mutex::new(None);
+
+/// Run `f` on the transfer table after dropping timed-out transfers
+fn with_transfers<R>(f: impl FnOnce(&mut Transfers) -> R) -> R {
+    let mut guard = TRANSFERS.lock().unwrap_or_else(|e| e.into_inner());
+    let transfers = guard.get_or_insert_with(|| Transfers {
+        next_handle: 1,
+        active: HashMap::new(),
+    });
+    transfers
+        .active
+        .retain(|_, t| t.last_activity.elapsed() < TRANSFER_TIMEOUT);
+    f(transfers)
+}
+
+impl State {
+    /// Payload size an import declared; 0 for exports
+    fn declared(&self) -> usize {
+        match self {
+            State::Receiving { total_len, .. } => *total_len,
+            #[cfg(feature = "prover")]
+            State::ReceivingKeys { total_len, .. } => *total_len,
+            #[cfg(feature = "prover")]
+            _ => 0,
+        }
+    }
+}
+
+impl Transfer {
+    #[cfg(feature = "prover")]
+    fn bytes(&self) -> usize {
+        match &self.state {
+            State::Receiving { buffer, .. } | State::Sending { buffer, .. } => buffer.capacity(),
+            State::ReceivingKeys { decoder, .. } => decoder.bytes(),
+            State::Pending => 0,
+        }
+    }
+}
+
+/// Active transfers and the bytes their buffers hold
+#[cfg(feature = "prover")]
+pub(crate) fn held() -> (usize, usize) {
+    with_transfers(|transfers| {
+        let bytes = transfers.active.values().map(Transfer::bytes).sum();
+        (transfers.active.len(), bytes)
+    })
+}
+
+/// Drop timed-out transfers and release the table's spare room; returns the
+/// transfers dropped and the bytes freed. Transfers still within
+/// TRANSFER_TIMEOUT are in flight and kept.
+#[cfg(feature = "prover")]
+pub(crate) fn sweep() -> (usize, usize) {
+    let mut guard = TRANSFERS.lock().unwrap_or_else(|e| e.into_inner());
+    let transfers = match guard.as_mut() {
+        Some(transfers) => transfers,
+        None => return (0, 0),
+    };
+    let (mut dropped, mut freed) = (0, 0);
+    transfers.active.retain(|_, t| {
+        let keep = t.last_activity.elapsed() < TRANSFER_TIMEOUT;
+        if !keep {
+            dropped += 1;
+            freed += t.bytes();
+        }
+        keep
+    });
+    transfers.active.shrink_to_fit();
+    (dropped, freed)
+}
+
+/// Fields of a compressed ProvingKey<Bn254> in serialization order: the
+/// verifying key (alpha_g1, beta_g2, gamma_g2, delta_g2, gamma_abc_g1), then
+/// beta_g1, delta_g1, a_query, b_g1_query, b_g2_query, h_query, l_query.
+/// Vectors are a u64 length followed by their elements.
+#[cfg(feature = "prover")]
+#[derive(Clone, Copy)]
+enum KeyField {
+    G1,
+    G2,
+    G1s,
+    G2s,
+}
+
+#[cfg(feature = "prover")]
+const KEY_LAYOUT: [KeyField; 12] = {
+    use KeyField::*;
+    [G1, G2, G2, G2, G1s, G1, G1, G1s, G1s, G2s, G1s, G1s]
+};
+
+/// Proving key decoded chunk by chunk
+#[cfg(feature = "prover")]
+#[derive(Default)]
+struct KeyDecoder {
+    // Start of the item the last chunk cut off
+    partial: Vec<u8>,
+    // Index into KEY_LAYOUT of the field being decoded
+    field: usize,
+    // Elements the current vector field still expects; None before its length
+    remaining: Option<u64>,
+    // Decoded G1 and G2 fields in layout order, one element for a single point
+    g1: Vec<Vec<G1Affine>>,
+    g2: Vec<Vec<G2Affine>>,
+}
+
+#[cfg(feature = "prover")]
+impl KeyDecoder {
+    /// Length of the next item (an element or a vector length); None once
+    /// the key is complete
+    fn next_len(&self) -> Option<usize> {
+        let field = KEY_LAYOUT.get(self.field)?;
+        Some(match (field, self.remaining) {
+            (KeyField::G1s | KeyField::G2s, None) => 8,
+            (KeyField::G1 | KeyField::G1s, _) => G1Affine::default().compressed_size(),
+            (KeyField::G2 | KeyField::G2s, _) => G2Affine::default().compressed_size(),
+        })
+    }
+
+    /// Decode every item `chunk` completes; `left` is the payload still to
+    /// come after it, which bounds a vector's declared length
+    fn feed(&mut self, mut chunk: &[u8], left: usize) -> Result<(), ()> {
+        while !chunk.is_empty() {
+            let need = self.next_len().ok_or(())?;
+            let take = (need - self.partial.len()).min(chunk.len());
+            self.partial.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+            if self.partial.len() == need {
+                self.item(left + chunk.len())?;
+                self.partial.clear();
+            }
+        }
+        Ok(())
+    }
+
+    fn item(&mut self, left: usize) -> Result<(), ()> {
+        let item = self.partial.as_slice();
+        let field = KEY_LAYOUT[self.field];
+        if let (KeyField::G1s | KeyField::G2s, None) = (field, self.remaining) {
+            let len = u64::from_le_bytes(item.try_into().map_err(|_| ())?);
+            let size = match field {
+                KeyField::G1s => G1Affine::default().compressed_size(),
+                _ => G2Affine::default().compressed_size(),
+            };
+            // Never reserve for more elements than the payload can still hold
+            if len > (left / size) as u64 {
+                return Err(());
+            }
+            match field {
+                KeyField::G1s => self.g1.push(Vec::with_capacity(len as usize)),
+                _ => self.g2.push(Vec::with_capacity(len as usize)),
+            }
+            self.remaining = Some(len);
+        } else {
+            let g1 = || G1Affine::deserialize_compressed(item).map_err(|_| ());
+            let g2 = || G2Affine::deserialize_compressed(item).map_err(|_| ());
+            match field {
+                KeyField::G1 => self.g1.push(vec![g1()?]),
+                KeyField::G2 => self.g2.push(vec![g2()?]),
+                KeyField::G1s => self.g1.last_mut().ok_or(())?.push(g1()?),
+                KeyField::G2s => self.g2.last_mut().ok_or(())?.push(g2()?),
+            }
+            self.remaining = self.remaining.map(|n| n - 1);
+        }
+        if matches!(field, KeyField::G1 | KeyField::G2) || self.remaining == Some(0) {
+            self.field += 1;
+            self.remaining = None;
+        }
+        Ok(())
+    }
+
+    /// The key, if every field was received and nothing is left over
+    fn finish(self) -> Option<ProvingKey<Bn254>> {
+        if self.field != KEY_LAYOUT.len() || !self.partial.is_empty() {
+            return None;
+        }
+        fn single<T: Copy>(points: Option<Vec<T>>) -> Option<T> {
+            points?.first().copied()
+        }
+        let mut g1 = self.g1.into_iter();
+        let mut g2 = self.g2.into_iter();
+        let vk = VerifyingKey {
+            alpha_g1: single(g1.next())?,
+            beta_g2: single(g2.next())?,
+            gamma_g2: single(g2.next())?,
+            delta_g2: single(g2.next())?,
+            gamma_abc_g1: g1.next()?,
+        };
+        Some(ProvingKey {
+            vk,
+            beta_g1: single(g1.next())?,
+            delta_g1: single(g1.next())?,
+            a_query: g1.next()?,
+            b_g1_query: g1.next()?,
+            b_g2_query: g2.next()?,
+            h_query: g1.next()?,
+            l_query: g1.next()?,
+        })
+    }
+
+    /// Bytes the decoded points and the partial item occupy
+    fn bytes(&self) -> usize {
+        self.partial.capacity()
+            + self.g1.iter().map(|v| v.capacity() * std::mem::size_of::<G1Affine>()).sum::<usize>()
+            + self.g2.iter().map(|v| v.capacity() * std::mem::size_of::<G2Affine>()).sum::<usize>()
+    }
+}
+
+#[cfg(feature = "prover")]
+fn import_keys(decoder: KeyDecoder) -> c_int {
+    let pk = match decoder.finish() {
+        Some(pk) => pk,
+        None => return last_error::fail(ZK_ERR_CORRUPT, "proving key is truncated"),
+    };
+    let pvk = PreparedVerifyingKey::from(pk.vk.clone());
+    set_keys(pk, pvk)
+}
+
+#[cfg(feature = "prover")]
+fn export_keys() -> Option<Vec<u8>> {
+    let pk_guard = PROVING_KEY.lock().ok()?;
+    let pk = pk_guard.as_ref()?;
+    let mut out = Vec::with_capacity(pk.compressed_size());
+    pk.serialize_compressed(&mut out).ok()?;
+    Some(out)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Start a chunked transfer of `kind` (ZK_TRANSFER_*)
+///
+/// Imports declare the exact payload size in `total_len`; exports pass 0.
+/// The handle is written to `handle_out`. Returns 0 on success,
+/// ZK_ERR_CAPACITY if the imports already open have declared so much that
+/// `total_len` no longer fits under the limit, -1 on failure.
+#[no_mangle]
+pub extern "C" fn ZK_TransferBegin(kind: c_int, total_len: usize, handle_out: *mut u64) -> c_int {
+    if handle_out.is_null() {
+        return -1;
+    }
+
+    let importable = (kind == ZK_TRANSFER_IMPORT_KEYS && cfg!(feature = "prover"))
+        || (kind == ZK_TRANSFER_IMPORT_PRESENTATION && cfg!(feature = "verifier"));
+
+    let state = match kind {
+        _ if importable => {
+            if total_len == 0 || total_len > MAX_TRANSFER_LEN {
+                return -1;
+            }
+            match kind {
+                #[cfg(feature = "prover")]
+                ZK_TRANSFER_IMPORT_KEYS => State::ReceivingKeys {
+                    total_len,
+                    received: 0,
+                    decoder: KeyDecoder::default(),
+                },
+                _ => State::Receiving {
+                    kind,
+                    total_len,
+                    buffer: Vec::new(),
+                },
+            }
+        }
+        #[cfg(feature = "prover")]
+        ZK_TRANSFER_EXPORT_KEYS if total_len == 0 => State::Pending,
+        _ => return -1,
+    };
+
+    let handle = with_transfers(|transfers| {
+        let declared: usize = transfers.active.values().map(|t| t.state.declared()).sum();
+        if declared + state.declared() > MAX_TRANSFER_LEN {
+            return None;
+        }
+        let handle = transfers.next_handle;
+        transfers.next_handle += 1;
+        transfers.active.insert(
+            handle,
+            Transfer {
+                state,
+                last_activity: Instant::now(),
+            },
+        );
+        Some(handle)
+    });
+    let handle = match handle {
+        Some(handle) => handle,
+        None => return ZK_ERR_CAPACITY,
+    };
+
+    unsafe {
+        *handle_out = handle;
+    }
+
+    0
+}
+}
+
+crate::unwind::entry_point! {
+/// Append the chunk starting at `offset` to an import
+///
+/// Returns 0 on success, ZK_ERR_UNKNOWN_HANDLE for an unknown or expired
+/// handle, ZK_ERR_CORRUPT if a proving key chunk completes a malformed
+/// point or length or runs past the key, -1 if the chunk is out of order or overruns total_len
+/// (the transfer is aborted on either failure). ZK_GetLastError describes
+/// a malformed key.
+#[no_mangle]
+pub extern "C" fn ZK_TransferChunk(
+    handle: u64,
+    offset: u64,
+    bytes: *const c_char,
+    len: usize,
+) -> c_int {
+    last_error::clear();
+    let chunk = match read_bytes(bytes, len, MAX_CHUNK_LEN) {
+        Ok(chunk) if !chunk.is_empty() => chunk,
+        _ => return -1,
+    };
+
+    with_transfers(|transfers| {
+        let transfer = match transfers.active.get_mut(&handle) {
+            Some(t) => t,
+            None => return ZK_ERR_UNKNOWN_HANDLE,
+        };
+
+        let accepted = match &mut transfer.state {
+            State::Receiving { total_len, buffer, .. }
+                if offset == buffer.len() as u64 && buffer.len() + len <= *total_len =>
+            {
+                if buffer.capacity() < buffer.len() + len {
+                    // Double as chunks arrive, but never past the declared size
+                    let target = (buffer.len() * 2).clamp(buffer.len() + len, *total_len);
+                    buffer.reserve_exact(target - buffer.len());
+                }
+                buffer.extend_from_slice(chunk);
+                Ok(())
+            }
+            #[cfg(feature = "prover")]
+            State::ReceivingKeys { total_len, received, decoder }
+                if offset == *received as u64 && *received + len <= *total_len =>
+            {
+                *received += len;
+                decoder.feed(chunk, *total_len - *received).map_err(|_| {
+                    let msg = "proving key chunk holds a malformed point or length, or data past the key";
+                    last_error::fail(ZK_ERR_CORRUPT, msg)
+                })
+            }
+            _ => Err(-1),
+        };
+
+        match accepted {
+            Ok(()) => {
+                transfer.last_activity = Instant::now();
+                0
+            }
+            Err(code) => {
+                transfers.active.remove(&handle);
+                code
+            }
+        }
+    })
+}
+}
+
+crate::unwind::entry_point! {
+/// Finish the upload phase of a transfer
+///
+/// Imports are finalized and their handle released: keys return 0 on
+/// success and ZK_ERR_CORRUPT (with ZK_GetLastError set) if the payload
+/// ends inside the key, presentations return 1 if the proof
+/// verifies and 0 otherwise. Exports serialize their payload and the handle
+/// moves on to ZK_TransferRead (returns 0). Incomplete imports return -1;
+/// unknown or expired handles ZK_ERR_UNKNOWN_HANDLE.
+#[no_mangle]
+pub extern "C" fn ZK_TransferEnd(handle: u64) -> c_int {
+    last_error::clear();
+    let transfer = match with_transfers(|transfers| transfers.active.remove(&handle)) {
+        Some(t) => t,
+        None => return ZK_ERR_UNKNOWN_HANDLE,
+    };
+
+    match transfer.state {
+        State::Receiving { kind, total_len, buffer } => {
+            if buffer.len() != total_len {
+                return -1;
+            }
+            match kind {
+                #[cfg(feature = "verifier")]
+                ZK_TRANSFER_IMPORT_PRESENTATION => {
+                    let started = Instant::now();
+                    presentation::verify_and_record(started, Presentation::from_bytes(&buffer))
+                }
+                _ => -1,
+            }
+        }
+        #[cfg(feature = "prover")]
+        State::ReceivingKeys { total_len, received, decoder } => {
+            if received != total_len {
+                return -1;
+            }
+            import_keys(decoder)
+        }
+        #[cfg(feature = "prover")]
+        State::Pending => {
+            // Serialize outside the table lock; KEYS is locked instead
+            let buffer = match export_keys() {
+                Some(buffer) => buffer,
+                None => return -1,
+            };
+            with_transfers(|transfers| {
+                transfers.active.insert(
+                    handle,
+                    Transfer {
+                        state: State::Sending { buffer, position: 0 },
+                        last_activity: Instant::now(),
+                    },
+                );
+            });
+            0
+        }
+        #[cfg(feature = "prover")]
+        State::Sending { .. } => -1,
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Copy the next chunk (at most `cap` bytes) of an export into `buf`
+///
+/// The number of bytes written goes to `read_out`; 0 means the export is
+/// complete and the handle has been released. Returns 0 on success, -1 on
+/// failure, ZK_ERR_UNKNOWN_HANDLE for an unknown or expired handle.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_TransferRead(
+    handle: u64,
+    buf: *mut c_char,
+    cap: usize,
+    read_out: *mut usize,
+) -> c_int {
+    if buf.is_null() || read_out.is_null() || cap == 0 {
+        return -1;
+    }
+
+    with_transfers(|transfers| {
+        let transfer = match transfers.active.get_mut(&handle) {
+            Some(t) => t,
+            None => return ZK_ERR_UNKNOWN_HANDLE,
+        };
+
+        let (buffer, position) = match &mut transfer.state {
+            State::Sending { buffer, position } => (buffer, position),
+            _ => return -1,
+        };
+
+        let n = (buffer.len() - *position).min(cap).min(MAX_CHUNK_LEN);
+        unsafe {
+            std::ptr::copy_nonoverlapping(buffer[*position..].as_ptr(), buf as *mut u8, n);
+            *read_out = n;
+        }
+        *position += n;
+        transfer.last_activity = Instant::now();
+
+        if n == 0 {
+            transfers.active.remove(&handle);
+        }
+        0
+    })
+}
+}
+
+crate::unwind::entry_point! {
+/// Abandon a transfer and free its buffer
+#[no_mangle]
+pub extern "C" fn ZK_TransferAbort(handle: u64) {
+    with_transfers(|transfers| {
+        transfers.active.remove(&handle);
+    });
+}
+}
@@ -0,0 +1,260 @@
+// ============================================================================
+// Prover Tuning
+// ============================================================================
+//
+// ZK_AutoTune probes the machine the prover runs on and picks the prover
+// settings from what it finds:
+//
+//   cores          std::thread::available_parallelism (honours CPU quotas
+//                  and affinity masks)
+//   memory         the cgroup memory limit (v2 memory.max, v1
+//                  memory.limit_in_bytes) and MemAvailable of /proc/meminfo,
+//                  the lower of those readable; 0 if none is
+//   field_mul_ns   a micro-benchmark of FIELD_MUL_ROUNDS chained Fr
+//                  multiplications
+//
+// The one prover setting that takes effect is the thread count: all cores,
+// but no more threads than the memory holds PROVE_THREAD_BYTES for, at most
+// MAX_THREADS. It sizes the rayon pool of "parallel" builds; without that
+// feature proving is single-threaded and the count is always 1. ark-groth16
+// 0.4 picks its MSM windows itself and keeps no precomputed tables or
+// arenas, so there is nothing further to choose. field_mul_ns is recorded
+// so that decisions taken on different machines can be compared.
+//
+// The decision is kept process-wide and reported by ZK_BuildInfo. A
+// deployment that wants the same settings everywhere exports it with
+// ZK_ExportTuning and pins it with ZK_ImportTuning, which applies it
+// without probing; the JSON may be edited to override the thread count.
+// rayon sizes its pool once, so either call must come before the first
+// prove (and before ZK_Init, which runs the setup); afterwards the
+// decision is recorded but returns ZK_TUNING_NOT_APPLIED.
+
+use ark_bn254::Fr;
+use ark_ff::{Field, UniformRand};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use serde_json::{json, Value};
+use std::ffi::CStr;
+use std::hint::black_box;
+use std::os::raw::{c_char, c_int};
+use crate::sync::Mutex;
+use std::time::Instant;
+
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_UNSUPPORTED_VERSION};
+use crate::ffi::write_cstr;
+
+/// The decision was recorded, but the thread pool was already running
+pub const ZK_TUNING_NOT_APPLIED: c_int = 1;
+
+/// Version of the exported tuning JSON
+pub const TUNING_VERSION: u64 = 1;
+/// Upper bound on prover threads, probed or pinned
+pub const MAX_THREADS: u32 = 64;
+/// Working memory one prover thread is allowed
+pub const PROVE_THREAD_BYTES: u64 = 64 * 1024 * 1024;
+/// Multiplications timed by the field benchmark
+pub const FIELD_MUL_ROUNDS: u32 = 1 << 16;
+
+static TUNING: Mutex<Option<Tuning>> = Mutex::new(None);
+
+/// A prover tuning decision and what it was taken from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tuning {
+    pub threads: u32,
+    pub cores: u32,
+    /// Bytes available to the process; 0 if unknown
+    pub memory_budget: u64,
+    pub field_mul_ns: u32,
+    /// Probed here, or pinned with ZK_ImportTuning
+    pub pinned: bool,
+    /// Whether `threads` sized the thread pool
+    pub applied: bool,
+}
+
+impl Tuning {
+    /// Probe this machine and choose
+    pub fn probe() -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+        let memory_budget = memory_budget().unwrap_or(0);
+        Tuning {
+            threads: select_threads(cores, memory_budget),
+            cores,
+            memory_budget,
+            field_mul_ns: field_mul_ns(),
+            pinned: false,
+            applied: false,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": TUNING_VERSION,
+            "threads": self.threads,
+            "cores": self.cores,
+            "memory_budget": self.memory_budget,
+            "field_mul_ns": self.field_mul_ns,
+            "source": if self.pinned { "pinned" } else { "probed" },
+            "applied": self.applied,
+        })
+    }
+
+    /// Read an exported tuning; only `version` and `threads` are required
+    pub fn from_json(doc: &Value) -> Result<Self, c_int> {
+        match doc.get("version").and_then(Value::as_u64) {
+            Some(TUNING_VERSION) => {}
+            Some(_) => return Err(ZK_ERR_UNSUPPORTED_VERSION),
+            None => return Err(ZK_ERR_CORRUPT),
+        }
+        let threads = doc
+            .get("threads")
+            .and_then(Value::as_u64)
+            .filter(|n| (1..=MAX_THREADS as u64).contains(n))
+            .ok_or(ZK_ERR_CORRUPT)? as u32;
+        let field = |key: &str| doc.get(key).and_then(Value::as_u64).unwrap_or(0);
+        Ok(Tuning {
+            threads,
+            cores: field("cores") as u32,
+            memory_budget: field("memory_budget"),
+            field_mul_ns: field("field_mul_ns") as u32,
+            pinned: true,
+            applied: false,
+        })
+    }
+}
+
+/// Threads for `cores` cores and a memory budget (0 if unknown)
+pub fn select_threads(cores: u32, memory_budget: u64) -> u32 {
+    if !cfg!(feature = "parallel") {
+        return 1;
+    }
+    let by_memory = match memory_budget {
+        0 => MAX_THREADS as u64,
+        bytes => (bytes / PROVE_THREAD_BYTES).max(1),
+    };
+    cores.clamp(1, MAX_THREADS).min(by_memory as u32)
+}
+
+/// Lowest of the cgroup limit and the memory available, if any is readable
+fn memory_budget() -> Option<u64> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    // "max" and the v1 "unlimited" value (close to u64::MAX) do not parse
+    // or are far above what the machine has, so min() discards them
+    let cgroup = ["/sys/fs/cgroup/memory.max", "/sys/fs/cgroup/memory/memory.limit_in_bytes"]
+        .iter()
+        .find_map(|path| read(path)?.trim().parse::<u64>().ok());
+    let available = read("/proc/meminfo").and_then(|info| {
+        let line = info.lines().find(|line| line.starts_with("MemAvailable:"))?;
+        let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kib * 1024)
+    });
+    match (cgroup, available) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Nanoseconds per Fr multiplication, rounded up
+fn field_mul_ns() -> u32 {
+    let mut rng = StdRng::seed_from_u64(0);
+    let factor = Fr::rand(&mut rng);
+    let mut acc = Fr::rand(&mut rng);
+    let started = Instant::now();
+    for _ in 0..FIELD_MUL_ROUNDS {
+        acc *= black_box(factor);
+    }
+    black_box(acc.square());
+    let ns = started.elapsed().as_nanos().div_ceil(FIELD_MUL_ROUNDS as u128);
+    ns.clamp(1, u32::MAX as u128) as u32
+}
+
+/// Size the thread pool; false if it is already running
+#[cfg(feature = "parallel")]
+fn apply(threads: u32) -> bool {
+    rayon::ThreadPoolBuilder::new().num_threads(threads as usize).build_global().is_ok()
+        || rayon::current_num_threads() == threads as usize
+}
+
+#[cfg(not(feature = "parallel"))]
+fn apply(threads: u32) -> bool {
+    threads == 1
+}
+
+/// Apply and record `tuning`, write it as JSON to `out`
+pub(crate) fn install(mut tuning: Tuning, out: *mut c_char, out_size: usize) -> c_int {
+    tuning.applied = apply(tuning.threads);
+    let code = if tuning.applied { 0 } else { ZK_TUNING_NOT_APPLIED };
+    let text = tuning.to_json().to_string();
+    match TUNING.lock() {
+        Ok(mut guard) => *guard = Some(tuning),
+        Err(_) => return -1,
+    }
+    if out.is_null() {
+        return code;
+    }
+    match write_cstr(out, out_size, &text) {
+        Ok(_) => code,
+        Err(e) => e.code(),
+    }
+}
+
+/// The recorded decision, if ZK_AutoTune or ZK_ImportTuning ran
+pub(crate) fn current() -> Option<Tuning> {
+    TUNING.lock().ok()?.clone()
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Probe the machine, choose and apply prover settings (see tuning.rs)
+///
+/// Writes the decision as JSON to `out` (optional; NULL skips). Returns 0
+/// if applied, ZK_TUNING_NOT_APPLIED if recorded but the thread pool was
+/// already running, ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small (the
+/// decision is recorded all the same).
+#[no_mangle]
+pub extern "C" fn ZK_AutoTune(out: *mut c_char, out_size: usize) -> c_int {
+    install(Tuning::probe(), out, out_size)
+}
+}
+
+crate::unwind::entry_point! {
+/// Write the recorded decision as JSON, for ZK_ImportTuning elsewhere
+///
+/// Returns 0 on success, 1 if no decision has been taken yet,
+/// ZK_ERR_BUFFER_TOO_SMALL if the buffer is too small, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ExportTuning(out: *mut c_char, out_size: usize) -> c_int {
+    let Some(tuning) = current() else {
+        return 1;
+    };
+    match write_cstr(out, out_size, &tuning.to_json().to_string()) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Pin an exported decision without probing
+///
+/// A thread count above 1 applies only in "parallel" builds. Writes the
+/// decision as recorded to `out` (optional). Returns as ZK_AutoTune, or
+/// ZK_ERR_CORRUPT for JSON that is not a tuning, ZK_ERR_UNSUPPORTED_VERSION
+/// for a newer one, -1 on NULL.
+#[no_mangle]
+pub extern "C" fn ZK_ImportTuning(tuning_json: *const c_char, out: *mut c_char, out_size: usize) -> c_int {
+    if tuning_json.is_null() {
+        return -1;
+    }
+    let Ok(text) = unsafe { CStr::from_ptr(tuning_json) }.to_str() else {
+        return ZK_ERR_CORRUPT;
+    };
+    let tuning = match serde_json::from_str(text).map_err(|_| ZK_ERR_CORRUPT).and_then(|doc| Tuning::from_json(&doc)) {
+        Ok(tuning) => tuning,
+        Err(code) => return code,
+    };
+    install(tuning, out, out_size)
+}
+}
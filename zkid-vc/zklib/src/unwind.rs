@@ -0,0 +1,98 @@
+// ============================================================================
+// Panic Boundary
+// ============================================================================
+//
+// Unwinding out of an extern "C" function into its C caller is undefined
+// behavior, and input that gets past the argument checks can still reach a
+// slice index or an unwrap. Every entry point runs its body through guard(),
+// which in the std build stops a panic at the boundary: the call returns
+// ZK_ERR_PANIC (a void function just returns) and the panic message is left
+// for ZK_GetLastError. Entry points are declared inside entry_point! {},
+// which wraps the body without re-indenting it.
+//
+// This needs panics to unwind, so the release profile no longer aborts. The
+// no_std embedded build cannot unwind and keeps panic = "abort"; there its
+// panic handler never returns and guard() just runs the body. A caught panic
+// does not disable the library: its locks recover from poisoning
+// (src/sync.rs).
+
+#[cfg(feature = "std")]
+use {
+    crate::error::ZK_ERR_PANIC,
+    crate::last_error,
+    std::any::Any,
+    std::os::raw::c_int,
+    std::panic::{self, AssertUnwindSafe},
+};
+
+/// What an entry point returns when its body panicked
+#[cfg(feature = "std")]
+pub(crate) trait PanicReturn {
+    fn on_panic() -> Self;
+}
+
+#[cfg(feature = "std")]
+impl PanicReturn for c_int {
+    fn on_panic() -> Self {
+        ZK_ERR_PANIC
+    }
+}
+
+#[cfg(feature = "std")]
+impl PanicReturn for () {
+    fn on_panic() {}
+}
+
+/// Run an entry point's body, turning a panic into ZK_ERR_PANIC
+///
+/// The body may leave caller memory or library state half written when it
+/// panics; state behind the library's locks is updated in single steps, so
+/// asserting unwind safety here does not expose torn values.
+#[cfg(feature = "std")]
+pub(crate) fn guard<R: PanicReturn>(body: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        last_error::fail(ZK_ERR_PANIC, &format!("internal panic: {}", message(payload.as_ref())));
+        R::on_panic()
+    })
+}
+
+/// Run an entry point's body; without std a panic never returns
+#[cfg(not(feature = "std"))]
+pub(crate) fn guard<R>(body: impl FnOnce() -> R) -> R {
+    body()
+}
+
+/// Declare an extern "C" entry point whose body runs through guard()
+///
+/// Takes one function item, attributes and doc comments included:
+///
+/// ```ignore
+/// entry_point! {
+/// /// Doc comment
+/// #[no_mangle]
+/// pub extern "C" fn ZK_Example(arg: c_int) -> c_int {
+///     arg
+/// }
+/// }
+/// ```
+macro_rules! entry_point {
+    (
+        $(#[$attr:meta])*
+        $vis:vis extern "C" fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        $(#[$attr])*
+        $vis extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $crate::unwind::guard(|| $body)
+        }
+    };
+}
+pub(crate) use entry_point;
+
+/// The text a panic was raised with
+#[cfg(feature = "std")]
+fn message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload.downcast_ref::<String>().map_or("no message", String::as_str),
+    }
+}
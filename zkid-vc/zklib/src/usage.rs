@@ -0,0 +1,150 @@
+// ============================================================================
+// Key Usage Separation
+// ============================================================================
+//
+// An issuer key signs more than one kind of artifact. Every signature
+// covers a context string naming its usage, followed by the artifact's
+// message, and every verifier checks under the one context it expects, so a
+// signature made for one usage never verifies as another:
+//
+//   usage                      signed message
+//   ZK_KEY_USAGE_CREDENTIAL    "zkid:usage:credential" | message hash
+//                              (credentials and their co-signatures)
+//   ZK_KEY_USAGE_STATUS        "zkid:status-attestation" | attestation body
+//   ZK_KEY_USAGE_CLAIM_GROUP   "zkid:claim-group" | "zkid.group.anchor.v1"
+//                              | group anchor (composite.rs)
+//   ZK_KEY_USAGE_HOLDER_REBIND "zkid:holder-rebind" | certificate body
+//                              (rebind.rs)
+//
+// Status attestations and claim groups carried their contexts from the
+// start. Credentials did not: blobs before version 5 were signed over the
+// bare message hash. They stay verifiable as such, but only as what their
+// version says (VerifiableCredential::legacy_signature); a version 5 blob
+// is only checked under the credential context. The field-based entry
+// points (ZK_SignVC, ZK_VerifyVCSignature) carry no version: they sign
+// under the context and, outside strict builds, also accept a bare-hash
+// signature. No other usage signs a bare 32-byte hash that a holder could
+// pass off as a credential's.
+//
+// Issuers that want a compromised status path not to burn their
+// credentials sign each usage with its own key: ZK_DeriveIssuerSubkey
+// derives it from a master secret with HKDF-SHA256 (salt SUBKEY_SALT, info
+// the usage's context), so only the master needs safekeeping and each
+// subkey's public half is published for its verifiers.
+
+use ed25519_dalek::SigningKey;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::os::raw::c_int;
+use zeroize::Zeroizing;
+
+use crate::domain;
+
+#[cfg(feature = "prover")]
+use {
+    crate::bytes_to_hex,
+    crate::ffi::write_cstr,
+    crate::hex_to_bytes,
+    crate::SECRET_KEY_LENGTH,
+    std::ffi::CStr,
+    std::os::raw::c_char,
+};
+
+pub const ZK_KEY_USAGE_CREDENTIAL: c_int = 1;
+pub const ZK_KEY_USAGE_STATUS: c_int = 2;
+pub const ZK_KEY_USAGE_CLAIM_GROUP: c_int = 3;
+pub const ZK_KEY_USAGE_HOLDER_REBIND: c_int = 4;
+
+/// HKDF salt of issuer subkeys
+pub const SUBKEY_SALT: &[u8] = b"zkid:issuer-subkey/v1";
+
+/// What an issuer signature is for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyUsage {
+    Credential,
+    Status,
+    ClaimGroup,
+    HolderRebind,
+}
+
+impl KeyUsage {
+    /// The usage for a ZK_KEY_USAGE_* code
+    pub fn from_code(code: c_int) -> Option<Self> {
+        match code {
+            ZK_KEY_USAGE_CREDENTIAL => Some(KeyUsage::Credential),
+            ZK_KEY_USAGE_STATUS => Some(KeyUsage::Status),
+            ZK_KEY_USAGE_CLAIM_GROUP => Some(KeyUsage::ClaimGroup),
+            ZK_KEY_USAGE_HOLDER_REBIND => Some(KeyUsage::HolderRebind),
+            _ => None,
+        }
+    }
+
+    /// Context string mixed into signatures of this usage
+    pub const fn context(self) -> &'static [u8] {
+        match self {
+            KeyUsage::Credential => domain::CREDENTIAL_CONTEXT,
+            KeyUsage::Status => b"zkid:status-attestation",
+            KeyUsage::ClaimGroup => b"zkid:claim-group",
+            KeyUsage::HolderRebind => b"zkid:holder-rebind",
+        }
+    }
+
+    /// What a key of this usage signs: the context, then `message`
+    pub fn signed_message(self, message: &[u8]) -> Vec<u8> {
+        [self.context(), message].concat()
+    }
+}
+
+/// The issuer subkey for `usage` derived from `master`
+pub fn derive_subkey(master: &[u8], usage: KeyUsage) -> SigningKey {
+    let mut secret = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(SUBKEY_SALT), master)
+        .expand(usage.context(), secret.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    SigningKey::from_bytes(&secret)
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Derive the issuer key for one usage from a master secret
+///
+/// `master_secret` is 32 bytes of hex (an issuer private key will do);
+/// `usage` is one of ZK_KEY_USAGE_*. Writes the hex subkey pair. The same
+/// master and usage always give the same key. Returns 0 on success,
+/// ZK_ERR_BUFFER_TOO_SMALL, -1 on NULL, a malformed secret or an unknown
+/// usage.
+#[cfg(feature = "prover")]
+#[no_mangle]
+pub extern "C" fn ZK_DeriveIssuerSubkey(
+    master_secret: *const c_char,
+    usage: c_int,
+    public_key_out: *mut c_char,
+    public_key_size: usize,
+    private_key_out: *mut c_char,
+    private_key_size: usize,
+) -> c_int {
+    if master_secret.is_null() || public_key_out.is_null() || private_key_out.is_null() {
+        return -1;
+    }
+    let usage = match KeyUsage::from_code(usage) {
+        Some(usage) => usage,
+        None => return -1,
+    };
+    let master = match unsafe { CStr::from_ptr(master_secret) }.to_str().ok().and_then(|hex| hex_to_bytes(hex).ok()) {
+        Some(bytes) if bytes.len() == SECRET_KEY_LENGTH => Zeroizing::new(bytes),
+        _ => return -1,
+    };
+
+    let subkey = derive_subkey(&master, usage);
+    let private_key_hex = Zeroizing::new(bytes_to_hex(subkey.as_bytes()));
+    match write_cstr(public_key_out, public_key_size, &bytes_to_hex(subkey.verifying_key().as_bytes()))
+        .and_then(|_| write_cstr(private_key_out, private_key_size, &private_key_hex))
+    {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,411 @@
+// ============================================================================
+// Native Credential Validation
+// ============================================================================
+//
+// Services that hold the full credential and need no privacy only have to
+// know whether it is valid. ZK_ValidateVC runs the native checks a prover
+// performs before proving, without any Groth16 work:
+//
+//   ZK_VALIDATE_SIGNATURE   the issuer signature verifies (and the
+//                           co-signature, if present)
+//   ZK_VALIDATE_TIME        the dates lie in the date window and the
+//                           credential is active (dates and validity
+//                           schedule) at the current time; a policy with
+//                           allow_expired_within_seconds also passes one
+//                           that expired at most that long ago, reporting
+//                           the overshoot (see grace.rs)
+//   ZK_VALIDATE_REVOCATION  the credential is neither revoked nor, with
+//                           ZK_SetRejectSuperseded, superseded
+//   ZK_VALIDATE_SCHEMA      the credential meets its schema's issuance rules:
+//                           a dual-control schema requires a co-signature
+//                           from a second key
+//
+// The blob provers (ZK_GenerateVCProofFromBlob, ZK_ContextGenerateVCProof)
+// run `validate` with PROVER_CHECKS and refuse to prove on the first
+// failure, so validation and proving cannot disagree about a credential.
+
+use ed25519_dalek::{VerifyingKey, PUBLIC_KEY_LENGTH};
+use std::os::raw::{c_char, c_int};
+
+use crate::clock::{self, TimeMode};
+use crate::context::Context;
+use crate::credential::{parse_verifying_key, SignatureCheckError};
+use crate::dates::{self, DateWindow};
+use crate::error::{ZK_ERR_BAD_SIGNATURE, ZK_ERR_COSIGNATURE_MISSING, ZK_ERR_STORAGE, ZK_ERR_VC_EXPIRED};
+use crate::ffi::{read_bytes, MAX_BLOB_LEN};
+use crate::revocation::{self, reject_superseded, RevocationData, RevocationRegistry};
+use crate::storage::storage;
+use crate::VerifiableCredential;
+
+pub const ZK_VALIDATE_SIGNATURE: u32 = 1 << 0;
+pub const ZK_VALIDATE_TIME: u32 = 1 << 1;
+pub const ZK_VALIDATE_REVOCATION: u32 = 1 << 2;
+pub const ZK_VALIDATE_SCHEMA: u32 = 1 << 3;
+pub const ZK_VALIDATE_ALL: u32 =
+    ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_TIME | ZK_VALIDATE_REVOCATION | ZK_VALIDATE_SCHEMA;
+
+/// Checks the provers run before proving
+pub const PROVER_CHECKS: u32 = ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_TIME | ZK_VALIDATE_SCHEMA;
+
+/// Report value of a check whose input was not supplied (or not selected)
+pub const ZK_CHECK_SKIPPED: c_int = 2;
+
+/// Outcome of one check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    Failed,
+    Skipped,
+    /// The check could not be decided (negative error code)
+    Undecided(c_int),
+}
+
+impl CheckOutcome {
+    /// 1, 0, ZK_CHECK_SKIPPED or the error code
+    pub fn code(self) -> c_int {
+        match self {
+            CheckOutcome::Passed => 1,
+            CheckOutcome::Failed => 0,
+            CheckOutcome::Skipped => ZK_CHECK_SKIPPED,
+            CheckOutcome::Undecided(code) => code,
+        }
+    }
+}
+
+/// Which checks run, and the settings they run under
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Policy {
+    /// ZK_VALIDATE_* bits
+    pub checks: u32,
+    pub time_mode: TimeMode,
+    pub date_window: DateWindow,
+    /// Seconds past expiry the time check still passes, degraded; 0 for none
+    pub allow_expired_within_seconds: u64,
+}
+
+impl Policy {
+    /// The process-wide time mode and date window
+    pub fn global(checks: u32) -> Self {
+        Self {
+            checks,
+            time_mode: clock::time_mode(),
+            date_window: dates::date_window(),
+            allow_expired_within_seconds: 0,
+        }
+    }
+
+    fn runs(&self, check: u32) -> bool {
+        self.checks & check != 0
+    }
+}
+
+/// Typed result of `validate`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub signature: CheckOutcome,
+    pub time_policy: CheckOutcome,
+    pub revocation: CheckOutcome,
+    pub schema: CheckOutcome,
+    /// Time the time check used, from the time source
+    pub time: Option<u64>,
+    /// Seconds past expiry, if the time check passed only through the
+    /// policy's allow_expired_within_seconds
+    pub overshoot: Option<u64>,
+    /// Epoch of the published revocation list the revocation check used, 0
+    /// if none was published or the check did not run
+    pub revocation_epoch: u64,
+}
+
+impl ValidationReport {
+    /// Whether no check failed or was left undecided
+    pub fn accepted(&self) -> bool {
+        self.first_error().is_ok()
+    }
+
+    /// Whether the credential passed the time check as expired
+    pub fn degraded(&self) -> bool {
+        self.overshoot.is_some()
+    }
+
+    /// The error code of the first failed check, in the order signature,
+    /// schema, time, revocation; a failed signature check maps to
+    /// ZK_ERR_BAD_SIGNATURE, a failed time check to ZK_ERR_VC_EXPIRED and
+    /// any other failed check to -1
+    pub fn first_error(&self) -> Result<(), c_int> {
+        let checks = [
+            (self.signature, ZK_ERR_BAD_SIGNATURE),
+            (self.schema, -1),
+            (self.time_policy, ZK_ERR_VC_EXPIRED),
+            (self.revocation, -1),
+        ];
+        for (outcome, failed) in checks {
+            match outcome {
+                CheckOutcome::Passed | CheckOutcome::Skipped => {}
+                CheckOutcome::Failed => return Err(failed),
+                CheckOutcome::Undecided(code) => return Err(code),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per-check outcome of ZK_ValidateVC (C layout)
+///
+/// Each field is 1 if the check passed, 0 if it failed, ZK_CHECK_SKIPPED if
+/// it was not selected, or a negative error code if it could not be decided.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkValidationReport {
+    pub signature: c_int,
+    /// ZK_ERR_TIME_MISMATCH if the time sources disagree
+    pub time_policy: c_int,
+    /// ZK_ERR_STORAGE if the registry lookup failed
+    pub revocation: c_int,
+    /// ZK_ERR_COSIGNATURE_MISSING for a dual-control schema without a
+    /// co-signature
+    pub schema: c_int,
+    /// Epoch of the published revocation list checked against, 0 if none
+    pub revocation_epoch: u64,
+}
+
+impl From<&ValidationReport> for ZkValidationReport {
+    fn from(report: &ValidationReport) -> Self {
+        Self {
+            signature: report.signature.code(),
+            time_policy: report.time_policy.code(),
+            revocation: report.revocation.code(),
+            schema: report.schema.code(),
+            revocation_epoch: report.revocation_epoch,
+        }
+    }
+}
+
+/// Whether the credential `issuer` signed is neither revoked nor, with
+/// ZK_SetRejectSuperseded, superseded: 1, 0 or ZK_ERR_STORAGE
+pub fn revocation_check(vc: &VerifiableCredential, issuer: Option<&[u8; PUBLIC_KEY_LENGTH]>) -> c_int {
+    revocation_status(issuer, &vc.credential_id())
+}
+
+/// revocation_check by credential identifier
+pub fn revocation_status(issuer: Option<&[u8; PUBLIC_KEY_LENGTH]>, credential_id: &str) -> c_int {
+    revocation_lookup(issuer, credential_id, revocation::published().as_deref())
+}
+
+/// revocation_status against the registry and `published`, the list loaded
+/// once for the verification in progress
+///
+/// The published list only applies when `issuer`, the key that signed the
+/// credential, is the list's issuer; the registry is always consulted.
+pub fn revocation_lookup(
+    issuer: Option<&[u8; PUBLIC_KEY_LENGTH]>,
+    credential_id: &str,
+    published: Option<&RevocationData>,
+) -> c_int {
+    let registry = RevocationRegistry::new(storage());
+    let listed = published.zip(issuer);
+
+    let superseded = if reject_superseded() {
+        match listed.and_then(|(data, issuer)| data.successor(issuer, credential_id)) {
+            Some(_) => Ok(true),
+            None => registry.successor(credential_id).map(|successor| successor.is_some()),
+        }
+    } else {
+        Ok(false)
+    };
+    let revoked = match listed {
+        Some((data, issuer)) if data.is_revoked(issuer, credential_id) => Ok(true),
+        _ => registry.is_revoked(credential_id.as_bytes()),
+    };
+    match (revoked, superseded) {
+        (Ok(false), Ok(false)) => 1,
+        (Ok(_), Ok(_)) => 0,
+        _ => ZK_ERR_STORAGE,
+    }
+}
+
+/// Signature and schema outcomes against the first of `issuers` whose key
+/// made the primary signature, and that key
+fn signature_checks<'a>(
+    vc: &VerifiableCredential,
+    issuers: &'a [VerifyingKey],
+    second_issuer: Option<&VerifyingKey>,
+) -> (CheckOutcome, CheckOutcome, Option<&'a VerifyingKey>) {
+    let schema_unmet = vc.requires_co_signature() && vc.co_signature.is_none();
+    let issuer = match signer(vc, issuers) {
+        Some(issuer) => issuer,
+        None if schema_unmet => {
+            return (CheckOutcome::Failed, CheckOutcome::Undecided(ZK_ERR_COSIGNATURE_MISSING), None)
+        }
+        None => return (CheckOutcome::Failed, CheckOutcome::Passed, None),
+    };
+
+    // The primary signature verified, so a missing co-signature is the
+    // schema's failure, a bad one the signature's
+    let (signature, schema) = match vc.verify_issuer_signatures(issuer, second_issuer) {
+        Ok(()) => (CheckOutcome::Passed, CheckOutcome::Passed),
+        Err(SignatureCheckError::CoSignatureMissing) => {
+            (CheckOutcome::Passed, CheckOutcome::Undecided(ZK_ERR_COSIGNATURE_MISSING))
+        }
+        Err(SignatureCheckError::BadSignature) => (CheckOutcome::Failed, CheckOutcome::Passed),
+    };
+    (signature, schema, Some(issuer))
+}
+
+/// The first of `issuers` whose key made the primary signature
+fn signer<'a>(vc: &VerifiableCredential, issuers: &'a [VerifyingKey]) -> Option<&'a VerifyingKey> {
+    issuers.iter().find(|key| vc.verify_signature(key))
+}
+
+/// Run the checks selected by `policy` on a decoded credential
+///
+/// The signature check accepts a credential signed by any of `issuers`;
+/// `second_issuer` pins the co-signer as in verify_issuer_signatures.
+/// `caller_time` goes through the policy's time mode.
+pub fn validate(
+    vc: &VerifiableCredential,
+    issuers: &[VerifyingKey],
+    second_issuer: Option<&VerifyingKey>,
+    policy: &Policy,
+    caller_time: u64,
+) -> ValidationReport {
+    let signature_checked = policy.runs(ZK_VALIDATE_SIGNATURE | ZK_VALIDATE_SCHEMA);
+    let (signature, schema, issuer) = if signature_checked {
+        signature_checks(vc, issuers, second_issuer)
+    } else {
+        (CheckOutcome::Skipped, CheckOutcome::Skipped, None)
+    };
+    let signature = if policy.runs(ZK_VALIDATE_SIGNATURE) { signature } else { CheckOutcome::Skipped };
+    let schema = if policy.runs(ZK_VALIDATE_SCHEMA) { schema } else { CheckOutcome::Skipped };
+
+    let (time_policy, time, overshoot) = if !policy.runs(ZK_VALIDATE_TIME) {
+        (CheckOutcome::Skipped, None, None)
+    } else {
+        match clock::now_with(policy.time_mode, caller_time) {
+            Ok(now) => {
+                let usable = policy.date_window.contains_range(vc.issue_date, vc.expiry_date) && vc.in_schedule(now);
+                let overshoot = if dates::is_active(now, vc.issue_date, vc.expiry_date) {
+                    Some(None)
+                } else {
+                    dates::expired_within(now, vc.issue_date, vc.expiry_date, policy.allow_expired_within_seconds)
+                        .map(Some)
+                };
+                match overshoot {
+                    Some(overshoot) if usable => (CheckOutcome::Passed, Some(now), overshoot),
+                    _ => (CheckOutcome::Failed, Some(now), None),
+                }
+            }
+            Err(code) => (CheckOutcome::Undecided(code), None, None),
+        }
+    };
+
+    let (revocation, revocation_epoch) = if !policy.runs(ZK_VALIDATE_REVOCATION) {
+        (CheckOutcome::Skipped, 0)
+    } else {
+        let published = revocation::published();
+        // A published list only speaks for the key that signed the credential
+        let issuer = match issuer {
+            Some(issuer) => Some(issuer.to_bytes()),
+            None if !signature_checked && published.is_some() => signer(vc, issuers).map(VerifyingKey::to_bytes),
+            None => None,
+        };
+        let outcome = match revocation_lookup(issuer.as_ref(), &vc.credential_id(), published.as_deref()) {
+            1 => CheckOutcome::Passed,
+            0 => CheckOutcome::Failed,
+            code => CheckOutcome::Undecided(code),
+        };
+        (outcome, published.map_or(0, |data| data.epoch_for(issuer.as_ref())))
+    };
+
+    ValidationReport {
+        signature,
+        time_policy,
+        revocation,
+        schema,
+        time,
+        overshoot,
+        revocation_epoch,
+    }
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Validate a credential natively, without generating or checking a proof
+///
+/// `vc_blob` is a VC blob (binary or hex text). The issuer comes from
+/// `issuer_public_key` (hex) or, if that is NULL, from the trust store of
+/// `ctx`: the signature check then accepts any trusted key (an empty store
+/// has none). With a context, its time mode and date window apply, and a
+/// given issuer key must also be trusted by it; without one the global
+/// settings do. `checks` selects ZK_VALIDATE_* bits; unselected checks
+/// report ZK_CHECK_SKIPPED. A blob that does not decode fails every selected
+/// check. Returns 1 if no selected check failed or was undecided, 0
+/// otherwise, -1 on NULL input, unknown check bits or with neither issuer
+/// key nor context while the signature check is selected.
+#[no_mangle]
+pub extern "C" fn ZK_ValidateVC(
+    vc_blob: *const c_char,
+    vc_blob_len: usize,
+    issuer_public_key: *const c_char,
+    ctx: *const Context,
+    current_time: u64,
+    checks: u32,
+    report_out: *mut ZkValidationReport,
+) -> c_int {
+    let ctx = unsafe { ctx.as_ref() };
+    if vc_blob.is_null()
+        || report_out.is_null()
+        || checks & !ZK_VALIDATE_ALL != 0
+        || (checks & ZK_VALIDATE_SIGNATURE != 0 && issuer_public_key.is_null() && ctx.is_none())
+    {
+        return -1;
+    }
+
+    let data = match read_bytes(vc_blob, vc_blob_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+
+    let issuers: Vec<VerifyingKey> = match (issuer_public_key.is_null(), ctx) {
+        (false, _) => parse_verifying_key(issuer_public_key)
+            .filter(|key| ctx.is_none_or(|ctx| ctx.is_trusted(key.as_bytes())))
+            .into_iter()
+            .collect(),
+        (true, Some(ctx)) => ctx
+            .trusted_issuers()
+            .filter_map(|key| VerifyingKey::from_bytes(key).ok())
+            .collect(),
+        (true, None) => Vec::new(),
+    };
+    let policy = match ctx {
+        Some(ctx) => match ctx.validation_policy(checks) {
+            Some(policy) => policy,
+            None => return -1,
+        },
+        None => Policy::global(checks),
+    };
+
+    let report = match VerifiableCredential::from_wire(data) {
+        Some(vc) => ZkValidationReport::from(&validate(&vc, &issuers, None, &policy, current_time)),
+        None => {
+            let selected = |check: u32| if checks & check != 0 { 0 } else { ZK_CHECK_SKIPPED };
+            ZkValidationReport {
+                signature: selected(ZK_VALIDATE_SIGNATURE),
+                time_policy: selected(ZK_VALIDATE_TIME),
+                revocation: selected(ZK_VALIDATE_REVOCATION),
+                schema: selected(ZK_VALIDATE_SCHEMA),
+                revocation_epoch: 0,
+            }
+        }
+    };
+    unsafe {
+        *report_out = report;
+    }
+
+    [report.signature, report.time_policy, report.revocation, report.schema]
+        .iter()
+        .all(|&code| code == 1 || code == ZK_CHECK_SKIPPED) as c_int
+}
+}
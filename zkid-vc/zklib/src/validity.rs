@@ -0,0 +1,268 @@
+// ============================================================================
+// Validity Window and Anchor of VC Proofs
+// ============================================================================
+//
+// A VC proof carries the time it was proven at, the anchor of the credential
+// and the issuer's signature over that anchor:
+//
+//   anchor = MiMC(vc_hash, issue_date, expiry_date, holder_key, blinding,
+//                 claims_root)
+//
+// vc_hash is the credential message hash mapped to a field element as proof
+// format 2 maps it, whatever format the proof is in, holder_key the
+// `zkid:holder-binding` key of a bound credential (holder.rs), 0 for an
+// unbound one, blinding the `zkid:anchor-blinding` claim (credential.rs), a
+// random field element ZK_SignVCBlob draws for every signature, 0 for a
+// credential signed without one, and claims_root the root of the claims
+// tree (disclosure.rs), 0 for a credential without claims or with more than
+// the tree holds. The root lets circuits over a single claim open it from
+// the signed anchor. The issuer signs
+//
+//   "zkid:usage:credential" | "zkid.vc.anchor.v1" | anchor
+//
+// (domain.rs; the anchor a compressed field element), 70 bytes, which no
+// context-prefixed or bare 32-byte message hash signature can be taken for.
+// The VC circuit proves
+//
+//   anchor = MiMC(vc_hash, issue_date, expiry_date, holder_key, blinding,
+//                 claims_root)
+//   issue_date <= current_time <= expiry_date
+//
+// for its private vc_hash, dates, blinding and root, with issuer_pubkey_hash,
+// nonce, current_time and the anchor public; holder_key is the constant 0
+// in the VC circuit and MiMC of a private holder secret in the bound ones.
+// The issuer key hash and nonce enter no constraint: Groth16 commits the
+// verifier to every public input, so a proof for one issuer key or nonce
+// verifies for no other, and what ties the proof to the issuer is the
+// signature over the anchor. The dates
+// are compared over the integers: each is shifted by 2^63 into 64 bits, and
+// current_time - issue_date and expiry_date - current_time are decomposed
+// into 65 bits, so neither difference can wrap around the field. The proof
+// wire is
+//
+//   "ZKVA" | current_time u64 | anchor | signature (64) | proof wire bytes
+//
+// (current_time little-endian). Every verifier checks the signature under
+// the issuer key it verifies for before the proof (`public_inputs`), so an
+// anchor no issuer signed, or dates or a holder key other than the signed
+// ones, does not verify: the circuit ties them to the anchor, the signature
+// ties the anchor to the issuer. current_time is the verifier's challenge
+// time, not the prover's clock: a verifier that has a current_time
+// (ZK_VerifyVCProof and friends) passes its own as the public input, so a
+// proof from another time, or for a credential that was not active at that
+// time, does not verify, whatever a modified prover skipped. Verifiers
+// without one (presentations, the embedded verifier) take the time the
+// proof carries; that it is recent is then the caller's business, like the
+// nonce's freshness.
+//
+// The anchor and signature are the same in every proof of a credential, so
+// presentations of one credential are linkable to each other. With a
+// blinding, the anchor reveals nothing about the credential to a verifier
+// that does not hold it: guessing the fields does not reproduce it. A
+// verifier that holds the VC, blinding claim included, recomputes the
+// anchor and sees which VC a proof is for; without a blinding (the
+// field-based signing entry points, unless the caller passes the claim)
+// the anchor hides vc_hash only as well as the VC's fields resist
+// guessing.
+//
+// core + alloc only, so the embedded verifier reads and checks the same
+// header.
+
+use alloc::vec::Vec;
+use ark_bn254::{Bn254, Fr};
+use ark_ff::Zero;
+use ark_groth16::Proof;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ed25519_dalek::{Signature, VerifyingKey, SIGNATURE_LENGTH};
+
+use crate::domain::{CREDENTIAL_CONTEXT, VC_ANCHOR_TAG};
+use crate::mimc;
+use crate::proof::{self, ProofDecodeError, PROOF_FORMAT_V2};
+
+const VALIDITY_MAGIC: &[u8; 4] = b"ZKVA";
+
+/// Length of a compressed anchor
+pub(crate) const ANCHOR_LEN: usize = 32;
+
+/// Length of the validity prefix on a VC proof
+pub const VALIDITY_PREFIX_LEN: usize = VALIDITY_MAGIC.len() + 8 + ANCHOR_LEN + SIGNATURE_LENGTH;
+
+/// Issue and expiry dates a VC proof commits to (unix seconds)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub issue_date: i64,
+    pub expiry_date: i64,
+}
+
+impl Window {
+    /// Field elements of the dates, in anchor order
+    pub fn fields(&self) -> [Fr; 2] {
+        [date_field(self.issue_date), date_field(self.expiry_date)]
+    }
+}
+
+/// Field element of a date (negative dates map to -|date|)
+pub fn date_field(date: i64) -> Fr {
+    let magnitude = Fr::from(date.unsigned_abs());
+    if date < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Field element of a VC message hash in an anchor, the same in every proof
+/// format
+pub fn vc_hash_field(vc_message_hash: &[u8]) -> Option<Fr> {
+    proof::field_for(PROOF_FORMAT_V2, vc_message_hash)
+}
+
+/// What an anchor commits to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opening {
+    /// Field element of the VC message hash (`vc_hash_field`)
+    pub vc_hash: Fr,
+    pub window: Window,
+    /// Holder key of a bound credential, 0 when unbound
+    pub holder_key: Fr,
+    /// The credential's anchor blinding, 0 without one
+    pub blinding: Fr,
+    /// Root of the credential's claims tree, 0 without claims
+    pub claims_root: Fr,
+}
+
+impl Opening {
+    /// Opening of an unbound credential without claims or blinding, given
+    /// by its message hash
+    pub fn of(vc_message_hash: &[u8], window: Window) -> Option<Self> {
+        Some(Self {
+            vc_hash: vc_hash_field(vc_message_hash)?,
+            window,
+            holder_key: Fr::zero(),
+            blinding: Fr::zero(),
+            claims_root: Fr::zero(),
+        })
+    }
+
+    /// The anchor the issuer signs
+    pub fn anchor(&self) -> Fr {
+        let [issue, expiry] = self.window.fields();
+        mimc::hash(&[self.vc_hash, issue, expiry, self.holder_key, self.blinding, self.claims_root])
+    }
+}
+
+/// What the issuer signs for an anchor
+pub fn anchor_message(anchor: &Fr) -> Vec<u8> {
+    let mut message = Vec::with_capacity(CREDENTIAL_CONTEXT.len() + VC_ANCHOR_TAG.len() + ANCHOR_LEN);
+    message.extend_from_slice(CREDENTIAL_CONTEXT);
+    message.extend_from_slice(VC_ANCHOR_TAG);
+    // A field element always fits its compressed length
+    let _ = anchor.serialize_compressed(&mut message);
+    message
+}
+
+/// Whether `signature` is `issuer_pubkey`'s (32 bytes) over `anchor`
+///
+/// Always the strict Ed25519 check: no anchor was signed before it.
+pub fn anchor_signed(issuer_pubkey: &[u8], anchor: &Fr, signature: &[u8; SIGNATURE_LENGTH]) -> bool {
+    let key = match issuer_pubkey.try_into().ok().and_then(|bytes| VerifyingKey::from_bytes(bytes).ok()) {
+        Some(key) => key,
+        None => return false,
+    };
+    key.verify_strict(&anchor_message(anchor), &Signature::from_bytes(signature)).is_ok()
+}
+
+/// What a VC proof states besides its issuer key and nonce
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// The time the proof was generated for (unix seconds)
+    pub current_time: u64,
+    /// Anchor of the proven credential
+    pub anchor: Fr,
+    /// The issuer's signature over the anchor
+    pub signature: [u8; SIGNATURE_LENGTH],
+}
+
+impl Header {
+    /// Header of a proof at `current_time` over an issuer-signed anchor
+    pub fn new(anchor: Fr, signature: [u8; SIGNATURE_LENGTH], current_time: u64) -> Self {
+        Self { current_time, anchor, signature }
+    }
+
+    /// Whether the anchor carries `issuer_pubkey`'s signature
+    pub fn signed_by(&self, issuer_pubkey: &[u8]) -> bool {
+        anchor_signed(issuer_pubkey, &self.anchor, &self.signature)
+    }
+
+    /// Prefix proof wire bytes with the header
+    pub fn tag(&self, proof: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(VALIDITY_PREFIX_LEN + proof.len());
+        out.extend_from_slice(VALIDITY_MAGIC);
+        out.extend_from_slice(&self.current_time.to_le_bytes());
+        // A field element always fits its compressed length
+        let _ = self.anchor.serialize_compressed(&mut out);
+        out.extend_from_slice(&self.signature);
+        out.extend_from_slice(proof);
+        out
+    }
+}
+
+/// Public inputs of the VC circuit, in circuit order, for a proof in
+/// `format` for `issuer_pubkey` checked at `current_time`
+///
+/// None unless the issuer signed the header's anchor (or for a key with no
+/// field in `format`): the proof alone only shows that the anchor opens to
+/// a credential active at `current_time`, not who issued it.
+pub fn public_inputs(
+    issuer_pubkey: &[u8],
+    format: u8,
+    nonce: u64,
+    current_time: u64,
+    header: &Header,
+) -> Option<[Fr; 4]> {
+    public_inputs_for(issuer_pubkey, format, Fr::from(nonce), current_time, header)
+}
+
+/// `public_inputs` with any field element in the nonce's place, for a
+/// circuit that binds a statement there instead (ballot.rs)
+pub fn public_inputs_for(
+    issuer_pubkey: &[u8],
+    format: u8,
+    nonce: Fr,
+    current_time: u64,
+    header: &Header,
+) -> Option<[Fr; 4]> {
+    if !header.signed_by(issuer_pubkey) {
+        return None;
+    }
+    let issuer_field = proof::field_for(format, issuer_pubkey)?;
+    Some([issuer_field, nonce, Fr::from(current_time), header.anchor])
+}
+
+/// Split a VC proof into its header and proof wire bytes
+///
+/// None for bytes without the prefix or with a non-canonical anchor.
+pub fn untag(bytes: &[u8]) -> Option<(Header, &[u8])> {
+    if !bytes.starts_with(VALIDITY_MAGIC) {
+        return None;
+    }
+    let time_at = VALIDITY_MAGIC.len();
+    let time = bytes.get(time_at..time_at + 8)?;
+    let current_time = u64::from_le_bytes(time.try_into().ok()?);
+    let anchor = Fr::deserialize_compressed(bytes.get(time_at + 8..time_at + 8 + ANCHOR_LEN)?).ok()?;
+    let signature = bytes.get(VALIDITY_PREFIX_LEN - SIGNATURE_LENGTH..VALIDITY_PREFIX_LEN)?;
+    let signature = signature.try_into().ok()?;
+    Some((Header { current_time, anchor, signature }, &bytes[VALIDITY_PREFIX_LEN..]))
+}
+
+/// Parse VC proof wire bytes: the header, format and proof
+pub fn decode(bytes: &[u8]) -> Result<(Header, u8, Proof<Bn254>), ProofDecodeError> {
+    let (header, inner) = untag(bytes).ok_or(ProofDecodeError::Malformed)?;
+    let (format, proof) = proof::decode(inner)?;
+    Ok((header, format, proof))
+}
+
+/// Encode a VC proof with its header
+pub fn encode(header: &Header, format: u8, proof: &Proof<Bn254>) -> Vec<u8> {
+    header.tag(&proof::encode(format, proof))
+}
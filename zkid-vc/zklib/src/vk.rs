@@ -0,0 +1,179 @@
+// ============================================================================
+// Verifying Key Forms
+// ============================================================================
+//
+// A verifying key travels in one of two binary forms:
+//
+//   raw       VerifyingKey, compressed (what ZK_ExportVerifyingKey hex-encodes)
+//   prepared  PreparedVerifyingKey, compressed: the raw key followed by
+//             e(alpha, beta) and the pairing-prepared -gamma and -delta
+//
+// Either form can be derived from the other. The prepared form embeds its raw
+// key, so it is only accepted if re-preparing that key reproduces every
+// precomputed element; otherwise a tampered e(alpha, beta) would be trusted.
+//
+// Outputs are binary. The length is written to `len_out` even when the
+// buffer is too small, so a caller can size its buffer with a first call.
+
+use ark_bn254::Bn254;
+use ark_groth16::{PreparedVerifyingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use alloc::vec::Vec;
+use core::ffi::c_int;
+
+use crate::encoding::{PointEncoding, ZkDeserialize, ZkSerialize};
+use crate::error::{ZK_ERR_CORRUPT, ZK_ERR_KEY_MISMATCH};
+use crate::ffi::{read_bytes, FfiError, MAX_BLOB_LEN};
+
+/// Why a key in either form was rejected
+#[derive(Debug, PartialEq, Eq)]
+pub enum VkError {
+    /// Truncated, trailing bytes or not valid curve points
+    Corrupt,
+    /// A prepared key whose precomputed elements do not match its raw key
+    Mismatch,
+}
+
+impl VkError {
+    pub fn code(&self) -> c_int {
+        match self {
+            VkError::Corrupt => ZK_ERR_CORRUPT,
+            VkError::Mismatch => ZK_ERR_KEY_MISMATCH,
+        }
+    }
+}
+
+/// Decode a raw compressed verifying key, rejecting trailing bytes
+pub fn decode_raw(bytes: &[u8]) -> Result<VerifyingKey<Bn254>, VkError> {
+    VerifyingKey::<Bn254>::zk_from_bytes(bytes, PointEncoding::Compressed).ok_or(VkError::Corrupt)
+}
+
+/// Decode a prepared verifying key and check it against its raw key
+pub fn decode_prepared(bytes: &[u8]) -> Result<PreparedVerifyingKey<Bn254>, VkError> {
+    let mut reader = bytes;
+    let pvk = PreparedVerifyingKey::<Bn254>::deserialize_compressed(&mut reader)
+        .map_err(|_| VkError::Corrupt)?;
+    if !reader.is_empty() {
+        return Err(VkError::Corrupt);
+    }
+    if PreparedVerifyingKey::from(pvk.vk.clone()) != pvk {
+        return Err(VkError::Mismatch);
+    }
+    Ok(pvk)
+}
+
+/// Frozen encoding of a raw key (see encoding.rs)
+pub fn encode(vk: &VerifyingKey<Bn254>) -> Vec<u8> {
+    vk.zk_to_bytes(PointEncoding::Compressed)
+}
+
+/// Compressed arkworks encoding of a prepared key
+pub fn encode_prepared(pvk: &PreparedVerifyingKey<Bn254>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(pvk.compressed_size());
+    pvk.serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
+/// Number of public inputs the key verifies (gamma_abc length minus one)
+pub fn public_inputs(vk: &VerifyingKey<Bn254>) -> usize {
+    vk.gamma_abc_g1.len().saturating_sub(1)
+}
+
+/// Copy `bytes` to `out`, always reporting the length through `len_out`
+pub(crate) fn write_out(bytes: &[u8], out: *mut u8, out_size: usize, len_out: *mut usize) -> c_int {
+    if len_out.is_null() {
+        return -1;
+    }
+    unsafe {
+        *len_out = bytes.len();
+    }
+    if out.is_null() {
+        return FfiError::Null.code();
+    }
+    if bytes.len() > out_size {
+        return FfiError::BufferTooSmall.code();
+    }
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    }
+    0
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Derive the prepared form from a raw verifying key
+///
+/// The prepared length is written to `pvk_len_out`. Returns 0 on success,
+/// ZK_ERR_CORRUPT if the raw key does not decode, ZK_ERR_BUFFER_TOO_SMALL if
+/// `pvk_out` is smaller than `*pvk_len_out`, -1 on NULL pointers.
+#[no_mangle]
+pub extern "C" fn ZK_PrepareVerifyingKey(
+    vk_bytes: *const u8,
+    vk_len: usize,
+    pvk_out: *mut u8,
+    pvk_out_size: usize,
+    pvk_len_out: *mut usize,
+) -> c_int {
+    let data = match read_bytes(vk_bytes, vk_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    let vk = match decode_raw(data) {
+        Ok(vk) => vk,
+        Err(e) => return e.code(),
+    };
+
+    write_out(&encode_prepared(&PreparedVerifyingKey::from(vk)), pvk_out, pvk_out_size, pvk_len_out)
+}
+}
+
+crate::unwind::entry_point! {
+/// Recover the raw verifying key from its prepared form
+///
+/// The raw length is written to `vk_len_out`. Returns 0 on success,
+/// ZK_ERR_CORRUPT if the prepared key does not decode, ZK_ERR_KEY_MISMATCH
+/// if its precomputed elements do not belong to its raw key,
+/// ZK_ERR_BUFFER_TOO_SMALL if `vk_out` is smaller than `*vk_len_out`, -1 on
+/// NULL pointers.
+#[no_mangle]
+pub extern "C" fn ZK_ExtractVerifyingKey(
+    pvk_bytes: *const u8,
+    pvk_len: usize,
+    vk_out: *mut u8,
+    vk_out_size: usize,
+    vk_len_out: *mut usize,
+) -> c_int {
+    let data = match read_bytes(pvk_bytes, pvk_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    let pvk = match decode_prepared(data) {
+        Ok(pvk) => pvk,
+        Err(e) => return e.code(),
+    };
+
+    write_out(&encode(&pvk.vk), vk_out, vk_out_size, vk_len_out)
+}
+}
+
+crate::unwind::entry_point! {
+/// Number of public inputs a raw verifying key accepts
+///
+/// The VC circuit has two (issuer key hash, nonce). Returns the count, or
+/// ZK_ERR_CORRUPT if the key does not decode, -1 on NULL input.
+#[no_mangle]
+pub extern "C" fn ZK_VerifyingKeyPublicInputs(vk_bytes: *const u8, vk_len: usize) -> c_int {
+    let data = match read_bytes(vk_bytes, vk_len, MAX_BLOB_LEN) {
+        Ok(data) => data,
+        Err(e) => return e.code(),
+    };
+    match decode_raw(data) {
+        Ok(vk) => c_int::try_from(public_inputs(&vk)).unwrap_or(c_int::MAX),
+        Err(e) => e.code(),
+    }
+}
+}
@@ -0,0 +1,319 @@
+// ============================================================================
+// W3C Verifiable Credentials JSON
+// ============================================================================
+//
+// Credentials as documents of the W3C VC data model (v1.1), for systems that
+// read JSON rather than blobs:
+//
+//   "@context"               ["https://www.w3.org/2018/credentials/v1"]
+//   "type"                   ["VerifiableCredential"]
+//   "issuer"                 issuer (a string, or an object with "id")
+//   "issuanceDate"           issue_date, RFC 3339 (dates.rs)
+//   "expirationDate"         expiry_date, RFC 3339
+//   "credentialSubject"      {"id": holder_id, claim key: claim value, ...}
+//   "zkid:claimOrder"        [claim keys in signed order]      (if any claims)
+//   "zkid:supersedes"        credential id                     (if any)
+//   "zkid:validitySchedule"  hex of the schedule encoding      (if any)
+//   "proof"                  (if signed)
+//     "type"                 "ZkidEd25519Signature"
+//     "proofPurpose"         "assertionMethod"
+//     "proofValue"           hex signature
+//     "messageVersion"       blob version of the message-hash layout
+//     "claimsRootSigned"     bool                    (messageVersion >= 7)
+//     "messageTagged"        bool                    (messageVersion >= 9)
+//     "coSignature"          {"keyId": hex, "proofValue": hex}  (if any)
+//
+// The signature covers the message hash (or from messageVersion 9 the
+// anchor over it, validity.rs), not the JSON, so a document carries what
+// the blob carries and verifies the same way after parsing: messageVersion,
+// claimsRootSigned and messageTagged name the hash layout as the blob
+// version and its trailing flags do (credential.rs). Claims are hashed in
+// order but a JSON object has none, so zkid:claimOrder records it; a document
+// without it (from another issuer) takes its claims in key order, the
+// canonical one. Claim values must be strings, and keys distinct and other
+// than "id".
+//
+// Members outside this list are ignored on parsing, as no signature covers
+// them. A document without a proof parses as ZK_EncodeVC's unsigned blob
+// would.
+
+use serde_json::{json, Map, Value};
+use std::os::raw::{c_char, c_int};
+
+use crate::credential::{implied_claims_root, implied_tag, parse_vc_blob, write_vc_blob, VC_BLOB_VERSION};
+use crate::ffi::{read_bytes, reserve_cstr, write_cstr, MAX_BLOB_LEN};
+use crate::schedule::ValiditySchedule;
+use crate::wire::Reader;
+use crate::{bytes_to_hex, dates, domain, hex_to_bytes, CoSignature, VerifiableCredential};
+
+/// First @context of every W3C VC v1.1 document
+pub const VC_CONTEXT_V1: &str = "https://www.w3.org/2018/credentials/v1";
+
+/// Proof type of a signature over the credential message hash
+pub const PROOF_TYPE: &str = "ZkidEd25519Signature";
+
+const VC_TYPE: &str = "VerifiableCredential";
+const PROOF_PURPOSE: &str = "assertionMethod";
+pub(crate) const CLAIM_ORDER: &str = "zkid:claimOrder";
+pub(crate) const SUPERSEDES: &str = "zkid:supersedes";
+pub(crate) const VALIDITY_SCHEDULE: &str = "zkid:validitySchedule";
+
+impl VerifiableCredential {
+    /// The credential as a W3C VC document
+    ///
+    /// None if a date has no RFC 3339 form or a claim key repeats or is "id".
+    pub fn to_json(&self) -> Option<Value> {
+        let mut subject = Map::new();
+        subject.insert("id".into(), json!(self.holder_id));
+        for (key, value) in &self.claims {
+            if subject.insert(key.clone(), json!(value)).is_some() {
+                return None;
+            }
+        }
+
+        let mut doc = json!({
+            "@context": [VC_CONTEXT_V1],
+            "type": [VC_TYPE],
+            "issuer": self.issuer,
+            "issuanceDate": dates::to_rfc3339(self.issue_date)?,
+            "expirationDate": dates::to_rfc3339(self.expiry_date)?,
+            "credentialSubject": subject,
+        });
+        self.extensions_json(&mut doc);
+        if let Some(proof) = self.proof_json() {
+            doc["proof"] = proof;
+        }
+        Some(doc)
+    }
+
+    /// Read a W3C VC document written by `to_json` or by another issuer
+    pub fn from_json(doc: &Value) -> Option<Self> {
+        if doc["@context"].get(0)?.as_str()? != VC_CONTEXT_V1
+            || !doc["type"].as_array()?.iter().any(|t| t.as_str() == Some(VC_TYPE))
+        {
+            return None;
+        }
+        let issuer = match &doc["issuer"] {
+            Value::Object(issuer) => issuer.get("id")?.as_str()?,
+            issuer => issuer.as_str()?,
+        };
+        let issue_date = dates::parse_rfc3339(doc["issuanceDate"].as_str()?)?;
+        let expiry_date = dates::parse_rfc3339(doc["expirationDate"].as_str()?)?;
+
+        let subject = doc["credentialSubject"].as_object()?;
+        let holder_id = subject.get("id")?.as_str()?;
+        let claims = ordered_claims(subject, doc.get(CLAIM_ORDER), |key| key != "id")?;
+        let (supersedes, validity_schedule) = read_extensions(doc)?;
+
+        let mut vc = VerifiableCredential {
+            holder_id: holder_id.to_string(),
+            issuer: issuer.to_string(),
+            issue_date,
+            expiry_date,
+            claims,
+            signature: Vec::new(),
+            co_signature: None,
+            supersedes,
+            validity_schedule,
+            legacy_signature: false,
+            claims_root_signed: true,
+            length_prefixed: true,
+            domain_separated: domain::tagged(),
+            anchored: true,
+            schedule_anchored: true,
+        };
+        if let Some(proof) = doc.get("proof") {
+            read_proof(&mut vc, proof)?;
+        }
+        Some(vc)
+    }
+
+    /// Add the zkid: members other than the proof to `doc`
+    pub(crate) fn extensions_json(&self, doc: &mut Value) {
+        if !self.claims.is_empty() {
+            doc[CLAIM_ORDER] = self.claims.iter().map(|(key, _)| json!(key)).collect();
+        }
+        if let Some(old_id) = &self.supersedes {
+            doc[SUPERSEDES] = json!(old_id);
+        }
+        if let Some(schedule) = &self.validity_schedule {
+            let mut encoded = Vec::new();
+            schedule.encode(&mut encoded);
+            doc[VALIDITY_SCHEDULE] = json!(bytes_to_hex(&encoded));
+        }
+    }
+
+    /// The proof block, None if the credential carries no signature
+    pub(crate) fn proof_json(&self) -> Option<Value> {
+        if self.signature.is_empty() && self.co_signature.is_none() {
+            return None;
+        }
+        let version = self.blob_version();
+        let mut proof = json!({
+            "type": PROOF_TYPE,
+            "proofPurpose": PROOF_PURPOSE,
+            "proofValue": bytes_to_hex(&self.signature),
+            "messageVersion": version,
+        });
+        if implied_claims_root(version).is_none() {
+            proof["claimsRootSigned"] = json!(self.claims_root_signed);
+        }
+        if implied_tag(version).is_none() {
+            proof["messageTagged"] = json!(self.domain_separated);
+        }
+        if let Some(co) = &self.co_signature {
+            proof["coSignature"] = json!({
+                "keyId": bytes_to_hex(&co.key_id),
+                "proofValue": bytes_to_hex(&co.signature),
+            });
+        }
+        Some(proof)
+    }
+}
+
+/// The string claims among `members` (the keys `is_claim` accepts), in the
+/// order of the key array `order` or, without one, in key order
+pub(crate) fn ordered_claims(
+    members: &Map<String, Value>,
+    order: Option<&Value>,
+    is_claim: impl Fn(&str) -> bool,
+) -> Option<Vec<(String, String)>> {
+    let claim_count = members.keys().filter(|key| is_claim(key)).count();
+    let claim_value = |key: &str| Some((key.to_string(), members.get(key)?.as_str()?.to_string()));
+    let order = match order {
+        Some(order) => order.as_array()?,
+        None => {
+            return members
+                .keys()
+                .filter(|key| is_claim(key))
+                .map(|key| claim_value(key))
+                .collect();
+        }
+    };
+    if order.len() != claim_count {
+        return None;
+    }
+    let claims = order
+        .iter()
+        .map(|key| key.as_str().filter(|&key| is_claim(key)).and_then(claim_value))
+        .collect::<Option<Vec<_>>>()?;
+    // As many distinct keys as there are claims: a permutation
+    let mut keys: Vec<&str> = claims.iter().map(|(key, _)| key.as_str()).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    (keys.len() == claim_count).then_some(claims)
+}
+
+/// The supersession pointer and validity schedule members of `doc`
+pub(crate) fn read_extensions(doc: &Value) -> Option<(Option<String>, Option<ValiditySchedule>)> {
+    let supersedes = match doc.get(SUPERSEDES) {
+        Some(old_id) => Some(old_id.as_str()?.to_string()),
+        None => None,
+    };
+    let validity_schedule = match doc.get(VALIDITY_SCHEDULE) {
+        Some(schedule) => {
+            let encoded = hex_to_bytes(schedule.as_str()?).ok()?;
+            let mut r = Reader::new(&encoded);
+            let schedule = ValiditySchedule::decode(&mut r)?;
+            if !r.is_empty() {
+                return None;
+            }
+            Some(schedule)
+        }
+        None => None,
+    };
+    Some((supersedes, validity_schedule))
+}
+
+/// Take the signatures and hash layout of `proof` into `vc`
+pub(crate) fn read_proof(vc: &mut VerifiableCredential, proof: &Value) -> Option<()> {
+    if proof["type"].as_str()? != PROOF_TYPE {
+        return None;
+    }
+    let version = u8::try_from(proof["messageVersion"].as_u64()?).ok()?;
+    if version == 0 || version > VC_BLOB_VERSION {
+        return None;
+    }
+    let claims_root_signed = match implied_claims_root(version) {
+        Some(signed) => signed,
+        None => proof["claimsRootSigned"].as_bool()?,
+    };
+    let domain_separated = match implied_tag(version) {
+        Some(tagged) => tagged,
+        None => proof["messageTagged"].as_bool()?,
+    };
+    vc.set_layout(version, claims_root_signed, domain_separated);
+    vc.signature = hex_to_bytes(proof["proofValue"].as_str()?).ok()?;
+    vc.co_signature = match proof.get("coSignature") {
+        Some(co) => Some(CoSignature {
+            key_id: hex_to_bytes(co["keyId"].as_str()?).ok()?.try_into().ok()?,
+            signature: hex_to_bytes(co["proofValue"].as_str()?).ok()?,
+        }),
+        None => None,
+    };
+    Some(())
+}
+
+// ============================================================================
+// C API Functions
+// ============================================================================
+
+crate::unwind::entry_point! {
+/// Write a hex VC blob as a W3C VC JSON document
+///
+/// As with other variable-size outputs, a NULL `json_out` (or size 0) with
+/// `required_size_out` set is a size query. Returns 0 on success, -1 for a
+/// malformed blob or one with no JSON form (a date outside years 1 to 9999,
+/// a repeated claim key or one named "id").
+#[no_mangle]
+pub extern "C" fn ZK_SerializeVCJson(
+    vc_blob: *const c_char,
+    json_out: *mut c_char,
+    json_out_size: usize,
+    required_size_out: *mut usize,
+) -> c_int {
+    if vc_blob.is_null() {
+        return -1;
+    }
+    let json = match parse_vc_blob(vc_blob).and_then(|vc| vc.to_json()) {
+        Some(doc) => doc.to_string(),
+        None => return -1,
+    };
+    if let Err(e) = reserve_cstr(json_out, json_out_size, json.len() + 1, required_size_out) {
+        return e.code();
+    }
+    match write_cstr(json_out, json_out_size, &json) {
+        Ok(_) => 0,
+        Err(e) => e.code(),
+    }
+}
+}
+
+crate::unwind::entry_point! {
+/// Read a W3C VC JSON document of `json_len` bytes into a hex VC blob
+///
+/// A document with a proof keeps its signatures and their hash layout, so
+/// the blob verifies as the one it was written from. Dates must lie inside
+/// the ZK_SetDateWindow window. Returns 0 on success, -1 for a malformed
+/// document.
+#[no_mangle]
+pub extern "C" fn ZK_ParseVCJson(
+    json: *const c_char,
+    json_len: usize,
+    vc_blob_out: *mut c_char,
+    vc_blob_out_size: usize,
+) -> c_int {
+    let json = match read_bytes(json, json_len, MAX_BLOB_LEN) {
+        Ok(bytes) => bytes,
+        Err(e) => return e.code(),
+    };
+    let vc = match serde_json::from_slice(json).ok().and_then(|doc| VerifiableCredential::from_json(&doc)) {
+        Some(vc) => vc,
+        None => return -1,
+    };
+    if !dates::valid_range(vc.issue_date, vc.expiry_date) {
+        return -1;
+    }
+    write_vc_blob(&vc, vc_blob_out, vc_blob_out_size)
+}
+}